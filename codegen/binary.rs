@@ -33,6 +33,17 @@ fn get_decode_method(kind: &str) -> String {
     snake_casify(kind)
 }
 
+/// Returns the expression parsing an operand of the given `kind` in
+/// binary::Parser. Literal strings are handed to the consumer by the parser,
+/// the rest is decoded directly.
+fn get_parse_expr(kind: &str) -> String {
+    if kind == "LiteralString" {
+        "self.parse_string()?".to_string()
+    } else {
+        format!("try_decode!(self.decoder.{}())", get_decode_method(kind))
+    }
+}
+
 /// Returns the generated operand decoding errors for binary::Decoder by
 /// walking the given SPIR-V operand kinds `grammar`.
 pub fn gen_operand_decode_errors(grammar: &Vec<structs::OperandKind>)
@@ -63,12 +74,12 @@ pub fn gen_operand_decode_errors(grammar: &Vec<structs::OperandKind>)
          {s:4}StreamExpected(usize),\n\
          {s:4}LimitReached(usize),\n\
          {errors}\n\
-         {s:4}/// Failed to decode a string.\n\
+         {s:4}/// A literal string is not valid UTF-8.\n\
          {s:4}///\n\
-         {s:4}/// For structured error handling, the second element could be\n\
-         {s:4}/// `string::FromUtf8Error`, but the will prohibit the compiler\n\
-         {s:4}/// from generating `PartialEq` for this enum.\n\
-         {s:4}DecodeStringFailed(usize, String),\n\
+         {s:4}/// The first element is the index of the string, and the second\n\
+         {s:4}/// element is the number of leading bytes that are valid UTF-8.\n\
+         {s:4}/// The raw bytes can still be read with `Decoder::string_bytes()`.\n\
+         {s:4}InvalidUtf8String(usize, usize),\n\
          }}\n\n",
         s = "",
         errors = errors.join("\n"));
@@ -91,8 +102,9 @@ pub fn gen_operand_decode_errors(grammar: &Vec<structs::OperandKind>)
          {s:12}Error::LimitReached(index) => write!(f, \"reached word limit \
              at index {{}}\", index),\n\
          {errors}\n\
-         {s:12}Error::DecodeStringFailed(index, ref e) => write!(f, \
-             \"cannot decode string at index {{}}: {{}}\", index, e),\n\
+         {s:12}Error::InvalidUtf8String(index, valid) => write!(f, \
+             \"invalid UTF-8 in string at index {{}} after byte {{}}\", \
+             index, valid),\n\
          {s:8}}}\n{s:4}}}\n}}\n\n",
         s = "",
         errors = errors.join("\n"));
@@ -105,6 +117,7 @@ pub fn gen_operand_decode_errors(grammar: &Vec<structs::OperandKind>)
          {s:8}match *self {{\n\
          {s:12}Error::StreamExpected(_) => \"expected more bytes \
              in the stream\",\n\
+         {s:12}Error::InvalidUtf8String(..) => \"invalid UTF-8 string\",\n\
          {s:12}_ => \"unknown operand value for the given kind\",\n\
          {s:8}}}\n{s:4}}}\n}}\n",
         s = "");
@@ -119,7 +132,7 @@ pub fn gen_operand_decode_methods(grammar: &Vec<structs::OperandKind>)
                                   -> String {
     let mut ret = String::new();
 
-    ret.push_str("use num::FromPrimitive;\n\n");
+    ret.push_str("use num_traits::FromPrimitive;\n\n");
 
     let methods: Vec<String> = grammar.iter().filter(|element| {
         // For kinds whose values may occupy more than one word, we need to
@@ -196,27 +209,28 @@ fn gen_operand_param_parse_methods(grammar: &Vec<structs::OperandKind>)
             // associated parameters.
             let cases: Vec<String> = pairs.into_iter().map(|(symbol, params)| {
                 let params: Vec<String> = params.iter().map(|element| {
-                    format!("mr::Operand::{kind}(\
-                             try_decode!(self.decoder.{decode}()))",
+                    format!("mr::Operand::{kind}({parse})",
                             kind = get_mr_operand_kind(element),
-                            decode = get_decode_method(element))
+                            parse = get_parse_expr(element))
+                }).collect();
+                let pushes: Vec<String> = params.iter().map(|param| {
+                    format!("{s:12}operands.push({});", param, s = "")
                 }).collect();
                 format!(
                     "{s:8}if {arg}.contains(spirv::{kind}::{bit}) {{\n\
-                         {s:12}params.append(&mut vec![{params}]);\n\
+                         {pushes}\n\
                      {s:8}}}",
                     s = "",
                     arg = lo_kind,
                     kind = kind,
                     bit = snake_casify(&symbol).to_uppercase(),
-                    params = params.join(", "))
+                    pushes = pushes.join("\n"))
             }).collect();
             format!(
                 "{s:4}fn parse_{k}_arguments(&mut self, {k}: \
-                     spirv::{kind}) -> Result<Vec<mr::Operand>> {{\n\
-                     {s:8}let mut params = vec![];\n\
+                     spirv::{kind}, operands: &mut mr::Operands) -> Result<()> {{\n\
                      {cases}\n\
-                     {s:8}Ok(params)\n\
+                     {s:8}Ok(())\n\
                  {s:4}}}",
                 s = "",
                 cases = cases.join("\n"),
@@ -225,25 +239,28 @@ fn gen_operand_param_parse_methods(grammar: &Vec<structs::OperandKind>)
         } else {  // ValueEnum
             let cases: Vec<String> = pairs.into_iter().map(|(symbol, params)| {
                 let params: Vec<String> = params.iter().map(|element| {
-                    format!("mr::Operand::{kind}(\
-                             try_decode!(self.decoder.{decode}()))",
+                    format!("mr::Operand::{kind}({parse})",
                             kind = get_mr_operand_kind(element),
-                            decode = get_decode_method(element))
+                            parse = get_parse_expr(element))
+                }).collect();
+                let pushes: Vec<String> = params.iter().map(|param| {
+                    format!("operands.push({});", param)
                 }).collect();
                 format!(
-                    "{s:12}spirv::{kind}::{symbol} => vec![{params}],",
+                    "{s:12}spirv::{kind}::{symbol} => {{ {pushes} }}",
                     s = "",
                     kind = kind,
                     symbol = symbol,
-                    params = params.join(", "))
+                    pushes = pushes.join(" "))
             }).collect();
             format!(
-                "{s:4}fn parse_{k}_arguments(&mut self, {k}: spirv::{kind})\
-                     {s:1}-> Result<Vec<mr::Operand>> {{\n\
-                     {s:8}Ok(match {k} {{\n\
+                "{s:4}fn parse_{k}_arguments(&mut self, {k}: spirv::{kind}, \
+                     operands: &mut mr::Operands) -> Result<()> {{\n\
+                     {s:8}match {k} {{\n\
                         {cases}\n\
-                        {s:12}_ => vec![]\n\
-                     {s:8}}})\n\
+                        {s:12}_ => (),\n\
+                     {s:8}}}\n\
+                     {s:8}Ok(())\n\
                  {s:4}}}",
                 s = "",
                 kind = kind,
@@ -266,9 +283,8 @@ pub fn gen_operand_parse_methods(grammar: &Vec<structs::OperandKind>) -> String
             format!(
                 "{s:12}GOpKind::{kind} => {{\n\
                  {s:16}let val = try_decode!(self.decoder.{decode}());\n\
-                 {s:16}let mut ops = vec![mr::Operand::{kind}(val)];\n\
-                 {s:16}ops.append(&mut self.parse_{k}_arguments(val)?);\n\
-                 {s:16}ops\n\
+                 {s:16}operands.push(mr::Operand::{kind}(val));\n\
+                 {s:16}self.parse_{k}_arguments(val, operands)?;\n\
                  {s:12}}}",
                 s = "",
                 kind = kind,
@@ -286,15 +302,14 @@ pub fn gen_operand_parse_methods(grammar: &Vec<structs::OperandKind>) -> String
     ];
     let pair_cases: Vec<String> = pair_kinds.iter().map(|&(k0, k1)| {
         format!("{s:12}GOpKind::{kind} => {{\n\
-                 {s:16}vec![\
-                 mr::Operand::{k0}(try_decode!(self.decoder.{m0}())), \
-                 mr::Operand::{k1}(try_decode!(self.decoder.{m1}()))\
-                 ]\n{s:12}}}",
+                 {s:16}operands.push(mr::Operand::{k0}({p0}));\n\
+                 {s:16}operands.push(mr::Operand::{k1}({p1}));\n\
+                 {s:12}}}",
                 s = "",
                 kind = format!("Pair{}{}", k0, k1),
                 k0 = get_mr_operand_kind(k0),
                 k1 = get_mr_operand_kind(k1),
-                m0 = get_decode_method(k0), m1=get_decode_method(k1))
+                p0 = get_parse_expr(k0), p1 = get_parse_expr(k1))
     }).collect();
 
     // These kinds are manually handled.
@@ -313,12 +328,11 @@ pub fn gen_operand_parse_methods(grammar: &Vec<structs::OperandKind>) -> String
             }
     }).map(|kind| {
         format!(
-            "{s:12}GOpKind::{gkind} => vec![mr::Operand::{mkind}\
-             (try_decode!(self.decoder.{decode}()))],",
+            "{s:12}GOpKind::{gkind} => operands.push(mr::Operand::{mkind}({parse})),",
              s = "",
              gkind = kind,
              mkind = get_mr_operand_kind(kind),
-             decode = get_decode_method(kind))
+             parse = get_parse_expr(kind))
     }).collect();
 
     let manual_cases: Vec<String> =
@@ -330,14 +344,15 @@ pub fn gen_operand_parse_methods(grammar: &Vec<structs::OperandKind>) -> String
 
     format!(
         "impl<'c, 'd> Parser<'c, 'd> {{\n\
-         {s:4}fn parse_operand(&mut self, kind: GOpKind) \
-             -> Result<Vec<mr::Operand>> {{\n\
-             {s:8}Ok(match kind {{\n\
+         {s:4}fn parse_operand(&mut self, kind: GOpKind, \
+             operands: &mut mr::Operands) -> Result<()> {{\n\
+             {s:8}match kind {{\n\
                  {normal_cases}\n\
                  {pair_cases}\n\
                  {further_parse_cases}\n\
                  {manual_cases}\n\
-             {s:8}}})\n\
+             {s:8}}}\n\
+             {s:8}Ok(())\n\
          {s:4}}}\n\n\
          {functions}\n\
          }}",
@@ -382,3 +397,42 @@ pub fn gen_disas_bit_enum_operands(grammar: &Vec<structs::OperandKind>) -> Strin
 
     elements.join("\n\n")
 }
+
+/// Returns the generated function looking up enumerants by name for the
+/// text assembler by walking the given SPIR-V operand kinds `grammar`.
+pub fn gen_asm_enumerants(grammar: &Vec<structs::OperandKind>) -> String {
+    let mut seen = std::collections::BTreeSet::new();
+    let cases: Vec<String> = grammar.iter().filter(|kind| {
+        kind.category == "ValueEnum" || kind.category == "BitEnum"
+    }).flat_map(|kind| {
+        kind.enumerants.iter().map(move |enumerant| (kind, enumerant))
+    }).filter(|&(kind, enumerant)| {
+        seen.insert((kind.kind.clone(), enumerant.symbol.clone()))
+    }).map(|(kind, enumerant)| {
+        let value = if enumerant.value.string.starts_with("0x") {
+            u32::from_str_radix(&enumerant.value.string[2..], 16).unwrap()
+        } else {
+            enumerant.value.number
+        };
+        let params: Vec<String> = enumerant.parameters.iter().map(|param| {
+            format!("GOpKind::{}", param.kind)
+        }).collect();
+        format!("{s:8}(GOpKind::{kind}, \"{name}\") => Some(({value}, &[{params}])),",
+                s = "",
+                kind = kind.kind,
+                name = enumerant.symbol,
+                value = value,
+                params = params.join(", "))
+    }).collect();
+
+    format!("/// Returns the value of the enumerant `name` of the operand kind `kind`,\n\
+             /// and the kinds of the parameters it takes.\n\
+             fn enumerant(kind: GOpKind, name: &str) -> Option<(u32, &'static [GOpKind])> {{\n\
+             {s:4}match (kind, name) {{\n\
+             {cases}\n\
+             {s:8}_ => None,\n\
+             {s:4}}}\n\
+             }}\n",
+            s = "",
+            cases = cases.join("\n"))
+}
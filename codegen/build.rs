@@ -21,6 +21,7 @@ extern crate serde_derive;
 extern crate serde_json;
 
 mod binary;
+mod capi;
 mod header;
 mod mr;
 mod sr;
@@ -101,14 +102,54 @@ fn main() {
     }
     let cl_grammar: structs::ExtInstSetGrammar = serde_json::from_str(&contents).unwrap();
 
+    // For NonSemantic.Shader.DebugInfo.100 extended instruction set.
+    {
+        let path = codegen_src_dir.join(
+            "external/SPIRV-Headers/include/spirv/unified1/\
+             extinst.nonsemantic.shader.debuginfo.100.grammar.json");
+        let filename = path.to_str().unwrap();
+        let mut file = fs::File::open(filename).unwrap();
+        contents.clear();
+        file.read_to_string(&mut contents).unwrap();
+    }
+    let debug_grammar: structs::ExtInstSetGrammar = serde_json::from_str(&contents).unwrap();
+
+    // For NonSemantic.DebugPrintf extended instruction set.
+    {
+        let path = codegen_src_dir.join(
+            "external/SPIRV-Headers/include/spirv/unified1/\
+             extinst.nonsemantic.debugprintf.grammar.json");
+        let filename = path.to_str().unwrap();
+        let mut file = fs::File::open(filename).unwrap();
+        contents.clear();
+        file.read_to_string(&mut contents).unwrap();
+    }
+    let printf_grammar: structs::ExtInstSetGrammar = serde_json::from_str(&contents).unwrap();
+
+    // For NonSemantic.ClspvReflection extended instruction set.
+    {
+        let path = codegen_src_dir.join(
+            "external/SPIRV-Headers/include/spirv/unified1/\
+             extinst.nonsemantic.clspvreflection.grammar.json");
+        let filename = path.to_str().unwrap();
+        let mut file = fs::File::open(filename).unwrap();
+        contents.clear();
+        file.read_to_string(&mut contents).unwrap();
+    }
+    let clspv_grammar: structs::ExtInstSetGrammar = serde_json::from_str(&contents).unwrap();
+
     {
         // Path to the generated SPIR-V header file.
         let path = codegen_src_dir.join("../spirv/spirv.rs");
         let core = header::gen_spirv_header(&grammar);
         let gl = header::gen_glsl_std_450_opcodes(&gl_grammar);
         let cl = header::gen_opencl_std_opcodes(&cl_grammar);
+        let debug = header::gen_debug_info_opcodes(&debug_grammar);
+        let printf = header::gen_debug_printf_opcodes(&printf_grammar);
+        let clspv = header::gen_clspv_reflection_opcodes(&clspv_grammar);
 
-        write!(core + "\n" + &gl + "\n" + &cl, path);
+        write!(core + "\n" + &gl + "\n" + &cl + "\n" + &debug + "\n" + &printf + "\n" + &clspv,
+               path);
 
     }
 
@@ -119,6 +160,13 @@ fn main() {
         write!(c, path);
     }
 
+    {
+        // Path to the generated reflect functions.
+        let path = codegen_src_dir.join("../rspirv/grammar/reflect.rs");
+        let c = table::gen_grammar_reflect(&grammar);
+        write!(c, path);
+    }
+
     {
         // Path to the generated operands kind in data representation.
         let path = codegen_src_dir.join("../rspirv/mr/operand.rs");
@@ -162,6 +210,24 @@ fn main() {
         let c = mr::gen_mr_builder_normal_insts(&grammar);
         write!(c, path);
     }
+    {
+        // Path to the generated builder for data representation.
+        let path = codegen_src_dir.join("../rspirv/mr/build_debug_info.rs");
+        let c = mr::gen_mr_builder_debug_info(&debug_grammar);
+        write!(c, path);
+    }
+    {
+        // Path to the generated builder for data representation.
+        let path = codegen_src_dir.join("../rspirv/mr/build_debug_printf.rs");
+        let c = mr::gen_mr_builder_debug_printf(&printf_grammar);
+        write!(c, path);
+    }
+    {
+        // Path to the generated builder for data representation.
+        let path = codegen_src_dir.join("../rspirv/mr/build_clspv_reflection.rs");
+        let c = mr::gen_mr_builder_clspv_reflection(&clspv_grammar);
+        write!(c, path);
+    }
 
     {
         // Path to the generated decoding errors.
@@ -188,6 +254,12 @@ fn main() {
         let c = binary::gen_disas_bit_enum_operands(&grammar.operand_kinds);
         write!(c, path);
     }
+    {
+        // Path to the generated enumerant lookup for the text assembler.
+        let path = codegen_src_dir.join("../rspirv/binary/asm_operand.rs");
+        let c = binary::gen_asm_enumerants(&grammar.operand_kinds);
+        write!(c, path);
+    }
 
     {
         let path = codegen_src_dir.join("../rspirv/sr/decoration.rs");
@@ -218,4 +290,33 @@ fn main() {
         let c = table::gen_opencl_std_100_inst_table(&cl_grammar);
         write!(c, path);
     }
+
+    {
+        let path = codegen_src_dir.join("../rspirv/grammar/debug_info_100.rs");
+        let c = table::gen_debug_info_100_inst_table(&debug_grammar);
+        write!(c, path);
+    }
+
+    {
+        let path = codegen_src_dir.join("../rspirv/grammar/debug_printf.rs");
+        let c = table::gen_debug_printf_inst_table(&printf_grammar);
+        write!(c, path);
+    }
+
+    {
+        let path = codegen_src_dir.join("../rspirv/grammar/clspv_reflection.rs");
+        let c = table::gen_clspv_reflection_inst_table(&clspv_grammar);
+        write!(c, path);
+    }
+
+    {
+        // Path to the C header of the C API, generated from its Rust source.
+        let source = codegen_src_dir.join("../rspirv/capi/mod.rs");
+        println!("cargo:rerun-if-changed={}", source.to_str().unwrap());
+        println!("cargo:rerun-if-changed={}", codegen_src_dir.to_str().unwrap());
+        contents.clear();
+        fs::File::open(&source).unwrap().read_to_string(&mut contents).unwrap();
+        let path = codegen_src_dir.join("../rspirv/capi/rspirv.h");
+        fs::write(path, capi::gen_capi_header(&contents)).unwrap();
+    }
 }
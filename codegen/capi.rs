@@ -0,0 +1,242 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate regex;
+
+static PREAMBLE: &str = "\
+/*
+ * Copyright 2017 Google Inc.
+ *
+ * Licensed under the Apache License, Version 2.0 (the \"License\");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an \"AS IS\" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+/*
+ * AUTOMATICALLY GENERATED from the C API in rspirv/capi/mod.rs.
+ * DO NOT MODIFY!
+ */
+
+/*
+ * C API of rspirv.
+ *
+ * The static and dynamic libraries are built with the `capi` feature
+ * enabled, e.g.,
+ *
+ *     cargo build -p rspirv --release --features capi
+ *
+ * All strings returned by the library are NUL-terminated and owned by the
+ * caller, who must release them with rspirv_string_free().
+ */
+
+#ifndef RSPIRV_H
+#define RSPIRV_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern \"C\" {
+#endif
+
+/* An opaque handle to a parsed SPIR-V module. */
+typedef struct rspirv_module rspirv_module;
+";
+
+static EPILOGUE: &str = "\
+#ifdef __cplusplus
+}  /* extern \"C\" */
+#endif
+
+#endif  /* RSPIRV_H */
+";
+
+/// The column C lines are wrapped at.
+const WIDTH: usize = 80;
+
+/// A function exported by the C API.
+struct Export {
+    doc: Vec<String>,
+    name: String,
+    params: Vec<(String, String)>,
+    ret: Option<String>,
+}
+
+/// Returns the C header declaring the functions exported in `source`, the
+/// Rust source of the C API.
+///
+/// The exported functions are the `#[no_mangle]` ones. Their doc comments
+/// are kept up to their `# Safety` section, with links to other functions
+/// turned into calls.
+pub fn gen_capi_header(source: &str) -> String {
+    let mut header = PREAMBLE.to_string();
+    for export in parse_exports(source) {
+        header.push('\n');
+        header.push_str(&gen_comment(&export.doc));
+        header.push_str(&gen_prototype(&export));
+    }
+    header.push('\n');
+    header.push_str(EPILOGUE);
+    header
+}
+
+fn parse_exports(source: &str) -> Vec<Export> {
+    let signature = regex::Regex::new(
+        r#"^pub unsafe extern "C" fn (\w+)\((.*?)\)\s*(?:->\s*(.+?))?\s*\{$"#).unwrap();
+    let mut exports = vec![];
+    let mut doc = vec![];
+    let mut lines = source.lines().map(str::trim);
+    while let Some(line) = lines.next() {
+        if line.starts_with("///") {
+            doc.push(line.trim_start_matches("///").trim().to_string());
+        } else if line == "#[no_mangle]" {
+            let mut text = String::new();
+            for line in &mut lines {
+                text.push_str(line);
+                if line.ends_with('{') {
+                    break;
+                }
+                text.push(' ');
+            }
+            let caps = signature
+                .captures(&text)
+                .unwrap_or_else(|| panic!("unrecognized C API function: {}", text));
+            let params = caps[2]
+                .split(',')
+                .map(str::trim)
+                .filter(|p| !p.is_empty())
+                .map(|p| {
+                    let (name, ty) = p.split_at(p.find(':').unwrap());
+                    (name.trim().to_string(), c_type(ty[1..].trim()))
+                })
+                .collect();
+            exports.push(Export {
+                doc: doc.split_off(0),
+                name: caps[1].to_string(),
+                params,
+                ret: caps.get(3).map(|ret| c_type(ret.as_str())),
+            });
+        } else {
+            doc.clear();
+        }
+    }
+    exports
+}
+
+/// Returns the C type of the given Rust type.
+fn c_type(ty: &str) -> String {
+    if let Some(pointee) = ty.strip_prefix("*mut ") {
+        format!("{}*", c_type(pointee))
+    } else if let Some(pointee) = ty.strip_prefix("*const ") {
+        format!("const {}*", c_type(pointee))
+    } else {
+        match ty {
+            "c_char" => "char",
+            "u8" => "uint8_t",
+            "u32" => "uint32_t",
+            "usize" => "size_t",
+            "bool" => "bool",
+            "Module" => "rspirv_module",
+            _ => panic!("no C type for Rust type {}", ty),
+        }.to_string()
+    }
+}
+
+fn gen_comment(doc: &[String]) -> String {
+    let fn_link = regex::Regex::new(r"\[`(\w+)`\]\(fn\.\w+\.html\)").unwrap();
+    let link = regex::Regex::new(r"\[([^\]]+)\]\([^)]*\)").unwrap();
+    let end = doc.iter().position(|l| l == "# Safety").unwrap_or(doc.len());
+    let text = doc[..end].join("\n");
+    let text = fn_link.replace_all(&text, "$1()");
+    let text = link.replace_all(&text, "$1");
+
+    let mut lines: Vec<String> = vec![];
+    for paragraph in text.split("\n\n") {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && 3 + line.len() + 1 + word.len() > WIDTH {
+                lines.push(line.split_off(0));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+
+    if lines.len() == 1 && 3 + lines[0].len() + 3 <= WIDTH {
+        return format!("/* {} */\n", lines[0]);
+    }
+    let mut comment = "/*\n".to_string();
+    for line in lines {
+        if line.is_empty() {
+            comment.push_str(" *\n");
+        } else {
+            comment.push_str(&format!(" * {}\n", line));
+        }
+    }
+    comment.push_str(" */\n");
+    comment
+}
+
+fn gen_prototype(export: &Export) -> String {
+    let head = format!("{} {}(",
+                       export.ret.as_ref().map_or("void", |ret| ret.as_str()),
+                       export.name);
+    let params: Vec<String> = export.params
+        .iter()
+        .map(|(name, ty)| format!("{} {}", ty, name))
+        .collect();
+    let params = if params.is_empty() {
+        vec!["void".to_string()]
+    } else {
+        params
+    };
+
+    let mut prototype = head.clone();
+    let mut column = head.len();
+    for (i, param) in params.iter().enumerate() {
+        let last = i + 1 == params.len();
+        let piece = format!("{}{}", param, if last { ");" } else { "," });
+        if i > 0 {
+            if column + 1 + piece.len() > WIDTH {
+                prototype.push('\n');
+                prototype.push_str(&" ".repeat(head.len()));
+                column = head.len();
+            } else {
+                prototype.push(' ');
+                column += 1;
+            }
+        }
+        prototype.push_str(&piece);
+        column += piece.len();
+    }
+    prototype.push('\n');
+    prototype
+}
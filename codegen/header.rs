@@ -17,7 +17,7 @@ use structs;
 use utils::*;
 
 static VAULE_ENUM_ATTRIBUTE: &'static str = "\
-#[repr(u32)]\n#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]";
+#[repr(u32)]\n#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]";
 
 static GLSL_STD_450_SPEC_LINK: &'static str = "\
 https://www.khronos.org/registry/spir-v/specs/1.0/GLSL.std.450.html";
@@ -25,6 +25,33 @@ https://www.khronos.org/registry/spir-v/specs/1.0/GLSL.std.450.html";
 static OPENCL_STD_SPEC_LINK: &'static str = "\
 https://www.khronos.org/registry/spir-v/specs/1.2/OpenCL.ExtendedInstructionSet.100.html";
 
+static DEBUG_INFO_SPEC_LINK: &'static str = "\
+https://github.khronos.org/SPIRV-Registry/nonsemantic/NonSemantic.Shader.DebugInfo.100.html";
+
+static DEBUG_PRINTF_SPEC_LINK: &'static str = "\
+https://github.khronos.org/SPIRV-Registry/nonsemantic/NonSemantic.DebugPrintf.html";
+
+static CLSPV_REFLECTION_SPEC_LINK: &'static str = "\
+https://github.khronos.org/SPIRV-Registry/nonsemantic/NonSemantic.ClspvReflection.html";
+
+/// Returns the code implementing `num_traits::FromPrimitive` for the value
+/// enum `kind` with the given `(variant, value)` pairs.
+///
+/// This is written out instead of derived so that the header only needs
+/// `num-traits` without its `std` feature.
+fn gen_from_primitive(kind: &str, variants: &[(String, u32)]) -> String {
+    let arms: Vec<String> = variants.iter().map(|&(ref variant, value)| {
+        format!("            {} => {}::{},", value, kind, variant)
+    }).collect();
+    format!("impl num_traits::FromPrimitive for {kind} {{\n    \
+             fn from_i64(n: i64) -> Option<Self> {{\n        \
+             if n < 0 {{ None }} else {{ Self::from_u64(n as u64) }}\n    }}\n\n    \
+             fn from_u64(n: u64) -> Option<Self> {{\n        \
+             Some(match n {{\n{arms}\n            _ => return None,\n        }})\n    }}\n}}\n",
+            kind = kind,
+            arms = arms.join("\n"))
+}
+
 /// Returns the markdown string containing a link to the spec for the given
 /// operand `kind`.
 fn get_spec_link(kind: &str) -> String {
@@ -66,6 +93,7 @@ fn gen_value_enum_operand_kind(grammar: &structs::OperandKind) -> String {
     // Use associated constants for these aliases.
     let mut seen_discriminator = BTreeMap::new();
     let mut enumerants = vec![];
+    let mut variants = vec![];
     let mut aliases = vec![];
     for e in &grammar.enumerants {
         if seen_discriminator.contains_key(&e.value.number) {
@@ -74,30 +102,32 @@ fn gen_value_enum_operand_kind(grammar: &structs::OperandKind) -> String {
                                  seen_discriminator.get(&e.value.number).unwrap()));
         } else {
             seen_discriminator.insert(e.value.number, &e.symbol);
-            enumerants.push(
-                if grammar.kind == "Dim" {
-                    // Special case for Dim. Its enumerants can start with a digit.
-                    // So prefix with the kind name here.
-                    format!("    Dim{} = {},", e.symbol, e.value.number)
-                } else {
-                    format!("    {} = {},", e.symbol, e.value.number)
-                });
+            let variant = if grammar.kind == "Dim" {
+                // Special case for Dim. Its enumerants can start with a digit.
+                // So prefix with the kind name here.
+                format!("Dim{}", e.symbol)
+            } else {
+                e.symbol.clone()
+            };
+            enumerants.push(format!("    {} = {},", variant, e.value.number));
+            variants.push((variant, e.value.number));
         }
     }
 
     let mut associated_consts = String::new();
     if !aliases.is_empty() {
-        associated_consts = format!("\n#[allow(non_upper_case_globals)]\nimpl {} {{\n{}\n}}",
+        associated_consts = format!("\n#[allow(non_upper_case_globals)]\nimpl {} {{\n{}\n}}\n",
                                     grammar.kind, aliases.join("\n"));
     }
 
-    format!("{doc}\n{attribute}\npub enum {kind} {{\n{enumerants}\n}}\n{aliases}",
+    format!("{doc}\n{attribute}\npub enum {kind} {{\n{enumerants}\n}}\n{aliases}\n{from}",
             doc = format!("/// SPIR-V operand kind: {}",
                           get_spec_link(&grammar.kind)),
             attribute = VAULE_ENUM_ATTRIBUTE,
             kind = grammar.kind,
             aliases = associated_consts,
-            enumerants = enumerants.join("\n"))
+            enumerants = enumerants.join("\n"),
+            from = gen_from_primitive(&grammar.kind, &variants))
 }
 
 /// Returns the code defining the enum for an operand kind by parsing
@@ -140,16 +170,20 @@ pub fn gen_spirv_header(grammar: &structs::Grammar) -> String {
     }
     { // Opcodes.
         // Get the instruction table.
-        let opcodes: Vec<String> = grammar.instructions.iter().map(|inst| {
+        let variants: Vec<(String, u32)> = grammar.instructions.iter().map(|inst| {
             // Omit the "Op" prefix.
-            format!("    {} = {},", &inst.opname[2..], inst.opcode)
+            (inst.opname[2..].to_string(), inst.opcode)
+        }).collect();
+        let opcodes: Vec<String> = variants.iter().map(|&(ref variant, opcode)| {
+            format!("    {} = {},", variant, opcode)
         }).collect();
         ret.push_str(&format!("/// SPIR-V {link} opcodes\n\
                                {attribute}\n\
-                               pub enum Op {{\n{opcodes}\n}}\n",
+                               pub enum Op {{\n{opcodes}\n}}\n\n{from}",
                               link = get_spec_link("instructions"),
                               attribute = VAULE_ENUM_ATTRIBUTE,
-                              opcodes = opcodes.join("\n")));
+                              opcodes = opcodes.join("\n"),
+                              from = gen_from_primitive("Op", &variants)));
     }
 
     ret
@@ -161,16 +195,20 @@ pub fn gen_glsl_std_450_opcodes(grammar: &structs::ExtInstSetGrammar) -> String
 
     { // Opcodes.
         // Get the instruction table.
-        let opcodes: Vec<String> = grammar.instructions.iter().map(|inst| {
+        let variants: Vec<(String, u32)> = grammar.instructions.iter().map(|inst| {
             // Omit the "Op" prefix.
-            format!("    {} = {},", inst.opname, inst.opcode)
+            (inst.opname.to_string(), inst.opcode)
+        }).collect();
+        let opcodes: Vec<String> = variants.iter().map(|&(ref variant, opcode)| {
+            format!("    {} = {},", variant, opcode)
         }).collect();
         ret.push_str(&format!("/// [GLSL.std.450]({link}) extended instruction opcode\n\
                                {attribute}\n\
-                               pub enum GLOp {{\n{opcodes}\n}}\n",
+                               pub enum GLOp {{\n{opcodes}\n}}\n\n{from}",
                               link = GLSL_STD_450_SPEC_LINK,
                               attribute = VAULE_ENUM_ATTRIBUTE,
-                              opcodes = opcodes.join("\n")));
+                              opcodes = opcodes.join("\n"),
+                              from = gen_from_primitive("GLOp", &variants)));
     }
 
     ret
@@ -182,17 +220,78 @@ pub fn gen_opencl_std_opcodes(grammar: &structs::ExtInstSetGrammar) -> String {
 
     { // Opcodes.
         // Get the instruction table.
-        let opcodes: Vec<String> = grammar.instructions.iter().map(|inst| {
+        let variants: Vec<(String, u32)> = grammar.instructions.iter().map(|inst| {
             // Omit the "Op" prefix.
-            format!("    {} = {},", inst.opname, inst.opcode)
+            (inst.opname.to_string(), inst.opcode)
+        }).collect();
+        let opcodes: Vec<String> = variants.iter().map(|&(ref variant, opcode)| {
+            format!("    {} = {},", variant, opcode)
         }).collect();
         ret.push_str(&format!("/// [OpenCL.std]({link}) extended instruction opcode\n\
                                {attribute}\n\
-                               pub enum CLOp {{\n{opcodes}\n}}\n",
+                               pub enum CLOp {{\n{opcodes}\n}}\n\n{from}",
                               link = OPENCL_STD_SPEC_LINK,
                               attribute = VAULE_ENUM_ATTRIBUTE,
-                              opcodes = opcodes.join("\n")));
+                              opcodes = opcodes.join("\n"),
+                              from = gen_from_primitive("CLOp", &variants)));
     }
 
     ret
 }
+
+/// Returns the NonSemantic.Shader.DebugInfo.100 extended instruction opcodes.
+pub fn gen_debug_info_opcodes(grammar: &structs::ExtInstSetGrammar) -> String {
+    let mut ret = String::new();
+
+    { // Opcodes.
+        // Get the instruction table.
+        let variants: Vec<(String, u32)> = grammar.instructions.iter().map(|inst| {
+            (inst.opname.to_string(), inst.opcode)
+        }).collect();
+        let opcodes: Vec<String> = variants.iter().map(|&(ref variant, opcode)| {
+            format!("    {} = {},", variant, opcode)
+        }).collect();
+        ret.push_str(&format!("/// [NonSemantic.Shader.DebugInfo.100]({link}) extended instruction opcode\n\
+                               {attribute}\n\
+                               pub enum DebugInfoOp {{\n{opcodes}\n}}\n\n{from}",
+                              link = DEBUG_INFO_SPEC_LINK,
+                              attribute = VAULE_ENUM_ATTRIBUTE,
+                              opcodes = opcodes.join("\n"),
+                              from = gen_from_primitive("DebugInfoOp", &variants)));
+    }
+
+    ret
+}
+
+/// Returns the opcode enum `name` for the non-semantic extended instruction
+/// set `set` documented at `link`.
+fn gen_non_semantic_opcodes(grammar: &structs::ExtInstSetGrammar,
+                            set: &str, name: &str, link: &str) -> String {
+    let variants: Vec<(String, u32)> = grammar.instructions.iter().map(|inst| {
+        (inst.opname.to_string(), inst.opcode)
+    }).collect();
+    let opcodes: Vec<String> = variants.iter().map(|&(ref variant, opcode)| {
+        format!("    {} = {},", variant, opcode)
+    }).collect();
+    format!("/// [{set}]({link}) extended instruction opcode\n\
+             {attribute}\n\
+             pub enum {name} {{\n{opcodes}\n}}\n\n{from}",
+            set = set,
+            link = link,
+            attribute = VAULE_ENUM_ATTRIBUTE,
+            name = name,
+            opcodes = opcodes.join("\n"),
+            from = gen_from_primitive(name, &variants))
+}
+
+/// Returns the NonSemantic.DebugPrintf extended instruction opcodes.
+pub fn gen_debug_printf_opcodes(grammar: &structs::ExtInstSetGrammar) -> String {
+    gen_non_semantic_opcodes(grammar, "NonSemantic.DebugPrintf",
+                             "DebugPrintfOp", DEBUG_PRINTF_SPEC_LINK)
+}
+
+/// Returns the NonSemantic.ClspvReflection extended instruction opcodes.
+pub fn gen_clspv_reflection_opcodes(grammar: &structs::ExtInstSetGrammar) -> String {
+    gen_non_semantic_opcodes(grammar, "NonSemantic.ClspvReflection",
+                             "ClspvReflectionOp", CLSPV_REFLECTION_SPEC_LINK)
+}
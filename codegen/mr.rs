@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use structs;
+use std::collections::BTreeSet;
 
 use utils::*;
 
@@ -94,6 +95,9 @@ fn get_init_list(params: &[structs::Operand]) -> Vec<String> {
                 let kind = get_mr_operand_kind(&param.kind);
                 Some(if kind == "LiteralString" {
                     format!("mr::Operand::LiteralString({}.into())", name)
+                } else if kind == "LiteralExtInstInteger" {
+                    // Symbolic if the builder knows the imported set.
+                    format!("self.ext_insts.operand(set, {})", name)
                 } else {
                     format!("mr::Operand::{}({})", kind, name)
                 })
@@ -211,6 +215,7 @@ pub fn gen_mr_operand_kinds(grammar: &Vec<structs::OperandKind>) -> String {
             "    LiteralFloat32(f32),",
             "    LiteralFloat64(f64),",
             "    LiteralExtInstInteger(u32),",
+            "    ExtInstOpcode(grammar::ExtInstSet, u32),",
             "    LiteralSpecConstantOpInteger(spirv::Op),"];
         let str_kinds: Vec<String> = kinds.iter().filter(|element| {
             element.ends_with("String")
@@ -245,11 +250,27 @@ pub fn gen_mr_operand_kinds(grammar: &Vec<structs::OperandKind>) -> String {
                                "LiteralFloat32", "LiteralFloat64"]);
         let cases: Vec<String> =
             kinds.iter().map(|element| {
-                format!("{s:12}Operand::{kind}(ref v) => \
-                         write!(f, \"{{:?}}\", v),",
-                        s = "",
-                        kind = element)
+                if *element == "Dim" {
+                    // Dim enumerants are prefixed with "Dim" in Rust; strip it.
+                    format!("{s:12}Operand::{kind}(ref v) => \
+                             write!(f, \"{{}}\", &format!(\"{{:?}}\", v)[3..]),",
+                            s = "",
+                            kind = element)
+                } else {
+                    format!("{s:12}Operand::{kind}(ref v) => \
+                             write!(f, \"{{:?}}\", v),",
+                            s = "",
+                            kind = element)
+                }
             }).collect();
+        // Extended instructions of known sets are shown by name.
+        let mut cases = cases;
+        cases.push(format!(
+            "{s:12}Operand::ExtInstOpcode(set, v) => match set.lookup_opcode(v) {{\n\
+             {s:16}Some(inst) => write!(f, \"{{}}\", inst.opname),\n\
+             {s:16}None => write!(f, \"{{:?}}\", v),\n\
+             {s:12}}},",
+            s = ""));
         let impl_code = format!(
             "impl fmt::Display for Operand {{\n\
              {s:4}fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {{\n\
@@ -259,9 +280,106 @@ pub fn gen_mr_operand_kinds(grammar: &Vec<structs::OperandKind>) -> String {
         ret.push_str(&impl_code);
     }
 
+    { // Capabilities and extensions required by operand values.
+        // The capabilities of Capability enumerants are the ones they
+        // implicitly declare instead of ones they require.
+        let capabilities = gen_operand_requirements(grammar, |kind, e| {
+            if kind.kind == "Capability" {
+                return vec![];
+            }
+            e.capabilities.iter().map(|c| format!("spirv::Capability::{}", c)).collect()
+        });
+        let extensions = gen_operand_requirements(grammar, |_, e| {
+            e.extensions.iter().map(|e| format!("\"{}\"", e)).collect()
+        });
+        let impl_code = format!(
+            "\nimpl Operand {{\n\
+             {s:4}/// Returns the capabilities the value of this operand requires.\n\
+             {s:4}///\n\
+             {s:4}/// Each entry lists alternatives, any one of which suffices.\n\
+             {s:4}pub fn required_capabilities(&self) -> Vec<&'static [spirv::Capability]> {{\n\
+             {s:8}match *self {{\n{capabilities}\n{s:12}_ => vec![],\n{s:8}}}\n{s:4}}}\n\n\
+             {s:4}/// Returns the extensions the value of this operand requires.\n\
+             {s:4}///\n\
+             {s:4}/// Each entry lists alternatives, any one of which suffices.\n\
+             {s:4}pub fn required_extensions(&self) -> Vec<&'static [&'static str]> {{\n\
+             {s:8}match *self {{\n{extensions}\n{s:12}_ => vec![],\n{s:8}}}\n{s:4}}}\n}}\n",
+            s = "",
+            capabilities = capabilities.join("\n"),
+            extensions = extensions.join("\n"));
+        ret.push_str(&impl_code);
+    }
+
     ret
 }
 
+/// Returns the match arms returning the requirements of enumerant operand
+/// values, as listed for each kind and enumerant by `get`.
+///
+/// Value enums get one arm per enumerant with requirements; bit enums get
+/// one arm per kind collecting the requirements of all set bits.
+fn gen_operand_requirements<F>(grammar: &Vec<structs::OperandKind>, get: F) -> Vec<String>
+    where F: Fn(&structs::OperandKind, &structs::Enumerant) -> Vec<String>
+{
+    let mut arms = vec![];
+    for kind in grammar {
+        if kind.category == "ValueEnum" {
+            let mut seen = BTreeSet::new();
+            for e in &kind.enumerants {
+                // Aliases share the variant of the first enumerant.
+                if !seen.insert(e.value.number) {
+                    continue;
+                }
+                let reqs = get(kind, e);
+                if reqs.is_empty() {
+                    continue;
+                }
+                let symbol = if kind.kind == "Dim" {
+                    format!("Dim{}", e.symbol)
+                } else {
+                    e.symbol.clone()
+                };
+                arms.push(format!("{s:12}Operand::{kind}(spirv::{kind}::{symbol}) => \
+                                   vec![&[{reqs}]],",
+                                  s = "",
+                                  kind = kind.kind,
+                                  symbol = symbol,
+                                  reqs = reqs.join(", ")));
+            }
+        } else if kind.category == "BitEnum" {
+            let bits: Vec<String> = kind.enumerants.iter().filter(|e| {
+                // The empty mask is contained in every value.
+                e.symbol != "None"
+            }).filter_map(|e| {
+                let reqs = get(kind, e);
+                if reqs.is_empty() {
+                    return None;
+                }
+                // Special treatment for "NaN"
+                let mut symbol = snake_casify(&e.symbol);
+                if &symbol == "not_na_n" {
+                    symbol = "not_nan".to_string()
+                }
+                Some(format!("{s:16}if v.contains(spirv::{kind}::{symbol}) {{ \
+                              reqs.push(&[{reqs}]); }}",
+                             s = "",
+                             kind = kind.kind,
+                             symbol = symbol.to_uppercase(),
+                             reqs = reqs.join(", ")))
+            }).collect();
+            if !bits.is_empty() {
+                arms.push(format!("{s:12}Operand::{kind}(v) => {{\n\
+                                   {s:16}let mut reqs: Vec<&'static [_]> = vec![];\n\
+                                   {bits}\n{s:16}reqs\n{s:12}}}",
+                                  s = "",
+                                  kind = kind.kind,
+                                  bits = bits.join("\n")));
+            }
+        }
+    }
+    arms
+}
+
 /// Returns the generated build methods for SPIR-V types by walking the given
 /// SPIR-V instructions `grammar`.
 pub fn gen_mr_builder_types(grammar: &structs::Grammar) -> String {
@@ -483,3 +601,117 @@ pub fn gen_mr_builder_annotation(grammar: &structs::Grammar) -> String {
     }).collect();
     format!("impl Builder {{\n{}\n}}", elements.join("\n\n"))
 }
+
+/// Returns a suitable name for the given parameter of an extended
+/// instruction, avoiding Rust keywords and the trailing noise some operand
+/// names in the grammar carry.
+fn get_ext_param_name(param: &structs::Operand) -> String {
+    if param.kind == "PairIdRefIdRef" {
+        return "enumerators".to_string();
+    }
+    if param.name.is_empty() && param.quantifier == "*" {
+        return "arguments".to_string();
+    }
+    let name = get_param_name(param);
+    match name.trim_end_matches('_') {
+        "type" => "ty".to_string(),
+        "macro" => "macro_def".to_string(),
+        // Clashes with the operand list under construction.
+        "operands" => "arguments".to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// Returns the generated build methods for all instructions of the
+/// non-semantic extended instruction set `set` in `grammar`, whose opcodes
+/// are in the `spirv::{op_enum}` enum.
+///
+/// The set is `grammar::ExtInstSet::{ext_set}` in rspirv. Its id is
+/// obtained with the `import` builder method, and each
+/// method name is the snake-cased opname following `prefix`. All operands
+/// of non-semantic instructions are ids.
+fn gen_mr_builder_non_semantic(grammar: &structs::ExtInstSetGrammar,
+                               set: &str, ext_set: &str, op_enum: &str,
+                               import: &str, prefix: &str) -> String {
+    let elements: Vec<String> = grammar.instructions.iter().map(|inst| {
+        let mut type_generics = String::new();
+        let mut params = vec!["result_type: spirv::Word".to_string()];
+        let mut init = vec!["mr::Operand::IdRef(set)".to_string(),
+                            format!("mr::Operand::ExtInstOpcode(\
+                                     grammar::ExtInstSet::{}, spirv::{}::{} as u32)",
+                                    ext_set, op_enum, inst.opname)];
+        let mut extras = vec![];
+        for param in &inst.operands {
+            let name = get_ext_param_name(param);
+            let kind = get_enum_underlying_type(&param.kind, false);
+            if param.quantifier == "" {
+                params.push(format!("{}: {}", name, kind));
+                init.push(format!("mr::Operand::IdRef({})", name));
+            } else if param.quantifier == "?" {
+                params.push(format!("{}: Option<{}>", name, kind));
+                extras.push(format!(
+                    "{s:8}if let Some(v) = {name} {{\n\
+                     {s:12}operands.push(mr::Operand::IdRef(v));\n\
+                     {s:8}}}\n",
+                    s = "",
+                    name = name));
+            } else {
+                type_generics = format!("<T: AsRef<[{}]>>", kind);
+                params.push(format!("{}: T", name));
+                extras.push(if param.kind == "PairIdRefIdRef" {
+                    format!("{s:8}for v in {name}.as_ref() {{\n\
+                             {s:12}operands.push(mr::Operand::IdRef(v.0));\n\
+                             {s:12}operands.push(mr::Operand::IdRef(v.1));\n\
+                             {s:8}}}\n",
+                            s = "",
+                            name = name)
+                } else {
+                    format!("{s:8}for v in {name}.as_ref() {{\n\
+                             {s:12}operands.push(mr::Operand::IdRef(*v));\n\
+                             {s:8}}}\n",
+                            s = "",
+                            name = name)
+                });
+            }
+        }
+        format!("{s:4}/// Appends a {opname} instruction from the {set}\n\
+                 {s:4}/// set to either the current basic block or the module if no basic block\n\
+                 {s:4}/// is under construction, and returns the result id.\n\
+                 {s:4}pub fn {name}{generic}(&mut self, {params}) -> spirv::Word {{\n\
+                 {s:8}let set = self.{import}();\n\
+                 {s:8}let id = self.id();\n\
+                 {s:8}let {m}operands = vec![{init}];\n\
+                 {extras}\
+                 {s:8}self.non_semantic_inst(mr::Instruction::new(\
+                     spirv::Op::ExtInst, Some(result_type), Some(id), operands));\n\
+                 {s:8}id\n\
+                 {s:4}}}",
+                s = "",
+                opname = inst.opname,
+                set = set,
+                import = import,
+                name = format!("{}{}", prefix, snake_casify(&inst.opname)),
+                generic = type_generics,
+                params = params.join(", "),
+                m = if extras.is_empty() { "" } else { "mut " },
+                init = init.join(", "),
+                extras = extras.join(""))
+    }).collect();
+    format!("impl Builder {{\n{}\n}}", elements.join("\n\n"))
+}
+
+pub fn gen_mr_builder_debug_info(grammar: &structs::ExtInstSetGrammar) -> String {
+    gen_mr_builder_non_semantic(grammar, "NonSemantic.Shader.DebugInfo.100",
+                                "DebugInfo100", "DebugInfoOp", "debug_info_import", "")
+}
+
+pub fn gen_mr_builder_debug_printf(grammar: &structs::ExtInstSetGrammar) -> String {
+    gen_mr_builder_non_semantic(grammar, "NonSemantic.DebugPrintf",
+                                "DebugPrintf", "DebugPrintfOp", "debug_printf_import", "")
+}
+
+pub fn gen_mr_builder_clspv_reflection(grammar: &structs::ExtInstSetGrammar) -> String {
+    // Prefixed to keep names such as `kernel` distinct from core methods.
+    gen_mr_builder_non_semantic(grammar, "NonSemantic.ClspvReflection",
+                                "ClspvReflection", "ClspvReflectionOp", "clspv_reflection_import", "clspv_")
+}
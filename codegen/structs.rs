@@ -36,6 +36,8 @@ pub struct Instruction {
     pub operands: Vec<Operand>,
     #[serde(default)]
     pub capabilities: Vec<String>,
+    #[serde(default)]
+    pub extensions: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -48,6 +50,8 @@ pub struct Enumerant {
     pub parameters: Vec<Operand>,
     #[serde(default)]
     pub capabilities: Vec<String>,
+    #[serde(default)]
+    pub extensions: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -75,7 +79,9 @@ pub struct Grammar {
 
 #[derive(Debug, Deserialize)]
 pub struct ExtInstSetGrammar {
+    #[serde(default)]
     pub copyright: Vec<String>,
+    #[serde(default)]
     pub version: u32,
     pub revision: u32,
     pub instructions: Vec<Instruction>,
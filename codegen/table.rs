@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use structs;
+use std::collections::BTreeSet;
 
 use utils::*;
 
@@ -49,10 +50,14 @@ fn gen_instruction_table(grammar: &Vec<structs::Instruction>,
                     caps = inst.capabilities.join(", "),
                     operands = operands.join(", "))
         } else {
-            format!("    inst!({opname}, [{caps}], [{operands}]),",
+            let exts: Vec<String> = inst.extensions.iter().map(|e| {
+                format!("\"{}\"", e)
+            }).collect();
+            format!("    inst!({opname}, [{caps}], [{exts}], [{operands}]),",
                     // Omit the "Op" prefix.
                     opname = &inst.opname[2..],
                     caps = inst.capabilities.join(", "),
+                    exts = exts.join(", "),
                     operands = operands.join(", "))
         }
     }).collect();
@@ -105,3 +110,195 @@ pub fn gen_opencl_std_100_inst_table(grammar: &structs::ExtInstSetGrammar) -> St
     gen_instruction_table(
         &grammar.instructions, "OPENCL_STD_100_INSTRUCTION_TABLE", true)
 }
+
+/// Writes the generated instruction table for NonSemantic.Shader.DebugInfo.100
+/// extended instruction set from `grammar` to the file with the given
+/// `filename`.
+pub fn gen_debug_info_100_inst_table(grammar: &structs::ExtInstSetGrammar) -> String {
+    gen_instruction_table(
+        &grammar.instructions, "DEBUG_INFO_100_INSTRUCTION_TABLE", true)
+}
+
+/// Writes the generated instruction table for NonSemantic.DebugPrintf
+/// extended instruction set from `grammar` to the file with the given
+/// `filename`.
+pub fn gen_debug_printf_inst_table(grammar: &structs::ExtInstSetGrammar) -> String {
+    gen_instruction_table(
+        &grammar.instructions, "DEBUG_PRINTF_INSTRUCTION_TABLE", true)
+}
+
+/// Writes the generated instruction table for NonSemantic.ClspvReflection
+/// extended instruction set from `grammar` to the file with the given
+/// `filename`.
+pub fn gen_clspv_reflection_inst_table(grammar: &structs::ExtInstSetGrammar) -> String {
+    gen_instruction_table(
+        &grammar.instructions, "CLSPV_REFLECTION_INSTRUCTION_TABLE", true)
+}
+
+/// Returns the `match` expression testing whether an opcode belongs to the
+/// instructions of the given `class` in `grammar`.
+fn gen_class_match(grammar: &structs::Grammar, class: &str) -> String {
+    let opcodes: Vec<String> = grammar.instructions.iter().filter(|inst| {
+        inst.class == class
+    }).map(|inst| {
+        format!("spirv::Op::{}", &inst.opname[2..])
+    }).collect();
+    format!("    match opcode {{\n        {} => true,\n        _ => false,\n    }}",
+            opcodes.join(" |\n        "))
+}
+
+/// Returns the roles of the ids in the logical operand named `name` of kind
+/// `kind` in instruction `inst`, in order. Operands without ids have none.
+fn get_id_roles(inst: &structs::Instruction, kind: &str, name: &str) -> Vec<&'static str> {
+    let name = name.trim_matches('\'');
+    let opname = inst.opname.as_str();
+    match kind {
+        "IdScope" => return vec!["Scope"],
+        "IdMemorySemantics" => return vec!["MemorySemantics"],
+        "PairIdRefIdRef" => return vec!["Value", "BranchTarget"],
+        "PairLiteralIntegerIdRef" => return vec!["BranchTarget"],
+        "PairIdRefLiteralInteger" => return vec!["DecorationTarget"],
+        "IdRef" => (),
+        _ => return vec![],
+    }
+    let role = if (inst.class == "Annotation" && name != "Decoration Group") ||
+                  opname == "OpName" || opname == "OpMemberName" {
+        if name.starts_with("Target") || name == "Structure Type" {
+            "DecorationTarget"
+        } else {
+            "Value"
+        }
+    } else if (inst.class == "Type" && !matches!(name, "Length" | "Rows" | "Columns" | "Use")) ||
+              opname == "OpFunction" {
+        "Type"
+    } else {
+        match name {
+            "Target Label" | "True Label" | "False Label" | "Default" |
+            "Merge Block" | "Continue Target" => "BranchTarget",
+            "Function" | "Entry Point" | "Invoke" => "Function",
+            "Type" if opname == "OpCooperativeMatrixLengthKHR" => "Type",
+            "Set" if opname == "OpExtInst" => "ExtInstSet",
+            "Pointer" | "Interface" => "MemoryObject",
+            "Base" if opname.contains("AccessChain") => "MemoryObject",
+            "Structure" if opname == "OpArrayLength" => "MemoryObject",
+            "Target" | "Source" if opname.starts_with("OpCopyMemory") => "MemoryObject",
+            "Destination" | "Source" if opname == "OpGroupAsyncCopy" => "MemoryObject",
+            _ => "Value",
+        }
+    };
+    vec![role]
+}
+
+/// Returns the generated match arms of the grammar::reflect::id_roles
+/// function, for all instructions with ids of non-value roles.
+fn gen_id_roles_match(grammar: &structs::Grammar) -> String {
+    let arms: Vec<String> = grammar.instructions.iter().filter_map(|inst| {
+        let roles: Vec<Vec<&str>> = inst.operands.iter().filter(|operand| {
+            operand.kind != "IdResultType" && operand.kind != "IdResult"
+        }).map(|operand| {
+            get_id_roles(inst, &operand.kind, &operand.name)
+        }).collect();
+        if roles.iter().flat_map(|r| r).all(|role| *role == "Value") {
+            return None;
+        }
+        let roles: Vec<String> = roles.iter().map(|r| {
+            let r: Vec<String> = r.iter().map(|role| format!("IdRole::{}", role)).collect();
+            format!("&[{}]", r.join(", "))
+        }).collect();
+        Some(format!("        spirv::Op::{} => &[{}],", &inst.opname[2..], roles.join(", ")))
+    }).collect();
+    format!("    match opcode {{\n{}\n        _ => &[],\n    }}", arms.join("\n"))
+}
+
+/// Returns the generated match arms of the
+/// grammar::reflect::implied_capabilities function, for all capabilities
+/// implicitly declaring others.
+fn gen_implied_capabilities_match(grammar: &structs::Grammar) -> String {
+    let capabilities: Vec<&structs::Enumerant> = grammar.operand_kinds
+        .iter()
+        .filter(|kind| kind.kind == "Capability")
+        .flat_map(|kind| &kind.enumerants)
+        .collect();
+    // Aliases share the variant of the first enumerant of their value.
+    let variant = |symbol: &str| {
+        let value = capabilities.iter().find(|e| e.symbol == symbol).unwrap().value.number;
+        &capabilities.iter().find(|e| e.value.number == value).unwrap().symbol
+    };
+    let mut seen = BTreeSet::new();
+    let arms: Vec<String> = capabilities
+        .iter()
+        .filter(|e| seen.insert(e.value.number) && !e.capabilities.is_empty())
+        .map(|e| {
+            let mut implied: Vec<String> = e.capabilities
+                .iter()
+                .map(|c| format!("spirv::Capability::{}", variant(c)))
+                .collect();
+            implied.dedup();
+            format!("        spirv::Capability::{} => &[{}],", e.symbol, implied.join(", "))
+        })
+        .collect();
+    format!("    match capability {{\n{}\n        _ => &[],\n    }}", arms.join("\n"))
+}
+
+/// Returns the body of the `known_extensions` function: the names of all
+/// extensions instructions and enumerants of the given `grammar` require,
+/// in order.
+fn gen_known_extensions(grammar: &structs::Grammar) -> String {
+    let enumerants = grammar.operand_kinds.iter().flat_map(|kind| &kind.enumerants);
+    let names: BTreeSet<&str> = grammar.instructions
+        .iter()
+        .flat_map(|inst| &inst.extensions)
+        .chain(enumerants.flat_map(|e| &e.extensions))
+        .map(|name| name.as_str())
+        .collect();
+    let names: Vec<String> = names.iter().map(|name| format!("        \"{}\",", name)).collect();
+    format!("    &[\n{}\n    ]", names.join("\n"))
+}
+
+/// Returns the generated grammar::reflect functions, which classify opcodes
+/// by the instruction classes of the given SPIR-V `grammar`.
+pub fn gen_grammar_reflect(grammar: &structs::Grammar) -> String {
+    format!("//! Reflect functions for SPIR-V instructions.\n\n\
+             use grammar::IdRole;\n\
+             use spirv;\n\n\
+             /// Returns true if the given opcode is for a location debug instruction.\n\
+             pub fn is_location_debug(opcode: spirv::Op) -> bool {{\n{location_debug}\n}}\n\n\
+             /// Returns true if the given opcode is for a non-location debug instruction.\n\
+             pub fn is_nonlocation_debug(opcode: spirv::Op) -> bool {{\n{debug}\n}}\n\n\
+             /// Returns true if the given opcode is for a debug instruction.\n\
+             pub fn is_debug(opcode: spirv::Op) -> bool {{\n    \
+             is_location_debug(opcode) || is_nonlocation_debug(opcode)\n}}\n\n\
+             /// Returns true if the given opcode is for an annotation instruction.\n\
+             pub fn is_annotation(opcode: spirv::Op) -> bool {{\n{annotation}\n}}\n\n\
+             /// Returns true if the given opcode is for a type-declaring instruction.\n\
+             pub fn is_type(opcode: spirv::Op) -> bool {{\n{ty}\n}}\n\n\
+             /// Returns true if the given opcode is for a constant-defining instruction.\n\
+             pub fn is_constant(opcode: spirv::Op) -> bool {{\n{constant}\n}}\n\n\
+             /// Returns true if the given opcode is for a variable-defining instruction.\n\
+             pub fn is_variable(opcode: spirv::Op) -> bool {{\n    \
+             opcode == spirv::Op::Variable\n}}\n\n\
+             /// Returns true if the given opcode is for a terminator instruction.\n\
+             pub fn is_terminator(opcode: spirv::Op) -> bool {{\n{terminator}\n}}\n\n\
+             /// Returns the roles of the ids in each logical operand of the given\n\
+             /// opcode, not counting the result type and id.\n\
+             ///\n\
+             /// Ids beyond the logical operands, such as the parameters of enumerants,\n\
+             /// are values. So are all ids of the opcodes this returns no roles for.\n\
+             pub fn id_roles(opcode: spirv::Op) -> &'static [&'static [IdRole]] {{\n{id_roles}\n}}\n\n\
+             /// Returns the capabilities the given capability implicitly declares,\n\
+             /// not counting those they in turn declare.\n\
+             pub fn implied_capabilities(capability: spirv::Capability) -> &'static [spirv::Capability] {{\n\
+             {implied}\n}}\n\n\
+             /// Returns the names of the extensions instructions and operands may\n\
+             /// require, in order.\n\
+             pub fn known_extensions() -> &'static [&'static str] {{\n{known}\n}}\n",
+            location_debug = gen_class_match(grammar, "DebugLine"),
+            debug = gen_class_match(grammar, "Debug"),
+            annotation = gen_class_match(grammar, "Annotation"),
+            ty = gen_class_match(grammar, "Type"),
+            constant = gen_class_match(grammar, "Constant"),
+            terminator = gen_class_match(grammar, "Terminator"),
+            id_roles = gen_id_roles_match(grammar),
+            implied = gen_implied_capabilities_match(grammar),
+            known = gen_known_extensions(grammar))
+}
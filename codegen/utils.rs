@@ -104,6 +104,15 @@ pub fn get_param_name(param: &structs::Operand) -> String {
         }
     } else {
         let re = regex::Regex::new(r"\W").unwrap();
-        snake_casify(&re.replace_all(&param.name.replace(" ", "_"), ""))
+        let name = snake_casify(&re.replace_all(&param.name.replace(" ", "_"), ""));
+        // Keeps clear of Rust keywords, and of the locals of the generated
+        // builder methods.
+        match name.as_str() {
+            "type" => "ty".to_string(),
+            "use" => "usage".to_string(),
+            "id" => "id_ref".to_string(),
+            "inst" => "inst_ref".to_string(),
+            _ => name,
+        }
     }
 }
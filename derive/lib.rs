@@ -0,0 +1,128 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Derives [`rspirv::mr::SpirvStruct`][trait] for Rust structs, declaring
+//! struct types with the same members as the Rust structs.
+//!
+//! This crate is re-exported by `rspirv` behind its `derive` feature, as
+//! `rspirv::mr::SpirvStruct`. Fields are declared with their
+//! `AsSpirvConst` types, or, if marked `#[spirv(nested)]`, as structs
+//! deriving `SpirvStruct` themselves:
+//!
+//! ```
+//! extern crate rspirv;
+//!
+//! use rspirv::mr::{Builder, LayoutRule, SpirvStruct};
+//! use std::mem;
+//!
+//! #[derive(SpirvStruct)]
+//! #[repr(C)]
+//! struct Light {
+//!     color: [f32; 3],
+//!     intensity: f32,
+//! }
+//!
+//! #[derive(SpirvStruct)]
+//! #[repr(C)]
+//! struct Scene {
+//!     #[spirv(nested)]
+//!     sun: Light,
+//!     time: f32,
+//! }
+//!
+//! fn main() {
+//!     let mut b = Builder::new();
+//!     let scene = Scene::struct_builder(&mut b, LayoutRule::Std430).unwrap();
+//!     // The layouts on both sides agree.
+//!     assert_eq!(scene.offsets(&b).unwrap(),
+//!                vec![0, mem::offset_of!(Scene, time) as u32]);
+//!     scene.build(&mut b).unwrap();
+//! }
+//! ```
+//!
+//! [trait]: https://docs.rs/rspirv/0.5/rspirv/mr/trait.SpirvStruct.html
+
+extern crate proc_macro;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+use proc_macro::TokenStream;
+use quote::Tokens;
+use syn::{Body, DeriveInput, Field, MetaItem, NestedMetaItem, VariantData};
+
+/// Derives `SpirvStruct` for a struct.
+#[proc_macro_derive(SpirvStruct, attributes(spirv))]
+pub fn spirv_struct_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse_derive_input(&input.to_string()).unwrap();
+    expand(&ast).parse().expect("Expanded output was no correct Rust code")
+}
+
+fn expand(input: &DeriveInput) -> Tokens {
+    let fields: &[Field] = match input.body {
+        Body::Struct(VariantData::Struct(ref fields)) |
+        Body::Struct(VariantData::Tuple(ref fields)) => fields,
+        Body::Struct(VariantData::Unit) => &[],
+        Body::Enum(_) => panic!("SpirvStruct can only be derived for structs"),
+    };
+    let members: Vec<Tokens> = fields.iter().map(member).collect();
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let input_type = &input.ident;
+    let name = input.ident.as_ref();
+    quote!{
+        impl#impl_generics ::rspirv::mr::SpirvStruct for #input_type#ty_generics #where_clause {
+            fn struct_builder(builder: &mut ::rspirv::mr::Builder,
+                              rule: ::rspirv::mr::LayoutRule)
+                -> ::std::result::Result<::rspirv::mr::StructBuilder, ::rspirv::mr::Error> {
+                let mut members = ::rspirv::mr::StructBuilder::new(rule);
+                members.name(#name);
+                #(#members)*
+                Ok(members)
+            }
+        }
+    }
+}
+
+/// Returns the code declaring the type of `field` and appending it to the
+/// struct builder `members`.
+fn member(field: &Field) -> Tokens {
+    let ty = &field.ty;
+    let declare = if is_nested(field) {
+        quote!(<#ty as ::rspirv::mr::SpirvStruct>::struct_builder(builder, rule)?
+                   .build(builder)?)
+    } else {
+        quote!(<#ty as ::rspirv::mr::AsSpirvConst>::spirv_type(builder))
+    };
+    match field.ident {
+        Some(ref ident) => {
+            let name = ident.as_ref();
+            quote!{ let ty = #declare; members.named_member(ty, #name); }
+        }
+        None => quote!{ let ty = #declare; members.member(ty); },
+    }
+}
+
+/// Returns true if `field` is marked `#[spirv(nested)]`.
+fn is_nested(field: &Field) -> bool {
+    field.attrs.iter().any(|attr| match attr.value {
+        MetaItem::List(ref ident, ref items) if ident == "spirv" => {
+            items.iter().any(|item| match *item {
+                NestedMetaItem::MetaItem(MetaItem::Word(ref word)) => word == "nested",
+                _ => false,
+            })
+        }
+        _ => false,
+    })
+}
@@ -0,0 +1,70 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate rspirv;
+extern crate spirv_headers as spirv;
+
+use rspirv::mr::{self, LayoutRule, SpirvStruct};
+
+#[derive(SpirvStruct)]
+#[allow(dead_code)]
+struct Material {
+    albedo: [f32; 3],
+    roughness: f32,
+}
+
+#[derive(SpirvStruct)]
+#[allow(dead_code)]
+struct Uniforms {
+    time: f32,
+    #[spirv(nested)]
+    material: Material,
+    count: u32,
+}
+
+#[derive(SpirvStruct)]
+#[allow(dead_code)]
+struct Pair(f32, u32);
+
+fn member_names(m: &mr::Module, ty: spirv::Word) -> Vec<String> {
+    m.debugs
+        .iter()
+        .filter(|inst| inst.class.opcode == spirv::Op::MemberName)
+        .filter(|inst| inst.operands[0] == mr::Operand::IdRef(ty))
+        .map(|inst| match inst.operands[2] {
+            mr::Operand::LiteralString(ref name) => name.clone(),
+            _ => panic!(),
+        })
+        .collect()
+}
+
+#[test]
+fn test_derive_spirv_struct() {
+    let mut b = mr::Builder::new();
+    let std140 = Uniforms::struct_builder(&mut b, LayoutRule::Std140).unwrap();
+    // The nested struct and its array elements are aligned to 16 bytes.
+    assert_eq!(std140.offsets(&b).unwrap(), vec![0, 16, 80]);
+    let std430 = Uniforms::struct_builder(&mut b, LayoutRule::Std430).unwrap();
+    assert_eq!(std430.offsets(&b).unwrap(), vec![0, 4, 20]);
+
+    let ty = std430.build(&mut b).unwrap();
+    let pair = Pair::struct_builder(&mut b, LayoutRule::Scalar).unwrap().build(&mut b).unwrap();
+    let m = b.module();
+    assert_eq!(member_names(&m, ty), vec!["time", "material", "count"]);
+    assert!(member_names(&m, pair).is_empty());
+    let name = m.debugs.iter().find(|inst| {
+        inst.class.opcode == spirv::Op::Name && inst.operands[0] == mr::Operand::IdRef(ty)
+    });
+    assert_eq!(name.unwrap().operands[1], mr::Operand::LiteralString("Uniforms".to_string()));
+}
@@ -25,6 +25,9 @@ fn main() {
         .version(env!("CARGO_PKG_VERSION"))
         .about("SPIR-V binary module disassembler from the rspirv project")
         .arg(clap::Arg::with_name("input").index(1).required(true))
+        .arg(clap::Arg::with_name("summary")
+                 .long("summary")
+                 .help("Prints an outline of the module instead of its disassembly"))
         .get_matches();
 
     let input = matches.value_of("input").unwrap();
@@ -34,6 +37,9 @@ fn main() {
     f.read_to_end(&mut buffer).expect("cannot read file");
 
     match rspirv::mr::load_bytes(&buffer) {
+        Ok(ref module) if matches.is_present("summary") => {
+            println!("{}", rspirv::report::outline(module))
+        }
         Ok(module) => println!("{}", module.disassemble()),
         Err(err) => println!("{}", err),
     }
@@ -0,0 +1,41 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![no_main]
+
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate rspirv;
+
+use rspirv::binary::{AsmOptions, Disassemble};
+
+fuzz_target!(|data: &[u8]| {
+    let text = match std::str::from_utf8(data) {
+        Ok(text) => text,
+        Err(_) => return,
+    };
+    // Whatever assembles into a module that loads must disassemble into
+    // text assembling back into the same module.
+    let module = match rspirv::binary::assemble_text(text).map(rspirv::mr::load_words) {
+        Ok(Ok(module)) => module,
+        _ => return,
+    };
+    let text = module.disassemble();
+    let options = AsmOptions { preserve_numeric_ids: true };
+    let words = rspirv::binary::assemble_text_with(&text, &options)
+        .expect("disassembled module fails to assemble");
+    let reassembled = rspirv::mr::load_words(words)
+        .expect("reassembled module fails to parse");
+    assert_eq!(text, reassembled.disassemble());
+});
@@ -0,0 +1,30 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![no_main]
+
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate rspirv;
+extern crate rspirv_fuzz;
+
+use rspirv::binary::{Assemble, Disassemble};
+use rspirv_fuzz::ArbitraryModule;
+
+fuzz_target!(|module: ArbitraryModule| {
+    let module = module.0;
+    let reparsed = rspirv::mr::load_words(module.assemble())
+        .expect("generated module fails to parse");
+    assert_eq!(module.disassemble(), reparsed.disassemble());
+});
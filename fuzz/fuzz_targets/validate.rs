@@ -0,0 +1,34 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![no_main]
+
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate rspirv;
+
+use rspirv::env::{TargetEnv, Validator};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(mut module) = rspirv::mr::load_bytes(data) {
+        for &env in &[TargetEnv::Universal(1, 6), TargetEnv::Vulkan1_2, TargetEnv::OpenCL2_0] {
+            // The incremental validator must agree with validating the
+            // whole module, both from scratch and when nothing changed.
+            let expected = env.validate(&module);
+            let mut validator = Validator::new(env);
+            assert_eq!(expected, validator.validate(&mut module));
+            assert_eq!(expected, validator.validate(&mut module));
+        }
+    }
+});
@@ -0,0 +1,131 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Structured input generators for fuzzing rspirv.
+
+extern crate arbitrary;
+extern crate rspirv;
+extern crate spirv_headers as spirv;
+
+use arbitrary::{Arbitrary, Unstructured};
+use rspirv::mr;
+
+/// A structurally valid module generated from fuzzer input.
+///
+/// The module contains a single compute entry point whose body is a chain
+/// of basic blocks and if-else selection constructs. The blocks compute on
+/// 32-bit integers, 32-bit floats, and booleans, using only values that
+/// dominate their uses.
+#[derive(Debug)]
+pub struct ArbitraryModule(pub mr::Module);
+
+/// Values available at the current position, by type.
+#[derive(Clone, Default)]
+struct Values {
+    ints: Vec<spirv::Word>,
+    floats: Vec<spirv::Word>,
+    bools: Vec<spirv::Word>,
+}
+
+struct Types {
+    int: spirv::Word,
+    float: spirv::Word,
+    boolean: spirv::Word,
+}
+
+fn pick(u: &mut Unstructured, values: &[spirv::Word]) -> arbitrary::Result<spirv::Word> {
+    u.choose(values).copied()
+}
+
+/// Emits up to a few arithmetic instructions into the current block.
+fn instructions(u: &mut Unstructured,
+                b: &mut mr::Builder,
+                t: &Types,
+                values: &mut Values)
+                -> arbitrary::Result<()> {
+    for _ in 0..u.int_in_range(0..=8)? {
+        let (a, c) = (pick(u, &values.ints)?, pick(u, &values.ints)?);
+        let (x, y) = (pick(u, &values.floats)?, pick(u, &values.floats)?);
+        let p = pick(u, &values.bools)?;
+        match u.int_in_range(0..=8)? {
+            0 => values.ints.push(b.iadd(t.int, None, a, c).unwrap()),
+            1 => values.ints.push(b.isub(t.int, None, a, c).unwrap()),
+            2 => values.ints.push(b.imul(t.int, None, a, c).unwrap()),
+            3 => values.ints.push(b.bitwise_and(t.int, None, a, c).unwrap()),
+            4 => values.ints.push(b.select(t.int, None, p, a, c).unwrap()),
+            5 => values.floats.push(b.fadd(t.float, None, x, y).unwrap()),
+            6 => values.floats.push(b.fmul(t.float, None, x, y).unwrap()),
+            7 => values.bools.push(b.sless_than(t.boolean, None, a, c).unwrap()),
+            _ => values.bools.push(b.logical_not(t.boolean, None, p).unwrap()),
+        }
+    }
+    Ok(())
+}
+
+impl<'a> Arbitrary<'a> for ArbitraryModule {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let t = Types {
+            int: b.type_int(32, 1),
+            float: b.type_float(32),
+            boolean: b.type_bool(),
+        };
+
+        let mut values = Values::default();
+        for _ in 0..u.int_in_range(1..=4)? {
+            let v = u32::arbitrary(u)?;
+            values.ints.push(b.constant_u32(t.int, v));
+        }
+        for _ in 0..u.int_in_range(1..=4)? {
+            let v = f32::arbitrary(u)?;
+            values.floats.push(b.constant_f32(t.float, v));
+        }
+        values.bools.push(b.constant_true(t.boolean));
+        values.bools.push(b.constant_false(t.boolean));
+
+        let voidf = b.type_function(void, vec![]);
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.entry_point(spirv::ExecutionModel::GLCompute, f, "main", vec![]);
+        b.execution_mode(f, spirv::ExecutionMode::LocalSize, vec![1, 1, 1]);
+        b.begin_basic_block(None).unwrap();
+
+        for _ in 0..u.int_in_range(0..=4)? {
+            instructions(u, &mut b, &t, &mut values)?;
+            let next = b.id();
+            if bool::arbitrary(u)? {
+                let cond = pick(u, &values.bools)?;
+                let (then_block, else_block) = (b.id(), b.id());
+                b.selection_merge(next, spirv::SelectionControl::NONE).unwrap();
+                b.branch_conditional(cond, then_block, else_block, vec![]).unwrap();
+                for &block in &[then_block, else_block] {
+                    // Values defined in the arms do not dominate the merge.
+                    let mut arm = values.clone();
+                    b.begin_basic_block(Some(block)).unwrap();
+                    instructions(u, &mut b, &t, &mut arm)?;
+                    b.branch(next).unwrap();
+                }
+            } else {
+                b.branch(next).unwrap();
+            }
+            b.begin_basic_block(Some(next)).unwrap();
+        }
+        instructions(u, &mut b, &t, &mut values)?;
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        Ok(ArbitraryModule(b.module()))
+    }
+}
@@ -0,0 +1,271 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use num_traits::FromPrimitive;
+use spirv::Word;
+use std::collections::HashMap;
+
+/// A suspicious use of scopes or memory semantics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncWarning {
+    /// A scope or memory semantics operand is not a constant, or not a
+    /// valid one.
+    NonConstantOperand,
+    /// The memory semantics have more than one of `Acquire`, `Release`,
+    /// `AcquireRelease`, and `SequentiallyConsistent`.
+    MultipleOrderings,
+    /// The memory semantics have an ordering but no storage class bits,
+    /// so that no memory is ordered.
+    OrderingWithoutStorage,
+    /// A barrier has storage class bits without an ordering, so that it
+    /// orders no memory.
+    StorageWithoutOrdering,
+    /// An atomic with an ordering does not order the storage class of the
+    /// memory it accesses.
+    PointerStorageNotOrdered,
+    /// An atomic is relaxed on `Workgroup` memory in a function without
+    /// any control barrier, so that other invocations of the workgroup
+    /// cannot observe other memory accesses in order with it.
+    RelaxedWithoutBarrier,
+    /// An atomic is at `Invocation` scope, and therefore not atomic with
+    /// respect to any other invocation.
+    InvocationScope,
+}
+
+/// An atomic or barrier instruction, with its scopes and memory semantics
+/// decoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyncOp {
+    /// The result id of the function the instruction is in.
+    pub function: Word,
+    pub opcode: spirv::Op,
+    /// The result id of the instruction, if any.
+    pub result_id: Option<Word>,
+    /// The execution scope of a control barrier.
+    pub execution_scope: Option<spirv::Scope>,
+    /// The memory scope, if it is a constant.
+    pub memory_scope: Option<spirv::Scope>,
+    /// The memory semantics, or for compare-exchange atomics its semantics
+    /// when equal, if they are a constant.
+    pub semantics: Option<spirv::MemorySemantics>,
+    /// The memory semantics of compare-exchange atomics when unequal.
+    pub unequal_semantics: Option<spirv::MemorySemantics>,
+    /// The storage class of the memory an atomic accesses.
+    pub storage_class: Option<spirv::StorageClass>,
+    /// The suspicious combinations found, in the order of the variants of
+    /// [`SyncWarning`](enum.SyncWarning.html).
+    pub warnings: Vec<SyncWarning>,
+}
+
+/// Lists the atomic and barrier instructions of `module` in order, with
+/// warnings about suspicious combinations of their operands.
+///
+/// Atomics are the instructions whose names start with `Atomic`, and
+/// barriers `OpControlBarrier` and `OpMemoryBarrier`. Memory semantics
+/// without storage class bits are expected of relaxed atomics, and so are
+/// not warned about. Scopes and semantics are decoded from `OpConstant`s
+/// only; specialization constants are warned about as non-constant.
+pub fn audit_atomics(module: &mr::Module) -> Vec<SyncOp> {
+    let functions = module.functions.iter().filter_map(|f| {
+        Some((f.def.as_ref()?.result_id?, f))
+    });
+    let insts = module.functions.iter().flat_map(|f| {
+        f.parameters.iter().chain(f.basic_blocks.iter().flat_map(|b| &b.instructions))
+    });
+    let types: HashMap<Word, Word> = module.types_global_values
+        .iter()
+        .chain(insts)
+        .filter_map(|inst| Some((inst.result_id?, inst.result_type?)))
+        .collect();
+    let defs: HashMap<Word, &mr::Instruction> = module.types_global_values
+        .iter()
+        .filter_map(|inst| inst.result_id.map(|id| (id, inst)))
+        .collect();
+    let constant = |id: Word| {
+        let inst = defs.get(&id)?;
+        match (inst.class.opcode, inst.operands.first()) {
+            (spirv::Op::Constant, Some(&mr::Operand::LiteralInt32(v))) => Some(v),
+            _ => None,
+        }
+    };
+    let storage_class = |pointer: Word| {
+        let inst = defs.get(types.get(&pointer)?)?;
+        match (inst.class.opcode, inst.operands.first()) {
+            (spirv::Op::TypePointer, Some(&mr::Operand::StorageClass(class))) => Some(class),
+            _ => None,
+        }
+    };
+
+    let mut ops = vec![];
+    for (function, f) in functions {
+        let insts: Vec<&mr::Instruction> =
+            f.basic_blocks.iter().flat_map(|b| &b.instructions).collect();
+        let has_control_barrier = insts.iter()
+            .any(|inst| inst.class.opcode == spirv::Op::ControlBarrier);
+        for inst in insts {
+            let opcode = inst.class.opcode;
+            let is_atomic = inst.class.opname.starts_with("Atomic");
+            if !is_atomic && opcode != spirv::Op::ControlBarrier &&
+               opcode != spirv::Op::MemoryBarrier {
+                continue;
+            }
+            let mut non_constant = false;
+            let mut scopes = vec![];
+            let mut semantics = vec![];
+            for operand in &inst.operands {
+                match *operand {
+                    mr::Operand::IdScope(id) => {
+                        let scope = constant(id).and_then(spirv::Scope::from_u32);
+                        non_constant |= scope.is_none();
+                        scopes.push(scope);
+                    }
+                    mr::Operand::IdMemorySemantics(id) => {
+                        let bits = constant(id).and_then(spirv::MemorySemantics::from_bits);
+                        non_constant |= bits.is_none();
+                        semantics.push(bits);
+                    }
+                    _ => (),
+                }
+            }
+            let (execution_scope, memory_scope) = match scopes[..] {
+                [execution, memory] => (execution, memory),
+                [memory] => (None, memory),
+                _ => (None, None),
+            };
+            let pointer_class = if is_atomic {
+                inst.operands.first().and_then(mr::Operand::id).and_then(storage_class)
+            } else {
+                None
+            };
+            let mut op = SyncOp {
+                function,
+                opcode,
+                result_id: inst.result_id,
+                execution_scope,
+                memory_scope,
+                semantics: semantics.first().cloned().unwrap_or(None),
+                unequal_semantics: semantics.get(1).cloned().unwrap_or(None),
+                storage_class: pointer_class,
+                warnings: vec![],
+            };
+            let all_semantics: Vec<_> = semantics.iter().flatten().cloned().collect();
+
+            if non_constant {
+                op.warnings.push(SyncWarning::NonConstantOperand);
+            }
+            if all_semantics.iter().any(|s| (*s & orderings()).bits().count_ones() > 1) {
+                op.warnings.push(SyncWarning::MultipleOrderings);
+            }
+            if all_semantics.iter().any(|s| s.intersects(orderings()) && !s.intersects(storage())) {
+                op.warnings.push(SyncWarning::OrderingWithoutStorage);
+            }
+            if !is_atomic &&
+               all_semantics.iter().any(|s| !s.intersects(orderings()) && s.intersects(storage())) {
+                op.warnings.push(SyncWarning::StorageWithoutOrdering);
+            }
+            let ordered_class = pointer_class.map_or(spirv::MemorySemantics::NONE, semantics_for);
+            let misses_class = |s: &spirv::MemorySemantics| {
+                s.intersects(orderings()) && s.intersects(storage()) && !s.contains(ordered_class)
+            };
+            if !ordered_class.is_empty() && all_semantics.iter().any(misses_class) {
+                op.warnings.push(SyncWarning::PointerStorageNotOrdered);
+            }
+            if pointer_class == Some(spirv::StorageClass::Workgroup) && !has_control_barrier &&
+               !all_semantics.is_empty() &&
+               all_semantics.iter().all(|s| !s.intersects(orderings())) {
+                op.warnings.push(SyncWarning::RelaxedWithoutBarrier);
+            }
+            if is_atomic && memory_scope == Some(spirv::Scope::Invocation) {
+                op.warnings.push(SyncWarning::InvocationScope);
+            }
+            ops.push(op);
+        }
+    }
+    ops
+}
+
+/// The memory semantics bits of orderings.
+fn orderings() -> spirv::MemorySemantics {
+    spirv::MemorySemantics::ACQUIRE | spirv::MemorySemantics::RELEASE |
+    spirv::MemorySemantics::ACQUIRE_RELEASE | spirv::MemorySemantics::SEQUENTIALLY_CONSISTENT
+}
+
+/// The memory semantics bits of storage classes.
+fn storage() -> spirv::MemorySemantics {
+    spirv::MemorySemantics::UNIFORM_MEMORY | spirv::MemorySemantics::SUBGROUP_MEMORY |
+    spirv::MemorySemantics::WORKGROUP_MEMORY | spirv::MemorySemantics::CROSS_WORKGROUP_MEMORY |
+    spirv::MemorySemantics::ATOMIC_COUNTER_MEMORY | spirv::MemorySemantics::IMAGE_MEMORY |
+    spirv::MemorySemantics::OUTPUT_MEMORY
+}
+
+/// Returns the memory semantics bit ordering memory of `class`, if any.
+fn semantics_for(class: spirv::StorageClass) -> spirv::MemorySemantics {
+    match class {
+        spirv::StorageClass::Uniform | spirv::StorageClass::StorageBuffer => {
+            spirv::MemorySemantics::UNIFORM_MEMORY
+        }
+        spirv::StorageClass::Workgroup => spirv::MemorySemantics::WORKGROUP_MEMORY,
+        spirv::StorageClass::CrossWorkgroup => spirv::MemorySemantics::CROSS_WORKGROUP_MEMORY,
+        spirv::StorageClass::AtomicCounter => spirv::MemorySemantics::ATOMIC_COUNTER_MEMORY,
+        spirv::StorageClass::Image => spirv::MemorySemantics::IMAGE_MEMORY,
+        spirv::StorageClass::Output => spirv::MemorySemantics::OUTPUT_MEMORY,
+        _ => spirv::MemorySemantics::NONE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{audit_atomics, SyncWarning};
+
+    #[test]
+    fn test_audit_atomics() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let ptr = b.type_pointer(None, spirv::StorageClass::Workgroup, uint);
+        let shared = b.variable(ptr, None, spirv::StorageClass::Workgroup, None);
+        let workgroup = b.constant_scope(uint, spirv::Scope::Workgroup);
+        let relaxed = b.constant_u32(uint, spirv::MemorySemantics::RELAXED.bits());
+        let acquire = b.constant_u32(uint, spirv::MemorySemantics::ACQUIRE.bits());
+        let one = b.constant_u32(uint, 1);
+        let fty = b.type_function(void, vec![]);
+
+        let counter = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.atomic_iadd(uint, None, shared, workgroup, relaxed, one).unwrap();
+        b.memory_barrier(workgroup, acquire).unwrap();
+        b.atomic_load(uint, None, shared, workgroup, acquire).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let m = b.module();
+
+        let ops = audit_atomics(&m);
+        let summary: Vec<_> = ops.iter().map(|op| (op.opcode, op.warnings.clone())).collect();
+        assert_eq!(summary,
+                   vec![(spirv::Op::AtomicIAdd, vec![SyncWarning::RelaxedWithoutBarrier]),
+                        (spirv::Op::MemoryBarrier, vec![SyncWarning::OrderingWithoutStorage]),
+                        (spirv::Op::AtomicLoad, vec![SyncWarning::OrderingWithoutStorage])]);
+        assert!(ops.iter().all(|op| op.function == counter));
+        assert_eq!(ops[0].memory_scope, Some(spirv::Scope::Workgroup));
+        assert_eq!(ops[0].semantics, Some(spirv::MemorySemantics::RELAXED));
+        assert_eq!(ops[0].storage_class, Some(spirv::StorageClass::Workgroup));
+        assert_eq!(ops[1].execution_scope, None);
+    }
+}
@@ -0,0 +1,171 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Graphviz export of control flow.
+
+use grammar;
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::fmt::Write;
+use super::Dominators;
+
+/// Returns the control flow graph of `function` in the Graphviz DOT
+/// language.
+///
+/// Each block is a node labeled by its label id and the opcode of its
+/// terminator; the entry block is drawn bold. Branches are solid edges,
+/// labeled `true` and `false` for conditional branches, and by the case
+/// literal or `default` for switches. The merge blocks of structured
+/// constructs are joined by dashed edges labeled `merge`, and continue
+/// targets by dotted edges labeled `continue`.
+pub fn to_dot(function: &mr::Function) -> String {
+    let mut dot = header(function);
+    for (index, block) in function.basic_blocks.iter().enumerate() {
+        let label = match block.label.as_ref().and_then(|l| l.result_id) {
+            Some(label) => label,
+            None => continue,
+        };
+        let terminator = block.instructions
+            .last()
+            .filter(|inst| grammar::reflect::is_terminator(inst.class.opcode));
+        let opname = terminator.map_or("", |inst| inst.class.opname);
+        let style = if index == 0 { ", style=bold" } else { "" };
+        writeln!(dot, "    {} [label=\"%{}\\nOp{}\"{}];", label, label, opname, style).unwrap();
+
+        let merge = block.instructions.iter().rev().nth(1).map(|inst| (inst.class.opcode, inst));
+        match merge {
+            Some((spirv::Op::SelectionMerge, inst)) => {
+                edge(&mut dot, label, inst.operands.first(), "style=dashed, label=\"merge\"");
+            }
+            Some((spirv::Op::LoopMerge, inst)) => {
+                edge(&mut dot, label, inst.operands.first(), "style=dashed, label=\"merge\"");
+                edge(&mut dot, label, inst.operands.get(1), "style=dotted, label=\"continue\"");
+            }
+            _ => (),
+        }
+
+        let inst = match terminator {
+            Some(inst) => inst,
+            None => continue,
+        };
+        match inst.class.opcode {
+            spirv::Op::BranchConditional => {
+                edge(&mut dot, label, inst.operands.get(1), "label=\"true\"");
+                edge(&mut dot, label, inst.operands.get(2), "label=\"false\"");
+            }
+            spirv::Op::Switch => {
+                edge(&mut dot, label, inst.operands.get(1), "label=\"default\"");
+                for case in inst.operands[2..].chunks(2) {
+                    if let [ref literal, ref target] = *case {
+                        let attrs = format!("label=\"{}\"", literal);
+                        edge(&mut dot, label, Some(target), &attrs);
+                    }
+                }
+            }
+            _ => {
+                for target in block.successors() {
+                    writeln!(dot, "    {} -> {};", label, target).unwrap();
+                }
+            }
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Returns the dominator tree of the reachable blocks of `function` in the
+/// Graphviz DOT language, with edges from immediate dominators to the
+/// blocks they dominate.
+pub fn dominator_tree_to_dot(function: &mr::Function) -> String {
+    let dominators = Dominators::new(function);
+    let mut dot = header(function);
+    for &label in dominators.reverse_postorder() {
+        let style = if dominators.entry() == Some(label) { ", style=bold" } else { "" };
+        writeln!(dot, "    {} [label=\"%{}\"{}];", label, label, style).unwrap();
+        if let Some(idom) = dominators.immediate_dominator(label) {
+            writeln!(dot, "    {} -> {};", idom, label).unwrap();
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Starts a graph named after `function`.
+fn header(function: &mr::Function) -> String {
+    let name = function.def
+        .as_ref()
+        .and_then(|def| def.result_id)
+        .map_or_else(String::new, |id| format!("%{}", id));
+    format!("digraph \"{}\" {{\n    node [shape=box];\n", name)
+}
+
+/// Adds an edge with the given attributes from `from` to the label
+/// `target`, if any.
+fn edge(dot: &mut String, from: Word, target: Option<&mr::Operand>, attrs: &str) {
+    if let Some(&mr::Operand::IdRef(target)) = target {
+        writeln!(dot, "    {} -> {} [{}];", from, target, attrs).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{dominator_tree_to_dot, to_dot};
+
+    #[test]
+    fn test_to_dot() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let boolean = b.type_bool();
+        let cond = b.constant_true(boolean);
+        let fty = b.type_function(void, vec![]);
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let entry = b.begin_basic_block(None).unwrap();
+        let then = b.id();
+        let merge = b.id();
+        b.selection_merge(merge, spirv::SelectionControl::NONE).unwrap();
+        b.branch_conditional(cond, then, merge, vec![]).unwrap();
+        b.begin_basic_block(Some(then)).unwrap();
+        b.branch(merge).unwrap();
+        b.begin_basic_block(Some(merge)).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let m = b.module();
+
+        let function = &m.functions[0];
+        assert_eq!(to_dot(function),
+                   format!("digraph \"%{f}\" {{\n    \
+                            node [shape=box];\n    \
+                            {e} [label=\"%{e}\\nOpBranchConditional\", style=bold];\n    \
+                            {e} -> {m} [style=dashed, label=\"merge\"];\n    \
+                            {e} -> {t} [label=\"true\"];\n    \
+                            {e} -> {m} [label=\"false\"];\n    \
+                            {t} [label=\"%{t}\\nOpBranch\"];\n    \
+                            {t} -> {m};\n    \
+                            {m} [label=\"%{m}\\nOpReturn\"];\n\
+                            }}\n",
+                           f = f,
+                           e = entry,
+                           t = then,
+                           m = merge));
+        let tree = dominator_tree_to_dot(function);
+        assert!(tree.contains(&format!("    {} -> {};\n", entry, then)));
+        assert!(tree.contains(&format!("    {} -> {};\n", entry, merge)));
+        assert_eq!(tree.matches("->").count(), 2);
+    }
+}
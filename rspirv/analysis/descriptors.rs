@@ -0,0 +1,349 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// The kind of descriptor in a descriptor array.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DescriptorKind {
+    /// An `OpTypeSampler`.
+    Sampler,
+    /// An `OpTypeSampledImage`, or an `OpTypeImage` used with a sampler.
+    SampledImage,
+    /// An `OpTypeImage` used without a sampler.
+    StorageImage,
+    /// A sampled `OpTypeImage` of the `Buffer` dimension.
+    UniformTexelBuffer,
+    /// A storage `OpTypeImage` of the `Buffer` dimension.
+    StorageTexelBuffer,
+    /// An `OpTypeImage` of the `SubpassData` dimension.
+    InputAttachment,
+    /// A `Block` struct in the `Uniform` storage class.
+    UniformBuffer,
+    /// A `Block` struct in the `StorageBuffer` storage class, or a
+    /// `BufferBlock` one in `Uniform`.
+    StorageBuffer,
+}
+
+/// How an array of descriptors is indexed across the whole module.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DescriptorIndexing {
+    /// The result id of the `OpVariable` declaring the binding.
+    pub variable: Word,
+    /// The kind of the descriptors in the array.
+    pub kind: DescriptorKind,
+    /// The `DescriptorSet` decoration on the variable, if any.
+    pub descriptor_set: Option<u32>,
+    /// The `Binding` decoration on the variable, if any.
+    pub binding: Option<u32>,
+    /// Whether the variable is an `OpTypeRuntimeArray`.
+    pub runtime_array: bool,
+    /// Indexed with a value that is not a constant.
+    pub dynamically_indexed: bool,
+    /// Indexed with a value decorated `NonUniform`, or into a result that
+    /// is.
+    pub non_uniformly_indexed: bool,
+    /// The values derived from non-uniform indexing which are not decorated
+    /// `NonUniform`, in the order they are defined.
+    pub missing_non_uniform: Vec<Word>,
+}
+
+/// Reports how each array of descriptors in the given `module` is indexed.
+///
+/// Descriptor arrays are `UniformConstant`, `Uniform`, or `StorageBuffer`
+/// variables whose type is a (runtime) array of images, samplers, or
+/// buffer blocks. An access chain into one is non-uniform if its index or
+/// its result is decorated `NonUniform`; then so must be its result and
+/// every value accessing the descriptor through it: further access chains,
+/// `OpCopyObject`s, and for images and samplers the loaded descriptor and
+/// the `OpSampledImage`, `OpImage`, and `OpImageTexelPointer` results
+/// derived from it. Values merged by `OpPhi` or `OpSelect`, or passed to
+/// functions, are not followed.
+///
+/// The result follows the order in which the variables are declared.
+pub fn descriptor_indexing(module: &mr::Module) -> Vec<DescriptorIndexing> {
+    let defs: HashMap<Word, &mr::Instruction> = module
+        .types_global_values
+        .iter()
+        .filter_map(|inst| inst.result_id.map(|id| (id, inst)))
+        .collect();
+    let mut blocks = HashMap::new();
+    let mut non_uniform = HashSet::new();
+    for inst in &module.annotations {
+        if inst.class.opcode != spirv::Op::Decorate {
+            continue;
+        }
+        if let (Some(id), Some(&mr::Operand::Decoration(decoration))) =
+            (id_operand(inst, 0), inst.operands.get(1))
+        {
+            match decoration {
+                spirv::Decoration::Block | spirv::Decoration::BufferBlock => {
+                    blocks.insert(id, decoration);
+                }
+                spirv::Decoration::NonUniform => {
+                    non_uniform.insert(id);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    let mut indexings: Vec<DescriptorIndexing> = module
+        .types_global_values
+        .iter()
+        .filter(|inst| inst.class.opcode == spirv::Op::Variable)
+        .filter_map(|inst| {
+            let class = match inst.operands.first() {
+                Some(&mr::Operand::StorageClass(class)) => class,
+                _ => return None,
+            };
+            let pointer = defs.get(&inst.result_type?)?;
+            let array = defs.get(&id_operand(pointer, 1)?)?;
+            let runtime_array = match array.class.opcode {
+                spirv::Op::TypeArray => false,
+                spirv::Op::TypeRuntimeArray => true,
+                _ => return None,
+            };
+            let kind = descriptor_kind(&defs, &blocks, class, id_operand(array, 0)?)?;
+            Some(DescriptorIndexing {
+                variable: inst.result_id?,
+                kind,
+                descriptor_set: None,
+                binding: None,
+                runtime_array,
+                dynamically_indexed: false,
+                non_uniformly_indexed: false,
+                missing_non_uniform: vec![],
+            })
+        })
+        .collect();
+
+    for inst in &module.annotations {
+        if inst.class.opcode != spirv::Op::Decorate || inst.operands.len() < 3 {
+            continue;
+        }
+        if let Some(indexing) = indexings
+            .iter_mut()
+            .find(|d| inst.operands[0] == mr::Operand::IdRef(d.variable))
+        {
+            match (&inst.operands[1], &inst.operands[2]) {
+                (&mr::Operand::Decoration(spirv::Decoration::DescriptorSet),
+                 &mr::Operand::LiteralInt32(v)) => indexing.descriptor_set = Some(v),
+                (&mr::Operand::Decoration(spirv::Decoration::Binding),
+                 &mr::Operand::LiteralInt32(v)) => indexing.binding = Some(v),
+                _ => (),
+            }
+        }
+    }
+
+    let insts: Vec<&mr::Instruction> = module
+        .functions
+        .iter()
+        .flat_map(|f| f.basic_blocks.iter())
+        .flat_map(|b| b.instructions.iter())
+        .collect();
+    // Edges from a value to the values accessing descriptors through it.
+    let mut edges: HashMap<Word, Vec<Word>> = HashMap::new();
+    for inst in &insts {
+        let from: &[usize] = match inst.class.opcode {
+            spirv::Op::AccessChain |
+            spirv::Op::InBoundsAccessChain |
+            spirv::Op::CopyObject |
+            spirv::Op::Load |
+            spirv::Op::Image |
+            spirv::Op::ImageTexelPointer => &[0],
+            spirv::Op::SampledImage => &[0, 1],
+            _ => continue,
+        };
+        for &i in from {
+            if let (Some(from), Some(to)) = (id_operand(inst, i), inst.result_id) {
+                edges.entry(from).or_default().push(to);
+            }
+        }
+    }
+    let loads: HashSet<Word> = insts
+        .iter()
+        .filter(|inst| inst.class.opcode == spirv::Op::Load)
+        .filter_map(|inst| inst.result_id)
+        .collect();
+
+    for indexing in &mut indexings {
+        let buffer = matches!(indexing.kind,
+                              DescriptorKind::UniformBuffer | DescriptorKind::StorageBuffer);
+        let mut required = BTreeSet::new();
+        let mut stack = vec![];
+        for inst in &insts {
+            let is_chain = matches!(inst.class.opcode,
+                                    spirv::Op::AccessChain | spirv::Op::InBoundsAccessChain);
+            if !is_chain || id_operand(inst, 0) != Some(indexing.variable) {
+                continue;
+            }
+            let (index, result) = match (id_operand(inst, 1), inst.result_id) {
+                (Some(index), Some(result)) => (index, result),
+                _ => continue,
+            };
+            if !defs.contains_key(&index) {
+                indexing.dynamically_indexed = true;
+            }
+            if non_uniform.contains(&index) || non_uniform.contains(&result) {
+                indexing.non_uniformly_indexed = true;
+                stack.push(result);
+            }
+        }
+        while let Some(id) = stack.pop() {
+            // Loading through a buffer pointer yields data, not descriptors.
+            if (buffer && loads.contains(&id)) || !required.insert(id) {
+                continue;
+            }
+            stack.extend(edges.get(&id).into_iter().flatten());
+        }
+        indexing.missing_non_uniform = insts
+            .iter()
+            .filter_map(|inst| inst.result_id)
+            .filter(|id| required.contains(id) && !non_uniform.contains(id))
+            .collect();
+    }
+
+    indexings
+}
+
+/// Returns the kind of the descriptor type `ty` in a variable of `class`,
+/// looking through arrays of descriptors.
+fn descriptor_kind(defs: &HashMap<Word, &mr::Instruction>,
+                   blocks: &HashMap<Word, spirv::Decoration>,
+                   class: spirv::StorageClass,
+                   ty: Word)
+                   -> Option<DescriptorKind> {
+    let inst = defs.get(&ty)?;
+    match (inst.class.opcode, class) {
+        (spirv::Op::TypeArray, _) | (spirv::Op::TypeRuntimeArray, _) => {
+            descriptor_kind(defs, blocks, class, id_operand(inst, 0)?)
+        }
+        (spirv::Op::TypeSampler, spirv::StorageClass::UniformConstant) => {
+            Some(DescriptorKind::Sampler)
+        }
+        (spirv::Op::TypeSampledImage, spirv::StorageClass::UniformConstant) => {
+            Some(DescriptorKind::SampledImage)
+        }
+        (spirv::Op::TypeImage, spirv::StorageClass::UniformConstant) => {
+            let sampled = inst.operands.get(5) == Some(&mr::Operand::LiteralInt32(1));
+            match (inst.operands.get(1), sampled) {
+                (Some(&mr::Operand::Dim(spirv::Dim::DimSubpassData)), _) => {
+                    Some(DescriptorKind::InputAttachment)
+                }
+                (Some(&mr::Operand::Dim(spirv::Dim::DimBuffer)), true) => {
+                    Some(DescriptorKind::UniformTexelBuffer)
+                }
+                (Some(&mr::Operand::Dim(spirv::Dim::DimBuffer)), false) => {
+                    Some(DescriptorKind::StorageTexelBuffer)
+                }
+                (_, true) => Some(DescriptorKind::SampledImage),
+                (_, false) => Some(DescriptorKind::StorageImage),
+            }
+        }
+        (spirv::Op::TypeStruct, spirv::StorageClass::Uniform) => {
+            match blocks.get(&ty) {
+                Some(&spirv::Decoration::Block) => Some(DescriptorKind::UniformBuffer),
+                Some(_) => Some(DescriptorKind::StorageBuffer),
+                None => None,
+            }
+        }
+        (spirv::Op::TypeStruct, spirv::StorageClass::StorageBuffer) => {
+            blocks.get(&ty).map(|_| DescriptorKind::StorageBuffer)
+        }
+        _ => None,
+    }
+}
+
+fn id_operand(inst: &mr::Instruction, index: usize) -> Option<Word> {
+    match inst.operands.get(index) {
+        Some(&mr::Operand::IdRef(id)) => Some(id),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{descriptor_indexing, DescriptorKind};
+
+    #[test]
+    fn test_descriptor_indexing() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let uint = b.type_int(32, 0);
+        let image = b.type_image(float,
+                                 spirv::Dim::Dim2D,
+                                 0,
+                                 0,
+                                 0,
+                                 1,
+                                 spirv::ImageFormat::Unknown,
+                                 None);
+        let sampled = b.type_sampled_image(image);
+        let textures = b.type_runtime_array(sampled);
+        let ptr_textures = b.type_pointer(None, spirv::StorageClass::UniformConstant, textures);
+        let ptr_texture = b.type_pointer(None, spirv::StorageClass::UniformConstant, sampled);
+        let block = b.type_struct(vec![float]);
+        let four = b.constant_u32(uint, 4);
+        let zero = b.constant_u32(uint, 0);
+        let blocks = b.type_array(block, four);
+        let ptr_blocks = b.type_pointer(None, spirv::StorageClass::Uniform, blocks);
+        let ptr_block = b.type_pointer(None, spirv::StorageClass::Uniform, block);
+        let ptr_float = b.type_pointer(None, spirv::StorageClass::Uniform, float);
+        let tex = b.variable(ptr_textures, None, spirv::StorageClass::UniformConstant, None);
+        let ubo = b.variable(ptr_blocks, None, spirv::StorageClass::Uniform, None);
+        b.decorate(block, spirv::Decoration::Block, vec![]);
+        b.decorate(tex, spirv::Decoration::Binding, vec![mr::Operand::LiteralInt32(3)]);
+
+        let fty = b.type_function(void, vec![uint]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let i = b.function_parameter(uint).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let chain = b.access_chain(ptr_texture, None, tex, vec![i]).unwrap();
+        let texture = b.load(sampled, None, chain, None, vec![]).unwrap();
+        b.image_query_levels(uint, None, texture).unwrap();
+        let first = b.access_chain(ptr_block, None, ubo, vec![zero]).unwrap();
+        b.access_chain(ptr_float, None, first, vec![zero]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.decorate(i, spirv::Decoration::NonUniform, vec![]);
+        b.decorate(texture, spirv::Decoration::NonUniform, vec![]);
+        let m = b.module();
+
+        let indexings = descriptor_indexing(&m);
+        assert_eq!(indexings.len(), 2);
+        assert_eq!(indexings[0].variable, tex);
+        assert_eq!(indexings[0].kind, DescriptorKind::SampledImage);
+        assert_eq!(indexings[0].binding, Some(3));
+        assert!(indexings[0].runtime_array);
+        assert!(indexings[0].dynamically_indexed);
+        assert!(indexings[0].non_uniformly_indexed);
+        assert_eq!(indexings[0].missing_non_uniform, vec![chain]);
+
+        assert_eq!(indexings[1].variable, ubo);
+        assert_eq!(indexings[1].kind, DescriptorKind::UniformBuffer);
+        assert!(!indexings[1].runtime_array);
+        assert!(!indexings[1].dynamically_indexed);
+        assert!(!indexings[1].non_uniformly_indexed);
+        assert!(indexings[1].missing_non_uniform.is_empty());
+    }
+}
@@ -0,0 +1,241 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The dominator tree of the blocks of a function.
+///
+/// Blocks are identified by their labels; blocks without a label are
+/// ignored. The first block is the entry block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dominators {
+    /// Reachable blocks in reverse postorder from the entry block.
+    order: Vec<Word>,
+    /// Immediate dominators of reachable blocks; the entry block is its own.
+    idoms: BTreeMap<Word, Word>,
+    /// Predecessors of all blocks, reachable or not.
+    predecessors: BTreeMap<Word, Vec<Word>>,
+}
+
+impl Dominators {
+    /// Computes the dominator tree of `function`.
+    ///
+    /// Uses the iterative algorithm of Cooper, Harvey, and Kennedy, "A
+    /// Simple, Fast Dominance Algorithm".
+    pub fn new(function: &mr::Function) -> Dominators {
+        let mut successors = BTreeMap::new();
+        let mut predecessors: BTreeMap<Word, Vec<Word>> = BTreeMap::new();
+        for block in &function.basic_blocks {
+            if let Some(label) = block.label.as_ref().and_then(|l| l.result_id) {
+                predecessors.entry(label).or_default();
+                let targets = block.successors();
+                for &target in &targets {
+                    let preds = predecessors.entry(target).or_default();
+                    if !preds.contains(&label) {
+                        preds.push(label);
+                    }
+                }
+                successors.insert(label, targets);
+            }
+        }
+
+        let mut order = vec![];
+        if let Some(entry) = function.basic_blocks
+            .first()
+            .and_then(|b| b.label.as_ref())
+            .and_then(|l| l.result_id) {
+            postorder(entry, &successors, &mut order);
+            order.reverse();
+        }
+        let number: BTreeMap<Word, usize> =
+            order.iter().enumerate().map(|(i, &label)| (label, i)).collect();
+
+        let mut idoms = BTreeMap::new();
+        if let Some(&entry) = order.first() {
+            idoms.insert(entry, entry);
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &label in order.iter().skip(1) {
+                let mut idom = None;
+                for &pred in &predecessors[&label] {
+                    if !idoms.contains_key(&pred) {
+                        continue;
+                    }
+                    idom = Some(match idom {
+                        None => pred,
+                        Some(other) => intersect(pred, other, &idoms, &number),
+                    });
+                }
+                if let Some(idom) = idom {
+                    if idoms.insert(label, idom) != Some(idom) {
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        Dominators {
+            order,
+            idoms,
+            predecessors,
+        }
+    }
+
+    /// Returns the label of the entry block.
+    pub fn entry(&self) -> Option<Word> {
+        self.order.first().cloned()
+    }
+
+    /// Returns the labels of the reachable blocks in reverse postorder.
+    pub fn reverse_postorder(&self) -> &[Word] {
+        &self.order
+    }
+
+    /// Returns true if the block labeled `label` is reachable from the
+    /// entry block.
+    pub fn is_reachable(&self, label: Word) -> bool {
+        self.idoms.contains_key(&label)
+    }
+
+    /// Returns the immediate dominator of the block labeled `label`, or
+    /// `None` for the entry block and unreachable blocks.
+    pub fn immediate_dominator(&self, label: Word) -> Option<Word> {
+        self.idoms.get(&label).cloned().filter(|&idom| idom != label)
+    }
+
+    /// Returns the labels of the blocks branching to the block labeled
+    /// `label`, whether reachable or not.
+    pub fn predecessors(&self, label: Word) -> &[Word] {
+        self.predecessors.get(&label).map_or(&[], |preds| &preds[..])
+    }
+
+    /// Returns true if the block labeled `a` dominates the one labeled `b`.
+    ///
+    /// Every block dominates itself. Unreachable blocks are neither
+    /// dominated by nor dominate any block.
+    pub fn dominates(&self, a: Word, b: Word) -> bool {
+        if !self.is_reachable(a) || !self.is_reachable(b) {
+            return false;
+        }
+        let mut current = b;
+        loop {
+            if current == a {
+                return true;
+            }
+            match self.immediate_dominator(current) {
+                Some(idom) => current = idom,
+                None => return false,
+            }
+        }
+    }
+}
+
+/// Appends the blocks reachable from `entry` to `order` in postorder.
+fn postorder(entry: Word, successors: &BTreeMap<Word, Vec<Word>>, order: &mut Vec<Word>) {
+    let mut visited = BTreeSet::new();
+    visited.insert(entry);
+    // (block, index of the next successor to visit)
+    let mut stack = vec![(entry, 0)];
+    while let Some(&(label, next)) = stack.last() {
+        match successors[&label].get(next) {
+            Some(&target) => {
+                stack.last_mut().unwrap().1 += 1;
+                // Branches to unknown labels lead nowhere.
+                if successors.contains_key(&target) && visited.insert(target) {
+                    stack.push((target, 0));
+                }
+            }
+            None => {
+                order.push(label);
+                stack.pop();
+            }
+        }
+    }
+}
+
+fn intersect(mut a: Word,
+             mut b: Word,
+             idoms: &BTreeMap<Word, Word>,
+             number: &BTreeMap<Word, usize>)
+             -> Word {
+    while a != b {
+        while number[&a] > number[&b] {
+            a = idoms[&a];
+        }
+        while number[&b] > number[&a] {
+            b = idoms[&b];
+        }
+    }
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::Dominators;
+
+    #[test]
+    fn test_dominators() {
+        // entry -> (then | merge), then -> merge, merge -> loop header,
+        // which loops back to itself; dead is unreachable.
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let bool_type = b.type_bool();
+        let cond = b.constant_true(bool_type);
+        let voidfvoid = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        let entry = b.begin_basic_block(None).unwrap();
+        let (then, merge, header, exit, dead) = (b.id(), b.id(), b.id(), b.id(), b.id());
+        b.selection_merge(merge, spirv::SelectionControl::NONE).unwrap();
+        b.branch_conditional(cond, then, merge, vec![]).unwrap();
+        b.begin_basic_block(Some(then)).unwrap();
+        b.branch(merge).unwrap();
+        b.begin_basic_block(Some(merge)).unwrap();
+        b.branch(header).unwrap();
+        b.begin_basic_block(Some(header)).unwrap();
+        b.loop_merge(exit, header, spirv::LoopControl::NONE, vec![]).unwrap();
+        b.branch_conditional(cond, header, exit, vec![]).unwrap();
+        b.begin_basic_block(Some(exit)).unwrap();
+        b.ret().unwrap();
+        b.begin_basic_block(Some(dead)).unwrap();
+        b.branch(exit).unwrap();
+        b.end_function().unwrap();
+        let m = b.module();
+
+        let doms = Dominators::new(&m.functions[0]);
+        assert_eq!(doms.entry(), Some(entry));
+        assert_eq!(doms.reverse_postorder()[0], entry);
+        assert_eq!(doms.immediate_dominator(entry), None);
+        assert_eq!(doms.immediate_dominator(then), Some(entry));
+        assert_eq!(doms.immediate_dominator(merge), Some(entry));
+        assert_eq!(doms.immediate_dominator(header), Some(merge));
+        assert_eq!(doms.immediate_dominator(exit), Some(header));
+        assert!(doms.dominates(entry, exit));
+        assert!(doms.dominates(header, header));
+        assert!(!doms.dominates(then, merge));
+
+        assert!(!doms.is_reachable(dead));
+        assert_eq!(doms.immediate_dominator(dead), None);
+        assert!(!doms.dominates(dead, exit));
+        assert_eq!(doms.predecessors(exit), &[header, dead]);
+        assert_eq!(doms.predecessors(header), &[merge, header]);
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grammar;
+use mr;
+
+use std::collections::BTreeSet;
+
+const NON_SEMANTIC_INFO: &str = "SPV_KHR_non_semantic_info";
+
+/// The extensions SPIR-V versions made core, with the first version that
+/// did.
+const CORE_EXTENSIONS: &[((u8, u8), &str)] = &[
+    ((1, 3), "SPV_KHR_16bit_storage"),
+    ((1, 3), "SPV_KHR_device_group"),
+    ((1, 3), "SPV_KHR_multiview"),
+    ((1, 3), "SPV_KHR_shader_draw_parameters"),
+    ((1, 3), "SPV_KHR_storage_buffer_storage_class"),
+    ((1, 3), "SPV_KHR_variable_pointers"),
+    ((1, 4), "SPV_GOOGLE_decorate_string"),
+    ((1, 4), "SPV_GOOGLE_hlsl_functionality1"),
+    ((1, 4), "SPV_KHR_float_controls"),
+    ((1, 4), "SPV_KHR_no_integer_wrap_decoration"),
+    ((1, 5), "SPV_EXT_descriptor_indexing"),
+    ((1, 5), "SPV_EXT_physical_storage_buffer"),
+    ((1, 5), "SPV_EXT_shader_viewport_index_layer"),
+    ((1, 5), "SPV_KHR_8bit_storage"),
+    ((1, 5), "SPV_KHR_physical_storage_buffer"),
+    ((1, 5), "SPV_KHR_vulkan_memory_model"),
+    ((1, 6), "SPV_EXT_demote_to_helper_invocation"),
+    ((1, 6), "SPV_KHR_non_semantic_info"),
+    ((1, 6), "SPV_KHR_terminate_invocation"),
+];
+
+/// Returns true if SPIR-V of the given `version` has the extension `name`
+/// in core.
+fn is_core(name: &str, version: (u8, u8)) -> bool {
+    CORE_EXTENSIONS.iter().any(|&(since, core)| core == name && since <= version)
+}
+
+/// How the declared extensions of a module differ from those it needs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExtensionReport {
+    /// The requirements met by no declared extension, nor by the SPIR-V
+    /// version of the module: each lists alternatives, any one of which
+    /// suffices.
+    pub missing: Vec<&'static [&'static str]>,
+    /// The declarations of extensions that are not needed, in order: of
+    /// those core in the SPIR-V version of the module, of those the
+    /// grammar knows but nothing requires, and repeated declarations.
+    pub superfluous: Vec<String>,
+}
+
+impl ExtensionReport {
+    /// Returns true if the declared extensions are exactly those needed.
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.superfluous.is_empty()
+    }
+}
+
+/// Compares the extensions `module` declares by `OpExtension` with those
+/// its instructions and operands require by the grammar, and non-semantic
+/// extended instruction sets require.
+///
+/// Extensions the grammar does not know are not reported as superfluous,
+/// as what requires them cannot be told. The SPIR-V version is that of the
+/// header, or 1.0 without one.
+pub fn check_extensions(module: &mr::Module) -> ExtensionReport {
+    let version = module.header.as_ref().map_or((1, 0), |header| header.version());
+    let mut required = module.requirements().extensions;
+    let non_semantic = module.ext_inst_imports.iter().any(|inst| {
+        matches!(inst.operands.first(),
+                 Some(mr::Operand::LiteralString(set)) if set.starts_with("NonSemantic."))
+    });
+    if non_semantic {
+        required.push(&[NON_SEMANTIC_INFO]);
+    }
+    let anything_requires = |name: &str| required.iter().any(|names| names.contains(&name));
+    let known = |name: &str| {
+        name == NON_SEMANTIC_INFO || grammar::reflect::known_extensions().contains(&name)
+    };
+
+    let declared = module.extension_names();
+    let mut report = ExtensionReport::default();
+    for &alternatives in &required {
+        if !alternatives.iter().any(|name| declared.contains(name) || is_core(name, version)) {
+            report.missing.push(alternatives);
+        }
+    }
+    let mut seen = BTreeSet::new();
+    for &name in &declared {
+        let unneeded = is_core(name, version) || (known(name) && !anything_requires(name));
+        if !seen.insert(name) || unneeded {
+            report.superfluous.push(name.to_owned());
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::check_extensions;
+
+    #[test]
+    fn test_check_extensions() {
+        let mut b = mr::Builder::new();
+        b.set_infer_requirements(false);
+        b.capability(spirv::Capability::Shader);
+        b.capability(spirv::Capability::ShaderNonUniform);
+        b.extension("SPV_KHR_multiview");
+        b.extension("SPV_KHR_multiview");
+        b.extension("SPV_VENDOR_unknown");
+        b.extension("SPV_KHR_variable_pointers");
+        b.ext_inst_import("NonSemantic.DebugPrintf");
+        let mut m = b.module();
+        m.header.as_mut().unwrap().version = 0x0001_0300;
+
+        let report = check_extensions(&m);
+        assert_eq!(report.missing,
+                   vec![&["SPV_EXT_descriptor_indexing"][..], &["SPV_KHR_non_semantic_info"][..]]);
+        assert_eq!(report.superfluous,
+                   vec!["SPV_KHR_multiview".to_owned(),
+                        "SPV_KHR_multiview".to_owned(),
+                        "SPV_KHR_variable_pointers".to_owned()]);
+        assert!(!report.is_empty());
+    }
+}
@@ -0,0 +1,703 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeSet, HashMap};
+
+/// The kind of an image or sampler resource.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResourceKind {
+    /// An `OpTypeImage` resource.
+    Image,
+    /// An `OpTypeSampler` resource.
+    Sampler,
+    /// An `OpTypeSampledImage` (combined image and sampler) resource.
+    SampledImage,
+}
+
+/// How an image or sampler binding is used across the whole module.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageUsage {
+    /// The result id of the `OpVariable` declaring the binding.
+    pub variable: Word,
+    /// The kind of the resource behind the variable.
+    pub kind: ResourceKind,
+    /// The `DescriptorSet` decoration on the variable, if any.
+    pub descriptor_set: Option<u32>,
+    /// The `Binding` decoration on the variable, if any.
+    pub binding: Option<u32>,
+    /// Used by any of the `OpImageSample*` or `OpImage*Gather` instructions.
+    pub sampled: bool,
+    /// Used by `OpImageFetch` or `OpImageSparseFetch`.
+    pub fetched: bool,
+    /// Used by `OpImageRead` or `OpImageSparseRead`.
+    pub storage_read: bool,
+    /// Used by `OpImageWrite`.
+    pub storage_write: bool,
+    /// Used by atomic instructions through `OpImageTexelPointer`.
+    pub atomic: bool,
+    /// Used by any of the depth-comparison (`Dref`) instructions.
+    pub depth_comparison: bool,
+    /// Used by any of the `OpImageQuery*` instructions.
+    pub queried: bool,
+    /// The union of all image operands used together with the binding.
+    pub image_operands: spirv::ImageOperands,
+}
+
+impl ImageUsage {
+    fn new(variable: Word, kind: ResourceKind) -> ImageUsage {
+        ImageUsage {
+            variable,
+            kind,
+            descriptor_set: None,
+            binding: None,
+            sampled: false,
+            fetched: false,
+            storage_read: false,
+            storage_write: false,
+            atomic: false,
+            depth_comparison: false,
+            queried: false,
+            image_operands: spirv::ImageOperands::NONE,
+        }
+    }
+}
+
+/// The ways an instruction can use an image or sampler operand.
+enum Access {
+    Sample,
+    SampleDref,
+    Fetch,
+    Read,
+    Write,
+    Query,
+    Atomic,
+}
+
+/// Reports how each image or sampler binding in the given `module` is used.
+///
+/// Bindings are `UniformConstant` variables whose type is (an array of)
+/// `OpTypeImage`, `OpTypeSampler`, or `OpTypeSampledImage`. Uses are traced
+/// through loads, access chains, `OpSampledImage`, `OpImage`,
+/// `OpCopyObject`, `OpPhi`, `OpSelect`, function-local copies, and function
+/// call arguments. A sampler combined into a sampled image is considered to
+/// be used by all sampling instructions on that sampled image, so that
+/// comparison samplers can be told apart from ordinary ones.
+///
+/// The result follows the order in which the variables are declared.
+pub fn image_usages(module: &mr::Module) -> Vec<ImageUsage> {
+    let defs: HashMap<Word, &mr::Instruction> = module
+        .types_global_values
+        .iter()
+        .filter_map(|inst| inst.result_id.map(|id| (id, inst)))
+        .collect();
+
+    let mut usages: Vec<ImageUsage> = module
+        .types_global_values
+        .iter()
+        .filter(|inst| inst.class.opcode == spirv::Op::Variable)
+        .filter(|inst| {
+            inst.operands.first()
+                == Some(&mr::Operand::StorageClass(spirv::StorageClass::UniformConstant))
+        })
+        .filter_map(|inst| {
+            let id = inst.result_id?;
+            let kind = resource_kind(&defs, inst.result_type?)?;
+            Some(ImageUsage::new(id, kind))
+        })
+        .collect();
+
+    for inst in &module.annotations {
+        if inst.class.opcode != spirv::Op::Decorate || inst.operands.len() < 3 {
+            continue;
+        }
+        if let Some(usage) = usages
+            .iter_mut()
+            .find(|u| inst.operands[0] == mr::Operand::IdRef(u.variable))
+        {
+            match (&inst.operands[1], &inst.operands[2]) {
+                (&mr::Operand::Decoration(spirv::Decoration::DescriptorSet),
+                 &mr::Operand::LiteralInt32(v)) => usage.descriptor_set = Some(v),
+                (&mr::Operand::Decoration(spirv::Decoration::Binding),
+                 &mr::Operand::LiteralInt32(v)) => usage.binding = Some(v),
+                _ => (),
+            }
+        }
+    }
+
+    let origins = trace_origins(module, usages.iter().map(|u| u.variable));
+
+    for inst in module
+        .functions
+        .iter()
+        .flat_map(|f| f.basic_blocks.iter())
+        .flat_map(|b| b.instructions.iter())
+    {
+        let access = match access_kind(inst.class.opcode) {
+            Some(access) => access,
+            None => continue,
+        };
+        let sources = match id_operand(inst, 0).and_then(|id| origins.get(&id)) {
+            Some(sources) => sources,
+            None => continue,
+        };
+        let image_operands = inst.operands.iter().fold(
+            spirv::ImageOperands::NONE,
+            |acc, operand| match *operand {
+                mr::Operand::ImageOperands(v) => acc | v,
+                _ => acc,
+            },
+        );
+        for usage in usages.iter_mut().filter(|u| sources.contains(&u.variable)) {
+            let samples = matches!(access, Access::Sample | Access::SampleDref);
+            // Only sampling instructions actually use the sampler half of
+            // a sampled image; others work on the image extracted from it.
+            if usage.kind == ResourceKind::Sampler && !samples {
+                continue;
+            }
+            match access {
+                Access::Sample => usage.sampled = true,
+                Access::SampleDref => {
+                    usage.sampled = true;
+                    usage.depth_comparison = true;
+                }
+                Access::Fetch => usage.fetched = true,
+                Access::Read => usage.storage_read = true,
+                Access::Write => usage.storage_write = true,
+                Access::Query => usage.queried = true,
+                Access::Atomic => usage.atomic = true,
+            }
+            usage.image_operands |= image_operands;
+        }
+    }
+
+    usages
+}
+
+/// How a sampler binding is used by sampling instructions, telling whether
+/// it can be an immutable sampler baked into the pipeline layout.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SamplerUsage {
+    /// The result id of the `OpVariable` declaring the binding.
+    pub variable: Word,
+    /// Either `Sampler` or `SampledImage`.
+    pub kind: ResourceKind,
+    /// The `DescriptorSet` decoration on the variable, if any.
+    pub descriptor_set: Option<u32>,
+    /// The `Binding` decoration on the variable, if any.
+    pub binding: Option<u32>,
+    /// The number of sampling instructions using the binding.
+    pub samples: usize,
+    /// Whether the sampling instructions compare depth (`Dref`), if they
+    /// all agree on it.
+    pub depth_comparison: Option<bool>,
+    /// The image operands only ever given constant values.
+    pub constant_operands: spirv::ImageOperands,
+    /// The image operands given a non-constant value at least once.
+    pub dynamic_operands: spirv::ImageOperands,
+    /// Whether a sampling instruction can use either this or another
+    /// sampler binding, e.g., through `OpSelect` or `OpPhi`.
+    pub selected: bool,
+    /// Whether the binding is a candidate for an immutable sampler.
+    pub immutable_candidate: bool,
+}
+
+/// Reports how each sampler binding in the given `module` is sampled, and
+/// flags the ones worth declaring as immutable samplers.
+///
+/// Bindings are `UniformConstant` variables whose type is (an array of)
+/// `OpTypeSampler` or `OpTypeSampledImage`, and uses are traced as for
+/// [`image_usages`](fn.image_usages.html). A binding is an immutable
+/// sampler candidate if it is sampled at all, either always or never with
+/// depth comparison, never with a non-constant `Bias`, `Lod`, `Grad`, or
+/// `MinLod` operand, and never by an instruction that can select between
+/// several sampler bindings. The state of such samplers is fully known when
+/// compiling the module. Specialization constants count as non-constant.
+///
+/// The result follows the order in which the variables are declared.
+pub fn sampler_usages(module: &mr::Module) -> Vec<SamplerUsage> {
+    let mut usages: Vec<SamplerUsage> = image_usages(module)
+        .into_iter()
+        .filter(|usage| usage.kind != ResourceKind::Image)
+        .map(|usage| SamplerUsage {
+            variable: usage.variable,
+            kind: usage.kind,
+            descriptor_set: usage.descriptor_set,
+            binding: usage.binding,
+            samples: 0,
+            depth_comparison: None,
+            constant_operands: spirv::ImageOperands::NONE,
+            dynamic_operands: spirv::ImageOperands::NONE,
+            selected: false,
+            immutable_candidate: false,
+        })
+        .collect();
+    let constants: BTreeSet<Word> = module
+        .types_global_values
+        .iter()
+        .filter(|inst| {
+            matches!(inst.class.opcode,
+                     spirv::Op::Constant |
+                     spirv::Op::ConstantComposite |
+                     spirv::Op::ConstantNull |
+                     spirv::Op::ConstantTrue |
+                     spirv::Op::ConstantFalse)
+        })
+        .filter_map(|inst| inst.result_id)
+        .collect();
+    let origins = trace_origins(module, usages.iter().map(|u| u.variable));
+    // Whether each binding has been sampled with depth comparison and
+    // without it.
+    let mut comparisons: HashMap<Word, (bool, bool)> = HashMap::new();
+
+    for inst in module
+        .functions
+        .iter()
+        .flat_map(|f| f.basic_blocks.iter())
+        .flat_map(|b| b.instructions.iter())
+    {
+        let dref = match access_kind(inst.class.opcode) {
+            Some(Access::Sample) => false,
+            Some(Access::SampleDref) => true,
+            _ => continue,
+        };
+        let sources = match id_operand(inst, 0).and_then(|id| origins.get(&id)) {
+            Some(sources) => sources,
+            None => continue,
+        };
+        let (constant, dynamic) = operand_constness(inst, &constants);
+        let selected = usages.iter().filter(|u| sources.contains(&u.variable)).count() > 1;
+        for usage in usages.iter_mut().filter(|u| sources.contains(&u.variable)) {
+            usage.samples += 1;
+            usage.constant_operands |= constant;
+            usage.dynamic_operands |= dynamic;
+            usage.selected |= selected;
+            let seen = comparisons.entry(usage.variable).or_insert((false, false));
+            if dref {
+                seen.0 = true;
+            } else {
+                seen.1 = true;
+            }
+        }
+    }
+
+    // The image operands controlling the level of detail, and so filtering.
+    let filtering = spirv::ImageOperands::BIAS | spirv::ImageOperands::LOD |
+        spirv::ImageOperands::GRAD | spirv::ImageOperands::MIN_LOD;
+    for usage in &mut usages {
+        usage.constant_operands.remove(usage.dynamic_operands);
+        usage.depth_comparison = match comparisons.get(&usage.variable) {
+            Some(&(true, false)) => Some(true),
+            Some(&(false, true)) => Some(false),
+            _ => None,
+        };
+        usage.immutable_candidate = usage.depth_comparison.is_some() && !usage.selected &&
+            !usage.dynamic_operands.intersects(filtering);
+    }
+    usages
+}
+
+/// Splits the image operands flagged on the given instruction `inst` into
+/// those given only the given `constants`, and the others.
+fn operand_constness(inst: &mr::Instruction, constants: &BTreeSet<Word>)
+                     -> (spirv::ImageOperands, spirv::ImageOperands) {
+    let mut constant = spirv::ImageOperands::NONE;
+    let mut dynamic = spirv::ImageOperands::NONE;
+    let flagged = inst.operands.iter().enumerate().find_map(|(i, operand)| match *operand {
+        mr::Operand::ImageOperands(mask) => Some((i, mask)),
+        _ => None,
+    });
+    let (position, mask) = match flagged {
+        Some(flagged) => flagged,
+        None => return (constant, dynamic),
+    };
+    // The operands of the flags follow the mask in the order of the bits.
+    let mut next = position + 1;
+    for bit in 0..32 {
+        let flag = match spirv::ImageOperands::from_bits(1 << bit) {
+            Some(flag) if mask.contains(flag) => flag,
+            _ => continue,
+        };
+        let count = if flag == spirv::ImageOperands::GRAD {
+            2
+        } else if flag.bits() <= spirv::ImageOperands::MAKE_TEXEL_VISIBLE.bits() {
+            1
+        } else {
+            0
+        };
+        let all_constant = (next..next + count)
+            .all(|i| id_operand(inst, i).is_some_and(|id| constants.contains(&id)));
+        if all_constant {
+            constant |= flag;
+        } else {
+            dynamic |= flag;
+        }
+        next += count;
+    }
+    (constant, dynamic)
+}
+
+/// Returns the resource kind of the given pointer type `ty`, looking
+/// through arrays of resources.
+fn resource_kind(defs: &HashMap<Word, &mr::Instruction>, ty: Word) -> Option<ResourceKind> {
+    let mut ty = ty;
+    loop {
+        let inst = defs.get(&ty)?;
+        ty = match inst.class.opcode {
+            spirv::Op::TypeImage => return Some(ResourceKind::Image),
+            spirv::Op::TypeSampler => return Some(ResourceKind::Sampler),
+            spirv::Op::TypeSampledImage => return Some(ResourceKind::SampledImage),
+            spirv::Op::TypePointer => id_operand(inst, 1)?,
+            spirv::Op::TypeArray | spirv::Op::TypeRuntimeArray => id_operand(inst, 0)?,
+            _ => return None,
+        };
+    }
+}
+
+/// Maps all values derived from the given `variables` to the set of
+/// variables they can originate from.
+fn trace_origins<I>(module: &mr::Module, variables: I) -> HashMap<Word, BTreeSet<Word>>
+where
+    I: Iterator<Item = Word>,
+{
+    let parameters: HashMap<Word, Vec<Word>> = module
+        .functions
+        .iter()
+        .filter_map(|f| {
+            let id = f.def.as_ref()?.result_id?;
+            Some((id, f.parameters.iter().filter_map(|p| p.result_id).collect()))
+        })
+        .collect();
+
+    // Edges from a value to the values derived from it.
+    let mut edges: HashMap<Word, Vec<Word>> = HashMap::new();
+    {
+        let mut add_edge = |from: Option<Word>, to: Option<Word>| {
+            if let (Some(from), Some(to)) = (from, to) {
+                edges.entry(from).or_default().push(to);
+            }
+        };
+        for inst in module
+            .functions
+            .iter()
+            .flat_map(|f| f.basic_blocks.iter())
+            .flat_map(|b| b.instructions.iter())
+        {
+            match inst.class.opcode {
+                spirv::Op::Load |
+                spirv::Op::AccessChain |
+                spirv::Op::InBoundsAccessChain |
+                spirv::Op::PtrAccessChain |
+                spirv::Op::InBoundsPtrAccessChain |
+                spirv::Op::CopyObject |
+                spirv::Op::Image |
+                spirv::Op::ImageTexelPointer => add_edge(id_operand(inst, 0), inst.result_id),
+                spirv::Op::SampledImage => {
+                    add_edge(id_operand(inst, 0), inst.result_id);
+                    add_edge(id_operand(inst, 1), inst.result_id);
+                }
+                spirv::Op::Select => {
+                    add_edge(id_operand(inst, 1), inst.result_id);
+                    add_edge(id_operand(inst, 2), inst.result_id);
+                }
+                spirv::Op::Phi => {
+                    for i in (0..inst.operands.len()).step_by(2) {
+                        add_edge(id_operand(inst, i), inst.result_id);
+                    }
+                }
+                spirv::Op::Store => add_edge(id_operand(inst, 1), id_operand(inst, 0)),
+                spirv::Op::FunctionCall => {
+                    let callee = id_operand(inst, 0).and_then(|f| parameters.get(&f));
+                    if let Some(params) = callee {
+                        for (i, param) in params.iter().enumerate() {
+                            add_edge(id_operand(inst, i + 1), Some(*param));
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    let mut origins: HashMap<Word, BTreeSet<Word>> = HashMap::new();
+    for var in variables {
+        let mut worklist = vec![var];
+        while let Some(id) = worklist.pop() {
+            if !origins.entry(id).or_default().insert(var) {
+                continue;
+            }
+            if let Some(derived) = edges.get(&id) {
+                worklist.extend(derived.iter().cloned());
+            }
+        }
+    }
+    origins
+}
+
+fn access_kind(opcode: spirv::Op) -> Option<Access> {
+    use spirv::Op::*;
+    Some(match opcode {
+        ImageSampleImplicitLod |
+        ImageSampleExplicitLod |
+        ImageSampleProjImplicitLod |
+        ImageSampleProjExplicitLod |
+        ImageSparseSampleImplicitLod |
+        ImageSparseSampleExplicitLod |
+        ImageSparseSampleProjImplicitLod |
+        ImageSparseSampleProjExplicitLod |
+        ImageGather |
+        ImageSparseGather |
+        ImageQueryLod => Access::Sample,
+        ImageSampleDrefImplicitLod |
+        ImageSampleDrefExplicitLod |
+        ImageSampleProjDrefImplicitLod |
+        ImageSampleProjDrefExplicitLod |
+        ImageSparseSampleDrefImplicitLod |
+        ImageSparseSampleDrefExplicitLod |
+        ImageSparseSampleProjDrefImplicitLod |
+        ImageSparseSampleProjDrefExplicitLod |
+        ImageDrefGather |
+        ImageSparseDrefGather => Access::SampleDref,
+        ImageFetch | ImageSparseFetch => Access::Fetch,
+        ImageRead | ImageSparseRead => Access::Read,
+        ImageWrite => Access::Write,
+        ImageQueryFormat |
+        ImageQueryOrder |
+        ImageQuerySizeLod |
+        ImageQuerySize |
+        ImageQueryLevels |
+        ImageQuerySamples => Access::Query,
+        AtomicLoad |
+        AtomicStore |
+        AtomicExchange |
+        AtomicCompareExchange |
+        AtomicCompareExchangeWeak |
+        AtomicIIncrement |
+        AtomicIDecrement |
+        AtomicIAdd |
+        AtomicISub |
+        AtomicSMin |
+        AtomicUMin |
+        AtomicSMax |
+        AtomicUMax |
+        AtomicAnd |
+        AtomicOr |
+        AtomicXor => Access::Atomic,
+        _ => return None,
+    })
+}
+
+fn id_operand(inst: &mr::Instruction, index: usize) -> Option<Word> {
+    match inst.operands.get(index) {
+        Some(&mr::Operand::IdRef(id)) => Some(id),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis;
+    use mr;
+    use spirv;
+
+    struct Resources {
+        b: mr::Builder,
+        void: spirv::Word,
+        float: spirv::Word,
+        vec4: spirv::Word,
+        image: spirv::Word,
+        sampler: spirv::Word,
+        sampled_image: spirv::Word,
+        image_var: spirv::Word,
+        sampler_var: spirv::Word,
+    }
+
+    fn setup() -> Resources {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let vec4 = b.type_vector(float, 4);
+        let image = b.type_image(float, spirv::Dim::Dim2D, 0, 0, 0, 1,
+                                 spirv::ImageFormat::Unknown, None);
+        let sampler = b.type_sampler();
+        let sampled_image = b.type_sampled_image(image);
+        let image_ptr = b.type_pointer(None, spirv::StorageClass::UniformConstant, image);
+        let sampler_ptr = b.type_pointer(None, spirv::StorageClass::UniformConstant, sampler);
+        let image_var = b.variable(image_ptr, None, spirv::StorageClass::UniformConstant, None);
+        let sampler_var = b.variable(sampler_ptr, None, spirv::StorageClass::UniformConstant,
+                                     None);
+        b.decorate(image_var, spirv::Decoration::DescriptorSet,
+                   vec![mr::Operand::LiteralInt32(1)]);
+        b.decorate(image_var, spirv::Decoration::Binding, vec![mr::Operand::LiteralInt32(3)]);
+        Resources { b, void, float, vec4, image, sampler, sampled_image, image_var, sampler_var }
+    }
+
+    #[test]
+    fn test_sampled_with_depth_comparison() {
+        let mut r = setup();
+        let voidf = r.b.type_function(r.void, vec![]);
+        let coord = r.b.constant_f32(r.float, 0.5);
+        r.b.begin_function(r.void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        r.b.begin_basic_block(None).unwrap();
+        let image = r.b.load(r.image, None, r.image_var, None, vec![]).unwrap();
+        let sampler = r.b.load(r.sampler, None, r.sampler_var, None, vec![]).unwrap();
+        let si = r.b.sampled_image(r.sampled_image, None, image, sampler).unwrap();
+        let copy = r.b.copy_object(r.sampled_image, None, si).unwrap();
+        r.b.image_sample_dref_explicit_lod(r.float, None, copy, coord, coord,
+                                           spirv::ImageOperands::LOD,
+                                           vec![mr::Operand::IdRef(coord)]).unwrap();
+        r.b.ret().unwrap();
+        r.b.end_function().unwrap();
+
+        let usages = analysis::image_usages(&r.b.module());
+        assert_eq!(2, usages.len());
+
+        let image = &usages[0];
+        assert_eq!(analysis::ResourceKind::Image, image.kind);
+        assert_eq!((Some(1), Some(3)), (image.descriptor_set, image.binding));
+        assert!(image.sampled && image.depth_comparison);
+        assert!(!image.fetched && !image.storage_read && !image.storage_write);
+        assert_eq!(spirv::ImageOperands::LOD, image.image_operands);
+
+        let sampler = &usages[1];
+        assert_eq!(analysis::ResourceKind::Sampler, sampler.kind);
+        assert_eq!((None, None), (sampler.descriptor_set, sampler.binding));
+        assert!(sampler.sampled && sampler.depth_comparison);
+    }
+
+    #[test]
+    fn test_storage_through_function_call() {
+        let mut r = setup();
+        let uint = r.b.type_int(32, 0);
+        let zero = r.b.constant_u32(uint, 0);
+        let helperf = r.b.type_function(r.void, vec![r.image]);
+        let mainf = r.b.type_function(r.void, vec![]);
+
+        let helper = r.b.begin_function(r.void, None, spirv::FunctionControl::NONE, helperf)
+            .unwrap();
+        let param = r.b.function_parameter(r.image).unwrap();
+        r.b.begin_basic_block(None).unwrap();
+        let texel = r.b.image_read(r.vec4, None, param, zero, None, vec![]).unwrap();
+        r.b.image_write(param, zero, texel, Some(spirv::ImageOperands::SAMPLE),
+                        vec![mr::Operand::IdRef(zero)]).unwrap();
+        r.b.ret().unwrap();
+        r.b.end_function().unwrap();
+
+        r.b.begin_function(r.void, None, spirv::FunctionControl::NONE, mainf).unwrap();
+        r.b.begin_basic_block(None).unwrap();
+        let image = r.b.load(r.image, None, r.image_var, None, vec![]).unwrap();
+        r.b.function_call(r.void, None, helper, vec![image]).unwrap();
+        r.b.ret().unwrap();
+        r.b.end_function().unwrap();
+
+        let usages = analysis::image_usages(&r.b.module());
+        let image = &usages[0];
+        assert!(image.storage_read && image.storage_write);
+        assert!(!image.sampled && !image.atomic);
+        assert_eq!(spirv::ImageOperands::SAMPLE, image.image_operands);
+        assert_eq!(analysis::ImageUsage {
+                       variable: r.sampler_var,
+                       kind: analysis::ResourceKind::Sampler,
+                       descriptor_set: None,
+                       binding: None,
+                       sampled: false,
+                       fetched: false,
+                       storage_read: false,
+                       storage_write: false,
+                       atomic: false,
+                       depth_comparison: false,
+                       queried: false,
+                       image_operands: spirv::ImageOperands::NONE,
+                   },
+                   usages[1]);
+    }
+
+    #[test]
+    fn test_atomic_through_texel_pointer() {
+        let mut r = setup();
+        let uint = r.b.type_int(32, 0);
+        let zero = r.b.constant_u32(uint, 0);
+        let one = r.b.constant_u32(uint, 1);
+        let texel_ptr = r.b.type_pointer(None, spirv::StorageClass::Image, uint);
+        let voidf = r.b.type_function(r.void, vec![]);
+        r.b.begin_function(r.void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        r.b.begin_basic_block(None).unwrap();
+        let ptr = r.b.image_texel_pointer(texel_ptr, None, r.image_var, zero, zero).unwrap();
+        r.b.atomic_iadd(uint, None, ptr, one, zero, one).unwrap();
+        let image = r.b.load(r.image, None, r.image_var, None, vec![]).unwrap();
+        r.b.image_query_size(uint, None, image).unwrap();
+        r.b.ret().unwrap();
+        r.b.end_function().unwrap();
+
+        let usages = analysis::image_usages(&r.b.module());
+        assert!(usages[0].atomic && usages[0].queried);
+        assert!(!usages[0].storage_read && !usages[0].storage_write);
+        assert!(!usages[1].atomic);
+    }
+
+    #[test]
+    fn test_sampler_usages() {
+        let mut r = setup();
+        let sampler_ptr = r.b.type_pointer(None, spirv::StorageClass::UniformConstant, r.sampler);
+        let dynamic_var = r.b.variable(sampler_ptr, None, spirv::StorageClass::UniformConstant,
+                                       None);
+        let voidf = r.b.type_function(r.void, vec![]);
+        let coord = r.b.constant_f32(r.float, 0.5);
+        let lod = r.b.constant_f32(r.float, 2.0);
+        r.b.begin_function(r.void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        r.b.begin_basic_block(None).unwrap();
+        let image = r.b.load(r.image, None, r.image_var, None, vec![]).unwrap();
+        let sampler = r.b.load(r.sampler, None, r.sampler_var, None, vec![]).unwrap();
+        let shadow = r.b.sampled_image(r.sampled_image, None, image, sampler).unwrap();
+        for _ in 0..2 {
+            r.b.image_sample_dref_explicit_lod(r.float, None, shadow, coord, coord,
+                                               spirv::ImageOperands::LOD,
+                                               vec![mr::Operand::IdRef(lod)]).unwrap();
+        }
+        let sampler = r.b.load(r.sampler, None, dynamic_var, None, vec![]).unwrap();
+        let si = r.b.sampled_image(r.sampled_image, None, image, sampler).unwrap();
+        let bias = r.b.fadd(r.float, None, lod, coord).unwrap();
+        r.b.image_sample_implicit_lod(r.vec4, None, si, coord,
+                                      Some(spirv::ImageOperands::BIAS |
+                                           spirv::ImageOperands::CONST_OFFSET),
+                                      vec![mr::Operand::IdRef(bias),
+                                           mr::Operand::IdRef(coord)]).unwrap();
+        r.b.ret().unwrap();
+        r.b.end_function().unwrap();
+
+        let usages = analysis::sampler_usages(&r.b.module());
+        assert_eq!(2, usages.len());
+        assert_eq!(analysis::SamplerUsage {
+                       variable: r.sampler_var,
+                       kind: analysis::ResourceKind::Sampler,
+                       descriptor_set: None,
+                       binding: None,
+                       samples: 2,
+                       depth_comparison: Some(true),
+                       constant_operands: spirv::ImageOperands::LOD,
+                       dynamic_operands: spirv::ImageOperands::NONE,
+                       selected: false,
+                       immutable_candidate: true,
+                   },
+                   usages[0]);
+        let dynamic = &usages[1];
+        assert_eq!((1, Some(false)), (dynamic.samples, dynamic.depth_comparison));
+        assert_eq!(spirv::ImageOperands::CONST_OFFSET, dynamic.constant_operands);
+        assert_eq!(spirv::ImageOperands::BIAS, dynamic.dynamic_operands);
+        assert!(!dynamic.immutable_candidate);
+    }
+}
@@ -0,0 +1,40 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Analyses over the data representation of SPIR-V modules.
+//!
+//! Analyses in this module inspect a [`mr::Module`](../mr/struct.Module.html)
+//! without modifying it and summarize the findings into plain structs.
+//! The [`cfg`](cfg/index.html) module renders control flow as Graphviz
+//! graphs for inspection.
+
+pub use self::atomics::{audit_atomics, SyncOp, SyncWarning};
+pub use self::descriptors::{descriptor_indexing, DescriptorIndexing, DescriptorKind};
+pub use self::dominators::Dominators;
+pub use self::extensions::{check_extensions, ExtensionReport};
+pub use self::image::{image_usages, sampler_usages, ImageUsage, ResourceKind, SamplerUsage};
+pub use self::pointers::{trace_pointer, Index, PointerOrigin, Step};
+pub use self::ssa::{verify_ssa, Site, SsaError};
+pub use self::variable_pointers::{function_variable_pointers, variable_pointers,
+                                  VariablePointer};
+
+mod atomics;
+pub mod cfg;
+mod descriptors;
+mod dominators;
+mod extensions;
+mod image;
+mod pointers;
+mod ssa;
+mod variable_pointers;
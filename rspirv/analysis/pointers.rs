@@ -0,0 +1,241 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeSet, HashMap};
+
+/// An index of an access chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Index {
+    /// The value of an `OpConstant`.
+    Constant(u64),
+    /// An index only known at run time, or by specialization: its id.
+    Dynamic(Word),
+}
+
+/// A step on the way from a root to a pointer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    /// Selects a member or element of the composite pointed to, as the
+    /// indexes of `OpAccessChain` do.
+    Index(Index),
+    /// Offsets the pointer by a number of elements, as the `Element` of
+    /// `OpPtrAccessChain` does.
+    Element(Index),
+}
+
+/// Where a pointer points into.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PointerOrigin {
+    /// The instruction the pointer is traced back to: an `OpVariable`
+    /// usually, else e.g. a parameter of a function never called, an
+    /// `OpLoad` of a pointer, or an `OpPhi` or `OpSelect`.
+    pub root: Word,
+    /// The steps from the root to the pointer, in order.
+    pub path: Vec<Step>,
+}
+
+/// What pointers are traced through.
+struct Tracer<'m> {
+    defs: HashMap<Word, &'m mr::Instruction>,
+    constants: HashMap<Word, u64>,
+    /// The function and position of each function parameter.
+    parameters: HashMap<Word, (Word, usize)>,
+    /// The `OpFunctionCall`s calling each function.
+    calls: HashMap<Word, Vec<&'m mr::Instruction>>,
+}
+
+impl<'m> Tracer<'m> {
+    fn new(module: &'m mr::Module) -> Tracer<'m> {
+        let mut tracer = Tracer {
+            defs: HashMap::new(),
+            constants: HashMap::new(),
+            parameters: HashMap::new(),
+            calls: HashMap::new(),
+        };
+        for inst in &module.types_global_values {
+            if let Some(id) = inst.result_id {
+                tracer.defs.insert(id, inst);
+            }
+            if inst.class.opcode == spirv::Op::Constant {
+                let value = match inst.operands.first() {
+                    Some(&mr::Operand::LiteralInt32(v)) => u64::from(v),
+                    Some(&mr::Operand::LiteralInt64(v)) => v,
+                    _ => continue,
+                };
+                tracer.constants.extend(inst.result_id.map(|id| (id, value)));
+            }
+        }
+        for f in &module.functions {
+            let function = match f.def.as_ref().and_then(|def| def.result_id) {
+                Some(id) => id,
+                None => continue,
+            };
+            for (index, parameter) in f.parameters.iter().enumerate() {
+                tracer.parameters.extend(parameter.result_id.map(|id| (id, (function, index))));
+            }
+            for inst in f.basic_blocks.iter().flat_map(|b| &b.instructions) {
+                if let Some(id) = inst.result_id {
+                    tracer.defs.insert(id, inst);
+                }
+                if inst.class.opcode == spirv::Op::FunctionCall {
+                    if let Some(callee) = inst.operands.first().and_then(mr::Operand::id) {
+                        tracer.calls.entry(callee).or_default().push(inst);
+                    }
+                }
+            }
+        }
+        tracer
+    }
+
+    fn index(&self, operand: &mr::Operand) -> Option<Index> {
+        let id = operand.id()?;
+        Some(self.constants.get(&id).map_or(Index::Dynamic(id), |&v| Index::Constant(v)))
+    }
+
+    /// Traces `id`, followed by the steps `rest` in reverse order, into
+    /// `origins`.
+    fn trace(&self,
+             id: Word,
+             rest: &mut Vec<Step>,
+             visited: &mut BTreeSet<Word>,
+             origins: &mut Vec<PointerOrigin>) {
+        if !visited.insert(id) {
+            return;
+        }
+        let root = |rest: &[Step]| {
+            PointerOrigin {
+                root: id,
+                path: rest.iter().rev().cloned().collect(),
+            }
+        };
+        if let Some(&(function, index)) = self.parameters.get(&id) {
+            let calls = self.calls.get(&function).map_or(&[][..], |calls| &calls[..]);
+            if calls.is_empty() {
+                origins.push(root(rest));
+            }
+            for call in calls {
+                if let Some(argument) = call.operands.get(index + 1).and_then(mr::Operand::id) {
+                    self.trace(argument, rest, visited, origins);
+                }
+            }
+        } else if let Some(inst) = self.defs.get(&id) {
+            let opcode = inst.class.opcode;
+            let chain = match opcode {
+                spirv::Op::AccessChain | spirv::Op::InBoundsAccessChain => Some(false),
+                spirv::Op::PtrAccessChain | spirv::Op::InBoundsPtrAccessChain => Some(true),
+                _ => None,
+            };
+            match (chain, inst.operands.first().and_then(mr::Operand::id)) {
+                (Some(element), Some(base)) => {
+                    let count = rest.len();
+                    let indexes = inst.operands[1..].iter().map(|operand| self.index(operand));
+                    for (i, index) in indexes.enumerate().rev() {
+                        let index = match index {
+                            Some(index) => index,
+                            None => continue,
+                        };
+                        rest.push(if element && i == 0 {
+                            Step::Element(index)
+                        } else {
+                            Step::Index(index)
+                        });
+                    }
+                    self.trace(base, rest, visited, origins);
+                    rest.truncate(count);
+                }
+                (None, Some(source)) if opcode == spirv::Op::CopyObject => {
+                    self.trace(source, rest, visited, origins)
+                }
+                _ => origins.push(root(rest)),
+            }
+        } else {
+            origins.push(root(rest));
+        }
+        visited.remove(&id);
+    }
+}
+
+/// Traces the pointer `id` of `module` back to what it points into,
+/// through access chains, `OpCopyObject`, and function parameters.
+///
+/// There is an origin per way the pointer can be computed: a parameter
+/// leads back to the corresponding argument of each call of its function.
+/// The result is in the order of calls, and empty if `id` is not defined
+/// in `module`.
+pub fn trace_pointer(module: &mr::Module, id: Word) -> Vec<PointerOrigin> {
+    let tracer = Tracer::new(module);
+    if !tracer.defs.contains_key(&id) && !tracer.parameters.contains_key(&id) {
+        return vec![];
+    }
+    let mut origins = vec![];
+    tracer.trace(id, &mut vec![], &mut BTreeSet::new(), &mut origins);
+    origins
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{trace_pointer, Index, PointerOrigin, Step};
+
+    #[test]
+    fn test_trace_pointer() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let four = b.constant_u32(uint, 4);
+        let one = b.constant_u32(uint, 1);
+        let array = b.type_array(uint, four);
+        let block = b.type_struct(vec![uint, array]);
+        let block_ptr = b.type_pointer(None, spirv::StorageClass::StorageBuffer, block);
+        let array_ptr = b.type_pointer(None, spirv::StorageClass::StorageBuffer, array);
+        let uint_ptr = b.type_pointer(None, spirv::StorageClass::StorageBuffer, uint);
+        let a = b.variable(block_ptr, None, spirv::StorageClass::StorageBuffer, None);
+        let c = b.variable(block_ptr, None, spirv::StorageClass::StorageBuffer, None);
+
+        let fty = b.type_function(void, vec![array_ptr, uint]);
+        let store = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let values = b.function_parameter(array_ptr).unwrap();
+        let i = b.function_parameter(uint).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let element = b.access_chain(uint_ptr, None, values, vec![i]).unwrap();
+        let copy = b.copy_object(uint_ptr, None, element).unwrap();
+        b.store(copy, one, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+
+        let fty = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let a_values = b.access_chain(array_ptr, None, a, vec![one]).unwrap();
+        let c_values = b.access_chain(array_ptr, None, c, vec![one]).unwrap();
+        b.function_call(void, None, store, vec![a_values, one]).unwrap();
+        b.function_call(void, None, store, vec![c_values, i]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let m = b.module();
+
+        let path = vec![Step::Index(Index::Constant(1)), Step::Index(Index::Dynamic(i))];
+        assert_eq!(trace_pointer(&m, copy),
+                   vec![PointerOrigin { root: a, path: path.clone() },
+                        PointerOrigin { root: c, path }]);
+        assert_eq!(trace_pointer(&m, a), vec![PointerOrigin { root: a, path: vec![] }]);
+        assert!(trace_pointer(&m, 1000).is_empty());
+    }
+}
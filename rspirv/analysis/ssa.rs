@@ -0,0 +1,311 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grammar;
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::BTreeMap;
+use std::collections::btree_map::Entry;
+use std::{error, fmt};
+
+use super::Dominators;
+
+/// Where an id is defined or used within a function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Site {
+    /// The parameter at the given index in `mr::Function::parameters`.
+    Parameter(usize),
+    /// The label of the block at the given index in
+    /// `mr::Function::basic_blocks`.
+    Label(usize),
+    /// The instruction at the given index in the instructions of the block
+    /// at the given index.
+    Instruction {
+        /// The index of the block in `mr::Function::basic_blocks`.
+        block: usize,
+        /// The index of the instruction in `mr::BasicBlock::instructions`.
+        index: usize,
+    },
+}
+
+/// SSA violations found by [`verify_ssa`](fn.verify_ssa.html).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SsaError {
+    /// The id is defined again at the site.
+    Redefined(Word, Site),
+    /// The id is used at the site, which its definition does not dominate.
+    NotDominated(Word, Site),
+    /// The `OpPhi` at the site names the label as a parent, but that block
+    /// does not branch to the phi's block.
+    PhiParentNotPredecessor(Word, Site),
+    /// The `OpPhi` at the site takes the id from a parent block, which the
+    /// definition of the id does not dominate.
+    PhiValueNotDominated(Word, Site),
+}
+
+impl SsaError {
+    /// Gives an descriptive string for each error.
+    ///
+    /// This method is intended to be used by fmt::Display and error::Error to
+    /// avoid duplication in implementation. So it's private.
+    fn describe(&self) -> &str {
+        match *self {
+            SsaError::Redefined(..) => "redefined id",
+            SsaError::NotDominated(..) => "use not dominated by definition",
+            SsaError::PhiParentNotPredecessor(..) => "phi parent is not a predecessor",
+            SsaError::PhiValueNotDominated(..) => "phi value does not dominate its parent",
+        }
+    }
+}
+
+impl error::Error for SsaError {
+    fn description(&self) -> &str {
+        self.describe()
+    }
+}
+
+impl fmt::Display for SsaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SsaError::Redefined(id, site) |
+            SsaError::NotDominated(id, site) |
+            SsaError::PhiParentNotPredecessor(id, site) |
+            SsaError::PhiValueNotDominated(id, site) => {
+                write!(f, "{} %{} at {}", self.describe(), id, site)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Site {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Site::Parameter(index) => write!(f, "parameter {}", index),
+            Site::Label(block) => write!(f, "label of block {}", block),
+            Site::Instruction { block, index } => {
+                write!(f, "instruction {} of block {}", index, block)
+            }
+        }
+    }
+}
+
+/// Verifies that `function` is in valid SSA form, given its dominator tree
+/// `dominators`, and returns all violations found, in function order.
+///
+/// Each id may be defined once, by a parameter, a label, or an
+/// instruction. A use of an id defined in the function must be dominated
+/// by its definition: it must come after it in the same block, or its
+/// block must be dominated by the block of the definition. Each value of
+/// an `OpPhi` instead must dominate the end of the parent block it comes
+/// from, and each parent must branch to the block of the phi.
+///
+/// Ids not defined in the function, e.g., types, constants, and global
+/// variables, are taken to be defined before it. Labels are only checked
+/// as phi parents. Uses in unreachable blocks are not checked.
+///
+/// # Examples
+///
+/// ```
+/// extern crate rspirv;
+/// extern crate spirv_headers as spirv;
+///
+/// use rspirv::analysis::{verify_ssa, Dominators, Site, SsaError};
+///
+/// fn main() {
+///     let mut b = rspirv::mr::Builder::new();
+///     let uint = b.type_int(32, 0);
+///     let fty = b.type_function(uint, vec![]);
+///     b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+///     b.begin_basic_block(None).unwrap();
+///     let later = b.id();
+///     let sum = b.iadd(uint, None, later, later).unwrap();
+///     b.iadd(uint, Some(later), sum, sum).unwrap();
+///     b.ret_value(sum).unwrap();
+///     b.end_function().unwrap();
+///     let module = b.module();
+///
+///     let function = &module.functions[0];
+///     let errors = verify_ssa(function, &Dominators::new(function));
+///     let site = Site::Instruction { block: 0, index: 0 };
+///     assert_eq!(errors, vec![SsaError::NotDominated(later, site),
+///                             SsaError::NotDominated(later, site)]);
+/// }
+/// ```
+pub fn verify_ssa(function: &mr::Function, dominators: &Dominators) -> Vec<SsaError> {
+    let mut errors = vec![];
+
+    let mut defs = BTreeMap::new();
+    {
+        let mut define = |id: Option<Word>, site: Site| if let Some(id) = id {
+            match defs.entry(id) {
+                Entry::Occupied(_) => errors.push(SsaError::Redefined(id, site)),
+                Entry::Vacant(entry) => {
+                    entry.insert(site);
+                }
+            }
+        };
+        for (index, param) in function.parameters.iter().enumerate() {
+            define(param.result_id, Site::Parameter(index));
+        }
+        for (block, bb) in function.basic_blocks.iter().enumerate() {
+            define(bb.label.as_ref().and_then(|l| l.result_id), Site::Label(block));
+            for (index, inst) in bb.instructions.iter().enumerate() {
+                define(inst.result_id, Site::Instruction { block, index });
+            }
+        }
+    }
+    let label_of = |block: usize| {
+        function.basic_blocks[block].label.as_ref().and_then(|l| l.result_id)
+    };
+
+    for (block, bb) in function.basic_blocks.iter().enumerate() {
+        let label = match label_of(block) {
+            Some(label) if dominators.is_reachable(label) => label,
+            _ => continue,
+        };
+        for (index, inst) in bb.instructions.iter().enumerate() {
+            let site = Site::Instruction { block, index };
+            if inst.class.opcode == spirv::Op::Phi {
+                for pair in inst.operands.chunks(2) {
+                    let (value, parent) = match (pair[0].id(), pair.get(1).and_then(mr::Operand::id)) {
+                        (Some(value), Some(parent)) => (value, parent),
+                        _ => continue,
+                    };
+                    if !dominators.predecessors(label).contains(&parent) {
+                        errors.push(SsaError::PhiParentNotPredecessor(parent, site));
+                        continue;
+                    }
+                    if !dominators.is_reachable(parent) {
+                        continue;
+                    }
+                    if let Some(&Site::Instruction { block: def_block, .. }) = defs.get(&value) {
+                        let dominated = label_of(def_block)
+                            .is_some_and(|def_label| dominators.dominates(def_label, parent));
+                        if !dominated {
+                            errors.push(SsaError::PhiValueNotDominated(value, site));
+                        }
+                    }
+                }
+                continue;
+            }
+            for (id, role) in inst.id_operands() {
+                if role == grammar::IdRole::BranchTarget {
+                    continue;
+                }
+                let dominated = match defs.get(&id) {
+                    Some(&Site::Instruction { block: def_block, index: def_index }) => {
+                        if def_block == block {
+                            def_index < index
+                        } else {
+                            label_of(def_block)
+                                .is_some_and(|def_label| dominators.dominates(def_label, label))
+                        }
+                    }
+                    _ => true,
+                };
+                if !dominated {
+                    errors.push(SsaError::NotDominated(id, site));
+                }
+            }
+        }
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use analysis::Dominators;
+    use super::{verify_ssa, Site, SsaError};
+
+    /// Builds a diamond: entry -> (left | right) -> merge, with a value
+    /// defined in each branch and merged by an OpPhi.
+    fn build() -> (mr::Module, Vec<spirv::Word>) {
+        let mut b = mr::Builder::new();
+        let uint = b.type_int(32, 0);
+        let bool_type = b.type_bool();
+        let cond = b.constant_true(bool_type);
+        let one = b.constant_u32(uint, 1);
+        let fty = b.type_function(uint, vec![uint]);
+        b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let param = b.function_parameter(uint).unwrap();
+        let entry = b.begin_basic_block(None).unwrap();
+        let (left, right, merge) = (b.id(), b.id(), b.id());
+        b.selection_merge(merge, spirv::SelectionControl::NONE).unwrap();
+        b.branch_conditional(cond, left, right, vec![]).unwrap();
+        b.begin_basic_block(Some(left)).unwrap();
+        let l = b.iadd(uint, None, param, one).unwrap();
+        b.branch(merge).unwrap();
+        b.begin_basic_block(Some(right)).unwrap();
+        let r = b.iadd(uint, None, param, param).unwrap();
+        b.branch(merge).unwrap();
+        b.begin_basic_block(Some(merge)).unwrap();
+        let phi = b.phi(uint, None, vec![(l, left), (r, right)]).unwrap();
+        b.ret_value(phi).unwrap();
+        b.end_function().unwrap();
+        (b.module(), vec![entry, left, right, merge, l, r])
+    }
+
+    fn verify(m: &mr::Module) -> Vec<SsaError> {
+        let f = &m.functions[0];
+        verify_ssa(f, &Dominators::new(f))
+    }
+
+    #[test]
+    fn test_valid() {
+        let (m, _) = build();
+        assert!(verify(&m).is_empty());
+    }
+
+    #[test]
+    fn test_not_dominated() {
+        let (mut m, ids) = build();
+        // Use the value of the left branch in the merge block.
+        m.functions[0].basic_blocks[3].instructions[1].operands[0] = mr::Operand::IdRef(ids[4]);
+        assert_eq!(verify(&m),
+                   vec![SsaError::NotDominated(ids[4], Site::Instruction { block: 3, index: 1 })]);
+    }
+
+    #[test]
+    fn test_phi() {
+        let (mut m, ids) = build();
+        // Swap the values, keeping the parents.
+        {
+            let operands = &mut m.functions[0].basic_blocks[3].instructions[0].operands;
+            operands.swap(0, 2);
+            operands.push(mr::Operand::IdRef(ids[4]));
+            operands.push(mr::Operand::IdRef(ids[0]));
+        }
+        let phi = Site::Instruction { block: 3, index: 0 };
+        assert_eq!(verify(&m),
+                   vec![SsaError::PhiValueNotDominated(ids[5], phi),
+                        SsaError::PhiValueNotDominated(ids[4], phi),
+                        SsaError::PhiParentNotPredecessor(ids[0], phi)]);
+    }
+
+    #[test]
+    fn test_redefined() {
+        let (mut m, ids) = build();
+        m.functions[0].basic_blocks[2].instructions[0].result_id = Some(ids[4]);
+        let errors = verify(&m);
+        assert_eq!(errors[0],
+                   SsaError::Redefined(ids[4], Site::Instruction { block: 2, index: 0 }));
+        assert_eq!(errors[0].to_string(),
+                   format!("redefined id %{} at instruction 0 of block 2", ids[4]));
+    }
+}
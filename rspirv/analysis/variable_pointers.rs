@@ -0,0 +1,183 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::HashMap;
+
+/// An instruction computing a pointer in a way logical addressing only
+/// allows with variable pointers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VariablePointer {
+    /// The result id of the function the instruction is in.
+    pub function: Word,
+    /// `OpSelect`, `OpPhi`, `OpFunctionCall`, or `OpPtrAccessChain`.
+    pub opcode: spirv::Op,
+    pub result_id: Word,
+    /// The storage class of the pointer.
+    pub storage_class: spirv::StorageClass,
+    /// The capability allowing such pointers of the storage class, or
+    /// `None` if no capability does.
+    pub required: Option<spirv::Capability>,
+    /// Whether the module declares the required capability, or does not
+    /// use logical addressing.
+    pub covered: bool,
+}
+
+/// Lists the instructions of `module` selecting pointers, merging them
+/// in `OpPhi`s, returning them from functions, or offsetting them with
+/// `OpPtrAccessChain`, in order.
+///
+/// `VariablePointersStorageBuffer` allows such pointers into the
+/// `StorageBuffer` storage class, and `VariablePointers` also those into
+/// `Workgroup`. Under the physical addressing models, all are covered.
+pub fn variable_pointers(module: &mr::Module) -> Vec<VariablePointer> {
+    let context = Context::new(module);
+    module.functions.iter().flat_map(|f| context.flows(f)).collect()
+}
+
+/// Lists the instructions of `function` of `module` computing variable
+/// pointers, as [`variable_pointers`](fn.variable_pointers.html) does.
+pub fn function_variable_pointers(module: &mr::Module,
+                                  function: &mr::Function)
+                                  -> Vec<VariablePointer> {
+    Context::new(module).flows(function)
+}
+
+/// What the module declares that decides which variable pointers are
+/// covered.
+struct Context {
+    capabilities: Vec<spirv::Capability>,
+    physical: bool,
+    /// Storage classes of pointer types by their ids.
+    pointers: HashMap<Word, spirv::StorageClass>,
+}
+
+impl Context {
+    fn new(module: &mr::Module) -> Context {
+        let capabilities = module.capabilities
+            .iter()
+            .filter_map(|inst| match inst.operands.first() {
+                Some(&mr::Operand::Capability(capability)) => Some(capability),
+                _ => None,
+            })
+            .collect();
+        let physical = module.memory_model.as_ref().is_some_and(|inst| {
+            matches!(inst.operands.first(),
+                     Some(&mr::Operand::AddressingModel(spirv::AddressingModel::Physical32)) |
+                     Some(&mr::Operand::AddressingModel(spirv::AddressingModel::Physical64)))
+        });
+        let pointers = module.types_global_values
+            .iter()
+            .filter(|inst| inst.class.opcode == spirv::Op::TypePointer)
+            .filter_map(|inst| match inst.operands.first() {
+                Some(&mr::Operand::StorageClass(class)) => Some((inst.result_id?, class)),
+                _ => None,
+            })
+            .collect();
+        Context {
+            capabilities,
+            physical,
+            pointers,
+        }
+    }
+
+    // VariablePointers implicitly declares VariablePointersStorageBuffer.
+    fn declared(&self, capability: spirv::Capability) -> bool {
+        self.capabilities.contains(&capability) ||
+        self.capabilities.contains(&spirv::Capability::VariablePointers)
+    }
+
+    fn flows(&self, f: &mr::Function) -> Vec<VariablePointer> {
+        let mut flows = vec![];
+        let function = match f.def.as_ref().and_then(|def| def.result_id) {
+            Some(id) => id,
+            None => return flows,
+        };
+        for inst in f.basic_blocks.iter().flat_map(|b| &b.instructions) {
+            let opcode = inst.class.opcode;
+            if !matches!(opcode,
+                         spirv::Op::Select |
+                         spirv::Op::Phi |
+                         spirv::Op::FunctionCall |
+                         spirv::Op::PtrAccessChain) {
+                continue;
+            }
+            let storage_class = match inst.result_type.and_then(|ty| self.pointers.get(&ty)) {
+                Some(&class) => class,
+                None => continue,
+            };
+            let required = match storage_class {
+                spirv::StorageClass::StorageBuffer => {
+                    Some(spirv::Capability::VariablePointersStorageBuffer)
+                }
+                spirv::StorageClass::Workgroup => Some(spirv::Capability::VariablePointers),
+                _ => None,
+            };
+            let covered = self.physical || required.is_some_and(|c| self.declared(c));
+            flows.push(VariablePointer {
+                function,
+                opcode,
+                result_id: inst.result_id.unwrap_or(0),
+                storage_class,
+                required,
+                covered,
+            });
+        }
+        flows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::variable_pointers;
+
+    #[test]
+    fn test_variable_pointers() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::VariablePointersStorageBuffer);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let boolean = b.type_bool();
+        let condition = b.constant_true(boolean);
+        let uint = b.type_int(32, 0);
+        let buffer = b.type_pointer(None, spirv::StorageClass::StorageBuffer, uint);
+        let shared = b.type_pointer(None, spirv::StorageClass::Workgroup, uint);
+        let a = b.variable(buffer, None, spirv::StorageClass::StorageBuffer, None);
+        let c = b.variable(buffer, None, spirv::StorageClass::StorageBuffer, None);
+        let d = b.variable(shared, None, spirv::StorageClass::Workgroup, None);
+        let e = b.variable(shared, None, spirv::StorageClass::Workgroup, None);
+        let fty = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let buffered = b.select(buffer, None, condition, a, c).unwrap();
+        let chosen = b.select(shared, None, condition, d, e).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let m = b.module();
+
+        let flows = variable_pointers(&m);
+        let summary: Vec<_> =
+            flows.iter().map(|flow| (flow.result_id, flow.required, flow.covered)).collect();
+        assert_eq!(summary,
+                   vec![(buffered, Some(spirv::Capability::VariablePointersStorageBuffer), true),
+                        (chosen, Some(spirv::Capability::VariablePointers), false)]);
+        assert!(flows.iter().all(|flow| flow.opcode == spirv::Op::Select));
+    }
+}
@@ -0,0 +1,256 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks the processing stages over a corpus of SPIR-V modules.
+//!
+//! The corpus consists of every `.spv` file under `benches/corpus/`, which
+//! holds a few real-world shaders (see the README there, also for fetching
+//! large corpora), any `.spv` file under the directories listed in the
+//! `RSPIRV_CORPUS` environment variable, and two modules synthesized with the
+//! builder, one of them large. Each module is parsed, disassembled, assembled,
+//! validated, and run through the size and performance pipelines, with one
+//! criterion group per stage.
+//!
+//! Run with `cargo bench -p rspirv --bench corpus`. Criterion filters on
+//! the group and module, e.g., `cargo bench -p rspirv --bench corpus --
+//! validate/synthetic`, and compares against the previous run.
+
+#[macro_use]
+extern crate criterion;
+extern crate rspirv;
+extern crate spirv_headers as spirv;
+
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput};
+
+use rspirv::binary::{Assemble, Disassemble};
+use rspirv::env::TargetEnv;
+use rspirv::mr;
+use rspirv::passes;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A benchmarked processing stage.
+///
+/// Stages are given the binary and a module freshly loaded from it, which
+/// they may edit.
+struct Stage {
+    name: &'static str,
+    run: fn(&[u32], &mut mr::Module),
+}
+
+fn stages() -> Vec<Stage> {
+    vec![
+        Stage {
+            name: "parse",
+            run: |code, _| drop(mr::load_words(code).unwrap()),
+        },
+        Stage {
+            name: "disassemble",
+            run: |_, module| drop(module.disassemble()),
+        },
+        Stage {
+            name: "assemble",
+            run: |_, module| drop(module.assemble()),
+        },
+        Stage {
+            name: "validate",
+            run: |_, module| drop(TargetEnv::Universal(1, 6).validate(module)),
+        },
+        Stage {
+            name: "size",
+            run: |_, module| drop(passes::size_pipeline().run(module)),
+        },
+        Stage {
+            name: "performance",
+            run: |_, module| drop(passes::performance_pipeline().run(module)),
+        },
+    ]
+}
+
+/// Collects the `.spv` files under `dir` recursively.
+fn collect(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        if path.is_dir() {
+            collect(&path, files);
+        } else if path.extension().is_some_and(|e| e == "spv") {
+            files.push(path);
+        }
+    }
+}
+
+fn to_words(bytes: &[u8]) -> Option<Vec<u32>> {
+    if !bytes.len().is_multiple_of(4) {
+        return None;
+    }
+    Some(bytes.chunks(4)
+        .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect())
+}
+
+/// A compute kernel summing a storage buffer in a loop.
+fn compute() -> Vec<u32> {
+    let mut b = mr::Builder::new();
+    b.capability(spirv::Capability::Shader);
+    b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+    let void = b.type_void();
+    let boolean = b.type_bool();
+    let uint = b.type_int(32, 0);
+    let array = b.type_runtime_array(uint);
+    let block = b.type_struct(vec![array]);
+    b.decorate(block, spirv::Decoration::BufferBlock, vec![]);
+    b.member_decorate(block, 0, spirv::Decoration::Offset, vec![mr::Operand::LiteralInt32(0)]);
+    b.decorate(array, spirv::Decoration::ArrayStride, vec![mr::Operand::LiteralInt32(4)]);
+    let block_ptr = b.type_pointer(None, spirv::StorageClass::Uniform, block);
+    let uint_ptr = b.type_pointer(None, spirv::StorageClass::Uniform, uint);
+    let buffer = b.variable(block_ptr, None, spirv::StorageClass::Uniform, None);
+    let zero = b.constant_u32(uint, 0);
+    let one = b.constant_u32(uint, 1);
+    let count = b.constant_u32(uint, 64);
+    let voidf = b.type_function(void, vec![]);
+    let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+    b.entry_point(spirv::ExecutionModel::GLCompute, f, "main", vec![]);
+    b.execution_mode(f, spirv::ExecutionMode::LocalSize, vec![64, 1, 1]);
+    b.begin_basic_block(None).unwrap();
+    let uint_fn_ptr = b.type_pointer(None, spirv::StorageClass::Function, uint);
+    let i = b.variable(uint_fn_ptr, None, spirv::StorageClass::Function, Some(zero));
+    let sum = b.variable(uint_fn_ptr, None, spirv::StorageClass::Function, Some(zero));
+    let header = b.id();
+    let body = b.id();
+    let cont = b.id();
+    let merge = b.id();
+    b.branch(header).unwrap();
+    b.begin_basic_block(Some(header)).unwrap();
+    b.loop_merge(merge, cont, spirv::LoopControl::NONE, vec![]).unwrap();
+    let index = b.load(uint, None, i, None, vec![]).unwrap();
+    let cond = b.uless_than(boolean, None, index, count).unwrap();
+    b.branch_conditional(cond, body, merge, vec![]).unwrap();
+    b.begin_basic_block(Some(body)).unwrap();
+    let ptr = b.access_chain(uint_ptr, None, buffer, vec![zero, index]).unwrap();
+    let value = b.load(uint, None, ptr, None, vec![]).unwrap();
+    let total = b.load(uint, None, sum, None, vec![]).unwrap();
+    let total = b.iadd(uint, None, total, value).unwrap();
+    b.store(sum, total, None, vec![]).unwrap();
+    b.branch(cont).unwrap();
+    b.begin_basic_block(Some(cont)).unwrap();
+    let next = b.iadd(uint, None, index, one).unwrap();
+    b.store(i, next, None, vec![]).unwrap();
+    b.branch(header).unwrap();
+    b.begin_basic_block(Some(merge)).unwrap();
+    let out = b.access_chain(uint_ptr, None, buffer, vec![zero, zero]).unwrap();
+    let total = b.load(uint, None, sum, None, vec![]).unwrap();
+    b.store(out, total, None, vec![]).unwrap();
+    b.ret().unwrap();
+    b.end_function().unwrap();
+    b.module().assemble()
+}
+
+/// A vertex shader passing through positions, repeated in many functions to
+/// resemble a large linked module.
+fn large(functions: u32) -> Vec<u32> {
+    let mut b = mr::Builder::new();
+    b.capability(spirv::Capability::Shader);
+    b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+    let void = b.type_void();
+    let float = b.type_float(32);
+    let vec4 = b.type_vector(float, 4);
+    let input = b.type_pointer(None, spirv::StorageClass::Input, vec4);
+    let output = b.type_pointer(None, spirv::StorageClass::Output, vec4);
+    let position = b.variable(input, None, spirv::StorageClass::Input, None);
+    let result = b.variable(output, None, spirv::StorageClass::Output, None);
+    b.decorate(position, spirv::Decoration::Location, vec![mr::Operand::LiteralInt32(0)]);
+    b.decorate(result, spirv::Decoration::BuiltIn,
+               vec![mr::Operand::BuiltIn(spirv::BuiltIn::Position)]);
+    let scale = b.constant_f32(float, 0.5);
+    let voidf = b.type_function(void, vec![]);
+    let mut callees = vec![];
+    for i in 0..functions {
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.name(f, format!("transform_{}", i));
+        b.begin_basic_block(None).unwrap();
+        let v = b.load(vec4, None, position, None, vec![]).unwrap();
+        let v = b.vector_times_scalar(vec4, None, v, scale).unwrap();
+        b.store(result, v, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        callees.push(f);
+    }
+    let main = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+    b.entry_point(spirv::ExecutionModel::Vertex, main, "main", vec![position, result]);
+    b.begin_basic_block(None).unwrap();
+    for f in callees {
+        b.function_call(void, None, f, vec![]).unwrap();
+    }
+    b.ret().unwrap();
+    b.end_function().unwrap();
+    b.module().assemble()
+}
+
+/// Gathers the modules of the corpus, skipping those that do not load.
+fn corpus() -> Vec<(String, Vec<u32>)> {
+    let mut corpus = vec![
+        ("synthetic/compute".to_string(), compute()),
+        ("synthetic/large".to_string(), large(5_000)),
+    ];
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("benches/corpus");
+    let mut files = vec![];
+    collect(&root, &mut files);
+    if let Some(dirs) = env::var_os("RSPIRV_CORPUS") {
+        for dir in env::split_paths(&dirs) {
+            collect(&dir, &mut files);
+        }
+    }
+    files.sort();
+    for file in files {
+        let name = file.strip_prefix(&root).unwrap_or(&file).display().to_string();
+        match fs::read(&file).ok().as_deref().and_then(to_words) {
+            Some(code) => corpus.push((name, code)),
+            None => eprintln!("skipping unreadable {}", name),
+        }
+    }
+    corpus.retain(|(name, code)| match mr::load_words(code) {
+        Ok(_) => true,
+        Err(err) => {
+            eprintln!("skipping {}: {}", name, err);
+            false
+        }
+    });
+    corpus
+}
+
+fn bench_stages(c: &mut Criterion) {
+    let corpus = corpus();
+    for stage in stages() {
+        let mut group = c.benchmark_group(stage.name);
+        for (name, code) in &corpus {
+            group.throughput(Throughput::Bytes(code.len() as u64 * 4));
+            group.bench_with_input(BenchmarkId::from_parameter(name), code, |b, code| {
+                // Each run gets a freshly loaded module, loaded untimed.
+                b.iter_batched(|| mr::load_words(code).unwrap(),
+                               |mut module| (stage.run)(code, &mut module),
+                               BatchSize::LargeInput)
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_stages);
+criterion_main!(benches);
@@ -0,0 +1,49 @@
+#!/bin/sh
+# Copyright 2017 Google Inc.
+#
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+#
+#      http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+
+# Collects the SPIR-V binaries in a git repository, and its licence files,
+# into large/. Without arguments, collects the default large corpus.
+#
+# Usage: fetch.sh [<git-repository-url> [<revision>]]
+
+set -e
+
+if [ -z "$1" ]; then
+    set -- https://github.com/SaschaWillems/Vulkan.git
+fi
+
+dir=$(cd "$(dirname "$0")" && pwd)
+name=$(basename "$1" .git)
+tmp=$(mktemp -d)
+trap 'rm -rf "$tmp"' EXIT
+
+if [ -n "$2" ]; then
+    git clone --quiet "$1" "$tmp/repo"
+    git -C "$tmp/repo" checkout --quiet "$2"
+else
+    git clone --quiet --depth 1 "$1" "$tmp/repo"
+fi
+
+mkdir -p "$dir/large/$name"
+find "$tmp/repo" -name '*.spv' -type f | while read -r file; do
+    target="$dir/large/$name/$(echo "${file#$tmp/repo/}" | tr '/' '_')"
+    cp "$file" "$target"
+done
+for file in "$tmp"/repo/LICENSE* "$tmp"/repo/COPYING*; do
+    if [ -f "$file" ]; then
+        cp "$file" "$dir/large/$name/"
+    fi
+done
+echo "collected $(ls "$dir/large/$name" | wc -l) modules into $dir/large/$name"
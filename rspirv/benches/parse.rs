@@ -0,0 +1,102 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compares loading a large module into a `Module` and into an `Arena`.
+//!
+//! Reports the wall time, the number of allocations made while parsing, and
+//! the number of allocations still alive afterwards, and checks that parsing
+//! into the arena makes an order of magnitude fewer allocations in total, and
+//! keeps fewer alive. Run with `cargo bench -p rspirv --bench parse`.
+
+extern crate rspirv;
+extern crate spirv_headers as spirv;
+
+use rspirv::binary::Assemble;
+use rspirv::mr;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+struct Counting;
+
+static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        LIVE.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(1, Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Builds a module with `count` small functions.
+fn build(count: u32) -> Vec<u32> {
+    let mut b = mr::Builder::new();
+    b.capability(spirv::Capability::Shader);
+    b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+    let void = b.type_void();
+    let uint = b.type_int(32, 0);
+    let ptr = b.type_pointer(None, spirv::StorageClass::Function, uint);
+    let one = b.constant_u32(uint, 1);
+    let voidf = b.type_function(void, vec![]);
+    for i in 0..count {
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.name(f, format!("function_{}", i % 16));
+        b.begin_basic_block(None).unwrap();
+        let var = b.variable(ptr, None, spirv::StorageClass::Function, None);
+        let mut value = b.load(uint, None, var, None, vec![]).unwrap();
+        for _ in 0..8 {
+            value = b.iadd(uint, None, value, one).unwrap();
+        }
+        b.store(var, value, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+    }
+    b.module().assemble()
+}
+
+/// Runs `f`, printing its time and allocations, and returns the number of
+/// allocations made and left alive.
+fn measure<T, F: FnOnce() -> T>(name: &str, f: F) -> (usize, usize) {
+    let allocs = ALLOCS.load(Ordering::Relaxed);
+    let live = LIVE.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    let allocs = ALLOCS.load(Ordering::Relaxed) - allocs;
+    let live = LIVE.load(Ordering::Relaxed) - live;
+    println!("{:8} {:>10.3?} {:>10} allocations {:>10} alive", name, elapsed, allocs, live);
+    drop(result);
+    (allocs, live)
+}
+
+fn main() {
+    let code = build(20_000);
+    println!("module of {} words", code.len());
+    let module = measure("module", || mr::load_words(&code).unwrap());
+    let arena = measure("arena", || mr::Arena::from_words(&code).unwrap());
+    assert!(arena.0 * 10 <= module.0,
+            "parsing into the arena allocates more than a tenth of loading the module");
+    assert!(arena.1 < module.1, "the arena keeps more allocations alive than the module");
+}
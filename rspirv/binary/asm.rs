@@ -0,0 +1,1041 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grammar;
+use mr;
+use prelude::*;
+use spirv;
+
+use grammar::CoreInstructionTable as GInstTable;
+use grammar::OperandKind as GOpKind;
+use grammar::OperandQuantifier as GOpCount;
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+use std::{error, fmt, result};
+use super::assemble::{assemble_str, Assemble};
+use super::tracker::Type;
+
+include!("asm_operand.rs");
+
+/// Text assembler errors.
+///
+/// The first element of each is the line, counting from 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The opcode is no core instruction.
+    UnknownOpcode(usize, String),
+    /// The token is no enumerant or extended instruction of the kind
+    /// expected.
+    UnknownEnumerant(usize, String),
+    /// The token does not fit the kind of operand expected.
+    InvalidOperand(usize, String),
+    /// The instruction ends before all its operands are given.
+    MissingOperand(usize),
+    /// The token follows the last operand of the instruction.
+    ExtraOperand(usize, String),
+    /// A literal string has no closing quote.
+    UnterminatedString(usize),
+    /// A result id is given to an instruction without one, or missing for
+    /// an instruction with one.
+    ResultIdMismatch(usize),
+    /// The placeholder has no binding.
+    UnboundPlaceholder(usize, String),
+}
+
+impl Error {
+    /// Gives an descriptive string for each error.
+    ///
+    /// This method is intended to be used by fmt::Display and error::Error to
+    /// avoid duplication in implementation. So it's private.
+    fn describe(&self) -> &str {
+        match *self {
+            Error::UnknownOpcode(..) => "unknown opcode",
+            Error::UnknownEnumerant(..) => "unknown enumerant",
+            Error::InvalidOperand(..) => "invalid operand",
+            Error::MissingOperand(_) => "missing operand",
+            Error::ExtraOperand(..) => "extra operand",
+            Error::UnterminatedString(_) => "unterminated string",
+            Error::ResultIdMismatch(_) => "result id mismatch",
+            Error::UnboundPlaceholder(..) => "unbound placeholder",
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        self.describe()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::UnknownOpcode(line, ref token) |
+            Error::UnknownEnumerant(line, ref token) |
+            Error::InvalidOperand(line, ref token) |
+            Error::ExtraOperand(line, ref token) |
+            Error::UnboundPlaceholder(line, ref token) => {
+                write!(f, "{} {} on line {}", self.describe(), token, line)
+            }
+            Error::MissingOperand(line) |
+            Error::UnterminatedString(line) |
+            Error::ResultIdMismatch(line) => write!(f, "{} on line {}", self.describe(), line),
+        }
+    }
+}
+
+pub type Result<T> = result::Result<T, Error>;
+
+/// Options for [`assemble_text_with`](fn.assemble_text_with.html).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AsmOptions {
+    /// Whether numeric ids like `%5` keep their number. Named ids are then
+    /// given the lowest numbers left free. Otherwise all ids are numbered
+    /// in the order they first appear.
+    pub preserve_numeric_ids: bool,
+}
+
+/// Assembles SPIR-V text into a binary module, numbering ids in the order
+/// they first appear.
+///
+/// See [`assemble_text_with`](fn.assemble_text_with.html) for the syntax.
+pub fn assemble_text(text: &str) -> Result<Vec<Word>> {
+    assemble_text_with(text, &AsmOptions::default())
+}
+
+/// Assembles SPIR-V text in the syntax of the disassembler, and of
+/// `spirv-as`, into a binary module.
+///
+/// Each line holds at most one instruction: an optional `%result =`, the
+/// opcode with its `Op` prefix, then the operands, separated by spaces.
+/// Ids are written `%` followed by a number or a name, which can be mixed
+/// in one text. Enumerants are written by their names, bit masks as names
+/// joined by `|`, and literal strings in double quotes with `\` escapes.
+/// Context dependent numbers are encoded by the width of their type, which
+/// they must fit; floating point numbers can also be written in hexadecimal,
+/// like `0x1.8p1` in C99.
+/// `;` starts a comment running to the end of the line.
+///
+/// `!` followed by an integer is a raw word, emitted as is in place of any
+/// one operand, e.g., to write values unknown to the grammar. In place of
+/// the opcode, it is the first word of an instruction whose operands are
+/// encoded without a grammar; ids, integers, floats, strings, and raw
+/// words are allowed there.
+///
+/// The module header takes the current SPIR-V version, and the bound
+/// follows the highest id.
+pub fn assemble_text_with(text: &str, options: &AsmOptions) -> Result<Vec<Word>> {
+    assemble(text, options, &[])
+}
+
+/// A value substituted for a placeholder by
+/// [`assemble_with`](fn.assemble_with.html).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Binding {
+    /// An id, kept as is. Ids of the template are numbered around it.
+    Id(Word),
+    /// An integer, encoded like an integer of the template.
+    Int(i64),
+    /// A floating point number, encoded like one of the template.
+    Float(f64),
+    /// A literal string.
+    Str(String),
+}
+
+/// Assembles the SPIR-V text `template`, substituting `bindings` for the
+/// placeholders written `${name}` in it.
+///
+/// A placeholder stands for exactly one operand, or the result id, and is
+/// substituted after the text is split into tokens, so that no binding can
+/// change how the rest of the text is read; placeholders within literal
+/// strings are left alone. It is an error for a placeholder to have no
+/// binding. Ids of the template are numbered in the order they first
+/// appear, skipping the bound ones.
+///
+/// See [`assemble_text_with`](fn.assemble_text_with.html) for the syntax.
+pub fn assemble_with(template: &str, bindings: &[(&str, Binding)]) -> Result<Vec<Word>> {
+    assemble(template, &AsmOptions::default(), bindings)
+}
+
+fn assemble(text: &str, options: &AsmOptions, bindings: &[(&str, Binding)]) -> Result<Vec<Word>> {
+    let mut lines = vec![];
+    for (index, line) in text.lines().enumerate() {
+        let mut tokens = tokenize(index + 1, line)?;
+        for token in &mut tokens {
+            if let Token::Placeholder(ref name) = *token {
+                let binding = bindings.iter()
+                                      .find(|&&(bound, _)| bound == name)
+                                      .map(|(_, binding)| binding)
+                                      .ok_or_else(|| {
+                                          Error::UnboundPlaceholder(index + 1, name.clone())
+                                      })?;
+                *token = match *binding {
+                    Binding::Id(id) => Token::Fixed(id),
+                    Binding::Int(value) => Token::Bare(value.to_string()),
+                    Binding::Float(value) => Token::Bare(format!("{:?}", value)),
+                    Binding::Str(ref s) => Token::Str(s.clone()),
+                };
+            }
+        }
+        if !tokens.is_empty() {
+            lines.push((index + 1, tokens));
+        }
+    }
+    let ids = number_ids(&lines, options.preserve_numeric_ids);
+    let fixed = lines.iter().flat_map(|(_, tokens)| tokens).filter_map(|token| match *token {
+        Token::Fixed(id) => Some(id),
+        _ => None,
+    });
+    let bound = ids.values().cloned().chain(fixed).max().unwrap_or(0) + 1;
+
+    let mut words = mr::ModuleHeader::new(bound).assemble();
+    let mut context = Context::default();
+    for (line, tokens) in &lines {
+        context.instruction(&ids, *line, tokens, &mut words)?;
+    }
+    Ok(words)
+}
+
+/// A token of SPIR-V assembly.
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// An id, without its `%`.
+    Id(String),
+    /// An id bound to a placeholder.
+    Fixed(Word),
+    /// A placeholder, without its `${` and `}`.
+    Placeholder(String),
+    /// A raw word, written with `!`.
+    Raw(Word),
+    /// A literal string, unescaped.
+    Str(String),
+    /// Anything else: an opcode, a number, an enumerant, or `=`.
+    Bare(String),
+}
+
+/// Splits `text`, the `line`-th line, into tokens.
+fn tokenize(line: usize, text: &str) -> Result<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        if c == ';' {
+            break;
+        }
+        if c == '"' {
+            chars.next();
+            let mut s = String::new();
+            loop {
+                match chars.next() {
+                    None => return Err(Error::UnterminatedString(line)),
+                    Some('"') => break,
+                    Some('\\') => {
+                        match chars.next() {
+                            None => return Err(Error::UnterminatedString(line)),
+                            Some('n') => s.push('\n'),
+                            Some('t') => s.push('\t'),
+                            Some('r') => s.push('\r'),
+                            Some('0') => s.push('\0'),
+                            Some(other) => s.push(other),
+                        }
+                    }
+                    Some(other) => s.push(other),
+                }
+            }
+            tokens.push(Token::Str(s));
+            continue;
+        }
+        if c == '=' {
+            chars.next();
+            tokens.push(Token::Bare("=".to_owned()));
+            continue;
+        }
+        let mut word = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() || c == ';' || c == '"' || c == '=' {
+                break;
+            }
+            word.push(c);
+            chars.next();
+        }
+        tokens.push(if let Some(name) = word.strip_prefix('%') {
+            Token::Id(name.to_owned())
+        } else if let Some(name) = word.strip_prefix("${").and_then(|w| w.strip_suffix('}')) {
+            Token::Placeholder(name.to_owned())
+        } else if let Some(value) = word.strip_prefix('!') {
+            match parse_int(value) {
+                Some(value) if value <= u64::from(u32::MAX) => Token::Raw(value as Word),
+                _ => return Err(Error::InvalidOperand(line, word)),
+            }
+        } else {
+            Token::Bare(word)
+        });
+    }
+    Ok(tokens)
+}
+
+/// Numbers the ids used in `lines`.
+fn number_ids(lines: &[(usize, Vec<Token>)], preserve_numeric: bool) -> BTreeMap<String, Word> {
+    let mut ids = BTreeMap::new();
+    let names = lines.iter().flat_map(|(_, tokens)| tokens).filter_map(|token| match *token {
+        Token::Id(ref name) => Some(name),
+        _ => None,
+    });
+    // Only digits make a number: `%+5` names another id than `%5`.
+    let numeric = |name: &str| {
+        if name.bytes().all(|b| b.is_ascii_digit()) {
+            name.parse::<Word>().ok().filter(|&id| id != 0)
+        } else {
+            None
+        }
+    };
+    let mut reserved: BTreeSet<Word> = lines.iter()
+        .flat_map(|(_, tokens)| tokens)
+        .filter_map(|token| match *token {
+            Token::Fixed(id) => Some(id),
+            _ => None,
+        })
+        .collect();
+    if preserve_numeric {
+        reserved.extend(names.clone().filter_map(|name| numeric(name)));
+    }
+    let mut next = 1;
+    for name in names {
+        if ids.contains_key(name) {
+            continue;
+        }
+        let id = match numeric(name) {
+            Some(id) if preserve_numeric => id,
+            _ => {
+                while reserved.contains(&next) {
+                    next += 1;
+                }
+                next += 1;
+                next - 1
+            }
+        };
+        ids.insert(name.clone(), id);
+    }
+    ids
+}
+
+/// Parses an integer, decimal or hexadecimal with `0x`, into its two's
+/// complement bits.
+fn parse_int(s: &str) -> Option<u64> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s),
+    };
+    let value = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok()?,
+        None => digits.parse::<u64>().ok()?,
+    };
+    if negative {
+        if value > 1 << 63 {
+            return None;
+        }
+        Some(value.wrapping_neg())
+    } else {
+        Some(value)
+    }
+}
+
+/// Gives the bits of the integer `text` in an integer type of `width` bits,
+/// if it fits. Negative numbers only fit signed types, and hexadecimal ones
+/// give the bits of signed types, like `0xff` for -1 in 8 bits.
+fn int_bits(text: &str, width: u32, signed: bool) -> Option<u64> {
+    let value = parse_int(text)?;
+    let max = u64::MAX >> (64 - width);
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+    if negative {
+        // The two's complement of the largest magnitude is `!(max >> 1)`.
+        match value {
+            0 => Some(0),
+            _ if signed && value >= !(max >> 1) => Some(value & max),
+            _ => None,
+        }
+    } else {
+        let hex = digits.starts_with("0x") || digits.starts_with("0X");
+        let limit = if signed && !hex { max >> 1 } else { max };
+        if value <= limit { Some(value) } else { None }
+    }
+}
+
+/// Gives the bits of the number `text` in a floating point type of `width`
+/// bits, 16, 32 or 64. Hexadecimal numbers are read like C99 hexadecimal
+/// floating point literals, e.g., `0x1.8p1` for 3, and must be exact up to
+/// rounding to nearest even; decimal ones are rounded.
+fn float_bits(text: &str, width: u32) -> Option<u64> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+    if let Some(hex) = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        let (significand, exponent) = parse_hex_float(hex)?;
+        return encode_float(negative, significand, exponent, width);
+    }
+    match width {
+        16 => {
+            let value = text.parse::<f64>().ok()?;
+            let sign = if value.is_sign_negative() { 0x8000 } else { 0 };
+            if value.is_nan() {
+                Some(0x7e00)
+            } else if value.is_infinite() {
+                Some(sign | 0x7c00)
+            } else {
+                let bits = value.to_bits();
+                let exponent = ((bits >> 52) & 0x7ff) as i32;
+                let fraction = bits & ((1 << 52) - 1);
+                let (significand, exponent) = match exponent {
+                    0 => (fraction, -1074),
+                    _ => (fraction | 1 << 52, exponent - 1075),
+                };
+                encode_float(value.is_sign_negative(), significand, exponent, 16)
+            }
+        }
+        32 => text.parse::<f32>().ok().map(|value| u64::from(value.to_bits())),
+        64 => text.parse::<f64>().ok().map(f64::to_bits),
+        _ => None,
+    }
+}
+
+/// Parses the digits of a hexadecimal floating point number after its
+/// `0x`, into a significand and a binary exponent.
+///
+/// Digits beyond the precision of the significand are only remembered in
+/// its lowest bit, which is enough for rounding.
+fn parse_hex_float(s: &str) -> Option<(u64, i32)> {
+    let (digits, exponent) = match s.find(['p', 'P']) {
+        Some(index) => (&s[..index], s[index + 1..].parse::<i32>().ok()?),
+        None => (s, 0),
+    };
+    let (integer, fraction) = match digits.find('.') {
+        Some(index) => (&digits[..index], &digits[index + 1..]),
+        None => (digits, ""),
+    };
+    if integer.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    let (mut significand, mut exponent) = (0u64, i64::from(exponent));
+    for (index, c) in integer.chars().chain(fraction.chars()).enumerate() {
+        let digit = u64::from(c.to_digit(16)?);
+        if significand >> 60 == 0 {
+            significand = significand << 4 | digit;
+            if index >= integer.len() {
+                exponent -= 4;
+            }
+        } else {
+            significand |= (digit != 0) as u64;
+            if index < integer.len() {
+                exponent += 4;
+            }
+        }
+    }
+    let exponent = exponent.max(i64::from(i32::MIN / 2)).min(i64::from(i32::MAX / 2));
+    Some((significand, exponent as i32))
+}
+
+/// Encodes `significand * 2^exponent`, negated if `negative`, in a floating
+/// point type of `width` bits, rounding to nearest even. Numbers too large
+/// for the type do not fit.
+fn encode_float(negative: bool, significand: u64, exponent: i32, width: u32) -> Option<u64> {
+    let (fraction_bits, exponent_bits) = match width {
+        16 => (10, 5),
+        32 => (23, 8),
+        64 => (52, 11),
+        _ => return None,
+    };
+    let sign = (negative as u64) << (width - 1);
+    if significand == 0 {
+        return Some(sign);
+    }
+    // Moves the leading one to the top bit.
+    let zeros = significand.leading_zeros();
+    let significand = significand << zeros;
+    let bias = (1 << (exponent_bits - 1)) - 1;
+    let biased = exponent - zeros as i32 + 63 + bias;
+    // Normal numbers keep their leading one, and the fraction bits.
+    let shift = (63 - fraction_bits + (1 - biased).max(0)) as u32;
+    let mut kept = if shift < 64 { significand >> shift } else { 0 };
+    if shift <= 64 {
+        let half: u64 = 1 << (shift - 1);
+        let rest = significand & (half << 1).wrapping_sub(1);
+        if rest > half || rest == half && kept & 1 == 1 {
+            kept += 1;
+        }
+    }
+    // The leading one of normal numbers overlaps the exponent, raising it
+    // by one; subnormal ones have none, rounding up to the smallest normal.
+    let bits = if biased > 0 {
+        ((biased as u64 - 1) << fraction_bits) + kept
+    } else {
+        kept
+    };
+    if bits >> fraction_bits >= (1 << exponent_bits) - 1 {
+        return None;
+    }
+    Some(sign | bits)
+}
+
+/// What the assembler knows of the instructions assembled so far.
+#[derive(Debug, Default)]
+struct Context {
+    /// The numeric types of ids, both types and values.
+    types: BTreeMap<Word, Type>,
+    /// The extended instruction sets imported.
+    sets: BTreeMap<Word, grammar::ExtInstSet>,
+}
+
+/// The operands of one instruction being assembled.
+struct Operands<'a> {
+    ids: &'a BTreeMap<String, Word>,
+    line: usize,
+    tokens: &'a [Token],
+    next: usize,
+    result_type: Option<Word>,
+    /// The first `IdRef` operand, e.g., the selector of `OpSwitch` or the
+    /// set of `OpExtInst`.
+    first_id: Option<Word>,
+    words: Vec<Word>,
+}
+
+impl Context {
+    /// Assembles the instruction on `line` into `words`.
+    fn instruction(&mut self,
+                   ids: &BTreeMap<String, Word>,
+                   line: usize,
+                   tokens: &[Token],
+                   words: &mut Vec<Word>)
+                   -> Result<()> {
+        let (result_id, tokens) = match tokens {
+            [Token::Id(name), Token::Bare(eq), rest @ ..] if eq == "=" => (Some(ids[name]), rest),
+            [Token::Fixed(id), Token::Bare(eq), rest @ ..] if eq == "=" => (Some(*id), rest),
+            _ => (None, tokens),
+        };
+        let mut operands = Operands {
+            ids,
+            line,
+            tokens,
+            next: 1,
+            result_type: None,
+            first_id: None,
+            words: vec![],
+        };
+        let opname = match tokens.first() {
+            Some(Token::Raw(first)) => {
+                if result_id.is_some() {
+                    return Err(Error::ResultIdMismatch(line));
+                }
+                operands.words.push(*first);
+                while operands.next < tokens.len() {
+                    operands.untyped()?;
+                }
+                words.extend(operands.words);
+                return Ok(());
+            }
+            Some(Token::Bare(opname)) => opname,
+            Some(other) => return Err(Error::UnknownOpcode(line, describe(other))),
+            None => return Err(Error::ResultIdMismatch(line)),
+        };
+        let grammar = opname.strip_prefix("Op")
+                            .and_then(GInstTable::lookup_opname)
+                            .ok_or_else(|| Error::UnknownOpcode(line, opname.clone()))?;
+
+        let mut has_result = false;
+        'operands: for operand in grammar.operands {
+            match operand.kind {
+                GOpKind::IdResult => {
+                    let id = result_id.ok_or(Error::ResultIdMismatch(line))?;
+                    operands.words.push(id);
+                    has_result = true;
+                    continue;
+                }
+                GOpKind::LiteralExtInstInteger => {
+                    self.ext_inst(&mut operands)?;
+                    break 'operands;
+                }
+                GOpKind::LiteralSpecConstantOpInteger => {
+                    operands.spec_constant_op()?;
+                    break 'operands;
+                }
+                _ => (),
+            }
+            match operand.quantifier {
+                GOpCount::One => operands.operand(self, operand.kind)?,
+                GOpCount::ZeroOrOne => {
+                    if !operands.done() {
+                        operands.operand(self, operand.kind)?;
+                    }
+                }
+                GOpCount::ZeroOrMore => {
+                    while !operands.done() {
+                        operands.operand(self, operand.kind)?;
+                    }
+                }
+            }
+        }
+        if let Some(extra) = tokens.get(operands.next) {
+            return Err(Error::ExtraOperand(line, describe(extra)));
+        }
+        if result_id.is_some() && !has_result {
+            return Err(Error::ResultIdMismatch(line));
+        }
+        self.track(grammar.opcode, result_id, operands.result_type, &operands.words);
+
+        words.push(((operands.words.len() as Word + 1) << 16) | grammar.opcode as Word);
+        words.extend(operands.words);
+        Ok(())
+    }
+
+    /// Encodes the extended instruction of `OpExtInst` and its operands.
+    fn ext_inst(&self, operands: &mut Operands) -> Result<()> {
+        let line = operands.line;
+        let set = operands.first_id.and_then(|id| self.sets.get(&id).cloned());
+        match operands.take()? {
+            Token::Raw(word) => operands.words.push(*word),
+            Token::Bare(name) => {
+                let opcode = match (parse_int(name), set) {
+                    (Some(value), _) if value <= u64::from(u32::MAX) => Some(value as Word),
+                    (None, Some(set)) => set.lookup_opname(name).map(|inst| inst.opcode),
+                    _ => None,
+                };
+                let opcode = opcode.ok_or_else(|| Error::UnknownEnumerant(line, name.clone()))?;
+                operands.words.push(opcode);
+            }
+            other => return Err(Error::InvalidOperand(line, describe(other))),
+        }
+        while !operands.done() {
+            operands.untyped()?;
+        }
+        Ok(())
+    }
+
+    /// Records the types and imports `words`, the operands of an
+    /// instruction, declare.
+    fn track(&mut self,
+             opcode: spirv::Op,
+             result_id: Option<Word>,
+             result_type: Option<Word>,
+             words: &[Word]) {
+        let id = match result_id {
+            Some(id) => id,
+            None => return,
+        };
+        match (opcode, words) {
+            (spirv::Op::TypeInt, &[_, width, signed]) => {
+                self.types.insert(id, Type::Integer(width, signed == 1));
+            }
+            (spirv::Op::TypeFloat, &[_, width, ..]) => {
+                self.types.insert(id, Type::Float(width));
+            }
+            (spirv::Op::ExtInstImport, _) => {
+                let bytes: Vec<u8> = words[1..]
+                    .iter()
+                    .flat_map(|word| word.to_le_bytes().to_vec())
+                    .take_while(|&byte| byte != 0)
+                    .collect();
+                let set = String::from_utf8(bytes)
+                    .ok()
+                    .and_then(|name| grammar::ExtInstSet::from_name(&name));
+                if let Some(set) = set {
+                    self.sets.insert(id, set);
+                }
+            }
+            _ => {
+                if let Some(ty) = result_type.and_then(|ty| self.types.get(&ty).cloned()) {
+                    self.types.insert(id, ty);
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Operands<'a> {
+    fn done(&self) -> bool {
+        self.next >= self.tokens.len()
+    }
+
+    fn take(&mut self) -> Result<&'a Token> {
+        let token = self.tokens.get(self.next).ok_or(Error::MissingOperand(self.line))?;
+        self.next += 1;
+        Ok(token)
+    }
+
+    fn invalid(&self, token: &Token) -> Error {
+        Error::InvalidOperand(self.line, describe(token))
+    }
+
+    /// Encodes a literal string, which cannot hold an embedded null.
+    fn string(&self, token: &Token, s: &str) -> Result<Vec<Word>> {
+        if s.contains('\0') {
+            Err(self.invalid(token))
+        } else {
+            Ok(assemble_str(s))
+        }
+    }
+
+    fn id(&self, name: &str) -> Word {
+        self.ids[name]
+    }
+
+    /// Encodes the next operand, of the given `kind`.
+    fn operand(&mut self, context: &Context, kind: GOpKind) -> Result<()> {
+        let token = self.take()?;
+        if let Token::Raw(word) = *token {
+            self.words.push(word);
+            return Ok(());
+        }
+        match kind {
+            GOpKind::IdResultType | GOpKind::IdRef | GOpKind::IdScope |
+            GOpKind::IdMemorySemantics => {
+                let id = match *token {
+                    Token::Id(ref name) => self.id(name),
+                    Token::Fixed(id) => id,
+                    _ => return Err(self.invalid(token)),
+                };
+                if kind == GOpKind::IdResultType {
+                    self.result_type = Some(id);
+                } else if kind == GOpKind::IdRef && self.first_id.is_none() {
+                    self.first_id = Some(id);
+                }
+                self.words.push(id);
+            }
+            GOpKind::IdResult => return Err(self.invalid(token)),
+            GOpKind::LiteralString => {
+                match *token {
+                    Token::Str(ref s) => {
+                        let words = self.string(token, s)?;
+                        self.words.extend(words)
+                    }
+                    _ => return Err(self.invalid(token)),
+                }
+            }
+            GOpKind::LiteralInteger | GOpKind::LiteralExtInstInteger |
+            GOpKind::LiteralSpecConstantOpInteger => {
+                let word = self.int32(token)?;
+                self.words.push(word);
+            }
+            GOpKind::LiteralContextDependentNumber => {
+                let ty = self.result_type.and_then(|ty| context.types.get(&ty).cloned());
+                self.number(token, ty)?;
+            }
+            GOpKind::PairLiteralIntegerIdRef => {
+                let ty = self.first_id.and_then(|id| context.types.get(&id).cloned());
+                self.number(token, ty)?;
+                self.operand(context, GOpKind::IdRef)?;
+            }
+            GOpKind::PairIdRefLiteralInteger => {
+                self.next -= 1;
+                self.operand(context, GOpKind::IdRef)?;
+                self.operand(context, GOpKind::LiteralInteger)?;
+            }
+            GOpKind::PairIdRefIdRef => {
+                self.next -= 1;
+                self.operand(context, GOpKind::IdRef)?;
+                self.operand(context, GOpKind::IdRef)?;
+            }
+            _ => {
+                let name = match *token {
+                    Token::Bare(ref name) => name,
+                    _ => return Err(self.invalid(token)),
+                };
+                if let Some(value) = parse_int(name) {
+                    if value > u64::from(u32::MAX) {
+                        return Err(self.invalid(token));
+                    }
+                    self.words.push(value as Word);
+                    return Ok(());
+                }
+                let mut value = 0;
+                let mut parameters = vec![];
+                for bit in name.split('|') {
+                    let (bit, params) = enumerant(kind, bit)
+                        .ok_or_else(|| Error::UnknownEnumerant(self.line, name.clone()))?;
+                    value |= bit;
+                    parameters.push((bit, params));
+                }
+                // Parameters of bit masks follow in the order of their bits.
+                parameters.sort_by_key(|&(bit, _)| bit);
+                self.words.push(value);
+                for &(_, params) in &parameters {
+                    for &param in params {
+                        self.operand(context, param)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Encodes the opcode of `OpSpecConstantOp` and its operands.
+    fn spec_constant_op(&mut self) -> Result<()> {
+        let token = self.take()?;
+        let opcode = match *token {
+            Token::Raw(word) => word,
+            Token::Bare(ref name) => {
+                match GInstTable::lookup_opname(name) {
+                    Some(inst) => inst.opcode as Word,
+                    None => self.int32(token)?,
+                }
+            }
+            _ => return Err(self.invalid(token)),
+        };
+        self.words.push(opcode);
+        while !self.done() {
+            self.untyped()?;
+        }
+        Ok(())
+    }
+
+    /// Encodes the next operand without knowing its kind.
+    fn untyped(&mut self) -> Result<()> {
+        let token = self.take()?;
+        match *token {
+            Token::Id(ref name) => {
+                let id = self.id(name);
+                self.words.push(id);
+            }
+            Token::Fixed(id) => self.words.push(id),
+            Token::Placeholder(_) => return Err(self.invalid(token)),
+            Token::Raw(word) => self.words.push(word),
+            Token::Str(ref s) => {
+                let words = self.string(token, s)?;
+                self.words.extend(words)
+            }
+            Token::Bare(ref text) => {
+                match parse_int(text) {
+                    Some(_) => {
+                        let word = self.int32(token)?;
+                        self.words.push(word);
+                    }
+                    None => self.number(token, Some(Type::Float(32)))?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses `token` as an integer of at most 32 bits.
+    fn int32(&self, token: &Token) -> Result<Word> {
+        match *token {
+            Token::Bare(ref text) => {
+                match parse_int(text) {
+                    Some(value) if value <= u64::from(u32::MAX) => Ok(value as Word),
+                    Some(value) if text.starts_with('-') && value >= 0xffff_ffff_8000_0000 => {
+                        Ok(value as Word)
+                    }
+                    _ => Err(self.invalid(token)),
+                }
+            }
+            _ => Err(self.invalid(token)),
+        }
+    }
+
+    /// Encodes `token` as a number of the type `ty`, by default a 32-bit
+    /// integer.
+    fn number(&mut self, token: &Token, ty: Option<Type>) -> Result<()> {
+        let text = match *token {
+            Token::Bare(ref text) => text,
+            _ => return Err(self.invalid(token)),
+        };
+        match ty {
+            Some(Type::Integer(width, signed)) if 0 < width && width <= 64 => {
+                let value = int_bits(text, width, signed).ok_or_else(|| self.invalid(token))?;
+                if width == 64 {
+                    self.words.extend(&[value as Word, (value >> 32) as Word]);
+                } else if signed && value & (1 << (width - 1)) != 0 {
+                    // Narrow signed integers are sign extended to the word.
+                    self.words.push(value as Word | !((1 << (width - 1)) - 1));
+                } else {
+                    self.words.push(value as Word);
+                }
+            }
+            Some(Type::Float(width)) => {
+                let bits = float_bits(text, width).ok_or_else(|| self.invalid(token))?;
+                if width == 64 {
+                    self.words.extend(&[bits as Word, (bits >> 32) as Word]);
+                } else {
+                    self.words.push(bits as Word);
+                }
+            }
+            _ => {
+                let word = self.int32(token)?;
+                self.words.push(word);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Gives the text of `token` for errors.
+fn describe(token: &Token) -> String {
+    match *token {
+        Token::Id(ref name) => format!("%{}", name),
+        Token::Fixed(id) => format!("%{}", id),
+        Token::Placeholder(ref name) => format!("${{{}}}", name),
+        Token::Raw(word) => format!("!{}", word),
+        Token::Str(ref s) => format!("{:?}", s),
+        Token::Bare(ref text) => text.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use binary::Disassemble;
+    use mr;
+    use spirv;
+
+    use super::{assemble_text, assemble_text_with, assemble_with, AsmOptions, Binding, Error};
+
+    #[test]
+    fn test_assemble_round_trip() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        let glsl = b.ext_inst_import("GLSL.std.450");
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let int = b.type_int(64, 1);
+        let ptr = b.type_pointer(None, spirv::StorageClass::Function, float);
+        let half = b.constant_f32(float, 0.5);
+        b.constant_u64(int, 1 << 40);
+        let fty = b.type_function(void, vec![]);
+        let main = b.begin_function(void, None, spirv::FunctionControl::INLINE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let v = b.variable(ptr, None, spirv::StorageClass::Function, None);
+        let value = b.load(float,
+                           None,
+                           v,
+                           Some(spirv::MemoryAccess::ALIGNED),
+                           vec![mr::Operand::LiteralInt32(4)])
+                     .unwrap();
+        b.ext_inst(float, None, glsl, spirv::GLOp::FSign as u32, vec![value]).unwrap();
+        b.store(v, half, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::Fragment, main, "main", vec![]);
+        b.name(main, "main \"entry\"");
+        let m = b.module();
+        let text = m.disassemble();
+
+        let options = AsmOptions { preserve_numeric_ids: true };
+        let words = assemble_text_with(&text, &options).unwrap();
+        assert_eq!(mr::load_words(&words).unwrap().disassemble(), text);
+        assert_eq!(words[3], m.header.unwrap().bound);
+    }
+
+    #[test]
+    fn test_assemble_numeric_ids() {
+        let text = "%void = OpTypeVoid\n\
+                    %10 = OpTypeFunction %void ; numbered\n\
+                    %main = OpFunction %void None %10\n\
+                    %2 = OpLabel\n\
+                    OpReturn\n\
+                    OpFunctionEnd\n\
+                    !0x00010000";
+        let words = assemble_text_with(text, &AsmOptions { preserve_numeric_ids: true }).unwrap();
+        let m = mr::load_words(&words[..words.len() - 1]).unwrap();
+        assert_eq!(m.types_global_values[0].result_id, Some(1));
+        assert_eq!(m.types_global_values[1].result_id, Some(10));
+        let f = &m.functions[0];
+        assert_eq!(f.def.as_ref().unwrap().result_id, Some(3));
+        assert_eq!(f.basic_blocks[0].label.as_ref().unwrap().result_id, Some(2));
+        assert_eq!(words[3], 11);
+        assert_eq!(words[words.len() - 1], 0x0001_0000);
+
+        let words = assemble_text(text).unwrap();
+        let m = mr::load_words(&words[..words.len() - 1]).unwrap();
+        assert_eq!(m.types_global_values[1].result_id, Some(2));
+        assert_eq!(m.functions[0].basic_blocks[0].label.as_ref().unwrap().result_id, Some(4));
+
+        // Raw words stand in for operands the grammar does not know.
+        let words = assemble_text("OpCapability !4242").unwrap();
+        assert_eq!(&words[5..], &[(2 << 16) | 17, 4242]);
+
+        assert_eq!(assemble_text("OpCapability Shady"),
+                   Err(Error::UnknownEnumerant(1, "Shady".to_owned())));
+        assert_eq!(assemble_text("\n%1 = OpNop"), Err(Error::ResultIdMismatch(2)));
+        assert_eq!(assemble_text("OpName %1"), Err(Error::MissingOperand(1)));
+        // Signs make names, not numbers.
+        let words = assemble_text_with("OpName %+5 \"a\"\nOpName %5 \"b\"",
+                                       &AsmOptions { preserve_numeric_ids: true }).unwrap();
+        assert_eq!((words[6], words[9]), (1, 5));
+        // Literal strings cannot hold a null, which would end them early.
+        assert_eq!(assemble_text("OpName %1 \"a\\0b\""),
+                   Err(Error::InvalidOperand(1, "\"a\\0b\"".to_owned())));
+    }
+
+    #[test]
+    fn test_assemble_numbers() {
+        let constant = |ty: &str, value: &str| {
+            let text = format!("%t = {}\n%c = OpConstant %t {}", ty, value);
+            assemble_text(&text).map(|words| words[words.len() - 1])
+        };
+        let invalid = |value: &str| Err(Error::InvalidOperand(2, value.to_owned()));
+        assert_eq!(constant("OpTypeInt 8 0", "255"), Ok(255));
+        assert_eq!(constant("OpTypeInt 8 0", "256"), invalid("256"));
+        assert_eq!(constant("OpTypeInt 32 0", "4294967295"), Ok(0xffff_ffff));
+        assert_eq!(constant("OpTypeInt 32 0", "-1"), invalid("-1"));
+        assert_eq!(constant("OpTypeInt 8 1", "-128"), Ok(-128i32 as u32));
+        assert_eq!(constant("OpTypeInt 8 1", "-129"), invalid("-129"));
+        assert_eq!(constant("OpTypeInt 8 1", "128"), invalid("128"));
+        // Hexadecimal numbers give the bits of signed integers.
+        assert_eq!(constant("OpTypeInt 8 1", "0xff"), Ok(0xffff_ffff));
+        assert_eq!(constant("OpTypeInt 64 0", "-1"), invalid("-1"));
+        assert_eq!(constant("OpTypeInt 64 1", "-9223372036854775808"), Ok(0x8000_0000));
+
+        assert_eq!(constant("OpTypeFloat 32", "0x1.8p1"), Ok(3.0f32.to_bits()));
+        assert_eq!(constant("OpTypeFloat 32", "-0x1p-149"), Ok(0x8000_0001));
+        assert_eq!(constant("OpTypeFloat 32", "0x1.fffffffp0"), Ok(2.0f32.to_bits()));
+        assert_eq!(constant("OpTypeFloat 32", "0x1p128"), invalid("0x1p128"));
+        assert_eq!(constant("OpTypeFloat 64", "0x1.8p1"), Ok((3.0f64.to_bits() >> 32) as u32));
+        assert_eq!(constant("OpTypeFloat 16", "0x1.8p1"), Ok(0x4200));
+        assert_eq!(constant("OpTypeFloat 16", "0x1p-24"), Ok(0x0001));
+        assert_eq!(constant("OpTypeFloat 16", "-2.5"), Ok(0xc100));
+        assert_eq!(constant("OpTypeFloat 16", "65504"), Ok(0x7bff));
+        assert_eq!(constant("OpTypeFloat 16", "65536"), invalid("65536"));
+        assert_eq!(constant("OpTypeFloat 32", "0x1.8q1"), invalid("0x1.8q1"));
+    }
+
+    #[test]
+    fn test_assemble_with() {
+        let template = "%int = OpTypeInt 32 1\n\
+                        ${answer} = OpConstant %int ${value}\n\
+                        %float = OpTypeFloat 32\n\
+                        %half = OpConstant %float ${half}\n\
+                        OpName ${answer} ${name}\n\
+                        OpName %half \"${name}\"";
+        let bindings = [("answer", Binding::Id(1)),
+                        ("value", Binding::Int(-42)),
+                        ("half", Binding::Float(0.5)),
+                        ("name", Binding::Str("x y".to_owned()))];
+        let m = mr::load_words(assemble_with(template, &bindings).unwrap()).unwrap();
+        let ids: Vec<_> = m.types_global_values.iter().map(|inst| inst.result_id).collect();
+        assert_eq!(ids, vec![Some(2), Some(1), Some(3), Some(4)]);
+        assert_eq!(m.types_global_values[1].operands,
+                   vec![mr::Operand::LiteralInt32(-42i32 as u32)]);
+        assert_eq!(m.types_global_values[3].operands, vec![mr::Operand::LiteralFloat32(0.5)]);
+        assert_eq!(m.debugs[0].operands, vec![mr::Operand::IdRef(1), mr::Operand::from("x y")]);
+        assert_eq!(m.debugs[1].operands[1], mr::Operand::from("${name}"));
+
+        assert_eq!(assemble_with("OpName %1 ${missing}", &bindings),
+                   Err(Error::UnboundPlaceholder(1, "missing".to_owned())));
+    }
+}
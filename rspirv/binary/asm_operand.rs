@@ -0,0 +1,570 @@
+// Copyright 2016 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// AUTOMATICALLY GENERATED from the SPIR-V JSON grammar:
+//   external/spirv.core.grammar.json.
+// DO NOT MODIFY!
+
+/// Returns the value of the enumerant `name` of the operand kind `kind`,
+/// and the kinds of the parameters it takes.
+fn enumerant(kind: GOpKind, name: &str) -> Option<(u32, &'static [GOpKind])> {
+    match (kind, name) {
+        (GOpKind::ImageOperands, "None") => Some((0, &[])),
+        (GOpKind::ImageOperands, "Bias") => Some((1, &[GOpKind::IdRef])),
+        (GOpKind::ImageOperands, "Lod") => Some((2, &[GOpKind::IdRef])),
+        (GOpKind::ImageOperands, "Grad") => Some((4, &[GOpKind::IdRef, GOpKind::IdRef])),
+        (GOpKind::ImageOperands, "ConstOffset") => Some((8, &[GOpKind::IdRef])),
+        (GOpKind::ImageOperands, "Offset") => Some((16, &[GOpKind::IdRef])),
+        (GOpKind::ImageOperands, "ConstOffsets") => Some((32, &[GOpKind::IdRef])),
+        (GOpKind::ImageOperands, "Sample") => Some((64, &[GOpKind::IdRef])),
+        (GOpKind::ImageOperands, "MinLod") => Some((128, &[GOpKind::IdRef])),
+        (GOpKind::ImageOperands, "MakeTexelAvailable") => Some((256, &[GOpKind::IdScope])),
+        (GOpKind::ImageOperands, "MakeTexelVisible") => Some((512, &[GOpKind::IdScope])),
+        (GOpKind::ImageOperands, "NonPrivateTexel") => Some((1024, &[])),
+        (GOpKind::ImageOperands, "VolatileTexel") => Some((2048, &[])),
+        (GOpKind::FPFastMathMode, "None") => Some((0, &[])),
+        (GOpKind::FPFastMathMode, "NotNaN") => Some((1, &[])),
+        (GOpKind::FPFastMathMode, "NotInf") => Some((2, &[])),
+        (GOpKind::FPFastMathMode, "NSZ") => Some((4, &[])),
+        (GOpKind::FPFastMathMode, "AllowRecip") => Some((8, &[])),
+        (GOpKind::FPFastMathMode, "Fast") => Some((16, &[])),
+        (GOpKind::SelectionControl, "None") => Some((0, &[])),
+        (GOpKind::SelectionControl, "Flatten") => Some((1, &[])),
+        (GOpKind::SelectionControl, "DontFlatten") => Some((2, &[])),
+        (GOpKind::LoopControl, "None") => Some((0, &[])),
+        (GOpKind::LoopControl, "Unroll") => Some((1, &[])),
+        (GOpKind::LoopControl, "DontUnroll") => Some((2, &[])),
+        (GOpKind::LoopControl, "DependencyInfinite") => Some((4, &[])),
+        (GOpKind::LoopControl, "DependencyLength") => Some((8, &[GOpKind::LiteralInteger])),
+        (GOpKind::FunctionControl, "None") => Some((0, &[])),
+        (GOpKind::FunctionControl, "Inline") => Some((1, &[])),
+        (GOpKind::FunctionControl, "DontInline") => Some((2, &[])),
+        (GOpKind::FunctionControl, "Pure") => Some((4, &[])),
+        (GOpKind::FunctionControl, "Const") => Some((8, &[])),
+        (GOpKind::MemorySemantics, "Relaxed") => Some((0, &[])),
+        (GOpKind::MemorySemantics, "None") => Some((0, &[])),
+        (GOpKind::MemorySemantics, "Acquire") => Some((2, &[])),
+        (GOpKind::MemorySemantics, "Release") => Some((4, &[])),
+        (GOpKind::MemorySemantics, "AcquireRelease") => Some((8, &[])),
+        (GOpKind::MemorySemantics, "SequentiallyConsistent") => Some((16, &[])),
+        (GOpKind::MemorySemantics, "UniformMemory") => Some((64, &[])),
+        (GOpKind::MemorySemantics, "SubgroupMemory") => Some((128, &[])),
+        (GOpKind::MemorySemantics, "WorkgroupMemory") => Some((256, &[])),
+        (GOpKind::MemorySemantics, "CrossWorkgroupMemory") => Some((512, &[])),
+        (GOpKind::MemorySemantics, "AtomicCounterMemory") => Some((1024, &[])),
+        (GOpKind::MemorySemantics, "ImageMemory") => Some((2048, &[])),
+        (GOpKind::MemorySemantics, "OutputMemory") => Some((4096, &[])),
+        (GOpKind::MemorySemantics, "MakeAvailable") => Some((8192, &[])),
+        (GOpKind::MemorySemantics, "MakeVisible") => Some((16384, &[])),
+        (GOpKind::MemorySemantics, "Volatile") => Some((32768, &[])),
+        (GOpKind::MemoryAccess, "None") => Some((0, &[])),
+        (GOpKind::MemoryAccess, "Volatile") => Some((1, &[])),
+        (GOpKind::MemoryAccess, "Aligned") => Some((2, &[GOpKind::LiteralInteger])),
+        (GOpKind::MemoryAccess, "Nontemporal") => Some((4, &[])),
+        (GOpKind::MemoryAccess, "MakePointerAvailable") => Some((8, &[GOpKind::IdScope])),
+        (GOpKind::MemoryAccess, "MakePointerVisible") => Some((16, &[GOpKind::IdScope])),
+        (GOpKind::MemoryAccess, "NonPrivatePointer") => Some((32, &[])),
+        (GOpKind::KernelProfilingInfo, "None") => Some((0, &[])),
+        (GOpKind::KernelProfilingInfo, "CmdExecTime") => Some((1, &[])),
+        (GOpKind::CooperativeMatrixOperands, "NoneKHR") => Some((0, &[])),
+        (GOpKind::CooperativeMatrixOperands, "MatrixASignedComponentsKHR") => Some((1, &[])),
+        (GOpKind::CooperativeMatrixOperands, "MatrixBSignedComponentsKHR") => Some((2, &[])),
+        (GOpKind::CooperativeMatrixOperands, "MatrixCSignedComponentsKHR") => Some((4, &[])),
+        (GOpKind::CooperativeMatrixOperands, "MatrixResultSignedComponentsKHR") => Some((8, &[])),
+        (GOpKind::CooperativeMatrixOperands, "SaturatingAccumulationKHR") => Some((16, &[])),
+        (GOpKind::SourceLanguage, "Unknown") => Some((0, &[])),
+        (GOpKind::SourceLanguage, "ESSL") => Some((1, &[])),
+        (GOpKind::SourceLanguage, "GLSL") => Some((2, &[])),
+        (GOpKind::SourceLanguage, "OpenCL_C") => Some((3, &[])),
+        (GOpKind::SourceLanguage, "OpenCL_CPP") => Some((4, &[])),
+        (GOpKind::SourceLanguage, "HLSL") => Some((5, &[])),
+        (GOpKind::ExecutionModel, "Vertex") => Some((0, &[])),
+        (GOpKind::ExecutionModel, "TessellationControl") => Some((1, &[])),
+        (GOpKind::ExecutionModel, "TessellationEvaluation") => Some((2, &[])),
+        (GOpKind::ExecutionModel, "Geometry") => Some((3, &[])),
+        (GOpKind::ExecutionModel, "Fragment") => Some((4, &[])),
+        (GOpKind::ExecutionModel, "GLCompute") => Some((5, &[])),
+        (GOpKind::ExecutionModel, "Kernel") => Some((6, &[])),
+        (GOpKind::ExecutionModel, "RayGenerationKHR") => Some((5313, &[])),
+        (GOpKind::ExecutionModel, "IntersectionKHR") => Some((5314, &[])),
+        (GOpKind::ExecutionModel, "AnyHitKHR") => Some((5315, &[])),
+        (GOpKind::ExecutionModel, "ClosestHitKHR") => Some((5316, &[])),
+        (GOpKind::ExecutionModel, "MissKHR") => Some((5317, &[])),
+        (GOpKind::ExecutionModel, "CallableKHR") => Some((5318, &[])),
+        (GOpKind::ExecutionModel, "TaskEXT") => Some((5364, &[])),
+        (GOpKind::ExecutionModel, "MeshEXT") => Some((5365, &[])),
+        (GOpKind::AddressingModel, "Logical") => Some((0, &[])),
+        (GOpKind::AddressingModel, "Physical32") => Some((1, &[])),
+        (GOpKind::AddressingModel, "Physical64") => Some((2, &[])),
+        (GOpKind::AddressingModel, "PhysicalStorageBuffer64") => Some((5348, &[])),
+        (GOpKind::MemoryModel, "Simple") => Some((0, &[])),
+        (GOpKind::MemoryModel, "GLSL450") => Some((1, &[])),
+        (GOpKind::MemoryModel, "OpenCL") => Some((2, &[])),
+        (GOpKind::MemoryModel, "Vulkan") => Some((3, &[])),
+        (GOpKind::ExecutionMode, "Invocations") => Some((0, &[GOpKind::LiteralInteger])),
+        (GOpKind::ExecutionMode, "SpacingEqual") => Some((1, &[])),
+        (GOpKind::ExecutionMode, "SpacingFractionalEven") => Some((2, &[])),
+        (GOpKind::ExecutionMode, "SpacingFractionalOdd") => Some((3, &[])),
+        (GOpKind::ExecutionMode, "VertexOrderCw") => Some((4, &[])),
+        (GOpKind::ExecutionMode, "VertexOrderCcw") => Some((5, &[])),
+        (GOpKind::ExecutionMode, "PixelCenterInteger") => Some((6, &[])),
+        (GOpKind::ExecutionMode, "OriginUpperLeft") => Some((7, &[])),
+        (GOpKind::ExecutionMode, "OriginLowerLeft") => Some((8, &[])),
+        (GOpKind::ExecutionMode, "EarlyFragmentTests") => Some((9, &[])),
+        (GOpKind::ExecutionMode, "PointMode") => Some((10, &[])),
+        (GOpKind::ExecutionMode, "Xfb") => Some((11, &[])),
+        (GOpKind::ExecutionMode, "DepthReplacing") => Some((12, &[])),
+        (GOpKind::ExecutionMode, "DepthGreater") => Some((14, &[])),
+        (GOpKind::ExecutionMode, "DepthLess") => Some((15, &[])),
+        (GOpKind::ExecutionMode, "DepthUnchanged") => Some((16, &[])),
+        (GOpKind::ExecutionMode, "LocalSize") => Some((17, &[GOpKind::LiteralInteger, GOpKind::LiteralInteger, GOpKind::LiteralInteger])),
+        (GOpKind::ExecutionMode, "LocalSizeHint") => Some((18, &[GOpKind::LiteralInteger, GOpKind::LiteralInteger, GOpKind::LiteralInteger])),
+        (GOpKind::ExecutionMode, "InputPoints") => Some((19, &[])),
+        (GOpKind::ExecutionMode, "InputLines") => Some((20, &[])),
+        (GOpKind::ExecutionMode, "InputLinesAdjacency") => Some((21, &[])),
+        (GOpKind::ExecutionMode, "Triangles") => Some((22, &[])),
+        (GOpKind::ExecutionMode, "InputTrianglesAdjacency") => Some((23, &[])),
+        (GOpKind::ExecutionMode, "Quads") => Some((24, &[])),
+        (GOpKind::ExecutionMode, "Isolines") => Some((25, &[])),
+        (GOpKind::ExecutionMode, "OutputVertices") => Some((26, &[GOpKind::LiteralInteger])),
+        (GOpKind::ExecutionMode, "OutputPoints") => Some((27, &[])),
+        (GOpKind::ExecutionMode, "OutputLineStrip") => Some((28, &[])),
+        (GOpKind::ExecutionMode, "OutputTriangleStrip") => Some((29, &[])),
+        (GOpKind::ExecutionMode, "VecTypeHint") => Some((30, &[GOpKind::LiteralInteger])),
+        (GOpKind::ExecutionMode, "ContractionOff") => Some((31, &[])),
+        (GOpKind::ExecutionMode, "Initializer") => Some((33, &[])),
+        (GOpKind::ExecutionMode, "Finalizer") => Some((34, &[])),
+        (GOpKind::ExecutionMode, "SubgroupSize") => Some((35, &[GOpKind::LiteralInteger])),
+        (GOpKind::ExecutionMode, "SubgroupsPerWorkgroup") => Some((36, &[GOpKind::LiteralInteger])),
+        (GOpKind::ExecutionMode, "SubgroupsPerWorkgroupId") => Some((37, &[GOpKind::IdRef])),
+        (GOpKind::ExecutionMode, "LocalSizeId") => Some((38, &[GOpKind::IdRef, GOpKind::IdRef, GOpKind::IdRef])),
+        (GOpKind::ExecutionMode, "LocalSizeHintId") => Some((39, &[GOpKind::IdRef])),
+        (GOpKind::ExecutionMode, "PostDepthCoverage") => Some((4446, &[])),
+        (GOpKind::ExecutionMode, "DenormPreserve") => Some((4459, &[GOpKind::LiteralInteger])),
+        (GOpKind::ExecutionMode, "DenormFlushToZero") => Some((4460, &[GOpKind::LiteralInteger])),
+        (GOpKind::ExecutionMode, "SignedZeroInfNanPreserve") => Some((4461, &[GOpKind::LiteralInteger])),
+        (GOpKind::ExecutionMode, "RoundingModeRTE") => Some((4462, &[GOpKind::LiteralInteger])),
+        (GOpKind::ExecutionMode, "RoundingModeRTZ") => Some((4463, &[GOpKind::LiteralInteger])),
+        (GOpKind::ExecutionMode, "StencilRefReplacingEXT") => Some((5027, &[])),
+        (GOpKind::ExecutionMode, "OutputLinesEXT") => Some((5269, &[])),
+        (GOpKind::ExecutionMode, "OutputPrimitivesEXT") => Some((5270, &[GOpKind::LiteralInteger])),
+        (GOpKind::ExecutionMode, "OutputTrianglesEXT") => Some((5298, &[])),
+        (GOpKind::StorageClass, "UniformConstant") => Some((0, &[])),
+        (GOpKind::StorageClass, "Input") => Some((1, &[])),
+        (GOpKind::StorageClass, "Uniform") => Some((2, &[])),
+        (GOpKind::StorageClass, "Output") => Some((3, &[])),
+        (GOpKind::StorageClass, "Workgroup") => Some((4, &[])),
+        (GOpKind::StorageClass, "CrossWorkgroup") => Some((5, &[])),
+        (GOpKind::StorageClass, "Private") => Some((6, &[])),
+        (GOpKind::StorageClass, "Function") => Some((7, &[])),
+        (GOpKind::StorageClass, "Generic") => Some((8, &[])),
+        (GOpKind::StorageClass, "PushConstant") => Some((9, &[])),
+        (GOpKind::StorageClass, "AtomicCounter") => Some((10, &[])),
+        (GOpKind::StorageClass, "Image") => Some((11, &[])),
+        (GOpKind::StorageClass, "StorageBuffer") => Some((12, &[])),
+        (GOpKind::StorageClass, "CallableDataKHR") => Some((5328, &[])),
+        (GOpKind::StorageClass, "IncomingCallableDataKHR") => Some((5329, &[])),
+        (GOpKind::StorageClass, "RayPayloadKHR") => Some((5338, &[])),
+        (GOpKind::StorageClass, "HitAttributeKHR") => Some((5339, &[])),
+        (GOpKind::StorageClass, "IncomingRayPayloadKHR") => Some((5342, &[])),
+        (GOpKind::StorageClass, "ShaderRecordBufferKHR") => Some((5343, &[])),
+        (GOpKind::StorageClass, "PhysicalStorageBuffer") => Some((5349, &[])),
+        (GOpKind::StorageClass, "TaskPayloadWorkgroupEXT") => Some((5402, &[])),
+        (GOpKind::Dim, "1D") => Some((0, &[])),
+        (GOpKind::Dim, "2D") => Some((1, &[])),
+        (GOpKind::Dim, "3D") => Some((2, &[])),
+        (GOpKind::Dim, "Cube") => Some((3, &[])),
+        (GOpKind::Dim, "Rect") => Some((4, &[])),
+        (GOpKind::Dim, "Buffer") => Some((5, &[])),
+        (GOpKind::Dim, "SubpassData") => Some((6, &[])),
+        (GOpKind::SamplerAddressingMode, "None") => Some((0, &[])),
+        (GOpKind::SamplerAddressingMode, "ClampToEdge") => Some((1, &[])),
+        (GOpKind::SamplerAddressingMode, "Clamp") => Some((2, &[])),
+        (GOpKind::SamplerAddressingMode, "Repeat") => Some((3, &[])),
+        (GOpKind::SamplerAddressingMode, "RepeatMirrored") => Some((4, &[])),
+        (GOpKind::SamplerFilterMode, "Nearest") => Some((0, &[])),
+        (GOpKind::SamplerFilterMode, "Linear") => Some((1, &[])),
+        (GOpKind::ImageFormat, "Unknown") => Some((0, &[])),
+        (GOpKind::ImageFormat, "Rgba32f") => Some((1, &[])),
+        (GOpKind::ImageFormat, "Rgba16f") => Some((2, &[])),
+        (GOpKind::ImageFormat, "R32f") => Some((3, &[])),
+        (GOpKind::ImageFormat, "Rgba8") => Some((4, &[])),
+        (GOpKind::ImageFormat, "Rgba8Snorm") => Some((5, &[])),
+        (GOpKind::ImageFormat, "Rg32f") => Some((6, &[])),
+        (GOpKind::ImageFormat, "Rg16f") => Some((7, &[])),
+        (GOpKind::ImageFormat, "R11fG11fB10f") => Some((8, &[])),
+        (GOpKind::ImageFormat, "R16f") => Some((9, &[])),
+        (GOpKind::ImageFormat, "Rgba16") => Some((10, &[])),
+        (GOpKind::ImageFormat, "Rgb10A2") => Some((11, &[])),
+        (GOpKind::ImageFormat, "Rg16") => Some((12, &[])),
+        (GOpKind::ImageFormat, "Rg8") => Some((13, &[])),
+        (GOpKind::ImageFormat, "R16") => Some((14, &[])),
+        (GOpKind::ImageFormat, "R8") => Some((15, &[])),
+        (GOpKind::ImageFormat, "Rgba16Snorm") => Some((16, &[])),
+        (GOpKind::ImageFormat, "Rg16Snorm") => Some((17, &[])),
+        (GOpKind::ImageFormat, "Rg8Snorm") => Some((18, &[])),
+        (GOpKind::ImageFormat, "R16Snorm") => Some((19, &[])),
+        (GOpKind::ImageFormat, "R8Snorm") => Some((20, &[])),
+        (GOpKind::ImageFormat, "Rgba32i") => Some((21, &[])),
+        (GOpKind::ImageFormat, "Rgba16i") => Some((22, &[])),
+        (GOpKind::ImageFormat, "Rgba8i") => Some((23, &[])),
+        (GOpKind::ImageFormat, "R32i") => Some((24, &[])),
+        (GOpKind::ImageFormat, "Rg32i") => Some((25, &[])),
+        (GOpKind::ImageFormat, "Rg16i") => Some((26, &[])),
+        (GOpKind::ImageFormat, "Rg8i") => Some((27, &[])),
+        (GOpKind::ImageFormat, "R16i") => Some((28, &[])),
+        (GOpKind::ImageFormat, "R8i") => Some((29, &[])),
+        (GOpKind::ImageFormat, "Rgba32ui") => Some((30, &[])),
+        (GOpKind::ImageFormat, "Rgba16ui") => Some((31, &[])),
+        (GOpKind::ImageFormat, "Rgba8ui") => Some((32, &[])),
+        (GOpKind::ImageFormat, "R32ui") => Some((33, &[])),
+        (GOpKind::ImageFormat, "Rgb10a2ui") => Some((34, &[])),
+        (GOpKind::ImageFormat, "Rg32ui") => Some((35, &[])),
+        (GOpKind::ImageFormat, "Rg16ui") => Some((36, &[])),
+        (GOpKind::ImageFormat, "Rg8ui") => Some((37, &[])),
+        (GOpKind::ImageFormat, "R16ui") => Some((38, &[])),
+        (GOpKind::ImageFormat, "R8ui") => Some((39, &[])),
+        (GOpKind::ImageChannelOrder, "R") => Some((0, &[])),
+        (GOpKind::ImageChannelOrder, "A") => Some((1, &[])),
+        (GOpKind::ImageChannelOrder, "RG") => Some((2, &[])),
+        (GOpKind::ImageChannelOrder, "RA") => Some((3, &[])),
+        (GOpKind::ImageChannelOrder, "RGB") => Some((4, &[])),
+        (GOpKind::ImageChannelOrder, "RGBA") => Some((5, &[])),
+        (GOpKind::ImageChannelOrder, "BGRA") => Some((6, &[])),
+        (GOpKind::ImageChannelOrder, "ARGB") => Some((7, &[])),
+        (GOpKind::ImageChannelOrder, "Intensity") => Some((8, &[])),
+        (GOpKind::ImageChannelOrder, "Luminance") => Some((9, &[])),
+        (GOpKind::ImageChannelOrder, "Rx") => Some((10, &[])),
+        (GOpKind::ImageChannelOrder, "RGx") => Some((11, &[])),
+        (GOpKind::ImageChannelOrder, "RGBx") => Some((12, &[])),
+        (GOpKind::ImageChannelOrder, "Depth") => Some((13, &[])),
+        (GOpKind::ImageChannelOrder, "DepthStencil") => Some((14, &[])),
+        (GOpKind::ImageChannelOrder, "sRGB") => Some((15, &[])),
+        (GOpKind::ImageChannelOrder, "sRGBx") => Some((16, &[])),
+        (GOpKind::ImageChannelOrder, "sRGBA") => Some((17, &[])),
+        (GOpKind::ImageChannelOrder, "sBGRA") => Some((18, &[])),
+        (GOpKind::ImageChannelOrder, "ABGR") => Some((19, &[])),
+        (GOpKind::ImageChannelDataType, "SnormInt8") => Some((0, &[])),
+        (GOpKind::ImageChannelDataType, "SnormInt16") => Some((1, &[])),
+        (GOpKind::ImageChannelDataType, "UnormInt8") => Some((2, &[])),
+        (GOpKind::ImageChannelDataType, "UnormInt16") => Some((3, &[])),
+        (GOpKind::ImageChannelDataType, "UnormShort565") => Some((4, &[])),
+        (GOpKind::ImageChannelDataType, "UnormShort555") => Some((5, &[])),
+        (GOpKind::ImageChannelDataType, "UnormInt101010") => Some((6, &[])),
+        (GOpKind::ImageChannelDataType, "SignedInt8") => Some((7, &[])),
+        (GOpKind::ImageChannelDataType, "SignedInt16") => Some((8, &[])),
+        (GOpKind::ImageChannelDataType, "SignedInt32") => Some((9, &[])),
+        (GOpKind::ImageChannelDataType, "UnsignedInt8") => Some((10, &[])),
+        (GOpKind::ImageChannelDataType, "UnsignedInt16") => Some((11, &[])),
+        (GOpKind::ImageChannelDataType, "UnsignedInt32") => Some((12, &[])),
+        (GOpKind::ImageChannelDataType, "HalfFloat") => Some((13, &[])),
+        (GOpKind::ImageChannelDataType, "Float") => Some((14, &[])),
+        (GOpKind::ImageChannelDataType, "UnormInt24") => Some((15, &[])),
+        (GOpKind::ImageChannelDataType, "UnormInt101010_2") => Some((16, &[])),
+        (GOpKind::FPRoundingMode, "RTE") => Some((0, &[])),
+        (GOpKind::FPRoundingMode, "RTZ") => Some((1, &[])),
+        (GOpKind::FPRoundingMode, "RTP") => Some((2, &[])),
+        (GOpKind::FPRoundingMode, "RTN") => Some((3, &[])),
+        (GOpKind::LinkageType, "Export") => Some((0, &[])),
+        (GOpKind::LinkageType, "Import") => Some((1, &[])),
+        (GOpKind::AccessQualifier, "ReadOnly") => Some((0, &[])),
+        (GOpKind::AccessQualifier, "WriteOnly") => Some((1, &[])),
+        (GOpKind::AccessQualifier, "ReadWrite") => Some((2, &[])),
+        (GOpKind::FunctionParameterAttribute, "Zext") => Some((0, &[])),
+        (GOpKind::FunctionParameterAttribute, "Sext") => Some((1, &[])),
+        (GOpKind::FunctionParameterAttribute, "ByVal") => Some((2, &[])),
+        (GOpKind::FunctionParameterAttribute, "Sret") => Some((3, &[])),
+        (GOpKind::FunctionParameterAttribute, "NoAlias") => Some((4, &[])),
+        (GOpKind::FunctionParameterAttribute, "NoCapture") => Some((5, &[])),
+        (GOpKind::FunctionParameterAttribute, "NoWrite") => Some((6, &[])),
+        (GOpKind::FunctionParameterAttribute, "NoReadWrite") => Some((7, &[])),
+        (GOpKind::Decoration, "RelaxedPrecision") => Some((0, &[])),
+        (GOpKind::Decoration, "SpecId") => Some((1, &[GOpKind::LiteralInteger])),
+        (GOpKind::Decoration, "Block") => Some((2, &[])),
+        (GOpKind::Decoration, "BufferBlock") => Some((3, &[])),
+        (GOpKind::Decoration, "RowMajor") => Some((4, &[])),
+        (GOpKind::Decoration, "ColMajor") => Some((5, &[])),
+        (GOpKind::Decoration, "ArrayStride") => Some((6, &[GOpKind::LiteralInteger])),
+        (GOpKind::Decoration, "MatrixStride") => Some((7, &[GOpKind::LiteralInteger])),
+        (GOpKind::Decoration, "GLSLShared") => Some((8, &[])),
+        (GOpKind::Decoration, "GLSLPacked") => Some((9, &[])),
+        (GOpKind::Decoration, "CPacked") => Some((10, &[])),
+        (GOpKind::Decoration, "BuiltIn") => Some((11, &[GOpKind::BuiltIn])),
+        (GOpKind::Decoration, "NoPerspective") => Some((13, &[])),
+        (GOpKind::Decoration, "Flat") => Some((14, &[])),
+        (GOpKind::Decoration, "Patch") => Some((15, &[])),
+        (GOpKind::Decoration, "Centroid") => Some((16, &[])),
+        (GOpKind::Decoration, "Sample") => Some((17, &[])),
+        (GOpKind::Decoration, "Invariant") => Some((18, &[])),
+        (GOpKind::Decoration, "Restrict") => Some((19, &[])),
+        (GOpKind::Decoration, "Aliased") => Some((20, &[])),
+        (GOpKind::Decoration, "Volatile") => Some((21, &[])),
+        (GOpKind::Decoration, "Constant") => Some((22, &[])),
+        (GOpKind::Decoration, "Coherent") => Some((23, &[])),
+        (GOpKind::Decoration, "NonWritable") => Some((24, &[])),
+        (GOpKind::Decoration, "NonReadable") => Some((25, &[])),
+        (GOpKind::Decoration, "Uniform") => Some((26, &[])),
+        (GOpKind::Decoration, "SaturatedConversion") => Some((28, &[])),
+        (GOpKind::Decoration, "Stream") => Some((29, &[GOpKind::LiteralInteger])),
+        (GOpKind::Decoration, "Location") => Some((30, &[GOpKind::LiteralInteger])),
+        (GOpKind::Decoration, "Component") => Some((31, &[GOpKind::LiteralInteger])),
+        (GOpKind::Decoration, "Index") => Some((32, &[GOpKind::LiteralInteger])),
+        (GOpKind::Decoration, "Binding") => Some((33, &[GOpKind::LiteralInteger])),
+        (GOpKind::Decoration, "DescriptorSet") => Some((34, &[GOpKind::LiteralInteger])),
+        (GOpKind::Decoration, "Offset") => Some((35, &[GOpKind::LiteralInteger])),
+        (GOpKind::Decoration, "XfbBuffer") => Some((36, &[GOpKind::LiteralInteger])),
+        (GOpKind::Decoration, "XfbStride") => Some((37, &[GOpKind::LiteralInteger])),
+        (GOpKind::Decoration, "FuncParamAttr") => Some((38, &[GOpKind::FunctionParameterAttribute])),
+        (GOpKind::Decoration, "FPRoundingMode") => Some((39, &[GOpKind::FPRoundingMode])),
+        (GOpKind::Decoration, "FPFastMathMode") => Some((40, &[GOpKind::FPFastMathMode])),
+        (GOpKind::Decoration, "LinkageAttributes") => Some((41, &[GOpKind::LiteralString, GOpKind::LinkageType])),
+        (GOpKind::Decoration, "NoContraction") => Some((42, &[])),
+        (GOpKind::Decoration, "InputAttachmentIndex") => Some((43, &[GOpKind::LiteralInteger])),
+        (GOpKind::Decoration, "Alignment") => Some((44, &[GOpKind::LiteralInteger])),
+        (GOpKind::Decoration, "MaxByteOffset") => Some((45, &[GOpKind::LiteralInteger])),
+        (GOpKind::Decoration, "AlignmentId") => Some((46, &[GOpKind::IdRef])),
+        (GOpKind::Decoration, "MaxByteOffsetId") => Some((47, &[GOpKind::IdRef])),
+        (GOpKind::Decoration, "ExplicitInterpAMD") => Some((4999, &[])),
+        (GOpKind::Decoration, "OverrideCoverageNV") => Some((5248, &[])),
+        (GOpKind::Decoration, "PassthroughNV") => Some((5250, &[])),
+        (GOpKind::Decoration, "ViewportRelativeNV") => Some((5252, &[])),
+        (GOpKind::Decoration, "SecondaryViewportRelativeNV") => Some((5256, &[GOpKind::LiteralInteger])),
+        (GOpKind::Decoration, "PerPrimitiveEXT") => Some((5271, &[])),
+        (GOpKind::Decoration, "NonUniform") => Some((5300, &[])),
+        (GOpKind::Decoration, "RestrictPointer") => Some((5355, &[])),
+        (GOpKind::Decoration, "AliasedPointer") => Some((5356, &[])),
+        (GOpKind::Decoration, "HlslCounterBufferGOOGLE") => Some((5634, &[GOpKind::IdRef])),
+        (GOpKind::Decoration, "HlslSemanticGOOGLE") => Some((5635, &[GOpKind::LiteralString])),
+        (GOpKind::Decoration, "UserTypeGOOGLE") => Some((5636, &[GOpKind::LiteralString])),
+        (GOpKind::BuiltIn, "Position") => Some((0, &[])),
+        (GOpKind::BuiltIn, "PointSize") => Some((1, &[])),
+        (GOpKind::BuiltIn, "ClipDistance") => Some((3, &[])),
+        (GOpKind::BuiltIn, "CullDistance") => Some((4, &[])),
+        (GOpKind::BuiltIn, "VertexId") => Some((5, &[])),
+        (GOpKind::BuiltIn, "InstanceId") => Some((6, &[])),
+        (GOpKind::BuiltIn, "PrimitiveId") => Some((7, &[])),
+        (GOpKind::BuiltIn, "InvocationId") => Some((8, &[])),
+        (GOpKind::BuiltIn, "Layer") => Some((9, &[])),
+        (GOpKind::BuiltIn, "ViewportIndex") => Some((10, &[])),
+        (GOpKind::BuiltIn, "TessLevelOuter") => Some((11, &[])),
+        (GOpKind::BuiltIn, "TessLevelInner") => Some((12, &[])),
+        (GOpKind::BuiltIn, "TessCoord") => Some((13, &[])),
+        (GOpKind::BuiltIn, "PatchVertices") => Some((14, &[])),
+        (GOpKind::BuiltIn, "FragCoord") => Some((15, &[])),
+        (GOpKind::BuiltIn, "PointCoord") => Some((16, &[])),
+        (GOpKind::BuiltIn, "FrontFacing") => Some((17, &[])),
+        (GOpKind::BuiltIn, "SampleId") => Some((18, &[])),
+        (GOpKind::BuiltIn, "SamplePosition") => Some((19, &[])),
+        (GOpKind::BuiltIn, "SampleMask") => Some((20, &[])),
+        (GOpKind::BuiltIn, "FragDepth") => Some((22, &[])),
+        (GOpKind::BuiltIn, "HelperInvocation") => Some((23, &[])),
+        (GOpKind::BuiltIn, "NumWorkgroups") => Some((24, &[])),
+        (GOpKind::BuiltIn, "WorkgroupSize") => Some((25, &[])),
+        (GOpKind::BuiltIn, "WorkgroupId") => Some((26, &[])),
+        (GOpKind::BuiltIn, "LocalInvocationId") => Some((27, &[])),
+        (GOpKind::BuiltIn, "GlobalInvocationId") => Some((28, &[])),
+        (GOpKind::BuiltIn, "LocalInvocationIndex") => Some((29, &[])),
+        (GOpKind::BuiltIn, "WorkDim") => Some((30, &[])),
+        (GOpKind::BuiltIn, "GlobalSize") => Some((31, &[])),
+        (GOpKind::BuiltIn, "EnqueuedWorkgroupSize") => Some((32, &[])),
+        (GOpKind::BuiltIn, "GlobalOffset") => Some((33, &[])),
+        (GOpKind::BuiltIn, "GlobalLinearId") => Some((34, &[])),
+        (GOpKind::BuiltIn, "SubgroupSize") => Some((36, &[])),
+        (GOpKind::BuiltIn, "SubgroupMaxSize") => Some((37, &[])),
+        (GOpKind::BuiltIn, "NumSubgroups") => Some((38, &[])),
+        (GOpKind::BuiltIn, "NumEnqueuedSubgroups") => Some((39, &[])),
+        (GOpKind::BuiltIn, "SubgroupId") => Some((40, &[])),
+        (GOpKind::BuiltIn, "SubgroupLocalInvocationId") => Some((41, &[])),
+        (GOpKind::BuiltIn, "VertexIndex") => Some((42, &[])),
+        (GOpKind::BuiltIn, "InstanceIndex") => Some((43, &[])),
+        (GOpKind::BuiltIn, "SubgroupEqMaskKHR") => Some((4416, &[])),
+        (GOpKind::BuiltIn, "SubgroupGeMaskKHR") => Some((4417, &[])),
+        (GOpKind::BuiltIn, "SubgroupGtMaskKHR") => Some((4418, &[])),
+        (GOpKind::BuiltIn, "SubgroupLeMaskKHR") => Some((4419, &[])),
+        (GOpKind::BuiltIn, "SubgroupLtMaskKHR") => Some((4420, &[])),
+        (GOpKind::BuiltIn, "BaseVertex") => Some((4424, &[])),
+        (GOpKind::BuiltIn, "BaseInstance") => Some((4425, &[])),
+        (GOpKind::BuiltIn, "DrawIndex") => Some((4426, &[])),
+        (GOpKind::BuiltIn, "DeviceIndex") => Some((4438, &[])),
+        (GOpKind::BuiltIn, "ViewIndex") => Some((4440, &[])),
+        (GOpKind::BuiltIn, "BaryCoordNoPerspAMD") => Some((4992, &[])),
+        (GOpKind::BuiltIn, "BaryCoordNoPerspCentroidAMD") => Some((4993, &[])),
+        (GOpKind::BuiltIn, "BaryCoordNoPerspSampleAMD") => Some((4994, &[])),
+        (GOpKind::BuiltIn, "BaryCoordSmoothAMD") => Some((4995, &[])),
+        (GOpKind::BuiltIn, "BaryCoordSmoothCentroidAMD") => Some((4996, &[])),
+        (GOpKind::BuiltIn, "BaryCoordSmoothSampleAMD") => Some((4997, &[])),
+        (GOpKind::BuiltIn, "BaryCoordPullModelAMD") => Some((4998, &[])),
+        (GOpKind::BuiltIn, "FragStencilRefEXT") => Some((5014, &[])),
+        (GOpKind::BuiltIn, "ViewportMaskNV") => Some((5253, &[])),
+        (GOpKind::BuiltIn, "SecondaryPositionNV") => Some((5257, &[])),
+        (GOpKind::BuiltIn, "SecondaryViewportMaskNV") => Some((5258, &[])),
+        (GOpKind::BuiltIn, "PositionPerViewNV") => Some((5261, &[])),
+        (GOpKind::BuiltIn, "ViewportMaskPerViewNV") => Some((5262, &[])),
+        (GOpKind::BuiltIn, "PrimitivePointIndicesEXT") => Some((5294, &[])),
+        (GOpKind::BuiltIn, "PrimitiveLineIndicesEXT") => Some((5295, &[])),
+        (GOpKind::BuiltIn, "PrimitiveTriangleIndicesEXT") => Some((5296, &[])),
+        (GOpKind::BuiltIn, "CullPrimitiveEXT") => Some((5299, &[])),
+        (GOpKind::BuiltIn, "LaunchIdKHR") => Some((5319, &[])),
+        (GOpKind::BuiltIn, "LaunchSizeKHR") => Some((5320, &[])),
+        (GOpKind::BuiltIn, "WorldRayOriginKHR") => Some((5321, &[])),
+        (GOpKind::BuiltIn, "WorldRayDirectionKHR") => Some((5322, &[])),
+        (GOpKind::BuiltIn, "ObjectRayOriginKHR") => Some((5323, &[])),
+        (GOpKind::BuiltIn, "ObjectRayDirectionKHR") => Some((5324, &[])),
+        (GOpKind::BuiltIn, "RayTminKHR") => Some((5325, &[])),
+        (GOpKind::BuiltIn, "RayTmaxKHR") => Some((5326, &[])),
+        (GOpKind::BuiltIn, "InstanceCustomIndexKHR") => Some((5327, &[])),
+        (GOpKind::BuiltIn, "ObjectToWorldKHR") => Some((5330, &[])),
+        (GOpKind::BuiltIn, "WorldToObjectKHR") => Some((5331, &[])),
+        (GOpKind::BuiltIn, "HitKindKHR") => Some((5333, &[])),
+        (GOpKind::BuiltIn, "IncomingRayFlagsKHR") => Some((5351, &[])),
+        (GOpKind::BuiltIn, "RayGeometryIndexKHR") => Some((5352, &[])),
+        (GOpKind::Scope, "CrossDevice") => Some((0, &[])),
+        (GOpKind::Scope, "Device") => Some((1, &[])),
+        (GOpKind::Scope, "Workgroup") => Some((2, &[])),
+        (GOpKind::Scope, "Subgroup") => Some((3, &[])),
+        (GOpKind::Scope, "Invocation") => Some((4, &[])),
+        (GOpKind::Scope, "QueueFamily") => Some((5, &[])),
+        (GOpKind::GroupOperation, "Reduce") => Some((0, &[])),
+        (GOpKind::GroupOperation, "InclusiveScan") => Some((1, &[])),
+        (GOpKind::GroupOperation, "ExclusiveScan") => Some((2, &[])),
+        (GOpKind::KernelEnqueueFlags, "NoWait") => Some((0, &[])),
+        (GOpKind::KernelEnqueueFlags, "WaitKernel") => Some((1, &[])),
+        (GOpKind::KernelEnqueueFlags, "WaitWorkGroup") => Some((2, &[])),
+        (GOpKind::Capability, "Matrix") => Some((0, &[])),
+        (GOpKind::Capability, "Shader") => Some((1, &[])),
+        (GOpKind::Capability, "Geometry") => Some((2, &[])),
+        (GOpKind::Capability, "Tessellation") => Some((3, &[])),
+        (GOpKind::Capability, "Addresses") => Some((4, &[])),
+        (GOpKind::Capability, "Linkage") => Some((5, &[])),
+        (GOpKind::Capability, "Kernel") => Some((6, &[])),
+        (GOpKind::Capability, "Vector16") => Some((7, &[])),
+        (GOpKind::Capability, "Float16Buffer") => Some((8, &[])),
+        (GOpKind::Capability, "Float16") => Some((9, &[])),
+        (GOpKind::Capability, "Float64") => Some((10, &[])),
+        (GOpKind::Capability, "Int64") => Some((11, &[])),
+        (GOpKind::Capability, "Int64Atomics") => Some((12, &[])),
+        (GOpKind::Capability, "ImageBasic") => Some((13, &[])),
+        (GOpKind::Capability, "ImageReadWrite") => Some((14, &[])),
+        (GOpKind::Capability, "ImageMipmap") => Some((15, &[])),
+        (GOpKind::Capability, "Pipes") => Some((17, &[])),
+        (GOpKind::Capability, "Groups") => Some((18, &[])),
+        (GOpKind::Capability, "DeviceEnqueue") => Some((19, &[])),
+        (GOpKind::Capability, "LiteralSampler") => Some((20, &[])),
+        (GOpKind::Capability, "AtomicStorage") => Some((21, &[])),
+        (GOpKind::Capability, "Int16") => Some((22, &[])),
+        (GOpKind::Capability, "TessellationPointSize") => Some((23, &[])),
+        (GOpKind::Capability, "GeometryPointSize") => Some((24, &[])),
+        (GOpKind::Capability, "ImageGatherExtended") => Some((25, &[])),
+        (GOpKind::Capability, "StorageImageMultisample") => Some((27, &[])),
+        (GOpKind::Capability, "UniformBufferArrayDynamicIndexing") => Some((28, &[])),
+        (GOpKind::Capability, "SampledImageArrayDynamicIndexing") => Some((29, &[])),
+        (GOpKind::Capability, "StorageBufferArrayDynamicIndexing") => Some((30, &[])),
+        (GOpKind::Capability, "StorageImageArrayDynamicIndexing") => Some((31, &[])),
+        (GOpKind::Capability, "ClipDistance") => Some((32, &[])),
+        (GOpKind::Capability, "CullDistance") => Some((33, &[])),
+        (GOpKind::Capability, "ImageCubeArray") => Some((34, &[])),
+        (GOpKind::Capability, "SampleRateShading") => Some((35, &[])),
+        (GOpKind::Capability, "ImageRect") => Some((36, &[])),
+        (GOpKind::Capability, "SampledRect") => Some((37, &[])),
+        (GOpKind::Capability, "GenericPointer") => Some((38, &[])),
+        (GOpKind::Capability, "Int8") => Some((39, &[])),
+        (GOpKind::Capability, "InputAttachment") => Some((40, &[])),
+        (GOpKind::Capability, "SparseResidency") => Some((41, &[])),
+        (GOpKind::Capability, "MinLod") => Some((42, &[])),
+        (GOpKind::Capability, "Sampled1D") => Some((43, &[])),
+        (GOpKind::Capability, "Image1D") => Some((44, &[])),
+        (GOpKind::Capability, "SampledCubeArray") => Some((45, &[])),
+        (GOpKind::Capability, "SampledBuffer") => Some((46, &[])),
+        (GOpKind::Capability, "ImageBuffer") => Some((47, &[])),
+        (GOpKind::Capability, "ImageMSArray") => Some((48, &[])),
+        (GOpKind::Capability, "StorageImageExtendedFormats") => Some((49, &[])),
+        (GOpKind::Capability, "ImageQuery") => Some((50, &[])),
+        (GOpKind::Capability, "DerivativeControl") => Some((51, &[])),
+        (GOpKind::Capability, "InterpolationFunction") => Some((52, &[])),
+        (GOpKind::Capability, "TransformFeedback") => Some((53, &[])),
+        (GOpKind::Capability, "GeometryStreams") => Some((54, &[])),
+        (GOpKind::Capability, "StorageImageReadWithoutFormat") => Some((55, &[])),
+        (GOpKind::Capability, "StorageImageWriteWithoutFormat") => Some((56, &[])),
+        (GOpKind::Capability, "MultiViewport") => Some((57, &[])),
+        (GOpKind::Capability, "SubgroupDispatch") => Some((58, &[])),
+        (GOpKind::Capability, "NamedBarrier") => Some((59, &[])),
+        (GOpKind::Capability, "PipeStorage") => Some((60, &[])),
+        (GOpKind::Capability, "GroupNonUniform") => Some((61, &[])),
+        (GOpKind::Capability, "GroupNonUniformVote") => Some((62, &[])),
+        (GOpKind::Capability, "GroupNonUniformBallot") => Some((64, &[])),
+        (GOpKind::Capability, "GroupNonUniformShuffle") => Some((65, &[])),
+        (GOpKind::Capability, "GroupNonUniformShuffleRelative") => Some((66, &[])),
+        (GOpKind::Capability, "SubgroupBallotKHR") => Some((4423, &[])),
+        (GOpKind::Capability, "DrawParameters") => Some((4427, &[])),
+        (GOpKind::Capability, "SubgroupVoteKHR") => Some((4431, &[])),
+        (GOpKind::Capability, "StorageBuffer16BitAccess") => Some((4433, &[])),
+        (GOpKind::Capability, "StorageUniformBufferBlock16") => Some((4433, &[])),
+        (GOpKind::Capability, "UniformAndStorageBuffer16BitAccess") => Some((4434, &[])),
+        (GOpKind::Capability, "StorageUniform16") => Some((4434, &[])),
+        (GOpKind::Capability, "StoragePushConstant16") => Some((4435, &[])),
+        (GOpKind::Capability, "StorageInputOutput16") => Some((4436, &[])),
+        (GOpKind::Capability, "DeviceGroup") => Some((4437, &[])),
+        (GOpKind::Capability, "MultiView") => Some((4439, &[])),
+        (GOpKind::Capability, "VariablePointersStorageBuffer") => Some((4441, &[])),
+        (GOpKind::Capability, "VariablePointers") => Some((4442, &[])),
+        (GOpKind::Capability, "AtomicStorageOps") => Some((4445, &[])),
+        (GOpKind::Capability, "SampleMaskPostDepthCoverage") => Some((4447, &[])),
+        (GOpKind::Capability, "StorageBuffer8BitAccess") => Some((4448, &[])),
+        (GOpKind::Capability, "UniformAndStorageBuffer8BitAccess") => Some((4449, &[])),
+        (GOpKind::Capability, "StoragePushConstant8") => Some((4450, &[])),
+        (GOpKind::Capability, "DenormPreserve") => Some((4464, &[])),
+        (GOpKind::Capability, "DenormFlushToZero") => Some((4465, &[])),
+        (GOpKind::Capability, "SignedZeroInfNanPreserve") => Some((4466, &[])),
+        (GOpKind::Capability, "RoundingModeRTE") => Some((4467, &[])),
+        (GOpKind::Capability, "RoundingModeRTZ") => Some((4468, &[])),
+        (GOpKind::Capability, "RayTracingKHR") => Some((4479, &[])),
+        (GOpKind::Capability, "ImageGatherBiasLodAMD") => Some((5009, &[])),
+        (GOpKind::Capability, "FragmentMaskAMD") => Some((5010, &[])),
+        (GOpKind::Capability, "StencilExportEXT") => Some((5013, &[])),
+        (GOpKind::Capability, "ImageReadWriteLodAMD") => Some((5015, &[])),
+        (GOpKind::Capability, "SampleMaskOverrideCoverageNV") => Some((5249, &[])),
+        (GOpKind::Capability, "GeometryShaderPassthroughNV") => Some((5251, &[])),
+        (GOpKind::Capability, "ShaderViewportIndexLayerEXT") => Some((5254, &[])),
+        (GOpKind::Capability, "ShaderViewportIndexLayerNV") => Some((5254, &[])),
+        (GOpKind::Capability, "ShaderViewportMaskNV") => Some((5255, &[])),
+        (GOpKind::Capability, "ShaderStereoViewNV") => Some((5259, &[])),
+        (GOpKind::Capability, "PerViewAttributesNV") => Some((5260, &[])),
+        (GOpKind::Capability, "MeshShadingEXT") => Some((5283, &[])),
+        (GOpKind::Capability, "ShaderNonUniform") => Some((5301, &[])),
+        (GOpKind::Capability, "RuntimeDescriptorArray") => Some((5302, &[])),
+        (GOpKind::Capability, "InputAttachmentArrayDynamicIndexing") => Some((5303, &[])),
+        (GOpKind::Capability, "UniformTexelBufferArrayDynamicIndexing") => Some((5304, &[])),
+        (GOpKind::Capability, "StorageTexelBufferArrayDynamicIndexing") => Some((5305, &[])),
+        (GOpKind::Capability, "UniformBufferArrayNonUniformIndexing") => Some((5306, &[])),
+        (GOpKind::Capability, "SampledImageArrayNonUniformIndexing") => Some((5307, &[])),
+        (GOpKind::Capability, "StorageBufferArrayNonUniformIndexing") => Some((5308, &[])),
+        (GOpKind::Capability, "StorageImageArrayNonUniformIndexing") => Some((5309, &[])),
+        (GOpKind::Capability, "InputAttachmentArrayNonUniformIndexing") => Some((5310, &[])),
+        (GOpKind::Capability, "UniformTexelBufferArrayNonUniformIndexing") => Some((5311, &[])),
+        (GOpKind::Capability, "StorageTexelBufferArrayNonUniformIndexing") => Some((5312, &[])),
+        (GOpKind::Capability, "VulkanMemoryModel") => Some((5345, &[])),
+        (GOpKind::Capability, "VulkanMemoryModelDeviceScope") => Some((5346, &[])),
+        (GOpKind::Capability, "PhysicalStorageBufferAddresses") => Some((5347, &[])),
+        (GOpKind::Capability, "DemoteToHelperInvocation") => Some((5379, &[])),
+        (GOpKind::Capability, "CooperativeMatrixKHR") => Some((6022, &[])),
+        (GOpKind::Capability, "AtomicFloat32AddEXT") => Some((6033, &[])),
+        (GOpKind::Capability, "AtomicFloat64AddEXT") => Some((6034, &[])),
+        (GOpKind::CooperativeMatrixLayout, "RowMajorKHR") => Some((0, &[])),
+        (GOpKind::CooperativeMatrixLayout, "ColumnMajorKHR") => Some((1, &[])),
+        (GOpKind::CooperativeMatrixUse, "MatrixAKHR") => Some((0, &[])),
+        (GOpKind::CooperativeMatrixUse, "MatrixBKHR") => Some((1, &[])),
+        (GOpKind::CooperativeMatrixUse, "MatrixAccumulatorKHR") => Some((2, &[])),
+        _ => None,
+    }
+}
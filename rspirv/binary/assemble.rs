@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use mr;
+use prelude::*;
 
 use utils::num::{bytes_to_u32_le, f32_to_u32};
 
@@ -28,16 +29,36 @@ impl Assemble for mr::ModuleHeader {
     }
 }
 
-fn assemble_str(s: &str) -> Vec<u32> {
-    let bytes = s.as_bytes();
-    let len = (bytes.len() + 3) >> 2;
-    let mut words: Vec<u32> = (0..len).map(|i| bytes_to_u32_le(&bytes[(i << 2)..])).collect();
-    if bytes.len() % 4 == 0 {
+/// Returns the number of words a literal string of `len` bytes occupies,
+/// including its null terminator and padding.
+pub fn string_word_count(len: usize) -> usize {
+    (len >> 2) + 1
+}
+
+/// Encodes the given bytes as a literal string.
+///
+/// The bytes are null-terminated and padded with nulls to a word boundary.
+/// A SPIR-V string cannot hold an embedded null, so the bytes are cut at
+/// the first null to keep the rest of the instruction decodable.
+pub fn assemble_bytes(bytes: &[u8]) -> Vec<u32> {
+    let bytes = match bytes.iter().position(|&b| b == 0) {
+        Some(nul) => &bytes[..nul],
+        None => bytes,
+    };
+    let mut words: Vec<u32> = bytes.chunks(4).map(bytes_to_u32_le).collect();
+    if words.len() < string_word_count(bytes.len()) {
         words.push(0)
     }
     words
 }
 
+/// Encodes the given UTF-8 string as a literal string.
+///
+/// See [`assemble_bytes`](fn.assemble_bytes.html) for the encoding.
+pub fn assemble_str(s: &str) -> Vec<u32> {
+    assemble_bytes(s.as_bytes())
+}
+
 impl Assemble for mr::Operand {
     fn assemble(&self) -> Vec<u32> {
         match *self {
@@ -49,6 +70,7 @@ impl Assemble for mr::Operand {
             mr::Operand::MemorySemantics(v) => vec![v.bits()],
             mr::Operand::MemoryAccess(v) => vec![v.bits()],
             mr::Operand::KernelProfilingInfo(v) => vec![v.bits()],
+            mr::Operand::CooperativeMatrixOperands(v) => vec![v.bits()],
             mr::Operand::SourceLanguage(v) => vec![v as u32],
             mr::Operand::ExecutionModel(v) => vec![v as u32],
             mr::Operand::AddressingModel(v) => vec![v as u32],
@@ -71,14 +93,20 @@ impl Assemble for mr::Operand {
             mr::Operand::GroupOperation(v) => vec![v as u32],
             mr::Operand::KernelEnqueueFlags(v) => vec![v as u32],
             mr::Operand::Capability(v) => vec![v as u32],
+            mr::Operand::CooperativeMatrixLayout(v) => vec![v as u32],
+            mr::Operand::CooperativeMatrixUse(v) => vec![v as u32],
             mr::Operand::IdMemorySemantics(v) |
             mr::Operand::IdScope(v) |
             mr::Operand::IdRef(v) |
             mr::Operand::LiteralInt32(v) |
-            mr::Operand::LiteralExtInstInteger(v) => vec![v],
-            mr::Operand::LiteralInt64(_) => unimplemented!(),
+            mr::Operand::LiteralExtInstInteger(v) |
+            mr::Operand::ExtInstOpcode(_, v) => vec![v],
+            mr::Operand::LiteralInt64(v) => vec![v as u32, (v >> 32) as u32],
             mr::Operand::LiteralFloat32(v) => vec![f32_to_u32(v)],
-            mr::Operand::LiteralFloat64(_) => unimplemented!(),
+            mr::Operand::LiteralFloat64(v) => {
+                let v = v.to_bits();
+                vec![v as u32, (v >> 32) as u32]
+            }
             mr::Operand::LiteralSpecConstantOpInteger(v) => vec![v as u32],
             mr::Operand::LiteralString(ref v) => assemble_str(v),
         }
@@ -87,6 +115,9 @@ impl Assemble for mr::Operand {
 
 impl Assemble for mr::Instruction {
     fn assemble(&self) -> Vec<u32> {
+        if let Some(ref words) = self.raw_words {
+            return words.clone();
+        }
         let mut code = vec![self.class.opcode as u32];
         if let Some(r) = self.result_type {
             code.push(r);
@@ -150,13 +181,29 @@ impl Assemble for mr::Module {
     }
 }
 
+/// An instruction of a module in its logical layout.
+/// Calls `f` on all instructions of `module` in its logical layout.
+pub(crate) fn visit_layout<'m>(module: &'m mr::Module, f: &mut dyn FnMut(&'m mr::Instruction)) {
+    let function_insts = module.functions.iter().flat_map(|f| {
+        f.def
+            .iter()
+            .chain(&f.parameters)
+            .chain(f.basic_blocks.iter().flat_map(|bb| bb.label.iter().chain(&bb.instructions)))
+            .chain(&f.end)
+    });
+    for inst in module.global_inst_iter().chain(function_insts) {
+        f(inst);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use mr;
     use spirv;
 
-    use binary::Assemble;
-    use super::{assemble_str, bytes_to_u32_le};
+    use binary::{Assemble, Decoder};
+    use utils::num::u32_to_bytes;
+    use super::{assemble_bytes, assemble_str, bytes_to_u32_le, string_word_count};
 
     #[test]
     fn test_assemble_str() {
@@ -167,6 +214,32 @@ mod tests {
                    assemble_str("hello"));
     }
 
+    #[test]
+    fn test_assemble_bytes() {
+        assert_eq!(vec![bytes_to_u32_le(b"\xff\xfe\0\0")], assemble_bytes(b"\xff\xfe"));
+        // Everything from an embedded null on is dropped.
+        assert_eq!(assemble_str("ok"), assemble_bytes(b"ok\0rust"));
+        assert_eq!(assemble_str("ok"), mr::Operand::from("ok\0rust").assemble());
+        assert_eq!(vec![0u32], assemble_bytes(b"\0"));
+        for len in 0..9 {
+            let bytes = vec![b'a'; len];
+            assert_eq!(string_word_count(len), assemble_bytes(&bytes).len());
+        }
+    }
+
+    #[test]
+    fn test_assemble_decode_string_roundtrip() {
+        for s in &["", "a", "abc", "abcd", "h\u{e9}llo", "\u{1f980}\u{1f980}"] {
+            let bytes: Vec<u8> = assemble_str(s)
+                .iter()
+                .flat_map(|w| u32_to_bytes(*w))
+                .collect();
+            let mut d = Decoder::new(&bytes);
+            assert_eq!(Ok(s.to_string()), d.string());
+            assert_eq!(bytes.len(), d.offset());
+        }
+    }
+
     #[test]
     fn test_assemble_operand_bitmask() {
         let v = spirv::FunctionControl::DONT_INLINE;
@@ -192,6 +265,13 @@ mod tests {
                    mr::Operand::BuiltIn(spirv::BuiltIn::InstanceId).assemble());
     }
 
+    #[test]
+    fn test_assemble_operand_64bit() {
+        assert_eq!(vec![0x89abcdef, 0x01234567],
+                   mr::Operand::LiteralInt64(0x0123456789abcdef).assemble());
+        assert_eq!(vec![0, 0x3ff00000], mr::Operand::LiteralFloat64(1.0).assemble());
+    }
+
     fn wc_op(wc: u32, op: spirv::Op) -> u32 {
         (wc << 16) | op as u32
     }
@@ -16,7 +16,7 @@
 //   external/spirv.core.grammar.json.
 // DO NOT MODIFY!
 
-use num::FromPrimitive;
+use num_traits::FromPrimitive;
 
 impl<'a> Decoder<'a> {
     /// Decodes and returns the next SPIR-V word as
@@ -99,6 +99,16 @@ impl<'a> Decoder<'a> {
         }
     }
 
+    /// Decodes and returns the next SPIR-V word as
+    /// a SPIR-V CooperativeMatrixOperands value.
+    pub fn cooperative_matrix_operands(&mut self) -> Result<spirv::CooperativeMatrixOperands> {
+        if let Ok(word) = self.word() {
+            spirv::CooperativeMatrixOperands::from_bits(word).ok_or(Error::CooperativeMatrixOperandsUnknown(self.offset - WORD_NUM_BYTES, word))
+        } else {
+            Err(Error::StreamExpected(self.offset))
+        }
+    }
+
     /// Decodes and returns the next SPIR-V word as
     /// a SPIR-V SourceLanguage value.
     pub fn source_language(&mut self) -> Result<spirv::SourceLanguage> {
@@ -318,4 +328,24 @@ impl<'a> Decoder<'a> {
             Err(Error::StreamExpected(self.offset))
         }
     }
+
+    /// Decodes and returns the next SPIR-V word as
+    /// a SPIR-V CooperativeMatrixLayout value.
+    pub fn cooperative_matrix_layout(&mut self) -> Result<spirv::CooperativeMatrixLayout> {
+        if let Ok(word) = self.word() {
+            spirv::CooperativeMatrixLayout::from_u32(word).ok_or(Error::CooperativeMatrixLayoutUnknown(self.offset - WORD_NUM_BYTES, word))
+        } else {
+            Err(Error::StreamExpected(self.offset))
+        }
+    }
+
+    /// Decodes and returns the next SPIR-V word as
+    /// a SPIR-V CooperativeMatrixUse value.
+    pub fn cooperative_matrix_use(&mut self) -> Result<spirv::CooperativeMatrixUse> {
+        if let Ok(word) = self.word() {
+            spirv::CooperativeMatrixUse::from_u32(word).ok_or(Error::CooperativeMatrixUseUnknown(self.offset - WORD_NUM_BYTES, word))
+        } else {
+            Err(Error::StreamExpected(self.offset))
+        }
+    }
 }
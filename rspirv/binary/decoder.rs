@@ -12,12 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use prelude::*;
 use spirv;
 
-use std::{mem, result};
+use std::{mem, result, str};
 use super::error::Error;
 
-use utils::num::u32_to_bytes;
 
 pub type Result<T> = result::Result<T, Error>;
 
@@ -77,6 +77,7 @@ const WORD_NUM_BYTES: usize = 4;
 ///     assert_eq!(Err(DecodeError::StreamExpected(12)), d.word());
 /// }
 /// ```
+#[derive(Clone)]
 pub struct Decoder<'a> {
     /// Raw bytes to decode
     bytes: &'a [u8],
@@ -173,22 +174,55 @@ impl<'a> Decoder<'a> {
     ///
     /// This method will consume as many words as necessary until finding a
     /// null character (`\0`), or reaching the limit or end of the stream
-    /// and erroring out.
+    /// and erroring out. Strings that are not valid UTF-8 are reported as
+    /// `InvalidUtf8String`; use [`string_bytes`](#method.string_bytes) to
+    /// read them losslessly.
     pub fn string(&mut self) -> Result<String> {
         let start_offset = self.offset;
+        let bytes = self.string_bytes()?;
+        String::from_utf8(bytes).map_err(|e| {
+            Error::InvalidUtf8String(start_offset, e.utf8_error().valid_up_to())
+        })
+    }
+
+    /// Decodes and returns the raw bytes of a literal string.
+    ///
+    /// Consumes words the same way as [`string`](#method.string), but does
+    /// not require the bytes to be UTF-8. The returned bytes exclude the
+    /// null terminator and the padding after it.
+    pub fn string_bytes(&mut self) -> Result<Vec<u8>> {
         let mut bytes = vec![];
+        self.append_string_bytes(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Decodes a literal string into `buf`, replacing its contents, and
+    /// returns it.
+    ///
+    /// Consumes words the same way as [`string`](#method.string), but lets
+    /// callers decoding many strings reuse one buffer instead of allocating
+    /// a new `String` for each.
+    pub fn string_into<'b>(&mut self, buf: &'b mut Vec<u8>) -> Result<&'b str> {
+        let start_offset = self.offset;
+        buf.clear();
+        self.append_string_bytes(buf)?;
+        str::from_utf8(buf).map_err(|e| Error::InvalidUtf8String(start_offset, e.valid_up_to()))
+    }
+
+    /// Appends the bytes of the next literal string to `bytes`, growing it
+    /// as the words are read.
+    fn append_string_bytes(&mut self, bytes: &mut Vec<u8>) -> Result<()> {
         loop {
             let word = self.word()?;
-            bytes.append(&mut u32_to_bytes(word));
-            if bytes.last() == Some(&0) {
-                break;
+            let word_bytes = word.to_le_bytes();
+            match word_bytes.iter().position(|&b| b == 0) {
+                Some(nul) => {
+                    bytes.extend_from_slice(&word_bytes[..nul]);
+                    return Ok(());
+                }
+                None => bytes.extend_from_slice(&word_bytes),
             }
         }
-        while !bytes.is_empty() && bytes.last() == Some(&0) {
-            bytes.pop();
-        }
-        String::from_utf8(bytes)
-            .map_err(|e| Error::DecodeStringFailed(start_offset, format!("{}", e)))
     }
 
     /// Decodes and returns the next SPIR-V word as a 32-bit
@@ -310,6 +344,41 @@ mod tests {
             let mut d = Decoder::new(&b);
             assert_eq!(Ok("I..don't know..".to_string()), d.string());
         }
+        {
+            // The string ends at the first null, whatever the padding holds.
+            let b = b"ok\0xnext\0\0\0\0".to_vec();
+            let mut d = Decoder::new(&b);
+            assert_eq!(Ok("ok".to_string()), d.string());
+            assert_eq!(Ok("next".to_string()), d.string());
+        }
+        {
+            let b = "h\u{e9}\0\0".as_bytes().to_vec();
+            let mut d = Decoder::new(&b);
+            assert_eq!(Ok("h\u{e9}".to_string()), d.string());
+        }
+    }
+
+    #[test]
+    fn test_decoding_invalid_utf8_string() {
+        let b = b"\0\0\0\0ab\xffc\0\0\0\0".to_vec();
+        {
+            let mut d = Decoder::new(&b);
+            assert_eq!(Ok(String::new()), d.string());
+            assert_eq!(Err(Error::InvalidUtf8String(4, 2)), d.string());
+        }
+        {
+            let mut d = Decoder::new(&b);
+            assert_eq!(Ok(vec![]), d.string_bytes());
+            assert_eq!(Ok(b"ab\xffc".to_vec()), d.string_bytes());
+            assert_eq!(Err(Error::StreamExpected(12)), d.string_bytes());
+        }
+        {
+            let mut d = Decoder::new(&b);
+            let mut buf = b"junk".to_vec();
+            assert_eq!(Ok(""), d.string_into(&mut buf));
+            assert_eq!(Err(Error::InvalidUtf8String(4, 2)), d.string_into(&mut buf));
+            assert_eq!(buf, b"ab\xffc");
+        }
     }
 
     #[test]
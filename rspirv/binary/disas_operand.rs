@@ -28,6 +28,10 @@ impl Disassemble for spirv::ImageOperands {
         if self.contains(spirv::ImageOperands::CONST_OFFSETS) { bits.push("ConstOffsets") }
         if self.contains(spirv::ImageOperands::SAMPLE) { bits.push("Sample") }
         if self.contains(spirv::ImageOperands::MIN_LOD) { bits.push("MinLod") }
+        if self.contains(spirv::ImageOperands::MAKE_TEXEL_AVAILABLE) { bits.push("MakeTexelAvailable") }
+        if self.contains(spirv::ImageOperands::MAKE_TEXEL_VISIBLE) { bits.push("MakeTexelVisible") }
+        if self.contains(spirv::ImageOperands::NON_PRIVATE_TEXEL) { bits.push("NonPrivateTexel") }
+        if self.contains(spirv::ImageOperands::VOLATILE_TEXEL) { bits.push("VolatileTexel") }
         bits.join("|")
     }
 }
@@ -93,6 +97,10 @@ impl Disassemble for spirv::MemorySemantics {
         if self.contains(spirv::MemorySemantics::CROSS_WORKGROUP_MEMORY) { bits.push("CrossWorkgroupMemory") }
         if self.contains(spirv::MemorySemantics::ATOMIC_COUNTER_MEMORY) { bits.push("AtomicCounterMemory") }
         if self.contains(spirv::MemorySemantics::IMAGE_MEMORY) { bits.push("ImageMemory") }
+        if self.contains(spirv::MemorySemantics::OUTPUT_MEMORY) { bits.push("OutputMemory") }
+        if self.contains(spirv::MemorySemantics::MAKE_AVAILABLE) { bits.push("MakeAvailable") }
+        if self.contains(spirv::MemorySemantics::MAKE_VISIBLE) { bits.push("MakeVisible") }
+        if self.contains(spirv::MemorySemantics::VOLATILE) { bits.push("Volatile") }
         bits.join("|")
     }
 }
@@ -104,6 +112,9 @@ impl Disassemble for spirv::MemoryAccess {
         if self.contains(spirv::MemoryAccess::VOLATILE) { bits.push("Volatile") }
         if self.contains(spirv::MemoryAccess::ALIGNED) { bits.push("Aligned") }
         if self.contains(spirv::MemoryAccess::NONTEMPORAL) { bits.push("Nontemporal") }
+        if self.contains(spirv::MemoryAccess::MAKE_POINTER_AVAILABLE) { bits.push("MakePointerAvailable") }
+        if self.contains(spirv::MemoryAccess::MAKE_POINTER_VISIBLE) { bits.push("MakePointerVisible") }
+        if self.contains(spirv::MemoryAccess::NON_PRIVATE_POINTER) { bits.push("NonPrivatePointer") }
         bits.join("|")
     }
 }
@@ -115,4 +126,17 @@ impl Disassemble for spirv::KernelProfilingInfo {
         if self.contains(spirv::KernelProfilingInfo::CMD_EXEC_TIME) { bits.push("CmdExecTime") }
         bits.join("|")
     }
+}
+
+impl Disassemble for spirv::CooperativeMatrixOperands {
+    fn disassemble(&self) -> String {
+        if self.is_empty() { return "None".to_string() }
+        let mut bits = vec![];
+        if self.contains(spirv::CooperativeMatrixOperands::MATRIX_ASIGNED_COMPONENTS_KHR) { bits.push("MatrixASignedComponentsKHR") }
+        if self.contains(spirv::CooperativeMatrixOperands::MATRIX_BSIGNED_COMPONENTS_KHR) { bits.push("MatrixBSignedComponentsKHR") }
+        if self.contains(spirv::CooperativeMatrixOperands::MATRIX_CSIGNED_COMPONENTS_KHR) { bits.push("MatrixCSignedComponentsKHR") }
+        if self.contains(spirv::CooperativeMatrixOperands::MATRIX_RESULT_SIGNED_COMPONENTS_KHR) { bits.push("MatrixResultSignedComponentsKHR") }
+        if self.contains(spirv::CooperativeMatrixOperands::SATURATING_ACCUMULATION_KHR) { bits.push("SaturatingAccumulationKHR") }
+        bits.join("|")
+    }
 }
\ No newline at end of file
@@ -12,10 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use grammar;
+use grammar::reflect;
 use mr;
+use prelude::*;
 use spirv;
 
-use super::tracker;
+use std::collections::BTreeMap;
 
 /// Trait for disassembling functionalities.
 pub trait Disassemble {
@@ -67,10 +70,14 @@ fn disas_join<T: Disassemble>(insts: &[T], delimiter: &str) -> String {
 
 impl Disassemble for mr::Instruction {
     fn disassemble(&self) -> String {
-        format!("{rid}{opcode}{rtype}{space}{operands}",
+        if let Some(ref words) = self.raw_words {
+            let opcode = words.first().map_or(0, |word| word & 0xffff);
+            return format!("; unknown instruction with opcode {}", opcode);
+        }
+        format!("{rid}Op{opcode}{rtype}{space}{operands}",
                 rid = self.result_id
                           .map_or(String::new(), |w| format!("%{} = ", w)),
-                opcode = format!("Op{}", self.class.opname),
+                opcode = self.class.opname,
                 // extra space both before and after the reseult type
                 rtype = self.result_type
                             .map_or(String::new(), |w| format!("  %{} ", w)),
@@ -127,79 +134,223 @@ macro_rules! push {
 
 impl Disassemble for mr::Module {
     fn disassemble(&self) -> String {
-        let mut ext_inst_set_tracker = tracker::ExtInstSetTracker::new();
-        for i in &self.ext_inst_imports {
-            ext_inst_set_tracker.track(i)
-        }
+        disassemble_with(self, &DisasOptions::default())
+    }
+}
 
-        let mut text = vec![];
-        if let Some(ref header) = self.header {
-            push!(&mut text, header.disassemble());
+/// Options for [`disassemble_with`](fn.disassemble_with.html).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DisasOptions {
+    /// Whether instructions with a result type are followed by a comment
+    /// spelling out the type, e.g., `; vec4<f32>` or `; ptr<Uniform, UBO>`.
+    pub type_comments: bool,
+}
+
+/// Disassembles `module` with the given `options` and returns the
+/// disassembly text.
+///
+/// Type comments name scalars like `f32` and `u32`, compose vectors,
+/// matrices, arrays, pointers, and functions like `vec4<f32>`,
+/// `mat4x3<f32>`, `array<u32, 4>`, `ptr<Function, f32>`, and
+/// `fn(f32) -> void`, and name structs by their `OpName`, if any.
+pub fn disassemble_with(module: &mr::Module, options: &DisasOptions) -> String {
+    let ext_insts = mr::ExtInstRegistry::of(module);
+    let types = if options.type_comments {
+        Some(TypeNames::of(module))
+    } else {
+        None
+    };
+    let disas = |inst: &mr::Instruction| {
+        let text = match inst.class.opcode {
+            spirv::Op::ExtInst => disas_ext_inst(inst, &ext_insts),
+            _ => inst.disassemble(),
+        };
+        match (&types, inst.result_type) {
+            (Some(types), Some(ty)) => format!("{} ; {}", text, types.name(ty)),
+            _ => text,
         }
+    };
+
+    let mut text = vec![];
+    if let Some(ref header) = module.header {
+        push!(&mut text, header.disassemble());
+    }
 
-        let global_insts = self.global_inst_iter()
-                               .map(|i| i.disassemble())
-                               .collect::<Vec<String>>()
-                               .join("\n");
-        push!(&mut text, global_insts);
+    let global_insts = module.global_inst_iter()
+                             .map(&disas)
+                             .collect::<Vec<String>>()
+                             .join("\n");
+    push!(&mut text, global_insts);
 
-        // TODO: Code here is essentially duplicated.
-        for f in &self.functions {
+    for f in &module.functions {
+        push!(&mut text, f.def.as_ref().map_or(String::new(), &disas));
+        for param in &f.parameters {
+            push!(&mut text, disas(param));
+        }
+        for bb in &f.basic_blocks {
             push!(&mut text,
-                  f.def.as_ref().map_or(String::new(), |i| i.disassemble()));
-            push!(&mut text, disas_join(&f.parameters, "\n"));
-            for bb in &f.basic_blocks {
-                push!(&mut text,
-                      bb.label
-                        .as_ref()
-                        .map_or(String::new(), |i| i.disassemble()));
-                for inst in &bb.instructions {
-                    match inst.class.opcode {
-                        spirv::Op::ExtInst => {
-                            push!(&mut text, disas_ext_inst(inst, &ext_inst_set_tracker))
-                        }
-                        _ => push!(&mut text, inst.disassemble()),
-                    }
+                  bb.label
+                    .as_ref()
+                    .map_or(String::new(), |i| i.disassemble()));
+            for inst in &bb.instructions {
+                push!(&mut text, disas(inst));
+            }
+        }
+        push!(&mut text,
+              f.end.as_ref().map_or(String::new(), |i| i.disassemble()));
+    }
+
+    text.join("\n")
+}
+
+/// Spells out the types of a module for type comments.
+struct TypeNames<'m> {
+    types: BTreeMap<spirv::Word, &'m mr::Instruction>,
+    constants: BTreeMap<spirv::Word, u64>,
+    names: BTreeMap<spirv::Word, &'m str>,
+}
+
+impl<'m> TypeNames<'m> {
+    fn of(module: &'m mr::Module) -> TypeNames<'m> {
+        let mut types = BTreeMap::new();
+        let mut constants = BTreeMap::new();
+        for inst in &module.types_global_values {
+            match (inst.class.opcode, inst.result_id, inst.operands.first()) {
+                (spirv::Op::Constant, Some(id), Some(&mr::Operand::LiteralInt32(v))) => {
+                    constants.insert(id, u64::from(v));
                 }
+                (spirv::Op::Constant, Some(id), Some(&mr::Operand::LiteralInt64(v))) => {
+                    constants.insert(id, v);
+                }
+                (opcode, Some(id), _) if reflect::is_type(opcode) => {
+                    types.insert(id, inst);
+                }
+                _ => (),
             }
-            push!(&mut text,
-                  f.end.as_ref().map_or(String::new(), |i| i.disassemble()));
         }
+        let names = module.debugs
+                          .iter()
+                          .filter(|inst| inst.class.opcode == spirv::Op::Name)
+                          .filter_map(|inst| match (inst.operands.first(), inst.operands.get(1)) {
+                              (Some(&mr::Operand::IdRef(id)),
+                               Some(mr::Operand::LiteralString(name))) => {
+                                  Some((id, name.as_str()))
+                              }
+                              _ => None,
+                          })
+                          .collect();
+        TypeNames { types, constants, names }
+    }
 
-        text.join("\n")
+    fn name(&self, id: spirv::Word) -> String {
+        // Forward pointers could loop, so give up at some depth.
+        self.name_within(id, 16)
     }
-}
 
-fn disas_ext_inst(inst: &mr::Instruction,
-                  ext_inst_set_tracker: &tracker::ExtInstSetTracker)
-                  -> String {
-    if inst.operands.len() < 2 {
-        return inst.disassemble();
+    fn name_within(&self, id: spirv::Word, depth: usize) -> String {
+        let inst = match self.types.get(&id) {
+            Some(inst) if depth > 0 => inst,
+            _ => return format!("%{}", id),
+        };
+        let name = |operand: Option<&mr::Operand>| match operand {
+            Some(&mr::Operand::IdRef(id)) => self.name_within(id, depth - 1),
+            _ => "?".to_string(),
+        };
+        let int = |operand: Option<&mr::Operand>| match operand {
+            Some(&mr::Operand::LiteralInt32(v)) => u64::from(v),
+            _ => 0,
+        };
+        let ops = &inst.operands;
+        match inst.class.opcode {
+            spirv::Op::TypeVoid => "void".to_string(),
+            spirv::Op::TypeBool => "bool".to_string(),
+            spirv::Op::TypeInt => {
+                let sign = if int(ops.get(1)) == 1 { "i" } else { "u" };
+                format!("{}{}", sign, int(ops.first()))
+            }
+            spirv::Op::TypeFloat => format!("f{}", int(ops.first())),
+            spirv::Op::TypeVector => format!("vec{}<{}>", int(ops.get(1)), name(ops.first())),
+            spirv::Op::TypeMatrix => {
+                let column = ops.first().and_then(mr::Operand::id).and_then(|c| self.types.get(&c));
+                match column {
+                    Some(column) if column.class.opcode == spirv::Op::TypeVector => {
+                        format!("mat{}x{}<{}>",
+                                int(ops.get(1)),
+                                int(column.operands.get(1)),
+                                name(column.operands.first()))
+                    }
+                    _ => format!("mat{}<{}>", int(ops.get(1)), name(ops.first())),
+                }
+            }
+            spirv::Op::TypeArray => {
+                let length = match ops.get(1) {
+                    Some(&mr::Operand::IdRef(length)) => match self.constants.get(&length) {
+                        Some(v) => v.to_string(),
+                        None => format!("%{}", length),
+                    },
+                    _ => "?".to_string(),
+                };
+                format!("array<{}, {}>", name(ops.first()), length)
+            }
+            spirv::Op::TypeRuntimeArray => format!("array<{}>", name(ops.first())),
+            spirv::Op::TypePointer => {
+                format!("ptr<{}, {}>", ops.first().map_or(String::new(), |c| c.disassemble()),
+                        name(ops.get(1)))
+            }
+            spirv::Op::TypeStruct => match self.names.get(&id) {
+                Some(n) => n.to_string(),
+                None => {
+                    let members: Vec<String> = ops.iter().map(|m| name(Some(m))).collect();
+                    format!("struct{{{}}}", members.join(", "))
+                }
+            },
+            spirv::Op::TypeFunction => {
+                let params: Vec<String> = ops.iter().skip(1).map(|p| name(Some(p))).collect();
+                format!("fn({}) -> {}", params.join(", "), name(ops.first()))
+            }
+            spirv::Op::TypeImage => {
+                format!("image<{}, {}>",
+                        name(ops.first()),
+                        ops.get(1).map_or(String::new(), |d| d.disassemble()))
+            }
+            spirv::Op::TypeSampledImage => format!("sampled<{}>", name(ops.first())),
+            spirv::Op::TypeSampler => "sampler".to_string(),
+            opcode => match self.names.get(&id) {
+                Some(n) => n.to_string(),
+                None => {
+                    let opname = grammar::CoreInstructionTable::get(opcode).opname;
+                    opname.trim_start_matches("Type").to_string()
+                }
+            },
+        }
     }
-    if let (&mr::Operand::IdRef(id), &mr::Operand::LiteralExtInstInteger(opcode)) =
-           (&inst.operands[0], &inst.operands[1]) {
-        if !ext_inst_set_tracker.have(id) {
-            return inst.disassemble();
+}
+
+fn disas_ext_inst(inst: &mr::Instruction, ext_insts: &mr::ExtInstRegistry) -> String {
+    let grammar = match (inst.operands.first(), inst.operands.get(1)) {
+        (_, Some(&mr::Operand::ExtInstOpcode(set, opcode))) => set.lookup_opcode(opcode),
+        (Some(&mr::Operand::IdRef(id)), Some(&mr::Operand::LiteralExtInstInteger(opcode))) => {
+            ext_insts.resolve(id, opcode)
         }
-        if let Some(grammar) = ext_inst_set_tracker.resolve(id, opcode) {
+        _ => None,
+    };
+    match grammar {
+        Some(grammar) => {
             let mut operands = vec![];
             operands.push(inst.operands[0].disassemble());
             operands.push(grammar.opname.to_string());
             for operand in &inst.operands[2..] {
                 operands.push(operand.disassemble())
             }
-            format!("{rid}{opcode}{rtype} {operands}",
+            format!("{rid}Op{opcode}{rtype} {operands}",
                     rid = inst.result_id
                               .map_or(String::new(), |w| format!("%{} = ", w)),
-                    opcode = format!("Op{}", inst.class.opname),
+                    opcode = inst.class.opname,
                     rtype = inst.result_type
                                 .map_or(String::new(), |w| format!("  %{} ", w)),
                     operands = operands.join(" "))
-        } else {
-            inst.disassemble()
         }
-    } else {
-        inst.disassemble()
+        None => inst.disassemble(),
     }
 }
 
@@ -209,6 +360,7 @@ mod tests {
     use spirv;
 
     use binary::Disassemble;
+    use super::{disassemble_with, DisasOptions};
 
     #[test]
     fn test_disassemble_operand_function_control() {
@@ -236,6 +388,12 @@ mod tests {
         assert_eq!("Release|WorkgroupMemory", o.disassemble());
     }
 
+    #[test]
+    fn test_disassemble_raw_instruction() {
+        let inst = mr::Instruction::raw(vec![(2 << 16) | 0x7ff0, 7]);
+        assert_eq!("; unknown instruction with opcode 32752", inst.disassemble());
+    }
+
     #[test]
     fn test_disassemble_module_one_inst_in_each_section() {
         let mut b = mr::Builder::new();
@@ -252,8 +410,8 @@ mod tests {
 
         let f = b.begin_function(void,
                                  None,
-                                 (spirv::FunctionControl::DONT_INLINE |
-                                  spirv::FunctionControl::CONST),
+                                 spirv::FunctionControl::DONT_INLINE |
+                                 spirv::FunctionControl::CONST,
                                  voidfvoid)
                  .unwrap();
         b.begin_basic_block(None).unwrap();
@@ -363,4 +521,72 @@ mod tests {
                     OpReturn\n\
                     OpFunctionEnd");
     }
+
+    #[test]
+    fn test_disassemble_ext_inst_non_semantic() {
+        let mut b = mr::Builder::new();
+
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let voidfvoid = b.type_function(void, vec![void]);
+        let name = b.string("main");
+        let uint = b.type_int(32, 0);
+        let value = b.constant_u32(uint, 7);
+
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.debug_printf(void, name, vec![value]);
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.clspv_kernel(void, main, name, None, None, None);
+
+        let module = b.module();
+        let printf = module.ext_inst_imports[0].result_id.unwrap();
+        let clspv = module.ext_inst_imports[1].result_id.unwrap();
+        let dis = module.disassemble();
+        assert!(dis.contains("OpExtension \"SPV_KHR_non_semantic_info\"\n"));
+        assert!(dis.contains(&format!("%{} = OpExtInstImport \"NonSemantic.DebugPrintf\"\n", printf)));
+        assert!(dis.contains(&format!("%{} = OpExtInstImport \"NonSemantic.ClspvReflection.6\"\n",
+                                      clspv)));
+        assert!(dis.contains(&format!("OpExtInst  %{}  %{} DebugPrintf %{} %{}\n",
+                                      void, printf, name, value)));
+        assert!(dis.contains(&format!("OpExtInst  %{}  %{} Kernel %{} %{}\n",
+                                      void, clspv, main, name)));
+    }
+
+    #[test]
+    fn test_disassemble_type_comments() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let uint = b.type_int(32, 0);
+        let vec4 = b.type_vector(float, 4);
+        let vec3 = b.type_vector(float, 3);
+        let mat = b.type_matrix(vec3, 4);
+        let four = b.constant_u32(uint, 4);
+        let array = b.type_array(vec4, four);
+        let ubo = b.type_struct(vec![mat, array]);
+        b.name(ubo, "MyUBO");
+        let ptr = b.type_pointer(None, spirv::StorageClass::Uniform, ubo);
+        let var = b.variable(ptr, None, spirv::StorageClass::Uniform, None);
+        let fty = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let elem = b.type_pointer(None, spirv::StorageClass::Uniform, mat);
+        let zero = b.constant_u32(uint, 0);
+        b.access_chain(elem, None, var, vec![zero]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let m = b.module();
+
+        let options = DisasOptions { type_comments: true };
+        let dis = disassemble_with(&m, &options);
+        assert!(dis.contains(&format!("%{} = OpConstant  %{}  4 ; u32\n", four, uint)));
+        assert!(dis.contains(&format!("OpVariable  %{}  Uniform ; ptr<Uniform, MyUBO>\n", ptr)));
+        assert!(dis.contains(&format!("OpFunction  %{}  None %{} ; void\n", void, fty)));
+        assert!(dis.contains("; ptr<Uniform, mat4x3<f32>>\n"));
+        assert_eq!(dis.lines().filter(|line| line.contains(';')).count(), 4 + 5);
+        assert_eq!(disassemble_with(&m, &DisasOptions::default()), m.disassemble());
+        assert!(!m.disassemble().contains(" ; "));
+    }
 }
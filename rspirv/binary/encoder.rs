@@ -0,0 +1,237 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use std::io;
+
+use super::assemble::{visit_layout, Assemble};
+
+/// The byte offset of the bound in the module header.
+const BOUND_OFFSET: u64 = 12;
+
+/// The SPIR-V binary encoder.
+///
+/// Writes the module header and instructions to any `io::Write` sink as
+/// they come, instead of assembling the whole module in memory first.
+/// Words are written in little-endian byte order.
+///
+/// The bound in the header may not be known when it is written. The
+/// encoder keeps track of the bound the encoded instructions require; with
+/// a seekable sink, [`finish`](#method.finish) patches it into the header.
+/// Otherwise, the bound has to be known up front, as
+/// [`encode_module`](fn.encode_module.html) does by walking the module
+/// twice.
+///
+/// # Examples
+///
+/// ```
+/// extern crate rspirv;
+/// extern crate spirv_headers as spirv;
+///
+/// use rspirv::binary::Encoder;
+/// use std::io::Cursor;
+///
+/// fn main() {
+///     let mut b = rspirv::mr::Builder::new();
+///     b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+///     b.type_void();
+///     let module = b.module();
+///
+///     // Start with an unknown bound and patch it in at the end.
+///     let mut encoder = Encoder::new(Cursor::new(vec![]),
+///                                    &rspirv::mr::ModuleHeader::new(0)).unwrap();
+///     for inst in module.global_inst_iter() {
+///         encoder.encode(inst).unwrap();
+///     }
+///     let bytes = encoder.finish().unwrap().into_inner();
+///
+///     let loaded = rspirv::mr::load_bytes(&bytes).unwrap();
+///     assert_eq!(loaded.header.unwrap().bound, 2);
+/// }
+/// ```
+pub struct Encoder<W> {
+    sink: W,
+    /// The bound written in the header
+    header_bound: spirv::Word,
+    /// The bound required by the instructions encoded so far
+    bound: spirv::Word,
+    /// The number of bytes written so far
+    written: u64,
+}
+
+impl<W: io::Write> Encoder<W> {
+    /// Creates a new encoder writing to `sink`, and writes the given module
+    /// `header` to it.
+    pub fn new(sink: W, header: &mr::ModuleHeader) -> io::Result<Encoder<W>> {
+        let mut encoder = Encoder {
+            sink,
+            header_bound: header.bound,
+            bound: 1,
+            written: 0,
+        };
+        encoder.write_words(&header.assemble())?;
+        Ok(encoder)
+    }
+
+    /// Encodes the given instruction.
+    pub fn encode(&mut self, inst: &mr::Instruction) -> io::Result<()> {
+        if let Some(id) = inst.result_id {
+            self.bound = self.bound.max(id + 1);
+        }
+        self.write_words(&inst.assemble())
+    }
+
+    /// Writes the given words of an instruction unchanged.
+    ///
+    /// Ids defined by these words are not taken into account for the bound.
+    pub fn encode_raw(&mut self, words: &[u32]) -> io::Result<()> {
+        self.write_words(words)
+    }
+
+    /// Returns the bound required by the instructions encoded so far.
+    pub fn bound(&self) -> spirv::Word {
+        self.bound
+    }
+
+    /// Returns the sink, leaving the bound in the header as written.
+    pub fn into_inner(self) -> W {
+        self.sink
+    }
+
+    fn write_words(&mut self, words: &[u32]) -> io::Result<()> {
+        for word in words {
+            self.sink.write_all(&word.to_le_bytes())?;
+        }
+        self.written += 4 * words.len() as u64;
+        Ok(())
+    }
+}
+
+impl<W: io::Write + io::Seek> Encoder<W> {
+    /// Patches the bound in the header, if the encoded instructions require
+    /// a larger one than written, and returns the sink, positioned at the
+    /// end of the module.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.bound > self.header_bound {
+            let back = self.written - BOUND_OFFSET;
+            self.sink.seek(io::SeekFrom::Current(-(back as i64)))?;
+            self.sink.write_all(&self.bound.to_le_bytes())?;
+            self.sink.seek(io::SeekFrom::Current(back as i64 - 4))?;
+        }
+        self.sink.flush()?;
+        Ok(self.sink)
+    }
+}
+
+/// Encodes the given `module` into `sink` and returns the sink.
+///
+/// The module is walked twice: once to compute the bound, which is the
+/// larger of the one in the module header and the one its instructions
+/// require, and once to write the words.
+pub fn encode_module<W: io::Write>(module: &mr::Module, sink: W) -> io::Result<W> {
+    let mut bound = module.header.as_ref().map_or(1, |h| h.bound);
+    visit_layout(module, &mut |inst| if let Some(id) = inst.result_id {
+        bound = bound.max(id + 1);
+    });
+    let header = match module.header {
+        Some(ref h) => {
+            mr::ModuleHeader {
+                magic_number: h.magic_number,
+                version: h.version,
+                generator: h.generator,
+                bound,
+                reserved_word: h.reserved_word,
+            }
+        }
+        None => mr::ModuleHeader::new(bound),
+    };
+
+    let mut encoder = Encoder::new(sink, &header)?;
+    let mut result = Ok(());
+    visit_layout(module, &mut |inst| if result.is_ok() {
+        result = encoder.encode(inst);
+    });
+    result?;
+    encoder.sink.flush()?;
+    Ok(encoder.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use binary::Assemble;
+    use std::io::{Cursor, Write};
+    use super::{encode_module, Encoder};
+
+    fn build() -> mr::Module {
+        let mut b = mr::Builder::new();
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let voidfvoid = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.module()
+    }
+
+    fn to_bytes(words: &[u32]) -> Vec<u8> {
+        words.iter().flat_map(|w| w.to_le_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_encode_module() {
+        let module = build();
+        let bytes = encode_module(&module, vec![]).unwrap();
+        assert_eq!(bytes, to_bytes(&module.assemble()));
+
+        // A missing header is filled in with the required bound.
+        let mut headless = build();
+        headless.header = None;
+        let bytes = encode_module(&headless, vec![]).unwrap();
+        assert_eq!(bytes, to_bytes(&module.assemble()));
+    }
+
+    #[test]
+    fn test_encoder_patches_bound() {
+        let module = build();
+        // Something already in the sink before the module.
+        let mut sink = Cursor::new(vec![]);
+        sink.write_all(b"pre").unwrap();
+
+        let mut encoder = Encoder::new(sink, &mr::ModuleHeader::new(0)).unwrap();
+        for inst in module.global_inst_iter() {
+            encoder.encode(inst).unwrap();
+        }
+        for inst in module.functions.iter().flat_map(|f| {
+            f.def.iter().chain(f.basic_blocks.iter().flat_map(|bb| {
+                bb.label.iter().chain(&bb.instructions)
+            })).chain(&f.end)
+        }) {
+            encoder.encode(inst).unwrap();
+        }
+        assert_eq!(encoder.bound(), 5);
+        let mut sink = encoder.finish().unwrap();
+        sink.write_all(b"post").unwrap();
+
+        let bytes = sink.into_inner();
+        assert_eq!(&bytes[..3], b"pre");
+        assert_eq!(&bytes[bytes.len() - 4..], b"post");
+        assert_eq!(&bytes[3..bytes.len() - 4], &to_bytes(&module.assemble())[..]);
+    }
+}
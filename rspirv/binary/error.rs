@@ -34,6 +34,7 @@ pub enum Error {
     MemorySemanticsUnknown(usize, spirv::Word),
     MemoryAccessUnknown(usize, spirv::Word),
     KernelProfilingInfoUnknown(usize, spirv::Word),
+    CooperativeMatrixOperandsUnknown(usize, spirv::Word),
     SourceLanguageUnknown(usize, spirv::Word),
     ExecutionModelUnknown(usize, spirv::Word),
     AddressingModelUnknown(usize, spirv::Word),
@@ -56,12 +57,14 @@ pub enum Error {
     GroupOperationUnknown(usize, spirv::Word),
     KernelEnqueueFlagsUnknown(usize, spirv::Word),
     CapabilityUnknown(usize, spirv::Word),
-    /// Failed to decode a string.
+    CooperativeMatrixLayoutUnknown(usize, spirv::Word),
+    CooperativeMatrixUseUnknown(usize, spirv::Word),
+    /// A literal string is not valid UTF-8.
     ///
-    /// For structured error handling, the second element could be
-    /// `string::FromUtf8Error`, but the will prohibit the compiler
-    /// from generating `PartialEq` for this enum.
-    DecodeStringFailed(usize, String),
+    /// The first element is the index of the string, and the second
+    /// element is the number of leading bytes that are valid UTF-8.
+    /// The raw bytes can still be read with `Decoder::string_bytes()`.
+    InvalidUtf8String(usize, usize),
 }
 
 impl fmt::Display for Error {
@@ -77,6 +80,7 @@ impl fmt::Display for Error {
             Error::MemorySemanticsUnknown(index, word) => write!(f, "unknown value {} for operand kind MemorySemantics at index {}", word, index),
             Error::MemoryAccessUnknown(index, word) => write!(f, "unknown value {} for operand kind MemoryAccess at index {}", word, index),
             Error::KernelProfilingInfoUnknown(index, word) => write!(f, "unknown value {} for operand kind KernelProfilingInfo at index {}", word, index),
+            Error::CooperativeMatrixOperandsUnknown(index, word) => write!(f, "unknown value {} for operand kind CooperativeMatrixOperands at index {}", word, index),
             Error::SourceLanguageUnknown(index, word) => write!(f, "unknown value {} for operand kind SourceLanguage at index {}", word, index),
             Error::ExecutionModelUnknown(index, word) => write!(f, "unknown value {} for operand kind ExecutionModel at index {}", word, index),
             Error::AddressingModelUnknown(index, word) => write!(f, "unknown value {} for operand kind AddressingModel at index {}", word, index),
@@ -99,7 +103,9 @@ impl fmt::Display for Error {
             Error::GroupOperationUnknown(index, word) => write!(f, "unknown value {} for operand kind GroupOperation at index {}", word, index),
             Error::KernelEnqueueFlagsUnknown(index, word) => write!(f, "unknown value {} for operand kind KernelEnqueueFlags at index {}", word, index),
             Error::CapabilityUnknown(index, word) => write!(f, "unknown value {} for operand kind Capability at index {}", word, index),
-            Error::DecodeStringFailed(index, ref e) => write!(f, "cannot decode string at index {}: {}", index, e),
+            Error::CooperativeMatrixLayoutUnknown(index, word) => write!(f, "unknown value {} for operand kind CooperativeMatrixLayout at index {}", word, index),
+            Error::CooperativeMatrixUseUnknown(index, word) => write!(f, "unknown value {} for operand kind CooperativeMatrixUse at index {}", word, index),
+            Error::InvalidUtf8String(index, valid) => write!(f, "invalid UTF-8 in string at index {} after byte {}", index, valid),
         }
     }
 }
@@ -108,6 +114,7 @@ impl error::Error for Error {
     fn description(&self) -> &str {
         match *self {
             Error::StreamExpected(_) => "expected more bytes in the stream",
+            Error::InvalidUtf8String(..) => "invalid UTF-8 string",
             _ => "unknown operand value for the given kind",
         }
     }
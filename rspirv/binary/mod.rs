@@ -23,6 +23,11 @@
 //!   of the SPIR-V grammar. It works with the
 //!   [`Consumer`](trait.Consumer.html) to process a SPIR-V binary on the
 //!   instruction level.
+//!
+//! On the output side, the [`Encoder`](struct.Encoder.html) writes
+//! instructions to any `io::Write` sink as they come, and
+//! [`assemble_text`](fn.assemble_text.html) assembles SPIR-V text into a
+//! binary module.
 
 pub use self::decoder::Decoder;
 pub use self::error::Error as DecodeError;
@@ -31,12 +36,23 @@ pub use self::parser::Action as ParseAction;
 pub use self::parser::Result as ParseResult;
 pub use self::parser::State as ParseState;
 
-pub use self::disassemble::Disassemble;
-pub use self::assemble::Assemble;
+pub use self::disassemble::{disassemble_with, Disassemble, DisasOptions};
+pub use self::asm::{assemble_text, assemble_text_with, assemble_with, AsmOptions};
+pub use self::asm::Binding as AsmBinding;
+pub use self::asm::Error as AsmError;
+pub use self::asm::Result as AsmResult;
+pub use self::assemble::{assemble_bytes, assemble_str, string_word_count, Assemble};
+#[cfg(feature = "std")]
+pub(crate) use self::assemble::visit_layout;
+#[cfg(feature = "std")]
+pub use self::encoder::{encode_module, Encoder};
 
+mod asm;
 mod assemble;
 mod decoder;
 mod disassemble;
+#[cfg(feature = "std")]
+mod encoder;
 mod error;
 mod parser;
 mod tracker;
@@ -17,172 +17,195 @@
 // DO NOT MODIFY!
 
 impl<'c, 'd> Parser<'c, 'd> {
-    fn parse_operand(&mut self, kind: GOpKind) -> Result<Vec<mr::Operand>> {
-        Ok(match kind {
-            GOpKind::FPFastMathMode => vec![mr::Operand::FPFastMathMode(try_decode!(self.decoder.fpfast_math_mode()))],
-            GOpKind::SelectionControl => vec![mr::Operand::SelectionControl(try_decode!(self.decoder.selection_control()))],
-            GOpKind::FunctionControl => vec![mr::Operand::FunctionControl(try_decode!(self.decoder.function_control()))],
-            GOpKind::MemorySemantics => vec![mr::Operand::MemorySemantics(try_decode!(self.decoder.memory_semantics()))],
-            GOpKind::KernelProfilingInfo => vec![mr::Operand::KernelProfilingInfo(try_decode!(self.decoder.kernel_profiling_info()))],
-            GOpKind::SourceLanguage => vec![mr::Operand::SourceLanguage(try_decode!(self.decoder.source_language()))],
-            GOpKind::ExecutionModel => vec![mr::Operand::ExecutionModel(try_decode!(self.decoder.execution_model()))],
-            GOpKind::AddressingModel => vec![mr::Operand::AddressingModel(try_decode!(self.decoder.addressing_model()))],
-            GOpKind::MemoryModel => vec![mr::Operand::MemoryModel(try_decode!(self.decoder.memory_model()))],
-            GOpKind::StorageClass => vec![mr::Operand::StorageClass(try_decode!(self.decoder.storage_class()))],
-            GOpKind::Dim => vec![mr::Operand::Dim(try_decode!(self.decoder.dim()))],
-            GOpKind::SamplerAddressingMode => vec![mr::Operand::SamplerAddressingMode(try_decode!(self.decoder.sampler_addressing_mode()))],
-            GOpKind::SamplerFilterMode => vec![mr::Operand::SamplerFilterMode(try_decode!(self.decoder.sampler_filter_mode()))],
-            GOpKind::ImageFormat => vec![mr::Operand::ImageFormat(try_decode!(self.decoder.image_format()))],
-            GOpKind::ImageChannelOrder => vec![mr::Operand::ImageChannelOrder(try_decode!(self.decoder.image_channel_order()))],
-            GOpKind::ImageChannelDataType => vec![mr::Operand::ImageChannelDataType(try_decode!(self.decoder.image_channel_data_type()))],
-            GOpKind::FPRoundingMode => vec![mr::Operand::FPRoundingMode(try_decode!(self.decoder.fprounding_mode()))],
-            GOpKind::LinkageType => vec![mr::Operand::LinkageType(try_decode!(self.decoder.linkage_type()))],
-            GOpKind::AccessQualifier => vec![mr::Operand::AccessQualifier(try_decode!(self.decoder.access_qualifier()))],
-            GOpKind::FunctionParameterAttribute => vec![mr::Operand::FunctionParameterAttribute(try_decode!(self.decoder.function_parameter_attribute()))],
-            GOpKind::BuiltIn => vec![mr::Operand::BuiltIn(try_decode!(self.decoder.built_in()))],
-            GOpKind::Scope => vec![mr::Operand::Scope(try_decode!(self.decoder.scope()))],
-            GOpKind::GroupOperation => vec![mr::Operand::GroupOperation(try_decode!(self.decoder.group_operation()))],
-            GOpKind::KernelEnqueueFlags => vec![mr::Operand::KernelEnqueueFlags(try_decode!(self.decoder.kernel_enqueue_flags()))],
-            GOpKind::Capability => vec![mr::Operand::Capability(try_decode!(self.decoder.capability()))],
-            GOpKind::IdMemorySemantics => vec![mr::Operand::IdMemorySemantics(try_decode!(self.decoder.id()))],
-            GOpKind::IdScope => vec![mr::Operand::IdScope(try_decode!(self.decoder.id()))],
-            GOpKind::IdRef => vec![mr::Operand::IdRef(try_decode!(self.decoder.id()))],
-            GOpKind::LiteralInteger => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            GOpKind::LiteralString => vec![mr::Operand::LiteralString(try_decode!(self.decoder.string()))],
-            GOpKind::LiteralExtInstInteger => vec![mr::Operand::LiteralExtInstInteger(try_decode!(self.decoder.ext_inst_integer()))],
+    fn parse_operand(&mut self, kind: GOpKind, operands: &mut mr::Operands) -> Result<()> {
+        match kind {
+            GOpKind::FPFastMathMode => operands.push(mr::Operand::FPFastMathMode(try_decode!(self.decoder.fpfast_math_mode()))),
+            GOpKind::SelectionControl => operands.push(mr::Operand::SelectionControl(try_decode!(self.decoder.selection_control()))),
+            GOpKind::FunctionControl => operands.push(mr::Operand::FunctionControl(try_decode!(self.decoder.function_control()))),
+            GOpKind::MemorySemantics => operands.push(mr::Operand::MemorySemantics(try_decode!(self.decoder.memory_semantics()))),
+            GOpKind::KernelProfilingInfo => operands.push(mr::Operand::KernelProfilingInfo(try_decode!(self.decoder.kernel_profiling_info()))),
+            GOpKind::CooperativeMatrixOperands => operands.push(mr::Operand::CooperativeMatrixOperands(try_decode!(self.decoder.cooperative_matrix_operands()))),
+            GOpKind::SourceLanguage => operands.push(mr::Operand::SourceLanguage(try_decode!(self.decoder.source_language()))),
+            GOpKind::ExecutionModel => operands.push(mr::Operand::ExecutionModel(try_decode!(self.decoder.execution_model()))),
+            GOpKind::AddressingModel => operands.push(mr::Operand::AddressingModel(try_decode!(self.decoder.addressing_model()))),
+            GOpKind::MemoryModel => operands.push(mr::Operand::MemoryModel(try_decode!(self.decoder.memory_model()))),
+            GOpKind::StorageClass => operands.push(mr::Operand::StorageClass(try_decode!(self.decoder.storage_class()))),
+            GOpKind::Dim => operands.push(mr::Operand::Dim(try_decode!(self.decoder.dim()))),
+            GOpKind::SamplerAddressingMode => operands.push(mr::Operand::SamplerAddressingMode(try_decode!(self.decoder.sampler_addressing_mode()))),
+            GOpKind::SamplerFilterMode => operands.push(mr::Operand::SamplerFilterMode(try_decode!(self.decoder.sampler_filter_mode()))),
+            GOpKind::ImageFormat => operands.push(mr::Operand::ImageFormat(try_decode!(self.decoder.image_format()))),
+            GOpKind::ImageChannelOrder => operands.push(mr::Operand::ImageChannelOrder(try_decode!(self.decoder.image_channel_order()))),
+            GOpKind::ImageChannelDataType => operands.push(mr::Operand::ImageChannelDataType(try_decode!(self.decoder.image_channel_data_type()))),
+            GOpKind::FPRoundingMode => operands.push(mr::Operand::FPRoundingMode(try_decode!(self.decoder.fprounding_mode()))),
+            GOpKind::LinkageType => operands.push(mr::Operand::LinkageType(try_decode!(self.decoder.linkage_type()))),
+            GOpKind::AccessQualifier => operands.push(mr::Operand::AccessQualifier(try_decode!(self.decoder.access_qualifier()))),
+            GOpKind::FunctionParameterAttribute => operands.push(mr::Operand::FunctionParameterAttribute(try_decode!(self.decoder.function_parameter_attribute()))),
+            GOpKind::BuiltIn => operands.push(mr::Operand::BuiltIn(try_decode!(self.decoder.built_in()))),
+            GOpKind::Scope => operands.push(mr::Operand::Scope(try_decode!(self.decoder.scope()))),
+            GOpKind::GroupOperation => operands.push(mr::Operand::GroupOperation(try_decode!(self.decoder.group_operation()))),
+            GOpKind::KernelEnqueueFlags => operands.push(mr::Operand::KernelEnqueueFlags(try_decode!(self.decoder.kernel_enqueue_flags()))),
+            GOpKind::Capability => operands.push(mr::Operand::Capability(try_decode!(self.decoder.capability()))),
+            GOpKind::CooperativeMatrixLayout => operands.push(mr::Operand::CooperativeMatrixLayout(try_decode!(self.decoder.cooperative_matrix_layout()))),
+            GOpKind::CooperativeMatrixUse => operands.push(mr::Operand::CooperativeMatrixUse(try_decode!(self.decoder.cooperative_matrix_use()))),
+            GOpKind::IdMemorySemantics => operands.push(mr::Operand::IdMemorySemantics(try_decode!(self.decoder.id()))),
+            GOpKind::IdScope => operands.push(mr::Operand::IdScope(try_decode!(self.decoder.id()))),
+            GOpKind::IdRef => operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id()))),
+            GOpKind::LiteralInteger => operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))),
+            GOpKind::LiteralString => operands.push(mr::Operand::LiteralString(self.parse_string()?)),
+            GOpKind::LiteralExtInstInteger => operands.push(mr::Operand::LiteralExtInstInteger(try_decode!(self.decoder.ext_inst_integer()))),
             GOpKind::PairLiteralIntegerIdRef => {
-                vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32())), mr::Operand::IdRef(try_decode!(self.decoder.id()))]
+                operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32())));
+                operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id())));
             }
             GOpKind::PairIdRefLiteralInteger => {
-                vec![mr::Operand::IdRef(try_decode!(self.decoder.id())), mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))]
+                operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id())));
+                operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32())));
             }
             GOpKind::PairIdRefIdRef => {
-                vec![mr::Operand::IdRef(try_decode!(self.decoder.id())), mr::Operand::IdRef(try_decode!(self.decoder.id()))]
+                operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id())));
+                operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id())));
             }
             GOpKind::ImageOperands => {
                 let val = try_decode!(self.decoder.image_operands());
-                let mut ops = vec![mr::Operand::ImageOperands(val)];
-                ops.append(&mut self.parse_image_operands_arguments(val)?);
-                ops
+                operands.push(mr::Operand::ImageOperands(val));
+                self.parse_image_operands_arguments(val, operands)?;
             }
             GOpKind::LoopControl => {
                 let val = try_decode!(self.decoder.loop_control());
-                let mut ops = vec![mr::Operand::LoopControl(val)];
-                ops.append(&mut self.parse_loop_control_arguments(val)?);
-                ops
+                operands.push(mr::Operand::LoopControl(val));
+                self.parse_loop_control_arguments(val, operands)?;
             }
             GOpKind::MemoryAccess => {
                 let val = try_decode!(self.decoder.memory_access());
-                let mut ops = vec![mr::Operand::MemoryAccess(val)];
-                ops.append(&mut self.parse_memory_access_arguments(val)?);
-                ops
+                operands.push(mr::Operand::MemoryAccess(val));
+                self.parse_memory_access_arguments(val, operands)?;
             }
             GOpKind::ExecutionMode => {
                 let val = try_decode!(self.decoder.execution_mode());
-                let mut ops = vec![mr::Operand::ExecutionMode(val)];
-                ops.append(&mut self.parse_execution_mode_arguments(val)?);
-                ops
+                operands.push(mr::Operand::ExecutionMode(val));
+                self.parse_execution_mode_arguments(val, operands)?;
             }
             GOpKind::Decoration => {
                 let val = try_decode!(self.decoder.decoration());
-                let mut ops = vec![mr::Operand::Decoration(val)];
-                ops.append(&mut self.parse_decoration_arguments(val)?);
-                ops
+                operands.push(mr::Operand::Decoration(val));
+                self.parse_decoration_arguments(val, operands)?;
             }
             GOpKind::IdResultType => panic!(),  // not handled here
             GOpKind::IdResult => panic!(),  // not handled here
             GOpKind::LiteralContextDependentNumber => panic!(),  // not handled here
             GOpKind::LiteralSpecConstantOpInteger => panic!(),  // not handled here
-        })
+        }
+        Ok(())
     }
 
-    fn parse_image_operands_arguments(&mut self, image_operands: spirv::ImageOperands) -> Result<Vec<mr::Operand>> {
-        let mut params = vec![];
+    fn parse_image_operands_arguments(&mut self, image_operands: spirv::ImageOperands, operands: &mut mr::Operands) -> Result<()> {
         if image_operands.contains(spirv::ImageOperands::BIAS) {
-            params.append(&mut vec![mr::Operand::IdRef(try_decode!(self.decoder.id()))]);
+            operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id())));
         }
         if image_operands.contains(spirv::ImageOperands::LOD) {
-            params.append(&mut vec![mr::Operand::IdRef(try_decode!(self.decoder.id()))]);
+            operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id())));
         }
         if image_operands.contains(spirv::ImageOperands::GRAD) {
-            params.append(&mut vec![mr::Operand::IdRef(try_decode!(self.decoder.id())), mr::Operand::IdRef(try_decode!(self.decoder.id()))]);
+            operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id())));
+            operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id())));
         }
         if image_operands.contains(spirv::ImageOperands::CONST_OFFSET) {
-            params.append(&mut vec![mr::Operand::IdRef(try_decode!(self.decoder.id()))]);
+            operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id())));
         }
         if image_operands.contains(spirv::ImageOperands::OFFSET) {
-            params.append(&mut vec![mr::Operand::IdRef(try_decode!(self.decoder.id()))]);
+            operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id())));
         }
         if image_operands.contains(spirv::ImageOperands::CONST_OFFSETS) {
-            params.append(&mut vec![mr::Operand::IdRef(try_decode!(self.decoder.id()))]);
+            operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id())));
         }
         if image_operands.contains(spirv::ImageOperands::SAMPLE) {
-            params.append(&mut vec![mr::Operand::IdRef(try_decode!(self.decoder.id()))]);
+            operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id())));
         }
         if image_operands.contains(spirv::ImageOperands::MIN_LOD) {
-            params.append(&mut vec![mr::Operand::IdRef(try_decode!(self.decoder.id()))]);
+            operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id())));
+        }
+        if image_operands.contains(spirv::ImageOperands::MAKE_TEXEL_AVAILABLE) {
+            operands.push(mr::Operand::IdScope(try_decode!(self.decoder.id())));
         }
-        Ok(params)
+        if image_operands.contains(spirv::ImageOperands::MAKE_TEXEL_VISIBLE) {
+            operands.push(mr::Operand::IdScope(try_decode!(self.decoder.id())));
+        }
+        Ok(())
     }
 
-    fn parse_loop_control_arguments(&mut self, loop_control: spirv::LoopControl) -> Result<Vec<mr::Operand>> {
-        let mut params = vec![];
+    fn parse_loop_control_arguments(&mut self, loop_control: spirv::LoopControl, operands: &mut mr::Operands) -> Result<()> {
         if loop_control.contains(spirv::LoopControl::DEPENDENCY_LENGTH) {
-            params.append(&mut vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))]);
+            operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32())));
         }
-        Ok(params)
+        Ok(())
     }
 
-    fn parse_memory_access_arguments(&mut self, memory_access: spirv::MemoryAccess) -> Result<Vec<mr::Operand>> {
-        let mut params = vec![];
+    fn parse_memory_access_arguments(&mut self, memory_access: spirv::MemoryAccess, operands: &mut mr::Operands) -> Result<()> {
         if memory_access.contains(spirv::MemoryAccess::ALIGNED) {
-            params.append(&mut vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))]);
+            operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32())));
+        }
+        if memory_access.contains(spirv::MemoryAccess::MAKE_POINTER_AVAILABLE) {
+            operands.push(mr::Operand::IdScope(try_decode!(self.decoder.id())));
         }
-        Ok(params)
+        if memory_access.contains(spirv::MemoryAccess::MAKE_POINTER_VISIBLE) {
+            operands.push(mr::Operand::IdScope(try_decode!(self.decoder.id())));
+        }
+        Ok(())
     }
 
-    fn parse_execution_mode_arguments(&mut self, execution_mode: spirv::ExecutionMode) -> Result<Vec<mr::Operand>> {
-        Ok(match execution_mode {
-            spirv::ExecutionMode::Invocations => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::ExecutionMode::LocalSize => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32())), mr::Operand::LiteralInt32(try_decode!(self.decoder.int32())), mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::ExecutionMode::LocalSizeHint => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32())), mr::Operand::LiteralInt32(try_decode!(self.decoder.int32())), mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::ExecutionMode::OutputVertices => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::ExecutionMode::VecTypeHint => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::ExecutionMode::SubgroupSize => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::ExecutionMode::SubgroupsPerWorkgroup => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::ExecutionMode::SubgroupsPerWorkgroupId => vec![mr::Operand::IdRef(try_decode!(self.decoder.id()))],
-            spirv::ExecutionMode::LocalSizeId => vec![mr::Operand::IdRef(try_decode!(self.decoder.id())), mr::Operand::IdRef(try_decode!(self.decoder.id())), mr::Operand::IdRef(try_decode!(self.decoder.id()))],
-            spirv::ExecutionMode::LocalSizeHintId => vec![mr::Operand::IdRef(try_decode!(self.decoder.id()))],
-            _ => vec![]
-        })
+    fn parse_execution_mode_arguments(&mut self, execution_mode: spirv::ExecutionMode, operands: &mut mr::Operands) -> Result<()> {
+        match execution_mode {
+            spirv::ExecutionMode::Invocations => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::ExecutionMode::LocalSize => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::ExecutionMode::LocalSizeHint => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::ExecutionMode::OutputVertices => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::ExecutionMode::VecTypeHint => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::ExecutionMode::SubgroupSize => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::ExecutionMode::SubgroupsPerWorkgroup => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::ExecutionMode::SubgroupsPerWorkgroupId => { operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id()))); }
+            spirv::ExecutionMode::LocalSizeId => { operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id()))); operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id()))); operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id()))); }
+            spirv::ExecutionMode::LocalSizeHintId => { operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id()))); }
+            spirv::ExecutionMode::DenormPreserve => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::ExecutionMode::DenormFlushToZero => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::ExecutionMode::SignedZeroInfNanPreserve => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::ExecutionMode::RoundingModeRTE => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::ExecutionMode::RoundingModeRTZ => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::ExecutionMode::OutputPrimitivesEXT => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            _ => (),
+        }
+        Ok(())
     }
 
-    fn parse_decoration_arguments(&mut self, decoration: spirv::Decoration) -> Result<Vec<mr::Operand>> {
-        Ok(match decoration {
-            spirv::Decoration::SpecId => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::Decoration::ArrayStride => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::Decoration::MatrixStride => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::Decoration::BuiltIn => vec![mr::Operand::BuiltIn(try_decode!(self.decoder.built_in()))],
-            spirv::Decoration::Stream => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::Decoration::Location => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::Decoration::Component => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::Decoration::Index => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::Decoration::Binding => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::Decoration::DescriptorSet => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::Decoration::Offset => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::Decoration::XfbBuffer => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::Decoration::XfbStride => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::Decoration::FuncParamAttr => vec![mr::Operand::FunctionParameterAttribute(try_decode!(self.decoder.function_parameter_attribute()))],
-            spirv::Decoration::FPRoundingMode => vec![mr::Operand::FPRoundingMode(try_decode!(self.decoder.fprounding_mode()))],
-            spirv::Decoration::FPFastMathMode => vec![mr::Operand::FPFastMathMode(try_decode!(self.decoder.fpfast_math_mode()))],
-            spirv::Decoration::LinkageAttributes => vec![mr::Operand::LiteralString(try_decode!(self.decoder.string())), mr::Operand::LinkageType(try_decode!(self.decoder.linkage_type()))],
-            spirv::Decoration::InputAttachmentIndex => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::Decoration::Alignment => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::Decoration::MaxByteOffset => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            spirv::Decoration::AlignmentId => vec![mr::Operand::IdRef(try_decode!(self.decoder.id()))],
-            spirv::Decoration::MaxByteOffsetId => vec![mr::Operand::IdRef(try_decode!(self.decoder.id()))],
-            spirv::Decoration::SecondaryViewportRelativeNV => vec![mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))],
-            _ => vec![]
-        })
+    fn parse_decoration_arguments(&mut self, decoration: spirv::Decoration, operands: &mut mr::Operands) -> Result<()> {
+        match decoration {
+            spirv::Decoration::SpecId => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::Decoration::ArrayStride => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::Decoration::MatrixStride => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::Decoration::BuiltIn => { operands.push(mr::Operand::BuiltIn(try_decode!(self.decoder.built_in()))); }
+            spirv::Decoration::Stream => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::Decoration::Location => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::Decoration::Component => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::Decoration::Index => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::Decoration::Binding => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::Decoration::DescriptorSet => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::Decoration::Offset => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::Decoration::XfbBuffer => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::Decoration::XfbStride => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::Decoration::FuncParamAttr => { operands.push(mr::Operand::FunctionParameterAttribute(try_decode!(self.decoder.function_parameter_attribute()))); }
+            spirv::Decoration::FPRoundingMode => { operands.push(mr::Operand::FPRoundingMode(try_decode!(self.decoder.fprounding_mode()))); }
+            spirv::Decoration::FPFastMathMode => { operands.push(mr::Operand::FPFastMathMode(try_decode!(self.decoder.fpfast_math_mode()))); }
+            spirv::Decoration::LinkageAttributes => { operands.push(mr::Operand::LiteralString(self.parse_string()?)); operands.push(mr::Operand::LinkageType(try_decode!(self.decoder.linkage_type()))); }
+            spirv::Decoration::InputAttachmentIndex => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::Decoration::Alignment => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::Decoration::MaxByteOffset => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::Decoration::AlignmentId => { operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id()))); }
+            spirv::Decoration::MaxByteOffsetId => { operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id()))); }
+            spirv::Decoration::SecondaryViewportRelativeNV => { operands.push(mr::Operand::LiteralInt32(try_decode!(self.decoder.int32()))); }
+            spirv::Decoration::HlslCounterBufferGOOGLE => { operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id()))); }
+            spirv::Decoration::HlslSemanticGOOGLE => { operands.push(mr::Operand::LiteralString(self.parse_string()?)); }
+            spirv::Decoration::UserTypeGOOGLE => { operands.push(mr::Operand::LiteralString(self.parse_string()?)); }
+            _ => (),
+        }
+        Ok(())
     }
 }
\ No newline at end of file
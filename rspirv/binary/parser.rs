@@ -14,6 +14,7 @@
 
 use mr;
 use grammar;
+use prelude::*;
 use spirv;
 
 use std::{error, fmt, result, slice};
@@ -171,6 +172,26 @@ pub trait Consumer {
     fn consume_header(&mut self, module: mr::ModuleHeader) -> Action;
     /// Consume the given instruction.
     fn consume_instruction(&mut self, inst: mr::Instruction) -> Action;
+    /// Consume the words of an instruction with an unknown opcode, or with
+    /// a literal string that is not UTF-8, including its first word.
+    ///
+    /// Only called if the parser keeps unknown instructions; see
+    /// [`Parser::set_keep_unknown`](struct.Parser.html#method.set_keep_unknown).
+    /// The default implementation ignores the instruction.
+    fn consume_unknown_instruction(&mut self, _words: Vec<u32>) -> Action {
+        Action::Continue
+    }
+    /// Gives the value of the next literal string operand, `s`, decoded
+    /// into a buffer the parser reuses.
+    ///
+    /// Strings are handed over in the order of their operands, before the
+    /// instruction holding them is consumed. The default implementation
+    /// copies `s`; consumers keeping strings elsewhere, like an
+    /// [`Arena`](../mr/struct.Arena.html) interning them, can return an
+    /// empty `String` instead, which does not allocate.
+    fn consume_string(&mut self, s: &str) -> String {
+        s.to_owned()
+    }
 }
 
 /// Parses the given `binary` and consumes the module using the given
@@ -238,12 +259,28 @@ pub struct Parser<'c, 'd> {
     decoder: decoder::Decoder<'d>,
     consumer: &'c mut Consumer,
     type_tracker: TypeTracker,
+    ext_insts: mr::ExtInstRegistry,
+    /// Whether OpNop instructions are dropped instead of consumed
+    drop_nops: bool,
+    /// Whether instructions are annotated with their requirements
+    annotate_requirements: bool,
+    /// Whether instructions with unknown opcodes are kept as raw words
+    keep_unknown: bool,
+    /// The buffer literal strings are decoded into
+    string: Vec<u8>,
     /// The index of the current instructions
     ///
     /// Starting from 1, 0 means invalid
     inst_index: usize,
 }
 
+/// An instruction read by the parser.
+enum Parsed {
+    Known(mr::Instruction),
+    /// The words of an instruction with an unknown opcode.
+    Unknown(Vec<u32>),
+}
+
 /// Tries to decode `$e` and returns the error if errored out.
 macro_rules! try_decode {
     ($e: expr) => (match $e {
@@ -260,10 +297,51 @@ impl<'c, 'd> Parser<'c, 'd> {
             decoder: decoder::Decoder::new(binary),
             consumer: consumer,
             type_tracker: TypeTracker::new(),
+            ext_insts: mr::ExtInstRegistry::new(),
+            drop_nops: false,
+            annotate_requirements: false,
+            keep_unknown: false,
+            string: vec![],
             inst_index: 0,
         }
     }
 
+    /// Sets whether OpNop instructions are dropped instead of being sent to
+    /// the consumer.
+    ///
+    /// Some toolchains pad instruction streams with OpNop, including at
+    /// module level where the [`Loader`](../mr/struct.Loader.html) would
+    /// otherwise reject it. Dropped instructions still count towards the
+    /// instruction indices reported in errors.
+    pub fn set_drop_nops(&mut self, drop: bool) {
+        self.drop_nops = drop;
+    }
+
+    /// Sets whether each instruction sent to the consumer carries the
+    /// capabilities and extensions it requires in its `requirements`.
+    ///
+    /// This lets consumers accumulate the requirements of a module while
+    /// loading it, instead of walking the whole module afterwards.
+    pub fn set_annotate_requirements(&mut self, annotate: bool) {
+        self.annotate_requirements = annotate;
+    }
+
+    /// Sets whether instructions with unknown opcodes, such as those of
+    /// vendor extensions newer than the grammar, are sent to the consumer
+    /// as raw words instead of failing the parse.
+    ///
+    /// So are instructions with literal strings that are not valid UTF-8,
+    /// which [`mr::Operand::LiteralString`](../mr/enum.Operand.html) cannot
+    /// hold.
+    ///
+    /// The [`Loader`](../mr/struct.Loader.html) keeps them as instructions
+    /// with [raw words](../mr/struct.Instruction.html#structfield.raw_words)
+    /// where they were read, so that assembling the module again reproduces
+    /// them unchanged.
+    pub fn set_keep_unknown(&mut self, keep: bool) {
+        self.keep_unknown = keep;
+    }
+
     /// Does the parsing.
     pub fn parse(mut self) -> Result<()> {
         match self.consumer.initialize() {
@@ -281,8 +359,26 @@ impl<'c, 'd> Parser<'c, 'd> {
         loop {
             let result = self.parse_inst();
             match result {
-                Ok(inst) => {
-                    self.type_tracker.track(&inst);
+                Ok(Parsed::Unknown(words)) => {
+                    match self.consumer.consume_unknown_instruction(words) {
+                        Action::Continue => (),
+                        Action::Stop => return Err(State::ConsumerStopRequested),
+                        Action::Error(err) => return Err(State::ConsumerError(err)),
+                    }
+                }
+                Ok(Parsed::Known(mut inst)) => {
+                    if self.drop_nops && inst.class.opcode == spirv::Op::Nop {
+                        continue;
+                    }
+                    if self.annotate_requirements {
+                        inst.requirements = Some(mr::Requirements::of(&inst));
+                    }
+                    // Only the types of constants are resolved, so values,
+                    // most of the instructions, need no tracking.
+                    if grammar::reflect::is_type(inst.class.opcode) {
+                        self.type_tracker.track(&inst);
+                    }
+                    self.ext_insts.track(&inst);
                     match self.consumer.consume_instruction(inst) {
                         Action::Continue => (),
                         Action::Stop => return Err(State::ConsumerStopRequested),
@@ -321,7 +417,7 @@ impl<'c, 'd> Parser<'c, 'd> {
         }
     }
 
-    fn parse_inst(&mut self) -> Result<mr::Instruction> {
+    fn parse_inst(&mut self) -> Result<Parsed> {
         self.inst_index += 1;
         if let Ok(word) = self.decoder.word() {
             let (wc, opcode) = Parser::split_into_word_count_and_opcode(word);
@@ -330,13 +426,26 @@ impl<'c, 'd> Parser<'c, 'd> {
                                                 self.inst_index));
             }
             if let Some(grammar) = GInstTable::lookup_opcode(opcode) {
+                let start = self.decoder.clone();
                 self.decoder.set_limit((wc - 1) as usize);
-                let result = self.parse_operands(grammar);
+                let result = self.parse_operands(grammar, (wc - 1) as usize);
+                if let Err(State::OperandError(DecodeError::InvalidUtf8String(..))) = result {
+                    if self.keep_unknown {
+                        self.decoder = start;
+                        let mut words = vec![word];
+                        words.append(&mut try_decode!(self.decoder.words((wc - 1) as usize)));
+                        return Ok(Parsed::Unknown(words));
+                    }
+                }
                 if !self.decoder.limit_reached() {
                     return Err(State::OperandExceeded(self.decoder.offset(), self.inst_index));
                 }
                 self.decoder.clear_limit();
-                result
+                result.map(Parsed::Known)
+            } else if self.keep_unknown {
+                let mut words = vec![word];
+                words.append(&mut try_decode!(self.decoder.words((wc - 1) as usize)));
+                Ok(Parsed::Unknown(words))
             } else {
                 Err(State::OpcodeUnknown(self.decoder.offset() - WORD_NUM_BYTES,
                                          self.inst_index,
@@ -382,9 +491,7 @@ impl<'c, 'd> Parser<'c, 'd> {
         }
     }
 
-    fn parse_spec_constant_op(&mut self) -> Result<Vec<mr::Operand>> {
-        let mut operands = vec![];
-
+    fn parse_spec_constant_op(&mut self, operands: &mut mr::Operands) -> Result<()> {
         let number = try_decode!(self.decoder.int32());
         if let Some(g) = GInstTable::lookup_opcode(number as u16) {
             // TODO: check whether this opcode is allowed here.
@@ -395,16 +502,71 @@ impl<'c, 'd> Parser<'c, 'd> {
                     operands.push(mr::Operand::IdRef(try_decode!(self.decoder.id())))
                 }
             }
-            Ok(operands)
+            Ok(())
         } else {
             Err(State::SpecConstantOpIntegerIncorrect(self.decoder.offset(), self.inst_index))
         }
     }
 
-    fn parse_operands(&mut self, grammar: GInstRef) -> Result<mr::Instruction> {
+    /// Decodes a literal string and hands it to the consumer, which gives
+    /// the value of the operand.
+    fn parse_string(&mut self) -> Result<String> {
+        let s = try_decode!(self.decoder.string_into(&mut self.string));
+        Ok(self.consumer.consume_string(s))
+    }
+
+    /// Parses the extended instruction number of an OpExtInst and, if its
+    /// instruction set has been imported and is known, the operands after it
+    /// following the layout in the extended instruction set grammar.
+    ///
+    /// Operands not covered this way are left to the OpExtInst grammar,
+    /// which decodes them as ids.
+    fn parse_ext_inst(&mut self, operands: &mut mr::Operands) -> Result<()> {
+        let number = try_decode!(self.decoder.ext_inst_integer());
+        let set = match operands.first() {
+            Some(&mr::Operand::IdRef(set)) => Some(set),
+            _ => None,
+        };
+        operands.push(match set {
+            Some(set) => self.ext_insts.operand(set, number),
+            None => mr::Operand::LiteralExtInstInteger(number),
+        });
+        let grammar = match set.and_then(|set| self.ext_insts.resolve(set, number)) {
+            Some(g) => g,
+            None => return Ok(()),
+        };
+        let mut loperand_index: usize = 0;
+        while loperand_index < grammar.operands.len() {
+            let loperand = &grammar.operands[loperand_index];
+            if self.decoder.limit_reached() {
+                return match loperand.quantifier {
+                    GOpCount::One => {
+                        Err(State::OperandExpected(self.decoder.offset(), self.inst_index))
+                    }
+                    GOpCount::ZeroOrOne | GOpCount::ZeroOrMore => Ok(()),
+                };
+            }
+            self.parse_operand(loperand.kind, operands)?;
+            if loperand.quantifier != GOpCount::ZeroOrMore {
+                loperand_index += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses the operands of an instruction with the given `grammar` and
+    /// `num_words` words after the opcode.
+    fn parse_operands(&mut self, grammar: GInstRef, num_words: usize) -> Result<mr::Instruction> {
         let mut rtype = None;
         let mut rid = None;
-        let mut coperands = vec![]; // concrete operands
+        // Concrete operands. Each operand takes at least one word, so this
+        // is the only allocation for the operand list, if any. The result
+        // type and id take words without being operands.
+        let results = grammar.operands
+            .iter()
+            .take_while(|o| o.kind == GOpKind::IdResultType || o.kind == GOpKind::IdResult)
+            .count();
+        let mut coperands = mr::Operands::with_capacity(num_words.saturating_sub(results));
 
         let mut loperand_index: usize = 0; // logical operand index
         while loperand_index < grammar.operands.len() {
@@ -425,9 +587,10 @@ impl<'c, 'd> Parser<'c, 'd> {
                         coperands.push(self.parse_literal(id)?)
                     }
                     GOpKind::LiteralSpecConstantOpInteger => {
-                        coperands.append(&mut self.parse_spec_constant_op()?)
+                        self.parse_spec_constant_op(&mut coperands)?
                     }
-                    _ => coperands.append(&mut self.parse_operand(loperand.kind)?),
+                    GOpKind::LiteralExtInstInteger => self.parse_ext_inst(&mut coperands)?,
+                    _ => self.parse_operand(loperand.kind, &mut coperands)?,
                 }
                 match loperand.quantifier {
                     GOpCount::One | GOpCount::ZeroOrOne => loperand_index += 1,
@@ -451,6 +614,7 @@ include!("parse_operand.rs");
 
 #[cfg(test)]
 mod tests {
+    use grammar;
     use mr;
     use spirv;
 
@@ -763,6 +927,36 @@ mod tests {
                    inst.operands);
     }
 
+    #[test]
+    fn test_parsing_ext_inst_operands() {
+        let mut b = ModuleBuilder::new();
+        // %1 = OpExtInstImport "OpenCL.std"
+        b.inst(spirv::Op::ExtInstImport,
+               vec![1, 0x6e65704f, 0x732e4c43, 0x00006474]);
+        // %5 = OpExtInst %2 %1 vloadn %3 %4 4
+        b.inst(spirv::Op::ExtInst, vec![2, 5, 1, 171, 3, 4, 4]);
+        // %6 = OpExtInst %2 %7 171 %3 %4 %4, from an unknown set
+        b.inst(spirv::Op::ExtInst, vec![2, 6, 7, 171, 3, 4, 4]);
+        let mut c = RetainingConsumer::new();
+        {
+            let p = Parser::new(b.get(), &mut c);
+            assert_matches!(p.parse(), Ok(()));
+        }
+        assert_eq!(3, c.insts.len());
+        assert_eq!(vec![mr::Operand::IdRef(1),
+                        mr::Operand::ExtInstOpcode(grammar::ExtInstSet::OpenCLStd100, 171),
+                        mr::Operand::IdRef(3),
+                        mr::Operand::IdRef(4),
+                        mr::Operand::LiteralInt32(4)],
+                   c.insts[1].operands);
+        assert_eq!(vec![mr::Operand::IdRef(7),
+                        mr::Operand::LiteralExtInstInteger(171),
+                        mr::Operand::IdRef(3),
+                        mr::Operand::IdRef(4),
+                        mr::Operand::IdRef(4)],
+                   c.insts[2].operands);
+    }
+
     #[derive(Debug)]
     struct ErrorString(&'static str);
     impl error::Error for ErrorString {
@@ -1149,6 +1343,52 @@ mod tests {
                    inst.operands);
     }
 
+    #[test]
+    fn test_parsing_drop_nops() {
+        let mut b = ModuleBuilder::new();
+        b.inst(spirv::Op::Nop, vec![]);
+        b.inst(spirv::Op::Capability, vec![22]);
+        b.inst(spirv::Op::Nop, vec![]);
+        {
+            let mut c = RetainingConsumer::new();
+            {
+                let p = Parser::new(b.get(), &mut c);
+                assert_matches!(p.parse(), Ok(()));
+            }
+            assert_eq!(3, c.insts.len());
+        }
+        {
+            let mut c = RetainingConsumer::new();
+            {
+                let mut p = Parser::new(b.get(), &mut c);
+                p.set_drop_nops(true);
+                assert_matches!(p.parse(), Ok(()));
+            }
+            assert_eq!(1, c.insts.len());
+            assert_eq!("Capability", c.insts[0].class.opname);
+        }
+    }
+
+    #[test]
+    fn test_parsing_annotate_requirements() {
+        let mut b = ModuleBuilder::new();
+        // OpCapability DemoteToHelperInvocation
+        b.inst(spirv::Op::Capability, vec![5379]);
+        // OpNop
+        b.inst(spirv::Op::Nop, vec![]);
+        let mut c = RetainingConsumer::new();
+        {
+            let mut p = Parser::new(b.get(), &mut c);
+            p.set_annotate_requirements(true);
+            assert_matches!(p.parse(), Ok(()));
+        }
+        assert_eq!(2, c.insts.len());
+        let demote: &[&str] = &["SPV_EXT_demote_to_helper_invocation"];
+        assert_eq!(Some(vec![demote]),
+                   c.insts[0].requirements.as_ref().map(|r| r.extensions.clone()));
+        assert_eq!(Some(mr::Requirements::new()), c.insts[1].requirements);
+    }
+
     #[test]
     fn test_parse_words() {
         let words = vec![0x07230203, 0x01000000, 0, 0, 0, 0x00020011, 0x00000016];
@@ -1162,4 +1402,42 @@ mod tests {
         assert_eq!(vec![mr::Operand::Capability(spirv::Capability::Int16)],
                    inst.operands);
     }
+
+    /// Keeps the strings handed to it and gives their lengths instead.
+    struct StringCounter {
+        strings: Vec<String>,
+        names: Vec<mr::Operand>,
+    }
+    impl Consumer for StringCounter {
+        fn initialize(&mut self) -> Action {
+            Action::Continue
+        }
+        fn finalize(&mut self) -> Action {
+            Action::Continue
+        }
+        fn consume_header(&mut self, _: mr::ModuleHeader) -> Action {
+            Action::Continue
+        }
+        fn consume_instruction(&mut self, inst: mr::Instruction) -> Action {
+            self.names.push(inst.operands[1].clone());
+            Action::Continue
+        }
+        fn consume_string(&mut self, s: &str) -> String {
+            self.strings.push(s.to_owned());
+            s.len().to_string()
+        }
+    }
+
+    #[test]
+    fn test_parsing_consume_string() {
+        let mut b = ModuleBuilder::new();
+        // OpName %1 "main"
+        b.inst(spirv::Op::Name, vec![1, 0x6e69616d, 0]);
+        // OpName %2 "x"
+        b.inst(spirv::Op::Name, vec![2, 0x78]);
+        let mut c = StringCounter { strings: vec![], names: vec![] };
+        assert_matches!(Parser::new(b.get(), &mut c).parse(), Ok(()));
+        assert_eq!(c.strings, vec!["main", "x"]);
+        assert_eq!(c.names, vec![mr::Operand::from("4"), mr::Operand::from("1")]);
+    }
 }
@@ -18,11 +18,6 @@ use spirv;
 
 use std::collections;
 
-use grammar::GlslStd450InstructionTable as GGlInstTable;
-use grammar::OpenCLStd100InstructionTable as GClInstTable;
-
-type GExtInstRef = &'static grammar::ExtendedInstruction<'static>;
-
 // TODO: Add support for other types.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Type {
@@ -40,12 +35,12 @@ pub struct TypeTracker {
     /// Mapping from an id to its type.
     ///
     /// Ids for both defining and using types are all kept here.
-    types: collections::HashMap<spirv::Word, Type>,
+    types: collections::BTreeMap<spirv::Word, Type>,
 }
 
 impl TypeTracker {
     pub fn new() -> TypeTracker {
-        TypeTracker { types: collections::HashMap::new() }
+        TypeTracker { types: collections::BTreeMap::new() }
     }
 
     pub fn track(&mut self, inst: &mr::Instruction) {
@@ -80,63 +75,3 @@ impl TypeTracker {
         self.types.get(&id).cloned()
     }
 }
-
-enum ExtInstSet {
-    GlslStd450,
-    OpenCLStd100,
-}
-
-/// Struct for tracking extended instruction sets.
-///
-/// If a given extended instruction set is not supported, it will just be
-/// silently ignored.
-pub struct ExtInstSetTracker {
-    sets: collections::HashMap<spirv::Word, ExtInstSet>,
-}
-
-impl ExtInstSetTracker {
-    pub fn new() -> ExtInstSetTracker {
-        ExtInstSetTracker { sets: collections::HashMap::new() }
-    }
-
-    /// Tracks the extended instruction set declared by the given `inst`.
-    ///
-    /// If the given extended instruction set is not recognized, it will
-    /// be silently ignored.
-    pub fn track(&mut self, inst: &mr::Instruction) {
-        if inst.class.opcode != spirv::Op::ExtInstImport || inst.result_id.is_none() ||
-           inst.operands.is_empty() {
-            return;
-        }
-        if let mr::Operand::LiteralString(ref s) = inst.operands[0] {
-            if s == "GLSL.std.450" {
-                self.sets
-                    .insert(inst.result_id.unwrap(), ExtInstSet::GlslStd450);
-            } else if s == "OpenCL.std" {
-                self.sets
-                    .insert(inst.result_id.unwrap(), ExtInstSet::OpenCLStd100);
-            }
-        }
-    }
-
-    /// Returns true if the given extended instruction `set` has been
-    /// recognized thus tracked.
-    pub fn have(&self, set: spirv::Word) -> bool {
-        self.sets.get(&set).is_some()
-    }
-
-    /// Resolves the extended instruction with `opcode` in set `set`.
-    ///
-    /// This method will return `None` for both untracked instruction
-    /// sets and unknown opcode in tracked instruction sets.
-    pub fn resolve(&self, set: spirv::Word, opcode: spirv::Word) -> Option<GExtInstRef> {
-        if let Some(ext_inst_set) = self.sets.get(&set) {
-            match *ext_inst_set {
-                ExtInstSet::GlslStd450 => GGlInstTable::lookup_opcode(opcode),
-                ExtInstSet::OpenCLStd100 => GClInstTable::lookup_opcode(opcode),
-            }
-        } else {
-            None
-        }
-    }
-}
@@ -0,0 +1,209 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! C API for the parser and disassembler.
+//!
+//! This module is only available with the `capi` feature. It exports a small
+//! set of functions with the C calling convention and unmangled names, so that
+//! C and C++ code can link against the static or dynamic library built along
+//! with the Rust one. The matching declarations are in the
+//! [`HEADER`](constant.HEADER.html) (`capi/rspirv.h` in the source tree),
+//! which the codegen crate generates from the functions here; run
+//! `cargo build -p rspirv-codegen` after changing them.
+//!
+//! All pointers handed out by these functions are owned by the caller and
+//! must be released with [`rspirv_free`](fn.rspirv_free.html) or
+//! [`rspirv_string_free`](fn.rspirv_string_free.html).
+//!
+//! Panics never unwind into the caller, which would be undefined behavior;
+//! the functions report them as failures instead.
+
+use mr;
+
+use binary::Disassemble;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::{ptr, slice};
+
+/// The C header declaring the functions in this module.
+pub const HEADER: &str = include_str!("rspirv.h");
+
+/// An opaque handle to a parsed SPIR-V module.
+///
+/// It is `rspirv_module` on the C side.
+pub struct Module {
+    module: mr::Module,
+}
+
+/// Parses the SPIR-V binary in `words` containing `word_count` words.
+///
+/// Returns the parsed module on success, or null on failure, including a
+/// panic of the parser. On failure, if `error` is not null, it receives a
+/// description of the failure, which must be released with
+/// [`rspirv_string_free`](fn.rspirv_string_free.html).
+///
+/// # Safety
+///
+/// `words` must point to `word_count` readable words, and `error` must be
+/// null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn rspirv_parse(words: *const u32,
+                                      word_count: usize,
+                                      error: *mut *mut c_char)
+                                      -> *mut Module {
+    let words = if words.is_null() {
+        &[]
+    } else {
+        slice::from_raw_parts(words, word_count)
+    };
+    let result = panic::catch_unwind(|| mr::load_words(words).map_err(|s| s.to_string()));
+    match result.unwrap_or_else(|_| Err("the parser panicked".to_string())) {
+        Ok(module) => Box::into_raw(Box::new(Module { module })),
+        Err(message) => {
+            if !error.is_null() {
+                *error = into_c_string(message);
+            }
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Disassembles the given `module`.
+///
+/// Returns null if `module` is null, the disassembler panics, or the
+/// disassembly contains interior NUL characters.
+///
+/// # Safety
+///
+/// `module` must be null or a pointer returned by
+/// [`rspirv_parse`](fn.rspirv_parse.html) and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn rspirv_disassemble(module: *const Module) -> *mut c_char {
+    match module.as_ref() {
+        Some(m) => {
+            panic::catch_unwind(|| into_c_string(m.module.disassemble()))
+                .unwrap_or(ptr::null_mut())
+        }
+        None => ptr::null_mut(),
+    }
+}
+
+/// Releases a module returned by [`rspirv_parse`](fn.rspirv_parse.html).
+/// Does nothing if `module` is null.
+///
+/// # Safety
+///
+/// `module` must be null or a pointer returned by `rspirv_parse` and not yet
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn rspirv_free(module: *mut Module) {
+    if !module.is_null() {
+        let module = AssertUnwindSafe(Box::from_raw(module));
+        let _ = panic::catch_unwind(move || drop(module));
+    }
+}
+
+/// Releases a string returned by the library. Does nothing if `string` is
+/// null.
+///
+/// # Safety
+///
+/// `string` must be null or a pointer returned by this module and not yet
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn rspirv_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+fn into_c_string(s: String) -> *mut c_char {
+    match CString::new(s) {
+        Ok(s) => s.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use binary::Assemble;
+    use std::ffi::CStr;
+    use std::ptr;
+
+    #[test]
+    fn test_parse_and_disassemble() {
+        let mut b = mr::Builder::new();
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let code = b.module().assemble();
+
+        unsafe {
+            let module = super::rspirv_parse(code.as_ptr(), code.len(), ptr::null_mut());
+            assert!(!module.is_null());
+            let dis = super::rspirv_disassemble(module);
+            assert_eq!(CStr::from_ptr(dis).to_str().unwrap(),
+                       "; SPIR-V\n\
+                        ; Version: 1.2\n\
+                        ; Generator: rspirv\n\
+                        ; Bound: 1\n\
+                        OpMemoryModel Logical GLSL450");
+            super::rspirv_string_free(dis);
+            super::rspirv_free(module);
+        }
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let code = [0x07230203u32, 0x00010000];
+        let mut error = ptr::null_mut();
+        unsafe {
+            let module = super::rspirv_parse(code.as_ptr(), code.len(), &mut error);
+            assert!(module.is_null());
+            assert!(!error.is_null());
+            assert!(!CStr::from_ptr(error).to_bytes().is_empty());
+            super::rspirv_string_free(error);
+            super::rspirv_free(module);
+        }
+    }
+
+    #[test]
+    fn test_header_declares_exports() {
+        // The C prototypes of the exported functions, which must match
+        // their Rust signatures.
+        let prototypes = [
+            "rspirv_module* rspirv_parse(const uint32_t* words, size_t word_count, char** error);",
+            "char* rspirv_disassemble(const rspirv_module* module);",
+            "void rspirv_free(rspirv_module* module);",
+            "void rspirv_string_free(char* string);",
+        ];
+        // The comments mention the functions too.
+        let mut code = String::new();
+        let mut rest = super::HEADER;
+        while let Some(start) = rest.find("/*") {
+            code.push_str(&rest[..start]);
+            rest = &rest[start..];
+            rest = &rest[rest.find("*/").unwrap() + 2..];
+        }
+        code.push_str(rest);
+        let code = code.split_whitespace().collect::<Vec<_>>().join(" ");
+        for prototype in &prototypes {
+            assert!(code.contains(prototype), "{}", prototype);
+        }
+        // And the header declares no other functions.
+        assert_eq!(code.matches(");").count(), prototypes.len());
+    }
+}
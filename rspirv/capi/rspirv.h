@@ -0,0 +1,80 @@
+/*
+ * Copyright 2017 Google Inc.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+/*
+ * AUTOMATICALLY GENERATED from the C API in rspirv/capi/mod.rs.
+ * DO NOT MODIFY!
+ */
+
+/*
+ * C API of rspirv.
+ *
+ * The static and dynamic libraries are built with the `capi` feature
+ * enabled, e.g.,
+ *
+ *     cargo build -p rspirv --release --features capi
+ *
+ * All strings returned by the library are NUL-terminated and owned by the
+ * caller, who must release them with rspirv_string_free().
+ */
+
+#ifndef RSPIRV_H
+#define RSPIRV_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* An opaque handle to a parsed SPIR-V module. */
+typedef struct rspirv_module rspirv_module;
+
+/*
+ * Parses the SPIR-V binary in `words` containing `word_count` words.
+ *
+ * Returns the parsed module on success, or null on failure, including a panic
+ * of the parser. On failure, if `error` is not null, it receives a description
+ * of the failure, which must be released with rspirv_string_free().
+ */
+rspirv_module* rspirv_parse(const uint32_t* words, size_t word_count,
+                            char** error);
+
+/*
+ * Disassembles the given `module`.
+ *
+ * Returns null if `module` is null, the disassembler panics, or the disassembly
+ * contains interior NUL characters.
+ */
+char* rspirv_disassemble(const rspirv_module* module);
+
+/*
+ * Releases a module returned by rspirv_parse(). Does nothing if `module` is
+ * null.
+ */
+void rspirv_free(rspirv_module* module);
+
+/*
+ * Releases a string returned by the library. Does nothing if `string` is null.
+ */
+void rspirv_string_free(char* string);
+
+#ifdef __cplusplus
+}  /* extern "C" */
+#endif
+
+#endif  /* RSPIRV_H */
@@ -0,0 +1,278 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use binary;
+use grammar;
+use mr;
+use spirv;
+
+#[cfg(feature = "json")]
+use serde_json::Value;
+use spirv::Word;
+use std::collections::BTreeMap;
+
+/// A source location recorded by an `OpLine` instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Location {
+    /// The result id of the `OpString` naming the file.
+    pub file: Word,
+    /// The line in the file.
+    pub line: u32,
+    /// The column in the line.
+    pub column: u32,
+}
+
+/// Source locations of the instructions of a module, as given by `OpLine`
+/// and `OpNoLine`.
+///
+/// Instructions are identified by their index in the binary form of the
+/// module, counting from zero at the first instruction after the header.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LineMap {
+    files: BTreeMap<Word, String>,
+    locations: BTreeMap<usize, Location>,
+}
+
+impl LineMap {
+    /// Returns the name of the file given by the `OpString` with the given
+    /// result id.
+    pub fn file(&self, id: Word) -> Option<&str> {
+        self.files.get(&id).map(|s| s.as_str())
+    }
+
+    /// Returns the source location of the instruction at `index`.
+    pub fn location(&self, index: usize) -> Option<&Location> {
+        self.locations.get(&index)
+    }
+
+    /// Returns the source locations of all located instructions, keyed by
+    /// instruction index.
+    pub fn locations(&self) -> &BTreeMap<usize, Location> {
+        &self.locations
+    }
+
+    /// Exports this line map as a [version 3 source map][spec].
+    ///
+    /// The module is taken to be a single generated line, in which each
+    /// instruction is one column: the generated column of a mapping is the
+    /// index of the instruction. Source lines and columns are converted to
+    /// start from zero, as source maps require. Instructions in files not
+    /// named by an `OpString` are left unmapped.
+    ///
+    /// [spec]: https://sourcemaps.info/spec.html
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Value {
+        let sources: Vec<Word> = self.files.keys().cloned().collect();
+        let mut mappings = String::new();
+        // The fields of the previous segment, which later ones are relative to.
+        let mut previous = [0i64; 4];
+        let mut last: Option<(usize, &Location)> = None;
+        let located = self.locations.iter().filter(|&(_, l)| self.files.contains_key(&l.file));
+        for (&index, location) in located {
+            // A gap after a run of mapped instructions leaves them unmapped.
+            if let Some((last_index, _)) = last {
+                if last_index + 1 != index {
+                    push_segment(&mut mappings, &mut previous, &[(last_index + 1) as i64]);
+                }
+            }
+            let continues = match last {
+                Some((last_index, last_location)) => {
+                    last_index + 1 == index && last_location == location
+                }
+                None => false,
+            };
+            if !continues {
+                let source = sources.binary_search(&location.file).unwrap();
+                push_segment(&mut mappings,
+                             &mut previous,
+                             &[index as i64,
+                               source as i64,
+                               i64::from(location.line.saturating_sub(1)),
+                               i64::from(location.column.saturating_sub(1))]);
+            }
+            last = Some((index, location));
+        }
+        if let Some((last_index, _)) = last {
+            push_segment(&mut mappings, &mut previous, &[(last_index + 1) as i64]);
+        }
+
+        json!({
+            "version": 3,
+            "sources": self.files.values().collect::<Vec<&String>>(),
+            "names": [],
+            "mappings": mappings,
+        })
+    }
+}
+
+/// Appends a segment with the given absolute `fields` to `mappings`,
+/// encoding each relative to the same field of the `previous` segment.
+#[cfg(feature = "json")]
+fn push_segment(mappings: &mut String, previous: &mut [i64; 4], fields: &[i64]) {
+    const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    if !mappings.is_empty() {
+        mappings.push(',');
+    }
+    for (field, prev) in fields.iter().zip(previous.iter_mut()) {
+        let delta = field - *prev;
+        *prev = *field;
+        // Base64 VLQ: the sign goes in the lowest bit, then five bits per
+        // digit, lowest first, with the sixth bit flagging continuation.
+        let mut vlq = if delta < 0 { ((-delta) << 1) | 1 } else { delta << 1 };
+        loop {
+            let mut digit = (vlq & 0x1f) as usize;
+            vlq >>= 5;
+            if vlq > 0 {
+                digit |= 0x20;
+            }
+            mappings.push(BASE64[digit] as char);
+            if vlq == 0 {
+                break;
+            }
+        }
+    }
+}
+
+/// Collects the `OpLine` source locations of the instructions of `module`.
+///
+/// An `OpLine` applies to the instructions after it, until an `OpNoLine`,
+/// another `OpLine`, or the end of the block or function. `OpLine` and
+/// `OpNoLine` themselves are counted in instruction indices, but not
+/// located. Files not named by an `OpString` are left out of
+/// [`LineMap::file`](struct.LineMap.html#method.file), but their locations
+/// are kept.
+///
+/// # Examples
+///
+/// ```
+/// extern crate rspirv;
+/// extern crate spirv_headers as spirv;
+///
+/// use rspirv::debuginfo::line_map;
+///
+/// fn main() {
+///     let mut b = rspirv::mr::Builder::new();
+///     let file = b.string("shader.glsl");
+///     b.line(file, 3, 7);
+///     b.type_void();
+///     let map = line_map(&b.module());
+///
+///     // OpString is at 0, OpLine at 1, and OpTypeVoid at 2.
+///     let location = map.location(2).unwrap();
+///     assert_eq!(map.file(location.file), Some("shader.glsl"));
+///     assert_eq!((location.line, location.column), (3, 7));
+/// }
+/// ```
+pub fn line_map(module: &mr::Module) -> LineMap {
+    let mut map = LineMap::default();
+    for inst in &module.debugs {
+        if let (spirv::Op::String, Some(id), Some(mr::Operand::LiteralString(file))) =
+            (inst.class.opcode, inst.result_id, inst.operands.first()) {
+            map.files.insert(id, file.clone());
+        }
+    }
+
+    let mut index = 0;
+    let mut current = None;
+    binary::visit_layout(module, &mut |inst| {
+        match inst.class.opcode {
+            spirv::Op::Line => {
+                current = match &inst.operands[..] {
+                    &[mr::Operand::IdRef(file),
+                      mr::Operand::LiteralInt32(line),
+                      mr::Operand::LiteralInt32(column)] => {
+                        Some(Location { file, line, column })
+                    }
+                    _ => None,
+                }
+            }
+            spirv::Op::NoLine => current = None,
+            opcode => {
+                if let Some(location) = current {
+                    map.locations.insert(index, location);
+                }
+                if grammar::reflect::is_terminator(opcode) ||
+                   opcode == spirv::Op::FunctionEnd {
+                    current = None;
+                }
+            }
+        }
+        index += 1;
+    });
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use binary::Assemble;
+    use mr;
+    use spirv;
+
+    use super::{line_map, Location};
+
+    /// Builds a module whose function body adds two numbers on line 2 of
+    /// "a.comp" and returns on line 4 of "b.comp".
+    fn build() -> (mr::Module, spirv::Word, spirv::Word) {
+        let mut b = mr::Builder::new();
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let a = b.string("a.comp");
+        let c = b.string("b.comp");
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let one = b.constant_u32(uint, 1);
+        let voidfvoid = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.line(a, 2, 5);
+        b.iadd(uint, None, one, one).unwrap();
+        b.iadd(uint, None, one, one).unwrap();
+        b.no_line();
+        b.iadd(uint, None, one, one).unwrap();
+        b.line(c, 4, 1);
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        (b.module(), a, c)
+    }
+
+    #[test]
+    fn test_line_map() {
+        let (m, a, c) = build();
+        let map = line_map(&m);
+        assert_eq!(map.file(a), Some("a.comp"));
+        assert_eq!(map.file(c), Some("b.comp"));
+
+        // 0: OpMemoryModel, 1-2: OpString, 3-6: types and constant,
+        // 7: OpFunction, 8: OpLabel, 9: OpLine, 10-11: OpIAdd,
+        // 12: OpNoLine, 13: OpIAdd, 14: OpLine, 15: OpReturn.
+        let on_a = Location { file: a, line: 2, column: 5 };
+        let expected = vec![(10, on_a), (11, on_a), (15, Location { file: c, line: 4, column: 1 })];
+        assert_eq!(map.locations().iter().map(|(&i, &l)| (i, l)).collect::<Vec<_>>(),
+                   expected);
+
+        // Indices are those of the binary.
+        let loaded = mr::load_words(m.assemble()).unwrap();
+        assert_eq!(line_map(&loaded), map);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_to_json() {
+        let (m, _, _) = build();
+        let json = line_map(&m).to_json();
+        assert_eq!(json["version"], 3);
+        assert_eq!(json["sources"], json!(["a.comp", "b.comp"]));
+        // [10, 0, 1, 4], [12], [15, 1, 3, 0], [16]
+        assert_eq!(json["mappings"], "UACI,E,GCEJ,C");
+    }
+}
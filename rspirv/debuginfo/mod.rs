@@ -0,0 +1,32 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Source-level debug information.
+//!
+//! This module reads the `NonSemantic.Shader.DebugInfo.100` extended
+//! instructions of a module into a [`SourceMap`](struct.SourceMap.html),
+//! which relates instructions to the source files, functions, and lines
+//! they were compiled from. The instructions themselves can be emitted with
+//! the `debug_*` methods of [`mr::Builder`](../mr/struct.Builder.html).
+//!
+//! For the core `OpLine` and `OpNoLine` instructions, a
+//! [`LineMap`](struct.LineMap.html) relates instructions to file
+//! locations by their index in the binary, and can be exported as a JSON
+//! source map with the `json` feature.
+
+pub use self::line_map::{line_map, LineMap, Location};
+pub use self::source_map::{source_map, DebugFunction, Line, Position, Source, SourceMap};
+
+mod line_map;
+mod source_map;
@@ -0,0 +1,425 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grammar;
+use mr;
+use spirv;
+
+use num_traits::FromPrimitive;
+use spirv::{DebugInfoOp, Word};
+use std::collections::BTreeMap;
+
+/// A source file recorded by a `DebugSource` instruction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Source {
+    /// The path of the file.
+    pub file: String,
+    /// The text of the file, stitched together from any following
+    /// `DebugSourceContinued` instructions, if embedded.
+    pub text: Option<String>,
+}
+
+/// A function recorded by a `DebugFunction` instruction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DebugFunction {
+    /// The name of the function in the source.
+    pub name: String,
+    /// The result id of the `DebugSource` the function is defined in.
+    pub source: Word,
+    /// The line the function is defined on.
+    pub line: u32,
+    /// The column the function is defined on.
+    pub column: u32,
+    /// The result id of the `OpFunction` implementing it, as given by a
+    /// `DebugFunctionDefinition` instruction.
+    pub definition: Option<Word>,
+}
+
+/// A source range recorded by a `DebugLine` instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Line {
+    /// The result id of the `DebugSource` the range is in.
+    pub source: Word,
+    /// The first line of the range.
+    pub line_start: u32,
+    /// The last line of the range.
+    pub line_end: u32,
+    /// The first column of the range.
+    pub column_start: u32,
+    /// The last column of the range.
+    pub column_end: u32,
+}
+
+/// Where an instruction sits in the functions of a module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    /// The index of the function in `mr::Module::functions`.
+    pub function: usize,
+    /// The index of the block in `mr::Function::basic_blocks`.
+    pub block: usize,
+    /// The index of the instruction in `mr::BasicBlock::instructions`.
+    pub instruction: usize,
+}
+
+/// Debug information of a module, queryable by instruction.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SourceMap {
+    sources: BTreeMap<Word, Source>,
+    functions: BTreeMap<Word, DebugFunction>,
+    lines: BTreeMap<Position, Line>,
+    positions: BTreeMap<Word, Position>,
+}
+
+impl SourceMap {
+    /// Returns the source file recorded by the `DebugSource` with the given
+    /// result id.
+    pub fn source(&self, id: Word) -> Option<&Source> {
+        self.sources.get(&id)
+    }
+
+    /// Returns all source files, keyed by `DebugSource` result ids.
+    pub fn sources(&self) -> &BTreeMap<Word, Source> {
+        &self.sources
+    }
+
+    /// Returns the function recorded by the `DebugFunction` with the given
+    /// result id.
+    pub fn function(&self, id: Word) -> Option<&DebugFunction> {
+        self.functions.get(&id)
+    }
+
+    /// Returns all functions, keyed by `DebugFunction` result ids.
+    pub fn functions(&self) -> &BTreeMap<Word, DebugFunction> {
+        &self.functions
+    }
+
+    /// Returns the function defined by the `OpFunction` with the given
+    /// result id.
+    pub fn function_of(&self, function: Word) -> Option<&DebugFunction> {
+        self.functions.values().find(|f| f.definition == Some(function))
+    }
+
+    /// Returns the source range of the instruction at `position`.
+    pub fn line(&self, position: Position) -> Option<&Line> {
+        self.lines.get(&position)
+    }
+
+    /// Returns the source range of the instruction with the given result id.
+    pub fn line_of(&self, id: Word) -> Option<&Line> {
+        self.positions.get(&id).and_then(|position| self.lines.get(position))
+    }
+
+    /// Returns the positions of all instructions whose source range covers
+    /// `line` in the `DebugSource` with result id `source`, in module order.
+    pub fn positions_at(&self, source: Word, line: u32) -> Vec<Position> {
+        self.lines
+            .iter()
+            .filter(|&(_, l)| l.source == source && l.line_start <= line && line <= l.line_end)
+            .map(|(&position, _)| position)
+            .collect()
+    }
+}
+
+/// Id-indexed values the debug info instructions refer to.
+struct Values<'m> {
+    set: Option<Word>,
+    strings: BTreeMap<Word, &'m str>,
+    constants: BTreeMap<Word, u32>,
+}
+
+impl<'m> Values<'m> {
+    fn new(module: &'m mr::Module) -> Values<'m> {
+        let set = mr::ExtInstRegistry::of(module).id_of(grammar::ExtInstSet::DebugInfo100);
+        let strings = module.debugs
+            .iter()
+            .filter_map(|inst| match (inst.class.opcode, inst.result_id, inst.operands.first()) {
+                (spirv::Op::String, Some(id), Some(mr::Operand::LiteralString(s))) => {
+                    Some((id, s.as_str()))
+                }
+                _ => None,
+            })
+            .collect();
+        let constants = module.types_global_values
+            .iter()
+            .filter_map(|inst| match (inst.class.opcode, inst.result_id, inst.operands.first()) {
+                (spirv::Op::Constant, Some(id), Some(&mr::Operand::LiteralInt32(v))) => {
+                    Some((id, v))
+                }
+                _ => None,
+            })
+            .collect();
+        Values { set, strings, constants }
+    }
+
+    /// Returns the debug info opcode and arguments of `inst`, if it is a
+    /// `NonSemantic.Shader.DebugInfo.100` instruction.
+    fn decode(&self, inst: &mr::Instruction) -> Option<(DebugInfoOp, Vec<Word>)> {
+        if inst.class.opcode != spirv::Op::ExtInst {
+            return None;
+        }
+        // Instructions built by hand may carry the raw opcode.
+        let opcode = match (&inst.operands[..], self.set) {
+            (&[_, mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, n), ..], _) => {
+                DebugInfoOp::from_u32(n)?
+            }
+            (&[mr::Operand::IdRef(set), mr::Operand::LiteralExtInstInteger(n), ..], Some(s))
+                if set == s => DebugInfoOp::from_u32(n)?,
+            _ => return None,
+        };
+        let args = inst.operands[2..]
+            .iter()
+            .map(|operand| match *operand {
+                mr::Operand::IdRef(id) => Some(id),
+                _ => None,
+            })
+            .collect::<Option<Vec<Word>>>()?;
+        Some((opcode, args))
+    }
+
+    fn string(&self, id: Word) -> Option<String> {
+        self.strings.get(&id).map(|s| s.to_string())
+    }
+
+    fn constant(&self, id: Word) -> Option<u32> {
+        self.constants.get(&id).cloned()
+    }
+
+    fn line(&self, args: &[Word]) -> Option<Line> {
+        Some(Line {
+            source: *args.first()?,
+            line_start: self.constant(*args.get(1)?)?,
+            line_end: self.constant(*args.get(2)?)?,
+            column_start: self.constant(*args.get(3)?)?,
+            column_end: self.constant(*args.get(4)?)?,
+        })
+    }
+}
+
+/// Collects the `NonSemantic.Shader.DebugInfo.100` debug information of
+/// `module` into a source map.
+///
+/// A `DebugLine` applies to the instructions after it in the same block,
+/// until a `DebugNoLine` or another `DebugLine`. Instructions whose
+/// operands cannot be resolved, e.g., lines not given by 32-bit
+/// `OpConstant`s, are skipped.
+///
+/// # Examples
+///
+/// ```
+/// extern crate rspirv;
+/// extern crate spirv_headers as spirv;
+///
+/// use rspirv::debuginfo::source_map;
+///
+/// fn main() {
+///     let mut b = rspirv::mr::Builder::new();
+///     let void = b.type_void();
+///     let file = b.string("shader.glsl");
+///     let text = b.string("void main() {}");
+///     let source = b.debug_source(void, file, Some(text));
+///
+///     let map = source_map(&b.module());
+///     let source = map.source(source).unwrap();
+///     assert_eq!(source.file, "shader.glsl");
+///     assert_eq!(source.text, Some("void main() {}".to_string()));
+/// }
+/// ```
+pub fn source_map(module: &mr::Module) -> SourceMap {
+    let values = Values::new(module);
+    let mut map = SourceMap::default();
+
+    // The source whose text a DebugSourceContinued continues.
+    let mut continued = None;
+    for inst in &module.types_global_values {
+        let (opcode, args) = match values.decode(inst) {
+            Some(decoded) => decoded,
+            None => continue,
+        };
+        match opcode {
+            DebugInfoOp::DebugSource => {
+                continued = None;
+                let (id, file) = match (inst.result_id, args.first().and_then(|&f| values.string(f))) {
+                    (Some(id), Some(file)) => (id, file),
+                    _ => continue,
+                };
+                let text = args.get(1).and_then(|&t| values.string(t));
+                map.sources.insert(id, Source { file, text });
+                continued = Some(id);
+            }
+            DebugInfoOp::DebugSourceContinued => {
+                let text = args.first().and_then(|&t| values.string(t));
+                let source = continued.and_then(|id| map.sources.get_mut(&id));
+                if let (Some(source), Some(text)) = (source, text) {
+                    source.text.get_or_insert_with(String::new).push_str(&text);
+                }
+            }
+            DebugInfoOp::DebugFunction => {
+                continued = None;
+                let function = (|| {
+                    Some(DebugFunction {
+                        name: values.string(*args.first()?)?,
+                        source: *args.get(2)?,
+                        line: values.constant(*args.get(3)?)?,
+                        column: values.constant(*args.get(4)?)?,
+                        definition: None,
+                    })
+                })();
+                if let (Some(id), Some(function)) = (inst.result_id, function) {
+                    map.functions.insert(id, function);
+                }
+            }
+            _ => continued = None,
+        }
+    }
+
+    for (f, function) in module.functions.iter().enumerate() {
+        for (b, block) in function.basic_blocks.iter().enumerate() {
+            let mut line = None;
+            for (i, inst) in block.instructions.iter().enumerate() {
+                match values.decode(inst) {
+                    Some((DebugInfoOp::DebugLine, args)) => line = values.line(&args),
+                    Some((DebugInfoOp::DebugNoLine, _)) => line = None,
+                    Some((DebugInfoOp::DebugFunctionDefinition, args)) => {
+                        if let (Some(debug), Some(&definition)) = (args.first(), args.get(1)) {
+                            if let Some(debug) = map.functions.get_mut(debug) {
+                                debug.definition = Some(definition);
+                            }
+                        }
+                    }
+                    _ => {
+                        if let Some(line) = line {
+                            let position = Position { function: f, block: b, instruction: i };
+                            map.lines.insert(position, line);
+                            if let Some(id) = inst.result_id {
+                                map.positions.insert(id, position);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use binary::{Assemble, Disassemble};
+    use mr;
+    use spirv;
+
+    use super::{source_map, DebugFunction, Line, Position};
+
+    /// Builds a module with one function whose body adds two numbers on
+    /// line 2 of a source file spread over a DebugSource and a
+    /// DebugSourceContinued.
+    fn build() -> (mr::Module, spirv::Word, spirv::Word, spirv::Word) {
+        let mut b = mr::Builder::new();
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let file = b.string("a.comp");
+        let head = b.string("void main() {\n");
+        let tail = b.string("  x = 1 + 2;\n}\n");
+        let name = b.string("main");
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let numbers: Vec<spirv::Word> = (0..8).map(|n| b.constant_u32(uint, n)).collect();
+        let voidfvoid = b.type_function(void, vec![]);
+
+        let source = b.debug_source(void, file, Some(head));
+        b.debug_source_continued(void, tail);
+        let unit = b.debug_compilation_unit(void, numbers[1], numbers[4], source, numbers[2]);
+        let ty = b.debug_type_function(void, numbers[0], void, vec![]);
+        let debug = b.debug_function(void,
+                                     name,
+                                     ty,
+                                     source,
+                                     numbers[1],
+                                     numbers[5],
+                                     unit,
+                                     name,
+                                     numbers[0],
+                                     numbers[1],
+                                     None);
+
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.debug_function_definition(void, debug, f);
+        b.debug_line(void, source, numbers[2], numbers[2], numbers[6], numbers[7]);
+        let sum = b.iadd(uint, None, numbers[1], numbers[2]).unwrap();
+        b.debug_no_line(void);
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        (b.module(), source, debug, sum)
+    }
+
+    #[test]
+    fn test_sources() {
+        let (m, source, _, _) = build();
+        let map = source_map(&m);
+        assert_eq!(map.sources().len(), 1);
+        let source = map.source(source).unwrap();
+        assert_eq!(source.file, "a.comp");
+        assert_eq!(source.text.as_ref().unwrap(),
+                   "void main() {\n  x = 1 + 2;\n}\n");
+    }
+
+    #[test]
+    fn test_functions_and_lines() {
+        let (m, source, debug, sum) = build();
+        let f = m.functions[0].def.as_ref().unwrap().result_id.unwrap();
+        let map = source_map(&m);
+
+        let expected = DebugFunction {
+            name: "main".to_string(),
+            source,
+            line: 1,
+            column: 5,
+            definition: Some(f),
+        };
+        assert_eq!(map.function(debug), Some(&expected));
+        assert_eq!(map.function_of(f), Some(&expected));
+
+        let line = Line {
+            source,
+            line_start: 2,
+            line_end: 2,
+            column_start: 6,
+            column_end: 7,
+        };
+        let position = Position { function: 0, block: 0, instruction: 2 };
+        assert_eq!(map.line_of(sum), Some(&line));
+        assert_eq!(map.line(position), Some(&line));
+        assert_eq!(map.positions_at(source, 2), vec![position]);
+        // Return comes after DebugNoLine.
+        assert_eq!(map.line(Position { instruction: 4, ..position }), None);
+        assert!(map.positions_at(source, 1).is_empty());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let (m, source, _, sum) = build();
+        let m = mr::load_words(m.assemble()).unwrap();
+        let map = source_map(&m);
+        assert_eq!(map.line_of(sum).unwrap().source, source);
+
+        let set = m.ext_inst_imports[0].result_id.unwrap();
+        let void = m.types_global_values[0].result_id.unwrap();
+        let dis = m.disassemble();
+        assert!(dis.contains(&format!("%{} = OpExtInst  %{}  %{} DebugSource %1 %2",
+                                      source,
+                                      void,
+                                      set)));
+        assert!(dis.contains("OpExtension \"SPV_KHR_non_semantic_info\""));
+    }
+}
@@ -0,0 +1,155 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use spirv;
+
+use spirv::Word;
+use std::{error, fmt, result};
+
+/// Ways modules fall outside a target environment.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The SPIR-V version of the module, as major and minor numbers.
+    Version(u8, u8),
+    /// A declared capability.
+    Capability(spirv::Capability),
+    /// A declared extension.
+    Extension(String),
+    /// The addressing and memory models of the module.
+    MemoryModel(spirv::AddressingModel, spirv::MemoryModel),
+    /// The module has neither an entry point nor the `Linkage`
+    /// capability.
+    MissingEntryPoint,
+    /// The member of the given index of the given `Block` or
+    /// `BufferBlock` struct type has no `Offset` decoration.
+    MissingOffset(Word, u32),
+    /// The given variable of the given storage class has an initializer.
+    Initializer(Word, spirv::StorageClass),
+    /// The given instruction computes a variable pointer without the
+    /// capability allowing it, if any; see
+    /// [`variable_pointers`](../analysis/fn.variable_pointers.html).
+    VariablePointer(Word, Option<spirv::Capability>),
+    /// An instruction of the given opcode refers to the given id, which
+    /// nothing in the module defines.
+    UndefinedId(spirv::Op, Word),
+    /// The given variable or function parameter, holding or being a
+    /// `PhysicalStorageBuffer` pointer, has none or both of the aliasing
+    /// decorations it needs one of.
+    PointerAliasing(Word),
+    /// An instruction of the given opcode accesses memory through the
+    /// given `PhysicalStorageBuffer` pointer without the `Aligned` memory
+    /// operand.
+    MissingAlignment(spirv::Op, Word),
+    /// The given entry point lacks the given execution mode.
+    MissingExecutionMode(Word, spirv::ExecutionMode),
+    /// The given mesh shader entry point declares none or several output
+    /// topologies, or outputs the primitive indices of another topology.
+    MeshTopology(Word),
+    /// The given variable, decorated `PerPrimitiveEXT`, is neither an
+    /// input nor an output.
+    PerPrimitive(Word),
+    /// The given result of an instruction of the given opcode, or the
+    /// object stored by it, is computed from cooperative matrices of
+    /// mismatched types: ones of other uses, scopes, or shapes.
+    CooperativeMatrix(spirv::Op, Word),
+    /// An atomic instruction of the given opcode operates on values whose
+    /// type or width requires the given capability, which is not declared.
+    AtomicWidth(spirv::Op, spirv::Capability),
+    /// Push constants are read through the given pointer up to the given
+    /// end, in bytes, beyond the size allowed.
+    PushConstantSize(Word, u32),
+}
+
+impl Error {
+    /// Gives an descriptive string for each error.
+    ///
+    /// This method is intended to be used by fmt::Display and error::Error to
+    /// avoid duplication in implementation. So it's private.
+    fn describe(&self) -> &str {
+        match *self {
+            Error::Version(..) => "SPIR-V version not allowed",
+            Error::Capability(_) => "capability not allowed",
+            Error::Extension(_) => "extension not allowed",
+            Error::MemoryModel(..) => "memory model not allowed",
+            Error::MissingEntryPoint => "missing entry point",
+            Error::MissingOffset(..) => "missing member offset",
+            Error::Initializer(..) => "initializer not allowed",
+            Error::VariablePointer(..) => "variable pointer not allowed",
+            Error::UndefinedId(..) => "undefined id",
+            Error::PointerAliasing(_) => "missing or conflicting pointer aliasing",
+            Error::MissingAlignment(..) => "missing alignment",
+            Error::MissingExecutionMode(..) => "missing execution mode",
+            Error::MeshTopology(_) => "inconsistent mesh output topology",
+            Error::PerPrimitive(_) => "per-primitive decoration not allowed",
+            Error::CooperativeMatrix(..) => "mismatched cooperative matrix types",
+            Error::AtomicWidth(..) => "atomic operand width without capability",
+            Error::PushConstantSize(..) => "push constant access beyond the limit",
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        self.describe()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Version(major, minor) => write!(f, "{} {}.{}", self.describe(), major, minor),
+            Error::Capability(capability) => write!(f, "{} {:?}", self.describe(), capability),
+            Error::Extension(ref name) => write!(f, "{} {}", self.describe(), name),
+            Error::MemoryModel(addressing, memory) => {
+                write!(f, "{} {:?} {:?}", self.describe(), addressing, memory)
+            }
+            Error::MissingEntryPoint => write!(f, "{}", self.describe()),
+            Error::MissingOffset(ty, member) => {
+                write!(f, "{} of member {} of %{}", self.describe(), member, ty)
+            }
+            Error::Initializer(variable, class) => {
+                write!(f, "{} on {:?} variable %{}", self.describe(), class, variable)
+            }
+            Error::VariablePointer(id, Some(capability)) => {
+                write!(f, "{} %{} without {:?}", self.describe(), id, capability)
+            }
+            Error::VariablePointer(id, None) => write!(f, "{} %{}", self.describe(), id),
+            Error::UndefinedId(opcode, id) => {
+                write!(f, "{} %{} used by Op{:?}", self.describe(), id, opcode)
+            }
+            Error::PointerAliasing(id) => write!(f, "{} on %{}", self.describe(), id),
+            Error::MissingAlignment(opcode, pointer) => {
+                write!(f, "{} of Op{:?} through %{}", self.describe(), opcode, pointer)
+            }
+            Error::MissingExecutionMode(entry_point, mode) => {
+                write!(f, "{} {:?} of %{}", self.describe(), mode, entry_point)
+            }
+            Error::MeshTopology(entry_point) => {
+                write!(f, "{} of %{}", self.describe(), entry_point)
+            }
+            Error::PerPrimitive(variable) => write!(f, "{} on %{}", self.describe(), variable),
+            Error::CooperativeMatrix(opcode, id) => {
+                write!(f, "{} for %{} of Op{:?}", self.describe(), id, opcode)
+            }
+            Error::AtomicWidth(opcode, capability) => {
+                write!(f, "{} {:?} of Op{:?}", self.describe(), capability, opcode)
+            }
+            Error::PushConstantSize(pointer, end) => {
+                write!(f, "{} through %{} up to byte {}", self.describe(), pointer, end)
+            }
+        }
+    }
+}
+
+pub type Result<T> = result::Result<T, Error>;
@@ -0,0 +1,486 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Target environments.
+//!
+//! A [`TargetEnv`](enum.TargetEnv.html) describes what a client API
+//! accepts of SPIR-V modules: versions, capabilities, extensions, memory
+//! models, and environment-specific validation rules. Modules can be
+//! checked against one with [`TargetEnv::validate`], and the passes in
+//! [`passes`](../passes/index.html) take one to infer capabilities and
+//! legalize for it. A [`Validator`](struct.Validator.html) validates a
+//! module again after edits, only checking what changed, and
+//! [`validate_push_constant_size`](fn.validate_push_constant_size.html)
+//! checks the push constants read against a device limit.
+//!
+//! [`TargetEnv::validate`]: enum.TargetEnv.html#method.validate
+
+pub use self::error::{Error, Result};
+pub use self::validate::{validate_push_constant_size, Validator};
+
+mod error;
+mod validate;
+
+use spirv;
+
+use std::fmt;
+
+/// The client API family of a [`TargetEnv`](enum.TargetEnv.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Api {
+    /// No client API: everything SPIR-V allows.
+    Universal,
+    Vulkan,
+    OpenGL,
+    OpenCL,
+}
+
+/// The environment-specific rules modules are validated against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidationRules {
+    /// Whether modules need an entry point unless they declare the
+    /// `Linkage` capability.
+    pub requires_entry_point: bool,
+    /// Whether all members of `Block` and `BufferBlock` structs need an
+    /// `Offset` decoration.
+    pub requires_block_offsets: bool,
+    /// Whether block layouts may follow the relaxed rules of
+    /// `VK_KHR_relaxed_block_layout`.
+    pub relaxed_block_layout: bool,
+    /// The storage classes of the variables that may have initializers,
+    /// or `None` if those of all storage classes may.
+    pub initializer_storage_classes: Option<&'static [spirv::StorageClass]>,
+    /// Whether pointers may only be selected, merged, returned, or offset
+    /// as the declared variable pointer capabilities allow.
+    pub checks_variable_pointers: bool,
+    /// Whether variables and function parameters holding or being
+    /// `PhysicalStorageBuffer` pointers need an aliasing decoration, and
+    /// loads and stores through them the `Aligned` memory operand.
+    pub checks_physical_storage_buffers: bool,
+    /// Whether mesh shaders need their output topology and limits, and
+    /// only inputs and outputs may be decorated `PerPrimitiveEXT`.
+    pub checks_mesh_shaders: bool,
+}
+
+/// A target environment, named after the client API and version modules
+/// are consumed by, like the `--target-env` of the SPIR-V tools.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TargetEnv {
+    /// SPIR-V of the given major and minor version, for no particular
+    /// client API.
+    Universal(u8, u8),
+    Vulkan1_0,
+    Vulkan1_1,
+    /// Vulkan 1.1 with `VK_KHR_spirv_1_4`.
+    Vulkan1_1Spirv1_4,
+    Vulkan1_2,
+    Vulkan1_3,
+    OpenGL4_0,
+    OpenGL4_1,
+    OpenGL4_2,
+    OpenGL4_3,
+    OpenGL4_5,
+    OpenCL1_2,
+    OpenCL2_0,
+    OpenCL2_1,
+    OpenCL2_2,
+}
+
+const SHADER_INITIALIZERS: &[spirv::StorageClass] = &[
+    spirv::StorageClass::Output,
+    spirv::StorageClass::Private,
+    spirv::StorageClass::Function,
+];
+
+const WORKGROUP_INITIALIZERS: &[spirv::StorageClass] = &[
+    spirv::StorageClass::Output,
+    spirv::StorageClass::Private,
+    spirv::StorageClass::Function,
+    spirv::StorageClass::Workgroup,
+];
+
+const VULKAN_1_0: &[spirv::Capability] = &[
+    spirv::Capability::Matrix,
+    spirv::Capability::Shader,
+    spirv::Capability::InputAttachment,
+    spirv::Capability::Sampled1D,
+    spirv::Capability::Image1D,
+    spirv::Capability::SampledBuffer,
+    spirv::Capability::ImageBuffer,
+    spirv::Capability::ImageQuery,
+    spirv::Capability::DerivativeControl,
+    spirv::Capability::Geometry,
+    spirv::Capability::Tessellation,
+    spirv::Capability::Float64,
+    spirv::Capability::Int64,
+    spirv::Capability::Int64Atomics,
+    spirv::Capability::Int16,
+    spirv::Capability::TessellationPointSize,
+    spirv::Capability::GeometryPointSize,
+    spirv::Capability::ImageGatherExtended,
+    spirv::Capability::StorageImageMultisample,
+    spirv::Capability::UniformBufferArrayDynamicIndexing,
+    spirv::Capability::SampledImageArrayDynamicIndexing,
+    spirv::Capability::StorageBufferArrayDynamicIndexing,
+    spirv::Capability::StorageImageArrayDynamicIndexing,
+    spirv::Capability::ClipDistance,
+    spirv::Capability::CullDistance,
+    spirv::Capability::ImageCubeArray,
+    spirv::Capability::SampleRateShading,
+    spirv::Capability::SparseResidency,
+    spirv::Capability::MinLod,
+    spirv::Capability::SampledCubeArray,
+    spirv::Capability::ImageMSArray,
+    spirv::Capability::StorageImageExtendedFormats,
+    spirv::Capability::InterpolationFunction,
+    spirv::Capability::StorageImageReadWithoutFormat,
+    spirv::Capability::StorageImageWriteWithoutFormat,
+    spirv::Capability::MultiViewport,
+];
+
+const VULKAN_1_1: &[spirv::Capability] = &[
+    spirv::Capability::DeviceGroup,
+    spirv::Capability::MultiView,
+    spirv::Capability::GroupNonUniform,
+    spirv::Capability::GroupNonUniformVote,
+    spirv::Capability::GroupNonUniformBallot,
+    spirv::Capability::GroupNonUniformShuffle,
+    spirv::Capability::GroupNonUniformShuffleRelative,
+    spirv::Capability::StorageBuffer16BitAccess,
+    spirv::Capability::UniformAndStorageBuffer16BitAccess,
+    spirv::Capability::StoragePushConstant16,
+    spirv::Capability::StorageInputOutput16,
+    spirv::Capability::VariablePointers,
+    spirv::Capability::VariablePointersStorageBuffer,
+    spirv::Capability::DrawParameters,
+];
+
+const VULKAN_1_2: &[spirv::Capability] = &[
+    spirv::Capability::Float16,
+    spirv::Capability::Int8,
+    spirv::Capability::DenormPreserve,
+    spirv::Capability::DenormFlushToZero,
+    spirv::Capability::SignedZeroInfNanPreserve,
+    spirv::Capability::RoundingModeRTE,
+    spirv::Capability::RoundingModeRTZ,
+    spirv::Capability::VulkanMemoryModel,
+    spirv::Capability::VulkanMemoryModelDeviceScope,
+    spirv::Capability::PhysicalStorageBufferAddresses,
+    spirv::Capability::ShaderNonUniform,
+    spirv::Capability::RuntimeDescriptorArray,
+    spirv::Capability::InputAttachmentArrayDynamicIndexing,
+    spirv::Capability::UniformTexelBufferArrayDynamicIndexing,
+    spirv::Capability::StorageTexelBufferArrayDynamicIndexing,
+    spirv::Capability::UniformBufferArrayNonUniformIndexing,
+    spirv::Capability::SampledImageArrayNonUniformIndexing,
+    spirv::Capability::StorageBufferArrayNonUniformIndexing,
+    spirv::Capability::StorageImageArrayNonUniformIndexing,
+    spirv::Capability::InputAttachmentArrayNonUniformIndexing,
+    spirv::Capability::UniformTexelBufferArrayNonUniformIndexing,
+    spirv::Capability::StorageTexelBufferArrayNonUniformIndexing,
+];
+
+const VULKAN_1_3: &[spirv::Capability] = &[spirv::Capability::DemoteToHelperInvocation];
+
+const OPENGL_4: &[spirv::Capability] = &[
+    spirv::Capability::Matrix,
+    spirv::Capability::Shader,
+    spirv::Capability::Geometry,
+    spirv::Capability::Tessellation,
+    spirv::Capability::Float64,
+    spirv::Capability::AtomicStorage,
+    spirv::Capability::TessellationPointSize,
+    spirv::Capability::GeometryPointSize,
+    spirv::Capability::ImageGatherExtended,
+    spirv::Capability::StorageImageMultisample,
+    spirv::Capability::UniformBufferArrayDynamicIndexing,
+    spirv::Capability::SampledImageArrayDynamicIndexing,
+    spirv::Capability::StorageBufferArrayDynamicIndexing,
+    spirv::Capability::StorageImageArrayDynamicIndexing,
+    spirv::Capability::ClipDistance,
+    spirv::Capability::CullDistance,
+    spirv::Capability::ImageCubeArray,
+    spirv::Capability::SampleRateShading,
+    spirv::Capability::ImageRect,
+    spirv::Capability::SampledRect,
+    spirv::Capability::SparseResidency,
+    spirv::Capability::MinLod,
+    spirv::Capability::Sampled1D,
+    spirv::Capability::Image1D,
+    spirv::Capability::SampledCubeArray,
+    spirv::Capability::SampledBuffer,
+    spirv::Capability::ImageBuffer,
+    spirv::Capability::ImageMSArray,
+    spirv::Capability::StorageImageExtendedFormats,
+    spirv::Capability::ImageQuery,
+    spirv::Capability::DerivativeControl,
+    spirv::Capability::InterpolationFunction,
+    spirv::Capability::TransformFeedback,
+    spirv::Capability::GeometryStreams,
+    spirv::Capability::StorageImageReadWithoutFormat,
+    spirv::Capability::StorageImageWriteWithoutFormat,
+    spirv::Capability::MultiViewport,
+];
+
+const OPENCL_1_2: &[spirv::Capability] = &[
+    spirv::Capability::Addresses,
+    spirv::Capability::Float16Buffer,
+    spirv::Capability::Float16,
+    spirv::Capability::Float64,
+    spirv::Capability::Int8,
+    spirv::Capability::Int16,
+    spirv::Capability::Int64,
+    spirv::Capability::Int64Atomics,
+    spirv::Capability::Kernel,
+    spirv::Capability::Linkage,
+    spirv::Capability::Vector16,
+    spirv::Capability::ImageBasic,
+    spirv::Capability::ImageReadWrite,
+    spirv::Capability::ImageMipmap,
+    spirv::Capability::LiteralSampler,
+    spirv::Capability::Sampled1D,
+    spirv::Capability::Image1D,
+    spirv::Capability::SampledBuffer,
+    spirv::Capability::ImageBuffer,
+];
+
+const OPENCL_2_0: &[spirv::Capability] = &[
+    spirv::Capability::DeviceEnqueue,
+    spirv::Capability::GenericPointer,
+    spirv::Capability::Groups,
+    spirv::Capability::Pipes,
+];
+
+const OPENCL_2_2: &[spirv::Capability] = &[
+    spirv::Capability::SubgroupDispatch,
+    spirv::Capability::NamedBarrier,
+    spirv::Capability::PipeStorage,
+];
+
+impl TargetEnv {
+    /// Returns the environment of the given name, as the SPIR-V tools
+    /// spell them, e.g., `vulkan1.1spv1.4`, `opengl4.5`, `opencl2.2`, or
+    /// `spv1.3`.
+    pub fn from_name(name: &str) -> Option<TargetEnv> {
+        let env = match name {
+            "vulkan1.0" => TargetEnv::Vulkan1_0,
+            "vulkan1.1" => TargetEnv::Vulkan1_1,
+            "vulkan1.1spv1.4" => TargetEnv::Vulkan1_1Spirv1_4,
+            "vulkan1.2" => TargetEnv::Vulkan1_2,
+            "vulkan1.3" => TargetEnv::Vulkan1_3,
+            "opengl4.0" => TargetEnv::OpenGL4_0,
+            "opengl4.1" => TargetEnv::OpenGL4_1,
+            "opengl4.2" => TargetEnv::OpenGL4_2,
+            "opengl4.3" => TargetEnv::OpenGL4_3,
+            "opengl4.5" => TargetEnv::OpenGL4_5,
+            "opencl1.2" => TargetEnv::OpenCL1_2,
+            "opencl2.0" => TargetEnv::OpenCL2_0,
+            "opencl2.1" => TargetEnv::OpenCL2_1,
+            "opencl2.2" => TargetEnv::OpenCL2_2,
+            _ => {
+                let version = name.strip_prefix("spv1.")?.parse().ok()?;
+                if version > 6 {
+                    return None;
+                }
+                TargetEnv::Universal(1, version)
+            }
+        };
+        Some(env)
+    }
+
+    /// Returns the client API family of this environment.
+    pub fn api(self) -> Api {
+        match self {
+            TargetEnv::Universal(..) => Api::Universal,
+            TargetEnv::Vulkan1_0 |
+            TargetEnv::Vulkan1_1 |
+            TargetEnv::Vulkan1_1Spirv1_4 |
+            TargetEnv::Vulkan1_2 |
+            TargetEnv::Vulkan1_3 => Api::Vulkan,
+            TargetEnv::OpenGL4_0 |
+            TargetEnv::OpenGL4_1 |
+            TargetEnv::OpenGL4_2 |
+            TargetEnv::OpenGL4_3 |
+            TargetEnv::OpenGL4_5 => Api::OpenGL,
+            TargetEnv::OpenCL1_2 |
+            TargetEnv::OpenCL2_0 |
+            TargetEnv::OpenCL2_1 |
+            TargetEnv::OpenCL2_2 => Api::OpenCL,
+        }
+    }
+
+    /// Returns the latest SPIR-V version, as major and minor numbers, the
+    /// environment accepts.
+    pub fn spirv_version(self) -> (u8, u8) {
+        match self {
+            TargetEnv::Universal(major, minor) => (major, minor),
+            TargetEnv::Vulkan1_1 => (1, 3),
+            TargetEnv::Vulkan1_1Spirv1_4 => (1, 4),
+            TargetEnv::Vulkan1_2 => (1, 5),
+            TargetEnv::Vulkan1_3 => (1, 6),
+            TargetEnv::OpenCL2_2 => (1, 2),
+            _ => (1, 0),
+        }
+    }
+
+    /// Returns true if the environment accepts modules of the given
+    /// SPIR-V version.
+    pub fn allows_version(self, version: (u8, u8)) -> bool {
+        version.0 == 1 && version <= self.spirv_version()
+    }
+
+    /// Returns true if the environment accepts `capability` without
+    /// extensions.
+    ///
+    /// Capabilities of extensions are accepted where the module declares
+    /// the extension and the environment allows it.
+    pub fn allows_capability(self, capability: spirv::Capability) -> bool {
+        let version = self.spirv_version();
+        let tables: &[(bool, &[spirv::Capability])] = match self.api() {
+            Api::Universal => return true,
+            Api::Vulkan => &[
+                (true, VULKAN_1_0),
+                (version >= (1, 3), VULKAN_1_1),
+                (version >= (1, 5), VULKAN_1_2),
+                (version >= (1, 6), VULKAN_1_3),
+            ],
+            Api::OpenGL => &[(true, OPENGL_4)],
+            Api::OpenCL => &[
+                (true, OPENCL_1_2),
+                (self != TargetEnv::OpenCL1_2, OPENCL_2_0),
+                (version >= (1, 2), OPENCL_2_2),
+            ],
+        };
+        tables.iter().any(|&(enabled, table)| enabled && table.contains(&capability))
+    }
+
+    /// Returns true if the environment accepts the extension of the given
+    /// name.
+    ///
+    /// Vulkan and OpenGL accept all extensions but Intel's, which are meant
+    /// for OpenCL; OpenCL accepts Khronos and Intel extensions.
+    pub fn allows_extension(self, name: &str) -> bool {
+        match self.api() {
+            Api::Universal => true,
+            Api::Vulkan | Api::OpenGL => !name.starts_with("SPV_INTEL_"),
+            Api::OpenCL => name.starts_with("SPV_KHR_") || name.starts_with("SPV_INTEL_"),
+        }
+    }
+
+    /// Returns true if the environment accepts the given addressing and
+    /// memory models.
+    pub fn allows_memory_model(self,
+                               addressing: spirv::AddressingModel,
+                               memory: spirv::MemoryModel)
+                               -> bool {
+        use spirv::AddressingModel as A;
+        use spirv::MemoryModel as M;
+        match self.api() {
+            Api::Universal => true,
+            Api::Vulkan => {
+                (addressing == A::Logical || addressing == A::PhysicalStorageBuffer64) &&
+                (memory == M::GLSL450 || memory == M::Vulkan)
+            }
+            Api::OpenGL => addressing == A::Logical && memory == M::GLSL450,
+            Api::OpenCL => {
+                (addressing == A::Physical32 || addressing == A::Physical64) &&
+                memory == M::OpenCL
+            }
+        }
+    }
+
+    /// Returns the environment-specific rules modules are validated
+    /// against.
+    pub fn rules(self) -> ValidationRules {
+        let api = self.api();
+        let shader = api == Api::Vulkan || api == Api::OpenGL;
+        ValidationRules {
+            requires_entry_point: shader,
+            requires_block_offsets: shader,
+            relaxed_block_layout: api == Api::Vulkan && self.spirv_version() >= (1, 3),
+            initializer_storage_classes: match api {
+                _ if !shader => None,
+                // VK_KHR_zero_initialize_workgroup_memory is core in Vulkan 1.3.
+                Api::Vulkan if self == TargetEnv::Vulkan1_3 => Some(WORKGROUP_INITIALIZERS),
+                _ => Some(SHADER_INITIALIZERS),
+            },
+            checks_variable_pointers: shader,
+            checks_physical_storage_buffers: shader,
+            checks_mesh_shaders: shader,
+        }
+    }
+}
+
+impl fmt::Display for TargetEnv {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            TargetEnv::Universal(major, minor) => return write!(f, "spv{}.{}", major, minor),
+            TargetEnv::Vulkan1_0 => "vulkan1.0",
+            TargetEnv::Vulkan1_1 => "vulkan1.1",
+            TargetEnv::Vulkan1_1Spirv1_4 => "vulkan1.1spv1.4",
+            TargetEnv::Vulkan1_2 => "vulkan1.2",
+            TargetEnv::Vulkan1_3 => "vulkan1.3",
+            TargetEnv::OpenGL4_0 => "opengl4.0",
+            TargetEnv::OpenGL4_1 => "opengl4.1",
+            TargetEnv::OpenGL4_2 => "opengl4.2",
+            TargetEnv::OpenGL4_3 => "opengl4.3",
+            TargetEnv::OpenGL4_5 => "opengl4.5",
+            TargetEnv::OpenCL1_2 => "opencl1.2",
+            TargetEnv::OpenCL2_0 => "opencl2.0",
+            TargetEnv::OpenCL2_1 => "opencl2.1",
+            TargetEnv::OpenCL2_2 => "opencl2.2",
+        };
+        f.write_str(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spirv;
+
+    use super::{Api, TargetEnv};
+
+    #[test]
+    fn test_names() {
+        for name in &["vulkan1.1spv1.4", "opengl4.5", "opencl2.2", "spv1.3"] {
+            assert_eq!(TargetEnv::from_name(name).unwrap().to_string(), *name);
+        }
+        assert_eq!(TargetEnv::from_name("spv1.3"), Some(TargetEnv::Universal(1, 3)));
+        assert_eq!(TargetEnv::from_name("spv1.7"), None);
+        assert_eq!(TargetEnv::from_name("vulkan"), None);
+    }
+
+    #[test]
+    fn test_allowances() {
+        let vk10 = TargetEnv::Vulkan1_0;
+        let vk12 = TargetEnv::Vulkan1_2;
+        assert_eq!(vk12.api(), Api::Vulkan);
+        assert!(vk12.allows_version((1, 5)));
+        assert!(!vk10.allows_version((1, 1)));
+        assert!(!vk10.allows_capability(spirv::Capability::VulkanMemoryModel));
+        assert!(vk12.allows_capability(spirv::Capability::VulkanMemoryModel));
+        assert!(!vk12.allows_capability(spirv::Capability::Kernel));
+        assert!(TargetEnv::OpenCL2_0.allows_capability(spirv::Capability::GenericPointer));
+        assert!(!TargetEnv::OpenCL1_2.allows_capability(spirv::Capability::GenericPointer));
+        assert!(vk10.allows_memory_model(spirv::AddressingModel::Logical,
+                                         spirv::MemoryModel::GLSL450));
+        assert!(!vk10.allows_memory_model(spirv::AddressingModel::Physical64,
+                                          spirv::MemoryModel::OpenCL));
+        assert!(TargetEnv::OpenCL2_1.allows_memory_model(spirv::AddressingModel::Physical64,
+                                                         spirv::MemoryModel::OpenCL));
+        assert!(!vk10.rules().relaxed_block_layout);
+        assert!(TargetEnv::Vulkan1_1.rules().relaxed_block_layout);
+        assert!(!TargetEnv::OpenCL2_2.rules().requires_entry_point);
+    }
+}
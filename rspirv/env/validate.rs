@@ -0,0 +1,1018 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use analysis;
+use grammar;
+use mr;
+use reflect;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+use super::{Error, Result, TargetEnv};
+
+impl TargetEnv {
+    /// Checks that `module` only uses what the environment accepts, and
+    /// follows its validation [`rules`](#method.rules).
+    ///
+    /// Only what depends on the environment is checked: the SPIR-V
+    /// version, declared capabilities and extensions, and memory model,
+    /// then the environment-specific rules. Besides, ids referred to but
+    /// never defined, e.g., left behind by a transform removing
+    /// instructions, are reported, as are cooperative matrix instructions
+    /// on matrices of mismatched types, and atomics on values whose width
+    /// requires an undeclared capability, e.g., `Int64Atomics`. Errors are
+    /// reported for the first offending declaration found.
+    pub fn validate(self, module: &mr::Module) -> Result<()> {
+        let defs = defined_ids(module);
+        self.check_module(module, &defs)?;
+        let checks: Vec<FunctionChecks> =
+            module.functions.iter().map(|f| self.check_function(module, f, &defs)).collect();
+        first_error(&checks)
+    }
+
+    /// Checks everything but what is in functions, given the ids `defs`
+    /// defined in `module`.
+    fn check_module(self, module: &mr::Module, defs: &BTreeSet<Word>) -> Result<()> {
+        if let Some(ref header) = module.header {
+            let (major, minor) = header.version();
+            if !self.allows_version((major, minor)) {
+                return Err(Error::Version(major, minor));
+            }
+        }
+
+        let mut extensions = BTreeSet::new();
+        for inst in &module.extensions {
+            if let Some(mr::Operand::LiteralString(name)) = inst.operands.first() {
+                if !self.allows_extension(name) {
+                    return Err(Error::Extension(name.clone()));
+                }
+                extensions.insert(name.as_str());
+            }
+        }
+        let capabilities: Vec<spirv::Capability> = module.capabilities
+            .iter()
+            .filter_map(|inst| match inst.operands.first() {
+                Some(&mr::Operand::Capability(capability)) => Some(capability),
+                _ => None,
+            })
+            .collect();
+        for &capability in &capabilities {
+            let extended = || {
+                let reqs = mr::Operand::Capability(capability).required_extensions();
+                !reqs.is_empty() &&
+                reqs.iter().all(|names| names.iter().any(|name| extensions.contains(name)))
+            };
+            if !self.allows_capability(capability) && !extended() {
+                return Err(Error::Capability(capability));
+            }
+        }
+
+        if let Some(ref inst) = module.memory_model {
+            if let (Some(&mr::Operand::AddressingModel(addressing)),
+                    Some(&mr::Operand::MemoryModel(memory))) =
+                (inst.operands.first(), inst.operands.get(1)) {
+                if !self.allows_memory_model(addressing, memory) {
+                    return Err(Error::MemoryModel(addressing, memory));
+                }
+            }
+        }
+
+        let rules = self.rules();
+        if rules.requires_entry_point && module.entry_points.is_empty() &&
+           !capabilities.contains(&spirv::Capability::Linkage) {
+            return Err(Error::MissingEntryPoint);
+        }
+        if rules.requires_block_offsets {
+            check_block_offsets(module)?;
+        }
+        if let Some(classes) = rules.initializer_storage_classes {
+            check_initializers(&module.types_global_values, classes)?;
+        }
+        if rules.checks_physical_storage_buffers {
+            check_aliasing(&module.types_global_values, &BufferPointers::of(module))?;
+        }
+        if rules.checks_mesh_shaders {
+            check_mesh_shaders(module)?;
+        }
+        check_ids(module.global_inst_iter(), defs)
+    }
+
+    /// Checks `function` of `module`, given the ids `defs` defined in
+    /// `module`.
+    fn check_function(self,
+                      module: &mr::Module,
+                      function: &mr::Function,
+                      defs: &BTreeSet<Word>)
+                      -> FunctionChecks {
+        let rules = self.rules();
+        let blocks = function.basic_blocks
+            .iter()
+            .flat_map(|b| b.label.iter().chain(&b.instructions));
+        let insts = function.def
+            .iter()
+            .chain(&function.parameters)
+            .chain(blocks)
+            .chain(&function.end);
+        let mut checks = FunctionChecks {
+            ids: check_ids(insts, defs),
+            cooperative_matrices: check_cooperative_matrices(module, function),
+            atomics: check_atomics(module, function),
+            initializers: Ok(()),
+            variable_pointers: Ok(()),
+            buffer_pointers: Ok(()),
+        };
+        if let Some(classes) = rules.initializer_storage_classes {
+            let insts = function.basic_blocks.iter().flat_map(|b| &b.instructions);
+            checks.initializers = check_initializers(insts, classes);
+        }
+        if rules.checks_variable_pointers {
+            let flow = analysis::function_variable_pointers(module, function)
+                .into_iter()
+                .find(|flow| !flow.covered);
+            if let Some(flow) = flow {
+                checks.variable_pointers = Err(Error::VariablePointer(flow.result_id,
+                                                                      flow.required));
+            }
+        }
+        if rules.checks_physical_storage_buffers {
+            let pointers = BufferPointers::of(module);
+            if !pointers.types.is_empty() {
+                let variables = function.basic_blocks.iter().take(1).flat_map(|b| &b.instructions);
+                checks.buffer_pointers =
+                    check_aliasing(function.parameters.iter().chain(variables), &pointers)
+                        .and_then(|_| check_alignment(module, function, &pointers));
+            }
+        }
+        checks
+    }
+}
+
+/// The results of checking a function, kept apart so that errors are
+/// reported in the order of the checks first, then of the functions.
+#[derive(Clone, Debug)]
+struct FunctionChecks {
+    ids: Result<()>,
+    cooperative_matrices: Result<()>,
+    atomics: Result<()>,
+    initializers: Result<()>,
+    variable_pointers: Result<()>,
+    buffer_pointers: Result<()>,
+}
+
+/// Returns the first error of `checks`.
+fn first_error(checks: &[FunctionChecks]) -> Result<()> {
+    for c in checks {
+        c.ids.clone()?;
+    }
+    for c in checks {
+        c.cooperative_matrices.clone()?;
+    }
+    for c in checks {
+        c.atomics.clone()?;
+    }
+    for c in checks {
+        c.initializers.clone()?;
+    }
+    for c in checks {
+        c.variable_pointers.clone()?;
+    }
+    for c in checks {
+        c.buffer_pointers.clone()?;
+    }
+    Ok(())
+}
+
+/// Validates a module again after edits, only checking the functions
+/// changed since the last time.
+///
+/// The changes are those the module [tracks](../mr/struct.Changes.html),
+/// which passes and the editing methods of the module mark; the validator
+/// takes them on each run. Declarations and types count
+/// for all functions, so changes to the capabilities, imports, memory
+/// model, decorations, or types and global values, or removing a
+/// function, check all functions again. A validator is meant to be used
+/// with one module; a module counts as entirely changed until its changes
+/// are first taken.
+#[derive(Debug)]
+pub struct Validator {
+    env: TargetEnv,
+    /// The result of checking everything but functions, if still valid.
+    module: Option<Result<()>>,
+    /// The results of checking functions by their ids, if still valid.
+    functions: BTreeMap<Word, FunctionChecks>,
+}
+
+impl Validator {
+    /// Creates a validator for `env`.
+    pub fn new(env: TargetEnv) -> Validator {
+        Validator {
+            env,
+            module: None,
+            functions: BTreeMap::new(),
+        }
+    }
+
+    /// Checks `module` as [`TargetEnv::validate`](enum.TargetEnv.html#method.validate)
+    /// does, reusing the results for what did not change since the last
+    /// run.
+    pub fn validate(&mut self, module: &mut mr::Module) -> Result<()> {
+        let changes = module.take_changes();
+        let defs = defined_ids(module);
+        // Declarations may refer to the results of functions, e.g., in
+        // names and decorations.
+        if changes.is_all() || !changes.sections().is_empty() || self.module.is_none() {
+            self.module = Some(self.env.check_module(module, &defs));
+        }
+        // Functions refer to global values, imports, and other functions,
+        // and are checked against the decorations of their results.
+        let shared = [mr::Section::Capabilities,
+                      mr::Section::ExtInstImports,
+                      mr::Section::MemoryModel,
+                      mr::Section::Annotations,
+                      mr::Section::TypesGlobalValues];
+        let removed = changes.sections().iter().any(|s| match *s {
+            mr::Section::Function(id) => !defs.contains(&id),
+            _ => false,
+        });
+        if removed || shared.iter().any(|&s| changes.contains(s)) {
+            self.functions.clear();
+        }
+        for section in changes.sections() {
+            if let mr::Section::Function(id) = *section {
+                self.functions.remove(&id);
+            }
+        }
+
+        let env = self.env;
+        let mut checks = vec![];
+        let mut ids = BTreeSet::new();
+        for f in &module.functions {
+            match f.def.as_ref().and_then(|def| def.result_id) {
+                Some(id) => {
+                    ids.insert(id);
+                    let cached = self.functions
+                        .entry(id)
+                        .or_insert_with(|| env.check_function(module, f, &defs));
+                    checks.push(cached.clone());
+                }
+                None => checks.push(env.check_function(module, f, &defs)),
+            }
+        }
+        self.functions.retain(|id, _| ids.contains(id));
+        self.module.clone().unwrap_or(Ok(()))?;
+        first_error(&checks)
+    }
+}
+
+/// Checks that all members of `Block` and `BufferBlock` structs in
+/// `module` have an `Offset` decoration.
+fn check_block_offsets(module: &mr::Module) -> Result<()> {
+    let mut blocks = BTreeSet::new();
+    let mut offsets: BTreeSet<(Word, u32)> = BTreeSet::new();
+    for inst in &module.annotations {
+        match (inst.class.opcode, &inst.operands[..]) {
+            (spirv::Op::Decorate,
+             &[mr::Operand::IdRef(target), mr::Operand::Decoration(decoration), ..])
+                if decoration == spirv::Decoration::Block ||
+                   decoration == spirv::Decoration::BufferBlock => {
+                blocks.insert(target);
+            }
+            (spirv::Op::MemberDecorate,
+             &[mr::Operand::IdRef(target),
+               mr::Operand::LiteralInt32(member),
+               mr::Operand::Decoration(spirv::Decoration::Offset),
+               ..]) => {
+                offsets.insert((target, member));
+            }
+            _ => {}
+        }
+    }
+    for inst in &module.types_global_values {
+        let id = match inst.result_id {
+            Some(id) if inst.class.opcode == spirv::Op::TypeStruct && blocks.contains(&id) => id,
+            _ => continue,
+        };
+        for member in 0..inst.operands.len() as u32 {
+            if !offsets.contains(&(id, member)) {
+                return Err(Error::MissingOffset(id, member));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the result ids of `module`.
+fn defined_ids(module: &mr::Module) -> BTreeSet<Word> {
+    let functions = module.functions.iter().flat_map(|f| {
+        let blocks = f.basic_blocks.iter().flat_map(|b| b.label.iter().chain(&b.instructions));
+        f.def.iter().chain(&f.parameters).chain(blocks)
+    });
+    module.global_inst_iter().chain(functions).filter_map(|inst| inst.result_id).collect()
+}
+
+/// Checks that `insts` only refer to the ids `defs`.
+fn check_ids<'i, I>(insts: I, defs: &BTreeSet<Word>) -> Result<()>
+    where I: IntoIterator<Item = &'i mr::Instruction>
+{
+    for inst in insts {
+        let mut ids = inst.result_type
+            .into_iter()
+            .chain(inst.operands.iter().filter_map(mr::Operand::id));
+        if let Some(id) = ids.find(|id| !defs.contains(id)) {
+            return Err(Error::UndefinedId(inst.class.opcode, id));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that only variables of the storage `classes` among `insts` have
+/// initializers.
+fn check_initializers<'i, I>(insts: I, classes: &[spirv::StorageClass]) -> Result<()>
+    where I: IntoIterator<Item = &'i mr::Instruction>
+{
+    for inst in insts {
+        if inst.class.opcode != spirv::Op::Variable || inst.operands.len() < 2 {
+            continue;
+        }
+        if let Some(&mr::Operand::StorageClass(class)) = inst.operands.first() {
+            if !classes.contains(&class) {
+                return Err(Error::Initializer(inst.result_id.unwrap_or(0), class));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The `PhysicalStorageBuffer` pointer types of a module, and the
+/// aliasing decorations of its ids.
+struct BufferPointers {
+    /// The pointer types in the `PhysicalStorageBuffer` storage class.
+    types: BTreeSet<Word>,
+    /// The pointer types, in other storage classes, to those.
+    holders: BTreeSet<Word>,
+    /// The aliasing decorations by the ids decorated.
+    aliasing: BTreeMap<Word, Vec<spirv::Decoration>>,
+}
+
+impl BufferPointers {
+    fn of(module: &mr::Module) -> BufferPointers {
+        let mut types = BTreeSet::new();
+        let mut holders = BTreeSet::new();
+        for inst in &module.types_global_values {
+            if let (spirv::Op::TypePointer,
+                    Some(id),
+                    &[mr::Operand::StorageClass(class), mr::Operand::IdRef(pointee)]) =
+                (inst.class.opcode, inst.result_id, &inst.operands[..]) {
+                if class == spirv::StorageClass::PhysicalStorageBuffer {
+                    types.insert(id);
+                } else if types.contains(&pointee) {
+                    holders.insert(id);
+                }
+            }
+        }
+        let mut aliasing: BTreeMap<Word, Vec<spirv::Decoration>> = BTreeMap::new();
+        for inst in &module.annotations {
+            if let (spirv::Op::Decorate,
+                    &[mr::Operand::IdRef(target), mr::Operand::Decoration(decoration), ..]) =
+                (inst.class.opcode, &inst.operands[..]) {
+                aliasing.entry(target).or_default().push(decoration);
+            }
+        }
+        BufferPointers { types, holders, aliasing }
+    }
+}
+
+/// Checks that the variables and function parameters among `insts`
+/// holding `PhysicalStorageBuffer` pointers have exactly one of the
+/// `AliasedPointer` and `RestrictPointer` decorations, and the parameters
+/// being such pointers exactly one of `Aliased` and `Restrict`.
+fn check_aliasing<'i, I>(insts: I, pointers: &BufferPointers) -> Result<()>
+    where I: IntoIterator<Item = &'i mr::Instruction>
+{
+    use spirv::Decoration as D;
+    for inst in insts {
+        let ty = match inst.result_type {
+            Some(ty) => ty,
+            None => continue,
+        };
+        let (aliased, restrict) = match inst.class.opcode {
+            spirv::Op::Variable |
+            spirv::Op::FunctionParameter if pointers.holders.contains(&ty) => {
+                (D::AliasedPointer, D::RestrictPointer)
+            }
+            spirv::Op::FunctionParameter if pointers.types.contains(&ty) => {
+                (D::Aliased, D::Restrict)
+            }
+            _ => continue,
+        };
+        let id = inst.result_id.unwrap_or(0);
+        let decorations = pointers.aliasing.get(&id).map_or(&[][..], |d| &d[..]);
+        if decorations.contains(&aliased) == decorations.contains(&restrict) {
+            return Err(Error::PointerAliasing(id));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the loads and stores of `function` through
+/// `PhysicalStorageBuffer` pointers have the `Aligned` memory operand.
+fn check_alignment(module: &mr::Module,
+                   function: &mr::Function,
+                   pointers: &BufferPointers)
+                   -> Result<()> {
+    let insts = || function.basic_blocks.iter().flat_map(|b| &b.instructions);
+    let types: BTreeMap<Word, Word> = module.types_global_values
+        .iter()
+        .chain(&function.parameters)
+        .chain(insts())
+        .filter_map(|inst| inst.result_id.and_then(|id| inst.result_type.map(|ty| (id, ty))))
+        .collect();
+    for inst in insts() {
+        let access = match inst.class.opcode {
+            spirv::Op::Load => 1,
+            spirv::Op::Store => 2,
+            _ => continue,
+        };
+        let pointer = match inst.operands.first() {
+            Some(&mr::Operand::IdRef(pointer)) => pointer,
+            _ => continue,
+        };
+        if !types.get(&pointer).is_some_and(|ty| pointers.types.contains(ty)) {
+            continue;
+        }
+        match inst.operands.get(access) {
+            Some(&mr::Operand::MemoryAccess(a)) if a.contains(spirv::MemoryAccess::ALIGNED) => {}
+            _ => return Err(Error::MissingAlignment(inst.class.opcode, pointer)),
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the mesh shader entry points of `module` declare their
+/// limits and exactly one output topology, matching the primitive indices
+/// they output, and that only inputs and outputs are decorated
+/// `PerPrimitiveEXT`.
+fn check_mesh_shaders(module: &mr::Module) -> Result<()> {
+    let mut decorations: BTreeMap<Word, Vec<&[mr::Operand]>> = BTreeMap::new();
+    for inst in &module.annotations {
+        if let (spirv::Op::Decorate, Some(&mr::Operand::IdRef(target))) =
+            (inst.class.opcode, inst.operands.first()) {
+            decorations.entry(target).or_default().push(&inst.operands[1..]);
+        }
+    }
+    let decorated = |id: Word, decoration: spirv::Decoration| {
+        decorations.get(&id).map_or(&[][..], |d| &d[..]).iter().find(|operands| {
+            operands.first() == Some(&mr::Operand::Decoration(decoration))
+        })
+    };
+
+    for inst in &module.types_global_values {
+        if let (spirv::Op::Variable, Some(id), Some(&mr::Operand::StorageClass(class))) =
+            (inst.class.opcode, inst.result_id, inst.operands.first()) {
+            if decorated(id, spirv::Decoration::PerPrimitiveEXT).is_some() &&
+               class != spirv::StorageClass::Input &&
+               class != spirv::StorageClass::Output {
+                return Err(Error::PerPrimitive(id));
+            }
+        }
+    }
+
+    for inst in &module.entry_points {
+        let function = match (inst.operands.first(), inst.operands.get(1)) {
+            (Some(&mr::Operand::ExecutionModel(spirv::ExecutionModel::MeshEXT)),
+             Some(&mr::Operand::IdRef(function))) => function,
+            _ => continue,
+        };
+        let modes: Vec<spirv::ExecutionMode> = module.execution_modes
+            .iter()
+            .filter_map(|inst| match (inst.operands.first(), inst.operands.get(1)) {
+                (Some(&mr::Operand::IdRef(target)), Some(&mr::Operand::ExecutionMode(mode)))
+                    if target == function => Some(mode),
+                _ => None,
+            })
+            .collect();
+        for &mode in &[spirv::ExecutionMode::OutputVertices,
+                       spirv::ExecutionMode::OutputPrimitivesEXT] {
+            if !modes.contains(&mode) {
+                return Err(Error::MissingExecutionMode(function, mode));
+            }
+        }
+        let topologies: Vec<reflect::MeshTopology> =
+            modes.into_iter().filter_map(reflect::MeshTopology::from_execution_mode).collect();
+        if topologies.len() != 1 {
+            return Err(Error::MeshTopology(function));
+        }
+        let indices = inst.operands
+            .iter()
+            .skip(3)
+            .filter_map(mr::Operand::id)
+            .filter_map(|id| match decorated(id, spirv::Decoration::BuiltIn)?.get(1) {
+                Some(&mr::Operand::BuiltIn(b)) => reflect::MeshTopology::from_indices(b),
+                _ => None,
+            });
+        for topology in indices {
+            if topology != topologies[0] {
+                return Err(Error::MeshTopology(function));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A cooperative matrix type, with the values of its scope, shape, and
+/// use, if they are constants.
+struct CooperativeMatrix {
+    scope: Option<u32>,
+    rows: Option<u32>,
+    columns: Option<u32>,
+    usage: Option<u32>,
+}
+
+/// Checks that the cooperative matrix instructions of `function` load,
+/// store, and multiply cooperative matrices, of agreeing uses, scopes, and
+/// shapes for multiplications.
+fn check_cooperative_matrices(module: &mr::Module, function: &mr::Function) -> Result<()> {
+    use spirv::CooperativeMatrixUse as U;
+    let constants: BTreeMap<Word, u32> = module.types_global_values
+        .iter()
+        .filter_map(|inst| match (inst.class.opcode, inst.result_id, inst.operands.first()) {
+            (spirv::Op::Constant, Some(id), Some(&mr::Operand::LiteralInt32(value))) => {
+                Some((id, value))
+            }
+            _ => None,
+        })
+        .collect();
+    let constant = |operand: Option<&mr::Operand>| constants.get(&operand?.id()?).cloned();
+    let matrices: BTreeMap<Word, CooperativeMatrix> = module.types_global_values
+        .iter()
+        .filter(|inst| inst.class.opcode == spirv::Op::TypeCooperativeMatrixKHR)
+        .filter_map(|inst| {
+            Some((inst.result_id?,
+                  CooperativeMatrix {
+                      scope: constant(inst.operands.get(1)),
+                      rows: constant(inst.operands.get(2)),
+                      columns: constant(inst.operands.get(3)),
+                      usage: constant(inst.operands.get(4)),
+                  }))
+        })
+        .collect();
+    if matrices.is_empty() {
+        return Ok(());
+    }
+
+    let insts = || function.basic_blocks.iter().flat_map(|b| &b.instructions);
+    let types: BTreeMap<Word, Word> = module.types_global_values
+        .iter()
+        .chain(&function.parameters)
+        .chain(insts())
+        .filter_map(|inst| inst.result_id.and_then(|id| inst.result_type.map(|ty| (id, ty))))
+        .collect();
+    let type_of = |operand: Option<&mr::Operand>| types.get(&operand?.id()?).cloned();
+    let agree = |a: Option<u32>, b: Option<u32>| a.is_none() || b.is_none() || a == b;
+    let used_as = |m: &CooperativeMatrix, usage: U| agree(m.usage, Some(usage as u32));
+    for inst in insts() {
+        let matches = match inst.class.opcode {
+            spirv::Op::CooperativeMatrixLoadKHR => {
+                inst.result_type.is_some_and(|ty| matrices.contains_key(&ty))
+            }
+            spirv::Op::CooperativeMatrixStoreKHR => {
+                type_of(inst.operands.get(1)).is_some_and(|ty| matrices.contains_key(&ty))
+            }
+            spirv::Op::CooperativeMatrixLengthKHR => {
+                inst.operands.first().and_then(mr::Operand::id).is_some_and(|ty| {
+                    matrices.contains_key(&ty)
+                })
+            }
+            spirv::Op::CooperativeMatrixMulAddKHR => {
+                let matrix = |index| {
+                    type_of(inst.operands.get(index)).and_then(|ty| matrices.get(&ty))
+                };
+                match (matrix(0), matrix(1), matrix(2)) {
+                    (Some(a), Some(b), Some(c)) => {
+                        inst.result_type == type_of(inst.operands.get(2)) &&
+                        used_as(a, U::MatrixAKHR) && used_as(b, U::MatrixBKHR) &&
+                        used_as(c, U::MatrixAccumulatorKHR) &&
+                        agree(a.scope, b.scope) && agree(a.scope, c.scope) &&
+                        agree(a.rows, c.rows) && agree(b.columns, c.columns) &&
+                        agree(a.columns, b.rows)
+                    }
+                    _ => false,
+                }
+            }
+            _ => continue,
+        };
+        if !matches {
+            let id = inst.result_id.or_else(|| inst.operands.get(1).and_then(mr::Operand::id));
+            return Err(Error::CooperativeMatrix(inst.class.opcode, id.unwrap_or(0)));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the atomics of `function` only operate on values of types
+/// whose capabilities `module` declares, directly or implicitly.
+fn check_atomics(module: &mr::Module, function: &mr::Function) -> Result<()> {
+    let insts = || function.basic_blocks.iter().flat_map(|b| &b.instructions);
+    if !insts().any(|inst| inst.class.opname.starts_with("Atomic")) {
+        return Ok(());
+    }
+    let mut declared = BTreeSet::new();
+    let mut pending: Vec<spirv::Capability> = module.capabilities
+        .iter()
+        .filter_map(|inst| match inst.operands.first() {
+            Some(&mr::Operand::Capability(capability)) => Some(capability),
+            _ => None,
+        })
+        .collect();
+    while let Some(capability) = pending.pop() {
+        if declared.insert(capability) {
+            pending.extend_from_slice(grammar::reflect::implied_capabilities(capability));
+        }
+    }
+    let defs: BTreeMap<Word, &mr::Instruction> = module.types_global_values
+        .iter()
+        .chain(&function.parameters)
+        .chain(insts())
+        .filter_map(|inst| inst.result_id.map(|id| (id, inst)))
+        .collect();
+    for inst in insts() {
+        if let Some(ty) = mr::atomic_value_type(inst, &defs) {
+            for alternatives in mr::Requirements::of_atomic(inst, ty).capabilities {
+                if !alternatives.iter().any(|c| declared.contains(c)) {
+                    return Err(Error::AtomicWidth(inst.class.opcode, alternatives[0]));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the entry points of `module` read no push constants past
+/// the first `max_size` bytes, e.g., the `maxPushConstantsSize` limit of a
+/// Vulkan device.
+///
+/// The reads are those found by
+/// [`reflect::push_constants`](../reflect/fn.push_constants.html); the
+/// first one reaching past the limit is reported.
+pub fn validate_push_constant_size(module: &mr::Module, max_size: u32) -> Result<()> {
+    for usage in reflect::push_constants(module) {
+        for access in &usage.accesses {
+            let end = access.range.offset + access.range.size;
+            if end > max_size {
+                return Err(Error::PushConstantSize(access.pointer, end));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use passes;
+    use spirv;
+
+    use env::{validate_push_constant_size, Error, TargetEnv, Validator};
+
+    fn build() -> mr::Builder {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let voidfvoid = b.type_function(void, vec![]);
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::GLCompute, f, "main", vec![]);
+        b
+    }
+
+    /// Returns the module of `b` for the given SPIR-V version.
+    fn module(b: mr::Builder, version: (u8, u8)) -> mr::Module {
+        let mut m = b.module();
+        m.header.as_mut().unwrap().version = (u32::from(version.0) << 16) |
+                                             (u32::from(version.1) << 8);
+        m
+    }
+
+    #[test]
+    fn test_validate() {
+        let m = module(build(), (1, 0));
+        assert_eq!(TargetEnv::Vulkan1_0.validate(&m), Ok(()));
+        assert_eq!(TargetEnv::OpenGL4_5.validate(&m), Ok(()));
+        assert_eq!(TargetEnv::OpenCL2_0.validate(&m),
+                   Err(Error::Capability(spirv::Capability::Shader)));
+
+        let m = module(build(), (1, 3));
+        assert_eq!(TargetEnv::Vulkan1_0.validate(&m), Err(Error::Version(1, 3)));
+        assert_eq!(TargetEnv::Vulkan1_1.validate(&m), Ok(()));
+
+        let mut b = build();
+        b.capability(spirv::Capability::VulkanMemoryModel);
+        assert_eq!(TargetEnv::Vulkan1_0.validate(&module(b, (1, 0))),
+                   Err(Error::Capability(spirv::Capability::VulkanMemoryModel)));
+        let mut b = build();
+        b.capability(spirv::Capability::VulkanMemoryModel);
+        b.extension("SPV_KHR_vulkan_memory_model");
+        assert_eq!(TargetEnv::Vulkan1_0.validate(&module(b, (1, 0))), Ok(()));
+    }
+
+    #[test]
+    fn test_rules() {
+        let mut b = build();
+        let uint = b.type_int(32, 0);
+        let block = b.type_struct(vec![uint, uint]);
+        b.decorate(block, spirv::Decoration::Block, vec![]);
+        b.member_decorate(block, 0, spirv::Decoration::Offset, vec![mr::Operand::LiteralInt32(0)]);
+        let m = module(b, (1, 0));
+        assert_eq!(TargetEnv::Vulkan1_0.validate(&m), Err(Error::MissingOffset(block, 1)));
+        assert_eq!(TargetEnv::Universal(1, 0).validate(&m), Ok(()));
+
+        let mut m = module(build(), (1, 0));
+        m.entry_points.clear();
+        assert_eq!(TargetEnv::Vulkan1_0.validate(&m), Err(Error::MissingEntryPoint));
+
+        let mut b = build();
+        let uint = b.type_int(32, 0);
+        let zero = b.constant_null(uint);
+        let ptr = b.type_pointer(None, spirv::StorageClass::Workgroup, uint);
+        let shared = b.variable(ptr, None, spirv::StorageClass::Workgroup, Some(zero));
+        let m = module(b, (1, 3));
+        assert_eq!(TargetEnv::Vulkan1_2.validate(&m),
+                   Err(Error::Initializer(shared, spirv::StorageClass::Workgroup)));
+        assert_eq!(TargetEnv::Vulkan1_3.validate(&m), Ok(()));
+
+        let mut b = build();
+        let uint = b.type_int(32, 0);
+        let ptr = b.type_pointer(None, spirv::StorageClass::StorageBuffer, uint);
+        let buffer = b.variable(ptr, None, spirv::StorageClass::StorageBuffer, None);
+        let fty = b.type_function(ptr, vec![]);
+        let f = b.begin_function(ptr, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ret_value(buffer).unwrap();
+        b.end_function().unwrap();
+        b.begin_function(ptr, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let call = b.function_call(ptr, None, f, vec![]).unwrap();
+        b.ret_value(call).unwrap();
+        b.end_function().unwrap();
+        let mut m = module(b, (1, 3));
+        let capability = spirv::Capability::VariablePointersStorageBuffer;
+        assert_eq!(TargetEnv::Vulkan1_1.validate(&m),
+                   Err(Error::VariablePointer(call, Some(capability))));
+        let operands = vec![mr::Operand::Capability(capability)];
+        m.capabilities.push(mr::Instruction::new(spirv::Op::Capability, None, None, operands));
+        assert_eq!(TargetEnv::Vulkan1_1.validate(&m), Ok(()));
+
+        // Removing the callee leaves the call dangling.
+        m.functions.remove(1);
+        assert_eq!(TargetEnv::Vulkan1_1.validate(&m),
+                   Err(Error::UndefinedId(spirv::Op::FunctionCall, f)));
+        let operands = vec![mr::Operand::IdRef(f), mr::Operand::from("f")];
+        m.debugs.push(mr::Instruction::new(spirv::Op::Name, None, None, operands));
+        assert_eq!(TargetEnv::Vulkan1_1.validate(&m),
+                   Err(Error::UndefinedId(spirv::Op::Name, f)));
+    }
+
+    #[test]
+    fn test_physical_storage_buffers() {
+        let mut b = build();
+        b.physical_storage_buffer_addressing(spirv::MemoryModel::GLSL450);
+        let uint = b.type_int(32, 0);
+        let buffer = b.type_struct(vec![uint]);
+        b.decorate(buffer, spirv::Decoration::Block, vec![]);
+        b.member_decorate(buffer, 0, spirv::Decoration::Offset, vec![mr::Operand::LiteralInt32(0)]);
+        let reference = b.type_buffer_reference(buffer);
+        let uint_ref = b.type_buffer_reference(uint);
+        let fty = b.type_function(uint, vec![reference]);
+        b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let param = b.function_parameter(reference).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let zero = b.constant_u32(uint, 0);
+        let member = b.access_chain(uint_ref, None, param, vec![zero]).unwrap();
+        let value = b.load(uint, None, member, None, vec![]).unwrap();
+        b.ret_value(value).unwrap();
+        b.end_function().unwrap();
+        let mut m = module(b, (1, 5));
+        assert_eq!(TargetEnv::Vulkan1_2.validate(&m), Err(Error::PointerAliasing(param)));
+
+        let operands = vec![mr::Operand::IdRef(param),
+                            mr::Operand::Decoration(spirv::Decoration::Restrict)];
+        m.annotations.push(mr::Instruction::new(spirv::Op::Decorate, None, None, operands));
+        assert_eq!(TargetEnv::Vulkan1_2.validate(&m),
+                   Err(Error::MissingAlignment(spirv::Op::Load, member)));
+        let load = &mut m.functions[1].basic_blocks[0].instructions[1];
+        load.operands.push(mr::Operand::MemoryAccess(spirv::MemoryAccess::ALIGNED));
+        load.operands.push(mr::Operand::LiteralInt32(4));
+        assert_eq!(TargetEnv::Vulkan1_2.validate(&m), Ok(()));
+    }
+
+    #[test]
+    fn test_mesh_shaders() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::MeshShadingEXT);
+        b.extension("SPV_EXT_mesh_shader");
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let uvec2 = b.type_vector(uint, 2);
+        let one = b.constant_u32(uint, 1);
+        let lines = b.type_array(uvec2, one);
+        let lines_ptr = b.type_pointer(None, spirv::StorageClass::Output, lines);
+        let indices = b.variable(lines_ptr, None, spirv::StorageClass::Output, None);
+        b.decorate(indices, spirv::Decoration::BuiltIn,
+                   vec![mr::Operand::BuiltIn(spirv::BuiltIn::PrimitiveLineIndicesEXT)]);
+        let voidf = b.type_function(void, vec![]);
+        let mesh = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.set_mesh_outputs_ext(one, one).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::MeshEXT, mesh, "main", vec![indices]);
+        b.execution_mode(mesh, spirv::ExecutionMode::OutputVertices, [2]);
+        let mut m = module(b, (1, 4));
+        assert_eq!(TargetEnv::Vulkan1_2.validate(&m),
+                   Err(Error::MissingExecutionMode(mesh,
+                                                   spirv::ExecutionMode::OutputPrimitivesEXT)));
+
+        let mode = |mode, params: Vec<mr::Operand>| {
+            let mut operands = vec![mr::Operand::IdRef(mesh), mr::Operand::ExecutionMode(mode)];
+            operands.extend(params);
+            mr::Instruction::new(spirv::Op::ExecutionMode, None, None, operands)
+        };
+        m.execution_modes.push(mode(spirv::ExecutionMode::OutputPrimitivesEXT,
+                                    vec![mr::Operand::LiteralInt32(1)]));
+        assert_eq!(TargetEnv::Vulkan1_2.validate(&m), Err(Error::MeshTopology(mesh)));
+        m.execution_modes.push(mode(spirv::ExecutionMode::OutputTrianglesEXT, vec![]));
+        assert_eq!(TargetEnv::Vulkan1_2.validate(&m), Err(Error::MeshTopology(mesh)));
+        m.execution_modes.pop();
+        m.execution_modes.push(mode(spirv::ExecutionMode::OutputLinesEXT, vec![]));
+        assert_eq!(TargetEnv::Vulkan1_2.validate(&m), Ok(()));
+    }
+
+    #[test]
+    fn test_cooperative_matrices() {
+        let mut b = build();
+        b.capability(spirv::Capability::CooperativeMatrixKHR);
+        b.extension("SPV_KHR_cooperative_matrix");
+        let (fma, c) = {
+            let mut t = b.typed();
+            let float = t.type_float(32);
+            let uint = t.type_int(32, 0);
+            let subgroup = t.constant_u32(uint, spirv::Scope::Subgroup as u32);
+            let eight = t.constant_u32(uint, 8);
+            let sixteen = t.constant_u32(uint, 16);
+            let usage = |t: &mut mr::typed::Typed, usage: spirv::CooperativeMatrixUse| {
+                t.constant_u32(uint, usage as u32)
+            };
+            let (use_a, use_b) = (usage(&mut t, spirv::CooperativeMatrixUse::MatrixAKHR),
+                                  usage(&mut t, spirv::CooperativeMatrixUse::MatrixBKHR));
+            let use_c = usage(&mut t, spirv::CooperativeMatrixUse::MatrixAccumulatorKHR);
+            let row_major = spirv::CooperativeMatrixLayout::RowMajorKHR as u32;
+            let row_major = t.constant_u32(uint, row_major);
+            let a = t.type_cooperative_matrix(float, subgroup, eight, sixteen, use_a);
+            let b = t.type_cooperative_matrix(float, subgroup, sixteen, eight, use_b);
+            let c = t.type_cooperative_matrix(float, subgroup, eight, eight, use_c);
+            let ptr = t.type_pointer(spirv::StorageClass::StorageBuffer, float);
+            let buffer = t.variable(ptr, spirv::StorageClass::StorageBuffer, None);
+            let void = t.type_void();
+            let voidf = t.type_function(void, &[]);
+            t.begin_function(void, spirv::FunctionControl::NONE, voidf).unwrap();
+            t.builder().begin_basic_block(None).unwrap();
+            let ma = t.cooperative_matrix_load(a, buffer, row_major, None).unwrap();
+            let mb = t.cooperative_matrix_load(b, buffer, row_major, None).unwrap();
+            let mc = t.cooperative_matrix_load(c, buffer, row_major, None).unwrap();
+            let ops = spirv::CooperativeMatrixOperands::NONE_KHR;
+            let fma = t.cooperative_matrix_mul_add(c, ma, mb, mc, ops).unwrap();
+            t.cooperative_matrix_store(buffer, fma, row_major, None).unwrap();
+            t.cooperative_matrix_length(uint, c).unwrap();
+            t.builder().ret().unwrap();
+            t.builder().end_function().unwrap();
+            (fma.word(), mc.word())
+        };
+        let mut m = module(b, (1, 3));
+        assert_eq!(TargetEnv::Universal(1, 3).validate(&m), Ok(()));
+
+        // Multiplying the accumulator by itself mismatches the uses.
+        let inst = &mut m.functions[1].basic_blocks[0].instructions[3];
+        inst.operands[0] = mr::Operand::IdRef(c);
+        assert_eq!(TargetEnv::Universal(1, 3).validate(&m),
+                   Err(Error::CooperativeMatrix(spirv::Op::CooperativeMatrixMulAddKHR, fma)));
+    }
+
+    #[test]
+    fn test_atomic_widths() {
+        let mut b = build();
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let ulong = b.type_int(64, 0);
+        let voidfvoid = b.type_function(void, vec![]);
+        let device = b.constant_u32(uint, spirv::Scope::Device as u32);
+        let relaxed = b.constant_u32(uint, 0);
+        let one = b.constant_u64(ulong, 1);
+        let ptr = b.type_pointer(None, spirv::StorageClass::Workgroup, ulong);
+        let counter = b.variable(ptr, None, spirv::StorageClass::Workgroup, None);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.atomic_iadd(ulong, None, counter, device, relaxed, one).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let mut m = module(b, (1, 3));
+        assert_eq!(TargetEnv::Universal(1, 3).validate(&m),
+                   Err(Error::AtomicWidth(spirv::Op::AtomicIAdd,
+                                          spirv::Capability::Int64Atomics)));
+
+        m.capabilities.push(mr::Instruction::new(
+            spirv::Op::Capability, None, None,
+            vec![mr::Operand::Capability(spirv::Capability::Int64Atomics)]));
+        assert_eq!(TargetEnv::Universal(1, 3).validate(&m), Ok(()));
+    }
+
+    #[test]
+    fn test_validator() {
+        let mut b = build();
+        let uint = b.type_int(32, 0);
+        let zero = b.constant_null(uint);
+        let ptr = b.type_pointer(None, spirv::StorageClass::Workgroup, uint);
+        let void = b.type_void();
+        let voidf = b.type_function(void, vec![]);
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let shared = b.variable(ptr, None, spirv::StorageClass::Workgroup, None);
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let mut m = module(b, (1, 0));
+
+        let mut validator = Validator::new(TargetEnv::Vulkan1_0);
+        assert!(m.changes().is_all());
+        assert_eq!(validator.validate(&mut m), Ok(()));
+        assert!(!m.changes().is_all());
+
+        // Edits are only seen in what is marked as changed.
+        m.functions[1].basic_blocks[0].instructions[0].operands.push(mr::Operand::IdRef(zero));
+        let expected = Err(Error::Initializer(shared, spirv::StorageClass::Workgroup));
+        assert_eq!(TargetEnv::Vulkan1_0.validate(&m), expected);
+        assert_eq!(validator.validate(&mut m), Ok(()));
+        m.function_mut(f).unwrap();
+        assert_eq!(validator.validate(&mut m), expected);
+
+        // Passes mark what they change.
+        passes::lower_initializers(&mut m, &[spirv::StorageClass::Workgroup]);
+        assert_eq!(TargetEnv::Vulkan1_0.validate(&m), Ok(()));
+        assert_eq!(validator.validate(&mut m), Ok(()));
+
+        m.entry_points.clear();
+        m.touch(mr::Section::EntryPoints);
+        assert_eq!(validator.validate(&mut m), Err(Error::MissingEntryPoint));
+        m.functions.pop();
+        m.touch(mr::Section::Function(f));
+        assert_eq!(validator.validate(&mut m), Err(Error::MissingEntryPoint));
+    }
+
+    #[test]
+    fn test_validate_push_constant_size() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let vec4 = b.type_vector(float, 4);
+        let uint = b.type_int(32, 0);
+        let (zero, one) = (b.constant_u32(uint, 0), b.constant_u32(uint, 1));
+        let block = b.type_struct(vec![vec4, vec4]);
+        b.decorate(block, spirv::Decoration::Block, vec![]);
+        b.member_decorate(block, 0, spirv::Decoration::Offset, vec![mr::Operand::LiteralInt32(0)]);
+        b.member_decorate(block, 1, spirv::Decoration::Offset,
+                          vec![mr::Operand::LiteralInt32(128)]);
+        let ptr_block = b.type_pointer(None, spirv::StorageClass::PushConstant, block);
+        let ptr_vec4 = b.type_pointer(None, spirv::StorageClass::PushConstant, vec4);
+        let pc = b.variable(ptr_block, None, spirv::StorageClass::PushConstant, None);
+        let voidf = b.type_function(void, vec![]);
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let first = b.access_chain(ptr_vec4, None, pc, vec![zero]).unwrap();
+        b.load(vec4, None, first, None, vec![]).unwrap();
+        let second = b.access_chain(ptr_vec4, None, pc, vec![one]).unwrap();
+        b.load(vec4, None, second, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::Fragment, f, "main", vec![]);
+        let m = b.module();
+
+        assert_eq!(validate_push_constant_size(&m, 256), Ok(()));
+        assert_eq!(validate_push_constant_size(&m, 144), Ok(()));
+        assert_eq!(validate_push_constant_size(&m, 128),
+                   Err(Error::PushConstantSize(second, 144)));
+    }
+}
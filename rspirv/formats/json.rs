@@ -0,0 +1,438 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Structured JSON dump of SPIR-V modules.
+//!
+//! The JSON document has the following shape:
+//!
+//! ```json
+//! {
+//!   "header": {
+//!     "magic_number": 119734787,
+//!     "version": 66048,
+//!     "generator": 851968,
+//!     "bound": 5,
+//!     "reserved_word": 0
+//!   },
+//!   "instructions": [
+//!     {
+//!       "opname": "OpMemoryModel",
+//!       "opcode": 14,
+//!       "operands": [
+//!         { "kind": "AddressingModel", "value": 0, "name": "Logical" },
+//!         { "kind": "MemoryModel", "value": 1, "name": "GLSL450" }
+//!       ]
+//!     },
+//!     {
+//!       "opname": "OpTypeInt",
+//!       "opcode": 21,
+//!       "result_id": 1,
+//!       "operands": [
+//!         { "kind": "LiteralInt32", "value": 32 },
+//!         { "kind": "LiteralInt32", "value": 1 }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Instructions appear in module order. `result_type` and `result_id` are
+//! only present for instructions that have them. Each operand records its
+//! kind (the [`mr::Operand`](../../mr/enum.Operand.html) variant name) and
+//! its value: a number for ids, literals, enumerants, and bit masks, or a
+//! string for literal strings. Enumerants and bit masks additionally carry a
+//! human readable `name`, and floating point literals carry their raw `bits`
+//! so that NaNs and infinities survive the trip. Both `name` and `bits` are
+//! informational for numbers that JSON can represent; `opcode` takes
+//! precedence over `opname` when importing.
+
+use binary;
+use grammar;
+use mr;
+use serde_json;
+use spirv;
+
+use binary::{Assemble, Disassemble};
+use serde_json::Value;
+use std::{error, fmt, result};
+
+/// JSON import errors.
+#[derive(Debug)]
+pub enum Error {
+    /// A required field is missing or has the wrong JSON type.
+    MissingField(&'static str),
+    /// The instruction names an unknown opcode.
+    UnknownOpcode(String),
+    /// The operand value does not match the operand kind.
+    WrongOperandValue(String),
+    /// The instructions do not form a valid SPIR-V module.
+    Parse(binary::ParseState),
+}
+
+impl Error {
+    /// Gives an descriptive string for each error.
+    ///
+    /// This method is intended to be used by fmt::Display and error::Error to
+    /// avoid duplication in implementation. So it's private.
+    fn describe(&self) -> &str {
+        match *self {
+            Error::MissingField(_) => "missing or malformed field",
+            Error::UnknownOpcode(_) => "unknown opcode",
+            Error::WrongOperandValue(_) => "wrong operand value",
+            Error::Parse(_) => "invalid module",
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        self.describe()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::MissingField(field) => write!(f, "{} '{}'", self.describe(), field),
+            Error::UnknownOpcode(ref op) |
+            Error::WrongOperandValue(ref op) => write!(f, "{} {}", self.describe(), op),
+            Error::Parse(ref state) => write!(f, "{}: {}", self.describe(), state),
+        }
+    }
+}
+
+pub type Result<T> = result::Result<T, Error>;
+
+/// Exports the given `module` as a structured JSON value.
+///
+/// # Examples
+///
+/// ```
+/// extern crate rspirv;
+/// extern crate spirv_headers as spirv;
+///
+/// fn main() {
+///     let mut b = rspirv::mr::Builder::new();
+///     b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+///     let json = rspirv::formats::json::export(&b.module());
+///
+///     assert_eq!(json["instructions"][0]["opname"], "OpMemoryModel");
+///     assert_eq!(json["instructions"][0]["operands"][1]["name"], "GLSL450");
+/// }
+/// ```
+pub fn export(module: &mr::Module) -> Value {
+    let mut insts = vec![];
+    for inst in module.global_inst_iter() {
+        insts.push(export_inst(inst));
+    }
+    for f in &module.functions {
+        if let Some(ref d) = f.def {
+            insts.push(export_inst(d));
+        }
+        for param in &f.parameters {
+            insts.push(export_inst(param));
+        }
+        for bb in &f.basic_blocks {
+            if let Some(ref l) = bb.label {
+                insts.push(export_inst(l));
+            }
+            for inst in &bb.instructions {
+                insts.push(export_inst(inst));
+            }
+        }
+        if let Some(ref e) = f.end {
+            insts.push(export_inst(e));
+        }
+    }
+
+    let mut json = serde_json::Map::new();
+    if let Some(ref h) = module.header {
+        json.insert("header".to_string(), json!({
+            "magic_number": h.magic_number,
+            "version": h.version,
+            "generator": h.generator,
+            "bound": h.bound,
+            "reserved_word": h.reserved_word,
+        }));
+    }
+    json.insert("instructions".to_string(), Value::Array(insts));
+    Value::Object(json)
+}
+
+/// Imports a module from the structured JSON `value` produced by
+/// [`export`](fn.export.html).
+///
+/// The instructions are encoded into SPIR-V words and loaded with the
+/// [`Loader`](../../mr/struct.Loader.html), so the result is laid out
+/// exactly as if it were parsed from a binary.
+pub fn import(value: &Value) -> Result<mr::Module> {
+    let mut code = match value.get("header") {
+        Some(header) => {
+            let field = |name| {
+                header.get(name).and_then(as_word).ok_or(Error::MissingField(name))
+            };
+            vec![field("magic_number")?,
+                 field("version")?,
+                 field("generator")?,
+                 field("bound")?,
+                 field("reserved_word")?]
+        }
+        None => mr::ModuleHeader::new(0).assemble(),
+    };
+    let insts = value.get("instructions")
+        .and_then(Value::as_array)
+        .ok_or(Error::MissingField("instructions"))?;
+    for inst in insts {
+        code.append(&mut import_inst(inst)?);
+    }
+    mr::load_words(code).map_err(Error::Parse)
+}
+
+fn export_inst(inst: &mr::Instruction) -> Value {
+    let mut json = serde_json::Map::new();
+    json.insert("opname".to_string(), json!(format!("Op{}", inst.class.opname)));
+    json.insert("opcode".to_string(), json!(inst.class.opcode as u32));
+    if let Some(r) = inst.result_type {
+        json.insert("result_type".to_string(), json!(r));
+    }
+    if let Some(r) = inst.result_id {
+        json.insert("result_id".to_string(), json!(r));
+    }
+    let operands = inst.operands.iter().map(export_operand).collect();
+    json.insert("operands".to_string(), Value::Array(operands));
+    Value::Object(json)
+}
+
+/// Returns the name of the `mr::Operand` variant for the given `operand`.
+fn operand_kind(operand: &mr::Operand) -> String {
+    let debug = format!("{:?}", operand);
+    match debug.find('(') {
+        Some(index) => debug[..index].to_string(),
+        None => debug,
+    }
+}
+
+fn export_operand(operand: &mr::Operand) -> Value {
+    let kind = operand_kind(operand);
+    match *operand {
+        mr::Operand::LiteralString(ref v) => json!({ "kind": kind, "value": v }),
+        mr::Operand::LiteralInt64(v) => json!({ "kind": kind, "value": v }),
+        mr::Operand::LiteralFloat32(v) => {
+            json!({ "kind": kind, "value": v, "bits": v.to_bits() })
+        }
+        mr::Operand::LiteralFloat64(v) => {
+            json!({ "kind": kind, "value": v, "bits": v.to_bits() })
+        }
+        mr::Operand::IdMemorySemantics(v) |
+        mr::Operand::IdScope(v) |
+        mr::Operand::IdRef(v) |
+        mr::Operand::LiteralInt32(v) |
+        mr::Operand::LiteralExtInstInteger(v) => json!({ "kind": kind, "value": v }),
+        _ => {
+            json!({
+                "kind": kind,
+                "value": operand.assemble()[0],
+                "name": operand.disassemble(),
+            })
+        }
+    }
+}
+
+fn as_word(value: &Value) -> Option<spirv::Word> {
+    value.as_u64().and_then(|v| if v <= u64::from(u32::MAX) { Some(v as u32) } else { None })
+}
+
+fn import_inst(inst: &Value) -> Result<Vec<u32>> {
+    let opcode = match inst.get("opcode") {
+        Some(opcode) => {
+            let opcode = as_word(opcode).ok_or(Error::MissingField("opcode"))?;
+            if opcode > u32::from(u16::MAX) ||
+               grammar::CoreInstructionTable::lookup_opcode(opcode as u16).is_none() {
+                return Err(Error::UnknownOpcode(opcode.to_string()));
+            }
+            opcode
+        }
+        None => {
+            let opname = inst.get("opname")
+                .and_then(Value::as_str)
+                .ok_or(Error::MissingField("opname"))?;
+            let name = opname.trim_start_matches("Op");
+            match grammar::CoreInstructionTable::lookup_opname(name) {
+                Some(grammar) => grammar.opcode as u32,
+                None => return Err(Error::UnknownOpcode(opname.to_string())),
+            }
+        }
+    };
+
+    let mut code = vec![opcode];
+    for field in &["result_type", "result_id"] {
+        if let Some(id) = inst.get(*field) {
+            code.push(as_word(id).ok_or(Error::MissingField(field))?);
+        }
+    }
+    if let Some(operands) = inst.get("operands") {
+        let operands = operands.as_array().ok_or(Error::MissingField("operands"))?;
+        for operand in operands {
+            code.append(&mut import_operand(operand)?);
+        }
+    }
+    if code.len() > usize::from(u16::MAX) {
+        return Err(Error::WrongOperandValue("list too long".to_string()));
+    }
+    code[0] |= (code.len() as u32) << 16;
+    Ok(code)
+}
+
+fn import_operand(operand: &Value) -> Result<Vec<u32>> {
+    let kind = operand.get("kind")
+        .and_then(Value::as_str)
+        .ok_or(Error::MissingField("kind"))?;
+    let value = operand.get("value").ok_or(Error::MissingField("value"))?;
+    let bits = operand.get("bits").and_then(Value::as_u64);
+    let wrong = || Error::WrongOperandValue(format!("for {}: {}", kind, value));
+
+    match kind {
+        "LiteralString" => {
+            let s = value.as_str().ok_or_else(wrong)?;
+            Ok(mr::Operand::LiteralString(s.to_string()).assemble())
+        }
+        "LiteralFloat32" => {
+            let bits = match bits {
+                Some(b) if b <= u64::from(u32::MAX) => b as u32,
+                Some(_) => return Err(wrong()),
+                None => (value.as_f64().ok_or_else(wrong)? as f32).to_bits(),
+            };
+            Ok(vec![bits])
+        }
+        "LiteralFloat64" | "LiteralInt64" => {
+            let bits = match bits {
+                Some(b) => b,
+                None if kind == "LiteralInt64" => value.as_u64().ok_or_else(wrong)?,
+                None => value.as_f64().ok_or_else(wrong)?.to_bits(),
+            };
+            Ok(vec![bits as u32, (bits >> 32) as u32])
+        }
+        _ => Ok(vec![as_word(value).ok_or_else(wrong)?]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use binary::Disassemble;
+
+    #[test]
+    fn test_round_trip() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.ext_inst_import("GLSL.std.450");
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let voidf = b.type_function(void, vec![]);
+        let f = b.begin_function(void, None, spirv::FunctionControl::DONT_INLINE, voidf)
+            .unwrap();
+        b.entry_point(spirv::ExecutionModel::GLCompute, f, "main", vec![]);
+        b.execution_mode(f, spirv::ExecutionMode::LocalSize, vec![8, 8, 1]);
+        b.constant_f32(float, 1.5);
+        b.constant_f32(float, f32::NAN);
+        b.begin_basic_block(None).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let module = b.module();
+
+        let json = super::export(&module);
+        assert_eq!(json["instructions"][0]["opname"], "OpCapability");
+        assert_eq!(json["instructions"][0]["operands"][0]["name"], "Shader");
+
+        let text = json.to_string();
+        let imported = super::import(&::serde_json::from_str(&text).unwrap()).unwrap();
+        assert_eq!(module.disassemble(), imported.disassemble());
+        assert_eq!(json, super::export(&imported));
+    }
+
+    #[test]
+    fn test_import_by_opname() {
+        let json = json!({
+            "instructions": [
+                {
+                    "opname": "OpMemoryModel",
+                    "operands": [
+                        { "kind": "AddressingModel", "value": 0 },
+                        { "kind": "MemoryModel", "value": 1 }
+                    ]
+                },
+                { "opname": "OpTypeVoid", "result_id": 1 },
+                { "opname": "OpName", "operands": [
+                    { "kind": "IdRef", "value": 1 },
+                    { "kind": "LiteralString", "value": "void" }
+                ]}
+            ]
+        });
+        let module = super::import(&json).unwrap();
+        assert_eq!(module.disassemble(),
+                   "; SPIR-V\n\
+                    ; Version: 1.2\n\
+                    ; Generator: rspirv\n\
+                    ; Bound: 0\n\
+                    OpMemoryModel Logical GLSL450\n\
+                    OpName %1 \"void\"\n\
+                    %1 = OpTypeVoid");
+    }
+
+    #[test]
+    fn test_wide_literals() {
+        let json = json!({
+            "instructions": [
+                { "opname": "OpTypeFloat", "result_id": 1, "operands": [
+                    { "kind": "LiteralInt32", "value": 64 }
+                ]},
+                { "opname": "OpTypeInt", "result_id": 2, "operands": [
+                    { "kind": "LiteralInt32", "value": 64 },
+                    { "kind": "LiteralInt32", "value": 0 }
+                ]},
+                { "opname": "OpConstant", "result_type": 1, "result_id": 3, "operands": [
+                    { "kind": "LiteralFloat64", "value": -0.25 }
+                ]},
+                { "opname": "OpConstant", "result_type": 2, "result_id": 4, "operands": [
+                    { "kind": "LiteralInt64", "value": 0x1_0000_0002u64 }
+                ]}
+            ]
+        });
+        let module = super::import(&json).unwrap();
+        assert_eq!(module.types_global_values[2].operands,
+                   vec![mr::Operand::LiteralFloat64(-0.25)]);
+        assert_eq!(module.types_global_values[3].operands,
+                   vec![mr::Operand::LiteralInt64(0x1_0000_0002)]);
+        let exported = super::export(&module);
+        assert_eq!(exported["instructions"][2]["operands"][0]["bits"],
+                   (-0.25f64).to_bits());
+        assert_eq!(super::import(&exported).unwrap().disassemble(), module.disassemble());
+    }
+
+    #[test]
+    fn test_import_errors() {
+        assert_matches!(super::import(&json!({})),
+                        Err(super::Error::MissingField("instructions")));
+        assert_matches!(super::import(&json!({ "instructions": [{ "opname": "OpFoo" }] })),
+                        Err(super::Error::UnknownOpcode(_)));
+        let json = json!({ "instructions": [{
+            "opname": "OpName",
+            "operands": [{ "kind": "LiteralString", "value": 1 }]
+        }]});
+        assert_matches!(super::import(&json), Err(super::Error::WrongOperandValue(_)));
+    }
+}
@@ -0,0 +1,24 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Alternative serialization formats for SPIR-V modules.
+//!
+//! Formats in this module convert a [`mr::Module`](../mr/struct.Module.html)
+//! to and from representations other than the SPIR-V binary, which are easier
+//! to consume from scripts and other tools.
+//!
+//! Each format is gated behind a cargo feature of the same name.
+
+#[cfg(feature = "json")]
+pub mod json;
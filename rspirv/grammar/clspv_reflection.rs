@@ -0,0 +1,62 @@
+// Copyright 2016 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// AUTOMATICALLY GENERATED from the SPIR-V JSON grammar:
+//   external/spirv.core.grammar.json.
+// DO NOT MODIFY!
+
+#[cfg_attr(rustfmt, rustfmt_skip)]
+static CLSPV_REFLECTION_INSTRUCTION_TABLE: &'static [ExtendedInstruction<'static>] = &[
+    ext_inst!(Kernel, 1, [], [(IdRef, One), (IdRef, One), (IdRef, ZeroOrOne), (IdRef, ZeroOrOne), (IdRef, ZeroOrOne)]),
+    ext_inst!(ArgumentInfo, 2, [], [(IdRef, One), (IdRef, ZeroOrOne), (IdRef, ZeroOrOne), (IdRef, ZeroOrOne), (IdRef, ZeroOrOne)]),
+    ext_inst!(ArgumentStorageBuffer, 3, [], [(IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, ZeroOrOne)]),
+    ext_inst!(ArgumentUniform, 4, [], [(IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, ZeroOrOne)]),
+    ext_inst!(ArgumentPodStorageBuffer, 5, [], [(IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, ZeroOrOne)]),
+    ext_inst!(ArgumentPodUniform, 6, [], [(IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, ZeroOrOne)]),
+    ext_inst!(ArgumentPodPushConstant, 7, [], [(IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, ZeroOrOne)]),
+    ext_inst!(ArgumentSampledImage, 8, [], [(IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, ZeroOrOne)]),
+    ext_inst!(ArgumentStorageImage, 9, [], [(IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, ZeroOrOne)]),
+    ext_inst!(ArgumentSampler, 10, [], [(IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, ZeroOrOne)]),
+    ext_inst!(ArgumentWorkgroup, 11, [], [(IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, ZeroOrOne)]),
+    ext_inst!(SpecConstantWorkgroupSize, 12, [], [(IdRef, One), (IdRef, One), (IdRef, One)]),
+    ext_inst!(SpecConstantGlobalOffset, 13, [], [(IdRef, One), (IdRef, One), (IdRef, One)]),
+    ext_inst!(SpecConstantWorkDim, 14, [], [(IdRef, One)]),
+    ext_inst!(PushConstantGlobalOffset, 15, [], [(IdRef, One), (IdRef, One)]),
+    ext_inst!(PushConstantEnqueuedLocalSize, 16, [], [(IdRef, One), (IdRef, One)]),
+    ext_inst!(PushConstantGlobalSize, 17, [], [(IdRef, One), (IdRef, One)]),
+    ext_inst!(PushConstantRegionOffset, 18, [], [(IdRef, One), (IdRef, One)]),
+    ext_inst!(PushConstantNumWorkgroups, 19, [], [(IdRef, One), (IdRef, One)]),
+    ext_inst!(PushConstantRegionGroupOffset, 20, [], [(IdRef, One), (IdRef, One)]),
+    ext_inst!(ConstantDataStorageBuffer, 21, [], [(IdRef, One), (IdRef, One), (IdRef, One)]),
+    ext_inst!(ConstantDataUniform, 22, [], [(IdRef, One), (IdRef, One), (IdRef, One)]),
+    ext_inst!(LiteralSampler, 23, [], [(IdRef, One), (IdRef, One), (IdRef, One)]),
+    ext_inst!(PropertyRequiredWorkgroupSize, 24, [], [(IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One)]),
+    ext_inst!(SpecConstantSubgroupMaxSize, 25, [], [(IdRef, One)]),
+    ext_inst!(ArgumentPointerPushConstant, 26, [], [(IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, ZeroOrOne)]),
+    ext_inst!(ArgumentPointerUniform, 27, [], [(IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, ZeroOrOne)]),
+    ext_inst!(ProgramScopeVariablesStorageBuffer, 28, [], [(IdRef, One), (IdRef, One), (IdRef, One)]),
+    ext_inst!(ProgramScopeVariablePointerRelocation, 29, [], [(IdRef, One), (IdRef, One), (IdRef, One)]),
+    ext_inst!(ImageArgumentInfoChannelOrderPushConstant, 30, [], [(IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One)]),
+    ext_inst!(ImageArgumentInfoChannelDataTypePushConstant, 31, [], [(IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One)]),
+    ext_inst!(ImageArgumentInfoChannelOrderUniform, 32, [], [(IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One)]),
+    ext_inst!(ImageArgumentInfoChannelDataTypeUniform, 33, [], [(IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One)]),
+    ext_inst!(ArgumentStorageTexelBuffer, 34, [], [(IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, ZeroOrOne)]),
+    ext_inst!(ArgumentUniformTexelBuffer, 35, [], [(IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One), (IdRef, ZeroOrOne)]),
+    ext_inst!(ConstantDataPointerPushConstant, 36, [], [(IdRef, One), (IdRef, One), (IdRef, One)]),
+    ext_inst!(ProgramScopeVariablePointerPushConstant, 37, [], [(IdRef, One), (IdRef, One), (IdRef, One)]),
+    ext_inst!(PrintfInfo, 38, [], [(IdRef, One), (IdRef, One), (IdRef, ZeroOrMore)]),
+    ext_inst!(PrintfBufferStorageBuffer, 39, [], [(IdRef, One), (IdRef, One), (IdRef, One)]),
+    ext_inst!(PrintfBufferPointerPushConstant, 40, [], [(IdRef, One), (IdRef, One), (IdRef, One)]),
+    ext_inst!(NormalizedSamplerMaskPushConstant, 41, [], [(IdRef, One), (IdRef, One), (IdRef, One), (IdRef, One)]),
+];
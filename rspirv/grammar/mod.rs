@@ -20,7 +20,12 @@
 //! It also provides many reflect functions.
 
 pub use self::syntax::{Instruction, ExtendedInstruction};
+pub use self::syntax::ClspvReflectionInstructionTable;
 pub use self::syntax::CoreInstructionTable;
+pub use self::syntax::DebugInfo100InstructionTable;
+pub use self::syntax::DebugPrintfInstructionTable;
+pub use self::syntax::ExtInstSet;
+pub use self::syntax::IdRole;
 pub use self::syntax::GlslStd450InstructionTable;
 pub use self::syntax::OpenCLStd100InstructionTable;
 pub use self::syntax::{LogicalOperand, OperandKind, OperandQuantifier};
@@ -12,14 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+// AUTOMATICALLY GENERATED from the SPIR-V JSON grammar:
+//   external/spirv.core.grammar.json.
+// DO NOT MODIFY!
+
 //! Reflect functions for SPIR-V instructions.
 
+use grammar::IdRole;
 use spirv;
 
 /// Returns true if the given opcode is for a location debug instruction.
 pub fn is_location_debug(opcode: spirv::Op) -> bool {
     match opcode {
-        spirv::Op::Line | spirv::Op::NoLine => true,
+        spirv::Op::Line |
+        spirv::Op::NoLine => true,
         _ => false,
     }
 }
@@ -32,7 +38,8 @@ pub fn is_nonlocation_debug(opcode: spirv::Op) -> bool {
         spirv::Op::SourceExtension |
         spirv::Op::Name |
         spirv::Op::MemberName |
-        spirv::Op::String => true,
+        spirv::Op::String |
+        spirv::Op::ModuleProcessed => true,
         _ => false,
     }
 }
@@ -49,12 +56,14 @@ pub fn is_annotation(opcode: spirv::Op) -> bool {
         spirv::Op::MemberDecorate |
         spirv::Op::DecorationGroup |
         spirv::Op::GroupDecorate |
-        spirv::Op::GroupMemberDecorate => true,
+        spirv::Op::GroupMemberDecorate |
+        spirv::Op::DecorateId |
+        spirv::Op::DecorateString |
+        spirv::Op::MemberDecorateString => true,
         _ => false,
     }
 }
 
-
 /// Returns true if the given opcode is for a type-declaring instruction.
 pub fn is_type(opcode: spirv::Op) -> bool {
     match opcode {
@@ -78,7 +87,11 @@ pub fn is_type(opcode: spirv::Op) -> bool {
         spirv::Op::TypeReserveId |
         spirv::Op::TypeQueue |
         spirv::Op::TypePipe |
-        spirv::Op::TypeForwardPointer => true,
+        spirv::Op::TypeForwardPointer |
+        spirv::Op::TypePipeStorage |
+        spirv::Op::TypeNamedBarrier |
+        spirv::Op::TypeCooperativeMatrixKHR |
+        spirv::Op::TypeAccelerationStructureKHR => true,
         _ => false,
     }
 }
@@ -96,7 +109,8 @@ pub fn is_constant(opcode: spirv::Op) -> bool {
         spirv::Op::SpecConstantFalse |
         spirv::Op::SpecConstant |
         spirv::Op::SpecConstantComposite |
-        spirv::Op::SpecConstantOp => true,
+        spirv::Op::SpecConstantOp |
+        spirv::Op::ConstantPipeStorage => true,
         _ => false,
     }
 }
@@ -109,14 +123,287 @@ pub fn is_variable(opcode: spirv::Op) -> bool {
 /// Returns true if the given opcode is for a terminator instruction.
 pub fn is_terminator(opcode: spirv::Op) -> bool {
     match opcode {
-        spirv::Op::Phi |
         spirv::Op::Branch |
         spirv::Op::BranchConditional |
         spirv::Op::Switch |
         spirv::Op::Kill |
         spirv::Op::Return |
         spirv::Op::ReturnValue |
-        spirv::Op::Unreachable => true,
+        spirv::Op::Unreachable |
+        spirv::Op::TerminateInvocation |
+        spirv::Op::IgnoreIntersectionKHR |
+        spirv::Op::TerminateRayKHR |
+        spirv::Op::EmitMeshTasksEXT => true,
         _ => false,
     }
 }
+
+/// Returns the roles of the ids in each logical operand of the given
+/// opcode, not counting the result type and id.
+///
+/// Ids beyond the logical operands, such as the parameters of enumerants,
+/// are values. So are all ids of the opcodes this returns no roles for.
+pub fn id_roles(opcode: spirv::Op) -> &'static [&'static [IdRole]] {
+    match opcode {
+        spirv::Op::Name => &[&[IdRole::DecorationTarget], &[]],
+        spirv::Op::MemberName => &[&[IdRole::DecorationTarget], &[], &[]],
+        spirv::Op::ExtInst => &[&[IdRole::ExtInstSet], &[], &[IdRole::Value]],
+        spirv::Op::EntryPoint => &[&[], &[IdRole::Function], &[], &[IdRole::MemoryObject]],
+        spirv::Op::ExecutionMode => &[&[IdRole::Function], &[]],
+        spirv::Op::TypeVector => &[&[IdRole::Type], &[]],
+        spirv::Op::TypeMatrix => &[&[IdRole::Type], &[]],
+        spirv::Op::TypeImage => &[&[IdRole::Type], &[], &[], &[], &[], &[], &[], &[]],
+        spirv::Op::TypeSampledImage => &[&[IdRole::Type]],
+        spirv::Op::TypeArray => &[&[IdRole::Type], &[IdRole::Value]],
+        spirv::Op::TypeRuntimeArray => &[&[IdRole::Type]],
+        spirv::Op::TypeStruct => &[&[IdRole::Type]],
+        spirv::Op::TypePointer => &[&[], &[IdRole::Type]],
+        spirv::Op::TypeFunction => &[&[IdRole::Type], &[IdRole::Type]],
+        spirv::Op::TypeForwardPointer => &[&[IdRole::Type], &[]],
+        spirv::Op::Function => &[&[], &[IdRole::Type]],
+        spirv::Op::FunctionCall => &[&[IdRole::Function], &[IdRole::Value]],
+        spirv::Op::Load => &[&[IdRole::MemoryObject], &[]],
+        spirv::Op::Store => &[&[IdRole::MemoryObject], &[IdRole::Value], &[]],
+        spirv::Op::CopyMemory => &[&[IdRole::MemoryObject], &[IdRole::MemoryObject], &[]],
+        spirv::Op::CopyMemorySized => &[&[IdRole::MemoryObject], &[IdRole::MemoryObject], &[IdRole::Value], &[]],
+        spirv::Op::AccessChain => &[&[IdRole::MemoryObject], &[IdRole::Value]],
+        spirv::Op::InBoundsAccessChain => &[&[IdRole::MemoryObject], &[IdRole::Value]],
+        spirv::Op::PtrAccessChain => &[&[IdRole::MemoryObject], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::ArrayLength => &[&[IdRole::MemoryObject], &[]],
+        spirv::Op::GenericPtrMemSemantics => &[&[IdRole::MemoryObject]],
+        spirv::Op::InBoundsPtrAccessChain => &[&[IdRole::MemoryObject], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::Decorate => &[&[IdRole::DecorationTarget], &[]],
+        spirv::Op::MemberDecorate => &[&[IdRole::DecorationTarget], &[], &[]],
+        spirv::Op::GroupDecorate => &[&[IdRole::Value], &[IdRole::DecorationTarget]],
+        spirv::Op::GroupMemberDecorate => &[&[IdRole::Value], &[IdRole::DecorationTarget]],
+        spirv::Op::ConvertPtrToU => &[&[IdRole::MemoryObject]],
+        spirv::Op::PtrCastToGeneric => &[&[IdRole::MemoryObject]],
+        spirv::Op::GenericCastToPtr => &[&[IdRole::MemoryObject]],
+        spirv::Op::GenericCastToPtrExplicit => &[&[IdRole::MemoryObject], &[]],
+        spirv::Op::ControlBarrier => &[&[IdRole::Scope], &[IdRole::Scope], &[IdRole::MemorySemantics]],
+        spirv::Op::MemoryBarrier => &[&[IdRole::Scope], &[IdRole::MemorySemantics]],
+        spirv::Op::AtomicLoad => &[&[IdRole::MemoryObject], &[IdRole::Scope], &[IdRole::MemorySemantics]],
+        spirv::Op::AtomicStore => &[&[IdRole::MemoryObject], &[IdRole::Scope], &[IdRole::MemorySemantics], &[IdRole::Value]],
+        spirv::Op::AtomicExchange => &[&[IdRole::MemoryObject], &[IdRole::Scope], &[IdRole::MemorySemantics], &[IdRole::Value]],
+        spirv::Op::AtomicCompareExchange => &[&[IdRole::MemoryObject], &[IdRole::Scope], &[IdRole::MemorySemantics], &[IdRole::MemorySemantics], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::AtomicCompareExchangeWeak => &[&[IdRole::MemoryObject], &[IdRole::Scope], &[IdRole::MemorySemantics], &[IdRole::MemorySemantics], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::AtomicIIncrement => &[&[IdRole::MemoryObject], &[IdRole::Scope], &[IdRole::MemorySemantics]],
+        spirv::Op::AtomicIDecrement => &[&[IdRole::MemoryObject], &[IdRole::Scope], &[IdRole::MemorySemantics]],
+        spirv::Op::AtomicIAdd => &[&[IdRole::MemoryObject], &[IdRole::Scope], &[IdRole::MemorySemantics], &[IdRole::Value]],
+        spirv::Op::AtomicISub => &[&[IdRole::MemoryObject], &[IdRole::Scope], &[IdRole::MemorySemantics], &[IdRole::Value]],
+        spirv::Op::AtomicSMin => &[&[IdRole::MemoryObject], &[IdRole::Scope], &[IdRole::MemorySemantics], &[IdRole::Value]],
+        spirv::Op::AtomicUMin => &[&[IdRole::MemoryObject], &[IdRole::Scope], &[IdRole::MemorySemantics], &[IdRole::Value]],
+        spirv::Op::AtomicSMax => &[&[IdRole::MemoryObject], &[IdRole::Scope], &[IdRole::MemorySemantics], &[IdRole::Value]],
+        spirv::Op::AtomicUMax => &[&[IdRole::MemoryObject], &[IdRole::Scope], &[IdRole::MemorySemantics], &[IdRole::Value]],
+        spirv::Op::AtomicAnd => &[&[IdRole::MemoryObject], &[IdRole::Scope], &[IdRole::MemorySemantics], &[IdRole::Value]],
+        spirv::Op::AtomicOr => &[&[IdRole::MemoryObject], &[IdRole::Scope], &[IdRole::MemorySemantics], &[IdRole::Value]],
+        spirv::Op::AtomicXor => &[&[IdRole::MemoryObject], &[IdRole::Scope], &[IdRole::MemorySemantics], &[IdRole::Value]],
+        spirv::Op::Phi => &[&[IdRole::Value, IdRole::BranchTarget]],
+        spirv::Op::LoopMerge => &[&[IdRole::BranchTarget], &[IdRole::BranchTarget], &[]],
+        spirv::Op::SelectionMerge => &[&[IdRole::BranchTarget], &[]],
+        spirv::Op::Branch => &[&[IdRole::BranchTarget]],
+        spirv::Op::BranchConditional => &[&[IdRole::Value], &[IdRole::BranchTarget], &[IdRole::BranchTarget], &[]],
+        spirv::Op::Switch => &[&[IdRole::Value], &[IdRole::BranchTarget], &[IdRole::BranchTarget]],
+        spirv::Op::LifetimeStart => &[&[IdRole::MemoryObject], &[]],
+        spirv::Op::LifetimeStop => &[&[IdRole::MemoryObject], &[]],
+        spirv::Op::GroupAsyncCopy => &[&[IdRole::Scope], &[IdRole::MemoryObject], &[IdRole::MemoryObject], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::GroupWaitEvents => &[&[IdRole::Scope], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::GroupAll => &[&[IdRole::Scope], &[IdRole::Value]],
+        spirv::Op::GroupAny => &[&[IdRole::Scope], &[IdRole::Value]],
+        spirv::Op::GroupBroadcast => &[&[IdRole::Scope], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::GroupIAdd => &[&[IdRole::Scope], &[], &[IdRole::Value]],
+        spirv::Op::GroupFAdd => &[&[IdRole::Scope], &[], &[IdRole::Value]],
+        spirv::Op::GroupFMin => &[&[IdRole::Scope], &[], &[IdRole::Value]],
+        spirv::Op::GroupUMin => &[&[IdRole::Scope], &[], &[IdRole::Value]],
+        spirv::Op::GroupSMin => &[&[IdRole::Scope], &[], &[IdRole::Value]],
+        spirv::Op::GroupFMax => &[&[IdRole::Scope], &[], &[IdRole::Value]],
+        spirv::Op::GroupUMax => &[&[IdRole::Scope], &[], &[IdRole::Value]],
+        spirv::Op::GroupSMax => &[&[IdRole::Scope], &[], &[IdRole::Value]],
+        spirv::Op::ReadPipe => &[&[IdRole::Value], &[IdRole::MemoryObject], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::WritePipe => &[&[IdRole::Value], &[IdRole::MemoryObject], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::ReservedReadPipe => &[&[IdRole::Value], &[IdRole::Value], &[IdRole::Value], &[IdRole::MemoryObject], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::ReservedWritePipe => &[&[IdRole::Value], &[IdRole::Value], &[IdRole::Value], &[IdRole::MemoryObject], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::GroupReserveReadPipePackets => &[&[IdRole::Scope], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::GroupReserveWritePipePackets => &[&[IdRole::Scope], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::GroupCommitReadPipe => &[&[IdRole::Scope], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::GroupCommitWritePipe => &[&[IdRole::Scope], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::EnqueueKernel => &[&[IdRole::Value], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value], &[IdRole::Function], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::GetKernelNDrangeSubGroupCount => &[&[IdRole::Value], &[IdRole::Function], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::GetKernelNDrangeMaxSubGroupSize => &[&[IdRole::Value], &[IdRole::Function], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::GetKernelWorkGroupSize => &[&[IdRole::Function], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::GetKernelPreferredWorkGroupSizeMultiple => &[&[IdRole::Function], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::AtomicFlagTestAndSet => &[&[IdRole::MemoryObject], &[IdRole::Scope], &[IdRole::MemorySemantics]],
+        spirv::Op::AtomicFlagClear => &[&[IdRole::MemoryObject], &[IdRole::Scope], &[IdRole::MemorySemantics]],
+        spirv::Op::SizeOf => &[&[IdRole::MemoryObject]],
+        spirv::Op::GetKernelLocalSizeForSubgroupCount => &[&[IdRole::Value], &[IdRole::Function], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::GetKernelMaxNumSubgroups => &[&[IdRole::Function], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::MemoryNamedBarrier => &[&[IdRole::Value], &[IdRole::Scope], &[IdRole::MemorySemantics]],
+        spirv::Op::ExecutionModeId => &[&[IdRole::Function], &[]],
+        spirv::Op::DecorateId => &[&[IdRole::DecorationTarget], &[]],
+        spirv::Op::GroupNonUniformElect => &[&[IdRole::Scope]],
+        spirv::Op::GroupNonUniformAll => &[&[IdRole::Scope], &[IdRole::Value]],
+        spirv::Op::GroupNonUniformAny => &[&[IdRole::Scope], &[IdRole::Value]],
+        spirv::Op::GroupNonUniformAllEqual => &[&[IdRole::Scope], &[IdRole::Value]],
+        spirv::Op::GroupNonUniformBroadcast => &[&[IdRole::Scope], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::GroupNonUniformBroadcastFirst => &[&[IdRole::Scope], &[IdRole::Value]],
+        spirv::Op::GroupNonUniformBallot => &[&[IdRole::Scope], &[IdRole::Value]],
+        spirv::Op::GroupNonUniformInverseBallot => &[&[IdRole::Scope], &[IdRole::Value]],
+        spirv::Op::GroupNonUniformBallotBitExtract => &[&[IdRole::Scope], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::GroupNonUniformBallotBitCount => &[&[IdRole::Scope], &[], &[IdRole::Value]],
+        spirv::Op::GroupNonUniformBallotFindLSB => &[&[IdRole::Scope], &[IdRole::Value]],
+        spirv::Op::GroupNonUniformBallotFindMSB => &[&[IdRole::Scope], &[IdRole::Value]],
+        spirv::Op::GroupNonUniformShuffle => &[&[IdRole::Scope], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::GroupNonUniformShuffleXor => &[&[IdRole::Scope], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::GroupNonUniformShuffleUp => &[&[IdRole::Scope], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::GroupNonUniformShuffleDown => &[&[IdRole::Scope], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::TypeCooperativeMatrixKHR => &[&[IdRole::Type], &[IdRole::Scope], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value]],
+        spirv::Op::CooperativeMatrixLoadKHR => &[&[IdRole::MemoryObject], &[IdRole::Value], &[IdRole::Value], &[]],
+        spirv::Op::CooperativeMatrixStoreKHR => &[&[IdRole::MemoryObject], &[IdRole::Value], &[IdRole::Value], &[IdRole::Value], &[]],
+        spirv::Op::CooperativeMatrixLengthKHR => &[&[IdRole::Type]],
+        spirv::Op::GroupIAddNonUniformAMD => &[&[IdRole::Scope], &[], &[IdRole::Value]],
+        spirv::Op::GroupFAddNonUniformAMD => &[&[IdRole::Scope], &[], &[IdRole::Value]],
+        spirv::Op::GroupFMinNonUniformAMD => &[&[IdRole::Scope], &[], &[IdRole::Value]],
+        spirv::Op::GroupUMinNonUniformAMD => &[&[IdRole::Scope], &[], &[IdRole::Value]],
+        spirv::Op::GroupSMinNonUniformAMD => &[&[IdRole::Scope], &[], &[IdRole::Value]],
+        spirv::Op::GroupFMaxNonUniformAMD => &[&[IdRole::Scope], &[], &[IdRole::Value]],
+        spirv::Op::GroupUMaxNonUniformAMD => &[&[IdRole::Scope], &[], &[IdRole::Value]],
+        spirv::Op::GroupSMaxNonUniformAMD => &[&[IdRole::Scope], &[], &[IdRole::Value]],
+        spirv::Op::DecorateString => &[&[IdRole::DecorationTarget], &[]],
+        spirv::Op::MemberDecorateString => &[&[IdRole::DecorationTarget], &[], &[]],
+        spirv::Op::AtomicFAddEXT => &[&[IdRole::MemoryObject], &[IdRole::Scope], &[IdRole::MemorySemantics], &[IdRole::Value]],
+        _ => &[],
+    }
+}
+
+/// Returns the capabilities the given capability implicitly declares,
+/// not counting those they in turn declare.
+pub fn implied_capabilities(capability: spirv::Capability) -> &'static [spirv::Capability] {
+    match capability {
+        spirv::Capability::Shader => &[spirv::Capability::Matrix],
+        spirv::Capability::Geometry => &[spirv::Capability::Shader],
+        spirv::Capability::Tessellation => &[spirv::Capability::Shader],
+        spirv::Capability::Vector16 => &[spirv::Capability::Kernel],
+        spirv::Capability::Float16Buffer => &[spirv::Capability::Kernel],
+        spirv::Capability::Int64Atomics => &[spirv::Capability::Int64],
+        spirv::Capability::ImageBasic => &[spirv::Capability::Kernel],
+        spirv::Capability::ImageReadWrite => &[spirv::Capability::ImageBasic],
+        spirv::Capability::ImageMipmap => &[spirv::Capability::ImageBasic],
+        spirv::Capability::Pipes => &[spirv::Capability::Kernel],
+        spirv::Capability::DeviceEnqueue => &[spirv::Capability::Kernel],
+        spirv::Capability::LiteralSampler => &[spirv::Capability::Kernel],
+        spirv::Capability::AtomicStorage => &[spirv::Capability::Shader],
+        spirv::Capability::TessellationPointSize => &[spirv::Capability::Tessellation],
+        spirv::Capability::GeometryPointSize => &[spirv::Capability::Geometry],
+        spirv::Capability::ImageGatherExtended => &[spirv::Capability::Shader],
+        spirv::Capability::StorageImageMultisample => &[spirv::Capability::Shader],
+        spirv::Capability::UniformBufferArrayDynamicIndexing => &[spirv::Capability::Shader],
+        spirv::Capability::SampledImageArrayDynamicIndexing => &[spirv::Capability::Shader],
+        spirv::Capability::StorageBufferArrayDynamicIndexing => &[spirv::Capability::Shader],
+        spirv::Capability::StorageImageArrayDynamicIndexing => &[spirv::Capability::Shader],
+        spirv::Capability::ClipDistance => &[spirv::Capability::Shader],
+        spirv::Capability::CullDistance => &[spirv::Capability::Shader],
+        spirv::Capability::ImageCubeArray => &[spirv::Capability::SampledCubeArray],
+        spirv::Capability::SampleRateShading => &[spirv::Capability::Shader],
+        spirv::Capability::ImageRect => &[spirv::Capability::SampledRect],
+        spirv::Capability::SampledRect => &[spirv::Capability::Shader],
+        spirv::Capability::GenericPointer => &[spirv::Capability::Addresses],
+        spirv::Capability::Int8 => &[spirv::Capability::Kernel],
+        spirv::Capability::InputAttachment => &[spirv::Capability::Shader],
+        spirv::Capability::SparseResidency => &[spirv::Capability::Shader],
+        spirv::Capability::MinLod => &[spirv::Capability::Shader],
+        spirv::Capability::Image1D => &[spirv::Capability::Sampled1D],
+        spirv::Capability::SampledCubeArray => &[spirv::Capability::Shader],
+        spirv::Capability::ImageBuffer => &[spirv::Capability::SampledBuffer],
+        spirv::Capability::ImageMSArray => &[spirv::Capability::Shader],
+        spirv::Capability::StorageImageExtendedFormats => &[spirv::Capability::Shader],
+        spirv::Capability::ImageQuery => &[spirv::Capability::Shader],
+        spirv::Capability::DerivativeControl => &[spirv::Capability::Shader],
+        spirv::Capability::InterpolationFunction => &[spirv::Capability::Shader],
+        spirv::Capability::TransformFeedback => &[spirv::Capability::Shader],
+        spirv::Capability::GeometryStreams => &[spirv::Capability::Geometry],
+        spirv::Capability::StorageImageReadWithoutFormat => &[spirv::Capability::Shader],
+        spirv::Capability::StorageImageWriteWithoutFormat => &[spirv::Capability::Shader],
+        spirv::Capability::MultiViewport => &[spirv::Capability::Geometry],
+        spirv::Capability::SubgroupDispatch => &[spirv::Capability::DeviceEnqueue],
+        spirv::Capability::NamedBarrier => &[spirv::Capability::Kernel],
+        spirv::Capability::PipeStorage => &[spirv::Capability::Pipes],
+        spirv::Capability::GroupNonUniformVote => &[spirv::Capability::GroupNonUniform],
+        spirv::Capability::GroupNonUniformBallot => &[spirv::Capability::GroupNonUniform],
+        spirv::Capability::GroupNonUniformShuffle => &[spirv::Capability::GroupNonUniform],
+        spirv::Capability::GroupNonUniformShuffleRelative => &[spirv::Capability::GroupNonUniform],
+        spirv::Capability::UniformAndStorageBuffer16BitAccess => &[spirv::Capability::StorageBuffer16BitAccess],
+        spirv::Capability::MultiView => &[spirv::Capability::Shader],
+        spirv::Capability::VariablePointersStorageBuffer => &[spirv::Capability::Shader],
+        spirv::Capability::VariablePointers => &[spirv::Capability::VariablePointersStorageBuffer],
+        spirv::Capability::UniformAndStorageBuffer8BitAccess => &[spirv::Capability::StorageBuffer8BitAccess],
+        spirv::Capability::RayTracingKHR => &[spirv::Capability::Shader],
+        spirv::Capability::ImageGatherBiasLodAMD => &[spirv::Capability::Shader],
+        spirv::Capability::FragmentMaskAMD => &[spirv::Capability::Shader],
+        spirv::Capability::StencilExportEXT => &[spirv::Capability::Shader],
+        spirv::Capability::ImageReadWriteLodAMD => &[spirv::Capability::Shader],
+        spirv::Capability::SampleMaskOverrideCoverageNV => &[spirv::Capability::SampleRateShading],
+        spirv::Capability::GeometryShaderPassthroughNV => &[spirv::Capability::Geometry],
+        spirv::Capability::ShaderViewportIndexLayerEXT => &[spirv::Capability::MultiViewport],
+        spirv::Capability::ShaderViewportMaskNV => &[spirv::Capability::ShaderViewportIndexLayerEXT],
+        spirv::Capability::ShaderStereoViewNV => &[spirv::Capability::ShaderViewportMaskNV],
+        spirv::Capability::PerViewAttributesNV => &[spirv::Capability::MultiView],
+        spirv::Capability::MeshShadingEXT => &[spirv::Capability::Shader],
+        spirv::Capability::ShaderNonUniform => &[spirv::Capability::Shader],
+        spirv::Capability::RuntimeDescriptorArray => &[spirv::Capability::Shader],
+        spirv::Capability::InputAttachmentArrayDynamicIndexing => &[spirv::Capability::InputAttachment],
+        spirv::Capability::UniformTexelBufferArrayDynamicIndexing => &[spirv::Capability::SampledBuffer],
+        spirv::Capability::StorageTexelBufferArrayDynamicIndexing => &[spirv::Capability::ImageBuffer],
+        spirv::Capability::UniformBufferArrayNonUniformIndexing => &[spirv::Capability::ShaderNonUniform],
+        spirv::Capability::SampledImageArrayNonUniformIndexing => &[spirv::Capability::ShaderNonUniform],
+        spirv::Capability::StorageBufferArrayNonUniformIndexing => &[spirv::Capability::ShaderNonUniform],
+        spirv::Capability::StorageImageArrayNonUniformIndexing => &[spirv::Capability::ShaderNonUniform],
+        spirv::Capability::InputAttachmentArrayNonUniformIndexing => &[spirv::Capability::InputAttachment, spirv::Capability::ShaderNonUniform],
+        spirv::Capability::UniformTexelBufferArrayNonUniformIndexing => &[spirv::Capability::SampledBuffer, spirv::Capability::ShaderNonUniform],
+        spirv::Capability::StorageTexelBufferArrayNonUniformIndexing => &[spirv::Capability::ImageBuffer, spirv::Capability::ShaderNonUniform],
+        spirv::Capability::PhysicalStorageBufferAddresses => &[spirv::Capability::Shader],
+        spirv::Capability::DemoteToHelperInvocation => &[spirv::Capability::Shader],
+        _ => &[],
+    }
+}
+
+/// Returns the names of the extensions instructions and operands may
+/// require, in order.
+pub fn known_extensions() -> &'static [&'static str] {
+    &[
+        "SPV_AMD_shader_fragment_mask",
+        "SPV_AMD_shader_image_load_store_lod",
+        "SPV_AMD_texture_gather_bias_lod",
+        "SPV_EXT_demote_to_helper_invocation",
+        "SPV_EXT_descriptor_indexing",
+        "SPV_EXT_mesh_shader",
+        "SPV_EXT_physical_storage_buffer",
+        "SPV_EXT_shader_atomic_float_add",
+        "SPV_EXT_shader_stencil_export",
+        "SPV_EXT_shader_viewport_index_layer",
+        "SPV_GOOGLE_decorate_string",
+        "SPV_GOOGLE_hlsl_functionality1",
+        "SPV_GOOGLE_user_type",
+        "SPV_KHR_16bit_storage",
+        "SPV_KHR_8bit_storage",
+        "SPV_KHR_cooperative_matrix",
+        "SPV_KHR_device_group",
+        "SPV_KHR_float_controls",
+        "SPV_KHR_multiview",
+        "SPV_KHR_physical_storage_buffer",
+        "SPV_KHR_post_depth_coverage",
+        "SPV_KHR_ray_tracing",
+        "SPV_KHR_shader_atomic_counter_ops",
+        "SPV_KHR_shader_ballot",
+        "SPV_KHR_shader_draw_parameters",
+        "SPV_KHR_storage_buffer_storage_class",
+        "SPV_KHR_subgroup_vote",
+        "SPV_KHR_terminate_invocation",
+        "SPV_KHR_variable_pointers",
+        "SPV_KHR_vulkan_memory_model",
+        "SPV_NVX_multiview_per_view_attributes",
+        "SPV_NV_geometry_shader_passthrough",
+        "SPV_NV_sample_mask_override_coverage",
+        "SPV_NV_stereo_view_rendering",
+        "SPV_NV_viewport_array2",
+    ]
+}
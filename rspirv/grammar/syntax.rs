@@ -23,6 +23,8 @@ pub struct Instruction<'a> {
     pub opcode: spirv::Op,
     /// Capabilities required for this instruction.
     pub capabilities: &'a [spirv::Capability],
+    /// Extensions required for this instruction.
+    pub extensions: &'a [&'a str],
     /// Logical operands for this instruction.
     ///
     /// This includes result type id and result id.
@@ -50,6 +52,33 @@ pub struct LogicalOperand {
     pub quantifier: OperandQuantifier,
 }
 
+/// The role of an id referred to by an instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IdRole {
+    /// The result type of the instruction.
+    ResultType,
+    /// A type the declared type is built from or the type of a function.
+    Type,
+    /// A value used by the instruction. Ids without a more specific role
+    /// are values.
+    Value,
+    /// A pointer to the memory the instruction accesses.
+    MemoryObject,
+    /// The label of a basic block, such as a branch target or the parent
+    /// block of an `OpPhi` value.
+    BranchTarget,
+    /// The target of a decoration or a debug name.
+    DecorationTarget,
+    /// A called, enqueued, or entry point function.
+    Function,
+    /// The extended instruction set of an `OpExtInst`.
+    ExtInstSet,
+    /// A memory or execution scope.
+    Scope,
+    /// A memory semantics mask.
+    MemorySemantics,
+}
+
 /// The repeat specification for a SPIR-V logical operand.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum OperandQuantifier {
@@ -63,13 +92,17 @@ pub enum OperandQuantifier {
 
 /// Declares the grammar for an SPIR-V instruction.
 macro_rules! inst {
-    ($op:ident, [$( $cap:ident ),*], [$( ($kind:ident, $quant:ident) ),*]) => {
+    ($op:ident, [$( $cap:ident ),*], [$( $ext:expr ),*],
+     [$( ($kind:ident, $quant:ident) ),*]) => {
         Instruction {
             opname: stringify!($op),
             opcode: spirv::Op::$op,
             capabilities: &[
                 $( spirv::Capability::$cap ),*
             ],
+            extensions: &[
+                $( $ext ),*
+            ],
             operands: &[
                 $( LogicalOperand {
                     kind: OperandKind::$kind,
@@ -114,6 +147,13 @@ impl CoreInstructionTable {
         })
     }
 
+    /// Looks up the given `opname` (without the `Op` prefix) in the
+    /// instruction table and returns a reference to the instruction grammar
+    /// entry if found.
+    pub fn lookup_opname(opname: &str) -> Option<&'static Instruction<'static>> {
+        INSTRUCTION_TABLE.iter().find(|inst| inst.opname == opname)
+    }
+
     /// Returns a reference to the instruction grammar entry with the given
     /// `opcode`.
     pub fn get(opcode: spirv::Op) -> &'static Instruction<'static> {
@@ -177,3 +217,148 @@ impl OpenCLStd100InstructionTable {
 }
 
 include!("opencl_std_100.rs");
+
+/// The table for all `NonSemantic.Shader.DebugInfo.100` extended
+/// instructions.
+///
+/// This table is staic data stored in the library.
+pub struct DebugInfo100InstructionTable;
+
+impl DebugInfo100InstructionTable {
+    /// Looks up the given `opcode` in the instruction table and returns
+    /// a reference to the instruction grammar entry if found.
+    pub fn lookup_opcode(opcode: u32) -> Option<&'static ExtendedInstruction<'static>> {
+        DEBUG_INFO_100_INSTRUCTION_TABLE.iter().find(|inst| {
+            inst.opcode == opcode
+        })
+    }
+
+    /// Returns a reference to the instruction grammar entry with the given
+    /// `opcode`.
+    pub fn get(opcode: spirv::DebugInfoOp) -> &'static ExtendedInstruction<'static> {
+        DEBUG_INFO_100_INSTRUCTION_TABLE
+            .iter()
+            .find(|inst| inst.opcode == opcode as spirv::Word)
+            .expect("internal error")
+    }
+}
+
+include!("debug_info_100.rs");
+
+/// The table for all `NonSemantic.DebugPrintf` extended instructions.
+///
+/// This table is staic data stored in the library.
+pub struct DebugPrintfInstructionTable;
+
+impl DebugPrintfInstructionTable {
+    /// Looks up the given `opcode` in the instruction table and returns
+    /// a reference to the instruction grammar entry if found.
+    pub fn lookup_opcode(opcode: u32) -> Option<&'static ExtendedInstruction<'static>> {
+        DEBUG_PRINTF_INSTRUCTION_TABLE.iter().find(|inst| {
+            inst.opcode == opcode
+        })
+    }
+
+    /// Returns a reference to the instruction grammar entry with the given
+    /// `opcode`.
+    pub fn get(opcode: spirv::DebugPrintfOp) -> &'static ExtendedInstruction<'static> {
+        DEBUG_PRINTF_INSTRUCTION_TABLE
+            .iter()
+            .find(|inst| inst.opcode == opcode as spirv::Word)
+            .expect("internal error")
+    }
+}
+
+include!("debug_printf.rs");
+
+/// The table for all `NonSemantic.ClspvReflection` extended instructions.
+///
+/// This table is staic data stored in the library.
+pub struct ClspvReflectionInstructionTable;
+
+impl ClspvReflectionInstructionTable {
+    /// Looks up the given `opcode` in the instruction table and returns
+    /// a reference to the instruction grammar entry if found.
+    pub fn lookup_opcode(opcode: u32) -> Option<&'static ExtendedInstruction<'static>> {
+        CLSPV_REFLECTION_INSTRUCTION_TABLE.iter().find(|inst| {
+            inst.opcode == opcode
+        })
+    }
+
+    /// Returns a reference to the instruction grammar entry with the given
+    /// `opcode`.
+    pub fn get(opcode: spirv::ClspvReflectionOp) -> &'static ExtendedInstruction<'static> {
+        CLSPV_REFLECTION_INSTRUCTION_TABLE
+            .iter()
+            .find(|inst| inst.opcode == opcode as spirv::Word)
+            .expect("internal error")
+    }
+}
+
+include!("clspv_reflection.rs");
+
+/// The extended instruction sets with a known grammar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ExtInstSet {
+    GlslStd450,
+    OpenCLStd100,
+    DebugInfo100,
+    DebugPrintf,
+    ClspvReflection,
+}
+
+impl ExtInstSet {
+    /// Returns the extended instruction set imported under `name` by
+    /// `OpExtInstImport`, if it is known.
+    ///
+    /// All versions of `NonSemantic.ClspvReflection` map to the same set,
+    /// since versions only ever add instructions.
+    pub fn from_name(name: &str) -> Option<ExtInstSet> {
+        match name {
+            "GLSL.std.450" => Some(ExtInstSet::GlslStd450),
+            "OpenCL.std" => Some(ExtInstSet::OpenCLStd100),
+            "NonSemantic.Shader.DebugInfo.100" => Some(ExtInstSet::DebugInfo100),
+            "NonSemantic.DebugPrintf" => Some(ExtInstSet::DebugPrintf),
+            _ if name.starts_with("NonSemantic.ClspvReflection.") => {
+                Some(ExtInstSet::ClspvReflection)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the name this extended instruction set is imported under.
+    pub fn name(self) -> &'static str {
+        match self {
+            ExtInstSet::GlslStd450 => "GLSL.std.450",
+            ExtInstSet::OpenCLStd100 => "OpenCL.std",
+            ExtInstSet::DebugInfo100 => "NonSemantic.Shader.DebugInfo.100",
+            ExtInstSet::DebugPrintf => "NonSemantic.DebugPrintf",
+            ExtInstSet::ClspvReflection => "NonSemantic.ClspvReflection.6",
+        }
+    }
+
+    /// Looks up the given `opcode` in the instruction table of this set and
+    /// returns a reference to the instruction grammar entry if found.
+    pub fn lookup_opcode(self, opcode: u32) -> Option<&'static ExtendedInstruction<'static>> {
+        match self {
+            ExtInstSet::GlslStd450 => GlslStd450InstructionTable::lookup_opcode(opcode),
+            ExtInstSet::OpenCLStd100 => OpenCLStd100InstructionTable::lookup_opcode(opcode),
+            ExtInstSet::DebugInfo100 => DebugInfo100InstructionTable::lookup_opcode(opcode),
+            ExtInstSet::DebugPrintf => DebugPrintfInstructionTable::lookup_opcode(opcode),
+            ExtInstSet::ClspvReflection => ClspvReflectionInstructionTable::lookup_opcode(opcode),
+        }
+    }
+
+    /// Looks up the given `opname` in the instruction table of this set and
+    /// returns a reference to the instruction grammar entry if found.
+    pub fn lookup_opname(self, opname: &str) -> Option<&'static ExtendedInstruction<'static>> {
+        let table = match self {
+            ExtInstSet::GlslStd450 => GLSL_STD_450_INSTRUCTION_TABLE,
+            ExtInstSet::OpenCLStd100 => OPENCL_STD_100_INSTRUCTION_TABLE,
+            ExtInstSet::DebugInfo100 => DEBUG_INFO_100_INSTRUCTION_TABLE,
+            ExtInstSet::DebugPrintf => DEBUG_PRINTF_INSTRUCTION_TABLE,
+            ExtInstSet::ClspvReflection => CLSPV_REFLECTION_INSTRUCTION_TABLE,
+        };
+        table.iter().find(|inst| inst.opname == opname)
+    }
+}
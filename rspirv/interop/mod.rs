@@ -0,0 +1,196 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Interoperation with other SPIR-V libraries, e.g., naga.
+//!
+//! Other libraries read and write SPIR-V as words: naga's SPIR-V front end
+//! parses words, and its back end writes them. A [`Bridge`](struct.Bridge.html)
+//! holds a module both as words and as a
+//! [`mr::Module`](../mr/struct.Module.html), validated once for a target
+//! environment, so that passing it back and forth does not parse or
+//! assemble it again.
+//!
+//! This module is gated behind the `interop` feature. It depends on no
+//! other library; words are handed over as slices.
+
+use binary;
+use env;
+use mr;
+
+use binary::Assemble;
+use std::{error, fmt, result};
+
+/// Interoperation errors.
+#[derive(Debug)]
+pub enum Error {
+    /// The words could not be parsed.
+    Parse(binary::ParseState),
+    /// The module falls outside the target environment.
+    Env(env::Error),
+}
+
+impl Error {
+    /// Gives an descriptive string for each error.
+    ///
+    /// This method is intended to be used by fmt::Display and error::Error to
+    /// avoid duplication in implementation. So it's private.
+    fn describe(&self) -> &str {
+        match *self {
+            Error::Parse(_) => "parsing failed",
+            Error::Env(_) => "validation failed",
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        self.describe()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Parse(ref err) => write!(f, "{}: {}", self.describe(), err),
+            Error::Env(ref err) => write!(f, "{}: {}", self.describe(), err),
+        }
+    }
+}
+
+impl From<binary::ParseState> for Error {
+    fn from(err: binary::ParseState) -> Error {
+        Error::Parse(err)
+    }
+}
+
+impl From<env::Error> for Error {
+    fn from(err: env::Error) -> Error {
+        Error::Env(err)
+    }
+}
+
+pub type Result<T> = result::Result<T, Error>;
+
+/// A module validated for a target environment, as both words and a data
+/// representation.
+#[derive(Debug)]
+pub struct Bridge {
+    env: env::TargetEnv,
+    words: Vec<u32>,
+    module: mr::Module,
+}
+
+impl Bridge {
+    /// Parses `words`, e.g., written by naga's SPIR-V back end, and checks
+    /// that they form a module for `env`.
+    pub fn from_words(words: Vec<u32>, env: env::TargetEnv) -> Result<Bridge> {
+        let module = mr::load_words(&words)?;
+        env.validate(&module)?;
+        Ok(Bridge { env, words, module })
+    }
+
+    /// Checks that `module` is for `env`, and assembles it.
+    pub fn from_module(module: mr::Module, env: env::TargetEnv) -> Result<Bridge> {
+        env.validate(&module)?;
+        let words = module.assemble();
+        Ok(Bridge { env, words, module })
+    }
+
+    /// Returns the target environment the module is validated for.
+    pub fn env(&self) -> env::TargetEnv {
+        self.env
+    }
+
+    /// Returns the words of the module, e.g., for naga's SPIR-V front end.
+    pub fn words(&self) -> &[u32] {
+        &self.words
+    }
+
+    /// Returns the data representation of the module.
+    pub fn module(&self) -> &mr::Module {
+        &self.module
+    }
+
+    /// Changes the module with `f`, then validates and assembles it again.
+    ///
+    /// The bridge is left unchanged if the changed module is not for the
+    /// target environment.
+    pub fn modify<F: FnOnce(&mut mr::Module)>(&mut self, f: F) -> Result<()> {
+        let mut module = mr::load_words(&self.words)?;
+        f(&mut module);
+        self.env.validate(&module)?;
+        self.words = module.assemble();
+        self.module = module;
+        Ok(())
+    }
+
+    /// Returns the words of the module.
+    pub fn into_words(self) -> Vec<u32> {
+        self.words
+    }
+
+    /// Returns the data representation of the module.
+    pub fn into_module(self) -> mr::Module {
+        self.module
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use binary::Assemble;
+    use env;
+    use mr;
+    use spirv;
+
+    use super::{Bridge, Error};
+
+    #[test]
+    fn test_bridge() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let voidf = b.type_function(void, vec![]);
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::GLCompute, f, "main", vec![]);
+        b.name(f, "main");
+        let words = b.module().assemble();
+
+        let mut bridge = Bridge::from_words(words.clone(), env::TargetEnv::Vulkan1_1).unwrap();
+        assert_eq!(bridge.words(), &words[..]);
+        assert_eq!(bridge.module().entry_points.len(), 1);
+
+        let err = bridge.modify(|m| {
+                let inst = mr::Instruction::new(spirv::Op::Capability,
+                                                None,
+                                                None,
+                                                vec![spirv::Capability::Kernel.into()]);
+                m.capabilities.push(inst);
+            })
+            .unwrap_err();
+        assert_matches!(err, Error::Env(env::Error::Capability(spirv::Capability::Kernel)));
+        assert_eq!(bridge.words(), &words[..]);
+
+        bridge.modify(|m| m.debugs.clear()).unwrap();
+        assert!(bridge.module().debugs.is_empty());
+        assert_eq!(bridge.words().len(), words.len() - 4);
+        let module = bridge.into_module();
+        assert_eq!(module.assemble().len(), words.len() - 4);
+        assert_matches!(Bridge::from_words(vec![0; 5], env::TargetEnv::Vulkan1_1),
+                        Err(Error::Parse(_)));
+    }
+}
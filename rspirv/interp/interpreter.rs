@@ -0,0 +1,1252 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grammar;
+use mr;
+use reflect::TypeIndex;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, HashMap};
+use std::{error, fmt, result};
+use super::ops::{self, Pointer, Value};
+
+/// The default maximal number of instructions a run may execute.
+const DEFAULT_STEP_LIMIT: usize = 1 << 24;
+
+/// Interpreter errors.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// No `GLCompute` entry point has the given name.
+    MissingEntryPoint(String),
+    /// The module uses an instruction outside the supported subset.
+    Unsupported(spirv::Op),
+    /// The module has a variable in an unsupported storage class.
+    UnsupportedStorageClass(spirv::StorageClass),
+    /// The module has an input variable for an unsupported built-in.
+    UnsupportedBuiltIn(spirv::BuiltIn),
+    /// No buffer is bound at the given descriptor set and binding.
+    UnboundBuffer(u32, u32),
+    /// The module uses an id without defining it.
+    UndefinedId(Word),
+    /// The operands of the instruction have unexpected kinds.
+    TypeMismatch(spirv::Op),
+    /// The module accesses memory or a composite out of bounds.
+    OutOfBounds,
+    /// The module divides an integer by zero.
+    DivisionByZero,
+    /// The module executed OpUnreachable.
+    Unreachable,
+    /// The run did not finish within the step limit.
+    StepLimit,
+}
+
+impl Error {
+    /// Gives an descriptive string for each error.
+    ///
+    /// This method is intended to be used by fmt::Display and error::Error to
+    /// avoid duplication in implementation. So it's private.
+    fn describe(&self) -> &str {
+        match *self {
+            Error::MissingEntryPoint(_) => "missing GLCompute entry point",
+            Error::Unsupported(_) => "unsupported instruction",
+            Error::UnsupportedStorageClass(_) => "unsupported storage class",
+            Error::UnsupportedBuiltIn(_) => "unsupported built-in",
+            Error::UnboundBuffer(..) => "unbound buffer",
+            Error::UndefinedId(_) => "undefined id",
+            Error::TypeMismatch(_) => "operand type mismatch",
+            Error::OutOfBounds => "out of bounds access",
+            Error::DivisionByZero => "integer division by zero",
+            Error::Unreachable => "reached OpUnreachable",
+            Error::StepLimit => "step limit exceeded",
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        self.describe()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::MissingEntryPoint(ref name) => write!(f, "{} '{}'", self.describe(), name),
+            Error::Unsupported(op) |
+            Error::TypeMismatch(op) => write!(f, "{} Op{:?}", self.describe(), op),
+            Error::UnsupportedStorageClass(class) => write!(f, "{} {:?}", self.describe(), class),
+            Error::UnsupportedBuiltIn(builtin) => write!(f, "{} {:?}", self.describe(), builtin),
+            Error::UnboundBuffer(set, binding) => {
+                write!(f, "{} at set {} binding {}", self.describe(), set, binding)
+            }
+            Error::UndefinedId(id) => write!(f, "{} %{}", self.describe(), id),
+            _ => write!(f, "{}", self.describe()),
+        }
+    }
+}
+
+pub type Result<T> = result::Result<T, Error>;
+
+/// An interpreter for compute shaders.
+///
+/// The interpreter executes the invocations of a dispatch one after another.
+/// It supports 32-bit integer and float arithmetic, vectors and other
+/// composites, structured and unstructured control flow, function calls,
+/// `Private` and `Function` variables, the invocation id built-ins, and
+/// buffers in the `Uniform` and `StorageBuffer` storage classes. Buffers are
+/// backed by the words [bound](#method.bind_buffer) to their descriptor set
+/// and binding, following the explicit layout decorations of the module.
+///
+/// As invocations do not run concurrently, barriers and `Workgroup`
+/// variables are not supported.
+pub struct Interpreter<'m> {
+    module: &'m mr::Module,
+    index: TypeIndex<'m>,
+    buffers: BTreeMap<(u32, u32), Vec<u32>>,
+    specializations: BTreeMap<u32, u32>,
+    step_limit: usize,
+}
+
+impl<'m> Interpreter<'m> {
+    /// Creates a new interpreter for the given `module`.
+    pub fn new(module: &'m mr::Module) -> Interpreter<'m> {
+        Interpreter {
+            module,
+            index: TypeIndex::new(module),
+            buffers: BTreeMap::new(),
+            specializations: BTreeMap::new(),
+            step_limit: DEFAULT_STEP_LIMIT,
+        }
+    }
+
+    /// Binds `words` as the memory of the buffer at the given descriptor
+    /// `set` and `binding`, replacing any previously bound memory.
+    pub fn bind_buffer(&mut self, set: u32, binding: u32, words: Vec<u32>) {
+        self.buffers.insert((set, binding), words);
+    }
+
+    /// Returns the memory of the buffer at the given descriptor `set` and
+    /// `binding`, if bound.
+    pub fn buffer(&self, set: u32, binding: u32) -> Option<&[u32]> {
+        self.buffers.get(&(set, binding)).map(|words| &words[..])
+    }
+
+    /// Sets the value of the specialization constant with the given
+    /// `spec_id`. Floats are given by their bits and booleans as 0 or 1.
+    pub fn specialize(&mut self, spec_id: u32, value: u32) {
+        self.specializations.insert(spec_id, value);
+    }
+
+    /// Sets the maximal number of instructions a run may execute.
+    pub fn set_step_limit(&mut self, limit: usize) {
+        self.step_limit = limit;
+    }
+
+    /// Runs the `GLCompute` entry point named `entry_point` for the given
+    /// number of `workgroups` in each dimension.
+    ///
+    /// Writes to buffers stay in the bound memory, also if the run fails.
+    pub fn run(&mut self, entry_point: &str, workgroups: [u32; 3]) -> Result<()> {
+        let entry = self.module
+            .entry_points
+            .iter()
+            .find(|inst| {
+                inst.operands.first() ==
+                Some(&mr::Operand::ExecutionModel(spirv::ExecutionModel::GLCompute)) &&
+                inst.operands.get(2) == Some(&mr::Operand::LiteralString(entry_point.to_owned()))
+            })
+            .ok_or_else(|| Error::MissingEntryPoint(entry_point.to_owned()))?;
+        let function_id = id(&entry.operands[1])?;
+        let local_size = self.module
+            .execution_modes
+            .iter()
+            .find(|inst| {
+                inst.operands.first() == Some(&mr::Operand::IdRef(function_id)) &&
+                inst.operands.get(1) ==
+                Some(&mr::Operand::ExecutionMode(spirv::ExecutionMode::LocalSize))
+            })
+            .map_or(Ok([1, 1, 1]), |inst| -> Result<[u32; 3]> {
+                Ok([literal(&inst.operands[2])?,
+                    literal(&inst.operands[3])?,
+                    literal(&inst.operands[4])?])
+            })?;
+
+        let mut machine = Machine {
+            module: self.module,
+            index: &self.index,
+            buffers: &mut self.buffers,
+            specializations: &self.specializations,
+            functions: self.module
+                .functions
+                .iter()
+                .filter_map(|f| f.def.as_ref().and_then(|d| d.result_id).map(|id| (id, f)))
+                .collect(),
+            globals: HashMap::new(),
+            memory: vec![],
+            builtins: vec![],
+            steps: 0,
+            step_limit: self.step_limit,
+        };
+        machine.globals()?;
+        let function = *machine.functions.get(&function_id).ok_or(Error::UndefinedId(function_id))?;
+        let memory = machine.memory.clone();
+
+        for group in ids(workgroups) {
+            for local in ids(local_size) {
+                machine.memory = memory.clone();
+                machine.set_builtins(workgroups, local_size, group, local)?;
+                machine.call(function, vec![])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns all ids in the grid of the given `size`, x varying fastest.
+fn ids(size: [u32; 3]) -> impl Iterator<Item = [u32; 3]> {
+    (0..size[2]).flat_map(move |z| {
+        (0..size[1]).flat_map(move |y| (0..size[0]).map(move |x| [x, y, z]))
+    })
+}
+
+fn id(operand: &mr::Operand) -> Result<Word> {
+    match *operand {
+        mr::Operand::IdRef(id) => Ok(id),
+        _ => Err(Error::UndefinedId(0)),
+    }
+}
+
+fn literal(operand: &mr::Operand) -> Result<u32> {
+    match *operand {
+        mr::Operand::LiteralInt32(v) => Ok(v),
+        _ => Err(Error::OutOfBounds),
+    }
+}
+
+fn int(value: Value, opcode: spirv::Op) -> Result<u32> {
+    match value {
+        Value::Int(v) => Ok(v),
+        _ => Err(Error::TypeMismatch(opcode)),
+    }
+}
+
+fn pointer(value: Value, opcode: spirv::Op) -> Result<Pointer> {
+    match value {
+        Value::Pointer(p) => Ok(p),
+        _ => Err(Error::TypeMismatch(opcode)),
+    }
+}
+
+fn lookup(locals: &HashMap<Word, Value>,
+          globals: &HashMap<Word, Value>,
+          operand: &mr::Operand)
+          -> Result<Value> {
+    let id = id(operand)?;
+    locals.get(&id).or_else(|| globals.get(&id)).cloned().ok_or(Error::UndefinedId(id))
+}
+
+/// Returns the value `phi` takes when entered from the block labeled
+/// `previous`.
+fn phi(locals: &HashMap<Word, Value>,
+       globals: &HashMap<Word, Value>,
+       phi: &mr::Instruction,
+       previous: Option<Word>)
+       -> Result<Value> {
+    for pair in phi.operands.chunks(2) {
+        if pair.len() == 2 && Some(id(&pair[1])?) == previous {
+            return lookup(locals, globals, &pair[0]);
+        }
+    }
+    Err(Error::TypeMismatch(spirv::Op::Phi))
+}
+
+/// Returns the element of `value` at the given `path` of indices.
+fn place<'v>(value: &'v mut Value, path: &[u32]) -> Result<&'v mut Value> {
+    let mut value = value;
+    for &index in path {
+        value = match *value {
+            Value::Composite(ref mut elements) => {
+                elements.get_mut(index as usize).ok_or(Error::OutOfBounds)?
+            }
+            _ => return Err(Error::OutOfBounds),
+        };
+    }
+    Ok(value)
+}
+
+fn bitcast(value: Value, to_float: bool) -> Value {
+    match (value, to_float) {
+        (Value::Composite(xs), _) => {
+            Value::Composite(xs.into_iter().map(|x| bitcast(x, to_float)).collect())
+        }
+        (Value::Float(v), false) => Value::Int(v.to_bits()),
+        (Value::Int(v), true) => Value::Float(f32::from_bits(v)),
+        (v, _) => v,
+    }
+}
+
+/// The state of a run.
+struct Machine<'a, 'm: 'a> {
+    module: &'m mr::Module,
+    index: &'a TypeIndex<'m>,
+    buffers: &'a mut BTreeMap<(u32, u32), Vec<u32>>,
+    specializations: &'a BTreeMap<u32, u32>,
+    functions: HashMap<Word, &'m mr::Function>,
+    globals: HashMap<Word, Value>,
+    /// The values of all variables outside buffers.
+    memory: Vec<Value>,
+    /// The variables holding built-in inputs.
+    builtins: Vec<(usize, spirv::BuiltIn)>,
+    steps: usize,
+    step_limit: usize,
+}
+
+impl<'a, 'm> Machine<'a, 'm> {
+    fn def(&self, ty: Word) -> Result<&'m mr::Instruction> {
+        self.index.def(ty).ok_or(Error::UndefinedId(ty))
+    }
+
+    fn check_width(inst: &mr::Instruction) -> Result<()> {
+        match inst.operands.first() {
+            Some(&mr::Operand::LiteralInt32(32)) => Ok(()),
+            _ => Err(Error::Unsupported(inst.class.opcode)),
+        }
+    }
+
+    /// Returns the value of the given scalar type with the given bits.
+    fn scalar(&self, ty: Word, bits: u32) -> Result<Value> {
+        let inst = self.def(ty)?;
+        match inst.class.opcode {
+            spirv::Op::TypeInt => Machine::check_width(inst).map(|_| Value::Int(bits)),
+            spirv::Op::TypeFloat => {
+                Machine::check_width(inst).map(|_| Value::Float(f32::from_bits(bits)))
+            }
+            spirv::Op::TypeBool => Ok(Value::Bool(bits != 0)),
+            op => Err(Error::Unsupported(op)),
+        }
+    }
+
+    /// Returns the zero value of the given type.
+    fn zero(&self, ty: Word) -> Result<Value> {
+        let inst = self.def(ty)?;
+        Ok(match inst.class.opcode {
+            spirv::Op::TypeInt | spirv::Op::TypeFloat | spirv::Op::TypeBool => self.scalar(ty, 0)?,
+            spirv::Op::TypeVector | spirv::Op::TypeMatrix => {
+                let zero = self.zero(id(&inst.operands[0])?)?;
+                Value::Composite(vec![zero; literal(&inst.operands[1])? as usize])
+            }
+            spirv::Op::TypeArray => {
+                let zero = self.zero(id(&inst.operands[0])?)?;
+                let len = match self.globals.get(&id(&inst.operands[1])?) {
+                    Some(&Value::Int(len)) => len as usize,
+                    _ => return Err(Error::TypeMismatch(inst.class.opcode)),
+                };
+                Value::Composite(vec![zero; len])
+            }
+            spirv::Op::TypeStruct => {
+                let members: Result<_> = inst.operands.iter().map(|m| self.zero(id(m)?)).collect();
+                Value::Composite(members?)
+            }
+            op => return Err(Error::Unsupported(op)),
+        })
+    }
+
+    /// Returns the given decoration operand on `target`.
+    fn decoration(&self, target: Word, decoration: spirv::Decoration) -> Option<&'m mr::Operand> {
+        self.module
+            .annotations
+            .iter()
+            .find(|inst| {
+                inst.class.opcode == spirv::Op::Decorate &&
+                inst.operands.first() == Some(&mr::Operand::IdRef(target)) &&
+                inst.operands.get(1) == Some(&mr::Operand::Decoration(decoration))
+            })
+            .and_then(|inst| inst.operands.get(2))
+    }
+
+    /// Evaluates the module-level constants and allocates the module-level
+    /// variables.
+    fn globals(&mut self) -> Result<()> {
+        for inst in &self.module.types_global_values {
+            let opcode = inst.class.opcode;
+            let result_id = match inst.result_id {
+                Some(id) if !grammar::reflect::is_type(opcode) => id,
+                _ => continue,
+            };
+            let specialized = match self.decoration(result_id, spirv::Decoration::SpecId) {
+                Some(&mr::Operand::LiteralInt32(spec_id)) => {
+                    self.specializations.get(&spec_id).cloned()
+                }
+                _ => None,
+            };
+            let result_type = inst.result_type.unwrap_or(0);
+            let value = match opcode {
+                spirv::Op::ConstantTrue | spirv::Op::SpecConstantTrue => Value::Bool(true),
+                spirv::Op::ConstantFalse | spirv::Op::SpecConstantFalse => Value::Bool(false),
+                spirv::Op::Constant | spirv::Op::SpecConstant => {
+                    let bits = match inst.operands[0] {
+                        mr::Operand::LiteralInt32(v) => v,
+                        mr::Operand::LiteralFloat32(v) => v.to_bits(),
+                        _ => return Err(Error::Unsupported(opcode)),
+                    };
+                    self.scalar(result_type, bits)?
+                }
+                spirv::Op::ConstantComposite | spirv::Op::SpecConstantComposite => {
+                    let empty = HashMap::new();
+                    let constituents: Result<_> =
+                        inst.operands.iter().map(|c| lookup(&empty, &self.globals, c)).collect();
+                    Value::Composite(constituents?)
+                }
+                spirv::Op::ConstantNull | spirv::Op::Undef => self.zero(result_type)?,
+                spirv::Op::SpecConstantOp => {
+                    let op = match inst.operands[0] {
+                        mr::Operand::LiteralSpecConstantOpInteger(op) => op,
+                        _ => return Err(Error::TypeMismatch(opcode)),
+                    };
+                    let empty = HashMap::new();
+                    let args: Result<Vec<_>> = inst.operands[1..]
+                        .iter()
+                        .map(|a| lookup(&empty, &self.globals, a))
+                        .collect();
+                    let mut args = args?.into_iter();
+                    match (args.next(), args.next(), args.next()) {
+                        (Some(x), None, None) if ops::is_unary(op) => ops::unary(op, x)?,
+                        (Some(x), Some(y), None) if ops::is_binary(op) => ops::binary(op, x, y)?,
+                        (Some(Value::Bool(c)), Some(x), Some(y)) if op == spirv::Op::Select => {
+                            if c { x } else { y }
+                        }
+                        _ => return Err(Error::Unsupported(op)),
+                    }
+                }
+                spirv::Op::Variable => self.global_variable(inst)?,
+                _ => return Err(Error::Unsupported(opcode)),
+            };
+            let value = match (specialized, value) {
+                (Some(bits), Value::Bool(_)) => Value::Bool(bits != 0),
+                (Some(bits), Value::Int(_)) | (Some(bits), Value::Float(_)) => {
+                    self.scalar(result_type, bits)?
+                }
+                (_, value) => value,
+            };
+            self.globals.insert(result_id, value);
+        }
+        Ok(())
+    }
+
+    fn global_variable(&mut self, inst: &mr::Instruction) -> Result<Value> {
+        let result_id = inst.result_id.unwrap_or(0);
+        let pointee = self.index
+            .pointee_type(inst.result_type.unwrap_or(0))
+            .ok_or(Error::TypeMismatch(spirv::Op::Variable))?;
+        match inst.operands[0] {
+            mr::Operand::StorageClass(spirv::StorageClass::Uniform) |
+            mr::Operand::StorageClass(spirv::StorageClass::StorageBuffer) => {
+                let set =
+                    self.index.decoration_literal(result_id, spirv::Decoration::DescriptorSet);
+                let binding = self.index.decoration_literal(result_id, spirv::Decoration::Binding);
+                let key = (set.unwrap_or(0), binding.unwrap_or(0));
+                Ok(Value::Pointer(Pointer::Buffer(key, 0, pointee)))
+            }
+            mr::Operand::StorageClass(spirv::StorageClass::Private) => {
+                self.allocate(inst, &HashMap::new())
+            }
+            mr::Operand::StorageClass(spirv::StorageClass::Input) => {
+                let builtin = match self.decoration(result_id, spirv::Decoration::BuiltIn) {
+                    Some(&mr::Operand::BuiltIn(builtin)) => builtin,
+                    _ => return Err(Error::UnsupportedStorageClass(spirv::StorageClass::Input)),
+                };
+                let value = self.allocate(inst, &HashMap::new())?;
+                self.builtins.push((self.memory.len() - 1, builtin));
+                Ok(value)
+            }
+            mr::Operand::StorageClass(class) => Err(Error::UnsupportedStorageClass(class)),
+            _ => Err(Error::TypeMismatch(spirv::Op::Variable)),
+        }
+    }
+
+    /// Allocates the memory for the given OpVariable and returns a pointer
+    /// to it.
+    fn allocate(&mut self, inst: &mr::Instruction, locals: &HashMap<Word, Value>) -> Result<Value> {
+        let pointee = self.index
+            .pointee_type(inst.result_type.unwrap_or(0))
+            .ok_or(Error::TypeMismatch(spirv::Op::Variable))?;
+        let value = match inst.operands.get(1) {
+            Some(init) => lookup(locals, &self.globals, init)?,
+            None => self.zero(pointee)?,
+        };
+        self.memory.push(value);
+        Ok(Value::Pointer(Pointer::Variable(self.memory.len() - 1, vec![])))
+    }
+
+    fn set_builtins(&mut self,
+                    workgroups: [u32; 3],
+                    local_size: [u32; 3],
+                    group: [u32; 3],
+                    local: [u32; 3])
+                    -> Result<()> {
+        let uvec3 = |v: [u32; 3]| Value::Composite(v.iter().map(|&c| Value::Int(c)).collect());
+        for &(var, builtin) in &self.builtins {
+            self.memory[var] = match builtin {
+                spirv::BuiltIn::GlobalInvocationId => {
+                    uvec3([0, 1, 2].map(|i| group[i] * local_size[i] + local[i]))
+                }
+                spirv::BuiltIn::LocalInvocationId => uvec3(local),
+                spirv::BuiltIn::WorkgroupId => uvec3(group),
+                spirv::BuiltIn::NumWorkgroups => uvec3(workgroups),
+                spirv::BuiltIn::WorkgroupSize => uvec3(local_size),
+                spirv::BuiltIn::LocalInvocationIndex => {
+                    Value::Int((local[2] * local_size[1] + local[1]) * local_size[0] + local[0])
+                }
+                builtin => return Err(Error::UnsupportedBuiltIn(builtin)),
+            };
+        }
+        Ok(())
+    }
+
+    fn buffer(&self, key: (u32, u32)) -> Result<&Vec<u32>> {
+        self.buffers.get(&key).ok_or(Error::UnboundBuffer(key.0, key.1))
+    }
+
+    /// Returns the byte offset and the type of the element `index` of the
+    /// buffer type `ty`.
+    fn buffer_element(&self, ty: Word, index: u32) -> Result<(u32, Word)> {
+        let inst = self.def(ty)?;
+        match inst.class.opcode {
+            spirv::Op::TypeStruct => {
+                let member = id(inst.operands.get(index as usize).ok_or(Error::OutOfBounds)?)?;
+                let offset = self.index
+                    .member_decoration_literal(ty, index, spirv::Decoration::Offset)
+                    .ok_or(Error::Unsupported(spirv::Op::TypeStruct))?;
+                Ok((offset, member))
+            }
+            spirv::Op::TypeArray | spirv::Op::TypeRuntimeArray => {
+                let element = id(&inst.operands[0])?;
+                let stride = self.index
+                    .decoration_literal(ty, spirv::Decoration::ArrayStride)
+                    .or_else(|| self.index.type_size(element))
+                    .ok_or(Error::Unsupported(inst.class.opcode))?;
+                Ok((index.wrapping_mul(stride), element))
+            }
+            spirv::Op::TypeVector => {
+                let element = id(&inst.operands[0])?;
+                let size = self.index
+                    .type_size(element)
+                    .ok_or(Error::Unsupported(spirv::Op::TypeVector))?;
+                Ok((index.wrapping_mul(size), element))
+            }
+            op => Err(Error::Unsupported(op)),
+        }
+    }
+
+    /// Returns the number of elements of the buffer type `ty`.
+    fn buffer_len(&self, ty: Word) -> Result<u32> {
+        let inst = self.def(ty)?;
+        match inst.class.opcode {
+            spirv::Op::TypeStruct => Ok(inst.operands.len() as u32),
+            spirv::Op::TypeVector => literal(&inst.operands[1]),
+            spirv::Op::TypeArray => {
+                match self.globals.get(&id(&inst.operands[1])?) {
+                    Some(&Value::Int(len)) => Ok(len),
+                    _ => Err(Error::TypeMismatch(spirv::Op::TypeArray)),
+                }
+            }
+            op => Err(Error::Unsupported(op)),
+        }
+    }
+
+    fn load_buffer(&self, key: (u32, u32), offset: u32, ty: Word) -> Result<Value> {
+        let inst = self.def(ty)?;
+        match inst.class.opcode {
+            spirv::Op::TypeInt | spirv::Op::TypeFloat => {
+                if offset & 3 != 0 {
+                    return Err(Error::OutOfBounds);
+                }
+                let word = *self.buffer(key)?.get((offset / 4) as usize).ok_or(Error::OutOfBounds)?;
+                self.scalar(ty, word)
+            }
+            _ => {
+                let elements: Result<_> = (0..self.buffer_len(ty)?)
+                    .map(|i| {
+                        let (element_offset, element) = self.buffer_element(ty, i)?;
+                        self.load_buffer(key, offset.wrapping_add(element_offset), element)
+                    })
+                    .collect();
+                Ok(Value::Composite(elements?))
+            }
+        }
+    }
+
+    fn store_buffer(&mut self, key: (u32, u32), offset: u32, ty: Word, value: Value) -> Result<()> {
+        let word = match value {
+            Value::Int(v) => v,
+            Value::Float(v) => v.to_bits(),
+            Value::Composite(elements) => {
+                for (i, element) in elements.into_iter().enumerate() {
+                    let (element_offset, element_type) = self.buffer_element(ty, i as u32)?;
+                    let element_offset = offset.wrapping_add(element_offset);
+                    self.store_buffer(key, element_offset, element_type, element)?;
+                }
+                return Ok(());
+            }
+            _ => return Err(Error::TypeMismatch(spirv::Op::Store)),
+        };
+        if offset & 3 != 0 {
+            return Err(Error::OutOfBounds);
+        }
+        let words = self.buffers.get_mut(&key).ok_or(Error::UnboundBuffer(key.0, key.1))?;
+        *words.get_mut((offset / 4) as usize).ok_or(Error::OutOfBounds)? = word;
+        Ok(())
+    }
+
+    fn load(&mut self, pointer: &Pointer) -> Result<Value> {
+        match *pointer {
+            Pointer::Variable(var, ref path) => {
+                place(&mut self.memory[var], path).map(|v| v.clone())
+            }
+            Pointer::Buffer(key, offset, ty) => self.load_buffer(key, offset, ty),
+        }
+    }
+
+    fn store(&mut self, pointer: &Pointer, value: Value) -> Result<()> {
+        match *pointer {
+            Pointer::Variable(var, ref path) => {
+                *place(&mut self.memory[var], path)? = value;
+                Ok(())
+            }
+            Pointer::Buffer(key, offset, ty) => self.store_buffer(key, offset, ty, value),
+        }
+    }
+
+    fn access(&self, base: Pointer, indices: Vec<u32>) -> Result<Pointer> {
+        match base {
+            Pointer::Variable(var, mut path) => {
+                path.extend(indices);
+                Ok(Pointer::Variable(var, path))
+            }
+            Pointer::Buffer(key, mut offset, mut ty) => {
+                for index in indices {
+                    let (element_offset, element) = self.buffer_element(ty, index)?;
+                    offset = offset.wrapping_add(element_offset);
+                    ty = element;
+                }
+                Ok(Pointer::Buffer(key, offset, ty))
+            }
+        }
+    }
+
+    /// Executes the instruction `inst` with the given `pointer` operand.
+    fn atomic(&mut self,
+              inst: &mr::Instruction,
+              pointer: &Pointer,
+              value: Option<Value>)
+              -> Result<Value> {
+        let opcode = inst.class.opcode;
+        let old = self.load(pointer)?;
+        let new = match (opcode, value) {
+            (spirv::Op::AtomicLoad, _) => return Ok(old),
+            (spirv::Op::AtomicStore, Some(v)) |
+            (spirv::Op::AtomicExchange, Some(v)) => v,
+            (spirv::Op::AtomicIIncrement, _) => {
+                ops::binary(spirv::Op::IAdd, old.clone(), Value::Int(1))?
+            }
+            (spirv::Op::AtomicIDecrement, _) => {
+                ops::binary(spirv::Op::ISub, old.clone(), Value::Int(1))?
+            }
+            (spirv::Op::AtomicIAdd, Some(v)) => ops::binary(spirv::Op::IAdd, old.clone(), v)?,
+            (spirv::Op::AtomicISub, Some(v)) => ops::binary(spirv::Op::ISub, old.clone(), v)?,
+            (spirv::Op::AtomicAnd, Some(v)) => ops::binary(spirv::Op::BitwiseAnd, old.clone(), v)?,
+            (spirv::Op::AtomicOr, Some(v)) => ops::binary(spirv::Op::BitwiseOr, old.clone(), v)?,
+            (spirv::Op::AtomicXor, Some(v)) => ops::binary(spirv::Op::BitwiseXor, old.clone(), v)?,
+            _ => return Err(Error::TypeMismatch(opcode)),
+        };
+        self.store(pointer, new)?;
+        Ok(old)
+    }
+
+    fn call(&mut self, function: &'m mr::Function, args: Vec<Value>) -> Result<Option<Value>> {
+        let mut locals: HashMap<Word, Value> = HashMap::new();
+        for (param, arg) in function.parameters.iter().zip(args) {
+            locals.insert(param.result_id.unwrap_or(0), arg);
+        }
+        let labels: HashMap<Word, usize> = function.basic_blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, block)| {
+                block.label.as_ref().and_then(|l| l.result_id).map(|id| (id, i))
+            })
+            .collect();
+
+        let (mut block, mut previous) = (0, None);
+        loop {
+            let mut next = None;
+            let insts = &function.basic_blocks[block].instructions;
+            // The phis of a block take effect together, so they all read the
+            // values from before the block is entered.
+            let phis = insts.iter().take_while(|inst| inst.class.opcode == spirv::Op::Phi).count();
+            let mut incoming = Vec::with_capacity(phis);
+            for inst in &insts[..phis] {
+                self.steps += 1;
+                if self.steps > self.step_limit {
+                    return Err(Error::StepLimit);
+                }
+                incoming.push((inst.result_id, phi(&locals, &self.globals, inst, previous)?));
+            }
+            for (result_id, value) in incoming {
+                if let Some(id) = result_id {
+                    locals.insert(id, value);
+                }
+            }
+            for inst in &insts[phis..] {
+                self.steps += 1;
+                if self.steps > self.step_limit {
+                    return Err(Error::StepLimit);
+                }
+                macro_rules! get {
+                    ($index:expr) => (lookup(&locals, &self.globals, &inst.operands[$index])?)
+                }
+                let opcode = inst.class.opcode;
+                let value = match opcode {
+                    spirv::Op::Nop |
+                    spirv::Op::Line |
+                    spirv::Op::NoLine |
+                    spirv::Op::SelectionMerge |
+                    spirv::Op::LoopMerge |
+                    spirv::Op::MemoryBarrier => continue,
+                    spirv::Op::Return => return Ok(None),
+                    spirv::Op::ReturnValue => return Ok(Some(get!(0))),
+                    spirv::Op::Unreachable => return Err(Error::Unreachable),
+                    spirv::Op::Branch => {
+                        next = Some(id(&inst.operands[0])?);
+                        break;
+                    }
+                    spirv::Op::BranchConditional => {
+                        let target = match get!(0) {
+                            Value::Bool(true) => 1,
+                            Value::Bool(false) => 2,
+                            _ => return Err(Error::TypeMismatch(opcode)),
+                        };
+                        next = Some(id(&inst.operands[target])?);
+                        break;
+                    }
+                    spirv::Op::Switch => {
+                        let selector = int(get!(0), opcode)?;
+                        let mut target = id(&inst.operands[1])?;
+                        for case in inst.operands[2..].chunks(2) {
+                            if case.len() == 2 && literal(&case[0])? == selector {
+                                target = id(&case[1])?;
+                                break;
+                            }
+                        }
+                        next = Some(target);
+                        break;
+                    }
+                    spirv::Op::Variable => Some(self.allocate(inst, &locals)?),
+                    spirv::Op::Undef => Some(self.zero(inst.result_type.unwrap_or(0))?),
+                    spirv::Op::Load => {
+                        let p = pointer(get!(0), opcode)?;
+                        Some(self.load(&p)?)
+                    }
+                    spirv::Op::Store => {
+                        let (p, value) = (pointer(get!(0), opcode)?, get!(1));
+                        self.store(&p, value)?;
+                        continue;
+                    }
+                    spirv::Op::CopyMemory => {
+                        let target = pointer(get!(0), opcode)?;
+                        let source = pointer(get!(1), opcode)?;
+                        let value = self.load(&source)?;
+                        self.store(&target, value)?;
+                        continue;
+                    }
+                    spirv::Op::AccessChain | spirv::Op::InBoundsAccessChain => {
+                        let base = pointer(get!(0), opcode)?;
+                        let mut indices = vec![];
+                        for i in 1..inst.operands.len() {
+                            indices.push(int(get!(i), opcode)?);
+                        }
+                        Some(Value::Pointer(self.access(base, indices)?))
+                    }
+                    spirv::Op::ArrayLength => {
+                        let member = literal(&inst.operands[1])?;
+                        match pointer(get!(0), opcode)? {
+                            Pointer::Buffer(key, offset, ty) => {
+                                let (member_offset, array) = self.buffer_element(ty, member)?;
+                                let (stride, _) = self.buffer_element(array, 1)?;
+                                let bytes = (self.buffer(key)?.len() as u32).wrapping_mul(4);
+                                let start = offset.wrapping_add(member_offset);
+                                Some(Value::Int(bytes.saturating_sub(start) / stride.max(1)))
+                            }
+                            _ => return Err(Error::TypeMismatch(opcode)),
+                        }
+                    }
+                    spirv::Op::AtomicLoad |
+                    spirv::Op::AtomicIIncrement |
+                    spirv::Op::AtomicIDecrement => {
+                        let p = pointer(get!(0), opcode)?;
+                        Some(self.atomic(inst, &p, None)?)
+                    }
+                    spirv::Op::AtomicStore => {
+                        let (p, value) = (pointer(get!(0), opcode)?, get!(3));
+                        self.atomic(inst, &p, Some(value))?;
+                        continue;
+                    }
+                    spirv::Op::AtomicExchange |
+                    spirv::Op::AtomicIAdd |
+                    spirv::Op::AtomicISub |
+                    spirv::Op::AtomicAnd |
+                    spirv::Op::AtomicOr |
+                    spirv::Op::AtomicXor => {
+                        let (p, value) = (pointer(get!(0), opcode)?, get!(3));
+                        Some(self.atomic(inst, &p, Some(value))?)
+                    }
+                    spirv::Op::FunctionCall => {
+                        let callee = id(&inst.operands[0])?;
+                        let callee =
+                            *self.functions.get(&callee).ok_or(Error::UndefinedId(callee))?;
+                        let mut args = vec![];
+                        for i in 1..inst.operands.len() {
+                            args.push(get!(i));
+                        }
+                        self.call(callee, args)?
+                    }
+                    spirv::Op::CopyObject => Some(get!(0)),
+                    spirv::Op::Bitcast => {
+                        let mut ty = self.def(inst.result_type.unwrap_or(0))?;
+                        if ty.class.opcode == spirv::Op::TypeVector {
+                            ty = self.def(id(&ty.operands[0])?)?;
+                        }
+                        Some(bitcast(get!(0), ty.class.opcode == spirv::Op::TypeFloat))
+                    }
+                    spirv::Op::CompositeConstruct => {
+                        let mut constituents = vec![];
+                        for i in 0..inst.operands.len() {
+                            constituents.push(get!(i));
+                        }
+                        // Vectors may be constructed from smaller vectors.
+                        let is_vector = self.def(inst.result_type.unwrap_or(0))?.class.opcode ==
+                                        spirv::Op::TypeVector;
+                        if is_vector {
+                            constituents = constituents.into_iter()
+                                .flat_map(|c| match c {
+                                    Value::Composite(cs) => cs,
+                                    c => vec![c],
+                                })
+                                .collect();
+                        }
+                        Some(Value::Composite(constituents))
+                    }
+                    spirv::Op::CompositeExtract => {
+                        let mut composite = get!(0);
+                        let mut path = vec![];
+                        for index in &inst.operands[1..] {
+                            path.push(literal(index)?);
+                        }
+                        Some(place(&mut composite, &path)?.clone())
+                    }
+                    spirv::Op::CompositeInsert => {
+                        let (object, mut composite) = (get!(0), get!(1));
+                        let mut path = vec![];
+                        for index in &inst.operands[2..] {
+                            path.push(literal(index)?);
+                        }
+                        *place(&mut composite, &path)? = object;
+                        Some(composite)
+                    }
+                    spirv::Op::VectorExtractDynamic => {
+                        let (mut vector, index) = (get!(0), int(get!(1), opcode)?);
+                        Some(place(&mut vector, &[index])?.clone())
+                    }
+                    spirv::Op::VectorInsertDynamic => {
+                        let (mut vector, component) = (get!(0), get!(1));
+                        let index = int(get!(2), opcode)?;
+                        *place(&mut vector, &[index])? = component;
+                        Some(vector)
+                    }
+                    spirv::Op::VectorShuffle => {
+                        let components = match (get!(0), get!(1)) {
+                            (Value::Composite(mut xs), Value::Composite(ys)) => {
+                                xs.extend(ys);
+                                xs
+                            }
+                            _ => return Err(Error::TypeMismatch(opcode)),
+                        };
+                        let mut result = vec![];
+                        for index in &inst.operands[2..] {
+                            // 0xFFFFFFFF selects an undefined component.
+                            let index = literal(index)? as usize;
+                            let index = if index == 0xFFFF_FFFF { 0 } else { index };
+                            result.push(components.get(index).cloned().ok_or(Error::OutOfBounds)?);
+                        }
+                        Some(Value::Composite(result))
+                    }
+                    spirv::Op::VectorTimesScalar => {
+                        let (vector, scalar) = (get!(0), get!(1));
+                        match vector {
+                            Value::Composite(xs) => {
+                                let xs: Result<_> = xs.into_iter()
+                                    .map(|x| ops::binary(spirv::Op::FMul, x, scalar.clone()))
+                                    .collect();
+                                Some(Value::Composite(xs?))
+                            }
+                            _ => return Err(Error::TypeMismatch(opcode)),
+                        }
+                    }
+                    spirv::Op::Dot => {
+                        match ops::binary(spirv::Op::FMul, get!(0), get!(1))? {
+                            Value::Composite(xs) => {
+                                let mut sum = Value::Float(0.0);
+                                for x in xs {
+                                    sum = ops::binary(spirv::Op::FAdd, sum, x)?;
+                                }
+                                Some(sum)
+                            }
+                            _ => return Err(Error::TypeMismatch(opcode)),
+                        }
+                    }
+                    spirv::Op::Any | spirv::Op::All => {
+                        match get!(0) {
+                            Value::Composite(xs) => {
+                                let bools: Vec<_> =
+                                    xs.iter().map(|x| *x == Value::Bool(true)).collect();
+                                Some(Value::Bool(if opcode == spirv::Op::Any {
+                                    bools.iter().any(|&b| b)
+                                } else {
+                                    bools.iter().all(|&b| b)
+                                }))
+                            }
+                            _ => return Err(Error::TypeMismatch(opcode)),
+                        }
+                    }
+                    spirv::Op::Select => {
+                        match (get!(0), get!(1), get!(2)) {
+                            (Value::Bool(c), x, y) => Some(if c { x } else { y }),
+                            (Value::Composite(cs), Value::Composite(xs), Value::Composite(ys)) => {
+                                let zs = cs.into_iter()
+                                    .zip(xs.into_iter().zip(ys))
+                                    .map(|(c, (x, y))| if c == Value::Bool(true) { x } else { y })
+                                    .collect();
+                                Some(Value::Composite(zs))
+                            }
+                            _ => return Err(Error::TypeMismatch(opcode)),
+                        }
+                    }
+                    op if ops::is_unary(op) => Some(ops::unary(op, get!(0))?),
+                    op if ops::is_binary(op) => Some(ops::binary(op, get!(0), get!(1))?),
+                    op => return Err(Error::Unsupported(op)),
+                };
+                if let (Some(value), Some(id)) = (value, inst.result_id) {
+                    locals.insert(id, value);
+                }
+            }
+            let target = next.ok_or(Error::Unreachable)?;
+            previous = function.basic_blocks[block].label.as_ref().and_then(|l| l.result_id);
+            block = *labels.get(&target).ok_or(Error::UndefinedId(target))?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{Error, Interpreter};
+
+    /// Common declarations of the test kernels.
+    struct Kernel {
+        b: mr::Builder,
+        uint: spirv::Word,
+        boolean: spirv::Word,
+        uint_uniform: spirv::Word,
+        buffer: spirv::Word,
+        gid: spirv::Word,
+    }
+
+    /// Starts a kernel over a buffer of unsigned integers at set 0 and
+    /// binding 0, with a function body started.
+    fn kernel(local_size: u32) -> Kernel {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let boolean = b.type_bool();
+        let uvec3 = b.type_vector(uint, 3);
+        let array = b.type_runtime_array(uint);
+        let block = b.type_struct(vec![array]);
+        let block_uniform = b.type_pointer(None, spirv::StorageClass::Uniform, block);
+        let uint_uniform = b.type_pointer(None, spirv::StorageClass::Uniform, uint);
+        let uvec3_input = b.type_pointer(None, spirv::StorageClass::Input, uvec3);
+        b.decorate(array, spirv::Decoration::ArrayStride, vec![mr::Operand::LiteralInt32(4)]);
+        b.decorate(block, spirv::Decoration::BufferBlock, vec![]);
+        b.member_decorate(block, 0, spirv::Decoration::Offset, vec![mr::Operand::LiteralInt32(0)]);
+        let buffer = b.variable(block_uniform, None, spirv::StorageClass::Uniform, None);
+        b.decorate(buffer, spirv::Decoration::DescriptorSet, vec![mr::Operand::LiteralInt32(0)]);
+        b.decorate(buffer, spirv::Decoration::Binding, vec![mr::Operand::LiteralInt32(0)]);
+        let gid = b.variable(uvec3_input, None, spirv::StorageClass::Input, None);
+        b.decorate(gid, spirv::Decoration::BuiltIn,
+                   vec![mr::Operand::BuiltIn(spirv::BuiltIn::GlobalInvocationId)]);
+
+        let voidf = b.type_function(void, vec![]);
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.entry_point(spirv::ExecutionModel::GLCompute, f, "main", vec![gid]);
+        b.execution_mode(f, spirv::ExecutionMode::LocalSize, vec![local_size, 1, 1]);
+        b.begin_basic_block(None).unwrap();
+        Kernel {
+            b,
+            uint,
+            boolean,
+            uint_uniform,
+            buffer,
+            gid,
+        }
+    }
+
+    impl Kernel {
+        /// Returns a pointer to the buffer element at the invocation id.
+        fn element(&mut self) -> spirv::Word {
+            let uvec3 = self.b.type_vector(self.uint, 3);
+            let gid = self.b.load(uvec3, None, self.gid, None, vec![]).unwrap();
+            let x = self.b.composite_extract(self.uint, None, gid, vec![0]).unwrap();
+            let zero = self.b.constant_u32(self.uint, 0);
+            self.b.access_chain(self.uint_uniform, None, self.buffer, vec![zero, x]).unwrap()
+        }
+
+        fn finish(mut self) -> mr::Module {
+            self.b.ret().unwrap();
+            self.b.end_function().unwrap();
+            self.b.module()
+        }
+    }
+
+    #[test]
+    fn test_double_buffer() {
+        for &(local_size, groups) in &[(1, 6), (2, 3), (6, 1)] {
+            let mut k = kernel(local_size);
+            let p = k.element();
+            let v = k.b.load(k.uint, None, p, None, vec![]).unwrap();
+            let twice = k.b.iadd(k.uint, None, v, v).unwrap();
+            k.b.store(p, twice, None, vec![]).unwrap();
+            let module = k.finish();
+
+            let mut interp = Interpreter::new(&module);
+            interp.bind_buffer(0, 0, vec![1, 2, 3, 4, 5, 6]);
+            interp.run("main", [groups, 1, 1]).unwrap();
+            assert_eq!(interp.buffer(0, 0), Some(&[2, 4, 6, 8, 10, 12][..]));
+        }
+    }
+
+    #[test]
+    fn test_loop_with_phi() {
+        // Stores the sum of 0..element to the element.
+        let mut k = kernel(1);
+        let p = k.element();
+        let n = k.b.load(k.uint, None, p, None, vec![]).unwrap();
+        let (zero, one) = (k.b.constant_u32(k.uint, 0), k.b.constant_u32(k.uint, 1));
+        let (header, body, merge) = (k.b.id(), k.b.id(), k.b.id());
+        let (i, sum, next_i, next_sum) = (k.b.id(), k.b.id(), k.b.id(), k.b.id());
+        let start = k.b.id();
+        k.b.branch(start).unwrap();
+        k.b.begin_basic_block(Some(start)).unwrap();
+        k.b.branch(header).unwrap();
+        k.b.begin_basic_block(Some(header)).unwrap();
+        k.b.phi(k.uint, Some(i), vec![(zero, start), (next_i, body)]).unwrap();
+        k.b.phi(k.uint, Some(sum), vec![(zero, start), (next_sum, body)]).unwrap();
+        let cond = k.b.uless_than(k.boolean, None, i, n).unwrap();
+        k.b.loop_merge(merge, body, spirv::LoopControl::NONE, vec![]).unwrap();
+        k.b.branch_conditional(cond, body, merge, vec![]).unwrap();
+        k.b.begin_basic_block(Some(body)).unwrap();
+        k.b.iadd(k.uint, Some(next_sum), sum, i).unwrap();
+        k.b.iadd(k.uint, Some(next_i), i, one).unwrap();
+        k.b.branch(header).unwrap();
+        k.b.begin_basic_block(Some(merge)).unwrap();
+        k.b.store(p, sum, None, vec![]).unwrap();
+        let module = k.finish();
+
+        let mut interp = Interpreter::new(&module);
+        interp.bind_buffer(0, 0, vec![0, 1, 4, 10]);
+        interp.run("main", [4, 1, 1]).unwrap();
+        assert_eq!(interp.buffer(0, 0), Some(&[0, 0, 6, 45][..]));
+    }
+
+    #[test]
+    fn test_swapping_phis() {
+        // Swaps a and b element times, and stores a.
+        let mut k = kernel(1);
+        let p = k.element();
+        let n = k.b.load(k.uint, None, p, None, vec![]).unwrap();
+        let (zero, one) = (k.b.constant_u32(k.uint, 0), k.b.constant_u32(k.uint, 1));
+        let two = k.b.constant_u32(k.uint, 2);
+        let (header, body, merge) = (k.b.id(), k.b.id(), k.b.id());
+        let (i, a, b, next_i) = (k.b.id(), k.b.id(), k.b.id(), k.b.id());
+        let start = k.b.id();
+        k.b.branch(start).unwrap();
+        k.b.begin_basic_block(Some(start)).unwrap();
+        k.b.branch(header).unwrap();
+        k.b.begin_basic_block(Some(header)).unwrap();
+        k.b.phi(k.uint, Some(i), vec![(zero, start), (next_i, body)]).unwrap();
+        k.b.phi(k.uint, Some(a), vec![(one, start), (b, body)]).unwrap();
+        k.b.phi(k.uint, Some(b), vec![(two, start), (a, body)]).unwrap();
+        let cond = k.b.uless_than(k.boolean, None, i, n).unwrap();
+        k.b.loop_merge(merge, body, spirv::LoopControl::NONE, vec![]).unwrap();
+        k.b.branch_conditional(cond, body, merge, vec![]).unwrap();
+        k.b.begin_basic_block(Some(body)).unwrap();
+        k.b.iadd(k.uint, Some(next_i), i, one).unwrap();
+        k.b.branch(header).unwrap();
+        k.b.begin_basic_block(Some(merge)).unwrap();
+        k.b.store(p, a, None, vec![]).unwrap();
+        let module = k.finish();
+
+        let mut interp = Interpreter::new(&module);
+        interp.bind_buffer(0, 0, vec![0, 1, 2, 3]);
+        interp.run("main", [4, 1, 1]).unwrap();
+        assert_eq!(interp.buffer(0, 0), Some(&[1, 2, 1, 2][..]));
+    }
+
+    #[test]
+    fn test_function_call_and_switch() {
+        let mut k = kernel(1);
+        // uint classify(uint v) {
+        //     switch (v) { case 1: return 10; case 2: return 20; default: return v; }
+        // }
+        let uintf = k.b.type_function(k.uint, vec![k.uint]);
+        let (ten, twenty) = (k.b.constant_u32(k.uint, 10), k.b.constant_u32(k.uint, 20));
+        let p = k.element();
+        let v = k.b.load(k.uint, None, p, None, vec![]).unwrap();
+        let classify = k.b.id();
+        let r = k.b.function_call(k.uint, None, classify, vec![v]).unwrap();
+        k.b.store(p, r, None, vec![]).unwrap();
+        k.b.ret().unwrap();
+        k.b.end_function().unwrap();
+
+        k.b.begin_function(k.uint, Some(classify), spirv::FunctionControl::NONE, uintf).unwrap();
+        let param = k.b.function_parameter(k.uint).unwrap();
+        k.b.begin_basic_block(None).unwrap();
+        let (one, two, default) = (k.b.id(), k.b.id(), k.b.id());
+        let merge = k.b.id();
+        k.b.selection_merge(merge, spirv::SelectionControl::NONE).unwrap();
+        k.b.switch(param, default, vec![(1, one), (2, two)]).unwrap();
+        for &(block, value) in &[(one, ten), (two, twenty), (default, param)] {
+            k.b.begin_basic_block(Some(block)).unwrap();
+            k.b.ret_value(value).unwrap();
+        }
+        k.b.begin_basic_block(Some(merge)).unwrap();
+        k.b.unreachable().unwrap();
+        k.b.end_function().unwrap();
+        let module = k.b.module();
+
+        let mut interp = Interpreter::new(&module);
+        interp.bind_buffer(0, 0, vec![1, 2, 3]);
+        interp.run("main", [3, 1, 1]).unwrap();
+        assert_eq!(interp.buffer(0, 0), Some(&[10, 20, 3][..]));
+    }
+
+    #[test]
+    fn test_specialization() {
+        let mut k = kernel(1);
+        let scale = k.b.spec_constant_u32(k.uint, 3);
+        k.b.decorate(scale, spirv::Decoration::SpecId, vec![mr::Operand::LiteralInt32(7)]);
+        let p = k.element();
+        let v = k.b.load(k.uint, None, p, None, vec![]).unwrap();
+        let scaled = k.b.imul(k.uint, None, v, scale).unwrap();
+        k.b.store(p, scaled, None, vec![]).unwrap();
+        let module = k.finish();
+
+        let mut interp = Interpreter::new(&module);
+        interp.bind_buffer(0, 0, vec![5]);
+        interp.run("main", [1, 1, 1]).unwrap();
+        assert_eq!(interp.buffer(0, 0), Some(&[15][..]));
+        interp.specialize(7, 2);
+        interp.run("main", [1, 1, 1]).unwrap();
+        assert_eq!(interp.buffer(0, 0), Some(&[30][..]));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut k = kernel(1);
+        let p = k.element();
+        let v = k.b.load(k.uint, None, p, None, vec![]).unwrap();
+        let zero = k.b.constant_u32(k.uint, 0);
+        let q = k.b.udiv(k.uint, None, v, zero).unwrap();
+        k.b.store(p, q, None, vec![]).unwrap();
+        let module = k.finish();
+
+        let mut interp = Interpreter::new(&module);
+        assert_eq!(interp.run("other", [1, 1, 1]),
+                   Err(Error::MissingEntryPoint("other".to_owned())));
+        assert_eq!(interp.run("main", [1, 1, 1]), Err(Error::UnboundBuffer(0, 0)));
+        interp.bind_buffer(0, 0, vec![]);
+        assert_eq!(interp.run("main", [1, 1, 1]), Err(Error::OutOfBounds));
+        interp.bind_buffer(0, 0, vec![1]);
+        assert_eq!(interp.run("main", [1, 1, 1]), Err(Error::DivisionByZero));
+    }
+
+    #[test]
+    fn test_step_limit() {
+        let mut k = kernel(1);
+        let header = k.b.id();
+        k.b.branch(header).unwrap();
+        k.b.begin_basic_block(Some(header)).unwrap();
+        k.b.branch(header).unwrap();
+        let merge = k.b.id();
+        k.b.begin_basic_block(Some(merge)).unwrap();
+        let module = k.finish();
+
+        let mut interp = Interpreter::new(&module);
+        interp.set_step_limit(1000);
+        assert_eq!(interp.run("main", [1, 1, 1]), Err(Error::StepLimit));
+    }
+
+    #[test]
+    fn test_struct_layout() {
+        // struct { float a; vec2 b; } at offsets 0 and 8; b = b * a.
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let uint = b.type_int(32, 0);
+        let vec2 = b.type_vector(float, 2);
+        let block = b.type_struct(vec![float, vec2]);
+        b.decorate(block, spirv::Decoration::Block, vec![]);
+        b.member_decorate(block, 0, spirv::Decoration::Offset, vec![mr::Operand::LiteralInt32(0)]);
+        b.member_decorate(block, 1, spirv::Decoration::Offset, vec![mr::Operand::LiteralInt32(8)]);
+        let block_storage = b.type_pointer(None, spirv::StorageClass::StorageBuffer, block);
+        let vec2_storage = b.type_pointer(None, spirv::StorageClass::StorageBuffer, vec2);
+        let buffer = b.variable(block_storage, None, spirv::StorageClass::StorageBuffer, None);
+        b.decorate(buffer, spirv::Decoration::DescriptorSet, vec![mr::Operand::LiteralInt32(1)]);
+        b.decorate(buffer, spirv::Decoration::Binding, vec![mr::Operand::LiteralInt32(2)]);
+        let voidf = b.type_function(void, vec![]);
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.entry_point(spirv::ExecutionModel::GLCompute, f, "main", vec![]);
+        b.begin_basic_block(None).unwrap();
+        let whole = b.load(block, None, buffer, None, vec![]).unwrap();
+        let a = b.composite_extract(float, None, whole, vec![0]).unwrap();
+        let v = b.composite_extract(vec2, None, whole, vec![1]).unwrap();
+        let scaled = b.vector_times_scalar(vec2, None, v, a).unwrap();
+        let one = b.constant_u32(uint, 1);
+        let p = b.access_chain(vec2_storage, None, buffer, vec![one]).unwrap();
+        b.store(p, scaled, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let module = b.module();
+
+        let bits = |v: f32| v.to_bits();
+        let mut interp = Interpreter::new(&module);
+        interp.bind_buffer(1, 2, vec![bits(2.0), 7, bits(1.5), bits(-3.0)]);
+        interp.run("main", [1, 1, 1]).unwrap();
+        assert_eq!(interp.buffer(1, 2), Some(&[bits(2.0), 7, bits(3.0), bits(-6.0)][..]));
+    }
+}
@@ -0,0 +1,72 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A reference interpreter for a subset of compute shaders.
+//!
+//! The [`Interpreter`](struct.Interpreter.html) runs compute entry points of
+//! a [`mr::Module`](../mr/struct.Module.html) against buffer memory provided
+//! by the caller. It is meant for checking transforms for semantic
+//! equivalence and for unit testing kernels without a GPU, not for speed.
+//!
+//! # Examples
+//!
+//! ```
+//! extern crate rspirv;
+//! extern crate spirv_headers as spirv;
+//!
+//! use rspirv::interp::Interpreter;
+//! use rspirv::mr::Operand;
+//!
+//! fn main() {
+//!     // buffer[0] = buffer[0] + buffer[1]
+//!     let mut b = rspirv::mr::Builder::new();
+//!     b.capability(spirv::Capability::Shader);
+//!     b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+//!     let void = b.type_void();
+//!     let uint = b.type_int(32, 0);
+//!     let block = b.type_struct(vec![uint, uint]);
+//!     b.decorate(block, spirv::Decoration::Block, vec![]);
+//!     b.member_decorate(block, 0, spirv::Decoration::Offset, vec![Operand::LiteralInt32(0)]);
+//!     b.member_decorate(block, 1, spirv::Decoration::Offset, vec![Operand::LiteralInt32(4)]);
+//!     let block_ptr = b.type_pointer(None, spirv::StorageClass::StorageBuffer, block);
+//!     let uint_ptr = b.type_pointer(None, spirv::StorageClass::StorageBuffer, uint);
+//!     let buffer = b.variable(block_ptr, None, spirv::StorageClass::StorageBuffer, None);
+//!     b.decorate(buffer, spirv::Decoration::DescriptorSet, vec![Operand::LiteralInt32(0)]);
+//!     b.decorate(buffer, spirv::Decoration::Binding, vec![Operand::LiteralInt32(0)]);
+//!     let (zero, one) = (b.constant_u32(uint, 0), b.constant_u32(uint, 1));
+//!     let voidf = b.type_function(void, vec![]);
+//!     let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+//!     b.entry_point(spirv::ExecutionModel::GLCompute, f, "main", vec![]);
+//!     b.begin_basic_block(None).unwrap();
+//!     let (p, q) = (b.access_chain(uint_ptr, None, buffer, vec![zero]).unwrap(),
+//!                   b.access_chain(uint_ptr, None, buffer, vec![one]).unwrap());
+//!     let (x, y) = (b.load(uint, None, p, None, vec![]).unwrap(),
+//!                   b.load(uint, None, q, None, vec![]).unwrap());
+//!     let sum = b.iadd(uint, None, x, y).unwrap();
+//!     b.store(p, sum, None, vec![]).unwrap();
+//!     b.ret().unwrap();
+//!     b.end_function().unwrap();
+//!     let module = b.module();
+//!
+//!     let mut interp = Interpreter::new(&module);
+//!     interp.bind_buffer(0, 0, vec![40, 2]);
+//!     interp.run("main", [1, 1, 1]).unwrap();
+//!     assert_eq!(interp.buffer(0, 0), Some(&[42, 2][..]));
+//! }
+//! ```
+
+pub use self::interpreter::{Error, Interpreter, Result};
+
+mod interpreter;
+mod ops;
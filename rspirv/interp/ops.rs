@@ -0,0 +1,225 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use spirv;
+
+use spirv::Word;
+use super::interpreter::{Error, Result};
+
+/// A value computed by the interpreter.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// A 32-bit integer, regardless of signedness.
+    Int(u32),
+    Float(f32),
+    Bool(bool),
+    /// A vector, array, or struct.
+    Composite(Vec<Value>),
+    Pointer(Pointer),
+}
+
+/// A pointer into interpreter memory or into a bound buffer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Pointer {
+    /// A variable and the indices into it.
+    Variable(usize, Vec<u32>),
+    /// A byte offset into the buffer at the given descriptor set and binding,
+    /// and the pointee type.
+    Buffer((u32, u32), u32, Word),
+}
+
+/// Applies the unary operation `opcode` componentwise to `x`.
+pub fn unary(opcode: spirv::Op, x: Value) -> Result<Value> {
+    use self::Value::{Bool, Composite, Float, Int};
+    Ok(match (opcode, x) {
+        (_, Composite(xs)) => {
+            let xs: Result<_> = xs.into_iter().map(|x| unary(opcode, x)).collect();
+            Composite(xs?)
+        }
+        (spirv::Op::SNegate, Int(x)) => Int(x.wrapping_neg()),
+        (spirv::Op::Not, Int(x)) => Int(!x),
+        (spirv::Op::FNegate, Float(x)) => Float(-x),
+        (spirv::Op::LogicalNot, Bool(x)) => Bool(!x),
+        (spirv::Op::IsNan, Float(x)) => Bool(x.is_nan()),
+        (spirv::Op::IsInf, Float(x)) => Bool(x.is_infinite()),
+        // Out-of-range conversions are undefined; saturate like Rust does.
+        (spirv::Op::ConvertFToU, Float(x)) => Int(x as u32),
+        (spirv::Op::ConvertFToS, Float(x)) => Int(x as i32 as u32),
+        (spirv::Op::ConvertUToF, Int(x)) => Float(x as f32),
+        (spirv::Op::ConvertSToF, Int(x)) => Float(x as i32 as f32),
+        (spirv::Op::SNegate, _) |
+        (spirv::Op::Not, _) |
+        (spirv::Op::FNegate, _) |
+        (spirv::Op::LogicalNot, _) |
+        (spirv::Op::IsNan, _) |
+        (spirv::Op::IsInf, _) |
+        (spirv::Op::ConvertFToU, _) |
+        (spirv::Op::ConvertFToS, _) |
+        (spirv::Op::ConvertUToF, _) |
+        (spirv::Op::ConvertSToF, _) => return Err(Error::TypeMismatch(opcode)),
+        (opcode, _) => return Err(Error::Unsupported(opcode)),
+    })
+}
+
+/// Returns true if `opcode` is a unary operation supported by
+/// [`unary`](fn.unary.html).
+pub fn is_unary(opcode: spirv::Op) -> bool {
+    matches!(opcode,
+             spirv::Op::SNegate | spirv::Op::Not | spirv::Op::FNegate | spirv::Op::LogicalNot |
+             spirv::Op::IsNan | spirv::Op::IsInf | spirv::Op::ConvertFToU |
+             spirv::Op::ConvertFToS | spirv::Op::ConvertUToF | spirv::Op::ConvertSToF)
+}
+
+/// Applies the binary operation `opcode` componentwise to `x` and `y`.
+pub fn binary(opcode: spirv::Op, x: Value, y: Value) -> Result<Value> {
+    use self::Value::{Bool, Composite, Float, Int};
+    let signed = |x: u32| x as i32;
+    let unordered = |x: f32, y: f32| x.is_nan() || y.is_nan();
+    Ok(match (opcode, x, y) {
+        (_, Composite(xs), Composite(ys)) => {
+            if xs.len() != ys.len() {
+                return Err(Error::TypeMismatch(opcode));
+            }
+            let zs: Result<_> = xs.into_iter().zip(ys).map(|(x, y)| binary(opcode, x, y)).collect();
+            Composite(zs?)
+        }
+        (spirv::Op::IAdd, Int(x), Int(y)) => Int(x.wrapping_add(y)),
+        (spirv::Op::ISub, Int(x), Int(y)) => Int(x.wrapping_sub(y)),
+        (spirv::Op::IMul, Int(x), Int(y)) => Int(x.wrapping_mul(y)),
+        (spirv::Op::UDiv, Int(_), Int(0)) |
+        (spirv::Op::SDiv, Int(_), Int(0)) |
+        (spirv::Op::UMod, Int(_), Int(0)) |
+        (spirv::Op::SRem, Int(_), Int(0)) |
+        (spirv::Op::SMod, Int(_), Int(0)) => return Err(Error::DivisionByZero),
+        (spirv::Op::UDiv, Int(x), Int(y)) => Int(x / y),
+        (spirv::Op::SDiv, Int(x), Int(y)) => Int(signed(x).wrapping_div(signed(y)) as u32),
+        (spirv::Op::UMod, Int(x), Int(y)) => Int(x % y),
+        (spirv::Op::SRem, Int(x), Int(y)) => Int(signed(x).wrapping_rem(signed(y)) as u32),
+        (spirv::Op::SMod, Int(x), Int(y)) => {
+            // The result takes the sign of the divisor.
+            let r = signed(x).wrapping_rem(signed(y));
+            Int(if r != 0 && (r < 0) != (signed(y) < 0) {
+                r.wrapping_add(signed(y)) as u32
+            } else {
+                r as u32
+            })
+        }
+        (spirv::Op::ShiftLeftLogical, Int(x), Int(y)) => Int(x.wrapping_shl(y)),
+        (spirv::Op::ShiftRightLogical, Int(x), Int(y)) => Int(x.wrapping_shr(y)),
+        (spirv::Op::ShiftRightArithmetic, Int(x), Int(y)) => Int(signed(x).wrapping_shr(y) as u32),
+        (spirv::Op::BitwiseAnd, Int(x), Int(y)) => Int(x & y),
+        (spirv::Op::BitwiseOr, Int(x), Int(y)) => Int(x | y),
+        (spirv::Op::BitwiseXor, Int(x), Int(y)) => Int(x ^ y),
+        (spirv::Op::IEqual, Int(x), Int(y)) => Bool(x == y),
+        (spirv::Op::INotEqual, Int(x), Int(y)) => Bool(x != y),
+        (spirv::Op::UGreaterThan, Int(x), Int(y)) => Bool(x > y),
+        (spirv::Op::SGreaterThan, Int(x), Int(y)) => Bool(signed(x) > signed(y)),
+        (spirv::Op::UGreaterThanEqual, Int(x), Int(y)) => Bool(x >= y),
+        (spirv::Op::SGreaterThanEqual, Int(x), Int(y)) => Bool(signed(x) >= signed(y)),
+        (spirv::Op::ULessThan, Int(x), Int(y)) => Bool(x < y),
+        (spirv::Op::SLessThan, Int(x), Int(y)) => Bool(signed(x) < signed(y)),
+        (spirv::Op::ULessThanEqual, Int(x), Int(y)) => Bool(x <= y),
+        (spirv::Op::SLessThanEqual, Int(x), Int(y)) => Bool(signed(x) <= signed(y)),
+        (spirv::Op::FAdd, Float(x), Float(y)) => Float(x + y),
+        (spirv::Op::FSub, Float(x), Float(y)) => Float(x - y),
+        (spirv::Op::FMul, Float(x), Float(y)) => Float(x * y),
+        (spirv::Op::FDiv, Float(x), Float(y)) => Float(x / y),
+        (spirv::Op::FRem, Float(x), Float(y)) => Float(x % y),
+        (spirv::Op::FMod, Float(x), Float(y)) => Float(x - y * (x / y).floor()),
+        (spirv::Op::FOrdEqual, Float(x), Float(y)) => Bool(x == y),
+        (spirv::Op::FOrdNotEqual, Float(x), Float(y)) => Bool(x != y && !unordered(x, y)),
+        (spirv::Op::FOrdLessThan, Float(x), Float(y)) => Bool(x < y),
+        (spirv::Op::FOrdGreaterThan, Float(x), Float(y)) => Bool(x > y),
+        (spirv::Op::FOrdLessThanEqual, Float(x), Float(y)) => Bool(x <= y),
+        (spirv::Op::FOrdGreaterThanEqual, Float(x), Float(y)) => Bool(x >= y),
+        (spirv::Op::FUnordEqual, Float(x), Float(y)) => Bool(x == y || unordered(x, y)),
+        (spirv::Op::FUnordNotEqual, Float(x), Float(y)) => Bool(x != y),
+        (spirv::Op::FUnordLessThan, Float(x), Float(y)) => Bool(x < y || unordered(x, y)),
+        (spirv::Op::FUnordGreaterThan, Float(x), Float(y)) => Bool(x > y || unordered(x, y)),
+        (spirv::Op::FUnordLessThanEqual, Float(x), Float(y)) => Bool(x <= y || unordered(x, y)),
+        (spirv::Op::FUnordGreaterThanEqual, Float(x), Float(y)) => Bool(x >= y || unordered(x, y)),
+        (spirv::Op::LogicalAnd, Bool(x), Bool(y)) => Bool(x && y),
+        (spirv::Op::LogicalOr, Bool(x), Bool(y)) => Bool(x || y),
+        (spirv::Op::LogicalEqual, Bool(x), Bool(y)) => Bool(x == y),
+        (spirv::Op::LogicalNotEqual, Bool(x), Bool(y)) => Bool(x != y),
+        (opcode, ..) if is_binary(opcode) => return Err(Error::TypeMismatch(opcode)),
+        (opcode, ..) => return Err(Error::Unsupported(opcode)),
+    })
+}
+
+/// Returns true if `opcode` is a binary operation supported by
+/// [`binary`](fn.binary.html).
+pub fn is_binary(opcode: spirv::Op) -> bool {
+    matches!(opcode,
+             spirv::Op::IAdd | spirv::Op::ISub | spirv::Op::IMul | spirv::Op::UDiv |
+             spirv::Op::SDiv | spirv::Op::UMod | spirv::Op::SRem | spirv::Op::SMod |
+             spirv::Op::ShiftLeftLogical | spirv::Op::ShiftRightLogical |
+             spirv::Op::ShiftRightArithmetic | spirv::Op::BitwiseAnd | spirv::Op::BitwiseOr |
+             spirv::Op::BitwiseXor | spirv::Op::IEqual | spirv::Op::INotEqual |
+             spirv::Op::UGreaterThan | spirv::Op::SGreaterThan | spirv::Op::UGreaterThanEqual |
+             spirv::Op::SGreaterThanEqual | spirv::Op::ULessThan | spirv::Op::SLessThan |
+             spirv::Op::ULessThanEqual | spirv::Op::SLessThanEqual | spirv::Op::FAdd |
+             spirv::Op::FSub | spirv::Op::FMul | spirv::Op::FDiv | spirv::Op::FRem |
+             spirv::Op::FMod | spirv::Op::FOrdEqual | spirv::Op::FOrdNotEqual |
+             spirv::Op::FOrdLessThan | spirv::Op::FOrdGreaterThan |
+             spirv::Op::FOrdLessThanEqual | spirv::Op::FOrdGreaterThanEqual |
+             spirv::Op::FUnordEqual | spirv::Op::FUnordNotEqual | spirv::Op::FUnordLessThan |
+             spirv::Op::FUnordGreaterThan | spirv::Op::FUnordLessThanEqual |
+             spirv::Op::FUnordGreaterThanEqual | spirv::Op::LogicalAnd | spirv::Op::LogicalOr |
+             spirv::Op::LogicalEqual | spirv::Op::LogicalNotEqual)
+}
+
+#[cfg(test)]
+mod tests {
+    use spirv;
+
+    use super::{binary, unary, Value};
+    use super::Value::{Bool, Composite, Float, Int};
+    use interp::Error;
+
+    #[test]
+    fn test_signed_division() {
+        let neg = |x: i32| Int(x as u32);
+        assert_eq!(binary(spirv::Op::SDiv, neg(-7), Int(2)), Ok(neg(-3)));
+        assert_eq!(binary(spirv::Op::SRem, neg(-7), Int(2)), Ok(neg(-1)));
+        assert_eq!(binary(spirv::Op::SMod, neg(-7), Int(2)), Ok(Int(1)));
+        assert_eq!(binary(spirv::Op::SMod, Int(7), neg(-2)), Ok(neg(-1)));
+        assert_eq!(binary(spirv::Op::UDiv, Int(7), Int(0)), Err(Error::DivisionByZero));
+    }
+
+    #[test]
+    fn test_componentwise() {
+        let v = |x: f32, y: f32| Composite(vec![Float(x), Float(y)]);
+        assert_eq!(binary(spirv::Op::FMul, v(1.5, 2.0), v(2.0, -1.0)), Ok(v(3.0, -2.0)));
+        assert_eq!(unary(spirv::Op::FNegate, v(1.0, 0.5)), Ok(v(-1.0, -0.5)));
+        assert_eq!(binary(spirv::Op::FOrdLessThan, v(1.0, 2.0), v(2.0, 1.0)),
+                   Ok(Composite(vec![Bool(true), Bool(false)])));
+    }
+
+    #[test]
+    fn test_unordered_comparisons() {
+        let nan = Float(f32::NAN);
+        assert_eq!(binary(spirv::Op::FOrdEqual, nan.clone(), nan.clone()), Ok(Bool(false)));
+        assert_eq!(binary(spirv::Op::FUnordEqual, nan.clone(), Float(1.0)), Ok(Bool(true)));
+        assert_eq!(binary(spirv::Op::FOrdNotEqual, nan, Float(1.0)), Ok(Bool(false)));
+    }
+
+    #[test]
+    fn test_type_mismatch() {
+        assert_eq!(binary(spirv::Op::IAdd, Int(1), Float(1.0)),
+                   Err(Error::TypeMismatch(spirv::Op::IAdd)));
+        assert_eq!(unary(spirv::Op::Nop, Value::Bool(true)),
+                   Err(Error::Unsupported(spirv::Op::Nop)));
+    }
+}
@@ -26,6 +26,32 @@
 //!   (under developing)
 //! * SPIR-V [binary](binary/index.html) module decoding and parsing
 //!   functionalities
+//! * [Analyses](analysis/index.html) over the data representation, e.g.,
+//!   image and sampler usage
+//! * [Transforms](passes/index.html) over the data representation, e.g.,
+//!   legalizing terminators for a target SPIR-V version
+//! * Shader interface [reflection](reflect/index.html), e.g., block layouts
+//!   and ray tracing pipeline interfaces
+//! * Source-level [debug information](debuginfo/index.html), e.g., mapping
+//!   instructions to source lines
+//! * Alternative [formats](formats/index.html) for SPIR-V modules, e.g.,
+//!   structured JSON dumps (behind the `json` feature)
+//! * [Reports](report/index.html) over modules, e.g., binary size by
+//!   category
+//! * A [symbol table](symbols/index.html) of the names in modules, with
+//!   consistent renaming
+//! * An [interpreter](interp/index.html) for a subset of compute shaders
+//! * Module [generation](testing/index.html) for differential testing of
+//!   transforms
+//! * A [C API](capi/index.html) for the parser and disassembler (behind the
+//!   `capi` feature)
+//! * Conversions between modules and Vulkan shader code (behind the `vulkan`
+//!   feature)
+//! * Struct types laid out like Rust structs, with
+//!   [`SpirvStruct`](mr/trait.SpirvStruct.html) (derivable behind the
+//!   `derive` feature)
+//! * A [bridge](interop/index.html) to other SPIR-V libraries, e.g., naga
+//!   (behind the `interop` feature)
 //!
 //! The data representation (DR) focuses on presenting the data within a
 //! SPIR-V module; it uses plain vectors to hold data of SPIR-V instructions,
@@ -39,6 +65,14 @@
 //! structs. The purpose of SR is to facilitate SPIR-V analysis and
 //! transformations.
 //!
+//! # `no_std` support
+//!
+//! With the default `std` feature disabled, this library builds as `no_std`
+//! with `alloc`, e.g., for `wasm32-unknown-unknown`. Only the
+//! [grammar](grammar/index.html), the [data representation](mr/index.html),
+//! and [binary](binary/index.html) encoding and parsing are available then;
+//! errors implement `core::error::Error`, which needs Rust 1.81 or later.
+//!
 //! # Examples
 //!
 //! Building a SPIR-V module, assembling it, parsing it, and then
@@ -94,20 +128,63 @@
 //! }
 //! ```
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![cfg_attr(feature = "clippy", feature(plugin))]
 #![cfg_attr(feature = "clippy", plugin(clippy))]
 
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
 #[cfg(test)]
 #[macro_use]
 extern crate assert_matches;
 #[macro_use]
 extern crate derive_more;
-extern crate num;
+extern crate num_traits;
+#[cfg(feature = "derive")]
+extern crate rspirv_derive;
+#[cfg(feature = "json")]
+#[macro_use]
+extern crate serde_json;
 extern crate spirv_headers as spirv;
 
+#[cfg(feature = "std")]
+pub mod analysis;
 pub mod binary;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "std")]
+pub mod debuginfo;
+#[cfg(feature = "std")]
+pub mod env;
+#[cfg(feature = "std")]
+pub mod formats;
 pub mod grammar;
+#[cfg(feature = "std")]
+pub mod interp;
+#[cfg(feature = "interop")]
+pub mod interop;
 pub mod mr;
+#[cfg(feature = "std")]
+pub mod passes;
+#[cfg(feature = "std")]
+pub mod reflect;
+#[cfg(feature = "std")]
+pub mod report;
+#[cfg(feature = "std")]
 pub mod sr;
+#[cfg(feature = "std")]
+pub mod symbols;
+#[cfg(feature = "std")]
+pub mod testing;
 
+mod prelude;
 mod utils;
+
+/// The parts of `std` used by the `no_std` modules, assembled from `core`
+/// and `alloc`, so that those modules can keep using `std::` paths.
+#[cfg(not(feature = "std"))]
+mod std {
+    pub use alloc::{borrow, boxed, collections, string, vec};
+    pub use core::{array, convert, error, fmt, iter, marker, mem, ops, result, slice, str};
+}
@@ -0,0 +1,395 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use binary;
+use grammar;
+use mr;
+use prelude::*;
+use spirv;
+
+use binary::{ParseAction, ParseResult};
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use utils::hash::{fnv_bytes, FNV_OFFSET_BASIS};
+
+/// Index of an instruction in an [`Arena`](struct.Arena.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InstId(u32);
+
+/// Index of an interned string in an [`Arena`](struct.Arena.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StrId(u32);
+
+/// An operand stored in an [`Arena`](struct.Arena.html).
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArenaOperand {
+    /// Any operand other than a literal string.
+    Value(mr::Operand),
+    /// An interned literal string.
+    String(StrId),
+}
+
+/// An instruction stored in an [`Arena`](struct.Arena.html).
+#[derive(Debug)]
+struct Inst {
+    class: &'static grammar::Instruction<'static>,
+    result_type: Option<spirv::Word>,
+    result_id: Option<spirv::Word>,
+    operands: Range<u32>,
+}
+
+/// A compact, append-only store for the instructions of a module.
+///
+/// Instead of one `Vec<Operand>` per instruction and one `String` per literal
+/// string as in [`Module`](struct.Module.html), an arena keeps all operands
+/// of all instructions in one vector, and all literal strings interned in one
+/// buffer. Instructions are kept in module order and referred to by
+/// [`InstId`](struct.InstId.html).
+///
+/// An arena implements [`Consumer`](../binary/trait.Consumer.html), so it can
+/// be filled by the [parser](../binary/struct.Parser.html) directly. It then
+/// interns literal strings as the parser decodes them, so that no `String` is
+/// allocated for them.
+///
+/// # Examples
+///
+/// ```
+/// extern crate rspirv;
+/// extern crate spirv_headers as spirv;
+///
+/// use rspirv::binary::Assemble;
+/// use rspirv::mr::{Arena, ArenaOperand};
+///
+/// fn main() {
+///     let mut b = rspirv::mr::Builder::new();
+///     b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+///     let void = b.type_void();
+///     b.name(void, "void");
+///     let arena = Arena::from_words(b.module().assemble()).unwrap();
+///
+///     let name = arena.iter().find(|&i| arena.class(i).opcode == spirv::Op::Name).unwrap();
+///     match arena.operands(name)[1] {
+///         ArenaOperand::String(s) => assert_eq!(arena.string(s), "void"),
+///         _ => unreachable!(),
+///     }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct Arena {
+    header: Option<mr::ModuleHeader>,
+    insts: Vec<Inst>,
+    operands: Vec<ArenaOperand>,
+    text: String,
+    strings: Vec<Range<u32>>,
+    /// The interned strings by the hash of their text, which is only kept
+    /// in `text`. Strings whose hash another string already has are in
+    /// `collisions`.
+    string_ids: BTreeMap<u64, StrId>,
+    collisions: Vec<StrId>,
+    /// The strings interned by the parser for the instruction it is
+    /// parsing, in the order of their operands.
+    parsed_strings: Vec<StrId>,
+}
+
+impl Arena {
+    /// Creates a new empty arena.
+    pub fn new() -> Arena {
+        Default::default()
+    }
+
+    /// Parses the SPIR-V `binary` into a new arena.
+    pub fn from_words<T: AsRef<[u32]>>(binary: T) -> ParseResult<Arena> {
+        let mut arena = Arena::new();
+        binary::parse_words(binary, &mut arena)?;
+        Ok(arena)
+    }
+
+    /// Returns the module header, if any.
+    pub fn header(&self) -> Option<&mr::ModuleHeader> {
+        self.header.as_ref()
+    }
+
+    /// Returns the number of instructions in this arena.
+    pub fn len(&self) -> usize {
+        self.insts.len()
+    }
+
+    /// Returns true if this arena contains no instructions.
+    pub fn is_empty(&self) -> bool {
+        self.insts.is_empty()
+    }
+
+    /// Returns the ids of all instructions in module order.
+    pub fn iter(&self) -> impl Iterator<Item = InstId> {
+        (0..self.insts.len() as u32).map(InstId)
+    }
+
+    /// Interns the given string `s` and returns its id.
+    pub fn intern(&mut self, s: &str) -> StrId {
+        let mut hash = FNV_OFFSET_BASIS;
+        fnv_bytes(&mut hash, s.as_bytes());
+        if let Some(id) = self.find(hash, s) {
+            return id;
+        }
+        let start = self.text.len() as u32;
+        self.text.push_str(s);
+        let id = StrId(self.strings.len() as u32);
+        self.strings.push(start..self.text.len() as u32);
+        match self.string_ids.entry(hash) {
+            Entry::Vacant(entry) => {
+                entry.insert(id);
+            }
+            Entry::Occupied(_) => self.collisions.push(id),
+        }
+        id
+    }
+
+    /// Returns the id of the interned string `s` hashing to `hash`.
+    fn find(&self, hash: u64, s: &str) -> Option<StrId> {
+        let &id = self.string_ids.get(&hash)?;
+        if self.string(id) == s {
+            return Some(id);
+        }
+        self.collisions.iter().cloned().find(|&id| self.string(id) == s)
+    }
+
+    /// Returns the interned string with the given `id`.
+    pub fn string(&self, id: StrId) -> &str {
+        let range = &self.strings[id.0 as usize];
+        &self.text[range.start as usize..range.end as usize]
+    }
+
+    /// Appends the given `inst` and returns its id.
+    pub fn push(&mut self, inst: mr::Instruction) -> InstId {
+        let start = self.operands.len() as u32;
+        // Strings interned while parsing stand for the operands holding them.
+        let mut parsed = 0;
+        for operand in inst.operands {
+            let operand = match operand {
+                mr::Operand::LiteralString(s) => {
+                    let id = match self.parsed_strings.get(parsed) {
+                        Some(&id) => id,
+                        None => self.intern(&s),
+                    };
+                    parsed += 1;
+                    ArenaOperand::String(id)
+                }
+                operand => ArenaOperand::Value(operand),
+            };
+            self.operands.push(operand);
+        }
+        self.parsed_strings.clear();
+        let id = InstId(self.insts.len() as u32);
+        self.insts.push(Inst {
+            class: inst.class,
+            result_type: inst.result_type,
+            result_id: inst.result_id,
+            operands: start..self.operands.len() as u32,
+        });
+        id
+    }
+
+    /// Returns the grammar of the instruction `id`.
+    pub fn class(&self, id: InstId) -> &'static grammar::Instruction<'static> {
+        self.insts[id.0 as usize].class
+    }
+
+    /// Returns the result type id of the instruction `id`, if any.
+    pub fn result_type(&self, id: InstId) -> Option<spirv::Word> {
+        self.insts[id.0 as usize].result_type
+    }
+
+    /// Returns the result id of the instruction `id`, if any.
+    pub fn result_id(&self, id: InstId) -> Option<spirv::Word> {
+        self.insts[id.0 as usize].result_id
+    }
+
+    /// Returns the operands of the instruction `id`.
+    pub fn operands(&self, id: InstId) -> &[ArenaOperand] {
+        let range = &self.insts[id.0 as usize].operands;
+        &self.operands[range.start as usize..range.end as usize]
+    }
+
+    /// Returns a standalone copy of the instruction `id`.
+    pub fn instruction(&self, id: InstId) -> mr::Instruction {
+        let inst = &self.insts[id.0 as usize];
+        let operands: mr::Operands = self.operands(id)
+            .iter()
+            .map(|operand| match *operand {
+                ArenaOperand::Value(ref v) => v.clone(),
+                ArenaOperand::String(s) => mr::Operand::LiteralString(self.string(s).to_owned()),
+            })
+            .collect();
+        mr::Instruction::new(inst.class.opcode, inst.result_type, inst.result_id, operands)
+    }
+
+    /// Converts the contents of this arena into a [`Module`](struct.Module.html).
+    pub fn to_module(&self) -> Result<mr::Module, binary::ParseState> {
+        use binary::Consumer;
+
+        let mut loader = mr::Loader::new();
+        let check = |action| match action {
+            ParseAction::Continue => Ok(()),
+            ParseAction::Stop => Err(binary::ParseState::ConsumerStopRequested),
+            ParseAction::Error(err) => Err(binary::ParseState::ConsumerError(err)),
+        };
+        if let Some(ref h) = self.header {
+            check(loader.consume_header(mr::ModuleHeader {
+                magic_number: h.magic_number,
+                version: h.version,
+                generator: h.generator,
+                bound: h.bound,
+                reserved_word: h.reserved_word,
+            }))?;
+        }
+        for id in self.iter() {
+            check(loader.consume_instruction(self.instruction(id)))?;
+        }
+        check(loader.finalize())?;
+        Ok(loader.module())
+    }
+}
+
+impl binary::Consumer for Arena {
+    fn initialize(&mut self) -> ParseAction {
+        ParseAction::Continue
+    }
+
+    fn finalize(&mut self) -> ParseAction {
+        ParseAction::Continue
+    }
+
+    fn consume_header(&mut self, header: mr::ModuleHeader) -> ParseAction {
+        self.header = Some(header);
+        ParseAction::Continue
+    }
+
+    fn consume_instruction(&mut self, inst: mr::Instruction) -> ParseAction {
+        self.push(inst);
+        ParseAction::Continue
+    }
+
+    fn consume_unknown_instruction(&mut self, _words: Vec<u32>) -> ParseAction {
+        // Drops the strings of an instruction that was not parsed after all.
+        self.parsed_strings.clear();
+        ParseAction::Continue
+    }
+
+    fn consume_string(&mut self, s: &str) -> String {
+        let id = self.intern(s);
+        self.parsed_strings.push(id);
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use binary::{Assemble, Disassemble};
+    use utils::hash::{fnv_bytes, FNV_OFFSET_BASIS};
+    use super::{Arena, ArenaOperand};
+
+    fn build() -> mr::Module {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let vec2 = b.type_vector(float, 2);
+        let s = b.type_struct(vec![vec2, vec2]);
+        b.member_name(s, 0, "x");
+        b.member_name(s, 1, "x");
+        let voidf = b.type_function(void, vec![]);
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.entry_point(spirv::ExecutionModel::GLCompute, f, "main", vec![]);
+        b.begin_basic_block(None).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.module()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let module = build();
+        let arena = Arena::from_words(module.assemble()).unwrap();
+        assert_eq!(arena.len(), 14);
+        assert_eq!(arena.to_module().unwrap().disassemble(), module.disassemble());
+    }
+
+    #[test]
+    fn test_interned_strings() {
+        let arena = Arena::from_words(build().assemble()).unwrap();
+        let strings: Vec<_> = arena.iter()
+            .filter(|&id| arena.class(id).opcode == spirv::Op::MemberName)
+            .map(|id| match arena.operands(id)[2] {
+                ArenaOperand::String(s) => s,
+                _ => panic!(),
+            })
+            .collect();
+        assert_eq!(strings.len(), 2);
+        assert_eq!(strings[0], strings[1]);
+        assert_eq!(arena.string(strings[0]), "x");
+    }
+
+    #[test]
+    fn test_intern() {
+        let mut arena = Arena::new();
+        let words: Vec<String> = (0..100).map(|i| format!("word{}", i)).collect();
+        let ids: Vec<_> = words.iter().map(|w| arena.intern(w)).collect();
+        assert_eq!(ids, words.iter().map(|w| arena.intern(w)).collect::<Vec<_>>());
+        for (id, word) in ids.iter().zip(&words) {
+            assert_eq!(arena.string(*id), word);
+        }
+        assert_eq!(arena.intern(""), arena.intern(""));
+        assert_ne!(arena.intern(""), ids[0]);
+        // Strings are only stored once, in the text.
+        assert_eq!(arena.text.len(), words.iter().map(String::len).sum::<usize>());
+    }
+
+    #[test]
+    fn test_intern_collisions() {
+        let mut arena = Arena::new();
+        let a = arena.intern("a");
+        // Make "b" collide with "a".
+        let mut hash = FNV_OFFSET_BASIS;
+        fnv_bytes(&mut hash, b"b");
+        arena.string_ids.insert(hash, a);
+
+        let b = arena.intern("b");
+        assert_ne!(a, b);
+        assert_eq!(arena.collisions, vec![b]);
+        assert_eq!(arena.intern("b"), b);
+        assert_eq!(arena.string(b), "b");
+    }
+
+    #[test]
+    fn test_flat_operands() {
+        let mut arena = Arena::new();
+        let a = arena.push(mr::Instruction::new(spirv::Op::TypeInt,
+                                                None,
+                                                Some(1),
+                                                vec![mr::Operand::LiteralInt32(32),
+                                                     mr::Operand::LiteralInt32(1)]));
+        let b = arena.push(mr::Instruction::new(spirv::Op::TypeVoid, None, Some(2), vec![]));
+        assert_eq!(arena.operands(a),
+                   &[ArenaOperand::Value(mr::Operand::LiteralInt32(32)),
+                     ArenaOperand::Value(mr::Operand::LiteralInt32(1))]);
+        assert!(arena.operands(b).is_empty());
+        assert_eq!(arena.result_id(b), Some(2));
+        assert_eq!(arena.instruction(a).operands.len(), 2);
+    }
+}
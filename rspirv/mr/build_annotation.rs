@@ -56,4 +56,18 @@ impl Builder {
         inst.operands.extend_from_slice(additional_params.as_ref());
         self.module.annotations.push(inst);
     }
+
+    /// Appends an OpDecorateString instruction.
+    pub fn decorate_string<T: AsRef<[mr::Operand]>>(&mut self, target: spirv::Word, decoration: spirv::Decoration, additional_params: T) {
+        let mut inst = mr::Instruction::new(spirv::Op::DecorateString, None, None, vec![mr::Operand::IdRef(target), mr::Operand::Decoration(decoration)]);
+        inst.operands.extend_from_slice(additional_params.as_ref());
+        self.module.annotations.push(inst);
+    }
+
+    /// Appends an OpMemberDecorateString instruction.
+    pub fn member_decorate_string<T: AsRef<[mr::Operand]>>(&mut self, structure_type: spirv::Word, member: u32, decoration: spirv::Decoration, additional_params: T) {
+        let mut inst = mr::Instruction::new(spirv::Op::MemberDecorateString, None, None, vec![mr::Operand::IdRef(structure_type), mr::Operand::LiteralInt32(member), mr::Operand::Decoration(decoration)]);
+        inst.operands.extend_from_slice(additional_params.as_ref());
+        self.module.annotations.push(inst);
+    }
 }
\ No newline at end of file
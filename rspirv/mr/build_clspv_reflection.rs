@@ -0,0 +1,533 @@
+// Copyright 2016 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// AUTOMATICALLY GENERATED from the SPIR-V JSON grammar:
+//   external/spirv.core.grammar.json.
+// DO NOT MODIFY!
+
+impl Builder {
+    /// Appends a Kernel instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_kernel(&mut self, result_type: spirv::Word, kernel: spirv::Word, name: spirv::Word, num_arguments: Option<spirv::Word>, flags: Option<spirv::Word>, attributes: Option<spirv::Word>) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::Kernel as u32), mr::Operand::IdRef(kernel), mr::Operand::IdRef(name)];
+        if let Some(v) = num_arguments {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        if let Some(v) = flags {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        if let Some(v) = attributes {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ArgumentInfo instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_argument_info(&mut self, result_type: spirv::Word, name: spirv::Word, type_name: Option<spirv::Word>, address_qualifier: Option<spirv::Word>, access_qualifier: Option<spirv::Word>, type_qualifier: Option<spirv::Word>) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ArgumentInfo as u32), mr::Operand::IdRef(name)];
+        if let Some(v) = type_name {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        if let Some(v) = address_qualifier {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        if let Some(v) = access_qualifier {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        if let Some(v) = type_qualifier {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ArgumentStorageBuffer instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_argument_storage_buffer(&mut self, result_type: spirv::Word, decl: spirv::Word, ordinal: spirv::Word, descriptor_set: spirv::Word, binding: spirv::Word, arg_info: Option<spirv::Word>) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ArgumentStorageBuffer as u32), mr::Operand::IdRef(decl), mr::Operand::IdRef(ordinal), mr::Operand::IdRef(descriptor_set), mr::Operand::IdRef(binding)];
+        if let Some(v) = arg_info {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ArgumentUniform instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_argument_uniform(&mut self, result_type: spirv::Word, decl: spirv::Word, ordinal: spirv::Word, descriptor_set: spirv::Word, binding: spirv::Word, arg_info: Option<spirv::Word>) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ArgumentUniform as u32), mr::Operand::IdRef(decl), mr::Operand::IdRef(ordinal), mr::Operand::IdRef(descriptor_set), mr::Operand::IdRef(binding)];
+        if let Some(v) = arg_info {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ArgumentPodStorageBuffer instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_argument_pod_storage_buffer(&mut self, result_type: spirv::Word, decl: spirv::Word, ordinal: spirv::Word, descriptor_set: spirv::Word, binding: spirv::Word, offset: spirv::Word, size: spirv::Word, arg_info: Option<spirv::Word>) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ArgumentPodStorageBuffer as u32), mr::Operand::IdRef(decl), mr::Operand::IdRef(ordinal), mr::Operand::IdRef(descriptor_set), mr::Operand::IdRef(binding), mr::Operand::IdRef(offset), mr::Operand::IdRef(size)];
+        if let Some(v) = arg_info {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ArgumentPodUniform instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_argument_pod_uniform(&mut self, result_type: spirv::Word, decl: spirv::Word, ordinal: spirv::Word, descriptor_set: spirv::Word, binding: spirv::Word, offset: spirv::Word, size: spirv::Word, arg_info: Option<spirv::Word>) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ArgumentPodUniform as u32), mr::Operand::IdRef(decl), mr::Operand::IdRef(ordinal), mr::Operand::IdRef(descriptor_set), mr::Operand::IdRef(binding), mr::Operand::IdRef(offset), mr::Operand::IdRef(size)];
+        if let Some(v) = arg_info {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ArgumentPodPushConstant instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_argument_pod_push_constant(&mut self, result_type: spirv::Word, decl: spirv::Word, ordinal: spirv::Word, offset: spirv::Word, size: spirv::Word, arg_info: Option<spirv::Word>) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ArgumentPodPushConstant as u32), mr::Operand::IdRef(decl), mr::Operand::IdRef(ordinal), mr::Operand::IdRef(offset), mr::Operand::IdRef(size)];
+        if let Some(v) = arg_info {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ArgumentSampledImage instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_argument_sampled_image(&mut self, result_type: spirv::Word, decl: spirv::Word, ordinal: spirv::Word, descriptor_set: spirv::Word, binding: spirv::Word, arg_info: Option<spirv::Word>) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ArgumentSampledImage as u32), mr::Operand::IdRef(decl), mr::Operand::IdRef(ordinal), mr::Operand::IdRef(descriptor_set), mr::Operand::IdRef(binding)];
+        if let Some(v) = arg_info {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ArgumentStorageImage instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_argument_storage_image(&mut self, result_type: spirv::Word, decl: spirv::Word, ordinal: spirv::Word, descriptor_set: spirv::Word, binding: spirv::Word, arg_info: Option<spirv::Word>) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ArgumentStorageImage as u32), mr::Operand::IdRef(decl), mr::Operand::IdRef(ordinal), mr::Operand::IdRef(descriptor_set), mr::Operand::IdRef(binding)];
+        if let Some(v) = arg_info {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ArgumentSampler instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_argument_sampler(&mut self, result_type: spirv::Word, decl: spirv::Word, ordinal: spirv::Word, descriptor_set: spirv::Word, binding: spirv::Word, arg_info: Option<spirv::Word>) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ArgumentSampler as u32), mr::Operand::IdRef(decl), mr::Operand::IdRef(ordinal), mr::Operand::IdRef(descriptor_set), mr::Operand::IdRef(binding)];
+        if let Some(v) = arg_info {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ArgumentWorkgroup instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_argument_workgroup(&mut self, result_type: spirv::Word, decl: spirv::Word, ordinal: spirv::Word, spec_id: spirv::Word, elem_size: spirv::Word, arg_info: Option<spirv::Word>) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ArgumentWorkgroup as u32), mr::Operand::IdRef(decl), mr::Operand::IdRef(ordinal), mr::Operand::IdRef(spec_id), mr::Operand::IdRef(elem_size)];
+        if let Some(v) = arg_info {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a SpecConstantWorkgroupSize instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_spec_constant_workgroup_size(&mut self, result_type: spirv::Word, x: spirv::Word, y: spirv::Word, z: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::SpecConstantWorkgroupSize as u32), mr::Operand::IdRef(x), mr::Operand::IdRef(y), mr::Operand::IdRef(z)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a SpecConstantGlobalOffset instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_spec_constant_global_offset(&mut self, result_type: spirv::Word, x: spirv::Word, y: spirv::Word, z: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::SpecConstantGlobalOffset as u32), mr::Operand::IdRef(x), mr::Operand::IdRef(y), mr::Operand::IdRef(z)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a SpecConstantWorkDim instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_spec_constant_work_dim(&mut self, result_type: spirv::Word, dim: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::SpecConstantWorkDim as u32), mr::Operand::IdRef(dim)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a PushConstantGlobalOffset instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_push_constant_global_offset(&mut self, result_type: spirv::Word, offset: spirv::Word, size: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::PushConstantGlobalOffset as u32), mr::Operand::IdRef(offset), mr::Operand::IdRef(size)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a PushConstantEnqueuedLocalSize instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_push_constant_enqueued_local_size(&mut self, result_type: spirv::Word, offset: spirv::Word, size: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::PushConstantEnqueuedLocalSize as u32), mr::Operand::IdRef(offset), mr::Operand::IdRef(size)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a PushConstantGlobalSize instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_push_constant_global_size(&mut self, result_type: spirv::Word, offset: spirv::Word, size: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::PushConstantGlobalSize as u32), mr::Operand::IdRef(offset), mr::Operand::IdRef(size)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a PushConstantRegionOffset instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_push_constant_region_offset(&mut self, result_type: spirv::Word, offset: spirv::Word, size: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::PushConstantRegionOffset as u32), mr::Operand::IdRef(offset), mr::Operand::IdRef(size)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a PushConstantNumWorkgroups instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_push_constant_num_workgroups(&mut self, result_type: spirv::Word, offset: spirv::Word, size: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::PushConstantNumWorkgroups as u32), mr::Operand::IdRef(offset), mr::Operand::IdRef(size)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a PushConstantRegionGroupOffset instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_push_constant_region_group_offset(&mut self, result_type: spirv::Word, offset: spirv::Word, size: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::PushConstantRegionGroupOffset as u32), mr::Operand::IdRef(offset), mr::Operand::IdRef(size)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ConstantDataStorageBuffer instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_constant_data_storage_buffer(&mut self, result_type: spirv::Word, descriptor_set: spirv::Word, binding: spirv::Word, data: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ConstantDataStorageBuffer as u32), mr::Operand::IdRef(descriptor_set), mr::Operand::IdRef(binding), mr::Operand::IdRef(data)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ConstantDataUniform instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_constant_data_uniform(&mut self, result_type: spirv::Word, descriptor_set: spirv::Word, binding: spirv::Word, data: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ConstantDataUniform as u32), mr::Operand::IdRef(descriptor_set), mr::Operand::IdRef(binding), mr::Operand::IdRef(data)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a LiteralSampler instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_literal_sampler(&mut self, result_type: spirv::Word, descriptor_set: spirv::Word, binding: spirv::Word, mask: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::LiteralSampler as u32), mr::Operand::IdRef(descriptor_set), mr::Operand::IdRef(binding), mr::Operand::IdRef(mask)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a PropertyRequiredWorkgroupSize instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_property_required_workgroup_size(&mut self, result_type: spirv::Word, kernel: spirv::Word, x: spirv::Word, y: spirv::Word, z: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::PropertyRequiredWorkgroupSize as u32), mr::Operand::IdRef(kernel), mr::Operand::IdRef(x), mr::Operand::IdRef(y), mr::Operand::IdRef(z)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a SpecConstantSubgroupMaxSize instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_spec_constant_subgroup_max_size(&mut self, result_type: spirv::Word, size: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::SpecConstantSubgroupMaxSize as u32), mr::Operand::IdRef(size)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ArgumentPointerPushConstant instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_argument_pointer_push_constant(&mut self, result_type: spirv::Word, kernel: spirv::Word, ordinal: spirv::Word, offset: spirv::Word, size: spirv::Word, arg_info: Option<spirv::Word>) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ArgumentPointerPushConstant as u32), mr::Operand::IdRef(kernel), mr::Operand::IdRef(ordinal), mr::Operand::IdRef(offset), mr::Operand::IdRef(size)];
+        if let Some(v) = arg_info {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ArgumentPointerUniform instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_argument_pointer_uniform(&mut self, result_type: spirv::Word, kernel: spirv::Word, ordinal: spirv::Word, descriptor_set: spirv::Word, binding: spirv::Word, offset: spirv::Word, size: spirv::Word, arg_info: Option<spirv::Word>) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ArgumentPointerUniform as u32), mr::Operand::IdRef(kernel), mr::Operand::IdRef(ordinal), mr::Operand::IdRef(descriptor_set), mr::Operand::IdRef(binding), mr::Operand::IdRef(offset), mr::Operand::IdRef(size)];
+        if let Some(v) = arg_info {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ProgramScopeVariablesStorageBuffer instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_program_scope_variables_storage_buffer(&mut self, result_type: spirv::Word, descriptor_set: spirv::Word, binding: spirv::Word, data: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ProgramScopeVariablesStorageBuffer as u32), mr::Operand::IdRef(descriptor_set), mr::Operand::IdRef(binding), mr::Operand::IdRef(data)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ProgramScopeVariablePointerRelocation instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_program_scope_variable_pointer_relocation(&mut self, result_type: spirv::Word, object_offset: spirv::Word, pointer_offset: spirv::Word, pointer_size: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ProgramScopeVariablePointerRelocation as u32), mr::Operand::IdRef(object_offset), mr::Operand::IdRef(pointer_offset), mr::Operand::IdRef(pointer_size)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ImageArgumentInfoChannelOrderPushConstant instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_image_argument_info_channel_order_push_constant(&mut self, result_type: spirv::Word, kernel: spirv::Word, ordinal: spirv::Word, offset: spirv::Word, size: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ImageArgumentInfoChannelOrderPushConstant as u32), mr::Operand::IdRef(kernel), mr::Operand::IdRef(ordinal), mr::Operand::IdRef(offset), mr::Operand::IdRef(size)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ImageArgumentInfoChannelDataTypePushConstant instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_image_argument_info_channel_data_type_push_constant(&mut self, result_type: spirv::Word, kernel: spirv::Word, ordinal: spirv::Word, offset: spirv::Word, size: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ImageArgumentInfoChannelDataTypePushConstant as u32), mr::Operand::IdRef(kernel), mr::Operand::IdRef(ordinal), mr::Operand::IdRef(offset), mr::Operand::IdRef(size)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ImageArgumentInfoChannelOrderUniform instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_image_argument_info_channel_order_uniform(&mut self, result_type: spirv::Word, kernel: spirv::Word, ordinal: spirv::Word, descriptor_set: spirv::Word, binding: spirv::Word, offset: spirv::Word, size: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ImageArgumentInfoChannelOrderUniform as u32), mr::Operand::IdRef(kernel), mr::Operand::IdRef(ordinal), mr::Operand::IdRef(descriptor_set), mr::Operand::IdRef(binding), mr::Operand::IdRef(offset), mr::Operand::IdRef(size)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ImageArgumentInfoChannelDataTypeUniform instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_image_argument_info_channel_data_type_uniform(&mut self, result_type: spirv::Word, kernel: spirv::Word, ordinal: spirv::Word, descriptor_set: spirv::Word, binding: spirv::Word, offset: spirv::Word, size: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ImageArgumentInfoChannelDataTypeUniform as u32), mr::Operand::IdRef(kernel), mr::Operand::IdRef(ordinal), mr::Operand::IdRef(descriptor_set), mr::Operand::IdRef(binding), mr::Operand::IdRef(offset), mr::Operand::IdRef(size)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ArgumentStorageTexelBuffer instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_argument_storage_texel_buffer(&mut self, result_type: spirv::Word, decl: spirv::Word, ordinal: spirv::Word, descriptor_set: spirv::Word, binding: spirv::Word, arg_info: Option<spirv::Word>) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ArgumentStorageTexelBuffer as u32), mr::Operand::IdRef(decl), mr::Operand::IdRef(ordinal), mr::Operand::IdRef(descriptor_set), mr::Operand::IdRef(binding)];
+        if let Some(v) = arg_info {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ArgumentUniformTexelBuffer instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_argument_uniform_texel_buffer(&mut self, result_type: spirv::Word, decl: spirv::Word, ordinal: spirv::Word, descriptor_set: spirv::Word, binding: spirv::Word, arg_info: Option<spirv::Word>) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ArgumentUniformTexelBuffer as u32), mr::Operand::IdRef(decl), mr::Operand::IdRef(ordinal), mr::Operand::IdRef(descriptor_set), mr::Operand::IdRef(binding)];
+        if let Some(v) = arg_info {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ConstantDataPointerPushConstant instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_constant_data_pointer_push_constant(&mut self, result_type: spirv::Word, offset: spirv::Word, size: spirv::Word, data: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ConstantDataPointerPushConstant as u32), mr::Operand::IdRef(offset), mr::Operand::IdRef(size), mr::Operand::IdRef(data)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a ProgramScopeVariablePointerPushConstant instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_program_scope_variable_pointer_push_constant(&mut self, result_type: spirv::Word, offset: spirv::Word, size: spirv::Word, data: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::ProgramScopeVariablePointerPushConstant as u32), mr::Operand::IdRef(offset), mr::Operand::IdRef(size), mr::Operand::IdRef(data)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a PrintfInfo instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_printf_info<T: AsRef<[spirv::Word]>>(&mut self, result_type: spirv::Word, printf_id: spirv::Word, format_string: spirv::Word, argument_sizes: T) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::PrintfInfo as u32), mr::Operand::IdRef(printf_id), mr::Operand::IdRef(format_string)];
+        for v in argument_sizes.as_ref() {
+            operands.push(mr::Operand::IdRef(*v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a PrintfBufferStorageBuffer instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_printf_buffer_storage_buffer(&mut self, result_type: spirv::Word, descriptor_set: spirv::Word, binding: spirv::Word, buffer_size: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::PrintfBufferStorageBuffer as u32), mr::Operand::IdRef(descriptor_set), mr::Operand::IdRef(binding), mr::Operand::IdRef(buffer_size)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a PrintfBufferPointerPushConstant instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_printf_buffer_pointer_push_constant(&mut self, result_type: spirv::Word, offset: spirv::Word, size: spirv::Word, buffer_size: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::PrintfBufferPointerPushConstant as u32), mr::Operand::IdRef(offset), mr::Operand::IdRef(size), mr::Operand::IdRef(buffer_size)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a NormalizedSamplerMaskPushConstant instruction from the NonSemantic.ClspvReflection
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn clspv_normalized_sampler_mask_push_constant(&mut self, result_type: spirv::Word, kernel: spirv::Word, ordinal: spirv::Word, offset: spirv::Word, size: spirv::Word) -> spirv::Word {
+        let set = self.clspv_reflection_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::ClspvReflection, spirv::ClspvReflectionOp::NormalizedSamplerMaskPushConstant as u32), mr::Operand::IdRef(kernel), mr::Operand::IdRef(ordinal), mr::Operand::IdRef(offset), mr::Operand::IdRef(size)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+}
\ No newline at end of file
@@ -0,0 +1,561 @@
+// Copyright 2016 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// AUTOMATICALLY GENERATED from the SPIR-V JSON grammar:
+//   external/spirv.core.grammar.json.
+// DO NOT MODIFY!
+
+impl Builder {
+    /// Appends a DebugInfoNone instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_info_none(&mut self, result_type: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugInfoNone as u32)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugCompilationUnit instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_compilation_unit(&mut self, result_type: spirv::Word, version: spirv::Word, dwarf_version: spirv::Word, source: spirv::Word, language: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugCompilationUnit as u32), mr::Operand::IdRef(version), mr::Operand::IdRef(dwarf_version), mr::Operand::IdRef(source), mr::Operand::IdRef(language)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugTypeBasic instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_type_basic(&mut self, result_type: spirv::Word, name: spirv::Word, size: spirv::Word, encoding: spirv::Word, flags: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugTypeBasic as u32), mr::Operand::IdRef(name), mr::Operand::IdRef(size), mr::Operand::IdRef(encoding), mr::Operand::IdRef(flags)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugTypePointer instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_type_pointer(&mut self, result_type: spirv::Word, base_type: spirv::Word, storage_class: spirv::Word, flags: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugTypePointer as u32), mr::Operand::IdRef(base_type), mr::Operand::IdRef(storage_class), mr::Operand::IdRef(flags)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugTypeQualifier instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_type_qualifier(&mut self, result_type: spirv::Word, base_type: spirv::Word, type_qualifier: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugTypeQualifier as u32), mr::Operand::IdRef(base_type), mr::Operand::IdRef(type_qualifier)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugTypeArray instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_type_array<T: AsRef<[spirv::Word]>>(&mut self, result_type: spirv::Word, base_type: spirv::Word, component_counts: T) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugTypeArray as u32), mr::Operand::IdRef(base_type)];
+        for v in component_counts.as_ref() {
+            operands.push(mr::Operand::IdRef(*v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugTypeVector instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_type_vector(&mut self, result_type: spirv::Word, base_type: spirv::Word, component_count: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugTypeVector as u32), mr::Operand::IdRef(base_type), mr::Operand::IdRef(component_count)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugTypedef instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_typedef(&mut self, result_type: spirv::Word, name: spirv::Word, base_type: spirv::Word, source: spirv::Word, line: spirv::Word, column: spirv::Word, parent: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugTypedef as u32), mr::Operand::IdRef(name), mr::Operand::IdRef(base_type), mr::Operand::IdRef(source), mr::Operand::IdRef(line), mr::Operand::IdRef(column), mr::Operand::IdRef(parent)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugTypeFunction instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_type_function<T: AsRef<[spirv::Word]>>(&mut self, result_type: spirv::Word, flags: spirv::Word, return_type: spirv::Word, parameter_types: T) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugTypeFunction as u32), mr::Operand::IdRef(flags), mr::Operand::IdRef(return_type)];
+        for v in parameter_types.as_ref() {
+            operands.push(mr::Operand::IdRef(*v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugTypeEnum instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_type_enum<T: AsRef<[(spirv::Word, spirv::Word)]>>(&mut self, result_type: spirv::Word, name: spirv::Word, underlying_type: spirv::Word, source: spirv::Word, line: spirv::Word, column: spirv::Word, parent: spirv::Word, size: spirv::Word, flags: spirv::Word, enumerators: T) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugTypeEnum as u32), mr::Operand::IdRef(name), mr::Operand::IdRef(underlying_type), mr::Operand::IdRef(source), mr::Operand::IdRef(line), mr::Operand::IdRef(column), mr::Operand::IdRef(parent), mr::Operand::IdRef(size), mr::Operand::IdRef(flags)];
+        for v in enumerators.as_ref() {
+            operands.push(mr::Operand::IdRef(v.0));
+            operands.push(mr::Operand::IdRef(v.1));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugTypeComposite instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_type_composite<T: AsRef<[spirv::Word]>>(&mut self, result_type: spirv::Word, name: spirv::Word, tag: spirv::Word, source: spirv::Word, line: spirv::Word, column: spirv::Word, parent: spirv::Word, linkage_name: spirv::Word, size: spirv::Word, flags: spirv::Word, members: T) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugTypeComposite as u32), mr::Operand::IdRef(name), mr::Operand::IdRef(tag), mr::Operand::IdRef(source), mr::Operand::IdRef(line), mr::Operand::IdRef(column), mr::Operand::IdRef(parent), mr::Operand::IdRef(linkage_name), mr::Operand::IdRef(size), mr::Operand::IdRef(flags)];
+        for v in members.as_ref() {
+            operands.push(mr::Operand::IdRef(*v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugTypeMember instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_type_member(&mut self, result_type: spirv::Word, name: spirv::Word, ty: spirv::Word, source: spirv::Word, line: spirv::Word, column: spirv::Word, offset: spirv::Word, size: spirv::Word, flags: spirv::Word, value: Option<spirv::Word>) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugTypeMember as u32), mr::Operand::IdRef(name), mr::Operand::IdRef(ty), mr::Operand::IdRef(source), mr::Operand::IdRef(line), mr::Operand::IdRef(column), mr::Operand::IdRef(offset), mr::Operand::IdRef(size), mr::Operand::IdRef(flags)];
+        if let Some(v) = value {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugTypeInheritance instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_type_inheritance(&mut self, result_type: spirv::Word, parent: spirv::Word, offset: spirv::Word, size: spirv::Word, flags: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugTypeInheritance as u32), mr::Operand::IdRef(parent), mr::Operand::IdRef(offset), mr::Operand::IdRef(size), mr::Operand::IdRef(flags)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugTypePtrToMember instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_type_ptr_to_member(&mut self, result_type: spirv::Word, member_type: spirv::Word, parent: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugTypePtrToMember as u32), mr::Operand::IdRef(member_type), mr::Operand::IdRef(parent)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugTypeTemplate instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_type_template<T: AsRef<[spirv::Word]>>(&mut self, result_type: spirv::Word, target: spirv::Word, parameters: T) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugTypeTemplate as u32), mr::Operand::IdRef(target)];
+        for v in parameters.as_ref() {
+            operands.push(mr::Operand::IdRef(*v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugTypeTemplateParameter instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_type_template_parameter(&mut self, result_type: spirv::Word, name: spirv::Word, actual_type: spirv::Word, value: spirv::Word, source: spirv::Word, line: spirv::Word, column: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugTypeTemplateParameter as u32), mr::Operand::IdRef(name), mr::Operand::IdRef(actual_type), mr::Operand::IdRef(value), mr::Operand::IdRef(source), mr::Operand::IdRef(line), mr::Operand::IdRef(column)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugTypeTemplateTemplateParameter instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_type_template_template_parameter(&mut self, result_type: spirv::Word, name: spirv::Word, template_name: spirv::Word, source: spirv::Word, line: spirv::Word, column: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugTypeTemplateTemplateParameter as u32), mr::Operand::IdRef(name), mr::Operand::IdRef(template_name), mr::Operand::IdRef(source), mr::Operand::IdRef(line), mr::Operand::IdRef(column)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugTypeTemplateParameterPack instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_type_template_parameter_pack<T: AsRef<[spirv::Word]>>(&mut self, result_type: spirv::Word, name: spirv::Word, source: spirv::Word, line: spirv::Word, column: spirv::Word, template_parameters: T) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugTypeTemplateParameterPack as u32), mr::Operand::IdRef(name), mr::Operand::IdRef(source), mr::Operand::IdRef(line), mr::Operand::IdRef(column)];
+        for v in template_parameters.as_ref() {
+            operands.push(mr::Operand::IdRef(*v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugGlobalVariable instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_global_variable(&mut self, result_type: spirv::Word, name: spirv::Word, ty: spirv::Word, source: spirv::Word, line: spirv::Word, column: spirv::Word, parent: spirv::Word, linkage_name: spirv::Word, variable: spirv::Word, flags: spirv::Word, static_member_declaration: Option<spirv::Word>) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugGlobalVariable as u32), mr::Operand::IdRef(name), mr::Operand::IdRef(ty), mr::Operand::IdRef(source), mr::Operand::IdRef(line), mr::Operand::IdRef(column), mr::Operand::IdRef(parent), mr::Operand::IdRef(linkage_name), mr::Operand::IdRef(variable), mr::Operand::IdRef(flags)];
+        if let Some(v) = static_member_declaration {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugFunctionDeclaration instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_function_declaration(&mut self, result_type: spirv::Word, name: spirv::Word, ty: spirv::Word, source: spirv::Word, line: spirv::Word, column: spirv::Word, parent: spirv::Word, linkage_name: spirv::Word, flags: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugFunctionDeclaration as u32), mr::Operand::IdRef(name), mr::Operand::IdRef(ty), mr::Operand::IdRef(source), mr::Operand::IdRef(line), mr::Operand::IdRef(column), mr::Operand::IdRef(parent), mr::Operand::IdRef(linkage_name), mr::Operand::IdRef(flags)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugFunction instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_function(&mut self, result_type: spirv::Word, name: spirv::Word, ty: spirv::Word, source: spirv::Word, line: spirv::Word, column: spirv::Word, parent: spirv::Word, linkage_name: spirv::Word, flags: spirv::Word, scope_line: spirv::Word, declaration: Option<spirv::Word>) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugFunction as u32), mr::Operand::IdRef(name), mr::Operand::IdRef(ty), mr::Operand::IdRef(source), mr::Operand::IdRef(line), mr::Operand::IdRef(column), mr::Operand::IdRef(parent), mr::Operand::IdRef(linkage_name), mr::Operand::IdRef(flags), mr::Operand::IdRef(scope_line)];
+        if let Some(v) = declaration {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugLexicalBlock instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_lexical_block(&mut self, result_type: spirv::Word, source: spirv::Word, line: spirv::Word, column: spirv::Word, parent: spirv::Word, name: Option<spirv::Word>) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugLexicalBlock as u32), mr::Operand::IdRef(source), mr::Operand::IdRef(line), mr::Operand::IdRef(column), mr::Operand::IdRef(parent)];
+        if let Some(v) = name {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugLexicalBlockDiscriminator instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_lexical_block_discriminator(&mut self, result_type: spirv::Word, source: spirv::Word, discriminator: spirv::Word, parent: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugLexicalBlockDiscriminator as u32), mr::Operand::IdRef(source), mr::Operand::IdRef(discriminator), mr::Operand::IdRef(parent)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugScope instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_scope(&mut self, result_type: spirv::Word, scope: spirv::Word, inlined_at: Option<spirv::Word>) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugScope as u32), mr::Operand::IdRef(scope)];
+        if let Some(v) = inlined_at {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugNoScope instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_no_scope(&mut self, result_type: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugNoScope as u32)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugInlinedAt instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_inlined_at(&mut self, result_type: spirv::Word, line: spirv::Word, scope: spirv::Word, inlined: Option<spirv::Word>) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugInlinedAt as u32), mr::Operand::IdRef(line), mr::Operand::IdRef(scope)];
+        if let Some(v) = inlined {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugLocalVariable instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_local_variable(&mut self, result_type: spirv::Word, name: spirv::Word, ty: spirv::Word, source: spirv::Word, line: spirv::Word, column: spirv::Word, parent: spirv::Word, flags: spirv::Word, arg_number: Option<spirv::Word>) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugLocalVariable as u32), mr::Operand::IdRef(name), mr::Operand::IdRef(ty), mr::Operand::IdRef(source), mr::Operand::IdRef(line), mr::Operand::IdRef(column), mr::Operand::IdRef(parent), mr::Operand::IdRef(flags)];
+        if let Some(v) = arg_number {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugInlinedVariable instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_inlined_variable(&mut self, result_type: spirv::Word, variable: spirv::Word, inlined: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugInlinedVariable as u32), mr::Operand::IdRef(variable), mr::Operand::IdRef(inlined)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugDeclare instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_declare<T: AsRef<[spirv::Word]>>(&mut self, result_type: spirv::Word, local_variable: spirv::Word, variable: spirv::Word, expression: spirv::Word, indexes: T) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugDeclare as u32), mr::Operand::IdRef(local_variable), mr::Operand::IdRef(variable), mr::Operand::IdRef(expression)];
+        for v in indexes.as_ref() {
+            operands.push(mr::Operand::IdRef(*v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugValue instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_value<T: AsRef<[spirv::Word]>>(&mut self, result_type: spirv::Word, local_variable: spirv::Word, value: spirv::Word, expression: spirv::Word, indexes: T) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugValue as u32), mr::Operand::IdRef(local_variable), mr::Operand::IdRef(value), mr::Operand::IdRef(expression)];
+        for v in indexes.as_ref() {
+            operands.push(mr::Operand::IdRef(*v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugOperation instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_operation<T: AsRef<[spirv::Word]>>(&mut self, result_type: spirv::Word, op_code: spirv::Word, arguments: T) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugOperation as u32), mr::Operand::IdRef(op_code)];
+        for v in arguments.as_ref() {
+            operands.push(mr::Operand::IdRef(*v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugExpression instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_expression<T: AsRef<[spirv::Word]>>(&mut self, result_type: spirv::Word, arguments: T) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugExpression as u32)];
+        for v in arguments.as_ref() {
+            operands.push(mr::Operand::IdRef(*v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugMacroDef instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_macro_def(&mut self, result_type: spirv::Word, source: spirv::Word, line: spirv::Word, name: spirv::Word, value: Option<spirv::Word>) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugMacroDef as u32), mr::Operand::IdRef(source), mr::Operand::IdRef(line), mr::Operand::IdRef(name)];
+        if let Some(v) = value {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugMacroUndef instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_macro_undef(&mut self, result_type: spirv::Word, source: spirv::Word, line: spirv::Word, macro_def: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugMacroUndef as u32), mr::Operand::IdRef(source), mr::Operand::IdRef(line), mr::Operand::IdRef(macro_def)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugImportedEntity instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_imported_entity(&mut self, result_type: spirv::Word, name: spirv::Word, tag: spirv::Word, source: spirv::Word, entity: spirv::Word, line: spirv::Word, column: spirv::Word, parent: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugImportedEntity as u32), mr::Operand::IdRef(name), mr::Operand::IdRef(tag), mr::Operand::IdRef(source), mr::Operand::IdRef(entity), mr::Operand::IdRef(line), mr::Operand::IdRef(column), mr::Operand::IdRef(parent)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugSource instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_source(&mut self, result_type: spirv::Word, file: spirv::Word, text: Option<spirv::Word>) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugSource as u32), mr::Operand::IdRef(file)];
+        if let Some(v) = text {
+            operands.push(mr::Operand::IdRef(v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugFunctionDefinition instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_function_definition(&mut self, result_type: spirv::Word, function: spirv::Word, definition: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugFunctionDefinition as u32), mr::Operand::IdRef(function), mr::Operand::IdRef(definition)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugSourceContinued instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_source_continued(&mut self, result_type: spirv::Word, text: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugSourceContinued as u32), mr::Operand::IdRef(text)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugLine instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_line(&mut self, result_type: spirv::Word, source: spirv::Word, line_start: spirv::Word, line_end: spirv::Word, column_start: spirv::Word, column_end: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugLine as u32), mr::Operand::IdRef(source), mr::Operand::IdRef(line_start), mr::Operand::IdRef(line_end), mr::Operand::IdRef(column_start), mr::Operand::IdRef(column_end)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugNoLine instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_no_line(&mut self, result_type: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugNoLine as u32)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugBuildIdentifier instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_build_identifier(&mut self, result_type: spirv::Word, identifier: spirv::Word, flags: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugBuildIdentifier as u32), mr::Operand::IdRef(identifier), mr::Operand::IdRef(flags)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugStoragePath instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_storage_path(&mut self, result_type: spirv::Word, path: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugStoragePath as u32), mr::Operand::IdRef(path)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugEntryPoint instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_entry_point(&mut self, result_type: spirv::Word, entry_point: spirv::Word, compilation_unit: spirv::Word, compiler_signature: spirv::Word, commandline_arguments: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugEntryPoint as u32), mr::Operand::IdRef(entry_point), mr::Operand::IdRef(compilation_unit), mr::Operand::IdRef(compiler_signature), mr::Operand::IdRef(commandline_arguments)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+
+    /// Appends a DebugTypeMatrix instruction from the NonSemantic.Shader.DebugInfo.100
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_type_matrix(&mut self, result_type: spirv::Word, vector_type: spirv::Word, vector_count: spirv::Word, column_major: spirv::Word) -> spirv::Word {
+        let set = self.debug_info_import();
+        let id = self.id();
+        let operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugInfo100, spirv::DebugInfoOp::DebugTypeMatrix as u32), mr::Operand::IdRef(vector_type), mr::Operand::IdRef(vector_count), mr::Operand::IdRef(column_major)];
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+}
\ No newline at end of file
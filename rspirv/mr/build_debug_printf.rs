@@ -0,0 +1,33 @@
+// Copyright 2016 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// AUTOMATICALLY GENERATED from the SPIR-V JSON grammar:
+//   external/spirv.core.grammar.json.
+// DO NOT MODIFY!
+
+impl Builder {
+    /// Appends a DebugPrintf instruction from the NonSemantic.DebugPrintf
+    /// set to either the current basic block or the module if no basic block
+    /// is under construction, and returns the result id.
+    pub fn debug_printf<T: AsRef<[spirv::Word]>>(&mut self, result_type: spirv::Word, format: spirv::Word, arguments: T) -> spirv::Word {
+        let set = self.debug_printf_import();
+        let id = self.id();
+        let mut operands = vec![mr::Operand::IdRef(set), mr::Operand::ExtInstOpcode(grammar::ExtInstSet::DebugPrintf, spirv::DebugPrintfOp::DebugPrintf as u32), mr::Operand::IdRef(format)];
+        for v in arguments.as_ref() {
+            operands.push(mr::Operand::IdRef(*v));
+        }
+        self.non_semantic_inst(mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), operands));
+        id
+    }
+}
\ No newline at end of file
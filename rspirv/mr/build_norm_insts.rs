@@ -35,7 +35,7 @@ impl Builder {
             Some(v) => v,
             None => self.id(),
         };
-        let mut inst = mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), vec![mr::Operand::IdRef(set), mr::Operand::LiteralExtInstInteger(instruction)]);
+        let mut inst = mr::Instruction::new(spirv::Op::ExtInst, Some(result_type), Some(id), vec![mr::Operand::IdRef(set), self.ext_insts.operand(set, instruction)]);
         for v in operands.as_ref() {
             inst.operands.push(mr::Operand::IdRef(*v))
         };
@@ -3316,6 +3316,244 @@ impl Builder {
         Ok(self.basic_block.as_mut().unwrap().instructions.push(inst))
     }
 
+    /// Appends an OpGroupNonUniformElect instruction to the current basic block.
+    pub fn group_non_uniform_elect(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, execution: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::GroupNonUniformElect, Some(result_type), Some(id), vec![mr::Operand::IdScope(execution)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpGroupNonUniformAll instruction to the current basic block.
+    pub fn group_non_uniform_all(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, execution: spirv::Word, predicate: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::GroupNonUniformAll, Some(result_type), Some(id), vec![mr::Operand::IdScope(execution), mr::Operand::IdRef(predicate)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpGroupNonUniformAny instruction to the current basic block.
+    pub fn group_non_uniform_any(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, execution: spirv::Word, predicate: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::GroupNonUniformAny, Some(result_type), Some(id), vec![mr::Operand::IdScope(execution), mr::Operand::IdRef(predicate)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpGroupNonUniformAllEqual instruction to the current basic block.
+    pub fn group_non_uniform_all_equal(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, execution: spirv::Word, value: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::GroupNonUniformAllEqual, Some(result_type), Some(id), vec![mr::Operand::IdScope(execution), mr::Operand::IdRef(value)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpGroupNonUniformBroadcast instruction to the current basic block.
+    pub fn group_non_uniform_broadcast(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, execution: spirv::Word, value: spirv::Word, id_ref: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::GroupNonUniformBroadcast, Some(result_type), Some(id), vec![mr::Operand::IdScope(execution), mr::Operand::IdRef(value), mr::Operand::IdRef(id_ref)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpGroupNonUniformBroadcastFirst instruction to the current basic block.
+    pub fn group_non_uniform_broadcast_first(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, execution: spirv::Word, value: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::GroupNonUniformBroadcastFirst, Some(result_type), Some(id), vec![mr::Operand::IdScope(execution), mr::Operand::IdRef(value)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpGroupNonUniformBallot instruction to the current basic block.
+    pub fn group_non_uniform_ballot(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, execution: spirv::Word, predicate: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::GroupNonUniformBallot, Some(result_type), Some(id), vec![mr::Operand::IdScope(execution), mr::Operand::IdRef(predicate)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpGroupNonUniformInverseBallot instruction to the current basic block.
+    pub fn group_non_uniform_inverse_ballot(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, execution: spirv::Word, value: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::GroupNonUniformInverseBallot, Some(result_type), Some(id), vec![mr::Operand::IdScope(execution), mr::Operand::IdRef(value)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpGroupNonUniformBallotBitExtract instruction to the current basic block.
+    pub fn group_non_uniform_ballot_bit_extract(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, execution: spirv::Word, value: spirv::Word, index: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::GroupNonUniformBallotBitExtract, Some(result_type), Some(id), vec![mr::Operand::IdScope(execution), mr::Operand::IdRef(value), mr::Operand::IdRef(index)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpGroupNonUniformBallotBitCount instruction to the current basic block.
+    pub fn group_non_uniform_ballot_bit_count(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, execution: spirv::Word, operation: spirv::GroupOperation, value: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::GroupNonUniformBallotBitCount, Some(result_type), Some(id), vec![mr::Operand::IdScope(execution), mr::Operand::GroupOperation(operation), mr::Operand::IdRef(value)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpGroupNonUniformBallotFindLSB instruction to the current basic block.
+    pub fn group_non_uniform_ballot_find_lsb(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, execution: spirv::Word, value: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::GroupNonUniformBallotFindLSB, Some(result_type), Some(id), vec![mr::Operand::IdScope(execution), mr::Operand::IdRef(value)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpGroupNonUniformBallotFindMSB instruction to the current basic block.
+    pub fn group_non_uniform_ballot_find_msb(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, execution: spirv::Word, value: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::GroupNonUniformBallotFindMSB, Some(result_type), Some(id), vec![mr::Operand::IdScope(execution), mr::Operand::IdRef(value)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpGroupNonUniformShuffle instruction to the current basic block.
+    pub fn group_non_uniform_shuffle(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, execution: spirv::Word, value: spirv::Word, id_ref: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::GroupNonUniformShuffle, Some(result_type), Some(id), vec![mr::Operand::IdScope(execution), mr::Operand::IdRef(value), mr::Operand::IdRef(id_ref)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpGroupNonUniformShuffleXor instruction to the current basic block.
+    pub fn group_non_uniform_shuffle_xor(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, execution: spirv::Word, value: spirv::Word, mask: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::GroupNonUniformShuffleXor, Some(result_type), Some(id), vec![mr::Operand::IdScope(execution), mr::Operand::IdRef(value), mr::Operand::IdRef(mask)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpGroupNonUniformShuffleUp instruction to the current basic block.
+    pub fn group_non_uniform_shuffle_up(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, execution: spirv::Word, value: spirv::Word, delta: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::GroupNonUniformShuffleUp, Some(result_type), Some(id), vec![mr::Operand::IdScope(execution), mr::Operand::IdRef(value), mr::Operand::IdRef(delta)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpGroupNonUniformShuffleDown instruction to the current basic block.
+    pub fn group_non_uniform_shuffle_down(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, execution: spirv::Word, value: spirv::Word, delta: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::GroupNonUniformShuffleDown, Some(result_type), Some(id), vec![mr::Operand::IdScope(execution), mr::Operand::IdRef(value), mr::Operand::IdRef(delta)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpCopyLogical instruction to the current basic block.
+    pub fn copy_logical(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, operand: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::CopyLogical, Some(result_type), Some(id), vec![mr::Operand::IdRef(operand)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
     /// Appends an OpSubgroupBallotKHR instruction to the current basic block.
     pub fn subgroup_ballot_khr(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, predicate: spirv::Word) -> BuildResult<spirv::Word> {
         if self.basic_block.is_none() {
@@ -3400,6 +3638,106 @@ impl Builder {
         Ok(id)
     }
 
+    /// Appends an OpTraceRayKHR instruction to the current basic block.
+    pub fn trace_ray_khr(&mut self, accel: spirv::Word, ray_flags: spirv::Word, cull_mask: spirv::Word, sbt_offset: spirv::Word, sbt_stride: spirv::Word, miss_index: spirv::Word, ray_origin: spirv::Word, ray_tmin: spirv::Word, ray_direction: spirv::Word, ray_tmax: spirv::Word, payload: spirv::Word) -> BuildResult<()> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let inst = mr::Instruction::new(spirv::Op::TraceRayKHR, None, None, vec![mr::Operand::IdRef(accel), mr::Operand::IdRef(ray_flags), mr::Operand::IdRef(cull_mask), mr::Operand::IdRef(sbt_offset), mr::Operand::IdRef(sbt_stride), mr::Operand::IdRef(miss_index), mr::Operand::IdRef(ray_origin), mr::Operand::IdRef(ray_tmin), mr::Operand::IdRef(ray_direction), mr::Operand::IdRef(ray_tmax), mr::Operand::IdRef(payload)]);
+        Ok(self.basic_block.as_mut().unwrap().instructions.push(inst))
+    }
+
+    /// Appends an OpExecuteCallableKHR instruction to the current basic block.
+    pub fn execute_callable_khr(&mut self, sbt_index: spirv::Word, callable_data: spirv::Word) -> BuildResult<()> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let inst = mr::Instruction::new(spirv::Op::ExecuteCallableKHR, None, None, vec![mr::Operand::IdRef(sbt_index), mr::Operand::IdRef(callable_data)]);
+        Ok(self.basic_block.as_mut().unwrap().instructions.push(inst))
+    }
+
+    /// Appends an OpConvertUToAccelerationStructureKHR instruction to the current basic block.
+    pub fn convert_uto_acceleration_structure_khr(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, accel: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::ConvertUToAccelerationStructureKHR, Some(result_type), Some(id), vec![mr::Operand::IdRef(accel)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpCooperativeMatrixLoadKHR instruction to the current basic block.
+    pub fn cooperative_matrix_load_khr<T: AsRef<[mr::Operand]>>(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, pointer: spirv::Word, memory_layout: spirv::Word, stride: Option<spirv::Word>, memory_operand: Option<spirv::MemoryAccess>, additional_params: T) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let mut inst = mr::Instruction::new(spirv::Op::CooperativeMatrixLoadKHR, Some(result_type), Some(id), vec![mr::Operand::IdRef(pointer), mr::Operand::IdRef(memory_layout)]);
+        if let Some(v) = stride {
+            inst.operands.push(mr::Operand::IdRef(v));
+        };
+        if let Some(v) = memory_operand {
+            inst.operands.push(mr::Operand::MemoryAccess(v));
+        };
+        inst.operands.extend_from_slice(additional_params.as_ref());
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpCooperativeMatrixStoreKHR instruction to the current basic block.
+    pub fn cooperative_matrix_store_khr<T: AsRef<[mr::Operand]>>(&mut self, pointer: spirv::Word, object: spirv::Word, memory_layout: spirv::Word, stride: Option<spirv::Word>, memory_operand: Option<spirv::MemoryAccess>, additional_params: T) -> BuildResult<()> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let mut inst = mr::Instruction::new(spirv::Op::CooperativeMatrixStoreKHR, None, None, vec![mr::Operand::IdRef(pointer), mr::Operand::IdRef(object), mr::Operand::IdRef(memory_layout)]);
+        if let Some(v) = stride {
+            inst.operands.push(mr::Operand::IdRef(v));
+        };
+        if let Some(v) = memory_operand {
+            inst.operands.push(mr::Operand::MemoryAccess(v));
+        };
+        inst.operands.extend_from_slice(additional_params.as_ref());
+        Ok(self.basic_block.as_mut().unwrap().instructions.push(inst))
+    }
+
+    /// Appends an OpCooperativeMatrixMulAddKHR instruction to the current basic block.
+    pub fn cooperative_matrix_mul_add_khr(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, a: spirv::Word, b: spirv::Word, c: spirv::Word, cooperative_matrix_operands: Option<spirv::CooperativeMatrixOperands>) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let mut inst = mr::Instruction::new(spirv::Op::CooperativeMatrixMulAddKHR, Some(result_type), Some(id), vec![mr::Operand::IdRef(a), mr::Operand::IdRef(b), mr::Operand::IdRef(c)]);
+        if let Some(v) = cooperative_matrix_operands {
+            inst.operands.push(mr::Operand::CooperativeMatrixOperands(v));
+        };
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpCooperativeMatrixLengthKHR instruction to the current basic block.
+    pub fn cooperative_matrix_length_khr(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, ty: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::CooperativeMatrixLengthKHR, Some(result_type), Some(id), vec![mr::Operand::IdRef(ty)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
     /// Appends an OpGroupIAddNonUniformAMD instruction to the current basic block.
     pub fn group_iadd_non_uniform_amd(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, execution: spirv::Word, operation: spirv::GroupOperation, x: spirv::Word) -> BuildResult<spirv::Word> {
         if self.basic_block.is_none() {
@@ -3539,4 +3877,64 @@ impl Builder {
         self.basic_block.as_mut().unwrap().instructions.push(inst);
         Ok(id)
     }
+
+    /// Appends an OpSetMeshOutputsEXT instruction to the current basic block.
+    pub fn set_mesh_outputs_ext(&mut self, vertex_count: spirv::Word, primitive_count: spirv::Word) -> BuildResult<()> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let inst = mr::Instruction::new(spirv::Op::SetMeshOutputsEXT, None, None, vec![mr::Operand::IdRef(vertex_count), mr::Operand::IdRef(primitive_count)]);
+        Ok(self.basic_block.as_mut().unwrap().instructions.push(inst))
+    }
+
+    /// Appends an OpReportIntersectionKHR instruction to the current basic block.
+    pub fn report_intersection_khr(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, hit: spirv::Word, hit_kind: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::ReportIntersectionKHR, Some(result_type), Some(id), vec![mr::Operand::IdRef(hit), mr::Operand::IdRef(hit_kind)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpDemoteToHelperInvocation instruction to the current basic block.
+    pub fn demote_to_helper_invocation(&mut self) -> BuildResult<()> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let inst = mr::Instruction::new(spirv::Op::DemoteToHelperInvocation, None, None, vec![]);
+        Ok(self.basic_block.as_mut().unwrap().instructions.push(inst))
+    }
+
+    /// Appends an OpIsHelperInvocationEXT instruction to the current basic block.
+    pub fn is_helper_invocation_ext(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::IsHelperInvocationEXT, Some(result_type), Some(id), vec![]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
+
+    /// Appends an OpAtomicFAddEXT instruction to the current basic block.
+    pub fn atomic_fadd_ext(&mut self, result_type: spirv::Word, result_id: Option<spirv::Word>, pointer: spirv::Word, memory: spirv::Word, semantics: spirv::Word, value: spirv::Word) -> BuildResult<spirv::Word> {
+        if self.basic_block.is_none() {
+            return Err(Error::DetachedInstruction);
+        }
+        let id = match result_id {
+            Some(v) => v,
+            None => self.id(),
+        };
+        let inst = mr::Instruction::new(spirv::Op::AtomicFAddEXT, Some(result_type), Some(id), vec![mr::Operand::IdRef(pointer), mr::Operand::IdScope(memory), mr::Operand::IdMemorySemantics(semantics), mr::Operand::IdRef(value)]);
+        self.basic_block.as_mut().unwrap().instructions.push(inst);
+        Ok(id)
+    }
 }
\ No newline at end of file
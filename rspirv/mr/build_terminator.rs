@@ -65,4 +65,31 @@ impl Builder {
         let inst = mr::Instruction::new(spirv::Op::Unreachable, None, None, vec![]);
         self.end_basic_block(inst)
     }
+
+    /// Appends an OpTerminateInvocation instruction and ends the current basic block.
+    pub fn terminate_invocation(&mut self) -> BuildResult<()> {
+        let inst = mr::Instruction::new(spirv::Op::TerminateInvocation, None, None, vec![]);
+        self.end_basic_block(inst)
+    }
+
+    /// Appends an OpIgnoreIntersectionKHR instruction and ends the current basic block.
+    pub fn ignore_intersection_khr(&mut self) -> BuildResult<()> {
+        let inst = mr::Instruction::new(spirv::Op::IgnoreIntersectionKHR, None, None, vec![]);
+        self.end_basic_block(inst)
+    }
+
+    /// Appends an OpTerminateRayKHR instruction and ends the current basic block.
+    pub fn terminate_ray_khr(&mut self) -> BuildResult<()> {
+        let inst = mr::Instruction::new(spirv::Op::TerminateRayKHR, None, None, vec![]);
+        self.end_basic_block(inst)
+    }
+
+    /// Appends an OpEmitMeshTasksEXT instruction and ends the current basic block.
+    pub fn emit_mesh_tasks_ext(&mut self, group_count_x: spirv::Word, group_count_y: spirv::Word, group_count_z: spirv::Word, payload: Option<spirv::Word>) -> BuildResult<()> {
+        let mut inst = mr::Instruction::new(spirv::Op::EmitMeshTasksEXT, None, None, vec![mr::Operand::IdRef(group_count_x), mr::Operand::IdRef(group_count_y), mr::Operand::IdRef(group_count_z)]);
+        if let Some(v) = payload {
+            inst.operands.push(mr::Operand::IdRef(v));
+        };
+        self.end_basic_block(inst)
+    }
 }
\ No newline at end of file
@@ -165,4 +165,18 @@ impl Builder {
         self.module.types_global_values.push(mr::Instruction::new(spirv::Op::TypeNamedBarrier, None, Some(id), vec![]));
         id
     }
+
+    /// Appends an OpTypeCooperativeMatrixKHR instruction and returns the result id.
+    pub fn type_cooperative_matrix_khr(&mut self, component_type: spirv::Word, scope: spirv::Word, rows: spirv::Word, columns: spirv::Word, usage: spirv::Word) -> spirv::Word {
+        let id = self.id();
+        self.module.types_global_values.push(mr::Instruction::new(spirv::Op::TypeCooperativeMatrixKHR, None, Some(id), vec![mr::Operand::IdRef(component_type), mr::Operand::IdScope(scope), mr::Operand::IdRef(rows), mr::Operand::IdRef(columns), mr::Operand::IdRef(usage)]));
+        id
+    }
+
+    /// Appends an OpTypeAccelerationStructureKHR instruction and returns the result id.
+    pub fn type_acceleration_structure_khr(&mut self) -> spirv::Word {
+        let id = self.id();
+        self.module.types_global_values.push(mr::Instruction::new(spirv::Op::TypeAccelerationStructureKHR, None, Some(id), vec![]));
+        id
+    }
 }
\ No newline at end of file
@@ -14,12 +14,19 @@
 
 #![cfg_attr(feature = "clippy", allow(too_many_arguments))]
 
+use grammar;
 use mr;
+use prelude::*;
 use spirv;
 
+use std::collections::{BTreeMap, BTreeSet};
 use std::result;
 use super::Error;
 
+
+/// The maximum number of words in an instruction, including the first one.
+const MAX_WORD_COUNT: usize = 0xffff;
+
 type BuildResult<T> = result::Result<T, Error>;
 
 /// The data representation builder.
@@ -46,6 +53,10 @@ type BuildResult<T> = result::Result<T, Error>;
 ///   forward referencing instruction, and then later fill the optional
 ///   `result_id` parameter of the target instruction with the same id.
 ///
+/// With [`set_infer_requirements`](#method.set_infer_requirements), the
+/// builder declares the capabilities and extensions the instructions built
+/// require, as listed in the grammar, when the module is finished.
+///
 /// Instructions belonging to the module (e.g., `OpDecorate`) can be appended
 /// at any time, no matter that a basic block is currently under construction
 /// or not. Intructions that can appear both in the module and basic block
@@ -60,7 +71,8 @@ type BuildResult<T> = result::Result<T, Error>;
 /// block construction (e.g., `OpFunction` and `OpLabel`).
 ///
 /// Errors returned are enumerants related to function structure from the
-/// [`Error`](enum.Error.html) enum.
+/// [`Error`](enum.Error.html) enum, and `DuplicateSpecId` for
+/// specialization constants given a `SpecId` already taken.
 ///
 /// # Examples
 ///
@@ -102,9 +114,17 @@ type BuildResult<T> = result::Result<T, Error>;
 #[derive(Default)]
 pub struct Builder {
     module: mr::Module,
-    next_id: u32,
+    ids: mr::IdAllocator,
     function: Option<mr::Function>,
     basic_block: Option<mr::BasicBlock>,
+    ext_insts: mr::ExtInstRegistry,
+    /// Specialization constants by the `SpecId` given them.
+    spec_ids: BTreeMap<u32, spirv::Word>,
+    infer_requirements: bool,
+    excluded_capabilities: BTreeSet<spirv::Capability>,
+    excluded_extensions: BTreeSet<String>,
+    /// The OpString instructions appended, by their strings.
+    strings: BTreeMap<String, spirv::Word>,
 }
 
 impl Builder {
@@ -112,24 +132,152 @@ impl Builder {
     pub fn new() -> Builder {
         Builder {
             module: mr::Module::new(),
-            next_id: 1,
+            ids: mr::IdAllocator::new(),
             function: None,
             basic_block: None,
+            ext_insts: mr::ExtInstRegistry::new(),
+            spec_ids: BTreeMap::new(),
+            infer_requirements: false,
+            excluded_capabilities: BTreeSet::new(),
+            excluded_extensions: BTreeSet::new(),
+            strings: BTreeMap::new(),
         }
     }
 
     /// Returns the `Module` under construction.
+    ///
+    /// If requirements are inferred, the
+    /// [`missing_requirements`](#method.missing_requirements) are declared
+    /// first.
     pub fn module(self) -> mr::Module {
+        let (capabilities, extensions) = if self.infer_requirements {
+            self.missing_requirements()
+        } else {
+            (vec![], vec![])
+        };
         let mut module = self.module;
-        module.header = Some(mr::ModuleHeader::new(self.next_id));
+        for capability in capabilities {
+            module.capabilities.push(mr::Instruction::new(
+                spirv::Op::Capability,
+                None,
+                None,
+                vec![mr::Operand::Capability(capability)],
+            ));
+        }
+        for name in extensions {
+            module.extensions.push(mr::Instruction::new(
+                spirv::Op::Extension,
+                None,
+                None,
+                vec![mr::Operand::LiteralString(name.to_owned())],
+            ));
+        }
+        module.header = Some(mr::ModuleHeader::new(self.ids.bound()));
         module
     }
 
+    /// Sets whether the capabilities and extensions required by the
+    /// instructions built but not declared are declared when the module is
+    /// finished. Off by default.
+    pub fn set_infer_requirements(&mut self, infer: bool) {
+        self.infer_requirements = infer;
+    }
+
+    /// Keeps `capability` from being declared by inference; another
+    /// alternative is declared instead, if any.
+    pub fn exclude_capability(&mut self, capability: spirv::Capability) {
+        self.excluded_capabilities.insert(capability);
+    }
+
+    /// Keeps the extension `name` from being declared by inference; another
+    /// alternative is declared instead, if any.
+    pub fn exclude_extension<T: Into<String>>(&mut self, name: T) {
+        self.excluded_extensions.insert(name.into());
+    }
+
+    /// Returns the requirements of the instructions built so far,
+    /// including those of atomics on the types of their values.
+    pub fn requirements<'a>(&'a self) -> mr::Requirements {
+        let block_insts = |b: &'a mr::BasicBlock| b.label.iter().chain(&b.instructions);
+        let function_insts = |f: &'a mr::Function| {
+            f.def.iter().chain(&f.parameters).chain(f.basic_blocks.iter().flat_map(block_insts))
+        };
+        let insts = self.module
+            .global_inst_iter()
+            .chain(self.module.functions.iter().chain(&self.function).flat_map(function_insts))
+            .chain(self.basic_block.iter().flat_map(block_insts));
+        mr::Requirements::of_all(insts)
+    }
+
+    /// Returns the capabilities and extensions inference would declare:
+    /// for each requirement of the instructions built so far, none of whose
+    /// alternatives is declared, the first alternative not excluded,
+    /// together with the extensions enabling it.
+    ///
+    /// Capabilities declared also declare those they depend on.
+    pub fn missing_requirements(&self) -> (Vec<spirv::Capability>, Vec<&'static str>) {
+        let mut declared = BTreeSet::new();
+        for inst in &self.module.capabilities {
+            if let Some(&mr::Operand::Capability(capability)) = inst.operands.first() {
+                declare(&mut declared, capability);
+            }
+        }
+        let mut extensions: Vec<&str> = self.module
+            .extensions
+            .iter()
+            .filter_map(|inst| match inst.operands.first() {
+                Some(mr::Operand::LiteralString(name)) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        let mut reqs = self.requirements();
+
+        let mut capabilities = vec![];
+        for alternatives in reqs.capabilities.clone() {
+            if alternatives.iter().any(|c| declared.contains(c)) {
+                continue;
+            }
+            let first = alternatives.iter().find(|c| !self.excluded_capabilities.contains(c));
+            if let Some(&capability) = first {
+                declare(&mut declared, capability);
+                capabilities.push(capability);
+                reqs.add(&mr::Requirements::of(&mr::Instruction::new(
+                    spirv::Op::Capability,
+                    None,
+                    None,
+                    vec![mr::Operand::Capability(capability)],
+                )));
+            }
+        }
+        let mut added = vec![];
+        for alternatives in reqs.extensions {
+            if alternatives.iter().any(|name| extensions.contains(name)) {
+                continue;
+            }
+            let first = alternatives.iter().find(|&&name| !self.excluded_extensions.contains(name));
+            if let Some(&name) = first {
+                extensions.push(name);
+                added.push(name);
+            }
+        }
+        (capabilities, added)
+    }
+
+    /// Returns the module under construction, as built so far, without
+    /// the function and basic block under construction.
+    pub fn module_ref(&self) -> &mr::Module {
+        &self.module
+    }
+
     /// Returns the next unused id.
     pub fn id(&mut self) -> spirv::Word {
-        let id = self.next_id;
-        self.next_id += 1;
-        id
+        self.ids.id()
+    }
+
+    /// Returns the allocator of the ids of the module under construction,
+    /// e.g., to reserve ranges or recycle ids.
+    pub fn id_allocator(&mut self) -> &mut mr::IdAllocator {
+        &mut self.ids
     }
 
     /// Begins building of a new function.
@@ -271,10 +419,81 @@ impl Builder {
             Some(id),
             vec![mr::Operand::LiteralString(extended_inst_set.into())],
         );
+        self.ext_insts.track(&inst);
         self.module.ext_inst_imports.push(inst);
         id
     }
 
+    /// Returns the id of the `NonSemantic.Shader.DebugInfo.100` extended
+    /// instruction set, importing it first if needed.
+    ///
+    /// The first import also declares the `SPV_KHR_non_semantic_info`
+    /// extension, which non-semantic instruction sets require.
+    pub fn debug_info_import(&mut self) -> spirv::Word {
+        self.non_semantic_import(grammar::ExtInstSet::DebugInfo100)
+    }
+
+    /// Returns the id of the `NonSemantic.DebugPrintf` extended instruction
+    /// set, importing it first if needed.
+    ///
+    /// The first import also declares the `SPV_KHR_non_semantic_info`
+    /// extension, which non-semantic instruction sets require.
+    pub fn debug_printf_import(&mut self) -> spirv::Word {
+        self.non_semantic_import(grammar::ExtInstSet::DebugPrintf)
+    }
+
+    /// Returns the id of the `NonSemantic.ClspvReflection` extended
+    /// instruction set, importing its latest version first if needed.
+    ///
+    /// The first import also declares the `SPV_KHR_non_semantic_info`
+    /// extension, which non-semantic instruction sets require.
+    pub fn clspv_reflection_import(&mut self) -> spirv::Word {
+        self.non_semantic_import(grammar::ExtInstSet::ClspvReflection)
+    }
+
+    fn non_semantic_import(&mut self, set: grammar::ExtInstSet) -> spirv::Word {
+        match self.ext_insts.id_of(set) {
+            Some(id) => id,
+            None => {
+                self.extension("SPV_KHR_non_semantic_info");
+                self.ext_inst_import(set.name())
+            }
+        }
+    }
+
+    /// Appends an OpSource instruction embedding the source `text`, split
+    /// over as many OpSourceContinued instructions as needed to keep every
+    /// instruction within the maximum word count.
+    ///
+    /// Strings are split on character boundaries, so that each piece is
+    /// valid UTF-8 by itself.
+    pub fn embed_source(
+        &mut self,
+        source_language: spirv::SourceLanguage,
+        version: u32,
+        file: Option<spirv::Word>,
+        text: &str,
+    ) {
+        // The opcode, language, and version words, plus the file id.
+        let fixed_words = 3 + file.map_or(0, |_| 1);
+        let (first, mut rest) = split_string(text, MAX_WORD_COUNT - fixed_words);
+        self.source(source_language, version, file, Some(first));
+        while !rest.is_empty() {
+            let (piece, remaining) = split_string(rest, MAX_WORD_COUNT - 1);
+            self.source_continued(piece);
+            rest = remaining;
+        }
+    }
+
+    /// Appends the given non-semantic instruction to either the current
+    /// basic block or the module if no basic block is under construction.
+    fn non_semantic_inst(&mut self, inst: mr::Instruction) {
+        match self.basic_block {
+            Some(ref mut bb) => bb.instructions.push(inst),
+            None => self.module.types_global_values.push(inst),
+        }
+    }
+
     /// Appends an OpMemoryModel instruction.
     pub fn memory_model(
         &mut self,
@@ -332,6 +551,36 @@ impl Builder {
         let inst = mr::Instruction::new(spirv::Op::ExecutionMode, None, None, operands);
         self.module.execution_modes.push(inst);
     }
+
+    /// Appends the OpExecutionMode instructions a mesh shader entry point
+    /// needs: its output `topology`, one of `OutputPoints`,
+    /// `OutputLinesEXT`, and `OutputTrianglesEXT`, and the maximal numbers
+    /// of vertices and primitives it outputs.
+    pub fn mesh_outputs(
+        &mut self,
+        entry_point: spirv::Word,
+        topology: spirv::ExecutionMode,
+        max_vertices: u32,
+        max_primitives: u32,
+    ) {
+        self.execution_mode(entry_point, topology, []);
+        self.execution_mode(entry_point, spirv::ExecutionMode::OutputVertices, [max_vertices]);
+        self.execution_mode(
+            entry_point,
+            spirv::ExecutionMode::OutputPrimitivesEXT,
+            [max_primitives],
+        );
+    }
+}
+
+/// Splits `s` into a prefix that fits, nul-terminated, within `words`
+/// words, and the rest.
+fn split_string(s: &str, words: usize) -> (&str, &str) {
+    let mut end = s.len().min(words * 4 - 1);
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    s.split_at(end)
 }
 
 include!("build_type.rs");
@@ -339,6 +588,9 @@ include!("build_constant.rs");
 include!("build_annotation.rs");
 include!("build_terminator.rs");
 include!("build_debug.rs");
+include!("build_debug_info.rs");
+include!("build_debug_printf.rs");
+include!("build_clspv_reflection.rs");
 
 impl Builder {
     /// Appends an OpDecorationGroup instruction and returns the result id.
@@ -353,23 +605,51 @@ impl Builder {
         id
     }
 
+    /// Appends an OpString instruction for `s` and returns the result id,
+    /// or returns the result id of the one already appended for `s`.
+    ///
+    /// Strings are thus interned, e.g., to name the file of
+    /// [`embed_source`](#method.embed_source) and of OpLine instructions
+    /// once however many times they are used. Builders always start from
+    /// an empty module, so every OpString of the module is appended here
+    /// and interned.
     pub fn string<T: Into<String>>(&mut self, s: T) -> spirv::Word {
+        let s = s.into();
+        if let Some(&id) = self.strings.get(&s) {
+            return id;
+        }
         let id = self.id();
+        self.strings.insert(s.clone(), id);
         self.module.debugs.push(mr::Instruction::new(
             spirv::Op::String,
             None,
             Some(id),
-            vec![mr::Operand::LiteralString(s.into())],
+            vec![mr::Operand::LiteralString(s)],
         ));
         id
     }
 
-    #[allow(unused_variables)]
+    /// Appends an OpLine instruction to either the current basic block or
+    /// the module if no basic block is under construction.
     pub fn line(&mut self, file: spirv::Word, line: spirv::Word, column: spirv::Word) {
-        unimplemented!()
+        let inst = mr::Instruction::new(
+            spirv::Op::Line,
+            None,
+            None,
+            vec![
+                mr::Operand::IdRef(file),
+                mr::Operand::LiteralInt32(line),
+                mr::Operand::LiteralInt32(column),
+            ],
+        );
+        self.non_semantic_inst(inst);
     }
+
+    /// Appends an OpNoLine instruction to either the current basic block
+    /// or the module if no basic block is under construction.
     pub fn no_line(&mut self) {
-        unimplemented!()
+        let inst = mr::Instruction::new(spirv::Op::NoLine, None, None, vec![]);
+        self.non_semantic_inst(inst);
     }
 }
 
@@ -414,6 +694,76 @@ impl Builder {
         id
     }
 
+    /// Declares the `PhysicalStorageBufferAddresses` capability and its
+    /// extension, unless already declared, and appends an OpMemoryModel
+    /// instruction with the `PhysicalStorageBuffer64` addressing model.
+    pub fn physical_storage_buffer_addressing(&mut self, memory_model: spirv::MemoryModel) {
+        let capability = spirv::Capability::PhysicalStorageBufferAddresses;
+        if !self.module.capabilities.iter().any(|inst| {
+            inst.operands.first() == Some(&mr::Operand::Capability(capability))
+        }) {
+            self.capability(capability);
+        }
+        let extension = "SPV_KHR_physical_storage_buffer";
+        if !self.module.extensions.iter().any(|inst| {
+            inst.operands.first() == Some(&mr::Operand::from(extension))
+        }) {
+            self.extension(extension);
+        }
+        self.memory_model(spirv::AddressingModel::PhysicalStorageBuffer64, memory_model);
+    }
+
+    /// Appends an OpTypePointer instruction to `pointee_type` in the
+    /// `PhysicalStorageBuffer` storage class and returns the result id.
+    pub fn type_buffer_reference(&mut self, pointee_type: spirv::Word) -> spirv::Word {
+        self.type_pointer(None, spirv::StorageClass::PhysicalStorageBuffer, pointee_type)
+    }
+
+    /// Appends an OpTypeForwardPointer instruction for a new pointer type
+    /// in the `PhysicalStorageBuffer` storage class and returns its id.
+    ///
+    /// Forward references let structs refer to themselves, e.g., in linked
+    /// lists; the pointer type is defined later with
+    /// [`type_pointer`](#method.type_pointer) and the id returned.
+    pub fn forward_buffer_reference(&mut self) -> spirv::Word {
+        let id = self.id();
+        self.type_forward_pointer(id, spirv::StorageClass::PhysicalStorageBuffer);
+        id
+    }
+
+    /// Appends an OpLoad instruction through `pointer` with the `Aligned`
+    /// memory operand, as loads through buffer references require.
+    pub fn load_aligned(
+        &mut self,
+        result_type: spirv::Word,
+        pointer: spirv::Word,
+        alignment: u32,
+    ) -> BuildResult<spirv::Word> {
+        self.load(
+            result_type,
+            None,
+            pointer,
+            Some(spirv::MemoryAccess::ALIGNED),
+            [mr::Operand::LiteralInt32(alignment)],
+        )
+    }
+
+    /// Appends an OpStore instruction through `pointer` with the `Aligned`
+    /// memory operand, as stores through buffer references require.
+    pub fn store_aligned(
+        &mut self,
+        pointer: spirv::Word,
+        object: spirv::Word,
+        alignment: u32,
+    ) -> BuildResult<()> {
+        self.store(
+            pointer,
+            object,
+            Some(spirv::MemoryAccess::ALIGNED),
+            [mr::Operand::LiteralInt32(alignment)],
+        )
+    }
+
     /// Appends an OpTypeOpaque instruction and returns the result id.
     pub fn type_opaque<T: Into<String>>(&mut self, type_name: T) -> spirv::Word {
         let id = self.id();
@@ -454,6 +804,60 @@ impl Builder {
         id
     }
 
+    /// Appends an OpConstant instruction with the given 64-bit integer `value`.
+    pub fn constant_u64(&mut self, result_type: spirv::Word, value: u64) -> spirv::Word {
+        let id = self.id();
+        let inst = mr::Instruction::new(
+            spirv::Op::Constant,
+            Some(result_type),
+            Some(id),
+            vec![mr::Operand::LiteralInt64(value)],
+        );
+        self.module.types_global_values.push(inst);
+        id
+    }
+
+    /// Appends an OpConstant instruction of the given 32-bit integer type
+    /// for `scope`, as taken by the scope operands of group and subgroup
+    /// instructions.
+    pub fn constant_scope(&mut self, result_type: spirv::Word, scope: spirv::Scope) -> spirv::Word {
+        self.constant_u32(result_type, scope as u32)
+    }
+
+    /// Returns the id of the type or constant with the given opcode, result
+    /// type, and operands declared before, appending it if there is none.
+    ///
+    /// Unlike the other methods declaring types and constants, which always
+    /// append, this declares each of them once, e.g., for the types and
+    /// constituents of [`constant_composite_from`](#method.constant_composite_from).
+    pub fn find_or_add_global(
+        &mut self,
+        opcode: spirv::Op,
+        result_type: Option<spirv::Word>,
+        operands: Vec<mr::Operand>,
+    ) -> spirv::Word {
+        let existing = self.module.types_global_values.iter().find(|inst| {
+            inst.class.opcode == opcode
+                && inst.result_type == result_type
+                && inst.operands == operands
+        });
+        if let Some(id) = existing.and_then(|inst| inst.result_id) {
+            return id;
+        }
+        let id = self.id();
+        let inst = mr::Instruction::new(opcode, result_type, Some(id), operands);
+        self.module.types_global_values.push(inst);
+        id
+    }
+
+    /// Declares `value` as a constant, together with its type and its
+    /// constituents, and returns the result id of the constant.
+    ///
+    /// See [`AsSpirvConst`](trait.AsSpirvConst.html) for the values taken.
+    pub fn constant_composite_from<T: mr::AsSpirvConst>(&mut self, value: &T) -> spirv::Word {
+        value.spirv_const(self)
+    }
+
     /// Appends an OpSpecConstant instruction with the given 32-bit float `value`.
     /// or the module if no basic block is under construction.
     pub fn spec_constant_f32(&mut self, result_type: spirv::Word, value: f32) -> spirv::Word {
@@ -482,6 +886,79 @@ impl Builder {
         id
     }
 
+    /// Appends an OpSpecConstant instruction with the given 32-bit integer
+    /// `default` value, decorated with `spec_id` as its `SpecId`.
+    ///
+    /// Returns `DuplicateSpecId`, appending nothing, if a specialization
+    /// constant was already given `spec_id` by this builder.
+    pub fn spec_constant_u32_with_id(
+        &mut self,
+        result_type: spirv::Word,
+        default: u32,
+        spec_id: u32,
+    ) -> BuildResult<spirv::Word> {
+        self.check_spec_id(spec_id)?;
+        let id = self.spec_constant_u32(result_type, default);
+        Ok(self.decorate_spec_id(id, spec_id))
+    }
+
+    /// Appends an OpSpecConstant instruction with the given 32-bit float
+    /// `default` value, decorated with `spec_id` as its `SpecId`.
+    ///
+    /// Returns `DuplicateSpecId` like
+    /// [`spec_constant_u32_with_id`](#method.spec_constant_u32_with_id).
+    pub fn spec_constant_f32_with_id(
+        &mut self,
+        result_type: spirv::Word,
+        default: f32,
+        spec_id: u32,
+    ) -> BuildResult<spirv::Word> {
+        self.check_spec_id(spec_id)?;
+        let id = self.spec_constant_f32(result_type, default);
+        Ok(self.decorate_spec_id(id, spec_id))
+    }
+
+    /// Appends an OpSpecConstantTrue or OpSpecConstantFalse instruction for
+    /// the `default` value, decorated with `spec_id` as its `SpecId`.
+    ///
+    /// Returns `DuplicateSpecId` like
+    /// [`spec_constant_u32_with_id`](#method.spec_constant_u32_with_id).
+    pub fn spec_constant_bool_with_id(
+        &mut self,
+        result_type: spirv::Word,
+        default: bool,
+        spec_id: u32,
+    ) -> BuildResult<spirv::Word> {
+        self.check_spec_id(spec_id)?;
+        let id = if default {
+            self.spec_constant_true(result_type)
+        } else {
+            self.spec_constant_false(result_type)
+        };
+        Ok(self.decorate_spec_id(id, spec_id))
+    }
+
+    /// Returns the specialization constant given `spec_id` by this builder.
+    pub fn spec_constant_by_id(&self, spec_id: u32) -> Option<spirv::Word> {
+        self.spec_ids.get(&spec_id).cloned()
+    }
+
+    fn check_spec_id(&self, spec_id: u32) -> BuildResult<()> {
+        if self.spec_ids.contains_key(&spec_id) {
+            Err(Error::DuplicateSpecId)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Decorates the specialization constant `id` with `spec_id`, and
+    /// returns `id`.
+    fn decorate_spec_id(&mut self, id: spirv::Word, spec_id: u32) -> spirv::Word {
+        self.decorate(id, spirv::Decoration::SpecId, vec![mr::Operand::LiteralInt32(spec_id)]);
+        self.spec_ids.insert(spec_id, id);
+        id
+    }
+
     /// Appends an OpVariable instruction to either the current basic block
     /// or the module if no basic block is under construction.
     pub fn variable(
@@ -508,6 +985,41 @@ impl Builder {
         id
     }
 
+    /// Appends an OpVariable instruction with the given `initializer` to
+    /// either the current basic block or the module, like
+    /// [`variable`](#method.variable).
+    ///
+    /// Returns `WrongInitializer`, appending nothing, unless `initializer`
+    /// is a constant or a module-scope variable declared before, of the
+    /// type `result_type` points to.
+    pub fn initialized_variable(
+        &mut self,
+        result_type: spirv::Word,
+        result_id: Option<spirv::Word>,
+        storage_class: spirv::StorageClass,
+        initializer: spirv::Word,
+    ) -> BuildResult<spirv::Word> {
+        let globals = &self.module.types_global_values;
+        let pointee = globals
+            .iter()
+            .find(|inst| {
+                inst.class.opcode == spirv::Op::TypePointer && inst.result_id == Some(result_type)
+            })
+            .and_then(|inst| inst.operands.get(1))
+            .and_then(mr::Operand::id);
+        let valid = globals.iter().any(|inst| {
+            inst.result_id == Some(initializer)
+                && inst.result_type.is_some()
+                && inst.result_type == pointee
+                && (grammar::reflect::is_constant(inst.class.opcode)
+                    || inst.class.opcode == spirv::Op::Variable)
+        });
+        if !valid {
+            return Err(Error::WrongInitializer);
+        }
+        Ok(self.variable(result_type, result_id, storage_class, Some(initializer)))
+    }
+
     /// Appends an OpUndef instruction to either the current basic block
     /// or the module if no basic block is under construction.
     pub fn undef(
@@ -527,19 +1039,53 @@ impl Builder {
         }
         id
     }
+
+    /// Appends an OpSwitch instruction on a 64-bit `selector` and ends the
+    /// current basic block.
+    ///
+    /// Unlike [`switch`](#method.switch), the case literals are 64-bit
+    /// integers, each encoded in two words.
+    pub fn switch64<T: AsRef<[(u64, spirv::Word)]>>(
+        &mut self,
+        selector: spirv::Word,
+        default: spirv::Word,
+        target: T,
+    ) -> BuildResult<()> {
+        let mut inst = mr::Instruction::new(
+            spirv::Op::Switch,
+            None,
+            None,
+            vec![mr::Operand::IdRef(selector), mr::Operand::IdRef(default)],
+        );
+        for &(literal, label) in target.as_ref() {
+            inst.operands.push(mr::Operand::LiteralInt64(literal));
+            inst.operands.push(mr::Operand::IdRef(label));
+        }
+        self.end_basic_block(inst)
+    }
+}
+
+/// Adds `capability` and those it implicitly declares to `declared`.
+fn declare(declared: &mut BTreeSet<spirv::Capability>, capability: spirv::Capability) {
+    if declared.insert(capability) {
+        for &implied in grammar::reflect::implied_capabilities(capability) {
+            declare(declared, implied);
+        }
+    }
 }
 
 include!("build_norm_insts.rs");
 
 #[cfg(test)]
 mod tests {
+    use grammar;
     use mr;
     use spirv;
 
     use std::f32;
     use super::Builder;
 
-    use binary::Disassemble;
+    use binary::{Assemble, Disassemble};
 
     fn has_only_one_global_inst(module: &mr::Module) -> bool {
         if !module.functions.is_empty() {
@@ -676,6 +1222,85 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_infer_requirements() {
+        let mut b = Builder::new();
+        b.set_infer_requirements(true);
+        b.capability(spirv::Capability::StorageBuffer16BitAccess);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let fty = b.type_function(void, vec![]);
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::Geometry, f, "main", vec![]);
+        assert_eq!(b.missing_requirements(),
+                   (vec![spirv::Capability::Shader, spirv::Capability::Geometry],
+                    vec!["SPV_KHR_16bit_storage"]));
+        b.exclude_capability(spirv::Capability::Geometry);
+        let m = b.module();
+        let capabilities: Vec<&mr::Operand> =
+            m.capabilities.iter().map(|inst| &inst.operands[0]).collect();
+        assert_eq!(capabilities,
+                   vec![&mr::Operand::Capability(spirv::Capability::StorageBuffer16BitAccess),
+                        &mr::Operand::Capability(spirv::Capability::Shader)]);
+        assert_eq!(m.extensions[0].operands, vec![mr::Operand::from("SPV_KHR_16bit_storage")]);
+    }
+
+    #[test]
+    fn test_infer_atomic_requirements() {
+        let mut b = Builder::new();
+        b.set_infer_requirements(true);
+        b.capability(spirv::Capability::Shader);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let ulong = b.type_int(64, 0);
+        let double = b.type_float(64);
+        let fty = b.type_function(void, vec![]);
+        let device = b.constant_u32(uint, spirv::Scope::Device as u32);
+        let relaxed = b.constant_u32(uint, 0);
+        let one = b.constant_u64(ulong, 1);
+        let half = b.undef(double, None);
+        let ulong_ptr = b.type_pointer(None, spirv::StorageClass::Workgroup, ulong);
+        let double_ptr = b.type_pointer(None, spirv::StorageClass::Workgroup, double);
+        let counter = b.variable(ulong_ptr, None, spirv::StorageClass::Workgroup, None);
+        let sum = b.variable(double_ptr, None, spirv::StorageClass::Workgroup, None);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.atomic_store(counter, device, relaxed, one).unwrap();
+        assert_eq!(b.missing_requirements(), (vec![spirv::Capability::Int64Atomics], vec![]));
+        b.atomic_fadd_ext(double, None, sum, device, relaxed, half).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        assert_eq!(b.missing_requirements(),
+                   (vec![spirv::Capability::Int64Atomics,
+                         spirv::Capability::AtomicFloat64AddEXT],
+                    vec!["SPV_EXT_shader_atomic_float_add"]));
+    }
+
+    #[test]
+    fn test_spec_constant_with_id() {
+        let mut b = Builder::new();
+        let uint = b.type_int(32, 0);
+        let boolean = b.type_bool();
+        let size = b.spec_constant_u32_with_id(uint, 64, 0).unwrap();
+        let flag = b.spec_constant_bool_with_id(boolean, false, 1).unwrap();
+        assert!(b.spec_constant_u32_with_id(uint, 32, 0).is_err());
+        assert_eq!(b.spec_constant_by_id(0), Some(size));
+        let m = b.module();
+
+        assert_eq!(m.types_global_values.len(), 4);
+        assert_eq!(m.types_global_values[3].class.opcode, spirv::Op::SpecConstantFalse);
+        assert_eq!(m.annotations.len(), 2);
+        let inst = &m.annotations[1];
+        assert_eq!(inst.operands,
+                   vec![mr::Operand::IdRef(flag),
+                        mr::Operand::Decoration(spirv::Decoration::SpecId),
+                        mr::Operand::LiteralInt32(1)]);
+    }
+
     #[test]
     fn test_spec_constant_f32() {
         let mut b = Builder::new();
@@ -943,4 +1568,105 @@ mod tests {
                     OpFunctionEnd"
         );
     }
+
+    #[test]
+    fn test_build_switch64() {
+        let mut b = Builder::new();
+        let void = b.type_void();
+        let ulong = b.type_int(64, 0);
+        let selector = b.constant_u64(ulong, 0x1_0000_0002);
+        let voidfvoid = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let (case, default) = (b.id(), b.id());
+        b.switch64(selector, default, vec![(0x1_0000_0002, case)]).unwrap();
+        b.end_function().unwrap();
+        let m = b.module();
+
+        assert_eq!(m.types_global_values[2].operands,
+                   vec![mr::Operand::LiteralInt64(0x1_0000_0002)]);
+        let inst = &m.functions[0].basic_blocks[0].instructions[0];
+        // Each case literal takes two words, low-order word first.
+        assert_eq!(inst.assemble(),
+                   vec![(6 << 16) | spirv::Op::Switch as u32, selector, default, 2, 1, case]);
+    }
+
+    #[test]
+    fn test_string_interning() {
+        let mut b = Builder::new();
+        let file = b.string("main.glsl");
+        let other = b.string(String::from("other.glsl"));
+        assert_eq!(b.string(String::from("main.glsl")), file);
+        assert_ne!(file, other);
+        let m = b.module();
+
+        let strings: Vec<_> = m.debugs
+            .iter()
+            .map(|inst| (inst.class.opcode, inst.result_id, inst.operands[0].clone()))
+            .collect();
+        assert_eq!(strings,
+                   vec![(spirv::Op::String, Some(file), mr::Operand::from("main.glsl")),
+                        (spirv::Op::String, Some(other), mr::Operand::from("other.glsl"))]);
+    }
+
+    #[test]
+    fn test_embed_source() {
+        let mut b = Builder::new();
+        let file = b.string("long.hlsl");
+        // Two-byte characters, offset by one byte, so that the word limits
+        // fall in the middle of a character.
+        let text = format!("x{}", "\u{e9}".repeat(300_000));
+        b.embed_source(spirv::SourceLanguage::HLSL, 500, Some(file), &text);
+        // Strings are interned.
+        assert_eq!(b.string("long.hlsl"), file);
+        let m = b.module();
+
+        assert_eq!(m.debugs.len(), 4);
+        assert_eq!(m.debugs[1].class.opcode, spirv::Op::Source);
+        assert_eq!(m.debugs[2].class.opcode, spirv::Op::SourceContinued);
+        assert_eq!(m.debugs[3].class.opcode, spirv::Op::SourceContinued);
+        for inst in &m.debugs {
+            assert!(inst.assemble().len() <= 0xffff);
+        }
+
+        let sources = m.embedded_sources();
+        assert_eq!(sources,
+                   vec![mr::EmbeddedSource {
+                            language: spirv::SourceLanguage::HLSL,
+                            version: 500,
+                            file: Some("long.hlsl".to_string()),
+                            source: text,
+                        }]);
+    }
+
+    #[test]
+    fn test_ext_inst_symbolic() {
+        let mut b = Builder::new();
+        let glsl = b.ext_inst_import("GLSL.std.450");
+        let unknown = b.ext_inst_import("Unknown.set");
+        let float = b.type_float(32);
+        let fty = b.type_function(float, vec![]);
+        b.begin_function(float, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let one = b.undef(float, None);
+        b.ext_inst(float, None, glsl, spirv::GLOp::Sqrt as u32, vec![one]).unwrap();
+        b.ext_inst(float, None, unknown, 31, vec![one]).unwrap();
+        b.ret_value(one).unwrap();
+        b.end_function().unwrap();
+        let m = b.module();
+
+        let insts = &m.functions[0].basic_blocks[0].instructions;
+        assert_eq!(insts[1].operands[1],
+                   mr::Operand::ExtInstOpcode(grammar::ExtInstSet::GlslStd450, 31));
+        assert_eq!(insts[2].operands[1], mr::Operand::LiteralExtInstInteger(31));
+        assert_eq!(insts[1].operands[1].to_string(), "Sqrt");
+    }
+
+    #[test]
+    fn test_non_semantic_import_reuses_other_versions() {
+        let mut b = Builder::new();
+        let clspv = b.ext_inst_import("NonSemantic.ClspvReflection.5");
+        assert_eq!(b.clspv_reflection_import(), clspv);
+        assert_eq!(b.module().ext_inst_imports.len(), 1);
+    }
 }
@@ -0,0 +1,224 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use binary::Assemble;
+use mr;
+use prelude::*;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+use std::mem;
+
+/// A part of a module that changes independently of the others: a section
+/// of the logical layout, or a function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Section {
+    Header,
+    Capabilities,
+    Extensions,
+    ExtInstImports,
+    MemoryModel,
+    EntryPoints,
+    ExecutionModes,
+    Debugs,
+    Annotations,
+    TypesGlobalValues,
+    /// The function of the given id, which may be added or removed.
+    Function(Word),
+}
+
+/// The sections of a module changed since the changes were last taken.
+///
+/// Until then, e.g., for a module just loaded or built, all sections count
+/// as changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Changes {
+    tracked: bool,
+    sections: BTreeSet<Section>,
+}
+
+impl Changes {
+    /// Returns true if all sections count as changed.
+    pub fn is_all(&self) -> bool {
+        !self.tracked
+    }
+
+    /// Returns true if `section` changed.
+    pub fn contains(&self, section: Section) -> bool {
+        !self.tracked || self.sections.contains(&section)
+    }
+
+    /// Returns the sections marked as changed, which are all that did
+    /// unless [`is_all`](#method.is_all).
+    pub fn sections(&self) -> &BTreeSet<Section> {
+        &self.sections
+    }
+}
+
+impl mr::Module {
+    /// Marks `section` as changed.
+    ///
+    /// Changes to the public fields of the module are not tracked; whoever
+    /// edits them marks what they edit, here or with
+    /// [`record_edits`](#method.record_edits).
+    pub fn touch(&mut self, section: Section) {
+        self.changes.sections.insert(section);
+    }
+
+    /// Runs `edit` on the module, and marks the sections and functions it
+    /// changed.
+    ///
+    /// The sections are compared as assembled before and after, so `edit`
+    /// may change the public fields freely. Functions without a result id
+    /// cannot be marked.
+    pub fn record_edits<F, R>(&mut self, edit: F) -> R
+        where F: FnOnce(&mut mr::Module) -> R
+    {
+        let before = sections(self);
+        let result = edit(self);
+        let after = sections(self);
+        for (&section, words) in &after {
+            if before.get(&section) != Some(words) {
+                self.touch(section);
+            }
+        }
+        for &section in before.keys() {
+            if !after.contains_key(&section) {
+                self.touch(section);
+            }
+        }
+        result
+    }
+
+    /// Returns the function of the given `id` for editing, and marks it as
+    /// changed.
+    pub fn function_mut(&mut self, id: Word) -> Option<&mut mr::Function> {
+        let index = self.functions
+            .iter()
+            .position(|f| f.def.as_ref().and_then(|def| def.result_id) == Some(id))?;
+        self.touch(Section::Function(id));
+        Some(&mut self.functions[index])
+    }
+
+    /// Returns the sections changed since the changes were last taken.
+    pub fn changes(&self) -> &Changes {
+        &self.changes
+    }
+
+    /// Returns the sections changed since the changes were last taken, and
+    /// tracks changes anew from now on.
+    pub fn take_changes(&mut self) -> Changes {
+        let fresh = Changes {
+            tracked: true,
+            sections: BTreeSet::new(),
+        };
+        mem::replace(&mut self.changes, fresh)
+    }
+}
+
+/// Returns the words of the sections and functions of `module`.
+fn sections(module: &mr::Module) -> BTreeMap<Section, Vec<u32>> {
+    fn words<'i, I>(insts: I) -> Vec<u32>
+        where I: IntoIterator<Item = &'i mr::Instruction>
+    {
+        insts.into_iter().flat_map(|inst| inst.assemble()).collect()
+    }
+    let mut sections = BTreeMap::new();
+    sections.insert(Section::Header,
+                    module.header.as_ref().map_or(vec![], |header| header.assemble()));
+    sections.insert(Section::Capabilities, words(&module.capabilities));
+    sections.insert(Section::Extensions, words(&module.extensions));
+    sections.insert(Section::ExtInstImports, words(&module.ext_inst_imports));
+    sections.insert(Section::MemoryModel, words(&module.memory_model));
+    sections.insert(Section::EntryPoints, words(&module.entry_points));
+    sections.insert(Section::ExecutionModes, words(&module.execution_modes));
+    sections.insert(Section::Debugs, words(&module.debugs));
+    sections.insert(Section::Annotations, words(&module.annotations));
+    sections.insert(Section::TypesGlobalValues, words(&module.types_global_values));
+    for f in &module.functions {
+        if let Some(id) = f.def.as_ref().and_then(|def| def.result_id) {
+            let code: &mut Vec<u32> = sections.entry(Section::Function(id)).or_default();
+            code.extend(f.assemble());
+        }
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::Section;
+
+    #[test]
+    fn test_changes() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let voidf = b.type_function(void, vec![]);
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        assert!(m.changes().is_all());
+        assert!(m.take_changes().contains(Section::Capabilities));
+        assert!(!m.changes().contains(Section::Function(f)));
+
+        m.touch(Section::Annotations);
+        assert!(m.function_mut(f).is_some());
+        assert!(m.function_mut(void).is_none());
+        let changes = m.take_changes();
+        assert!(!changes.is_all());
+        assert_eq!(changes.sections().iter().cloned().collect::<Vec<_>>(),
+                   vec![Section::Annotations, Section::Function(f)]);
+        assert!(m.changes().sections().is_empty());
+    }
+
+    #[test]
+    fn test_record_edits() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let voidf = b.type_function(void, vec![]);
+        let mut functions = vec![];
+        for _ in 0..2 {
+            let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+            b.begin_basic_block(None).unwrap();
+            b.ret().unwrap();
+            b.end_function().unwrap();
+            functions.push(f);
+        }
+        let mut m = b.module();
+        m.take_changes();
+
+        // Edits that change nothing are not marked, removed functions are.
+        let count = m.record_edits(|m| {
+            let nop = mr::Instruction::new(spirv::Op::Nop, None, None, vec![]);
+            m.functions[0].basic_blocks[0].instructions.insert(0, nop);
+            m.functions.pop();
+            m.capabilities.clear();
+            2
+        });
+        assert_eq!(count, 2);
+        assert_eq!(m.take_changes().sections().iter().cloned().collect::<Vec<_>>(),
+                   vec![Section::Function(functions[0]), Section::Function(functions[1])]);
+
+        m.record_edits(|m| m.annotations.extend(m.debugs.drain(..)));
+        assert!(m.take_changes().sections().is_empty());
+        m.record_edits(|m| m.header.as_mut().unwrap().bound += 1);
+        assert_eq!(m.take_changes().sections().iter().cloned().collect::<Vec<_>>(),
+                   vec![Section::Header]);
+    }
+}
@@ -0,0 +1,205 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use prelude::*;
+use spirv;
+
+use spirv::Word;
+
+/// Rust values declarable as SPIR-V constants, e.g., lookup tables, with
+/// [`Builder::constant_composite_from`](struct.Builder.html#method.constant_composite_from).
+///
+/// Booleans, 32- and 64-bit integers and floats are declared as scalars,
+/// arrays as arrays of their elements, and tuples as structs of their
+/// fields. Types and constants are declared once, with
+/// [`Builder::find_or_add_global`](struct.Builder.html#method.find_or_add_global),
+/// and shared by all the constants using them, including structs with the
+/// same members. Declaring 64-bit values, or empty arrays, is left to
+/// produce modules the `Int64` and `Float64` capabilities or validation
+/// have to take care of.
+///
+/// Structs implement it by declaring their members, e.g.:
+///
+/// ```
+/// extern crate rspirv;
+/// extern crate spirv_headers as spirv;
+///
+/// use rspirv::mr::{AsSpirvConst, Builder, Operand};
+///
+/// struct Light {
+///     color: [f32; 3],
+///     intensity: f32,
+/// }
+///
+/// impl AsSpirvConst for Light {
+///     fn spirv_type(b: &mut Builder) -> spirv::Word {
+///         let members = vec![Operand::IdRef(<[f32; 3]>::spirv_type(b)),
+///                            Operand::IdRef(f32::spirv_type(b))];
+///         b.find_or_add_global(spirv::Op::TypeStruct, None, members)
+///     }
+///
+///     fn spirv_const(&self, b: &mut Builder) -> spirv::Word {
+///         let ty = Light::spirv_type(b);
+///         let members = vec![Operand::IdRef(self.color.spirv_const(b)),
+///                            Operand::IdRef(self.intensity.spirv_const(b))];
+///         b.find_or_add_global(spirv::Op::ConstantComposite, Some(ty), members)
+///     }
+/// }
+///
+/// fn main() {
+///     let mut b = Builder::new();
+///     let lights = [Light { color: [1.0, 0.5, 0.0], intensity: 2.0 },
+///                   Light { color: [0.0, 0.5, 1.0], intensity: 1.0 }];
+///     b.constant_composite_from(&lights);
+///     // Seven types and lengths, the four floats, and five composites.
+///     assert_eq!(b.module().types_global_values.len(), 16);
+/// }
+/// ```
+pub trait AsSpirvConst {
+    /// Declares the type of the values, and returns its result id.
+    fn spirv_type(builder: &mut mr::Builder) -> Word where Self: Sized;
+
+    /// Declares this value, and returns its result id.
+    fn spirv_const(&self, builder: &mut mr::Builder) -> Word;
+}
+
+fn struct_type(builder: &mut mr::Builder, members: Vec<Word>) -> Word {
+    let members = members.into_iter().map(mr::Operand::IdRef).collect();
+    builder.find_or_add_global(spirv::Op::TypeStruct, None, members)
+}
+
+fn composite(builder: &mut mr::Builder, ty: Word, constituents: Vec<Word>) -> Word {
+    let constituents = constituents.into_iter().map(mr::Operand::IdRef).collect();
+    builder.find_or_add_global(spirv::Op::ConstantComposite, Some(ty), constituents)
+}
+
+impl AsSpirvConst for bool {
+    fn spirv_type(builder: &mut mr::Builder) -> Word {
+        builder.find_or_add_global(spirv::Op::TypeBool, None, vec![])
+    }
+
+    fn spirv_const(&self, builder: &mut mr::Builder) -> Word {
+        let ty = bool::spirv_type(builder);
+        let opcode = if *self {
+            spirv::Op::ConstantTrue
+        } else {
+            spirv::Op::ConstantFalse
+        };
+        builder.find_or_add_global(opcode, Some(ty), vec![])
+    }
+}
+
+macro_rules! scalar {
+    ($rust:ty, $opcode:ident, [$($operand:expr),*], $literal:ident, $value:ty) => {
+        impl AsSpirvConst for $rust {
+            fn spirv_type(builder: &mut mr::Builder) -> Word {
+                let operands = vec![$(mr::Operand::LiteralInt32($operand)),*];
+                builder.find_or_add_global(spirv::Op::$opcode, None, operands)
+            }
+
+            fn spirv_const(&self, builder: &mut mr::Builder) -> Word {
+                let ty = <$rust>::spirv_type(builder);
+                let value = vec![mr::Operand::$literal(*self as $value)];
+                builder.find_or_add_global(spirv::Op::Constant, Some(ty), value)
+            }
+        }
+    }
+}
+
+scalar!(u32, TypeInt, [32, 0], LiteralInt32, u32);
+scalar!(i32, TypeInt, [32, 1], LiteralInt32, u32);
+scalar!(u64, TypeInt, [64, 0], LiteralInt64, u64);
+scalar!(i64, TypeInt, [64, 1], LiteralInt64, u64);
+scalar!(f32, TypeFloat, [32], LiteralFloat32, f32);
+scalar!(f64, TypeFloat, [64], LiteralFloat64, f64);
+
+impl<T: AsSpirvConst, const N: usize> AsSpirvConst for [T; N] {
+    fn spirv_type(builder: &mut mr::Builder) -> Word {
+        let element = T::spirv_type(builder);
+        let length = (N as u32).spirv_const(builder);
+        let operands = vec![mr::Operand::IdRef(element), mr::Operand::IdRef(length)];
+        builder.find_or_add_global(spirv::Op::TypeArray, None, operands)
+    }
+
+    fn spirv_const(&self, builder: &mut mr::Builder) -> Word {
+        let ty = <[T; N]>::spirv_type(builder);
+        let elements = self.iter().map(|element| element.spirv_const(builder)).collect();
+        composite(builder, ty, elements)
+    }
+}
+
+macro_rules! tuple {
+    ($($field:ident: $index:tt),+) => {
+        impl<$($field: AsSpirvConst),+> AsSpirvConst for ($($field,)+) {
+            fn spirv_type(builder: &mut mr::Builder) -> Word {
+                let members = vec![$($field::spirv_type(builder)),+];
+                struct_type(builder, members)
+            }
+
+            fn spirv_const(&self, builder: &mut mr::Builder) -> Word {
+                let ty = <($($field,)+)>::spirv_type(builder);
+                let members = vec![$(self.$index.spirv_const(builder)),+];
+                composite(builder, ty, members)
+            }
+        }
+    }
+}
+
+tuple!(A: 0);
+tuple!(A: 0, B: 1);
+tuple!(A: 0, B: 1, C: 2);
+tuple!(A: 0, B: 1, C: 2, D: 3);
+tuple!(A: 0, B: 1, C: 2, D: 3, E: 4);
+tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    #[test]
+    fn test_constant_composite_from() {
+        let mut b = mr::Builder::new();
+        let table = [(1u32, [0.5f32, 0.5]), (2, [0.5, 1.0]), (1, [0.5, 0.5])];
+        let id = b.constant_composite_from(&table);
+        let same = b.constant_composite_from(&[true, false]);
+        let m = b.module();
+
+        let globals: Vec<(spirv::Op, Vec<mr::Operand>)> = m.types_global_values
+            .iter()
+            .map(|inst| (inst.class.opcode, inst.operands.to_vec()))
+            .collect();
+        let (uint, float, two, array2, tuple, three) = (1, 2, 3, 4, 5, 6);
+        assert_eq!(globals[..7],
+                   [(spirv::Op::TypeInt,
+                     vec![mr::Operand::LiteralInt32(32), mr::Operand::LiteralInt32(0)]),
+                    (spirv::Op::TypeFloat, vec![mr::Operand::LiteralInt32(32)]),
+                    (spirv::Op::Constant, vec![mr::Operand::LiteralInt32(2)]),
+                    (spirv::Op::TypeArray,
+                     vec![mr::Operand::IdRef(float), mr::Operand::IdRef(two)]),
+                    (spirv::Op::TypeStruct,
+                     vec![mr::Operand::IdRef(uint), mr::Operand::IdRef(array2)]),
+                    (spirv::Op::Constant, vec![mr::Operand::LiteralInt32(3)]),
+                    (spirv::Op::TypeArray,
+                     vec![mr::Operand::IdRef(tuple), mr::Operand::IdRef(three)])]);
+        let table = m.types_global_values.iter().find(|inst| inst.result_id == Some(id)).unwrap();
+        assert_eq!(table.operands.len(), 3);
+        // The first and last rows are the same constant.
+        assert_eq!(table.operands[0], table.operands[2]);
+        assert_ne!(table.operands[0], table.operands[1]);
+        let bools = m.types_global_values.iter().find(|inst| inst.result_id == Some(same));
+        assert_eq!(bools.unwrap().operands.len(), 2);
+    }
+}
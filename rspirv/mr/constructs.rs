@@ -13,10 +13,14 @@
 // limitations under the License.
 
 use grammar;
+use prelude::*;
 use spirv;
 
 use spirv::Word;
 use std::{convert, fmt, iter};
+use std::collections::BTreeMap;
+use super::changes::Changes;
+use super::operands::Operands;
 
 /// Data representation of a SPIR-V module.
 ///
@@ -56,6 +60,8 @@ pub struct Module {
     pub types_global_values: Vec<Instruction>,
     /// All functions.
     pub functions: Vec<Function>,
+    /// The sections changed since the changes were last taken.
+    pub(crate) changes: Changes,
 }
 
 /// Data representation of a SPIR-V module header.
@@ -90,6 +96,20 @@ pub struct BasicBlock {
     pub instructions: Vec<Instruction>,
 }
 
+/// Source text embedded in a module by an OpSource instruction and the
+/// OpSourceContinued instructions directly following it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmbeddedSource {
+    /// The source language.
+    pub language: spirv::SourceLanguage,
+    /// The version of the source language.
+    pub version: u32,
+    /// The file name, taken from the OpString named by the OpSource.
+    pub file: Option<String>,
+    /// The complete source text.
+    pub source: String,
+}
+
 /// Data representation of a SPIR-V instruction.
 #[derive(Debug)]
 pub struct Instruction {
@@ -100,10 +120,41 @@ pub struct Instruction {
     /// Result id.
     pub result_id: Option<Word>,
     /// Operands.
-    pub operands: Vec<Operand>,
+    pub operands: Operands,
+    /// The capabilities and extensions this instruction requires.
+    ///
+    /// Only filled in by the parser when requested with
+    /// [`Parser::set_annotate_requirements`](../binary/struct.Parser.html#method.set_annotate_requirements);
+    /// use [`Requirements::of`](struct.Requirements.html#method.of) to
+    /// compute them otherwise.
+    pub requirements: Option<Requirements>,
+    /// All words of an instruction the grammar does not know, including the
+    /// first one holding the word count and opcode.
+    ///
+    /// Only filled in by the [`Loader`](struct.Loader.html) when parsing
+    /// with [`Parser::set_keep_unknown`](../binary/struct.Parser.html#method.set_keep_unknown).
+    /// Such an instruction has the class of `OpNop` and no result or
+    /// operands, and is assembled as these words unchanged. It stays in the
+    /// section or basic block it was read in, so that it moves along with
+    /// its neighbours when the module is edited.
+    pub raw_words: Option<Vec<u32>>,
+}
+
+/// The capabilities and extensions required by instructions, as listed in
+/// the grammar.
+///
+/// Each entry lists alternatives, any one of which suffices; entries are
+/// kept in the order they are first seen without duplicates.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Requirements {
+    /// Required capabilities.
+    pub capabilities: Vec<&'static [spirv::Capability]>,
+    /// Required extensions.
+    pub extensions: Vec<&'static [&'static str]>,
 }
 
 /// Instruction iterator.
+#[derive(Clone)]
 pub struct InstIter<'i> {
     instructions: Vec<&'i Instruction>,
     index: usize,
@@ -149,6 +200,7 @@ impl Module {
             annotations: vec![],
             types_global_values: vec![],
             functions: vec![],
+            changes: Changes::default(),
         }
     }
 
@@ -179,6 +231,89 @@ impl Module {
         insts.append(&mut i);
         InstIter::new(insts)
     }
+
+    /// Returns the names of the extensions declared by OpExtension, in
+    /// order.
+    pub fn extension_names(&self) -> Vec<&str> {
+        self.extensions
+            .iter()
+            .filter_map(|inst| match inst.operands.first() {
+                Some(Operand::LiteralString(name)) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns true if the extension `name` is declared by OpExtension.
+    pub fn has_extension(&self, name: &str) -> bool {
+        self.extension_names().contains(&name)
+    }
+
+    /// Returns the requirements of all instructions, including those in
+    /// functions and those of atomics on the types of their values.
+    pub fn requirements(&self) -> Requirements {
+        let functions = self.functions.iter().flat_map(|f| {
+            f.def
+                .iter()
+                .chain(&f.parameters)
+                .chain(f.basic_blocks.iter().flat_map(|b| b.label.iter().chain(&b.instructions)))
+                .chain(&f.end)
+        });
+        Requirements::of_all(self.global_inst_iter().chain(functions))
+    }
+
+    /// Returns the source texts embedded in OpSource instructions, each
+    /// stitched together with the OpSourceContinued instructions directly
+    /// following it.
+    ///
+    /// OpSource instructions without source text are skipped.
+    pub fn embedded_sources(&self) -> Vec<EmbeddedSource> {
+        let strings: BTreeMap<Word, &str> = self.debugs
+            .iter()
+            .filter_map(|inst| match (inst.class.opcode, inst.result_id, inst.operands.first()) {
+                (spirv::Op::String, Some(id), Some(Operand::LiteralString(s))) => {
+                    Some((id, s.as_str()))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let mut sources: Vec<EmbeddedSource> = vec![];
+        // Whether the last instruction seen continues into the last source.
+        let mut continuing = false;
+        for inst in &self.debugs {
+            match inst.class.opcode {
+                spirv::Op::Source => {
+                    continuing = false;
+                    let (language, version) = match (inst.operands.first(), inst.operands.get(1)) {
+                        (Some(&Operand::SourceLanguage(l)), Some(&Operand::LiteralInt32(v))) => (l, v),
+                        _ => continue,
+                    };
+                    let mut file = None;
+                    let mut source = None;
+                    for operand in &inst.operands[2..] {
+                        match *operand {
+                            Operand::IdRef(id) => file = strings.get(&id).map(|s| s.to_string()),
+                            Operand::LiteralString(ref s) => source = Some(s.clone()),
+                            _ => (),
+                        }
+                    }
+                    if let Some(source) = source {
+                        sources.push(EmbeddedSource { language, version, file, source });
+                        continuing = true;
+                    }
+                }
+                spirv::Op::SourceContinued if continuing => {
+                    if let (Some(last), Some(Operand::LiteralString(s))) =
+                           (sources.last_mut(), inst.operands.first()) {
+                        last.source.push_str(s);
+                    }
+                }
+                _ => continuing = false,
+            }
+        }
+        sources
+    }
 }
 
 impl ModuleHeader {
@@ -245,20 +380,226 @@ impl BasicBlock {
             instructions: vec![],
         }
     }
+
+    /// Returns the distinct labels the terminator of this block branches
+    /// to, in order.
+    pub fn successors(&self) -> Vec<Word> {
+        let mut labels = vec![];
+        if let Some(inst) = self.instructions.last() {
+            if grammar::reflect::is_terminator(inst.class.opcode) {
+                for (id, role) in inst.id_operands() {
+                    if role == grammar::IdRole::BranchTarget && !labels.contains(&id) {
+                        labels.push(id);
+                    }
+                }
+            }
+        }
+        labels
+    }
 }
 
 impl Instruction {
     /// Creates a new `Instruction` instance.
-    pub fn new(opcode: spirv::Op,
-               result_type: Option<Word>,
-               result_id: Option<Word>,
-               operands: Vec<Operand>)
-               -> Instruction {
+    pub fn new<O: Into<Operands>>(opcode: spirv::Op,
+                                  result_type: Option<Word>,
+                                  result_id: Option<Word>,
+                                  operands: O)
+                                  -> Instruction {
         Instruction {
             class: grammar::CoreInstructionTable::get(opcode),
             result_type: result_type,
             result_id: result_id,
-            operands: operands,
+            operands: operands.into(),
+            requirements: None,
+            raw_words: None,
+        }
+    }
+
+    /// Creates an instruction kept as the given words, for an opcode the
+    /// grammar does not know.
+    pub fn raw(words: Vec<u32>) -> Instruction {
+        let mut inst = Instruction::new(spirv::Op::Nop, None, None, vec![]);
+        inst.raw_words = Some(words);
+        inst
+    }
+
+    /// Returns the ids this instruction refers to, in order, together with
+    /// their roles as given by the grammar.
+    ///
+    /// The result id is not included, since it is defined rather than
+    /// referred to by the instruction.
+    pub fn id_operands(&self) -> impl Iterator<Item = (Word, grammar::IdRole)> {
+        let roles = grammar::reflect::id_roles(self.class.opcode);
+        let logical_operands = self.class.operands.iter().filter(|operand| {
+            operand.kind != grammar::OperandKind::IdResultType &&
+            operand.kind != grammar::OperandKind::IdResult
+        });
+        let mut ids: Vec<(Word, grammar::IdRole)> =
+            self.result_type.iter().map(|&id| (id, grammar::IdRole::ResultType)).collect();
+        let mut operands = self.operands.iter().peekable();
+        for (index, logical) in logical_operands.enumerate() {
+            let width = match logical.kind {
+                grammar::OperandKind::PairLiteralIntegerIdRef |
+                grammar::OperandKind::PairIdRefLiteralInteger |
+                grammar::OperandKind::PairIdRefIdRef => 2,
+                _ => 1,
+            };
+            while operands.peek().is_some() {
+                let mut position = 0;
+                for operand in operands.by_ref().take(width) {
+                    if let Some(id) = operand.id() {
+                        let role = roles.get(index).and_then(|r| r.get(position));
+                        ids.push((id, role.cloned().unwrap_or(grammar::IdRole::Value)));
+                        position += 1;
+                    }
+                }
+                if logical.quantifier != grammar::OperandQuantifier::ZeroOrMore {
+                    break;
+                }
+            }
+        }
+        ids.extend(operands.filter_map(Operand::id).map(|id| (id, grammar::IdRole::Value)));
+        ids.into_iter()
+    }
+}
+
+impl Requirements {
+    /// Creates an empty set of requirements.
+    pub fn new() -> Requirements {
+        Requirements::default()
+    }
+
+    /// Returns the requirements of `inst`: those of its opcode together
+    /// with those of its operand values.
+    pub fn of(inst: &Instruction) -> Requirements {
+        let mut reqs = Requirements::new();
+        if !inst.class.capabilities.is_empty() {
+            reqs.add_capabilities(inst.class.capabilities);
+        }
+        if !inst.class.extensions.is_empty() {
+            reqs.add_extensions(inst.class.extensions);
+        }
+        for operand in &inst.operands {
+            for caps in operand.required_capabilities() {
+                reqs.add_capabilities(caps);
+            }
+            for exts in operand.required_extensions() {
+                reqs.add_extensions(exts);
+            }
+        }
+        reqs
+    }
+
+    /// Returns the requirements of the atomic instruction `inst` on values
+    /// of the scalar type `ty`, which the grammar does not list:
+    /// `Int64Atomics` for 64-bit integers, and `AtomicFloat32AddEXT` or
+    /// `AtomicFloat64AddEXT` for OpAtomicFAddEXT on floats of that width.
+    pub fn of_atomic(inst: &Instruction, ty: &Instruction) -> Requirements {
+        use spirv::Capability as C;
+        let mut reqs = Requirements::new();
+        let width = match ty.operands.first() {
+            Some(&Operand::LiteralInt32(width)) => width,
+            _ => return reqs,
+        };
+        match (ty.class.opcode, width, inst.class.opcode) {
+            (spirv::Op::TypeInt, 64, _) => reqs.add_capabilities(&[C::Int64Atomics]),
+            (spirv::Op::TypeFloat, 32, spirv::Op::AtomicFAddEXT) => {
+                reqs.add_capabilities(&[C::AtomicFloat32AddEXT])
+            }
+            (spirv::Op::TypeFloat, 64, spirv::Op::AtomicFAddEXT) => {
+                reqs.add_capabilities(&[C::AtomicFloat64AddEXT])
+            }
+            _ => {}
+        }
+        reqs
+    }
+
+    /// Returns the requirements of all of `insts`, including those of
+    /// atomics on the types of their values, looking the types up among
+    /// `insts`.
+    ///
+    /// The requirements on types come before those of the grammar, which
+    /// they narrow down.
+    pub fn of_all<'i, I>(insts: I) -> Requirements
+        where I: IntoIterator<Item = &'i Instruction> + Clone
+    {
+        let defs: BTreeMap<Word, &Instruction> = insts.clone()
+            .into_iter()
+            .filter_map(|inst| inst.result_id.map(|id| (id, inst)))
+            .collect();
+        let mut reqs = Requirements::new();
+        for inst in insts {
+            if let Some(ty) = atomic_value_type(inst, &defs) {
+                reqs.add(&Requirements::of_atomic(inst, ty));
+            }
+            reqs.add(&Requirements::of(inst));
+        }
+        reqs
+    }
+
+    /// Adds all requirements of `other` to this one.
+    pub fn add(&mut self, other: &Requirements) {
+        for caps in &other.capabilities {
+            self.add_capabilities(caps);
+        }
+        for exts in &other.extensions {
+            self.add_extensions(exts);
+        }
+    }
+
+    /// Returns true if nothing is required.
+    pub fn is_empty(&self) -> bool {
+        self.capabilities.is_empty() && self.extensions.is_empty()
+    }
+
+    fn add_capabilities(&mut self, caps: &'static [spirv::Capability]) {
+        if !self.capabilities.contains(&caps) {
+            self.capabilities.push(caps);
+        }
+    }
+
+    fn add_extensions(&mut self, exts: &'static [&'static str]) {
+        if !self.extensions.contains(&exts) {
+            self.extensions.push(exts);
+        }
+    }
+}
+
+/// Returns the type of the values the atomic instruction `inst` operates
+/// on, given the definitions `defs` of ids: that of its result, or of the
+/// value stored by OpAtomicStore.
+pub(crate) fn atomic_value_type<'i>(inst: &Instruction,
+                                    defs: &BTreeMap<Word, &'i Instruction>)
+                                    -> Option<&'i Instruction> {
+    if !inst.class.opname.starts_with("Atomic") {
+        return None;
+    }
+    let ty = match inst.class.opcode {
+        spirv::Op::AtomicStore => defs.get(&inst.operands.get(3)?.id()?)?.result_type?,
+        _ => inst.result_type?,
+    };
+    defs.get(&ty).cloned()
+}
+
+impl Operand {
+    /// Returns the id held by this operand, if it is an id operand.
+    pub fn id(&self) -> Option<Word> {
+        match *self {
+            Operand::IdRef(id) |
+            Operand::IdScope(id) |
+            Operand::IdMemorySemantics(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the id held by this operand, if it
+    /// is an id operand.
+    pub fn id_mut(&mut self) -> Option<&mut Word> {
+        match *self {
+            Operand::IdRef(ref mut id) |
+            Operand::IdScope(ref mut id) |
+            Operand::IdMemorySemantics(ref mut id) => Some(id),
+            _ => None,
         }
     }
 }
@@ -279,9 +620,85 @@ impl convert::From<u32> for Operand {
 
 #[cfg(test)]
 mod tests {
+    use grammar::IdRole;
     use mr;
     use spirv;
 
+    #[test]
+    fn test_id_operands() {
+        // OpPhi pairs values with their parent blocks.
+        let inst = mr::Instruction::new(
+            spirv::Op::Phi, Some(1), Some(2),
+            vec![mr::Operand::IdRef(3), mr::Operand::IdRef(4),
+                 mr::Operand::IdRef(5), mr::Operand::IdRef(6)]);
+        assert_eq!(vec![(1, IdRole::ResultType), (3, IdRole::Value), (4, IdRole::BranchTarget),
+                        (5, IdRole::Value), (6, IdRole::BranchTarget)],
+                   inst.id_operands().collect::<Vec<_>>());
+
+        let inst = mr::Instruction::new(
+            spirv::Op::Switch, None, None,
+            vec![mr::Operand::IdRef(1), mr::Operand::IdRef(2),
+                 mr::Operand::LiteralInt32(0), mr::Operand::IdRef(3),
+                 mr::Operand::LiteralInt32(1), mr::Operand::IdRef(4)]);
+        assert_eq!(vec![(1, IdRole::Value), (2, IdRole::BranchTarget),
+                        (3, IdRole::BranchTarget), (4, IdRole::BranchTarget)],
+                   inst.id_operands().collect::<Vec<_>>());
+
+        let inst = mr::Instruction::new(
+            spirv::Op::AtomicIAdd, Some(1), Some(2),
+            vec![mr::Operand::IdRef(3), mr::Operand::IdScope(4),
+                 mr::Operand::IdMemorySemantics(5), mr::Operand::IdRef(6)]);
+        assert_eq!(vec![(1, IdRole::ResultType), (3, IdRole::MemoryObject), (4, IdRole::Scope),
+                        (5, IdRole::MemorySemantics), (6, IdRole::Value)],
+                   inst.id_operands().collect::<Vec<_>>());
+
+        // Ids in enumerant parameters are values.
+        let inst = mr::Instruction::new(
+            spirv::Op::DecorateId, None, None,
+            vec![mr::Operand::IdRef(1), mr::Operand::Decoration(spirv::Decoration::AlignmentId),
+                 mr::Operand::IdRef(2)]);
+        assert_eq!(vec![(1, IdRole::DecorationTarget), (2, IdRole::Value)],
+                   inst.id_operands().collect::<Vec<_>>());
+
+        let inst = mr::Instruction::new(
+            spirv::Op::TypeStruct, None, Some(1),
+            vec![mr::Operand::IdRef(2), mr::Operand::IdRef(3)]);
+        assert_eq!(vec![(2, IdRole::Type), (3, IdRole::Type)],
+                   inst.id_operands().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_requirements_of() {
+        let inst = mr::Instruction::new(
+            spirv::Op::ImageSampleImplicitLod, Some(1), Some(2),
+            vec![mr::Operand::IdRef(3), mr::Operand::IdRef(4),
+                 mr::Operand::ImageOperands(spirv::ImageOperands::BIAS |
+                                            spirv::ImageOperands::MIN_LOD),
+                 mr::Operand::IdRef(5), mr::Operand::IdRef(6)]);
+        let reqs = mr::Requirements::of(&inst);
+        let shader: &[spirv::Capability] = &[spirv::Capability::Shader];
+        let min_lod: &[spirv::Capability] = &[spirv::Capability::MinLod];
+        assert_eq!(vec![shader, min_lod], reqs.capabilities);
+        assert!(reqs.extensions.is_empty());
+
+        let inst = mr::Instruction::new(
+            spirv::Op::Capability, None, None,
+            vec![mr::Operand::Capability(spirv::Capability::DemoteToHelperInvocation)]);
+        let reqs = mr::Requirements::of(&inst);
+        assert!(reqs.capabilities.is_empty());
+        let demote: &[&str] = &["SPV_EXT_demote_to_helper_invocation"];
+        assert_eq!(vec![demote], reqs.extensions);
+
+        let mut all = mr::Requirements::new();
+        assert!(all.is_empty());
+        all.add(&reqs);
+        all.add(&mr::Requirements::of(&mr::Instruction::new(
+            spirv::Op::IsHelperInvocationEXT, Some(1), Some(2), vec![])));
+        let capability: &[spirv::Capability] = &[spirv::Capability::DemoteToHelperInvocation];
+        assert_eq!(vec![capability], all.capabilities);
+        assert_eq!(vec![demote], all.extensions);
+    }
+
     #[test]
     fn test_convert_from_string() {
         assert_eq!(mr::Operand::LiteralString("wow".to_string()),
@@ -323,4 +740,34 @@ mod tests {
         assert_eq!(mr::Operand::LiteralSpecConstantOpInteger(spirv::Op::IAdd),
                    mr::Operand::from(spirv::Op::IAdd));
     }
+
+    #[test]
+    fn test_embedded_sources() {
+        let mut b = mr::Builder::new();
+        let file = b.string("a.glsl");
+        b.source(spirv::SourceLanguage::GLSL, 450, Some(file), Some("void "));
+        b.source_continued("main() ");
+        b.source_continued("{}");
+        // No text to collect.
+        b.source::<String>(spirv::SourceLanguage::GLSL, 450, None, None);
+        b.source(spirv::SourceLanguage::ESSL, 310, None, Some("int x;"));
+        b.source_extension("GL_GOOGLE_include_directive");
+        // Does not directly follow the OpSource.
+        b.source_continued("int y;");
+        let m = b.module();
+
+        assert_eq!(m.embedded_sources(),
+                   vec![mr::EmbeddedSource {
+                            language: spirv::SourceLanguage::GLSL,
+                            version: 450,
+                            file: Some("a.glsl".to_string()),
+                            source: "void main() {}".to_string(),
+                        },
+                        mr::EmbeddedSource {
+                            language: spirv::SourceLanguage::ESSL,
+                            version: 310,
+                            file: None,
+                            source: "int x;".to_string(),
+                        }]);
+    }
 }
@@ -0,0 +1,314 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use prelude::*;
+use spirv;
+
+use spirv::Word;
+use std::result;
+use super::Error;
+
+/// An execution mode of an entry point.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExecutionModeInfo {
+    pub mode: spirv::ExecutionMode,
+    /// The operands following the execution mode: literals, or ids for
+    /// modes declared by `OpExecutionModeId`.
+    pub operands: Vec<mr::Operand>,
+    /// Whether the mode is declared by `OpExecutionModeId`.
+    pub by_id: bool,
+}
+
+/// The output topologies of geometry shaders.
+const OUTPUT_TOPOLOGIES: &[spirv::ExecutionMode] = &[spirv::ExecutionMode::OutputPoints,
+                                                     spirv::ExecutionMode::OutputLineStrip,
+                                                     spirv::ExecutionMode::OutputTriangleStrip];
+
+/// Returns true if `a` and `b` cannot both be declared for an entry point,
+/// and the one set replaces the other.
+fn conflicts(a: spirv::ExecutionMode, b: spirv::ExecutionMode) -> bool {
+    use spirv::ExecutionMode::*;
+    let family = |mode| match mode {
+        LocalSize | LocalSizeId => Some(0),
+        LocalSizeHint | LocalSizeHintId => Some(1),
+        OutputPoints | OutputLineStrip | OutputTriangleStrip => Some(2),
+        _ => None,
+    };
+    a == b || (family(a).is_some() && family(a) == family(b))
+}
+
+/// Returns the entry point and mode of an `OpExecutionMode` or
+/// `OpExecutionModeId`.
+fn target(inst: &mr::Instruction) -> Option<(Word, spirv::ExecutionMode)> {
+    match (inst.operands.first(), inst.operands.get(1)) {
+        (Some(&mr::Operand::IdRef(entry)), Some(&mr::Operand::ExecutionMode(mode))) => {
+            Some((entry, mode))
+        }
+        _ => None,
+    }
+}
+
+impl mr::Module {
+    /// Returns the execution modes of the entry point function `entry`, in
+    /// declaration order.
+    pub fn execution_modes(&self, entry: Word) -> Vec<ExecutionModeInfo> {
+        self.execution_modes
+            .iter()
+            .filter_map(|inst| {
+                let (_, mode) = target(inst).filter(|&(target, _)| target == entry)?;
+                Some(ExecutionModeInfo {
+                    mode,
+                    operands: inst.operands[2..].to_vec(),
+                    by_id: inst.class.opcode == spirv::Op::ExecutionModeId,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the operands of the execution mode `mode` of `entry`.
+    fn execution_mode_operands(&self,
+                               entry: Word,
+                               mode: spirv::ExecutionMode)
+                               -> Option<Vec<mr::Operand>> {
+        self.execution_modes(entry)
+            .into_iter()
+            .find(|info| info.mode == mode)
+            .map(|info| info.operands)
+    }
+
+    /// Returns the workgroup size of the entry point `entry`, given by
+    /// `LocalSize`, or by `LocalSizeId` with the values of its constants,
+    /// using the default values of specialization constants.
+    pub fn local_size(&self, entry: Word) -> Option<[u32; 3]> {
+        let literal = |operand: &mr::Operand| match *operand {
+            mr::Operand::LiteralInt32(v) => Some(v),
+            _ => None,
+        };
+        let constant = |operand: &mr::Operand| {
+            let id = operand.id()?;
+            let inst = self.types_global_values.iter().find(|inst| inst.result_id == Some(id))?;
+            match inst.class.opcode {
+                spirv::Op::Constant | spirv::Op::SpecConstant => literal(inst.operands.first()?),
+                _ => None,
+            }
+        };
+        let sizes: Option<Vec<u32>> =
+            match self.execution_mode_operands(entry, spirv::ExecutionMode::LocalSize) {
+                Some(operands) => operands.iter().map(literal).collect(),
+                None => {
+                    self.execution_mode_operands(entry, spirv::ExecutionMode::LocalSizeId)?
+                        .iter()
+                        .map(constant)
+                        .collect()
+                }
+            };
+        match sizes?[..] {
+            [x, y, z] => Some([x, y, z]),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of invocations of the geometry shader `entry`.
+    pub fn invocations(&self, entry: Word) -> Option<u32> {
+        match self.execution_mode_operands(entry, spirv::ExecutionMode::Invocations)?[..] {
+            [mr::Operand::LiteralInt32(count)] => Some(count),
+            _ => None,
+        }
+    }
+
+    /// Returns the maximum number of vertices output by the entry point
+    /// `entry`.
+    pub fn output_vertices(&self, entry: Word) -> Option<u32> {
+        match self.execution_mode_operands(entry, spirv::ExecutionMode::OutputVertices)?[..] {
+            [mr::Operand::LiteralInt32(count)] => Some(count),
+            _ => None,
+        }
+    }
+
+    /// Returns the output topology of the geometry shader `entry`:
+    /// `OutputPoints`, `OutputLineStrip`, or `OutputTriangleStrip`.
+    pub fn output_topology(&self, entry: Word) -> Option<spirv::ExecutionMode> {
+        self.execution_modes(entry)
+            .into_iter()
+            .map(|info| info.mode)
+            .find(|mode| OUTPUT_TOPOLOGIES.contains(mode))
+    }
+
+    /// Declares the execution mode `mode` with the literal `params` for
+    /// the entry point `entry` by `OpExecutionMode`.
+    ///
+    /// The mode replaces the modes it conflicts with: the same mode,
+    /// `LocalSizeId` for `LocalSize` and the other way around, and likewise
+    /// for `LocalSizeHint`, and the other output topologies for output
+    /// topologies. It takes the place of the first of them, or follows the
+    /// other modes of `entry`.
+    pub fn set_execution_mode(&mut self,
+                              entry: Word,
+                              mode: spirv::ExecutionMode,
+                              params: &[u32]) {
+        let operands = params.iter().map(|&v| mr::Operand::LiteralInt32(v)).collect();
+        self.replace_execution_mode(spirv::Op::ExecutionMode, entry, mode, operands);
+    }
+
+    /// Declares the execution mode `mode` with the id `params` for the
+    /// entry point `entry` by `OpExecutionModeId`, replacing conflicting
+    /// modes like [`set_execution_mode`](#method.set_execution_mode).
+    ///
+    /// Returns `UnsupportedVersion`, declaring nothing, if the header of
+    /// the module is for a version of SPIR-V before 1.2, which introduced
+    /// `OpExecutionModeId`.
+    pub fn set_execution_mode_id(&mut self,
+                                 entry: Word,
+                                 mode: spirv::ExecutionMode,
+                                 params: &[Word])
+                                 -> result::Result<(), Error> {
+        if self.header.as_ref().is_some_and(|header| header.version() < (1, 2)) {
+            return Err(Error::UnsupportedVersion);
+        }
+        let operands = params.iter().map(|&id| mr::Operand::IdRef(id)).collect();
+        self.replace_execution_mode(spirv::Op::ExecutionModeId, entry, mode, operands);
+        Ok(())
+    }
+
+    /// Sets the workgroup size of the entry point `entry` by `LocalSize`.
+    pub fn set_local_size(&mut self, entry: Word, size: [u32; 3]) {
+        self.set_execution_mode(entry, spirv::ExecutionMode::LocalSize, &size);
+    }
+
+    /// Sets the workgroup size of the entry point `entry` to the constants
+    /// `size` by `LocalSizeId`, e.g., to specialization constants.
+    ///
+    /// Returns `UnsupportedVersion` like
+    /// [`set_execution_mode_id`](#method.set_execution_mode_id).
+    pub fn set_local_size_id(&mut self, entry: Word, size: [Word; 3]) -> result::Result<(), Error> {
+        self.set_execution_mode_id(entry, spirv::ExecutionMode::LocalSizeId, &size)
+    }
+
+    /// Sets the number of invocations of the geometry shader `entry`.
+    pub fn set_invocations(&mut self, entry: Word, count: u32) {
+        self.set_execution_mode(entry, spirv::ExecutionMode::Invocations, &[count]);
+    }
+
+    /// Sets the maximum number of vertices output by the entry point
+    /// `entry`.
+    pub fn set_output_vertices(&mut self, entry: Word, count: u32) {
+        self.set_execution_mode(entry, spirv::ExecutionMode::OutputVertices, &[count]);
+    }
+
+    /// Sets the output topology of the geometry shader `entry` to
+    /// `topology`, one of `OutputPoints`, `OutputLineStrip`, and
+    /// `OutputTriangleStrip`, replacing the one declared before.
+    pub fn set_output_topology(&mut self, entry: Word, topology: spirv::ExecutionMode) {
+        debug_assert!(OUTPUT_TOPOLOGIES.contains(&topology));
+        self.set_execution_mode(entry, topology, &[]);
+    }
+
+    /// Removes the execution mode `mode` of the entry point `entry`, and
+    /// returns true if it was declared.
+    pub fn remove_execution_mode(&mut self, entry: Word, mode: spirv::ExecutionMode) -> bool {
+        let count = self.execution_modes.len();
+        self.execution_modes.retain(|inst| target(inst) != Some((entry, mode)));
+        if self.execution_modes.len() == count {
+            return false;
+        }
+        self.touch(mr::Section::ExecutionModes);
+        true
+    }
+
+    fn replace_execution_mode(&mut self,
+                              opcode: spirv::Op,
+                              entry: Word,
+                              mode: spirv::ExecutionMode,
+                              params: Vec<mr::Operand>) {
+        let mut operands = vec![mr::Operand::IdRef(entry), mr::Operand::ExecutionMode(mode)];
+        operands.extend(params);
+        let inst = mr::Instruction::new(opcode, None, None, operands);
+        self.touch(mr::Section::ExecutionModes);
+
+        let replaced = |inst: &mr::Instruction| {
+            target(inst).is_some_and(|(target, other)| target == entry && conflicts(mode, other))
+        };
+        let position = self.execution_modes.iter().position(replaced);
+        match position {
+            Some(position) => {
+                self.execution_modes[position] = inst;
+                let mut index = 0;
+                self.execution_modes.retain(|inst| {
+                    index += 1;
+                    index - 1 == position || !replaced(inst)
+                });
+            }
+            None => {
+                let position = self.execution_modes
+                    .iter()
+                    .rposition(|inst| target(inst).is_some_and(|(target, _)| target == entry))
+                    .map_or(self.execution_modes.len(), |last| last + 1);
+                self.execution_modes.insert(position, inst);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    #[test]
+    fn test_execution_modes() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let x = b.spec_constant_u32(uint, 64);
+        let one = b.constant_u32(uint, 1);
+        let fty = b.type_function(void, vec![]);
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::Geometry, main, "main", vec![]);
+        b.execution_mode(main, spirv::ExecutionMode::OutputPoints, vec![]);
+        b.execution_mode(main, spirv::ExecutionMode::OutputVertices, vec![4]);
+        let mut m = b.module();
+
+        assert_eq!(m.output_topology(main), Some(spirv::ExecutionMode::OutputPoints));
+        assert_eq!(m.output_vertices(main), Some(4));
+        assert_eq!(m.invocations(main), None);
+        m.set_output_topology(main, spirv::ExecutionMode::OutputTriangleStrip);
+        m.set_invocations(main, 2);
+        let modes: Vec<_> = m.execution_modes(main).into_iter().map(|info| info.mode).collect();
+        assert_eq!(modes,
+                   vec![spirv::ExecutionMode::OutputTriangleStrip,
+                        spirv::ExecutionMode::OutputVertices,
+                        spirv::ExecutionMode::Invocations]);
+        assert_eq!(m.invocations(main), Some(2));
+
+        m.set_local_size(main, [8, 8, 1]);
+        assert_eq!(m.local_size(main), Some([8, 8, 1]));
+        m.set_local_size_id(main, [x, one, one]).unwrap();
+        assert_eq!(m.local_size(main), Some([64, 1, 1]));
+        let modes = m.execution_modes(main);
+        assert_eq!(modes.len(), 4);
+        assert!(modes[3].by_id);
+        assert!(m.remove_execution_mode(main, spirv::ExecutionMode::LocalSizeId));
+        assert_eq!(m.local_size(main), None);
+
+        m.header.as_mut().unwrap().version = 0x0001_0000;
+        match m.set_local_size_id(main, [x, one, one]) {
+            Err(mr::Error::UnsupportedVersion) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grammar;
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::BTreeMap;
+
+/// Registry of the extended instruction sets imported by a module.
+///
+/// Maps the result ids of `OpExtInstImport` instructions to the known
+/// extended instruction sets they import. Imports of unknown sets are
+/// silently ignored.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExtInstRegistry {
+    sets: BTreeMap<Word, grammar::ExtInstSet>,
+}
+
+impl ExtInstRegistry {
+    /// Creates a new empty registry.
+    pub fn new() -> ExtInstRegistry {
+        ExtInstRegistry { sets: BTreeMap::new() }
+    }
+
+    /// Creates a registry of all extended instruction sets imported by
+    /// `module`.
+    pub fn of(module: &mr::Module) -> ExtInstRegistry {
+        let mut registry = ExtInstRegistry::new();
+        for inst in &module.ext_inst_imports {
+            registry.track(inst);
+        }
+        registry
+    }
+
+    /// Records the extended instruction set imported by `inst`.
+    ///
+    /// Instructions other than `OpExtInstImport` and imports of unknown
+    /// sets are ignored.
+    pub fn track(&mut self, inst: &mr::Instruction) {
+        if inst.class.opcode != spirv::Op::ExtInstImport {
+            return;
+        }
+        if let (Some(id), Some(mr::Operand::LiteralString(name))) =
+            (inst.result_id, inst.operands.first()) {
+            if let Some(set) = grammar::ExtInstSet::from_name(name) {
+                self.sets.insert(id, set);
+            }
+        }
+    }
+
+    /// Returns the known extended instruction set imported as `id`.
+    pub fn get(&self, id: Word) -> Option<grammar::ExtInstSet> {
+        self.sets.get(&id).cloned()
+    }
+
+    /// Returns the id the given extended instruction `set` is imported as.
+    pub fn id_of(&self, set: grammar::ExtInstSet) -> Option<Word> {
+        self.sets.iter().find(|&(_, s)| *s == set).map(|(id, _)| *id)
+    }
+
+    /// Returns the operand for extended instruction `opcode` of the set
+    /// imported as `id`.
+    ///
+    /// The operand is symbolic if the set is known, and the raw
+    /// `LiteralExtInstInteger` otherwise.
+    pub fn operand(&self, id: Word, opcode: u32) -> mr::Operand {
+        match self.get(id) {
+            Some(set) => mr::Operand::ExtInstOpcode(set, opcode),
+            None => mr::Operand::LiteralExtInstInteger(opcode),
+        }
+    }
+
+    /// Resolves the grammar of the extended instruction `opcode` of the set
+    /// imported as `id`.
+    ///
+    /// Returns `None` for both unknown sets and unknown opcodes in known
+    /// sets.
+    pub fn resolve(&self,
+                   id: Word,
+                   opcode: u32)
+                   -> Option<&'static grammar::ExtendedInstruction<'static>> {
+        self.get(id).and_then(|set| set.lookup_opcode(opcode))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use grammar;
+    use mr;
+    use spirv;
+
+    use super::ExtInstRegistry;
+
+    #[test]
+    fn test_ext_inst_registry() {
+        let mut b = mr::Builder::new();
+        let glsl = b.ext_inst_import("GLSL.std.450");
+        let clspv = b.ext_inst_import("NonSemantic.ClspvReflection.5");
+        let unknown = b.ext_inst_import("Unknown.set");
+        let registry = ExtInstRegistry::of(&b.module());
+
+        assert_eq!(Some(grammar::ExtInstSet::GlslStd450), registry.get(glsl));
+        assert_eq!(Some(grammar::ExtInstSet::ClspvReflection), registry.get(clspv));
+        assert_eq!(None, registry.get(unknown));
+        assert_eq!(Some(clspv), registry.id_of(grammar::ExtInstSet::ClspvReflection));
+        assert_eq!(None, registry.id_of(grammar::ExtInstSet::OpenCLStd100));
+
+        let sqrt = spirv::GLOp::Sqrt as u32;
+        assert_eq!(mr::Operand::ExtInstOpcode(grammar::ExtInstSet::GlslStd450, sqrt),
+                   registry.operand(glsl, sqrt));
+        assert_eq!(mr::Operand::LiteralExtInstInteger(sqrt),
+                   registry.operand(unknown, sqrt));
+        assert_eq!(Some("Sqrt"), registry.resolve(glsl, sqrt).map(|i| i.opname));
+        assert!(registry.resolve(unknown, sqrt).is_none());
+    }
+}
@@ -0,0 +1,307 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use prelude::*;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The linkage of a function extracted by
+/// [`Module::extract_function`](struct.Module.html#method.extract_function).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Interface {
+    /// The name the function is exported as.
+    pub export: String,
+    /// The functions and variables imported, with the names they are
+    /// imported as, in order of their ids.
+    pub imports: Vec<(Word, String)>,
+}
+
+impl mr::Module {
+    /// Returns a standalone module holding the function `id` and everything
+    /// it depends on, together with its linkage, or `None` if there is no
+    /// such function.
+    ///
+    /// The function is exported. The functions it calls are declared
+    /// without bodies and imported, and the module-scope variables it uses
+    /// imported without initializers; they keep their decorations. Types,
+    /// constants, extended instruction imports, strings, names, and
+    /// decorations are copied as far as they are needed. The extracted
+    /// module keeps the ids, capabilities, extensions, memory model, and
+    /// header of this one, declaring the `Linkage` capability besides, and
+    /// has no entry points.
+    ///
+    /// Linkage names are those given by `OpName`, or `_` followed by the id
+    /// for unnamed ones.
+    pub fn extract_function(&self, id: Word) -> Option<(mr::Module, Interface)> {
+        let function = self.functions
+            .iter()
+            .find(|f| f.def.as_ref().and_then(|def| def.result_id) == Some(id))?;
+        let functions: BTreeMap<Word, &mr::Function> = self.functions
+            .iter()
+            .filter_map(|f| f.def.as_ref()?.result_id.map(|id| (id, f)))
+            .collect();
+        let globals: BTreeMap<Word, &mr::Instruction> = self.types_global_values
+            .iter()
+            .filter_map(|inst| inst.result_id.map(|id| (id, inst)))
+            .collect();
+        let names: BTreeMap<Word, &str> = self.debugs
+            .iter()
+            .filter(|inst| inst.class.opcode == spirv::Op::Name)
+            .filter_map(|inst| match (inst.operands.first(), inst.operands.get(1)) {
+                (Some(&mr::Operand::IdRef(id)), Some(mr::Operand::LiteralString(name))) => {
+                    Some((id, name.as_str()))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let insts: Vec<&mr::Instruction> = function.def
+            .iter()
+            .chain(&function.parameters)
+            .chain(function.basic_blocks.iter().flat_map(|b| b.label.iter().chain(&b.instructions)))
+            .chain(&function.end)
+            .collect();
+        let mut stack: Vec<Word> = insts.iter().flat_map(|inst| refs(inst)).collect();
+        stack.extend(insts.iter().filter_map(|inst| inst.result_id).filter(|&r| r != id));
+        let mut included = BTreeSet::new();
+        included.insert(id);
+        let mut callees = BTreeSet::new();
+        let mut variables = BTreeSet::new();
+        while let Some(next) = stack.pop() {
+            if !included.insert(next) {
+                continue;
+            }
+            if let Some(callee) = functions.get(&next) {
+                callees.insert(next);
+                stack.extend(callee.def.iter().chain(&callee.parameters).flat_map(refs));
+                stack.extend(callee.parameters.iter().filter_map(|p| p.result_id));
+            } else if let Some(inst) = globals.get(&next) {
+                if inst.class.opcode == spirv::Op::Variable {
+                    variables.insert(next);
+                    // Imported variables have no initializer.
+                    stack.extend(inst.result_type);
+                } else {
+                    stack.extend(refs(inst));
+                }
+            }
+            for inst in self.annotations.iter().filter(|inst| targets(inst, next)) {
+                stack.extend(refs(inst));
+            }
+        }
+        let is_included = |inst: &mr::Instruction| {
+            inst.result_id.is_some_and(|id| included.contains(&id))
+        };
+        let copy = |inst: &mr::Instruction| {
+            mr::Instruction::new(inst.class.opcode,
+                                 inst.result_type,
+                                 inst.result_id,
+                                 inst.operands.clone())
+        };
+
+        let mut module = mr::Module {
+            header: self.header.as_ref().map(|header| {
+                mr::ModuleHeader {
+                    magic_number: header.magic_number,
+                    version: header.version,
+                    generator: header.generator,
+                    bound: header.bound,
+                    reserved_word: header.reserved_word,
+                }
+            }),
+            capabilities: self.capabilities.iter().map(copy).collect(),
+            extensions: self.extensions.iter().map(copy).collect(),
+            ext_inst_imports: self.ext_inst_imports
+                .iter()
+                .filter(|inst| is_included(inst))
+                .map(copy)
+                .collect(),
+            memory_model: self.memory_model.as_ref().map(copy),
+            debugs: self.debugs
+                .iter()
+                .filter(|inst| match inst.class.opcode {
+                    spirv::Op::String => is_included(inst),
+                    spirv::Op::Name | spirv::Op::MemberName => {
+                        inst.operands.first().and_then(mr::Operand::id).is_some_and(|id| {
+                            included.contains(&id)
+                        })
+                    }
+                    _ => false,
+                })
+                .map(copy)
+                .collect(),
+            annotations: self.annotations
+                .iter()
+                .filter(|inst| included.iter().any(|&id| targets(inst, id)))
+                .map(copy)
+                .collect(),
+            types_global_values: self.types_global_values
+                .iter()
+                .filter(|inst| match inst.result_id {
+                    Some(id) => included.contains(&id),
+                    // Forward pointers go along with the pointers they declare.
+                    None => refs(inst).any(|id| included.contains(&id)),
+                })
+                .map(|inst| {
+                    let mut inst = copy(inst);
+                    if inst.class.opcode == spirv::Op::Variable {
+                        inst.operands.truncate(1);
+                    }
+                    inst
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let linkage = mr::Operand::Capability(spirv::Capability::Linkage);
+        if !module.capabilities.iter().any(|inst| inst.operands.first() == Some(&linkage)) {
+            module.capabilities
+                .push(mr::Instruction::new(spirv::Op::Capability, None, None, vec![linkage]));
+        }
+
+        let link = |id: Word| names.get(&id).map_or_else(|| format!("_{}", id), |&n| n.to_owned());
+        let mut interface = Interface { export: link(id), imports: vec![] };
+        let linked = Some((id, spirv::LinkageType::Export))
+            .into_iter()
+            .chain(callees.iter().chain(&variables).map(|&id| (id, spirv::LinkageType::Import)));
+        for (target, linkage) in linked {
+            let name = link(target);
+            if linkage == spirv::LinkageType::Import {
+                interface.imports.push((target, name.clone()));
+            }
+            module.annotations
+                .push(mr::Instruction::new(spirv::Op::Decorate,
+                                           None,
+                                           None,
+                                           vec![mr::Operand::IdRef(target),
+                                                mr::Operand::Decoration(
+                                                    spirv::Decoration::LinkageAttributes),
+                                                mr::Operand::LiteralString(name),
+                                                mr::Operand::LinkageType(linkage)]));
+        }
+        interface.imports.sort();
+        for f in &self.functions {
+            let fid = f.def.as_ref().and_then(|def| def.result_id);
+            if fid == Some(id) {
+                module.functions.push(mr::Function {
+                    def: f.def.as_ref().map(copy),
+                    end: f.end.as_ref().map(copy),
+                    parameters: f.parameters.iter().map(copy).collect(),
+                    basic_blocks: f.basic_blocks
+                        .iter()
+                        .map(|b| {
+                            mr::BasicBlock {
+                                label: b.label.as_ref().map(copy),
+                                instructions: b.instructions.iter().map(copy).collect(),
+                            }
+                        })
+                        .collect(),
+                });
+            } else if fid.is_some_and(|fid| callees.contains(&fid)) {
+                module.functions.push(mr::Function {
+                    def: f.def.as_ref().map(copy),
+                    end: f.end.as_ref().map(copy),
+                    parameters: f.parameters.iter().map(copy).collect(),
+                    basic_blocks: vec![],
+                });
+            }
+        }
+        Some((module, interface))
+    }
+}
+
+/// Returns the ids `inst` refers to, including its result type.
+fn refs(inst: &mr::Instruction) -> impl Iterator<Item = Word> + '_ {
+    inst.result_type.into_iter().chain(inst.operands.iter().filter_map(mr::Operand::id))
+}
+
+/// Returns whether the annotation `inst` decorates `id`.
+fn targets(inst: &mr::Instruction, id: Word) -> bool {
+    match inst.class.opcode {
+        spirv::Op::Decorate |
+        spirv::Op::DecorateId |
+        spirv::Op::DecorateString |
+        spirv::Op::MemberDecorate |
+        spirv::Op::MemberDecorateString => {
+            inst.operands.first() == Some(&mr::Operand::IdRef(id))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    #[test]
+    fn test_extract_function() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let float = b.type_float(32);
+        b.type_vector(float, 4);
+        let ptr = b.type_pointer(None, spirv::StorageClass::Private, uint);
+        let one = b.constant_u32(uint, 1);
+        let counter = b.variable(ptr, None, spirv::StorageClass::Private, Some(one));
+        b.name(counter, "counter");
+        let fty = b.type_function(uint, vec![uint]);
+        let double = b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let x = b.function_parameter(uint).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let y = b.iadd(uint, None, x, x).unwrap();
+        b.ret_value(y).unwrap();
+        b.end_function().unwrap();
+        let main = b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let z = b.function_parameter(uint).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let value = b.load(uint, None, counter, None, vec![]).unwrap();
+        let doubled = b.function_call(uint, None, double, vec![value]).unwrap();
+        let sum = b.iadd(uint, None, doubled, z).unwrap();
+        b.ret_value(sum).unwrap();
+        b.end_function().unwrap();
+        b.name(main, "main");
+        let vty = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, vty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let m = b.module();
+
+        let (extracted, interface) = m.extract_function(main).unwrap();
+        assert_eq!(interface.export, "main");
+        assert_eq!(interface.imports,
+                   vec![(counter, "counter".to_owned()), (double, format!("_{}", double))]);
+        // The vector types and the `void` function are left out.
+        let globals: Vec<_> =
+            extracted.types_global_values.iter().map(|inst| inst.result_id.unwrap()).collect();
+        assert_eq!(globals, vec![uint, ptr, counter, fty]);
+        assert_eq!(extracted.types_global_values[2].operands.len(), 1);
+        assert_eq!(extracted.functions.len(), 2);
+        assert!(extracted.functions[0].basic_blocks.is_empty());
+        assert_eq!(extracted.functions[1].basic_blocks[0].instructions.len(), 4);
+        assert_eq!(extracted.capabilities.len(), 2);
+        assert_eq!(extracted.debugs.len(), 2);
+        assert_eq!(extracted.annotations.len(), 3);
+        assert_eq!(extracted.annotations[0].operands,
+                   vec![mr::Operand::IdRef(main),
+                        mr::Operand::Decoration(spirv::Decoration::LinkageAttributes),
+                        mr::Operand::from("main"),
+                        mr::Operand::LinkageType(spirv::LinkageType::Export)]);
+
+        assert!(m.extract_function(y).is_none());
+    }
+}
@@ -0,0 +1,224 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use binary::Assemble;
+use grammar;
+use mr;
+use prelude::*;
+use spirv;
+
+use spirv::Word;
+use std::collections::BTreeMap;
+use utils::hash::{fnv_bytes, FNV_OFFSET_BASIS};
+
+/// Options for [`Module::stable_hash_with`](struct.Module.html#method.stable_hash_with).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HashOptions {
+    /// Whether debug information affects the hash: the debug section,
+    /// `OpLine` and `OpNoLine`, and `NonSemantic.Shader.DebugInfo.100`
+    /// extended instructions and their import.
+    pub debug_info: bool,
+}
+
+impl mr::Module {
+    /// Returns a hash of the contents of this module, ignoring debug
+    /// information.
+    ///
+    /// See [`stable_hash_with`](#method.stable_hash_with) for details.
+    pub fn stable_hash(&self) -> u64 {
+        self.stable_hash_with(&HashOptions::default())
+    }
+
+    /// Returns a hash of the contents of this module that does not depend
+    /// on how ids are numbered, suitable as a cache key.
+    ///
+    /// Ids are renumbered in the order they first appear in the module,
+    /// and the sections whose order carries no meaning (capabilities,
+    /// extensions, extended instruction set imports, execution modes,
+    /// annotations, and debug instructions) are sorted. The header is not
+    /// hashed. The hash is FNV-1a over the renumbered words, so it is the
+    /// same on every platform and in every run.
+    pub fn stable_hash_with(&self, options: &HashOptions) -> u64 {
+        let debug_info_set = if options.debug_info {
+            None
+        } else {
+            mr::ExtInstRegistry::of(self).id_of(grammar::ExtInstSet::DebugInfo100)
+        };
+        let mut canon = Canonicalizer::new();
+
+        let mut imports: Vec<&mr::Instruction> = self.ext_inst_imports
+            .iter()
+            .filter(|inst| debug_info_set.is_none() || inst.result_id != debug_info_set)
+            .collect();
+        imports.sort_by_key(|inst| {
+            inst.operands.iter().flat_map(|operand| operand.assemble()).collect::<Vec<u32>>()
+        });
+
+        let mut sections = vec![canon.sorted(&self.capabilities),
+                                canon.sorted(&self.extensions),
+                                canon.encode_all(imports)];
+        let ordered = canon.encode_all(self.memory_model
+            .iter()
+            .chain(&self.entry_points)
+            .chain(&self.types_global_values)
+            .filter(|inst| !is_debug_info(inst, debug_info_set)));
+        let mut functions = vec![];
+        for f in &self.functions {
+            functions.extend(canon.encode_all(f.def.iter().chain(&f.parameters)));
+            for block in &f.basic_blocks {
+                functions.extend(canon.encode_all(block.label
+                    .iter()
+                    .chain(&block.instructions)
+                    .filter(|inst| {
+                        !is_debug_info(inst, debug_info_set) &&
+                        (options.debug_info || !grammar::reflect::is_location_debug(inst.class.opcode))
+                    })));
+            }
+            functions.extend(canon.encode_all(&f.end));
+        }
+        // Ids first appearing here number after those of the ordered sections.
+        sections.push(canon.sorted(&self.execution_modes));
+        sections.push(canon.sorted(&self.annotations));
+        if options.debug_info {
+            sections.push(canon.sorted(&self.debugs));
+        }
+        sections.push(ordered);
+        sections.push(functions);
+
+        let mut hash = FNV_OFFSET_BASIS;
+        for section in &sections {
+            fnv_word(&mut hash, section.len() as u32);
+            for words in section {
+                fnv_word(&mut hash, words.len() as u32);
+                for &word in words {
+                    fnv_word(&mut hash, word);
+                }
+            }
+        }
+        hash
+    }
+}
+
+/// Returns true if `inst` is an extended instruction of the debug info set
+/// imported as `set`.
+fn is_debug_info(inst: &mr::Instruction, set: Option<Word>) -> bool {
+    set.is_some() && inst.class.opcode == spirv::Op::ExtInst &&
+    inst.operands.first().and_then(mr::Operand::id) == set
+}
+
+fn fnv_word(hash: &mut u64, word: u32) {
+    fnv_bytes(hash, &word.to_le_bytes())
+}
+
+/// Encodes instructions into words with canonically numbered ids.
+struct Canonicalizer {
+    ids: BTreeMap<Word, Word>,
+}
+
+impl Canonicalizer {
+    fn new() -> Canonicalizer {
+        Canonicalizer { ids: BTreeMap::new() }
+    }
+
+    fn id(&mut self, id: Word) -> Word {
+        let next = self.ids.len() as Word + 1;
+        *self.ids.entry(id).or_insert(next)
+    }
+
+    fn encode(&mut self, inst: &mr::Instruction) -> Vec<u32> {
+        let mut words = vec![inst.class.opcode as u32];
+        if let Some(id) = inst.result_type {
+            words.push(self.id(id));
+        }
+        if let Some(id) = inst.result_id {
+            words.push(self.id(id));
+        }
+        for operand in &inst.operands {
+            match operand.id() {
+                Some(id) => words.push(self.id(id)),
+                None => words.extend(operand.assemble()),
+            }
+        }
+        words
+    }
+
+    fn encode_all<'i, I>(&mut self, insts: I) -> Vec<Vec<u32>>
+        where I: IntoIterator<Item = &'i mr::Instruction>
+    {
+        insts.into_iter().map(|inst| self.encode(inst)).collect()
+    }
+
+    fn sorted(&mut self, insts: &[mr::Instruction]) -> Vec<Vec<u32>> {
+        let mut encoded = self.encode_all(insts);
+        encoded.sort();
+        encoded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    fn build(skip_id: bool, name: &str, value: u32) -> mr::Module {
+        let mut b = mr::Builder::new();
+        if skip_id {
+            b.id();
+        }
+        b.capability(spirv::Capability::Shader);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let uint = b.type_int(32, 0);
+        let c = b.constant_u32(uint, value);
+        b.name(c, name);
+        b.decorate(c, spirv::Decoration::SpecId, vec![mr::Operand::LiteralInt32(1)]);
+        b.decorate(uint, spirv::Decoration::RelaxedPrecision, vec![]);
+        let fty = b.type_function(uint, vec![]);
+        b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let sum = b.iadd(uint, None, c, c).unwrap();
+        b.ret_value(sum).unwrap();
+        b.end_function().unwrap();
+        b.module()
+    }
+
+    #[test]
+    fn test_stable_hash() {
+        let m = build(false, "c", 1);
+        assert_eq!(m.stable_hash(), build(true, "c", 1).stable_hash());
+        assert_eq!(m.stable_hash(), build(false, "renamed", 1).stable_hash());
+        assert_ne!(m.stable_hash(), build(false, "c", 2).stable_hash());
+
+        let mut reordered = build(false, "c", 1);
+        reordered.annotations.reverse();
+        assert_eq!(m.stable_hash(), reordered.stable_hash());
+
+        let options = mr::HashOptions { debug_info: true };
+        assert_eq!(m.stable_hash_with(&options),
+                   build(true, "c", 1).stable_hash_with(&options));
+        assert_ne!(m.stable_hash_with(&options),
+                   build(false, "renamed", 1).stable_hash_with(&options));
+    }
+
+    #[test]
+    fn test_stable_hash_skips_debug_info() {
+        let mut with_debug = build(false, "c", 1);
+        with_debug.functions[0].basic_blocks[0]
+            .instructions
+            .insert(0, mr::Instruction::new(spirv::Op::NoLine, None, None, vec![]));
+        let m = build(false, "c", 1);
+        assert_eq!(m.stable_hash(), with_debug.stable_hash());
+        let options = mr::HashOptions { debug_info: true };
+        assert_ne!(m.stable_hash_with(&options), with_debug.stable_hash_with(&options));
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+
+use spirv::Word;
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+/// Allocates the result ids of a module.
+///
+/// Ids are allocated in increasing order from the bound, which grows past
+/// every id allocated. Allocation is deterministic: the same calls always
+/// allocate the same ids.
+///
+/// Ranges of contiguous ids can be reserved, e.g., to lay out the ids of a
+/// module linked in. If recycling is enabled, freed ids are allocated
+/// again, smallest first, before the bound grows; reserved ranges are
+/// always taken from the bound.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdAllocator {
+    bound: Word,
+    recycling: bool,
+    freed: BTreeSet<Word>,
+}
+
+impl IdAllocator {
+    /// Creates an allocator allocating from 1.
+    pub fn new() -> IdAllocator {
+        IdAllocator::with_bound(1)
+    }
+
+    /// Creates an allocator allocating from `bound`.
+    pub fn with_bound(bound: Word) -> IdAllocator {
+        IdAllocator {
+            bound: bound.max(1),
+            recycling: false,
+            freed: BTreeSet::new(),
+        }
+    }
+
+    /// Creates an allocator allocating the ids not used in `module`: from
+    /// its bound, or from one past its largest result id if it has no
+    /// header.
+    pub fn for_module(module: &mr::Module) -> IdAllocator {
+        if let Some(ref header) = module.header {
+            return IdAllocator::with_bound(header.bound);
+        }
+        let functions = module.functions.iter().flat_map(|f| {
+            f.def
+                .iter()
+                .chain(&f.parameters)
+                .chain(f.basic_blocks.iter().flat_map(|b| b.label.iter().chain(&b.instructions)))
+        });
+        let largest = module.global_inst_iter()
+            .chain(functions)
+            .filter_map(|inst| inst.result_id)
+            .max();
+        IdAllocator::with_bound(largest.map_or(1, |id| id + 1))
+    }
+
+    /// Returns the bound: one past the largest id allocated or reserved.
+    pub fn bound(&self) -> Word {
+        self.bound
+    }
+
+    /// Sets whether freed ids are allocated again. Disabling recycling
+    /// forgets the ids freed so far.
+    pub fn set_recycling(&mut self, recycling: bool) {
+        self.recycling = recycling;
+        if !recycling {
+            self.freed.clear();
+        }
+    }
+
+    /// Returns true if freed ids are allocated again.
+    pub fn is_recycling(&self) -> bool {
+        self.recycling
+    }
+
+    /// Allocates an id: the smallest freed one if recycling, or the bound.
+    pub fn id(&mut self) -> Word {
+        if let Some(&id) = self.freed.iter().next() {
+            self.freed.remove(&id);
+            return id;
+        }
+        self.bound += 1;
+        self.bound - 1
+    }
+
+    /// Reserves `count` contiguous ids from the bound, and returns them.
+    pub fn reserve(&mut self, count: Word) -> Range<Word> {
+        let start = self.bound;
+        self.bound += count;
+        start..self.bound
+    }
+
+    /// Considers all ids below `bound` as allocated, e.g., those of a
+    /// module to merge with.
+    pub fn advance_to(&mut self, bound: Word) {
+        self.bound = self.bound.max(bound);
+    }
+
+    /// Frees `id`, which is allocated again if recycling; otherwise, does
+    /// nothing.
+    ///
+    /// The caller makes sure `id` is not used anymore. Ids never allocated
+    /// are ignored.
+    pub fn free(&mut self, id: Word) {
+        if self.recycling && id != 0 && id < self.bound {
+            self.freed.insert(id);
+        }
+    }
+
+    /// Sets the bound in the header of `module`, if it has one.
+    pub fn update_header(&self, module: &mut mr::Module) {
+        if let Some(ref mut header) = module.header {
+            if header.bound != self.bound {
+                header.bound = self.bound;
+                module.touch(mr::Section::Header);
+            }
+        }
+    }
+}
+
+impl Default for IdAllocator {
+    fn default() -> IdAllocator {
+        IdAllocator::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+
+    use super::IdAllocator;
+
+    #[test]
+    fn test_id_allocator() {
+        let mut ids = IdAllocator::new();
+        assert_eq!((ids.id(), ids.id()), (1, 2));
+        assert_eq!(ids.reserve(3), 3..6);
+        ids.free(4);
+        assert_eq!(ids.id(), 6);
+
+        ids.set_recycling(true);
+        ids.free(4);
+        ids.free(2);
+        ids.free(9);
+        assert_eq!(ids.reserve(2), 7..9);
+        assert_eq!((ids.id(), ids.id(), ids.id()), (2, 4, 9));
+        ids.advance_to(5);
+        assert_eq!(ids.bound(), 10);
+        ids.advance_to(20);
+        assert_eq!(ids.id(), 20);
+
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        b.type_function(void, vec![]);
+        let mut module = b.module();
+        assert_eq!(IdAllocator::for_module(&module).bound(), 3);
+        module.header = None;
+        assert_eq!(IdAllocator::for_module(&module).id(), 3);
+    }
+}
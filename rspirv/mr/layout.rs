@@ -0,0 +1,369 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use prelude::*;
+use spirv;
+
+use spirv::Word;
+use std::collections::BTreeMap;
+use std::result;
+use super::Error;
+
+type BuildResult<T> = result::Result<T, Error>;
+
+/// Rules laying out the members of structs in memory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayoutRule {
+    /// The `std140` layout of GLSL, e.g., for uniform buffers: arrays and
+    /// structs are aligned to 16 bytes, and so are array elements and
+    /// matrix columns.
+    Std140,
+    /// The `std430` layout of GLSL, e.g., for storage buffers.
+    Std430,
+    /// The scalar layout of `VK_EXT_scalar_block_layout`: everything is
+    /// aligned to its scalar components.
+    Scalar,
+}
+
+impl LayoutRule {
+    /// Returns the size and alignment in bytes of the type `ty` of
+    /// `module` laid out by this rule, ignoring its layout decorations.
+    ///
+    /// Returns `None` for types without an explicit layout, like
+    /// [`StructBuilder::offsets`](struct.StructBuilder.html#method.offsets).
+    /// Runtime arrays have a size of 0.
+    pub fn size_and_align(self, module: &mr::Module, ty: Word) -> Option<(u32, u32)> {
+        Types::new(module, self).layout(ty)
+    }
+}
+
+/// Builds a struct type with an explicit layout.
+///
+/// Members are laid out in order by a [`LayoutRule`](enum.LayoutRule.html),
+/// and [`build`](#method.build) appends the `OpTypeStruct` together with
+/// the `Offset` of each member, the `MatrixStride` and `ColMajor` of
+/// matrix members and arrays thereof, the `ArrayStride` of the array types
+/// of members not decorated already, and the names given.
+///
+/// Member types must be declared before, and structs among them laid out
+/// by the same rule. Matrices are laid out column-major.
+#[derive(Clone, Debug)]
+pub struct StructBuilder {
+    rule: LayoutRule,
+    name: Option<String>,
+    members: Vec<(Word, Option<String>)>,
+}
+
+impl StructBuilder {
+    /// Creates a builder for a struct laid out by `rule`, without members.
+    pub fn new(rule: LayoutRule) -> StructBuilder {
+        StructBuilder {
+            rule,
+            name: None,
+            members: vec![],
+        }
+    }
+
+    /// Names the struct.
+    pub fn name<T: Into<String>>(&mut self, name: T) -> &mut StructBuilder {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Appends a member of type `ty`.
+    pub fn member(&mut self, ty: Word) -> &mut StructBuilder {
+        self.members.push((ty, None));
+        self
+    }
+
+    /// Appends a member of type `ty` named `name`.
+    pub fn named_member<T: Into<String>>(&mut self, ty: Word, name: T) -> &mut StructBuilder {
+        self.members.push((ty, Some(name.into())));
+        self
+    }
+
+    /// Returns the offsets of the members.
+    ///
+    /// Returns `NoExplicitLayout` if a member type has no explicit layout,
+    /// e.g., a boolean, a pointer, or an opaque type, or is not declared by
+    /// `builder`.
+    pub fn offsets(&self, builder: &mr::Builder) -> BuildResult<Vec<u32>> {
+        let types = Types::new(builder.module_ref(), self.rule);
+        let mut offsets = vec![];
+        let mut end = 0;
+        for &(ty, _) in &self.members {
+            let (size, align) = types.layout(ty).ok_or(Error::NoExplicitLayout)?;
+            let offset = round_up(end, align);
+            offsets.push(offset);
+            end = offset + size;
+        }
+        Ok(offsets)
+    }
+
+    /// Appends the struct type and its decorations and names, and returns
+    /// the result id of the type.
+    ///
+    /// Returns `NoExplicitLayout` like [`offsets`](#method.offsets),
+    /// appending nothing.
+    pub fn build(&self, builder: &mut mr::Builder) -> BuildResult<Word> {
+        let offsets = self.offsets(builder)?;
+        let mut matrix_strides = vec![];
+        let mut array_strides = BTreeMap::new();
+        {
+            let module = builder.module_ref();
+            let types = Types::new(module, self.rule);
+            for &(ty, _) in &self.members {
+                let mut ty = ty;
+                while let Some((element, _)) = types.array(ty) {
+                    let stride = types.array_stride(element).ok_or(Error::NoExplicitLayout)?;
+                    if !has_array_stride(module, ty) {
+                        array_strides.insert(ty, stride);
+                    }
+                    ty = element;
+                }
+                matrix_strides.push(types.matrix(ty).and_then(|(column, _)| {
+                    types.matrix_stride(column)
+                }));
+            }
+        }
+
+        let member_types: Vec<Word> = self.members.iter().map(|&(ty, _)| ty).collect();
+        let id = builder.type_struct(member_types);
+        for (array, stride) in array_strides {
+            let stride = vec![mr::Operand::LiteralInt32(stride)];
+            builder.decorate(array, spirv::Decoration::ArrayStride, stride);
+        }
+        if let Some(ref name) = self.name {
+            builder.name(id, name.clone());
+        }
+        for (index, (_, name)) in self.members.iter().enumerate() {
+            let member = index as u32;
+            if let Some(name) = name {
+                builder.member_name(id, member, name.clone());
+            }
+            let offset = vec![mr::Operand::LiteralInt32(offsets[index])];
+            builder.member_decorate(id, member, spirv::Decoration::Offset, offset);
+            if let Some(stride) = matrix_strides[index] {
+                builder.member_decorate(id, member, spirv::Decoration::ColMajor, vec![]);
+                let stride = vec![mr::Operand::LiteralInt32(stride)];
+                builder.member_decorate(id, member, spirv::Decoration::MatrixStride, stride);
+            }
+        }
+        Ok(id)
+    }
+}
+
+/// Rust structs laid out as SPIR-V structs, usually implemented with
+/// `#[derive(SpirvStruct)]` and the `derive` feature.
+///
+/// The derived implementation names the struct and its members after the
+/// Rust struct and its fields, and declares the type of each field with
+/// [`AsSpirvConst::spirv_type`](trait.AsSpirvConst.html#tymethod.spirv_type),
+/// or, for fields marked `#[spirv(nested)]`, builds it as a struct laid
+/// out by the same rule, appending it anew each time. Comparing the
+/// [offsets](struct.StructBuilder.html#method.offsets) with those of a
+/// `#[repr(C)]` struct checks that both sides agree.
+pub trait SpirvStruct {
+    /// Declares the types of the members with `builder`, and returns a
+    /// struct builder laying them out by `rule`.
+    fn struct_builder(builder: &mut mr::Builder, rule: LayoutRule) -> BuildResult<StructBuilder>;
+}
+
+/// The types of a module, laid out by a rule.
+struct Types<'m> {
+    defs: BTreeMap<Word, &'m mr::Instruction>,
+    rule: LayoutRule,
+}
+
+impl<'m> Types<'m> {
+    fn new(module: &'m mr::Module, rule: LayoutRule) -> Types<'m> {
+        let defs = module.types_global_values
+            .iter()
+            .filter_map(|inst| inst.result_id.map(|id| (id, inst)))
+            .collect();
+        Types { defs, rule }
+    }
+
+    fn literal(&self, inst: &mr::Instruction, index: usize) -> Option<u32> {
+        match inst.operands.get(index) {
+            Some(&mr::Operand::LiteralInt32(v)) => Some(v),
+            _ => None,
+        }
+    }
+
+    fn id(&self, inst: &mr::Instruction, index: usize) -> Option<Word> {
+        inst.operands.get(index).and_then(mr::Operand::id)
+    }
+
+    /// Returns the element type and length of the array type `ty`, with
+    /// no length for runtime arrays.
+    fn array(&self, ty: Word) -> Option<(Word, Option<u32>)> {
+        let inst = self.defs.get(&ty)?;
+        match inst.class.opcode {
+            spirv::Op::TypeArray => {
+                let length = self.defs.get(&self.id(inst, 1)?)?;
+                match length.class.opcode {
+                    spirv::Op::Constant | spirv::Op::SpecConstant => {
+                        Some((self.id(inst, 0)?, Some(self.literal(length, 0)?)))
+                    }
+                    _ => None,
+                }
+            }
+            spirv::Op::TypeRuntimeArray => Some((self.id(inst, 0)?, None)),
+            _ => None,
+        }
+    }
+
+    /// Returns the column type and count of the matrix type `ty`.
+    fn matrix(&self, ty: Word) -> Option<(Word, u32)> {
+        let inst = self.defs.get(&ty)?;
+        if inst.class.opcode == spirv::Op::TypeMatrix {
+            Some((self.id(inst, 0)?, self.literal(inst, 1)?))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the size and alignment of `ty`, in bytes; runtime arrays
+    /// have no size.
+    fn layout(&self, ty: Word) -> Option<(u32, u32)> {
+        let inst = self.defs.get(&ty)?;
+        match inst.class.opcode {
+            spirv::Op::TypeInt | spirv::Op::TypeFloat => {
+                let size = self.literal(inst, 0)? / 8;
+                Some((size, size))
+            }
+            spirv::Op::TypeVector => {
+                let (size, align) = self.layout(self.id(inst, 0)?)?;
+                let count = self.literal(inst, 1)?;
+                match self.rule {
+                    LayoutRule::Scalar => Some((size * count, align)),
+                    _ => Some((size * count, align * if count == 2 { 2 } else { 4 })),
+                }
+            }
+            spirv::Op::TypeMatrix => {
+                let (column, count) = self.matrix(ty)?;
+                let stride = self.matrix_stride(column)?;
+                Some((stride * count, self.aggregate_align(self.layout(column)?.1)))
+            }
+            spirv::Op::TypeArray | spirv::Op::TypeRuntimeArray => {
+                let (element, length) = self.array(ty)?;
+                let stride = self.array_stride(element)?;
+                let align = self.aggregate_align(self.layout(element)?.1);
+                Some((stride * length.unwrap_or(0), align))
+            }
+            spirv::Op::TypeStruct => {
+                let mut end = 0;
+                let mut align = 1;
+                for member in inst.operands.iter().map(mr::Operand::id) {
+                    let (size, member_align) = self.layout(member?)?;
+                    end = round_up(end, member_align) + size;
+                    align = align.max(member_align);
+                }
+                let align = self.aggregate_align(align);
+                Some((round_up(end, align), align))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the alignment of an array or struct whose elements or
+    /// members are aligned to `align`.
+    fn aggregate_align(&self, align: u32) -> u32 {
+        match self.rule {
+            LayoutRule::Std140 => round_up(align, 16),
+            _ => align,
+        }
+    }
+
+    fn array_stride(&self, element: Word) -> Option<u32> {
+        let (size, align) = self.layout(element)?;
+        Some(round_up(size, self.aggregate_align(align)))
+    }
+
+    fn matrix_stride(&self, column: Word) -> Option<u32> {
+        let (size, align) = self.layout(column)?;
+        match self.rule {
+            LayoutRule::Scalar => Some(size),
+            _ => Some(round_up(size, self.aggregate_align(align))),
+        }
+    }
+}
+
+/// Rounds `value` up to the next multiple of `align`.
+pub(crate) fn round_up(value: u32, align: u32) -> u32 {
+    value.div_ceil(align) * align
+}
+
+/// Returns true if the array type `ty` is decorated with an `ArrayStride`.
+fn has_array_stride(module: &mr::Module, ty: Word) -> bool {
+    module.annotations.iter().any(|inst| {
+        inst.class.opcode == spirv::Op::Decorate &&
+        inst.operands.first() == Some(&mr::Operand::IdRef(ty)) &&
+        inst.operands.get(1) == Some(&mr::Operand::Decoration(spirv::Decoration::ArrayStride))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{LayoutRule, StructBuilder};
+
+    #[test]
+    fn test_struct_builder() {
+        let mut b = mr::Builder::new();
+        let uint = b.type_int(32, 0);
+        let float = b.type_float(32);
+        let vec3 = b.type_vector(float, 3);
+        let vec4 = b.type_vector(float, 4);
+        let mat4 = b.type_matrix(vec4, 4);
+        let three = b.constant_u32(uint, 3);
+        let floats = b.type_array(float, three);
+
+        let mut s = StructBuilder::new(LayoutRule::Std140);
+        s.name("Uniforms")
+         .named_member(float, "a")
+         .named_member(vec3, "b")
+         .member(mat4)
+         .member(floats);
+        assert_eq!(s.offsets(&b).unwrap(), vec![0, 16, 32, 96]);
+        s.rule = LayoutRule::Std430;
+        assert_eq!(s.offsets(&b).unwrap(), vec![0, 16, 32, 96]);
+        s.rule = LayoutRule::Scalar;
+        assert_eq!(s.offsets(&b).unwrap(), vec![0, 4, 16, 80]);
+
+        s.rule = LayoutRule::Std140;
+        let id = s.build(&mut b).unwrap();
+        let boolean = b.type_bool();
+        assert!(StructBuilder::new(LayoutRule::Std430).member(boolean).build(&mut b).is_err());
+        let m = b.module();
+        let decorations: Vec<Vec<mr::Operand>> =
+            m.annotations.iter().map(|inst| inst.operands.to_vec()).collect();
+        assert_eq!(decorations[0],
+                   vec![mr::Operand::IdRef(floats),
+                        mr::Operand::Decoration(spirv::Decoration::ArrayStride),
+                        mr::Operand::LiteralInt32(16)]);
+        assert_eq!(decorations[2][3], mr::Operand::LiteralInt32(16));
+        assert_eq!(decorations[5],
+                   vec![mr::Operand::IdRef(id),
+                        mr::Operand::LiteralInt32(2),
+                        mr::Operand::Decoration(spirv::Decoration::MatrixStride),
+                        mr::Operand::LiteralInt32(16)]);
+        assert_eq!(decorations.len(), 7);
+        assert_eq!(m.debugs.len(), 3);
+    }
+}
@@ -14,6 +14,7 @@
 
 use binary;
 use mr;
+use prelude::*;
 use spirv;
 use grammar;
 
@@ -37,6 +38,15 @@ pub enum Error {
     WrongOpExtInstImportOperand,
     WrongOpMemoryModelOperand,
     WrongOpNameOperand,
+    /// A `SpecId` already given to another specialization constant.
+    DuplicateSpecId,
+    /// A struct member type without an explicit layout.
+    NoExplicitLayout,
+    /// An instruction the SPIR-V version of the module does not have.
+    UnsupportedVersion,
+    /// A variable initializer that is neither a constant nor a global
+    /// variable of the pointee type.
+    WrongInitializer,
 }
 
 impl Error {
@@ -62,6 +72,10 @@ impl Error {
             Error::WrongOpExtInstImportOperand => "wrong OpExtInstImport operand",
             Error::WrongOpMemoryModelOperand => "wrong OpMemoryModel operand",
             Error::WrongOpNameOperand => "wrong OpName operand",
+            Error::DuplicateSpecId => "found duplicate SpecId",
+            Error::NoExplicitLayout => "found member type without explicit layout",
+            Error::UnsupportedVersion => "found instruction unsupported by SPIR-V version",
+            Error::WrongInitializer => "found wrong variable initializer",
         }
     }
 }
@@ -90,6 +104,8 @@ pub struct Loader {
     module: mr::Module,
     function: Option<mr::Function>,
     block: Option<mr::BasicBlock>,
+    /// Raw instructions read in the current function outside of its blocks
+    raw: Vec<mr::Instruction>,
 }
 
 impl Loader {
@@ -99,6 +115,7 @@ impl Loader {
             module: mr::Module::new(),
             function: None,
             block: None,
+            raw: vec![],
         }
     }
 
@@ -106,6 +123,32 @@ impl Loader {
     pub fn module(self) -> mr::Module {
         self.module
     }
+
+    /// Returns the section of the last instruction read outside of
+    /// functions, which is the last non-empty one in layout order.
+    ///
+    /// The memory model stands for the entry points, since it holds a
+    /// single instruction.
+    fn last_global_section(&mut self) -> &mut Vec<mr::Instruction> {
+        let m = &mut self.module;
+        if !m.types_global_values.is_empty() {
+            &mut m.types_global_values
+        } else if !m.annotations.is_empty() {
+            &mut m.annotations
+        } else if !m.debugs.is_empty() {
+            &mut m.debugs
+        } else if !m.execution_modes.is_empty() {
+            &mut m.execution_modes
+        } else if !m.entry_points.is_empty() || m.memory_model.is_some() {
+            &mut m.entry_points
+        } else if !m.ext_inst_imports.is_empty() {
+            &mut m.ext_inst_imports
+        } else if !m.extensions.is_empty() {
+            &mut m.extensions
+        } else {
+            &mut m.capabilities
+        }
+    }
 }
 
 /// Returns `$error` if `$condition` evaluates to false.
@@ -131,6 +174,24 @@ impl binary::Consumer for Loader {
         ParseAction::Continue
     }
 
+    /// Keeps the instruction with its neighbours: at the end of the current
+    /// block, or of the section of the last instruction outside of
+    /// functions. Those in a function but outside of its blocks are kept at
+    /// the start of the next block, or before the terminator of the last
+    /// one, so that blocks still start with their label and end with their
+    /// terminator.
+    fn consume_unknown_instruction(&mut self, words: Vec<u32>) -> ParseAction {
+        let inst = mr::Instruction::raw(words);
+        if let Some(ref mut block) = self.block {
+            block.instructions.push(inst);
+        } else if self.function.is_some() {
+            self.raw.push(inst);
+        } else {
+            self.last_global_section().push(inst);
+        }
+        ParseAction::Continue
+    }
+
     fn consume_instruction(&mut self, inst: mr::Instruction) -> ParseAction {
         let opcode = inst.class.opcode;
         match opcode {
@@ -154,6 +215,11 @@ impl binary::Consumer for Loader {
             spirv::Op::Undef if self.function.is_none() => {
                 self.module.types_global_values.push(inst)
             }
+            // Non-semantic extended instructions, e.g., debug info, can
+            // appear among the global values.
+            spirv::Op::ExtInst if self.function.is_none() => {
+                self.module.types_global_values.push(inst)
+            }
             spirv::Op::Function => {
                 if_ret_err!(self.function.is_some(), NestedFunction);
                 let mut f = mr::Function::new();
@@ -163,8 +229,16 @@ impl binary::Consumer for Loader {
             spirv::Op::FunctionEnd => {
                 if_ret_err!(self.function.is_none(), MismatchedFunctionEnd);
                 if_ret_err!(self.block.is_some(), UnclosedBasicBlock);
-                self.function.as_mut().unwrap().end = Some(inst);
-                self.module.functions.push(self.function.take().unwrap())
+                let mut function = self.function.take().unwrap();
+                match function.basic_blocks.last_mut() {
+                    Some(block) => {
+                        let at = block.instructions.len().saturating_sub(1);
+                        block.instructions.splice(at..at, self.raw.drain(..));
+                    }
+                    None => function.parameters.append(&mut self.raw),
+                }
+                function.end = Some(inst);
+                self.module.functions.push(function)
             }
             spirv::Op::FunctionParameter => {
                 if_ret_err!(self.function.is_none(), DetachedFunctionParameter);
@@ -175,6 +249,7 @@ impl binary::Consumer for Loader {
                 if_ret_err!(self.block.is_some(), NestedBasicBlock);
                 let mut block = mr::BasicBlock::new();
                 block.label = Some(inst);
+                block.instructions.append(&mut self.raw);
                 self.block = Some(block)
             }
             opcode if grammar::reflect::is_terminator(opcode) => {
@@ -274,9 +349,13 @@ pub fn load_words<T: AsRef<[u32]>>(binary: T) -> ParseResult<mr::Module> {
 
 #[cfg(test)]
 mod tests {
+    use binary;
+    use binary::Assemble;
     use mr;
     use spirv;
 
+    use utils::num::u32_to_bytes;
+
     #[test]
     fn test_load_variable() {
         let mut b = mr::Builder::new();
@@ -346,4 +425,142 @@ mod tests {
         assert_eq!(inst.class.opcode, spirv::Op::Undef);
         assert_eq!(inst.result_id.unwrap(), local);
     }
+
+    #[test]
+    fn test_load_phi() {
+        let mut b = mr::Builder::new();
+
+        let void = b.type_void();
+        let boolean = b.type_bool();
+        let uint = b.type_int(32, 0);
+        let one = b.constant_u32(uint, 1);
+        let two = b.constant_u32(uint, 2);
+        let cond = b.constant_true(boolean);
+        let voidfvoid = b.type_function(void, vec![void]);
+
+        b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        let entry = b.begin_basic_block(None).unwrap();
+        let (then, merge) = (b.id(), b.id());
+        b.selection_merge(merge, spirv::SelectionControl::NONE).unwrap();
+        b.branch_conditional(cond, then, merge, vec![]).unwrap();
+        b.begin_basic_block(Some(then)).unwrap();
+        b.branch(merge).unwrap();
+        b.begin_basic_block(Some(merge)).unwrap();
+        let phi = b.phi(uint, None, vec![(one, entry), (two, then)]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+
+        // OpPhi starts its block instead of ending it.
+        let m = mr::load_words(b.module().assemble()).unwrap();
+        assert_eq!(m.functions.len(), 1);
+        let f = &m.functions[0];
+        assert_eq!(f.basic_blocks.len(), 3);
+        let bb = &f.basic_blocks[2];
+        assert_eq!(bb.instructions.len(), 2);
+        assert_eq!(bb.instructions[0].class.opcode, spirv::Op::Phi);
+        assert_eq!(bb.instructions[0].result_id, Some(phi));
+        assert_eq!(bb.instructions[1].class.opcode, spirv::Op::Return);
+    }
+
+    #[test]
+    fn test_load_global_classes() {
+        let mut b = mr::Builder::new();
+        b.module_processed("opt");
+        let uint = b.type_int(32, 0);
+        let one = b.constant_u32(uint, 1);
+        let var = b.variable(uint, None, spirv::StorageClass::Private, None);
+        b.decorate_id(var, spirv::Decoration::AlignmentId, vec![mr::Operand::IdRef(one)]);
+        b.type_named_barrier();
+
+        // These are placed by the instruction classes of the grammar.
+        let m = mr::load_words(b.module().assemble()).unwrap();
+        assert_eq!(m.debugs.len(), 1);
+        assert_eq!(m.debugs[0].class.opcode, spirv::Op::ModuleProcessed);
+        assert_eq!(m.annotations.len(), 1);
+        assert_eq!(m.annotations[0].class.opcode, spirv::Op::DecorateId);
+        assert_eq!(m.types_global_values.len(), 4);
+        assert_eq!(m.types_global_values[3].class.opcode, spirv::Op::TypeNamedBarrier);
+    }
+
+    #[test]
+    fn test_load_unknown_instructions() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let voidfvoid = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let m = b.module();
+
+        // Opcodes unknown to the grammar, with operands of their own.
+        let unknown = |opcode: u32| vec![(3 << 16) | opcode, 7, 8];
+        let mut words = m.header.as_ref().unwrap().assemble();
+        words.extend(unknown(0x7ff0));
+        for inst in m.global_inst_iter() {
+            words.extend(inst.assemble());
+        }
+        words.extend(unknown(0x7ff1));
+        let f = &m.functions[0];
+        words.extend(f.def.as_ref().unwrap().assemble());
+        words.extend(f.basic_blocks[0].label.as_ref().unwrap().assemble());
+        let body = words.len();
+        words.extend(unknown(0x7ff2));
+        words.extend(unknown(0x7ff3));
+        words.extend(f.basic_blocks[0].instructions[0].assemble());
+        words.extend(f.end.as_ref().unwrap().assemble());
+
+        assert!(mr::load_words(&words).is_err());
+
+        let mut loader = mr::Loader::new();
+        {
+            let bytes: Vec<u8> = words.iter().flat_map(|w| u32_to_bytes(*w).to_vec()).collect();
+            let mut p = binary::Parser::new(&bytes, &mut loader);
+            p.set_keep_unknown(true);
+            p.parse().unwrap();
+        }
+        let mut loaded = loader.module();
+        assert_eq!(loaded.capabilities[0].raw_words, Some(unknown(0x7ff0)));
+        assert_eq!(loaded.types_global_values[2].raw_words, Some(unknown(0x7ff1)));
+        let raw: Vec<_> = loaded.functions[0].basic_blocks[0]
+            .instructions
+            .iter()
+            .map(|inst| inst.raw_words.clone())
+            .collect();
+        assert_eq!(raw, vec![Some(unknown(0x7ff2)), Some(unknown(0x7ff3)), None]);
+        assert_eq!(loaded.assemble(), words);
+
+        // Edits before them carry them along.
+        let nop = mr::Instruction::new(spirv::Op::Nop, None, None, vec![]);
+        words.splice(body..body, nop.assemble());
+        loaded.functions[0].basic_blocks[0].instructions.insert(0, nop);
+        assert_eq!(loaded.assemble(), words);
+    }
+
+    #[test]
+    fn test_keep_invalid_utf8_strings() {
+        let mut b = mr::Builder::new();
+        let file = b.string("main.glsl");
+        let m = b.module();
+        let mut words = m.header.as_ref().unwrap().assemble();
+        words.extend(m.debugs[0].assemble());
+        // An OpString of the bytes ff fe, which are not UTF-8.
+        let invalid = vec![(3 << 16) | spirv::Op::String as u32, file + 1, 0x0000_feff];
+        words.extend(&invalid);
+        words[3] = file + 2;
+
+        assert!(mr::load_words(&words).is_err());
+
+        let mut loader = mr::Loader::new();
+        {
+            let bytes: Vec<u8> = words.iter().flat_map(|w| u32_to_bytes(*w).to_vec()).collect();
+            let mut p = binary::Parser::new(&bytes, &mut loader);
+            p.set_keep_unknown(true);
+            p.parse().unwrap();
+        }
+        let loaded = loader.module();
+        assert_eq!(loaded.debugs.len(), 2);
+        assert_eq!(loaded.debugs[1].raw_words, Some(invalid));
+        assert_eq!(loaded.assemble(), words);
+    }
 }
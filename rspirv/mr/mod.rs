@@ -32,13 +32,70 @@
 //! provides a [loader](struct.Loader.html) for loading SPIR-V binaries
 //! (together with the [parser](../binary/struct.Parser.html)) and a
 //! [builder](struct.Builder.html) for building a SPIR-V data representation
-//! interactively.
+//! interactively. For large modules, an [arena](struct.Arena.html) stores
+//! instructions more compactly than a [module](struct.Module.html), and
+//! [patterns](pattern/index.html) match instructions by their shape, and
+//! [visitors](visit/index.html) walk modules in layout order. The builder
+//! can also build with [typed ids](typed/index.html), and lay out structs
+//! with a [struct builder](struct.StructBuilder.html). A
+//! [module view](struct.ModuleRef.html) shares indexes of a module between
+//! threads. An [id allocator](struct.IdAllocator.html) allocates the
+//! result ids of the builder and of transforms, and
+//! [`Undefs`](struct.Undefs.html) stand in for the values of removed
+//! instructions. Rust values implementing
+//! [`AsSpirvConst`](trait.AsSpirvConst.html) are declared as constants by
+//! the builder. With the `vulkan` feature, modules convert to and from
+//! the shader code Vulkan takes.
 
+pub use self::arena::{Arena, ArenaOperand, InstId, StrId};
 pub use self::builder::Builder;
-pub use self::constructs::{BasicBlock, Function, Instruction, InstIter};
-pub use self::constructs::{Module, ModuleHeader, Operand};
+pub use self::changes::{Changes, Section};
+pub use self::constants::AsSpirvConst;
+pub use self::constructs::{BasicBlock, EmbeddedSource, Function, Instruction, InstIter};
+pub use self::constructs::{Module, ModuleHeader, Operand, Requirements};
+#[cfg(feature = "std")]
+pub(crate) use self::constructs::atomic_value_type;
+pub use self::execution_mode::ExecutionModeInfo;
+pub use self::ext_inst::ExtInstRegistry;
+pub use self::extract::Interface;
+pub use self::hash::HashOptions;
+pub use self::ids::IdAllocator;
+pub use self::layout::{LayoutRule, SpirvStruct, StructBuilder};
+#[cfg(feature = "std")]
+pub(crate) use self::layout::round_up;
+#[cfg(feature = "derive")]
+pub use rspirv_derive::SpirvStruct;
 pub use self::loader::{Error, load_bytes, load_words, Loader};
+pub use self::operands::{Operands, OperandsIntoIter};
+pub use self::specialize::EntryPointOverrides;
+pub use self::undef::Undefs;
+#[cfg(feature = "std")]
+pub use self::view::ModuleRef;
+pub use self::visit::{MutVisitor, Visitor};
+#[cfg(feature = "vulkan")]
+pub use self::vulkan::words_of;
 
+mod arena;
 mod builder;
+mod changes;
+mod constants;
 mod constructs;
+mod execution_mode;
+mod ext_inst;
+mod extract;
+mod hash;
+mod ids;
+mod layout;
 mod loader;
+mod operands;
+pub mod pattern;
+mod specialize;
+mod split;
+pub mod typed;
+mod undef;
+mod uses;
+#[cfg(feature = "std")]
+mod view;
+pub mod visit;
+#[cfg(feature = "vulkan")]
+mod vulkan;
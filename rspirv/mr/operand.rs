@@ -27,6 +27,7 @@ pub enum Operand {
     MemorySemantics(spirv::MemorySemantics),
     MemoryAccess(spirv::MemoryAccess),
     KernelProfilingInfo(spirv::KernelProfilingInfo),
+    CooperativeMatrixOperands(spirv::CooperativeMatrixOperands),
     SourceLanguage(spirv::SourceLanguage),
     ExecutionModel(spirv::ExecutionModel),
     AddressingModel(spirv::AddressingModel),
@@ -49,6 +50,8 @@ pub enum Operand {
     GroupOperation(spirv::GroupOperation),
     KernelEnqueueFlags(spirv::KernelEnqueueFlags),
     Capability(spirv::Capability),
+    CooperativeMatrixLayout(spirv::CooperativeMatrixLayout),
+    CooperativeMatrixUse(spirv::CooperativeMatrixUse),
     IdMemorySemantics(spirv::Word),
     IdScope(spirv::Word),
     IdRef(spirv::Word),
@@ -57,6 +60,7 @@ pub enum Operand {
     LiteralFloat32(f32),
     LiteralFloat64(f64),
     LiteralExtInstInteger(u32),
+    ExtInstOpcode(grammar::ExtInstSet, u32),
     LiteralSpecConstantOpInteger(spirv::Op),
     LiteralString(String),
 }
@@ -72,6 +76,7 @@ impl fmt::Display for Operand {
             Operand::MemorySemantics(ref v) => write!(f, "{:?}", v),
             Operand::MemoryAccess(ref v) => write!(f, "{:?}", v),
             Operand::KernelProfilingInfo(ref v) => write!(f, "{:?}", v),
+            Operand::CooperativeMatrixOperands(ref v) => write!(f, "{:?}", v),
             Operand::SourceLanguage(ref v) => write!(f, "{:?}", v),
             Operand::ExecutionModel(ref v) => write!(f, "{:?}", v),
             Operand::AddressingModel(ref v) => write!(f, "{:?}", v),
@@ -94,6 +99,8 @@ impl fmt::Display for Operand {
             Operand::GroupOperation(ref v) => write!(f, "{:?}", v),
             Operand::KernelEnqueueFlags(ref v) => write!(f, "{:?}", v),
             Operand::Capability(ref v) => write!(f, "{:?}", v),
+            Operand::CooperativeMatrixLayout(ref v) => write!(f, "{:?}", v),
+            Operand::CooperativeMatrixUse(ref v) => write!(f, "{:?}", v),
             Operand::IdMemorySemantics(ref v) => write!(f, "{:?}", v),
             Operand::IdScope(ref v) => write!(f, "{:?}", v),
             Operand::IdRef(ref v) => write!(f, "{:?}", v),
@@ -104,6 +111,498 @@ impl fmt::Display for Operand {
             Operand::LiteralInt64(ref v) => write!(f, "{:?}", v),
             Operand::LiteralFloat32(ref v) => write!(f, "{:?}", v),
             Operand::LiteralFloat64(ref v) => write!(f, "{:?}", v),
+            Operand::ExtInstOpcode(set, v) => match set.lookup_opcode(v) {
+                Some(inst) => write!(f, "{}", inst.opname),
+                None => write!(f, "{:?}", v),
+            },
+        }
+    }
+}
+
+impl Operand {
+    /// Returns the capabilities the value of this operand requires.
+    ///
+    /// Each entry lists alternatives, any one of which suffices.
+    pub fn required_capabilities(&self) -> Vec<&'static [spirv::Capability]> {
+        match *self {
+            Operand::ImageOperands(v) => {
+                let mut reqs: Vec<&'static [_]> = vec![];
+                if v.contains(spirv::ImageOperands::BIAS) { reqs.push(&[spirv::Capability::Shader]); }
+                if v.contains(spirv::ImageOperands::OFFSET) { reqs.push(&[spirv::Capability::ImageGatherExtended]); }
+                if v.contains(spirv::ImageOperands::MIN_LOD) { reqs.push(&[spirv::Capability::MinLod]); }
+                if v.contains(spirv::ImageOperands::MAKE_TEXEL_AVAILABLE) { reqs.push(&[spirv::Capability::VulkanMemoryModel]); }
+                if v.contains(spirv::ImageOperands::MAKE_TEXEL_VISIBLE) { reqs.push(&[spirv::Capability::VulkanMemoryModel]); }
+                if v.contains(spirv::ImageOperands::NON_PRIVATE_TEXEL) { reqs.push(&[spirv::Capability::VulkanMemoryModel]); }
+                if v.contains(spirv::ImageOperands::VOLATILE_TEXEL) { reqs.push(&[spirv::Capability::VulkanMemoryModel]); }
+                reqs
+            }
+            Operand::FPFastMathMode(v) => {
+                let mut reqs: Vec<&'static [_]> = vec![];
+                if v.contains(spirv::FPFastMathMode::NOT_NAN) { reqs.push(&[spirv::Capability::Kernel]); }
+                if v.contains(spirv::FPFastMathMode::NOT_INF) { reqs.push(&[spirv::Capability::Kernel]); }
+                if v.contains(spirv::FPFastMathMode::NSZ) { reqs.push(&[spirv::Capability::Kernel]); }
+                if v.contains(spirv::FPFastMathMode::ALLOW_RECIP) { reqs.push(&[spirv::Capability::Kernel]); }
+                if v.contains(spirv::FPFastMathMode::FAST) { reqs.push(&[spirv::Capability::Kernel]); }
+                reqs
+            }
+            Operand::MemorySemantics(v) => {
+                let mut reqs: Vec<&'static [_]> = vec![];
+                if v.contains(spirv::MemorySemantics::UNIFORM_MEMORY) { reqs.push(&[spirv::Capability::Shader]); }
+                if v.contains(spirv::MemorySemantics::ATOMIC_COUNTER_MEMORY) { reqs.push(&[spirv::Capability::AtomicStorage]); }
+                if v.contains(spirv::MemorySemantics::OUTPUT_MEMORY) { reqs.push(&[spirv::Capability::VulkanMemoryModel]); }
+                if v.contains(spirv::MemorySemantics::MAKE_AVAILABLE) { reqs.push(&[spirv::Capability::VulkanMemoryModel]); }
+                if v.contains(spirv::MemorySemantics::MAKE_VISIBLE) { reqs.push(&[spirv::Capability::VulkanMemoryModel]); }
+                if v.contains(spirv::MemorySemantics::VOLATILE) { reqs.push(&[spirv::Capability::VulkanMemoryModel]); }
+                reqs
+            }
+            Operand::MemoryAccess(v) => {
+                let mut reqs: Vec<&'static [_]> = vec![];
+                if v.contains(spirv::MemoryAccess::MAKE_POINTER_AVAILABLE) { reqs.push(&[spirv::Capability::VulkanMemoryModel]); }
+                if v.contains(spirv::MemoryAccess::MAKE_POINTER_VISIBLE) { reqs.push(&[spirv::Capability::VulkanMemoryModel]); }
+                if v.contains(spirv::MemoryAccess::NON_PRIVATE_POINTER) { reqs.push(&[spirv::Capability::VulkanMemoryModel]); }
+                reqs
+            }
+            Operand::KernelProfilingInfo(v) => {
+                let mut reqs: Vec<&'static [_]> = vec![];
+                if v.contains(spirv::KernelProfilingInfo::CMD_EXEC_TIME) { reqs.push(&[spirv::Capability::Kernel]); }
+                reqs
+            }
+            Operand::ExecutionModel(spirv::ExecutionModel::Vertex) => vec![&[spirv::Capability::Shader]],
+            Operand::ExecutionModel(spirv::ExecutionModel::TessellationControl) => vec![&[spirv::Capability::Tessellation]],
+            Operand::ExecutionModel(spirv::ExecutionModel::TessellationEvaluation) => vec![&[spirv::Capability::Tessellation]],
+            Operand::ExecutionModel(spirv::ExecutionModel::Geometry) => vec![&[spirv::Capability::Geometry]],
+            Operand::ExecutionModel(spirv::ExecutionModel::Fragment) => vec![&[spirv::Capability::Shader]],
+            Operand::ExecutionModel(spirv::ExecutionModel::GLCompute) => vec![&[spirv::Capability::Shader]],
+            Operand::ExecutionModel(spirv::ExecutionModel::Kernel) => vec![&[spirv::Capability::Kernel]],
+            Operand::ExecutionModel(spirv::ExecutionModel::RayGenerationKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::ExecutionModel(spirv::ExecutionModel::IntersectionKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::ExecutionModel(spirv::ExecutionModel::AnyHitKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::ExecutionModel(spirv::ExecutionModel::ClosestHitKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::ExecutionModel(spirv::ExecutionModel::MissKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::ExecutionModel(spirv::ExecutionModel::CallableKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::ExecutionModel(spirv::ExecutionModel::TaskEXT) => vec![&[spirv::Capability::MeshShadingEXT]],
+            Operand::ExecutionModel(spirv::ExecutionModel::MeshEXT) => vec![&[spirv::Capability::MeshShadingEXT]],
+            Operand::AddressingModel(spirv::AddressingModel::Physical32) => vec![&[spirv::Capability::Addresses]],
+            Operand::AddressingModel(spirv::AddressingModel::Physical64) => vec![&[spirv::Capability::Addresses]],
+            Operand::AddressingModel(spirv::AddressingModel::PhysicalStorageBuffer64) => vec![&[spirv::Capability::PhysicalStorageBufferAddresses]],
+            Operand::MemoryModel(spirv::MemoryModel::Simple) => vec![&[spirv::Capability::Shader]],
+            Operand::MemoryModel(spirv::MemoryModel::GLSL450) => vec![&[spirv::Capability::Shader]],
+            Operand::MemoryModel(spirv::MemoryModel::OpenCL) => vec![&[spirv::Capability::Kernel]],
+            Operand::MemoryModel(spirv::MemoryModel::Vulkan) => vec![&[spirv::Capability::VulkanMemoryModel]],
+            Operand::ExecutionMode(spirv::ExecutionMode::Invocations) => vec![&[spirv::Capability::Geometry]],
+            Operand::ExecutionMode(spirv::ExecutionMode::SpacingEqual) => vec![&[spirv::Capability::Tessellation]],
+            Operand::ExecutionMode(spirv::ExecutionMode::SpacingFractionalEven) => vec![&[spirv::Capability::Tessellation]],
+            Operand::ExecutionMode(spirv::ExecutionMode::SpacingFractionalOdd) => vec![&[spirv::Capability::Tessellation]],
+            Operand::ExecutionMode(spirv::ExecutionMode::VertexOrderCw) => vec![&[spirv::Capability::Tessellation]],
+            Operand::ExecutionMode(spirv::ExecutionMode::VertexOrderCcw) => vec![&[spirv::Capability::Tessellation]],
+            Operand::ExecutionMode(spirv::ExecutionMode::PixelCenterInteger) => vec![&[spirv::Capability::Shader]],
+            Operand::ExecutionMode(spirv::ExecutionMode::OriginUpperLeft) => vec![&[spirv::Capability::Shader]],
+            Operand::ExecutionMode(spirv::ExecutionMode::OriginLowerLeft) => vec![&[spirv::Capability::Shader]],
+            Operand::ExecutionMode(spirv::ExecutionMode::EarlyFragmentTests) => vec![&[spirv::Capability::Shader]],
+            Operand::ExecutionMode(spirv::ExecutionMode::PointMode) => vec![&[spirv::Capability::Tessellation]],
+            Operand::ExecutionMode(spirv::ExecutionMode::Xfb) => vec![&[spirv::Capability::TransformFeedback]],
+            Operand::ExecutionMode(spirv::ExecutionMode::DepthReplacing) => vec![&[spirv::Capability::Shader]],
+            Operand::ExecutionMode(spirv::ExecutionMode::DepthGreater) => vec![&[spirv::Capability::Shader]],
+            Operand::ExecutionMode(spirv::ExecutionMode::DepthLess) => vec![&[spirv::Capability::Shader]],
+            Operand::ExecutionMode(spirv::ExecutionMode::DepthUnchanged) => vec![&[spirv::Capability::Shader]],
+            Operand::ExecutionMode(spirv::ExecutionMode::LocalSizeHint) => vec![&[spirv::Capability::Kernel]],
+            Operand::ExecutionMode(spirv::ExecutionMode::InputPoints) => vec![&[spirv::Capability::Geometry]],
+            Operand::ExecutionMode(spirv::ExecutionMode::InputLines) => vec![&[spirv::Capability::Geometry]],
+            Operand::ExecutionMode(spirv::ExecutionMode::InputLinesAdjacency) => vec![&[spirv::Capability::Geometry]],
+            Operand::ExecutionMode(spirv::ExecutionMode::Triangles) => vec![&[spirv::Capability::Geometry, spirv::Capability::Tessellation]],
+            Operand::ExecutionMode(spirv::ExecutionMode::InputTrianglesAdjacency) => vec![&[spirv::Capability::Geometry]],
+            Operand::ExecutionMode(spirv::ExecutionMode::Quads) => vec![&[spirv::Capability::Tessellation]],
+            Operand::ExecutionMode(spirv::ExecutionMode::Isolines) => vec![&[spirv::Capability::Tessellation]],
+            Operand::ExecutionMode(spirv::ExecutionMode::OutputVertices) => vec![&[spirv::Capability::Geometry, spirv::Capability::Tessellation, spirv::Capability::MeshShadingEXT]],
+            Operand::ExecutionMode(spirv::ExecutionMode::OutputPoints) => vec![&[spirv::Capability::Geometry, spirv::Capability::MeshShadingEXT]],
+            Operand::ExecutionMode(spirv::ExecutionMode::OutputLineStrip) => vec![&[spirv::Capability::Geometry]],
+            Operand::ExecutionMode(spirv::ExecutionMode::OutputTriangleStrip) => vec![&[spirv::Capability::Geometry]],
+            Operand::ExecutionMode(spirv::ExecutionMode::VecTypeHint) => vec![&[spirv::Capability::Kernel]],
+            Operand::ExecutionMode(spirv::ExecutionMode::ContractionOff) => vec![&[spirv::Capability::Kernel]],
+            Operand::ExecutionMode(spirv::ExecutionMode::Initializer) => vec![&[spirv::Capability::Kernel]],
+            Operand::ExecutionMode(spirv::ExecutionMode::Finalizer) => vec![&[spirv::Capability::Kernel]],
+            Operand::ExecutionMode(spirv::ExecutionMode::SubgroupSize) => vec![&[spirv::Capability::SubgroupDispatch]],
+            Operand::ExecutionMode(spirv::ExecutionMode::SubgroupsPerWorkgroup) => vec![&[spirv::Capability::SubgroupDispatch]],
+            Operand::ExecutionMode(spirv::ExecutionMode::SubgroupsPerWorkgroupId) => vec![&[spirv::Capability::SubgroupDispatch]],
+            Operand::ExecutionMode(spirv::ExecutionMode::LocalSizeHintId) => vec![&[spirv::Capability::Kernel]],
+            Operand::ExecutionMode(spirv::ExecutionMode::PostDepthCoverage) => vec![&[spirv::Capability::SampleMaskPostDepthCoverage]],
+            Operand::ExecutionMode(spirv::ExecutionMode::DenormPreserve) => vec![&[spirv::Capability::DenormPreserve]],
+            Operand::ExecutionMode(spirv::ExecutionMode::DenormFlushToZero) => vec![&[spirv::Capability::DenormFlushToZero]],
+            Operand::ExecutionMode(spirv::ExecutionMode::SignedZeroInfNanPreserve) => vec![&[spirv::Capability::SignedZeroInfNanPreserve]],
+            Operand::ExecutionMode(spirv::ExecutionMode::RoundingModeRTE) => vec![&[spirv::Capability::RoundingModeRTE]],
+            Operand::ExecutionMode(spirv::ExecutionMode::RoundingModeRTZ) => vec![&[spirv::Capability::RoundingModeRTZ]],
+            Operand::ExecutionMode(spirv::ExecutionMode::StencilRefReplacingEXT) => vec![&[spirv::Capability::StencilExportEXT]],
+            Operand::ExecutionMode(spirv::ExecutionMode::OutputLinesEXT) => vec![&[spirv::Capability::MeshShadingEXT]],
+            Operand::ExecutionMode(spirv::ExecutionMode::OutputPrimitivesEXT) => vec![&[spirv::Capability::MeshShadingEXT]],
+            Operand::ExecutionMode(spirv::ExecutionMode::OutputTrianglesEXT) => vec![&[spirv::Capability::MeshShadingEXT]],
+            Operand::StorageClass(spirv::StorageClass::Uniform) => vec![&[spirv::Capability::Shader]],
+            Operand::StorageClass(spirv::StorageClass::Output) => vec![&[spirv::Capability::Shader]],
+            Operand::StorageClass(spirv::StorageClass::Private) => vec![&[spirv::Capability::Shader]],
+            Operand::StorageClass(spirv::StorageClass::Generic) => vec![&[spirv::Capability::GenericPointer]],
+            Operand::StorageClass(spirv::StorageClass::PushConstant) => vec![&[spirv::Capability::Shader]],
+            Operand::StorageClass(spirv::StorageClass::AtomicCounter) => vec![&[spirv::Capability::AtomicStorage]],
+            Operand::StorageClass(spirv::StorageClass::StorageBuffer) => vec![&[spirv::Capability::Shader]],
+            Operand::StorageClass(spirv::StorageClass::CallableDataKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::StorageClass(spirv::StorageClass::IncomingCallableDataKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::StorageClass(spirv::StorageClass::RayPayloadKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::StorageClass(spirv::StorageClass::HitAttributeKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::StorageClass(spirv::StorageClass::IncomingRayPayloadKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::StorageClass(spirv::StorageClass::ShaderRecordBufferKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::StorageClass(spirv::StorageClass::PhysicalStorageBuffer) => vec![&[spirv::Capability::PhysicalStorageBufferAddresses]],
+            Operand::StorageClass(spirv::StorageClass::TaskPayloadWorkgroupEXT) => vec![&[spirv::Capability::MeshShadingEXT]],
+            Operand::Dim(spirv::Dim::Dim1D) => vec![&[spirv::Capability::Sampled1D]],
+            Operand::Dim(spirv::Dim::DimCube) => vec![&[spirv::Capability::Shader]],
+            Operand::Dim(spirv::Dim::DimRect) => vec![&[spirv::Capability::SampledRect]],
+            Operand::Dim(spirv::Dim::DimBuffer) => vec![&[spirv::Capability::SampledBuffer]],
+            Operand::Dim(spirv::Dim::DimSubpassData) => vec![&[spirv::Capability::InputAttachment]],
+            Operand::SamplerAddressingMode(spirv::SamplerAddressingMode::None) => vec![&[spirv::Capability::Kernel]],
+            Operand::SamplerAddressingMode(spirv::SamplerAddressingMode::ClampToEdge) => vec![&[spirv::Capability::Kernel]],
+            Operand::SamplerAddressingMode(spirv::SamplerAddressingMode::Clamp) => vec![&[spirv::Capability::Kernel]],
+            Operand::SamplerAddressingMode(spirv::SamplerAddressingMode::Repeat) => vec![&[spirv::Capability::Kernel]],
+            Operand::SamplerAddressingMode(spirv::SamplerAddressingMode::RepeatMirrored) => vec![&[spirv::Capability::Kernel]],
+            Operand::SamplerFilterMode(spirv::SamplerFilterMode::Nearest) => vec![&[spirv::Capability::Kernel]],
+            Operand::SamplerFilterMode(spirv::SamplerFilterMode::Linear) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageFormat(spirv::ImageFormat::Rgba32f) => vec![&[spirv::Capability::Shader]],
+            Operand::ImageFormat(spirv::ImageFormat::Rgba16f) => vec![&[spirv::Capability::Shader]],
+            Operand::ImageFormat(spirv::ImageFormat::R32f) => vec![&[spirv::Capability::Shader]],
+            Operand::ImageFormat(spirv::ImageFormat::Rgba8) => vec![&[spirv::Capability::Shader]],
+            Operand::ImageFormat(spirv::ImageFormat::Rgba8Snorm) => vec![&[spirv::Capability::Shader]],
+            Operand::ImageFormat(spirv::ImageFormat::Rg32f) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::Rg16f) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::R11fG11fB10f) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::R16f) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::Rgba16) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::Rgb10A2) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::Rg16) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::Rg8) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::R16) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::R8) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::Rgba16Snorm) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::Rg16Snorm) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::Rg8Snorm) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::R16Snorm) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::R8Snorm) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::Rgba32i) => vec![&[spirv::Capability::Shader]],
+            Operand::ImageFormat(spirv::ImageFormat::Rgba16i) => vec![&[spirv::Capability::Shader]],
+            Operand::ImageFormat(spirv::ImageFormat::Rgba8i) => vec![&[spirv::Capability::Shader]],
+            Operand::ImageFormat(spirv::ImageFormat::R32i) => vec![&[spirv::Capability::Shader]],
+            Operand::ImageFormat(spirv::ImageFormat::Rg32i) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::Rg16i) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::Rg8i) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::R16i) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::R8i) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::Rgba32ui) => vec![&[spirv::Capability::Shader]],
+            Operand::ImageFormat(spirv::ImageFormat::Rgba16ui) => vec![&[spirv::Capability::Shader]],
+            Operand::ImageFormat(spirv::ImageFormat::Rgba8ui) => vec![&[spirv::Capability::Shader]],
+            Operand::ImageFormat(spirv::ImageFormat::R32ui) => vec![&[spirv::Capability::Shader]],
+            Operand::ImageFormat(spirv::ImageFormat::Rgb10a2ui) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::Rg32ui) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::Rg16ui) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::Rg8ui) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::R16ui) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageFormat(spirv::ImageFormat::R8ui) => vec![&[spirv::Capability::StorageImageExtendedFormats]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::R) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::A) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::RG) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::RA) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::RGB) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::RGBA) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::BGRA) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::ARGB) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::Intensity) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::Luminance) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::Rx) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::RGx) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::RGBx) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::Depth) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::DepthStencil) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::sRGB) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::sRGBx) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::sRGBA) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::sBGRA) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelOrder(spirv::ImageChannelOrder::ABGR) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelDataType(spirv::ImageChannelDataType::SnormInt8) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelDataType(spirv::ImageChannelDataType::SnormInt16) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelDataType(spirv::ImageChannelDataType::UnormInt8) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelDataType(spirv::ImageChannelDataType::UnormInt16) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelDataType(spirv::ImageChannelDataType::UnormShort565) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelDataType(spirv::ImageChannelDataType::UnormShort555) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelDataType(spirv::ImageChannelDataType::UnormInt101010) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelDataType(spirv::ImageChannelDataType::SignedInt8) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelDataType(spirv::ImageChannelDataType::SignedInt16) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelDataType(spirv::ImageChannelDataType::SignedInt32) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelDataType(spirv::ImageChannelDataType::UnsignedInt8) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelDataType(spirv::ImageChannelDataType::UnsignedInt16) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelDataType(spirv::ImageChannelDataType::UnsignedInt32) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelDataType(spirv::ImageChannelDataType::HalfFloat) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelDataType(spirv::ImageChannelDataType::Float) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelDataType(spirv::ImageChannelDataType::UnormInt24) => vec![&[spirv::Capability::Kernel]],
+            Operand::ImageChannelDataType(spirv::ImageChannelDataType::UnormInt101010_2) => vec![&[spirv::Capability::Kernel]],
+            Operand::FPRoundingMode(spirv::FPRoundingMode::RTE) => vec![&[spirv::Capability::Kernel, spirv::Capability::StorageUniformBufferBlock16, spirv::Capability::StorageUniform16, spirv::Capability::StoragePushConstant16, spirv::Capability::StorageInputOutput16]],
+            Operand::FPRoundingMode(spirv::FPRoundingMode::RTZ) => vec![&[spirv::Capability::Kernel, spirv::Capability::StorageUniformBufferBlock16, spirv::Capability::StorageUniform16, spirv::Capability::StoragePushConstant16, spirv::Capability::StorageInputOutput16]],
+            Operand::FPRoundingMode(spirv::FPRoundingMode::RTP) => vec![&[spirv::Capability::Kernel, spirv::Capability::StorageUniformBufferBlock16, spirv::Capability::StorageUniform16, spirv::Capability::StoragePushConstant16, spirv::Capability::StorageInputOutput16]],
+            Operand::FPRoundingMode(spirv::FPRoundingMode::RTN) => vec![&[spirv::Capability::Kernel, spirv::Capability::StorageUniformBufferBlock16, spirv::Capability::StorageUniform16, spirv::Capability::StoragePushConstant16, spirv::Capability::StorageInputOutput16]],
+            Operand::LinkageType(spirv::LinkageType::Export) => vec![&[spirv::Capability::Linkage]],
+            Operand::LinkageType(spirv::LinkageType::Import) => vec![&[spirv::Capability::Linkage]],
+            Operand::AccessQualifier(spirv::AccessQualifier::ReadOnly) => vec![&[spirv::Capability::Kernel]],
+            Operand::AccessQualifier(spirv::AccessQualifier::WriteOnly) => vec![&[spirv::Capability::Kernel]],
+            Operand::AccessQualifier(spirv::AccessQualifier::ReadWrite) => vec![&[spirv::Capability::Kernel]],
+            Operand::FunctionParameterAttribute(spirv::FunctionParameterAttribute::Zext) => vec![&[spirv::Capability::Kernel]],
+            Operand::FunctionParameterAttribute(spirv::FunctionParameterAttribute::Sext) => vec![&[spirv::Capability::Kernel]],
+            Operand::FunctionParameterAttribute(spirv::FunctionParameterAttribute::ByVal) => vec![&[spirv::Capability::Kernel]],
+            Operand::FunctionParameterAttribute(spirv::FunctionParameterAttribute::Sret) => vec![&[spirv::Capability::Kernel]],
+            Operand::FunctionParameterAttribute(spirv::FunctionParameterAttribute::NoAlias) => vec![&[spirv::Capability::Kernel]],
+            Operand::FunctionParameterAttribute(spirv::FunctionParameterAttribute::NoCapture) => vec![&[spirv::Capability::Kernel]],
+            Operand::FunctionParameterAttribute(spirv::FunctionParameterAttribute::NoWrite) => vec![&[spirv::Capability::Kernel]],
+            Operand::FunctionParameterAttribute(spirv::FunctionParameterAttribute::NoReadWrite) => vec![&[spirv::Capability::Kernel]],
+            Operand::Decoration(spirv::Decoration::RelaxedPrecision) => vec![&[spirv::Capability::Shader]],
+            Operand::Decoration(spirv::Decoration::SpecId) => vec![&[spirv::Capability::Shader, spirv::Capability::Kernel]],
+            Operand::Decoration(spirv::Decoration::Block) => vec![&[spirv::Capability::Shader]],
+            Operand::Decoration(spirv::Decoration::BufferBlock) => vec![&[spirv::Capability::Shader]],
+            Operand::Decoration(spirv::Decoration::RowMajor) => vec![&[spirv::Capability::Matrix]],
+            Operand::Decoration(spirv::Decoration::ColMajor) => vec![&[spirv::Capability::Matrix]],
+            Operand::Decoration(spirv::Decoration::ArrayStride) => vec![&[spirv::Capability::Shader]],
+            Operand::Decoration(spirv::Decoration::MatrixStride) => vec![&[spirv::Capability::Matrix]],
+            Operand::Decoration(spirv::Decoration::GLSLShared) => vec![&[spirv::Capability::Shader]],
+            Operand::Decoration(spirv::Decoration::GLSLPacked) => vec![&[spirv::Capability::Shader]],
+            Operand::Decoration(spirv::Decoration::CPacked) => vec![&[spirv::Capability::Kernel]],
+            Operand::Decoration(spirv::Decoration::NoPerspective) => vec![&[spirv::Capability::Shader]],
+            Operand::Decoration(spirv::Decoration::Flat) => vec![&[spirv::Capability::Shader]],
+            Operand::Decoration(spirv::Decoration::Patch) => vec![&[spirv::Capability::Tessellation]],
+            Operand::Decoration(spirv::Decoration::Centroid) => vec![&[spirv::Capability::Shader]],
+            Operand::Decoration(spirv::Decoration::Sample) => vec![&[spirv::Capability::SampleRateShading]],
+            Operand::Decoration(spirv::Decoration::Invariant) => vec![&[spirv::Capability::Shader]],
+            Operand::Decoration(spirv::Decoration::Constant) => vec![&[spirv::Capability::Kernel]],
+            Operand::Decoration(spirv::Decoration::Uniform) => vec![&[spirv::Capability::Shader]],
+            Operand::Decoration(spirv::Decoration::SaturatedConversion) => vec![&[spirv::Capability::Kernel]],
+            Operand::Decoration(spirv::Decoration::Stream) => vec![&[spirv::Capability::GeometryStreams]],
+            Operand::Decoration(spirv::Decoration::Location) => vec![&[spirv::Capability::Shader]],
+            Operand::Decoration(spirv::Decoration::Component) => vec![&[spirv::Capability::Shader]],
+            Operand::Decoration(spirv::Decoration::Index) => vec![&[spirv::Capability::Shader]],
+            Operand::Decoration(spirv::Decoration::Binding) => vec![&[spirv::Capability::Shader]],
+            Operand::Decoration(spirv::Decoration::DescriptorSet) => vec![&[spirv::Capability::Shader]],
+            Operand::Decoration(spirv::Decoration::Offset) => vec![&[spirv::Capability::Shader]],
+            Operand::Decoration(spirv::Decoration::XfbBuffer) => vec![&[spirv::Capability::TransformFeedback]],
+            Operand::Decoration(spirv::Decoration::XfbStride) => vec![&[spirv::Capability::TransformFeedback]],
+            Operand::Decoration(spirv::Decoration::FuncParamAttr) => vec![&[spirv::Capability::Kernel]],
+            Operand::Decoration(spirv::Decoration::FPRoundingMode) => vec![&[spirv::Capability::Kernel, spirv::Capability::StorageUniformBufferBlock16, spirv::Capability::StorageUniform16, spirv::Capability::StoragePushConstant16, spirv::Capability::StorageInputOutput16]],
+            Operand::Decoration(spirv::Decoration::FPFastMathMode) => vec![&[spirv::Capability::Kernel]],
+            Operand::Decoration(spirv::Decoration::LinkageAttributes) => vec![&[spirv::Capability::Linkage]],
+            Operand::Decoration(spirv::Decoration::NoContraction) => vec![&[spirv::Capability::Shader]],
+            Operand::Decoration(spirv::Decoration::InputAttachmentIndex) => vec![&[spirv::Capability::InputAttachment]],
+            Operand::Decoration(spirv::Decoration::Alignment) => vec![&[spirv::Capability::Kernel]],
+            Operand::Decoration(spirv::Decoration::MaxByteOffset) => vec![&[spirv::Capability::Addresses]],
+            Operand::Decoration(spirv::Decoration::AlignmentId) => vec![&[spirv::Capability::Kernel]],
+            Operand::Decoration(spirv::Decoration::MaxByteOffsetId) => vec![&[spirv::Capability::Addresses]],
+            Operand::Decoration(spirv::Decoration::OverrideCoverageNV) => vec![&[spirv::Capability::SampleMaskOverrideCoverageNV]],
+            Operand::Decoration(spirv::Decoration::PassthroughNV) => vec![&[spirv::Capability::GeometryShaderPassthroughNV]],
+            Operand::Decoration(spirv::Decoration::ViewportRelativeNV) => vec![&[spirv::Capability::ShaderViewportMaskNV]],
+            Operand::Decoration(spirv::Decoration::SecondaryViewportRelativeNV) => vec![&[spirv::Capability::ShaderStereoViewNV]],
+            Operand::Decoration(spirv::Decoration::PerPrimitiveEXT) => vec![&[spirv::Capability::MeshShadingEXT]],
+            Operand::Decoration(spirv::Decoration::NonUniform) => vec![&[spirv::Capability::ShaderNonUniform]],
+            Operand::Decoration(spirv::Decoration::RestrictPointer) => vec![&[spirv::Capability::PhysicalStorageBufferAddresses]],
+            Operand::Decoration(spirv::Decoration::AliasedPointer) => vec![&[spirv::Capability::PhysicalStorageBufferAddresses]],
+            Operand::BuiltIn(spirv::BuiltIn::Position) => vec![&[spirv::Capability::Shader]],
+            Operand::BuiltIn(spirv::BuiltIn::PointSize) => vec![&[spirv::Capability::Shader]],
+            Operand::BuiltIn(spirv::BuiltIn::ClipDistance) => vec![&[spirv::Capability::ClipDistance]],
+            Operand::BuiltIn(spirv::BuiltIn::CullDistance) => vec![&[spirv::Capability::CullDistance]],
+            Operand::BuiltIn(spirv::BuiltIn::VertexId) => vec![&[spirv::Capability::Shader]],
+            Operand::BuiltIn(spirv::BuiltIn::InstanceId) => vec![&[spirv::Capability::Shader]],
+            Operand::BuiltIn(spirv::BuiltIn::PrimitiveId) => vec![&[spirv::Capability::Geometry, spirv::Capability::Tessellation, spirv::Capability::MeshShadingEXT]],
+            Operand::BuiltIn(spirv::BuiltIn::InvocationId) => vec![&[spirv::Capability::Geometry, spirv::Capability::Tessellation]],
+            Operand::BuiltIn(spirv::BuiltIn::Layer) => vec![&[spirv::Capability::Geometry, spirv::Capability::MeshShadingEXT]],
+            Operand::BuiltIn(spirv::BuiltIn::ViewportIndex) => vec![&[spirv::Capability::MultiViewport, spirv::Capability::MeshShadingEXT]],
+            Operand::BuiltIn(spirv::BuiltIn::TessLevelOuter) => vec![&[spirv::Capability::Tessellation]],
+            Operand::BuiltIn(spirv::BuiltIn::TessLevelInner) => vec![&[spirv::Capability::Tessellation]],
+            Operand::BuiltIn(spirv::BuiltIn::TessCoord) => vec![&[spirv::Capability::Tessellation]],
+            Operand::BuiltIn(spirv::BuiltIn::PatchVertices) => vec![&[spirv::Capability::Tessellation]],
+            Operand::BuiltIn(spirv::BuiltIn::FragCoord) => vec![&[spirv::Capability::Shader]],
+            Operand::BuiltIn(spirv::BuiltIn::PointCoord) => vec![&[spirv::Capability::Shader]],
+            Operand::BuiltIn(spirv::BuiltIn::FrontFacing) => vec![&[spirv::Capability::Shader]],
+            Operand::BuiltIn(spirv::BuiltIn::SampleId) => vec![&[spirv::Capability::SampleRateShading]],
+            Operand::BuiltIn(spirv::BuiltIn::SamplePosition) => vec![&[spirv::Capability::SampleRateShading]],
+            Operand::BuiltIn(spirv::BuiltIn::SampleMask) => vec![&[spirv::Capability::Shader]],
+            Operand::BuiltIn(spirv::BuiltIn::FragDepth) => vec![&[spirv::Capability::Shader]],
+            Operand::BuiltIn(spirv::BuiltIn::HelperInvocation) => vec![&[spirv::Capability::Shader]],
+            Operand::BuiltIn(spirv::BuiltIn::WorkDim) => vec![&[spirv::Capability::Kernel]],
+            Operand::BuiltIn(spirv::BuiltIn::GlobalSize) => vec![&[spirv::Capability::Kernel]],
+            Operand::BuiltIn(spirv::BuiltIn::EnqueuedWorkgroupSize) => vec![&[spirv::Capability::Kernel]],
+            Operand::BuiltIn(spirv::BuiltIn::GlobalOffset) => vec![&[spirv::Capability::Kernel]],
+            Operand::BuiltIn(spirv::BuiltIn::GlobalLinearId) => vec![&[spirv::Capability::Kernel]],
+            Operand::BuiltIn(spirv::BuiltIn::SubgroupSize) => vec![&[spirv::Capability::Kernel]],
+            Operand::BuiltIn(spirv::BuiltIn::SubgroupMaxSize) => vec![&[spirv::Capability::Kernel]],
+            Operand::BuiltIn(spirv::BuiltIn::NumSubgroups) => vec![&[spirv::Capability::Kernel]],
+            Operand::BuiltIn(spirv::BuiltIn::NumEnqueuedSubgroups) => vec![&[spirv::Capability::Kernel]],
+            Operand::BuiltIn(spirv::BuiltIn::SubgroupId) => vec![&[spirv::Capability::Kernel]],
+            Operand::BuiltIn(spirv::BuiltIn::SubgroupLocalInvocationId) => vec![&[spirv::Capability::Kernel]],
+            Operand::BuiltIn(spirv::BuiltIn::VertexIndex) => vec![&[spirv::Capability::Shader]],
+            Operand::BuiltIn(spirv::BuiltIn::InstanceIndex) => vec![&[spirv::Capability::Shader]],
+            Operand::BuiltIn(spirv::BuiltIn::SubgroupEqMaskKHR) => vec![&[spirv::Capability::SubgroupBallotKHR]],
+            Operand::BuiltIn(spirv::BuiltIn::SubgroupGeMaskKHR) => vec![&[spirv::Capability::SubgroupBallotKHR]],
+            Operand::BuiltIn(spirv::BuiltIn::SubgroupGtMaskKHR) => vec![&[spirv::Capability::SubgroupBallotKHR]],
+            Operand::BuiltIn(spirv::BuiltIn::SubgroupLeMaskKHR) => vec![&[spirv::Capability::SubgroupBallotKHR]],
+            Operand::BuiltIn(spirv::BuiltIn::SubgroupLtMaskKHR) => vec![&[spirv::Capability::SubgroupBallotKHR]],
+            Operand::BuiltIn(spirv::BuiltIn::BaseVertex) => vec![&[spirv::Capability::DrawParameters]],
+            Operand::BuiltIn(spirv::BuiltIn::BaseInstance) => vec![&[spirv::Capability::DrawParameters]],
+            Operand::BuiltIn(spirv::BuiltIn::DrawIndex) => vec![&[spirv::Capability::DrawParameters]],
+            Operand::BuiltIn(spirv::BuiltIn::DeviceIndex) => vec![&[spirv::Capability::DeviceGroup]],
+            Operand::BuiltIn(spirv::BuiltIn::ViewIndex) => vec![&[spirv::Capability::MultiView]],
+            Operand::BuiltIn(spirv::BuiltIn::FragStencilRefEXT) => vec![&[spirv::Capability::StencilExportEXT]],
+            Operand::BuiltIn(spirv::BuiltIn::ViewportMaskNV) => vec![&[spirv::Capability::ShaderViewportMaskNV]],
+            Operand::BuiltIn(spirv::BuiltIn::SecondaryPositionNV) => vec![&[spirv::Capability::ShaderStereoViewNV]],
+            Operand::BuiltIn(spirv::BuiltIn::SecondaryViewportMaskNV) => vec![&[spirv::Capability::ShaderStereoViewNV]],
+            Operand::BuiltIn(spirv::BuiltIn::PositionPerViewNV) => vec![&[spirv::Capability::PerViewAttributesNV]],
+            Operand::BuiltIn(spirv::BuiltIn::ViewportMaskPerViewNV) => vec![&[spirv::Capability::PerViewAttributesNV]],
+            Operand::BuiltIn(spirv::BuiltIn::PrimitivePointIndicesEXT) => vec![&[spirv::Capability::MeshShadingEXT]],
+            Operand::BuiltIn(spirv::BuiltIn::PrimitiveLineIndicesEXT) => vec![&[spirv::Capability::MeshShadingEXT]],
+            Operand::BuiltIn(spirv::BuiltIn::PrimitiveTriangleIndicesEXT) => vec![&[spirv::Capability::MeshShadingEXT]],
+            Operand::BuiltIn(spirv::BuiltIn::CullPrimitiveEXT) => vec![&[spirv::Capability::MeshShadingEXT]],
+            Operand::BuiltIn(spirv::BuiltIn::LaunchIdKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::BuiltIn(spirv::BuiltIn::LaunchSizeKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::BuiltIn(spirv::BuiltIn::WorldRayOriginKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::BuiltIn(spirv::BuiltIn::WorldRayDirectionKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::BuiltIn(spirv::BuiltIn::ObjectRayOriginKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::BuiltIn(spirv::BuiltIn::ObjectRayDirectionKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::BuiltIn(spirv::BuiltIn::RayTminKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::BuiltIn(spirv::BuiltIn::RayTmaxKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::BuiltIn(spirv::BuiltIn::InstanceCustomIndexKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::BuiltIn(spirv::BuiltIn::ObjectToWorldKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::BuiltIn(spirv::BuiltIn::WorldToObjectKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::BuiltIn(spirv::BuiltIn::HitKindKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::BuiltIn(spirv::BuiltIn::IncomingRayFlagsKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::BuiltIn(spirv::BuiltIn::RayGeometryIndexKHR) => vec![&[spirv::Capability::RayTracingKHR]],
+            Operand::Scope(spirv::Scope::QueueFamily) => vec![&[spirv::Capability::VulkanMemoryModel]],
+            Operand::GroupOperation(spirv::GroupOperation::Reduce) => vec![&[spirv::Capability::Kernel]],
+            Operand::GroupOperation(spirv::GroupOperation::InclusiveScan) => vec![&[spirv::Capability::Kernel]],
+            Operand::GroupOperation(spirv::GroupOperation::ExclusiveScan) => vec![&[spirv::Capability::Kernel]],
+            Operand::KernelEnqueueFlags(spirv::KernelEnqueueFlags::NoWait) => vec![&[spirv::Capability::Kernel]],
+            Operand::KernelEnqueueFlags(spirv::KernelEnqueueFlags::WaitKernel) => vec![&[spirv::Capability::Kernel]],
+            Operand::KernelEnqueueFlags(spirv::KernelEnqueueFlags::WaitWorkGroup) => vec![&[spirv::Capability::Kernel]],
+            _ => vec![],
+        }
+    }
+
+    /// Returns the extensions the value of this operand requires.
+    ///
+    /// Each entry lists alternatives, any one of which suffices.
+    pub fn required_extensions(&self) -> Vec<&'static [&'static str]> {
+        match *self {
+            Operand::ImageOperands(v) => {
+                let mut reqs: Vec<&'static [_]> = vec![];
+                if v.contains(spirv::ImageOperands::MAKE_TEXEL_AVAILABLE) { reqs.push(&["SPV_KHR_vulkan_memory_model"]); }
+                if v.contains(spirv::ImageOperands::MAKE_TEXEL_VISIBLE) { reqs.push(&["SPV_KHR_vulkan_memory_model"]); }
+                if v.contains(spirv::ImageOperands::NON_PRIVATE_TEXEL) { reqs.push(&["SPV_KHR_vulkan_memory_model"]); }
+                if v.contains(spirv::ImageOperands::VOLATILE_TEXEL) { reqs.push(&["SPV_KHR_vulkan_memory_model"]); }
+                reqs
+            }
+            Operand::MemorySemantics(v) => {
+                let mut reqs: Vec<&'static [_]> = vec![];
+                if v.contains(spirv::MemorySemantics::OUTPUT_MEMORY) { reqs.push(&["SPV_KHR_vulkan_memory_model"]); }
+                if v.contains(spirv::MemorySemantics::MAKE_AVAILABLE) { reqs.push(&["SPV_KHR_vulkan_memory_model"]); }
+                if v.contains(spirv::MemorySemantics::MAKE_VISIBLE) { reqs.push(&["SPV_KHR_vulkan_memory_model"]); }
+                if v.contains(spirv::MemorySemantics::VOLATILE) { reqs.push(&["SPV_KHR_vulkan_memory_model"]); }
+                reqs
+            }
+            Operand::MemoryAccess(v) => {
+                let mut reqs: Vec<&'static [_]> = vec![];
+                if v.contains(spirv::MemoryAccess::MAKE_POINTER_AVAILABLE) { reqs.push(&["SPV_KHR_vulkan_memory_model"]); }
+                if v.contains(spirv::MemoryAccess::MAKE_POINTER_VISIBLE) { reqs.push(&["SPV_KHR_vulkan_memory_model"]); }
+                if v.contains(spirv::MemoryAccess::NON_PRIVATE_POINTER) { reqs.push(&["SPV_KHR_vulkan_memory_model"]); }
+                reqs
+            }
+            Operand::AddressingModel(spirv::AddressingModel::PhysicalStorageBuffer64) => vec![&["SPV_EXT_physical_storage_buffer", "SPV_KHR_physical_storage_buffer"]],
+            Operand::MemoryModel(spirv::MemoryModel::Vulkan) => vec![&["SPV_KHR_vulkan_memory_model"]],
+            Operand::ExecutionMode(spirv::ExecutionMode::DenormPreserve) => vec![&["SPV_KHR_float_controls"]],
+            Operand::ExecutionMode(spirv::ExecutionMode::DenormFlushToZero) => vec![&["SPV_KHR_float_controls"]],
+            Operand::ExecutionMode(spirv::ExecutionMode::SignedZeroInfNanPreserve) => vec![&["SPV_KHR_float_controls"]],
+            Operand::ExecutionMode(spirv::ExecutionMode::RoundingModeRTE) => vec![&["SPV_KHR_float_controls"]],
+            Operand::ExecutionMode(spirv::ExecutionMode::RoundingModeRTZ) => vec![&["SPV_KHR_float_controls"]],
+            Operand::ExecutionMode(spirv::ExecutionMode::OutputLinesEXT) => vec![&["SPV_EXT_mesh_shader"]],
+            Operand::ExecutionMode(spirv::ExecutionMode::OutputPrimitivesEXT) => vec![&["SPV_EXT_mesh_shader"]],
+            Operand::ExecutionMode(spirv::ExecutionMode::OutputTrianglesEXT) => vec![&["SPV_EXT_mesh_shader"]],
+            Operand::StorageClass(spirv::StorageClass::StorageBuffer) => vec![&["SPV_KHR_storage_buffer_storage_class", "SPV_KHR_variable_pointers"]],
+            Operand::StorageClass(spirv::StorageClass::CallableDataKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::StorageClass(spirv::StorageClass::IncomingCallableDataKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::StorageClass(spirv::StorageClass::RayPayloadKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::StorageClass(spirv::StorageClass::HitAttributeKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::StorageClass(spirv::StorageClass::IncomingRayPayloadKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::StorageClass(spirv::StorageClass::ShaderRecordBufferKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::StorageClass(spirv::StorageClass::PhysicalStorageBuffer) => vec![&["SPV_EXT_physical_storage_buffer", "SPV_KHR_physical_storage_buffer"]],
+            Operand::StorageClass(spirv::StorageClass::TaskPayloadWorkgroupEXT) => vec![&["SPV_EXT_mesh_shader"]],
+            Operand::Decoration(spirv::Decoration::PerPrimitiveEXT) => vec![&["SPV_EXT_mesh_shader"]],
+            Operand::Decoration(spirv::Decoration::NonUniform) => vec![&["SPV_EXT_descriptor_indexing"]],
+            Operand::Decoration(spirv::Decoration::RestrictPointer) => vec![&["SPV_EXT_physical_storage_buffer", "SPV_KHR_physical_storage_buffer"]],
+            Operand::Decoration(spirv::Decoration::AliasedPointer) => vec![&["SPV_EXT_physical_storage_buffer", "SPV_KHR_physical_storage_buffer"]],
+            Operand::Decoration(spirv::Decoration::HlslCounterBufferGOOGLE) => vec![&["SPV_GOOGLE_hlsl_functionality1"]],
+            Operand::Decoration(spirv::Decoration::HlslSemanticGOOGLE) => vec![&["SPV_GOOGLE_hlsl_functionality1"]],
+            Operand::Decoration(spirv::Decoration::UserTypeGOOGLE) => vec![&["SPV_GOOGLE_user_type"]],
+            Operand::BuiltIn(spirv::BuiltIn::LaunchIdKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::BuiltIn(spirv::BuiltIn::LaunchSizeKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::BuiltIn(spirv::BuiltIn::WorldRayOriginKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::BuiltIn(spirv::BuiltIn::WorldRayDirectionKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::BuiltIn(spirv::BuiltIn::ObjectRayOriginKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::BuiltIn(spirv::BuiltIn::ObjectRayDirectionKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::BuiltIn(spirv::BuiltIn::RayTminKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::BuiltIn(spirv::BuiltIn::RayTmaxKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::BuiltIn(spirv::BuiltIn::InstanceCustomIndexKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::BuiltIn(spirv::BuiltIn::ObjectToWorldKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::BuiltIn(spirv::BuiltIn::WorldToObjectKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::BuiltIn(spirv::BuiltIn::HitKindKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::BuiltIn(spirv::BuiltIn::IncomingRayFlagsKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::BuiltIn(spirv::BuiltIn::RayGeometryIndexKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::Capability(spirv::Capability::SubgroupBallotKHR) => vec![&["SPV_KHR_shader_ballot"]],
+            Operand::Capability(spirv::Capability::DrawParameters) => vec![&["SPV_KHR_shader_draw_parameters"]],
+            Operand::Capability(spirv::Capability::SubgroupVoteKHR) => vec![&["SPV_KHR_subgroup_vote"]],
+            Operand::Capability(spirv::Capability::StorageBuffer16BitAccess) => vec![&["SPV_KHR_16bit_storage"]],
+            Operand::Capability(spirv::Capability::UniformAndStorageBuffer16BitAccess) => vec![&["SPV_KHR_16bit_storage"]],
+            Operand::Capability(spirv::Capability::StoragePushConstant16) => vec![&["SPV_KHR_16bit_storage"]],
+            Operand::Capability(spirv::Capability::StorageInputOutput16) => vec![&["SPV_KHR_16bit_storage"]],
+            Operand::Capability(spirv::Capability::DeviceGroup) => vec![&["SPV_KHR_device_group"]],
+            Operand::Capability(spirv::Capability::MultiView) => vec![&["SPV_KHR_multiview"]],
+            Operand::Capability(spirv::Capability::VariablePointersStorageBuffer) => vec![&["SPV_KHR_variable_pointers"]],
+            Operand::Capability(spirv::Capability::VariablePointers) => vec![&["SPV_KHR_variable_pointers"]],
+            Operand::Capability(spirv::Capability::AtomicStorageOps) => vec![&["SPV_KHR_shader_atomic_counter_ops"]],
+            Operand::Capability(spirv::Capability::SampleMaskPostDepthCoverage) => vec![&["SPV_KHR_post_depth_coverage"]],
+            Operand::Capability(spirv::Capability::StorageBuffer8BitAccess) => vec![&["SPV_KHR_8bit_storage"]],
+            Operand::Capability(spirv::Capability::UniformAndStorageBuffer8BitAccess) => vec![&["SPV_KHR_8bit_storage"]],
+            Operand::Capability(spirv::Capability::StoragePushConstant8) => vec![&["SPV_KHR_8bit_storage"]],
+            Operand::Capability(spirv::Capability::DenormPreserve) => vec![&["SPV_KHR_float_controls"]],
+            Operand::Capability(spirv::Capability::DenormFlushToZero) => vec![&["SPV_KHR_float_controls"]],
+            Operand::Capability(spirv::Capability::SignedZeroInfNanPreserve) => vec![&["SPV_KHR_float_controls"]],
+            Operand::Capability(spirv::Capability::RoundingModeRTE) => vec![&["SPV_KHR_float_controls"]],
+            Operand::Capability(spirv::Capability::RoundingModeRTZ) => vec![&["SPV_KHR_float_controls"]],
+            Operand::Capability(spirv::Capability::RayTracingKHR) => vec![&["SPV_KHR_ray_tracing"]],
+            Operand::Capability(spirv::Capability::ImageGatherBiasLodAMD) => vec![&["SPV_AMD_texture_gather_bias_lod"]],
+            Operand::Capability(spirv::Capability::FragmentMaskAMD) => vec![&["SPV_AMD_shader_fragment_mask"]],
+            Operand::Capability(spirv::Capability::StencilExportEXT) => vec![&["SPV_EXT_shader_stencil_export"]],
+            Operand::Capability(spirv::Capability::ImageReadWriteLodAMD) => vec![&["SPV_AMD_shader_image_load_store_lod"]],
+            Operand::Capability(spirv::Capability::SampleMaskOverrideCoverageNV) => vec![&["SPV_NV_sample_mask_override_coverage"]],
+            Operand::Capability(spirv::Capability::GeometryShaderPassthroughNV) => vec![&["SPV_NV_geometry_shader_passthrough"]],
+            Operand::Capability(spirv::Capability::ShaderViewportIndexLayerEXT) => vec![&["SPV_EXT_shader_viewport_index_layer"]],
+            Operand::Capability(spirv::Capability::ShaderViewportMaskNV) => vec![&["SPV_NV_viewport_array2"]],
+            Operand::Capability(spirv::Capability::ShaderStereoViewNV) => vec![&["SPV_NV_stereo_view_rendering"]],
+            Operand::Capability(spirv::Capability::PerViewAttributesNV) => vec![&["SPV_NVX_multiview_per_view_attributes"]],
+            Operand::Capability(spirv::Capability::MeshShadingEXT) => vec![&["SPV_EXT_mesh_shader"]],
+            Operand::Capability(spirv::Capability::ShaderNonUniform) => vec![&["SPV_EXT_descriptor_indexing"]],
+            Operand::Capability(spirv::Capability::RuntimeDescriptorArray) => vec![&["SPV_EXT_descriptor_indexing"]],
+            Operand::Capability(spirv::Capability::InputAttachmentArrayDynamicIndexing) => vec![&["SPV_EXT_descriptor_indexing"]],
+            Operand::Capability(spirv::Capability::UniformTexelBufferArrayDynamicIndexing) => vec![&["SPV_EXT_descriptor_indexing"]],
+            Operand::Capability(spirv::Capability::StorageTexelBufferArrayDynamicIndexing) => vec![&["SPV_EXT_descriptor_indexing"]],
+            Operand::Capability(spirv::Capability::UniformBufferArrayNonUniformIndexing) => vec![&["SPV_EXT_descriptor_indexing"]],
+            Operand::Capability(spirv::Capability::SampledImageArrayNonUniformIndexing) => vec![&["SPV_EXT_descriptor_indexing"]],
+            Operand::Capability(spirv::Capability::StorageBufferArrayNonUniformIndexing) => vec![&["SPV_EXT_descriptor_indexing"]],
+            Operand::Capability(spirv::Capability::StorageImageArrayNonUniformIndexing) => vec![&["SPV_EXT_descriptor_indexing"]],
+            Operand::Capability(spirv::Capability::InputAttachmentArrayNonUniformIndexing) => vec![&["SPV_EXT_descriptor_indexing"]],
+            Operand::Capability(spirv::Capability::UniformTexelBufferArrayNonUniformIndexing) => vec![&["SPV_EXT_descriptor_indexing"]],
+            Operand::Capability(spirv::Capability::StorageTexelBufferArrayNonUniformIndexing) => vec![&["SPV_EXT_descriptor_indexing"]],
+            Operand::Capability(spirv::Capability::VulkanMemoryModel) => vec![&["SPV_KHR_vulkan_memory_model"]],
+            Operand::Capability(spirv::Capability::VulkanMemoryModelDeviceScope) => vec![&["SPV_KHR_vulkan_memory_model"]],
+            Operand::Capability(spirv::Capability::PhysicalStorageBufferAddresses) => vec![&["SPV_EXT_physical_storage_buffer", "SPV_KHR_physical_storage_buffer"]],
+            Operand::Capability(spirv::Capability::DemoteToHelperInvocation) => vec![&["SPV_EXT_demote_to_helper_invocation"]],
+            Operand::Capability(spirv::Capability::CooperativeMatrixKHR) => vec![&["SPV_KHR_cooperative_matrix"]],
+            Operand::Capability(spirv::Capability::AtomicFloat32AddEXT) => vec![&["SPV_EXT_shader_atomic_float_add"]],
+            Operand::Capability(spirv::Capability::AtomicFloat64AddEXT) => vec![&["SPV_EXT_shader_atomic_float_add"]],
+            _ => vec![],
         }
     }
 }
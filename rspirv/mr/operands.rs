@@ -0,0 +1,392 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr::Operand;
+use prelude::*;
+
+use std::{array, fmt, iter, mem, ops, slice, vec};
+
+/// The number of operands stored without allocating.
+const INLINE: usize = 4;
+
+/// Fills the unused inline slots; it owns nothing to drop.
+const VACANT: Operand = Operand::LiteralInt32(0);
+
+/// The operands of an instruction.
+///
+/// Most instructions have few operands, so up to four are stored inline,
+/// and more on the heap. Operands dereference to a slice, and otherwise
+/// mostly behave like a `Vec<Operand>`, which they convert from and to.
+#[derive(Clone)]
+pub struct Operands(Storage);
+
+#[derive(Clone)]
+enum Storage {
+    Inline(usize, [Operand; INLINE]),
+    Heap(Vec<Operand>),
+}
+
+impl Operands {
+    /// Creates empty operands.
+    pub fn new() -> Operands {
+        Operands(Storage::Inline(0, [VACANT; INLINE]))
+    }
+
+    /// Creates empty operands with room for `capacity` operands.
+    pub fn with_capacity(capacity: usize) -> Operands {
+        if capacity <= INLINE {
+            Operands::new()
+        } else {
+            Operands(Storage::Heap(Vec::with_capacity(capacity)))
+        }
+    }
+
+    /// Returns true if the operands are stored inline.
+    pub fn is_inline(&self) -> bool {
+        matches!(self.0, Storage::Inline(..))
+    }
+
+    /// Moves the operands to the heap, and returns them there.
+    fn spill(&mut self) -> &mut Vec<Operand> {
+        if let Storage::Inline(len, ref mut inline) = self.0 {
+            let mut heap = Vec::with_capacity(INLINE * 2);
+            heap.extend(inline[..len].iter_mut().map(|o| mem::replace(o, VACANT)));
+            self.0 = Storage::Heap(heap);
+        }
+        match self.0 {
+            Storage::Heap(ref mut heap) => heap,
+            Storage::Inline(..) => unreachable!(),
+        }
+    }
+
+    /// Appends `operand`.
+    pub fn push(&mut self, operand: Operand) {
+        match self.0 {
+            Storage::Inline(ref mut len, ref mut inline) if *len < INLINE => {
+                inline[*len] = operand;
+                *len += 1;
+            }
+            _ => self.spill().push(operand),
+        }
+    }
+
+    /// Appends clones of `operands`.
+    pub fn extend_from_slice(&mut self, operands: &[Operand]) {
+        self.extend(operands.iter().cloned())
+    }
+
+    /// Removes the last operand and returns it.
+    pub fn pop(&mut self) -> Option<Operand> {
+        match self.0 {
+            Storage::Inline(0, _) => None,
+            Storage::Inline(ref mut len, ref mut inline) => {
+                *len -= 1;
+                Some(mem::replace(&mut inline[*len], VACANT))
+            }
+            Storage::Heap(ref mut heap) => heap.pop(),
+        }
+    }
+
+    /// Inserts `operand` at `index`, shifting the following ones.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn insert(&mut self, index: usize, operand: Operand) {
+        let len = self.len();
+        assert!(index <= len, "insertion index out of bounds");
+        self.push(operand);
+        self[index..].rotate_right(1);
+    }
+
+    /// Removes the operand at `index` and returns it, shifting the
+    /// following ones.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Operand {
+        assert!(index < self.len(), "removal index out of bounds");
+        self[index..].rotate_left(1);
+        self.pop().unwrap()
+    }
+
+    /// Keeps the first `len` operands only.
+    pub fn truncate(&mut self, len: usize) {
+        while self.len() > len {
+            self.pop();
+        }
+    }
+
+    /// Removes all operands.
+    pub fn clear(&mut self) {
+        self.truncate(0)
+    }
+
+    /// Keeps only the operands for which `keep` returns true.
+    pub fn retain<F: FnMut(&Operand) -> bool>(&mut self, mut keep: F) {
+        let mut kept = 0;
+        for index in 0..self.len() {
+            if keep(&self[index]) {
+                self.swap(kept, index);
+                kept += 1;
+            }
+        }
+        self.truncate(kept);
+    }
+
+    /// Splits the operands at `index`, keeping those before and returning
+    /// those after.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn split_off(&mut self, index: usize) -> Operands {
+        assert!(index <= self.len(), "split index out of bounds");
+        match self.0 {
+            Storage::Inline(ref mut len, ref mut inline) => {
+                let moved = inline[index..*len].iter_mut().map(|o| mem::replace(o, VACANT));
+                let rest = moved.collect();
+                *len = index;
+                rest
+            }
+            Storage::Heap(ref mut heap) => Operands::from(heap.split_off(index)),
+        }
+    }
+
+    /// Returns the operands as a vector.
+    pub fn into_vec(self) -> Vec<Operand> {
+        match self.0 {
+            Storage::Inline(len, inline) => IntoIterator::into_iter(inline).take(len).collect(),
+            Storage::Heap(heap) => heap,
+        }
+    }
+}
+
+impl Default for Operands {
+    fn default() -> Operands {
+        Operands::new()
+    }
+}
+
+impl ops::Deref for Operands {
+    type Target = [Operand];
+
+    fn deref(&self) -> &[Operand] {
+        match self.0 {
+            Storage::Inline(len, ref inline) => &inline[..len],
+            Storage::Heap(ref heap) => heap,
+        }
+    }
+}
+
+impl ops::DerefMut for Operands {
+    fn deref_mut(&mut self) -> &mut [Operand] {
+        match self.0 {
+            Storage::Inline(len, ref mut inline) => &mut inline[..len],
+            Storage::Heap(ref mut heap) => heap,
+        }
+    }
+}
+
+impl fmt::Debug for Operands {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl PartialEq for Operands {
+    fn eq(&self, other: &Operands) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl PartialEq<Vec<Operand>> for Operands {
+    fn eq(&self, other: &Vec<Operand>) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl PartialEq<Operands> for Vec<Operand> {
+    fn eq(&self, other: &Operands) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl From<Vec<Operand>> for Operands {
+    fn from(operands: Vec<Operand>) -> Operands {
+        if operands.len() <= INLINE {
+            operands.into_iter().collect()
+        } else {
+            Operands(Storage::Heap(operands))
+        }
+    }
+}
+
+impl<'a> From<&'a [Operand]> for Operands {
+    fn from(operands: &'a [Operand]) -> Operands {
+        operands.iter().cloned().collect()
+    }
+}
+
+impl From<Operands> for Vec<Operand> {
+    fn from(operands: Operands) -> Vec<Operand> {
+        operands.into_vec()
+    }
+}
+
+impl iter::FromIterator<Operand> for Operands {
+    fn from_iter<I: IntoIterator<Item = Operand>>(iter: I) -> Operands {
+        let mut operands = Operands::new();
+        operands.extend(iter);
+        operands
+    }
+}
+
+impl iter::Extend<Operand> for Operands {
+    fn extend<I: IntoIterator<Item = Operand>>(&mut self, iter: I) {
+        for operand in iter {
+            self.push(operand);
+        }
+    }
+}
+
+/// An iterator moving the operands out of [`Operands`](struct.Operands.html).
+pub struct OperandsIntoIter(IntoIterStorage);
+
+enum IntoIterStorage {
+    Inline(iter::Take<array::IntoIter<Operand, INLINE>>),
+    Heap(vec::IntoIter<Operand>),
+}
+
+impl Iterator for OperandsIntoIter {
+    type Item = Operand;
+
+    fn next(&mut self) -> Option<Operand> {
+        match self.0 {
+            IntoIterStorage::Inline(ref mut inline) => inline.next(),
+            IntoIterStorage::Heap(ref mut heap) => heap.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.0 {
+            IntoIterStorage::Inline(ref inline) => inline.size_hint(),
+            IntoIterStorage::Heap(ref heap) => heap.size_hint(),
+        }
+    }
+}
+
+impl DoubleEndedIterator for OperandsIntoIter {
+    fn next_back(&mut self) -> Option<Operand> {
+        match self.0 {
+            IntoIterStorage::Inline(ref mut inline) => inline.next_back(),
+            IntoIterStorage::Heap(ref mut heap) => heap.next_back(),
+        }
+    }
+}
+
+impl ExactSizeIterator for OperandsIntoIter {}
+
+impl IntoIterator for Operands {
+    type Item = Operand;
+    type IntoIter = OperandsIntoIter;
+
+    fn into_iter(self) -> OperandsIntoIter {
+        OperandsIntoIter(match self.0 {
+            Storage::Inline(len, inline) => {
+                IntoIterStorage::Inline(IntoIterator::into_iter(inline).take(len))
+            }
+            Storage::Heap(heap) => IntoIterStorage::Heap(heap.into_iter()),
+        })
+    }
+}
+
+impl<'a> IntoIterator for &'a Operands {
+    type Item = &'a Operand;
+    type IntoIter = slice::Iter<'a, Operand>;
+
+    fn into_iter(self) -> slice::Iter<'a, Operand> {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Operands {
+    type Item = &'a mut Operand;
+    type IntoIter = slice::IterMut<'a, Operand>;
+
+    fn into_iter(self) -> slice::IterMut<'a, Operand> {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr::Operand;
+
+    use super::Operands;
+
+    #[test]
+    fn test_operands() {
+        let mut operands: Operands = (1..4).map(Operand::IdRef).collect();
+        assert!(operands.is_inline());
+        operands.insert(0, Operand::from("a"));
+        assert!(operands.is_inline());
+        operands.push(Operand::IdRef(4));
+        assert!(!operands.is_inline());
+        assert_eq!(operands,
+                   vec![Operand::from("a"),
+                        Operand::IdRef(1),
+                        Operand::IdRef(2),
+                        Operand::IdRef(3),
+                        Operand::IdRef(4)]);
+
+        assert_eq!(operands.remove(0), Operand::from("a"));
+        operands.retain(|o| *o != Operand::IdRef(2));
+        let rest = operands.split_off(1);
+        assert_eq!(operands, vec![Operand::IdRef(1)]);
+        assert_eq!(rest, vec![Operand::IdRef(3), Operand::IdRef(4)]);
+        assert!(rest.is_inline());
+        assert_eq!(Operands::from(rest.into_vec()).pop(), Some(Operand::IdRef(4)));
+    }
+
+    #[test]
+    fn test_split_off() {
+        let mut operands: Operands = (0..8).map(Operand::IdRef).collect();
+        let mut rest = operands.split_off(2);
+        assert_eq!(operands, (0..2).map(Operand::IdRef).collect::<Vec<_>>());
+        assert_eq!(rest, (2..8).map(Operand::IdRef).collect::<Vec<_>>());
+        assert!(!rest.is_inline());
+
+        let tail = rest.split_off(6);
+        assert!(tail.is_empty());
+        let mut operands = rest.split_off(3);
+        assert_eq!(operands.len(), 3);
+        let tail = operands.split_off(1);
+        assert!(operands.is_inline() && tail.is_inline());
+        assert_eq!(operands, vec![Operand::IdRef(5)]);
+        assert_eq!(tail, vec![Operand::IdRef(6), Operand::IdRef(7)]);
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let inline: Operands = (0..3).map(Operand::IdRef).collect();
+        let mut iter = inline.into_iter();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next_back(), Some(Operand::IdRef(2)));
+        assert_eq!(iter.collect::<Vec<_>>(), vec![Operand::IdRef(0), Operand::IdRef(1)]);
+
+        let heap: Operands = (0..6).map(Operand::IdRef).collect();
+        assert!(!heap.is_inline());
+        let mut iter = heap.into_iter();
+        assert_eq!(iter.next(), Some(Operand::IdRef(0)));
+        assert_eq!(iter.len(), 5);
+        assert_eq!(iter.rev().collect::<Vec<_>>(),
+                   (1..6).rev().map(Operand::IdRef).collect::<Vec<_>>());
+    }
+}
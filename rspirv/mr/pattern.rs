@@ -0,0 +1,338 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Combinators matching instructions by opcode and operand shape.
+//!
+//! A pattern over an instruction is its opcodes and a tuple of patterns
+//! over its operands, which capture parts of the operands as they match:
+//!
+//! ```
+//! extern crate rspirv;
+//! extern crate spirv_headers as spirv;
+//!
+//! use rspirv::mr;
+//! use rspirv::mr::pattern::{def, definitions, id, literal, match_inst};
+//!
+//! fn main() {
+//!     let mut b = mr::Builder::new();
+//!     let uint = b.type_int(32, 0);
+//!     let zero = b.constant_u32(uint, 0);
+//!     let void = b.type_void();
+//!     let fty = b.type_function(void, vec![uint]);
+//!     b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+//!     let x = b.function_parameter(uint).unwrap();
+//!     b.begin_basic_block(None).unwrap();
+//!     b.iadd(uint, None, x, zero).unwrap();
+//!     b.ret().unwrap();
+//!     b.end_function().unwrap();
+//!     let module = b.module();
+//!
+//!     // `x + 0`, capturing `x` and the literal.
+//!     let defs = definitions(&module);
+//!     let inst = &module.functions[0].basic_blocks[0].instructions[0];
+//!     let pattern = (id(), def(&defs, spirv::Op::Constant, (literal(),)));
+//!     assert_eq!(match_inst(inst, spirv::Op::IAdd, pattern), Some((x, (0,))));
+//! }
+//! ```
+//!
+//! Any `Fn(&Operand) -> Option<T>` is a pattern too, capturing a `T`.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::BTreeMap;
+
+/// A pattern over one operand.
+pub trait Pattern {
+    /// What matching captures.
+    type Output;
+
+    /// Returns the captures if `operand` matches.
+    fn match_operand(&self, operand: &mr::Operand) -> Option<Self::Output>;
+}
+
+impl<F, T> Pattern for F
+    where F: Fn(&mr::Operand) -> Option<T>
+{
+    type Output = T;
+
+    fn match_operand(&self, operand: &mr::Operand) -> Option<T> {
+        self(operand)
+    }
+}
+
+/// A pattern over the operands of an instruction; see
+/// [`match_inst`](fn.match_inst.html).
+pub trait Operands {
+    /// What matching captures.
+    type Output;
+
+    /// Returns the captures if `operands` match.
+    fn match_operands(&self, operands: &[mr::Operand]) -> Option<Self::Output>;
+}
+
+macro_rules! tuple_operands {
+    ($count:expr; $( $name:ident $index:tt ),*) => {
+        impl<$( $name: Pattern ),*> Operands for ($( $name, )*) {
+            type Output = ($( $name::Output, )*);
+
+            #[allow(unused_variables)]
+            fn match_operands(&self, operands: &[mr::Operand]) -> Option<Self::Output> {
+                if operands.len() != $count {
+                    return None;
+                }
+                Some(($( self.$index.match_operand(&operands[$index])?, )*))
+            }
+        }
+    }
+}
+
+tuple_operands!(0;);
+tuple_operands!(1; A 0);
+tuple_operands!(2; A 0, B 1);
+tuple_operands!(3; A 0, B 1, C 2);
+tuple_operands!(4; A 0, B 1, C 2, D 3);
+tuple_operands!(5; A 0, B 1, C 2, D 3, E 4);
+tuple_operands!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+
+/// Matches the operands matched by the given patterns, followed by any
+/// number of other operands; see [`prefix`](fn.prefix.html).
+#[derive(Clone, Copy, Debug)]
+pub struct Prefix<P>(P);
+
+macro_rules! prefix_operands {
+    ($count:expr; $( $name:ident $index:tt ),*) => {
+        impl<$( $name: Pattern ),*> Operands for Prefix<($( $name, )*)> {
+            type Output = ($( $name::Output, )*);
+
+            fn match_operands(&self, operands: &[mr::Operand]) -> Option<Self::Output> {
+                if operands.len() < $count {
+                    return None;
+                }
+                Some(($( (self.0).$index.match_operand(&operands[$index])?, )*))
+            }
+        }
+    }
+}
+
+prefix_operands!(1; A 0);
+prefix_operands!(2; A 0, B 1);
+prefix_operands!(3; A 0, B 1, C 2);
+prefix_operands!(4; A 0, B 1, C 2, D 3);
+
+/// The opcodes an instruction pattern accepts: one opcode, or a slice of
+/// alternatives.
+pub trait Opcodes {
+    fn accepts(&self, opcode: spirv::Op) -> bool;
+}
+
+impl Opcodes for spirv::Op {
+    fn accepts(&self, opcode: spirv::Op) -> bool {
+        *self == opcode
+    }
+}
+
+impl Opcodes for &[spirv::Op] {
+    fn accepts(&self, opcode: spirv::Op) -> bool {
+        self.contains(&opcode)
+    }
+}
+
+/// Returns the captures of the operand patterns `operands`, a tuple, if
+/// `inst` has one of the given `opcodes` and exactly the operands
+/// matched, not counting its result type and id.
+pub fn match_inst<O: Opcodes, P: Operands>(inst: &mr::Instruction,
+                                           opcodes: O,
+                                           operands: P)
+                                           -> Option<P::Output> {
+    if opcodes.accepts(inst.class.opcode) {
+        operands.match_operands(&inst.operands)
+    } else {
+        None
+    }
+}
+
+/// Like [`match_inst`](fn.match_inst.html) for binary instructions whose
+/// operands commute, e.g., `OpIAdd`: the operands are also tried swapped.
+pub fn match_commutative<O, A, B>(inst: &mr::Instruction,
+                                  opcodes: O,
+                                  a: A,
+                                  b: B)
+                                  -> Option<(A::Output, B::Output)>
+    where O: Opcodes,
+          A: Pattern,
+          B: Pattern
+{
+    if !opcodes.accepts(inst.class.opcode) || inst.operands.len() != 2 {
+        return None;
+    }
+    let (x, y) = (&inst.operands[0], &inst.operands[1]);
+    a.match_operand(x)
+        .and_then(|a| b.match_operand(y).map(|b| (a, b)))
+        .or_else(|| a.match_operand(y).and_then(|a| b.match_operand(x).map(|b| (a, b))))
+}
+
+/// Matches the operands matched by `operands`, a tuple of up to four
+/// patterns, followed by any number of other operands.
+pub fn prefix<P>(operands: P) -> Prefix<P> {
+    Prefix(operands)
+}
+
+/// Matches any operand, capturing nothing.
+pub fn any() -> impl Pattern<Output = ()> {
+    |_: &mr::Operand| Some(())
+}
+
+/// Matches an id operand, capturing the id.
+pub fn id() -> impl Pattern<Output = Word> {
+    |operand: &mr::Operand| operand.id()
+}
+
+/// Matches the id operand `id`.
+pub fn is(id: Word) -> impl Pattern<Output = ()> {
+    move |operand: &mr::Operand| operand.id().filter(|&v| v == id).map(|_| ())
+}
+
+/// Matches a 32- or 64-bit integer literal, capturing its value.
+pub fn literal() -> impl Pattern<Output = u64> {
+    |operand: &mr::Operand| match *operand {
+        mr::Operand::LiteralInt32(v) => Some(u64::from(v)),
+        mr::Operand::LiteralInt64(v) => Some(v),
+        _ => None,
+    }
+}
+
+/// Matches a 32- or 64-bit floating-point literal, capturing its value.
+pub fn float() -> impl Pattern<Output = f64> {
+    |operand: &mr::Operand| match *operand {
+        mr::Operand::LiteralFloat32(v) => Some(f64::from(v)),
+        mr::Operand::LiteralFloat64(v) => Some(v),
+        _ => None,
+    }
+}
+
+/// Matches operands equal to `expected`.
+pub fn eq(expected: mr::Operand) -> impl Pattern<Output = ()> {
+    move |operand: &mr::Operand| if *operand == expected { Some(()) } else { None }
+}
+
+/// Where [`def`](fn.def.html) looks up the instructions defining ids.
+pub trait Definitions {
+    /// Returns the instruction defining `id`, if known.
+    fn definition_of(&self, id: Word) -> Option<&mr::Instruction>;
+}
+
+impl Definitions for BTreeMap<Word, &mr::Instruction> {
+    fn definition_of(&self, id: Word) -> Option<&mr::Instruction> {
+        self.get(&id).cloned()
+    }
+}
+
+impl Definitions for BTreeMap<Word, mr::Instruction> {
+    fn definition_of(&self, id: Word) -> Option<&mr::Instruction> {
+        self.get(&id)
+    }
+}
+
+/// Matches an id defined by an instruction matching `opcodes` and
+/// `operands`, capturing what `operands` captures.
+///
+/// Definitions are looked up in `defs`, e.g., as collected by
+/// [`definitions`](fn.definitions.html).
+pub fn def<'a, D, O, P>(defs: &'a D,
+                        opcodes: O,
+                        operands: P)
+                        -> impl Pattern<Output = P::Output> + 'a
+    where D: Definitions + ?Sized,
+          O: Opcodes + 'a,
+          P: Operands + 'a
+{
+    move |operand: &mr::Operand| {
+        let inst = defs.definition_of(operand.id()?)?;
+        match_inst(inst, &opcodes, &operands)
+    }
+}
+
+impl<O: Opcodes> Opcodes for &O {
+    fn accepts(&self, opcode: spirv::Op) -> bool {
+        (**self).accepts(opcode)
+    }
+}
+
+impl<P: Operands> Operands for &P {
+    type Output = P::Output;
+
+    fn match_operands(&self, operands: &[mr::Operand]) -> Option<P::Output> {
+        (**self).match_operands(operands)
+    }
+}
+
+/// Returns the instructions of `module` by their result ids.
+pub fn definitions(module: &mr::Module) -> BTreeMap<Word, &mr::Instruction> {
+    let functions = module.functions.iter().flat_map(|f| {
+        f.def
+            .iter()
+            .chain(&f.parameters)
+            .chain(f.basic_blocks.iter().flat_map(|b| b.label.iter().chain(&b.instructions)))
+    });
+    module.global_inst_iter()
+          .chain(functions)
+          .filter_map(|inst| inst.result_id.map(|id| (id, inst)))
+          .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{any, def, definitions, float, id, is, match_commutative, match_inst, prefix};
+
+    #[test]
+    fn test_match_inst() {
+        let mut b = mr::Builder::new();
+        let float_ty = b.type_float(32);
+        let two = b.constant_f32(float_ty, 2.0);
+        let void = b.type_void();
+        let fty = b.type_function(void, vec![float_ty]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let x = b.function_parameter(float_ty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let product = b.fmul(float_ty, None, two, x).unwrap();
+        let sum = b.fadd(float_ty, None, x, product).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let m = b.module();
+        let defs = definitions(&m);
+        let insts = &m.functions[0].basic_blocks[0].instructions;
+
+        // x * c, in either order.
+        let scaled = (id(), def(&defs, spirv::Op::Constant, (float(),)));
+        assert_eq!(match_inst(&insts[0], spirv::Op::FMul, scaled), None);
+        let m0 = match_commutative(&insts[0],
+                                   spirv::Op::FMul,
+                                   id(),
+                                   def(&defs, spirv::Op::Constant, (float(),)));
+        assert_eq!(m0, Some((x, (2.0,))));
+
+        // a + b * c with b * c a product.
+        let ops: &[spirv::Op] = &[spirv::Op::FAdd, spirv::Op::IAdd];
+        let fma = (is(x), def(&defs, spirv::Op::FMul, (any(), id())));
+        assert_eq!(match_inst(&insts[1], ops, fma), Some(((), ((), x))));
+        assert_eq!(match_inst(&insts[1], spirv::Op::FAdd, prefix((id(),))), Some((x,)));
+        assert_eq!(match_inst(&insts[1], spirv::Op::FAdd, (id(),)), None);
+        assert!(defs.contains_key(&sum) && defs.contains_key(&two));
+    }
+}
@@ -0,0 +1,343 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use prelude::*;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// What differs in a clone made by
+/// [`Module::clone_entry_point_with`](struct.Module.html#method.clone_entry_point_with).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EntryPointOverrides {
+    /// The name the clone is registered under.
+    pub name: String,
+    /// The values of specialization constants by `SpecId`: the bits of
+    /// 32-bit scalars, or nonzero for true booleans.
+    pub spec_constants: BTreeMap<u32, u32>,
+    /// The execution modes set for the clone, with their literal
+    /// operands, replacing the modes they conflict with.
+    pub execution_modes: Vec<(spirv::ExecutionMode, Vec<u32>)>,
+}
+
+impl mr::Module {
+    /// Clones the entry point of the given `name` together with the
+    /// functions it calls, specializes the clone by `overrides`, and
+    /// returns the result id of its function, or `None` if there is no such
+    /// entry point.
+    ///
+    /// The clone keeps the interface and execution modes of the entry
+    /// point, and the names and decorations of its functions and their
+    /// instructions. In it, the overridden specialization constants are
+    /// replaced with constants of their values, and the `OpSpecConstantOp`s
+    /// and `OpSpecConstantComposite`s depending on them with copies; those
+    /// of other than 32-bit or boolean types are not overridden. Other
+    /// module-scope declarations are shared with the original. New ids
+    /// start at the bound of the module, which is raised past them.
+    pub fn clone_entry_point_with(&mut self,
+                                  name: &str,
+                                  overrides: &EntryPointOverrides)
+                                  -> Option<Word> {
+        let entry_point = self.entry_points.iter().find(|inst| {
+            matches!(inst.operands.get(2), Some(mr::Operand::LiteralString(n)) if n == name)
+        })?;
+        let entry = entry_point.operands.get(1).and_then(mr::Operand::id)?;
+        let copy = |inst: &mr::Instruction| {
+            mr::Instruction::new(inst.class.opcode,
+                                 inst.result_type,
+                                 inst.result_id,
+                                 inst.operands.clone())
+        };
+        let mut new_entry_point = copy(entry_point);
+
+        let functions: BTreeMap<Word, &mr::Function> = self.functions
+            .iter()
+            .filter_map(|f| f.def.as_ref()?.result_id.map(|id| (id, f)))
+            .collect();
+        let mut tree = BTreeSet::new();
+        let mut stack = vec![entry];
+        while let Some(next) = stack.pop() {
+            let f = match functions.get(&next) {
+                Some(f) if tree.insert(next) => f,
+                _ => continue,
+            };
+            stack.extend(f.basic_blocks
+                .iter()
+                .flat_map(|b| &b.instructions)
+                .filter(|inst| inst.class.opcode == spirv::Op::FunctionCall)
+                .filter_map(|inst| inst.operands.first().and_then(mr::Operand::id)));
+        }
+
+        let mut next_id = self.header.as_ref().map_or_else(|| {
+            self.global_inst_iter()
+                .chain(self.functions.iter().flat_map(|f| {
+                    f.def
+                        .iter()
+                        .chain(&f.parameters)
+                        .chain(f.basic_blocks.iter().flat_map(|b| {
+                            b.label.iter().chain(&b.instructions)
+                        }))
+                }))
+                .filter_map(|inst| inst.result_id)
+                .max()
+                .map_or(1, |id| id + 1)
+        }, |header| header.bound);
+        let mut fresh = || {
+            next_id += 1;
+            next_id - 1
+        };
+
+        // Ids of the original instructions, mapped to those of the clones.
+        let mut ids = BTreeMap::new();
+        let mut clones = vec![];
+        for f in self.functions.iter().filter(|f| {
+            f.def.as_ref().and_then(|def| def.result_id).is_some_and(|id| tree.contains(&id))
+        }) {
+            let insts = f.def
+                .iter()
+                .chain(&f.parameters)
+                .chain(f.basic_blocks.iter().flat_map(|b| b.label.iter().chain(&b.instructions)));
+            for inst in insts {
+                if let Some(id) = inst.result_id {
+                    ids.insert(id, fresh());
+                }
+            }
+            clones.push(mr::Function {
+                def: f.def.as_ref().map(copy),
+                end: f.end.as_ref().map(copy),
+                parameters: f.parameters.iter().map(copy).collect(),
+                basic_blocks: f.basic_blocks
+                    .iter()
+                    .map(|b| {
+                        mr::BasicBlock {
+                            label: b.label.as_ref().map(copy),
+                            instructions: b.instructions.iter().map(copy).collect(),
+                        }
+                    })
+                    .collect(),
+            });
+        }
+
+        let spec_ids: BTreeMap<Word, u32> = self.annotations
+            .iter()
+            .filter_map(|inst| match inst.operands[..] {
+                [mr::Operand::IdRef(id),
+                 mr::Operand::Decoration(spirv::Decoration::SpecId),
+                 mr::Operand::LiteralInt32(spec_id)] => Some((id, spec_id)),
+                _ => None,
+            })
+            .collect();
+        let mut constants = BTreeMap::new();
+        let mut specialized = vec![];
+        for inst in &self.types_global_values {
+            let id = match inst.result_id {
+                Some(id) => id,
+                None => continue,
+            };
+            let value = spec_ids.get(&id).and_then(|s| overrides.spec_constants.get(s));
+            let constant = match (inst.class.opcode, value, inst.operands.first()) {
+                (spirv::Op::SpecConstantTrue, Some(&v), _) |
+                (spirv::Op::SpecConstantFalse, Some(&v), _) => {
+                    let opcode = if v != 0 {
+                        spirv::Op::ConstantTrue
+                    } else {
+                        spirv::Op::ConstantFalse
+                    };
+                    Some((opcode, vec![]))
+                }
+                (spirv::Op::SpecConstant, Some(&v), Some(&mr::Operand::LiteralInt32(_))) => {
+                    Some((spirv::Op::Constant, vec![mr::Operand::LiteralInt32(v)]))
+                }
+                (spirv::Op::SpecConstant, Some(&v), Some(&mr::Operand::LiteralFloat32(_))) => {
+                    let operands = vec![mr::Operand::LiteralFloat32(f32::from_bits(v))];
+                    Some((spirv::Op::Constant, operands))
+                }
+                (spirv::Op::SpecConstantOp, ..) |
+                (spirv::Op::SpecConstantComposite, ..) => {
+                    let depends = inst.operands
+                        .iter()
+                        .filter_map(mr::Operand::id)
+                        .any(|id| constants.contains_key(&id));
+                    if depends {
+                        Some((inst.class.opcode, inst.operands.to_vec()))
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            };
+            if let Some((opcode, operands)) = constant {
+                let clone = fresh();
+                constants.insert(id, clone);
+                let ty = inst.result_type;
+                specialized.push(mr::Instruction::new(opcode, ty, Some(clone), operands));
+            }
+        }
+
+        let remap = |inst: &mut mr::Instruction| {
+            if let Some(id) = inst.result_id.as_mut() {
+                *id = ids.get(id).cloned().unwrap_or(*id);
+            }
+            for id in inst.operands.iter_mut().filter_map(mr::Operand::id_mut) {
+                *id = ids.get(id).or_else(|| constants.get(id)).cloned().unwrap_or(*id);
+            }
+        };
+        for inst in &mut specialized {
+            remap(inst);
+        }
+        for f in &mut clones {
+            let insts = f.def
+                .iter_mut()
+                .chain(&mut f.parameters)
+                .chain(f.basic_blocks.iter_mut().flat_map(|b| {
+                    b.label.iter_mut().chain(&mut b.instructions)
+                }))
+                .chain(&mut f.end);
+            for inst in insts {
+                remap(inst);
+            }
+        }
+        remap(&mut new_entry_point);
+        new_entry_point.operands[2] = mr::Operand::LiteralString(overrides.name.clone());
+        let clone = ids[&entry];
+
+        let mut modes: Vec<mr::Instruction> = self.execution_modes
+            .iter()
+            .filter(|inst| inst.operands.first() == Some(&mr::Operand::IdRef(entry)))
+            .map(copy)
+            .collect();
+        let targets_clone = |inst: &mr::Instruction| match inst.class.opcode {
+            spirv::Op::Name |
+            spirv::Op::Decorate |
+            spirv::Op::DecorateId |
+            spirv::Op::DecorateString |
+            spirv::Op::MemberDecorate |
+            spirv::Op::MemberDecorateString => {
+                inst.operands.first().and_then(mr::Operand::id).is_some_and(|id| {
+                    ids.contains_key(&id)
+                })
+            }
+            _ => false,
+        };
+        let mut debugs: Vec<mr::Instruction> =
+            self.debugs.iter().filter(|inst| targets_clone(inst)).map(copy).collect();
+        let mut annotations: Vec<mr::Instruction> =
+            self.annotations.iter().filter(|inst| targets_clone(inst)).map(copy).collect();
+        for inst in modes.iter_mut().chain(&mut debugs).chain(&mut annotations) {
+            remap(inst);
+        }
+
+        let touched = [(mr::Section::TypesGlobalValues, !specialized.is_empty()),
+                       (mr::Section::ExecutionModes, !modes.is_empty()),
+                       (mr::Section::Debugs, !debugs.is_empty()),
+                       (mr::Section::Annotations, !annotations.is_empty()),
+                       (mr::Section::EntryPoints, true),
+                       (mr::Section::Header, self.header.is_some())];
+        for (section, changed) in touched {
+            if changed {
+                self.touch(section);
+            }
+        }
+        for f in &clones {
+            if let Some(id) = f.def.as_ref().and_then(|def| def.result_id) {
+                self.touch(mr::Section::Function(id));
+            }
+        }
+        self.types_global_values.extend(specialized);
+        self.functions.extend(clones);
+        self.entry_points.push(new_entry_point);
+        self.execution_modes.extend(modes);
+        self.debugs.extend(debugs);
+        self.annotations.extend(annotations);
+        for &(mode, ref params) in &overrides.execution_modes {
+            self.set_execution_mode(clone, mode, params);
+        }
+        if let Some(ref mut header) = self.header {
+            header.bound = next_id;
+        }
+        Some(clone)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::EntryPointOverrides;
+
+    #[test]
+    fn test_clone_entry_point_with() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let count = b.spec_constant_u32_with_id(uint, 8, 3).unwrap();
+        let double = b.spec_constant_op(uint, spirv::Op::IAdd);
+        let ptr = b.type_pointer(None, spirv::StorageClass::Private, uint);
+        let var = b.variable(ptr, None, spirv::StorageClass::Private, None);
+        let fty = b.type_function(void, vec![]);
+
+        let store = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.store(var, double, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.function_call(void, None, store, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::GLCompute, main, "main", vec![]);
+        b.execution_mode(main, spirv::ExecutionMode::LocalSize, vec![8, 1, 1]);
+        b.name(store, "store");
+        let mut m = b.module();
+        let sum = m.types_global_values.iter_mut().find(|inst| inst.result_id == Some(double));
+        sum.unwrap().operands.extend(vec![mr::Operand::IdRef(count), mr::Operand::IdRef(count)]);
+
+        let overrides = EntryPointOverrides {
+            name: "main_16".to_owned(),
+            spec_constants: vec![(3, 16)].into_iter().collect(),
+            execution_modes: vec![(spirv::ExecutionMode::LocalSize, vec![16, 1, 1])],
+        };
+        let bound = m.header.as_ref().unwrap().bound;
+        let clone = m.clone_entry_point_with("main", &overrides).unwrap();
+        assert!(m.clone_entry_point_with("missing", &overrides).is_none());
+        assert_eq!(m.functions.len(), 4);
+        assert_eq!(m.entry_points.len(), 2);
+        assert_eq!(m.local_size(main), Some([8, 1, 1]));
+        assert_eq!(m.local_size(clone), Some([16, 1, 1]));
+        assert!(m.header.as_ref().unwrap().bound > bound);
+
+        let specialized: Vec<_> = m.types_global_values
+            .iter()
+            .filter(|inst| inst.result_id.is_some_and(|id| id >= bound))
+            .collect();
+        assert_eq!(specialized[0].class.opcode, spirv::Op::Constant);
+        assert_eq!(specialized[0].operands, vec![mr::Operand::LiteralInt32(16)]);
+        let frozen = specialized[0].result_id.unwrap();
+        assert_eq!(specialized[1].class.opcode, spirv::Op::SpecConstantOp);
+        assert_eq!(&specialized[1].operands[1..],
+                   &[mr::Operand::IdRef(frozen), mr::Operand::IdRef(frozen)]);
+
+        let sum = specialized[1].result_id.unwrap();
+        let stored = &m.functions[2].basic_blocks[0].instructions[0];
+        assert_eq!(stored.operands, vec![mr::Operand::IdRef(var), mr::Operand::IdRef(sum)]);
+        let store_clone = m.functions[2].def.as_ref().unwrap().result_id.unwrap();
+        let call = &m.functions[3].basic_blocks[0].instructions[0];
+        assert_eq!(call.operands, vec![mr::Operand::IdRef(store_clone)]);
+        assert_eq!(m.debugs.len(), 2);
+    }
+}
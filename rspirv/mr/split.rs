@@ -0,0 +1,285 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use prelude::*;
+use spirv;
+
+use spirv::Word;
+
+impl mr::Function {
+    /// Splits the block at index `block` before the instruction at
+    /// `inst_index`, and returns the label of the new block holding the
+    /// rest of the instructions.
+    ///
+    /// The new block is labeled with `*next_id`, which is then incremented,
+    /// and placed right after the split block, which now branches to it.
+    /// `OpPhi`s of successors that named the split block as a parent name
+    /// the new block instead.
+    ///
+    /// The split point is moved past the leading `OpPhi`s of the block, and
+    /// before its terminator; an `OpSelectionMerge` moves along with the
+    /// terminator. An `OpLoopMerge` stays in the split block, so that it
+    /// remains the loop header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block` is out of range.
+    pub fn split_block_at(&mut self, block: usize, inst_index: usize, next_id: &mut Word) -> Word {
+        let label = *next_id;
+        *next_id += 1;
+
+        let old = &mut self.basic_blocks[block];
+        let old_label = old.label.as_ref().and_then(|l| l.result_id);
+        let phis = old.instructions
+            .iter()
+            .take_while(|inst| inst.class.opcode == spirv::Op::Phi)
+            .count();
+        let len = old.instructions.len();
+        let mut index = inst_index.max(phis).min(len.saturating_sub(1));
+        if let Some(i) = len.checked_sub(2) {
+            if index > i && old.instructions[i].class.opcode == spirv::Op::SelectionMerge {
+                index = i;
+            }
+        }
+        let mut rest = old.instructions.split_off(index);
+        // Keep the loop merge in the header; it still declares the loop
+        // after the split.
+        let loop_merge = rest.iter().position(|inst| inst.class.opcode == spirv::Op::LoopMerge);
+        if let Some(i) = loop_merge {
+            old.instructions.push(rest.remove(i));
+        }
+        old.instructions.push(mr::Instruction::new(spirv::Op::Branch,
+                                                   None,
+                                                   None,
+                                                   vec![mr::Operand::IdRef(label)]));
+
+        let mut new = mr::BasicBlock::new();
+        new.label = Some(mr::Instruction::new(spirv::Op::Label, None, Some(label), vec![]));
+        new.instructions.append(&mut rest);
+        let successors = new.successors();
+        self.basic_blocks.insert(block + 1, new);
+
+        if let Some(old_label) = old_label {
+            for b in &mut self.basic_blocks {
+                let id = b.label.as_ref().and_then(|l| l.result_id);
+                if id.is_some_and(|id| successors.contains(&id)) {
+                    replace_phi_parent(b, old_label, label);
+                }
+            }
+        }
+        label
+    }
+
+    /// Splits all critical edges of this function, and returns the labels
+    /// of the new blocks.
+    ///
+    /// An edge is critical if its source has several successors and its
+    /// target several predecessors. Each is split by a new block labeled
+    /// from `*next_id`, which is incremented for each, placed right after
+    /// the source and branching to the target. Only the terminator of the
+    /// source is redirected; merge instructions keep naming their targets.
+    /// `OpPhi`s of the target name the new block as the parent instead of
+    /// the source.
+    pub fn split_critical_edges(&mut self, next_id: &mut Word) -> Vec<Word> {
+        let labels: Vec<Option<Word>> = self.basic_blocks
+            .iter()
+            .map(|b| b.label.as_ref().and_then(|l| l.result_id))
+            .collect();
+        let successors: Vec<Vec<Word>> = self.basic_blocks
+            .iter()
+            .map(mr::BasicBlock::successors)
+            .collect();
+        let predecessors = |target: Word| successors.iter().filter(|s| s.contains(&target)).count();
+
+        // (source block index, target label)
+        let mut critical = vec![];
+        for (index, targets) in successors.iter().enumerate() {
+            if targets.len() < 2 || labels[index].is_none() {
+                continue;
+            }
+            for &target in targets {
+                if predecessors(target) > 1 {
+                    critical.push((index, target));
+                }
+            }
+        }
+
+        let mut added = vec![];
+        // Insert from the back so that earlier indices stay valid.
+        for &(index, target) in critical.iter().rev() {
+            let label = *next_id;
+            *next_id += 1;
+            let source = labels[index].unwrap();
+
+            if let Some(inst) = self.basic_blocks[index].instructions.last_mut() {
+                for id in inst.operands.iter_mut().filter_map(mr::Operand::id_mut) {
+                    if *id == target {
+                        *id = label;
+                    }
+                }
+            }
+            for b in &mut self.basic_blocks {
+                if b.label.as_ref().and_then(|l| l.result_id) == Some(target) {
+                    replace_phi_parent(b, source, label);
+                }
+            }
+            let mut edge = mr::BasicBlock::new();
+            edge.label = Some(mr::Instruction::new(spirv::Op::Label, None, Some(label), vec![]));
+            edge.instructions.push(mr::Instruction::new(spirv::Op::Branch,
+                                                        None,
+                                                        None,
+                                                        vec![mr::Operand::IdRef(target)]));
+            self.basic_blocks.insert(index + 1, edge);
+            added.push(label);
+        }
+        added.reverse();
+        added
+    }
+}
+
+/// Renames the parent `old` to `new` in the `OpPhi`s of `block`.
+fn replace_phi_parent(block: &mut mr::BasicBlock, old: Word, new: Word) {
+    for inst in &mut block.instructions {
+        if inst.class.opcode != spirv::Op::Phi {
+            break;
+        }
+        for parent in inst.operands.iter_mut().skip(1).step_by(2) {
+            if *parent == mr::Operand::IdRef(old) {
+                *parent = mr::Operand::IdRef(new);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use utils::test::opcodes;
+
+    fn label(block: &mr::BasicBlock) -> spirv::Word {
+        block.label.as_ref().unwrap().result_id.unwrap()
+    }
+
+    #[test]
+    fn test_split_block_at() {
+        let mut b = mr::Builder::new();
+        let uint = b.type_int(32, 0);
+        let one = b.constant_u32(uint, 1);
+        let fty = b.type_function(uint, vec![]);
+        b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let entry = b.begin_basic_block(None).unwrap();
+        let exit = b.id();
+        let sum = b.iadd(uint, None, one, one).unwrap();
+        b.iadd(uint, None, sum, one).unwrap();
+        b.selection_merge(exit, spirv::SelectionControl::NONE).unwrap();
+        b.branch(exit).unwrap();
+        b.begin_basic_block(Some(exit)).unwrap();
+        let phi = b.phi(uint, None, vec![(sum, entry)]).unwrap();
+        b.ret_value(phi).unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+        let mut next_id = m.header.as_ref().unwrap().bound;
+
+        let f = &mut m.functions[0];
+        let new = f.split_block_at(0, 1, &mut next_id);
+        assert_eq!(next_id, new + 1);
+        assert_eq!(opcodes(&f.basic_blocks[0]), vec![spirv::Op::IAdd, spirv::Op::Branch]);
+        assert_eq!(f.basic_blocks[0].instructions[1].operands, vec![mr::Operand::IdRef(new)]);
+        assert_eq!(label(&f.basic_blocks[1]), new);
+        assert_eq!(opcodes(&f.basic_blocks[1]),
+                   vec![spirv::Op::IAdd, spirv::Op::SelectionMerge, spirv::Op::Branch]);
+        assert_eq!(f.basic_blocks[2].instructions[0].operands,
+                   vec![mr::Operand::IdRef(sum), mr::Operand::IdRef(new)]);
+
+        // The selection merge moves along with the terminator.
+        let last = f.split_block_at(1, 2, &mut next_id);
+        assert_eq!(opcodes(&f.basic_blocks[1]), vec![spirv::Op::IAdd, spirv::Op::Branch]);
+        assert_eq!(label(&f.basic_blocks[2]), last);
+        assert_eq!(opcodes(&f.basic_blocks[2]),
+                   vec![spirv::Op::SelectionMerge, spirv::Op::Branch]);
+    }
+
+    #[test]
+    fn test_split_loop_header() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let voidfvoid = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        let header = b.begin_basic_block(None).unwrap();
+        let merge = b.id();
+        b.nop().unwrap();
+        b.loop_merge(merge, header, spirv::LoopControl::NONE, vec![]).unwrap();
+        b.branch(header).unwrap();
+        b.begin_basic_block(Some(merge)).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+        let mut next_id = m.header.as_ref().unwrap().bound;
+
+        let f = &mut m.functions[0];
+        f.split_block_at(0, 1, &mut next_id);
+        assert_eq!(opcodes(&f.basic_blocks[0]),
+                   vec![spirv::Op::Nop, spirv::Op::LoopMerge, spirv::Op::Branch]);
+        assert_eq!(opcodes(&f.basic_blocks[1]), vec![spirv::Op::Branch]);
+        assert_eq!(f.basic_blocks[1].instructions[0].operands,
+                   vec![mr::Operand::IdRef(header)]);
+    }
+
+    #[test]
+    fn test_split_critical_edges() {
+        let mut b = mr::Builder::new();
+        let uint = b.type_int(32, 0);
+        let bool_type = b.type_bool();
+        let cond = b.constant_true(bool_type);
+        let one = b.constant_u32(uint, 1);
+        let two = b.constant_u32(uint, 2);
+        let fty = b.type_function(uint, vec![]);
+        b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let entry = b.begin_basic_block(None).unwrap();
+        let then = b.id();
+        let merge = b.id();
+        b.selection_merge(merge, spirv::SelectionControl::NONE).unwrap();
+        b.branch_conditional(cond, then, merge, vec![]).unwrap();
+        b.begin_basic_block(Some(then)).unwrap();
+        b.branch(merge).unwrap();
+        b.begin_basic_block(Some(merge)).unwrap();
+        let phi = b.phi(uint, None, vec![(one, entry), (two, then)]).unwrap();
+        b.ret_value(phi).unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+        let mut next_id = m.header.as_ref().unwrap().bound;
+
+        let f = &mut m.functions[0];
+        let added = f.split_critical_edges(&mut next_id);
+        assert_eq!(added.len(), 1);
+        let edge = added[0];
+        assert_eq!(f.basic_blocks.len(), 4);
+        assert_eq!(label(&f.basic_blocks[1]), edge);
+        assert_eq!(f.basic_blocks[1].instructions[0].operands,
+                   vec![mr::Operand::IdRef(merge)]);
+        // The merge instruction still names the merge block.
+        assert_eq!(f.basic_blocks[0].instructions[0].operands[0], mr::Operand::IdRef(merge));
+        assert_eq!(f.basic_blocks[0].instructions[1].operands,
+                   vec![mr::Operand::IdRef(cond), mr::Operand::IdRef(then), mr::Operand::IdRef(edge)]);
+        assert_eq!(f.basic_blocks[3].instructions[0].operands,
+                   vec![mr::Operand::IdRef(one),
+                        mr::Operand::IdRef(edge),
+                        mr::Operand::IdRef(two),
+                        mr::Operand::IdRef(then)]);
+        assert!(f.split_critical_edges(&mut next_id).is_empty());
+    }
+}
@@ -0,0 +1,402 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ids typed by the kind of what they identify, and a builder taking and
+//! returning them.
+//!
+//! [`Builder::typed`](../struct.Builder.html#method.typed) opts into
+//! building with [`TypeId`](type.TypeId.html)s, [`ValueId`](type.ValueId.html)s,
+//! and [`ConstantId`](type.ConstantId.html)s instead of plain words, so that
+//! passing a value where a type is expected fails to compile:
+//!
+//! ```compile_fail
+//! extern crate rspirv;
+//!
+//! fn main() {
+//!     let mut b = rspirv::mr::Builder::new();
+//!     let mut t = b.typed();
+//!     let uint = t.type_int(32, 0);
+//!     let one = t.constant_u32(uint, 1);
+//!     t.type_vector(one, 4);
+//! }
+//! ```
+//!
+//! Constants convert into values, and any id into a word, e.g., to call
+//! the methods of the underlying [`builder`](struct.Typed.html#method.builder).
+
+use mr;
+use prelude::*;
+use spirv;
+
+use spirv::Word;
+use std::marker::PhantomData;
+use std::result;
+
+type BuildResult<T> = result::Result<T, mr::Error>;
+
+/// The kind of type ids.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Type {}
+
+/// The kind of value ids, e.g., of the results of instructions in
+/// functions, variables, and functions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Value {}
+
+/// The kind of constant ids.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Constant {}
+
+/// An id of what the kind `K` identifies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Id<K> {
+    word: Word,
+    kind: PhantomData<K>,
+}
+
+pub type TypeId = Id<Type>;
+pub type ValueId = Id<Value>;
+pub type ConstantId = Id<Constant>;
+
+impl<K> Id<K> {
+    /// Wraps the id `word`, trusting it to identify something of kind `K`.
+    pub fn new(word: Word) -> Id<K> {
+        Id {
+            word,
+            kind: PhantomData,
+        }
+    }
+
+    /// Returns the id as a word.
+    pub fn word(self) -> Word {
+        self.word
+    }
+}
+
+impl<K> From<Id<K>> for Word {
+    fn from(id: Id<K>) -> Word {
+        id.word
+    }
+}
+
+impl From<ConstantId> for ValueId {
+    fn from(id: ConstantId) -> ValueId {
+        Id::new(id.word)
+    }
+}
+
+fn words<K>(ids: &[Id<K>]) -> Vec<Word> {
+    ids.iter().map(|id| id.word).collect()
+}
+
+/// A [`Builder`](../struct.Builder.html) building with typed ids; see the
+/// [module documentation](index.html).
+///
+/// It covers types, scalar and composite constants, functions, variables,
+/// memory accesses, arithmetic, and cooperative matrices; the underlying
+/// builder builds the rest.
+pub struct Typed<'b> {
+    builder: &'b mut mr::Builder,
+}
+
+impl mr::Builder {
+    /// Returns a view of this builder taking and returning typed ids.
+    pub fn typed(&mut self) -> Typed<'_> {
+        Typed { builder: self }
+    }
+}
+
+impl<'b> Typed<'b> {
+    /// Returns the underlying builder.
+    pub fn builder(&mut self) -> &mut mr::Builder {
+        self.builder
+    }
+
+    pub fn type_void(&mut self) -> TypeId {
+        Id::new(self.builder.type_void())
+    }
+
+    pub fn type_bool(&mut self) -> TypeId {
+        Id::new(self.builder.type_bool())
+    }
+
+    pub fn type_int(&mut self, width: u32, signedness: u32) -> TypeId {
+        Id::new(self.builder.type_int(width, signedness))
+    }
+
+    pub fn type_float(&mut self, width: u32) -> TypeId {
+        Id::new(self.builder.type_float(width))
+    }
+
+    pub fn type_vector(&mut self, component_type: TypeId, component_count: u32) -> TypeId {
+        Id::new(self.builder.type_vector(component_type.word, component_count))
+    }
+
+    pub fn type_array(&mut self, element_type: TypeId, length: ConstantId) -> TypeId {
+        Id::new(self.builder.type_array(element_type.word, length.word))
+    }
+
+    pub fn type_struct(&mut self, field_types: &[TypeId]) -> TypeId {
+        Id::new(self.builder.type_struct(words(field_types)))
+    }
+
+    pub fn type_pointer(&mut self, storage_class: spirv::StorageClass, pointee: TypeId) -> TypeId {
+        Id::new(self.builder.type_pointer(None, storage_class, pointee.word))
+    }
+
+    pub fn type_function(&mut self, return_type: TypeId, parameter_types: &[TypeId]) -> TypeId {
+        Id::new(self.builder.type_function(return_type.word, words(parameter_types)))
+    }
+
+    /// Returns a cooperative matrix type of `rows` by `columns` components
+    /// shared by the invocations of `scope`, for the given `usage`, a
+    /// `spirv::CooperativeMatrixUse`.
+    pub fn type_cooperative_matrix(&mut self,
+                                   component_type: TypeId,
+                                   scope: ConstantId,
+                                   rows: ConstantId,
+                                   columns: ConstantId,
+                                   usage: ConstantId)
+                                   -> TypeId {
+        Id::new(self.builder.type_cooperative_matrix_khr(component_type.word,
+                                                         scope.word,
+                                                         rows.word,
+                                                         columns.word,
+                                                         usage.word))
+    }
+
+    pub fn constant_true(&mut self, result_type: TypeId) -> ConstantId {
+        Id::new(self.builder.constant_true(result_type.word))
+    }
+
+    pub fn constant_false(&mut self, result_type: TypeId) -> ConstantId {
+        Id::new(self.builder.constant_false(result_type.word))
+    }
+
+    pub fn constant_u32(&mut self, result_type: TypeId, value: u32) -> ConstantId {
+        Id::new(self.builder.constant_u32(result_type.word, value))
+    }
+
+    pub fn constant_f32(&mut self, result_type: TypeId, value: f32) -> ConstantId {
+        Id::new(self.builder.constant_f32(result_type.word, value))
+    }
+
+    pub fn constant_composite(&mut self,
+                              result_type: TypeId,
+                              constituents: &[ConstantId])
+                              -> ConstantId {
+        Id::new(self.builder.constant_composite(result_type.word, words(constituents)))
+    }
+
+    pub fn constant_null(&mut self, result_type: TypeId) -> ConstantId {
+        Id::new(self.builder.constant_null(result_type.word))
+    }
+
+    /// Begins a function of the function type `function_type` returning
+    /// `return_type`, and returns the function.
+    pub fn begin_function(&mut self,
+                          return_type: TypeId,
+                          control: spirv::FunctionControl,
+                          function_type: TypeId)
+                          -> BuildResult<ValueId> {
+        self.builder
+            .begin_function(return_type.word, None, control, function_type.word)
+            .map(Id::new)
+    }
+
+    pub fn function_parameter(&mut self, result_type: TypeId) -> BuildResult<ValueId> {
+        self.builder.function_parameter(result_type.word).map(Id::new)
+    }
+
+    pub fn variable(&mut self,
+                    result_type: TypeId,
+                    storage_class: spirv::StorageClass,
+                    initializer: Option<ValueId>)
+                    -> ValueId {
+        let initializer = initializer.map(Id::word);
+        Id::new(self.builder.variable(result_type.word, None, storage_class, initializer))
+    }
+
+    pub fn load(&mut self, result_type: TypeId, pointer: ValueId) -> BuildResult<ValueId> {
+        self.builder.load(result_type.word, None, pointer.word, None, vec![]).map(Id::new)
+    }
+
+    pub fn store<V: Into<ValueId>>(&mut self, pointer: ValueId, object: V) -> BuildResult<()> {
+        self.builder.store(pointer.word, object.into().word, None, vec![])
+    }
+
+    /// Loads a cooperative matrix through `pointer`, laid out as `layout`,
+    /// a `spirv::CooperativeMatrixLayout`, says, with rows or columns
+    /// `stride` elements apart.
+    pub fn cooperative_matrix_load(&mut self,
+                                   result_type: TypeId,
+                                   pointer: ValueId,
+                                   layout: ConstantId,
+                                   stride: Option<ValueId>)
+                                   -> BuildResult<ValueId> {
+        self.builder
+            .cooperative_matrix_load_khr(result_type.word,
+                                         None,
+                                         pointer.word,
+                                         layout.word,
+                                         stride.map(Id::word),
+                                         None,
+                                         vec![])
+            .map(Id::new)
+    }
+
+    /// Stores the cooperative matrix `object` through `pointer`, laid out
+    /// as for [`cooperative_matrix_load`](#method.cooperative_matrix_load).
+    pub fn cooperative_matrix_store(&mut self,
+                                    pointer: ValueId,
+                                    object: ValueId,
+                                    layout: ConstantId,
+                                    stride: Option<ValueId>)
+                                    -> BuildResult<()> {
+        self.builder.cooperative_matrix_store_khr(pointer.word,
+                                                  object.word,
+                                                  layout.word,
+                                                  stride.map(Id::word),
+                                                  None,
+                                                  vec![])
+    }
+
+    /// Computes `a * b + c` on cooperative matrices.
+    pub fn cooperative_matrix_mul_add(&mut self,
+                                      result_type: TypeId,
+                                      a: ValueId,
+                                      b: ValueId,
+                                      c: ValueId,
+                                      operands: spirv::CooperativeMatrixOperands)
+                                      -> BuildResult<ValueId> {
+        let operands = if operands.is_empty() { None } else { Some(operands) };
+        self.builder
+            .cooperative_matrix_mul_add_khr(result_type.word, None, a.word, b.word, c.word, operands)
+            .map(Id::new)
+    }
+
+    /// Returns the number of components of the cooperative matrix type
+    /// `matrix_type` each invocation holds.
+    pub fn cooperative_matrix_length(&mut self,
+                                     result_type: TypeId,
+                                     matrix_type: TypeId)
+                                     -> BuildResult<ValueId> {
+        self.builder
+            .cooperative_matrix_length_khr(result_type.word, None, matrix_type.word)
+            .map(Id::new)
+    }
+
+    pub fn access_chain(&mut self,
+                        result_type: TypeId,
+                        base: ValueId,
+                        indexes: &[ValueId])
+                        -> BuildResult<ValueId> {
+        self.builder.access_chain(result_type.word, None, base.word, words(indexes)).map(Id::new)
+    }
+
+    pub fn composite_construct(&mut self,
+                               result_type: TypeId,
+                               constituents: &[ValueId])
+                               -> BuildResult<ValueId> {
+        self.builder
+            .composite_construct(result_type.word, None, words(constituents))
+            .map(Id::new)
+    }
+
+    pub fn function_call(&mut self,
+                         result_type: TypeId,
+                         function: ValueId,
+                         arguments: &[ValueId])
+                         -> BuildResult<ValueId> {
+        self.builder
+            .function_call(result_type.word, None, function.word, words(arguments))
+            .map(Id::new)
+    }
+
+    pub fn iadd<A, B>(&mut self, result_type: TypeId, a: A, b: B) -> BuildResult<ValueId>
+        where A: Into<ValueId>,
+              B: Into<ValueId>
+    {
+        self.builder.iadd(result_type.word, None, a.into().word, b.into().word).map(Id::new)
+    }
+
+    pub fn isub<A, B>(&mut self, result_type: TypeId, a: A, b: B) -> BuildResult<ValueId>
+        where A: Into<ValueId>,
+              B: Into<ValueId>
+    {
+        self.builder.isub(result_type.word, None, a.into().word, b.into().word).map(Id::new)
+    }
+
+    pub fn imul<A, B>(&mut self, result_type: TypeId, a: A, b: B) -> BuildResult<ValueId>
+        where A: Into<ValueId>,
+              B: Into<ValueId>
+    {
+        self.builder.imul(result_type.word, None, a.into().word, b.into().word).map(Id::new)
+    }
+
+    pub fn fadd<A, B>(&mut self, result_type: TypeId, a: A, b: B) -> BuildResult<ValueId>
+        where A: Into<ValueId>,
+              B: Into<ValueId>
+    {
+        self.builder.fadd(result_type.word, None, a.into().word, b.into().word).map(Id::new)
+    }
+
+    pub fn fsub<A, B>(&mut self, result_type: TypeId, a: A, b: B) -> BuildResult<ValueId>
+        where A: Into<ValueId>,
+              B: Into<ValueId>
+    {
+        self.builder.fsub(result_type.word, None, a.into().word, b.into().word).map(Id::new)
+    }
+
+    pub fn fmul<A, B>(&mut self, result_type: TypeId, a: A, b: B) -> BuildResult<ValueId>
+        where A: Into<ValueId>,
+              B: Into<ValueId>
+    {
+        self.builder.fmul(result_type.word, None, a.into().word, b.into().word).map(Id::new)
+    }
+
+    pub fn ret_value<V: Into<ValueId>>(&mut self, value: V) -> BuildResult<()> {
+        self.builder.ret_value(value.into().word)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use spirv::Word;
+
+    #[test]
+    fn test_typed() {
+        let mut b = mr::Builder::new();
+        let (f, sum) = {
+            let mut t = b.typed();
+            let uint = t.type_int(32, 0);
+            let one = t.constant_u32(uint, 1);
+            let fty = t.type_function(uint, &[uint]);
+            let f = t.begin_function(uint, spirv::FunctionControl::NONE, fty).unwrap();
+            let x = t.function_parameter(uint).unwrap();
+            t.builder().begin_basic_block(None).unwrap();
+            let sum = t.iadd(uint, x, one).unwrap();
+            t.ret_value(sum).unwrap();
+            t.builder().end_function().unwrap();
+            (Word::from(f), sum.word())
+        };
+        let m = b.module();
+        assert_eq!(m.functions[0].def.as_ref().unwrap().result_id, Some(f));
+        let inst = &m.functions[0].basic_blocks[0].instructions[0];
+        assert_eq!((inst.class.opcode, inst.result_id), (spirv::Op::IAdd, Some(sum)));
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The global `OpUndef`s of a module, one per type.
+///
+/// Undefined values are added to the module as needed, and reused for
+/// all values of the same type.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Undefs {
+    by_type: BTreeMap<Word, Word>,
+}
+
+impl Undefs {
+    /// Collects the global `OpUndef`s of `module`, keeping the first one of
+    /// each type.
+    pub fn of(module: &mr::Module) -> Undefs {
+        let mut by_type = BTreeMap::new();
+        for inst in &module.types_global_values {
+            if inst.class.opcode != spirv::Op::Undef {
+                continue;
+            }
+            if let (Some(ty), Some(id)) = (inst.result_type, inst.result_id) {
+                by_type.entry(ty).or_insert(id);
+            }
+        }
+        Undefs { by_type }
+    }
+
+    /// Returns the `OpUndef` of type `ty`, if there is one.
+    pub fn get(&self, ty: Word) -> Option<Word> {
+        self.by_type.get(&ty).cloned()
+    }
+
+    /// Returns the `OpUndef` of type `ty`, adding it to `module` with an id
+    /// from `ids` if there is none.
+    pub fn undef(&mut self,
+                 module: &mut mr::Module,
+                 ids: &mut mr::IdAllocator,
+                 ty: Word)
+                 -> Word {
+        if let Some(id) = self.get(ty) {
+            return id;
+        }
+        let id = ids.id();
+        module.types_global_values
+            .push(mr::Instruction::new(spirv::Op::Undef, Some(ty), Some(id), vec![]));
+        module.touch(mr::Section::TypesGlobalValues);
+        self.by_type.insert(ty, id);
+        id
+    }
+}
+
+impl mr::Module {
+    /// Replaces the remaining uses of the results of `removed`, which were
+    /// deleted from the module, with `OpUndef`s of their types, and
+    /// returns the number of operands replaced.
+    ///
+    /// Names and decorations of the removed results are removed, as are
+    /// the removed variables from entry point interfaces. Results without
+    /// a type, e.g., labels, have no undefined value; their uses are left
+    /// for the caller, and reported by
+    /// [`TargetEnv::validate`](../env/enum.TargetEnv.html#method.validate).
+    pub fn replace_removed_uses<'r, I>(&mut self, ids: &mut mr::IdAllocator, removed: I) -> usize
+        where I: IntoIterator<Item = &'r mr::Instruction>
+    {
+        let removed: BTreeMap<Word, Option<Word>> = removed.into_iter()
+            .filter_map(|inst| inst.result_id.map(|id| (id, inst.result_type)))
+            .collect();
+        if removed.is_empty() {
+            return 0;
+        }
+        let targets_removed = |inst: &mr::Instruction| match inst.operands.first() {
+            Some(&mr::Operand::IdRef(id)) => removed.contains_key(&id),
+            _ => false,
+        };
+        let (debugs, annotations) = (self.debugs.len(), self.annotations.len());
+        self.debugs.retain(|inst| !targets_removed(inst));
+        self.annotations.retain(|inst| !targets_removed(inst));
+        if self.debugs.len() != debugs {
+            self.touch(mr::Section::Debugs);
+        }
+        if self.annotations.len() != annotations {
+            self.touch(mr::Section::Annotations);
+        }
+        let mut interfaces_changed = false;
+        for inst in &mut self.entry_points {
+            if inst.operands.len() > 3 {
+                let interface = inst.operands.split_off(3);
+                let len = interface.len();
+                inst.operands.extend(interface.into_iter()
+                    .filter(|operand| !operand.id().is_some_and(|id| removed.contains_key(&id))));
+                interfaces_changed |= inst.operands.len() != 3 + len;
+            }
+        }
+        if interfaces_changed {
+            self.touch(mr::Section::EntryPoints);
+        }
+
+        let functions = self.functions.iter().flat_map(|f| {
+            let blocks = f.basic_blocks.iter().flat_map(|b| b.label.iter().chain(&b.instructions));
+            f.def.iter().chain(&f.parameters).chain(blocks).chain(&f.end)
+        });
+        let used: BTreeSet<Word> = self.global_inst_iter()
+            .chain(functions)
+            .flat_map(|inst| {
+                inst.result_type.into_iter().chain(inst.operands.iter().filter_map(mr::Operand::id))
+            })
+            .filter(|id| removed.contains_key(id))
+            .collect();
+        let mut undefs = Undefs::of(self);
+        let mut count = 0;
+        for id in used {
+            if let Some(ty) = removed[&id] {
+                let undef = undefs.undef(self, ids, ty);
+                count += self.replace_all_uses(id, undef);
+            }
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::Undefs;
+
+    #[test]
+    fn test_replace_removed_uses() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let one = b.constant_u32(uint, 1);
+        let voidf = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let x = b.iadd(uint, None, one, one).unwrap();
+        let y = b.imul(uint, None, x, x).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.name(x, "x");
+        let mut m = b.module();
+        let mut ids = mr::IdAllocator::for_module(&m);
+
+        let mut undefs = Undefs::of(&m);
+        assert_eq!(undefs.get(uint), None);
+        let undef = undefs.undef(&mut m, &mut ids, uint);
+        assert_eq!(undefs.undef(&mut m, &mut ids, uint), undef);
+        assert_eq!(m.types_global_values.last().unwrap().class.opcode, spirv::Op::Undef);
+        let globals = m.types_global_values.len();
+
+        let removed = m.functions[0].basic_blocks[0].instructions.remove(0);
+        assert_eq!(m.replace_removed_uses(&mut ids, Some(&removed)), 2);
+        assert_eq!(m.types_global_values.len(), globals);
+        let mul = &m.functions[0].basic_blocks[0].instructions[0];
+        assert_eq!(mul.result_id, Some(y));
+        assert_eq!(mul.operands, vec![mr::Operand::IdRef(undef), mr::Operand::IdRef(undef)]);
+        assert!(m.debugs.is_empty());
+    }
+}
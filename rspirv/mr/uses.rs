@@ -0,0 +1,249 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use prelude::*;
+use spirv;
+
+use mr::Section;
+use spirv::Word;
+
+impl mr::Module {
+    /// Replaces all uses of the id `old` with `new`, and returns the number
+    /// of operands replaced.
+    ///
+    /// Every id operand and result type referring to `old` is replaced in
+    /// place, so the value and parent block operands of `OpPhi` stay
+    /// paired; replacing a label updates both branch targets and phi
+    /// parents. The instruction defining `old` is left alone.
+    ///
+    /// Names and decorations of `old` move to `new`, except for those
+    /// `new` already has: a name of `new` is kept over the one of `old`,
+    /// and identical decorations are kept once. Entry point interfaces
+    /// list `new` at most once. Instructions kept as
+    /// [raw words](struct.Instruction.html#structfield.raw_words) are not
+    /// rewritten.
+    pub fn replace_all_uses(&mut self, old: Word, new: Word) -> usize {
+        if old == new {
+            return 0;
+        }
+        let mut count = 0;
+        // The sections changed, marked once all are rewritten.
+        let mut touched = vec![];
+        let globals = [(Section::Capabilities, &mut self.capabilities),
+                       (Section::Extensions, &mut self.extensions),
+                       (Section::ExtInstImports, &mut self.ext_inst_imports),
+                       (Section::ExecutionModes, &mut self.execution_modes),
+                       (Section::TypesGlobalValues, &mut self.types_global_values)];
+        for (section, insts) in globals {
+            let n: usize = insts.iter_mut().map(|inst| replace_in(inst, old, new)).sum();
+            touched.push((section, n > 0));
+            count += n;
+        }
+        let n: usize = self.memory_model.iter_mut().map(|inst| replace_in(inst, old, new)).sum();
+        touched.push((Section::MemoryModel, n > 0));
+        count += n;
+        for f in &mut self.functions {
+            let mut n = 0;
+            for inst in f.def.iter_mut().chain(&mut f.parameters).chain(&mut f.end) {
+                n += replace_in(inst, old, new);
+            }
+            for block in &mut f.basic_blocks {
+                for inst in block.label.iter_mut().chain(&mut block.instructions) {
+                    n += replace_in(inst, old, new);
+                }
+            }
+            if let Some(id) = f.def.as_ref().and_then(|def| def.result_id) {
+                touched.push((Section::Function(id), n > 0));
+            }
+            count += n;
+        }
+
+        let mut changed = false;
+        for inst in &mut self.entry_points {
+            let n = replace_in(inst, old, new);
+            // Operands after the execution model, function, and name are
+            // the interface.
+            if inst.operands.len() > 3 {
+                let mut seen = false;
+                let interface = inst.operands.split_off(3);
+                let len = interface.len();
+                inst.operands.extend(interface.into_iter().filter(|operand| {
+                    let duplicate = seen && operand.id() == Some(new);
+                    seen |= operand.id() == Some(new);
+                    !duplicate
+                }));
+                changed |= inst.operands.len() != 3 + len;
+            }
+            changed |= n > 0;
+            count += n;
+        }
+        touched.push((Section::EntryPoints, changed));
+
+        let n = retarget(&mut self.debugs, old, new, |a, b| {
+            a.class.opcode == b.class.opcode &&
+            match a.class.opcode {
+                spirv::Op::Name => a.operands.first() == b.operands.first(),
+                spirv::Op::MemberName => a.operands.get(..2) == b.operands.get(..2),
+                _ => same(a, b),
+            }
+        });
+        touched.push((Section::Debugs, n > 0));
+        count += n;
+        let n = retarget(&mut self.annotations, old, new, same);
+        touched.push((Section::Annotations, n > 0));
+        count += n;
+
+        for (section, changed) in touched {
+            if changed {
+                self.touch(section);
+            }
+        }
+        count
+    }
+}
+
+/// Replaces `old` with `new` in the result type and id operands of `inst`,
+/// and returns the number of replacements.
+fn replace_in(inst: &mut mr::Instruction, old: Word, new: Word) -> usize {
+    let mut count = 0;
+    let ids = inst.result_type
+        .iter_mut()
+        .chain(inst.operands.iter_mut().filter_map(mr::Operand::id_mut));
+    for id in ids {
+        if *id == old {
+            *id = new;
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Returns true if `a` and `b` are the same instruction.
+fn same(a: &mr::Instruction, b: &mr::Instruction) -> bool {
+    a.class.opcode == b.class.opcode && a.result_type == b.result_type &&
+    a.result_id == b.result_id && a.operands == b.operands
+}
+
+/// Replaces `old` with `new` in `insts`, dropping the rewritten
+/// instructions that `conflicts` with another instruction already
+/// referring to `new`, or with an earlier rewritten one.
+fn retarget<F>(insts: &mut Vec<mr::Instruction>, old: Word, new: Word, conflicts: F) -> usize
+    where F: Fn(&mr::Instruction, &mr::Instruction) -> bool
+{
+    let mut count = 0;
+    let rewritten: Vec<bool> = insts.iter_mut()
+        .map(|inst| {
+            let n = replace_in(inst, old, new);
+            count += n;
+            n > 0
+        })
+        .collect();
+    let dropped: Vec<bool> = (0..insts.len())
+        .map(|i| {
+            rewritten[i] &&
+            (0..insts.len()).any(|j| {
+                j != i && (!rewritten[j] || j < i) && conflicts(&insts[i], &insts[j])
+            })
+        })
+        .collect();
+    let mut index = 0;
+    insts.retain(|_| {
+        index += 1;
+        !dropped[index - 1]
+    });
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    #[test]
+    fn test_replace_all_uses() {
+        let mut b = mr::Builder::new();
+        let uint = b.type_int(32, 0);
+        let old = b.undef(uint, None);
+        let new = b.undef(uint, None);
+        let ptr = b.type_pointer(None, spirv::StorageClass::Input, uint);
+        let input = b.variable(ptr, None, spirv::StorageClass::Input, None);
+        b.name(old, "old");
+        b.name(new, "new");
+        b.decorate(old, spirv::Decoration::RelaxedPrecision, vec![]);
+        b.decorate(new, spirv::Decoration::RelaxedPrecision, vec![]);
+        b.decorate(old, spirv::Decoration::NoContraction, vec![]);
+        let fty = b.type_function(uint, vec![]);
+        let f = b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.entry_point(spirv::ExecutionModel::GLCompute, f, "main", vec![input, old, new]);
+        let entry = b.begin_basic_block(None).unwrap();
+        let next = b.id();
+        b.branch(next).unwrap();
+        b.begin_basic_block(Some(next)).unwrap();
+        let phi = b.phi(uint, None, vec![(old, entry)]).unwrap();
+        let sum = b.iadd(uint, None, old, phi).unwrap();
+        b.ret_value(sum).unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        assert_eq!(m.replace_all_uses(old, new), 6);
+
+        // The definition of old stays.
+        assert_eq!(m.types_global_values[1].result_id, Some(old));
+        let blocks = &m.functions[0].basic_blocks;
+        assert_eq!(blocks[1].instructions[0].operands,
+                   vec![mr::Operand::IdRef(new), mr::Operand::IdRef(entry)]);
+        assert_eq!(blocks[1].instructions[1].operands,
+                   vec![mr::Operand::IdRef(new), mr::Operand::IdRef(phi)]);
+        assert_eq!(m.entry_points[0].operands[3..].to_vec(),
+                   vec![mr::Operand::IdRef(input), mr::Operand::IdRef(new)]);
+
+        // new keeps its own name, and RelaxedPrecision once.
+        assert_eq!(m.debugs.len(), 1);
+        assert_eq!(m.debugs[0].operands[1], mr::Operand::LiteralString("new".to_string()));
+        let decorations: Vec<&mr::Operand> = m.annotations
+            .iter()
+            .inspect(|inst| assert_eq!(inst.operands[0], mr::Operand::IdRef(new)))
+            .map(|inst| &inst.operands[1])
+            .collect();
+        assert_eq!(decorations,
+                   vec![&mr::Operand::Decoration(spirv::Decoration::RelaxedPrecision),
+                        &mr::Operand::Decoration(spirv::Decoration::NoContraction)]);
+    }
+
+    #[test]
+    fn test_replace_label() {
+        let mut b = mr::Builder::new();
+        let uint = b.type_int(32, 0);
+        let one = b.constant_u32(uint, 1);
+        let fty = b.type_function(uint, vec![]);
+        b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let entry = b.begin_basic_block(None).unwrap();
+        let old = b.id();
+        let new = b.id();
+        b.branch(old).unwrap();
+        b.begin_basic_block(Some(old)).unwrap();
+        let phi = b.phi(uint, None, vec![(one, entry)]).unwrap();
+        b.ret_value(phi).unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        assert_eq!(m.replace_all_uses(entry, new), 1);
+        assert_eq!(m.replace_all_uses(old, new), 1);
+        let blocks = &m.functions[0].basic_blocks;
+        assert_eq!(blocks[0].instructions[0].operands, vec![mr::Operand::IdRef(new)]);
+        assert_eq!(blocks[1].instructions[0].operands,
+                   vec![mr::Operand::IdRef(one), mr::Operand::IdRef(new)]);
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use analysis::Dominators;
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::BTreeMap;
+use std::ops::Deref;
+
+/// An immutable view of a module, with indexes computed once.
+///
+/// The view borrows the module, which stays frozen as long as the view
+/// lives. It is `Sync`, so analyses running in parallel, e.g., on rayon's
+/// threads, can share one view instead of each indexing or cloning the
+/// module. It dereferences to the module.
+#[derive(Debug)]
+pub struct ModuleRef<'m> {
+    module: &'m mr::Module,
+    /// Instructions by their result id.
+    defs: BTreeMap<Word, &'m mr::Instruction>,
+    /// Decorations and member decorations by their target.
+    decorations: BTreeMap<Word, Vec<&'m mr::Instruction>>,
+    /// Functions by their id, with their control flow.
+    functions: BTreeMap<Word, (&'m mr::Function, Dominators)>,
+    /// Basic blocks by their label.
+    blocks: BTreeMap<Word, &'m mr::BasicBlock>,
+}
+
+impl<'m> ModuleRef<'m> {
+    /// Indexes `module`.
+    pub fn new(module: &'m mr::Module) -> ModuleRef<'m> {
+        let mut view = ModuleRef {
+            module,
+            defs: BTreeMap::new(),
+            decorations: BTreeMap::new(),
+            functions: BTreeMap::new(),
+            blocks: BTreeMap::new(),
+        };
+        for inst in module.global_inst_iter() {
+            if let Some(id) = inst.result_id {
+                view.defs.insert(id, inst);
+            }
+        }
+        for inst in &module.annotations {
+            let target = match inst.class.opcode {
+                spirv::Op::Decorate |
+                spirv::Op::DecorateId |
+                spirv::Op::DecorateString |
+                spirv::Op::MemberDecorate |
+                spirv::Op::MemberDecorateString => inst.operands.first(),
+                _ => None,
+            };
+            if let Some(&mr::Operand::IdRef(target)) = target {
+                view.decorations.entry(target).or_default().push(inst);
+            }
+        }
+        for function in &module.functions {
+            let blocks = function.basic_blocks
+                .iter()
+                .flat_map(|b| b.label.iter().chain(&b.instructions));
+            let insts = function.def.iter().chain(&function.parameters).chain(blocks);
+            for inst in insts {
+                if let Some(id) = inst.result_id {
+                    view.defs.insert(id, inst);
+                }
+            }
+            for block in &function.basic_blocks {
+                if let Some(label) = block.label.as_ref().and_then(|l| l.result_id) {
+                    view.blocks.insert(label, block);
+                }
+            }
+            if let Some(id) = function.def.as_ref().and_then(|d| d.result_id) {
+                view.functions.insert(id, (function, Dominators::new(function)));
+            }
+        }
+        view
+    }
+
+    /// Returns the viewed module.
+    pub fn module(&self) -> &'m mr::Module {
+        self.module
+    }
+
+    /// Returns the instruction defining `id`.
+    pub fn def(&self, id: Word) -> Option<&'m mr::Instruction> {
+        self.defs.get(&id).cloned()
+    }
+
+    /// Returns the decorations and member decorations of `target`, in
+    /// module order.
+    pub fn decorations(&self, target: Word) -> &[&'m mr::Instruction] {
+        self.decorations.get(&target).map_or(&[], |d| d.as_slice())
+    }
+
+    /// Returns true if `target` itself, not one of its members, has the
+    /// given decoration.
+    pub fn has_decoration(&self, target: Word, decoration: spirv::Decoration) -> bool {
+        self.decorations(target).iter().any(|inst| {
+            inst.class.opcode != spirv::Op::MemberDecorate &&
+            inst.class.opcode != spirv::Op::MemberDecorateString &&
+            inst.operands.get(1) == Some(&mr::Operand::Decoration(decoration))
+        })
+    }
+
+    /// Returns the function of the given `id`.
+    pub fn function(&self, id: Word) -> Option<&'m mr::Function> {
+        self.functions.get(&id).map(|&(function, _)| function)
+    }
+
+    /// Returns the control flow of the function of the given `id`.
+    pub fn cfg(&self, id: Word) -> Option<&Dominators> {
+        self.functions.get(&id).map(|(_, cfg)| cfg)
+    }
+
+    /// Returns the basic block of the given `label`.
+    pub fn block(&self, label: Word) -> Option<&'m mr::BasicBlock> {
+        self.blocks.get(&label).cloned()
+    }
+}
+
+impl<'m> Deref for ModuleRef<'m> {
+    type Target = mr::Module;
+
+    fn deref(&self) -> &mr::Module {
+        self.module
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use std::thread;
+
+    use super::ModuleRef;
+
+    #[test]
+    fn test_module_ref() {
+        let mut b = mr::Builder::new();
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let boolean = b.type_bool();
+        let cond = b.constant_true(boolean);
+        let voidf = b.type_function(void, vec![]);
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.decorate(cond, spirv::Decoration::RelaxedPrecision, vec![]);
+        let entry = b.begin_basic_block(None).unwrap();
+        let (then, merge) = (b.id(), b.id());
+        b.selection_merge(merge, spirv::SelectionControl::NONE).unwrap();
+        b.branch_conditional(cond, then, merge, vec![]).unwrap();
+        b.begin_basic_block(Some(then)).unwrap();
+        let x = b.logical_not(boolean, None, cond).unwrap();
+        b.branch(merge).unwrap();
+        b.begin_basic_block(Some(merge)).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let module = b.module();
+
+        let view = ModuleRef::new(&module);
+        assert_eq!(view.types_global_values.len(), 4);
+        assert_eq!(view.def(voidf).unwrap().class.opcode, spirv::Op::TypeFunction);
+        assert_eq!(view.def(x).unwrap().class.opcode, spirv::Op::LogicalNot);
+        assert_eq!(view.def(merge).unwrap().class.opcode, spirv::Op::Label);
+        assert!(view.has_decoration(cond, spirv::Decoration::RelaxedPrecision));
+        assert!(view.decorations(x).is_empty());
+        assert_eq!(view.block(then).unwrap().instructions.len(), 2);
+
+        let view = &view;
+        thread::scope(|s| {
+            let predecessors = s.spawn(|| view.cfg(f).unwrap().predecessors(merge).to_vec());
+            let dominates = s.spawn(|| view.cfg(f).unwrap().dominates(entry, then));
+            assert_eq!(predecessors.join().unwrap(), vec![entry, then]);
+            assert!(dominates.join().unwrap());
+        });
+    }
+}
@@ -0,0 +1,463 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Visitors over modules, in the logical layout order of their
+//! instructions.
+//!
+//! [`walk_module`](fn.walk_module.html) calls the [`Visitor`](trait.Visitor.html)
+//! hook for each instruction in the section it belongs to. The hooks for
+//! instructions default to `visit_instruction`, which visits the result
+//! type and id operands, and the hooks for functions and blocks default to
+//! walking them; an overriding hook can call the `walk_*` function to go
+//! on walking. [`MutVisitor`](trait.MutVisitor.html) is the same over
+//! mutable modules.
+
+use grammar;
+use mr;
+use prelude::*;
+
+use spirv::Word;
+
+/// A visitor over a module; see [`walk_module`](fn.walk_module.html).
+pub trait Visitor {
+    fn visit_header(&mut self, _header: &mr::ModuleHeader) {}
+
+    fn visit_capability(&mut self, inst: &mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_extension(&mut self, inst: &mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_ext_inst_import(&mut self, inst: &mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_memory_model(&mut self, inst: &mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_entry_point(&mut self, inst: &mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_execution_mode(&mut self, inst: &mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_debug(&mut self, inst: &mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_annotation(&mut self, inst: &mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_type(&mut self, inst: &mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    /// Visits a constant or specialization constant.
+    fn visit_constant(&mut self, inst: &mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    /// Visits a module-scope variable.
+    fn visit_global_variable(&mut self, inst: &mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_function(&mut self, function: &mr::Function) {
+        walk_function(self, function)
+    }
+
+    fn visit_parameter(&mut self, inst: &mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_block(&mut self, block: &mr::BasicBlock) {
+        walk_block(self, block)
+    }
+
+    fn visit_label(&mut self, inst: &mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    /// Visits the last instruction of a block, if it is a terminator.
+    fn visit_terminator(&mut self, inst: &mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    /// Visits an instruction not visited by a more specific hook, e.g., an
+    /// instruction in a block, or `OpFunction`.
+    fn visit_instruction(&mut self, inst: &mr::Instruction) {
+        walk_instruction(self, inst)
+    }
+
+    /// Visits the result type or an id operand of an instruction, with its
+    /// role as given by the grammar.
+    fn visit_operand_id(&mut self, _id: Word, _role: grammar::IdRole) {}
+}
+
+/// Walks `module` with `visitor`, section by section, in layout order.
+///
+/// Instructions kept as [raw words](struct.Instruction.html#structfield.raw_words)
+/// are not visited, here nor in the walks of functions and blocks.
+pub fn walk_module<V: Visitor + ?Sized>(visitor: &mut V, module: &mr::Module) {
+    if let Some(ref header) = module.header {
+        visitor.visit_header(header);
+    }
+    known(&module.capabilities).for_each(|inst| visitor.visit_capability(inst));
+    known(&module.extensions).for_each(|inst| visitor.visit_extension(inst));
+    known(&module.ext_inst_imports).for_each(|inst| visitor.visit_ext_inst_import(inst));
+    if let Some(ref inst) = module.memory_model {
+        visitor.visit_memory_model(inst);
+    }
+    known(&module.entry_points).for_each(|inst| visitor.visit_entry_point(inst));
+    known(&module.execution_modes).for_each(|inst| visitor.visit_execution_mode(inst));
+    known(&module.debugs).for_each(|inst| visitor.visit_debug(inst));
+    known(&module.annotations).for_each(|inst| visitor.visit_annotation(inst));
+    for inst in known(&module.types_global_values) {
+        match Global::of(inst) {
+            Global::Type => visitor.visit_type(inst),
+            Global::Constant => visitor.visit_constant(inst),
+            Global::Variable => visitor.visit_global_variable(inst),
+            Global::Other => visitor.visit_instruction(inst),
+        }
+    }
+    module.functions.iter().for_each(|function| visitor.visit_function(function));
+}
+
+/// Walks `function` with `visitor`: its `OpFunction`, parameters, blocks,
+/// and `OpFunctionEnd`.
+pub fn walk_function<V: Visitor + ?Sized>(visitor: &mut V, function: &mr::Function) {
+    if let Some(ref inst) = function.def {
+        visitor.visit_instruction(inst);
+    }
+    known(&function.parameters).for_each(|inst| visitor.visit_parameter(inst));
+    function.basic_blocks.iter().for_each(|block| visitor.visit_block(block));
+    if let Some(ref inst) = function.end {
+        visitor.visit_instruction(inst);
+    }
+}
+
+/// Walks `block` with `visitor`: its label and instructions.
+pub fn walk_block<V: Visitor + ?Sized>(visitor: &mut V, block: &mr::BasicBlock) {
+    if let Some(ref inst) = block.label {
+        visitor.visit_label(inst);
+    }
+    for (index, inst) in block.instructions.iter().enumerate() {
+        if inst.raw_words.is_some() {
+            continue;
+        }
+        if index + 1 == block.instructions.len() &&
+           grammar::reflect::is_terminator(inst.class.opcode) {
+            visitor.visit_terminator(inst);
+        } else {
+            visitor.visit_instruction(inst);
+        }
+    }
+}
+
+/// Returns the instructions of `insts` not kept as raw words.
+fn known(insts: &[mr::Instruction]) -> impl Iterator<Item = &mr::Instruction> {
+    insts.iter().filter(|inst| inst.raw_words.is_none())
+}
+
+/// Walks the result type and id operands of `inst`, in order, with
+/// `visitor`.
+pub fn walk_instruction<V: Visitor + ?Sized>(visitor: &mut V, inst: &mr::Instruction) {
+    for (id, role) in inst.id_operands() {
+        visitor.visit_operand_id(id, role);
+    }
+}
+
+/// A visitor over a mutable module; see
+/// [`walk_module_mut`](fn.walk_module_mut.html).
+///
+/// The hooks are those of [`Visitor`](trait.Visitor.html).
+pub trait MutVisitor {
+    fn visit_header(&mut self, _header: &mut mr::ModuleHeader) {}
+
+    fn visit_capability(&mut self, inst: &mut mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_extension(&mut self, inst: &mut mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_ext_inst_import(&mut self, inst: &mut mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_memory_model(&mut self, inst: &mut mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_entry_point(&mut self, inst: &mut mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_execution_mode(&mut self, inst: &mut mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_debug(&mut self, inst: &mut mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_annotation(&mut self, inst: &mut mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_type(&mut self, inst: &mut mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_constant(&mut self, inst: &mut mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_global_variable(&mut self, inst: &mut mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_function(&mut self, function: &mut mr::Function) {
+        walk_function_mut(self, function)
+    }
+
+    fn visit_parameter(&mut self, inst: &mut mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_block(&mut self, block: &mut mr::BasicBlock) {
+        walk_block_mut(self, block)
+    }
+
+    fn visit_label(&mut self, inst: &mut mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_terminator(&mut self, inst: &mut mr::Instruction) {
+        self.visit_instruction(inst)
+    }
+
+    fn visit_instruction(&mut self, inst: &mut mr::Instruction) {
+        walk_instruction_mut(self, inst)
+    }
+
+    fn visit_operand_id(&mut self, _id: &mut Word, _role: grammar::IdRole) {}
+}
+
+/// Walks `module` with `visitor`, like [`walk_module`](fn.walk_module.html).
+pub fn walk_module_mut<V: MutVisitor + ?Sized>(visitor: &mut V, module: &mut mr::Module) {
+    if let Some(ref mut header) = module.header {
+        visitor.visit_header(header);
+    }
+    known_mut(&mut module.capabilities).for_each(|inst| visitor.visit_capability(inst));
+    known_mut(&mut module.extensions).for_each(|inst| visitor.visit_extension(inst));
+    known_mut(&mut module.ext_inst_imports).for_each(|inst| visitor.visit_ext_inst_import(inst));
+    if let Some(ref mut inst) = module.memory_model {
+        visitor.visit_memory_model(inst);
+    }
+    known_mut(&mut module.entry_points).for_each(|inst| visitor.visit_entry_point(inst));
+    known_mut(&mut module.execution_modes).for_each(|inst| visitor.visit_execution_mode(inst));
+    known_mut(&mut module.debugs).for_each(|inst| visitor.visit_debug(inst));
+    known_mut(&mut module.annotations).for_each(|inst| visitor.visit_annotation(inst));
+    for inst in known_mut(&mut module.types_global_values) {
+        match Global::of(inst) {
+            Global::Type => visitor.visit_type(inst),
+            Global::Constant => visitor.visit_constant(inst),
+            Global::Variable => visitor.visit_global_variable(inst),
+            Global::Other => visitor.visit_instruction(inst),
+        }
+    }
+    module.functions.iter_mut().for_each(|function| visitor.visit_function(function));
+}
+
+/// Walks `function` with `visitor`, like
+/// [`walk_function`](fn.walk_function.html).
+pub fn walk_function_mut<V: MutVisitor + ?Sized>(visitor: &mut V, function: &mut mr::Function) {
+    if let Some(ref mut inst) = function.def {
+        visitor.visit_instruction(inst);
+    }
+    known_mut(&mut function.parameters).for_each(|inst| visitor.visit_parameter(inst));
+    function.basic_blocks.iter_mut().for_each(|block| visitor.visit_block(block));
+    if let Some(ref mut inst) = function.end {
+        visitor.visit_instruction(inst);
+    }
+}
+
+/// Walks `block` with `visitor`, like [`walk_block`](fn.walk_block.html).
+pub fn walk_block_mut<V: MutVisitor + ?Sized>(visitor: &mut V, block: &mut mr::BasicBlock) {
+    if let Some(ref mut inst) = block.label {
+        visitor.visit_label(inst);
+    }
+    let count = block.instructions.len();
+    for (index, inst) in block.instructions.iter_mut().enumerate() {
+        if inst.raw_words.is_some() {
+            continue;
+        }
+        if index + 1 == count && grammar::reflect::is_terminator(inst.class.opcode) {
+            visitor.visit_terminator(inst);
+        } else {
+            visitor.visit_instruction(inst);
+        }
+    }
+}
+
+/// Returns the instructions of `insts` not kept as raw words.
+fn known_mut(insts: &mut [mr::Instruction]) -> impl Iterator<Item = &mut mr::Instruction> {
+    insts.iter_mut().filter(|inst| inst.raw_words.is_none())
+}
+
+/// Walks the result type and id operands of `inst` with `visitor`, like
+/// [`walk_instruction`](fn.walk_instruction.html).
+pub fn walk_instruction_mut<V: MutVisitor + ?Sized>(visitor: &mut V, inst: &mut mr::Instruction) {
+    let roles: Vec<grammar::IdRole> = inst.id_operands().map(|(_, role)| role).collect();
+    let ids = inst.result_type
+        .iter_mut()
+        .chain(inst.operands.iter_mut().filter_map(mr::Operand::id_mut));
+    for (id, role) in ids.zip(roles) {
+        visitor.visit_operand_id(id, role);
+    }
+}
+
+/// The kinds of instructions among the types and global values.
+enum Global {
+    Type,
+    Constant,
+    Variable,
+    Other,
+}
+
+impl Global {
+    fn of(inst: &mr::Instruction) -> Global {
+        let opcode = inst.class.opcode;
+        if grammar::reflect::is_type(opcode) {
+            Global::Type
+        } else if grammar::reflect::is_constant(opcode) {
+            Global::Constant
+        } else if grammar::reflect::is_variable(opcode) {
+            Global::Variable
+        } else {
+            Global::Other
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use grammar;
+    use mr;
+    use spirv;
+
+    use spirv::Word;
+    use super::{walk_module, walk_module_mut, MutVisitor, Visitor};
+
+    fn module() -> (mr::Module, Word) {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let ptr = b.type_pointer(None, spirv::StorageClass::Private, float);
+        let var = b.variable(ptr, None, spirv::StorageClass::Private, None);
+        let one = b.constant_f32(float, 1.0);
+        let fty = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.store(var, one, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        (b.module(), var)
+    }
+
+    #[test]
+    fn test_visitor() {
+        #[derive(Default)]
+        struct Counts {
+            types: usize,
+            constants: usize,
+            variables: usize,
+            terminators: Vec<spirv::Op>,
+            pointers: Vec<Word>,
+        }
+
+        impl Visitor for Counts {
+            fn visit_type(&mut self, _: &mr::Instruction) {
+                self.types += 1;
+            }
+
+            fn visit_constant(&mut self, inst: &mr::Instruction) {
+                self.constants += 1;
+                self.visit_instruction(inst);
+            }
+
+            fn visit_global_variable(&mut self, inst: &mr::Instruction) {
+                self.variables += 1;
+                self.visit_instruction(inst);
+            }
+
+            fn visit_terminator(&mut self, inst: &mr::Instruction) {
+                self.terminators.push(inst.class.opcode);
+            }
+
+            fn visit_operand_id(&mut self, id: Word, role: grammar::IdRole) {
+                if role == grammar::IdRole::MemoryObject {
+                    self.pointers.push(id);
+                }
+            }
+        }
+
+        let (m, var) = module();
+        let mut counts = Counts::default();
+        walk_module(&mut counts, &m);
+        assert_eq!((counts.types, counts.constants, counts.variables), (4, 1, 1));
+        assert_eq!(counts.terminators, vec![spirv::Op::Return]);
+        assert_eq!(counts.pointers, vec![var]);
+    }
+
+    #[test]
+    fn test_mut_visitor() {
+        struct Offset(Word);
+
+        impl MutVisitor for Offset {
+            fn visit_header(&mut self, header: &mut mr::ModuleHeader) {
+                header.bound += self.0;
+            }
+
+            fn visit_instruction(&mut self, inst: &mut mr::Instruction) {
+                if let Some(ref mut id) = inst.result_id {
+                    *id += self.0;
+                }
+                super::walk_instruction_mut(self, inst);
+            }
+
+            fn visit_operand_id(&mut self, id: &mut Word, _: grammar::IdRole) {
+                *id += self.0;
+            }
+        }
+
+        let (mut m, var) = module();
+        let bound = m.header.as_ref().unwrap().bound;
+        walk_module_mut(&mut Offset(100), &mut m);
+        assert_eq!(m.header.as_ref().unwrap().bound, bound + 100);
+        assert_eq!(m.types_global_values[3].result_id, Some(var + 100));
+        let store = &m.functions[0].basic_blocks[0].instructions[0];
+        assert_eq!(store.operands[0], mr::Operand::IdRef(var + 100));
+        assert_eq!(m.functions[0].def.as_ref().unwrap().result_type,
+                   m.types_global_values[0].result_id);
+    }
+}
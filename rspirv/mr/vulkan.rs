@@ -0,0 +1,103 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use prelude::*;
+use spirv;
+
+use binary::{Assemble, ParseResult};
+use std::borrow::Cow;
+
+impl mr::Module {
+    /// Assembles this module into the words `pCode` of a
+    /// `VkShaderModuleCreateInfo` points to.
+    pub fn as_words(&self) -> Vec<u32> {
+        self.assemble()
+    }
+
+    /// Returns the size in bytes of the assembled module, as `codeSize` of a
+    /// `VkShaderModuleCreateInfo` wants it.
+    pub fn code_size(&self) -> usize {
+        self.as_words().len() * 4
+    }
+
+    /// Loads a module from the words of its binary.
+    ///
+    /// Words in the opposite byte order, e.g., of a binary read by another
+    /// host, are swapped first; the magic number tells which order it is.
+    pub fn from_words(words: &[u32]) -> ParseResult<mr::Module> {
+        match words.first() {
+            Some(&magic) if magic == spirv::MAGIC_NUMBER.swap_bytes() => {
+                let swapped: Vec<u32> = words.iter().map(|w| w.swap_bytes()).collect();
+                mr::load_words(swapped)
+            }
+            _ => mr::load_words(words),
+        }
+    }
+}
+
+/// Reinterprets `bytes` as the words of a SPIR-V binary, e.g., of an
+/// `include_bytes!`, in host byte order.
+///
+/// The words are borrowed if `bytes` are aligned to a word, and copied
+/// otherwise. Returns `None` if the length of `bytes` is not a multiple of
+/// four.
+pub fn words_of<'a>(bytes: &'a [u8]) -> Option<Cow<'a, [u32]>> {
+    if bytes.len() & 3 != 0 {
+        return None;
+    }
+    // Any four bytes form a valid u32, so viewing the middle is sound.
+    let (head, words, _) = unsafe { bytes.align_to::<u32>() };
+    if head.is_empty() {
+        Some(Cow::Borrowed(words))
+    } else {
+        let words = bytes
+            .chunks(4)
+            .map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        Some(Cow::Owned(words))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use binary::Disassemble;
+    use mr;
+    use spirv;
+
+    use super::words_of;
+
+    #[test]
+    fn test_shader_code() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let module = b.module();
+
+        let words = module.as_words();
+        assert_eq!(module.code_size(), words.len() * 4);
+        let expected = module.disassemble();
+        assert_eq!(mr::Module::from_words(&words).unwrap().disassemble(), expected);
+        let swapped: Vec<u32> = words.iter().map(|w| w.swap_bytes()).collect();
+        assert_eq!(mr::Module::from_words(&swapped).unwrap().disassemble(), expected);
+
+        let mut bytes = vec![0u8];
+        for word in &words {
+            bytes.extend_from_slice(&word.to_ne_bytes());
+        }
+        assert_eq!(&*words_of(&bytes[1..]).unwrap(), &words[..]);
+        assert_eq!(words_of(&bytes[1..5]).unwrap().len(), 1);
+        assert!(words_of(&bytes[1..4]).is_none());
+    }
+}
@@ -0,0 +1,600 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+use std::mem;
+
+/// A pointer into a struct variable to split: the variable, and the
+/// members indexed down to the struct pointed to.
+type Path = (Word, Vec<u32>);
+
+/// Splits the `Function` and `Private` variables of `module` holding
+/// structs into one variable per member, down through nested structs, and
+/// returns the number of variables split.
+///
+/// Access chains into the structs become pointers to the new variables,
+/// or access chains from them for the indices left, so that passes like
+/// [`eliminate_loads_stores`](fn.eliminate_loads_stores.html) can work on
+/// each member. Loads and stores of whole structs load and store each
+/// member, with `OpCompositeConstruct` and `OpCompositeExtract`, and
+/// initializers are split into their constituents.
+///
+/// Only variables whose pointers are used for nothing but non-volatile
+/// `OpLoad`s and `OpStore`s, and access chains with constant struct
+/// indices, are split, and only if they have no decorations and are
+/// initialized by `OpConstantComposite`s or `OpConstantNull`, if at all.
+pub fn split_struct_variables(module: &mut mr::Module) -> usize {
+    module.record_edits(|module| {
+        let structs: BTreeMap<Word, Vec<Word>> = module.types_global_values
+            .iter()
+            .filter(|inst| inst.class.opcode == spirv::Op::TypeStruct)
+            .filter_map(|inst| {
+                let members = inst.operands.iter().filter_map(mr::Operand::id).collect();
+                inst.result_id.map(|id| (id, members))
+            })
+            .collect();
+        let pointees: BTreeMap<Word, Word> = module.types_global_values
+            .iter()
+            .filter(|inst| inst.class.opcode == spirv::Op::TypePointer)
+            .filter_map(|inst| {
+                let pointee = inst.operands.get(1).and_then(mr::Operand::id);
+                inst.result_id.and_then(|id| pointee.map(|pointee| (id, pointee)))
+            })
+            .collect();
+        let constants: BTreeMap<Word, &mr::Instruction> = module.types_global_values
+            .iter()
+            .filter(|inst| {
+                matches!(inst.class.opcode,
+                         spirv::Op::Constant |
+                         spirv::Op::ConstantComposite |
+                         spirv::Op::ConstantNull)
+            })
+            .filter_map(|inst| inst.result_id.map(|id| (id, inst)))
+            .collect();
+        let indices: BTreeMap<Word, u32> = constants.iter()
+            .filter_map(|(&id, inst)| match (inst.class.opcode, &inst.operands[..]) {
+                (spirv::Op::Constant, &[mr::Operand::LiteralInt32(value)]) => Some((id, value)),
+                _ => None,
+            })
+            .collect();
+
+        // The variables to split, with their struct types and initializers.
+        let mut variables: BTreeMap<Word, (Word, Option<Word>)> = BTreeMap::new();
+        let function_variables = module.functions
+            .iter()
+            .flat_map(|f| f.basic_blocks.iter().take(1).flat_map(|b| &b.instructions));
+        for inst in module.types_global_values.iter().chain(function_variables) {
+            let class = match inst.operands.first() {
+                Some(&mr::Operand::StorageClass(class)) => class,
+                _ => continue,
+            };
+            let ty = inst.result_type.and_then(|ty| pointees.get(&ty).cloned());
+            let splittable = inst.class.opcode == spirv::Op::Variable &&
+                             (class == spirv::StorageClass::Function ||
+                              class == spirv::StorageClass::Private);
+            if let (true, Some(id), Some(ty)) = (splittable, inst.result_id, ty) {
+                if structs.contains_key(&ty) {
+                    let initializer = inst.operands.get(1).and_then(mr::Operand::id);
+                    variables.insert(id, (ty, initializer));
+                }
+            }
+        }
+        for inst in &module.annotations {
+            if let Some(target) = inst.operands.first().and_then(mr::Operand::id) {
+                variables.remove(&target);
+            }
+        }
+        variables.retain(|_, &mut (ty, initializer)| {
+            initializer.is_none_or(|init| splits(&structs, &constants, ty, init))
+        });
+
+        // Pointers to the structs, and access chains reaching members.
+        let mut paths: BTreeMap<Word, Path> =
+            variables.keys().map(|&v| (v, (v, vec![]))).collect();
+        let mut rejected = BTreeSet::new();
+        let insts =
+            module.functions.iter().flat_map(|f| &f.basic_blocks).flat_map(|b| &b.instructions);
+        for inst in insts {
+            for (i, operand) in inst.operands.iter().enumerate() {
+                let (root, path) = match operand.id().and_then(|id| paths.get(&id)) {
+                    Some(path) => path.clone(),
+                    None => continue,
+                };
+                let volatile = inst.operands.iter().any(|operand| match *operand {
+                    mr::Operand::MemoryAccess(access) => {
+                        access.contains(spirv::MemoryAccess::VOLATILE)
+                    }
+                    _ => false,
+                });
+                let plain = match inst.class.opcode {
+                    spirv::Op::Load | spirv::Op::Store => i == 0 && !volatile,
+                    spirv::Op::AccessChain | spirv::Op::InBoundsAccessChain if i == 0 => {
+                        let mut ty = type_at(&structs, variables[&root].0, &path);
+                        let mut path = path;
+                        let mut constant = true;
+                        for index in inst.operands[1..].iter().filter_map(mr::Operand::id) {
+                            let members = match structs.get(&ty) {
+                                Some(members) => members,
+                                None => break,
+                            };
+                            let member = indices.get(&index).cloned();
+                            match member.and_then(|m| members.get(m as usize).map(|&t| (m, t))) {
+                                Some((member, member_ty)) => {
+                                    path.push(member);
+                                    ty = member_ty;
+                                }
+                                None => {
+                                    constant = false;
+                                    break;
+                                }
+                            }
+                        }
+                        if constant && structs.contains_key(&ty) {
+                            paths.extend(inst.result_id.map(|id| (id, (root, path))));
+                        }
+                        constant
+                    }
+                    _ => false,
+                };
+                if !plain {
+                    rejected.insert(root);
+                }
+            }
+        }
+        variables.retain(|v, _| !rejected.contains(v));
+        paths.retain(|_, &mut (root, _)| variables.contains_key(&root));
+        if variables.is_empty() {
+            return 0;
+        }
+
+        let mut splitter = Splitter {
+            structs: &structs,
+            constants: &constants,
+            ids: mr::IdAllocator::for_module(module),
+            pointers: module.types_global_values
+                .iter()
+                .filter(|inst| inst.class.opcode == spirv::Op::TypePointer)
+                .filter_map(|inst| match (inst.result_id, &inst.operands[..]) {
+                    (Some(id),
+                     &[mr::Operand::StorageClass(class), mr::Operand::IdRef(pointee)]) => {
+                        Some(((class, pointee), id))
+                    }
+                    _ => None,
+                })
+                .collect(),
+            nulls: constants.values()
+                .filter(|inst| inst.class.opcode == spirv::Op::ConstantNull)
+                .filter_map(|inst| {
+                    inst.result_type.and_then(|ty| inst.result_id.map(|id| (ty, id)))
+                })
+                .collect(),
+            leaves: BTreeMap::new(),
+            added: vec![],
+        };
+        // The new variables, global or by function, replacing the old ones.
+        let mut globals = vec![];
+        let mut locals: BTreeMap<Word, Vec<mr::Instruction>> = BTreeMap::new();
+        let first_blocks = module.functions.iter().flat_map(|f| f.basic_blocks.iter().take(1));
+        let variable_insts = first_blocks.flat_map(|b| &b.instructions);
+        for inst in module.types_global_values.iter().chain(variable_insts) {
+            let id = match inst.result_id {
+                Some(id) if variables.contains_key(&id) => id,
+                _ => continue,
+            };
+            let class = match inst.operands[0] {
+                mr::Operand::StorageClass(class) => class,
+                _ => continue,
+            };
+            let (ty, initializer) = variables[&id];
+            let mut split = vec![];
+            splitter.split(class, (id, vec![]), ty, initializer, &mut split);
+            if class == spirv::StorageClass::Private {
+                globals.extend(split);
+            } else {
+                locals.insert(id, split);
+            }
+        }
+        let (leaves, mut ids) = (splitter.leaves, splitter.ids);
+        module.types_global_values.extend(splitter.added);
+        module.types_global_values.retain(|inst| {
+            !inst.result_id.is_some_and(|id| variables.contains_key(&id))
+        });
+        module.types_global_values.extend(globals);
+        module.debugs.retain(|inst| {
+            !inst.operands.first().and_then(mr::Operand::id).is_some_and(|id| {
+                variables.contains_key(&id)
+            })
+        });
+        for inst in &mut module.entry_points {
+            let operands = mem::take(&mut inst.operands);
+            for operand in operands {
+                match operand.id().filter(|id| variables.contains_key(id)) {
+                    Some(variable) => {
+                        let split = leaves.iter().filter(|&(&(v, _), _)| v == variable);
+                        inst.operands.extend(split.map(|(_, &leaf)| mr::Operand::IdRef(leaf)));
+                    }
+                    None => inst.operands.push(operand),
+                }
+            }
+        }
+
+        let mut rewriter = Rewriter {
+            structs: &structs,
+            paths: &paths,
+            leaves: &leaves,
+            indices: &indices,
+            ids: &mut ids,
+            forwarded: vec![],
+        };
+        let mut functions = mem::take(&mut module.functions);
+        for function in &mut functions {
+            for block in &mut function.basic_blocks {
+                let old = mem::take(&mut block.instructions);
+                for inst in old {
+                    let split = inst.result_id.and_then(|id| locals.remove(&id));
+                    match split {
+                        Some(split) => block.instructions.extend(split),
+                        None => rewriter.rewrite(&variables, inst, &mut block.instructions),
+                    }
+                }
+            }
+        }
+        module.functions = functions;
+        for (old, new) in rewriter.forwarded {
+            module.replace_all_uses(old, new);
+        }
+        ids.update_header(module);
+        variables.len()
+    })
+}
+
+/// Returns true if the initializer `init` of a variable of the struct
+/// type `ty` splits into constants for all its members.
+fn splits(structs: &BTreeMap<Word, Vec<Word>>,
+          constants: &BTreeMap<Word, &mr::Instruction>,
+          ty: Word,
+          init: Word)
+          -> bool {
+    let inst = match constants.get(&init) {
+        Some(inst) => inst,
+        None => return false,
+    };
+    let members = match structs.get(&ty) {
+        Some(members) => members,
+        None => return true,
+    };
+    match inst.class.opcode {
+        spirv::Op::ConstantNull => true,
+        spirv::Op::ConstantComposite => {
+            members.iter().zip(&inst.operands).all(|(&member, constituent)| {
+                constituent.id().is_some_and(|c| splits(structs, constants, member, c))
+            })
+        }
+        _ => false,
+    }
+}
+
+/// Returns the type reached by indexing down the struct type `ty` along
+/// `path`.
+fn type_at(structs: &BTreeMap<Word, Vec<Word>>, ty: Word, path: &[u32]) -> Word {
+    path.iter().fold(ty, |ty, &member| structs[&ty][member as usize])
+}
+
+/// Declares the variables replacing struct variables.
+struct Splitter<'a> {
+    structs: &'a BTreeMap<Word, Vec<Word>>,
+    constants: &'a BTreeMap<Word, &'a mr::Instruction>,
+    ids: mr::IdAllocator,
+    /// The pointer types, by storage class and pointee.
+    pointers: BTreeMap<(spirv::StorageClass, Word), Word>,
+    /// The null constants, by type.
+    nulls: BTreeMap<Word, Word>,
+    /// The new variables, by the variable they split and their path.
+    leaves: BTreeMap<Path, Word>,
+    /// The pointer types and null constants added.
+    added: Vec<mr::Instruction>,
+}
+
+impl<'a> Splitter<'a> {
+    fn pointer(&mut self, class: spirv::StorageClass, ty: Word) -> Word {
+        if let Some(&id) = self.pointers.get(&(class, ty)) {
+            return id;
+        }
+        let id = self.ids.id();
+        self.added.push(mr::Instruction::new(spirv::Op::TypePointer,
+                                             None,
+                                             Some(id),
+                                             vec![mr::Operand::StorageClass(class),
+                                                  mr::Operand::IdRef(ty)]));
+        self.pointers.insert((class, ty), id);
+        id
+    }
+
+    fn null(&mut self, ty: Word) -> Word {
+        if let Some(&id) = self.nulls.get(&ty) {
+            return id;
+        }
+        let id = self.ids.id();
+        self.added.push(mr::Instruction::new(spirv::Op::ConstantNull, Some(ty), Some(id), vec![]));
+        self.nulls.insert(ty, id);
+        id
+    }
+
+    /// Appends to `split` the variables replacing the member at `path` of
+    /// type `ty`, initialized by `init`, or by the members of its struct
+    /// if `init` is a null struct.
+    fn split(&mut self,
+             class: spirv::StorageClass,
+             path: Path,
+             ty: Word,
+             init: Option<Word>,
+             split: &mut Vec<mr::Instruction>) {
+        let structs = self.structs;
+        let constant = init.and_then(|init| self.constants.get(&init).cloned());
+        if let Some(members) = structs.get(&ty) {
+            for (m, &member) in members.iter().enumerate() {
+                let init = match constant {
+                    Some(inst) if inst.class.opcode == spirv::Op::ConstantComposite => {
+                        inst.operands.get(m).and_then(mr::Operand::id)
+                    }
+                    _ => init,
+                };
+                let mut path = path.clone();
+                path.1.push(m as u32);
+                self.split(class, path, member, init, split);
+            }
+            return;
+        }
+        let init = match constant {
+            Some(inst) if inst.class.opcode == spirv::Op::ConstantNull &&
+                          inst.result_type != Some(ty) => Some(self.null(ty)),
+            _ => init,
+        };
+        let pointer = self.pointer(class, ty);
+        let id = self.ids.id();
+        let mut operands = vec![mr::Operand::StorageClass(class)];
+        operands.extend(init.map(mr::Operand::IdRef));
+        split.push(mr::Instruction::new(spirv::Op::Variable, Some(pointer), Some(id), operands));
+        self.leaves.insert(path, id);
+    }
+}
+
+/// Rewrites the accesses to split variables.
+struct Rewriter<'a> {
+    structs: &'a BTreeMap<Word, Vec<Word>>,
+    paths: &'a BTreeMap<Word, Path>,
+    leaves: &'a BTreeMap<Path, Word>,
+    /// The values of integer constants, for struct indices.
+    indices: &'a BTreeMap<Word, u32>,
+    ids: &'a mut mr::IdAllocator,
+    /// The access chains replaced by new variables.
+    forwarded: Vec<(Word, Word)>,
+}
+
+impl<'a> Rewriter<'a> {
+    /// Appends `inst`, rewritten to access the new variables, to `insts`.
+    fn rewrite(&mut self,
+               variables: &BTreeMap<Word, (Word, Option<Word>)>,
+               mut inst: mr::Instruction,
+               insts: &mut Vec<mr::Instruction>) {
+        let path = match inst.operands.first().and_then(mr::Operand::id) {
+            Some(pointer) => match self.paths.get(&pointer) {
+                Some(path) => path.clone(),
+                None => return insts.push(inst),
+            },
+            None => return insts.push(inst),
+        };
+        let ty = type_at(self.structs, variables[&path.0].0, &path.1);
+        match inst.class.opcode {
+            spirv::Op::Load => {
+                let id = inst.result_id.unwrap_or(0);
+                self.load(path, ty, Some(id), insts);
+            }
+            spirv::Op::Store => {
+                if let Some(value) = inst.operands.get(1).and_then(mr::Operand::id) {
+                    self.store(path, ty, value, insts);
+                }
+            }
+            _ => {
+                // An access chain: its struct indices select the variable.
+                let result = inst.result_id.unwrap_or(0);
+                if self.paths.contains_key(&result) {
+                    return;
+                }
+                let (mut path, mut ty) = (path, ty);
+                let mut indices = inst.operands[1..].iter();
+                while let Some(members) = self.structs.get(&ty) {
+                    let index = indices.next().and_then(mr::Operand::id);
+                    let member = index.and_then(|index| self.indices.get(&index)).cloned();
+                    let member = member.unwrap_or(0);
+                    path.1.push(member);
+                    ty = members[member as usize];
+                }
+                let leaf = self.leaves[&path];
+                let indices: Vec<mr::Operand> = indices.cloned().collect();
+                if indices.is_empty() {
+                    self.forwarded.push((result, leaf));
+                } else {
+                    let mut operands = vec![mr::Operand::IdRef(leaf)];
+                    operands.extend(indices);
+                    inst.operands = operands.into();
+                    insts.push(inst);
+                }
+            }
+        }
+    }
+
+    /// Appends to `insts` the loads of the members of the struct of type
+    /// `ty` at `path`, and returns the id of the struct they make.
+    fn load(&mut self,
+            path: Path,
+            ty: Word,
+            result_id: Option<Word>,
+            insts: &mut Vec<mr::Instruction>)
+            -> Word {
+        let id = result_id.unwrap_or_else(|| self.ids.id());
+        let members = match self.structs.get(&ty) {
+            Some(members) => members.clone(),
+            None => {
+                let leaf = self.leaves[&path];
+                insts.push(mr::Instruction::new(spirv::Op::Load,
+                                                Some(ty),
+                                                Some(id),
+                                                vec![mr::Operand::IdRef(leaf)]));
+                return id;
+            }
+        };
+        let mut constituents = vec![];
+        for (m, &member) in members.iter().enumerate() {
+            let mut path = path.clone();
+            path.1.push(m as u32);
+            constituents.push(mr::Operand::IdRef(self.load(path, member, None, insts)));
+        }
+        insts.push(mr::Instruction::new(spirv::Op::CompositeConstruct,
+                                        Some(ty),
+                                        Some(id),
+                                        constituents));
+        id
+    }
+
+    /// Appends to `insts` the stores of the members of `value`, of type
+    /// `ty`, to the variables at `path`.
+    fn store(&mut self, path: Path, ty: Word, value: Word, insts: &mut Vec<mr::Instruction>) {
+        let members = match self.structs.get(&ty) {
+            Some(members) => members.clone(),
+            None => {
+                let leaf = self.leaves[&path];
+                insts.push(mr::Instruction::new(spirv::Op::Store,
+                                                None,
+                                                None,
+                                                vec![mr::Operand::IdRef(leaf),
+                                                     mr::Operand::IdRef(value)]));
+                return;
+            }
+        };
+        for (m, &member) in members.iter().enumerate() {
+            let id = self.ids.id();
+            insts.push(mr::Instruction::new(spirv::Op::CompositeExtract,
+                                            Some(member),
+                                            Some(id),
+                                            vec![mr::Operand::IdRef(value),
+                                                 mr::Operand::LiteralInt32(m as u32)]));
+            let mut path = path.clone();
+            path.1.push(m as u32);
+            self.store(path, member, id, insts);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::split_struct_variables;
+    use utils::test::opcodes;
+
+    #[test]
+    fn test_split_struct_variables() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let float = b.type_float(32);
+        let vec2 = b.type_vector(float, 2);
+        let zero = b.constant_u32(uint, 0);
+        let one = b.constant_u32(uint, 1);
+        let two = b.constant_u32(uint, 2);
+        let array = b.type_array(uint, two);
+        let inner = b.type_struct(vec![float, vec2]);
+        let outer = b.type_struct(vec![uint, inner, array]);
+        let fn_outer = b.type_pointer(None, spirv::StorageClass::Function, outer);
+        let fn_inner = b.type_pointer(None, spirv::StorageClass::Function, inner);
+        let fn_float = b.type_pointer(None, spirv::StorageClass::Function, float);
+        let fn_uint = b.type_pointer(None, spirv::StorageClass::Function, uint);
+        let private_inner = b.type_pointer(None, spirv::StorageClass::Private, inner);
+        let private_vec2 = b.type_pointer(None, spirv::StorageClass::Private, vec2);
+        let null = b.constant_null(inner);
+        let global = b.variable(private_inner, None, spirv::StorageClass::Private, Some(null));
+        b.name(global, "global");
+        let fty = b.type_function(void, vec![uint, outer]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let i = b.function_parameter(uint).unwrap();
+        let value = b.function_parameter(outer).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let local = b.variable(fn_outer, None, spirv::StorageClass::Function, None);
+        b.store(local, value, None, vec![]).unwrap();
+        let x = b.access_chain(fn_float, None, local, vec![one, zero]).unwrap();
+        b.load(float, None, x, None, vec![]).unwrap();
+        let element = b.access_chain(fn_uint, None, local, vec![two, i]).unwrap();
+        b.store(element, i, None, vec![]).unwrap();
+        let nested = b.access_chain(fn_inner, None, local, vec![one]).unwrap();
+        b.load(inner, None, nested, None, vec![]).unwrap();
+        let y = b.access_chain(private_vec2, None, global, vec![one]).unwrap();
+        b.load(vec2, None, y, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        assert_eq!(split_struct_variables(&mut m), 2);
+        assert!(m.debugs.is_empty());
+        let globals: Vec<&mr::Instruction> = m.types_global_values
+            .iter()
+            .filter(|inst| inst.class.opcode == spirv::Op::Variable)
+            .collect();
+        assert_eq!(globals.len(), 2);
+        assert!(globals.iter().all(|inst| inst.operands.len() == 2));
+        let nulls: Vec<Option<spirv::Word>> = m.types_global_values
+            .iter()
+            .filter(|inst| inst.class.opcode == spirv::Op::ConstantNull)
+            .map(|inst| inst.result_type)
+            .collect();
+        assert_eq!(nulls, vec![Some(inner), Some(float), Some(vec2)]);
+
+        let block = &m.functions[0].basic_blocks[0];
+        assert_eq!(opcodes(block),
+                   vec![spirv::Op::Variable,
+                        spirv::Op::Variable,
+                        spirv::Op::Variable,
+                        spirv::Op::Variable,
+                        // The whole store, member by member.
+                        spirv::Op::CompositeExtract,
+                        spirv::Op::Store,
+                        spirv::Op::CompositeExtract,
+                        spirv::Op::CompositeExtract,
+                        spirv::Op::Store,
+                        spirv::Op::CompositeExtract,
+                        spirv::Op::Store,
+                        spirv::Op::CompositeExtract,
+                        spirv::Op::Store,
+                        spirv::Op::Load,
+                        spirv::Op::AccessChain,
+                        spirv::Op::Store,
+                        // The load of the nested struct, member by member.
+                        spirv::Op::Load,
+                        spirv::Op::Load,
+                        spirv::Op::CompositeConstruct,
+                        spirv::Op::Load,
+                        spirv::Op::Return]);
+        let insts = &block.instructions;
+        let leaf = |index: usize| insts[index].result_id.unwrap();
+        assert_eq!(insts[13].operands[0], mr::Operand::IdRef(leaf(1)));
+        assert_eq!(insts[14].operands[..],
+                   [mr::Operand::IdRef(leaf(3)), mr::Operand::IdRef(i)]);
+        assert_eq!(insts[19].operands[0], mr::Operand::IdRef(globals[1].result_id.unwrap()));
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::BTreeMap;
+use std::mem;
+
+/// Merges the blocks of the functions of `module` into their predecessor
+/// when it is their only one and branches to them unconditionally, and
+/// returns the number of blocks merged.
+///
+/// Blocks referred to by anything but the branch, e.g., as merge blocks
+/// or continue targets, are kept, as are the blocks loop headers branch
+/// to. The `OpPhi`s of merged blocks are replaced by their values, and
+/// removed with their names and decorations; phis of the following blocks
+/// take the predecessor as parent.
+pub fn merge_blocks(module: &mut mr::Module) -> usize {
+    module.record_edits(|module| {
+        let mut removed = vec![];
+        for function in &mut module.functions {
+            removed.extend(merge_function_blocks(function));
+        }
+        let merged = removed.iter().filter(|inst| inst.class.opcode == spirv::Op::Label).count();
+        if !removed.is_empty() {
+            let mut ids = mr::IdAllocator::for_module(module);
+            module.replace_removed_uses(&mut ids, &removed);
+            ids.update_header(module);
+        }
+        merged
+    })
+}
+
+fn label(block: &mr::BasicBlock) -> Option<Word> {
+    block.label.as_ref()?.result_id
+}
+
+/// Merges the blocks of `function`, and returns the labels and phis
+/// removed.
+fn merge_function_blocks(function: &mut mr::Function) -> Vec<mr::Instruction> {
+    // The references to labels outside of phis, which merging keeps.
+    let mut references: BTreeMap<Word, usize> = BTreeMap::new();
+    for inst in function.basic_blocks.iter().flat_map(|b| &b.instructions) {
+        if inst.class.opcode != spirv::Op::Phi {
+            for id in inst.operands.iter().filter_map(mr::Operand::id) {
+                *references.entry(id).or_insert(0) += 1;
+            }
+        }
+    }
+    let mut slots: Vec<Option<mr::BasicBlock>> =
+        mem::take(&mut function.basic_blocks).into_iter().map(Some).collect();
+    let indices: BTreeMap<Word, usize> = slots.iter()
+        .enumerate()
+        .filter_map(|(i, b)| Some((b.as_ref().and_then(label)?, i)))
+        .collect();
+
+    let mut removed = vec![];
+    // The values replacing the removed phis, and the blocks merged into
+    // others, with those others.
+    let mut values: BTreeMap<Word, Word> = BTreeMap::new();
+    let mut parents: BTreeMap<Word, Word> = BTreeMap::new();
+    for index in 0..slots.len() {
+        while let Some(ref block) = slots[index] {
+            let n = block.instructions.len();
+            let loop_header = n >= 2 &&
+                              block.instructions[n - 2].class.opcode == spirv::Op::LoopMerge;
+            let target = match block.instructions.last() {
+                Some(inst) if inst.class.opcode == spirv::Op::Branch && !loop_header => {
+                    inst.operands.first().and_then(mr::Operand::id)
+                }
+                _ => None,
+            };
+            let next = match target.and_then(|t| indices.get(&t).map(|&i| (t, i))) {
+                Some((target, next)) if next != 0 && next != index &&
+                                        references.get(&target) == Some(&1) => next,
+                _ => break,
+            };
+            let absorbed = match slots[next].take() {
+                Some(absorbed) => absorbed,
+                None => break,
+            };
+            let into = label(slots[index].as_ref().unwrap()).unwrap_or(0);
+            let block = slots[index].as_mut().unwrap();
+            block.instructions.pop();
+            for inst in absorbed.instructions {
+                if inst.class.opcode == spirv::Op::Phi {
+                    if let (Some(id), Some(value)) =
+                           (inst.result_id, inst.operands.first().and_then(mr::Operand::id)) {
+                        values.insert(id, value);
+                    }
+                    removed.push(inst);
+                } else {
+                    block.instructions.push(inst);
+                }
+            }
+            if let Some(merged) = absorbed.label.as_ref().and_then(|l| l.result_id) {
+                parents.insert(merged, into);
+            }
+            removed.extend(absorbed.label);
+        }
+    }
+
+    function.basic_blocks = slots.into_iter().flatten().collect();
+    let resolve = |map: &BTreeMap<Word, Word>, mut id: Word| {
+        while let Some(&next) = map.get(&id) {
+            id = next;
+        }
+        id
+    };
+    for inst in function.basic_blocks.iter_mut().flat_map(|b| &mut b.instructions) {
+        let is_phi = inst.class.opcode == spirv::Op::Phi;
+        for (i, id) in inst.operands.iter_mut().filter_map(mr::Operand::id_mut).enumerate() {
+            *id = if is_phi && i % 2 == 1 {
+                resolve(&parents, *id)
+            } else {
+                resolve(&values, *id)
+            };
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::merge_blocks;
+
+    #[test]
+    fn test_merge_blocks() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let boolean = b.type_bool();
+        let float = b.type_float(32);
+        let fty = b.type_function(void, vec![boolean, float]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let c = b.function_parameter(boolean).unwrap();
+        let x = b.function_parameter(float).unwrap();
+        let (middle, header, then, merge) = (b.id(), b.id(), b.id(), b.id());
+        let entry = b.begin_basic_block(None).unwrap();
+        b.branch(middle).unwrap();
+        b.begin_basic_block(Some(middle)).unwrap();
+        let y = b.phi(float, None, vec![(x, entry)]).unwrap();
+        b.name(y, "y");
+        b.branch(header).unwrap();
+        // The header of a selection, merged too.
+        b.begin_basic_block(Some(header)).unwrap();
+        let z = b.fadd(float, None, y, x).unwrap();
+        b.selection_merge(merge, spirv::SelectionControl::NONE).unwrap();
+        b.branch_conditional(c, then, merge, vec![]).unwrap();
+        b.begin_basic_block(Some(then)).unwrap();
+        b.branch(merge).unwrap();
+        // A merge block, kept.
+        b.begin_basic_block(Some(merge)).unwrap();
+        b.phi(float, None, vec![(z, header), (x, then)]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        assert_eq!(merge_blocks(&mut m), 2);
+        assert!(m.debugs.is_empty());
+        let blocks = &m.functions[0].basic_blocks;
+        assert_eq!(blocks.len(), 3);
+        let opcodes: Vec<spirv::Op> =
+            blocks[0].instructions.iter().map(|inst| inst.class.opcode).collect();
+        assert_eq!(opcodes,
+                   vec![spirv::Op::FAdd, spirv::Op::SelectionMerge, spirv::Op::BranchConditional]);
+        assert_eq!(blocks[0].instructions[0].operands,
+                   vec![mr::Operand::IdRef(x), mr::Operand::IdRef(x)]);
+        assert_eq!(blocks[2].instructions[0].operands,
+                   vec![mr::Operand::IdRef(z),
+                        mr::Operand::IdRef(entry),
+                        mr::Operand::IdRef(x),
+                        mr::Operand::IdRef(then)]);
+    }
+}
@@ -0,0 +1,355 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use analysis::Dominators;
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+use std::mem;
+
+/// Folds the `OpBranchConditional`s of `module` whose conditions are
+/// constant into `OpBranch`es to the taken targets, and removes the blocks
+/// no longer reachable.
+///
+/// Conditions are constant if they are `OpConstantTrue` or
+/// `OpConstantFalse`, or `OpSpecConstantTrue` or `OpSpecConstantFalse`
+/// whose `SpecId` is given a value in `specializations`: a nonzero value
+/// means true. Spec constants without a value may still be specialized
+/// later, so their branches are kept.
+///
+/// The `OpSelectionMerge` of a folded branch is removed. Unreachable
+/// blocks are removed, except those still named as the merge block or
+/// continue target of a reachable header: their instructions are replaced
+/// by `OpUnreachable`, or by a branch back to the loop header for continue
+/// targets. `OpPhi`s drop the values from removed parents, and take an
+/// `OpUndef` from parents without a value left. Names and decorations of
+/// the removed results are removed too, and their remaining uses, e.g., by
+/// other removed blocks kept as stubs, take an `OpUndef`.
+pub fn eliminate_dead_branches(module: &mut mr::Module, specializations: &BTreeMap<u32, u32>) {
+    module.record_edits(|module| {
+        let spec_ids: BTreeMap<Word, u32> = module.annotations
+            .iter()
+            .filter(|inst| {
+                inst.class.opcode == spirv::Op::Decorate &&
+                inst.operands.get(1) == Some(&mr::Operand::Decoration(spirv::Decoration::SpecId))
+            })
+            .filter_map(|inst| match (inst.operands.first(), inst.operands.get(2)) {
+                (Some(&mr::Operand::IdRef(target)), Some(&mr::Operand::LiteralInt32(spec_id))) => {
+                    Some((target, spec_id))
+                }
+                _ => None,
+            })
+            .collect();
+        let mut conditions = BTreeMap::new();
+        for inst in &module.types_global_values {
+            let id = match inst.result_id {
+                Some(id) => id,
+                None => continue,
+            };
+            let value = match inst.class.opcode {
+                spirv::Op::ConstantTrue => Some(true),
+                spirv::Op::ConstantFalse => Some(false),
+                spirv::Op::SpecConstantTrue | spirv::Op::SpecConstantFalse => {
+                    spec_ids.get(&id).and_then(|s| specializations.get(s)).map(|&v| v != 0)
+                }
+                _ => None,
+            };
+            if let Some(value) = value {
+                conditions.insert(id, value);
+            }
+        }
+
+        let mut ids = mr::IdAllocator::for_module(module);
+        // OpUndefs added for phis, by type.
+        let mut undefs = BTreeMap::new();
+        let mut removed = vec![];
+        for f in &mut module.functions {
+            if fold_branches(f, &conditions) {
+                let undef = |ty: Word| {
+                    *undefs.entry(ty).or_insert_with(|| ids.id())
+                };
+                removed.extend(remove_unreachable(f, undef));
+            }
+        }
+
+        for (ty, id) in undefs {
+            module.types_global_values
+                .push(mr::Instruction::new(spirv::Op::Undef, Some(ty), Some(id), vec![]));
+        }
+        module.replace_removed_uses(&mut ids, &removed);
+        ids.update_header(module);
+    })
+}
+
+/// Replaces the branches of `function` on conditions with known values by
+/// branches to the taken targets, and returns true if any was replaced.
+fn fold_branches(function: &mut mr::Function, conditions: &BTreeMap<Word, bool>) -> bool {
+    let mut changed = false;
+    for block in &mut function.basic_blocks {
+        let taken = match block.instructions.last() {
+            Some(inst) if inst.class.opcode == spirv::Op::BranchConditional => {
+                let condition = inst.operands.first().and_then(mr::Operand::id);
+                match condition.and_then(|c| conditions.get(&c)) {
+                    Some(&true) => inst.operands.get(1).and_then(mr::Operand::id),
+                    Some(&false) => inst.operands.get(2).and_then(mr::Operand::id),
+                    None => None,
+                }
+            }
+            _ => None,
+        };
+        let taken = match taken {
+            Some(taken) => taken,
+            None => continue,
+        };
+        block.instructions.pop();
+        let merge = block.instructions.last().map(|inst| inst.class.opcode);
+        if merge == Some(spirv::Op::SelectionMerge) {
+            block.instructions.pop();
+        }
+        block.instructions.push(mr::Instruction::new(spirv::Op::Branch,
+                                                     None,
+                                                     None,
+                                                     vec![mr::Operand::IdRef(taken)]));
+        changed = true;
+    }
+    changed
+}
+
+/// Removes the unreachable blocks of `function`, keeping the merge blocks
+/// and continue targets of reachable headers as stubs, and repairs the
+/// `OpPhi`s of the remaining blocks, getting undefined values of a type
+/// from `undef`. Returns the removed instructions.
+fn remove_unreachable<F>(function: &mut mr::Function, mut undef: F) -> Vec<mr::Instruction>
+    where F: FnMut(Word) -> Word
+{
+    let dominators = Dominators::new(function);
+    let label_of = |block: &mr::BasicBlock| block.label.as_ref().and_then(|l| l.result_id);
+
+    // Continue targets, with their headers, and merge blocks of reachable
+    // headers.
+    let mut continues = BTreeMap::new();
+    let mut merges = BTreeSet::new();
+    for block in &function.basic_blocks {
+        match label_of(block) {
+            Some(label) if dominators.is_reachable(label) => {
+                for inst in &block.instructions {
+                    let target = |index: usize| inst.operands.get(index).and_then(mr::Operand::id);
+                    match inst.class.opcode {
+                        spirv::Op::LoopMerge => {
+                            merges.extend(target(0));
+                            if let Some(target) = target(1) {
+                                continues.insert(target, label);
+                            }
+                        }
+                        spirv::Op::SelectionMerge => merges.extend(target(0)),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut removed = vec![];
+    let mut removed_ids = BTreeSet::new();
+    let mut stubs = BTreeSet::new();
+    let blocks = mem::take(&mut function.basic_blocks);
+    for mut block in blocks {
+        let label = match label_of(&block) {
+            Some(label) if !dominators.is_reachable(label) => label,
+            _ => {
+                function.basic_blocks.push(block);
+                continue;
+            }
+        };
+        removed_ids.extend(block.instructions.iter().filter_map(|inst| inst.result_id));
+        let stub = if let Some(&header) = continues.get(&label) {
+            mr::Instruction::new(spirv::Op::Branch, None, None, vec![mr::Operand::IdRef(header)])
+        } else if merges.contains(&label) {
+            mr::Instruction::new(spirv::Op::Unreachable, None, None, vec![])
+        } else {
+            removed_ids.insert(label);
+            removed.extend(block.label);
+            removed.append(&mut block.instructions);
+            continue;
+        };
+        removed.extend(mem::replace(&mut block.instructions, vec![stub]));
+        stubs.insert(label);
+        function.basic_blocks.push(block);
+    }
+
+    let dominators = Dominators::new(function);
+    for block in &mut function.basic_blocks {
+        let label = match label_of(block) {
+            Some(label) if !stubs.contains(&label) => label,
+            _ => continue,
+        };
+        let preds = dominators.predecessors(label);
+        for inst in &mut block.instructions {
+            if inst.class.opcode != spirv::Op::Phi {
+                break;
+            }
+            let pairs = inst.operands.split_off(0);
+            let mut parents = BTreeSet::new();
+            for pair in pairs.chunks(2) {
+                let (value, parent) = match (pair[0].id(), pair.get(1).and_then(mr::Operand::id)) {
+                    (Some(value), Some(parent)) => (value, parent),
+                    _ => continue,
+                };
+                if !preds.contains(&parent) || !parents.insert(parent) {
+                    continue;
+                }
+                let value = match inst.result_type {
+                    Some(ty) if removed_ids.contains(&value) => undef(ty),
+                    _ => value,
+                };
+                inst.operands.push(mr::Operand::IdRef(value));
+                inst.operands.push(mr::Operand::IdRef(parent));
+            }
+            if let Some(ty) = inst.result_type {
+                for &pred in preds.iter().filter(|p| !parents.contains(p)) {
+                    inst.operands.push(mr::Operand::IdRef(undef(ty)));
+                    inst.operands.push(mr::Operand::IdRef(pred));
+                }
+            }
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use std::collections::BTreeMap;
+
+    use super::eliminate_dead_branches;
+    use utils::test::opcodes;
+
+    fn label(block: &mr::BasicBlock) -> spirv::Word {
+        block.label.as_ref().unwrap().result_id.unwrap()
+    }
+
+    /// Builds a selection on a spec constant with SpecId 3, defaulting to
+    /// true, with a value merged by an OpPhi.
+    fn build() -> (mr::Module, Vec<spirv::Word>) {
+        let mut b = mr::Builder::new();
+        let uint = b.type_int(32, 0);
+        let bool_type = b.type_bool();
+        let cond = b.spec_constant_true(bool_type);
+        b.decorate(cond, spirv::Decoration::SpecId, vec![mr::Operand::LiteralInt32(3)]);
+        let one = b.constant_u32(uint, 1);
+        let fty = b.type_function(uint, vec![uint]);
+        b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let param = b.function_parameter(uint).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let (left, right, merge) = (b.id(), b.id(), b.id());
+        b.selection_merge(merge, spirv::SelectionControl::NONE).unwrap();
+        b.branch_conditional(cond, left, right, vec![]).unwrap();
+        b.begin_basic_block(Some(left)).unwrap();
+        let l = b.iadd(uint, None, param, one).unwrap();
+        b.branch(merge).unwrap();
+        b.begin_basic_block(Some(right)).unwrap();
+        let r = b.iadd(uint, None, param, param).unwrap();
+        b.name(r, "r");
+        b.branch(merge).unwrap();
+        b.begin_basic_block(Some(merge)).unwrap();
+        let phi = b.phi(uint, None, vec![(l, left), (r, right)]).unwrap();
+        b.ret_value(phi).unwrap();
+        b.end_function().unwrap();
+        (b.module(), vec![left, right, merge, l, r])
+    }
+
+    #[test]
+    fn test_specialized_selection() {
+        let (mut m, ids) = build();
+        let mut specializations = BTreeMap::new();
+        specializations.insert(3, 0);
+        eliminate_dead_branches(&mut m, &specializations);
+
+        let blocks = &m.functions[0].basic_blocks;
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(opcodes(&blocks[0]), vec![spirv::Op::Branch]);
+        assert_eq!(blocks[0].instructions[0].operands, vec![mr::Operand::IdRef(ids[1])]);
+        assert_eq!(label(&blocks[1]), ids[1]);
+        assert_eq!(blocks[2].instructions[0].operands,
+                   vec![mr::Operand::IdRef(ids[4]), mr::Operand::IdRef(ids[1])]);
+        assert_eq!(m.debugs.len(), 1);
+    }
+
+    #[test]
+    fn test_unspecialized_kept() {
+        let (mut m, _) = build();
+        eliminate_dead_branches(&mut m, &BTreeMap::new());
+        assert_eq!(m.functions[0].basic_blocks.len(), 4);
+        assert_eq!(opcodes(&m.functions[0].basic_blocks[0]),
+                   vec![spirv::Op::SelectionMerge, spirv::Op::BranchConditional]);
+    }
+
+    #[test]
+    fn test_dead_loop() {
+        // The loop body is never entered; its continue target and merge
+        // block stay as required by the loop header.
+        let mut b = mr::Builder::new();
+        let uint = b.type_int(32, 0);
+        let bool_type = b.type_bool();
+        let cond = b.constant_false(bool_type);
+        let zero = b.constant_u32(uint, 0);
+        let one = b.constant_u32(uint, 1);
+        let void = b.type_void();
+        let voidfvoid = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        let entry = b.begin_basic_block(None).unwrap();
+        let (header, body, latch, exit) = (b.id(), b.id(), b.id(), b.id());
+        b.branch(header).unwrap();
+        b.begin_basic_block(Some(header)).unwrap();
+        let i = b.id();
+        b.phi(uint, None, vec![(zero, entry), (i, latch)]).unwrap();
+        b.loop_merge(exit, latch, spirv::LoopControl::NONE, vec![]).unwrap();
+        b.branch_conditional(cond, body, exit, vec![]).unwrap();
+        b.begin_basic_block(Some(body)).unwrap();
+        b.branch(latch).unwrap();
+        b.begin_basic_block(Some(latch)).unwrap();
+        b.iadd(uint, Some(i), zero, one).unwrap();
+        b.branch(header).unwrap();
+        b.begin_basic_block(Some(exit)).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+        let bound = m.header.as_ref().unwrap().bound;
+
+        eliminate_dead_branches(&mut m, &BTreeMap::new());
+
+        let blocks = &m.functions[0].basic_blocks;
+        let labels: Vec<spirv::Word> = blocks.iter().map(label).collect();
+        assert_eq!(labels, vec![entry, header, latch, exit]);
+        assert_eq!(opcodes(&blocks[1]),
+                   vec![spirv::Op::Phi, spirv::Op::LoopMerge, spirv::Op::Branch]);
+        assert_eq!(blocks[1].instructions[2].operands, vec![mr::Operand::IdRef(exit)]);
+        assert_eq!(opcodes(&blocks[2]), vec![spirv::Op::Branch]);
+        // The value from the continue target is gone.
+        let undef = m.types_global_values.last().unwrap();
+        assert_eq!(undef.class.opcode, spirv::Op::Undef);
+        assert_eq!(undef.result_id, Some(bound));
+        assert_eq!(m.header.as_ref().unwrap().bound, bound + 1);
+        assert_eq!(blocks[1].instructions[0].operands,
+                   vec![mr::Operand::IdRef(zero),
+                        mr::Operand::IdRef(entry),
+                        mr::Operand::IdRef(bound),
+                        mr::Operand::IdRef(latch)]);
+    }
+}
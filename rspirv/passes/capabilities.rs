@@ -0,0 +1,168 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use env::TargetEnv;
+use grammar;
+use mr;
+use spirv;
+
+use std::collections::BTreeSet;
+use super::{add_capability, add_extension, Error, Result};
+
+/// Declares the capabilities the instructions of `module` require but it
+/// does not declare, as `env` accepts them, together with the extensions
+/// enabling them.
+///
+/// A requirement is met by a declared capability, or one declared
+/// capabilities implicitly declare. Otherwise the first alternative `env`
+/// accepts without extensions is declared, or else the first one enabled
+/// by extensions `env` allows. It is an error for a requirement to have no
+/// alternative available in `env`.
+///
+/// Only capabilities are inferred: extensions instructions require by
+/// themselves are left to the caller, since later SPIR-V versions made many
+/// of them core.
+pub fn infer_capabilities(module: &mut mr::Module, env: TargetEnv) -> Result<()> {
+    module.record_edits(|module| {
+        let mut declared = BTreeSet::new();
+        for inst in &module.capabilities {
+            if let Some(&mr::Operand::Capability(capability)) = inst.operands.first() {
+                declare(&mut declared, capability);
+            }
+        }
+        let mut reqs = mr::Requirements::new();
+        let functions = module.functions.iter().flat_map(|f| {
+            f.def
+                .iter()
+                .chain(&f.parameters)
+                .chain(f.basic_blocks.iter().flat_map(|b| b.label.iter().chain(&b.instructions)))
+        });
+        for inst in module.global_inst_iter().chain(functions) {
+            reqs.add(&mr::Requirements::of(inst));
+        }
+
+        let mut capabilities = vec![];
+        let mut extensions = vec![];
+        for alternatives in reqs.capabilities {
+            if alternatives.iter().any(|c| declared.contains(c)) {
+                continue;
+            }
+            let core = alternatives.iter().find(|&&c| env.allows_capability(c));
+            let (capability, enabling) = match core {
+                Some(&capability) => (capability, vec![]),
+                None => {
+                    alternatives.iter()
+                        .find_map(|&c| extensions_for(c, env).map(|names| (c, names)))
+                        .ok_or(Error::UnavailableCapability(alternatives[0]))?
+                }
+            };
+            declare(&mut declared, capability);
+            capabilities.push(capability);
+            extensions.extend(enabling);
+        }
+        for capability in capabilities {
+            add_capability(module, capability);
+        }
+        for name in extensions {
+            add_extension(module, name);
+        }
+        Ok(())
+    })
+}
+
+/// Adds `capability` and those it implicitly declares to `declared`.
+fn declare(declared: &mut BTreeSet<spirv::Capability>, capability: spirv::Capability) {
+    if declared.insert(capability) {
+        for &implied in grammar::reflect::implied_capabilities(capability) {
+            declare(declared, implied);
+        }
+    }
+}
+
+/// Returns the extensions `env` allows that enable `capability`, or
+/// `None` if the capability is not enabled by extensions, or not by ones
+/// `env` allows.
+fn extensions_for(capability: spirv::Capability, env: TargetEnv) -> Option<Vec<&'static str>> {
+    let reqs = mr::Operand::Capability(capability).required_extensions();
+    if reqs.is_empty() {
+        return None;
+    }
+    reqs.iter()
+        .map(|names| names.iter().cloned().find(|name| env.allows_extension(name)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use env::TargetEnv;
+    use mr;
+    use spirv;
+
+    use super::infer_capabilities;
+    use passes::Error;
+
+    fn capabilities(m: &mr::Module) -> Vec<mr::Operand> {
+        m.capabilities.iter().map(|inst| inst.operands[0].clone()).collect()
+    }
+
+    #[test]
+    fn test_infer_capabilities() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::Vulkan);
+        // Matrix comes with Shader.
+        let float = b.type_float(32);
+        let vec2 = b.type_vector(float, 2);
+        b.type_matrix(vec2, 2);
+        let void = b.type_void();
+        let voidfvoid = b.type_function(void, vec![]);
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::Geometry, f, "main", vec![]);
+        let mut m = b.module();
+
+        infer_capabilities(&mut m, TargetEnv::Vulkan1_0).unwrap();
+        assert_eq!(capabilities(&m),
+                   vec![mr::Operand::Capability(spirv::Capability::Shader),
+                        mr::Operand::Capability(spirv::Capability::VulkanMemoryModel),
+                        mr::Operand::Capability(spirv::Capability::Geometry)]);
+        // Vulkan 1.0 only has the Vulkan memory model through its extension.
+        assert_eq!(m.extensions.len(), 1);
+        assert_eq!(m.extensions[0].operands,
+                   vec![mr::Operand::from("SPV_KHR_vulkan_memory_model")]);
+
+        m.capabilities.truncate(1);
+        m.extensions.clear();
+        infer_capabilities(&mut m, TargetEnv::Vulkan1_2).unwrap();
+        assert_eq!(m.capabilities.len(), 3);
+        assert!(m.extensions.is_empty());
+    }
+
+    #[test]
+    fn test_unavailable_capability() {
+        let mut b = mr::Builder::new();
+        b.memory_model(spirv::AddressingModel::Physical64, spirv::MemoryModel::OpenCL);
+        let mut m = b.module();
+        assert_eq!(infer_capabilities(&mut m, TargetEnv::Vulkan1_2),
+                   Err(Error::UnavailableCapability(spirv::Capability::Addresses)));
+        assert!(m.capabilities.is_empty());
+
+        infer_capabilities(&mut m, TargetEnv::OpenCL2_0).unwrap();
+        assert_eq!(capabilities(&m),
+                   vec![mr::Operand::Capability(spirv::Capability::Addresses),
+                        mr::Operand::Capability(spirv::Capability::Kernel)]);
+    }
+}
@@ -0,0 +1,1087 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grammar;
+use mr;
+use spirv;
+
+use mr::pattern;
+use spirv::Word;
+use std::collections::BTreeMap;
+use std::mem;
+
+use super::FloatControls;
+
+/// The opcode of `fma` in the `OpenCL.std` extended instruction set.
+const OPENCL_FMA: u32 = 26;
+
+/// The maximum number of runs of
+/// [`Combiner::run_to_fixpoint`](struct.Combiner.html#method.run_to_fixpoint).
+const MAX_RUNS: usize = 16;
+
+/// A rewrite of an instruction, as returned by a [`Rule`](struct.Rule.html).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Rewrite {
+    /// Replaces all uses of the result with the given value, and removes
+    /// the instruction.
+    Value(Word),
+    /// Replaces the instruction with one of the given opcode and operands,
+    /// keeping its result type and id.
+    Instruction(spirv::Op, Vec<mr::Operand>),
+}
+
+/// A rule of the [`Combiner`](struct.Combiner.html).
+#[derive(Clone, Copy)]
+pub struct Rule {
+    /// The name of the rule.
+    pub name: &'static str,
+    /// Returns the rewrite of the given instruction, if the rule applies.
+    ///
+    /// The combiner answers queries about the module being combined, and
+    /// makes the constants and instructions the rewrite needs.
+    pub apply: fn(&mut Combiner, &mr::Instruction) -> Option<Rewrite>,
+}
+
+/// The rules of [`combine_instructions`](fn.combine_instructions.html), in
+/// the order they are tried.
+pub const RULES: &[Rule] = &[
+    Rule { name: "add-zero", apply: add_zero },
+    Rule { name: "sub-self", apply: sub_self },
+    Rule { name: "mul-one", apply: mul_one },
+    Rule { name: "mul-zero", apply: mul_zero },
+    Rule { name: "mul-pow2", apply: mul_pow2 },
+    Rule { name: "udiv-pow2", apply: udiv_pow2 },
+    Rule { name: "umod-pow2", apply: umod_pow2 },
+    Rule { name: "fold-float", apply: fold_float },
+    Rule { name: "fma", apply: fma },
+];
+
+/// A table-driven instruction combiner.
+///
+/// Each instruction of each function is rewritten by the first rule that
+/// applies to it, and rewritten instructions are tried again, until no
+/// rule applies or they are replaced by a value. Operands replaced by
+/// earlier rewrites are substituted before trying the rules.
+///
+/// Rules see the module as it was before combining, except for the
+/// instructions already rewritten or emitted, and the uses of ids, which
+/// are kept up to date as instructions are rewritten. The combiner
+/// implements [`Definitions`](../mr/pattern/trait.Definitions.html), so that
+/// rules may match operands with [`pattern::def`](../mr/pattern/fn.def.html).
+///
+/// [`run_to_fixpoint`](#method.run_to_fixpoint) runs the combiner again
+/// until no rule applies, which also combines the instructions rules emit.
+pub struct Combiner {
+    rules: Vec<Rule>,
+    /// Width and signedness of integer types.
+    ints: BTreeMap<Word, (u32, bool)>,
+    /// Widths of floating-point types.
+    floats: BTreeMap<Word, u32>,
+    /// Component types and counts of vector types.
+    vectors: BTreeMap<Word, (Word, u32)>,
+    /// Types of values.
+    types: BTreeMap<Word, Word>,
+    /// Constants and instructions in functions, by result id.
+    defs: BTreeMap<Word, mr::Instruction>,
+    /// Number of uses of ids by instructions in functions.
+    uses: BTreeMap<Word, usize>,
+    /// Decorations of ids.
+    decorations: BTreeMap<Word, Vec<(spirv::Decoration, Vec<mr::Operand>)>>,
+    float_controls: FloatControls,
+    kernel: bool,
+    imports: Vec<(grammar::ExtInstSet, Word)>,
+    ids: mr::IdAllocator,
+    /// Constants made by rules, by type and bits of the value.
+    made: BTreeMap<(Word, u64), Word>,
+    added: Vec<mr::Instruction>,
+    /// Instructions emitted by the rule being tried.
+    emitted: Vec<mr::Instruction>,
+}
+
+impl Default for Combiner {
+    fn default() -> Combiner {
+        Combiner::new()
+    }
+}
+
+impl Combiner {
+    /// Creates a combiner with the default [`RULES`](constant.RULES.html).
+    pub fn new() -> Combiner {
+        Combiner::with_rules(RULES.to_vec())
+    }
+
+    /// Creates a combiner trying the given `rules` in order.
+    pub fn with_rules(rules: Vec<Rule>) -> Combiner {
+        Combiner {
+            rules,
+            ints: BTreeMap::new(),
+            floats: BTreeMap::new(),
+            vectors: BTreeMap::new(),
+            types: BTreeMap::new(),
+            defs: BTreeMap::new(),
+            uses: BTreeMap::new(),
+            decorations: BTreeMap::new(),
+            float_controls: FloatControls::default(),
+            kernel: false,
+            imports: vec![],
+            ids: mr::IdAllocator::new(),
+            made: BTreeMap::new(),
+            added: vec![],
+            emitted: vec![],
+        }
+    }
+
+    /// Appends `rule`, to be tried after the existing rules.
+    pub fn add_rule(&mut self, rule: Rule) {
+        self.rules.push(rule);
+    }
+
+    /// Returns the rules of this combiner, in the order they are tried.
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Combines the instructions of the functions of `module`, and returns
+    /// the number of rewrites.
+    pub fn run(&mut self, module: &mut mr::Module) -> usize {
+        self.scan(module);
+        let mut replaced: BTreeMap<Word, Word> = BTreeMap::new();
+        let mut rewrites = 0;
+        // The functions rewritten, by their ids.
+        let mut touched = vec![];
+        for f in &mut module.functions {
+            let mut edited = false;
+            for block in &mut f.basic_blocks {
+                let mut index = 0;
+                while index < block.instructions.len() {
+                    let inst = &mut block.instructions[index];
+                    for id in inst.operands.iter_mut().filter_map(mr::Operand::id_mut) {
+                        if let Some(&value) = replaced.get(id) {
+                            *id = value;
+                            edited = true;
+                        }
+                    }
+                    for _ in 0..self.rules.len() + 1 {
+                        let inst = &block.instructions[index];
+                        let mut rewrite = None;
+                        for i in 0..self.rules.len() {
+                            let rule = self.rules[i];
+                            rewrite = (rule.apply)(self, inst);
+                            if rewrite.is_some() {
+                                break;
+                            }
+                            self.discard_emitted();
+                        }
+                        // Decorations of the result may not apply to the value.
+                        let id = inst.result_id.filter(|id| !self.decorations.contains_key(id));
+                        if id.is_none() && matches!(rewrite, Some(Rewrite::Value(_))) {
+                            rewrite = None;
+                            self.discard_emitted();
+                        }
+                        let emitted = mem::take(&mut self.emitted);
+                        let n = emitted.len();
+                        block.instructions.splice(index..index, emitted);
+                        index += n;
+                        edited |= n > 0;
+                        let inst = &mut block.instructions[index];
+                        match (rewrite, id) {
+                            (Some(Rewrite::Value(value)), Some(id)) => {
+                                let value = replaced.get(&value).cloned().unwrap_or(value);
+                                self.count_uses(&inst.operands, false);
+                                let uses = self.uses.remove(&id).unwrap_or(0);
+                                *self.uses.entry(value).or_default() += uses;
+                                replaced.insert(id, value);
+                                rewrites += 1;
+                                break;
+                            }
+                            (Some(Rewrite::Instruction(opcode, operands)), _) => {
+                                self.count_uses(&inst.operands, false);
+                                self.count_uses(&operands, true);
+                                *inst = mr::Instruction::new(opcode,
+                                                             inst.result_type,
+                                                             inst.result_id,
+                                                             operands);
+                                if let Some(id) = inst.result_id {
+                                    self.defs.insert(id, copy(inst));
+                                }
+                                rewrites += 1;
+                                edited = true;
+                            }
+                            _ => break,
+                        }
+                    }
+                    index += 1;
+                }
+            }
+            if edited {
+                touched.extend(f.def.as_ref().and_then(|def| def.result_id));
+            }
+        }
+
+        for f in &mut module.functions {
+            let mut edited = false;
+            for block in &mut f.basic_blocks {
+                let len = block.instructions.len();
+                block.instructions
+                    .retain(|inst| !inst.result_id.is_some_and(|id| replaced.contains_key(&id)));
+                edited |= block.instructions.len() != len;
+            }
+            if edited {
+                touched.extend(f.def.as_ref().and_then(|def| def.result_id));
+            }
+        }
+        for id in touched {
+            module.touch(mr::Section::Function(id));
+        }
+        if !self.added.is_empty() {
+            module.touch(mr::Section::TypesGlobalValues);
+        }
+        module.types_global_values.append(&mut self.added);
+        self.ids.update_header(module);
+        for (id, value) in replaced {
+            module.replace_all_uses(id, value);
+        }
+        rewrites
+    }
+
+    /// Runs the combiner on `module` until it rewrites nothing, or at most
+    /// 16 times, and returns the number of rewrites.
+    ///
+    /// Unlike [`run`](#method.run), this combines instructions emitted by
+    /// rules, and instructions whose operands are rewritten after them.
+    pub fn run_to_fixpoint(&mut self, module: &mut mr::Module) -> usize {
+        let mut rewrites = 0;
+        for _ in 0..MAX_RUNS {
+            match self.run(module) {
+                0 => break,
+                n => rewrites += n,
+            }
+        }
+        rewrites
+    }
+
+    /// Adds or removes the uses of ids by `operands`.
+    fn count_uses(&mut self, operands: &[mr::Operand], add: bool) {
+        for id in operands.iter().filter_map(mr::Operand::id) {
+            let uses = self.uses.entry(id).or_default();
+            if add {
+                *uses += 1;
+            } else {
+                *uses = uses.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Forgets the instructions emitted by a rule that does not apply.
+    fn discard_emitted(&mut self) {
+        for inst in mem::take(&mut self.emitted) {
+            self.count_uses(&inst.operands, false);
+            if let Some(id) = inst.result_id {
+                self.defs.remove(&id);
+                self.types.remove(&id);
+            }
+        }
+    }
+
+    /// Collects what rules may query about `module`.
+    fn scan(&mut self, module: &mr::Module) {
+        self.ints.clear();
+        self.floats.clear();
+        self.vectors.clear();
+        self.types.clear();
+        self.defs.clear();
+        self.uses.clear();
+        self.decorations.clear();
+        self.made.clear();
+        self.ids = mr::IdAllocator::for_module(module);
+        for inst in &module.types_global_values {
+            let id = match inst.result_id {
+                Some(id) => id,
+                None => continue,
+            };
+            let literal = |index: usize| match inst.operands.get(index) {
+                Some(&mr::Operand::LiteralInt32(v)) => Some(v),
+                _ => None,
+            };
+            match inst.class.opcode {
+                spirv::Op::TypeInt => {
+                    if let (Some(width), Some(signed)) = (literal(0), literal(1)) {
+                        self.ints.insert(id, (width, signed != 0));
+                    }
+                }
+                spirv::Op::TypeFloat => {
+                    if let Some(width) = literal(0) {
+                        self.floats.insert(id, width);
+                    }
+                }
+                spirv::Op::TypeVector => {
+                    let component = inst.operands.first().and_then(mr::Operand::id);
+                    if let (Some(component), Some(n)) = (component, literal(1)) {
+                        self.vectors.insert(id, (component, n));
+                    }
+                }
+                spirv::Op::Constant | spirv::Op::ConstantComposite => {
+                    self.defs.insert(id, copy(inst));
+                }
+                _ => {}
+            }
+            if let Some(ty) = inst.result_type {
+                self.types.insert(id, ty);
+            }
+        }
+        for f in &module.functions {
+            let insts = f.parameters.iter().chain(f.basic_blocks.iter().flat_map(|b| &b.instructions));
+            for inst in insts {
+                if let (Some(id), Some(ty)) = (inst.result_id, inst.result_type) {
+                    self.types.insert(id, ty);
+                    self.defs.insert(id, copy(inst));
+                }
+                for (id, _) in inst.id_operands() {
+                    *self.uses.entry(id).or_default() += 1;
+                }
+            }
+        }
+        for inst in &module.annotations {
+            if inst.class.opcode != spirv::Op::Decorate {
+                continue;
+            }
+            if let (Some(&mr::Operand::IdRef(target)), Some(&mr::Operand::Decoration(decoration))) =
+                (inst.operands.first(), inst.operands.get(1)) {
+                self.decorations
+                    .entry(target)
+                    .or_default()
+                    .push((decoration, inst.operands[2..].to_vec()));
+            }
+        }
+        self.float_controls = FloatControls::new(module);
+        self.kernel = module.capabilities
+            .iter()
+            .any(|inst| {
+                inst.operands.first() == Some(&mr::Operand::Capability(spirv::Capability::Kernel))
+            });
+        self.imports = module.ext_inst_imports
+            .iter()
+            .filter_map(|inst| match (inst.result_id, inst.operands.first()) {
+                (Some(id), Some(mr::Operand::LiteralString(name))) => {
+                    grammar::ExtInstSet::from_name(name).map(|set| (set, id))
+                }
+                _ => None,
+            })
+            .collect();
+    }
+
+    /// Returns the type of the value `id`.
+    pub fn type_of(&self, id: Word) -> Option<Word> {
+        self.types.get(&id).cloned()
+    }
+
+    /// Returns the component type of the vector type `ty`, or `ty` itself
+    /// if it is not a vector type.
+    pub fn scalar_type(&self, ty: Word) -> Word {
+        self.vectors.get(&ty).map_or(ty, |&(component, _)| component)
+    }
+
+    /// Returns the width and signedness of the integer type, or vector of
+    /// integers, `ty`.
+    pub fn int_type(&self, ty: Word) -> Option<(u32, bool)> {
+        self.ints.get(&self.scalar_type(ty)).cloned()
+    }
+
+    /// Returns the width of the floating-point type, or vector of
+    /// floating-point values, `ty`.
+    pub fn float_type(&self, ty: Word) -> Option<u32> {
+        self.floats.get(&self.scalar_type(ty)).cloned()
+    }
+
+    /// Returns the opcode and operands of the instruction defining `id`,
+    /// for constants and values defined in functions.
+    pub fn definition(&self, id: Word) -> Option<(spirv::Op, &[mr::Operand])> {
+        self.defs.get(&id).map(|inst| (inst.class.opcode, &inst.operands[..]))
+    }
+
+    /// Returns the number of uses of `id` by instructions in functions, as
+    /// of the instructions combined so far.
+    pub fn uses(&self, id: Word) -> usize {
+        self.uses.get(&id).cloned().unwrap_or(0)
+    }
+
+    /// Returns the literal of the scalar constant `id`, or of the
+    /// constituents of the composite constant `id` if they are all the
+    /// same scalar constant.
+    fn literal(&self, id: Word) -> Option<&mr::Operand> {
+        match self.definition(id) {
+            Some((spirv::Op::Constant, operands)) => operands.first(),
+            Some((spirv::Op::ConstantComposite, operands)) => {
+                let first = operands.first().and_then(mr::Operand::id)?;
+                if operands.iter().all(|c| c.id() == Some(first)) {
+                    match self.definition(first) {
+                        Some((spirv::Op::Constant, operands)) => operands.first(),
+                        _ => None,
+                    }
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the integer constant `id`, or of all
+    /// components of the vector constant `id`.
+    pub fn int_constant(&self, id: Word) -> Option<u64> {
+        self.int_type(self.type_of(id)?)?;
+        match self.literal(id) {
+            Some(&mr::Operand::LiteralInt32(v)) => Some(u64::from(v)),
+            Some(&mr::Operand::LiteralInt64(v)) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the floating-point constant `id`, or of all
+    /// components of the vector constant `id`.
+    pub fn float_constant(&self, id: Word) -> Option<f64> {
+        match self.literal(id) {
+            Some(&mr::Operand::LiteralFloat32(v)) => Some(f64::from(v)),
+            Some(&mr::Operand::LiteralFloat64(v)) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the decoration applies to `id`.
+    pub fn is_decorated(&self, id: Word, decoration: spirv::Decoration) -> bool {
+        self.decorations.get(&id).is_some_and(|ds| ds.iter().any(|&(d, _)| d == decoration))
+    }
+
+    /// Returns the floating-point semantics of the module being combined,
+    /// which rules must keep.
+    pub fn float_controls(&self) -> &FloatControls {
+        &self.float_controls
+    }
+
+    /// Returns true if the floating-point operation `id` may be contracted
+    /// with others, e.g., into a fused multiply-add.
+    ///
+    /// See [`FloatControls::may_contract`](struct.FloatControls.html#method.may_contract).
+    pub fn may_contract(&self, id: Word) -> bool {
+        self.float_controls.may_contract(id)
+    }
+
+    /// Returns the id the extended instruction set is imported as.
+    pub fn ext_inst_import(&self, set: grammar::ExtInstSet) -> Option<Word> {
+        self.imports.iter().find(|&&(s, _)| s == set).map(|&(_, id)| id)
+    }
+
+    /// Returns an integer constant of type `ty`, or a vector constant with
+    /// all components of that value, making it if the combiner has not
+    /// made one already.
+    ///
+    /// Returns `None` if `ty` is not an integer type or vector thereof.
+    pub fn make_int_constant(&mut self, ty: Word, value: u64) -> Option<Word> {
+        let (width, _) = self.int_type(ty)?;
+        let operand = if width > 32 {
+            mr::Operand::LiteralInt64(value)
+        } else {
+            mr::Operand::LiteralInt32(value as u32)
+        };
+        Some(self.make_constant(ty, value, operand))
+    }
+
+    /// Returns a floating-point constant of type `ty`, or a vector
+    /// constant with all components of that value, making it if the
+    /// combiner has not made one already.
+    ///
+    /// Returns `None` if `ty` is not a 32- or 64-bit floating-point type
+    /// or vector thereof.
+    pub fn make_float_constant(&mut self, ty: Word, value: f64) -> Option<Word> {
+        let (bits, operand) = match self.float_type(ty)? {
+            32 => (u64::from((value as f32).to_bits()), mr::Operand::LiteralFloat32(value as f32)),
+            64 => (value.to_bits(), mr::Operand::LiteralFloat64(value)),
+            _ => return None,
+        };
+        Some(self.make_constant(ty, bits, operand))
+    }
+
+    /// Returns a constant of the scalar type, or vector type, `ty` with
+    /// components of the scalar literal `operand`, whose bits are `bits`.
+    fn make_constant(&mut self, ty: Word, bits: u64, operand: mr::Operand) -> Word {
+        if let Some(&id) = self.made.get(&(ty, bits)) {
+            return id;
+        }
+        let scalar = self.scalar_type(ty);
+        let (opcode, operands) = if scalar == ty {
+            (spirv::Op::Constant, vec![operand])
+        } else {
+            let component = self.make_constant(scalar, bits, operand);
+            let n = self.vectors[&ty].1 as usize;
+            (spirv::Op::ConstantComposite, vec![mr::Operand::IdRef(component); n])
+        };
+        let id = self.ids.id();
+        let inst = mr::Instruction::new(opcode, Some(ty), Some(id), operands);
+        self.defs.insert(id, copy(&inst));
+        self.types.insert(id, ty);
+        self.added.push(inst);
+        self.made.insert((ty, bits), id);
+        id
+    }
+
+    /// Adds an instruction of the given opcode, result type, and operands
+    /// before the instruction being rewritten, and returns its result id.
+    ///
+    /// The instructions a rule emits are kept only if it returns a rewrite.
+    pub fn emit(&mut self,
+                opcode: spirv::Op,
+                result_type: Option<Word>,
+                operands: Vec<mr::Operand>)
+                -> Word {
+        let id = self.ids.id();
+        self.count_uses(&operands, true);
+        let inst = mr::Instruction::new(opcode, result_type, Some(id), operands);
+        self.defs.insert(id, copy(&inst));
+        if let Some(ty) = result_type {
+            self.types.insert(id, ty);
+        }
+        self.emitted.push(inst);
+        id
+    }
+}
+
+impl pattern::Definitions for Combiner {
+    fn definition_of(&self, id: Word) -> Option<&mr::Instruction> {
+        self.defs.get(&id)
+    }
+}
+
+/// Returns a copy of `inst`.
+fn copy(inst: &mr::Instruction) -> mr::Instruction {
+    mr::Instruction::new(inst.class.opcode, inst.result_type, inst.result_id, inst.operands.clone())
+}
+
+/// Combines the instructions of the functions of `module` with the
+/// default [`RULES`](constant.RULES.html), and returns the number of
+/// rewrites.
+///
+/// The rules fold additions and subtractions of zero, multiplications by
+/// one and zero, and integer subtractions of a value from itself; reduce
+/// integer multiplications and unsigned divisions and remainders by
+/// powers of two to shifts and masks; fold floating-point arithmetic on
+/// constants; and form fused multiply-adds from floating-point additions
+/// of products used nowhere else, where contraction is allowed, using the
+/// `Fma` of an imported `GLSL.std.450` or `OpenCL.std`.
+///
+/// Floating-point rules keep the semantics of the module's
+/// [`FloatControls`](struct.FloatControls.html).
+pub fn combine_instructions(module: &mut mr::Module) -> usize {
+    Combiner::new().run(module)
+}
+
+/// Returns the two operand ids of the binary operation `inst`.
+fn binary(inst: &mr::Instruction) -> Option<(Word, Word)> {
+    match (inst.operands.first().and_then(mr::Operand::id),
+           inst.operands.get(1).and_then(mr::Operand::id),
+           inst.operands.len()) {
+        (Some(a), Some(b), 2) => Some((a, b)),
+        _ => None,
+    }
+}
+
+fn add_zero(c: &mut Combiner, inst: &mr::Instruction) -> Option<Rewrite> {
+    let (a, b) = binary(inst)?;
+    let int_zero = |id: Word| c.int_constant(id) == Some(0);
+    let value = match inst.class.opcode {
+        spirv::Op::IAdd if int_zero(a) => b,
+        spirv::Op::IAdd | spirv::Op::ISub if int_zero(b) => a,
+        // Only adding -0.0 leaves all values as they are: -0.0 + 0.0 is
+        // 0.0.
+        spirv::Op::FAdd | spirv::Op::FSub if !keeps_value(c, inst) => return None,
+        spirv::Op::FAdd => {
+            let negative_zero = |id: Word| {
+                c.float_constant(id).is_some_and(|v| v == 0.0 && v.is_sign_negative())
+            };
+            if negative_zero(a) {
+                b
+            } else if negative_zero(b) {
+                a
+            } else {
+                return None;
+            }
+        }
+        spirv::Op::FSub => {
+            match c.float_constant(b) {
+                Some(v) if v == 0.0 && v.is_sign_positive() => a,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    Some(Rewrite::Value(value))
+}
+
+fn sub_self(c: &mut Combiner, inst: &mr::Instruction) -> Option<Rewrite> {
+    let (a, b) = binary(inst)?;
+    if inst.class.opcode != spirv::Op::ISub || a != b {
+        return None;
+    }
+    c.make_int_constant(inst.result_type?, 0).map(Rewrite::Value)
+}
+
+fn mul_one(c: &mut Combiner, inst: &mr::Instruction) -> Option<Rewrite> {
+    let (a, b) = binary(inst)?;
+    let opcode = inst.class.opcode;
+    if opcode != spirv::Op::IMul && opcode != spirv::Op::FMul {
+        return None;
+    }
+    if opcode == spirv::Op::FMul && !keeps_value(c, inst) {
+        return None;
+    }
+    let one = |id: Word| if opcode == spirv::Op::IMul {
+        c.int_constant(id) == Some(1)
+    } else {
+        c.float_constant(id) == Some(1.0)
+    };
+    if one(b) {
+        Some(Rewrite::Value(a))
+    } else if one(a) {
+        Some(Rewrite::Value(b))
+    } else {
+        None
+    }
+}
+
+fn mul_zero(c: &mut Combiner, inst: &mr::Instruction) -> Option<Rewrite> {
+    let (a, b) = binary(inst)?;
+    match inst.class.opcode {
+        spirv::Op::IMul => {
+            [a, b].iter().cloned().find(|&id| c.int_constant(id) == Some(0)).map(Rewrite::Value)
+        }
+        // x * 0.0 is 0.0 only if the sign of zeros, infinities, and NaNs
+        // need not be kept.
+        spirv::Op::FMul => {
+            let (id, ty) = (inst.result_id?, inst.result_type?);
+            let width = c.float_type(ty)?;
+            let controls = c.float_controls();
+            if !controls.may_fold(id) || controls.preserves_signed_zero_inf_nan(id, width) {
+                return None;
+            }
+            [a, b].iter().find(|&&id| c.float_constant(id) == Some(0.0))?;
+            c.make_float_constant(ty, 0.0).map(Rewrite::Value)
+        }
+        _ => None,
+    }
+}
+
+/// Returns true if the floating-point operation `inst` may be replaced by
+/// one of its operands, which it would return unchanged were it not for
+/// flushing denormalized values to zero.
+fn keeps_value(c: &Combiner, inst: &mr::Instruction) -> bool {
+    let width = inst.result_type.and_then(|ty| c.float_type(ty));
+    match (inst.result_id, width) {
+        (Some(id), Some(width)) => {
+            c.float_controls().may_fold(id) && !c.float_controls().flushes_denorms(width)
+        }
+        _ => false,
+    }
+}
+
+/// Returns the exponent of the power of two the integer constant `id` is.
+fn exponent(c: &Combiner, id: Word) -> Option<u64> {
+    let width = c.int_type(c.type_of(id)?)?.0;
+    let value = c.int_constant(id)?;
+    // Negative powers of two of signed types are not.
+    if value.is_power_of_two() && value.trailing_zeros() < width.saturating_sub(1).max(1) {
+        Some(u64::from(value.trailing_zeros()))
+    } else {
+        None
+    }
+}
+
+fn mul_pow2(c: &mut Combiner, inst: &mr::Instruction) -> Option<Rewrite> {
+    let (a, b) = binary(inst)?;
+    if inst.class.opcode != spirv::Op::IMul {
+        return None;
+    }
+    let (value, k) = match (exponent(c, a), exponent(c, b)) {
+        (_, Some(k)) if k > 0 => (a, k),
+        (Some(k), _) if k > 0 => (b, k),
+        _ => return None,
+    };
+    let shift = c.make_int_constant(inst.result_type?, k)?;
+    Some(Rewrite::Instruction(spirv::Op::ShiftLeftLogical,
+                              vec![mr::Operand::IdRef(value), mr::Operand::IdRef(shift)]))
+}
+
+fn udiv_pow2(c: &mut Combiner, inst: &mr::Instruction) -> Option<Rewrite> {
+    let (a, b) = binary(inst)?;
+    if inst.class.opcode != spirv::Op::UDiv {
+        return None;
+    }
+    let k = exponent(c, b)?;
+    if k == 0 {
+        return Some(Rewrite::Value(a));
+    }
+    let shift = c.make_int_constant(inst.result_type?, k)?;
+    Some(Rewrite::Instruction(spirv::Op::ShiftRightLogical,
+                              vec![mr::Operand::IdRef(a), mr::Operand::IdRef(shift)]))
+}
+
+fn umod_pow2(c: &mut Combiner, inst: &mr::Instruction) -> Option<Rewrite> {
+    let (a, b) = binary(inst)?;
+    if inst.class.opcode != spirv::Op::UMod {
+        return None;
+    }
+    let k = exponent(c, b)?;
+    let mask = c.make_int_constant(inst.result_type?, (1 << k) - 1)?;
+    Some(Rewrite::Instruction(spirv::Op::BitwiseAnd,
+                              vec![mr::Operand::IdRef(a), mr::Operand::IdRef(mask)]))
+}
+
+/// Returns `value` rounded to the nearest even floating-point value of
+/// the given width.
+fn round(value: f64, width: u32) -> f64 {
+    if width == 32 {
+        f64::from(value as f32)
+    } else {
+        value
+    }
+}
+
+fn is_denorm(value: f64, width: u32) -> bool {
+    if width == 32 {
+        (value as f32).is_subnormal()
+    } else {
+        value.is_subnormal()
+    }
+}
+
+fn fold_float(c: &mut Combiner, inst: &mr::Instruction) -> Option<Rewrite> {
+    let (a, b) = binary(inst)?;
+    let (id, ty) = (inst.result_id?, inst.result_type?);
+    let op: fn(f64, f64) -> f64 = match inst.class.opcode {
+        spirv::Op::FAdd => |x, y| x + y,
+        spirv::Op::FSub => |x, y| x - y,
+        spirv::Op::FMul => |x, y| x * y,
+        spirv::Op::FDiv => |x, y| x / y,
+        _ => return None,
+    };
+    let width = c.float_type(ty)?;
+    let (x, y) = (c.float_constant(a)?, c.float_constant(b)?);
+    let controls = c.float_controls();
+    // Operations are folded rounding to the nearest even value, which
+    // double rounding of 32-bit operations through 64 bits keeps exact.
+    if width != 32 && width != 64 || !controls.may_fold(id) || controls.rounds_toward_zero(width) {
+        return None;
+    }
+    if inst.class.opcode == spirv::Op::FDiv && y == 0.0 {
+        return None;
+    }
+    let flush = controls.flushes_denorms(width);
+    let denorms = is_denorm(x, width) || is_denorm(y, width) || is_denorm(op(x, y), width);
+    if denorms && flush && controls.preserves_denorms(width) {
+        // Entry points disagree on the result.
+        return None;
+    }
+    let flushed = |v: f64| if flush && is_denorm(v, width) {
+        0f64.copysign(v)
+    } else {
+        v
+    };
+    let value = flushed(round(op(flushed(x), flushed(y)), width));
+    c.make_float_constant(ty, value).map(Rewrite::Value)
+}
+
+fn fma(c: &mut Combiner, inst: &mr::Instruction) -> Option<Rewrite> {
+    let (a, b) = binary(inst)?;
+    let id = inst.result_id?;
+    if inst.class.opcode != spirv::Op::FAdd || !c.may_contract(id) {
+        return None;
+    }
+    let product = |p: Word| match c.definition(p) {
+        Some((spirv::Op::FMul, operands)) if c.uses(p) == 1 && c.may_contract(p) => {
+            let (x, y) = (operands.first().and_then(mr::Operand::id),
+                          operands.get(1).and_then(mr::Operand::id));
+            match (x, y) {
+                (Some(x), Some(y)) => Some((x, y)),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    let ((x, y), addend) = match (product(a), product(b)) {
+        (Some(p), _) => (p, b),
+        (None, Some(p)) => (p, a),
+        _ => return None,
+    };
+    let (set, opcode) = if c.kernel {
+        (grammar::ExtInstSet::OpenCLStd100, OPENCL_FMA)
+    } else {
+        (grammar::ExtInstSet::GlslStd450, spirv::GLOp::Fma as u32)
+    };
+    let import = c.ext_inst_import(set)?;
+    Some(Rewrite::Instruction(spirv::Op::ExtInst,
+                              vec![mr::Operand::IdRef(import),
+                                   mr::Operand::ExtInstOpcode(set, opcode),
+                                   mr::Operand::IdRef(x),
+                                   mr::Operand::IdRef(y),
+                                   mr::Operand::IdRef(addend)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use grammar;
+    use mr;
+    use spirv;
+
+    use mr::pattern::{def, id, match_inst};
+    use super::{combine_instructions, Combiner, Rewrite, Rule, OPENCL_FMA, RULES};
+    use utils::test::opcodes;
+
+    #[test]
+    fn test_integer_rules() {
+        let mut b = mr::Builder::new();
+        let uint = b.type_int(32, 0);
+        let zero = b.constant_u32(uint, 0);
+        let one = b.constant_u32(uint, 1);
+        let eight = b.constant_u32(uint, 8);
+        let fty = b.type_function(uint, vec![uint]);
+        b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let x = b.function_parameter(uint).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let sum = b.iadd(uint, None, zero, x).unwrap();
+        let product = b.imul(uint, None, sum, one).unwrap();
+        let shifted = b.imul(uint, None, eight, product).unwrap();
+        let quotient = b.udiv(uint, None, shifted, eight).unwrap();
+        let rest = b.umod(uint, None, quotient, eight).unwrap();
+        let none = b.isub(uint, None, rest, rest).unwrap();
+        let total = b.iadd(uint, None, rest, none).unwrap();
+        b.ret_value(total).unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+        let bound = m.header.as_ref().unwrap().bound;
+
+        assert_eq!(combine_instructions(&mut m), 7);
+
+        let block = &m.functions[0].basic_blocks[0];
+        assert_eq!(opcodes(block),
+                   vec![spirv::Op::ShiftLeftLogical,
+                        spirv::Op::ShiftRightLogical,
+                        spirv::Op::BitwiseAnd,
+                        spirv::Op::ReturnValue]);
+        // x << 3, shared by the shifts.
+        let three = bound;
+        assert_eq!(block.instructions[0].operands,
+                   vec![mr::Operand::IdRef(x), mr::Operand::IdRef(three)]);
+        assert_eq!(block.instructions[1].operands,
+                   vec![mr::Operand::IdRef(shifted), mr::Operand::IdRef(three)]);
+        assert_eq!(block.instructions[2].operands,
+                   vec![mr::Operand::IdRef(quotient), mr::Operand::IdRef(bound + 1)]);
+        // rest - rest is a new zero, and rest + zero is rest.
+        assert_eq!(block.instructions[3].operands, vec![mr::Operand::IdRef(rest)]);
+        let added: Vec<&[mr::Operand]> = m.types_global_values[5..]
+            .iter()
+            .map(|inst| &inst.operands[..])
+            .collect();
+        assert_eq!(added,
+                   vec![&[mr::Operand::LiteralInt32(3)][..],
+                        &[mr::Operand::LiteralInt32(7)][..],
+                        &[mr::Operand::LiteralInt32(0)][..]]);
+        assert_eq!(m.header.as_ref().unwrap().bound, bound + 3);
+    }
+
+    /// Builds `x * y + z` in a function importing GLSL.std.450, with the
+    /// product decorated by `decoration`.
+    fn build_fma(decoration: Option<spirv::Decoration>) -> mr::Module {
+        let mut b = mr::Builder::new();
+        b.ext_inst_import("GLSL.std.450");
+        let float = b.type_float(32);
+        let fty = b.type_function(float, vec![float, float, float]);
+        b.begin_function(float, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let x = b.function_parameter(float).unwrap();
+        let y = b.function_parameter(float).unwrap();
+        let z = b.function_parameter(float).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let product = b.fmul(float, None, x, y).unwrap();
+        if let Some(decoration) = decoration {
+            b.decorate(product, decoration, vec![]);
+        }
+        let sum = b.fadd(float, None, z, product).unwrap();
+        b.ret_value(sum).unwrap();
+        b.end_function().unwrap();
+        b.module()
+    }
+
+    #[test]
+    fn test_fma() {
+        let mut m = build_fma(None);
+        assert_eq!(combine_instructions(&mut m), 1);
+        let inst = &m.functions[0].basic_blocks[0].instructions[1];
+        assert_eq!(inst.class.opcode, spirv::Op::ExtInst);
+        let fma = spirv::GLOp::Fma as u32;
+        assert_eq!(inst.operands[1],
+                   mr::Operand::ExtInstOpcode(grammar::ExtInstSet::GlslStd450, fma));
+        let params: Vec<mr::Operand> = m.functions[0]
+            .parameters
+            .iter()
+            .map(|p| mr::Operand::IdRef(p.result_id.unwrap()))
+            .collect();
+        assert_eq!(inst.operands[2..], params[..]);
+
+        let mut m = build_fma(Some(spirv::Decoration::NoContraction));
+        assert_eq!(combine_instructions(&mut m), 0);
+
+        assert_eq!(grammar::ExtInstSet::OpenCLStd100.lookup_opcode(OPENCL_FMA).unwrap().opname,
+                   "fma");
+    }
+
+    /// Builds a function returning `x + -0.0`, `x * 0.0`, and
+    /// `2e-38 * 0.25`, for a `float` parameter `x`, in a module with the
+    /// given 32-bit float controls modes.
+    fn build_float(modes: &[spirv::ExecutionMode]) -> mr::Module {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        let float = b.type_float(32);
+        let negative_zero = b.constant_f32(float, -0.0);
+        let zero = b.constant_f32(float, 0.0);
+        let small = b.constant_f32(float, 2e-38);
+        let quarter = b.constant_f32(float, 0.25);
+        let fty = b.type_function(float, vec![float]);
+        let f = b.begin_function(float, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let x = b.function_parameter(float).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let sum = b.fadd(float, None, x, negative_zero).unwrap();
+        let product = b.fmul(float, None, sum, zero).unwrap();
+        let denorm = b.fmul(float, None, small, quarter).unwrap();
+        let total = b.fadd(float, None, product, denorm).unwrap();
+        b.ret_value(total).unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::GLCompute, f, "main", vec![]);
+        for &mode in modes {
+            b.execution_mode(f, mode, vec![32]);
+        }
+        b.module()
+    }
+
+    fn constant(m: &mr::Module, id: spirv::Word) -> &mr::Operand {
+        let inst = m.types_global_values.iter().find(|inst| inst.result_id == Some(id)).unwrap();
+        &inst.operands[0]
+    }
+
+    #[test]
+    fn test_float_controls() {
+        let mut m = build_float(&[]);
+        // x + -0.0 is x, x * 0.0 is 0.0, and the rest is folded.
+        assert_eq!(combine_instructions(&mut m), 4);
+        let block = &m.functions[0].basic_blocks[0];
+        assert_eq!(opcodes(block), vec![spirv::Op::ReturnValue]);
+        let result = block.instructions[0].operands[0].id().unwrap();
+        assert_eq!(*constant(&m, result), mr::Operand::LiteralFloat32(2e-38 * 0.25));
+
+        let mut m = build_float(&[spirv::ExecutionMode::DenormFlushToZero,
+                                  spirv::ExecutionMode::SignedZeroInfNanPreserve]);
+        // Only the denormalized product is folded, and flushed.
+        assert_eq!(combine_instructions(&mut m), 1);
+        let block = &m.functions[0].basic_blocks[0];
+        assert_eq!(opcodes(block),
+                   vec![spirv::Op::FAdd, spirv::Op::FMul, spirv::Op::FAdd, spirv::Op::ReturnValue]);
+        let flushed = block.instructions[2].operands[1].id().unwrap();
+        assert_eq!(*constant(&m, flushed), mr::Operand::LiteralFloat32(0.0));
+
+        let mut m = build_float(&[spirv::ExecutionMode::RoundingModeRTZ,
+                                  spirv::ExecutionMode::DenormPreserve]);
+        // Rounding toward zero leaves the constant product alone.
+        assert_eq!(combine_instructions(&mut m), 2);
+        assert_eq!(opcodes(&m.functions[0].basic_blocks[0]),
+                   vec![spirv::Op::FMul, spirv::Op::FAdd, spirv::Op::ReturnValue]);
+    }
+
+    #[test]
+    fn test_custom_rule() {
+        // Replaces x * x by OpIAdd x x, for the sake of the test.
+        fn square(_: &mut Combiner, inst: &mr::Instruction) -> Option<Rewrite> {
+            if inst.class.opcode == spirv::Op::IMul && inst.operands[0] == inst.operands[1] {
+                Some(Rewrite::Instruction(spirv::Op::IAdd, inst.operands.to_vec()))
+            } else {
+                None
+            }
+        }
+
+        let mut b = mr::Builder::new();
+        let uint = b.type_int(32, 0);
+        let zero = b.constant_u32(uint, 0);
+        let fty = b.type_function(uint, vec![uint]);
+        b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let x = b.function_parameter(uint).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let sum = b.imul(uint, None, x, x).unwrap();
+        let sum = b.iadd(uint, None, sum, zero).unwrap();
+        b.ret_value(sum).unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        let mut combiner = Combiner::with_rules(vec![]);
+        combiner.add_rule(Rule {
+            name: "square",
+            apply: square,
+        });
+        assert_eq!(combiner.rules().len(), 1);
+        assert_eq!(combiner.run(&mut m), 1);
+        let block = &m.functions[0].basic_blocks[0];
+        assert_eq!(opcodes(block), vec![spirv::Op::IAdd, spirv::Op::IAdd, spirv::Op::ReturnValue]);
+    }
+
+    #[test]
+    fn test_run_to_fixpoint() {
+        // Factors a * b + a * c into a * (b + c).
+        fn factor(c: &mut Combiner, inst: &mr::Instruction) -> Option<Rewrite> {
+            let products = (def(&*c, spirv::Op::IMul, (id(), id())),
+                            def(&*c, spirv::Op::IMul, (id(), id())));
+            let ((a, b), (a2, d)) = match_inst(inst, spirv::Op::IAdd, products)?;
+            if a != a2 {
+                return None;
+            }
+            let operands = vec![mr::Operand::IdRef(b), mr::Operand::IdRef(d)];
+            let sum = c.emit(spirv::Op::IAdd, inst.result_type, operands);
+            Some(Rewrite::Instruction(spirv::Op::IMul,
+                                      vec![mr::Operand::IdRef(a), mr::Operand::IdRef(sum)]))
+        }
+
+        let mut b = mr::Builder::new();
+        let uint = b.type_int(32, 0);
+        let zero = b.constant_u32(uint, 0);
+        let fty = b.type_function(uint, vec![uint, uint]);
+        b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let x = b.function_parameter(uint).unwrap();
+        let y = b.function_parameter(uint).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let xy = b.imul(uint, None, x, y).unwrap();
+        let x0 = b.imul(uint, None, x, zero).unwrap();
+        let sum = b.iadd(uint, None, xy, x0).unwrap();
+        b.ret_value(sum).unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        // The emitted y + 0 is only folded by the second run.
+        let mut combiner = Combiner::with_rules(vec![Rule {
+                                                         name: "factor",
+                                                         apply: factor,
+                                                     },
+                                                     RULES[0]]);
+        assert_eq!(combiner.run_to_fixpoint(&mut m), 2);
+        assert_eq!((combiner.uses(xy), combiner.uses(x0), combiner.uses(y)), (0, 0, 2));
+        let block = &m.functions[0].basic_blocks[0];
+        assert_eq!(opcodes(block), vec![spirv::Op::IMul, spirv::Op::IMul, spirv::Op::IMul,
+                                        spirv::Op::ReturnValue]);
+        assert_eq!(block.instructions[2].operands,
+                   vec![mr::Operand::IdRef(x), mr::Operand::IdRef(y)]);
+        assert_eq!(m.header.unwrap().bound, sum + 2);
+    }
+}
@@ -0,0 +1,451 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The component literal of `OpVectorShuffle` for undefined components.
+const UNDEFINED_COMPONENT: u32 = 0xffff_ffff;
+
+/// What the pass knows about the values of a module.
+struct Values {
+    /// Defining instructions of composites and of values composites are
+    /// built from: (opcode, operands).
+    defs: BTreeMap<Word, (spirv::Op, Vec<mr::Operand>)>,
+    /// Types of values.
+    types: BTreeMap<Word, Word>,
+    /// Component counts of vector types.
+    vectors: BTreeMap<Word, u32>,
+    /// Results replaced by other values.
+    replaced: BTreeMap<Word, Word>,
+}
+
+impl Values {
+    /// Returns the value `id` was replaced with, or `id`.
+    fn resolve(&self, mut id: Word) -> Word {
+        // The bound guards against cycles in invalid modules.
+        for _ in 0..self.replaced.len() {
+            match self.replaced.get(&id) {
+                Some(&next) => id = next,
+                None => break,
+            }
+        }
+        id
+    }
+
+    /// Returns the component count of the vector `id`, or `None` if it is
+    /// not known to be a vector.
+    fn size(&self, id: Word) -> Option<u32> {
+        self.types.get(&id).and_then(|ty| self.vectors.get(ty)).cloned()
+    }
+
+    /// Returns the scalar the vector `id` is constructed by repeating, if
+    /// any.
+    fn splat(&self, id: Word) -> Option<Word> {
+        let constituents = match self.defs.get(&id) {
+            Some(&(spirv::Op::CompositeConstruct, ref operands)) => operands,
+            _ => return None,
+        };
+        let first = constituents.first().and_then(mr::Operand::id).map(|c| self.resolve(c))?;
+        let all = constituents.iter().all(|c| c.id().map(|c| self.resolve(c)) == Some(first));
+        if all && self.size(id).is_some() && self.size(first).is_none() {
+            Some(first)
+        } else {
+            None
+        }
+    }
+}
+
+/// Simplifies the composite operations of the functions of `module`,
+/// replacing results with the values they are known to equal.
+///
+/// * An `OpCompositeExtract` from an `OpCompositeConstruct` or
+///   `OpConstantComposite` extracts from the constituent holding the
+///   element instead. From an `OpCompositeInsert`, it extracts from the
+///   inserted object if the insertion covers the element, or from the
+///   composite inserted into if the two do not overlap. From an
+///   `OpVectorShuffle`, it extracts the selected component of its
+///   vectors. Extracts ending up with no indices are replaced by the
+///   value extracted.
+/// * An `OpVectorShuffle` selecting only from another shuffle selects
+///   from the vectors of that shuffle instead. A shuffle selecting all
+///   components of one vector in order is replaced by it, and one
+///   selecting only from splats of the same scalar, i.e.,
+///   `OpCompositeConstruct`s repeating it, becomes a splat itself.
+///
+/// Decorated results are kept, since their decorations may not apply to
+/// the values they equal.
+pub fn simplify_composites(module: &mut mr::Module) {
+    module.record_edits(|module| {
+        let decorated: BTreeSet<Word> = module.annotations
+            .iter()
+            .filter_map(|inst| inst.operands.first().and_then(mr::Operand::id))
+            .collect();
+        let mut values = Values {
+            defs: BTreeMap::new(),
+            types: BTreeMap::new(),
+            vectors: BTreeMap::new(),
+            replaced: BTreeMap::new(),
+        };
+        for inst in &module.types_global_values {
+            if inst.class.opcode == spirv::Op::TypeVector {
+                if let (Some(id), Some(&mr::Operand::LiteralInt32(n))) =
+                    (inst.result_id, inst.operands.get(1)) {
+                    values.vectors.insert(id, n);
+                }
+            }
+            if let (Some(id), Some(ty)) = (inst.result_id, inst.result_type) {
+                values.types.insert(id, ty);
+                if inst.class.opcode == spirv::Op::ConstantComposite {
+                    values.defs.insert(id, (inst.class.opcode, inst.operands.to_vec()));
+                }
+            }
+        }
+        for f in &module.functions {
+            let insts = f.parameters
+                .iter()
+                .chain(f.basic_blocks.iter().flat_map(|b| &b.instructions));
+            for inst in insts {
+                if let (Some(id), Some(ty)) = (inst.result_id, inst.result_type) {
+                    values.types.insert(id, ty);
+                }
+            }
+        }
+
+        for block in module.functions.iter_mut().flat_map(|f| &mut f.basic_blocks) {
+            for inst in &mut block.instructions {
+                let value = match inst.class.opcode {
+                    spirv::Op::CompositeExtract => simplify_extract(inst, &values),
+                    spirv::Op::VectorShuffle => simplify_shuffle(inst, &values),
+                    _ => None,
+                };
+                let id = match inst.result_id {
+                    Some(id) => id,
+                    None => continue,
+                };
+                match value {
+                    Some(value) if !decorated.contains(&id) => {
+                        values.replaced.insert(id, value);
+                    }
+                    _ => {
+                        values.defs.insert(id, (inst.class.opcode, inst.operands.to_vec()));
+                    }
+                }
+            }
+        }
+        if values.replaced.is_empty() {
+            return;
+        }
+
+        for block in module.functions.iter_mut().flat_map(|f| &mut f.basic_blocks) {
+            block.instructions
+                .retain(|inst| !inst.result_id.is_some_and(|id| values.replaced.contains_key(&id)));
+        }
+        let replaced: Vec<(Word, Word)> =
+            values.replaced.keys().map(|&id| (id, values.resolve(id))).collect();
+        for (id, value) in replaced {
+            module.replace_all_uses(id, value);
+        }
+    })
+}
+
+/// Rewrites the `OpCompositeExtract` `inst` to extract from the value
+/// closest to the element, and returns the element if it is that value.
+fn simplify_extract(inst: &mut mr::Instruction, values: &Values) -> Option<Word> {
+    let mut indices: Vec<u32> = inst.operands
+        .get(1..)?
+        .iter()
+        .map(|operand| match *operand {
+            mr::Operand::LiteralInt32(index) => Some(index),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+    let mut composite = values.resolve(inst.operands.first().and_then(mr::Operand::id)?);
+    for _ in 0..values.defs.len() + 1 {
+        if indices.is_empty() {
+            return Some(composite);
+        }
+        let (opcode, operands) = match values.defs.get(&composite) {
+            Some(&(opcode, ref operands)) => (opcode, operands),
+            None => break,
+        };
+        let id = |index: usize| {
+            operands.get(index).and_then(mr::Operand::id).map(|id| values.resolve(id))
+        };
+        match opcode {
+            spirv::Op::CompositeConstruct |
+            spirv::Op::ConstantComposite => {
+                let mut index = indices[0];
+                let mut found = None;
+                if values.size(composite).is_some() {
+                    // Vectors may be constructed from smaller vectors.
+                    for constituent in (0..operands.len()).filter_map(&id) {
+                        let size = values.size(constituent);
+                        if index < size.unwrap_or(1) {
+                            found = Some((constituent, size.map(|_| index)));
+                            break;
+                        }
+                        index -= size.unwrap_or(1);
+                    }
+                } else {
+                    found = id(index as usize).map(|constituent| (constituent, None));
+                }
+                let (constituent, inner) = match found {
+                    Some(found) => found,
+                    None => break,
+                };
+                composite = constituent;
+                indices.remove(0);
+                if let Some(inner) = inner {
+                    indices.insert(0, inner);
+                }
+            }
+            spirv::Op::CompositeInsert => {
+                let inserted: Vec<u32> = operands[2..]
+                    .iter()
+                    .filter_map(|operand| match *operand {
+                        mr::Operand::LiteralInt32(index) => Some(index),
+                        _ => None,
+                    })
+                    .collect();
+                let (object, into) = match (id(0), id(1)) {
+                    (Some(object), Some(into)) => (object, into),
+                    _ => break,
+                };
+                let common = inserted.iter().zip(&indices).take_while(|&(a, b)| a == b).count();
+                if common == inserted.len() {
+                    composite = object;
+                    indices.drain(..common);
+                } else if common == indices.len() {
+                    // The element holds the insertion.
+                    break;
+                } else {
+                    composite = into;
+                }
+            }
+            spirv::Op::VectorShuffle => {
+                let component = match operands.get(2 + indices[0] as usize) {
+                    Some(&mr::Operand::LiteralInt32(c)) if c != UNDEFINED_COMPONENT => c,
+                    _ => break,
+                };
+                let (first, second) = match (id(0), id(1)) {
+                    (Some(first), Some(second)) => (first, second),
+                    _ => break,
+                };
+                let n = match values.size(first) {
+                    Some(n) => n,
+                    None => break,
+                };
+                if component < n {
+                    composite = first;
+                    indices[0] = component;
+                } else {
+                    composite = second;
+                    indices[0] = component - n;
+                }
+            }
+            _ => break,
+        }
+    }
+
+    inst.operands = vec![mr::Operand::IdRef(composite)].into();
+    inst.operands.extend(indices.into_iter().map(mr::Operand::LiteralInt32));
+    None
+}
+
+/// Rewrites the `OpVectorShuffle` `inst` to select from the vectors
+/// closest to its components, and returns the vector it equals, if any.
+fn simplify_shuffle(inst: &mut mr::Instruction, values: &Values) -> Option<Word> {
+    let mut first = values.resolve(inst.operands.first().and_then(mr::Operand::id)?);
+    let mut second = values.resolve(inst.operands.get(1).and_then(mr::Operand::id)?);
+    let mut components: Vec<u32> = inst.operands
+        .get(2..)?
+        .iter()
+        .map(|operand| match *operand {
+            mr::Operand::LiteralInt32(c) => Some(c),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+    let defined = |components: &[u32]| -> Vec<u32> {
+        components.iter().cloned().filter(|&c| c != UNDEFINED_COMPONENT).collect()
+    };
+
+    for _ in 0..values.defs.len() + 1 {
+        let n = values.size(first)?;
+        let used = defined(&components);
+        let (inner, offset) = if used.iter().all(|&c| c < n) {
+            (first, 0)
+        } else if used.iter().all(|&c| c >= n) {
+            (second, n)
+        } else {
+            break;
+        };
+        let operands = match values.defs.get(&inner) {
+            Some(&(spirv::Op::VectorShuffle, ref operands)) => operands,
+            _ => break,
+        };
+        let mapped: Option<Vec<u32>> = components.iter()
+            .map(|&c| if c == UNDEFINED_COMPONENT {
+                Some(c)
+            } else {
+                match operands.get(2 + (c - offset) as usize) {
+                    Some(&mr::Operand::LiteralInt32(c)) => Some(c),
+                    _ => None,
+                }
+            })
+            .collect();
+        let (a, b) = (operands.first().and_then(mr::Operand::id),
+                      operands.get(1).and_then(mr::Operand::id));
+        match (mapped, a, b) {
+            (Some(mapped), Some(a), Some(b)) => {
+                components = mapped;
+                first = values.resolve(a);
+                second = values.resolve(b);
+            }
+            _ => break,
+        }
+    }
+
+    let n = values.size(first)?;
+    let len = components.len() as u32;
+    if components.iter().cloned().eq(0..len) && values.size(first) == Some(len) {
+        return Some(first);
+    }
+    if components.iter().cloned().eq(n..n + len) && values.size(second) == Some(len) {
+        return Some(second);
+    }
+
+    let used = defined(&components);
+    let splats: Vec<Option<Word>> = used.iter()
+        .map(|&c| values.splat(if c < n { first } else { second }))
+        .collect();
+    if used.len() == components.len() {
+        if let Some(&Some(scalar)) = splats.first() {
+            if splats.iter().all(|&s| s == Some(scalar)) {
+                *inst = mr::Instruction::new(spirv::Op::CompositeConstruct,
+                                             inst.result_type,
+                                             inst.result_id,
+                                             vec![mr::Operand::IdRef(scalar); components.len()]);
+                return None;
+            }
+        }
+    }
+
+    inst.operands = vec![mr::Operand::IdRef(first), mr::Operand::IdRef(second)].into();
+    inst.operands.extend(components.into_iter().map(mr::Operand::LiteralInt32));
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::simplify_composites;
+    use utils::test::opcodes;
+
+    #[test]
+    fn test_extract_chains() {
+        let mut b = mr::Builder::new();
+        let float = b.type_float(32);
+        let vec2 = b.type_vector(float, 2);
+        let vec4 = b.type_vector(float, 4);
+        let fty = b.type_function(float, vec![vec2, float]);
+        b.begin_function(float, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let v = b.function_parameter(vec2).unwrap();
+        let x = b.function_parameter(float).unwrap();
+        b.begin_basic_block(None).unwrap();
+        // (x, v.x, v.y, x)
+        let built = b.composite_construct(vec4, None, vec![x, v, x]).unwrap();
+        let first = b.composite_extract(float, None, built, vec![3]).unwrap();
+        let inner = b.composite_extract(float, None, built, vec![2]).unwrap();
+        let inserted = b.composite_insert(vec4, None, first, built, vec![1]).unwrap();
+        let covered = b.composite_extract(float, None, inserted, vec![1]).unwrap();
+        let past = b.composite_extract(float, None, inserted, vec![2]).unwrap();
+        let sum = b.fadd(float, None, first, inner).unwrap();
+        let sum = b.fadd(float, None, sum, covered).unwrap();
+        let sum = b.fadd(float, None, sum, past).unwrap();
+        b.ret_value(sum).unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        simplify_composites(&mut m);
+
+        let block = &m.functions[0].basic_blocks[0];
+        assert_eq!(opcodes(block),
+                   vec![spirv::Op::CompositeConstruct,
+                        spirv::Op::CompositeExtract,
+                        spirv::Op::CompositeInsert,
+                        spirv::Op::CompositeExtract,
+                        spirv::Op::FAdd,
+                        spirv::Op::FAdd,
+                        spirv::Op::FAdd,
+                        spirv::Op::ReturnValue]);
+        // Both extracts past the insertion read the parameter.
+        assert_eq!(block.instructions[1].operands,
+                   vec![mr::Operand::IdRef(v), mr::Operand::LiteralInt32(1)]);
+        assert_eq!(block.instructions[3].operands,
+                   vec![mr::Operand::IdRef(v), mr::Operand::LiteralInt32(1)]);
+        assert_eq!(block.instructions[4].operands,
+                   vec![mr::Operand::IdRef(x), mr::Operand::IdRef(inner)]);
+        assert_eq!(block.instructions[5].operands[1], mr::Operand::IdRef(x));
+    }
+
+    #[test]
+    fn test_shuffles() {
+        let mut b = mr::Builder::new();
+        let float = b.type_float(32);
+        let vec2 = b.type_vector(float, 2);
+        let vec3 = b.type_vector(float, 3);
+        let fty = b.type_function(vec3, vec![vec3, vec3, float]);
+        b.begin_function(vec3, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let p = b.function_parameter(vec3).unwrap();
+        let q = b.function_parameter(vec3).unwrap();
+        let x = b.function_parameter(float).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let swizzled = b.vector_shuffle(vec3, None, p, q, vec![2, 4, 0]).unwrap();
+        // Undoes the swizzle, selecting all of p in order.
+        let back = b.vector_shuffle(vec3, None, swizzled, swizzled, vec![2, 0xffff_ffff, 0]).unwrap();
+        let identity = b.vector_shuffle(vec3, None, swizzled, q, vec![3, 4, 5]).unwrap();
+        let splat = b.composite_construct(vec2, None, vec![x, x]).unwrap();
+        let widened = b.vector_shuffle(vec3, None, splat, splat, vec![0, 3, 1]).unwrap();
+        let sum = b.fadd(vec3, None, back, identity).unwrap();
+        let sum = b.fadd(vec3, None, sum, widened).unwrap();
+        let y = b.composite_extract(float, None, swizzled, vec![1]).unwrap();
+        b.name(y, "y");
+        b.ret_value(sum).unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        simplify_composites(&mut m);
+
+        let block = &m.functions[0].basic_blocks[0];
+        assert_eq!(block.instructions[1].operands,
+                   vec![mr::Operand::IdRef(p),
+                        mr::Operand::IdRef(q),
+                        mr::Operand::LiteralInt32(0),
+                        mr::Operand::LiteralInt32(0xffff_ffff),
+                        mr::Operand::LiteralInt32(2)]);
+        assert_eq!(block.instructions[3].class.opcode, spirv::Op::CompositeConstruct);
+        assert_eq!(block.instructions[3].result_id, Some(widened));
+        assert_eq!(block.instructions[3].operands, vec![mr::Operand::IdRef(x); 3]);
+        assert_eq!(block.instructions[4].operands,
+                   vec![mr::Operand::IdRef(back), mr::Operand::IdRef(q)]);
+        assert_eq!(block.instructions[6].operands,
+                   vec![mr::Operand::IdRef(q), mr::Operand::LiteralInt32(1)]);
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Forwards the sources of `OpCopyObject` and `OpCopyLogical` to all uses
+/// of the copies in the functions of `module`, and removes the copies.
+///
+/// An `OpCopyObject` always has the type of its source. An
+/// `OpCopyLogical` is only forwarded when its source is known to be of
+/// the same type; copies between different but logically matching types
+/// are kept. Decorated copies are kept too, since their decorations may
+/// not apply to the source. Names of the copies move to unnamed sources,
+/// as described in
+/// [`Module::replace_all_uses`](../mr/struct.Module.html#method.replace_all_uses).
+pub fn propagate_copies(module: &mut mr::Module) {
+    module.record_edits(|module| {
+        let decorated: BTreeSet<Word> = module.annotations
+            .iter()
+            .filter_map(|inst| inst.operands.first().and_then(mr::Operand::id))
+            .collect();
+        let mut types = BTreeMap::new();
+        {
+            let mut record = |inst: &mr::Instruction| if let (Some(id), Some(ty)) =
+                (inst.result_id, inst.result_type) {
+                types.insert(id, ty);
+            };
+            for inst in &module.types_global_values {
+                record(inst);
+            }
+            for f in &module.functions {
+                let insts = f.parameters
+                    .iter()
+                    .chain(f.basic_blocks.iter().flat_map(|b| &b.instructions));
+                for inst in insts {
+                    record(inst);
+                }
+            }
+        }
+
+        // (copy, source), in function order.
+        let copies: Vec<(Word, Word)> = module.functions
+            .iter()
+            .flat_map(|f| &f.basic_blocks)
+            .flat_map(|b| &b.instructions)
+            .filter_map(|inst| {
+                let forwardable = match inst.class.opcode {
+                    spirv::Op::CopyObject => true,
+                    spirv::Op::CopyLogical => {
+                        let source = inst.operands.first().and_then(mr::Operand::id);
+                        inst.result_type.is_some() &&
+                        source.and_then(|s| types.get(&s).cloned()) == inst.result_type
+                    }
+                    _ => false,
+                };
+                let source = inst.operands.first().and_then(mr::Operand::id);
+                match (forwardable, inst.result_id, source) {
+                    (true, Some(copy), Some(source)) if !decorated.contains(&copy) => {
+                        Some((copy, source))
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+        if copies.is_empty() {
+            return;
+        }
+
+        let sources: BTreeMap<Word, Word> = copies.iter().cloned().collect();
+        for block in module.functions.iter_mut().flat_map(|f| &mut f.basic_blocks) {
+            block.instructions
+                .retain(|inst| !inst.result_id.is_some_and(|id| sources.contains_key(&id)));
+        }
+        for &(copy, mut source) in &copies {
+            // Look through copies of copies; the bound guards against cycles
+            // in invalid modules.
+            for _ in 0..sources.len() {
+                match sources.get(&source) {
+                    Some(&next) => source = next,
+                    None => break,
+                }
+            }
+            module.replace_all_uses(copy, source);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::propagate_copies;
+
+    #[test]
+    fn test_propagate_copies() {
+        let mut b = mr::Builder::new();
+        let uint = b.type_int(32, 0);
+        let int = b.type_int(32, 1);
+        let one = b.constant_u32(uint, 1);
+        let fty = b.type_function(uint, vec![]);
+        b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let copy = b.copy_object(uint, None, one).unwrap();
+        // A copy of a copy.
+        let chained = b.copy_logical(uint, None, copy).unwrap();
+        // Of a different type, so kept.
+        let retyped = b.copy_logical(int, None, one).unwrap();
+        let decorated = b.copy_object(uint, None, one).unwrap();
+        b.decorate(decorated, spirv::Decoration::RelaxedPrecision, vec![]);
+        b.name(chained, "chained");
+        let sum = b.iadd(uint, None, chained, decorated).unwrap();
+        let sum = b.iadd(uint, None, sum, retyped).unwrap();
+        b.ret_value(sum).unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        propagate_copies(&mut m);
+
+        let block = &m.functions[0].basic_blocks[0];
+        let opcodes: Vec<spirv::Op> = block.instructions.iter().map(|i| i.class.opcode).collect();
+        assert_eq!(opcodes,
+                   vec![spirv::Op::CopyLogical,
+                        spirv::Op::CopyObject,
+                        spirv::Op::IAdd,
+                        spirv::Op::IAdd,
+                        spirv::Op::ReturnValue]);
+        assert_eq!(block.instructions[2].operands,
+                   vec![mr::Operand::IdRef(one), mr::Operand::IdRef(decorated)]);
+        assert_eq!(block.instructions[3].operands[1], mr::Operand::IdRef(retyped));
+        assert_eq!(m.debugs[0].operands[0], mr::Operand::IdRef(one));
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+
+use mr::Section;
+use std::ops::Deref;
+
+/// An instruction of a module being rewritten, copied only when changed.
+#[derive(Debug)]
+pub enum CowInstruction<'m> {
+    /// The instruction in the module, unchanged so far.
+    Borrowed(&'m mr::Instruction),
+    /// The replacement of the instruction.
+    Owned(mr::Instruction),
+}
+
+impl<'m> CowInstruction<'m> {
+    /// Returns the instruction for changing, copying it first if it is
+    /// still borrowed.
+    pub fn to_mut(&mut self) -> &mut mr::Instruction {
+        if let CowInstruction::Borrowed(inst) = *self {
+            *self = CowInstruction::Owned(copy(inst));
+        }
+        match *self {
+            CowInstruction::Owned(ref mut inst) => inst,
+            CowInstruction::Borrowed(_) => unreachable!(),
+        }
+    }
+
+    /// Replaces the instruction with `inst`.
+    pub fn replace(&mut self, inst: mr::Instruction) {
+        *self = CowInstruction::Owned(inst);
+    }
+
+    /// Returns true if the instruction was changed or replaced.
+    pub fn is_owned(&self) -> bool {
+        matches!(*self, CowInstruction::Owned(_))
+    }
+
+    /// Returns the instruction, copying it if it is still borrowed.
+    pub fn into_owned(self) -> mr::Instruction {
+        match self {
+            CowInstruction::Borrowed(inst) => copy(inst),
+            CowInstruction::Owned(inst) => inst,
+        }
+    }
+}
+
+impl<'m> Deref for CowInstruction<'m> {
+    type Target = mr::Instruction;
+
+    fn deref(&self) -> &mr::Instruction {
+        match *self {
+            CowInstruction::Borrowed(inst) => inst,
+            CowInstruction::Owned(ref inst) => inst,
+        }
+    }
+}
+
+fn copy(inst: &mr::Instruction) -> mr::Instruction {
+    let mut copy = mr::Instruction::new(inst.class.opcode,
+                                        inst.result_type,
+                                        inst.result_id,
+                                        inst.operands.clone());
+    copy.requirements = inst.requirements.clone();
+    copy
+}
+
+/// Rewrites the instructions of `module` with `f`, and returns the number
+/// of instructions it changed.
+///
+/// `f` is called on each instruction in layout order, with the module as
+/// it was before rewriting, so that it can look up other instructions
+/// while rewriting. Only the instructions `f` changes are copied, and only
+/// their sections are marked as [changed](../mr/struct.Changes.html).
+pub fn rewrite_instructions<F>(module: &mut mr::Module, mut f: F) -> usize
+    where F: FnMut(&mr::Module, &mut CowInstruction)
+{
+    let mut edits = vec![];
+    {
+        let module = &*module;
+        let mut index = 0;
+        for_each(module, |_, inst| {
+            let mut cow = CowInstruction::Borrowed(inst);
+            f(module, &mut cow);
+            if let CowInstruction::Owned(inst) = cow {
+                edits.push((index, inst));
+            }
+            index += 1;
+        });
+    }
+
+    let count = edits.len();
+    let mut touched = vec![];
+    let mut edits = edits.into_iter().peekable();
+    let mut index = 0;
+    for_each_mut(module, |section, inst| {
+        if edits.peek().is_some_and(|&(i, _)| i == index) {
+            *inst = edits.next().unwrap().1;
+            touched.push(section);
+        }
+        index += 1;
+    });
+    for section in touched {
+        module.touch(section);
+    }
+    count
+}
+
+/// Returns the section of `function`.
+fn function_section(function: &mr::Function) -> Section {
+    Section::Function(function.def.as_ref().and_then(|d| d.result_id).unwrap_or(0))
+}
+
+/// Calls `f` on each instruction of `module` in layout order, with its
+/// section.
+fn for_each<'m, F: FnMut(Section, &'m mr::Instruction)>(module: &'m mr::Module, mut f: F) {
+    let globals = [(Section::Capabilities, &module.capabilities),
+                   (Section::Extensions, &module.extensions),
+                   (Section::ExtInstImports, &module.ext_inst_imports)];
+    for &(section, insts) in &globals {
+        insts.iter().for_each(|inst| f(section, inst));
+    }
+    module.memory_model.iter().for_each(|inst| f(Section::MemoryModel, inst));
+    let globals = [(Section::EntryPoints, &module.entry_points),
+                   (Section::ExecutionModes, &module.execution_modes),
+                   (Section::Debugs, &module.debugs),
+                   (Section::Annotations, &module.annotations),
+                   (Section::TypesGlobalValues, &module.types_global_values)];
+    for &(section, insts) in &globals {
+        insts.iter().for_each(|inst| f(section, inst));
+    }
+    for function in &module.functions {
+        let section = function_section(function);
+        let blocks = function.basic_blocks
+            .iter()
+            .flat_map(|b| b.label.iter().chain(&b.instructions));
+        function.def
+            .iter()
+            .chain(&function.parameters)
+            .chain(blocks)
+            .chain(&function.end)
+            .for_each(|inst| f(section, inst));
+    }
+}
+
+/// Calls `f` on each instruction of `module` in the order of
+/// [`for_each`](fn.for_each.html).
+fn for_each_mut<F: FnMut(Section, &mut mr::Instruction)>(module: &mut mr::Module, mut f: F) {
+    let globals = [(Section::Capabilities, &mut module.capabilities),
+                   (Section::Extensions, &mut module.extensions),
+                   (Section::ExtInstImports, &mut module.ext_inst_imports)];
+    for (section, insts) in globals {
+        insts.iter_mut().for_each(|inst| f(section, inst));
+    }
+    module.memory_model.iter_mut().for_each(|inst| f(Section::MemoryModel, inst));
+    let globals = [(Section::EntryPoints, &mut module.entry_points),
+                   (Section::ExecutionModes, &mut module.execution_modes),
+                   (Section::Debugs, &mut module.debugs),
+                   (Section::Annotations, &mut module.annotations),
+                   (Section::TypesGlobalValues, &mut module.types_global_values)];
+    for (section, insts) in globals {
+        insts.iter_mut().for_each(|inst| f(section, inst));
+    }
+    for function in &mut module.functions {
+        let section = function_section(function);
+        let blocks = function.basic_blocks
+            .iter_mut()
+            .flat_map(|b| b.label.iter_mut().chain(&mut b.instructions));
+        function.def
+            .iter_mut()
+            .chain(&mut function.parameters)
+            .chain(blocks)
+            .chain(&mut function.end)
+            .for_each(|inst| f(section, inst));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::rewrite_instructions;
+
+    #[test]
+    fn test_rewrite_instructions() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let one = b.constant_u32(uint, 1);
+        let two = b.constant_u32(uint, 2);
+        let voidf = b.type_function(void, vec![]);
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let x = b.iadd(uint, None, one, two).unwrap();
+        let y = b.iadd(uint, None, one, x).unwrap();
+        b.imul(uint, None, y, two).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+        m.take_changes();
+
+        // Puts the constant operand of additions last.
+        let count = rewrite_instructions(&mut m, |module, inst| {
+            let constant = |operand: &mr::Operand| {
+                let id = operand.id();
+                id.is_some() && module.types_global_values.iter().any(|c| c.result_id == id)
+            };
+            if inst.class.opcode == spirv::Op::IAdd && constant(&inst.operands[0]) &&
+               !constant(&inst.operands[1]) {
+                inst.to_mut().operands.swap(0, 1);
+            }
+        });
+
+        assert_eq!(count, 1);
+        let insts = &m.functions[0].basic_blocks[0].instructions;
+        assert_eq!(insts[0].operands, vec![mr::Operand::IdRef(one), mr::Operand::IdRef(two)]);
+        assert_eq!(insts[1].operands, vec![mr::Operand::IdRef(x), mr::Operand::IdRef(one)]);
+        let touched: Vec<_> = m.changes().sections().iter().cloned().collect();
+        assert_eq!(touched, vec![mr::Section::Function(f)]);
+    }
+}
@@ -0,0 +1,314 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grammar;
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::BTreeSet;
+use std::mem;
+use super::flatten::is_speculatable;
+use super::is_spec_constant;
+
+/// Removes the code of `module` whose results are never used, and returns
+/// the number of functions and instructions removed.
+///
+/// Functions are dead unless an entry point or a global instruction refers
+/// to them, or a function that is not dead calls them; modules declaring
+/// `Linkage` keep all of their functions. Instructions in functions are
+/// dead if nothing uses their results and they have no side effects:
+/// computations, non-volatile loads, access chains, phis, image reads
+/// and samples, and `Function` variables, whose non-volatile stores are
+/// dead too if nothing else uses them. Outside of functions, unused
+/// types, non-specialization constants, `OpUndef`s, and `Private` and
+/// `Workgroup` variables are dead. Removing dead code may make more code
+/// dead, which is removed too.
+///
+/// Debug instructions, and decorations of the results, do not count as
+/// uses; they are removed with the results.
+pub fn eliminate_dead_code(module: &mut mr::Module) -> usize {
+    module.record_edits(|module| {
+        let mut removed = vec![];
+        let mut count = 0;
+        if !module.capabilities.iter().any(|inst| {
+            inst.operands.first() == Some(&mr::Operand::Capability(spirv::Capability::Linkage))
+        }) {
+            let live = live_functions(module);
+            for function in mem::take(&mut module.functions) {
+                let id = function.def.as_ref().and_then(|def| def.result_id);
+                if id.is_some_and(|id| live.contains(&id)) {
+                    module.functions.push(function);
+                } else {
+                    count += 1;
+                    let blocks = function.basic_blocks.into_iter().flat_map(|b| {
+                        b.label.into_iter().chain(b.instructions)
+                    });
+                    removed.extend(function.def
+                        .into_iter()
+                        .chain(function.parameters)
+                        .chain(blocks));
+                }
+            }
+        }
+
+        let glsl = mr::ExtInstRegistry::of(module).id_of(grammar::ExtInstSet::GlslStd450);
+        loop {
+            let counted = count;
+            let used = used_ids(module);
+            let dead =
+                |inst: &mr::Instruction| inst.result_id.is_some_and(|id| !used.contains(&id));
+            let before = removed.len();
+            for function in &mut module.functions {
+                let stored = stored_only(function);
+                for block in &mut function.basic_blocks {
+                    block.instructions.retain(|inst| {
+                        let dead = inst.class.opcode == spirv::Op::Store &&
+                                   inst.operands[0].id().is_some_and(|id| stored.contains(&id));
+                        count += dead as usize;
+                        !dead
+                    });
+                }
+            }
+            for block in module.functions.iter_mut().flat_map(|f| &mut f.basic_blocks) {
+                let (dead, live): (Vec<_>, Vec<_>) = mem::take(&mut block.instructions)
+                    .into_iter()
+                    .partition(|inst| dead(inst) && is_removable(inst, glsl));
+                block.instructions = live;
+                count += dead.len();
+                removed.extend(dead);
+            }
+            let (dead, live): (Vec<_>, Vec<_>) = mem::take(&mut module.types_global_values)
+                .into_iter()
+                .partition(|inst| dead(inst) && is_removable_global(inst));
+            module.types_global_values = live;
+            count += dead.len();
+            removed.extend(dead);
+            if removed.len() == before && count == counted {
+                break;
+            }
+        }
+
+        let mut ids = mr::IdAllocator::for_module(module);
+        module.replace_removed_uses(&mut ids, &removed);
+        ids.update_header(module);
+        count
+    })
+}
+
+/// Returns the ids of the functions entry points or global instructions
+/// refer to, and of those they call or refer to, directly or not.
+fn live_functions(module: &mr::Module) -> BTreeSet<Word> {
+    let functions: BTreeSet<Word> = module.functions
+        .iter()
+        .filter_map(|f| f.def.as_ref()?.result_id)
+        .collect();
+    let mut stack: Vec<Word> = module.entry_points
+        .iter()
+        .chain(&module.types_global_values)
+        .flat_map(|inst| inst.operands.iter().filter_map(mr::Operand::id))
+        .filter(|id| functions.contains(id))
+        .collect();
+    let mut live = BTreeSet::new();
+    while let Some(f) = stack.pop() {
+        if !live.insert(f) {
+            continue;
+        }
+        let function = module.functions
+            .iter()
+            .find(|function| function.def.as_ref().and_then(|def| def.result_id) == Some(f));
+        let insts = function.into_iter()
+            .flat_map(|f| &f.basic_blocks)
+            .flat_map(|b| &b.instructions);
+        stack.extend(insts.flat_map(|inst| inst.operands.iter().filter_map(mr::Operand::id))
+            .filter(|id| functions.contains(id) && !live.contains(id)));
+    }
+    live
+}
+
+/// Returns the `Function` variables of `function` only stored to, by
+/// non-volatile `OpStore`s.
+fn stored_only(function: &mr::Function) -> BTreeSet<Word> {
+    let insts = || function.basic_blocks.iter().flat_map(|b| &b.instructions);
+    let mut variables: BTreeSet<Word> = insts()
+        .filter(|inst| inst.class.opcode == spirv::Op::Variable)
+        .filter_map(|inst| inst.result_id)
+        .collect();
+    for inst in insts() {
+        let store = inst.class.opcode == spirv::Op::Store && !is_volatile(inst);
+        for (i, id) in inst.operands.iter().filter_map(mr::Operand::id).enumerate() {
+            if !(store && i == 0) {
+                variables.remove(&id);
+            }
+        }
+    }
+    variables
+}
+
+fn is_volatile(inst: &mr::Instruction) -> bool {
+    inst.operands.iter().any(|operand| match *operand {
+        mr::Operand::MemoryAccess(access) => access.contains(spirv::MemoryAccess::VOLATILE),
+        _ => false,
+    })
+}
+
+/// Returns the ids used by `module`, not counting debug instructions and
+/// the targets of decorations.
+fn used_ids(module: &mr::Module) -> BTreeSet<Word> {
+    fn ids(inst: &mr::Instruction) -> impl Iterator<Item = Word> + '_ {
+        inst.result_type.into_iter().chain(inst.operands.iter().filter_map(mr::Operand::id))
+    }
+    let mut used = BTreeSet::new();
+    for inst in module.global_inst_iter() {
+        let opcode = inst.class.opcode;
+        if grammar::reflect::is_annotation(opcode) {
+            // Only ids given as decoration parameters are used.
+            let targets = match opcode {
+                spirv::Op::GroupDecorate | spirv::Op::GroupMemberDecorate => inst.operands.len(),
+                _ => 1,
+            };
+            used.extend(inst.operands.iter().skip(targets).filter_map(mr::Operand::id));
+        } else if opcode == spirv::Op::EntryPoint {
+            // Listing a variable in the interface is no use of it.
+            used.extend(inst.operands.get(1).and_then(mr::Operand::id));
+        } else if !grammar::reflect::is_nonlocation_debug(opcode) {
+            used.extend(ids(inst));
+        }
+    }
+    for f in &module.functions {
+        for inst in f.def.iter().chain(&f.parameters).chain(&f.end) {
+            used.extend(ids(inst));
+        }
+        for block in &f.basic_blocks {
+            for inst in block.label.iter().chain(&block.instructions) {
+                used.extend(ids(inst));
+            }
+        }
+    }
+    used
+}
+
+/// Returns true if `inst`, in a function, has no side effects.
+fn is_removable(inst: &mr::Instruction, glsl: Option<Word>) -> bool {
+    use spirv::Op;
+    match inst.class.opcode {
+        Op::Load => !is_volatile(inst),
+        Op::AccessChain | Op::InBoundsAccessChain | Op::PtrAccessChain | Op::Phi | Op::Variable |
+        Op::ArrayLength | Op::UDiv | Op::SDiv | Op::UMod | Op::SRem | Op::SMod => true,
+        Op::SampledImage | Op::Image | Op::ImageRead | Op::ImageFetch | Op::ImageGather |
+        Op::ImageDrefGather | Op::ImageSampleImplicitLod | Op::ImageSampleExplicitLod |
+        Op::ImageSampleDrefImplicitLod | Op::ImageSampleDrefExplicitLod |
+        Op::ImageSampleProjImplicitLod | Op::ImageSampleProjExplicitLod |
+        Op::ImageSampleProjDrefImplicitLod | Op::ImageSampleProjDrefExplicitLod |
+        Op::ImageQuerySizeLod | Op::ImageQuerySize | Op::ImageQueryLod | Op::ImageQueryLevels |
+        Op::ImageQuerySamples => true,
+        _ => is_speculatable(inst, glsl),
+    }
+}
+
+/// Returns true if `inst`, outside of functions, can be removed when
+/// unused.
+fn is_removable_global(inst: &mr::Instruction) -> bool {
+    match inst.class.opcode {
+        spirv::Op::Undef => true,
+        spirv::Op::Variable => match inst.operands.first() {
+            Some(&mr::Operand::StorageClass(class)) => {
+                class == spirv::StorageClass::Private || class == spirv::StorageClass::Workgroup
+            }
+            _ => false,
+        },
+        opcode => {
+            (grammar::reflect::is_type(opcode) && opcode != spirv::Op::TypeForwardPointer) ||
+            (grammar::reflect::is_constant(opcode) && !is_spec_constant(opcode))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::eliminate_dead_code;
+
+    #[test]
+    fn test_eliminate_dead_code() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let float = b.type_float(32);
+        b.type_vector(float, 3);
+        let ptr_float = b.type_pointer(None, spirv::StorageClass::Private, float);
+        let ptr_out = b.type_pointer(None, spirv::StorageClass::Output, float);
+        let one = b.constant_f32(float, 1.0);
+        let two = b.constant_f32(float, 2.0);
+        let private = b.variable(ptr_float, None, spirv::StorageClass::Private, None);
+        let output = b.variable(ptr_out, None, spirv::StorageClass::Output, None);
+        b.name(private, "private");
+        b.decorate(two, spirv::Decoration::RelaxedPrecision, vec![]);
+        let fty = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let x = b.load(float, None, private, None, vec![]).unwrap();
+        b.fmul(float, None, x, two).unwrap();
+        b.store(output, one, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::Fragment, main, "main", vec![output, private]);
+        let mut m = b.module();
+
+        // The helper, the multiplication and the load, the variable and
+        // its pointer type, the constant, and the vector type.
+        assert_eq!(eliminate_dead_code(&mut m), 7);
+        assert_eq!(m.functions.len(), 1);
+        assert_eq!(m.functions[0].def.as_ref().unwrap().result_id, Some(main));
+        let ids: Vec<u32> =
+            m.types_global_values.iter().filter_map(|inst| inst.result_id).collect();
+        assert_eq!(ids, vec![void, float, ptr_out, one, output, fty]);
+        assert!(m.debugs.is_empty() && m.annotations.is_empty());
+        assert_eq!(m.entry_points[0].operands.len(), 4);
+        assert_eq!(m.functions[0].basic_blocks[0].instructions.len(), 2);
+    }
+
+    #[test]
+    fn test_eliminate_dead_code_keeps_pointer_writes() {
+        let mut b = mr::Builder::new();
+        let glsl = b.ext_inst_import("GLSL.std.450");
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let ptr_out = b.type_pointer(None, spirv::StorageClass::Output, float);
+        let value = b.constant_f32(float, 1.5);
+        let output = b.variable(ptr_out, None, spirv::StorageClass::Output, None);
+        let fty = b.type_function(void, vec![]);
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ext_inst(float, None, glsl, spirv::GLOp::Sqrt as u32, vec![value]).unwrap();
+        let modf = b.ext_inst(float, None, glsl, spirv::GLOp::Modf as u32, vec![value, output])
+            .unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::Fragment, main, "main", vec![output]);
+        let mut m = b.module();
+
+        // The unused square root goes, but `Modf` stores the whole part
+        // through its pointer and stays.
+        assert_eq!(eliminate_dead_code(&mut m), 1);
+        let insts = &m.functions[0].basic_blocks[0].instructions;
+        assert_eq!(insts.len(), 2);
+        assert_eq!(insts[0].result_id, Some(modf));
+    }
+}
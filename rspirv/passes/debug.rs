@@ -0,0 +1,90 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grammar;
+use mr;
+use spirv;
+
+use super::remove_extension;
+
+/// Removes the debug information of `module`: its strings, sources, names,
+/// and processes, its `OpLine` and `OpNoLine` instructions, and its
+/// `NonSemantic.Shader.DebugInfo.100` extended instructions, with their
+/// import.
+///
+/// `SPV_KHR_non_semantic_info` is removed too if no other non-semantic set
+/// stays imported.
+pub fn strip_debug_info(module: &mut mr::Module) {
+    module.record_edits(|module| {
+        let registry = mr::ExtInstRegistry::of(module);
+        let debug_info = registry.id_of(grammar::ExtInstSet::DebugInfo100);
+        let is_debug = |inst: &mr::Instruction| {
+            grammar::reflect::is_location_debug(inst.class.opcode) ||
+            (debug_info.is_some() && inst.class.opcode == spirv::Op::ExtInst &&
+             inst.operands.first().and_then(mr::Operand::id) == debug_info)
+        };
+
+        module.debugs.clear();
+        module.types_global_values.retain(|inst| !is_debug(inst));
+        for block in module.functions.iter_mut().flat_map(|f| &mut f.basic_blocks) {
+            block.instructions.retain(|inst| !is_debug(inst));
+        }
+        if debug_info.is_some() {
+            module.ext_inst_imports.retain(|inst| inst.result_id != debug_info);
+            let non_semantic = module.ext_inst_imports.iter().any(|inst| {
+                matches!(inst.operands.first(),
+                         Some(mr::Operand::LiteralString(name)) if name.starts_with("NonSemantic."))
+            });
+            if !non_semantic {
+                remove_extension(module, "SPV_KHR_non_semantic_info");
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::strip_debug_info;
+
+    #[test]
+    fn test_strip_debug_info() {
+        let mut b = mr::Builder::new();
+        b.extension("SPV_KHR_non_semantic_info");
+        let set = b.ext_inst_import("NonSemantic.Shader.DebugInfo.100");
+        let void = b.type_void();
+        let file = b.string("shader.glsl");
+        b.source(spirv::SourceLanguage::GLSL, 450, Some(file), None::<String>);
+        let fty = b.type_function(void, vec![]);
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.name(main, "main");
+        b.begin_basic_block(None).unwrap();
+        b.line(file, 1, 1);
+        b.ext_inst(void, None, set, 103, vec![]).unwrap();
+        b.no_line();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        strip_debug_info(&mut m);
+        assert!(m.debugs.is_empty());
+        assert!(m.extensions.is_empty());
+        assert!(m.ext_inst_imports.is_empty());
+        let insts = &m.functions[0].basic_blocks[0].instructions;
+        assert_eq!(insts.len(), 1);
+        assert_eq!(insts[0].class.opcode, spirv::Op::Return);
+    }
+}
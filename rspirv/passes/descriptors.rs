@@ -0,0 +1,167 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use analysis::{descriptor_indexing, DescriptorKind};
+use mr;
+use spirv;
+
+use super::{add_capability, add_extension};
+
+const DESCRIPTOR_INDEXING: &str = "SPV_EXT_descriptor_indexing";
+
+/// Decorates the values derived from non-uniform descriptor indexing in
+/// `module` `NonUniform` where they are not, and returns the number of
+/// decorations added.
+///
+/// Which values need it is as reported by
+/// [`descriptor_indexing`](../analysis/fn.descriptor_indexing.html). The
+/// capabilities the indexing needs are declared as well: the dynamic and
+/// non-uniform indexing ones for each kind of descriptor indexed so,
+/// `ShaderNonUniform` for the decorations, and `RuntimeDescriptorArray`
+/// for runtime arrays of descriptors, together with the
+/// `SPV_EXT_descriptor_indexing` extension before SPIR-V 1.5 if any of
+/// them comes from it.
+pub fn fix_descriptor_indexing(module: &mut mr::Module) -> usize {
+    module.record_edits(|module| {
+        let indexings = descriptor_indexing(module);
+        let mut capabilities = vec![];
+        let mut decorated = 0;
+        for indexing in indexings {
+            if indexing.runtime_array {
+                capabilities.push(spirv::Capability::RuntimeDescriptorArray);
+            }
+            if indexing.dynamically_indexed {
+                capabilities.push(dynamic_indexing(indexing.kind));
+            }
+            if indexing.non_uniformly_indexed {
+                capabilities.push(spirv::Capability::ShaderNonUniform);
+                capabilities.push(non_uniform_indexing(indexing.kind));
+            }
+            for id in indexing.missing_non_uniform {
+                let decoration = mr::Operand::Decoration(spirv::Decoration::NonUniform);
+                let operands = vec![mr::Operand::IdRef(id), decoration];
+                module.annotations
+                    .push(mr::Instruction::new(spirv::Op::Decorate, None, None, operands));
+                decorated += 1;
+            }
+        }
+
+        let extended = capabilities
+            .iter()
+            .any(|&c| !mr::Operand::Capability(c).required_extensions().is_empty());
+        if extended && module.header.as_ref().is_none_or(|header| header.version() < (1, 5)) {
+            add_extension(module, DESCRIPTOR_INDEXING);
+        }
+        for capability in capabilities {
+            add_capability(module, capability);
+        }
+        decorated
+    })
+}
+
+/// Returns the capability for indexing arrays of `kind` dynamically.
+fn dynamic_indexing(kind: DescriptorKind) -> spirv::Capability {
+    match kind {
+        DescriptorKind::Sampler |
+        DescriptorKind::SampledImage => spirv::Capability::SampledImageArrayDynamicIndexing,
+        DescriptorKind::StorageImage => spirv::Capability::StorageImageArrayDynamicIndexing,
+        DescriptorKind::UniformTexelBuffer => {
+            spirv::Capability::UniformTexelBufferArrayDynamicIndexing
+        }
+        DescriptorKind::StorageTexelBuffer => {
+            spirv::Capability::StorageTexelBufferArrayDynamicIndexing
+        }
+        DescriptorKind::InputAttachment => spirv::Capability::InputAttachmentArrayDynamicIndexing,
+        DescriptorKind::UniformBuffer => spirv::Capability::UniformBufferArrayDynamicIndexing,
+        DescriptorKind::StorageBuffer => spirv::Capability::StorageBufferArrayDynamicIndexing,
+    }
+}
+
+/// Returns the capability for indexing arrays of `kind` non-uniformly.
+fn non_uniform_indexing(kind: DescriptorKind) -> spirv::Capability {
+    match kind {
+        DescriptorKind::Sampler |
+        DescriptorKind::SampledImage => spirv::Capability::SampledImageArrayNonUniformIndexing,
+        DescriptorKind::StorageImage => spirv::Capability::StorageImageArrayNonUniformIndexing,
+        DescriptorKind::UniformTexelBuffer => {
+            spirv::Capability::UniformTexelBufferArrayNonUniformIndexing
+        }
+        DescriptorKind::StorageTexelBuffer => {
+            spirv::Capability::StorageTexelBufferArrayNonUniformIndexing
+        }
+        DescriptorKind::InputAttachment => {
+            spirv::Capability::InputAttachmentArrayNonUniformIndexing
+        }
+        DescriptorKind::UniformBuffer => spirv::Capability::UniformBufferArrayNonUniformIndexing,
+        DescriptorKind::StorageBuffer => spirv::Capability::StorageBufferArrayNonUniformIndexing,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use analysis;
+    use mr;
+    use spirv;
+
+    use super::fix_descriptor_indexing;
+
+    #[test]
+    fn test_fix_descriptor_indexing() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let uint = b.type_int(32, 0);
+        let block = b.type_struct(vec![float]);
+        let blocks = b.type_runtime_array(block);
+        let ptr_blocks = b.type_pointer(None, spirv::StorageClass::StorageBuffer, blocks);
+        let ptr_block = b.type_pointer(None, spirv::StorageClass::StorageBuffer, block);
+        let ptr_float = b.type_pointer(None, spirv::StorageClass::StorageBuffer, float);
+        let zero = b.constant_u32(uint, 0);
+        let ssbo = b.variable(ptr_blocks, None, spirv::StorageClass::StorageBuffer, None);
+        b.decorate(block, spirv::Decoration::Block, vec![]);
+
+        let fty = b.type_function(void, vec![uint]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let i = b.function_parameter(uint).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let chain = b.access_chain(ptr_block, None, ssbo, vec![i]).unwrap();
+        let member = b.access_chain(ptr_float, None, chain, vec![zero]).unwrap();
+        let value = b.load(float, None, member, None, vec![]).unwrap();
+        b.store(member, value, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.decorate(i, spirv::Decoration::NonUniform, vec![]);
+        let mut m = b.module();
+
+        assert_eq!(fix_descriptor_indexing(&mut m), 2);
+        let decorated: Vec<_> = m.annotations[2..].iter().map(|a| a.operands[0].clone()).collect();
+        assert_eq!(decorated, vec![mr::Operand::IdRef(chain), mr::Operand::IdRef(member)]);
+        assert!(analysis::descriptor_indexing(&m)[0].missing_non_uniform.is_empty());
+        let capabilities: Vec<_> = m.capabilities.iter().map(|c| c.operands[0].clone()).collect();
+        assert_eq!(capabilities,
+                   vec![mr::Operand::Capability(spirv::Capability::Shader),
+                        mr::Operand::Capability(spirv::Capability::RuntimeDescriptorArray),
+                        mr::Operand::Capability(
+                            spirv::Capability::StorageBufferArrayDynamicIndexing),
+                        mr::Operand::Capability(spirv::Capability::ShaderNonUniform),
+                        mr::Operand::Capability(
+                            spirv::Capability::StorageBufferArrayNonUniformIndexing)]);
+        assert_eq!(m.extensions[0].operands,
+                   vec![mr::Operand::from("SPV_EXT_descriptor_indexing")]);
+
+        assert_eq!(fix_descriptor_indexing(&mut m), 0);
+        assert_eq!(m.capabilities.len(), 5);
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use binary::Assemble;
+use grammar;
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+use super::is_spec_constant;
+
+/// Removes the duplicate capabilities, extensions, extended instruction
+/// set imports, types, constants, and decorations of `module`, and returns
+/// the number of instructions removed.
+///
+/// Types and constants are duplicates if they have the same opcode, result
+/// type, operands, and decorations, after replacing the duplicates they
+/// refer to; the first one is kept, and the uses of the others replaced by
+/// it. Specialization constants, forward pointers, and ids decorated
+/// through decoration groups are kept.
+pub fn remove_duplicates(module: &mut mr::Module) -> usize {
+    module.record_edits(|module| {
+        let before = count(module);
+        for insts in [&mut module.capabilities, &mut module.extensions] {
+            let mut seen = BTreeSet::new();
+            insts.retain(|inst| seen.insert(words(&inst.operands)));
+        }
+
+        let mut replaced: Vec<(Word, Word)> = vec![];
+        let mut imports: BTreeMap<String, Word> = BTreeMap::new();
+        for inst in &module.ext_inst_imports {
+            if let (Some(mr::Operand::LiteralString(name)), Some(id)) =
+                   (inst.operands.first(), inst.result_id) {
+                let first = *imports.entry(name.clone()).or_insert(id);
+                if first != id {
+                    replaced.push((id, first));
+                }
+            }
+        }
+        for &(old, new) in &replaced {
+            module.replace_all_uses(old, new);
+        }
+
+        let mut grouped = BTreeSet::new();
+        let mut decorations: BTreeMap<Word, Vec<Key>> = BTreeMap::new();
+        for inst in &module.annotations {
+            match inst.class.opcode {
+                spirv::Op::GroupDecorate | spirv::Op::GroupMemberDecorate => {
+                    grouped.extend(inst.operands.iter().skip(1).filter_map(mr::Operand::id));
+                }
+                _ => {
+                    if let Some(id) = inst.operands.first().and_then(mr::Operand::id) {
+                        let decoration = (inst.class.opcode as u32, words(&inst.operands[1..]));
+                        decorations.entry(id).or_default().push(decoration);
+                    }
+                }
+            }
+        }
+        for decorations in decorations.values_mut() {
+            decorations.sort();
+        }
+
+        let mut first: BTreeMap<(Key, Option<Word>, Vec<Key>), Word> = BTreeMap::new();
+        let mut index = 0;
+        while index < module.types_global_values.len() {
+            let inst = &module.types_global_values[index];
+            index += 1;
+            let opcode = inst.class.opcode;
+            let dedupable = opcode != spirv::Op::TypeForwardPointer &&
+                            (grammar::reflect::is_type(opcode) ||
+                             (grammar::reflect::is_constant(opcode) && !is_spec_constant(opcode)));
+            let id = match inst.result_id {
+                Some(id) if dedupable && !grouped.contains(&id) => id,
+                _ => continue,
+            };
+            let key = ((opcode as u32, words(&inst.operands)),
+                       inst.result_type,
+                       decorations.get(&id).cloned().unwrap_or_default());
+            let kept = *first.entry(key).or_insert(id);
+            if kept != id {
+                // The uses of the duplicate after it are rewritten before
+                // they are compared.
+                module.replace_all_uses(id, kept);
+                replaced.push((id, kept));
+            }
+        }
+
+        let removed: BTreeSet<Word> = replaced.iter().map(|&(old, _)| old).collect();
+        let kept = |inst: &mr::Instruction| !inst.result_id.is_some_and(|id| removed.contains(&id));
+        module.ext_inst_imports.retain(kept);
+        module.types_global_values.retain(kept);
+        let mut seen = BTreeSet::new();
+        module.annotations.retain(|inst| {
+            seen.insert((inst.class.opcode as u32, words(&inst.operands)))
+        });
+        before - count(module)
+    })
+}
+
+/// An opcode and operands, as words.
+type Key = (u32, Vec<u32>);
+
+fn words(operands: &[mr::Operand]) -> Vec<u32> {
+    operands.iter().flat_map(|operand| operand.assemble()).collect()
+}
+
+fn count(module: &mr::Module) -> usize {
+    module.capabilities.len() + module.extensions.len() + module.ext_inst_imports.len() +
+    module.types_global_values.len() + module.annotations.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::remove_duplicates;
+
+    #[test]
+    fn test_remove_duplicates() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.capability(spirv::Capability::Shader);
+        let float = b.type_float(32);
+        let also_float = b.type_float(32);
+        let vec2 = b.type_vector(float, 2);
+        let also_vec2 = b.type_vector(also_float, 2);
+        let one = b.constant_f32(float, 1.0);
+        let also_one = b.constant_f32(also_float, 1.0);
+        let uint = b.type_int(32, 0);
+        let four = b.constant_u32(uint, 4);
+        let strided = b.type_array(vec2, four);
+        let also_strided = b.type_array(also_vec2, four);
+        let other_stride = b.type_array(vec2, four);
+        for &(array, stride) in &[(strided, 8), (also_strided, 8), (other_stride, 16)] {
+            b.decorate(array,
+                       spirv::Decoration::ArrayStride,
+                       vec![mr::Operand::LiteralInt32(stride)]);
+        }
+        b.name(also_one, "one");
+        let v = b.constant_composite(also_vec2, vec![one, also_one]);
+        let mut m = b.module();
+
+        assert_eq!(remove_duplicates(&mut m), 6);
+        assert_eq!(m.capabilities.len(), 1);
+        let ids: Vec<u32> =
+            m.types_global_values.iter().filter_map(|inst| inst.result_id).collect();
+        assert_eq!(ids, vec![float, vec2, one, uint, four, strided, other_stride, v]);
+        assert_eq!(m.annotations.len(), 2);
+        assert_eq!(m.debugs[0].operands[0], mr::Operand::IdRef(one));
+        let composite = m.types_global_values.last().unwrap();
+        assert_eq!(composite.result_type, Some(vec2));
+        assert_eq!(composite.operands, vec![mr::Operand::IdRef(one), mr::Operand::IdRef(one)]);
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use spirv;
+
+use std::{error, fmt, result};
+
+/// Pass errors.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The module uses an instruction the pass cannot rewrite for the
+    /// requested target.
+    Unsupported(spirv::Op),
+    /// The `OpSwitch` ending the block with the given label has case
+    /// literals not encoded for the width of its selector.
+    SwitchLiteralWidth(spirv::Word),
+    /// The `OpSwitch` ending the block with the given label branches to
+    /// different targets for the same case literal.
+    DuplicateSwitchCase(spirv::Word),
+    /// The module requires a capability the target environment has no
+    /// alternative to; the first alternative is given.
+    UnavailableCapability(spirv::Capability),
+    /// The module has no instruction or block with the given id where the
+    /// pass expected one.
+    UnknownId(spirv::Word),
+    /// The function of the given id has no parameter of the given index.
+    UnknownParameter(spirv::Word, usize),
+}
+
+impl Error {
+    /// Gives an descriptive string for each error.
+    ///
+    /// This method is intended to be used by fmt::Display and error::Error to
+    /// avoid duplication in implementation. So it's private.
+    fn describe(&self) -> &str {
+        match *self {
+            Error::Unsupported(_) => "unsupported instruction",
+            Error::SwitchLiteralWidth(_) => "switch literal of wrong width",
+            Error::DuplicateSwitchCase(_) => "duplicate switch case",
+            Error::UnavailableCapability(_) => "capability unavailable in the target environment",
+            Error::UnknownId(_) => "unknown id",
+            Error::UnknownParameter(..) => "unknown parameter",
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        self.describe()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Unsupported(opcode) => write!(f, "{} Op{:?}", self.describe(), opcode),
+            Error::UnavailableCapability(capability) => {
+                write!(f, "{} {:?}", self.describe(), capability)
+            }
+            Error::UnknownId(id) => write!(f, "{} %{}", self.describe(), id),
+            Error::UnknownParameter(function, index) => {
+                write!(f, "{} {} of function %{}", self.describe(), index, function)
+            }
+            Error::SwitchLiteralWidth(label) |
+            Error::DuplicateSwitchCase(label) => {
+                write!(f, "{} in block %{}", self.describe(), label)
+            }
+        }
+    }
+}
+
+pub type Result<T> = result::Result<T, Error>;
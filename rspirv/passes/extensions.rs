@@ -0,0 +1,74 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use analysis;
+use mr;
+
+/// Makes the extensions `module` declares those it needs, as
+/// [`check_extensions`](../analysis/fn.check_extensions.html) tells.
+///
+/// Superfluous declarations are removed, keeping the first of repeated
+/// ones, and the first alternative of each missing requirement is
+/// declared.
+pub fn fix_extensions(module: &mut mr::Module) {
+    module.record_edits(|module| {
+        let report = analysis::check_extensions(module);
+        let mut superfluous = report.superfluous;
+        for index in (0..module.extensions.len()).rev() {
+            let position = match module.extensions[index].operands.first() {
+                Some(mr::Operand::LiteralString(name)) => {
+                    superfluous.iter().position(|s| s == name)
+                }
+                _ => None,
+            };
+            if let Some(position) = position {
+                superfluous.remove(position);
+                module.extensions.remove(index);
+            }
+        }
+        for alternatives in report.missing {
+            if let Some(name) = alternatives.first() {
+                super::add_extension(module, name);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::fix_extensions;
+
+    #[test]
+    fn test_fix_extensions() {
+        let mut b = mr::Builder::new();
+        b.set_infer_requirements(false);
+        b.capability(spirv::Capability::Shader);
+        b.capability(spirv::Capability::VariablePointers);
+        b.extension("SPV_KHR_variable_pointers");
+        b.extension("SPV_KHR_multiview");
+        b.extension("SPV_KHR_variable_pointers");
+        b.ext_inst_import("NonSemantic.DebugPrintf");
+        let mut m = b.module();
+        m.header.as_mut().unwrap().version = 0x0001_0000;
+
+        fix_extensions(&mut m);
+        assert_eq!(m.extension_names(),
+                   vec!["SPV_KHR_variable_pointers", "SPV_KHR_non_semantic_info"]);
+        assert!(m.has_extension("SPV_KHR_non_semantic_info"));
+        assert!(!m.has_extension("SPV_KHR_multiview"));
+    }
+}
@@ -0,0 +1,430 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grammar;
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+use std::mem;
+
+/// How [`flatten_branches`](fn.flatten_branches.html) chooses the branches
+/// to flatten.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlattenOptions {
+    /// The most instructions the sides of a branch may have together, not
+    /// counting their terminators, for it to be flattened.
+    pub max_instructions: usize,
+    /// Whether to also flatten branches whose `OpSelectionMerge` asks for
+    /// `DontFlatten`.
+    pub ignore_dont_flatten: bool,
+}
+
+impl Default for FlattenOptions {
+    fn default() -> FlattenOptions {
+        FlattenOptions {
+            max_instructions: 8,
+            ignore_dont_flatten: false,
+        }
+    }
+}
+
+/// Flattens the short if and if-else regions of `module` into straight
+/// code, choosing the values they merge with `OpSelect`, and returns the
+/// number of regions flattened.
+///
+/// A region is flattened if its header ends with an `OpSelectionMerge`
+/// and an `OpBranchConditional` to blocks, or the merge block, that branch
+/// straight to the merge block, nothing else branches to these blocks or
+/// the merge block, and the blocks only compute values without side
+/// effects, e.g., no loads, stores, calls, or integer divisions, which
+/// could fault or be observed when executed without being taken. Their
+/// instructions move to the header, true side first, which branches to
+/// the merge block, and the `OpPhi`s of the merge block become
+/// `OpSelect`s, so they must have scalar types, or vector types if the
+/// module targets SPIR-V 1.4 or later.
+///
+/// Regions whose sides have more than `options.max_instructions`
+/// instructions are kept, unless their selection control asks for
+/// `Flatten`, and so are those asking for `DontFlatten`, unless
+/// `options.ignore_dont_flatten` is set.
+pub fn flatten_branches(module: &mut mr::Module, options: &FlattenOptions) -> usize {
+    module.record_edits(|module| {
+        // A scalar condition selects between vectors only since SPIR-V 1.4.
+        let vectors = module.header.as_ref().is_some_and(|header| header.version() >= (1, 4));
+        let selectable: BTreeSet<Word> = module.types_global_values
+            .iter()
+            .filter(|inst| {
+                let opcode = inst.class.opcode;
+                matches!(opcode,
+                         spirv::Op::TypeBool | spirv::Op::TypeInt | spirv::Op::TypeFloat) ||
+                (vectors && opcode == spirv::Op::TypeVector)
+            })
+            .filter_map(|inst| inst.result_id)
+            .collect();
+        let glsl = mr::ExtInstRegistry::of(module).id_of(grammar::ExtInstSet::GlslStd450);
+
+        let mut flattened = 0;
+        let mut removed = vec![];
+        for function in &mut module.functions {
+            let regions = find_regions(function, options, &selectable, glsl);
+            flattened += regions.len();
+            removed.extend(flatten(function, &regions));
+        }
+        if !removed.is_empty() {
+            let mut ids = mr::IdAllocator::for_module(module);
+            module.replace_removed_uses(&mut ids, &removed);
+            ids.update_header(module);
+        }
+        flattened
+    })
+}
+
+/// A region to flatten, by block index.
+struct Region {
+    header: usize,
+    merge: usize,
+    /// The condition of the branch, with the labels of the sides.
+    condition: Word,
+    sides: [Word; 2],
+    /// The blocks of the sides, true side first, if not the merge block.
+    arms: Vec<usize>,
+}
+
+fn label(block: &mr::BasicBlock) -> Word {
+    block.label.as_ref().and_then(|l| l.result_id).unwrap_or(0)
+}
+
+fn find_regions(function: &mr::Function,
+                options: &FlattenOptions,
+                selectable: &BTreeSet<Word>,
+                glsl: Option<Word>)
+                -> Vec<Region> {
+    let blocks: BTreeMap<Word, usize> =
+        function.basic_blocks.iter().enumerate().map(|(i, b)| (label(b), i)).collect();
+    // The number of references to each label outside of OpPhis.
+    let mut references: BTreeMap<Word, usize> = BTreeMap::new();
+    for inst in function.basic_blocks.iter().flat_map(|b| &b.instructions) {
+        if inst.class.opcode != spirv::Op::Phi {
+            for id in inst.operands.iter().filter_map(mr::Operand::id) {
+                if blocks.contains_key(&id) {
+                    *references.entry(id).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    let mut regions = vec![];
+    for (header, block) in function.basic_blocks.iter().enumerate() {
+        let n = block.instructions.len();
+        if n < 2 {
+            continue;
+        }
+        let (merge_inst, branch) = (&block.instructions[n - 2], &block.instructions[n - 1]);
+        if merge_inst.class.opcode != spirv::Op::SelectionMerge ||
+           branch.class.opcode != spirv::Op::BranchConditional {
+            continue;
+        }
+        let control = match merge_inst.operands.get(1) {
+            Some(&mr::Operand::SelectionControl(control)) => control,
+            _ => spirv::SelectionControl::NONE,
+        };
+        if control.contains(spirv::SelectionControl::DONT_FLATTEN) &&
+           !options.ignore_dont_flatten {
+            continue;
+        }
+        let ids: Vec<Word> = branch.operands.iter().take(3).filter_map(mr::Operand::id).collect();
+        let merge_label = merge_inst.operands.first().and_then(mr::Operand::id);
+        let (condition, sides, merge_label) = match (&ids[..], merge_label) {
+            (&[condition, t, f], Some(merge)) if t != f => (condition, [t, f], merge),
+            _ => continue,
+        };
+        let merge = match blocks.get(&merge_label) {
+            Some(&merge) => merge,
+            None => continue,
+        };
+
+        let mut arms = vec![];
+        let mut size = 0;
+        let mut expected = 1;
+        let mut pure = true;
+        for &side in &sides {
+            expected += 1;
+            if side == merge_label {
+                continue;
+            }
+            let arm = match blocks.get(&side) {
+                Some(&arm) => &function.basic_blocks[arm],
+                None => {
+                    pure = false;
+                    break;
+                }
+            };
+            let body = &arm.instructions[..arm.instructions.len().saturating_sub(1)];
+            let exit = arm.instructions.last();
+            let to_merge = exit.is_some_and(|inst| {
+                inst.class.opcode == spirv::Op::Branch &&
+                inst.operands.first() == Some(&mr::Operand::IdRef(merge_label))
+            });
+            pure &= to_merge && references.get(&side) == Some(&1) &&
+                    body.iter().all(|inst| is_speculatable(inst, glsl));
+            size += body.len();
+            arms.push(blocks[&side]);
+        }
+        let phis_selectable = function.basic_blocks[merge].instructions
+            .iter()
+            .take_while(|inst| inst.class.opcode == spirv::Op::Phi)
+            .all(|phi| {
+                phi.operands.len() == 4 &&
+                phi.result_type.is_some_and(|ty| selectable.contains(&ty))
+            });
+        let cheap = size <= options.max_instructions ||
+                    control.contains(spirv::SelectionControl::FLATTEN);
+        if pure && cheap && phis_selectable && references.get(&merge_label) == Some(&expected) {
+            regions.push(Region { header, merge, condition, sides, arms });
+        }
+    }
+    regions
+}
+
+/// Returns true if `inst` can be executed without being taken: it has no
+/// side effects and cannot fault. `glsl` is the id of the imported
+/// `GLSL.std.450` set, if any.
+pub(super) fn is_speculatable(inst: &mr::Instruction, glsl: Option<Word>) -> bool {
+    use spirv::Op;
+    match inst.class.opcode {
+        Op::ExtInst => {
+            glsl.is_some() && inst.operands.first().and_then(mr::Operand::id) == glsl &&
+            !writes_through_pointer(inst)
+        }
+        Op::SNegate | Op::FNegate | Op::IAdd | Op::FAdd | Op::ISub | Op::FSub | Op::IMul |
+        Op::FMul | Op::FDiv | Op::FRem | Op::FMod | Op::VectorTimesScalar |
+        Op::MatrixTimesScalar | Op::VectorTimesMatrix | Op::MatrixTimesVector |
+        Op::MatrixTimesMatrix | Op::OuterProduct | Op::Dot | Op::IAddCarry | Op::ISubBorrow |
+        Op::UMulExtended | Op::SMulExtended => true,
+        Op::ShiftRightLogical | Op::ShiftRightArithmetic | Op::ShiftLeftLogical |
+        Op::BitwiseOr | Op::BitwiseXor | Op::BitwiseAnd | Op::Not | Op::BitFieldInsert |
+        Op::BitFieldSExtract | Op::BitFieldUExtract | Op::BitReverse | Op::BitCount => true,
+        Op::Any | Op::All | Op::IsNan | Op::IsInf | Op::IsFinite | Op::IsNormal |
+        Op::SignBitSet | Op::LogicalEqual | Op::LogicalNotEqual | Op::LogicalOr |
+        Op::LogicalAnd | Op::LogicalNot | Op::Select | Op::IEqual | Op::INotEqual |
+        Op::UGreaterThan | Op::SGreaterThan | Op::UGreaterThanEqual | Op::SGreaterThanEqual |
+        Op::ULessThan | Op::SLessThan | Op::ULessThanEqual | Op::SLessThanEqual |
+        Op::FOrdEqual | Op::FUnordEqual | Op::FOrdNotEqual | Op::FUnordNotEqual |
+        Op::FOrdLessThan | Op::FUnordLessThan | Op::FOrdGreaterThan | Op::FUnordGreaterThan |
+        Op::FOrdLessThanEqual | Op::FUnordLessThanEqual | Op::FOrdGreaterThanEqual |
+        Op::FUnordGreaterThanEqual => true,
+        Op::ConvertFToU | Op::ConvertFToS | Op::ConvertSToF | Op::ConvertUToF | Op::UConvert |
+        Op::SConvert | Op::FConvert | Op::QuantizeToF16 | Op::Bitcast => true,
+        Op::VectorExtractDynamic | Op::VectorInsertDynamic | Op::VectorShuffle |
+        Op::CompositeConstruct | Op::CompositeExtract | Op::CompositeInsert | Op::CopyObject |
+        Op::Transpose | Op::Undef => true,
+        _ => false,
+    }
+}
+
+/// Returns true if the `GLSL.std.450` instruction `inst` stores through its
+/// pointer operand, as `Modf` and `Frexp` do.
+fn writes_through_pointer(inst: &mr::Instruction) -> bool {
+    let opcode = match inst.operands.get(1) {
+        Some(&mr::Operand::ExtInstOpcode(_, opcode)) |
+        Some(&mr::Operand::LiteralExtInstInteger(opcode)) => opcode,
+        _ => return true,
+    };
+    opcode == spirv::GLOp::Modf as u32 || opcode == spirv::GLOp::Frexp as u32
+}
+
+/// Flattens `regions` of `function`, and returns the removed labels and
+/// branches.
+fn flatten(function: &mut mr::Function, regions: &[Region]) -> Vec<mr::Instruction> {
+    let mut removed = vec![];
+    let mut dead = BTreeSet::new();
+    for region in regions {
+        let mut hoisted = vec![];
+        for &arm in &region.arms {
+            let block = &mut function.basic_blocks[arm];
+            let mut insts = mem::take(&mut block.instructions);
+            removed.extend(insts.pop());
+            removed.extend(block.label.take());
+            hoisted.extend(insts);
+            dead.insert(arm);
+        }
+        let header_label = label(&function.basic_blocks[region.header]);
+        let header = &mut function.basic_blocks[region.header];
+        header.instructions.pop();
+        let merge_inst = header.instructions.pop();
+        header.instructions.extend(hoisted);
+        let merge_label = merge_inst.and_then(|inst| inst.operands.first()?.id());
+        let to_merge = mr::Operand::IdRef(merge_label.unwrap_or(0));
+        let branch = mr::Instruction::new(spirv::Op::Branch, None, None, vec![to_merge]);
+        header.instructions.push(branch);
+
+        // The parents the merge block is reached from on each side.
+        let parents: Vec<Word> = region.sides
+            .iter()
+            .map(|&side| if Some(side) == merge_label { header_label } else { side })
+            .collect();
+        let merge = &mut function.basic_blocks[region.merge];
+        for inst in &mut merge.instructions {
+            if inst.class.opcode != spirv::Op::Phi {
+                break;
+            }
+            let value_from = |parent: Word| {
+                inst.operands
+                    .chunks(2)
+                    .find(|pair| pair.get(1) == Some(&mr::Operand::IdRef(parent)))
+                    .map(|pair| pair[0].clone())
+            };
+            let (on_true, on_false) = match (value_from(parents[0]), value_from(parents[1])) {
+                (Some(on_true), Some(on_false)) => (on_true, on_false),
+                _ => continue,
+            };
+            *inst = mr::Instruction::new(spirv::Op::Select,
+                                         inst.result_type,
+                                         inst.result_id,
+                                         vec![mr::Operand::IdRef(region.condition),
+                                              on_true,
+                                              on_false]);
+        }
+    }
+    let mut index = 0;
+    function.basic_blocks.retain(|_| {
+        index += 1;
+        !dead.contains(&(index - 1))
+    });
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{flatten_branches, FlattenOptions};
+    use utils::test::opcodes;
+
+    /// Builds a function of if-else, if, and dividing if regions, returning
+    /// it with its condition and the values of the first two regions.
+    fn build() -> (mr::Module, [spirv::Word; 5]) {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let boolean = b.type_bool();
+        let uint = b.type_int(32, 0);
+        let float = b.type_float(32);
+        let fty = b.type_function(void, vec![boolean, uint, float]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let c = b.function_parameter(boolean).unwrap();
+        let i = b.function_parameter(uint).unwrap();
+        let x = b.function_parameter(float).unwrap();
+        let (then, otherwise, first) = (b.id(), b.id(), b.id());
+        let (only, second, divide, third) = (b.id(), b.id(), b.id(), b.id());
+        b.begin_basic_block(None).unwrap();
+        b.selection_merge(first, spirv::SelectionControl::NONE).unwrap();
+        b.branch_conditional(c, then, otherwise, vec![]).unwrap();
+        b.begin_basic_block(Some(then)).unwrap();
+        let square = b.fmul(float, None, x, x).unwrap();
+        b.branch(first).unwrap();
+        b.begin_basic_block(Some(otherwise)).unwrap();
+        let double = b.fadd(float, None, x, x).unwrap();
+        b.branch(first).unwrap();
+        // Also the header of an if without else.
+        b.begin_basic_block(Some(first)).unwrap();
+        let y = b.phi(float, None, vec![(square, then), (double, otherwise)]).unwrap();
+        b.selection_merge(second, spirv::SelectionControl::NONE).unwrap();
+        b.branch_conditional(c, only, second, vec![]).unwrap();
+        b.begin_basic_block(Some(only)).unwrap();
+        let z = b.fadd(float, None, y, x).unwrap();
+        b.branch(second).unwrap();
+        // Integer divisions may fault, so the last if is kept.
+        b.begin_basic_block(Some(second)).unwrap();
+        b.phi(float, None, vec![(z, only), (y, first)]).unwrap();
+        b.selection_merge(third, spirv::SelectionControl::NONE).unwrap();
+        b.branch_conditional(c, divide, third, vec![]).unwrap();
+        b.begin_basic_block(Some(divide)).unwrap();
+        let quotient = b.udiv(uint, None, i, i).unwrap();
+        b.branch(third).unwrap();
+        b.begin_basic_block(Some(third)).unwrap();
+        b.phi(uint, None, vec![(quotient, divide), (i, second)]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        (b.module(), [c, square, double, y, z])
+    }
+
+    #[test]
+    fn test_flatten_branches() {
+        let options = FlattenOptions { max_instructions: 1, ..FlattenOptions::default() };
+        assert_eq!(flatten_branches(&mut build().0, &options), 1);
+
+        let (mut m, [c, square, double, y, z]) = build();
+        assert_eq!(flatten_branches(&mut m, &FlattenOptions::default()), 2);
+        let blocks = &m.functions[0].basic_blocks;
+        assert_eq!(blocks.len(), 5);
+        assert_eq!(opcodes(&blocks[0]),
+                   vec![spirv::Op::FMul, spirv::Op::FAdd, spirv::Op::Branch]);
+        assert_eq!(opcodes(&blocks[1]),
+                   vec![spirv::Op::Select, spirv::Op::FAdd, spirv::Op::Branch]);
+        assert_eq!(blocks[1].instructions[0].operands,
+                   vec![mr::Operand::IdRef(c),
+                        mr::Operand::IdRef(square),
+                        mr::Operand::IdRef(double)]);
+        assert_eq!(blocks[2].instructions[0].operands,
+                   vec![mr::Operand::IdRef(c), mr::Operand::IdRef(z), mr::Operand::IdRef(y)]);
+        assert_eq!(opcodes(&blocks[2]),
+                   vec![spirv::Op::Select,
+                        spirv::Op::SelectionMerge,
+                        spirv::Op::BranchConditional]);
+    }
+
+    #[test]
+    fn test_vector_phis() {
+        let build = |version| {
+            let mut b = mr::Builder::new();
+            let void = b.type_void();
+            let boolean = b.type_bool();
+            let float = b.type_float(32);
+            let vec4 = b.type_vector(float, 4);
+            let fty = b.type_function(void, vec![boolean, vec4]);
+            b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+            let c = b.function_parameter(boolean).unwrap();
+            let v = b.function_parameter(vec4).unwrap();
+            let (header, then, merge) = (b.id(), b.id(), b.id());
+            b.begin_basic_block(Some(header)).unwrap();
+            b.selection_merge(merge, spirv::SelectionControl::NONE).unwrap();
+            b.branch_conditional(c, then, merge, vec![]).unwrap();
+            b.begin_basic_block(Some(then)).unwrap();
+            let sum = b.fadd(vec4, None, v, v).unwrap();
+            b.branch(merge).unwrap();
+            b.begin_basic_block(Some(merge)).unwrap();
+            b.phi(vec4, None, vec![(sum, then), (v, header)]).unwrap();
+            b.ret().unwrap();
+            b.end_function().unwrap();
+            let mut m = b.module();
+            m.header.as_mut().unwrap().version = version;
+            m
+        };
+
+        // Before SPIR-V 1.4, a scalar condition cannot select vectors.
+        let mut m = build(0x0001_0300);
+        assert_eq!(flatten_branches(&mut m, &FlattenOptions::default()), 0);
+        assert_eq!(m.functions[0].basic_blocks.len(), 3);
+
+        let mut m = build(0x0001_0400);
+        assert_eq!(flatten_branches(&mut m, &FlattenOptions::default()), 1);
+        let blocks = &m.functions[0].basic_blocks;
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(opcodes(&blocks[0]), vec![spirv::Op::FAdd, spirv::Op::Branch]);
+        assert_eq!(opcodes(&blocks[1]), vec![spirv::Op::Select, spirv::Op::Return]);
+    }
+}
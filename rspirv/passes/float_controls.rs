@@ -0,0 +1,216 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The floating-point semantics a module declares, which transforms of
+/// floating-point operations must keep.
+///
+/// Execution modes are declared per entry point, but functions may be
+/// shared between entry points, so a mode is taken to apply to all
+/// functions if any entry point declares it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FloatControls {
+    /// Widths declared `DenormPreserve`.
+    denorm_preserve: BTreeSet<u32>,
+    /// Widths declared `DenormFlushToZero`.
+    denorm_flush_to_zero: BTreeSet<u32>,
+    /// Widths declared `SignedZeroInfNanPreserve`.
+    signed_zero_inf_nan_preserve: BTreeSet<u32>,
+    /// Widths declared `RoundingModeRTZ`.
+    rounding_mode_rtz: BTreeSet<u32>,
+    contraction_off: bool,
+    kernel: bool,
+    /// Results decorated `NoContraction`.
+    no_contraction: BTreeSet<Word>,
+    /// `FPFastMathMode` decorations, by result.
+    fast_math: BTreeMap<Word, spirv::FPFastMathMode>,
+}
+
+impl FloatControls {
+    /// Collects the floating-point semantics `module` declares through
+    /// its execution modes, capabilities, and decorations.
+    pub fn new(module: &mr::Module) -> FloatControls {
+        let mut controls = FloatControls::default();
+        for inst in &module.execution_modes {
+            let width = match inst.operands.get(2) {
+                Some(&mr::Operand::LiteralInt32(width)) => width,
+                _ => 0,
+            };
+            let widths = match inst.operands.get(1) {
+                Some(&mr::Operand::ExecutionMode(mode)) => {
+                    match mode {
+                        spirv::ExecutionMode::ContractionOff => {
+                            controls.contraction_off = true;
+                            continue;
+                        }
+                        spirv::ExecutionMode::DenormPreserve => &mut controls.denorm_preserve,
+                        spirv::ExecutionMode::DenormFlushToZero => {
+                            &mut controls.denorm_flush_to_zero
+                        }
+                        spirv::ExecutionMode::SignedZeroInfNanPreserve => {
+                            &mut controls.signed_zero_inf_nan_preserve
+                        }
+                        spirv::ExecutionMode::RoundingModeRTZ => &mut controls.rounding_mode_rtz,
+                        _ => continue,
+                    }
+                }
+                _ => continue,
+            };
+            widths.insert(width);
+        }
+        controls.kernel = module.capabilities
+            .iter()
+            .any(|inst| {
+                inst.operands.first() == Some(&mr::Operand::Capability(spirv::Capability::Kernel))
+            });
+        for inst in &module.annotations {
+            if inst.class.opcode != spirv::Op::Decorate {
+                continue;
+            }
+            let target = match inst.operands.first() {
+                Some(&mr::Operand::IdRef(target)) => target,
+                _ => continue,
+            };
+            match (inst.operands.get(1), inst.operands.get(2)) {
+                (Some(&mr::Operand::Decoration(spirv::Decoration::NoContraction)), _) => {
+                    controls.no_contraction.insert(target);
+                }
+                (Some(&mr::Operand::Decoration(spirv::Decoration::FPFastMathMode)),
+                 Some(&mr::Operand::FPFastMathMode(mode))) => {
+                    *controls.fast_math.entry(target).or_insert(spirv::FPFastMathMode::NONE) |=
+                        mode;
+                }
+                _ => {}
+            }
+        }
+        controls
+    }
+
+    /// Returns true if denormalized values of the given width may be
+    /// flushed to zero, i.e., if some entry point declares
+    /// `DenormFlushToZero` for the width.
+    ///
+    /// Transforms may then not drop operations, such as adding -0.0 or
+    /// multiplying by 1.0, that would flush their operand.
+    pub fn flushes_denorms(&self, width: u32) -> bool {
+        self.denorm_flush_to_zero.contains(&width)
+    }
+
+    /// Returns true if denormalized values of the given width must be
+    /// preserved, i.e., if some entry point declares `DenormPreserve` for
+    /// the width.
+    pub fn preserves_denorms(&self, width: u32) -> bool {
+        self.denorm_preserve.contains(&width)
+    }
+
+    /// Returns true if operations of the given width must round toward
+    /// zero, rather than to the nearest even value, in some entry point.
+    pub fn rounds_toward_zero(&self, width: u32) -> bool {
+        self.rounding_mode_rtz.contains(&width)
+    }
+
+    /// Returns true if the floating-point operation `id` of the given
+    /// width must handle signed zeros, infinities, and NaNs as IEEE 754
+    /// does.
+    ///
+    /// Shaders need not unless some entry point declares
+    /// `SignedZeroInfNanPreserve` for the width. Kernels must unless the
+    /// operation is decorated with the `NotNaN`, `NotInf`, and `NSZ`, or
+    /// `Fast`, modes of `FPFastMathMode`.
+    pub fn preserves_signed_zero_inf_nan(&self, id: Word, width: u32) -> bool {
+        if self.signed_zero_inf_nan_preserve.contains(&width) {
+            return true;
+        }
+        if !self.kernel {
+            return false;
+        }
+        let relaxed = spirv::FPFastMathMode::NOT_NAN | spirv::FPFastMathMode::NOT_INF |
+                      spirv::FPFastMathMode::NSZ;
+        !self.fast_math.get(&id).is_some_and(|&mode| {
+            mode.contains(relaxed) || mode.contains(spirv::FPFastMathMode::FAST)
+        })
+    }
+
+    /// Returns true if the floating-point operation `id` may be contracted
+    /// with others, e.g., into a fused multiply-add.
+    ///
+    /// Contraction is not allowed for operations decorated `NoContraction`,
+    /// nor in modules with a `ContractionOff` execution mode. Kernels
+    /// further require the operation to be decorated with the `Fast` mode
+    /// of `FPFastMathMode`.
+    pub fn may_contract(&self, id: Word) -> bool {
+        if self.contraction_off || self.no_contraction.contains(&id) {
+            return false;
+        }
+        !self.kernel ||
+        self.fast_math.get(&id).is_some_and(|mode| mode.contains(spirv::FPFastMathMode::FAST))
+    }
+
+    /// Returns true if the floating-point operation `id` may be folded or
+    /// simplified at all: operations decorated `NoContraction` are kept as
+    /// they are.
+    pub fn may_fold(&self, id: Word) -> bool {
+        !self.no_contraction.contains(&id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::FloatControls;
+
+    #[test]
+    fn test_float_controls() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        let void = b.type_void();
+        let voidfvoid = b.type_function(void, vec![]);
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::GLCompute, f, "main", vec![]);
+        b.execution_mode(f, spirv::ExecutionMode::DenormFlushToZero, vec![32]);
+        b.execution_mode(f, spirv::ExecutionMode::SignedZeroInfNanPreserve, vec![64]);
+        b.decorate(f, spirv::Decoration::NoContraction, vec![]);
+        let controls = FloatControls::new(&b.module());
+
+        assert!(controls.flushes_denorms(32));
+        assert!(!controls.flushes_denorms(64));
+        assert!(!controls.preserves_denorms(32));
+        assert!(!controls.rounds_toward_zero(32));
+        assert!(!controls.preserves_signed_zero_inf_nan(0, 32));
+        assert!(controls.preserves_signed_zero_inf_nan(0, 64));
+        assert!(!controls.may_contract(f));
+        assert!(!controls.may_fold(f));
+        assert!(controls.may_contract(0));
+
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Kernel);
+        b.decorate(1, spirv::Decoration::FPFastMathMode,
+                   vec![mr::Operand::FPFastMathMode(spirv::FPFastMathMode::FAST)]);
+        let controls = FloatControls::new(&b.module());
+        assert!(controls.may_contract(1));
+        assert!(!controls.may_contract(2));
+        assert!(!controls.preserves_signed_zero_inf_nan(1, 32));
+        assert!(controls.preserves_signed_zero_inf_nan(2, 32));
+    }
+}
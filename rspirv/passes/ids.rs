@@ -0,0 +1,88 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grammar;
+use mr;
+
+use mr::visit::{self, MutVisitor};
+use spirv::Word;
+use std::collections::BTreeMap;
+
+/// Renumbers the ids of `module` from 1, in the order they first appear,
+/// and lowers the bound in its header to match.
+///
+/// Removing code leaves gaps in the ids; consumers sizing tables by the
+/// bound waste less memory on compact ids.
+pub fn compact_ids(module: &mut mr::Module) {
+    module.record_edits(|module| {
+        let mut renumber = Renumber { ids: BTreeMap::new() };
+        visit::walk_module_mut(&mut renumber, module);
+        if let Some(ref mut header) = module.header {
+            header.bound = renumber.ids.len() as Word + 1;
+        }
+    })
+}
+
+struct Renumber {
+    ids: BTreeMap<Word, Word>,
+}
+
+impl Renumber {
+    fn renumber(&mut self, id: &mut Word) {
+        let next = self.ids.len() as Word + 1;
+        *id = *self.ids.entry(*id).or_insert(next);
+    }
+}
+
+impl MutVisitor for Renumber {
+    fn visit_instruction(&mut self, inst: &mut mr::Instruction) {
+        if let Some(ref mut id) = inst.result_id {
+            self.renumber(id);
+        }
+        visit::walk_instruction_mut(self, inst)
+    }
+
+    fn visit_operand_id(&mut self, id: &mut Word, _role: grammar::IdRole) {
+        self.renumber(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::compact_ids;
+
+    #[test]
+    fn test_compact_ids() {
+        let mut b = mr::Builder::new();
+        b.id();
+        let float = b.type_float(32);
+        b.id();
+        let one = b.constant_f32(float, 1.0);
+        b.name(one, "one");
+        let mut m = b.module();
+        assert_eq!(m.header.as_ref().unwrap().bound, 5);
+
+        compact_ids(&mut m);
+        assert_eq!(m.header.as_ref().unwrap().bound, 3);
+        // The name comes first.
+        assert_eq!(m.debugs[0].operands[0], mr::Operand::IdRef(1));
+        assert_eq!(m.types_global_values[0].result_id, Some(2));
+        let constant = &m.types_global_values[1];
+        assert_eq!((constant.result_type, constant.result_id), (Some(2), Some(1)));
+        assert_eq!(constant.class.opcode, spirv::Op::Constant);
+    }
+}
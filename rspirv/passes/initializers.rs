@@ -0,0 +1,131 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::BTreeSet;
+
+/// Removes the initializers of the variables of the storage `classes` in
+/// `module`, storing them explicitly instead, e.g., for environments that
+/// do not allow them; see
+/// [`ValidationRules`](../env/struct.ValidationRules.html).
+///
+/// Module-scope variables are stored to at the start of each entry point
+/// function, in declaration order, and function-scope variables after the
+/// variables at the start of their function. Entry point functions are
+/// expected not to be called by other functions.
+pub fn lower_initializers(module: &mut mr::Module, classes: &[spirv::StorageClass]) {
+    module.record_edits(|module| {
+        let lowered = |inst: &mut mr::Instruction| -> Option<(Word, Word)> {
+            if inst.class.opcode != spirv::Op::Variable || inst.operands.len() != 2 {
+                return None;
+            }
+            match inst.operands[0] {
+                mr::Operand::StorageClass(class) if classes.contains(&class) => (),
+                _ => return None,
+            }
+            let initializer = inst.operands.pop()?.id()?;
+            Some((inst.result_id?, initializer))
+        };
+        let globals: Vec<(Word, Word)> =
+            module.types_global_values.iter_mut().filter_map(lowered).collect();
+        let entry_points: BTreeSet<Word> = module.entry_points
+            .iter()
+            .filter_map(|inst| inst.operands.get(1).and_then(mr::Operand::id))
+            .collect();
+
+        for f in &mut module.functions {
+            let is_entry_point = f.def
+                .as_ref()
+                .and_then(|def| def.result_id)
+                .is_some_and(|id| entry_points.contains(&id));
+            let block = match f.basic_blocks.first_mut() {
+                Some(block) => block,
+                None => continue,
+            };
+            let start = block.instructions
+                .iter()
+                .take_while(|inst| inst.class.opcode == spirv::Op::Variable)
+                .count();
+            let locals: Vec<(Word, Word)> =
+                block.instructions[..start].iter_mut().filter_map(lowered).collect();
+            let stores = if is_entry_point { &globals[..] } else { &[][..] };
+            let stores = stores.iter().chain(&locals).map(|&(variable, initializer)| {
+                let operands = vec![mr::Operand::IdRef(variable), mr::Operand::IdRef(initializer)];
+                mr::Instruction::new(spirv::Op::Store, None, None, operands)
+            });
+            let rest = block.instructions.split_off(start);
+            block.instructions.extend(stores);
+            block.instructions.extend(rest);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::lower_initializers;
+
+    #[test]
+    fn test_lower_initializers() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let one = b.constant_u32(uint, 1);
+        let private = b.type_pointer(None, spirv::StorageClass::Private, uint);
+        let function = b.type_pointer(None, spirv::StorageClass::Function, uint);
+        let output = b.type_pointer(None, spirv::StorageClass::Output, uint);
+        let counter = b.initialized_variable(private, None, spirv::StorageClass::Private, one)
+            .unwrap();
+        let out = b.initialized_variable(output, None, spirv::StorageClass::Output, one).unwrap();
+        match b.initialized_variable(private, None, spirv::StorageClass::Private, uint) {
+            Err(mr::Error::WrongInitializer) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+        let fty = b.type_function(void, vec![]);
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let local = b.initialized_variable(function, None, spirv::StorageClass::Function, one)
+            .unwrap();
+        b.load(uint, None, local, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::GLCompute, main, "main", vec![]);
+        let mut m = b.module();
+
+        lower_initializers(&mut m,
+                           &[spirv::StorageClass::Private, spirv::StorageClass::Function]);
+        let globals = &m.types_global_values;
+        assert!(globals.iter().any(|inst| inst.result_id == Some(counter) &&
+                                          inst.operands.len() == 1));
+        assert!(globals.iter().any(|inst| inst.result_id == Some(out) &&
+                                          inst.operands.len() == 2));
+        let insts = &m.functions[0].basic_blocks[0].instructions;
+        let opcodes: Vec<spirv::Op> = insts.iter().map(|inst| inst.class.opcode).collect();
+        assert_eq!(opcodes,
+                   vec![spirv::Op::Variable,
+                        spirv::Op::Store,
+                        spirv::Op::Store,
+                        spirv::Op::Load,
+                        spirv::Op::Return]);
+        assert_eq!(insts[0].operands.len(), 1);
+        assert_eq!(insts[1].operands,
+                   vec![mr::Operand::IdRef(counter), mr::Operand::IdRef(one)]);
+        assert_eq!(insts[2].operands, vec![mr::Operand::IdRef(local), mr::Operand::IdRef(one)]);
+    }
+}
@@ -0,0 +1,313 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::BTreeMap;
+
+/// Inlines the function calls of `module`, and returns the number of calls
+/// inlined.
+///
+/// A callee is inlined if it calls no function, after its own calls are
+/// inlined, is not `DontInline`, and returns from a single block, where
+/// the caller goes on. Calls in loop headers are kept. The block of the
+/// call is split after it, and the blocks of the callee inserted between
+/// the halves, with new ids; phis of the following blocks take the second
+/// half as parent. The `Function` variables of the callee move to the
+/// first block of the caller, their initializers turning into stores, and
+/// the decorations of its results are copied, but not their names.
+///
+/// Callees are kept even if not called anymore; see
+/// [`eliminate_dead_code`](fn.eliminate_dead_code.html).
+pub fn inline_functions(module: &mut mr::Module) -> usize {
+    module.record_edits(|module| {
+        let mut ids = mr::IdAllocator::for_module(module);
+        let mut calls = vec![];
+        let mut results = vec![];
+        loop {
+            let inlinable: BTreeMap<Word, usize> = module.functions
+                .iter()
+                .enumerate()
+                .filter(|&(_, f)| is_inlinable(f))
+                .filter_map(|(i, f)| Some((f.def.as_ref()?.result_id?, i)))
+                .collect();
+            let before = calls.len();
+            for caller in 0..module.functions.len() {
+                while let Some((block, index, callee)) =
+                          find_call(&module.functions[caller], &inlinable) {
+                    let body = Body::of(&module.functions[callee],
+                                        &module.functions[caller].basic_blocks[block].instructions
+                                            [index],
+                                        &module.annotations,
+                                        &mut ids);
+                    module.annotations.extend(body.annotations);
+                    let function = &mut module.functions[caller];
+                    let call = function.basic_blocks[block].instructions.remove(index);
+                    let rest = function.basic_blocks[block].instructions.split_off(index);
+                    let parent = label(&function.basic_blocks[block]);
+                    let next = ids.id();
+                    function.basic_blocks[block].instructions.push(branch(body.entry));
+                    let mut blocks = body.blocks;
+                    if let Some(last) = blocks.last_mut() {
+                        last.instructions.push(branch(next));
+                    }
+                    let mut continuation = mr::BasicBlock::new();
+                    continuation.label =
+                        Some(mr::Instruction::new(spirv::Op::Label, None, Some(next), vec![]));
+                    continuation.instructions = rest;
+                    blocks.push(continuation);
+                    function.basic_blocks.splice(block + 1..block + 1, blocks);
+                    for inst in function.basic_blocks.iter_mut().flat_map(|b| &mut b.instructions) {
+                        if inst.class.opcode == spirv::Op::Phi {
+                            let ids = inst.operands.iter_mut().filter_map(mr::Operand::id_mut);
+                            for (i, id) in ids.enumerate() {
+                                if i % 2 == 1 && Some(*id) == parent {
+                                    *id = next;
+                                }
+                            }
+                        }
+                    }
+                    let entry = &mut function.basic_blocks[0].instructions;
+                    let at = entry.iter()
+                        .position(|inst| inst.class.opcode != spirv::Op::Variable)
+                        .unwrap_or(entry.len());
+                    entry.splice(at..at, body.variables);
+                    if let (Some(id), Some(value)) = (call.result_id, body.value) {
+                        results.push((id, value));
+                    }
+                    calls.push(call);
+                }
+            }
+            if calls.len() == before {
+                break;
+            }
+        }
+
+        for &(id, value) in &results {
+            module.replace_all_uses(id, value);
+        }
+        module.replace_removed_uses(&mut ids, &calls);
+        ids.update_header(module);
+        calls.len()
+    })
+}
+
+fn label(block: &mr::BasicBlock) -> Option<Word> {
+    block.label.as_ref()?.result_id
+}
+
+fn branch(target: Word) -> mr::Instruction {
+    mr::Instruction::new(spirv::Op::Branch, None, None, vec![mr::Operand::IdRef(target)])
+}
+
+fn is_return(opcode: spirv::Op) -> bool {
+    opcode == spirv::Op::Return || opcode == spirv::Op::ReturnValue
+}
+
+/// Returns true if calls to `function` can be inlined.
+fn is_inlinable(function: &mr::Function) -> bool {
+    let inline = match function.def.as_ref().and_then(|def| def.operands.first()) {
+        Some(&mr::Operand::FunctionControl(control)) => {
+            !control.contains(spirv::FunctionControl::DONT_INLINE)
+        }
+        _ => false,
+    };
+    let insts = || function.basic_blocks.iter().flat_map(|b| &b.instructions);
+    inline && !function.basic_blocks.is_empty() &&
+    !insts().any(|inst| inst.class.opcode == spirv::Op::FunctionCall) &&
+    insts().filter(|inst| is_return(inst.class.opcode)).count() == 1
+}
+
+/// Returns the block and index in it of the first call of `function` to
+/// one of `inlinable`, outside of loop headers, with the index of the
+/// callee.
+fn find_call(function: &mr::Function,
+             inlinable: &BTreeMap<Word, usize>)
+             -> Option<(usize, usize, usize)> {
+    for (b, block) in function.basic_blocks.iter().enumerate() {
+        if block.instructions.iter().any(|inst| inst.class.opcode == spirv::Op::LoopMerge) {
+            continue;
+        }
+        for (i, inst) in block.instructions.iter().enumerate() {
+            if inst.class.opcode != spirv::Op::FunctionCall {
+                continue;
+            }
+            if let Some(&callee) = inst.operands.first().and_then(mr::Operand::id)
+                .and_then(|id| inlinable.get(&id)) {
+                return Some((b, i, callee));
+            }
+        }
+    }
+    None
+}
+
+/// The code of a callee, as inlined at a call.
+struct Body {
+    /// The label of the first block.
+    entry: Word,
+    /// The blocks, the returning one last and without terminator.
+    blocks: Vec<mr::BasicBlock>,
+    /// The variables, without initializers.
+    variables: Vec<mr::Instruction>,
+    /// The returned value.
+    value: Option<Word>,
+    /// The decorations of the results.
+    annotations: Vec<mr::Instruction>,
+}
+
+impl Body {
+    fn of(callee: &mr::Function,
+          call: &mr::Instruction,
+          annotations: &[mr::Instruction],
+          ids: &mut mr::IdAllocator)
+          -> Body {
+        let mut map: BTreeMap<Word, Word> = BTreeMap::new();
+        let args = call.operands.iter().skip(1).filter_map(mr::Operand::id);
+        for (param, arg) in callee.parameters.iter().zip(args) {
+            if let Some(id) = param.result_id {
+                map.insert(id, arg);
+            }
+        }
+        for block in &callee.basic_blocks {
+            for inst in block.label.iter().chain(&block.instructions) {
+                if let Some(id) = inst.result_id {
+                    map.insert(id, ids.id());
+                }
+            }
+        }
+        let copy = |inst: &mr::Instruction| {
+            let mut inst = mr::Instruction::new(inst.class.opcode,
+                                                inst.result_type,
+                                                inst.result_id,
+                                                inst.operands.clone());
+            for id in inst.result_id.iter_mut()
+                .chain(inst.operands.iter_mut().filter_map(mr::Operand::id_mut)) {
+                *id = map.get(id).cloned().unwrap_or(*id);
+            }
+            inst
+        };
+
+        let mut variables = vec![];
+        let mut value = None;
+        let mut returning = None;
+        let mut blocks = vec![];
+        for (b, block) in callee.basic_blocks.iter().enumerate() {
+            let mut copied = mr::BasicBlock::new();
+            copied.label = block.label.as_ref().map(copy);
+            for inst in &block.instructions {
+                let mut inst = copy(inst);
+                if is_return(inst.class.opcode) {
+                    value = inst.operands.first().and_then(mr::Operand::id);
+                    returning = Some(b);
+                } else if b == 0 && inst.class.opcode == spirv::Op::Variable {
+                    if inst.operands.len() > 1 {
+                        let init = inst.operands.split_off(1);
+                        let pointer = mr::Operand::IdRef(inst.result_id.unwrap_or(0));
+                        let mut operands = vec![pointer];
+                        operands.extend(init);
+                        copied.instructions
+                            .push(mr::Instruction::new(spirv::Op::Store, None, None, operands));
+                    }
+                    variables.push(inst);
+                } else {
+                    copied.instructions.push(inst);
+                }
+            }
+            blocks.push(copied);
+        }
+        if let Some(returning) = returning {
+            let last = blocks.remove(returning);
+            blocks.push(last);
+        }
+        let entry = callee.basic_blocks.first().and_then(label).and_then(|l| map.get(&l));
+
+        let params: Vec<Word> = callee.parameters.iter().filter_map(|p| p.result_id).collect();
+        let annotations = annotations.iter()
+            .filter(|inst| {
+                inst.operands.first().and_then(mr::Operand::id).is_some_and(|id| {
+                    map.contains_key(&id) && !params.contains(&id)
+                })
+            })
+            .map(copy)
+            .collect();
+        Body {
+            entry: entry.cloned().unwrap_or(0),
+            blocks,
+            variables,
+            value,
+            annotations,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::inline_functions;
+
+    #[test]
+    fn test_inline_functions() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let ptr = b.type_pointer(None, spirv::StorageClass::Function, float);
+        let one = b.constant_f32(float, 1.0);
+        let square_ty = b.type_function(float, vec![float]);
+        let square = b.begin_function(float, None, spirv::FunctionControl::NONE, square_ty)
+            .unwrap();
+        let x = b.function_parameter(float).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let local = b.variable(ptr, None, spirv::StorageClass::Function, Some(one));
+        let y = b.fmul(float, None, x, x).unwrap();
+        b.decorate(y, spirv::Decoration::NoContraction, vec![]);
+        b.store(local, y, None, vec![]).unwrap();
+        let z = b.load(float, None, local, None, vec![]).unwrap();
+        b.ret_value(z).unwrap();
+        b.end_function().unwrap();
+        let fty = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let result = b.function_call(float, None, square, vec![one]).unwrap();
+        b.fadd(float, None, result, one).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        assert_eq!(inline_functions(&mut m), 1);
+        assert_eq!(m.annotations.len(), 2);
+        let blocks = &m.functions[1].basic_blocks;
+        assert_eq!(blocks.len(), 3);
+        let opcodes: Vec<Vec<spirv::Op>> = blocks.iter()
+            .map(|block| block.instructions.iter().map(|inst| inst.class.opcode).collect())
+            .collect();
+        assert_eq!(opcodes,
+                   vec![vec![spirv::Op::Variable, spirv::Op::Branch],
+                        vec![spirv::Op::Store,
+                             spirv::Op::FMul,
+                             spirv::Op::Store,
+                             spirv::Op::Load,
+                             spirv::Op::Branch],
+                        vec![spirv::Op::FAdd, spirv::Op::Return]]);
+        assert_eq!(blocks[0].instructions[0].operands.len(), 1);
+        let mul = &blocks[1].instructions[1];
+        assert_eq!(mul.operands, vec![mr::Operand::IdRef(one), mr::Operand::IdRef(one)]);
+        assert_eq!(m.annotations[1].operands[0], mr::Operand::IdRef(mul.result_id.unwrap()));
+        let load = blocks[1].instructions[3].result_id.unwrap();
+        assert_eq!(blocks[2].instructions[0].operands[0], mr::Operand::IdRef(load));
+    }
+}
@@ -0,0 +1,655 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeSet, HashMap};
+use super::locations::footprint;
+use super::{find_or_add, memory, Error, Result};
+
+/// An input, output, or resource variable of an entry point.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InterfaceVariable {
+    /// The result id of the `OpVariable`.
+    pub variable: Word,
+    pub storage_class: spirv::StorageClass,
+    /// The `DescriptorSet` decoration on the variable, if any.
+    pub descriptor_set: Option<u32>,
+    /// The `Binding` decoration on the variable, if any.
+    pub binding: Option<u32>,
+    /// The `Location` decoration on the variable, if any.
+    pub location: Option<u32>,
+}
+
+/// What [`prune_interface`](fn.prune_interface.html) removed and kept.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    /// The variables removed, in declaration order.
+    pub removed: Vec<InterfaceVariable>,
+    /// The variables the entry point uses, in declaration order.
+    pub used: Vec<InterfaceVariable>,
+}
+
+/// Removes the `Input`, `Output`, `Uniform`, `UniformConstant`,
+/// `StorageBuffer`, and `PushConstant` variables of `module` the entry
+/// point of the function `entry_point` never statically uses, and reports
+/// them with those it does use.
+///
+/// A variable is used if an instruction of a function the entry point
+/// calls, directly or not, or a global instruction refers to it. Variables
+/// other entry points use are kept too, only not reported as used. The
+/// removed variables are dropped from entry point interfaces, and their
+/// names and decorations removed; their types are left to other passes.
+///
+/// It is an error, reported as `UnknownId`, for `entry_point` to be no
+/// entry point of `module`.
+pub fn prune_interface(module: &mut mr::Module, entry_point: Word) -> Result<PruneReport> {
+    module.record_edits(|module| {
+        let entry = mr::Operand::IdRef(entry_point);
+        if !module.entry_points.iter().any(|inst| inst.operands.get(1) == Some(&entry)) {
+            return Err(Error::UnknownId(entry_point));
+        }
+        let used = used_ids(module, &[entry_point]);
+        let others: Vec<Word> = module
+            .entry_points
+            .iter()
+            .filter_map(|inst| inst.operands.get(1).and_then(mr::Operand::id))
+            .filter(|&f| f != entry_point)
+            .collect();
+        let kept = used_ids(module, &others);
+
+        let mut report = PruneReport::default();
+        let mut removed = BTreeSet::new();
+        for inst in &module.types_global_values {
+            let storage_class = match inst.operands.first() {
+                Some(&mr::Operand::StorageClass(class))
+                    if inst.class.opcode == spirv::Op::Variable => class,
+                _ => continue,
+            };
+            let prunable = matches!(storage_class,
+                                    spirv::StorageClass::Input |
+                                    spirv::StorageClass::Output |
+                                    spirv::StorageClass::Uniform |
+                                    spirv::StorageClass::UniformConstant |
+                                    spirv::StorageClass::StorageBuffer |
+                                    spirv::StorageClass::PushConstant);
+            let variable = match inst.result_id {
+                Some(id) if prunable => id,
+                _ => continue,
+            };
+            let decoration = |wanted| {
+                module.annotations.iter().find_map(|inst| {
+                    match (inst.class.opcode, &inst.operands[..]) {
+                        (spirv::Op::Decorate,
+                         &[mr::Operand::IdRef(target),
+                           mr::Operand::Decoration(decoration),
+                           mr::Operand::LiteralInt32(v)]) => {
+                            Some(v).filter(|_| target == variable && decoration == wanted)
+                        }
+                        _ => None,
+                    }
+                })
+            };
+            let interface = InterfaceVariable {
+                variable,
+                storage_class,
+                descriptor_set: decoration(spirv::Decoration::DescriptorSet),
+                binding: decoration(spirv::Decoration::Binding),
+                location: decoration(spirv::Decoration::Location),
+            };
+            if used.contains(&variable) {
+                report.used.push(interface);
+            } else if !kept.contains(&variable) {
+                removed.insert(variable);
+                report.removed.push(interface);
+            }
+        }
+
+        remove_variables(module, &removed);
+        Ok(report)
+    })
+}
+
+/// Removes the `removed` variables of `module`, with their names and
+/// decorations, from the interfaces of entry points too.
+fn remove_variables(module: &mut mr::Module, removed: &BTreeSet<Word>) {
+    let is_removed = |operand: &mr::Operand| operand.id().is_some_and(|id| removed.contains(&id));
+    module.types_global_values
+        .retain(|inst| !(inst.class.opcode == spirv::Op::Variable &&
+                         inst.result_id.is_some_and(|id| removed.contains(&id))));
+    for inst in &mut module.entry_points {
+        // The interface follows the execution model, function, and name.
+        let mut index = 0;
+        inst.operands.retain(|operand| {
+            index += 1;
+            index <= 3 || !is_removed(operand)
+        });
+    }
+    module.debugs.retain(|inst| {
+        !(inst.class.opcode == spirv::Op::Name && inst.operands.first().is_some_and(is_removed))
+    });
+    module.annotations.retain(|inst| !inst.operands.first().is_some_and(is_removed));
+    for inst in &mut module.annotations {
+        if inst.class.opcode == spirv::Op::GroupDecorate {
+            let mut index = 0;
+            inst.operands.retain(|operand| {
+                index += 1;
+                index == 1 || !is_removed(operand)
+            });
+        }
+    }
+}
+
+/// Removes the outputs of the vertex stage `vs` that the fragment stage
+/// `fs` never reads, and the inputs `fs` never uses, and renumbers the
+/// locations left in both modules from 0, in the same order, returning the
+/// number of outputs removed.
+///
+/// Outputs and inputs are matched by their `Location` decorations and the
+/// locations their types take. An input is used if an instruction of a
+/// function an entry point of `fs` calls, directly or not, or a global
+/// instruction refers to it; unused ones are removed, with their names and
+/// decorations. An output no used input takes a location of becomes a
+/// `Private` variable without decorations, and so do the access chains
+/// into it, leaving the stores to it for other passes; outputs whose
+/// pointers are used other than by loads, stores, and access chains are
+/// kept. Variables without a `Location`, e.g., built-ins, are left alone.
+///
+/// It is an error, reported as `Unsupported(MemberDecorate)`, for either
+/// module to decorate struct members with locations, and as
+/// `Unsupported(Variable)` for a variable with a location to be of a type
+/// whose locations are not known.
+pub fn optimize_interface(vs: &mut mr::Module, fs: &mut mr::Module) -> Result<usize> {
+    let outputs = located_variables(vs, spirv::StorageClass::Output)?;
+    let inputs = located_variables(fs, spirv::StorageClass::Input)?;
+    let entry_points: Vec<Word> = fs.entry_points
+        .iter()
+        .filter_map(|inst| inst.operands.get(1).and_then(mr::Operand::id))
+        .collect();
+    let used = used_ids(fs, &entry_points);
+    let (inputs, unused): (Vec<Located>, Vec<Located>) =
+        inputs.into_iter().partition(|input| used.contains(&input.variable));
+    let escaping = escaping_pointers(vs, &outputs);
+    let (outputs, dead): (Vec<Located>, Vec<Located>) = outputs.into_iter().partition(|output| {
+        escaping.contains(&output.variable) || inputs.iter().any(|input| input.overlaps(output))
+    });
+
+    let live: BTreeSet<u32> = outputs.iter()
+        .chain(&inputs)
+        .flat_map(|v| v.location..v.location + v.locations)
+        .collect();
+    let renumbered: HashMap<u32, u32> =
+        live.iter().enumerate().map(|(new, &old)| (old, new as u32)).collect();
+    let dead: BTreeSet<Word> = dead.iter().map(|output| output.variable).collect();
+    vs.record_edits(|vs| {
+        make_private(vs, &dead);
+        relocate(vs, &outputs, &renumbered);
+    });
+    fs.record_edits(|fs| {
+        remove_variables(fs, &unused.iter().map(|input| input.variable).collect());
+        relocate(fs, &inputs, &renumbered);
+    });
+    Ok(dead.len())
+}
+
+/// A variable with a `Location` decoration.
+struct Located {
+    variable: Word,
+    location: u32,
+    /// The number of locations taken.
+    locations: u32,
+}
+
+impl Located {
+    fn overlaps(&self, other: &Located) -> bool {
+        self.location < other.location + other.locations &&
+        other.location < self.location + self.locations
+    }
+}
+
+/// Returns the variables of the storage class `class` of `module` with a
+/// `Location` decoration.
+fn located_variables(module: &mr::Module, class: spirv::StorageClass) -> Result<Vec<Located>> {
+    let location = mr::Operand::Decoration(spirv::Decoration::Location);
+    let mut locations = HashMap::new();
+    for inst in &module.annotations {
+        match (inst.class.opcode, &inst.operands[..]) {
+            (spirv::Op::MemberDecorate, [_, _, decoration, ..]) if *decoration == location => {
+                return Err(Error::Unsupported(spirv::Op::MemberDecorate));
+            }
+            (spirv::Op::Decorate,
+             &[mr::Operand::IdRef(target), ref decoration, mr::Operand::LiteralInt32(value)])
+                if *decoration == location => {
+                locations.insert(target, value);
+            }
+            _ => (),
+        }
+    }
+    let defs: HashMap<Word, &mr::Instruction> = module.types_global_values
+        .iter()
+        .filter_map(|inst| inst.result_id.map(|id| (id, inst)))
+        .collect();
+    let mut located = vec![];
+    for inst in &module.types_global_values {
+        if inst.class.opcode != spirv::Op::Variable ||
+           inst.operands.first() != Some(&mr::Operand::StorageClass(class)) {
+            continue;
+        }
+        let (variable, location) = match inst.result_id.and_then(|id| {
+            Some((id, *locations.get(&id)?))
+        }) {
+            Some(found) => found,
+            None => continue,
+        };
+        let pointee = inst.result_type
+            .and_then(|ty| defs.get(&ty))
+            .and_then(|pointer| pointer.operands.get(1))
+            .and_then(mr::Operand::id);
+        let footprint = pointee.and_then(|pointee| footprint(&defs, pointee, &[]));
+        let locations = footprint.ok_or(Error::Unsupported(spirv::Op::Variable))?.locations;
+        located.push(Located { variable, location, locations });
+    }
+    Ok(located)
+}
+
+/// Returns the `variables` of `module` whose pointers, or access chains
+/// into them, are used other than by loads, stores, and access chains.
+fn escaping_pointers(module: &mr::Module, variables: &[Located]) -> BTreeSet<Word> {
+    let mut roots: HashMap<Word, Word> =
+        variables.iter().map(|v| (v.variable, v.variable)).collect();
+    let mut escaping = BTreeSet::new();
+    let insts = module.functions.iter().flat_map(|f| &f.basic_blocks).flat_map(|b| {
+        &b.instructions
+    });
+    for inst in insts {
+        for (position, operand) in inst.operands.iter().enumerate() {
+            let root = match operand.id().and_then(|id| roots.get(&id)) {
+                Some(&root) => root,
+                None => continue,
+            };
+            match (inst.class.opcode, position) {
+                (spirv::Op::AccessChain, 0) | (spirv::Op::InBoundsAccessChain, 0) => {
+                    roots.extend(inst.result_id.map(|id| (id, root)));
+                }
+                (spirv::Op::Load, 0) | (spirv::Op::Store, 0) => (),
+                _ => {
+                    escaping.insert(root);
+                }
+            }
+        }
+    }
+    escaping
+}
+
+/// Turns the `dead` output variables of `module`, and the access chains
+/// into them, into `Private` ones without decorations.
+fn make_private(module: &mut mr::Module, dead: &BTreeSet<Word>) {
+    if dead.is_empty() {
+        return;
+    }
+    let pointees: HashMap<Word, Word> = module.types_global_values
+        .iter()
+        .filter(|inst| inst.class.opcode == spirv::Op::TypePointer)
+        .filter_map(|inst| Some((inst.result_id?, inst.operands.get(1)?.id()?)))
+        .collect();
+    let mut ids = mr::IdAllocator::for_module(module);
+    for &variable in dead {
+        let position = module.types_global_values
+            .iter()
+            .position(|inst| inst.result_id == Some(variable));
+        let position = match position {
+            Some(position) => position,
+            None => continue,
+        };
+        let pointee = module.types_global_values[position]
+            .result_type
+            .and_then(|ty| pointees.get(&ty).cloned());
+        if let Some(pointee) = pointee {
+            let ty = private_pointer(module, &mut ids, pointee, position);
+            let inst = module.types_global_values
+                .iter_mut()
+                .find(|inst| inst.result_id == Some(variable))
+                .unwrap();
+            inst.result_type = Some(ty);
+            inst.operands[0] = mr::Operand::StorageClass(spirv::StorageClass::Private);
+        }
+    }
+
+    // Access chains follow what they index into in the blocks.
+    let mut chains = dead.clone();
+    for function in &module.functions {
+        for inst in function.basic_blocks.iter().flat_map(|b| &b.instructions) {
+            let base = inst.operands.first().and_then(mr::Operand::id);
+            if memory::is_access_chain(inst.class.opcode) &&
+               base.is_some_and(|base| chains.contains(&base)) {
+                chains.extend(inst.result_id);
+            }
+        }
+    }
+    let mut retyped = HashMap::new();
+    for f in 0..module.functions.len() {
+        for b in 0..module.functions[f].basic_blocks.len() {
+            for i in 0..module.functions[f].basic_blocks[b].instructions.len() {
+                let inst = &module.functions[f].basic_blocks[b].instructions[i];
+                if !memory::is_access_chain(inst.class.opcode) ||
+                   !inst.result_id.is_some_and(|id| chains.contains(&id)) {
+                    continue;
+                }
+                let pointee = match inst.result_type.and_then(|ty| pointees.get(&ty)) {
+                    Some(&pointee) => pointee,
+                    None => continue,
+                };
+                let ty = *retyped.entry(pointee).or_insert_with(|| {
+                    let operands = vec![mr::Operand::StorageClass(spirv::StorageClass::Private),
+                                        mr::Operand::IdRef(pointee)];
+                    find_or_add(module, &mut ids, spirv::Op::TypePointer, None, operands)
+                });
+                module.functions[f].basic_blocks[b].instructions[i].result_type = Some(ty);
+            }
+        }
+    }
+    ids.update_header(module);
+
+    let is_dead = |operand: &mr::Operand| operand.id().is_some_and(|id| dead.contains(&id));
+    module.annotations.retain(|inst| !inst.operands.first().is_some_and(is_dead));
+    for inst in &mut module.annotations {
+        if inst.class.opcode == spirv::Op::GroupDecorate {
+            let mut index = 0;
+            inst.operands.retain(|operand| {
+                index += 1;
+                index == 1 || !is_dead(operand)
+            });
+        }
+    }
+    // Before SPIR-V 1.4, interfaces only list inputs and outputs.
+    let version = module.header.as_ref().map_or((1, 0), |header| header.version());
+    if version < (1, 4) {
+        for inst in &mut module.entry_points {
+            let mut index = 0;
+            inst.operands.retain(|operand| {
+                index += 1;
+                index <= 3 || !is_dead(operand)
+            });
+        }
+    }
+}
+
+/// Returns the `Private` pointer type to `pointee` of `module`, declared
+/// before the global value at `position`.
+fn private_pointer(module: &mut mr::Module,
+                   ids: &mut mr::IdAllocator,
+                   pointee: Word,
+                   position: usize)
+                   -> Word {
+    let operands = vec![mr::Operand::StorageClass(spirv::StorageClass::Private),
+                        mr::Operand::IdRef(pointee)];
+    let globals = &mut module.types_global_values;
+    let existing = globals.iter().position(|inst| {
+        inst.class.opcode == spirv::Op::TypePointer && inst.operands == operands
+    });
+    match existing {
+        Some(index) if index < position => globals[index].result_id.unwrap_or(0),
+        Some(index) => {
+            // The pointee is declared before the variable pointing to it.
+            let pointer = globals.remove(index);
+            let id = pointer.result_id.unwrap_or(0);
+            globals.insert(position, pointer);
+            id
+        }
+        None => {
+            let id = ids.id();
+            let pointer = mr::Instruction::new(spirv::Op::TypePointer, None, Some(id), operands);
+            globals.insert(position, pointer);
+            id
+        }
+    }
+}
+
+/// Renumbers the `Location` decorations of the `variables` of `module`.
+fn relocate(module: &mut mr::Module, variables: &[Located], renumbered: &HashMap<u32, u32>) {
+    let variables: BTreeSet<Word> = variables.iter().map(|v| v.variable).collect();
+    for inst in &mut module.annotations {
+        if inst.class.opcode != spirv::Op::Decorate {
+            continue;
+        }
+        if let [mr::Operand::IdRef(target),
+                mr::Operand::Decoration(spirv::Decoration::Location),
+                mr::Operand::LiteralInt32(ref mut location)] = inst.operands[..] {
+            if variables.contains(&target) {
+                *location = renumbered.get(location).cloned().unwrap_or(*location);
+            }
+        }
+    }
+}
+
+/// Returns the ids global instructions other than names, decorations, and
+/// entry points of `module` refer to, with those instructions of the given
+/// functions and the functions they call refer to.
+fn used_ids(module: &mr::Module, functions: &[Word]) -> BTreeSet<Word> {
+    let bodies: HashMap<Word, &mr::Function> = module
+        .functions
+        .iter()
+        .filter_map(|f| f.def.as_ref()?.result_id.map(|id| (id, f)))
+        .collect();
+    let mut used: BTreeSet<Word> = module
+        .types_global_values
+        .iter()
+        .flat_map(|inst| inst.operands.iter().filter_map(mr::Operand::id))
+        .collect();
+    let mut visited = BTreeSet::new();
+    let mut stack = functions.to_vec();
+    while let Some(f) = stack.pop() {
+        if !visited.insert(f) {
+            continue;
+        }
+        let function = match bodies.get(&f) {
+            Some(function) => function,
+            None => continue,
+        };
+        for inst in function.basic_blocks.iter().flat_map(|b| b.instructions.iter()) {
+            if inst.class.opcode == spirv::Op::FunctionCall {
+                stack.extend(inst.operands.first().and_then(mr::Operand::id));
+            }
+            used.extend(inst.operands.iter().filter_map(mr::Operand::id));
+        }
+    }
+    used
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{optimize_interface, prune_interface, InterfaceVariable};
+    use passes::Error;
+
+    #[test]
+    fn test_prune_interface() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let ptr_in = b.type_pointer(None, spirv::StorageClass::Input, float);
+        let ptr_out = b.type_pointer(None, spirv::StorageClass::Output, float);
+        let ptr_uniform = b.type_pointer(None, spirv::StorageClass::Uniform, float);
+        let color = b.variable(ptr_in, None, spirv::StorageClass::Input, None);
+        let unused = b.variable(ptr_in, None, spirv::StorageClass::Input, None);
+        let output = b.variable(ptr_out, None, spirv::StorageClass::Output, None);
+        let ubo = b.variable(ptr_uniform, None, spirv::StorageClass::Uniform, None);
+        let other = b.variable(ptr_uniform, None, spirv::StorageClass::Uniform, None);
+        b.name(unused, "unused");
+        b.decorate(unused, spirv::Decoration::Location, vec![mr::Operand::LiteralInt32(1)]);
+        b.decorate(ubo, spirv::Decoration::DescriptorSet, vec![mr::Operand::LiteralInt32(0)]);
+        b.decorate(ubo, spirv::Decoration::Binding, vec![mr::Operand::LiteralInt32(2)]);
+
+        let fty = b.type_function(void, vec![]);
+        let helper = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let value = b.load(float, None, color, None, vec![]).unwrap();
+        b.store(output, value, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.function_call(void, None, helper, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let compute = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.load(float, None, other, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::Fragment, main, "main", vec![color, unused, output]);
+        b.entry_point(spirv::ExecutionModel::GLCompute, compute, "compute", vec![]);
+        let mut m = b.module();
+
+        let report = prune_interface(&mut m, main).unwrap();
+        let variable = |variable, storage_class| {
+            InterfaceVariable {
+                variable,
+                storage_class,
+                descriptor_set: None,
+                binding: None,
+                location: None,
+            }
+        };
+        assert_eq!(report.removed,
+                   vec![InterfaceVariable {
+                            location: Some(1),
+                            ..variable(unused, spirv::StorageClass::Input)
+                        },
+                        InterfaceVariable {
+                            descriptor_set: Some(0),
+                            binding: Some(2),
+                            ..variable(ubo, spirv::StorageClass::Uniform)
+                        }]);
+        assert_eq!(report.used,
+                   vec![variable(color, spirv::StorageClass::Input),
+                        variable(output, spirv::StorageClass::Output)]);
+        assert_eq!(m.entry_points[0].operands[3..].to_vec(),
+                   vec![mr::Operand::IdRef(color), mr::Operand::IdRef(output)]);
+        assert!(m.debugs.is_empty());
+        assert!(m.annotations.is_empty());
+        let variables = m.types_global_values
+            .iter()
+            .filter(|inst| inst.class.opcode == spirv::Op::Variable)
+            .count();
+        assert_eq!(variables, 3);
+
+        assert_eq!(prune_interface(&mut m, value), Err(Error::UnknownId(value)));
+    }
+
+    fn locations(m: &mr::Module) -> Vec<(u32, u32)> {
+        m.annotations
+            .iter()
+            .filter_map(|inst| match inst.operands[..] {
+                [mr::Operand::IdRef(target),
+                 mr::Operand::Decoration(spirv::Decoration::Location),
+                 mr::Operand::LiteralInt32(location)] => Some((target, location)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_optimize_interface() {
+        let location = |b: &mut mr::Builder, variable, location| {
+            b.decorate(variable, spirv::Decoration::Location,
+                       vec![mr::Operand::LiteralInt32(location)]);
+        };
+        // Outputs a vec4 at 0, a vec2 at 1, and a mat2 at 2 and 3.
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let float = b.type_float(32);
+        let vec2 = b.type_vector(float, 2);
+        let vec4 = b.type_vector(float, 4);
+        let mat2 = b.type_matrix(vec2, 2);
+        let zero = b.constant_u32(uint, 0);
+        let one = b.constant_f32(float, 1.0);
+        let out = |b: &mut mr::Builder, ty| {
+            let ptr = b.type_pointer(None, spirv::StorageClass::Output, ty);
+            b.variable(ptr, None, spirv::StorageClass::Output, None)
+        };
+        let (color, uv, transform) = (out(&mut b, vec4), out(&mut b, vec2), out(&mut b, mat2));
+        let ptr_float = b.type_pointer(None, spirv::StorageClass::Output, float);
+        location(&mut b, color, 0);
+        location(&mut b, uv, 1);
+        location(&mut b, transform, 2);
+        b.decorate(uv, spirv::Decoration::NoPerspective, vec![]);
+        let fty = b.type_function(void, vec![]);
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let u = b.access_chain(ptr_float, None, uv, vec![zero]).unwrap();
+        b.store(u, one, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::Vertex, main, "main", vec![color, uv, transform]);
+        let mut vs = b.module();
+        let (uv_type, component) = (vec2, float);
+
+        // Reads the color and the matrix, but not the coordinates.
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let vec2 = b.type_vector(float, 2);
+        let vec4 = b.type_vector(float, 4);
+        let mat2 = b.type_matrix(vec2, 2);
+        let input = |b: &mut mr::Builder, ty| {
+            let ptr = b.type_pointer(None, spirv::StorageClass::Input, ty);
+            b.variable(ptr, None, spirv::StorageClass::Input, None)
+        };
+        let (color_in, uv_in, transform_in) =
+            (input(&mut b, vec4), input(&mut b, vec2), input(&mut b, mat2));
+        location(&mut b, color_in, 0);
+        location(&mut b, uv_in, 1);
+        location(&mut b, transform_in, 2);
+        let fty = b.type_function(void, vec![]);
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.load(vec4, None, color_in, None, vec![]).unwrap();
+        b.load(mat2, None, transform_in, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::Fragment, main, "main",
+                      vec![color_in, uv_in, transform_in]);
+        let mut fs = b.module();
+
+        assert_eq!(optimize_interface(&mut vs, &mut fs), Ok(1));
+        assert_eq!(locations(&vs), vec![(color, 0), (transform, 1)]);
+        assert_eq!(locations(&fs), vec![(color_in, 0), (transform_in, 1)]);
+        assert_eq!(vs.annotations.len(), 2);
+        assert_eq!(vs.entry_points[0].operands[3..].to_vec(),
+                   vec![mr::Operand::IdRef(color), mr::Operand::IdRef(transform)]);
+        assert_eq!(fs.entry_points[0].operands[3..].to_vec(),
+                   vec![mr::Operand::IdRef(color_in), mr::Operand::IdRef(transform_in)]);
+
+        // The coordinates and the access chain into them are private.
+        let private = mr::Operand::StorageClass(spirv::StorageClass::Private);
+        let globals = &vs.types_global_values;
+        let variable = globals.iter().position(|inst| inst.result_id == Some(uv)).unwrap();
+        assert_eq!(globals[variable].operands, vec![private.clone()]);
+        let pointer = globals.iter()
+            .position(|inst| inst.result_id == globals[variable].result_type)
+            .unwrap();
+        assert!(pointer < variable);
+        assert_eq!(globals[pointer].operands, vec![private.clone(), mr::Operand::IdRef(uv_type)]);
+        let chain = &vs.functions[0].basic_blocks[0].instructions[0];
+        let chain_type = globals.iter().find(|inst| inst.result_id == chain.result_type).unwrap();
+        assert_eq!(chain_type.operands, vec![private, mr::Operand::IdRef(component)]);
+        assert_eq!(vs.header.as_ref().unwrap().bound, chain_type.result_id.unwrap() + 1);
+    }
+}
@@ -0,0 +1,406 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use super::{add_capability, find_or_add, Error, Result};
+
+/// Options for [`wrap_kernel`](fn.wrap_kernel.html).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KernelOptions {
+    /// The name of the entry point.
+    pub name: String,
+    /// The size of the workgroups, given by `LocalSize`.
+    pub local_size: [u32; 3],
+}
+
+impl Default for KernelOptions {
+    fn default() -> KernelOptions {
+        KernelOptions {
+            name: String::from("main"),
+            local_size: [1, 1, 1],
+        }
+    }
+}
+
+/// A function the kernel of [`wrap_kernel`](fn.wrap_kernel.html) calls.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KernelCase {
+    /// The selector calling the function.
+    pub selector: u32,
+    /// The result id of the function.
+    pub function: Word,
+    /// The name the function was exported as.
+    pub name: String,
+    /// The offsets in the push constants of the arguments of the function.
+    pub offsets: Vec<u32>,
+}
+
+/// The kernel added by [`wrap_kernel`](fn.wrap_kernel.html).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Kernel {
+    /// The result id of the entry point function.
+    pub entry_point: Word,
+    /// The result id of the `PushConstant` variable.
+    pub push_constants: Word,
+    /// The size in bytes of the push constants.
+    pub size: u32,
+    /// The functions called, in the order of their selectors.
+    pub cases: Vec<KernelCase>,
+}
+
+/// Adds a `GLCompute` entry point to `module` calling one of its exported
+/// functions, chosen by a push constant, and returns a description of it.
+///
+/// The push constants are a `Block` struct laid out by
+/// [`Std430`](../mr/enum.LayoutRule.html): the selector, a 32-bit
+/// unsigned integer, followed by the arguments of each of the functions.
+/// The functions are exported by `LinkageAttributes`, and get selectors
+/// from 0 in the order they are defined; the entry point switches on the
+/// selector, loads the arguments for the case, and calls the function,
+/// ignoring its result. Other selectors do nothing.
+///
+/// The entry point lists the module-scope variables in its interface: all
+/// of them from SPIR-V 1.4, and those of the `Input` and `Output` storage
+/// classes before. The `Shader` capability is declared. The functions are
+/// not exported anymore, and the `Linkage` capability is removed if
+/// nothing else is exported or imported.
+///
+/// It is an error for a function to take an argument without an explicit
+/// layout, e.g., a pointer or a boolean, or anything but scalars and
+/// vectors; the type is given.
+pub fn wrap_kernel(module: &mut mr::Module, options: &KernelOptions) -> Result<Kernel> {
+    module.record_edits(|module| {
+        let exports: Vec<(Word, String)> = module.annotations
+            .iter()
+            .filter_map(|inst| match &inst.operands[..] {
+                [mr::Operand::IdRef(id),
+                 mr::Operand::Decoration(spirv::Decoration::LinkageAttributes),
+                 mr::Operand::LiteralString(name),
+                 mr::Operand::LinkageType(spirv::LinkageType::Export)] => Some((*id, name.clone())),
+                _ => None,
+            })
+            .collect();
+        let types: Vec<(Word, Option<Word>, Vec<Word>)> = module.functions
+            .iter()
+            .filter(|f| !f.basic_blocks.is_empty())
+            .filter_map(|f| {
+                let def = f.def.as_ref()?;
+                let params = f.parameters.iter().filter_map(|p| p.result_type).collect();
+                Some((def.result_id?, def.result_type, params))
+            })
+            .collect();
+
+        // The layout of the push constants.
+        let mut members = vec![];
+        let mut end: u32 = 4;
+        let mut cases = vec![];
+        for (id, result_type, params) in types {
+            let name = match exports.iter().find(|&&(export, _)| export == id) {
+                Some((_, name)) => name.clone(),
+                None => continue,
+            };
+            let mut offsets = vec![];
+            for &ty in &params {
+                let opcode = module.types_global_values
+                    .iter()
+                    .find(|inst| inst.result_id == Some(ty))
+                    .map(|inst| inst.class.opcode)
+                    .ok_or(Error::UnknownId(ty))?;
+                let layout = match opcode {
+                    spirv::Op::TypeInt | spirv::Op::TypeFloat | spirv::Op::TypeVector => {
+                        mr::LayoutRule::Std430.size_and_align(module, ty)
+                    }
+                    _ => None,
+                };
+                let (size, align) = layout.ok_or(Error::Unsupported(opcode))?;
+                let offset = end.div_ceil(align) * align;
+                offsets.push(offset);
+                members.push((ty, offset));
+                end = offset + size;
+            }
+            cases.push((KernelCase {
+                selector: cases.len() as u32,
+                function: id,
+                name,
+                offsets,
+            }, result_type, params));
+        }
+
+        let mut ids = mr::IdAllocator::for_module(module);
+        let uint = find_or_add(module,
+                               &mut ids,
+                               spirv::Op::TypeInt,
+                               None,
+                               vec![mr::Operand::LiteralInt32(32), mr::Operand::LiteralInt32(0)]);
+        let block = ids.id();
+        let mut member_types = vec![mr::Operand::IdRef(uint)];
+        member_types.extend(members.iter().map(|&(ty, _)| mr::Operand::IdRef(ty)));
+        module.types_global_values
+            .push(mr::Instruction::new(spirv::Op::TypeStruct, None, Some(block), member_types));
+        let decoration = |opcode, operands| mr::Instruction::new(opcode, None, None, operands);
+        let block_decoration = mr::Operand::Decoration(spirv::Decoration::Block);
+        module.annotations.push(decoration(spirv::Op::Decorate,
+                                           vec![mr::Operand::IdRef(block), block_decoration]));
+        let offsets = Some(0).into_iter().chain(members.iter().map(|&(_, offset)| offset));
+        for (member, offset) in offsets.enumerate() {
+            let operands = vec![mr::Operand::IdRef(block),
+                                mr::Operand::LiteralInt32(member as u32),
+                                mr::Operand::Decoration(spirv::Decoration::Offset),
+                                mr::Operand::LiteralInt32(offset)];
+            module.annotations.push(decoration(spirv::Op::MemberDecorate, operands));
+        }
+        let push_constant = mr::Operand::StorageClass(spirv::StorageClass::PushConstant);
+        let pointer = |module: &mut mr::Module, ids: &mut mr::IdAllocator, ty| {
+            find_or_add(module,
+                        ids,
+                        spirv::Op::TypePointer,
+                        None,
+                        vec![push_constant.clone(), mr::Operand::IdRef(ty)])
+        };
+        let block_pointer = pointer(module, &mut ids, block);
+        let variable = ids.id();
+        module.types_global_values.push(mr::Instruction::new(spirv::Op::Variable,
+                                                             Some(block_pointer),
+                                                             Some(variable),
+                                                             vec![push_constant.clone()]));
+
+        // Loads the member `index` of the push constants, of type `ty`.
+        let load = |module: &mut mr::Module, ids: &mut mr::IdAllocator, index: u32, ty| {
+            let ptr = pointer(module, ids, ty);
+            let index = find_or_add(module,
+                                    ids,
+                                    spirv::Op::Constant,
+                                    Some(uint),
+                                    vec![mr::Operand::LiteralInt32(index)]);
+            let (chain, value) = (ids.id(), ids.id());
+            let operands = vec![mr::Operand::IdRef(variable), mr::Operand::IdRef(index)];
+            (value,
+             vec![mr::Instruction::new(spirv::Op::AccessChain, Some(ptr), Some(chain), operands),
+                  mr::Instruction::new(spirv::Op::Load,
+                                       Some(ty),
+                                       Some(value),
+                                       vec![mr::Operand::IdRef(chain)])])
+        };
+        let block = |label, instructions| {
+            let mut block = mr::BasicBlock::new();
+            block.label = Some(mr::Instruction::new(spirv::Op::Label, None, Some(label), vec![]));
+            block.instructions = instructions;
+            block
+        };
+
+        let (entry, merge) = (ids.id(), ids.id());
+        let (selector, mut insts) = load(module, &mut ids, 0, uint);
+        let mut switch = vec![mr::Operand::IdRef(selector), mr::Operand::IdRef(merge)];
+        let mut blocks = vec![];
+        let mut member = 1;
+        for &(ref case, result_type, ref params) in &cases {
+            let label = ids.id();
+            switch.push(mr::Operand::LiteralInt32(case.selector));
+            switch.push(mr::Operand::IdRef(label));
+            let mut call = vec![mr::Operand::IdRef(case.function)];
+            let mut body = vec![];
+            for &ty in params {
+                let (value, insts) = load(module, &mut ids, member, ty);
+                member += 1;
+                call.push(mr::Operand::IdRef(value));
+                body.extend(insts);
+            }
+            body.push(mr::Instruction::new(spirv::Op::FunctionCall,
+                                           result_type,
+                                           Some(ids.id()),
+                                           call));
+            body.push(mr::Instruction::new(spirv::Op::Branch,
+                                           None,
+                                           None,
+                                           vec![mr::Operand::IdRef(merge)]));
+            blocks.push(block(label, body));
+        }
+        let control = mr::Operand::SelectionControl(spirv::SelectionControl::NONE);
+        insts.push(mr::Instruction::new(spirv::Op::SelectionMerge,
+                                        None,
+                                        None,
+                                        vec![mr::Operand::IdRef(merge), control]));
+        insts.push(mr::Instruction::new(spirv::Op::Switch, None, None, switch));
+        blocks.insert(0, block(entry, insts));
+        let ret = mr::Instruction::new(spirv::Op::Return, None, None, vec![]);
+        blocks.push(block(merge, vec![ret]));
+
+        let void = find_or_add(module, &mut ids, spirv::Op::TypeVoid, None, vec![]);
+        let fty = find_or_add(module,
+                              &mut ids,
+                              spirv::Op::TypeFunction,
+                              None,
+                              vec![mr::Operand::IdRef(void)]);
+        let entry_point = ids.id();
+        let mut function = mr::Function::new();
+        let control = mr::Operand::FunctionControl(spirv::FunctionControl::NONE);
+        function.def = Some(mr::Instruction::new(spirv::Op::Function,
+                                                 Some(void),
+                                                 Some(entry_point),
+                                                 vec![control, mr::Operand::IdRef(fty)]));
+        function.basic_blocks = blocks;
+        function.end = Some(mr::Instruction::new(spirv::Op::FunctionEnd, None, None, vec![]));
+        module.functions.push(function);
+
+        let all = module.header.as_ref().is_some_and(|header| header.version() >= (1, 4));
+        let interface = module.types_global_values.iter().filter(|inst| {
+            inst.class.opcode == spirv::Op::Variable &&
+            match inst.operands.first() {
+                Some(&mr::Operand::StorageClass(class)) => {
+                    class != spirv::StorageClass::Function &&
+                    (all || class == spirv::StorageClass::Input ||
+                     class == spirv::StorageClass::Output)
+                }
+                _ => false,
+            }
+        });
+        let mut operands = vec![mr::Operand::ExecutionModel(spirv::ExecutionModel::GLCompute),
+                                mr::Operand::IdRef(entry_point),
+                                mr::Operand::from(&options.name[..])];
+        operands.extend(interface.filter_map(|inst| inst.result_id).map(mr::Operand::IdRef));
+        module.entry_points.push(mr::Instruction::new(spirv::Op::EntryPoint, None, None, operands));
+        let mut operands = vec![mr::Operand::IdRef(entry_point),
+                                mr::Operand::ExecutionMode(spirv::ExecutionMode::LocalSize)];
+        operands.extend(options.local_size.iter().map(|&size| mr::Operand::LiteralInt32(size)));
+        module.execution_modes
+            .push(mr::Instruction::new(spirv::Op::ExecutionMode, None, None, operands));
+        add_capability(module, spirv::Capability::Shader);
+
+        let wrapped: Vec<Word> = cases.iter().map(|(case, _, _)| case.function).collect();
+        let linkage = |inst: &mr::Instruction| {
+            inst.operands.get(1) ==
+            Some(&mr::Operand::Decoration(spirv::Decoration::LinkageAttributes))
+        };
+        module.annotations.retain(|inst| {
+            !(linkage(inst) && inst.operands[0].id().is_some_and(|id| wrapped.contains(&id)))
+        });
+        if !module.annotations.iter().any(linkage) {
+            let operand = mr::Operand::Capability(spirv::Capability::Linkage);
+            module.capabilities.retain(|inst| inst.operands.first() != Some(&operand));
+        }
+        ids.update_header(module);
+
+        Ok(Kernel {
+            entry_point,
+            push_constants: variable,
+            size: end,
+            cases: cases.into_iter().map(|(case, _, _)| case).collect(),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{wrap_kernel, KernelOptions};
+    use passes::Error;
+
+    #[test]
+    fn test_wrap_kernel() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Linkage);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let float = b.type_float(32);
+        let vec3 = b.type_vector(float, 3);
+        let clear_ty = b.type_function(void, vec![]);
+        let clear = b.begin_function(void, None, spirv::FunctionControl::NONE, clear_ty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let scale_ty = b.type_function(float, vec![float, vec3]);
+        let scale = b.begin_function(float, None, spirv::FunctionControl::NONE, scale_ty).unwrap();
+        let x = b.function_parameter(float).unwrap();
+        b.function_parameter(vec3).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ret_value(x).unwrap();
+        b.end_function().unwrap();
+        for &(function, name) in &[(clear, "clear"), (scale, "scale")] {
+            b.decorate(function,
+                       spirv::Decoration::LinkageAttributes,
+                       vec![mr::Operand::from(name),
+                            mr::Operand::LinkageType(spirv::LinkageType::Export)]);
+        }
+        let mut m = b.module();
+
+        let options = KernelOptions { local_size: [64, 1, 1], ..Default::default() };
+        let kernel = wrap_kernel(&mut m, &options).unwrap();
+        assert_eq!(kernel.size, 28);
+        let cases: Vec<(u32, u32, &str, Vec<u32>)> = kernel.cases
+            .iter()
+            .map(|case| (case.selector, case.function, &case.name[..], case.offsets.clone()))
+            .collect();
+        assert_eq!(cases, vec![(0, clear, "clear", vec![]), (1, scale, "scale", vec![4, 16])]);
+        let capabilities: Vec<&mr::Operand> =
+            m.capabilities.iter().map(|inst| &inst.operands[0]).collect();
+        assert_eq!(capabilities, vec![&mr::Operand::Capability(spirv::Capability::Shader)]);
+        // The block and the offsets of its members.
+        assert_eq!(m.annotations.len(), 4);
+        assert_eq!(m.entry_points[0].operands,
+                   vec![mr::Operand::ExecutionModel(spirv::ExecutionModel::GLCompute),
+                        mr::Operand::IdRef(kernel.entry_point),
+                        mr::Operand::from("main")]);
+        assert_eq!(m.execution_modes[0].operands[2..],
+                   [mr::Operand::LiteralInt32(64),
+                    mr::Operand::LiteralInt32(1),
+                    mr::Operand::LiteralInt32(1)]);
+
+        let function = m.functions.last().unwrap();
+        let blocks: Vec<Vec<spirv::Op>> = function.basic_blocks
+            .iter()
+            .map(|block| block.instructions.iter().map(|inst| inst.class.opcode).collect())
+            .collect();
+        assert_eq!(blocks,
+                   vec![vec![spirv::Op::AccessChain,
+                             spirv::Op::Load,
+                             spirv::Op::SelectionMerge,
+                             spirv::Op::Switch],
+                        vec![spirv::Op::FunctionCall, spirv::Op::Branch],
+                        vec![spirv::Op::AccessChain,
+                             spirv::Op::Load,
+                             spirv::Op::AccessChain,
+                             spirv::Op::Load,
+                             spirv::Op::FunctionCall,
+                             spirv::Op::Branch],
+                        vec![spirv::Op::Return]]);
+        let load = &function.basic_blocks[0].instructions[1];
+        assert_eq!(load.result_type, Some(uint));
+        let call = &function.basic_blocks[2].instructions[4];
+        assert_eq!(call.result_type, Some(float));
+        assert_eq!(call.operands.len(), 3);
+
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let boolean = b.type_bool();
+        let fty = b.type_function(void, vec![boolean]);
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.function_parameter(boolean).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.decorate(f,
+                   spirv::Decoration::LinkageAttributes,
+                   vec![mr::Operand::from("f"),
+                        mr::Operand::LinkageType(spirv::LinkageType::Export)]);
+        let mut m = b.module();
+        assert_eq!(wrap_kernel(&mut m, &KernelOptions::default()),
+                   Err(Error::Unsupported(spirv::Op::TypeBool)));
+    }
+}
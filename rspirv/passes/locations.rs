@@ -0,0 +1,439 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// The decorations variables sharing a location must agree on.
+const INTERPOLATION: &[spirv::Decoration] = &[
+    spirv::Decoration::Flat,
+    spirv::Decoration::NoPerspective,
+    spirv::Decoration::Centroid,
+    spirv::Decoration::Sample,
+    spirv::Decoration::Patch,
+];
+
+/// What the components of a location are packed with: the opcode, width,
+/// and signedness of the scalar type, and the interpolation decorations.
+type Key = (spirv::Op, u32, u32, Vec<spirv::Decoration>);
+
+/// The locations a value of some type takes.
+#[derive(Clone)]
+pub(super) struct Footprint {
+    pub(super) locations: u32,
+    /// The components taken in each location, and the key of the scalar
+    /// type, if they can be shared with other values.
+    packing: Option<(u32, Key)>,
+}
+
+/// The locations of one storage class in use, with the components in use
+/// in each of them and what those are packed with.
+#[derive(Default)]
+struct Occupancy {
+    locations: BTreeMap<u32, (u8, Option<Key>)>,
+}
+
+impl Occupancy {
+    fn fits(&self, location: u32, component: u32, footprint: &Footprint) -> bool {
+        (location..location + footprint.locations).all(|l| {
+            let (mask, key) = match self.locations.get(&l) {
+                Some(&(mask, ref key)) => (mask, key),
+                None => return true,
+            };
+            match footprint.packing {
+                Some((components, ref packed)) => {
+                    mask & component_mask(component, components) == 0 &&
+                    key.as_ref() == Some(packed)
+                }
+                None => mask == 0,
+            }
+        })
+    }
+
+    fn take(&mut self, location: u32, component: u32, footprint: &Footprint) {
+        for l in location..location + footprint.locations {
+            let entry = self.locations.entry(l).or_insert((0, None));
+            match footprint.packing {
+                Some((components, ref key)) => {
+                    entry.0 |= component_mask(component, components);
+                    entry.1 = Some(key.clone());
+                }
+                None => *entry = (0xf, None),
+            }
+        }
+    }
+
+    /// Returns the first location and component a value of `footprint`
+    /// fits at.
+    fn find(&self, footprint: &Footprint) -> (u32, u32) {
+        let (components, step) = match footprint.packing {
+            // 64-bit components start at components 0 or 2.
+            Some((components, (_, 64, _, _))) => (components, 2),
+            Some((components, _)) => (components, 1),
+            None => (4, 4),
+        };
+        let mut location = 0;
+        loop {
+            let mut component = 0;
+            while component + components <= 4 {
+                if self.fits(location, component, footprint) {
+                    return (location, component);
+                }
+                component += step;
+            }
+            location += 1;
+        }
+    }
+}
+
+fn component_mask(component: u32, components: u32) -> u8 {
+    (((1u32 << components) - 1) << component) as u8
+}
+
+/// Assigns `Location` decorations to the `Input` and `Output` variables of
+/// `module` that have none, in declaration order, and returns the number
+/// of variables decorated.
+///
+/// See [`assign_locations_with`](fn.assign_locations_with.html).
+pub fn assign_locations(module: &mut mr::Module) -> usize {
+    assign_locations_with(module, |_, _| None)
+}
+
+/// Assigns `Location` decorations to the `Input` and `Output` variables of
+/// `module` that have none, and returns the number of variables decorated.
+///
+/// `location` is called with each such variable and its storage class
+/// first, and picks its location if it returns one. The others then take
+/// the first locations not in use in declaration order, scalars, vectors,
+/// and arrays of them packed into the unused components of locations other
+/// variables of the same scalar type and interpolation decorations use,
+/// with a `Component` decoration. Variables of each storage class get
+/// locations of their own, counting the per-vertex array dimension of
+/// tessellation and geometry interfaces out.
+///
+/// Built-in variables, blocks with decorated members, and variables whose
+/// size is not known are left alone.
+pub fn assign_locations_with<F>(module: &mut mr::Module, mut location: F) -> usize
+where
+    F: FnMut(Word, spirv::StorageClass) -> Option<u32>,
+{
+    module.record_edits(|module| {
+        let defs: HashMap<Word, &mr::Instruction> = module
+            .types_global_values
+            .iter()
+            .filter_map(|inst| inst.result_id.map(|id| (id, inst)))
+            .collect();
+        let mut decorations: HashMap<Word, Vec<&[mr::Operand]>> = HashMap::new();
+        let mut decorated_members = BTreeSet::new();
+        for inst in &module.annotations {
+            match (inst.class.opcode, inst.operands.first()) {
+                (spirv::Op::Decorate, Some(&mr::Operand::IdRef(id))) => {
+                    decorations.entry(id).or_default().push(&inst.operands[1..]);
+                }
+                (spirv::Op::MemberDecorate, Some(&mr::Operand::IdRef(id))) => {
+                    let located =
+                        matches!(inst.operands.get(2),
+                                 Some(mr::Operand::Decoration(spirv::Decoration::Location)) |
+                                 Some(mr::Operand::Decoration(spirv::Decoration::BuiltIn)));
+                    if located {
+                        decorated_members.insert(id);
+                    }
+                }
+                _ => (),
+            }
+        }
+        let decoration = |id: Word, wanted: spirv::Decoration| {
+            let operands = decorations.get(&id)?;
+            operands
+                .iter()
+                .find(|operands| operands.first() == Some(&mr::Operand::Decoration(wanted)))
+                .map(|operands| match operands.get(1) {
+                    Some(&mr::Operand::LiteralInt32(v)) => v,
+                    _ => 0,
+                })
+        };
+        let arrayed: BTreeSet<Word> = module
+            .entry_points
+            .iter()
+            .filter(|inst| {
+                matches!(inst.operands.first(),
+                         Some(mr::Operand::ExecutionModel(
+                             spirv::ExecutionModel::TessellationControl)) |
+                         Some(mr::Operand::ExecutionModel(
+                             spirv::ExecutionModel::TessellationEvaluation)) |
+                         Some(mr::Operand::ExecutionModel(spirv::ExecutionModel::Geometry)))
+            })
+            .flat_map(|inst| inst.operands.iter().skip(3).filter_map(mr::Operand::id))
+            .collect();
+        let tessellation_control: BTreeSet<Word> = module
+            .entry_points
+            .iter()
+            .filter(|inst| {
+                inst.operands.first() ==
+                Some(&mr::Operand::ExecutionModel(spirv::ExecutionModel::TessellationControl))
+            })
+            .flat_map(|inst| inst.operands.iter().skip(3).filter_map(mr::Operand::id))
+            .collect();
+
+        let mut occupancies: HashMap<spirv::StorageClass, Occupancy> = HashMap::new();
+        let mut pending = vec![];
+        for inst in &module.types_global_values {
+            let class = match inst.operands.first() {
+                Some(&mr::Operand::StorageClass(class))
+                    if inst.class.opcode == spirv::Op::Variable => class,
+                _ => continue,
+            };
+            if class != spirv::StorageClass::Input && class != spirv::StorageClass::Output {
+                continue;
+            }
+            let (id, ty) = match (inst.result_id, inst.result_type) {
+                (Some(id), Some(ty)) => (id, ty),
+                _ => continue,
+            };
+            let mut ty = match defs.get(&ty).and_then(|pointer| pointer.operands.get(1)) {
+                Some(&mr::Operand::IdRef(pointee)) => pointee,
+                _ => continue,
+            };
+            let per_vertex = match class {
+                spirv::StorageClass::Input => arrayed.contains(&id),
+                _ => tessellation_control.contains(&id),
+            };
+            if per_vertex && decoration(id, spirv::Decoration::Patch).is_none() {
+                match defs.get(&ty) {
+                    Some(array) if array.class.opcode == spirv::Op::TypeArray => {
+                        ty = match array.operands.first() {
+                            Some(&mr::Operand::IdRef(element)) => element,
+                            _ => continue,
+                        };
+                    }
+                    _ => continue,
+                }
+            }
+            if decoration(id, spirv::Decoration::BuiltIn).is_some() ||
+               contains_decorated_members(&defs, &decorated_members, ty)
+            {
+                continue;
+            }
+            let interpolation: Vec<_> = INTERPOLATION
+                .iter()
+                .cloned()
+                .filter(|&d| decoration(id, d).is_some())
+                .collect();
+            let footprint = match footprint(&defs, ty, &interpolation) {
+                Some(footprint) => footprint,
+                None => continue,
+            };
+            let occupancy = occupancies.entry(class).or_default();
+            match decoration(id, spirv::Decoration::Location) {
+                Some(location) => {
+                    let component = decoration(id, spirv::Decoration::Component).unwrap_or(0);
+                    occupancy.take(location, component, &footprint);
+                }
+                None => pending.push((id, class, footprint)),
+            }
+        }
+
+        // Locations picked by the callback are taken before any are found.
+        let mut assigned = vec![];
+        let mut rest = vec![];
+        for (id, class, footprint) in pending {
+            match location(id, class) {
+                Some(location) => {
+                    let unpacked = Footprint { packing: None, ..footprint };
+                    occupancies.entry(class).or_default().take(location, 0, &unpacked);
+                    assigned.push((id, location, 0));
+                }
+                None => rest.push((id, class, footprint)),
+            }
+        }
+        for (id, class, footprint) in rest {
+            let occupancy = occupancies.entry(class).or_default();
+            let (location, component) = occupancy.find(&footprint);
+            occupancy.take(location, component, &footprint);
+            assigned.push((id, location, component));
+        }
+
+        for &(id, location, component) in &assigned {
+            let mut decorate = |decoration, value| {
+                let operands = vec![mr::Operand::IdRef(id),
+                                    mr::Operand::Decoration(decoration),
+                                    mr::Operand::LiteralInt32(value)];
+                module.annotations
+                    .push(mr::Instruction::new(spirv::Op::Decorate, None, None, operands));
+            };
+            decorate(spirv::Decoration::Location, location);
+            if component != 0 {
+                decorate(spirv::Decoration::Component, component);
+            }
+        }
+        assigned.len()
+    })
+}
+
+/// Returns whether the struct type `ty`, or one nested in it, has members
+/// decorated with locations or built-ins.
+fn contains_decorated_members(defs: &HashMap<Word, &mr::Instruction>,
+                              decorated_members: &BTreeSet<Word>,
+                              ty: Word)
+                              -> bool {
+    let inst = match defs.get(&ty) {
+        Some(inst) => inst,
+        None => return false,
+    };
+    match inst.class.opcode {
+        spirv::Op::TypeStruct if decorated_members.contains(&ty) => true,
+        spirv::Op::TypeStruct | spirv::Op::TypeArray => {
+            inst.operands.iter().filter_map(mr::Operand::id).any(|member| {
+                contains_decorated_members(defs, decorated_members, member)
+            })
+        }
+        _ => false,
+    }
+}
+
+/// Returns the locations a value of type `ty` takes, or `None` if it is
+/// not known.
+pub(super) fn footprint(defs: &HashMap<Word, &mr::Instruction>,
+                        ty: Word,
+                        interpolation: &[spirv::Decoration])
+                        -> Option<Footprint> {
+    let inst = defs.get(&ty)?;
+    let literal = |index| match inst.operands.get(index) {
+        Some(&mr::Operand::LiteralInt32(v)) => Some(v),
+        _ => None,
+    };
+    let id = |index| inst.operands.get(index).and_then(mr::Operand::id);
+    match inst.class.opcode {
+        spirv::Op::TypeInt | spirv::Op::TypeFloat => {
+            let width = literal(0)?;
+            let signedness = literal(1).unwrap_or(0);
+            let key = (inst.class.opcode, width, signedness, interpolation.to_vec());
+            let components = if width == 64 { 2 } else { 1 };
+            Some(Footprint { locations: 1, packing: Some((components, key)) })
+        }
+        spirv::Op::TypeVector => {
+            let scalar = footprint(defs, id(0)?, interpolation)?;
+            let (components, key) = scalar.packing?;
+            let components = components * literal(1)?;
+            if components <= 4 {
+                Some(Footprint { locations: 1, packing: Some((components, key)) })
+            } else {
+                Some(Footprint { locations: components.div_ceil(4), packing: None })
+            }
+        }
+        spirv::Op::TypeMatrix => {
+            let column = footprint(defs, id(0)?, interpolation)?;
+            Some(Footprint { locations: column.locations * literal(1)?, packing: None })
+        }
+        spirv::Op::TypeArray => {
+            let element = footprint(defs, id(0)?, interpolation)?;
+            let length = defs.get(&id(1)?)?;
+            let length = match (length.class.opcode, length.operands.first()) {
+                (spirv::Op::Constant, Some(&mr::Operand::LiteralInt32(v))) => v,
+                _ => return None,
+            };
+            Some(Footprint { locations: element.locations * length, ..element })
+        }
+        spirv::Op::TypeStruct => {
+            let mut locations = 0;
+            for member in inst.operands.iter() {
+                locations += footprint(defs, member.id()?, interpolation)?.locations;
+            }
+            Some(Footprint { locations, packing: None })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{assign_locations, assign_locations_with};
+
+    fn decorations(m: &mr::Module) -> Vec<Vec<mr::Operand>> {
+        m.annotations.iter().map(|inst| inst.operands.to_vec()).collect()
+    }
+
+    #[test]
+    fn test_assign_locations() {
+        let mut b = mr::Builder::new();
+        let float = b.type_float(32);
+        let uint = b.type_int(32, 0);
+        let vec2 = b.type_vector(float, 2);
+        let vec4 = b.type_vector(float, 4);
+        let mat2 = b.type_matrix(vec2, 2);
+        let input = |b: &mut mr::Builder, ty| {
+            let ptr = b.type_pointer(None, spirv::StorageClass::Input, ty);
+            b.variable(ptr, None, spirv::StorageClass::Input, None)
+        };
+        let fixed = input(&mut b, vec4);
+        let position = input(&mut b, vec2);
+        let matrix = input(&mut b, mat2);
+        let weight = input(&mut b, float);
+        let index = input(&mut b, uint);
+        let ptr_out = b.type_pointer(None, spirv::StorageClass::Output, vec4);
+        let color = b.variable(ptr_out, None, spirv::StorageClass::Output, None);
+        b.decorate(fixed, spirv::Decoration::Location, vec![mr::Operand::LiteralInt32(0)]);
+        b.decorate(index, spirv::Decoration::Flat, vec![]);
+        let mut m = b.module();
+
+        assert_eq!(assign_locations(&mut m), 5);
+        let location = |id, location| {
+            vec![mr::Operand::IdRef(id),
+                 mr::Operand::Decoration(spirv::Decoration::Location),
+                 mr::Operand::LiteralInt32(location)]
+        };
+        let component = |id, component| {
+            vec![mr::Operand::IdRef(id),
+                 mr::Operand::Decoration(spirv::Decoration::Component),
+                 mr::Operand::LiteralInt32(component)]
+        };
+        // Floats pack after the vec2, the flat uint gets a location of its
+        // own, and outputs start over.
+        assert_eq!(decorations(&m)[2..].to_vec(),
+                   vec![location(position, 1),
+                        location(matrix, 2),
+                        location(weight, 1),
+                        component(weight, 2),
+                        location(index, 4),
+                        location(color, 0)]);
+        assert_eq!(assign_locations(&mut m), 0);
+    }
+
+    #[test]
+    fn test_assign_locations_with() {
+        let mut b = mr::Builder::new();
+        let float = b.type_float(32);
+        let ptr = b.type_pointer(None, spirv::StorageClass::Output, float);
+        let first = b.variable(ptr, None, spirv::StorageClass::Output, None);
+        let second = b.variable(ptr, None, spirv::StorageClass::Output, None);
+        let mut m = b.module();
+
+        let picked = assign_locations_with(&mut m, |id, class| {
+            assert_eq!(class, spirv::StorageClass::Output);
+            if id == second { Some(0) } else { None }
+        });
+        assert_eq!(picked, 2);
+        let decorated: Vec<_> =
+            decorations(&m).into_iter().map(|d| (d[0].clone(), d[2].clone())).collect();
+        assert_eq!(decorated,
+                   vec![(mr::Operand::IdRef(second), mr::Operand::LiteralInt32(0)),
+                        (mr::Operand::IdRef(first), mr::Operand::LiteralInt32(1))]);
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use analysis::Dominators;
+use grammar;
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+use std::mem;
+use super::flatten::is_speculatable;
+
+/// Moves the instructions of the loops of `module` whose operands are
+/// defined outside of the loop to before the loop, and returns the number
+/// of instructions moved.
+///
+/// Loops are the blocks their header dominates, but not their merge block.
+/// Only instructions without side effects that cannot fault are moved, see
+/// [`flatten_branches`](fn.flatten_branches.html), into the one block
+/// outside of the loop branching to its header; loops entered from
+/// several blocks are kept. Inner loops are handled first, so that their
+/// instructions can move out of several loops.
+pub fn hoist_loop_invariants(module: &mut mr::Module) -> usize {
+    module.record_edits(|module| {
+        let glsl = mr::ExtInstRegistry::of(module).id_of(grammar::ExtInstSet::GlslStd450);
+        let mut count = 0;
+        for function in &mut module.functions {
+            let dominators = Dominators::new(function);
+            let indices: BTreeMap<Word, usize> = function.basic_blocks
+                .iter()
+                .enumerate()
+                .filter_map(|(i, b)| Some((b.label.as_ref()?.result_id?, i)))
+                .collect();
+            let order = dominators.reverse_postorder();
+            let loops: Vec<(Word, Word)> = order.iter()
+                .filter_map(|&label| {
+                    let block = &function.basic_blocks[indices[&label]];
+                    let merge = block.instructions
+                        .iter()
+                        .find(|inst| inst.class.opcode == spirv::Op::LoopMerge)?;
+                    Some((label, merge.operands.first()?.id()?))
+                })
+                .collect();
+            for &(header, merge) in loops.iter().rev() {
+                let body: Vec<Word> = order.iter()
+                    .cloned()
+                    .filter(|&b| dominators.dominates(header, b) && !dominators.dominates(merge, b))
+                    .collect();
+                let mut preheaders = dominators.predecessors(header)
+                    .iter()
+                    .filter(|&&p| dominators.is_reachable(p) && !body.contains(&p));
+                let preheader = match (preheaders.next(), preheaders.next()) {
+                    (Some(&preheader), None) => indices[&preheader],
+                    _ => continue,
+                };
+
+                let mut defined: BTreeSet<Word> = body.iter()
+                    .flat_map(|b| &function.basic_blocks[indices[b]].instructions)
+                    .filter_map(|inst| inst.result_id)
+                    .collect();
+                let mut hoisted = vec![];
+                for b in &body {
+                    let block = &mut function.basic_blocks[indices[b]];
+                    let (invariant, rest): (Vec<_>, Vec<_>) = mem::take(&mut block.instructions)
+                        .into_iter()
+                        .partition(|inst| {
+                            let invariant = inst.result_id.is_some() &&
+                                            is_speculatable(inst, glsl) &&
+                                            !inst.operands
+                                .iter()
+                                .filter_map(mr::Operand::id)
+                                .any(|id| defined.contains(&id));
+                            if invariant {
+                                defined.remove(&inst.result_id.unwrap_or(0));
+                            }
+                            invariant
+                        });
+                    block.instructions = rest;
+                    hoisted.extend(invariant);
+                }
+                count += hoisted.len();
+
+                // Before the terminator, and the merge instruction if any.
+                let insts = &mut function.basic_blocks[preheader].instructions;
+                let n = insts.len();
+                let at = if n >= 2 && is_merge(insts[n - 2].class.opcode) {
+                    n - 2
+                } else {
+                    n.saturating_sub(1)
+                };
+                insts.splice(at..at, hoisted);
+            }
+        }
+        count
+    })
+}
+
+pub(super) fn is_merge(opcode: spirv::Op) -> bool {
+    opcode == spirv::Op::SelectionMerge || opcode == spirv::Op::LoopMerge
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::hoist_loop_invariants;
+
+    #[test]
+    fn test_hoist_loop_invariants() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let boolean = b.type_bool();
+        let float = b.type_float(32);
+        let fty = b.type_function(void, vec![boolean, float]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let c = b.function_parameter(boolean).unwrap();
+        let x = b.function_parameter(float).unwrap();
+        let (header, body, latch, merge) = (b.id(), b.id(), b.id(), b.id());
+        let entry = b.begin_basic_block(None).unwrap();
+        b.branch(header).unwrap();
+        b.begin_basic_block(Some(header)).unwrap();
+        let i = b.id();
+        let p = b.phi(float, None, vec![(x, entry), (i, latch)]).unwrap();
+        b.loop_merge(merge, latch, spirv::LoopControl::NONE, vec![]).unwrap();
+        b.branch_conditional(c, body, merge, vec![]).unwrap();
+        b.begin_basic_block(Some(body)).unwrap();
+        let square = b.fmul(float, None, x, x).unwrap();
+        let twice = b.fadd(float, None, square, square).unwrap();
+        // Depends on the phi.
+        b.fadd(float, Some(i), twice, p).unwrap();
+        b.branch(latch).unwrap();
+        b.begin_basic_block(Some(latch)).unwrap();
+        b.branch(header).unwrap();
+        b.begin_basic_block(Some(merge)).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        assert_eq!(hoist_loop_invariants(&mut m), 2);
+        let blocks = &m.functions[0].basic_blocks;
+        let results: Vec<Option<u32>> =
+            blocks[0].instructions.iter().map(|inst| inst.result_id).collect();
+        assert_eq!(results, vec![Some(square), Some(twice), None]);
+        assert_eq!(blocks[2].instructions.len(), 2);
+    }
+}
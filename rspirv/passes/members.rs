@@ -0,0 +1,563 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use reflect::TypeIndex;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+use super::{find_or_add, Error, Result};
+
+/// Where [`eliminate_dead_members`](fn.eliminate_dead_members.html) moved
+/// a struct member.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemberRemap {
+    /// The index of the member before the pass.
+    pub index: u32,
+    /// The `Offset` decoration of the member before the pass, if any.
+    pub offset: Option<u32>,
+    /// The index of the member after the pass, or `None` if it was removed.
+    pub new_index: Option<u32>,
+    /// The `Offset` decoration of the member after the pass, if any.
+    pub new_offset: Option<u32>,
+}
+
+/// How [`eliminate_dead_members`](fn.eliminate_dead_members.html) changed
+/// a struct type, for the host to lay out the data it uploads.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructRemap {
+    /// The result id of the struct type, unchanged by the pass.
+    pub type_id: Word,
+    /// The name given by OpName, if any.
+    pub name: Option<String>,
+    /// The members before the pass, in declaration order.
+    pub members: Vec<MemberRemap>,
+    /// The size of the struct in bytes after the pass, excluding any
+    /// trailing runtime array, if offsets and sizes are known.
+    pub size: Option<u32>,
+}
+
+/// The storage classes of the variables whose struct types are trimmed.
+const BLOCK_CLASSES: &[spirv::StorageClass] = &[spirv::StorageClass::Uniform,
+                                                 spirv::StorageClass::StorageBuffer,
+                                                 spirv::StorageClass::PushConstant];
+
+/// Removes the members of `Block` and `BufferBlock` struct types of
+/// `Uniform`, `StorageBuffer`, and `PushConstant` variables that the
+/// entry point of the function `entry_point` never accesses, and reports
+/// how the members of the changed types moved.
+///
+/// Members are accessed by `OpAccessChain`s and `OpInBoundsAccessChain`s
+/// with constant first indices, and by `OpArrayLength`s, in the functions
+/// the entry point calls, directly or not, on the variables or on copies
+/// of them passed on by `OpCopyObject` or as function arguments. These
+/// are renumbered, member names and decorations are removed or
+/// renumbered, and the remaining members packed closer: a member moves
+/// down to the end of the member before, rounded up to the largest power
+/// of two up to 32 dividing its old offset, unless the size of a member
+/// before it is unknown. Only the types of variables the entry point
+/// accesses no other way, and other entry points never access, which no
+/// other type or value refers to, and of which a member is removed, are
+/// changed, in place.
+///
+/// It is an error, reported as `UnknownId`, for `entry_point` to be no
+/// entry point of `module`.
+pub fn eliminate_dead_members(module: &mut mr::Module,
+                              entry_point: Word)
+                              -> Result<Vec<StructRemap>> {
+    module.record_edits(|module| {
+        let entry = mr::Operand::IdRef(entry_point);
+        if !module.entry_points.iter().any(|inst| inst.operands.get(1) == Some(&entry)) {
+            return Err(Error::UnknownId(entry_point));
+        }
+        let (remaps, roots) = plan(module, entry_point);
+        if !remaps.is_empty() {
+            rewrite(module, &remaps, &roots);
+        }
+        Ok(remaps)
+    })
+}
+
+/// What is known of the members of a candidate struct type.
+#[derive(Default)]
+struct Liveness {
+    /// The members accessed.
+    members: BTreeSet<u32>,
+    /// Whether the struct is used in a way the pass cannot follow.
+    whole: bool,
+}
+
+/// Returns the ids of `entry_point` and the functions it calls, directly or
+/// not.
+fn call_tree(module: &mr::Module, entry_point: Word) -> BTreeSet<Word> {
+    let bodies: BTreeMap<Word, &mr::Function> = module
+        .functions
+        .iter()
+        .filter_map(|f| f.def.as_ref()?.result_id.map(|id| (id, f)))
+        .collect();
+    let mut visited = BTreeSet::new();
+    let mut stack = vec![entry_point];
+    while let Some(f) = stack.pop() {
+        if !visited.insert(f) {
+            continue;
+        }
+        let insts = bodies.get(&f).into_iter().flat_map(|f| &f.basic_blocks).flat_map(|b| {
+            &b.instructions
+        });
+        for inst in insts.filter(|inst| inst.class.opcode == spirv::Op::FunctionCall) {
+            stack.extend(inst.operands.first().and_then(mr::Operand::id));
+        }
+    }
+    visited
+}
+
+/// Returns how to change the struct types, with the pointers to them to
+/// rewrite the accesses through.
+fn plan(module: &mr::Module, entry_point: Word) -> (Vec<StructRemap>, BTreeMap<Word, Word>) {
+    let index = TypeIndex::new(module);
+    // The pointers to candidate structs, with their structs.
+    let mut roots: BTreeMap<Word, Word> = BTreeMap::new();
+    let mut candidates: BTreeMap<Word, Liveness> = BTreeMap::new();
+    for inst in &module.types_global_values {
+        let class = match inst.operands.first() {
+            Some(&mr::Operand::StorageClass(class)) if inst.class.opcode == spirv::Op::Variable => {
+                class
+            }
+            _ => continue,
+        };
+        let pointee = inst.result_type.and_then(|ty| index.pointee_type(ty));
+        let block = pointee.filter(|&ty| {
+            index.def(ty).is_some_and(|def| def.class.opcode == spirv::Op::TypeStruct) &&
+            (index.has_decoration(ty, spirv::Decoration::Block) ||
+             index.has_decoration(ty, spirv::Decoration::BufferBlock))
+        });
+        if let (Some(variable), Some(block)) = (inst.result_id, block) {
+            if BLOCK_CLASSES.contains(&class) {
+                roots.insert(variable, block);
+                candidates.entry(block).or_default();
+            }
+        }
+    }
+    // Structs of other types, constants, or values can't change.
+    for inst in &module.types_global_values {
+        if inst.class.opcode != spirv::Op::TypePointer {
+            for id in inst.operands.iter().filter_map(mr::Operand::id) {
+                if let Some(liveness) = candidates.get_mut(&id) {
+                    liveness.whole = true;
+                }
+            }
+        }
+    }
+
+    // Follow the copies of the roots into functions.
+    let params: BTreeMap<Word, Vec<Word>> = module
+        .functions
+        .iter()
+        .filter_map(|f| {
+            let id = f.def.as_ref()?.result_id?;
+            Some((id, f.parameters.iter().filter_map(|p| p.result_id).collect()))
+        })
+        .collect();
+    let insts = || {
+        module.functions.iter().flat_map(|f| &f.basic_blocks).flat_map(|b| &b.instructions)
+    };
+    loop {
+        let mut copies = vec![];
+        for inst in insts() {
+            let root = |operand: &mr::Operand| operand.id().and_then(|id| roots.get(&id)).cloned();
+            match inst.class.opcode {
+                spirv::Op::CopyObject | spirv::Op::AccessChain | spirv::Op::InBoundsAccessChain
+                    if inst.operands.len() == 1 => {
+                    if let (Some(block), Some(id)) = (root(&inst.operands[0]), inst.result_id) {
+                        copies.push((id, block));
+                    }
+                }
+                spirv::Op::FunctionCall => {
+                    let callee = inst.operands.first().and_then(mr::Operand::id);
+                    let callee = callee.and_then(|f| params.get(&f)).map_or(&[][..], |p| &p[..]);
+                    for (param, arg) in callee.iter().zip(&inst.operands[1..]) {
+                        copies.extend(root(arg).map(|block| (*param, block)));
+                    }
+                }
+                _ => {}
+            }
+        }
+        let before = roots.len();
+        for (id, block) in copies {
+            match roots.get(&id) {
+                // Pointers to several structs, e.g., through a parameter.
+                Some(&other) if other != block => {
+                    for block in &[block, other] {
+                        candidates.get_mut(block).unwrap().whole = true;
+                    }
+                }
+                _ => {
+                    roots.insert(id, block);
+                }
+            }
+        }
+        if roots.len() == before {
+            break;
+        }
+    }
+
+    let constants = int_constants(module);
+    let reachable = call_tree(module, entry_point);
+    for function in &module.functions {
+        let inside = function.def
+            .as_ref()
+            .and_then(|def| def.result_id)
+            .is_some_and(|id| reachable.contains(&id));
+        for inst in function.basic_blocks.iter().flat_map(|b| &b.instructions) {
+            if let Some(liveness) = inst.result_type.and_then(|ty| candidates.get_mut(&ty)) {
+                liveness.whole = true;
+            }
+            for (position, id) in inst.operands.iter().enumerate().filter_map(|(i, o)| {
+                o.id().map(|id| (i, id))
+            }) {
+                if let Some(liveness) = candidates.get_mut(&id) {
+                    liveness.whole = true;
+                }
+                let liveness = match roots.get(&id) {
+                    Some(block) => candidates.get_mut(block).unwrap(),
+                    None => continue,
+                };
+                let member = match (inst.class.opcode, position) {
+                    (spirv::Op::CopyObject, _) => None,
+                    (spirv::Op::FunctionCall, p) if p > 0 => None,
+                    (spirv::Op::AccessChain, 0) |
+                    (spirv::Op::InBoundsAccessChain, 0) if inst.operands.len() == 1 => None,
+                    (spirv::Op::AccessChain, 0) | (spirv::Op::InBoundsAccessChain, 0) => {
+                        let constant = inst.operands[1].id().and_then(|c| constants.get(&c));
+                        Some(constant.map(|&(_, member)| member))
+                    }
+                    (spirv::Op::ArrayLength, 0) => match inst.operands.get(1) {
+                        Some(&mr::Operand::LiteralInt32(member)) => Some(Some(member)),
+                        _ => Some(None),
+                    },
+                    _ => Some(None),
+                };
+                match member {
+                    Some(Some(member)) if inside => {
+                        liveness.members.insert(member);
+                    }
+                    None if inside => {}
+                    _ => liveness.whole = true,
+                }
+            }
+        }
+    }
+
+    let mut remaps = vec![];
+    for (&block, liveness) in &candidates {
+        let layout = match index.struct_layout(block) {
+            Some(layout) if !liveness.whole && !liveness.members.is_empty() => layout,
+            _ => continue,
+        };
+        if liveness.members.len() == layout.members.len() {
+            continue;
+        }
+        let mut members = vec![];
+        let mut ends = vec![];
+        // The end of the last member kept, if known.
+        let mut end = Some(0);
+        for member in &layout.members {
+            if !liveness.members.contains(&member.index) {
+                members.push(MemberRemap {
+                    index: member.index,
+                    offset: member.offset,
+                    new_index: None,
+                    new_offset: None,
+                });
+                continue;
+            }
+            let new_offset = match (end, member.offset) {
+                (Some(end), Some(offset)) => Some(mr::round_up(end, alignment(offset))),
+                _ => member.offset,
+            };
+            let opcode = index.def(member.type_id).map(|ty| ty.class.opcode);
+            end = new_offset.and_then(|offset| Some(offset + member.size?));
+            ends.push(if opcode == Some(spirv::Op::TypeRuntimeArray) { new_offset } else { end });
+            // The size of a struct, array, or matrix leaves out its trailing
+            // padding, which the next member may not be placed in.
+            if matches!(opcode,
+                        Some(spirv::Op::TypeStruct) |
+                        Some(spirv::Op::TypeArray) |
+                        Some(spirv::Op::TypeMatrix)) {
+                end = end.zip(member.offset)
+                    .map(|(end, offset)| mr::round_up(end, alignment(offset)));
+            }
+            members.push(MemberRemap {
+                index: member.index,
+                offset: member.offset,
+                new_index: Some(ends.len() as u32 - 1),
+                new_offset,
+            });
+        }
+        remaps.push(StructRemap {
+            type_id: block,
+            name: layout.name,
+            members,
+            size: ends.iter().try_fold(0, |size, &end| Some(size.max(end?))),
+        });
+    }
+    let changed: BTreeSet<Word> = remaps.iter().map(|remap| remap.type_id).collect();
+    roots.retain(|_, block| changed.contains(block));
+    (remaps, roots)
+}
+
+/// Returns the largest power of two dividing `offset`, up to 32, that of
+/// 64-bit vectors of three or four components.
+fn alignment(offset: u32) -> u32 {
+    if offset == 0 {
+        32
+    } else {
+        (1 << offset.trailing_zeros()).min(32)
+    }
+}
+
+/// Returns the types and values of the 32-bit `OpConstant`s of `module`.
+fn int_constants(module: &mr::Module) -> BTreeMap<Word, (Word, u32)> {
+    module.types_global_values
+        .iter()
+        .filter(|inst| inst.class.opcode == spirv::Op::Constant)
+        .filter_map(|inst| match (inst.result_id, inst.result_type, &inst.operands[..]) {
+            (Some(id), Some(ty), &[mr::Operand::LiteralInt32(value)]) => Some((id, (ty, value))),
+            _ => None,
+        })
+        .collect()
+}
+
+fn rewrite(module: &mut mr::Module, remaps: &[StructRemap], roots: &BTreeMap<Word, Word>) {
+    let members: BTreeMap<(Word, u32), &MemberRemap> = remaps.iter()
+        .flat_map(|remap| remap.members.iter().map(move |m| ((remap.type_id, m.index), m)))
+        .collect();
+    let new_index = |block: Word, member: u32| members.get(&(block, member))?.new_index;
+
+    // Renumber the members accessed.
+    let constants = int_constants(module);
+    let is_access_of_root = |inst: &mr::Instruction| {
+        matches!(inst.class.opcode,
+                 spirv::Op::AccessChain |
+                 spirv::Op::InBoundsAccessChain |
+                 spirv::Op::ArrayLength) &&
+        inst.operands.len() > 1 && inst.operands[0].id().is_some_and(|id| roots.contains_key(&id))
+    };
+    let mut wanted = BTreeSet::new();
+    for function in &module.functions {
+        for inst in function.basic_blocks.iter().flat_map(|b| &b.instructions) {
+            if is_access_of_root(inst) && inst.class.opcode != spirv::Op::ArrayLength {
+                let block = roots[&inst.operands[0].id().unwrap()];
+                if let Some(&(ty, member)) = inst.operands[1].id().and_then(|c| constants.get(&c)) {
+                    wanted.extend(new_index(block, member).map(|member| (ty, member)));
+                }
+            }
+        }
+    }
+    let mut ids = mr::IdAllocator::for_module(module);
+    let renumbered: BTreeMap<(Word, u32), Word> = wanted.into_iter()
+        .map(|(ty, member)| {
+            let operands = vec![mr::Operand::LiteralInt32(member)];
+            ((ty, member),
+             find_or_add(module, &mut ids, spirv::Op::Constant, Some(ty), operands))
+        })
+        .collect();
+    ids.update_header(module);
+    for function in &mut module.functions {
+        for inst in function.basic_blocks.iter_mut().flat_map(|b| &mut b.instructions) {
+            if !is_access_of_root(inst) {
+                continue;
+            }
+            let block = roots[&inst.operands[0].id().unwrap()];
+            match inst.operands[1] {
+                mr::Operand::LiteralInt32(ref mut member) => {
+                    *member = new_index(block, *member).unwrap_or(*member);
+                }
+                mr::Operand::IdRef(ref mut constant) => {
+                    let renumbered = constants.get(constant).and_then(|&(ty, member)| {
+                        renumbered.get(&(ty, new_index(block, member)?))
+                    });
+                    *constant = renumbered.cloned().unwrap_or(*constant);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Drop the members removed from the types, and renumber their names
+    // and decorations.
+    for inst in &mut module.types_global_values {
+        if let Some(remap) = remaps.iter().find(|remap| Some(remap.type_id) == inst.result_id) {
+            let mut kept = remap.members.iter().map(|m| m.new_index.is_some());
+            inst.operands.retain(|_| kept.next().unwrap_or(true));
+        }
+    }
+    let member_of = |inst: &mr::Instruction| match (inst.operands.first(), inst.operands.get(1)) {
+        (Some(&mr::Operand::IdRef(target)), Some(&mr::Operand::LiteralInt32(member))) => {
+            members.get(&(target, member)).cloned()
+        }
+        _ => None,
+    };
+    let is_member_inst = |inst: &mr::Instruction| {
+        matches!(inst.class.opcode,
+                 spirv::Op::MemberName |
+                 spirv::Op::MemberDecorate |
+                 spirv::Op::MemberDecorateString)
+    };
+    for insts in [&mut module.debugs, &mut module.annotations] {
+        insts.retain(|inst| {
+            !is_member_inst(inst) || member_of(inst).is_none_or(|m| m.new_index.is_some())
+        });
+        for inst in insts.iter_mut().filter(|inst| is_member_inst(inst)) {
+            let member = match member_of(inst) {
+                Some(member) => member,
+                None => continue,
+            };
+            inst.operands[1] = mr::Operand::LiteralInt32(member.new_index.unwrap());
+            let is_offset = inst.operands.get(2) ==
+                            Some(&mr::Operand::Decoration(spirv::Decoration::Offset));
+            if let (true, Some(offset)) = (is_offset, member.new_offset) {
+                inst.operands[3] = mr::Operand::LiteralInt32(offset);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{eliminate_dead_members, MemberRemap};
+    use passes::Error;
+
+    #[test]
+    fn test_eliminate_dead_members() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let float = b.type_float(32);
+        let vec4 = b.type_vector(float, 4);
+        let params = b.type_struct(vec![float, vec4, float, float]);
+        b.decorate(params, spirv::Decoration::Block, vec![]);
+        for (member, (name, offset)) in [("a", 0), ("b", 16), ("c", 32), ("d", 36)]
+            .iter()
+            .enumerate() {
+            b.member_name(params, member as u32, *name);
+            b.member_decorate(params,
+                              member as u32,
+                              spirv::Decoration::Offset,
+                              vec![mr::Operand::LiteralInt32(*offset)]);
+        }
+        let ptr_params = b.type_pointer(None, spirv::StorageClass::Uniform, params);
+        let ptr_float = b.type_pointer(None, spirv::StorageClass::Uniform, float);
+        let two = b.constant_u32(uint, 2);
+        let three = b.constant_u32(uint, 3);
+        let uniforms = b.variable(ptr_params, None, spirv::StorageClass::Uniform, None);
+        let helper_ty = b.type_function(void, vec![ptr_params]);
+        let helper = b.begin_function(void, None, spirv::FunctionControl::NONE, helper_ty).unwrap();
+        let p = b.function_parameter(ptr_params).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let d = b.access_chain(ptr_float, None, p, vec![three]).unwrap();
+        b.load(float, None, d, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let main_ty = b.type_function(void, vec![]);
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, main_ty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let c = b.access_chain(ptr_float, None, uniforms, vec![two]).unwrap();
+        b.load(float, None, c, None, vec![]).unwrap();
+        b.function_call(void, None, helper, vec![uniforms]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::GLCompute, main, "main", vec![]);
+        let mut m = b.module();
+
+        assert_eq!(eliminate_dead_members(&mut m, helper), Err(Error::UnknownId(helper)));
+        let remaps = eliminate_dead_members(&mut m, main).unwrap();
+        assert_eq!(remaps.len(), 1);
+        assert_eq!(remaps[0].type_id, params);
+        assert_eq!(remaps[0].size, Some(8));
+        let moved = |index, offset, new_index, new_offset| {
+            MemberRemap { index, offset: Some(offset), new_index, new_offset }
+        };
+        assert_eq!(remaps[0].members,
+                   vec![moved(0, 0, None, None),
+                        moved(1, 16, None, None),
+                        moved(2, 32, Some(0), Some(0)),
+                        moved(3, 36, Some(1), Some(4))]);
+
+        let def = |id| m.types_global_values.iter().find(|inst| inst.result_id == Some(id));
+        assert_eq!(def(params).unwrap().operands,
+                   vec![mr::Operand::IdRef(float), mr::Operand::IdRef(float)]);
+        let index_of = |function: usize| {
+            let chain = &m.functions[function].basic_blocks[0].instructions[0];
+            def(chain.operands[1].id().unwrap()).unwrap().operands.clone()
+        };
+        assert_eq!(index_of(0), vec![mr::Operand::LiteralInt32(1)]);
+        assert_eq!(index_of(1), vec![mr::Operand::LiteralInt32(0)]);
+        let names: Vec<&mr::Operand> = m.debugs.iter().map(|inst| &inst.operands[1]).collect();
+        assert_eq!(names, vec![&mr::Operand::LiteralInt32(0), &mr::Operand::LiteralInt32(1)]);
+        let offsets: Vec<&mr::Operand> = m.annotations
+            .iter()
+            .filter(|inst| inst.class.opcode == spirv::Op::MemberDecorate)
+            .map(|inst| &inst.operands[3])
+            .collect();
+        assert_eq!(offsets, vec![&mr::Operand::LiteralInt32(0), &mr::Operand::LiteralInt32(4)]);
+
+        // The float after a kept struct stays out of its trailing padding.
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let float = b.type_float(32);
+        let vec3 = b.type_vector(float, 3);
+        let inner = b.type_struct(vec![vec3]);
+        b.member_decorate(inner, 0, spirv::Decoration::Offset, vec![mr::Operand::LiteralInt32(0)]);
+        let params = b.type_struct(vec![float, inner, float, float]);
+        b.decorate(params, spirv::Decoration::Block, vec![]);
+        for (member, offset) in [0, 16, 32, 36].iter().enumerate() {
+            b.member_decorate(params,
+                              member as u32,
+                              spirv::Decoration::Offset,
+                              vec![mr::Operand::LiteralInt32(*offset)]);
+        }
+        let ptr_params = b.type_pointer(None, spirv::StorageClass::Uniform, params);
+        let ptr_inner = b.type_pointer(None, spirv::StorageClass::Uniform, inner);
+        let ptr_float = b.type_pointer(None, spirv::StorageClass::Uniform, float);
+        let one = b.constant_u32(uint, 1);
+        let three = b.constant_u32(uint, 3);
+        let uniforms = b.variable(ptr_params, None, spirv::StorageClass::Uniform, None);
+        let main_ty = b.type_function(void, vec![]);
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, main_ty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let s = b.access_chain(ptr_inner, None, uniforms, vec![one]).unwrap();
+        b.load(inner, None, s, None, vec![]).unwrap();
+        let d = b.access_chain(ptr_float, None, uniforms, vec![three]).unwrap();
+        b.load(float, None, d, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::GLCompute, main, "main", vec![]);
+        let mut m = b.module();
+
+        let remaps = eliminate_dead_members(&mut m, main).unwrap();
+        assert_eq!(remaps.len(), 1);
+        assert_eq!(remaps[0].size, Some(20));
+        assert_eq!(remaps[0].members,
+                   vec![moved(0, 0, None, None),
+                        moved(1, 16, Some(0), Some(0)),
+                        moved(2, 32, None, None),
+                        moved(3, 36, Some(1), Some(16))]);
+    }
+}
@@ -0,0 +1,301 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use analysis::Dominators;
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The value each variable is known to hold.
+type Values = BTreeMap<Word, Word>;
+
+/// Removes redundant loads and dead stores of `Function` and `Private`
+/// variables in the functions of `module`.
+///
+/// Only variables whose pointers are used for nothing but non-volatile
+/// `OpLoad`s, `OpStore`s, and access chains are considered. For those:
+///
+/// * A load of a whole variable whose value is known, from an earlier
+///   store or load, is replaced by that value. Values are known after a
+///   store or load in the same block, and at the start of a block if all
+///   its predecessors come earlier in the dominator tree order and agree
+///   on the value. Stores through access chains make the value of the
+///   variable unknown, and so do function calls for `Private` variables.
+/// * A store is dead, and removed, if a later store in the same block
+///   overwrites the whole variable before anything may read it.
+/// * All stores to variables never read anywhere in the module are
+///   removed.
+pub fn eliminate_loads_stores(module: &mut mr::Module) {
+    module.record_edits(|module| {
+        let private: BTreeSet<Word> = module.types_global_values
+            .iter()
+            .filter(|inst| is_variable(inst, spirv::StorageClass::Private))
+            .filter_map(|inst| inst.result_id)
+            .collect();
+        let mut candidates = private.clone();
+        for f in &module.functions {
+            candidates.extend(f.basic_blocks
+                .iter()
+                .take(1)
+                .flat_map(|b| &b.instructions)
+                .filter(|inst| is_variable(inst, spirv::StorageClass::Function))
+                .filter_map(|inst| inst.result_id));
+        }
+
+        // The variables pointers are derived from through access chains.
+        let mut roots: BTreeMap<Word, Word> = candidates.iter().map(|&v| (v, v)).collect();
+        let insts = || {
+            module.functions.iter().flat_map(|f| &f.basic_blocks).flat_map(|b| &b.instructions)
+        };
+        for inst in insts() {
+            if is_access_chain(inst.class.opcode) {
+                let base = inst.operands.first().and_then(mr::Operand::id);
+                let root = base.and_then(|b| roots.get(&b).cloned());
+                if let (Some(id), Some(root)) = (inst.result_id, root) {
+                    roots.insert(id, root);
+                }
+            }
+        }
+        let mut read = BTreeSet::new();
+        for inst in insts() {
+            for (index, operand) in inst.operands.iter().enumerate() {
+                let root = match operand.id().and_then(|id| roots.get(&id)) {
+                    Some(&root) => root,
+                    None => continue,
+                };
+                if inst.class.opcode == spirv::Op::Load && index == 0 {
+                    read.insert(root);
+                }
+                if !is_plain_access(inst, index) {
+                    candidates.remove(&root);
+                }
+            }
+        }
+        roots.retain(|_, root| candidates.contains(root));
+
+        let mut replaced = BTreeMap::new();
+        for f in &mut module.functions {
+            let dead = eliminate_in(f, &roots, &private, &read, &mut replaced);
+            for (block, indices) in dead {
+                let mut index = 0;
+                f.basic_blocks[block].instructions.retain(|_| {
+                    index += 1;
+                    !indices.contains(&(index - 1))
+                });
+            }
+        }
+        for (load, value) in replaced {
+            module.replace_all_uses(load, value);
+        }
+    })
+}
+
+/// Finds the redundant loads and dead stores of `function`, recording the
+/// values loads are replaced with in `replaced`, and returns the
+/// instructions to remove by block.
+fn eliminate_in(function: &mr::Function,
+                roots: &BTreeMap<Word, Word>,
+                private: &BTreeSet<Word>,
+                read: &BTreeSet<Word>,
+                replaced: &mut BTreeMap<Word, Word>)
+                -> BTreeMap<usize, BTreeSet<usize>> {
+    let dominators = Dominators::new(function);
+    let blocks: BTreeMap<Word, usize> = function.basic_blocks
+        .iter()
+        .enumerate()
+        .filter_map(|(i, b)| b.label.as_ref().and_then(|l| l.result_id).map(|l| (l, i)))
+        .collect();
+    let mut exits: BTreeMap<Word, Values> = BTreeMap::new();
+    let mut dead: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+
+    for &label in dominators.reverse_postorder() {
+        let block = blocks[&label];
+        let preds: Vec<Word> = dominators.predecessors(label)
+            .iter()
+            .cloned()
+            .filter(|&p| dominators.is_reachable(p))
+            .collect();
+        let mut values = Values::new();
+        if preds.iter().all(|p| exits.contains_key(p)) {
+            if let Some((first, rest)) = preds.split_first() {
+                values = exits[first].clone();
+                for pred in rest {
+                    let other = &exits[pred];
+                    values.retain(|var, value| other.get(var) == Some(value));
+                }
+            }
+        }
+        // Stores no one may have read yet, by variable.
+        let mut pending: BTreeMap<Word, Vec<usize>> = BTreeMap::new();
+
+        for (index, inst) in function.basic_blocks[block].instructions.iter().enumerate() {
+            let pointer = inst.operands.first().and_then(mr::Operand::id);
+            let root = pointer.and_then(|p| roots.get(&p).cloned());
+            match (inst.class.opcode, pointer, root) {
+                (spirv::Op::Load, Some(pointer), Some(root)) => {
+                    pending.remove(&root);
+                    if pointer != root {
+                        continue;
+                    }
+                    if let Some(result) = inst.result_id {
+                        match values.get(&root) {
+                            Some(&value) => {
+                                replaced.insert(result, value);
+                                dead.entry(block).or_default().insert(index);
+                            }
+                            None => {
+                                values.insert(root, result);
+                            }
+                        }
+                    }
+                }
+                (spirv::Op::Store, Some(pointer), Some(root)) => {
+                    if !read.contains(&root) {
+                        dead.entry(block).or_default().insert(index);
+                        continue;
+                    }
+                    let stores = pending.entry(root).or_default();
+                    if pointer == root {
+                        dead.entry(block).or_default().extend(stores.drain(..));
+                        let value = inst.operands.get(1).and_then(mr::Operand::id);
+                        match value.map(|v| replaced.get(&v).cloned().unwrap_or(v)) {
+                            Some(value) => values.insert(root, value),
+                            None => values.remove(&root),
+                        };
+                    } else {
+                        values.remove(&root);
+                    }
+                    stores.push(index);
+                }
+                (spirv::Op::FunctionCall, ..) => {
+                    values.retain(|var, _| !private.contains(var));
+                    pending.retain(|var, _| !private.contains(var));
+                }
+                _ => {}
+            }
+        }
+        exits.insert(label, values);
+    }
+    dead
+}
+
+pub(super) fn is_variable(inst: &mr::Instruction, storage_class: spirv::StorageClass) -> bool {
+    inst.class.opcode == spirv::Op::Variable &&
+    inst.operands.first() == Some(&mr::Operand::StorageClass(storage_class))
+}
+
+pub(super) fn is_access_chain(opcode: spirv::Op) -> bool {
+    opcode == spirv::Op::AccessChain || opcode == spirv::Op::InBoundsAccessChain
+}
+
+/// Returns true if the pointer at operand `index` of `inst` is only read
+/// or written through by it, or used as the base of an access chain.
+pub(super) fn is_plain_access(inst: &mr::Instruction, index: usize) -> bool {
+    let volatile = inst.operands.iter().any(|operand| match *operand {
+        mr::Operand::MemoryAccess(access) => access.contains(spirv::MemoryAccess::VOLATILE),
+        _ => false,
+    });
+    match inst.class.opcode {
+        spirv::Op::Load | spirv::Op::Store => index == 0 && !volatile,
+        opcode => index == 0 && is_access_chain(opcode),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::eliminate_loads_stores;
+    use utils::test::opcodes;
+
+    #[test]
+    fn test_eliminate_loads_stores() {
+        let mut b = mr::Builder::new();
+        let uint = b.type_int(32, 0);
+        let ptr = b.type_pointer(None, spirv::StorageClass::Function, uint);
+        let one = b.constant_u32(uint, 1);
+        let two = b.constant_u32(uint, 2);
+        let fty = b.type_function(uint, vec![]);
+        b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let var = b.variable(ptr, None, spirv::StorageClass::Function, None);
+        let next = b.id();
+        b.store(var, one, None, vec![]).unwrap();
+        b.store(var, two, None, vec![]).unwrap();
+        let first = b.load(uint, None, var, None, vec![]).unwrap();
+        b.branch(next).unwrap();
+        b.begin_basic_block(Some(next)).unwrap();
+        // Known from the single predecessor.
+        let second = b.load(uint, None, var, None, vec![]).unwrap();
+        let sum = b.iadd(uint, None, first, second).unwrap();
+        b.ret_value(sum).unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        eliminate_loads_stores(&mut m);
+
+        let blocks = &m.functions[0].basic_blocks;
+        assert_eq!(opcodes(&blocks[0]),
+                   vec![spirv::Op::Variable, spirv::Op::Store, spirv::Op::Branch]);
+        assert_eq!(blocks[0].instructions[1].operands[1], mr::Operand::IdRef(two));
+        assert_eq!(opcodes(&blocks[1]), vec![spirv::Op::IAdd, spirv::Op::ReturnValue]);
+        assert_eq!(blocks[1].instructions[0].operands,
+                   vec![mr::Operand::IdRef(two), mr::Operand::IdRef(two)]);
+    }
+
+    #[test]
+    fn test_partial_and_escaping_accesses() {
+        let mut b = mr::Builder::new();
+        let uint = b.type_int(32, 0);
+        let vec2 = b.type_vector(uint, 2);
+        let ptr = b.type_pointer(None, spirv::StorageClass::Function, uint);
+        let vptr = b.type_pointer(None, spirv::StorageClass::Function, vec2);
+        let pptr = b.type_pointer(None, spirv::StorageClass::Private, uint);
+        let zero = b.constant_u32(uint, 0);
+        let whole = b.constant_composite(vec2, vec![zero, zero]);
+        let global = b.variable(pptr, None, spirv::StorageClass::Private, None);
+        let void = b.type_void();
+        let voidfvoid = b.type_function(void, vec![]);
+        let callee = b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let fty = b.type_function(uint, vec![]);
+        b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let v = b.variable(vptr, None, spirv::StorageClass::Function, None);
+        let volatile = b.variable(ptr, None, spirv::StorageClass::Function, None);
+        b.store(v, whole, None, vec![]).unwrap();
+        let part = b.access_chain(ptr, None, v, vec![zero]).unwrap();
+        b.store(part, zero, None, vec![]).unwrap();
+        // The partial store makes the value unknown.
+        b.load(vec2, None, v, None, vec![]).unwrap();
+        b.store(volatile, zero, Some(spirv::MemoryAccess::VOLATILE), vec![]).unwrap();
+        b.load(uint, None, volatile, None, vec![]).unwrap();
+        b.store(global, zero, None, vec![]).unwrap();
+        b.function_call(void, None, callee, vec![]).unwrap();
+        // The call may have changed the private variable.
+        let l = b.load(uint, None, global, None, vec![]).unwrap();
+        b.ret_value(l).unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+        let before = opcodes(&m.functions[1].basic_blocks[0]);
+
+        eliminate_loads_stores(&mut m);
+        assert_eq!(opcodes(&m.functions[1].basic_blocks[0]), before);
+    }
+}
@@ -0,0 +1,841 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+use super::{add_capability, add_extension, remove_extension, Error, Result};
+
+const VULKAN_MEMORY_MODEL: &str = "SPV_KHR_vulkan_memory_model";
+
+/// The memory semantics bits only the Vulkan memory model has.
+fn vulkan_semantics() -> spirv::MemorySemantics {
+    spirv::MemorySemantics::OUTPUT_MEMORY | spirv::MemorySemantics::MAKE_AVAILABLE |
+    spirv::MemorySemantics::MAKE_VISIBLE | spirv::MemorySemantics::VOLATILE
+}
+
+/// Whether accesses through a pointer are coherent and volatile.
+#[derive(Clone, Copy, Debug, Default)]
+struct Qualifiers {
+    coherent: bool,
+    volatile: bool,
+}
+
+impl Qualifiers {
+    fn of(decoration: spirv::Decoration) -> Qualifiers {
+        Qualifiers {
+            coherent: decoration == spirv::Decoration::Coherent,
+            volatile: decoration == spirv::Decoration::Volatile,
+        }
+    }
+
+    fn union(self, other: Qualifiers) -> Qualifiers {
+        Qualifiers {
+            coherent: self.coherent || other.coherent,
+            volatile: self.volatile || other.volatile,
+        }
+    }
+}
+
+/// A pointer traced to the memory object declaration it points into.
+#[derive(Clone, Copy, Debug)]
+struct Pointer {
+    /// The variable or function parameter declaring the memory object.
+    root: Word,
+    /// The qualifiers of the declaration and of the struct members
+    /// selected on the way to the pointer.
+    qualifiers: Qualifiers,
+}
+
+/// Rewrites `module` from the `GLSL450` or `Simple` memory model to the
+/// `Vulkan` one.
+///
+/// Accesses through pointers into memory object declarations or struct
+/// members decorated `Coherent` become non-private and make the pointer
+/// available or visible at queue family scope; those through `Volatile`
+/// ones become volatile, or for atomics get the `Volatile` semantics bit.
+/// `OpImageRead`, `OpImageSparseRead`, and `OpImageWrite` on images loaded
+/// through such pointers get the matching image operands. The decorations
+/// are then removed, as the Vulkan memory model disallows them, and device
+/// memory scopes become queue family ones.
+///
+/// Pointers are traced through access chains and `OpCopyObject` within
+/// functions; function parameters only carry their own decorations. It is
+/// an error for the module to use another memory model, or memory scopes
+/// or semantics that are not constants. Modules already using the Vulkan
+/// memory model are left as they are.
+pub fn upgrade_memory_model(module: &mut mr::Module) -> Result<()> {
+    module.record_edits(|module| {
+        match memory_model(module) {
+            Some(spirv::MemoryModel::Vulkan) => return Ok(()),
+            Some(spirv::MemoryModel::GLSL450) | Some(spirv::MemoryModel::Simple) => {}
+            _ => return Err(Error::Unsupported(spirv::Op::MemoryModel)),
+        }
+        let mut constants = Constants::new(module);
+        let (pointers, loads) = trace_pointers(module, &constants);
+        let mut edits = vec![];
+        for (f, function) in module.functions.iter().enumerate() {
+            for (b, block) in function.basic_blocks.iter().enumerate() {
+                for (i, inst) in block.instructions.iter().enumerate() {
+                    if let Some(operands) = upgrade(inst, &pointers, &loads, &mut constants)? {
+                        edits.push((f, b, i, operands));
+                    }
+                }
+            }
+        }
+
+        for (f, b, i, operands) in edits {
+            module.functions[f].basic_blocks[b].instructions[i].operands = operands;
+        }
+        module.annotations.retain(|inst| {
+            !matches!(decoration(inst),
+                      Some(spirv::Decoration::Coherent) | Some(spirv::Decoration::Volatile))
+        });
+        set_memory_model(module, spirv::MemoryModel::Vulkan);
+        add_capability(module, spirv::Capability::VulkanMemoryModel);
+        if module.header.as_ref().is_none_or(|header| header.version() < (1, 5)) {
+            add_extension(module, VULKAN_MEMORY_MODEL);
+        }
+        constants.add_to(module);
+        Ok(())
+    })
+}
+
+/// Rewrites `module` from the `Vulkan` memory model to the `GLSL450` one,
+/// the inverse of [`upgrade_memory_model`](fn.upgrade_memory_model.html).
+///
+/// The `GLSL450` memory model has no per-access availability and
+/// visibility operations, so memory object declarations accessed with
+/// them are decorated `Coherent` instead, and those accessed by volatile
+/// atomics or texel accesses `Volatile`. The Vulkan memory model bits are
+/// removed from memory, image, and semantics operands, queue family memory
+/// scopes become device ones, and the capabilities and extension of the
+/// Vulkan memory model are removed.
+///
+/// It is an error for an access needing a decoration to go through a
+/// pointer not traced to its declaration, for the module to use another
+/// memory model, or memory scopes or semantics that are not constants.
+/// Modules already using the `GLSL450` or `Simple` memory model are left
+/// as they are.
+pub fn downgrade_memory_model(module: &mut mr::Module) -> Result<()> {
+    module.record_edits(|module| {
+        match memory_model(module) {
+            Some(spirv::MemoryModel::GLSL450) | Some(spirv::MemoryModel::Simple) => return Ok(()),
+            Some(spirv::MemoryModel::Vulkan) => {}
+            _ => return Err(Error::Unsupported(spirv::Op::MemoryModel)),
+        }
+        let mut constants = Constants::new(module);
+        let (pointers, loads) = trace_pointers(module, &constants);
+        let mut decorations = Decorations::default();
+        let mut edits = vec![];
+        for (f, function) in module.functions.iter().enumerate() {
+            for (b, block) in function.basic_blocks.iter().enumerate() {
+                for (i, inst) in block.instructions.iter().enumerate() {
+                    let operands =
+                        downgrade(inst, &pointers, &loads, &mut constants, &mut decorations)?;
+                    if let Some(operands) = operands {
+                        edits.push((f, b, i, operands));
+                    }
+                }
+            }
+        }
+
+        for (f, b, i, operands) in edits {
+            module.functions[f].basic_blocks[b].instructions[i].operands = operands;
+        }
+        let required = decorations.coherent
+            .into_iter()
+            .map(|root| (root, spirv::Decoration::Coherent))
+            .chain(decorations.volatile
+                .into_iter()
+                .map(|root| (root, spirv::Decoration::Volatile)));
+        for (root, decoration) in required {
+            let decorated = module.annotations.iter().any(|inst| {
+                inst.class.opcode == spirv::Op::Decorate &&
+                inst.operands
+                    .starts_with(&[mr::Operand::IdRef(root), mr::Operand::Decoration(decoration)])
+            });
+            if !decorated {
+                let operands = vec![mr::Operand::IdRef(root), mr::Operand::Decoration(decoration)];
+                module.annotations
+                    .push(mr::Instruction::new(spirv::Op::Decorate, None, None, operands));
+            }
+        }
+        set_memory_model(module, spirv::MemoryModel::GLSL450);
+        module.capabilities.retain(|inst| {
+            !matches!(inst.operands.first(),
+                      Some(mr::Operand::Capability(spirv::Capability::VulkanMemoryModel)) |
+                      Some(mr::Operand::Capability(
+                          spirv::Capability::VulkanMemoryModelDeviceScope)))
+        });
+        remove_extension(module, VULKAN_MEMORY_MODEL);
+        constants.add_to(module);
+        Ok(())
+    })
+}
+
+fn memory_model(module: &mr::Module) -> Option<spirv::MemoryModel> {
+    match module.memory_model.as_ref().and_then(|inst| inst.operands.get(1)) {
+        Some(&mr::Operand::MemoryModel(model)) => Some(model),
+        _ => None,
+    }
+}
+
+fn set_memory_model(module: &mut mr::Module, model: spirv::MemoryModel) {
+    if let Some(ref mut inst) = module.memory_model {
+        inst.operands[1] = mr::Operand::MemoryModel(model);
+    }
+}
+
+/// Returns the decoration `inst` applies, if it decorates an id or a
+/// struct member.
+fn decoration(inst: &mr::Instruction) -> Option<spirv::Decoration> {
+    let index = match inst.class.opcode {
+        spirv::Op::Decorate | spirv::Op::DecorateString => 1,
+        spirv::Op::MemberDecorate | spirv::Op::MemberDecorateString => 2,
+        _ => return None,
+    };
+    match inst.operands.get(index) {
+        Some(&mr::Operand::Decoration(decoration)) => Some(decoration),
+        _ => None,
+    }
+}
+
+fn is_atomic(opcode: spirv::Op) -> bool {
+    matches!(opcode,
+             spirv::Op::AtomicLoad |
+             spirv::Op::AtomicStore |
+             spirv::Op::AtomicExchange |
+             spirv::Op::AtomicCompareExchange |
+             spirv::Op::AtomicCompareExchangeWeak |
+             spirv::Op::AtomicIIncrement |
+             spirv::Op::AtomicIDecrement |
+             spirv::Op::AtomicIAdd |
+             spirv::Op::AtomicISub |
+             spirv::Op::AtomicSMin |
+             spirv::Op::AtomicUMin |
+             spirv::Op::AtomicSMax |
+             spirv::Op::AtomicUMax |
+             spirv::Op::AtomicAnd |
+             spirv::Op::AtomicOr |
+             spirv::Op::AtomicXor |
+             spirv::Op::AtomicFlagTestAndSet |
+             spirv::Op::AtomicFlagClear)
+}
+
+/// The 32-bit integer constants of a module, with those to add to it.
+struct Constants {
+    values: BTreeMap<Word, u32>,
+    ids: BTreeMap<u32, Word>,
+    uint: Option<Word>,
+    allocator: mr::IdAllocator,
+    added: Vec<mr::Instruction>,
+}
+
+impl Constants {
+    fn new(module: &mr::Module) -> Constants {
+        let mut ints = BTreeSet::new();
+        let mut uint = None;
+        let mut values = BTreeMap::new();
+        let mut ids = BTreeMap::new();
+        for inst in &module.types_global_values {
+            let id = match inst.result_id {
+                Some(id) => id,
+                None => continue,
+            };
+            match (inst.class.opcode, &inst.operands[..]) {
+                (spirv::Op::TypeInt, &[mr::Operand::LiteralInt32(32), ref signedness]) => {
+                    ints.insert(id);
+                    if *signedness == mr::Operand::LiteralInt32(0) && uint.is_none() {
+                        uint = Some(id);
+                    }
+                }
+                (spirv::Op::Constant, &[mr::Operand::LiteralInt32(value)])
+                    if inst.result_type.is_some_and(|ty| ints.contains(&ty)) => {
+                    values.insert(id, value);
+                    ids.entry(value).or_insert(id);
+                }
+                _ => {}
+            }
+        }
+        Constants {
+            values,
+            ids,
+            uint,
+            allocator: mr::IdAllocator::for_module(module),
+            added: vec![],
+        }
+    }
+
+    fn value(&self, id: Word) -> Option<u32> {
+        self.values.get(&id).cloned()
+    }
+
+    /// Returns a constant of the given value, to be added if there is none.
+    fn id(&mut self, value: u32) -> Word {
+        if let Some(&id) = self.ids.get(&value) {
+            return id;
+        }
+        let uint = match self.uint {
+            Some(uint) => uint,
+            None => {
+                let uint = self.allocator.id();
+                self.added.push(mr::Instruction::new(spirv::Op::TypeInt,
+                                                     None,
+                                                     Some(uint),
+                                                     vec![mr::Operand::LiteralInt32(32),
+                                                          mr::Operand::LiteralInt32(0)]));
+                self.uint = Some(uint);
+                uint
+            }
+        };
+        let id = self.allocator.id();
+        self.added.push(mr::Instruction::new(spirv::Op::Constant,
+                                             Some(uint),
+                                             Some(id),
+                                             vec![mr::Operand::LiteralInt32(value)]));
+        self.values.insert(id, value);
+        self.ids.insert(value, id);
+        id
+    }
+
+    /// Adds the new constants to `module`, accounting for their ids.
+    fn add_to(self, module: &mut mr::Module) {
+        module.types_global_values.extend(self.added);
+        self.allocator.update_header(module);
+    }
+}
+
+/// The types access chains select from.
+enum Aggregate {
+    Struct(Vec<Word>),
+    Array(Word),
+}
+
+/// Traces the pointers of `module` through access chains and copies to
+/// the memory object declarations they point into, and the values loaded
+/// through them to their pointers.
+fn trace_pointers(module: &mr::Module,
+                  constants: &Constants)
+                  -> (BTreeMap<Word, Pointer>, BTreeMap<Word, Pointer>) {
+    let mut decorations: BTreeMap<(Word, Option<u32>), Qualifiers> = BTreeMap::new();
+    for inst in &module.annotations {
+        let key = match inst.operands[..] {
+            [mr::Operand::IdRef(target), mr::Operand::Decoration(_), ..] => (target, None),
+            [mr::Operand::IdRef(target), mr::Operand::LiteralInt32(member), ..] => {
+                (target, Some(member))
+            }
+            _ => continue,
+        };
+        if let Some(decoration) = decoration(inst) {
+            let qualifiers = decorations.entry(key).or_default();
+            *qualifiers = qualifiers.union(Qualifiers::of(decoration));
+        }
+    }
+    let qualifiers = |id: Word, member: Option<u32>| {
+        decorations.get(&(id, member)).cloned().unwrap_or_default()
+    };
+
+    let mut pointees = BTreeMap::new();
+    let mut aggregates = BTreeMap::new();
+    for inst in &module.types_global_values {
+        let id = match inst.result_id {
+            Some(id) => id,
+            None => continue,
+        };
+        let ids = || inst.operands.iter().filter_map(|operand| match *operand {
+            mr::Operand::IdRef(id) => Some(id),
+            _ => None,
+        });
+        match inst.class.opcode {
+            spirv::Op::TypePointer => {
+                if let Some(pointee) = ids().next() {
+                    pointees.insert(id, pointee);
+                }
+            }
+            spirv::Op::TypeStruct => {
+                aggregates.insert(id, Aggregate::Struct(ids().collect()));
+            }
+            spirv::Op::TypeArray | spirv::Op::TypeRuntimeArray => {
+                if let Some(element) = ids().next() {
+                    aggregates.insert(id, Aggregate::Array(element));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut pointers: BTreeMap<Word, (Pointer, Word)> = BTreeMap::new();
+    let mut loads = BTreeMap::new();
+    let functions = module.functions.iter().flat_map(|f| {
+        f.parameters.iter().chain(f.basic_blocks.iter().flat_map(|b| &b.instructions))
+    });
+    for inst in module.types_global_values.iter().chain(functions) {
+        let (id, pointee) = match (inst.result_id, inst.result_type) {
+            (Some(id), Some(ty)) => (id, pointees.get(&ty).cloned()),
+            _ => continue,
+        };
+        let base = match inst.operands.first() {
+            Some(&mr::Operand::IdRef(base)) => pointers.get(&base).cloned(),
+            _ => None,
+        };
+        let pointer = match (inst.class.opcode, pointee, base) {
+            (spirv::Op::Variable, Some(pointee), _) |
+            (spirv::Op::FunctionParameter, Some(pointee), _) => {
+                (Pointer { root: id, qualifiers: qualifiers(id, None) }, pointee)
+            }
+            (spirv::Op::Load, _, Some((pointer, _))) => {
+                loads.insert(id, pointer);
+                continue;
+            }
+            (spirv::Op::CopyObject, _, Some(base)) => base,
+            (opcode, Some(pointee), Some((mut pointer, mut ty))) => {
+                let skip = match opcode {
+                    spirv::Op::AccessChain | spirv::Op::InBoundsAccessChain => 1,
+                    // The first index steps over elements the base points to.
+                    spirv::Op::PtrAccessChain | spirv::Op::InBoundsPtrAccessChain => 2,
+                    _ => continue,
+                };
+                for index in &inst.operands[skip..] {
+                    match aggregates.get(&ty) {
+                        Some(Aggregate::Struct(members)) => {
+                            let member = match *index {
+                                mr::Operand::IdRef(index) => constants.value(index),
+                                _ => None,
+                            };
+                            match member.and_then(|m| members.get(m as usize).map(|&t| (m, t))) {
+                                Some((member, member_ty)) => {
+                                    pointer.qualifiers =
+                                        pointer.qualifiers.union(qualifiers(ty, Some(member)));
+                                    ty = member_ty;
+                                }
+                                None => break,
+                            }
+                        }
+                        Some(&Aggregate::Array(element)) => ty = element,
+                        None => break,
+                    }
+                }
+                (pointer, pointee)
+            }
+            _ => continue,
+        };
+        pointers.insert(id, pointer);
+    }
+    (pointers.into_iter().map(|(id, (pointer, _))| (id, pointer)).collect(), loads)
+}
+
+/// Returns the operands `inst` has in the Vulkan memory model, or `None`
+/// if they are unchanged.
+fn upgrade(inst: &mr::Instruction,
+           pointers: &BTreeMap<Word, Pointer>,
+           loads: &BTreeMap<Word, Pointer>,
+           constants: &mut Constants)
+           -> Result<Option<mr::Operands>> {
+    let opcode = inst.class.opcode;
+    let through = |map: &BTreeMap<Word, Pointer>, index: usize| match inst.operands.get(index) {
+        Some(&mr::Operand::IdRef(id)) => map.get(&id).map(|p| p.qualifiers).unwrap_or_default(),
+        _ => Qualifiers::default(),
+    };
+    let none = Qualifiers::default();
+    let mut operands = inst.operands.clone();
+    match opcode {
+        spirv::Op::Load => upgrade_access(&mut operands, 1, none, through(pointers, 0), constants),
+        spirv::Op::Store => upgrade_access(&mut operands, 2, through(pointers, 0), none, constants),
+        spirv::Op::CopyMemory | spirv::Op::CopyMemorySized => {
+            let index = if opcode == spirv::Op::CopyMemory { 2 } else { 3 };
+            upgrade_access(&mut operands,
+                           index,
+                           through(pointers, 0),
+                           through(pointers, 1),
+                           constants)
+        }
+        spirv::Op::ImageRead | spirv::Op::ImageSparseRead => {
+            upgrade_texel_access(&mut operands, 2, none, through(loads, 0), constants)
+        }
+        spirv::Op::ImageWrite => {
+            upgrade_texel_access(&mut operands, 3, through(loads, 0), none, constants)
+        }
+        spirv::Op::MemoryBarrier | spirv::Op::ControlBarrier => {
+            map_memory_operands(opcode, &mut operands, constants, upgrade_scope, |s| s)?
+        }
+        _ if is_atomic(opcode) => {
+            let volatile = through(pointers, 0).volatile;
+            map_memory_operands(opcode, &mut operands, constants, upgrade_scope, |semantics| {
+                if volatile {
+                    semantics | spirv::MemorySemantics::VOLATILE
+                } else {
+                    semantics
+                }
+            })?
+        }
+        _ => return Ok(None),
+    }
+    Ok(if operands != inst.operands { Some(operands) } else { None })
+}
+
+/// The memory object declarations to decorate when downgrading.
+#[derive(Default)]
+struct Decorations {
+    coherent: BTreeSet<Word>,
+    volatile: BTreeSet<Word>,
+}
+
+/// Returns the operands `inst` has in the `GLSL450` memory model, or
+/// `None` if they are unchanged, noting the declarations to decorate in
+/// `decorations`.
+fn downgrade(inst: &mr::Instruction,
+             pointers: &BTreeMap<Word, Pointer>,
+             loads: &BTreeMap<Word, Pointer>,
+             constants: &mut Constants,
+             decorations: &mut Decorations)
+             -> Result<Option<mr::Operands>> {
+    let opcode = inst.class.opcode;
+    let root = |map: &BTreeMap<Word, Pointer>, index: usize| {
+        let pointer = match inst.operands.get(index) {
+            Some(&mr::Operand::IdRef(id)) => map.get(&id),
+            _ => None,
+        };
+        pointer.map(|p| p.root).ok_or(Error::Unsupported(opcode))
+    };
+    let mut operands = inst.operands.clone();
+    match opcode {
+        spirv::Op::Load | spirv::Op::Store | spirv::Op::CopyMemory | spirv::Op::CopyMemorySized => {
+            let index = match opcode {
+                spirv::Op::Load => 1,
+                spirv::Op::CopyMemorySized => 3,
+                _ => 2,
+            };
+            let (available, visible) = downgrade_access(&mut operands, index);
+            // Loads read through their only pointer, copies through the
+            // second.
+            let source = if opcode == spirv::Op::Load { 0 } else { 1 };
+            if available {
+                decorations.coherent.insert(root(pointers, 0)?);
+            }
+            if visible {
+                decorations.coherent.insert(root(pointers, source)?);
+            }
+        }
+        spirv::Op::ImageRead | spirv::Op::ImageSparseRead | spirv::Op::ImageWrite => {
+            let index = if opcode == spirv::Op::ImageWrite { 3 } else { 2 };
+            let (made, volatile) = downgrade_texel_access(&mut operands, index);
+            if made {
+                decorations.coherent.insert(root(loads, 0)?);
+            }
+            if volatile {
+                decorations.volatile.insert(root(loads, 0)?);
+            }
+        }
+        spirv::Op::MemoryBarrier | spirv::Op::ControlBarrier => {
+            map_memory_operands(opcode,
+                                &mut operands,
+                                constants,
+                                downgrade_scope,
+                                |semantics| semantics & !vulkan_semantics())?
+        }
+        _ if is_atomic(opcode) => {
+            let mut volatile = false;
+            map_memory_operands(opcode, &mut operands, constants, downgrade_scope, |semantics| {
+                volatile |= semantics.contains(spirv::MemorySemantics::VOLATILE);
+                semantics & !vulkan_semantics()
+            })?;
+            if volatile {
+                decorations.volatile.insert(root(pointers, 0)?);
+            }
+        }
+        _ => return Ok(None),
+    }
+    Ok(if operands != inst.operands { Some(operands) } else { None })
+}
+
+fn upgrade_scope(scope: u32) -> u32 {
+    if scope == spirv::Scope::Device as u32 {
+        spirv::Scope::QueueFamily as u32
+    } else {
+        scope
+    }
+}
+
+fn downgrade_scope(scope: u32) -> u32 {
+    if scope == spirv::Scope::QueueFamily as u32 {
+        spirv::Scope::Device as u32
+    } else {
+        scope
+    }
+}
+
+/// Maps the memory scope operands of `operands`, those of an instruction
+/// with the given opcode, through `scope`, and its memory semantics
+/// operands through `semantics`.
+fn map_memory_operands<S, M>(opcode: spirv::Op,
+                             operands: &mut [mr::Operand],
+                             constants: &mut Constants,
+                             scope: S,
+                             mut semantics: M)
+                             -> Result<()>
+    where S: Fn(u32) -> u32,
+          M: FnMut(spirv::MemorySemantics) -> spirv::MemorySemantics
+{
+    let mut scopes = 0;
+    for operand in operands {
+        let (id, mapped) = match *operand {
+            mr::Operand::IdScope(ref mut id) => {
+                scopes += 1;
+                // The first scope of OpControlBarrier is its execution
+                // scope.
+                if opcode == spirv::Op::ControlBarrier && scopes == 1 {
+                    continue;
+                }
+                let value = constants.value(*id).ok_or(Error::Unsupported(opcode))?;
+                (id, scope(value))
+            }
+            mr::Operand::IdMemorySemantics(ref mut id) => {
+                let value = constants.value(*id).ok_or(Error::Unsupported(opcode))?;
+                (id, semantics(spirv::MemorySemantics::from_bits_truncate(value)).bits())
+            }
+            _ => continue,
+        };
+        if constants.value(*id) != Some(mapped) {
+            *id = constants.id(mapped);
+        }
+    }
+    Ok(())
+}
+
+/// Adds to the memory operands at `index` of `operands` what accesses
+/// need to make `available` pointers available and `visible` ones visible.
+fn upgrade_access(operands: &mut mr::Operands,
+                  index: usize,
+                  available: Qualifiers,
+                  visible: Qualifiers,
+                  constants: &mut Constants) {
+    let mut bits = spirv::MemoryAccess::NONE;
+    let mut scopes = vec![];
+    if available.coherent {
+        bits |= spirv::MemoryAccess::MAKE_POINTER_AVAILABLE |
+                spirv::MemoryAccess::NON_PRIVATE_POINTER;
+        scopes.push(mr::Operand::IdScope(constants.id(spirv::Scope::QueueFamily as u32)));
+    }
+    if visible.coherent {
+        bits |= spirv::MemoryAccess::MAKE_POINTER_VISIBLE |
+                spirv::MemoryAccess::NON_PRIVATE_POINTER;
+        scopes.push(mr::Operand::IdScope(constants.id(spirv::Scope::QueueFamily as u32)));
+    }
+    if available.volatile || visible.volatile {
+        bits |= spirv::MemoryAccess::VOLATILE;
+    }
+    if bits.is_empty() {
+        return;
+    }
+    if operands.len() <= index {
+        operands.push(mr::Operand::MemoryAccess(spirv::MemoryAccess::NONE));
+    }
+    if let mr::Operand::MemoryAccess(ref mut mask) = operands[index] {
+        *mask |= bits;
+    }
+    // The scopes follow all other parameters, having the highest bits.
+    operands.extend(scopes);
+}
+
+/// Adds to the image operands at `index` of `operands` what texel
+/// accesses need to make `available` images available and `visible` ones
+/// visible.
+fn upgrade_texel_access(operands: &mut mr::Operands,
+                        index: usize,
+                        available: Qualifiers,
+                        visible: Qualifiers,
+                        constants: &mut Constants) {
+    let mut bits = spirv::ImageOperands::NONE;
+    let mut scopes = vec![];
+    if available.coherent {
+        bits |= spirv::ImageOperands::MAKE_TEXEL_AVAILABLE |
+                spirv::ImageOperands::NON_PRIVATE_TEXEL;
+        scopes.push(mr::Operand::IdScope(constants.id(spirv::Scope::QueueFamily as u32)));
+    }
+    if visible.coherent {
+        bits |= spirv::ImageOperands::MAKE_TEXEL_VISIBLE |
+                spirv::ImageOperands::NON_PRIVATE_TEXEL;
+        scopes.push(mr::Operand::IdScope(constants.id(spirv::Scope::QueueFamily as u32)));
+    }
+    if available.volatile || visible.volatile {
+        bits |= spirv::ImageOperands::VOLATILE_TEXEL;
+    }
+    if bits.is_empty() {
+        return;
+    }
+    if operands.len() <= index {
+        operands.push(mr::Operand::ImageOperands(spirv::ImageOperands::NONE));
+    }
+    if let mr::Operand::ImageOperands(ref mut mask) = operands[index] {
+        *mask |= bits;
+    }
+    operands.extend(scopes);
+}
+
+/// Removes the Vulkan memory model bits from the memory operands at
+/// `index` of `operands`, returning whether they made the pointer
+/// available and visible.
+fn downgrade_access(operands: &mut mr::Operands, index: usize) -> (bool, bool) {
+    let mask = match operands.get(index) {
+        Some(&mr::Operand::MemoryAccess(mask)) => mask,
+        _ => return (false, false),
+    };
+    let kept = mask &
+               !(spirv::MemoryAccess::MAKE_POINTER_AVAILABLE |
+                 spirv::MemoryAccess::MAKE_POINTER_VISIBLE |
+                 spirv::MemoryAccess::NON_PRIVATE_POINTER);
+    if kept != mask {
+        let mask = if kept.is_empty() { None } else { Some(mr::Operand::MemoryAccess(kept)) };
+        strip_scopes(operands, index, mask);
+    }
+    (mask.contains(spirv::MemoryAccess::MAKE_POINTER_AVAILABLE),
+     mask.contains(spirv::MemoryAccess::MAKE_POINTER_VISIBLE))
+}
+
+/// Removes the Vulkan memory model bits from the image operands at
+/// `index` of `operands`, returning whether they made the image available
+/// or visible, and whether they were volatile.
+fn downgrade_texel_access(operands: &mut mr::Operands, index: usize) -> (bool, bool) {
+    let mask = match operands.get(index) {
+        Some(&mr::Operand::ImageOperands(mask)) => mask,
+        _ => return (false, false),
+    };
+    let kept = mask &
+               !(spirv::ImageOperands::MAKE_TEXEL_AVAILABLE |
+                 spirv::ImageOperands::MAKE_TEXEL_VISIBLE |
+                 spirv::ImageOperands::NON_PRIVATE_TEXEL |
+                 spirv::ImageOperands::VOLATILE_TEXEL);
+    if kept != mask {
+        let mask = if kept.is_empty() { None } else { Some(mr::Operand::ImageOperands(kept)) };
+        strip_scopes(operands, index, mask);
+    }
+    (mask.intersects(spirv::ImageOperands::MAKE_TEXEL_AVAILABLE |
+                     spirv::ImageOperands::MAKE_TEXEL_VISIBLE),
+     mask.contains(spirv::ImageOperands::VOLATILE_TEXEL))
+}
+
+/// Replaces the mask at `index` of `operands` with `mask`, or removes it
+/// if there is none, dropping the scopes among its parameters.
+///
+/// The availability and visibility scopes are the only scopes memory and
+/// image operands have.
+fn strip_scopes(operands: &mut mr::Operands, index: usize, mask: Option<mr::Operand>) {
+    let params: Vec<_> = operands.split_off(index)
+        .into_iter()
+        .skip(1)
+        .filter(|operand| !matches!(*operand, mr::Operand::IdScope(_)))
+        .collect();
+    operands.extend(mask);
+    operands.extend(params);
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{downgrade_memory_model, upgrade_memory_model};
+    use passes::Error;
+
+    /// Builds a function loading from a coherent buffer member, storing to
+    /// a volatile variable, and adding atomically to the buffer at device
+    /// scope.
+    fn build() -> mr::Module {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let uint = b.type_int(32, 0);
+        let zero = b.constant_u32(uint, 0);
+        let device = b.constant_scope(uint, spirv::Scope::Device);
+        let buffer_ty = b.type_struct(vec![uint, uint]);
+        let buffer_ptr = b.type_pointer(None, spirv::StorageClass::StorageBuffer, buffer_ty);
+        let uint_ptr = b.type_pointer(None, spirv::StorageClass::StorageBuffer, uint);
+        let private_ptr = b.type_pointer(None, spirv::StorageClass::Private, uint);
+        let buffer = b.variable(buffer_ptr, None, spirv::StorageClass::StorageBuffer, None);
+        let output = b.variable(private_ptr, None, spirv::StorageClass::Private, None);
+        b.member_decorate(buffer_ty, 0, spirv::Decoration::Coherent, vec![]);
+        b.decorate(output, spirv::Decoration::Volatile, vec![]);
+        let void = b.type_void();
+        let voidfvoid = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let member = b.access_chain(uint_ptr, None, buffer, vec![zero]).unwrap();
+        let x = b.load(uint, None, member, None, vec![]).unwrap();
+        b.store(output, x, None, vec![]).unwrap();
+        b.atomic_iadd(uint, None, member, device, zero, x).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.module()
+    }
+
+    #[test]
+    fn test_upgrade_memory_model() {
+        let mut m = build();
+        let bound = m.header.as_ref().unwrap().bound;
+        upgrade_memory_model(&mut m).unwrap();
+        assert_eq!(m.memory_model.as_ref().unwrap().operands[1],
+                   mr::Operand::MemoryModel(spirv::MemoryModel::Vulkan));
+        assert_eq!(m.capabilities[1].operands,
+                   vec![mr::Operand::Capability(spirv::Capability::VulkanMemoryModel)]);
+        assert_eq!(m.extensions[0].operands,
+                   vec![mr::Operand::from("SPV_KHR_vulkan_memory_model")]);
+        assert!(m.annotations.is_empty());
+
+        // The queue family scope is added.
+        let queue_family = bound;
+        assert_eq!(m.types_global_values.last().unwrap().operands,
+                   vec![mr::Operand::LiteralInt32(spirv::Scope::QueueFamily as u32)]);
+        assert_eq!(m.header.as_ref().unwrap().bound, bound + 1);
+        let insts = &m.functions[0].basic_blocks[0].instructions;
+        assert_eq!(insts[1].operands[1..],
+                   [mr::Operand::MemoryAccess(spirv::MemoryAccess::MAKE_POINTER_VISIBLE |
+                                              spirv::MemoryAccess::NON_PRIVATE_POINTER),
+                    mr::Operand::IdScope(queue_family)]);
+        assert_eq!(insts[2].operands[2..],
+                   [mr::Operand::MemoryAccess(spirv::MemoryAccess::VOLATILE)]);
+        assert_eq!(insts[3].operands[1], mr::Operand::IdScope(queue_family));
+    }
+
+    #[test]
+    fn test_downgrade_memory_model() {
+        let mut m = build();
+        upgrade_memory_model(&mut m).unwrap();
+        downgrade_memory_model(&mut m).unwrap();
+        assert_eq!(m.memory_model.as_ref().unwrap().operands[1],
+                   mr::Operand::MemoryModel(spirv::MemoryModel::GLSL450));
+        assert_eq!(m.capabilities.len(), 1);
+        assert!(m.extensions.is_empty());
+        // The coherent member is conservatively widened to its buffer.
+        let insts = &m.functions[0].basic_blocks[0].instructions;
+        let buffer = insts[0].operands[0].clone();
+        assert_eq!(m.annotations.len(), 1);
+        assert_eq!(m.annotations[0].operands,
+                   vec![buffer, mr::Operand::Decoration(spirv::Decoration::Coherent)]);
+        assert_eq!(insts[1].operands.len(), 1);
+        assert_eq!(insts[2].operands[2],
+                   mr::Operand::MemoryAccess(spirv::MemoryAccess::VOLATILE));
+        let device = m.types_global_values[2].result_id.unwrap();
+        assert_eq!(insts[3].operands[1], mr::Operand::IdScope(device));
+
+        let mut m = build();
+        m.memory_model.as_mut().unwrap().operands[1] =
+            mr::Operand::MemoryModel(spirv::MemoryModel::OpenCL);
+        assert_eq!(upgrade_memory_model(&mut m),
+                   Err(Error::Unsupported(spirv::Op::MemoryModel)));
+        assert_eq!(m.annotations.len(), 2);
+    }
+}
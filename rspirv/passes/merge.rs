@@ -0,0 +1,434 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeSet, HashMap};
+
+/// A global instruction of the merged module others can share: its
+/// opcode, result type, operands, and decorations.
+type Signature = (spirv::Op, Option<Word>, Vec<mr::Operand>, Vec<Vec<mr::Operand>>);
+
+/// The renaming of the ids of one input module.
+struct Renaming<'a> {
+    ids: HashMap<Word, Word>,
+    next_id: &'a mut Word,
+}
+
+impl<'a> Renaming<'a> {
+    fn id(&mut self, old: Word) -> Word {
+        let next_id = &mut *self.next_id;
+        *self.ids.entry(old).or_insert_with(|| {
+            *next_id += 1;
+            *next_id - 1
+        })
+    }
+
+    fn operands(&mut self, operands: &[mr::Operand]) -> Vec<mr::Operand> {
+        let mut operands = operands.to_vec();
+        for operand in &mut operands {
+            if let Some(id) = operand.id_mut() {
+                *id = self.id(*id);
+            }
+        }
+        operands
+    }
+
+    fn inst(&mut self, inst: &mr::Instruction) -> mr::Instruction {
+        let result_type = inst.result_type.map(|ty| self.id(ty));
+        let result_id = inst.result_id.map(|id| self.id(id));
+        let operands = self.operands(&inst.operands);
+        mr::Instruction::new(inst.class.opcode, result_type, result_id, operands)
+    }
+}
+
+/// Combines the given `modules`, usually with an entry point each, into
+/// one with all their entry points.
+///
+/// Ids are renamed so they do not conflict, and definitions the modules
+/// share are deduplicated: capabilities, extensions, extended instruction
+/// imports, and `OpString`s of the same text, types and constants with the
+/// same operands and decorations, `Uniform`, `UniformConstant`,
+/// `StorageBuffer`, and `PushConstant` variables likewise, and functions
+/// with the same instructions up to the renaming of the ids they define.
+/// Other variables are kept apart. The merged module takes the highest
+/// version of the modules, and the memory model and generator of the first.
+///
+/// Entry points keep their names, which should thus be distinct for each
+/// execution model.
+pub fn merge_entry_points(modules: &[mr::Module]) -> mr::Module {
+    let mut merged = mr::Module::default();
+    let mut next_id = 1;
+    let mut globals: Vec<(Signature, Word)> = vec![];
+    let mut version = 0;
+
+    for module in modules {
+        if let Some(ref header) = module.header {
+            version = version.max(header.version);
+            if merged.header.is_none() {
+                let mut merged_header = mr::ModuleHeader::new(0);
+                merged_header.generator = header.generator;
+                merged.header = Some(merged_header);
+            }
+        }
+        for inst in &module.capabilities {
+            if !merged.capabilities.iter().any(|c| c.operands == inst.operands) {
+                merged.capabilities.push(mr::Instruction::new(spirv::Op::Capability,
+                                                              None,
+                                                              None,
+                                                              inst.operands.clone()));
+            }
+        }
+        for inst in &module.extensions {
+            if !merged.extensions.iter().any(|e| e.operands == inst.operands) {
+                merged.extensions.push(mr::Instruction::new(spirv::Op::Extension,
+                                                            None,
+                                                            None,
+                                                            inst.operands.clone()));
+            }
+        }
+        if merged.memory_model.is_none() {
+            merged.memory_model = module.memory_model.as_ref().map(|inst| {
+                mr::Instruction::new(spirv::Op::MemoryModel, None, None, inst.operands.clone())
+            });
+        }
+
+        let mut renaming = Renaming { ids: HashMap::new(), next_id: &mut next_id };
+        // The ids defined by instructions shared with an earlier module, or
+        // by functions dropped as duplicates.
+        let mut shared = BTreeSet::new();
+        for inst in &module.ext_inst_imports {
+            let found = merged.ext_inst_imports
+                .iter()
+                .find(|e| e.operands == inst.operands)
+                .and_then(|e| e.result_id);
+            match (found, inst.result_id) {
+                (Some(id), Some(old)) => {
+                    renaming.ids.insert(old, id);
+                }
+                _ => merged.ext_inst_imports.push(renaming.inst(inst)),
+            }
+        }
+        for inst in module.debugs.iter().filter(|inst| inst.class.opcode == spirv::Op::String) {
+            let found = merged.debugs
+                .iter()
+                .find(|d| d.class.opcode == spirv::Op::String && d.operands == inst.operands)
+                .and_then(|d| d.result_id);
+            match (found, inst.result_id) {
+                (Some(id), Some(old)) => {
+                    renaming.ids.insert(old, id);
+                }
+                _ => merged.debugs.push(renaming.inst(inst)),
+            }
+        }
+
+        let unshareable = unshareable_ids(module);
+        let mut decorations: HashMap<Word, Vec<Vec<mr::Operand>>> = HashMap::new();
+        for inst in &module.annotations {
+            if let (spirv::Op::Decorate, Some(&mr::Operand::IdRef(target))) |
+                   (spirv::Op::DecorateString, Some(&mr::Operand::IdRef(target))) |
+                   (spirv::Op::MemberDecorate, Some(&mr::Operand::IdRef(target))) |
+                   (spirv::Op::MemberDecorateString, Some(&mr::Operand::IdRef(target))) =
+                   (inst.class.opcode, inst.operands.first())
+            {
+                decorations.entry(target).or_default().push(inst.operands[1..].to_vec());
+            }
+        }
+
+        for inst in &module.types_global_values {
+            let old = match inst.result_id {
+                // Forward references have taken an id already.
+                Some(old) if shareable(inst) &&
+                             !unshareable.contains(&old) &&
+                             !renaming.ids.contains_key(&old) => old,
+                _ => {
+                    merged.types_global_values.push(renaming.inst(inst));
+                    continue;
+                }
+            };
+            let signature = (inst.class.opcode,
+                             inst.result_type.map(|ty| renaming.id(ty)),
+                             renaming.operands(&inst.operands),
+                             decorations.get(&old).cloned().unwrap_or_default());
+            match globals.iter().find(|(s, _)| *s == signature) {
+                Some(&(_, id)) => {
+                    renaming.ids.insert(old, id);
+                    shared.insert(old);
+                }
+                None => {
+                    let id = renaming.id(old);
+                    let (opcode, result_type, operands, _) = signature.clone();
+                    merged.types_global_values
+                        .push(mr::Instruction::new(opcode, result_type, Some(id), operands));
+                    globals.push((signature, id));
+                }
+            }
+        }
+
+        for f in callees_first(module) {
+            let function = &module.functions[f];
+            let old = function.def.as_ref().and_then(|def| def.result_id);
+            let forward = old.is_some_and(|old| renaming.ids.contains_key(&old));
+            // Ids defined in the function are renamed apart, so the
+            // function can be dropped again if it is a duplicate.
+            let start = *renaming.next_id;
+            let mut local = HashMap::new();
+            for inst in function_insts(function) {
+                if let Some(id) = inst.result_id {
+                    if Some(id) != old || !forward {
+                        local.insert(id, renaming.id(id));
+                    }
+                }
+            }
+            let renamed = mr::Function {
+                def: function.def.as_ref().map(|inst| renaming.inst(inst)),
+                end: function.end.as_ref().map(|inst| renaming.inst(inst)),
+                parameters: function.parameters.iter().map(|inst| renaming.inst(inst)).collect(),
+                basic_blocks: function
+                    .basic_blocks
+                    .iter()
+                    .map(|b| {
+                        mr::BasicBlock {
+                            label: b.label.as_ref().map(|inst| renaming.inst(inst)),
+                            instructions: b.instructions
+                                .iter()
+                                .map(|inst| renaming.inst(inst))
+                                .collect(),
+                        }
+                    })
+                    .collect(),
+            };
+            let end = *renaming.next_id;
+            let duplicate = merged
+                .functions
+                .iter()
+                .find(|f| !forward && same_function(&renamed, f, start..end))
+                .and_then(|f| f.def.as_ref()?.result_id);
+            match (duplicate, old) {
+                (Some(id), Some(old)) => {
+                    for old in local.keys() {
+                        renaming.ids.remove(old);
+                        shared.insert(*old);
+                    }
+                    *renaming.next_id = start;
+                    renaming.ids.insert(old, id);
+                    shared.insert(old);
+                }
+                _ => merged.functions.push(renamed),
+            }
+        }
+
+        for inst in &module.entry_points {
+            merged.entry_points.push(renaming.inst(inst));
+        }
+        for inst in &module.execution_modes {
+            merged.execution_modes.push(renaming.inst(inst));
+        }
+        let targets_shared = |inst: &mr::Instruction| {
+            inst.operands.first().and_then(mr::Operand::id).is_some_and(|id| shared.contains(&id))
+        };
+        for inst in &module.debugs {
+            match inst.class.opcode {
+                spirv::Op::String => (),
+                spirv::Op::Name | spirv::Op::MemberName if targets_shared(inst) => (),
+                _ => merged.debugs.push(renaming.inst(inst)),
+            }
+        }
+        for inst in &module.annotations {
+            let decorates = inst.class.opcode != spirv::Op::GroupDecorate &&
+                            inst.class.opcode != spirv::Op::GroupMemberDecorate &&
+                            inst.class.opcode != spirv::Op::DecorationGroup;
+            if !(decorates && targets_shared(inst)) {
+                merged.annotations.push(renaming.inst(inst));
+            }
+        }
+    }
+
+    if let Some(ref mut header) = merged.header {
+        header.version = version;
+        header.bound = next_id;
+    }
+    merged
+}
+
+/// Returns whether `inst` is a global instruction identical ones of other
+/// modules can be merged with.
+fn shareable(inst: &mr::Instruction) -> bool {
+    match inst.class.opcode {
+        spirv::Op::Variable => {
+            matches!(inst.operands.first(),
+                     Some(mr::Operand::StorageClass(spirv::StorageClass::Uniform)) |
+                     Some(mr::Operand::StorageClass(spirv::StorageClass::UniformConstant)) |
+                     Some(mr::Operand::StorageClass(spirv::StorageClass::StorageBuffer)) |
+                     Some(mr::Operand::StorageClass(spirv::StorageClass::PushConstant)))
+        }
+        spirv::Op::ExtInst | spirv::Op::TypeForwardPointer => false,
+        _ => true,
+    }
+}
+
+/// Returns the ids of `module` decorated in ways decorations cannot be
+/// compared by: through groups, or with id operands.
+fn unshareable_ids(module: &mr::Module) -> BTreeSet<Word> {
+    let mut ids = BTreeSet::new();
+    for inst in &module.annotations {
+        match inst.class.opcode {
+            spirv::Op::GroupDecorate | spirv::Op::GroupMemberDecorate => {
+                ids.extend(inst.operands.iter().skip(1).filter_map(mr::Operand::id));
+            }
+            spirv::Op::DecorateId => ids.extend(inst.operands.first().and_then(mr::Operand::id)),
+            _ => (),
+        }
+    }
+    ids
+}
+
+fn function_insts(function: &mr::Function) -> impl Iterator<Item = &mr::Instruction> {
+    function.def
+        .iter()
+        .chain(&function.parameters)
+        .chain(function.basic_blocks.iter().flat_map(|b| b.label.iter().chain(&b.instructions)))
+        .chain(&function.end)
+}
+
+/// Returns the indices of the functions of `module`, callees before their
+/// callers and otherwise in order.
+fn callees_first(module: &mr::Module) -> Vec<usize> {
+    let indices: HashMap<Word, usize> = module
+        .functions
+        .iter()
+        .enumerate()
+        .filter_map(|(i, f)| f.def.as_ref()?.result_id.map(|id| (id, i)))
+        .collect();
+    let mut order = vec![];
+    let mut visited = vec![false; module.functions.len()];
+    for root in 0..module.functions.len() {
+        // Each entry is a function and whether its callees are done.
+        let mut stack = vec![(root, false)];
+        while let Some((f, done)) = stack.pop() {
+            if done {
+                order.push(f);
+                continue;
+            }
+            if visited[f] {
+                continue;
+            }
+            visited[f] = true;
+            stack.push((f, true));
+            let calls = module.functions[f]
+                .basic_blocks
+                .iter()
+                .flat_map(|b| b.instructions.iter())
+                .filter(|inst| inst.class.opcode == spirv::Op::FunctionCall)
+                .filter_map(|inst| inst.operands.first().and_then(mr::Operand::id));
+            let callees: Vec<usize> = calls.filter_map(|id| indices.get(&id).cloned()).collect();
+            stack.extend(callees.into_iter().rev().filter(|&c| !visited[c]).map(|c| (c, false)));
+        }
+    }
+    order
+}
+
+/// Returns whether `function`, whose ids in `local` it defines, is `other`
+/// up to the renaming of those ids.
+fn same_function(function: &mr::Function,
+                 other: &mr::Function,
+                 local: ::std::ops::Range<Word>)
+                 -> bool {
+    let shape = |f: &mr::Function| {
+        (f.parameters.len(), f.basic_blocks.iter().map(|b| b.instructions.len()).collect::<Vec<_>>())
+    };
+    if shape(function) != shape(other) {
+        return false;
+    }
+    let mut renamed = HashMap::new();
+    for (a, b) in function_insts(function).zip(function_insts(other)) {
+        if let (Some(a), Some(b)) = (a.result_id, b.result_id) {
+            renamed.insert(a, b);
+        }
+    }
+    let rename = |id: Word| if local.contains(&id) { renamed.get(&id).cloned() } else { Some(id) };
+    function_insts(function).zip(function_insts(other)).all(|(a, b)| {
+        a.class.opcode == b.class.opcode &&
+        a.result_type.map(rename) == b.result_type.map(Some) &&
+        a.result_id.map(rename) == b.result_id.map(Some) &&
+        a.operands.len() == b.operands.len() &&
+        a.operands.iter().zip(&b.operands).all(|(x, y)| match (x.id(), y.id()) {
+            (Some(x), Some(y)) => rename(x) == Some(y),
+            _ => x == y,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use std::collections::BTreeSet;
+
+    use super::merge_entry_points;
+
+    fn stage(model: spirv::ExecutionModel, name: &str, value: u32) -> mr::Module {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let ptr = b.type_pointer(None, spirv::StorageClass::Uniform, uint);
+        let ubo = b.variable(ptr, None, spirv::StorageClass::Uniform, None);
+        b.decorate(ubo, spirv::Decoration::Binding, vec![mr::Operand::LiteralInt32(0)]);
+        let constant = b.constant_u32(uint, value);
+        let fty = b.type_function(void, vec![]);
+        let helper = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.load(uint, None, ubo, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.function_call(void, None, helper, vec![]).unwrap();
+        b.store(ubo, constant, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(model, main, name, vec![]);
+        b.name(main, name);
+        b.module()
+    }
+
+    #[test]
+    fn test_merge_entry_points() {
+        let modules = [stage(spirv::ExecutionModel::Vertex, "vs", 1),
+                       stage(spirv::ExecutionModel::Fragment, "fs", 2)];
+        let m = merge_entry_points(&modules);
+
+        assert_eq!(m.capabilities.len(), 1);
+        assert!(m.memory_model.is_some());
+        // The types, the variable, and the helper are shared; the
+        // constants and the callers differ.
+        assert_eq!(m.types_global_values.len(), 7);
+        assert_eq!(m.functions.len(), 3);
+        assert_eq!(m.annotations.len(), 1);
+        assert_eq!(m.debugs.len(), 2);
+        let def = |f: &mr::Function| {
+            mr::Operand::IdRef(f.def.as_ref().unwrap().result_id.unwrap())
+        };
+        let mains: Vec<_> = m.entry_points.iter().map(|inst| inst.operands[1].clone()).collect();
+        assert_eq!(mains, vec![def(&m.functions[1]), def(&m.functions[2])]);
+        let ids: BTreeSet<_> = m.global_inst_iter().filter_map(|inst| inst.result_id).collect();
+        assert_eq!(ids.len(), 7);
+        assert!(m.header.as_ref().unwrap().bound > *ids.iter().max().unwrap());
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Transforms over the data representation.
+//!
+//! Each pass rewrites an [`mr::Module`](../mr/struct.Module.html) in place,
+//! and marks the sections and functions it changes as
+//! [changed](../mr/struct.Changes.html). Passes that can fail leave the
+//! module untouched when they return an error.
+
+pub use self::aggregates::split_struct_variables;
+pub use self::blocks::merge_blocks;
+pub use self::branches::eliminate_dead_branches;
+pub use self::capabilities::infer_capabilities;
+pub use self::combine::{combine_instructions, Combiner, Rewrite, Rule, RULES};
+pub use self::composites::simplify_composites;
+pub use self::copies::propagate_copies;
+pub use self::cow::{rewrite_instructions, CowInstruction};
+pub use self::dead_code::eliminate_dead_code;
+pub use self::debug::strip_debug_info;
+pub use self::descriptors::fix_descriptor_indexing;
+pub use self::duplicates::remove_duplicates;
+pub use self::error::{Error, Result};
+pub use self::extensions::fix_extensions;
+pub use self::flatten::{flatten_branches, FlattenOptions};
+pub use self::float_controls::FloatControls;
+pub use self::ids::compact_ids;
+pub use self::initializers::lower_initializers;
+pub use self::inline::inline_functions;
+pub use self::interface::{optimize_interface, prune_interface, InterfaceVariable, PruneReport};
+pub use self::kernel::{wrap_kernel, Kernel, KernelCase, KernelOptions};
+pub use self::locations::{assign_locations, assign_locations_with};
+pub use self::loops::hoist_loop_invariants;
+pub use self::members::{eliminate_dead_members, MemberRemap, StructRemap};
+pub use self::memory::eliminate_loads_stores;
+pub use self::memory_model::{downgrade_memory_model, upgrade_memory_model};
+pub use self::merge::merge_entry_points;
+pub use self::padding::{strip_dead_undefs, strip_nops};
+pub use self::pipeline::{legalize_hlsl, performance_pipeline, performance_pipeline_with,
+                         size_pipeline, Pass, PerformanceOptions, Pipeline};
+pub use self::printf::{insert_debug_printfs, DebugPrintf, InsertPoint};
+pub use self::resources::propagate_resource_copies;
+pub use self::robustness::clamp_buffer_indices;
+pub use self::schedule::schedule_instructions;
+pub use self::signature::{change_signature, CallSite, Parameter, Signature};
+pub use self::storage::{flatten_pointer_variables, legalize_storage, upgrade_buffer_blocks,
+                        StorageTarget};
+pub use self::subexpressions::eliminate_common_subexpressions;
+pub use self::subgroups::{legalize_subgroup_ops, SubgroupTarget};
+pub use self::switches::{lower_switches, normalize_switches, raise_branch_chains};
+pub use self::terminators::{legalize_terminators, TerminatorTarget};
+pub use self::widths::{widen_storage, StorageWidths};
+
+mod aggregates;
+mod blocks;
+mod branches;
+mod capabilities;
+mod combine;
+mod composites;
+mod copies;
+mod cow;
+mod dead_code;
+mod debug;
+mod descriptors;
+mod duplicates;
+mod error;
+mod extensions;
+mod flatten;
+mod float_controls;
+mod ids;
+mod initializers;
+mod inline;
+mod interface;
+mod kernel;
+mod locations;
+mod loops;
+mod members;
+mod memory;
+mod memory_model;
+mod merge;
+mod padding;
+mod pipeline;
+mod printf;
+mod resources;
+mod robustness;
+mod schedule;
+mod signature;
+mod storage;
+mod subexpressions;
+mod subgroups;
+mod switches;
+mod terminators;
+mod widths;
+
+use mr;
+use spirv;
+
+use spirv::Word;
+
+fn is_extension(inst: &mr::Instruction, name: &str) -> bool {
+    match inst.operands.first() {
+        Some(mr::Operand::LiteralString(s)) => s == name,
+        _ => false,
+    }
+}
+
+fn add_extension(module: &mut mr::Module, name: &str) {
+    if !module.extensions.iter().any(|inst| is_extension(inst, name)) {
+        module.extensions.push(mr::Instruction::new(spirv::Op::Extension,
+                                                    None,
+                                                    None,
+                                                    vec![mr::Operand::from(name)]));
+    }
+}
+
+fn remove_extension(module: &mut mr::Module, name: &str) {
+    module.extensions.retain(|inst| !is_extension(inst, name));
+}
+
+/// Declares `capability` in `module` unless it is already declared.
+fn add_capability(module: &mut mr::Module, capability: spirv::Capability) {
+    let operand = mr::Operand::Capability(capability);
+    if !module.capabilities.iter().any(|inst| inst.operands.first() == Some(&operand)) {
+        module.capabilities
+            .push(mr::Instruction::new(spirv::Op::Capability, None, None, vec![operand]));
+    }
+}
+
+/// Returns the global instruction of `module` with the given opcode,
+/// result type, and operands, adding it with a new id if there is none.
+fn find_or_add(module: &mut mr::Module,
+               ids: &mut mr::IdAllocator,
+               opcode: spirv::Op,
+               result_type: Option<Word>,
+               operands: Vec<mr::Operand>)
+               -> Word {
+    let existing = module.types_global_values.iter().find(|inst| {
+        inst.class.opcode == opcode && inst.result_type == result_type && inst.operands == operands
+    });
+    if let Some(id) = existing.and_then(|inst| inst.result_id) {
+        return id;
+    }
+    let id = ids.id();
+    module.types_global_values.push(mr::Instruction::new(opcode, result_type, Some(id), operands));
+    id
+}
+
+/// Returns true if `opcode` defines a specialization constant.
+fn is_spec_constant(opcode: spirv::Op) -> bool {
+    matches!(opcode,
+             spirv::Op::SpecConstantTrue |
+             spirv::Op::SpecConstantFalse |
+             spirv::Op::SpecConstant |
+             spirv::Op::SpecConstantComposite |
+             spirv::Op::SpecConstantOp)
+}
@@ -0,0 +1,146 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grammar;
+use mr;
+use spirv;
+
+use std::collections::BTreeSet;
+
+/// Removes all `OpNop` instructions from the functions of `module`.
+///
+/// Binaries with `OpNop` outside of functions cannot be loaded; to read
+/// those, drop them while parsing with
+/// [`Parser::set_drop_nops`](../binary/struct.Parser.html#method.set_drop_nops).
+pub fn strip_nops(module: &mut mr::Module) {
+    module.record_edits(|module| {
+        for block in module.functions.iter_mut().flat_map(|f| &mut f.basic_blocks) {
+            block.instructions
+                .retain(|inst| inst.class.opcode != spirv::Op::Nop || inst.raw_words.is_some());
+        }
+    })
+}
+
+/// Removes the `OpUndef` instructions of `module` whose results are never
+/// used, together with the names and decorations targeting them.
+///
+/// Debug and annotation instructions do not count as uses.
+pub fn strip_dead_undefs(module: &mut mr::Module) {
+    module.record_edits(|module| {
+        let mut used = BTreeSet::new();
+        {
+            let mut record = |inst: &mr::Instruction| {
+                if grammar::reflect::is_nonlocation_debug(inst.class.opcode) ||
+                   grammar::reflect::is_annotation(inst.class.opcode) {
+                    return;
+                }
+                used.extend(inst.id_operands().map(|(id, _)| id));
+            };
+            for inst in module.global_inst_iter() {
+                record(inst);
+            }
+            for f in &module.functions {
+                for inst in f.def.iter().chain(&f.parameters).chain(&f.end) {
+                    record(inst);
+                }
+                for block in &f.basic_blocks {
+                    for inst in block.label.iter().chain(&block.instructions) {
+                        record(inst);
+                    }
+                }
+            }
+        }
+
+        let mut removed = BTreeSet::new();
+        {
+            let mut is_dead = |inst: &mr::Instruction| match inst.result_id {
+                Some(id) if inst.class.opcode == spirv::Op::Undef && !used.contains(&id) => {
+                    removed.insert(id);
+                    true
+                }
+                _ => false,
+            };
+            module.types_global_values.retain(|inst| !is_dead(inst));
+            for block in module.functions.iter_mut().flat_map(|f| &mut f.basic_blocks) {
+                block.instructions.retain(|inst| !is_dead(inst));
+            }
+        }
+
+        let targets_removed = |inst: &mr::Instruction| match inst.operands.first() {
+            Some(&mr::Operand::IdRef(id)) => removed.contains(&id),
+            _ => false,
+        };
+        module.debugs.retain(|inst| !targets_removed(inst));
+        module.annotations.retain(|inst| !targets_removed(inst));
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{strip_dead_undefs, strip_nops};
+
+    #[test]
+    fn test_strip_nops() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let voidfvoid = b.type_function(void, vec![void]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.nop().unwrap();
+        b.nop().unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+        strip_nops(&mut m);
+        let block = &m.functions[0].basic_blocks[0];
+        assert_eq!(1, block.instructions.len());
+        assert_eq!(spirv::Op::Return, block.instructions[0].class.opcode);
+    }
+
+    #[test]
+    fn test_strip_dead_undefs() {
+        let mut b = mr::Builder::new();
+        let uint = b.type_int(32, 0);
+        let global_dead = b.undef(uint, None);
+        let global_used = b.undef(uint, None);
+        b.name(global_dead, "dead");
+        b.name(global_used, "used");
+        let fty = b.type_function(uint, vec![]);
+        b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.undef(uint, None);
+        let local_used = b.undef(uint, None);
+        let sum = b.iadd(uint, None, global_used, local_used).unwrap();
+        b.ret_value(sum).unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        strip_dead_undefs(&mut m);
+
+        let undefs: Vec<spirv::Word> = m.types_global_values
+            .iter()
+            .filter(|inst| inst.class.opcode == spirv::Op::Undef)
+            .filter_map(|inst| inst.result_id)
+            .collect();
+        assert_eq!(vec![global_used], undefs);
+        let block = &m.functions[0].basic_blocks[0];
+        assert_eq!(3, block.instructions.len());
+        assert_eq!(Some(local_used), block.instructions[0].result_id);
+        assert_eq!(1, m.debugs.len());
+        assert_eq!(mr::Operand::IdRef(global_used), m.debugs[0].operands[0]);
+    }
+}
@@ -0,0 +1,542 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+
+use std::collections::BTreeMap;
+use super::*;
+
+/// A pass of a [`Pipeline`](struct.Pipeline.html).
+#[derive(Clone, Copy)]
+pub struct Pass {
+    /// The name of the pass.
+    pub name: &'static str,
+    /// Runs the pass on the given module.
+    pub run: fn(&mut mr::Module) -> Result<()>,
+}
+
+/// A sequence of passes, run in order.
+#[derive(Clone, Default)]
+pub struct Pipeline {
+    passes: Vec<Pass>,
+}
+
+impl Pipeline {
+    /// Creates a pipeline without passes.
+    pub fn new() -> Pipeline {
+        Pipeline::default()
+    }
+
+    /// Creates a pipeline running the given passes.
+    pub fn with_passes(passes: Vec<Pass>) -> Pipeline {
+        Pipeline { passes }
+    }
+
+    /// Appends `pass`, to be run after the existing passes.
+    pub fn add_pass(&mut self, pass: Pass) {
+        self.passes.push(pass);
+    }
+
+    /// Returns the passes of this pipeline, in the order they are run.
+    pub fn passes(&self) -> &[Pass] {
+        &self.passes
+    }
+
+    /// Runs the passes on `module`, in order.
+    ///
+    /// The first error stops the pipeline. The failed pass leaves the
+    /// module untouched, but the passes before it are not undone.
+    pub fn run(&self, module: &mut mr::Module) -> Result<()> {
+        for pass in &self.passes {
+            (pass.run)(module)?;
+        }
+        Ok(())
+    }
+}
+
+const STRIP_DEBUG_INFO: Pass = Pass {
+    name: "strip-debug-info",
+    run: |m| {
+        strip_debug_info(m);
+        Ok(())
+    },
+};
+
+const REMOVE_DUPLICATES: Pass = Pass {
+    name: "remove-duplicates",
+    run: |m| {
+        remove_duplicates(m);
+        Ok(())
+    },
+};
+
+const ELIMINATE_DEAD_CODE: Pass = Pass {
+    name: "eliminate-dead-code",
+    run: |m| {
+        eliminate_dead_code(m);
+        Ok(())
+    },
+};
+
+const ELIMINATE_LOADS_STORES: Pass = Pass {
+    name: "eliminate-loads-stores",
+    run: |m| {
+        eliminate_loads_stores(m);
+        Ok(())
+    },
+};
+
+const PROPAGATE_COPIES: Pass = Pass {
+    name: "propagate-copies",
+    run: |m| {
+        propagate_copies(m);
+        Ok(())
+    },
+};
+
+const SIMPLIFY_COMPOSITES: Pass = Pass {
+    name: "simplify-composites",
+    run: |m| {
+        simplify_composites(m);
+        Ok(())
+    },
+};
+
+const COMBINE_INSTRUCTIONS: Pass = Pass {
+    name: "combine-instructions",
+    run: |m| {
+        combine_instructions(m);
+        Ok(())
+    },
+};
+
+const ELIMINATE_DEAD_BRANCHES: Pass = Pass {
+    name: "eliminate-dead-branches",
+    run: |m| {
+        eliminate_dead_branches(m, &BTreeMap::new());
+        Ok(())
+    },
+};
+
+const MERGE_BLOCKS: Pass = Pass {
+    name: "merge-blocks",
+    run: |m| {
+        merge_blocks(m);
+        Ok(())
+    },
+};
+
+const ELIMINATE_COMMON_SUBEXPRESSIONS: Pass = Pass {
+    name: "eliminate-common-subexpressions",
+    run: |m| {
+        eliminate_common_subexpressions(m);
+        Ok(())
+    },
+};
+
+const STRIP_NOPS: Pass = Pass {
+    name: "strip-nops",
+    run: |m| {
+        strip_nops(m);
+        Ok(())
+    },
+};
+
+const INLINE_FUNCTIONS: Pass = Pass {
+    name: "inline-functions",
+    run: |m| {
+        inline_functions(m);
+        Ok(())
+    },
+};
+
+const HOIST_LOOP_INVARIANTS: Pass = Pass {
+    name: "hoist-loop-invariants",
+    run: |m| {
+        hoist_loop_invariants(m);
+        Ok(())
+    },
+};
+
+const SCHEDULE_INSTRUCTIONS: Pass = Pass {
+    name: "schedule-instructions",
+    run: |m| {
+        schedule_instructions(m);
+        Ok(())
+    },
+};
+
+const COMPACT_IDS: Pass = Pass {
+    name: "compact-ids",
+    run: |m| {
+        compact_ids(m);
+        Ok(())
+    },
+};
+
+const FLATTEN_POINTER_VARIABLES: Pass = Pass {
+    name: "flatten-pointer-variables",
+    run: flatten_pointer_variables,
+};
+
+const SPLIT_STRUCT_VARIABLES: Pass = Pass {
+    name: "split-struct-variables",
+    run: |m| {
+        split_struct_variables(m);
+        Ok(())
+    },
+};
+
+const PROPAGATE_RESOURCE_COPIES: Pass = Pass {
+    name: "propagate-resource-copies",
+    run: |m| {
+        propagate_resource_copies(m);
+        Ok(())
+    },
+};
+
+/// The passes of [`size_pipeline`](fn.size_pipeline.html).
+const SIZE_PASSES: &[Pass] = &[STRIP_DEBUG_INFO,
+                               REMOVE_DUPLICATES,
+                               ELIMINATE_DEAD_CODE,
+                               ELIMINATE_LOADS_STORES,
+                               PROPAGATE_COPIES,
+                               SIMPLIFY_COMPOSITES,
+                               COMBINE_INSTRUCTIONS,
+                               ELIMINATE_DEAD_BRANCHES,
+                               MERGE_BLOCKS,
+                               ELIMINATE_COMMON_SUBEXPRESSIONS,
+                               ELIMINATE_DEAD_CODE,
+                               REMOVE_DUPLICATES,
+                               STRIP_NOPS];
+
+/// Returns a pipeline making modules smaller, like `spirv-opt -Os`.
+///
+/// It strips debug information, then removes duplicate types and
+/// constants and dead code, before simplifying the functions: loads and
+/// stores of variables are removed, copies propagated, composites and
+/// instructions simplified, branches on constants folded, straight-line
+/// blocks merged, and common subexpressions removed. The dead code and
+/// duplicates these leave are removed last. None of these passes fail.
+pub fn size_pipeline() -> Pipeline {
+    Pipeline::with_passes(SIZE_PASSES.to_vec())
+}
+
+/// The passes of [`legalize_hlsl`](fn.legalize_hlsl.html).
+const LEGALIZATION_PASSES: &[Pass] = &[INLINE_FUNCTIONS,
+                                       ELIMINATE_DEAD_CODE,
+                                       MERGE_BLOCKS,
+                                       FLATTEN_POINTER_VARIABLES,
+                                       SPLIT_STRUCT_VARIABLES,
+                                       SIMPLIFY_COMPOSITES,
+                                       PROPAGATE_RESOURCE_COPIES,
+                                       ELIMINATE_LOADS_STORES,
+                                       PROPAGATE_COPIES,
+                                       SIMPLIFY_COMPOSITES,
+                                       ELIMINATE_DEAD_BRANCHES,
+                                       MERGE_BLOCKS,
+                                       ELIMINATE_DEAD_CODE];
+
+/// Returns a pipeline legalizing the modules DXC emits from HLSL for
+/// Vulkan, like the legalization passes of `spirv-opt`.
+///
+/// DXC leaves code to legalization that Vulkan forbids: resources in
+/// function variables, structs, and arrays, pointers held in variables,
+/// and calls passing them. Calls are inlined first, so that all of such
+/// values are in one function. Variables holding pointers are flattened,
+/// struct variables split into their members, and copies of resource
+/// arrays replaced by the arrays; the loads and stores left forward
+/// resources to their uses. Branches on constants are folded last, and the
+/// dead code removed, including the variables and callees not used
+/// anymore.
+///
+/// Flattening fails when pointers are stored conditionally, or more than
+/// once; see
+/// [`flatten_pointer_variables`](fn.flatten_pointer_variables.html).
+pub fn legalize_hlsl() -> Pipeline {
+    Pipeline::with_passes(LEGALIZATION_PASSES.to_vec())
+}
+
+/// Options for
+/// [`performance_pipeline_with`](fn.performance_pipeline_with.html),
+/// enabling its stages; all but instruction scheduling are enabled by
+/// default.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PerformanceOptions {
+    /// Whether calls are inlined; see
+    /// [`inline_functions`](fn.inline_functions.html).
+    pub inline_functions: bool,
+    /// Whether loads and stores of variables are replaced by the values
+    /// stored; see [`eliminate_loads_stores`](fn.eliminate_loads_stores.html).
+    pub eliminate_loads_stores: bool,
+    /// Whether instructions are simplified; see
+    /// [`combine_instructions`](fn.combine_instructions.html).
+    pub combine_instructions: bool,
+    /// Whether common subexpressions are removed; see
+    /// [`eliminate_common_subexpressions`](fn.eliminate_common_subexpressions.html).
+    pub eliminate_common_subexpressions: bool,
+    /// Whether loop invariants are moved out of loops; see
+    /// [`hoist_loop_invariants`](fn.hoist_loop_invariants.html).
+    pub hoist_loop_invariants: bool,
+    /// Whether instructions are scheduled, off by default; see
+    /// [`schedule_instructions`](fn.schedule_instructions.html).
+    pub schedule_instructions: bool,
+    /// Whether branches on constants are folded; see
+    /// [`eliminate_dead_branches`](fn.eliminate_dead_branches.html).
+    pub eliminate_dead_branches: bool,
+    /// Whether ids are renumbered last; see
+    /// [`compact_ids`](fn.compact_ids.html).
+    pub compact_ids: bool,
+}
+
+impl Default for PerformanceOptions {
+    fn default() -> PerformanceOptions {
+        PerformanceOptions {
+            inline_functions: true,
+            eliminate_loads_stores: true,
+            combine_instructions: true,
+            eliminate_common_subexpressions: true,
+            hoist_loop_invariants: true,
+            schedule_instructions: false,
+            eliminate_dead_branches: true,
+            compact_ids: true,
+        }
+    }
+}
+
+/// Returns a pipeline making modules faster, like `spirv-opt -O`, with its
+/// default stages.
+///
+/// See [`performance_pipeline_with`](fn.performance_pipeline_with.html)
+/// for details.
+pub fn performance_pipeline() -> Pipeline {
+    performance_pipeline_with(&PerformanceOptions::default())
+}
+
+/// Returns a pipeline making modules faster, with the stages `options`
+/// enables.
+///
+/// Calls are inlined first, exposing the code of the callees to the
+/// following stages; dead code, like the callees not called anymore, is
+/// removed, and the blocks split at the calls merged. Then loads and
+/// stores of variables are replaced by their values, copies propagated,
+/// instructions simplified, branches on constants folded, and blocks
+/// merged again, before common subexpressions are removed and loop
+/// invariants moved out of loops, and, optionally, instructions are
+/// scheduled for compute-heavy kernels. The dead code these leave is
+/// removed, and the ids compacted last. Removing dead code, propagating
+/// copies, and merging blocks always run. None of these passes fail.
+pub fn performance_pipeline_with(options: &PerformanceOptions) -> Pipeline {
+    let stages = [(options.inline_functions, INLINE_FUNCTIONS),
+                  (true, ELIMINATE_DEAD_CODE),
+                  (true, MERGE_BLOCKS),
+                  (options.eliminate_loads_stores, ELIMINATE_LOADS_STORES),
+                  (true, PROPAGATE_COPIES),
+                  (options.combine_instructions, COMBINE_INSTRUCTIONS),
+                  (options.eliminate_dead_branches, ELIMINATE_DEAD_BRANCHES),
+                  (true, MERGE_BLOCKS),
+                  (options.eliminate_common_subexpressions, ELIMINATE_COMMON_SUBEXPRESSIONS),
+                  (options.hoist_loop_invariants, HOIST_LOOP_INVARIANTS),
+                  (options.schedule_instructions, SCHEDULE_INSTRUCTIONS),
+                  (true, ELIMINATE_DEAD_CODE),
+                  (options.compact_ids, COMPACT_IDS)];
+    let passes = stages.iter().filter(|&&(enabled, _)| enabled).map(|&(_, pass)| pass);
+    Pipeline::with_passes(passes.collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{legalize_hlsl, performance_pipeline, performance_pipeline_with, size_pipeline,
+                PerformanceOptions};
+
+    #[test]
+    fn test_size_pipeline() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let also_float = b.type_float(32);
+        let ptr_out = b.type_pointer(None, spirv::StorageClass::Output, float);
+        let ptr_fn = b.type_pointer(None, spirv::StorageClass::Function, also_float);
+        let ptr_in = b.type_pointer(None, spirv::StorageClass::Input, float);
+        let input = b.variable(ptr_in, None, spirv::StorageClass::Input, None);
+        let output = b.variable(ptr_out, None, spirv::StorageClass::Output, None);
+        let fty = b.type_function(void, vec![]);
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.name(main, "main");
+        b.begin_basic_block(None).unwrap();
+        let local = b.variable(ptr_fn, None, spirv::StorageClass::Function, None);
+        let value = b.load(float, None, input, None, vec![]).unwrap();
+        b.store(local, value, None, vec![]).unwrap();
+        let x = b.load(also_float, None, local, None, vec![]).unwrap();
+        let next = b.id();
+        b.branch(next).unwrap();
+        b.begin_basic_block(Some(next)).unwrap();
+        let y = b.fmul(float, None, x, x).unwrap();
+        let z = b.fmul(float, None, x, x).unwrap();
+        let sum = b.fadd(float, None, y, z).unwrap();
+        b.store(output, sum, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::Fragment, main, "main", vec![input, output]);
+        let mut m = b.module();
+
+        let pipeline = size_pipeline();
+        assert_eq!(pipeline.passes()[0].name, "strip-debug-info");
+        pipeline.run(&mut m).unwrap();
+        assert!(m.debugs.is_empty());
+        let blocks = &m.functions[0].basic_blocks;
+        assert_eq!(blocks.len(), 1);
+        let opcodes: Vec<spirv::Op> =
+            blocks[0].instructions.iter().map(|inst| inst.class.opcode).collect();
+        assert_eq!(opcodes,
+                   vec![spirv::Op::Load,
+                        spirv::Op::FMul,
+                        spirv::Op::FAdd,
+                        spirv::Op::Store,
+                        spirv::Op::Return]);
+        let globals = m.types_global_values.iter().map(|inst| inst.class.opcode);
+        assert_eq!(globals.filter(|&opcode| opcode == spirv::Op::TypeFloat).count(), 1);
+    }
+
+    #[test]
+    fn test_performance_pipeline() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let ptr_in = b.type_pointer(None, spirv::StorageClass::Input, float);
+        let ptr_out = b.type_pointer(None, spirv::StorageClass::Output, float);
+        let input = b.variable(ptr_in, None, spirv::StorageClass::Input, None);
+        let output = b.variable(ptr_out, None, spirv::StorageClass::Output, None);
+        let square_ty = b.type_function(float, vec![float]);
+        let square = b.begin_function(float, None, spirv::FunctionControl::NONE, square_ty)
+            .unwrap();
+        let x = b.function_parameter(float).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let y = b.fmul(float, None, x, x).unwrap();
+        b.ret_value(y).unwrap();
+        b.end_function().unwrap();
+        let fty = b.type_function(void, vec![]);
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let value = b.load(float, None, input, None, vec![]).unwrap();
+        let first = b.function_call(float, None, square, vec![value]).unwrap();
+        let second = b.function_call(float, None, square, vec![value]).unwrap();
+        let sum = b.fadd(float, None, first, second).unwrap();
+        b.store(output, sum, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::Fragment, main, "main", vec![input, output]);
+        let mut m = b.module();
+
+        performance_pipeline().run(&mut m).unwrap();
+        assert_eq!(m.functions.len(), 1);
+        let blocks = &m.functions[0].basic_blocks;
+        assert_eq!(blocks.len(), 1);
+        let opcodes: Vec<spirv::Op> =
+            blocks[0].instructions.iter().map(|inst| inst.class.opcode).collect();
+        assert_eq!(opcodes,
+                   vec![spirv::Op::Load,
+                        spirv::Op::FMul,
+                        spirv::Op::FAdd,
+                        spirv::Op::Store,
+                        spirv::Op::Return]);
+        // The types, variables, function, label, and four instructions.
+        assert_eq!(m.header.as_ref().unwrap().bound, 13);
+
+        let options = PerformanceOptions { inline_functions: false, ..Default::default() };
+        let pipeline = performance_pipeline_with(&options);
+        assert!(pipeline.passes().iter().all(|pass| pass.name != "inline-functions"));
+        assert_eq!(pipeline.passes().len(), performance_pipeline().passes().len() - 1);
+    }
+
+    #[test]
+    fn test_legalize_hlsl() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let vec4 = b.type_vector(float, 4);
+        let int = b.type_int(32, 1);
+        let ivec2 = b.type_vector(int, 2);
+        let image = b.type_image(float,
+                                 spirv::Dim::Dim2D,
+                                 0,
+                                 0,
+                                 0,
+                                 1,
+                                 spirv::ImageFormat::Unknown,
+                                 None);
+        let zero = b.constant_u32(int, 0);
+        let four = b.constant_u32(int, 4);
+        let coordinate = b.constant_composite(ivec2, vec![zero, zero]);
+        let array = b.type_array(image, four);
+        let ptr_resources = b.type_pointer(None, spirv::StorageClass::UniformConstant, array);
+        let ptr_array = b.type_pointer(None, spirv::StorageClass::Function, array);
+        let ptr_image = b.type_pointer(None, spirv::StorageClass::Function, image);
+        let ptr_out = b.type_pointer(None, spirv::StorageClass::Output, vec4);
+        let textures =
+            b.variable(ptr_resources, None, spirv::StorageClass::UniformConstant, None);
+        let output = b.variable(ptr_out, None, spirv::StorageClass::Output, None);
+        // The array of resources is passed by pointer to a local copy.
+        let fetch_ty = b.type_function(vec4, vec![ptr_array]);
+        let fetch = b.begin_function(vec4, None, spirv::FunctionControl::NONE, fetch_ty)
+            .unwrap();
+        let param = b.function_parameter(ptr_array).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let chain = b.access_chain(ptr_image, None, param, vec![zero]).unwrap();
+        let texture = b.load(image, None, chain, None, vec![]).unwrap();
+        let texel = b.image_fetch(vec4, None, texture, coordinate, None, vec![]).unwrap();
+        b.ret_value(texel).unwrap();
+        b.end_function().unwrap();
+        let fty = b.type_function(void, vec![]);
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let local = b.variable(ptr_array, None, spirv::StorageClass::Function, None);
+        let copy = b.load(array, None, textures, None, vec![]).unwrap();
+        b.store(local, copy, None, vec![]).unwrap();
+        let result = b.function_call(vec4, None, fetch, vec![local]).unwrap();
+        b.store(output, result, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::Fragment, main, "main", vec![output]);
+        let mut m = b.module();
+
+        legalize_hlsl().run(&mut m).unwrap();
+        assert_eq!(m.functions.len(), 1);
+        let blocks = &m.functions[0].basic_blocks;
+        assert_eq!(blocks.len(), 1);
+        let opcodes: Vec<spirv::Op> =
+            blocks[0].instructions.iter().map(|inst| inst.class.opcode).collect();
+        assert_eq!(opcodes,
+                   vec![spirv::Op::AccessChain,
+                        spirv::Op::Load,
+                        spirv::Op::ImageFetch,
+                        spirv::Op::Store,
+                        spirv::Op::Return]);
+        assert_eq!(blocks[0].instructions[0].operands[0], mr::Operand::IdRef(textures));
+        let function_pointers = m.types_global_values.iter().filter(|inst| {
+            inst.operands.first() ==
+            Some(&mr::Operand::StorageClass(spirv::StorageClass::Function))
+        });
+        assert_eq!(function_pointers.count(), 0);
+    }
+}
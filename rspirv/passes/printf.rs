@@ -0,0 +1,234 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grammar;
+use mr;
+use spirv;
+
+use spirv::Word;
+use super::{add_extension, find_or_add, Error, Result};
+
+const NON_SEMANTIC_INFO: &str = "SPV_KHR_non_semantic_info";
+
+/// A point in a function to insert instructions at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InsertPoint {
+    /// Right after the instruction with the given result id, or after all
+    /// `OpPhi`s and `OpVariable`s starting its block if it is one of them.
+    After(Word),
+    /// At the start of the block with the given label, after its `OpPhi`s
+    /// and `OpVariable`s.
+    Start(Word),
+    /// At the end of the block with the given label, before its merge
+    /// instruction and terminator.
+    End(Word),
+}
+
+/// A `DebugPrintf` of `NonSemantic.DebugPrintf` to insert.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DebugPrintf {
+    pub point: InsertPoint,
+    /// The format string, with a `%` conversion for each value.
+    pub format: String,
+    /// The ids of the values printed.
+    pub values: Vec<Word>,
+}
+
+/// Inserts `printfs` into the functions of `module`, and returns the
+/// result ids of the inserted instructions.
+///
+/// `NonSemantic.DebugPrintf` is imported, and the
+/// `SPV_KHR_non_semantic_info` extension it requires before SPIR-V 1.6
+/// declared, if needed; format strings become `OpString`s, shared with
+/// existing ones of the same text. No capabilities are needed. Printfs at
+/// the same point are inserted in the order given.
+///
+/// It is an error, reported as `UnknownId`, for a point to name no
+/// instruction or block in a function.
+pub fn insert_debug_printfs(module: &mut mr::Module, printfs: &[DebugPrintf]) -> Result<Vec<Word>> {
+    module.record_edits(|module| {
+        let mut positions = Vec::with_capacity(printfs.len());
+        for printf in printfs {
+            positions.push(position(module, printf.point)?);
+        }
+        if printfs.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut ids = mr::IdAllocator::for_module(module);
+        let set = grammar::ExtInstSet::DebugPrintf;
+        let import = match mr::ExtInstRegistry::of(module).id_of(set) {
+            Some(import) => import,
+            None => {
+                let import = ids.id();
+                let name = mr::Operand::from(set.name());
+                module.ext_inst_imports.push(mr::Instruction::new(spirv::Op::ExtInstImport,
+                                                                  None,
+                                                                  Some(import),
+                                                                  vec![name]));
+                import
+            }
+        };
+        if module.header.as_ref().is_none_or(|header| header.version() < (1, 6)) {
+            add_extension(module, NON_SEMANTIC_INFO);
+        }
+        let void = find_or_add(module, &mut ids, spirv::Op::TypeVoid, None, vec![]);
+
+        let opcode = mr::Operand::ExtInstOpcode(set, spirv::DebugPrintfOp::DebugPrintf as u32);
+        let mut inserted = Vec::with_capacity(printfs.len());
+        for printf in printfs {
+            let format = string(module, &mut ids, &printf.format);
+            let mut operands =
+                vec![mr::Operand::IdRef(import), opcode.clone(), mr::Operand::IdRef(format)];
+            operands.extend(printf.values.iter().map(|&value| mr::Operand::IdRef(value)));
+            let id = ids.id();
+            inserted.push(mr::Instruction::new(spirv::Op::ExtInst, Some(void), Some(id), operands));
+        }
+        let results = inserted.iter().filter_map(|inst| inst.result_id).collect();
+
+        // Insert from the back, so positions of earlier printfs hold and those
+        // at the same point keep their order.
+        let mut order: Vec<usize> = (0..printfs.len()).collect();
+        order.sort_by_key(|&n| positions[n]);
+        let mut inserted: Vec<Option<mr::Instruction>> = inserted.into_iter().map(Some).collect();
+        for &n in order.iter().rev() {
+            let (f, b, i) = positions[n];
+            if let Some(inst) = inserted[n].take() {
+                module.functions[f].basic_blocks[b].instructions.insert(i, inst);
+            }
+        }
+        ids.update_header(module);
+        Ok(results)
+    })
+}
+
+/// Returns the id of an `OpString` of `text` in `module`, adding it after
+/// the existing ones if there is none.
+fn string(module: &mut mr::Module, ids: &mut mr::IdAllocator, text: &str) -> Word {
+    let is_string = |inst: &mr::Instruction| inst.class.opcode == spirv::Op::String;
+    let existing = module.debugs.iter().find(|inst| {
+        is_string(inst) &&
+        matches!(inst.operands.first(), Some(mr::Operand::LiteralString(s)) if s == text)
+    });
+    if let Some(id) = existing.and_then(|inst| inst.result_id) {
+        return id;
+    }
+    let id = ids.id();
+    let index = module.debugs.iter().rposition(is_string).map_or(0, |i| i + 1);
+    module.debugs.insert(index,
+                         mr::Instruction::new(spirv::Op::String,
+                                              None,
+                                              Some(id),
+                                              vec![mr::Operand::from(text)]));
+    id
+}
+
+fn is_block_header(inst: &mr::Instruction) -> bool {
+    inst.class.opcode == spirv::Op::Phi || inst.class.opcode == spirv::Op::Variable
+}
+
+/// Returns the function, block, and instruction index `point` names in
+/// `module`.
+fn position(module: &mr::Module, point: InsertPoint) -> Result<(usize, usize, usize)> {
+    let id = match point {
+        InsertPoint::After(id) | InsertPoint::Start(id) | InsertPoint::End(id) => id,
+    };
+    for (f, function) in module.functions.iter().enumerate() {
+        for (b, block) in function.basic_blocks.iter().enumerate() {
+            let header = block.instructions.iter().take_while(|inst| is_block_header(inst)).count();
+            let labeled = block.label.as_ref().and_then(|l| l.result_id) == Some(id);
+            let index = match point {
+                InsertPoint::Start(_) if labeled => header,
+                InsertPoint::End(_) if labeled => {
+                    let ends = block.instructions.iter().rev().take_while(|inst| {
+                        grammar::reflect::is_terminator(inst.class.opcode) ||
+                        matches!(inst.class.opcode,
+                                 spirv::Op::SelectionMerge | spirv::Op::LoopMerge)
+                    });
+                    block.instructions.len() - ends.count()
+                }
+                InsertPoint::After(_) => {
+                    match block.instructions.iter().position(|inst| inst.result_id == Some(id)) {
+                        Some(i) if i < header => header,
+                        Some(i) => i + 1,
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+            return Ok((f, b, index));
+        }
+    }
+    Err(Error::UnknownId(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{insert_debug_printfs, DebugPrintf, InsertPoint};
+    use passes::Error;
+
+    #[test]
+    fn test_insert_debug_printfs() {
+        let mut b = mr::Builder::new();
+        b.string("%u");
+        let uint = b.type_int(32, 0);
+        let one = b.constant_u32(uint, 1);
+        let fty = b.type_function(uint, vec![uint]);
+        b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let x = b.function_parameter(uint).unwrap();
+        let entry = b.begin_basic_block(None).unwrap();
+        let y = b.iadd(uint, None, x, one).unwrap();
+        let z = b.imul(uint, None, y, y).unwrap();
+        b.ret_value(z).unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+        let bound = m.header.as_ref().unwrap().bound;
+
+        let printf = |point, format: &str, values| {
+            DebugPrintf { point, format: format.to_owned(), values }
+        };
+        let printfs = [printf(InsertPoint::End(entry), "%u", vec![z]),
+                       printf(InsertPoint::After(y), "y = %u", vec![y]),
+                       printf(InsertPoint::Start(entry), "start", vec![]),
+                       printf(InsertPoint::After(y), "again", vec![])];
+        let ids = insert_debug_printfs(&mut m, &printfs).unwrap();
+        assert_eq!(ids, vec![bound + 2, bound + 4, bound + 6, bound + 8]);
+
+        let insts = &m.functions[0].basic_blocks[0].instructions;
+        let ids: Vec<_> = insts.iter().map(|inst| inst.result_id).collect();
+        assert_eq!(ids,
+                   vec![Some(bound + 6),
+                        Some(y),
+                        Some(bound + 4),
+                        Some(bound + 8),
+                        Some(z),
+                        Some(bound + 2),
+                        None]);
+        // The existing "%u" is reused, the others added after it.
+        assert_eq!(insts[5].operands[2], mr::Operand::IdRef(m.debugs[0].result_id.unwrap()));
+        assert_eq!(insts[5].operands[3], mr::Operand::IdRef(z));
+        assert_eq!(m.debugs.len(), 4);
+        assert_eq!(m.ext_inst_imports[0].operands,
+                   vec![mr::Operand::from("NonSemantic.DebugPrintf")]);
+        assert_eq!(m.extensions[0].operands,
+                   vec![mr::Operand::from("SPV_KHR_non_semantic_info")]);
+
+        let unknown = printf(InsertPoint::After(bound + 100), "", vec![]);
+        assert_eq!(insert_debug_printfs(&mut m, &[unknown]),
+                   Err(Error::UnknownId(bound + 100)));
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+use super::find_or_add;
+use super::memory::{is_access_chain, is_plain_access, is_variable};
+
+/// Replaces the `Function` variables of `module` holding a copy of a
+/// `UniformConstant` variable, e.g., of an array of resources, by that
+/// variable, and returns the number of variables replaced.
+///
+/// A variable is replaced if it is stored to once, as a whole, with a
+/// value loaded from a `UniformConstant` variable of the same type, has
+/// no initializer nor decorations, and its pointers are used for nothing
+/// but non-volatile loads and access chains. Since resources cannot be
+/// written, loads through the variable read the same values as through
+/// the resource. Access chains into it get `UniformConstant` pointer
+/// types; the store is removed, and the loaded copy left for
+/// [`eliminate_dead_code`](fn.eliminate_dead_code.html).
+pub fn propagate_resource_copies(module: &mut mr::Module) -> usize {
+    module.record_edits(|module| {
+        let pointees: BTreeMap<Word, Word> = module.types_global_values
+            .iter()
+            .filter(|inst| inst.class.opcode == spirv::Op::TypePointer)
+            .filter_map(|inst| Some((inst.result_id?, inst.operands.get(1)?.id()?)))
+            .collect();
+        let resources: BTreeMap<Word, Word> = module.types_global_values
+            .iter()
+            .filter(|inst| is_variable(inst, spirv::StorageClass::UniformConstant))
+            .filter_map(|inst| Some((inst.result_id?, *pointees.get(&inst.result_type?)?)))
+            .collect();
+        let decorated: BTreeSet<Word> = module.annotations
+            .iter()
+            .filter_map(|inst| inst.operands.first().and_then(mr::Operand::id))
+            .collect();
+
+        // The variables replaced by the resource, and the access chains into
+        // them, by function.
+        let mut plans = vec![];
+        for function in &module.functions {
+            let insts = || function.basic_blocks.iter().flat_map(|b| &b.instructions);
+            let mut roots: BTreeMap<Word, Word> = function.basic_blocks
+                .iter()
+                .take(1)
+                .flat_map(|b| &b.instructions)
+                .filter(|inst| is_variable(inst, spirv::StorageClass::Function))
+                .filter(|inst| inst.operands.len() == 1)
+                .filter_map(|inst| inst.result_id)
+                .filter(|id| !decorated.contains(id))
+                .map(|id| (id, id))
+                .collect();
+            let mut chains = BTreeSet::new();
+            for inst in insts() {
+                if is_access_chain(inst.class.opcode) {
+                    let root = inst.operands.first().and_then(mr::Operand::id)
+                        .and_then(|base| roots.get(&base).cloned());
+                    if let (Some(id), Some(root)) = (inst.result_id, root) {
+                        roots.insert(id, root);
+                        chains.insert(id);
+                    }
+                }
+            }
+
+            let loads: BTreeMap<Word, Word> = insts()
+                .filter(|inst| inst.class.opcode == spirv::Op::Load && is_plain_access(inst, 0))
+                .filter_map(|inst| Some((inst.result_id?, inst.operands.first()?.id()?)))
+                .collect();
+            let mut sources: BTreeMap<Word, Option<Word>> = BTreeMap::new();
+            let mut rejected = BTreeSet::new();
+            for inst in insts() {
+                for (index, operand) in inst.operands.iter().enumerate() {
+                    let root = match operand.id().and_then(|id| roots.get(&id)) {
+                        Some(&root) => root,
+                        None => continue,
+                    };
+                    let pointer = operand.id();
+                    if inst.class.opcode == spirv::Op::Store && is_plain_access(inst, index) &&
+                       pointer == Some(root) {
+                        let source = inst.operands.get(1)
+                            .and_then(mr::Operand::id)
+                            .and_then(|value| loads.get(&value))
+                            .filter(|source| resources.contains_key(source))
+                            .cloned();
+                        if sources.insert(root, source).is_some() {
+                            rejected.insert(root);
+                        }
+                    } else if inst.class.opcode == spirv::Op::Store ||
+                              !is_plain_access(inst, index) {
+                        rejected.insert(root);
+                    }
+                }
+            }
+            let variables = function.basic_blocks.iter().take(1).flat_map(|b| &b.instructions);
+            let replaced: BTreeMap<Word, Word> = variables
+                .filter_map(|inst| {
+                    let id = inst.result_id?;
+                    let source = (*sources.get(&id)?)?;
+                    let same_type = inst.result_type.and_then(|ty| pointees.get(&ty)) ==
+                                    resources.get(&source);
+                    if rejected.contains(&id) || !same_type {
+                        return None;
+                    }
+                    Some((id, source))
+                })
+                .collect();
+            chains.retain(|chain| replaced.contains_key(&roots[chain]));
+            plans.push((replaced, chains));
+        }
+
+        let mut ids = mr::IdAllocator::for_module(module);
+        let mut count = 0;
+        for (f, (replaced, chains)) in plans.into_iter().enumerate() {
+            if replaced.is_empty() {
+                continue;
+            }
+            count += replaced.len();
+            let chained: BTreeSet<Word> = module.functions[f].basic_blocks
+                .iter()
+                .flat_map(|b| &b.instructions)
+                .filter(|inst| inst.result_id.is_some_and(|id| chains.contains(&id)))
+                .filter_map(|inst| pointees.get(&inst.result_type?).cloned())
+                .collect();
+            let storage_class = mr::Operand::StorageClass(spirv::StorageClass::UniformConstant);
+            let retyped: BTreeMap<Word, Word> = chained.into_iter()
+                .map(|pointee| {
+                    let operands = vec![storage_class.clone(), mr::Operand::IdRef(pointee)];
+                    (pointee, find_or_add(module, &mut ids, spirv::Op::TypePointer, None, operands))
+                })
+                .collect();
+            for block in &mut module.functions[f].basic_blocks {
+                block.instructions.retain(|inst| {
+                    let pointer = inst.operands.first().and_then(mr::Operand::id);
+                    let store = inst.class.opcode == spirv::Op::Store &&
+                                pointer.is_some_and(|p| replaced.contains_key(&p));
+                    !store && !inst.result_id.is_some_and(|id| replaced.contains_key(&id))
+                });
+                for inst in &mut block.instructions {
+                    if inst.result_id.is_some_and(|id| chains.contains(&id)) {
+                        let pointee = inst.result_type.and_then(|ty| pointees.get(&ty));
+                        if let Some(&ty) = pointee.and_then(|pointee| retyped.get(pointee)) {
+                            inst.result_type = Some(ty);
+                        }
+                    }
+                    for id in inst.operands.iter_mut().filter_map(mr::Operand::id_mut) {
+                        *id = replaced.get(id).cloned().unwrap_or(*id);
+                    }
+                }
+            }
+            let removed: BTreeSet<Word> = replaced.keys().cloned().collect();
+            module.debugs.retain(|inst| {
+                let target = inst.operands.first().and_then(mr::Operand::id);
+                !target.is_some_and(|id| removed.contains(&id))
+            });
+        }
+        ids.update_header(module);
+        count
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::propagate_resource_copies;
+
+    #[test]
+    fn test_propagate_resource_copies() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let uint = b.type_int(32, 0);
+        let image = b.type_image(float,
+                                 spirv::Dim::Dim2D,
+                                 0,
+                                 0,
+                                 0,
+                                 1,
+                                 spirv::ImageFormat::Unknown,
+                                 None);
+        let four = b.constant_u32(uint, 4);
+        let one = b.constant_u32(uint, 1);
+        let array = b.type_array(image, four);
+        let ptr_resources = b.type_pointer(None, spirv::StorageClass::UniformConstant, array);
+        let ptr_local = b.type_pointer(None, spirv::StorageClass::Function, array);
+        let ptr_image = b.type_pointer(None, spirv::StorageClass::Function, image);
+        let resources =
+            b.variable(ptr_resources, None, spirv::StorageClass::UniformConstant, None);
+        let fty = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let local = b.variable(ptr_local, None, spirv::StorageClass::Function, None);
+        // Stored twice, so kept.
+        let other = b.variable(ptr_local, None, spirv::StorageClass::Function, None);
+        b.name(local, "local");
+        let copy = b.load(array, None, resources, None, vec![]).unwrap();
+        b.store(local, copy, None, vec![]).unwrap();
+        b.store(other, copy, None, vec![]).unwrap();
+        b.store(other, copy, None, vec![]).unwrap();
+        let chain = b.access_chain(ptr_image, None, local, vec![one]).unwrap();
+        b.load(image, None, chain, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        assert_eq!(propagate_resource_copies(&mut m), 1);
+        assert!(m.debugs.is_empty());
+        let ptr_resource = m.types_global_values.last().unwrap();
+        assert_eq!(ptr_resource.operands,
+                   vec![mr::Operand::StorageClass(spirv::StorageClass::UniformConstant),
+                        mr::Operand::IdRef(image)]);
+        let insts = &m.functions[0].basic_blocks[0].instructions;
+        let opcodes: Vec<spirv::Op> = insts.iter().map(|inst| inst.class.opcode).collect();
+        assert_eq!(opcodes,
+                   vec![spirv::Op::Variable,
+                        spirv::Op::Load,
+                        spirv::Op::Store,
+                        spirv::Op::Store,
+                        spirv::Op::AccessChain,
+                        spirv::Op::Load,
+                        spirv::Op::Return]);
+        assert_eq!(insts[4].result_type, ptr_resource.result_id);
+        assert_eq!(insts[4].operands[0], mr::Operand::IdRef(resources));
+    }
+}
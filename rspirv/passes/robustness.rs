@@ -0,0 +1,458 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grammar;
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+use std::mem;
+use super::find_or_add;
+
+/// What access chains select from.
+enum Shape {
+    Struct(Vec<Word>),
+    /// Arrays, vectors, and matrices, with their element type and length
+    /// if it is a known constant.
+    Sized(Word, Option<u64>),
+    Runtime(Word),
+}
+
+/// The types and constants of a module access chains into buffers are
+/// clamped with.
+struct Types {
+    pointers: BTreeMap<Word, (spirv::StorageClass, Word)>,
+    shapes: BTreeMap<Word, Shape>,
+    ints: BTreeMap<Word, (u32, u32)>,
+    constants: BTreeMap<Word, u64>,
+}
+
+impl Types {
+    fn new(module: &mr::Module) -> Types {
+        let mut types = Types {
+            pointers: BTreeMap::new(),
+            shapes: BTreeMap::new(),
+            ints: BTreeMap::new(),
+            constants: BTreeMap::new(),
+        };
+        for inst in &module.types_global_values {
+            let id = match inst.result_id {
+                Some(id) => id,
+                None => continue,
+            };
+            let first = inst.operands.first().and_then(mr::Operand::id);
+            match (inst.class.opcode, &inst.operands[..]) {
+                (spirv::Op::TypePointer,
+                 &[mr::Operand::StorageClass(class), mr::Operand::IdRef(pointee)]) => {
+                    types.pointers.insert(id, (class, pointee));
+                }
+                (spirv::Op::TypeInt,
+                 &[mr::Operand::LiteralInt32(width), mr::Operand::LiteralInt32(signedness)]) => {
+                    types.ints.insert(id, (width, signedness));
+                }
+                (spirv::Op::Constant, [value]) => {
+                    let value = match *value {
+                        mr::Operand::LiteralInt32(v) => u64::from(v),
+                        mr::Operand::LiteralInt64(v) => v,
+                        _ => continue,
+                    };
+                    if inst.result_type.is_some_and(|ty| types.ints.contains_key(&ty)) {
+                        types.constants.insert(id, value);
+                    }
+                }
+                (spirv::Op::TypeStruct, _) => {
+                    let members = inst.operands.iter().filter_map(mr::Operand::id).collect();
+                    types.shapes.insert(id, Shape::Struct(members));
+                }
+                (spirv::Op::TypeArray, &[_, mr::Operand::IdRef(len)]) => {
+                    let len = types.constants.get(&len).cloned();
+                    types.shapes.extend(first.map(|element| (id, Shape::Sized(element, len))));
+                }
+                (spirv::Op::TypeVector, &[_, mr::Operand::LiteralInt32(n)]) |
+                (spirv::Op::TypeMatrix, &[_, mr::Operand::LiteralInt32(n)]) => {
+                    let len = Some(u64::from(n));
+                    types.shapes.extend(first.map(|element| (id, Shape::Sized(element, len))));
+                }
+                (spirv::Op::TypeRuntimeArray, _) => {
+                    if let Some(element) = first {
+                        types.shapes.insert(id, Shape::Runtime(element));
+                    }
+                }
+                _ => {}
+            }
+        }
+        types
+    }
+}
+
+/// Returns the buffer variables of `module`: `Uniform`, `StorageBuffer`,
+/// and `PushConstant` variables of `Block` or `BufferBlock` structs, or
+/// arrays of them.
+fn buffer_variables(module: &mr::Module, types: &Types) -> BTreeSet<Word> {
+    let mut buffers: BTreeSet<Word> = module.annotations
+        .iter()
+        .filter(|inst| {
+            inst.class.opcode == spirv::Op::Decorate &&
+            matches!(inst.operands.get(1),
+                     Some(mr::Operand::Decoration(spirv::Decoration::Block)) |
+                     Some(mr::Operand::Decoration(spirv::Decoration::BufferBlock)))
+        })
+        .filter_map(|inst| inst.operands.first().and_then(mr::Operand::id))
+        .collect();
+    for inst in &module.types_global_values {
+        let element = inst.operands.first().and_then(mr::Operand::id);
+        let array = matches!(inst.class.opcode,
+                             spirv::Op::TypeArray | spirv::Op::TypeRuntimeArray);
+        if array && element.is_some_and(|element| buffers.contains(&element)) {
+            buffers.extend(inst.result_id);
+        }
+    }
+    module.types_global_values
+        .iter()
+        .filter(|inst| inst.class.opcode == spirv::Op::Variable)
+        .filter(|inst| {
+            match inst.result_type.and_then(|ty| types.pointers.get(&ty)) {
+                Some(&(class, pointee)) => {
+                    matches!(class,
+                             spirv::StorageClass::Uniform |
+                             spirv::StorageClass::StorageBuffer |
+                             spirv::StorageClass::PushConstant) &&
+                    buffers.contains(&pointee)
+                }
+                None => false,
+            }
+        })
+        .filter_map(|inst| inst.result_id)
+        .collect()
+}
+
+/// Clamps the indices access chains in the functions of `module` select
+/// elements of buffer arrays, vectors, and matrices with, so that all
+/// accesses stay in bounds without robust buffer access, and returns the
+/// number of indices clamped.
+///
+/// Buffers are variables of the `Uniform`, `StorageBuffer`, and
+/// `PushConstant` storage classes of `Block` or `BufferBlock` structs, or
+/// arrays of them. Constant indices past the end of sized arrays, vectors,
+/// and matrices are replaced with the last index. Other indices, and all
+/// indices into runtime arrays, are converted to 32-bit unsigned integers
+/// and limited to the last index with the `UMin` of `GLSL.std.450`,
+/// imported if needed; for runtime arrays the last index is one less than
+/// their `OpArrayLength`.
+///
+/// Arrays sized by specialization constants, and pointers passed as
+/// function parameters, are left as they are. Runtime arrays of no
+/// elements stay out of bounds.
+pub fn clamp_buffer_indices(module: &mut mr::Module) -> usize {
+    module.record_edits(|module| {
+        let types = Types::new(module);
+        let buffers = buffer_variables(module, &types);
+        if buffers.is_empty() {
+            return 0;
+        }
+        let globals: BTreeMap<Word, Word> = module.types_global_values
+            .iter()
+            .filter_map(|inst| inst.result_id.and_then(|id| inst.result_type.map(|ty| (id, ty))))
+            .collect();
+        let mut clamper = Clamper {
+            types: &types,
+            ids: mr::IdAllocator::for_module(module),
+            uint: None,
+            glsl: None,
+            clamped: 0,
+        };
+        let mut functions = mem::take(&mut module.functions);
+        for function in &mut functions {
+            let mut values = globals.clone();
+            let insts = function.parameters
+                .iter()
+                .chain(function.basic_blocks.iter().flat_map(|b| &b.instructions));
+            for inst in insts {
+                if let (Some(id), Some(ty)) = (inst.result_id, inst.result_type) {
+                    values.insert(id, ty);
+                }
+            }
+            let mut rooted = buffers.clone();
+            for block in &mut function.basic_blocks {
+                let old = mem::take(&mut block.instructions);
+                for mut inst in old {
+                    let chain = matches!(inst.class.opcode,
+                                         spirv::Op::AccessChain | spirv::Op::InBoundsAccessChain |
+                                         spirv::Op::CopyObject);
+                    let base = inst.operands.first().and_then(mr::Operand::id);
+                    if chain && base.is_some_and(|base| rooted.contains(&base)) {
+                        rooted.extend(inst.result_id);
+                        if inst.class.opcode != spirv::Op::CopyObject {
+                            clamper.clamp(module, &values, &mut inst, &mut block.instructions);
+                        }
+                    }
+                    block.instructions.push(inst);
+                }
+            }
+        }
+        module.functions = functions;
+        clamper.ids.update_header(module);
+        clamper.clamped
+    })
+}
+
+/// Clamps access chain indices, adding what it needs to the module.
+struct Clamper<'t> {
+    types: &'t Types,
+    ids: mr::IdAllocator,
+    uint: Option<Word>,
+    glsl: Option<Word>,
+    clamped: usize,
+}
+
+impl<'t> Clamper<'t> {
+    fn id(&mut self) -> Word {
+        self.ids.id()
+    }
+
+    fn uint(&mut self, module: &mut mr::Module) -> Word {
+        if let Some(uint) = self.uint {
+            return uint;
+        }
+        let operands = vec![mr::Operand::LiteralInt32(32), mr::Operand::LiteralInt32(0)];
+        let uint = find_or_add(module, &mut self.ids, spirv::Op::TypeInt, None, operands);
+        self.uint = Some(uint);
+        uint
+    }
+
+    fn constant(&mut self, module: &mut mr::Module, value: u32) -> Word {
+        let uint = self.uint(module);
+        find_or_add(module,
+                    &mut self.ids,
+                    spirv::Op::Constant,
+                    Some(uint),
+                    vec![mr::Operand::LiteralInt32(value)])
+    }
+
+    fn glsl(&mut self, module: &mut mr::Module) -> Word {
+        let set = grammar::ExtInstSet::GlslStd450;
+        if let Some(glsl) = self.glsl.or_else(|| mr::ExtInstRegistry::of(module).id_of(set)) {
+            self.glsl = Some(glsl);
+            return glsl;
+        }
+        let glsl = self.id();
+        module.ext_inst_imports.push(mr::Instruction::new(spirv::Op::ExtInstImport,
+                                                          None,
+                                                          Some(glsl),
+                                                          vec![mr::Operand::from(set.name())]));
+        self.glsl = Some(glsl);
+        glsl
+    }
+
+    /// Appends to `insts` an instruction of the given opcode and operands
+    /// returning a `uint`, and returns its result id.
+    fn emit(&mut self,
+            module: &mut mr::Module,
+            insts: &mut Vec<mr::Instruction>,
+            opcode: spirv::Op,
+            operands: Vec<mr::Operand>)
+            -> Word {
+        let (uint, id) = (self.uint(module), self.id());
+        insts.push(mr::Instruction::new(opcode, Some(uint), Some(id), operands));
+        id
+    }
+
+    /// Clamps the indices of the access chain `inst`, appending the
+    /// instructions computing them to `insts`.
+    fn clamp(&mut self,
+             module: &mut mr::Module,
+             values: &BTreeMap<Word, Word>,
+             inst: &mut mr::Instruction,
+             insts: &mut Vec<mr::Instruction>) {
+        let types = self.types;
+        let base = match inst.operands.first() {
+            Some(&mr::Operand::IdRef(base)) => base,
+            _ => return,
+        };
+        let (class, mut ty) = match values.get(&base).and_then(|ty| types.pointers.get(ty)) {
+            Some(&pointer) => pointer,
+            None => return,
+        };
+        // The struct last indexed into, its member, and where in the
+        // indices it is.
+        let mut parent = None;
+        for k in 1..inst.operands.len() {
+            let index = match inst.operands[k] {
+                mr::Operand::IdRef(index) => index,
+                _ => return,
+            };
+            let constant = types.constants.get(&index).cloned();
+            let last = match types.shapes.get(&ty) {
+                Some(Shape::Struct(members)) => {
+                    let member = constant.and_then(|m| members.get(m as usize).map(|&t| (m, t)));
+                    match member {
+                        Some((member, member_ty)) => {
+                            parent = Some((ty, member as u32, k));
+                            ty = member_ty;
+                            continue;
+                        }
+                        None => return,
+                    }
+                }
+                Some(&Shape::Sized(element, len)) => {
+                    ty = element;
+                    match (len, constant) {
+                        (Some(len), Some(index)) if index < len => continue,
+                        (Some(len), Some(_)) => {
+                            inst.operands[k] =
+                                mr::Operand::IdRef(self.constant(module, len as u32 - 1));
+                            self.clamped += 1;
+                            continue;
+                        }
+                        (Some(len), None) => self.constant(module, len as u32 - 1),
+                        (None, _) => continue,
+                    }
+                }
+                Some(&Shape::Runtime(element)) => {
+                    ty = element;
+                    let (structure, member, at) = match parent {
+                        Some((structure, member, at)) if at == k - 1 => (structure, member, at),
+                        _ => return,
+                    };
+                    let pointer = if at == 1 {
+                        base
+                    } else {
+                        let operands = vec![mr::Operand::StorageClass(class),
+                                            mr::Operand::IdRef(structure)];
+                        let pointer_ty = find_or_add(module,
+                                                     &mut self.ids,
+                                                     spirv::Op::TypePointer,
+                                                     None,
+                                                     operands);
+                        let pointer = self.id();
+                        insts.push(mr::Instruction::new(inst.class.opcode,
+                                                        Some(pointer_ty),
+                                                        Some(pointer),
+                                                        inst.operands[..at].to_vec()));
+                        pointer
+                    };
+                    let len = self.emit(module,
+                                        insts,
+                                        spirv::Op::ArrayLength,
+                                        vec![mr::Operand::IdRef(pointer),
+                                             mr::Operand::LiteralInt32(member)]);
+                    let one = self.constant(module, 1);
+                    self.emit(module,
+                              insts,
+                              spirv::Op::ISub,
+                              vec![mr::Operand::IdRef(len), mr::Operand::IdRef(one)])
+                }
+                None => return,
+            };
+
+            let uint = self.uint(module);
+            let index = match values.get(&index).and_then(|ty| types.ints.get(ty)) {
+                _ if values.get(&index) == Some(&uint) => index,
+                Some(&(32, _)) => {
+                    self.emit(module, insts, spirv::Op::Bitcast, vec![mr::Operand::IdRef(index)])
+                }
+                Some(_) => {
+                    self.emit(module, insts, spirv::Op::UConvert, vec![mr::Operand::IdRef(index)])
+                }
+                None => continue,
+            };
+            let glsl = self.glsl(module);
+            let umin = mr::Operand::ExtInstOpcode(grammar::ExtInstSet::GlslStd450,
+                                                  spirv::GLOp::UMin as u32);
+            let clamped = self.emit(module,
+                                    insts,
+                                    spirv::Op::ExtInst,
+                                    vec![mr::Operand::IdRef(glsl),
+                                         umin,
+                                         mr::Operand::IdRef(index),
+                                         mr::Operand::IdRef(last)]);
+            inst.operands[k] = mr::Operand::IdRef(clamped);
+            self.clamped += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use grammar;
+    use mr;
+    use spirv;
+
+    use super::clamp_buffer_indices;
+    use utils::test::opcodes;
+
+    #[test]
+    fn test_clamp_buffer_indices() {
+        let mut b = mr::Builder::new();
+        let uint = b.type_int(32, 0);
+        let int = b.type_int(32, 1);
+        let zero = b.constant_u32(uint, 0);
+        let one = b.constant_u32(uint, 1);
+        let four = b.constant_u32(uint, 4);
+        let array = b.type_array(uint, four);
+        let runtime = b.type_runtime_array(uint);
+        let buffer = b.type_struct(vec![array, runtime]);
+        b.decorate(buffer, spirv::Decoration::Block, vec![]);
+        let buffer_ptr = b.type_pointer(None, spirv::StorageClass::StorageBuffer, buffer);
+        let uint_ptr = b.type_pointer(None, spirv::StorageClass::StorageBuffer, uint);
+        let var = b.variable(buffer_ptr, None, spirv::StorageClass::StorageBuffer, None);
+        let fty = b.type_function(uint, vec![int]);
+        b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let i = b.function_parameter(int).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let sized = b.access_chain(uint_ptr, None, var, vec![zero, i]).unwrap();
+        let past = b.access_chain(uint_ptr, None, var, vec![zero, four]).unwrap();
+        let within = b.access_chain(uint_ptr, None, var, vec![zero, one]).unwrap();
+        let runtime_index = b.access_chain(uint_ptr, None, var, vec![one, i]).unwrap();
+        let x = b.load(uint, None, sized, None, vec![]).unwrap();
+        let y = b.load(uint, None, past, None, vec![]).unwrap();
+        let z = b.load(uint, None, within, None, vec![]).unwrap();
+        let w = b.load(uint, None, runtime_index, None, vec![]).unwrap();
+        let xy = b.iadd(uint, None, x, y).unwrap();
+        let zw = b.iadd(uint, None, z, w).unwrap();
+        let sum = b.iadd(uint, None, xy, zw).unwrap();
+        b.ret_value(sum).unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        assert_eq!(clamp_buffer_indices(&mut m), 3);
+        let block = &m.functions[0].basic_blocks[0];
+        assert_eq!(opcodes(block)[..10],
+                   [spirv::Op::Bitcast,
+                    spirv::Op::ExtInst,
+                    spirv::Op::AccessChain,
+                    spirv::Op::AccessChain,
+                    spirv::Op::AccessChain,
+                    spirv::Op::ArrayLength,
+                    spirv::Op::ISub,
+                    spirv::Op::Bitcast,
+                    spirv::Op::ExtInst,
+                    spirv::Op::AccessChain]);
+        let three = m.types_global_values.last().unwrap();
+        assert_eq!(three.operands, vec![mr::Operand::LiteralInt32(3)]);
+        let insts = &block.instructions;
+        assert_eq!(insts[1].operands[1..],
+                   [mr::Operand::ExtInstOpcode(grammar::ExtInstSet::GlslStd450,
+                                               spirv::GLOp::UMin as u32),
+                    mr::Operand::IdRef(insts[0].result_id.unwrap()),
+                    mr::Operand::IdRef(three.result_id.unwrap())]);
+        assert_eq!(insts[3].operands[2], mr::Operand::IdRef(three.result_id.unwrap()));
+        assert_eq!(insts[4].operands[2], mr::Operand::IdRef(one));
+        assert_eq!(insts[5].operands,
+                   vec![mr::Operand::IdRef(var), mr::Operand::LiteralInt32(1)]);
+        assert_eq!(insts[9].operands[2], mr::Operand::IdRef(insts[8].result_id.unwrap()));
+        assert_eq!(m.ext_inst_imports.len(), 1);
+    }
+}
@@ -0,0 +1,426 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use analysis::Dominators;
+use grammar;
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, HashMap};
+use super::flatten::is_speculatable;
+use super::loops::is_merge;
+use super::memory::is_access_chain;
+
+/// The latency of loads, samples, and atomics in the latency model.
+const MEMORY_LATENCY: usize = 20;
+/// The latency of divisions and extended instructions in the latency model.
+const LONG_LATENCY: usize = 8;
+
+/// Schedules the instructions of `module`, and returns the number of
+/// instructions moved to another block or another place in their block.
+///
+/// Access chains are hoisted out of the selection constructs they are in,
+/// to the outermost header their operands are available in, so that
+/// addresses are computed once before the paths diverge. Instructions
+/// without side effects that cannot fault, see
+/// [`flatten_branches`](fn.flatten_branches.html), are then sunk to the
+/// block dominating all their uses closest to them, uses by `OpPhi`
+/// counting as in the block the value comes from. Instructions are neither
+/// hoisted out of nor sunk into loops.
+///
+/// Last, the instructions of each block are ordered by a list scheduler,
+/// starting the instructions with the longest chain of latencies to the
+/// end of the block first: loads, samples, and atomics take 20 cycles,
+/// divisions and extended instructions 8, others 1. `OpPhi` and
+/// `OpVariable` instructions stay first, and merge instructions and
+/// terminators last. Instructions with side effects, debug lines included,
+/// keep their order.
+pub fn schedule_instructions(module: &mut mr::Module) -> usize {
+    module.record_edits(|module| {
+        let glsl = mr::ExtInstRegistry::of(module).id_of(grammar::ExtInstSet::GlslStd450);
+        let mut count = 0;
+        for function in &mut module.functions {
+            let dominators = Dominators::new(function);
+            let labels: Vec<Option<Word>> = function.basic_blocks
+                .iter()
+                .map(|b| b.label.as_ref().and_then(|l| l.result_id))
+                .collect();
+            let indices: BTreeMap<Word, usize> = labels.iter()
+                .enumerate()
+                .filter_map(|(i, label)| Some(((*label)?, i)))
+                .collect();
+            let headers = loop_headers(function);
+            let regions = Regions { dominators: &dominators, labels: &labels, indices: &indices };
+
+            count += hoist_addresses(function, &regions, &headers);
+            count += sink(function, &regions, &headers, glsl);
+            for block in &mut function.basic_blocks {
+                count += schedule_block(block, glsl);
+            }
+        }
+        count
+    })
+}
+
+/// The blocks of a function and their dominators.
+struct Regions<'a> {
+    dominators: &'a Dominators,
+    labels: &'a [Option<Word>],
+    indices: &'a BTreeMap<Word, usize>,
+}
+
+impl<'a> Regions<'a> {
+    /// Returns the blocks defining the results of the instructions of
+    /// `function`, by label.
+    fn definitions(&self, function: &mr::Function) -> HashMap<Word, Word> {
+        function.basic_blocks
+            .iter()
+            .zip(self.labels)
+            .filter_map(|(block, &label)| Some((block, label?)))
+            .flat_map(|(block, label)| {
+                block.instructions.iter().filter_map(move |inst| Some((inst.result_id?, label)))
+            })
+            .collect()
+    }
+
+    /// Returns the number of loops the block labeled `label` is in.
+    fn depth(&self, headers: &[(Word, Word)], label: Word) -> usize {
+        headers.iter()
+            .filter(|&&(header, merge)| {
+                self.dominators.dominates(header, label) && !self.dominators.dominates(merge, label)
+            })
+            .count()
+    }
+}
+
+/// Returns the loop headers of `function` with their merge blocks.
+fn loop_headers(function: &mr::Function) -> Vec<(Word, Word)> {
+    construct_headers(function, spirv::Op::LoopMerge)
+}
+
+/// Returns the labels of the blocks of `function` with a merge instruction
+/// of the given `opcode`, and of their merge blocks.
+fn construct_headers(function: &mr::Function, opcode: spirv::Op) -> Vec<(Word, Word)> {
+    function.basic_blocks
+        .iter()
+        .filter_map(|block| {
+            let header = block.label.as_ref()?.result_id?;
+            let merge = block.instructions.iter().find(|inst| inst.class.opcode == opcode)?;
+            Some((header, merge.operands.first()?.id()?))
+        })
+        .collect()
+}
+
+/// Hoists the access chains in selection constructs of `function`, and
+/// returns the number of access chains hoisted.
+fn hoist_addresses(function: &mut mr::Function,
+                   regions: &Regions,
+                   loops: &[(Word, Word)])
+                   -> usize {
+    let dominators = regions.dominators;
+    let selections: BTreeMap<Word, Word> =
+        construct_headers(function, spirv::Op::SelectionMerge).into_iter().collect();
+    let mut definitions = regions.definitions(function);
+    let mut count = 0;
+    for &label in dominators.reverse_postorder() {
+        let b = regions.indices[&label];
+        let mut i = 0;
+        while i < function.basic_blocks[b].instructions.len() {
+            let inst = &function.basic_blocks[b].instructions[i];
+            if !is_access_chain(inst.class.opcode) {
+                i += 1;
+                continue;
+            }
+            // The outermost selection header of the same loop the
+            // operands are available in.
+            let mut target = None;
+            let mut block = label;
+            while let Some(parent) = dominators.immediate_dominator(block) {
+                if loops.iter().any(|&(header, _)| header == parent) {
+                    break;
+                }
+                block = parent;
+                let merge = match selections.get(&parent) {
+                    Some(&merge) => merge,
+                    None => continue,
+                };
+                if dominators.dominates(merge, label) {
+                    continue;
+                }
+                let available = inst.operands
+                    .iter()
+                    .filter_map(mr::Operand::id)
+                    .filter_map(|id| definitions.get(&id))
+                    .all(|&definition| dominators.dominates(definition, parent));
+                if !available {
+                    break;
+                }
+                target = Some(parent);
+            }
+            let target = match target {
+                Some(target) => target,
+                None => {
+                    i += 1;
+                    continue;
+                }
+            };
+            let inst = function.basic_blocks[b].instructions.remove(i);
+            definitions.extend(inst.result_id.map(|id| (id, target)));
+            // Before the selection merge instruction and the branch.
+            let insts = &mut function.basic_blocks[regions.indices[&target]].instructions;
+            let at = insts.len() - 2;
+            insts.insert(at, inst);
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Sinks the instructions of `function` without side effects closer to
+/// their uses, and returns the number of instructions sunk.
+fn sink(function: &mut mr::Function,
+        regions: &Regions,
+        loops: &[(Word, Word)],
+        glsl: Option<Word>)
+        -> usize {
+    let dominators = regions.dominators;
+    // The blocks using each id, once per use.
+    let mut uses: HashMap<Word, Vec<Word>> = HashMap::new();
+    for (block, &label) in function.basic_blocks.iter().zip(regions.labels) {
+        let label = match label {
+            Some(label) => label,
+            None => continue,
+        };
+        for inst in &block.instructions {
+            if inst.class.opcode == spirv::Op::Phi {
+                for pair in inst.operands.chunks(2) {
+                    let parent = pair.get(1).and_then(mr::Operand::id);
+                    if let (Some(value), Some(parent)) = (pair[0].id(), parent) {
+                        uses.entry(value).or_default().push(parent);
+                    }
+                }
+            } else {
+                for id in inst.operands.iter().filter_map(mr::Operand::id) {
+                    uses.entry(id).or_default().push(label);
+                }
+            }
+        }
+    }
+
+    let mut count = 0;
+    for &label in dominators.reverse_postorder().iter().rev() {
+        let b = regions.indices[&label];
+        let mut i = function.basic_blocks[b].instructions.len();
+        while i > 0 {
+            i -= 1;
+            let inst = &function.basic_blocks[b].instructions[i];
+            if !is_speculatable(inst, glsl) {
+                continue;
+            }
+            let blocks = match inst.result_id.and_then(|id| uses.get(&id)) {
+                Some(blocks) if !blocks.is_empty() => blocks,
+                _ => continue,
+            };
+            if blocks.iter().any(|&use_block| !dominators.is_reachable(use_block)) {
+                continue;
+            }
+            let target = blocks.iter().fold(blocks[0], |target, &use_block| {
+                let mut target = target;
+                while !dominators.dominates(target, use_block) {
+                    target = dominators.immediate_dominator(target).unwrap_or(use_block);
+                }
+                target
+            });
+            if target == label || !dominators.dominates(label, target) ||
+               regions.depth(loops, target) > regions.depth(loops, label) {
+                continue;
+            }
+
+            let inst = function.basic_blocks[b].instructions.remove(i);
+            for id in inst.operands.iter().filter_map(mr::Operand::id) {
+                let blocks = uses.get_mut(&id).map(|blocks| blocks.iter_mut());
+                if let Some(use_block) = blocks.and_then(|mut b| b.find(|b| **b == label)) {
+                    *use_block = target;
+                }
+            }
+            // After the phis, before all uses.
+            let insts = &mut function.basic_blocks[regions.indices[&target]].instructions;
+            let at = insts.iter().take_while(|inst| inst.class.opcode == spirv::Op::Phi).count();
+            insts.insert(at, inst);
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Returns the cycles the result of `inst` takes to be available.
+fn latency(inst: &mr::Instruction) -> usize {
+    use spirv::Op;
+    let opname = inst.class.opname;
+    match inst.class.opcode {
+        Op::Load | Op::ImageFetch | Op::ImageSparseFetch | Op::ImageRead | Op::ImageSparseRead |
+        Op::ImageGather | Op::ImageDrefGather | Op::ImageSparseGather |
+        Op::ImageSparseDrefGather => MEMORY_LATENCY,
+        _ if opname.starts_with("ImageSample") || opname.starts_with("ImageSparseSample") ||
+             opname.starts_with("Atomic") => MEMORY_LATENCY,
+        Op::ExtInst | Op::FDiv | Op::FRem | Op::FMod | Op::UDiv | Op::SDiv | Op::UMod |
+        Op::SRem | Op::SMod => LONG_LATENCY,
+        _ => 1,
+    }
+}
+
+/// Orders the instructions of `block` by the latency model, and returns
+/// the number of instructions moved.
+fn schedule_block(block: &mut mr::BasicBlock, glsl: Option<Word>) -> usize {
+    let insts = &block.instructions;
+    let start = insts.iter()
+        .take_while(|inst| {
+            matches!(inst.class.opcode,
+                     spirv::Op::Phi | spirv::Op::Variable | spirv::Op::Line | spirv::Op::NoLine)
+        })
+        .count();
+    let mut end = insts.len();
+    if end > start && grammar::reflect::is_terminator(insts[end - 1].class.opcode) {
+        end -= 1;
+    }
+    if end > start && is_merge(insts[end - 1].class.opcode) {
+        end -= 1;
+    }
+    let middle = &insts[start..end];
+    if middle.len() < 2 {
+        return 0;
+    }
+
+    // The instructions depending on each instruction, and the number of
+    // instructions each depends on.
+    let positions: HashMap<Word, usize> = middle.iter()
+        .enumerate()
+        .filter_map(|(i, inst)| Some((inst.result_id?, i)))
+        .collect();
+    let mut dependents: Vec<Vec<usize>> = vec![vec![]; middle.len()];
+    let mut pending = vec![0; middle.len()];
+    let mut last_ordered = None;
+    for (j, inst) in middle.iter().enumerate() {
+        let mut dependencies: Vec<usize> = inst.operands
+            .iter()
+            .filter_map(|operand| positions.get(&operand.id()?).cloned())
+            .filter(|&i| i < j)
+            .collect();
+        if !is_speculatable(inst, glsl) && !is_access_chain(inst.class.opcode) {
+            dependencies.extend(last_ordered);
+            last_ordered = Some(j);
+        }
+        dependencies.sort_unstable();
+        dependencies.dedup();
+        pending[j] = dependencies.len();
+        for i in dependencies {
+            dependents[i].push(j);
+        }
+    }
+    let mut heights = vec![0; middle.len()];
+    for i in (0..middle.len()).rev() {
+        let tail = dependents[i].iter().map(|&j| heights[j]).max().unwrap_or(0);
+        heights[i] = latency(&middle[i]) + tail;
+    }
+
+    let mut ready: Vec<usize> = (0..middle.len()).filter(|&i| pending[i] == 0).collect();
+    let mut order = Vec::with_capacity(middle.len());
+    while !ready.is_empty() {
+        // The highest ready instruction, the earliest among equals.
+        let (k, _) = ready.iter()
+            .enumerate()
+            .min_by_key(|&(_, &i)| (usize::MAX - heights[i], i))
+            .unwrap();
+        let i = ready.swap_remove(k);
+        order.push(i);
+        for &j in &dependents[i] {
+            pending[j] -= 1;
+            if pending[j] == 0 {
+                ready.push(j);
+            }
+        }
+    }
+    let moved = order.iter().enumerate().filter(|&(k, &i)| k != i).count();
+    if moved == 0 {
+        return 0;
+    }
+
+    let mut middle: Vec<Option<mr::Instruction>> =
+        block.instructions.drain(start..end).map(Some).collect();
+    let scheduled: Vec<mr::Instruction> =
+        order.into_iter().filter_map(|i| middle[i].take()).collect();
+    block.instructions.splice(start..start, scheduled);
+    moved
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::schedule_instructions;
+
+    #[test]
+    fn test_schedule_instructions() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let boolean = b.type_bool();
+        let float = b.type_float(32);
+        let uint = b.type_int(32, 0);
+        let array = b.type_runtime_array(float);
+        let ptr = b.type_pointer(None, spirv::StorageClass::StorageBuffer, array);
+        let elem_ptr = b.type_pointer(None, spirv::StorageClass::StorageBuffer, float);
+        let buffer = b.variable(ptr, None, spirv::StorageClass::StorageBuffer, None);
+        let fty = b.type_function(void, vec![boolean, uint, float]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let c = b.function_parameter(boolean).unwrap();
+        let i = b.function_parameter(uint).unwrap();
+        let x = b.function_parameter(float).unwrap();
+        let (then, merge) = (b.id(), b.id());
+        b.begin_basic_block(None).unwrap();
+        // Only used on one side of the branch.
+        let square = b.fmul(float, None, x, x).unwrap();
+        let sum = b.fadd(float, None, x, x).unwrap();
+        let quotient = b.fdiv(float, None, sum, x).unwrap();
+        b.selection_merge(merge, spirv::SelectionControl::NONE).unwrap();
+        b.branch_conditional(c, then, merge, vec![]).unwrap();
+        b.begin_basic_block(Some(then)).unwrap();
+        let elem = b.access_chain(elem_ptr, None, buffer, vec![i]).unwrap();
+        let loaded = b.load(float, None, elem, None, vec![]).unwrap();
+        let scaled = b.fmul(float, None, square, quotient).unwrap();
+        let value = b.fadd(float, None, scaled, loaded).unwrap();
+        b.store(elem, value, None, vec![]).unwrap();
+        b.branch(merge).unwrap();
+        b.begin_basic_block(Some(merge)).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        // The access chain is hoisted, three instructions are sunk, and
+        // the load swaps places with one of them.
+        assert_eq!(schedule_instructions(&mut m), 6);
+        let results = |block: &mr::BasicBlock| -> Vec<Option<u32>> {
+            block.instructions.iter().map(|inst| inst.result_id).collect()
+        };
+        let blocks = &m.functions[0].basic_blocks;
+        assert_eq!(results(&blocks[0]), vec![Some(elem), None, None]);
+        // The load starts first, the division before the multiplication.
+        assert_eq!(results(&blocks[1]),
+                   vec![Some(loaded), Some(sum), Some(quotient), Some(square), Some(scaled),
+                        Some(value), None, None]);
+        // Nothing moves into the merge block.
+        assert_eq!(results(&blocks[2]), vec![None]);
+    }
+}
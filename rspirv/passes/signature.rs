@@ -0,0 +1,335 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::mem;
+use super::{find_or_add, Error, Result};
+
+/// A parameter of a function in its new signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Parameter {
+    /// The parameter of the given index in the old signature, which keeps
+    /// its id.
+    Old(usize),
+    /// A new parameter of the given type.
+    New(Word),
+}
+
+/// A call with the arguments of the old signature of a function, to be
+/// adapted to the new one.
+#[derive(Debug)]
+pub struct CallSite<'a> {
+    caller: Word,
+    arguments: Vec<Word>,
+    ids: &'a mut mr::IdAllocator,
+    emitted: Vec<mr::Instruction>,
+    variables: Vec<mr::Instruction>,
+}
+
+impl<'a> CallSite<'a> {
+    /// Returns the id of the function making the call.
+    pub fn caller(&self) -> Word {
+        self.caller
+    }
+
+    /// Returns the arguments of the call, for the old signature.
+    pub fn arguments(&self) -> &[Word] {
+        &self.arguments
+    }
+
+    /// Adds an instruction of the given opcode, result type, and operands
+    /// before the call, and returns its result id.
+    ///
+    /// `OpVariable`s are added at the start of the function instead, as
+    /// they have to be.
+    pub fn emit(&mut self,
+                opcode: spirv::Op,
+                result_type: Option<Word>,
+                operands: Vec<mr::Operand>)
+                -> Word {
+        let id = self.ids.id();
+        let inst = mr::Instruction::new(opcode, result_type, Some(id), operands);
+        if opcode == spirv::Op::Variable {
+            self.variables.push(inst);
+        } else {
+            self.emitted.push(inst);
+        }
+        id
+    }
+}
+
+/// The function with its changed signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Signature {
+    /// The id of the function.
+    pub function: Word,
+    /// The ids of its parameters.
+    pub parameters: Vec<Word>,
+}
+
+/// Changes the signature of the function of the given id in `module` to
+/// take `parameters`, and returns it.
+///
+/// The function type and parameters are updated, and `adapter` gives the
+/// arguments of the new signature, one per parameter, for each call with
+/// those of the old one. Uses of old parameters that are left out are left
+/// for the caller to rewrite, as is the function body for parameters that
+/// change their meaning.
+///
+/// Without `thunk`, the function keeps its id and its calls are rewritten;
+/// entry points cannot change their signature. With `thunk`, the function
+/// moves to a new id, and a thunk with the old id and signature calls it
+/// through `adapter` instead, so that calls, entry points, names, and
+/// decorations, e.g., for linkage, stay as they are.
+///
+/// Returns an error if there is no such function or parameter.
+pub fn change_signature<F>(module: &mut mr::Module,
+                           function: Word,
+                           parameters: &[Parameter],
+                           thunk: bool,
+                           mut adapter: F)
+                           -> Result<Signature>
+    where F: FnMut(&mut CallSite) -> Vec<Word>
+{
+    module.record_edits(|module| {
+        let index = module.functions
+            .iter()
+            .position(|f| f.def.as_ref().and_then(|def| def.result_id) == Some(function))
+            .ok_or(Error::UnknownId(function))?;
+        let old: Vec<(Word, Word)> = module.functions[index]
+            .parameters
+            .iter()
+            .filter_map(|p| p.result_id.and_then(|id| p.result_type.map(|ty| (id, ty))))
+            .collect();
+        for parameter in parameters {
+            if let Parameter::Old(i) = *parameter {
+                if i >= old.len() {
+                    return Err(Error::UnknownParameter(function, i));
+                }
+            }
+        }
+        let entry_point = module.entry_points
+            .iter()
+            .any(|inst| inst.operands.get(1) == Some(&mr::Operand::IdRef(function)));
+        if entry_point && !thunk {
+            return Err(Error::Unsupported(spirv::Op::EntryPoint));
+        }
+
+        let mut ids = mr::IdAllocator::for_module(module);
+        let def = module.functions[index].def.as_ref().unwrap();
+        let return_type = def.result_type.unwrap_or(0);
+        let old_def = mr::Instruction::new(spirv::Op::Function,
+                                           def.result_type,
+                                           Some(function),
+                                           def.operands.clone());
+        let void = module.types_global_values.iter().any(|inst| {
+            inst.result_id == Some(return_type) && inst.class.opcode == spirv::Op::TypeVoid
+        });
+        let new: Vec<(Word, Word)> = parameters.iter()
+            .map(|&parameter| match parameter {
+                Parameter::Old(i) => old[i],
+                Parameter::New(ty) => (ids.id(), ty),
+            })
+            .collect();
+        let operands = Some(return_type)
+            .into_iter()
+            .chain(new.iter().map(|&(_, ty)| ty))
+            .map(mr::Operand::IdRef)
+            .collect();
+        let fn_type = find_or_add(module, &mut ids, spirv::Op::TypeFunction, None, operands);
+
+        let target = if thunk { ids.id() } else { function };
+        {
+            let f = &mut module.functions[index];
+            let def = f.def.as_mut().unwrap();
+            def.result_id = Some(target);
+            def.operands[1] = mr::Operand::IdRef(fn_type);
+            f.parameters = new.iter().map(|&(id, ty)| parameter(id, ty)).collect();
+        }
+
+        if thunk {
+            let mut f = mr::Function::new();
+            f.def = Some(old_def);
+            f.parameters = old.iter().map(|&(_, ty)| parameter(ids.id(), ty)).collect();
+            let arguments = f.parameters.iter().filter_map(|p| p.result_id).collect();
+            let mut block = mr::BasicBlock::new();
+            block.label =
+                Some(mr::Instruction::new(spirv::Op::Label, None, Some(ids.id()), vec![]));
+            let result = ids.id();
+            let (variables, emitted, call) =
+                adapt(&mut adapter, &mut ids, function, arguments, target, return_type, result);
+            block.instructions.extend(variables);
+            block.instructions.extend(emitted);
+            block.instructions.push(call);
+            block.instructions.push(if void {
+                mr::Instruction::new(spirv::Op::Return, None, None, vec![])
+            } else {
+                mr::Instruction::new(spirv::Op::ReturnValue,
+                                     None,
+                                     None,
+                                     vec![mr::Operand::IdRef(result)])
+            });
+            f.basic_blocks.push(block);
+            f.end = Some(mr::Instruction::new(spirv::Op::FunctionEnd, None, None, vec![]));
+            module.functions.insert(index + 1, f);
+        } else {
+            for f in &mut module.functions {
+                let caller = f.def.as_ref().and_then(|def| def.result_id).unwrap_or(0);
+                let mut variables = vec![];
+                for block in &mut f.basic_blocks {
+                    let old_insts = mem::take(&mut block.instructions);
+                    for inst in old_insts {
+                        let calls = inst.class.opcode == spirv::Op::FunctionCall &&
+                                    inst.operands.first() == Some(&mr::Operand::IdRef(function));
+                        if !calls {
+                            block.instructions.push(inst);
+                            continue;
+                        }
+                        let arguments =
+                            inst.operands[1..].iter().filter_map(mr::Operand::id).collect();
+                        let result = inst.result_id.unwrap_or(0);
+                        let (mut added, emitted, call) = adapt(&mut adapter,
+                                                               &mut ids,
+                                                               caller,
+                                                               arguments,
+                                                               function,
+                                                               return_type,
+                                                               result);
+                        variables.append(&mut added);
+                        block.instructions.extend(emitted);
+                        block.instructions.push(call);
+                    }
+                }
+                if let Some(entry) = f.basic_blocks.first_mut() {
+                    let at = entry.instructions
+                        .iter()
+                        .position(|inst| inst.class.opcode != spirv::Op::Variable)
+                        .unwrap_or(entry.instructions.len());
+                    entry.instructions.splice(at..at, variables);
+                }
+            }
+        }
+        ids.update_header(module);
+        Ok(Signature {
+            function: target,
+            parameters: new.into_iter().map(|(id, _)| id).collect(),
+        })
+    })
+}
+
+/// Returns a parameter of the given id and type.
+fn parameter(id: Word, ty: Word) -> mr::Instruction {
+    mr::Instruction::new(spirv::Op::FunctionParameter, Some(ty), Some(id), vec![])
+}
+
+/// Adapts a call from `caller` with the old `arguments`, and returns the
+/// variables and instructions to add before the call, and the call to
+/// `target` of the given result type and id.
+fn adapt<F>(adapter: &mut F,
+            ids: &mut mr::IdAllocator,
+            caller: Word,
+            arguments: Vec<Word>,
+            target: Word,
+            return_type: Word,
+            result: Word)
+            -> (Vec<mr::Instruction>, Vec<mr::Instruction>, mr::Instruction)
+    where F: FnMut(&mut CallSite) -> Vec<Word>
+{
+    let mut site = CallSite {
+        caller,
+        arguments,
+        ids,
+        emitted: vec![],
+        variables: vec![],
+    };
+    let arguments = adapter(&mut site);
+    let operands: mr::Operands = Some(target)
+        .into_iter()
+        .chain(arguments)
+        .map(mr::Operand::IdRef)
+        .collect();
+    let call = mr::Instruction::new(spirv::Op::FunctionCall,
+                                    Some(return_type),
+                                    Some(result),
+                                    operands);
+    (site.variables, site.emitted, call)
+}
+
+#[cfg(test)]
+mod tests {
+    use binary::Assemble;
+    use mr;
+    use spirv;
+
+    use super::{change_signature, Parameter};
+
+    #[test]
+    fn test_change_signature() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let ptr = b.type_pointer(None, spirv::StorageClass::Function, uint);
+        let getf = b.type_function(uint, vec![ptr]);
+        let get = b.begin_function(uint, None, spirv::FunctionControl::NONE, getf).unwrap();
+        let p = b.function_parameter(ptr).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let value = b.load(uint, None, p, None, vec![]).unwrap();
+        b.ret_value(value).unwrap();
+        b.end_function().unwrap();
+        let voidf = b.type_function(void, vec![]);
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let var = b.variable(ptr, None, spirv::StorageClass::Function, None);
+        let call = b.function_call(uint, None, get, vec![var]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::GLCompute, main, "main", vec![]);
+        let code = b.module().assemble();
+
+        // Passes the value instead of the pointer, loaded by the callers.
+        let load = |site: &mut super::CallSite| {
+            let operands = vec![mr::Operand::IdRef(site.arguments()[0])];
+            vec![site.emit(spirv::Op::Load, Some(uint), operands)]
+        };
+        let mut m = mr::load_words(&code).unwrap();
+        let new = [Parameter::New(uint)];
+        let signature = change_signature(&mut m, get, &new, false, load).unwrap();
+        assert_eq!(signature.function, get);
+        let ty = m.functions[0].def.as_ref().unwrap().operands[1].id().unwrap();
+        let ty = m.types_global_values.iter().find(|t| t.result_id == Some(ty)).unwrap();
+        assert_eq!(ty.operands, vec![mr::Operand::IdRef(uint), mr::Operand::IdRef(uint)]);
+        assert_eq!(m.functions[0].parameters[0].result_id, Some(signature.parameters[0]));
+        let insts = &m.functions[1].basic_blocks[0].instructions;
+        assert_eq!(insts[1].class.opcode, spirv::Op::Load);
+        assert_eq!(insts[2].result_id, Some(call));
+        let loaded = insts[1].result_id.unwrap();
+        assert_eq!(insts[2].operands, vec![mr::Operand::IdRef(get), mr::Operand::IdRef(loaded)]);
+
+        let mut m = mr::load_words(&code).unwrap();
+        assert!(change_signature(&mut m, main, &[], false, |_| vec![]).is_err());
+        let signature = change_signature(&mut m, get, &new, true, load).unwrap();
+        assert_ne!(signature.function, get);
+        let thunk = &m.functions[1];
+        assert_eq!(thunk.def.as_ref().unwrap().result_id, Some(get));
+        assert_eq!(thunk.def.as_ref().unwrap().operands[1], mr::Operand::IdRef(getf));
+        let insts = &thunk.basic_blocks[0].instructions;
+        assert_eq!(insts[1].operands[0], mr::Operand::IdRef(signature.function));
+        assert_eq!(insts[2].class.opcode, spirv::Op::ReturnValue);
+        assert_eq!(m.functions[2].basic_blocks[0].instructions[1].operands[0],
+                   mr::Operand::IdRef(get));
+    }
+}
@@ -0,0 +1,644 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use analysis::Dominators;
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+use super::{add_extension, find_or_add, Error, Result};
+
+const STORAGE_BUFFER_STORAGE_CLASS: &str = "SPV_KHR_storage_buffer_storage_class";
+
+/// The storage features a target accepts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StorageTarget {
+    /// Whether the target accepts `BufferBlock` structs in the `Uniform`
+    /// storage class, rather than only `Block` ones in the `StorageBuffer`
+    /// storage class.
+    pub buffer_blocks: bool,
+    /// Whether the target accepts variable pointers: pointers held by
+    /// function variables, or chosen by `OpPhi` and `OpSelect`.
+    pub variable_pointers: bool,
+    /// Whether the target accepts `OpUndef`.
+    pub undef: bool,
+    /// Whether the target accepts addressing models other than `Logical`.
+    pub physical_addressing: bool,
+}
+
+impl StorageTarget {
+    /// Creates a target with the restrictions WebGPU implementations like
+    /// wgpu and Dawn put on the modules they consume.
+    pub fn webgpu() -> StorageTarget {
+        StorageTarget {
+            buffer_blocks: false,
+            variable_pointers: false,
+            undef: false,
+            physical_addressing: false,
+        }
+    }
+}
+
+/// Legalizes the storage `module` uses for `target`.
+///
+/// * Without `buffer_blocks`, `BufferBlock` storage is upgraded as by
+///   [`upgrade_buffer_blocks`](fn.upgrade_buffer_blocks.html).
+/// * Without `variable_pointers`, function variables holding pointers are
+///   flattened as by
+///   [`flatten_pointer_variables`](fn.flatten_pointer_variables.html),
+///   and the variable pointer capabilities removed.
+/// * Without `undef`, `OpUndef`s become `OpConstantNull`s.
+///
+/// It is an error for the module to use what `target` does not accept and
+/// this pass cannot rewrite: physical addressing, pointers chosen by
+/// `OpPhi`, `OpSelect`, or returned from functions, or undefined values of
+/// types without a null value.
+pub fn legalize_storage(module: &mut mr::Module, target: &StorageTarget) -> Result<()> {
+    module.record_edits(|module| {
+        if !target.physical_addressing {
+            let addressing = module.memory_model.as_ref().and_then(|inst| inst.operands.first());
+            match addressing {
+                None | Some(&mr::Operand::AddressingModel(spirv::AddressingModel::Logical)) => {}
+                Some(_) => return Err(Error::Unsupported(spirv::Op::MemoryModel)),
+            }
+        }
+        let buffer_blocks = if target.buffer_blocks {
+            None
+        } else {
+            Some(plan_buffer_blocks(module)?)
+        };
+        let flattened = if target.variable_pointers {
+            None
+        } else {
+            let flattened = plan_pointer_variables(module)?;
+            check_variable_pointers(module)?;
+            Some(flattened)
+        };
+        if !target.undef {
+            check_undefs(module)?;
+        }
+
+        if let Some(plan) = buffer_blocks {
+            move_buffer_blocks(module, &plan);
+        }
+        if let Some(flattened) = flattened {
+            flatten(module, &flattened);
+            module.capabilities.retain(|inst| {
+                !matches!(inst.operands.first(),
+                          Some(mr::Operand::Capability(spirv::Capability::VariablePointers)) |
+                          Some(mr::Operand::Capability(
+                              spirv::Capability::VariablePointersStorageBuffer)))
+            });
+        }
+        if !target.undef {
+            replace_undefs(module);
+        }
+        Ok(())
+    })
+}
+
+/// Moves the `BufferBlock` structs of `module` in the `Uniform` storage
+/// class to the `StorageBuffer` one, decorating them `Block`, as SPIR-V
+/// 1.3 deprecated `BufferBlock`.
+///
+/// Variables of such structs, or arrays of them, and the pointers derived
+/// from them by access chains and copies, get pointer types of the new
+/// storage class. Pointer types used for nothing else change in place;
+/// others get a `StorageBuffer` twin. Modules before SPIR-V 1.3 get the
+/// `SPV_KHR_storage_buffer_storage_class` extension.
+///
+/// It is an error for such a pointer to be passed to or returned from a
+/// function, stored, or chosen by `OpPhi` or `OpSelect`, since those would
+/// need types beyond the pointers themselves to change.
+pub fn upgrade_buffer_blocks(module: &mut mr::Module) -> Result<()> {
+    module.record_edits(|module| {
+        let plan = plan_buffer_blocks(module)?;
+        move_buffer_blocks(module, &plan);
+        Ok(())
+    })
+}
+
+/// Removes the function variables of `module` holding pointers, forwarding
+/// the one pointer stored to each to its loads.
+///
+/// It is an error for such a variable to be used but by loads and stores,
+/// for it not to be stored exactly once, counting its initializer, or for
+/// the store not to come before all loads in each path through the
+/// function.
+pub fn flatten_pointer_variables(module: &mut mr::Module) -> Result<()> {
+    module.record_edits(|module| {
+        let flattened = plan_pointer_variables(module)?;
+        flatten(module, &flattened);
+        Ok(())
+    })
+}
+
+/// The `Uniform` pointers into `BufferBlock` structs of a module.
+struct BufferBlocks {
+    structs: BTreeSet<Word>,
+    pointers: BTreeSet<Word>,
+    /// The types of the pointers, with whether anything else uses them.
+    types: BTreeMap<Word, bool>,
+}
+
+fn function_insts(module: &mr::Module) -> impl Iterator<Item = &mr::Instruction> {
+    module.functions.iter().flat_map(|f| {
+        f.def
+            .iter()
+            .chain(&f.parameters)
+            .chain(f.basic_blocks.iter().flat_map(|b| b.label.iter().chain(&b.instructions)))
+            .chain(&f.end)
+    })
+}
+
+fn plan_buffer_blocks(module: &mr::Module) -> Result<BufferBlocks> {
+    let structs: BTreeSet<Word> = module.annotations
+        .iter()
+        .filter(|inst| {
+            inst.class.opcode == spirv::Op::Decorate &&
+            inst.operands.get(1) == Some(&mr::Operand::Decoration(spirv::Decoration::BufferBlock))
+        })
+        .filter_map(|inst| inst.operands.first().and_then(mr::Operand::id))
+        .collect();
+    let mut buffers = structs.clone();
+    let mut uniform_pointers = BTreeSet::new();
+    for inst in &module.types_global_values {
+        let id = match inst.result_id {
+            Some(id) => id,
+            None => continue,
+        };
+        match (inst.class.opcode, &inst.operands[..]) {
+            (spirv::Op::TypeArray, &[mr::Operand::IdRef(element), ..]) |
+            (spirv::Op::TypeRuntimeArray, &[mr::Operand::IdRef(element)])
+                if buffers.contains(&element) => {
+                buffers.insert(id);
+            }
+            (spirv::Op::TypePointer,
+             &[mr::Operand::StorageClass(spirv::StorageClass::Uniform),
+               mr::Operand::IdRef(pointee)]) if buffers.contains(&pointee) => {
+                uniform_pointers.insert(id);
+            }
+            _ => {}
+        }
+    }
+
+    let mut pointers: BTreeSet<Word> = module.types_global_values
+        .iter()
+        .filter(|inst| {
+            inst.class.opcode == spirv::Op::Variable &&
+            inst.result_type.is_some_and(|ty| uniform_pointers.contains(&ty))
+        })
+        .filter_map(|inst| inst.result_id)
+        .collect();
+    for inst in function_insts(module) {
+        let opcode = inst.class.opcode;
+        let base = inst.operands.first().and_then(mr::Operand::id);
+        let derived = matches!(opcode,
+                               spirv::Op::AccessChain |
+                               spirv::Op::InBoundsAccessChain |
+                               spirv::Op::PtrAccessChain |
+                               spirv::Op::InBoundsPtrAccessChain |
+                               spirv::Op::CopyObject);
+        if derived && base.is_some_and(|base| pointers.contains(&base)) {
+            pointers.extend(inst.result_id);
+            continue;
+        }
+        let escapes = inst.operands.iter().enumerate().any(|(index, operand)| {
+            let pointer = operand.id().is_some_and(|id| pointers.contains(&id));
+            pointer &&
+            match opcode {
+                spirv::Op::FunctionCall |
+                spirv::Op::Phi |
+                spirv::Op::Select |
+                spirv::Op::ReturnValue => true,
+                spirv::Op::Store => index == 1,
+                _ => false,
+            }
+        });
+        if escapes {
+            return Err(Error::Unsupported(opcode));
+        }
+    }
+
+    let mut types: BTreeMap<Word, bool> = BTreeMap::new();
+    for inst in module.types_global_values.iter().chain(function_insts(module)) {
+        if let (Some(id), Some(ty)) = (inst.result_id, inst.result_type) {
+            if pointers.contains(&id) {
+                types.insert(ty, false);
+            }
+        }
+    }
+    for inst in module.global_inst_iter().chain(function_insts(module)) {
+        let retyped = inst.result_id.is_some_and(|id| pointers.contains(&id));
+        let ids = inst.result_type
+            .iter()
+            .filter(|_| !retyped)
+            .cloned()
+            .chain(inst.operands.iter().filter_map(mr::Operand::id));
+        for id in ids {
+            if let Some(shared) = types.get_mut(&id) {
+                *shared = true;
+            }
+        }
+    }
+    Ok(BufferBlocks { structs, pointers, types })
+}
+
+fn move_buffer_blocks(module: &mut mr::Module, plan: &BufferBlocks) {
+    if plan.pointers.is_empty() {
+        return;
+    }
+    let mut ids = mr::IdAllocator::for_module(module);
+    let mut retyped = BTreeMap::new();
+    for (&ty, &shared) in &plan.types {
+        let globals = &mut module.types_global_values;
+        let index = match globals.iter().position(|inst| inst.result_id == Some(ty)) {
+            Some(index) => index,
+            None => continue,
+        };
+        let storage_buffer = mr::Operand::StorageClass(spirv::StorageClass::StorageBuffer);
+        if !shared {
+            globals[index].operands[0] = storage_buffer;
+            continue;
+        }
+        let operands = vec![storage_buffer, globals[index].operands[1].clone()];
+        // Pointer types declared later cannot be used by the pointers.
+        let existing = globals[..index].iter().find(|inst| {
+            inst.class.opcode == spirv::Op::TypePointer && inst.operands == operands
+        });
+        let twin = match existing.and_then(|inst| inst.result_id) {
+            Some(id) => id,
+            None => {
+                let id = ids.id();
+                globals.insert(index + 1,
+                               mr::Instruction::new(spirv::Op::TypePointer,
+                                                    None,
+                                                    Some(id),
+                                                    operands));
+                id
+            }
+        };
+        retyped.insert(ty, twin);
+    }
+
+    let functions = module.functions.iter_mut().flat_map(|f| {
+        f.basic_blocks.iter_mut().flat_map(|b| b.instructions.iter_mut())
+    });
+    for inst in module.types_global_values.iter_mut().chain(functions) {
+        if !inst.result_id.is_some_and(|id| plan.pointers.contains(&id)) {
+            continue;
+        }
+        if let Some(&twin) = inst.result_type.and_then(|ty| retyped.get(&ty)) {
+            inst.result_type = Some(twin);
+        }
+        if inst.class.opcode == spirv::Op::Variable {
+            inst.operands[0] = mr::Operand::StorageClass(spirv::StorageClass::StorageBuffer);
+        }
+    }
+    for inst in &mut module.annotations {
+        let target = inst.operands.first().and_then(mr::Operand::id);
+        if inst.class.opcode == spirv::Op::Decorate &&
+           target.is_some_and(|id| plan.structs.contains(&id)) &&
+           inst.operands[1] == mr::Operand::Decoration(spirv::Decoration::BufferBlock) {
+            inst.operands[1] = mr::Operand::Decoration(spirv::Decoration::Block);
+        }
+    }
+    if module.header.as_ref().is_none_or(|header| header.version() < (1, 3)) {
+        add_extension(module, STORAGE_BUFFER_STORAGE_CLASS);
+    }
+    ids.update_header(module);
+}
+
+/// A function variable holding a pointer, to remove with its accesses.
+struct Flattened {
+    function: usize,
+    /// The instructions to remove, by block and index.
+    removed: Vec<(usize, usize)>,
+    /// The loads, with the pointer stored.
+    loads: Vec<(Word, Word)>,
+}
+
+fn pointer_types(module: &mr::Module) -> BTreeMap<Word, Word> {
+    module.types_global_values
+        .iter()
+        .filter(|inst| inst.class.opcode == spirv::Op::TypePointer)
+        .filter_map(|inst| {
+            let pointee = inst.operands.get(1).and_then(mr::Operand::id);
+            inst.result_id.and_then(|id| pointee.map(|pointee| (id, pointee)))
+        })
+        .collect()
+}
+
+fn plan_pointer_variables(module: &mr::Module) -> Result<Vec<Flattened>> {
+    let pointers = pointer_types(module);
+    let holds_pointer = |ty: Option<Word>| {
+        ty.and_then(|ty| pointers.get(&ty)).is_some_and(|pointee| pointers.contains_key(pointee))
+    };
+    let mut plans = vec![];
+    for (f, function) in module.functions.iter().enumerate() {
+        let variables: BTreeMap<Word, usize> = function.basic_blocks
+            .iter()
+            .take(1)
+            .flat_map(|b| b.instructions.iter().enumerate())
+            .filter(|&(_, inst)| {
+                inst.class.opcode == spirv::Op::Variable && holds_pointer(inst.result_type)
+            })
+            .filter_map(|(i, inst)| inst.result_id.map(|id| (id, i)))
+            .collect();
+        if variables.is_empty() {
+            continue;
+        }
+        let mut stores: BTreeMap<Word, Vec<(usize, usize, Word)>> = BTreeMap::new();
+        let mut loads: BTreeMap<Word, Vec<(usize, usize, Word)>> = BTreeMap::new();
+        for (b, block) in function.basic_blocks.iter().enumerate() {
+            for (i, inst) in block.instructions.iter().enumerate() {
+                for (index, operand) in inst.operands.iter().enumerate() {
+                    let variable = match operand.id() {
+                        Some(id) if variables.contains_key(&id) => id,
+                        _ => continue,
+                    };
+                    let other = inst.operands.get(1).and_then(mr::Operand::id);
+                    let access = match (inst.class.opcode, index) {
+                        (spirv::Op::Load, 0) => inst.result_id.map(|id| (&mut loads, id)),
+                        (spirv::Op::Store, 0) => other.map(|value| (&mut stores, value)),
+                        _ => None,
+                    };
+                    match access {
+                        Some((accesses, id)) => {
+                            accesses.entry(variable).or_default().push((b, i, id))
+                        }
+                        None => return Err(Error::Unsupported(inst.class.opcode)),
+                    }
+                }
+            }
+        }
+
+        let dominators = Dominators::new(function);
+        let label = |b: usize| function.basic_blocks[b].label.as_ref().and_then(|l| l.result_id);
+        for (&variable, &index) in &variables {
+            let stores = stores.remove(&variable).unwrap_or_default();
+            let loads = loads.remove(&variable).unwrap_or_default();
+            let initializer = function.basic_blocks[0].instructions[index]
+                .operands
+                .get(1)
+                .and_then(mr::Operand::id);
+            let (value, store) = match (initializer, &stores[..]) {
+                (Some(value), &[]) => (value, None),
+                (None, &[(b, i, value)]) => (value, Some((b, i))),
+                _ if stores.is_empty() && loads.is_empty() => (0, None),
+                _ => return Err(Error::Unsupported(spirv::Op::Variable)),
+            };
+            if let Some((sb, si)) = store {
+                let before = |&(lb, li, _): &(usize, usize, Word)| {
+                    if lb == sb {
+                        return li > si;
+                    }
+                    match (label(sb), label(lb)) {
+                        (Some(s), Some(l)) => dominators.dominates(s, l),
+                        _ => false,
+                    }
+                };
+                if !loads.iter().all(before) {
+                    return Err(Error::Unsupported(spirv::Op::Load));
+                }
+            }
+            let mut removed = vec![(0, index)];
+            removed.extend(store);
+            removed.extend(loads.iter().map(|&(b, i, _)| (b, i)));
+            plans.push(Flattened {
+                function: f,
+                removed,
+                loads: loads.iter().map(|&(_, _, load)| (load, value)).collect(),
+            });
+        }
+    }
+    Ok(plans)
+}
+
+fn flatten(module: &mut mr::Module, flattened: &[Flattened]) {
+    let mut removed: BTreeMap<(usize, usize), BTreeSet<usize>> = BTreeMap::new();
+    let mut forwarded = BTreeMap::new();
+    for plan in flattened {
+        for &(b, i) in &plan.removed {
+            removed.entry((plan.function, b)).or_default().insert(i);
+        }
+        forwarded.extend(plan.loads.iter().cloned());
+    }
+    for ((f, b), indices) in removed {
+        let mut index = 0;
+        module.functions[f].basic_blocks[b].instructions.retain(|_| {
+            index += 1;
+            !indices.contains(&(index - 1))
+        });
+    }
+    // Stored pointers may be loaded from other flattened variables.
+    let resolved: Vec<(Word, Word)> = forwarded.iter()
+        .map(|(&load, &value)| {
+            let mut value = value;
+            while let Some(&next) = forwarded.get(&value) {
+                value = next;
+            }
+            (load, value)
+        })
+        .collect();
+    for (load, value) in resolved {
+        module.replace_all_uses(load, value);
+    }
+}
+
+/// Checks that no pointer of `module` is chosen by `OpPhi` or `OpSelect`,
+/// or returned from a function.
+fn check_variable_pointers(module: &mr::Module) -> Result<()> {
+    let pointers = pointer_types(module);
+    let chosen = function_insts(module).find(|inst| {
+        matches!(inst.class.opcode,
+                 spirv::Op::Phi | spirv::Op::Select | spirv::Op::FunctionCall) &&
+        inst.result_type.is_some_and(|ty| pointers.contains_key(&ty))
+    });
+    match chosen {
+        Some(inst) => Err(Error::Unsupported(inst.class.opcode)),
+        None => Ok(()),
+    }
+}
+
+/// Returns true if the type `ty` has a null value in `module`.
+fn has_null(types: &BTreeMap<Word, &mr::Instruction>, ty: Word) -> bool {
+    let inst = match types.get(&ty) {
+        Some(inst) => inst,
+        None => return false,
+    };
+    match inst.class.opcode {
+        spirv::Op::TypeBool |
+        spirv::Op::TypeInt |
+        spirv::Op::TypeFloat |
+        spirv::Op::TypeVector |
+        spirv::Op::TypeMatrix |
+        spirv::Op::TypePointer |
+        spirv::Op::TypeEvent |
+        spirv::Op::TypeDeviceEvent |
+        spirv::Op::TypeReserveId |
+        spirv::Op::TypeQueue => true,
+        spirv::Op::TypeArray => {
+            let element = inst.operands.first().and_then(mr::Operand::id);
+            element.is_some_and(|element| has_null(types, element))
+        }
+        spirv::Op::TypeStruct => {
+            inst.operands.iter().all(|member| member.id().is_some_and(|m| has_null(types, m)))
+        }
+        _ => false,
+    }
+}
+
+fn check_undefs(module: &mr::Module) -> Result<()> {
+    let types: BTreeMap<Word, &mr::Instruction> = module.types_global_values
+        .iter()
+        .filter_map(|inst| inst.result_id.map(|id| (id, inst)))
+        .collect();
+    let undefined = module.types_global_values.iter().chain(function_insts(module)).any(|inst| {
+        inst.class.opcode == spirv::Op::Undef &&
+        !inst.result_type.is_some_and(|ty| has_null(&types, ty))
+    });
+    if undefined {
+        Err(Error::Unsupported(spirv::Op::Undef))
+    } else {
+        Ok(())
+    }
+}
+
+/// Replaces the `OpUndef`s of `module` with `OpConstantNull`s, all of
+/// which have types with null values.
+fn replace_undefs(module: &mut mr::Module) {
+    for inst in &mut module.types_global_values {
+        if inst.class.opcode == spirv::Op::Undef {
+            *inst = mr::Instruction::new(spirv::Op::ConstantNull,
+                                         inst.result_type,
+                                         inst.result_id,
+                                         vec![]);
+        }
+    }
+    let mut undefs = vec![];
+    for function in &mut module.functions {
+        for block in &mut function.basic_blocks {
+            block.instructions.retain(|inst| {
+                let undef = inst.class.opcode == spirv::Op::Undef;
+                if let (true, Some(ty), Some(id)) = (undef, inst.result_type, inst.result_id) {
+                    undefs.push((ty, id));
+                }
+                !undef
+            });
+        }
+    }
+    let mut ids = mr::IdAllocator::for_module(module);
+    for (ty, id) in undefs {
+        let null = find_or_add(module, &mut ids, spirv::Op::ConstantNull, Some(ty), vec![]);
+        module.replace_all_uses(id, null);
+    }
+    ids.update_header(module);
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{legalize_storage, upgrade_buffer_blocks, StorageTarget};
+    use passes::Error;
+
+    #[test]
+    fn test_upgrade_buffer_blocks() {
+        let mut b = mr::Builder::new();
+        let uint = b.type_int(32, 0);
+        let zero = b.constant_u32(uint, 0);
+        let block = b.type_struct(vec![uint]);
+        let buffer = b.type_struct(vec![uint]);
+        b.decorate(block, spirv::Decoration::Block, vec![]);
+        b.decorate(buffer, spirv::Decoration::BufferBlock, vec![]);
+        let block_ptr = b.type_pointer(None, spirv::StorageClass::Uniform, block);
+        let buffer_ptr = b.type_pointer(None, spirv::StorageClass::Uniform, buffer);
+        let uint_ptr = b.type_pointer(None, spirv::StorageClass::Uniform, uint);
+        let uniforms = b.variable(block_ptr, None, spirv::StorageClass::Uniform, None);
+        let storage = b.variable(buffer_ptr, None, spirv::StorageClass::Uniform, None);
+        let void = b.type_void();
+        let voidfvoid = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let from = b.access_chain(uint_ptr, None, uniforms, vec![zero]).unwrap();
+        let to = b.access_chain(uint_ptr, None, storage, vec![zero]).unwrap();
+        let x = b.load(uint, None, from, None, vec![]).unwrap();
+        b.store(to, x, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+        let bound = m.header.as_ref().unwrap().bound;
+
+        upgrade_buffer_blocks(&mut m).unwrap();
+        let storage_buffer = mr::Operand::StorageClass(spirv::StorageClass::StorageBuffer);
+        // The struct pointer type changes in place, the shared uint one
+        // gets a twin right after it.
+        let globals = &m.types_global_values;
+        assert_eq!(globals[5].operands, vec![storage_buffer.clone(), mr::Operand::IdRef(buffer)]);
+        assert_eq!(globals[7].result_id, Some(bound));
+        assert_eq!(globals[7].operands, vec![storage_buffer.clone(), mr::Operand::IdRef(uint)]);
+        assert_eq!(globals[8].operands[0],
+                   mr::Operand::StorageClass(spirv::StorageClass::Uniform));
+        assert_eq!(globals[9].operands[0], storage_buffer);
+        let insts = &m.functions[0].basic_blocks[0].instructions;
+        assert_eq!(insts[0].result_type, Some(uint_ptr));
+        assert_eq!(insts[1].result_type, Some(bound));
+        assert_eq!(m.annotations[1].operands[1],
+                   mr::Operand::Decoration(spirv::Decoration::Block));
+        assert_eq!(m.extensions[0].operands,
+                   vec![mr::Operand::from("SPV_KHR_storage_buffer_storage_class")]);
+        assert_eq!(m.header.as_ref().unwrap().bound, bound + 1);
+    }
+
+    #[test]
+    fn test_legalize_storage() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::VariablePointers);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let uint = b.type_int(32, 0);
+        let uint_ptr = b.type_pointer(None, spirv::StorageClass::Private, uint);
+        let ptr_ptr = b.type_pointer(None, spirv::StorageClass::Function, uint_ptr);
+        let global = b.variable(uint_ptr, None, spirv::StorageClass::Private, None);
+        let fty = b.type_function(uint, vec![]);
+        b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let holder = b.variable(ptr_ptr, None, spirv::StorageClass::Function, None);
+        b.store(holder, global, None, vec![]).unwrap();
+        let undef = b.undef(uint, None);
+        let pointer = b.load(uint_ptr, None, holder, None, vec![]).unwrap();
+        b.store(pointer, undef, None, vec![]).unwrap();
+        let x = b.load(uint, None, pointer, None, vec![]).unwrap();
+        b.ret_value(x).unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        legalize_storage(&mut m, &StorageTarget::webgpu()).unwrap();
+        assert!(m.capabilities.is_empty());
+        let null = m.types_global_values.last().unwrap();
+        assert_eq!(null.class.opcode, spirv::Op::ConstantNull);
+        let insts = &m.functions[0].basic_blocks[0].instructions;
+        assert_eq!(insts.len(), 3);
+        assert_eq!(insts[0].operands,
+                   vec![mr::Operand::IdRef(global), mr::Operand::IdRef(null.result_id.unwrap())]);
+        assert_eq!(insts[1].operands, vec![mr::Operand::IdRef(global)]);
+
+        m.memory_model.as_mut().unwrap().operands[0] =
+            mr::Operand::AddressingModel(spirv::AddressingModel::Physical32);
+        assert_eq!(legalize_storage(&mut m, &StorageTarget::webgpu()),
+                   Err(Error::Unsupported(spirv::Op::MemoryModel)));
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use analysis::Dominators;
+use binary::Assemble;
+use grammar;
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+use super::flatten::is_speculatable;
+
+/// Replaces the instructions of the functions of `module` computing the
+/// same value as an instruction of a dominating block, or before them in
+/// their block, by that instruction, and returns the number of
+/// instructions removed.
+///
+/// Instructions compute the same value if they have the same opcode,
+/// result type, and operands, after replacing the instructions removed,
+/// and have no side effects and cannot fault; see
+/// [`flatten_branches`](fn.flatten_branches.html). `OpUndef`s and
+/// decorated results are kept. Names of the removed results are removed.
+pub fn eliminate_common_subexpressions(module: &mut mr::Module) -> usize {
+    module.record_edits(|module| {
+        let decorated: BTreeSet<Word> = module.annotations
+            .iter()
+            .filter_map(|inst| inst.operands.first().and_then(mr::Operand::id))
+            .collect();
+        let glsl = mr::ExtInstRegistry::of(module).id_of(grammar::ExtInstSet::GlslStd450);
+
+        let mut removed = BTreeSet::new();
+        for function in &mut module.functions {
+            let dominators = Dominators::new(function);
+            let blocks: BTreeMap<Word, usize> = function.basic_blocks
+                .iter()
+                .enumerate()
+                .filter_map(|(i, b)| b.label.as_ref()?.result_id.map(|label| (label, i)))
+                .collect();
+            let mut replaced: BTreeMap<Word, Word> = BTreeMap::new();
+            let mut available: BTreeMap<Key, Vec<(Word, Word)>> = BTreeMap::new();
+            for &label in dominators.reverse_postorder() {
+                let block = &mut function.basic_blocks[blocks[&label]];
+                for inst in &mut block.instructions {
+                    for id in inst.operands.iter_mut().filter_map(mr::Operand::id_mut) {
+                        *id = replaced.get(id).cloned().unwrap_or(*id);
+                    }
+                    let id = match inst.result_id {
+                        Some(id) if !decorated.contains(&id) &&
+                                    inst.class.opcode != spirv::Op::Undef &&
+                                    is_speculatable(inst, glsl) => id,
+                        _ => continue,
+                    };
+                    let key = (inst.class.opcode as u32,
+                               inst.result_type,
+                               inst.operands
+                                   .iter()
+                                   .flat_map(|operand| operand.assemble())
+                                   .collect());
+                    let candidates = available.entry(key).or_default();
+                    let dominating =
+                        candidates.iter().find(|&&(block, _)| dominators.dominates(block, label));
+                    match dominating {
+                        Some(&(_, existing)) => {
+                            replaced.insert(id, existing);
+                        }
+                        None => candidates.push((label, id)),
+                    }
+                }
+            }
+
+            // Uses by phis and by unreachable blocks may not be visited yet.
+            for block in &mut function.basic_blocks {
+                block.instructions
+                    .retain(|inst| !inst.result_id.is_some_and(|id| replaced.contains_key(&id)));
+                for inst in &mut block.instructions {
+                    for id in inst.operands.iter_mut().filter_map(mr::Operand::id_mut) {
+                        *id = replaced.get(id).cloned().unwrap_or(*id);
+                    }
+                }
+            }
+            removed.extend(replaced.keys().cloned());
+        }
+        module.debugs.retain(|inst| {
+            !inst.operands.first().and_then(mr::Operand::id).is_some_and(|id| removed.contains(&id))
+        });
+        removed.len()
+    })
+}
+
+/// An opcode, result type, and operands, as words.
+type Key = (u32, Option<Word>, Vec<u32>);
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::eliminate_common_subexpressions;
+
+    #[test]
+    fn test_eliminate_common_subexpressions() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let boolean = b.type_bool();
+        let float = b.type_float(32);
+        let fty = b.type_function(void, vec![boolean, float]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let c = b.function_parameter(boolean).unwrap();
+        let x = b.function_parameter(float).unwrap();
+        let (then, merge) = (b.id(), b.id());
+        let header = b.begin_basic_block(None).unwrap();
+        let square = b.fmul(float, None, x, x).unwrap();
+        b.selection_merge(merge, spirv::SelectionControl::NONE).unwrap();
+        b.branch_conditional(c, then, merge, vec![]).unwrap();
+        b.begin_basic_block(Some(then)).unwrap();
+        let again = b.fmul(float, None, x, x).unwrap();
+        let sum = b.fadd(float, None, again, x).unwrap();
+        b.name(again, "again");
+        b.branch(merge).unwrap();
+        b.begin_basic_block(Some(merge)).unwrap();
+        // Only dominated by the first block.
+        let other_sum = b.fadd(float, None, square, x).unwrap();
+        b.phi(float, None, vec![(sum, then), (x, header)]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        assert_eq!(eliminate_common_subexpressions(&mut m), 1);
+        assert!(m.debugs.is_empty());
+        let blocks = &m.functions[0].basic_blocks;
+        assert_eq!(blocks[1].instructions[0].result_id, Some(sum));
+        assert_eq!(blocks[1].instructions[0].operands,
+                   vec![mr::Operand::IdRef(square), mr::Operand::IdRef(x)]);
+        assert_eq!(blocks[2].instructions[0].result_id, Some(other_sum));
+    }
+}
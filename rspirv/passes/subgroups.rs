@@ -0,0 +1,311 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use env::TargetEnv;
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::BTreeSet;
+use super::{add_capability, add_extension, find_or_add, Error, Result};
+
+const SHADER_BALLOT: &str = "SPV_KHR_shader_ballot";
+
+/// The subgroup operations a target supports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SubgroupTarget {
+    /// Whether the target supports `OpGroupNonUniformBroadcast` and
+    /// `OpGroupNonUniformBroadcastFirst`.
+    pub broadcast: bool,
+    /// Whether the target supports `OpGroupNonUniformBallot` and
+    /// `OpGroupNonUniformBallotFindLSB`.
+    pub ballot: bool,
+    /// Whether the target supports the `GroupNonUniformShuffle`
+    /// capability.
+    pub shuffle: bool,
+    /// Whether the target supports `SPV_KHR_shader_ballot`.
+    pub shader_ballot: bool,
+}
+
+impl SubgroupTarget {
+    /// Creates a target supporting the operations the given
+    /// `capabilities` enable.
+    ///
+    /// Some targets support ballots but not broadcasts, though both come
+    /// with the `GroupNonUniformBallot` capability; clear `broadcast`
+    /// afterwards to describe them.
+    pub fn new(capabilities: &[spirv::Capability]) -> SubgroupTarget {
+        let ballot = capabilities.contains(&spirv::Capability::GroupNonUniformBallot);
+        SubgroupTarget {
+            broadcast: ballot,
+            ballot,
+            shuffle: capabilities.contains(&spirv::Capability::GroupNonUniformShuffle),
+            shader_ballot: capabilities.contains(&spirv::Capability::SubgroupBallotKHR),
+        }
+    }
+
+    /// Creates a target supporting the operations of the capabilities
+    /// `env` accepts without extensions.
+    ///
+    /// Environments accept more than devices may support: Vulkan leaves
+    /// subgroup operations beyond `GroupNonUniform` optional.
+    pub fn for_env(env: TargetEnv) -> SubgroupTarget {
+        let ballot = env.allows_capability(spirv::Capability::GroupNonUniformBallot);
+        SubgroupTarget {
+            broadcast: ballot,
+            ballot,
+            shuffle: env.allows_capability(spirv::Capability::GroupNonUniformShuffle),
+            shader_ballot: false,
+        }
+    }
+}
+
+/// How a subgroup operation is emulated.
+#[derive(Clone, Copy, PartialEq)]
+enum Lowering {
+    /// Shuffling from the lowest invocation in the ballot of all active
+    /// ones.
+    BallotShuffle,
+    /// Shuffling from the invocation broadcast from.
+    Shuffle,
+    FirstInvocation,
+    ReadInvocation,
+}
+
+/// Emulates the subgroup operations in the functions of `module` that
+/// `target` does not support with those it does.
+///
+/// * `OpGroupNonUniformBroadcastFirst` becomes an
+///   `OpGroupNonUniformShuffle` from the invocation
+///   `OpGroupNonUniformBallotFindLSB` finds in the ballot of all active
+///   invocations, or else, at subgroup scope, an
+///   `OpSubgroupFirstInvocationKHR`.
+/// * `OpGroupNonUniformBroadcast` becomes an `OpGroupNonUniformShuffle`
+///   from the same invocation, or else, at subgroup scope, an
+///   `OpSubgroupReadInvocationKHR`.
+///
+/// The capabilities and extensions the emulation uses are declared. It is
+/// an error for an operation to have no emulation on the target.
+pub fn legalize_subgroup_ops(module: &mut mr::Module, target: &SubgroupTarget) -> Result<()> {
+    module.record_edits(|module| {
+        let subgroup_scopes: BTreeSet<Word> = module.types_global_values
+            .iter()
+            .filter(|inst| {
+                inst.class.opcode == spirv::Op::Constant &&
+                inst.operands.first() ==
+                Some(&mr::Operand::LiteralInt32(spirv::Scope::Subgroup as u32))
+            })
+            .filter_map(|inst| inst.result_id)
+            .collect();
+        let mut lowerings = vec![];
+        for (f, function) in module.functions.iter().enumerate() {
+            for (b, block) in function.basic_blocks.iter().enumerate() {
+                for (i, inst) in block.instructions.iter().enumerate() {
+                    if let Some(lowering) = lowering(inst, target, &subgroup_scopes)? {
+                        lowerings.push((f, b, i, lowering));
+                    }
+                }
+            }
+        }
+        if lowerings.is_empty() {
+            return Ok(());
+        }
+
+        let mut ids = mr::IdAllocator::for_module(module);
+        let ballot_shuffle = lowerings.iter().any(|&(.., l)| l == Lowering::BallotShuffle);
+        let (uint, uvec4, tru) = if ballot_shuffle {
+            let mut global = |opcode, result_type, operands| {
+                find_or_add(module, &mut ids, opcode, result_type, operands)
+            };
+            let uint = global(spirv::Op::TypeInt,
+                              None,
+                              vec![mr::Operand::LiteralInt32(32), mr::Operand::LiteralInt32(0)]);
+            let uvec4 = global(spirv::Op::TypeVector,
+                               None,
+                               vec![mr::Operand::IdRef(uint), mr::Operand::LiteralInt32(4)]);
+            let boolean = global(spirv::Op::TypeBool, None, vec![]);
+            (uint, uvec4, global(spirv::Op::ConstantTrue, Some(boolean), vec![]))
+        } else {
+            (0, 0, 0)
+        };
+
+        // Rewrite from the back so indices of earlier instructions hold.
+        for &(f, b, i, lowering) in lowerings.iter().rev() {
+            let instructions = &mut module.functions[f].basic_blocks[b].instructions;
+            let inst = &instructions[i];
+            let (ty, id, operands) = (inst.result_type, inst.result_id, inst.operands.clone());
+            let (value, rest) = (operands[1].clone(), operands[2..].to_vec());
+            let replacement = match lowering {
+                Lowering::BallotShuffle => {
+                    let (mask, lane) = (ids.id(), ids.id());
+                    let scope = operands[0].clone();
+                    vec![mr::Instruction::new(spirv::Op::GroupNonUniformBallot,
+                                              Some(uvec4),
+                                              Some(mask),
+                                              vec![scope.clone(), mr::Operand::IdRef(tru)]),
+                         mr::Instruction::new(spirv::Op::GroupNonUniformBallotFindLSB,
+                                              Some(uint),
+                                              Some(lane),
+                                              vec![scope.clone(), mr::Operand::IdRef(mask)]),
+                         mr::Instruction::new(spirv::Op::GroupNonUniformShuffle,
+                                              ty,
+                                              id,
+                                              vec![scope, value, mr::Operand::IdRef(lane)])]
+                }
+                Lowering::Shuffle => {
+                    vec![mr::Instruction::new(spirv::Op::GroupNonUniformShuffle, ty, id, operands)]
+                }
+                Lowering::FirstInvocation | Lowering::ReadInvocation => {
+                    let opcode = if lowering == Lowering::FirstInvocation {
+                        spirv::Op::SubgroupFirstInvocationKHR
+                    } else {
+                        spirv::Op::SubgroupReadInvocationKHR
+                    };
+                    let operands: mr::Operands = Some(value).into_iter().chain(rest).collect();
+                    vec![mr::Instruction::new(opcode, ty, id, operands)]
+                }
+            };
+            instructions.splice(i..i + 1, replacement);
+        }
+
+        if lowerings.iter().any(|&(.., l)| l == Lowering::BallotShuffle || l == Lowering::Shuffle) {
+            add_capability(module, spirv::Capability::GroupNonUniformShuffle);
+        }
+        if lowerings.iter()
+            .any(|&(.., l)| l == Lowering::FirstInvocation || l == Lowering::ReadInvocation) {
+            add_capability(module, spirv::Capability::SubgroupBallotKHR);
+            add_extension(module, SHADER_BALLOT);
+        }
+        ids.update_header(module);
+        Ok(())
+    })
+}
+
+/// Returns how `inst` is emulated on `target`, or `None` if it needs no
+/// emulation.
+fn lowering(inst: &mr::Instruction,
+            target: &SubgroupTarget,
+            subgroup_scopes: &BTreeSet<Word>)
+            -> Result<Option<Lowering>> {
+    let opcode = inst.class.opcode;
+    let (first, via_shuffle, via_khr) = match opcode {
+        spirv::Op::GroupNonUniformBroadcastFirst => {
+            (true, target.ballot && target.shuffle, Lowering::FirstInvocation)
+        }
+        spirv::Op::GroupNonUniformBroadcast => (false, target.shuffle, Lowering::ReadInvocation),
+        _ => return Ok(None),
+    };
+    if target.broadcast {
+        return Ok(None);
+    }
+    let subgroup = match inst.operands.first() {
+        Some(&mr::Operand::IdScope(scope)) => subgroup_scopes.contains(&scope),
+        _ => false,
+    };
+    if via_shuffle {
+        Ok(Some(if first { Lowering::BallotShuffle } else { Lowering::Shuffle }))
+    } else if target.shader_ballot && subgroup {
+        Ok(Some(via_khr))
+    } else {
+        Err(Error::Unsupported(opcode))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use env::TargetEnv;
+    use mr;
+    use spirv;
+
+    use super::{legalize_subgroup_ops, SubgroupTarget};
+    use passes::Error;
+    use utils::test::opcodes;
+
+    /// Builds a function broadcasting its parameter from the first active
+    /// invocation and from invocation 1 of the subgroup.
+    fn build() -> mr::Module {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::GroupNonUniformBallot);
+        let uint = b.type_int(32, 0);
+        let subgroup = b.constant_scope(uint, spirv::Scope::Subgroup);
+        let one = b.constant_u32(uint, 1);
+        let fty = b.type_function(uint, vec![uint]);
+        b.begin_function(uint, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let x = b.function_parameter(uint).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let first = b.group_non_uniform_broadcast_first(uint, None, subgroup, x).unwrap();
+        let read = b.group_non_uniform_broadcast(uint, None, subgroup, x, one).unwrap();
+        let sum = b.iadd(uint, None, first, read).unwrap();
+        b.ret_value(sum).unwrap();
+        b.end_function().unwrap();
+        b.module()
+    }
+
+    #[test]
+    fn test_ballot_shuffle() {
+        let mut m = build();
+        let bound = m.header.as_ref().unwrap().bound;
+        // The lane broadcast from is the constant 1.
+        let one = mr::Operand::IdRef(m.types_global_values[2].result_id.unwrap());
+        assert_eq!(m.functions[0].basic_blocks[0].instructions[1].operands[2], one);
+        let mut target = SubgroupTarget::new(&[spirv::Capability::GroupNonUniformBallot,
+                                               spirv::Capability::GroupNonUniformShuffle]);
+        assert_eq!(target, SubgroupTarget::for_env(TargetEnv::Vulkan1_1));
+        assert!(!SubgroupTarget::for_env(TargetEnv::Vulkan1_0).ballot);
+        target.broadcast = false;
+        legalize_subgroup_ops(&mut m, &target).unwrap();
+
+        let block = &m.functions[0].basic_blocks[0];
+        assert_eq!(opcodes(block),
+                   vec![spirv::Op::GroupNonUniformBallot,
+                        spirv::Op::GroupNonUniformBallotFindLSB,
+                        spirv::Op::GroupNonUniformShuffle,
+                        spirv::Op::GroupNonUniformShuffle,
+                        spirv::Op::IAdd,
+                        spirv::Op::ReturnValue]);
+        assert_eq!(block.instructions[2].operands[2], mr::Operand::IdRef(bound + 4));
+        assert_eq!(block.instructions[3].operands[2], one);
+        // The uint type is reused; uvec4, bool, and true are added.
+        let added: Vec<spirv::Op> = m.types_global_values[4..]
+            .iter()
+            .map(|inst| inst.class.opcode)
+            .collect();
+        assert_eq!(added,
+                   vec![spirv::Op::TypeVector, spirv::Op::TypeBool, spirv::Op::ConstantTrue]);
+        assert_eq!(m.header.as_ref().unwrap().bound, bound + 5);
+        assert_eq!(m.capabilities[1].operands,
+                   vec![mr::Operand::Capability(spirv::Capability::GroupNonUniformShuffle)]);
+    }
+
+    #[test]
+    fn test_shader_ballot() {
+        let mut m = build();
+        let target = SubgroupTarget::new(&[spirv::Capability::SubgroupBallotKHR]);
+        legalize_subgroup_ops(&mut m, &target).unwrap();
+        assert_eq!(opcodes(&m.functions[0].basic_blocks[0]),
+                   vec![spirv::Op::SubgroupFirstInvocationKHR,
+                        spirv::Op::SubgroupReadInvocationKHR,
+                        spirv::Op::IAdd,
+                        spirv::Op::ReturnValue]);
+        assert_eq!(m.extensions[0].operands, vec![mr::Operand::from("SPV_KHR_shader_ballot")]);
+        let one = mr::Operand::IdRef(m.types_global_values[2].result_id.unwrap());
+        assert_eq!(m.functions[0].basic_blocks[0].instructions[1].operands[1], one);
+
+        let mut m = build();
+        let target = SubgroupTarget::new(&[]);
+        assert_eq!(legalize_subgroup_ops(&mut m, &target),
+                   Err(Error::Unsupported(spirv::Op::GroupNonUniformBroadcastFirst)));
+        assert_eq!(m.functions[0].basic_blocks[0].instructions.len(), 4);
+    }
+}
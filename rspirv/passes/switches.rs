@@ -0,0 +1,705 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use analysis::Dominators;
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+use std::mem;
+
+use super::{Error, Result};
+
+/// A decoded `OpSwitch`.
+struct Switch {
+    selector: Word,
+    default: Word,
+    /// The type of the selector, if known.
+    selector_type: Option<Word>,
+    /// The case literals, with their targets.
+    cases: Vec<(u64, Word)>,
+    /// Whether the literals take two words.
+    wide: bool,
+    width: u32,
+    signed: bool,
+}
+
+impl Switch {
+    /// Returns a key ordering the case `literal` as an integer of the type
+    /// of the selector.
+    fn key(&self, literal: u64) -> u64 {
+        if !self.signed || self.width == 0 || self.width >= 64 {
+            return literal;
+        }
+        let shift = 64 - self.width;
+        // Flipping the sign bit orders signed integers as unsigned ones.
+        (((literal << shift) as i64 >> shift) as u64) ^ (1 << 63)
+    }
+
+    fn operands(&self) -> Vec<mr::Operand> {
+        let mut operands = vec![mr::Operand::IdRef(self.selector), mr::Operand::IdRef(self.default)];
+        for &(literal, target) in &self.cases {
+            operands.push(if self.wide {
+                mr::Operand::LiteralInt64(literal)
+            } else {
+                mr::Operand::LiteralInt32(literal as u32)
+            });
+            operands.push(mr::Operand::IdRef(target));
+        }
+        operands
+    }
+}
+
+/// Decodes the `OpSwitch`es ending the blocks of `module`, by function and
+/// block index.
+fn decode_switches(module: &mr::Module) -> Result<BTreeMap<(usize, usize), Switch>> {
+    let mut ints = BTreeMap::new();
+    for inst in &module.types_global_values {
+        if inst.class.opcode != spirv::Op::TypeInt {
+            continue;
+        }
+        let (width, signed) = (inst.operands.first(), inst.operands.get(1));
+        if let (Some(id), Some(&mr::Operand::LiteralInt32(width)), Some(&mr::Operand::LiteralInt32(signed))) =
+            (inst.result_id, width, signed) {
+            ints.insert(id, (width, signed != 0));
+        }
+    }
+    let mut types = BTreeMap::new();
+    {
+        let insts = module.types_global_values.iter().chain(module.functions.iter().flat_map(|f| {
+            f.parameters.iter().chain(f.basic_blocks.iter().flat_map(|b| &b.instructions))
+        }));
+        for inst in insts {
+            if let (Some(id), Some(ty)) = (inst.result_id, inst.result_type) {
+                types.insert(id, ty);
+            }
+        }
+    }
+
+    let mut switches = BTreeMap::new();
+    for (fi, f) in module.functions.iter().enumerate() {
+        for (bi, block) in f.basic_blocks.iter().enumerate() {
+            let inst = match block.instructions.last() {
+                Some(inst) if inst.class.opcode == spirv::Op::Switch => inst,
+                _ => continue,
+            };
+            let label = block.label.as_ref().and_then(|l| l.result_id).unwrap_or(0);
+            let (selector, default) = match (inst.operands.first().and_then(mr::Operand::id),
+                                             inst.operands.get(1).and_then(mr::Operand::id)) {
+                (Some(selector), Some(default)) => (selector, default),
+                _ => continue,
+            };
+            let selector_type = types.get(&selector).cloned();
+            let int = selector_type.and_then(|ty| ints.get(&ty).cloned());
+            let literals = &inst.operands[2..];
+            let wide = match int {
+                Some((width, _)) => width > 32,
+                None => literals.iter().any(|op| matches!(*op, mr::Operand::LiteralInt64(_))),
+            };
+            let cases = decode_cases(literals, wide).ok_or(Error::SwitchLiteralWidth(label))?;
+            let (width, signed) = int.unwrap_or((if wide { 64 } else { 32 }, false));
+            switches.insert((fi, bi),
+                            Switch {
+                                selector,
+                                default,
+                                selector_type,
+                                cases,
+                                wide,
+                                width,
+                                signed,
+                            });
+        }
+    }
+    Ok(switches)
+}
+
+/// Decodes the case `operands` of an `OpSwitch`, returning `None` if they
+/// are not encoded as literals of the given width.
+fn decode_cases(operands: &[mr::Operand], wide: bool) -> Option<Vec<(u64, Word)>> {
+    let raw: Option<Vec<Word>> = operands.iter()
+        .map(|op| match *op {
+            mr::Operand::LiteralInt32(word) | mr::Operand::IdRef(word) => Some(word),
+            _ => None,
+        })
+        .collect();
+    if let (true, Some(words)) = (wide, raw) {
+        // The binary parser does not know the types of selectors, and
+        // reads one word for each literal.
+        if !words.len().is_multiple_of(3) {
+            return None;
+        }
+        return Some(words.chunks(3)
+            .map(|case| (u64::from(case[0]) | (u64::from(case[1]) << 32), case[2]))
+            .collect());
+    }
+    if !operands.len().is_multiple_of(2) {
+        return None;
+    }
+    operands.chunks(2)
+        .map(|pair| match (&pair[0], &pair[1], wide) {
+            (&mr::Operand::LiteralInt32(v), &mr::Operand::IdRef(target), false) => {
+                Some((u64::from(v), target))
+            }
+            (&mr::Operand::LiteralInt64(v), &mr::Operand::IdRef(target), true) => Some((v, target)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Normalizes the `OpSwitch`es of `module`.
+///
+/// Cases are sorted by their literals, as integers of the signedness of
+/// the selector. Cases branching to the default target, and repeated
+/// cases, are removed.
+///
+/// The case literals of 64-bit selectors are encoded in two words each.
+/// Cases of such switches read as one-word literals, as the binary parser
+/// does without knowing the type of the selector, are regrouped into
+/// `LiteralInt64`s.
+///
+/// Returns an error if the case literals cannot be encoded for the width
+/// of the selector, or if a literal branches to different targets.
+pub fn normalize_switches(module: &mut mr::Module) -> Result<()> {
+    module.record_edits(|module| {
+        let mut switches = decode_switches(module)?;
+        for (&(fi, bi), switch) in &mut switches {
+            let label = module.functions[fi].basic_blocks[bi]
+                .label
+                .as_ref()
+                .and_then(|l| l.result_id)
+                .unwrap_or(0);
+            let mut cases = mem::take(&mut switch.cases);
+            cases.sort_by_key(|&(literal, _)| switch.key(literal));
+            for pair in cases.windows(2) {
+                if switch.key(pair[0].0) == switch.key(pair[1].0) && pair[0].1 != pair[1].1 {
+                    return Err(Error::DuplicateSwitchCase(label));
+                }
+            }
+            cases.dedup();
+            cases.retain(|&(_, target)| target != switch.default);
+            switch.cases = cases;
+        }
+        for ((fi, bi), switch) in switches {
+            let inst = module.functions[fi].basic_blocks[bi].instructions.last_mut().unwrap();
+            inst.operands = switch.operands().into();
+        }
+        Ok(())
+    })
+}
+
+/// Lowers the `OpSwitch`es of `module` with at most `max_cases` cases into
+/// chains of `OpIEqual`s and `OpBranchConditional`s.
+///
+/// Each further case after the first is tested in a new block placed
+/// after the switch block, which falls through to the next test and
+/// eventually to the default target. In structured control flow each
+/// conditional branch needs its own merge block, so switches declared by
+/// an `OpSelectionMerge` are only lowered with at most one case. A switch
+/// without cases becomes an `OpBranch` to the default target. `OpPhi`s of
+/// the targets name the blocks they are now branched to from as parents.
+///
+/// Missing constants and the boolean type are added to the module.
+/// Returns an error if case literals cannot be decoded, as described in
+/// [`normalize_switches`](fn.normalize_switches.html).
+pub fn lower_switches(module: &mut mr::Module, max_cases: usize) -> Result<()> {
+    module.record_edits(|module| {
+        let switches = decode_switches(module)?;
+        if switches.values().all(|s| s.cases.len() > max_cases) {
+            return Ok(());
+        }
+
+        let mut ids = mr::IdAllocator::for_module(module);
+        let mut globals = vec![];
+        let mut bool_type = module.types_global_values
+            .iter()
+            .find(|inst| inst.class.opcode == spirv::Op::TypeBool)
+            .and_then(|inst| inst.result_id);
+        let mut constants = BTreeMap::new();
+        for inst in &module.types_global_values {
+            if inst.class.opcode != spirv::Op::Constant {
+                continue;
+            }
+            let literal = match inst.operands.first() {
+                Some(&mr::Operand::LiteralInt32(v)) => (u64::from(v), false),
+                Some(&mr::Operand::LiteralInt64(v)) => (v, true),
+                _ => continue,
+            };
+            if let (Some(id), Some(ty)) = (inst.result_id, inst.result_type) {
+                constants.entry((ty, literal)).or_insert(id);
+            }
+        }
+
+        // Rewrite from the back so that earlier block indices stay valid.
+        for (&(fi, bi), switch) in switches.iter().rev() {
+            let n = switch.cases.len();
+            let f = &mut module.functions[fi];
+            let header = match f.basic_blocks[bi].label.as_ref().and_then(|l| l.result_id) {
+                Some(label) => label,
+                None => continue,
+            };
+            let mut insts = mem::take(&mut f.basic_blocks[bi].instructions);
+            let terminator = insts.pop().unwrap();
+            let mut merge = match insts.last() {
+                Some(inst) if inst.class.opcode == spirv::Op::SelectionMerge => insts.pop(),
+                _ => None,
+            };
+            let ty = switch.selector_type;
+            if n > max_cases || (merge.is_some() && n > 1) || (n > 0 && ty.is_none()) {
+                insts.extend(merge);
+                insts.push(terminator);
+                f.basic_blocks[bi].instructions = insts;
+                continue;
+            }
+            if n == 0 {
+                insts.push(mr::Instruction::new(spirv::Op::Branch,
+                                                None,
+                                                None,
+                                                vec![mr::Operand::IdRef(switch.default)]));
+                f.basic_blocks[bi].instructions = insts;
+                continue;
+            }
+            let ty = ty.unwrap();
+
+            let mut chain = vec![header];
+            for _ in 1..n {
+                chain.push(ids.id());
+            }
+            let bool_type = *bool_type.get_or_insert_with(|| {
+                let id = ids.id();
+                globals.push(mr::Instruction::new(spirv::Op::TypeBool, None, Some(id), vec![]));
+                id
+            });
+            let mut blocks = vec![];
+            for (i, &(literal, target)) in switch.cases.iter().enumerate() {
+                let wide = switch.wide;
+                let constant = *constants.entry((ty, (literal, wide))).or_insert_with(|| {
+                    let literal = if wide {
+                        mr::Operand::LiteralInt64(literal)
+                    } else {
+                        mr::Operand::LiteralInt32(literal as u32)
+                    };
+                    let id = ids.id();
+                    globals.push(mr::Instruction::new(spirv::Op::Constant,
+                                                      Some(ty),
+                                                      Some(id),
+                                                      vec![literal]));
+                    id
+                });
+                let equal = ids.id();
+                let mut block = if i == 0 {
+                    mr::BasicBlock {
+                        label: f.basic_blocks[bi].label.take(),
+                        instructions: mem::take(&mut insts),
+                    }
+                } else {
+                    let mut block = mr::BasicBlock::new();
+                    block.label =
+                        Some(mr::Instruction::new(spirv::Op::Label, None, Some(chain[i]), vec![]));
+                    block
+                };
+                let operands =
+                    vec![mr::Operand::IdRef(switch.selector), mr::Operand::IdRef(constant)];
+                block.instructions.push(mr::Instruction::new(spirv::Op::IEqual,
+                                                             Some(bool_type),
+                                                             Some(equal),
+                                                             operands));
+                block.instructions.extend(merge.take());
+                let otherwise = chain.get(i + 1).cloned().unwrap_or(switch.default);
+                block.instructions.push(mr::Instruction::new(spirv::Op::BranchConditional,
+                                                             None,
+                                                             None,
+                                                             vec![mr::Operand::IdRef(equal),
+                                                                  mr::Operand::IdRef(target),
+                                                                  mr::Operand::IdRef(otherwise)]));
+                blocks.push(block);
+            }
+            f.basic_blocks.splice(bi..bi + 1, blocks);
+
+            // The chain blocks now branching to each target.
+            let mut parents: BTreeMap<Word, Vec<Word>> = BTreeMap::new();
+            for (i, &(_, target)) in switch.cases.iter().enumerate() {
+                parents.entry(target).or_default().push(chain[i]);
+            }
+            parents.entry(switch.default).or_default().push(chain[n - 1]);
+            for block in &mut f.basic_blocks {
+                let label = block.label.as_ref().and_then(|l| l.result_id);
+                let parents = match label.and_then(|l| parents.get(&l)) {
+                    Some(parents) => parents,
+                    None => continue,
+                };
+                for inst in &mut block.instructions {
+                    if inst.class.opcode != spirv::Op::Phi {
+                        break;
+                    }
+                    let pairs = inst.operands.split_off(0);
+                    for pair in pairs.chunks(2) {
+                        if pair.get(1) == Some(&mr::Operand::IdRef(header)) {
+                            for &parent in parents {
+                                inst.operands.push(pair[0].clone());
+                                inst.operands.push(mr::Operand::IdRef(parent));
+                            }
+                        } else {
+                            inst.operands.extend_from_slice(pair);
+                        }
+                    }
+                }
+            }
+        }
+
+        module.types_global_values.append(&mut globals);
+        ids.update_header(module);
+        Ok(())
+    })
+}
+
+/// Raises chains of `OpBranchConditional`s testing the same integer for
+/// equality with constants into `OpSwitch`es.
+///
+/// A chain starts at a block branching on an `OpIEqual` of a value and an
+/// `OpConstant`. It continues through the false targets that consist of
+/// nothing but such a comparison of the same value, used only by their
+/// branch, have no other predecessors, and test a new literal. Chains of
+/// at least two cases are replaced by an `OpSwitch` in the first block,
+/// defaulting to the false target of the last, and the other blocks are
+/// removed. Chains stop before a block whose targets would then be
+/// reached through several cases and have `OpPhi`s, as those could not
+/// tell the cases apart anymore.
+pub fn raise_branch_chains(module: &mut mr::Module) {
+    module.record_edits(|module| {
+        let constants: BTreeMap<Word, mr::Operand> = module.types_global_values
+            .iter()
+            .filter(|inst| inst.class.opcode == spirv::Op::Constant)
+            .filter_map(|inst| match (inst.result_id, inst.operands.first()) {
+                (Some(id), Some(literal @ &mr::Operand::LiteralInt32(_))) |
+                (Some(id), Some(literal @ &mr::Operand::LiteralInt64(_))) => {
+                    Some((id, literal.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        let mut removed = BTreeSet::new();
+        for f in &mut module.functions {
+            removed.extend(raise_in(f, &constants));
+        }
+        let targets_removed = |inst: &mr::Instruction| match inst.operands.first() {
+            Some(&mr::Operand::IdRef(id)) => removed.contains(&id),
+            _ => false,
+        };
+        module.debugs.retain(|inst| !targets_removed(inst));
+        module.annotations.retain(|inst| !targets_removed(inst));
+    })
+}
+
+/// Raises the branch chains of `function`, and returns the ids defined by
+/// the removed blocks.
+fn raise_in(function: &mut mr::Function, constants: &BTreeMap<Word, mr::Operand>) -> BTreeSet<Word> {
+    let dominators = Dominators::new(function);
+    let label_of = |block: &mr::BasicBlock| block.label.as_ref().and_then(|l| l.result_id);
+    let blocks: BTreeMap<Word, usize> = function.basic_blocks
+        .iter()
+        .enumerate()
+        .filter_map(|(i, b)| label_of(b).map(|l| (l, i)))
+        .collect();
+    let mut uses: BTreeMap<Word, usize> = BTreeMap::new();
+    for inst in function.basic_blocks.iter().flat_map(|b| &b.instructions) {
+        for (id, _) in inst.id_operands() {
+            *uses.entry(id).or_default() += 1;
+        }
+    }
+    let has_phis = |label: Word| {
+        blocks.get(&label)
+            .and_then(|&i| function.basic_blocks[i].instructions.first())
+            .is_some_and(|inst| inst.class.opcode == spirv::Op::Phi)
+    };
+    // (value, literal) compared by an OpIEqual.
+    let comparison = |inst: &mr::Instruction| {
+        if inst.class.opcode != spirv::Op::IEqual || inst.operands.len() != 2 {
+            return None;
+        }
+        match (inst.operands[0].id(), inst.operands[1].id()) {
+            (Some(a), Some(b)) => {
+                match (constants.get(&a), constants.get(&b)) {
+                    (None, Some(literal)) => Some((a, literal.clone())),
+                    (Some(literal), None) => Some((b, literal.clone())),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    };
+    // (condition, true target, false target) of an OpBranchConditional
+    // without weights.
+    let branch = |inst: &mr::Instruction| {
+        if inst.class.opcode != spirv::Op::BranchConditional || inst.operands.len() != 3 {
+            return None;
+        }
+        match (inst.operands[0].id(), inst.operands[1].id(), inst.operands[2].id()) {
+            (Some(c), Some(t), Some(f)) => Some((c, t, f)),
+            _ => None,
+        }
+    };
+
+    // (head block, selector, default, cases, consumed blocks)
+    let mut chains = vec![];
+    let mut consumed = BTreeSet::new();
+    for (h, block) in function.basic_blocks.iter().enumerate() {
+        let head = match label_of(block) {
+            Some(label) if !consumed.contains(&h) => label,
+            _ => continue,
+        };
+        let (condition, target, mut current) = match block.instructions.last().and_then(&branch) {
+            Some(branch) => branch,
+            None => continue,
+        };
+        let (selector, literal) = match block.instructions
+            .iter()
+            .find(|inst| inst.result_id == Some(condition))
+            .and_then(&comparison) {
+            Some(comparison) => comparison,
+            None => continue,
+        };
+        let mut cases = vec![(literal, target)];
+        let mut chain = vec![];
+        loop {
+            let b = match blocks.get(&current) {
+                Some(&b) if b != h && !consumed.contains(&b) && !chain.contains(&b) => b,
+                _ => break,
+            };
+            if dominators.predecessors(current).len() != 1 ||
+               function.basic_blocks[b].instructions.len() != 2 {
+                break;
+            }
+            let insts = &function.basic_blocks[b].instructions;
+            let (equal, (value, literal)) = match (insts[0].result_id, comparison(&insts[0])) {
+                (Some(equal), Some(comparison)) => (equal, comparison),
+                _ => break,
+            };
+            let (condition, target, otherwise) = match branch(&insts[1]) {
+                Some(branch) => branch,
+                None => break,
+            };
+            let targets: Vec<Word> = cases.iter().map(|&(_, t)| t).collect();
+            if value != selector || condition != equal || uses.get(&equal) != Some(&1) ||
+               cases.iter().any(|(l, _)| *l == literal) ||
+               (has_phis(target) && targets.contains(&target)) ||
+               (has_phis(otherwise) && (targets.contains(&otherwise) || otherwise == target)) {
+                break;
+            }
+            cases.push((literal, target));
+            chain.push(b);
+            current = otherwise;
+        }
+        if cases.len() < 2 {
+            continue;
+        }
+        consumed.extend(chain.iter().cloned());
+        chains.push((h, head, selector, current, cases, chain));
+    }
+
+    let mut removed = BTreeSet::new();
+    let mut renamed = BTreeMap::new();
+    for &(h, head, selector, default, ref cases, ref chain) in &chains {
+        let mut operands = vec![mr::Operand::IdRef(selector), mr::Operand::IdRef(default)];
+        for &(ref literal, target) in cases {
+            operands.push(literal.clone());
+            operands.push(mr::Operand::IdRef(target));
+        }
+        *function.basic_blocks[h].instructions.last_mut().unwrap() =
+            mr::Instruction::new(spirv::Op::Switch, None, None, operands);
+        for &b in chain {
+            let block = &function.basic_blocks[b];
+            let label = label_of(block).unwrap();
+            renamed.insert(label, head);
+            removed.insert(label);
+            removed.extend(block.instructions.iter().filter_map(|inst| inst.result_id));
+        }
+    }
+    let mut index = 0;
+    function.basic_blocks.retain(|_| {
+        index += 1;
+        !consumed.contains(&(index - 1))
+    });
+    for block in &mut function.basic_blocks {
+        for inst in &mut block.instructions {
+            if inst.class.opcode != spirv::Op::Phi {
+                break;
+            }
+            for parent in inst.operands.iter_mut().skip(1).step_by(2) {
+                if let Some(&head) = parent.id().and_then(|p| renamed.get(&p)) {
+                    *parent = mr::Operand::IdRef(head);
+                }
+            }
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use passes::Error;
+    use super::{lower_switches, normalize_switches, raise_branch_chains};
+    use utils::test::opcodes;
+
+    fn refs(ids: &[spirv::Word]) -> Vec<mr::Operand> {
+        ids.iter().map(|&id| mr::Operand::IdRef(id)).collect()
+    }
+
+    /// Builds a function switching on its signed parameter over the given
+    /// cases, indices into four target blocks, with target 3 as default.
+    /// Each target returns a distinct value through an OpPhi in the exit
+    /// block.
+    fn build(cases: &[(u32, usize)], merge: bool) -> (mr::Module, Vec<spirv::Word>) {
+        let mut b = mr::Builder::new();
+        let int = b.type_int(32, 1);
+        let fty = b.type_function(int, vec![int]);
+        b.begin_function(int, None, spirv::FunctionControl::NONE, fty).unwrap();
+        let param = b.function_parameter(int).unwrap();
+        let head = b.begin_basic_block(None).unwrap();
+        let targets = vec![b.id(), b.id(), b.id(), b.id()];
+        let exit = b.id();
+        if merge {
+            b.selection_merge(exit, spirv::SelectionControl::NONE).unwrap();
+        }
+        let cases: Vec<(u32, spirv::Word)> = cases.iter().map(|&(l, t)| (l, targets[t])).collect();
+        b.switch(param, targets[3], cases).unwrap();
+        let mut incoming = vec![];
+        for (i, &target) in targets.iter().enumerate() {
+            b.begin_basic_block(Some(target)).unwrap();
+            incoming.push((b.constant_u32(int, i as u32), target));
+            b.branch(exit).unwrap();
+        }
+        b.begin_basic_block(Some(exit)).unwrap();
+        let phi = b.phi(int, None, incoming).unwrap();
+        b.ret_value(phi).unwrap();
+        b.end_function().unwrap();
+        let mut ids = vec![head, param, exit];
+        ids.extend(targets);
+        (b.module(), ids)
+    }
+
+    #[test]
+    fn test_normalize_switches() {
+        let (mut m, ids) = build(&[(2, 1), (-1i32 as u32, 0), (2, 1), (7, 3)], true);
+        normalize_switches(&mut m).unwrap();
+        let switch = m.functions[0].basic_blocks[0].instructions.last().unwrap();
+        // Sorted as signed integers, without the duplicate and the case
+        // branching to the default target.
+        assert_eq!(switch.operands,
+                   vec![mr::Operand::IdRef(ids[1]),
+                        mr::Operand::IdRef(ids[6]),
+                        mr::Operand::LiteralInt32(-1i32 as u32),
+                        mr::Operand::IdRef(ids[3]),
+                        mr::Operand::LiteralInt32(2),
+                        mr::Operand::IdRef(ids[4])]);
+
+        let (mut m, ids) = build(&[(2, 1), (2, 0)], true);
+        assert_eq!(normalize_switches(&mut m), Err(Error::DuplicateSwitchCase(ids[0])));
+        assert_eq!(Error::DuplicateSwitchCase(ids[0]).to_string(),
+                   format!("duplicate switch case in block %{}", ids[0]));
+    }
+
+    #[test]
+    fn test_normalize_64_bit_literals() {
+        let mut b = mr::Builder::new();
+        let ulong = b.type_int(64, 0);
+        let selector = b.constant_u64(ulong, 5);
+        let void = b.type_void();
+        let voidfvoid = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let (high, low, default) = (b.id(), b.id(), b.id());
+        b.switch64(selector, default, vec![(0x1_0000_0000, high), (3, low)]).unwrap();
+        for &label in &[high, low, default] {
+            b.begin_basic_block(Some(label)).unwrap();
+            b.ret().unwrap();
+        }
+        b.end_function().unwrap();
+        let mut m = b.module();
+        // As read by the parser, one word for each operand.
+        m.functions[0].basic_blocks[0].instructions[0].operands =
+            vec![mr::Operand::IdRef(selector), mr::Operand::IdRef(default),
+                 mr::Operand::LiteralInt32(0), mr::Operand::IdRef(1), mr::Operand::LiteralInt32(high),
+                 mr::Operand::LiteralInt32(3), mr::Operand::IdRef(0), mr::Operand::LiteralInt32(low)].into();
+
+        normalize_switches(&mut m).unwrap();
+        assert_eq!(m.functions[0].basic_blocks[0].instructions[0].operands,
+                   vec![mr::Operand::IdRef(selector),
+                        mr::Operand::IdRef(default),
+                        mr::Operand::LiteralInt64(3),
+                        mr::Operand::IdRef(low),
+                        mr::Operand::LiteralInt64(0x1_0000_0000),
+                        mr::Operand::IdRef(high)]);
+
+        // A final literal without a target.
+        let label = m.functions[0].basic_blocks[0].label.as_ref().unwrap().result_id.unwrap();
+        m.functions[0].basic_blocks[0].instructions[0].operands.push(mr::Operand::LiteralInt64(9));
+        assert_eq!(normalize_switches(&mut m), Err(Error::SwitchLiteralWidth(label)));
+    }
+
+    #[test]
+    fn test_lower_and_raise() {
+        let (mut m, ids) = build(&[(4, 0), (5, 1), (6, 2)], false);
+        let bound = m.header.as_ref().unwrap().bound;
+        // Not small enough.
+        lower_switches(&mut m, 2).unwrap();
+        assert_eq!(m.functions[0].basic_blocks.len(), 6);
+
+        lower_switches(&mut m, 3).unwrap();
+        let blocks = &m.functions[0].basic_blocks;
+        assert_eq!(blocks.len(), 8);
+        for block in &blocks[..3] {
+            assert_eq!(opcodes(block), vec![spirv::Op::IEqual, spirv::Op::BranchConditional]);
+        }
+        let (second, third) = (bound, bound + 1);
+        assert_eq!(blocks[0].instructions[1].operands[1..], refs(&[ids[3], second])[..]);
+        assert_eq!(blocks[2].instructions[1].operands[1..], refs(&[ids[5], ids[6]])[..]);
+        // The boolean type, and the three constants.
+        assert_eq!(m.types_global_values.len(), 2 + 4 + 4);
+
+        raise_branch_chains(&mut m);
+        let blocks = &m.functions[0].basic_blocks;
+        assert_eq!(blocks.len(), 6);
+        assert!(blocks.iter().all(|b| b.label.as_ref().unwrap().result_id != Some(third)));
+        let switch = blocks[0].instructions.last().unwrap();
+        assert_eq!(switch.class.opcode, spirv::Op::Switch);
+        assert_eq!(switch.operands,
+                   vec![mr::Operand::IdRef(ids[1]),
+                        mr::Operand::IdRef(ids[6]),
+                        mr::Operand::LiteralInt32(4),
+                        mr::Operand::IdRef(ids[3]),
+                        mr::Operand::LiteralInt32(5),
+                        mr::Operand::IdRef(ids[4]),
+                        mr::Operand::LiteralInt32(6),
+                        mr::Operand::IdRef(ids[5])]);
+    }
+
+    #[test]
+    fn test_lower_structured() {
+        // Each conditional branch would need its own merge block.
+        let (mut m, _) = build(&[(4, 0), (5, 1)], true);
+        lower_switches(&mut m, 8).unwrap();
+        assert_eq!(m.functions[0].basic_blocks.len(), 6);
+
+        let (mut m, ids) = build(&[(4, 0)], true);
+        lower_switches(&mut m, 8).unwrap();
+        let blocks = &m.functions[0].basic_blocks;
+        assert_eq!(opcodes(&blocks[0]),
+                   vec![spirv::Op::IEqual, spirv::Op::SelectionMerge, spirv::Op::BranchConditional]);
+        assert_eq!(blocks[0].instructions[2].operands[1..], refs(&[ids[3], ids[6]])[..]);
+    }
+}
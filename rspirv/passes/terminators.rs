@@ -0,0 +1,318 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use env::TargetEnv;
+use mr;
+use spirv;
+
+use spirv::Word;
+use super::{add_extension, remove_extension, Error, Result};
+
+const TERMINATE_INVOCATION: &str = "SPV_KHR_terminate_invocation";
+const DEMOTE_TO_HELPER_INVOCATION: &str = "SPV_EXT_demote_to_helper_invocation";
+
+/// The ways a target accepts to end or demote a fragment invocation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TerminatorTarget {
+    /// The SPIR-V version, as major and minor numbers, the module is meant
+    /// for.
+    pub version: (u8, u8),
+    /// Whether the target supports `SPV_KHR_terminate_invocation` below
+    /// SPIR-V 1.6.
+    pub terminate_invocation: bool,
+    /// Whether the target supports the `DemoteToHelperInvocation`
+    /// capability.
+    pub demote_to_helper_invocation: bool,
+}
+
+impl TerminatorTarget {
+    /// Creates a target for the given SPIR-V `version` without extensions.
+    ///
+    /// Demoting to helper invocations is assumed to be supported from
+    /// SPIR-V 1.6 on, where it became core.
+    pub fn new(version: (u8, u8)) -> TerminatorTarget {
+        TerminatorTarget {
+            version,
+            terminate_invocation: false,
+            demote_to_helper_invocation: version >= (1, 6),
+        }
+    }
+
+    /// Creates a target for the SPIR-V version and capabilities of `env`,
+    /// without extensions.
+    pub fn for_env(env: TargetEnv) -> TerminatorTarget {
+        let version = env.spirv_version();
+        TerminatorTarget {
+            version,
+            terminate_invocation: false,
+            demote_to_helper_invocation:
+                version >= (1, 6) ||
+                env.allows_capability(spirv::Capability::DemoteToHelperInvocation),
+        }
+    }
+
+    fn supports_terminate_invocation(&self) -> bool {
+        self.version >= (1, 6) || self.terminate_invocation
+    }
+}
+
+/// Rewrites the fragment terminators in `module` into the forms `target`
+/// supports.
+///
+/// `OpKill` becomes `OpTerminateInvocation` where the target supports the
+/// latter, and the other way around where it does not; both end the
+/// invocation the same way. The `SPV_KHR_terminate_invocation` extension is
+/// declared or dropped as needed below SPIR-V 1.6.
+///
+/// Where the target lacks the `DemoteToHelperInvocation` capability, each
+/// `OpDemoteToHelperInvocation` is lowered to ending the invocation: the
+/// rest of its block is dropped, and the block's successors no longer see
+/// it as an `OpPhi` parent. This is only equivalent as long as nothing
+/// after the demotion feeds derivatives of other invocations. The
+/// capability and its extension are dropped afterwards. It is an error for
+/// such a module to query `OpIsHelperInvocationEXT`, since the answer
+/// cannot be kept.
+///
+/// The module header is left untouched; updating its version is up to the
+/// caller.
+pub fn legalize_terminators(module: &mut mr::Module, target: &TerminatorTarget) -> Result<()> {
+    module.record_edits(|module| {
+        let demote = target.demote_to_helper_invocation;
+        if !demote && uses(module, spirv::Op::IsHelperInvocationEXT) {
+            return Err(Error::Unsupported(spirv::Op::IsHelperInvocationEXT));
+        }
+        let (from, to) = if target.supports_terminate_invocation() {
+            (spirv::Op::Kill, spirv::Op::TerminateInvocation)
+        } else {
+            (spirv::Op::TerminateInvocation, spirv::Op::Kill)
+        };
+
+        for function in &mut module.functions {
+            // (block, former successors) for blocks cut short by a demotion.
+            let mut cut = vec![];
+            for block in &mut function.basic_blocks {
+                if !demote {
+                    let demotion = block.instructions
+                        .iter()
+                        .position(|inst| inst.class.opcode == spirv::Op::DemoteToHelperInvocation);
+                    if let Some(index) = demotion {
+                        let successors = block.instructions
+                            .last()
+                            .map(label_operands)
+                            .unwrap_or_default();
+                        block.instructions.truncate(index);
+                        block.instructions.push(mr::Instruction::new(from, None, None, vec![]));
+                        if let Some(label) = block.label.as_ref().and_then(|l| l.result_id) {
+                            cut.push((label, successors));
+                        }
+                    }
+                }
+                if let Some(inst) = block.instructions.last_mut() {
+                    if inst.class.opcode == from {
+                        *inst = mr::Instruction::new(to, None, None, vec![]);
+                    }
+                }
+            }
+            for (label, successors) in cut {
+                for block in &mut function.basic_blocks {
+                    let id = block.label.as_ref().and_then(|l| l.result_id);
+                    if id.is_some_and(|id| successors.contains(&id)) {
+                        remove_phi_parent(block, label);
+                    }
+                }
+            }
+        }
+
+        if !demote {
+            module.capabilities.retain(|inst| {
+                inst.operands.first() !=
+                Some(&mr::Operand::Capability(spirv::Capability::DemoteToHelperInvocation))
+            });
+            remove_extension(module, DEMOTE_TO_HELPER_INVOCATION);
+        }
+        if target.version < (1, 6) {
+            if uses(module, spirv::Op::TerminateInvocation) {
+                add_extension(module, TERMINATE_INVOCATION);
+            } else {
+                remove_extension(module, TERMINATE_INVOCATION);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Returns true if any function in `module` contains an `opcode`
+/// instruction.
+fn uses(module: &mr::Module, opcode: spirv::Op) -> bool {
+    module.functions
+        .iter()
+        .flat_map(|f| &f.basic_blocks)
+        .flat_map(|b| &b.instructions)
+        .any(|inst| inst.class.opcode == opcode)
+}
+
+/// Returns the ids a branch instruction names, which include its targets.
+fn label_operands(inst: &mr::Instruction) -> Vec<Word> {
+    inst.operands
+        .iter()
+        .filter_map(|operand| match *operand {
+            mr::Operand::IdRef(id) => Some(id),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Removes the incoming values from `parent` off the `OpPhi`s of `block`.
+fn remove_phi_parent(block: &mut mr::BasicBlock, parent: Word) {
+    for inst in &mut block.instructions {
+        if inst.class.opcode != spirv::Op::Phi {
+            break;
+        }
+        let pairs = inst.operands.split_off(0);
+        for pair in pairs.chunks(2) {
+            if pair.get(1) != Some(&mr::Operand::IdRef(parent)) {
+                inst.operands.extend_from_slice(pair);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use env::TargetEnv;
+    use mr;
+    use spirv;
+
+    use super::{legalize_terminators, TerminatorTarget};
+    use passes::Error;
+    use utils::test::opcodes;
+
+    /// Builds a fragment shader function made of a single block ending with
+    /// `terminate`.
+    fn build<F>(terminate: F) -> mr::Module
+        where F: FnOnce(&mut mr::Builder)
+    {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let voidfvoid = b.type_function(void, vec![void]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        b.begin_basic_block(None).unwrap();
+        terminate(&mut b);
+        b.end_function().unwrap();
+        b.module()
+    }
+
+    fn extensions(module: &mr::Module) -> Vec<mr::Operand> {
+        module.extensions.iter().map(|inst| inst.operands[0].clone()).collect()
+    }
+
+    #[test]
+    fn test_kill_to_terminate_invocation() {
+        let mut m = build(|b| b.kill().unwrap());
+        legalize_terminators(&mut m, &TerminatorTarget::new((1, 6))).unwrap();
+        assert_eq!(opcodes(&m.functions[0].basic_blocks[0]),
+                   vec![spirv::Op::TerminateInvocation]);
+        assert!(m.extensions.is_empty());
+
+        let mut m = build(|b| b.kill().unwrap());
+        let mut target = TerminatorTarget::new((1, 3));
+        target.terminate_invocation = true;
+        legalize_terminators(&mut m, &target).unwrap();
+        assert_eq!(opcodes(&m.functions[0].basic_blocks[0]),
+                   vec![spirv::Op::TerminateInvocation]);
+        assert_eq!(extensions(&m), vec![mr::Operand::from("SPV_KHR_terminate_invocation")]);
+    }
+
+    #[test]
+    fn test_terminate_invocation_to_kill() {
+        let mut m = build(|b| {
+            b.extension("SPV_KHR_terminate_invocation");
+            b.terminate_invocation().unwrap()
+        });
+        legalize_terminators(&mut m, &TerminatorTarget::new((1, 2))).unwrap();
+        assert_eq!(opcodes(&m.functions[0].basic_blocks[0]), vec![spirv::Op::Kill]);
+        assert!(m.extensions.is_empty());
+    }
+
+    #[test]
+    fn test_lower_demote_to_helper_invocation() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.capability(spirv::Capability::DemoteToHelperInvocation);
+        b.extension("SPV_EXT_demote_to_helper_invocation");
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let boolean = b.type_bool();
+        let one = b.constant_u32(uint, 1);
+        let two = b.constant_u32(uint, 2);
+        let cond = b.constant_true(boolean);
+        let voidfvoid = b.type_function(void, vec![void]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        let entry = b.begin_basic_block(None).unwrap();
+        let (then, merge) = (b.id(), b.id());
+        b.selection_merge(merge, spirv::SelectionControl::NONE).unwrap();
+        b.branch_conditional(cond, then, merge, vec![]).unwrap();
+        b.begin_basic_block(Some(then)).unwrap();
+        b.demote_to_helper_invocation().unwrap();
+        b.iadd(uint, None, one, two).unwrap();
+        b.branch(merge).unwrap();
+        b.begin_basic_block(Some(merge)).unwrap();
+        b.phi(uint, None, vec![(one, entry), (two, then)]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        legalize_terminators(&mut m, &TerminatorTarget::new((1, 2))).unwrap();
+        let blocks = &m.functions[0].basic_blocks;
+        assert_eq!(opcodes(&blocks[1]), vec![spirv::Op::Kill]);
+        assert_eq!(blocks[2].instructions[0].operands,
+                   vec![mr::Operand::IdRef(one), mr::Operand::IdRef(entry)]);
+        assert_eq!(m.capabilities.len(), 1);
+        assert!(m.extensions.is_empty());
+    }
+
+    #[test]
+    fn test_keep_demote_to_helper_invocation() {
+        let mut m = build(|b| {
+            b.demote_to_helper_invocation().unwrap();
+            b.ret().unwrap()
+        });
+        let mut target = TerminatorTarget::new((1, 3));
+        target.demote_to_helper_invocation = true;
+        legalize_terminators(&mut m, &target).unwrap();
+        assert_eq!(opcodes(&m.functions[0].basic_blocks[0]),
+                   vec![spirv::Op::DemoteToHelperInvocation, spirv::Op::Return]);
+
+        assert_eq!(TerminatorTarget::for_env(TargetEnv::Vulkan1_3), TerminatorTarget::new((1, 6)));
+        assert!(!TerminatorTarget::for_env(TargetEnv::Vulkan1_2).demote_to_helper_invocation);
+    }
+
+    #[test]
+    fn test_helper_invocation_query() {
+        let mut m = build(|b| {
+            let boolean = b.type_bool();
+            b.demote_to_helper_invocation().unwrap();
+            b.is_helper_invocation_ext(boolean, None).unwrap();
+            b.ret().unwrap()
+        });
+        assert_eq!(legalize_terminators(&mut m, &TerminatorTarget::new((1, 5))),
+                   Err(Error::Unsupported(spirv::Op::IsHelperInvocationEXT)));
+        assert_eq!(opcodes(&m.functions[0].basic_blocks[0]),
+                   vec![spirv::Op::DemoteToHelperInvocation,
+                        spirv::Op::IsHelperInvocationEXT,
+                        spirv::Op::Return]);
+    }
+}
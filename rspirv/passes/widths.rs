@@ -0,0 +1,632 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use reflect::TypeIndex;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+use std::mem;
+use super::{remove_extension, Error, Result};
+
+const STORAGE_8BIT: &str = "SPV_KHR_8bit_storage";
+const STORAGE_16BIT: &str = "SPV_KHR_16bit_storage";
+
+/// The storage classes whose scalars need the 8- and 16-bit storage
+/// capabilities.
+const BUFFER_CLASSES: &[spirv::StorageClass] = &[spirv::StorageClass::Uniform,
+                                                 spirv::StorageClass::StorageBuffer,
+                                                 spirv::StorageClass::PushConstant,
+                                                 spirv::StorageClass::PhysicalStorageBuffer];
+
+/// The widths of scalars a target can keep in buffers and push constants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StorageWidths {
+    /// Whether the target supports 8-bit integers in storage, as with
+    /// `SPV_KHR_8bit_storage`.
+    pub storage_8bit: bool,
+    /// Whether the target supports 16-bit integers and floats in storage,
+    /// as with `SPV_KHR_16bit_storage`.
+    pub storage_16bit: bool,
+}
+
+impl StorageWidths {
+    /// Creates widths the given `capabilities` support: 8- and 16-bit
+    /// storage count as supported with access to them in uniform and
+    /// storage buffers.
+    pub fn new(capabilities: &[spirv::Capability]) -> StorageWidths {
+        StorageWidths {
+            storage_8bit:
+                capabilities.contains(&spirv::Capability::UniformAndStorageBuffer8BitAccess),
+            storage_16bit:
+                capabilities.contains(&spirv::Capability::UniformAndStorageBuffer16BitAccess),
+        }
+    }
+
+    fn supports(&self, width: u32) -> bool {
+        match width {
+            8 => self.storage_8bit,
+            16 => self.storage_16bit,
+            _ => true,
+        }
+    }
+}
+
+/// Widens the 8- and 16-bit scalars `module` keeps in buffers and push
+/// constants to 32 bits, for targets without the storage capabilities for
+/// them, so that one module can serve targets with and without them.
+///
+/// Members and array elements of such types, scalar or vector, become
+/// 32-bit ones of the same signedness, converted with `OpUConvert`,
+/// `OpSConvert`, or `OpFConvert` where they are loaded and stored. Loads
+/// converted straight to the widened type are replaced by the widened
+/// loads. Members following a widened one move by as much as it grows,
+/// keeping at least the alignment of their old offsets, and array strides
+/// grow to hold widened elements. The storage capabilities of the widths
+/// not supported are removed, together with their extensions once nothing
+/// needs them.
+///
+/// The data bound to the module must be laid out the new way. It is an
+/// error for structs or arrays with widened members to be loaded, stored,
+/// or copied as a whole, to be pointed to in other storage classes, or for
+/// pointers to widened values to be used but by loads, stores, access
+/// chains, copies, calls, `OpPhi`, and `OpSelect`.
+pub fn widen_storage(module: &mut mr::Module, widths: &StorageWidths) -> Result<()> {
+    module.record_edits(|module| {
+        let plan = plan(module, widths)?;
+        if !plan.small.is_empty() {
+            widen(module, &plan);
+        }
+        let removed: &[spirv::Capability] = match (widths.storage_8bit, widths.storage_16bit) {
+            (true, true) => &[],
+            (false, true) => &[spirv::Capability::StorageBuffer8BitAccess,
+                               spirv::Capability::UniformAndStorageBuffer8BitAccess,
+                               spirv::Capability::StoragePushConstant8],
+            (true, false) => &[spirv::Capability::StorageBuffer16BitAccess,
+                               spirv::Capability::UniformAndStorageBuffer16BitAccess,
+                               spirv::Capability::StoragePushConstant16],
+            (false, false) => &[spirv::Capability::StorageBuffer8BitAccess,
+                                spirv::Capability::UniformAndStorageBuffer8BitAccess,
+                                spirv::Capability::StoragePushConstant8,
+                                spirv::Capability::StorageBuffer16BitAccess,
+                                spirv::Capability::UniformAndStorageBuffer16BitAccess,
+                                spirv::Capability::StoragePushConstant16],
+        };
+        module.capabilities.retain(|inst| match inst.operands.first() {
+            Some(&mr::Operand::Capability(capability)) => !removed.contains(&capability),
+            _ => true,
+        });
+        if !widths.storage_8bit {
+            remove_extension(module, STORAGE_8BIT);
+        }
+        let input_output = mr::Operand::Capability(spirv::Capability::StorageInputOutput16);
+        if !widths.storage_16bit &&
+           !module.capabilities.iter().any(|inst| inst.operands.first() == Some(&input_output)) {
+            remove_extension(module, STORAGE_16BIT);
+        }
+        Ok(())
+    })
+}
+
+/// The types of a module to widen, and their new layout.
+struct Widening {
+    /// The small scalar and vector types held in storage, with the
+    /// conversion to and from their widened twins.
+    small: BTreeMap<Word, spirv::Op>,
+    /// The structs and arrays holding them, changed in place.
+    composites: BTreeSet<Word>,
+    /// The pointer types to small types, changed to point to their twins.
+    pointers: BTreeSet<Word>,
+    /// The new offsets of struct members.
+    offsets: BTreeMap<(Word, u32), u32>,
+    /// The new strides of arrays.
+    strides: BTreeMap<Word, u32>,
+}
+
+/// Returns the largest power of two dividing `offset`, up to 16.
+fn alignment(offset: u32) -> u32 {
+    if offset == 0 {
+        16
+    } else {
+        (1 << offset.trailing_zeros()).min(16)
+    }
+}
+
+fn plan(module: &mr::Module, widths: &StorageWidths) -> Result<Widening> {
+    let index = TypeIndex::new(module);
+    let mut small = BTreeMap::new();
+    // The new sizes and alignments of small types and their holders.
+    let mut layouts: BTreeMap<Word, (Option<u32>, u32)> = BTreeMap::new();
+    let mut composites = BTreeSet::new();
+    let mut offsets = BTreeMap::new();
+    let mut strides = BTreeMap::new();
+    for inst in &module.types_global_values {
+        let id = match inst.result_id {
+            Some(id) => id,
+            None => continue,
+        };
+        let literal = |i: usize| match inst.operands.get(i) {
+            Some(&mr::Operand::LiteralInt32(value)) => Some(value),
+            _ => None,
+        };
+        let element = inst.operands.first().and_then(mr::Operand::id);
+        match inst.class.opcode {
+            spirv::Op::TypeInt | spirv::Op::TypeFloat => {
+                let width = literal(0).unwrap_or(32);
+                if widths.supports(width) {
+                    continue;
+                }
+                let conversion = match (inst.class.opcode, literal(1)) {
+                    (spirv::Op::TypeFloat, _) => spirv::Op::FConvert,
+                    (_, Some(0)) => spirv::Op::UConvert,
+                    _ => spirv::Op::SConvert,
+                };
+                small.insert(id, conversion);
+                layouts.insert(id, (Some(4), 4));
+            }
+            spirv::Op::TypeVector => {
+                let component = element.and_then(|c| small.get(&c).cloned());
+                if let (Some(conversion), Some(count)) = (component, literal(1)) {
+                    small.insert(id, conversion);
+                    layouts.insert(id, (Some(4 * count), if count == 2 { 8 } else { 16 }));
+                }
+            }
+            spirv::Op::TypeMatrix if element.is_some_and(|c| small.contains_key(&c)) => {
+                return Err(Error::Unsupported(spirv::Op::TypeMatrix));
+            }
+            spirv::Op::TypeArray | spirv::Op::TypeRuntimeArray => {
+                let element = match element {
+                    Some(element) if layouts.contains_key(&element) => element,
+                    _ => continue,
+                };
+                let (size, align) = layouts[&element];
+                let size = size.ok_or(Error::Unsupported(spirv::Op::TypeStruct))?;
+                let stride = index.decoration_literal(id, spirv::Decoration::ArrayStride);
+                let new_stride = match stride {
+                    Some(stride) if stride >= size && stride % align == 0 => stride,
+                    Some(stride) => mr::round_up(size, align.max(alignment(stride))),
+                    None => mr::round_up(size, align),
+                };
+                if stride.is_some() && stride != Some(new_stride) {
+                    strides.insert(id, new_stride);
+                }
+                let length = inst.operands.get(1).and_then(mr::Operand::id);
+                let length = length.and_then(|length| index.constant_u32(length));
+                composites.insert(id);
+                layouts.insert(id, (length.map(|length| length * new_stride), align));
+            }
+            spirv::Op::TypeStruct => {
+                let widened = inst.operands
+                    .iter()
+                    .any(|member| member.id().is_some_and(|m| layouts.contains_key(&m)));
+                if !widened {
+                    continue;
+                }
+                let layout = index.struct_layout(id).ok_or(Error::UnknownId(id))?;
+                let mut members = layout.members.clone();
+                if members.iter().any(|m| m.offset.is_none()) {
+                    return Err(Error::Unsupported(spirv::Op::TypeStruct));
+                }
+                members.sort_by_key(|m| m.offset);
+                let (mut shift, mut align) = (0, 4);
+                for member in &members {
+                    let old = member.offset.unwrap_or(0);
+                    let new = match layouts.get(&member.type_id) {
+                        Some(&(size, natural)) => {
+                            let new = mr::round_up(old + shift, natural.max(alignment(old)));
+                            let runtime = index.def(member.type_id).is_some_and(|ty| {
+                                ty.class.opcode == spirv::Op::TypeRuntimeArray
+                            });
+                            shift = if runtime {
+                                new - old
+                            } else {
+                                let sizes = size.and_then(|s| member.size.map(|old| (s, old)));
+                                let (size, old_size) =
+                                    sizes.ok_or(Error::Unsupported(spirv::Op::TypeStruct))?;
+                                new + size - old - old_size
+                            };
+                            align = align.max(natural);
+                            new
+                        }
+                        None => {
+                            let new = mr::round_up(old + shift, alignment(old));
+                            shift = new - old;
+                            new
+                        }
+                    };
+                    if new != old {
+                        offsets.insert((id, member.index), new);
+                    }
+                }
+                composites.insert(id);
+                layouts.insert(id, (layout.size.map(|size| size + shift), align));
+            }
+            _ => {}
+        }
+    }
+
+    let mut pointers = BTreeSet::new();
+    let mut composite_pointers = BTreeSet::new();
+    for inst in &module.types_global_values {
+        if inst.class.opcode != spirv::Op::TypePointer {
+            continue;
+        }
+        let buffer = match inst.operands.first() {
+            Some(&mr::Operand::StorageClass(class)) => BUFFER_CLASSES.contains(&class),
+            _ => false,
+        };
+        let pointee = inst.operands.get(1).and_then(mr::Operand::id);
+        match (inst.result_id, pointee) {
+            (Some(id), Some(pointee)) if buffer && small.contains_key(&pointee) => {
+                pointers.insert(id);
+            }
+            (Some(id), Some(pointee)) if composites.contains(&pointee) => {
+                if !buffer {
+                    return Err(Error::Unsupported(spirv::Op::TypePointer));
+                }
+                composite_pointers.insert(id);
+            }
+            _ => {}
+        }
+    }
+    if small.is_empty() || pointers.is_empty() && composite_pointers.is_empty() {
+        return Ok(Widening {
+            small: BTreeMap::new(),
+            composites,
+            pointers,
+            offsets,
+            strides,
+        });
+    }
+
+    for function in &module.functions {
+        let types = value_types(module, function);
+        let insts = function.basic_blocks.iter().flat_map(|b| &b.instructions);
+        for inst in insts {
+            for (i, operand) in inst.operands.iter().enumerate() {
+                let ty = match operand.id().and_then(|id| types.get(&id)) {
+                    Some(ty) => ty,
+                    None => continue,
+                };
+                let (widened, composite) = (pointers.contains(ty),
+                                            composite_pointers.contains(ty));
+                if !widened && !composite {
+                    continue;
+                }
+                let allowed = match inst.class.opcode {
+                    spirv::Op::Load | spirv::Op::Store => widened && i == 0,
+                    spirv::Op::AccessChain |
+                    spirv::Op::InBoundsAccessChain |
+                    spirv::Op::PtrAccessChain |
+                    spirv::Op::InBoundsPtrAccessChain => i == 0,
+                    spirv::Op::CopyObject |
+                    spirv::Op::FunctionCall |
+                    spirv::Op::Phi |
+                    spirv::Op::Select |
+                    spirv::Op::ArrayLength => true,
+                    _ => false,
+                };
+                if !allowed {
+                    return Err(Error::Unsupported(inst.class.opcode));
+                }
+            }
+        }
+    }
+    Ok(Widening {
+        small,
+        composites,
+        pointers,
+        offsets,
+        strides,
+    })
+}
+
+/// Returns the types of the values global or in `function` of `module`.
+fn value_types(module: &mr::Module, function: &mr::Function) -> BTreeMap<Word, Word> {
+    module.types_global_values
+        .iter()
+        .chain(&function.parameters)
+        .chain(function.basic_blocks.iter().flat_map(|b| &b.instructions))
+        .filter_map(|inst| inst.result_id.and_then(|id| inst.result_type.map(|ty| (id, ty))))
+        .collect()
+}
+
+/// Returns the twin of the type `small` of `module` with the given opcode
+/// and operands, declared right after it.
+fn twin(module: &mut mr::Module,
+        ids: &mut mr::IdAllocator,
+        small: Word,
+        opcode: spirv::Op,
+        operands: Vec<mr::Operand>)
+        -> Word {
+    let globals = &mut module.types_global_values;
+    let index = globals.iter().position(|inst| inst.result_id == Some(small)).unwrap_or(0);
+    let existing = globals.iter().position(|inst| {
+        inst.class.opcode == opcode && inst.result_type.is_none() && inst.operands == operands
+    });
+    match existing {
+        Some(existing) if existing < index => globals[existing].result_id.unwrap_or(0),
+        Some(existing) => {
+            // Nothing the twin depends on is declared after `small`.
+            let inst = globals.remove(existing);
+            let id = inst.result_id.unwrap_or(0);
+            globals.insert(index + 1, inst);
+            id
+        }
+        None => {
+            let id = ids.id();
+            globals.insert(index + 1, mr::Instruction::new(opcode, None, Some(id), operands));
+            id
+        }
+    }
+}
+
+fn widen(module: &mut mr::Module, plan: &Widening) {
+    let mut ids = mr::IdAllocator::for_module(module);
+    let mut twins: BTreeMap<Word, Word> = BTreeMap::new();
+    let small: Vec<Word> = module.types_global_values
+        .iter()
+        .filter_map(|inst| inst.result_id)
+        .filter(|id| plan.small.contains_key(id))
+        .collect();
+    for small in small {
+        let conversion = plan.small[&small];
+        let inst = match module.types_global_values.iter().find(|i| i.result_id == Some(small)) {
+            Some(inst) => inst,
+            None => continue,
+        };
+        let (opcode, operands) = match (inst.class.opcode, &inst.operands[..]) {
+            (spirv::Op::TypeVector, &[mr::Operand::IdRef(component), ref count]) => {
+                (spirv::Op::TypeVector,
+                 vec![mr::Operand::IdRef(twins[&component]), count.clone()])
+            }
+            (spirv::Op::TypeFloat, _) => {
+                (spirv::Op::TypeFloat, vec![mr::Operand::LiteralInt32(32)])
+            }
+            _ => {
+                let signedness = if conversion == spirv::Op::UConvert { 0 } else { 1 };
+                (spirv::Op::TypeInt,
+                 vec![mr::Operand::LiteralInt32(32), mr::Operand::LiteralInt32(signedness)])
+            }
+        };
+        let id = twin(module, &mut ids, small, opcode, operands);
+        twins.insert(small, id);
+    }
+
+    for inst in &mut module.types_global_values {
+        let pointer = inst.result_id.is_some_and(|id| plan.pointers.contains(&id));
+        let composite = inst.result_id.is_some_and(|id| plan.composites.contains(&id));
+        if !pointer && !composite {
+            continue;
+        }
+        for operand in &mut inst.operands {
+            if let mr::Operand::IdRef(ref mut id) = *operand {
+                if let Some(&twin) = twins.get(id) {
+                    *id = twin;
+                }
+            }
+        }
+    }
+    for inst in &mut module.annotations {
+        let target = inst.operands.first().and_then(mr::Operand::id).unwrap_or(0);
+        match (inst.class.opcode, &mut inst.operands[1..]) {
+            (spirv::Op::Decorate,
+             &mut [mr::Operand::Decoration(spirv::Decoration::ArrayStride),
+                   mr::Operand::LiteralInt32(ref mut stride)]) => {
+                if let Some(&new) = plan.strides.get(&target) {
+                    *stride = new;
+                }
+            }
+            (spirv::Op::MemberDecorate,
+             &mut [mr::Operand::LiteralInt32(member),
+                   mr::Operand::Decoration(spirv::Decoration::Offset),
+                   mr::Operand::LiteralInt32(ref mut offset)]) => {
+                if let Some(&new) = plan.offsets.get(&(target, member)) {
+                    *offset = new;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut functions = mem::take(&mut module.functions);
+    let mut forwarded = vec![];
+    for function in &mut functions {
+        let types = value_types(module, function);
+        let widened = |pointer: Option<Word>| {
+            pointer.and_then(|p| types.get(&p)).is_some_and(|ty| plan.pointers.contains(ty))
+        };
+        // The loads widened, by the result id of their narrowing.
+        let mut loads: BTreeMap<Word, Word> = BTreeMap::new();
+        for block in &mut function.basic_blocks {
+            let old = mem::take(&mut block.instructions);
+            for mut inst in old {
+                let pointer = inst.operands.first().and_then(mr::Operand::id);
+                let ty = match inst.class.opcode {
+                    spirv::Op::Load => inst.result_type,
+                    spirv::Op::Store => {
+                        inst.operands.get(1).and_then(mr::Operand::id).and_then(|v| {
+                            types.get(&v).cloned()
+                        })
+                    }
+                    _ => None,
+                };
+                let small = ty.and_then(|ty| plan.small.get(&ty).map(|&c| (ty, c)));
+                let (ty, conversion) = match small {
+                    Some(small) if widened(pointer) => small,
+                    _ => {
+                        block.instructions.push(inst);
+                        continue;
+                    }
+                };
+                let (twin, wide) = (twins[&ty], ids.id());
+                if inst.class.opcode == spirv::Op::Load {
+                    let narrow = inst.result_id.unwrap_or(0);
+                    inst.result_type = Some(twin);
+                    inst.result_id = Some(wide);
+                    block.instructions.push(inst);
+                    block.instructions.push(mr::Instruction::new(conversion,
+                                                                 Some(ty),
+                                                                 Some(narrow),
+                                                                 vec![mr::Operand::IdRef(wide)]));
+                    loads.insert(narrow, wide);
+                } else {
+                    let value = inst.operands[1].clone();
+                    block.instructions.push(mr::Instruction::new(conversion,
+                                                                 Some(twin),
+                                                                 Some(wide),
+                                                                 vec![value]));
+                    inst.operands[1] = mr::Operand::IdRef(wide);
+                    block.instructions.push(inst);
+                }
+            }
+        }
+
+        // Conversions of widened loads back to their twins are the loads.
+        let mut folded = BTreeSet::new();
+        for block in &mut function.basic_blocks {
+            block.instructions.retain(|inst| {
+                let narrow = inst.operands.first().and_then(mr::Operand::id);
+                let wide = narrow.and_then(|narrow| loads.get(&narrow).map(|&w| (narrow, w)));
+                let (narrow, wide, id) = match (wide, inst.result_id) {
+                    (Some((narrow, wide)), Some(id)) => (narrow, wide, id),
+                    _ => return true,
+                };
+                let ty = types[&narrow];
+                if plan.small.get(&ty) != Some(&inst.class.opcode) ||
+                   inst.result_type != Some(twins[&ty]) {
+                    return true;
+                }
+                folded.insert(narrow);
+                forwarded.push((id, wide));
+                false
+            });
+        }
+        let used: BTreeSet<Word> = function.basic_blocks
+            .iter()
+            .flat_map(|b| &b.instructions)
+            .flat_map(|inst| inst.operands.iter().filter_map(mr::Operand::id))
+            .collect();
+        for block in &mut function.basic_blocks {
+            block.instructions.retain(|inst| {
+                !inst.result_id.is_some_and(|id| folded.contains(&id) && !used.contains(&id))
+            });
+        }
+    }
+    module.functions = functions;
+    for (old, new) in forwarded {
+        module.replace_all_uses(old, new);
+    }
+    ids.update_header(module);
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{widen_storage, StorageWidths};
+
+    #[test]
+    fn test_widen_storage() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.capability(spirv::Capability::StorageBuffer16BitAccess);
+        b.extension("SPV_KHR_16bit_storage");
+        let void = b.type_void();
+        let ushort = b.type_int(16, 0);
+        let half = b.type_float(16);
+        let uint = b.type_int(32, 0);
+        let zero = b.constant_u32(uint, 0);
+        let one = b.constant_u32(uint, 1);
+        let two = b.constant_u32(uint, 2);
+        let three = b.constant_u32(uint, 3);
+        let halves = b.type_runtime_array(half);
+        b.decorate(halves, spirv::Decoration::ArrayStride, vec![mr::Operand::LiteralInt32(2)]);
+        let buffer = b.type_struct(vec![ushort, ushort, uint, halves]);
+        b.decorate(buffer, spirv::Decoration::Block, vec![]);
+        for (member, offset) in [0, 2, 4, 8].iter().enumerate() {
+            b.member_decorate(buffer,
+                              member as u32,
+                              spirv::Decoration::Offset,
+                              vec![mr::Operand::LiteralInt32(*offset)]);
+        }
+        let class = spirv::StorageClass::StorageBuffer;
+        let buffer_ptr = b.type_pointer(None, class, buffer);
+        let ushort_ptr = b.type_pointer(None, class, ushort);
+        let half_ptr = b.type_pointer(None, class, half);
+        let var = b.variable(buffer_ptr, None, class, None);
+        let fty = b.type_function(void, vec![]);
+        b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let first = b.access_chain(ushort_ptr, None, var, vec![zero]).unwrap();
+        let value = b.load(ushort, None, first, None, vec![]).unwrap();
+        let wide = b.uconvert(uint, None, value).unwrap();
+        let second = b.access_chain(ushort_ptr, None, var, vec![one]).unwrap();
+        b.store(second, value, None, vec![]).unwrap();
+        let element = b.access_chain(half_ptr, None, var, vec![three, two]).unwrap();
+        b.load(half, None, element, None, vec![]).unwrap();
+        b.iadd(uint, None, wide, wide).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let mut m = b.module();
+
+        let widths = StorageWidths::new(&[spirv::Capability::UniformAndStorageBuffer8BitAccess]);
+        widen_storage(&mut m, &widths).unwrap();
+        assert_eq!(m.capabilities.len(), 1);
+        assert!(m.extensions.is_empty());
+        let float = m.types_global_values
+            .iter()
+            .find(|inst| inst.class.opcode == spirv::Op::TypeFloat &&
+                         inst.operands[0] == mr::Operand::LiteralInt32(32))
+            .and_then(|inst| inst.result_id)
+            .unwrap();
+        let buffer_type = m.types_global_values
+            .iter()
+            .find(|inst| inst.result_id == Some(buffer))
+            .unwrap();
+        assert_eq!(buffer_type.operands,
+                   vec![mr::Operand::IdRef(uint),
+                        mr::Operand::IdRef(uint),
+                        mr::Operand::IdRef(uint),
+                        mr::Operand::IdRef(halves)]);
+        let literals: Vec<&mr::Operand> =
+            m.annotations.iter().filter_map(|inst| inst.operands.last()).collect();
+        assert_eq!(literals[0], &mr::Operand::LiteralInt32(4));
+        assert_eq!(&literals[3..],
+                   [&mr::Operand::LiteralInt32(4),
+                    &mr::Operand::LiteralInt32(8),
+                    &mr::Operand::LiteralInt32(16)]);
+
+        let insts = &m.functions[0].basic_blocks[0].instructions;
+        let opcodes: Vec<spirv::Op> = insts.iter().map(|inst| inst.class.opcode).collect();
+        assert_eq!(opcodes,
+                   [spirv::Op::AccessChain,
+                    spirv::Op::Load,
+                    spirv::Op::AccessChain,
+                    spirv::Op::Store,
+                    spirv::Op::AccessChain,
+                    spirv::Op::Load,
+                    spirv::Op::FConvert,
+                    spirv::Op::IAdd,
+                    spirv::Op::Return]);
+        // The value loaded is stored and added as it was loaded.
+        let load = insts[1].result_id.unwrap();
+        assert_eq!(insts[1].result_type, Some(uint));
+        assert_eq!(insts[3].operands[1], mr::Operand::IdRef(load));
+        assert_eq!((insts[5].result_type, insts[6].result_type), (Some(float), Some(half)));
+        assert_eq!(insts[7].operands, vec![mr::Operand::IdRef(load), mr::Operand::IdRef(load)]);
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::convert::TryFrom;
+
+use super::layout::{id_operand, TypeIndex};
+
+/// The length of an array type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArrayLength {
+    /// A length fixed at compile time.
+    Fixed(u32),
+    /// A length depending on specialization constants: the `SpecId`s of
+    /// the constants it depends on, in increasing order, and the length
+    /// for their default values.
+    Specializable(Vec<u32>, u32),
+    /// The length of a runtime array, known only from the bound buffer.
+    Runtime,
+}
+
+impl ArrayLength {
+    /// Returns the length for the default values of specialization
+    /// constants, or `None` for runtime arrays.
+    pub fn default_length(&self) -> Option<u32> {
+        match *self {
+            ArrayLength::Fixed(length) | ArrayLength::Specializable(_, length) => Some(length),
+            ArrayLength::Runtime => None,
+        }
+    }
+}
+
+/// Returns the length of the array or runtime array type `type_id` in
+/// `module`.
+///
+/// Lengths given by OpSpecConstantOp are evaluated for the default values
+/// of the specialization constants they use. A specialization constant
+/// without a `SpecId` cannot be specialized, and gives a fixed length.
+///
+/// Returns `None` if `type_id` is not an array type, or if its length is
+/// not a constant integer it can evaluate, e.g., one computed with an
+/// unsupported operation.
+pub fn array_length(module: &mr::Module, type_id: Word) -> Option<ArrayLength> {
+    let index = TypeIndex::new(module);
+    let inst = index.def(type_id)?;
+    match inst.class.opcode {
+        spirv::Op::TypeRuntimeArray => Some(ArrayLength::Runtime),
+        spirv::Op::TypeArray => {
+            let mut spec_ids = vec![];
+            let length = evaluate(&index, id_operand(inst, 1)?, &mut spec_ids)?;
+            let length = u32::try_from(length).ok()?;
+            if spec_ids.is_empty() {
+                Some(ArrayLength::Fixed(length))
+            } else {
+                spec_ids.sort_unstable();
+                spec_ids.dedup();
+                Some(ArrayLength::Specializable(spec_ids, length))
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns true if the integer type `id` is signed.
+fn is_signed(index: &TypeIndex, id: Word) -> bool {
+    index.def(id).is_some_and(|inst| inst.operands.get(1) == Some(&mr::Operand::LiteralInt32(1)))
+}
+
+/// Evaluates the scalar integer or boolean constant `id` for the default
+/// values of specialization constants, collecting the `SpecId`s it uses.
+///
+/// Values are sign-extended from signed types and zero-extended from
+/// unsigned ones; booleans are 0 or 1.
+fn evaluate(index: &TypeIndex, id: Word, spec_ids: &mut Vec<u32>) -> Option<i64> {
+    let inst = index.def(id)?;
+    if matches!(inst.class.opcode,
+                spirv::Op::SpecConstant |
+                spirv::Op::SpecConstantTrue |
+                spirv::Op::SpecConstantFalse) {
+        if let Some(spec_id) = index.decoration_literal(id, spirv::Decoration::SpecId) {
+            spec_ids.push(spec_id);
+        }
+    }
+    let signed = inst.result_type.is_some_and(|ty| is_signed(index, ty));
+    match inst.class.opcode {
+        spirv::Op::ConstantTrue | spirv::Op::SpecConstantTrue => Some(1),
+        spirv::Op::ConstantFalse | spirv::Op::SpecConstantFalse => Some(0),
+        spirv::Op::Constant | spirv::Op::SpecConstant => match inst.operands.first() {
+            Some(&mr::Operand::LiteralInt32(v)) if signed => Some(i64::from(v as i32)),
+            Some(&mr::Operand::LiteralInt32(v)) => Some(i64::from(v)),
+            Some(&mr::Operand::LiteralInt64(v)) => Some(v as i64),
+            _ => None,
+        },
+        spirv::Op::SpecConstantOp => {
+            let opcode = match inst.operands.first() {
+                Some(&mr::Operand::LiteralSpecConstantOpInteger(opcode)) => opcode,
+                _ => return None,
+            };
+            let mut args = vec![];
+            for operand in &inst.operands[1..] {
+                args.push(evaluate(index, operand.id()?, spec_ids)?);
+            }
+            operate(opcode, &args)
+        }
+        _ => None,
+    }
+}
+
+/// Applies the integer or boolean operation `opcode` to `args`.
+fn operate(opcode: spirv::Op, args: &[i64]) -> Option<i64> {
+    use spirv::Op::*;
+    Some(match (opcode, args) {
+        (SNegate, &[x]) => x.wrapping_neg(),
+        (Not, &[x]) => !x,
+        (LogicalNot, &[x]) => i64::from(x == 0),
+        (UConvert, &[x]) | (SConvert, &[x]) => x,
+        (IAdd, &[x, y]) => x.wrapping_add(y),
+        (ISub, &[x, y]) => x.wrapping_sub(y),
+        (IMul, &[x, y]) => x.wrapping_mul(y),
+        (UDiv, &[x, y]) | (SDiv, &[x, y]) => x.checked_div(y)?,
+        (UMod, &[x, y]) | (SRem, &[x, y]) => x.checked_rem(y)?,
+        (SMod, &[x, y]) => x.checked_rem_euclid(y)?,
+        (ShiftLeftLogical, &[x, y]) => x.checked_shl(u32::try_from(y).ok()?)?,
+        (ShiftRightLogical, &[x, y]) | (ShiftRightArithmetic, &[x, y]) => {
+            x.checked_shr(u32::try_from(y).ok()?)?
+        }
+        (BitwiseOr, &[x, y]) => x | y,
+        (BitwiseXor, &[x, y]) => x ^ y,
+        (BitwiseAnd, &[x, y]) => x & y,
+        (LogicalOr, &[x, y]) => i64::from(x != 0 || y != 0),
+        (LogicalAnd, &[x, y]) => i64::from(x != 0 && y != 0),
+        (IEqual, &[x, y]) | (LogicalEqual, &[x, y]) => i64::from(x == y),
+        (INotEqual, &[x, y]) | (LogicalNotEqual, &[x, y]) => i64::from(x != y),
+        (ULessThan, &[x, y]) | (SLessThan, &[x, y]) => i64::from(x < y),
+        (UGreaterThan, &[x, y]) | (SGreaterThan, &[x, y]) => i64::from(x > y),
+        (ULessThanEqual, &[x, y]) | (SLessThanEqual, &[x, y]) => i64::from(x <= y),
+        (UGreaterThanEqual, &[x, y]) | (SGreaterThanEqual, &[x, y]) => i64::from(x >= y),
+        (Select, &[c, x, y]) => if c != 0 { x } else { y },
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use reflect;
+    use spirv;
+
+    use reflect::ArrayLength;
+
+    #[test]
+    fn test_array_length() {
+        let mut b = mr::Builder::new();
+        let uint = b.type_int(32, 0);
+        let float = b.type_float(32);
+        let four = b.constant_u32(uint, 4);
+        let fixed = b.type_array(float, four);
+        let count = b.spec_constant_u32_with_id(uint, 8, 3).unwrap();
+        let specializable = b.type_array(float, count);
+        let unnamed = b.spec_constant_u32(uint, 2);
+        let unspecializable = b.type_array(float, unnamed);
+        let runtime = b.type_runtime_array(float);
+        let doubled = b.id();
+        let computed = b.type_array(float, doubled);
+        let mut module = b.module();
+        let operands = vec![mr::Operand::LiteralSpecConstantOpInteger(spirv::Op::IMul),
+                            mr::Operand::IdRef(count),
+                            mr::Operand::IdRef(four)];
+        let op = spirv::Op::SpecConstantOp;
+        let op = mr::Instruction::new(op, Some(uint), Some(doubled), operands);
+        module.types_global_values.push(op);
+
+        assert_eq!(reflect::array_length(&module, fixed), Some(ArrayLength::Fixed(4)));
+        assert_eq!(reflect::array_length(&module, specializable),
+                   Some(ArrayLength::Specializable(vec![3], 8)));
+        assert_eq!(reflect::array_length(&module, unspecializable), Some(ArrayLength::Fixed(2)));
+        assert_eq!(reflect::array_length(&module, runtime), Some(ArrayLength::Runtime));
+        let length = reflect::array_length(&module, computed).unwrap();
+        assert_eq!(length, ArrayLength::Specializable(vec![3], 32));
+        assert_eq!(length.default_length(), Some(32));
+        assert_eq!(reflect::array_length(&module, float), None);
+    }
+}
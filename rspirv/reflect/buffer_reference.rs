@@ -0,0 +1,101 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::HashSet;
+
+use super::layout::{id_operand, StructLayout, TypeIndex};
+
+/// A buffer reference type: a pointer type in the `PhysicalStorageBuffer`
+/// storage class, whose values are device addresses.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BufferReference {
+    /// The result id of the pointer type.
+    pub type_id: Word,
+    /// The result id of the pointee type.
+    pub pointee: Word,
+    /// Whether the pointer type is declared by OpTypeForwardPointer, e.g.,
+    /// for a struct referring to itself.
+    pub forward: bool,
+    /// The `ArrayStride` decoration of the pointer type, if any, used by
+    /// OpPtrAccessChain.
+    pub array_stride: Option<u32>,
+    /// The explicit layout of the pointee, if it is a struct.
+    pub layout: Option<StructLayout>,
+}
+
+/// Returns the buffer reference types of `module`, in declaration order.
+pub fn buffer_references(module: &mr::Module) -> Vec<BufferReference> {
+    let index = TypeIndex::new(module);
+    let forward: HashSet<Word> = module.types_global_values
+        .iter()
+        .filter(|inst| inst.class.opcode == spirv::Op::TypeForwardPointer)
+        .filter_map(|inst| id_operand(inst, 0))
+        .collect();
+    module.types_global_values
+        .iter()
+        .filter(|inst| {
+            inst.class.opcode == spirv::Op::TypePointer &&
+            inst.operands.first() ==
+            Some(&mr::Operand::StorageClass(spirv::StorageClass::PhysicalStorageBuffer))
+        })
+        .filter_map(|inst| {
+            let type_id = inst.result_id?;
+            let pointee = id_operand(inst, 1)?;
+            Some(BufferReference {
+                type_id,
+                pointee,
+                forward: forward.contains(&type_id),
+                array_stride: index.decoration_literal(type_id, spirv::Decoration::ArrayStride),
+                layout: index.struct_layout(pointee),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use reflect;
+    use spirv;
+
+    #[test]
+    fn test_buffer_references() {
+        let mut b = mr::Builder::new();
+        b.physical_storage_buffer_addressing(spirv::MemoryModel::GLSL450);
+        let uint = b.type_int(32, 0);
+        let next = b.forward_buffer_reference();
+        let node = b.type_struct(vec![uint, next]);
+        b.member_decorate(node, 0, spirv::Decoration::Offset, vec![mr::Operand::LiteralInt32(0)]);
+        b.member_decorate(node, 1, spirv::Decoration::Offset, vec![mr::Operand::LiteralInt32(8)]);
+        b.type_pointer(Some(next), spirv::StorageClass::PhysicalStorageBuffer, node);
+        let uint_ref = b.type_buffer_reference(uint);
+        b.decorate(uint_ref, spirv::Decoration::ArrayStride, vec![mr::Operand::LiteralInt32(4)]);
+        let module = b.module();
+
+        let references = reflect::buffer_references(&module);
+        assert_eq!(references.len(), 2);
+        assert_eq!((references[0].type_id, references[0].pointee), (next, node));
+        assert!(references[0].forward);
+        let layout = references[0].layout.as_ref().unwrap();
+        assert_eq!(layout.members[1].size, Some(8));
+        assert_eq!(layout.size, Some(16));
+        assert_eq!((references[1].type_id, references[1].forward), (uint_ref, false));
+        assert_eq!(references[1].array_stride, Some(4));
+        assert_eq!(references[1].layout, None);
+    }
+}
@@ -0,0 +1,537 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::HashMap;
+
+/// Explicit layout of a struct member.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemberLayout {
+    /// The index of the member inside the struct.
+    pub index: u32,
+    /// The name given by OpMemberName, if any.
+    pub name: Option<String>,
+    /// The result id of the member type.
+    pub type_id: Word,
+    /// The `Offset` decoration of the member, if any.
+    pub offset: Option<u32>,
+    /// The size of the member in bytes, if it can be computed.
+    pub size: Option<u32>,
+}
+
+/// Explicit layout of a struct type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StructLayout {
+    /// The result id of the struct type.
+    pub type_id: Word,
+    /// The name given by OpName, if any.
+    pub name: Option<String>,
+    /// The layout of all members, in declaration order.
+    pub members: Vec<MemberLayout>,
+    /// The size of the struct in bytes, excluding any trailing runtime
+    /// array, if offsets and sizes are known for all members.
+    pub size: Option<u32>,
+}
+
+/// Returns the explicit layout of the struct type `type_id` in `module`.
+///
+/// Returns `None` if `type_id` is not an OpTypeStruct.
+pub fn struct_layout(module: &mr::Module, type_id: Word) -> Option<StructLayout> {
+    TypeIndex::new(module).struct_layout(type_id)
+}
+
+/// Returns the size in bytes of the type `type_id` according to the explicit
+/// layout decorations in `module`.
+///
+/// Returns `None` for types without an explicit size, e.g., runtime arrays,
+/// booleans, opaque types, or pointers other than `PhysicalStorageBuffer`
+/// ones, which take 8 bytes.
+pub fn type_size(module: &mr::Module, type_id: Word) -> Option<u32> {
+    TypeIndex::new(module).type_size(type_id)
+}
+
+/// Returns the string parameter of `decoration` on `target` in `module`,
+/// e.g., the `HlslSemanticGOOGLE` or `UserTypeGOOGLE` given by
+/// `OpDecorateString`.
+pub fn decoration_string(module: &mr::Module,
+                         target: Word,
+                         decoration: spirv::Decoration)
+                         -> Option<&str> {
+    TypeIndex::new(module).decoration_string(target, decoration)
+}
+
+/// Returns the string parameter of `decoration` on the given member of the
+/// struct type `target` in `module`, as given by `OpMemberDecorateString`.
+pub fn member_decoration_string(module: &mr::Module,
+                                target: Word,
+                                member: u32,
+                                decoration: spirv::Decoration)
+                                -> Option<&str> {
+    TypeIndex::new(module).member_decoration_string(target, member, decoration)
+}
+
+/// Index over the types, constants, names, and decorations of a module.
+pub(crate) struct TypeIndex<'m> {
+    defs: HashMap<Word, &'m mr::Instruction>,
+    decorations: HashMap<Word, Vec<&'m mr::Instruction>>,
+    member_decorations: HashMap<(Word, u32), Vec<&'m mr::Instruction>>,
+    names: HashMap<Word, &'m str>,
+    member_names: HashMap<(Word, u32), &'m str>,
+}
+
+impl<'m> TypeIndex<'m> {
+    pub fn new(module: &'m mr::Module) -> TypeIndex<'m> {
+        let mut index = TypeIndex {
+            defs: HashMap::new(),
+            decorations: HashMap::new(),
+            member_decorations: HashMap::new(),
+            names: HashMap::new(),
+            member_names: HashMap::new(),
+        };
+        for inst in &module.types_global_values {
+            if let Some(id) = inst.result_id {
+                index.defs.insert(id, inst);
+            }
+        }
+        for inst in &module.annotations {
+            match (inst.class.opcode, inst.operands.first(), inst.operands.get(1)) {
+                (spirv::Op::Decorate, Some(&mr::Operand::IdRef(target)), _) |
+                (spirv::Op::DecorateString, Some(&mr::Operand::IdRef(target)), _) |
+                (spirv::Op::DecorateId, Some(&mr::Operand::IdRef(target)), _) => {
+                    index.decorations.entry(target).or_default().push(inst)
+                }
+                (spirv::Op::MemberDecorate,
+                 Some(&mr::Operand::IdRef(target)),
+                 Some(&mr::Operand::LiteralInt32(member))) |
+                (spirv::Op::MemberDecorateString,
+                 Some(&mr::Operand::IdRef(target)),
+                 Some(&mr::Operand::LiteralInt32(member))) => {
+                    index.member_decorations.entry((target, member)).or_default().push(inst)
+                }
+                _ => (),
+            }
+        }
+        for inst in &module.debugs {
+            match (inst.class.opcode,
+                   inst.operands.first(),
+                   inst.operands.get(1),
+                   inst.operands.get(2)) {
+                (spirv::Op::Name,
+                 Some(mr::Operand::IdRef(target)),
+                 Some(mr::Operand::LiteralString(name)),
+                 _) => {
+                    index.names.insert(*target, name);
+                }
+                (spirv::Op::MemberName,
+                 Some(mr::Operand::IdRef(target)),
+                 Some(mr::Operand::LiteralInt32(member)),
+                 Some(mr::Operand::LiteralString(name))) => {
+                    index.member_names.insert((*target, *member), name);
+                }
+                _ => (),
+            }
+        }
+        index
+    }
+
+    /// Returns the instruction defining the global `id`.
+    pub fn def(&self, id: Word) -> Option<&'m mr::Instruction> {
+        self.defs.get(&id).cloned()
+    }
+
+    /// Returns the name of the given `id`.
+    pub fn name(&self, id: Word) -> Option<String> {
+        self.names.get(&id).map(|n| n.to_string())
+    }
+
+    /// Returns the first literal parameter of `decoration` on `target`.
+    pub fn decoration_literal(&self, target: Word, decoration: spirv::Decoration) -> Option<u32> {
+        literal_param(self.decorations.get(&target)?, 1, decoration)
+    }
+
+    /// Returns the first literal parameter of `decoration` on the given
+    /// struct member.
+    pub fn member_decoration_literal(&self,
+                                     target: Word,
+                                     member: u32,
+                                     decoration: spirv::Decoration)
+                                     -> Option<u32> {
+        literal_param(self.member_decorations.get(&(target, member))?, 2, decoration)
+    }
+
+    /// Returns the string parameter of `decoration` on `target`, as given
+    /// by `OpDecorateString`.
+    pub fn decoration_string(&self,
+                             target: Word,
+                             decoration: spirv::Decoration)
+                             -> Option<&'m str> {
+        string_param(self.decorations.get(&target)?, 1, decoration)
+    }
+
+    /// Returns the string parameter of `decoration` on the given struct
+    /// member, as given by `OpMemberDecorateString`.
+    pub fn member_decoration_string(&self,
+                                    target: Word,
+                                    member: u32,
+                                    decoration: spirv::Decoration)
+                                    -> Option<&'m str> {
+        string_param(self.member_decorations.get(&(target, member))?, 2, decoration)
+    }
+
+    /// Returns true if `target` is decorated with `decoration`.
+    pub fn has_decoration(&self, target: Word, decoration: spirv::Decoration) -> bool {
+        self.decorations.get(&target).is_some_and(|decs| {
+            decs.iter().any(|inst| inst.operands.get(1) == Some(&mr::Operand::Decoration(decoration)))
+        })
+    }
+
+    /// Returns the `BuiltIn` decoration of `target`, if any.
+    pub fn builtin(&self, target: Word) -> Option<spirv::BuiltIn> {
+        let builtin = Some(&mr::Operand::Decoration(spirv::Decoration::BuiltIn));
+        self.decorations.get(&target)?
+            .iter()
+            .find(|inst| inst.operands.get(1) == builtin)
+            .and_then(|inst| match inst.operands.get(2) {
+                Some(&mr::Operand::BuiltIn(builtin)) => Some(builtin),
+                _ => None,
+            })
+    }
+
+    /// Returns true if the given struct member is decorated with `decoration`.
+    pub fn member_has_decoration(&self,
+                                 target: Word,
+                                 member: u32,
+                                 decoration: spirv::Decoration)
+                                 -> bool {
+        self.member_decorations.get(&(target, member)).is_some_and(|decs| {
+            decs.iter().any(|inst| inst.operands.get(2) == Some(&mr::Operand::Decoration(decoration)))
+        })
+    }
+
+    /// Returns the value of the 32-bit integer (spec) constant `id`, using
+    /// the default value for spec constants.
+    pub fn constant_u32(&self, id: Word) -> Option<u32> {
+        let inst = self.def(id)?;
+        match inst.class.opcode {
+            spirv::Op::Constant | spirv::Op::SpecConstant => match inst.operands.first() {
+                Some(&mr::Operand::LiteralInt32(v)) => Some(v),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the pointee type of the pointer type `id`.
+    pub fn pointee_type(&self, id: Word) -> Option<Word> {
+        let inst = self.def(id)?;
+        if inst.class.opcode != spirv::Op::TypePointer {
+            return None;
+        }
+        id_operand(inst, 1)
+    }
+
+    /// Returns the explicit layout of the struct type `id`.
+    pub fn struct_layout(&self, id: Word) -> Option<StructLayout> {
+        let inst = self.def(id)?;
+        if inst.class.opcode != spirv::Op::TypeStruct {
+            return None;
+        }
+        let members: Vec<MemberLayout> = (0..inst.operands.len())
+            .filter_map(|i| id_operand(inst, i).map(|ty| (i as u32, ty)))
+            .map(|(index, ty)| MemberLayout {
+                index,
+                name: self.member_names.get(&(id, index)).map(|n| n.to_string()),
+                type_id: ty,
+                offset: self.member_decoration_literal(id, index, spirv::Decoration::Offset),
+                size: self.member_size(id, index, ty),
+            })
+            .collect();
+        let size = members.iter().try_fold(0, |acc, m| {
+            let is_runtime_array = self.def(m.type_id)
+                .is_some_and(|t| t.class.opcode == spirv::Op::TypeRuntimeArray);
+            let end = if is_runtime_array {
+                m.offset?
+            } else {
+                m.offset?.checked_add(m.size?)?
+            };
+            Some(if end > acc { end } else { acc })
+        });
+        Some(StructLayout {
+            type_id: id,
+            name: self.name(id),
+            members,
+            size,
+        })
+    }
+
+    /// Returns the size of the given type in bytes.
+    pub fn type_size(&self, id: Word) -> Option<u32> {
+        let inst = self.def(id)?;
+        match inst.class.opcode {
+            spirv::Op::TypeInt | spirv::Op::TypeFloat => match inst.operands.first() {
+                Some(&mr::Operand::LiteralInt32(width)) => Some(width / 8),
+                _ => None,
+            },
+            spirv::Op::TypeVector | spirv::Op::TypeMatrix => {
+                self.type_size(id_operand(inst, 0)?)?.checked_mul(literal_operand(inst, 1)?)
+            }
+            spirv::Op::TypeArray => {
+                let element = id_operand(inst, 0)?;
+                let length = self.constant_u32(id_operand(inst, 1)?)?;
+                let stride = match self.decoration_literal(id, spirv::Decoration::ArrayStride) {
+                    Some(stride) => stride,
+                    None => self.type_size(element)?,
+                };
+                stride.checked_mul(length)
+            }
+            spirv::Op::TypeStruct => self.struct_layout(id)?.size,
+            spirv::Op::TypePointer => match inst.operands.first() {
+                Some(&mr::Operand::StorageClass(spirv::StorageClass::PhysicalStorageBuffer)) => {
+                    Some(8)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the size of a struct member in bytes, taking the member's
+    /// `MatrixStride` decoration into account.
+    fn member_size(&self, id: Word, member: u32, ty: Word) -> Option<u32> {
+        let stride = match self.member_decoration_literal(id, member, spirv::Decoration::MatrixStride) {
+            Some(stride) => stride,
+            None => return self.type_size(ty),
+        };
+        let mut element = ty;
+        let mut count = 1;
+        while let Some(inst) = self.def(element) {
+            match inst.class.opcode {
+                spirv::Op::TypeArray => {
+                    let length = self.constant_u32(id_operand(inst, 1)?)?;
+                    if let Some(array_stride) =
+                        self.decoration_literal(element, spirv::Decoration::ArrayStride) {
+                        return array_stride.checked_mul(length)?.checked_mul(count);
+                    }
+                    count = count.checked_mul(length)?;
+                    element = id_operand(inst, 0)?;
+                }
+                spirv::Op::TypeMatrix => {
+                    let columns = literal_operand(inst, 1)?;
+                    let rows = literal_operand(self.def(id_operand(inst, 0)?)?, 1)?;
+                    let vectors = if self.member_has_decoration(id, member, spirv::Decoration::RowMajor) {
+                        rows
+                    } else {
+                        columns
+                    };
+                    return stride.checked_mul(vectors)?.checked_mul(count);
+                }
+                _ => return self.type_size(ty),
+            }
+        }
+        None
+    }
+}
+
+fn literal_param(decorations: &[&mr::Instruction],
+                 start: usize,
+                 decoration: spirv::Decoration)
+                 -> Option<u32> {
+    decorations.iter()
+        .find(|inst| inst.operands.get(start) == Some(&mr::Operand::Decoration(decoration)))
+        .and_then(|inst| match inst.operands.get(start + 1) {
+            Some(&mr::Operand::LiteralInt32(v)) => Some(v),
+            _ => None,
+        })
+}
+
+fn string_param<'m>(decorations: &[&'m mr::Instruction],
+                    start: usize,
+                    decoration: spirv::Decoration)
+                    -> Option<&'m str> {
+    decorations.iter()
+        .find(|inst| inst.operands.get(start) == Some(&mr::Operand::Decoration(decoration)))
+        .and_then(|inst| match inst.operands.get(start + 1) {
+            Some(mr::Operand::LiteralString(s)) => Some(s.as_str()),
+            _ => None,
+        })
+}
+
+pub(in reflect) fn id_operand(inst: &mr::Instruction, index: usize) -> Option<Word> {
+    match inst.operands.get(index) {
+        Some(&mr::Operand::IdRef(id)) => Some(id),
+        _ => None,
+    }
+}
+
+fn literal_operand(inst: &mr::Instruction, index: usize) -> Option<u32> {
+    match inst.operands.get(index) {
+        Some(&mr::Operand::LiteralInt32(v)) => Some(v),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use binary::{Assemble, Disassemble};
+    use mr;
+    use reflect;
+    use spirv;
+
+    #[test]
+    fn test_struct_layout() {
+        let mut b = mr::Builder::new();
+        let float = b.type_float(32);
+        let uint = b.type_int(32, 0);
+        let vec4 = b.type_vector(float, 4);
+        let mat4 = b.type_matrix(vec4, 4);
+        let four = b.constant_u32(uint, 4);
+        let array = b.type_array(float, four);
+        let runtime = b.type_runtime_array(uint);
+        let s = b.type_struct(vec![vec4, mat4, array, runtime]);
+        b.decorate(array, spirv::Decoration::ArrayStride, vec![mr::Operand::LiteralInt32(16)]);
+        for (i, offset) in [0, 16, 80, 144].iter().enumerate() {
+            b.member_decorate(s, i as u32, spirv::Decoration::Offset,
+                              vec![mr::Operand::LiteralInt32(*offset)]);
+        }
+        b.member_decorate(s, 1, spirv::Decoration::MatrixStride,
+                          vec![mr::Operand::LiteralInt32(16)]);
+        b.member_name(s, 0, "color");
+        b.name(s, "Block");
+        let module = b.module();
+
+        let layout = reflect::struct_layout(&module, s).unwrap();
+        assert_eq!(Some("Block".to_string()), layout.name);
+        assert_eq!(Some("color".to_string()), layout.members[0].name);
+        let sizes: Vec<Option<u32>> = layout.members.iter().map(|m| m.size).collect();
+        assert_eq!(vec![Some(16), Some(64), Some(64), None], sizes);
+        assert_eq!(Some(144), layout.size);
+        assert_eq!(Some(144), reflect::type_size(&module, s));
+        assert_eq!(None, reflect::struct_layout(&module, float));
+    }
+
+    #[test]
+    fn test_missing_offset() {
+        let mut b = mr::Builder::new();
+        let float = b.type_float(32);
+        let s = b.type_struct(vec![float, float]);
+        b.member_decorate(s, 0, spirv::Decoration::Offset, vec![mr::Operand::LiteralInt32(0)]);
+        let module = b.module();
+
+        let layout = reflect::struct_layout(&module, s).unwrap();
+        assert_eq!(Some(0), layout.members[0].offset);
+        assert_eq!(None, layout.members[1].offset);
+        assert_eq!(Some(4), layout.members[1].size);
+        assert_eq!(None, layout.size);
+    }
+
+    #[test]
+    fn test_size_overflow() {
+        let mut b = mr::Builder::new();
+        let uint = b.type_int(32, 0);
+        let uvec4 = b.type_vector(uint, 4);
+        let length = b.constant_u32(uint, 1 << 30);
+        let array = b.type_array(uvec4, length);
+        let s = b.type_struct(vec![uint, array]);
+        b.decorate(array, spirv::Decoration::ArrayStride, vec![mr::Operand::LiteralInt32(16)]);
+        for (i, offset) in [0, 16].iter().enumerate() {
+            b.member_decorate(s, i as u32, spirv::Decoration::Offset,
+                              vec![mr::Operand::LiteralInt32(*offset)]);
+        }
+        let module = b.module();
+
+        assert_eq!(None, reflect::type_size(&module, array));
+        let layout = reflect::struct_layout(&module, s).unwrap();
+        assert_eq!(None, layout.members[1].size);
+        assert_eq!(None, layout.size);
+    }
+
+    #[test]
+    fn test_decoration_string() {
+        let mut b = mr::Builder::new();
+        b.extension("SPV_GOOGLE_hlsl_functionality1");
+        b.extension("SPV_GOOGLE_user_type");
+        let float = b.type_float(32);
+        let s = b.type_struct(vec![float]);
+        b.decorate_string(s, spirv::Decoration::UserTypeGOOGLE, vec!["cbuffer".into()]);
+        b.member_decorate_string(s,
+                                 0,
+                                 spirv::Decoration::HlslSemanticGOOGLE,
+                                 vec!["COLOR".into()]);
+        let words = b.module().assemble();
+        let module = mr::load_words(words).unwrap();
+
+        assert_eq!(module.annotations[0].class.opcode, spirv::Op::DecorateString);
+        assert_eq!(Some("cbuffer"),
+                   reflect::decoration_string(&module, s, spirv::Decoration::UserTypeGOOGLE));
+        assert_eq!(Some("COLOR"),
+                   reflect::member_decoration_string(&module,
+                                                     s,
+                                                     0,
+                                                     spirv::Decoration::HlslSemanticGOOGLE));
+        assert_eq!(None, reflect::decoration_string(&module, s, spirv::Decoration::Block));
+        let text = module.disassemble();
+        assert!(text.contains(&format!("OpDecorateString %{} UserTypeGOOGLE \"cbuffer\"", s)));
+        assert!(text.contains(&format!("OpMemberDecorateString %{} 0 HlslSemanticGOOGLE \"COLOR\"",
+                                       s)));
+    }
+
+    #[test]
+    fn test_google_decorations() {
+        let mut b = mr::Builder::new();
+        b.extension("SPV_GOOGLE_hlsl_functionality1");
+        b.extension("SPV_GOOGLE_user_type");
+        let float = b.type_float(32);
+        let s = b.type_struct(vec![float, float]);
+        let ptr = b.type_pointer(None, spirv::StorageClass::Uniform, s);
+        let buffer = b.variable(ptr, None, spirv::StorageClass::Uniform, None);
+        let counter = b.variable(ptr, None, spirv::StorageClass::Uniform, None);
+        b.decorate_string(buffer, spirv::Decoration::HlslSemanticGOOGLE, vec!["DATA".into()]);
+        b.member_decorate_string(s,
+                                 1,
+                                 spirv::Decoration::UserTypeGOOGLE,
+                                 vec!["structuredbuffer".into()]);
+        b.decorate_id(buffer,
+                      spirv::Decoration::HlslCounterBufferGOOGLE,
+                      vec![mr::Operand::IdRef(counter)]);
+        let words = b.module().assemble();
+        let module = mr::load_words(words).unwrap();
+
+        assert_eq!(Some("DATA"),
+                   reflect::decoration_string(&module,
+                                              buffer,
+                                              spirv::Decoration::HlslSemanticGOOGLE));
+        assert_eq!(None,
+                   reflect::decoration_string(&module, buffer, spirv::Decoration::UserTypeGOOGLE));
+        assert_eq!(Some("structuredbuffer"),
+                   reflect::member_decoration_string(&module,
+                                                     s,
+                                                     1,
+                                                     spirv::Decoration::UserTypeGOOGLE));
+        assert_eq!(None,
+                   reflect::member_decoration_string(&module,
+                                                     s,
+                                                     0,
+                                                     spirv::Decoration::UserTypeGOOGLE));
+        let index = reflect::TypeIndex::new(&module);
+        assert!(index.has_decoration(buffer, spirv::Decoration::HlslCounterBufferGOOGLE));
+        assert!(!index.has_decoration(counter, spirv::Decoration::HlslCounterBufferGOOGLE));
+        assert_eq!(None,
+                   reflect::decoration_string(&module,
+                                              buffer,
+                                              spirv::Decoration::HlslCounterBufferGOOGLE));
+    }
+}
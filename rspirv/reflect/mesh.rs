@@ -0,0 +1,318 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::BTreeSet;
+
+use super::layout::{id_operand, TypeIndex};
+
+/// The primitive topology output by a mesh shader.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MeshTopology {
+    Points,
+    Lines,
+    Triangles,
+}
+
+impl MeshTopology {
+    /// Returns the topology declared by the execution mode `mode`, if any.
+    pub fn from_execution_mode(mode: spirv::ExecutionMode) -> Option<MeshTopology> {
+        match mode {
+            spirv::ExecutionMode::OutputPoints => Some(MeshTopology::Points),
+            spirv::ExecutionMode::OutputLinesEXT => Some(MeshTopology::Lines),
+            spirv::ExecutionMode::OutputTrianglesEXT => Some(MeshTopology::Triangles),
+            _ => None,
+        }
+    }
+
+    /// Returns the topology of the primitives whose vertex indices the
+    /// built-in `builtin` holds, if any.
+    pub fn from_indices(builtin: spirv::BuiltIn) -> Option<MeshTopology> {
+        match builtin {
+            spirv::BuiltIn::PrimitivePointIndicesEXT => Some(MeshTopology::Points),
+            spirv::BuiltIn::PrimitiveLineIndicesEXT => Some(MeshTopology::Lines),
+            spirv::BuiltIn::PrimitiveTriangleIndicesEXT => Some(MeshTopology::Triangles),
+            _ => None,
+        }
+    }
+}
+
+/// A task or mesh shader entry point.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshStage {
+    /// The result id of the entry point function.
+    pub function: Word,
+    /// The name of the entry point.
+    pub name: String,
+    /// `TaskEXT` or `MeshEXT`.
+    pub execution_model: spirv::ExecutionModel,
+    /// The output topology of a mesh shader, if declared.
+    pub topology: Option<MeshTopology>,
+    /// The `OutputVertices` execution mode of a mesh shader, if any.
+    pub max_vertices: Option<u32>,
+    /// The `OutputPrimitivesEXT` execution mode of a mesh shader, if any.
+    pub max_primitives: Option<u32>,
+}
+
+/// An output variable of a mesh shader.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshOutput {
+    /// The result id of the `OpVariable`.
+    pub variable: Word,
+    /// The result id of the type of the output (not the pointer type).
+    pub type_id: Word,
+    /// The `BuiltIn` decoration on the variable, if any.
+    pub builtin: Option<spirv::BuiltIn>,
+    /// The `Location` decoration on the variable, if any.
+    pub location: Option<u32>,
+    /// Whether the output is per primitive rather than per vertex: it
+    /// holds primitive indices, or it or a member of its (array element)
+    /// struct type is decorated `PerPrimitiveEXT`.
+    pub per_primitive: bool,
+}
+
+/// A `TaskPayloadWorkgroupEXT` variable, which task shaders pass to the
+/// mesh shaders they launch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaskPayload {
+    /// The result id of the `OpVariable`.
+    pub variable: Word,
+    /// The result id of the type of the payload (not the pointer type).
+    pub type_id: Word,
+    /// The size of the payload in bytes, if it can be computed.
+    pub size: Option<u32>,
+}
+
+/// Reflection information of the task and mesh shader stages in a module.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MeshShadingReflection {
+    /// All entry points using the `TaskEXT` or `MeshEXT` execution model.
+    pub stages: Vec<MeshStage>,
+    /// All `Output` variables in the interfaces of mesh shaders.
+    pub outputs: Vec<MeshOutput>,
+    /// All task payload variables.
+    pub payloads: Vec<TaskPayload>,
+}
+
+/// Collects reflection information of the task and mesh shader stages in
+/// `module`.
+///
+/// All results follow the order of declaration in the module.
+pub fn mesh_shading(module: &mr::Module) -> MeshShadingReflection {
+    let index = TypeIndex::new(module);
+    let mut reflection = MeshShadingReflection::default();
+
+    let mut interfaces = BTreeSet::new();
+    for inst in &module.entry_points {
+        let (model, function, name) =
+            match (inst.operands.first(), inst.operands.get(1), inst.operands.get(2)) {
+                (Some(&mr::Operand::ExecutionModel(model)),
+                 Some(&mr::Operand::IdRef(function)),
+                 Some(mr::Operand::LiteralString(name))) if is_mesh_shading_model(model) => {
+                    (model, function, name)
+                }
+                _ => continue,
+            };
+        let mut stage = MeshStage {
+            function,
+            name: name.clone(),
+            execution_model: model,
+            topology: None,
+            max_vertices: None,
+            max_primitives: None,
+        };
+        for em in &module.execution_modes {
+            let (mode, literal) =
+                match (em.operands.first(), em.operands.get(1), em.operands.get(2)) {
+                    (Some(&mr::Operand::IdRef(target)),
+                     Some(&mr::Operand::ExecutionMode(mode)),
+                     literal) if target == function => {
+                        let literal = match literal {
+                            Some(&mr::Operand::LiteralInt32(value)) => Some(value),
+                            _ => None,
+                        };
+                        (mode, literal)
+                    }
+                    _ => continue,
+                };
+            match mode {
+                spirv::ExecutionMode::OutputVertices => stage.max_vertices = literal,
+                spirv::ExecutionMode::OutputPrimitivesEXT => stage.max_primitives = literal,
+                _ => {
+                    if let Some(topology) = MeshTopology::from_execution_mode(mode) {
+                        stage.topology = Some(topology);
+                    }
+                }
+            }
+        }
+        if model == spirv::ExecutionModel::MeshEXT {
+            interfaces.extend(inst.operands.iter().skip(3).filter_map(mr::Operand::id));
+        }
+        reflection.stages.push(stage);
+    }
+
+    for inst in &module.types_global_values {
+        if inst.class.opcode != spirv::Op::Variable {
+            continue;
+        }
+        let (variable, storage_class, type_id) =
+            match (inst.result_id, inst.operands.first(), inst.result_type) {
+                (Some(id), Some(&mr::Operand::StorageClass(sc)), Some(ty)) => {
+                    match index.pointee_type(ty) {
+                        Some(pointee) => (id, sc, pointee),
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+        match storage_class {
+            spirv::StorageClass::Output if interfaces.contains(&variable) => {
+                let builtin = index.builtin(variable);
+                let per_primitive = builtin.and_then(MeshTopology::from_indices).is_some() ||
+                                    index.has_decoration(variable,
+                                                         spirv::Decoration::PerPrimitiveEXT) ||
+                                    has_per_primitive_member(&index, type_id);
+                reflection.outputs.push(MeshOutput {
+                    variable,
+                    type_id,
+                    builtin,
+                    location: index.decoration_literal(variable, spirv::Decoration::Location),
+                    per_primitive,
+                })
+            }
+            spirv::StorageClass::TaskPayloadWorkgroupEXT => {
+                reflection.payloads.push(TaskPayload {
+                    variable,
+                    type_id,
+                    size: index.type_size(type_id),
+                })
+            }
+            _ => (),
+        }
+    }
+
+    reflection
+}
+
+fn is_mesh_shading_model(model: spirv::ExecutionModel) -> bool {
+    model == spirv::ExecutionModel::TaskEXT || model == spirv::ExecutionModel::MeshEXT
+}
+
+/// Returns true if `ty`, or its element type for arrays, is a struct with
+/// a member decorated `PerPrimitiveEXT`.
+fn has_per_primitive_member(index: &TypeIndex, ty: Word) -> bool {
+    let mut inst = match index.def(ty) {
+        Some(inst) => inst,
+        None => return false,
+    };
+    let mut ty = ty;
+    while inst.class.opcode == spirv::Op::TypeArray ||
+          inst.class.opcode == spirv::Op::TypeRuntimeArray {
+        match id_operand(inst, 0).and_then(|element| index.def(element).map(|d| (element, d))) {
+            Some((element, def)) => {
+                ty = element;
+                inst = def;
+            }
+            None => return false,
+        }
+    }
+    inst.class.opcode == spirv::Op::TypeStruct &&
+    (0..inst.operands.len() as u32)
+        .any(|member| index.member_has_decoration(ty, member, spirv::Decoration::PerPrimitiveEXT))
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use reflect;
+    use spirv;
+
+    use reflect::MeshTopology;
+
+    #[test]
+    fn test_mesh_shading() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::MeshShadingEXT);
+        b.extension("SPV_EXT_mesh_shader");
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let float = b.type_float(32);
+        let vec4 = b.type_vector(float, 4);
+        let uvec3 = b.type_vector(uint, 3);
+        let three = b.constant_u32(uint, 3);
+        let one = b.constant_u32(uint, 1);
+        let positions = b.type_array(vec4, three);
+        let indices = b.type_array(uvec3, one);
+        let colors = b.type_array(vec4, one);
+        let payload_ptr = b.type_pointer(None, spirv::StorageClass::TaskPayloadWorkgroupEXT, uint);
+        let positions_ptr = b.type_pointer(None, spirv::StorageClass::Output, positions);
+        let indices_ptr = b.type_pointer(None, spirv::StorageClass::Output, indices);
+        let colors_ptr = b.type_pointer(None, spirv::StorageClass::Output, colors);
+
+        let payload = b.variable(payload_ptr, None, spirv::StorageClass::TaskPayloadWorkgroupEXT,
+                                 None);
+        let position = b.variable(positions_ptr, None, spirv::StorageClass::Output, None);
+        b.decorate(position, spirv::Decoration::BuiltIn,
+                   vec![mr::Operand::BuiltIn(spirv::BuiltIn::Position)]);
+        let index = b.variable(indices_ptr, None, spirv::StorageClass::Output, None);
+        b.decorate(index, spirv::Decoration::BuiltIn,
+                   vec![mr::Operand::BuiltIn(spirv::BuiltIn::PrimitiveTriangleIndicesEXT)]);
+        let color = b.variable(colors_ptr, None, spirv::StorageClass::Output, None);
+        b.decorate(color, spirv::Decoration::Location, vec![mr::Operand::LiteralInt32(0)]);
+        b.decorate(color, spirv::Decoration::PerPrimitiveEXT, vec![]);
+
+        let voidf = b.type_function(void, vec![]);
+        let task = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.emit_mesh_tasks_ext(one, one, one, Some(payload)).unwrap();
+        b.end_function().unwrap();
+        let mesh = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.set_mesh_outputs_ext(three, one).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::TaskEXT, task, "task", vec![payload]);
+        b.entry_point(spirv::ExecutionModel::MeshEXT, mesh, "mesh",
+                      vec![payload, position, index, color]);
+        b.mesh_outputs(mesh, spirv::ExecutionMode::OutputTrianglesEXT, 3, 1);
+        let module = b.module();
+
+        let reflection = reflect::mesh_shading(&module);
+        assert_eq!(reflection.stages.len(), 2);
+        assert_eq!(reflection.stages[0].topology, None);
+        assert_eq!(reflection.stages[1],
+                   reflect::MeshStage {
+                       function: mesh,
+                       name: "mesh".to_owned(),
+                       execution_model: spirv::ExecutionModel::MeshEXT,
+                       topology: Some(MeshTopology::Triangles),
+                       max_vertices: Some(3),
+                       max_primitives: Some(1),
+                   });
+        let outputs: Vec<_> = reflection.outputs
+            .iter()
+            .map(|o| (o.variable, o.builtin, o.location, o.per_primitive))
+            .collect();
+        assert_eq!(outputs,
+                   vec![(position, Some(spirv::BuiltIn::Position), None, false),
+                        (index, Some(spirv::BuiltIn::PrimitiveTriangleIndicesEXT), None, true),
+                        (color, None, Some(0), true)]);
+        assert_eq!(reflection.payloads,
+                   vec![reflect::TaskPayload { variable: payload, type_id: uint, size: Some(4) }]);
+    }
+}
@@ -0,0 +1,40 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reflection of shader interfaces from the data representation.
+//!
+//! The functions in this module extract the information needed to build
+//! pipelines around a SPIR-V module, e.g., resource bindings and the
+//! explicit layout of the blocks they use, directly from the instructions
+//! and decorations in a [`mr::Module`](../mr/struct.Module.html).
+
+pub use self::array::{array_length, ArrayLength};
+pub use self::buffer_reference::{buffer_references, BufferReference};
+pub use self::layout::{decoration_string, member_decoration_string};
+pub use self::layout::{struct_layout, type_size, MemberLayout, StructLayout};
+pub use self::mesh::{mesh_shading, MeshOutput, MeshShadingReflection, MeshStage};
+pub use self::mesh::{MeshTopology, TaskPayload};
+pub use self::push_constants::{push_constants, PushConstantAccess, PushConstantRange};
+pub use self::push_constants::PushConstantUsage;
+pub use self::ray_tracing::{ray_tracing, AccelerationStructureBinding, RayData};
+pub use self::ray_tracing::{RayTracingReflection, RayTracingStage, ShaderRecordBuffer};
+
+pub(crate) use self::layout::TypeIndex;
+
+mod array;
+mod buffer_reference;
+mod layout;
+mod mesh;
+mod push_constants;
+mod ray_tracing;
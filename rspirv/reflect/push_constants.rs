@@ -0,0 +1,397 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::layout::{id_operand, TypeIndex};
+
+/// A range of bytes of push constants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PushConstantRange {
+    /// The offset of the first byte.
+    pub offset: u32,
+    /// The number of bytes.
+    pub size: u32,
+}
+
+/// A read of push constants through a pointer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PushConstantAccess {
+    /// The result id of the `PushConstant` variable.
+    pub variable: Word,
+    /// The result id of the pointer read through: the variable, an access
+    /// chain into it, or a copy of either.
+    pub pointer: Word,
+    /// The bytes the pointer may read.
+    pub range: PushConstantRange,
+}
+
+/// The push constants an entry point reads.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PushConstantUsage {
+    /// The result id of the entry point function.
+    pub function: Word,
+    /// The name of the entry point.
+    pub name: String,
+    /// The execution model of the entry point.
+    pub execution_model: spirv::ExecutionModel,
+    /// The reads, in the order of the instructions of the functions of
+    /// the module, each pointer once.
+    pub accesses: Vec<PushConstantAccess>,
+    /// The bytes read, in increasing order, overlapping and adjacent
+    /// ranges merged.
+    pub ranges: Vec<PushConstantRange>,
+}
+
+/// Collects the push constants each entry point of `module` reads, in the
+/// order of the entry points.
+///
+/// Pointers into `PushConstant` variables are followed through
+/// `OpAccessChain`s, `OpInBoundsAccessChain`s, `OpCopyObject`s, and into
+/// the parameters of the functions the entry point calls, directly or not;
+/// every other use of them is a read. Constant indices narrow a pointer to
+/// the struct member, array element, or vector component they select, by
+/// the `Offset` and `ArrayStride` decorations; other indices, and indices
+/// into matrices, leave it covering the whole object indexed, and a
+/// parameter given several pointers covers them all. Variables whose size
+/// is unknown are not followed.
+pub fn push_constants(module: &mr::Module) -> Vec<PushConstantUsage> {
+    let index = TypeIndex::new(module);
+    let roots: BTreeMap<Word, Region> = module.types_global_values
+        .iter()
+        .filter(|inst| inst.class.opcode == spirv::Op::Variable)
+        .filter(|inst| {
+            inst.operands.first() ==
+            Some(&mr::Operand::StorageClass(spirv::StorageClass::PushConstant))
+        })
+        .filter_map(|inst| {
+            let variable = inst.result_id?;
+            let ty = index.pointee_type(inst.result_type?)?;
+            let end = index.type_size(ty)?;
+            Some((variable, Region { variable, offset: 0, end, ty: Some(ty) }))
+        })
+        .collect();
+
+    let mut usages = vec![];
+    for inst in &module.entry_points {
+        let (execution_model, function, name) =
+            match (inst.operands.first(), inst.operands.get(1), inst.operands.get(2)) {
+                (Some(&mr::Operand::ExecutionModel(model)),
+                 Some(&mr::Operand::IdRef(function)),
+                 Some(mr::Operand::LiteralString(name))) => (model, function, name.clone()),
+                _ => continue,
+            };
+        let accesses = accesses(module, &index, &roots, function);
+        let ranges = merge_ranges(accesses.iter().map(|access| access.range).collect());
+        usages.push(PushConstantUsage { function, name, execution_model, accesses, ranges });
+    }
+    usages
+}
+
+/// The bytes of a push-constant variable a pointer may point to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Region {
+    variable: Word,
+    offset: u32,
+    end: u32,
+    /// The type of the object the pointer points to, if it points to
+    /// exactly one.
+    ty: Option<Word>,
+}
+
+impl Region {
+    /// Returns the region covering this one and `other`.
+    fn merge(&self, other: &Region) -> Region {
+        if self == other {
+            return self.clone();
+        }
+        Region {
+            variable: self.variable,
+            offset: self.offset.min(other.offset),
+            end: self.end.max(other.end),
+            ty: None,
+        }
+    }
+
+    /// Returns the region an access chain with the given `indices` points
+    /// to from this one.
+    fn chain(&self, index: &TypeIndex, indices: &[mr::Operand]) -> Region {
+        let mut region = self.clone();
+        for operand in indices {
+            let ty = match region.ty {
+                Some(ty) => ty,
+                None => break,
+            };
+            let constant = operand.id().and_then(|id| index.constant_u32(id));
+            match constant.and_then(|constant| select(index, ty, constant)) {
+                Some((offset, size, element)) => {
+                    region.offset += offset;
+                    region.end = region.offset + size;
+                    region.ty = Some(element);
+                }
+                None => {
+                    region.ty = None;
+                    break;
+                }
+            }
+        }
+        region
+    }
+}
+
+/// Returns the offset, size, and type of the member, element, or component
+/// `constant` of the type `ty`.
+fn select(index: &TypeIndex, ty: Word, constant: u32) -> Option<(u32, u32, Word)> {
+    let def = index.def(ty)?;
+    match def.class.opcode {
+        spirv::Op::TypeStruct => {
+            let layout = index.struct_layout(ty)?;
+            let member = layout.members.get(constant as usize)?;
+            Some((member.offset?, member.size?, member.type_id))
+        }
+        spirv::Op::TypeArray | spirv::Op::TypeRuntimeArray | spirv::Op::TypeVector => {
+            let element = id_operand(def, 0)?;
+            let size = index.type_size(element)?;
+            let stride = match def.class.opcode {
+                spirv::Op::TypeVector => size,
+                _ => index.decoration_literal(ty, spirv::Decoration::ArrayStride).unwrap_or(size),
+            };
+            Some((constant.checked_mul(stride)?, size, element))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the ids of `entry_point` and the functions it calls, directly or
+/// not.
+fn call_tree(module: &mr::Module, entry_point: Word) -> BTreeSet<Word> {
+    let bodies: BTreeMap<Word, &mr::Function> = module.functions
+        .iter()
+        .filter_map(|f| f.def.as_ref()?.result_id.map(|id| (id, f)))
+        .collect();
+    let mut visited = BTreeSet::new();
+    let mut stack = vec![entry_point];
+    while let Some(f) = stack.pop() {
+        if !visited.insert(f) {
+            continue;
+        }
+        let insts = bodies.get(&f).into_iter().flat_map(|f| &f.basic_blocks).flat_map(|b| {
+            &b.instructions
+        });
+        for inst in insts.filter(|inst| inst.class.opcode == spirv::Op::FunctionCall) {
+            stack.extend(id_operand(inst, 0));
+        }
+    }
+    visited
+}
+
+/// Returns the reads of push constants by the entry point of the function
+/// `entry_point`, from the variables in `roots`.
+fn accesses(module: &mr::Module,
+            index: &TypeIndex,
+            roots: &BTreeMap<Word, Region>,
+            entry_point: Word)
+            -> Vec<PushConstantAccess> {
+    let reachable = call_tree(module, entry_point);
+    let functions: Vec<&mr::Function> = module.functions
+        .iter()
+        .filter(|f| f.def.as_ref().and_then(|def| def.result_id).is_some_and(|id| {
+            reachable.contains(&id)
+        }))
+        .collect();
+    let params: BTreeMap<Word, Vec<Word>> = functions.iter()
+        .filter_map(|f| {
+            let id = f.def.as_ref()?.result_id?;
+            Some((id, f.parameters.iter().filter_map(|p| p.result_id).collect()))
+        })
+        .collect();
+    let insts = || functions.iter().flat_map(|f| &f.basic_blocks).flat_map(|b| &b.instructions);
+
+    // The regions of the pointers, widened until no more change.
+    let mut pointers = roots.clone();
+    loop {
+        let mut changed = false;
+        for inst in insts() {
+            let region = |operand: Option<&mr::Operand>| {
+                operand.and_then(mr::Operand::id).and_then(|id| pointers.get(&id)).cloned()
+            };
+            let base = region(inst.operands.first());
+            let mut updates = vec![];
+            match inst.class.opcode {
+                spirv::Op::AccessChain | spirv::Op::InBoundsAccessChain => {
+                    if let (Some(id), Some(base)) = (inst.result_id, base) {
+                        updates.push((id, base.chain(index, &inst.operands[1..])));
+                    }
+                }
+                spirv::Op::CopyObject => {
+                    if let (Some(id), Some(base)) = (inst.result_id, base) {
+                        updates.push((id, base));
+                    }
+                }
+                spirv::Op::FunctionCall => {
+                    let callee = id_operand(inst, 0).and_then(|f| params.get(&f));
+                    for (&param, arg) in callee.into_iter().flatten().zip(&inst.operands[1..]) {
+                        updates.extend(region(Some(arg)).map(|region| (param, region)));
+                    }
+                }
+                _ => {}
+            }
+            for (id, region) in updates {
+                let merged = match pointers.get(&id) {
+                    Some(old) => old.merge(&region),
+                    None => region,
+                };
+                if pointers.get(&id) != Some(&merged) {
+                    pointers.insert(id, merged);
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let mut accesses = vec![];
+    for inst in insts() {
+        for (position, operand) in inst.operands.iter().enumerate() {
+            let pointer = operand.id();
+            let (pointer, region) = match pointer.and_then(|id| Some((id, pointers.get(&id)?))) {
+                Some(found) => found,
+                None => continue,
+            };
+            let followed = match inst.class.opcode {
+                spirv::Op::AccessChain | spirv::Op::InBoundsAccessChain => position == 0,
+                spirv::Op::CopyObject => true,
+                spirv::Op::FunctionCall => position > 0,
+                _ => false,
+            };
+            let access = PushConstantAccess {
+                variable: region.variable,
+                pointer,
+                range: PushConstantRange {
+                    offset: region.offset,
+                    size: region.end - region.offset,
+                },
+            };
+            if !followed && !accesses.contains(&access) {
+                accesses.push(access);
+            }
+        }
+    }
+    accesses
+}
+
+/// Sorts `ranges`, and merges the overlapping and adjacent ones.
+fn merge_ranges(mut ranges: Vec<PushConstantRange>) -> Vec<PushConstantRange> {
+    ranges.sort_by_key(|range| range.offset);
+    let mut merged: Vec<PushConstantRange> = vec![];
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.offset <= last.offset + last.size => {
+                let end = (last.offset + last.size).max(range.offset + range.size);
+                last.size = end - last.offset;
+            }
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use reflect;
+    use spirv;
+
+    use reflect::PushConstantRange;
+    use spirv::Word;
+
+    #[test]
+    fn test_push_constants() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let float = b.type_float(32);
+        let vec4 = b.type_vector(float, 4);
+        let four = b.constant_u32(uint, 4);
+        let (zero, one, two) = (b.constant_u32(uint, 0), b.constant_u32(uint, 1),
+                                b.constant_u32(uint, 2));
+        let array = b.type_array(vec4, four);
+        b.decorate(array, spirv::Decoration::ArrayStride, vec![mr::Operand::LiteralInt32(16)]);
+        // { uint; vec4[4]; float }
+        let block = b.type_struct(vec![uint, array, float]);
+        b.decorate(block, spirv::Decoration::Block, vec![]);
+        for (member, offset) in [0, 16, 80].iter().enumerate() {
+            b.member_decorate(block, member as u32, spirv::Decoration::Offset,
+                              vec![mr::Operand::LiteralInt32(*offset)]);
+        }
+        let ptr_block = b.type_pointer(None, spirv::StorageClass::PushConstant, block);
+        let ptr_uint = b.type_pointer(None, spirv::StorageClass::PushConstant, uint);
+        let ptr_vec4 = b.type_pointer(None, spirv::StorageClass::PushConstant, vec4);
+        let ptr_float = b.type_pointer(None, spirv::StorageClass::PushConstant, float);
+        let pc = b.variable(ptr_block, None, spirv::StorageClass::PushConstant, None);
+
+        // Reads the z component of the pointed vector.
+        let helperf = b.type_function(float, vec![ptr_vec4]);
+        let helper = b.begin_function(float, None, spirv::FunctionControl::NONE, helperf)
+            .unwrap();
+        let param = b.function_parameter(ptr_vec4).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let z = b.access_chain(ptr_float, None, param, vec![two]).unwrap();
+        let value = b.load(float, None, z, None, vec![]).unwrap();
+        b.ret_value(value).unwrap();
+        b.end_function().unwrap();
+
+        let voidf = b.type_function(void, vec![]);
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let count = b.access_chain(ptr_uint, None, pc, vec![zero]).unwrap();
+        let i = b.load(uint, None, count, None, vec![]).unwrap();
+        let second = b.access_chain(ptr_vec4, None, pc, vec![one, one]).unwrap();
+        b.function_call(float, None, helper, vec![second]).unwrap();
+        let dynamic = b.access_chain(ptr_vec4, None, pc, vec![one, i]).unwrap();
+        b.load(vec4, None, dynamic, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+
+        let other = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::GLCompute, main, "main", vec![]);
+        b.entry_point(spirv::ExecutionModel::GLCompute, other, "other", vec![]);
+        let module = b.module();
+
+        let usages = reflect::push_constants(&module);
+        assert_eq!(usages.len(), 2);
+        let ranges: Vec<(u32, u32, Word)> = usages[0].accesses
+            .iter()
+            .map(|access| (access.range.offset, access.range.size, access.pointer))
+            .collect();
+        // The z component of the second vector, then the count and all
+        // vectors.
+        assert_eq!(ranges, vec![(40, 4, z), (0, 4, count), (16, 64, dynamic)]);
+        assert!(usages[0].accesses.iter().all(|access| access.variable == pc));
+        assert_eq!(usages[0].ranges,
+                   vec![PushConstantRange { offset: 0, size: 4 },
+                        PushConstantRange { offset: 16, size: 64 }]);
+        assert_eq!(usages[1].name, "other");
+        assert!(usages[1].accesses.is_empty() && usages[1].ranges.is_empty());
+    }
+}
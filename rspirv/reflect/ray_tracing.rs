@@ -0,0 +1,347 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use super::layout::{id_operand, StructLayout, TypeIndex};
+use spirv::Word;
+
+/// A ray tracing entry point.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RayTracingStage {
+    /// The result id of the entry point function.
+    pub function: Word,
+    /// The name of the entry point.
+    pub name: String,
+    /// The ray tracing execution model of the entry point.
+    pub execution_model: spirv::ExecutionModel,
+}
+
+/// An acceleration structure binding.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccelerationStructureBinding {
+    /// The result id of the `OpVariable`.
+    pub variable: Word,
+    /// The `DescriptorSet` decoration on the variable, if any.
+    pub descriptor_set: Option<u32>,
+    /// The `Binding` decoration on the variable, if any.
+    pub binding: Option<u32>,
+    /// The number of acceleration structures bound, or `None` for runtime
+    /// arrays and arrays whose length cannot be evaluated.
+    pub count: Option<u32>,
+}
+
+/// A shader record buffer block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShaderRecordBuffer {
+    /// The result id of the `OpVariable`.
+    pub variable: Word,
+    /// The layout of the block struct.
+    pub layout: StructLayout,
+}
+
+/// A ray payload, hit attribute, or callable data variable.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RayData {
+    /// The result id of the `OpVariable`.
+    pub variable: Word,
+    /// The storage class of the variable.
+    pub storage_class: spirv::StorageClass,
+    /// The result id of the type of the data (not the pointer type).
+    pub type_id: Word,
+    /// The `Location` decoration on the variable, if any.
+    pub location: Option<u32>,
+    /// The size of the data in bytes, if it can be computed.
+    pub size: Option<u32>,
+    /// Whether the variable is passed to OpTraceRayKHR or
+    /// OpExecuteCallableKHR anywhere in the module.
+    pub passed: bool,
+}
+
+/// Reflection information of the ray tracing stages in a module.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RayTracingReflection {
+    /// All entry points using a ray tracing execution model.
+    pub stages: Vec<RayTracingStage>,
+    /// All acceleration structure bindings.
+    pub acceleration_structures: Vec<AccelerationStructureBinding>,
+    /// All `ShaderRecordBufferKHR` blocks.
+    pub shader_record_buffers: Vec<ShaderRecordBuffer>,
+    /// All `RayPayloadKHR` and `IncomingRayPayloadKHR` variables.
+    pub ray_payloads: Vec<RayData>,
+    /// All `HitAttributeKHR` variables.
+    pub hit_attributes: Vec<RayData>,
+    /// All `CallableDataKHR` and `IncomingCallableDataKHR` variables.
+    pub callable_data: Vec<RayData>,
+}
+
+/// Collects reflection information of the ray tracing stages in `module`.
+///
+/// All results follow the order of declaration in the module.
+pub fn ray_tracing(module: &mr::Module) -> RayTracingReflection {
+    let index = TypeIndex::new(module);
+    let mut reflection = RayTracingReflection::default();
+
+    for inst in &module.entry_points {
+        match (inst.operands.first(), inst.operands.get(1), inst.operands.get(2)) {
+            (Some(&mr::Operand::ExecutionModel(model)),
+             Some(&mr::Operand::IdRef(function)),
+             Some(mr::Operand::LiteralString(name))) if is_ray_tracing_model(model) => {
+                reflection.stages.push(RayTracingStage {
+                    function,
+                    name: name.clone(),
+                    execution_model: model,
+                })
+            }
+            _ => (),
+        }
+    }
+
+    // Payloads and callable data passed to the ray tracing instructions.
+    let passed: Vec<Word> = module
+        .functions
+        .iter()
+        .flat_map(|f| f.basic_blocks.iter())
+        .flat_map(|b| b.instructions.iter())
+        .filter_map(|inst| match inst.class.opcode {
+            spirv::Op::TraceRayKHR => id_operand(inst, 10),
+            spirv::Op::ExecuteCallableKHR => id_operand(inst, 1),
+            _ => None,
+        })
+        .collect();
+
+    for inst in &module.types_global_values {
+        if inst.class.opcode != spirv::Op::Variable {
+            continue;
+        }
+        let (variable, storage_class, pointee) =
+            match (inst.result_id, inst.operands.first(), inst.result_type) {
+                (Some(id), Some(&mr::Operand::StorageClass(sc)), Some(ty)) => {
+                    match index.pointee_type(ty) {
+                        Some(pointee) => (id, sc, pointee),
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+        let data = || RayData {
+            variable,
+            storage_class,
+            type_id: pointee,
+            location: index.decoration_literal(variable, spirv::Decoration::Location),
+            size: index.type_size(pointee),
+            passed: passed.contains(&variable),
+        };
+        match storage_class {
+            spirv::StorageClass::UniformConstant => {
+                if let Some(count) = acceleration_structure_count(&index, pointee) {
+                    reflection.acceleration_structures.push(AccelerationStructureBinding {
+                        variable,
+                        descriptor_set: index.decoration_literal(variable,
+                                                                 spirv::Decoration::DescriptorSet),
+                        binding: index.decoration_literal(variable, spirv::Decoration::Binding),
+                        count,
+                    })
+                }
+            }
+            spirv::StorageClass::ShaderRecordBufferKHR => {
+                if let Some(layout) = index.struct_layout(pointee) {
+                    reflection.shader_record_buffers.push(ShaderRecordBuffer { variable, layout })
+                }
+            }
+            spirv::StorageClass::RayPayloadKHR |
+            spirv::StorageClass::IncomingRayPayloadKHR => reflection.ray_payloads.push(data()),
+            spirv::StorageClass::HitAttributeKHR => reflection.hit_attributes.push(data()),
+            spirv::StorageClass::CallableDataKHR |
+            spirv::StorageClass::IncomingCallableDataKHR => reflection.callable_data.push(data()),
+            _ => (),
+        }
+    }
+
+    reflection
+}
+
+fn is_ray_tracing_model(model: spirv::ExecutionModel) -> bool {
+    matches!(model,
+             spirv::ExecutionModel::RayGenerationKHR |
+             spirv::ExecutionModel::IntersectionKHR |
+             spirv::ExecutionModel::AnyHitKHR |
+             spirv::ExecutionModel::ClosestHitKHR |
+             spirv::ExecutionModel::MissKHR |
+             spirv::ExecutionModel::CallableKHR)
+}
+
+/// Returns the number of acceleration structures held by `ty`, wrapped in
+/// `Some` if `ty` is (an array of) acceleration structures.
+fn acceleration_structure_count(index: &TypeIndex, ty: Word) -> Option<Option<u32>> {
+    let inst = index.def(ty)?;
+    match inst.class.opcode {
+        spirv::Op::TypeAccelerationStructureKHR => Some(Some(1)),
+        spirv::Op::TypeArray => {
+            let count = acceleration_structure_count(index, id_operand(inst, 0)?)?;
+            let length = index.constant_u32(id_operand(inst, 1)?);
+            Some(count.and_then(|c| c.checked_mul(length?)))
+        }
+        spirv::Op::TypeRuntimeArray => {
+            acceleration_structure_count(index, id_operand(inst, 0)?)?;
+            Some(None)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use reflect;
+    use spirv;
+
+    #[test]
+    fn test_ray_generation() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::RayTracingKHR);
+        b.extension("SPV_KHR_ray_tracing");
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let float = b.type_float(32);
+        let vec3 = b.type_vector(float, 3);
+        let vec4 = b.type_vector(float, 4);
+        let accel = b.type_acceleration_structure_khr();
+        let accel_ptr = b.type_pointer(None, spirv::StorageClass::UniformConstant, accel);
+        let record = b.type_struct(vec![uint, vec4]);
+        b.member_decorate(record, 0, spirv::Decoration::Offset,
+                          vec![mr::Operand::LiteralInt32(0)]);
+        b.member_decorate(record, 1, spirv::Decoration::Offset,
+                          vec![mr::Operand::LiteralInt32(16)]);
+        let record_ptr = b.type_pointer(None, spirv::StorageClass::ShaderRecordBufferKHR, record);
+        let payload_ptr = b.type_pointer(None, spirv::StorageClass::RayPayloadKHR, vec4);
+        let callable_ptr = b.type_pointer(None, spirv::StorageClass::CallableDataKHR, uint);
+        let zero = b.constant_u32(uint, 0);
+        let fzero = b.constant_f32(float, 0.0);
+        let dir = b.constant_composite(vec3, vec![fzero, fzero, fzero]);
+
+        let tlas = b.variable(accel_ptr, None, spirv::StorageClass::UniformConstant, None);
+        b.decorate(tlas, spirv::Decoration::DescriptorSet, vec![mr::Operand::LiteralInt32(0)]);
+        b.decorate(tlas, spirv::Decoration::Binding, vec![mr::Operand::LiteralInt32(2)]);
+        let sbt = b.variable(record_ptr, None, spirv::StorageClass::ShaderRecordBufferKHR, None);
+        let payload = b.variable(payload_ptr, None, spirv::StorageClass::RayPayloadKHR, None);
+        b.decorate(payload, spirv::Decoration::Location, vec![mr::Operand::LiteralInt32(0)]);
+        let unused = b.variable(payload_ptr, None, spirv::StorageClass::RayPayloadKHR, None);
+        let callable = b.variable(callable_ptr, None, spirv::StorageClass::CallableDataKHR, None);
+
+        let voidf = b.type_function(void, vec![]);
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.begin_basic_block(None).unwrap();
+        let loaded = b.load(accel, None, tlas, None, vec![]).unwrap();
+        b.trace_ray_khr(loaded, zero, zero, zero, zero, zero,
+                        dir, fzero, dir, fzero, payload).unwrap();
+        b.execute_callable_khr(zero, callable).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::RayGenerationKHR, main, "main",
+                      vec![tlas, sbt, payload, unused, callable]);
+        let module = b.module();
+
+        let reflection = reflect::ray_tracing(&module);
+        assert_eq!(vec![reflect::RayTracingStage {
+                            function: main,
+                            name: "main".to_string(),
+                            execution_model: spirv::ExecutionModel::RayGenerationKHR,
+                        }],
+                   reflection.stages);
+        assert_eq!(vec![reflect::AccelerationStructureBinding {
+                            variable: tlas,
+                            descriptor_set: Some(0),
+                            binding: Some(2),
+                            count: Some(1),
+                        }],
+                   reflection.acceleration_structures);
+
+        assert_eq!(1, reflection.shader_record_buffers.len());
+        let layout = &reflection.shader_record_buffers[0].layout;
+        assert_eq!(sbt, reflection.shader_record_buffers[0].variable);
+        assert_eq!(record, layout.type_id);
+        assert_eq!(Some(32), layout.size);
+
+        assert_eq!(vec![reflect::RayData {
+                            variable: payload,
+                            storage_class: spirv::StorageClass::RayPayloadKHR,
+                            type_id: vec4,
+                            location: Some(0),
+                            size: Some(16),
+                            passed: true,
+                        },
+                        reflect::RayData {
+                            variable: unused,
+                            storage_class: spirv::StorageClass::RayPayloadKHR,
+                            type_id: vec4,
+                            location: None,
+                            size: Some(16),
+                            passed: false,
+                        }],
+                   reflection.ray_payloads);
+        assert!(reflection.hit_attributes.is_empty());
+        assert_eq!(1, reflection.callable_data.len());
+        assert_eq!(uint, reflection.callable_data[0].type_id);
+        assert!(reflection.callable_data[0].passed);
+    }
+
+    #[test]
+    fn test_hit_stages() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let float = b.type_float(32);
+        let vec2 = b.type_vector(float, 2);
+        let accel = b.type_acceleration_structure_khr();
+        let four = b.constant_u32(uint, 4);
+        let accels = b.type_array(accel, four);
+        let accels_ptr = b.type_pointer(None, spirv::StorageClass::UniformConstant, accels);
+        let attr_ptr = b.type_pointer(None, spirv::StorageClass::HitAttributeKHR, vec2);
+        let in_payload_ptr = b.type_pointer(None, spirv::StorageClass::IncomingRayPayloadKHR, uint);
+        let tlases = b.variable(accels_ptr, None, spirv::StorageClass::UniformConstant, None);
+        let attr = b.variable(attr_ptr, None, spirv::StorageClass::HitAttributeKHR, None);
+        let in_payload = b.variable(in_payload_ptr, None,
+                                    spirv::StorageClass::IncomingRayPayloadKHR, None);
+
+        let voidf = b.type_function(void, vec![]);
+        let chit = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let ahit = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ignore_intersection_khr().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::ClosestHitKHR, chit, "chit", vec![attr, in_payload]);
+        b.entry_point(spirv::ExecutionModel::AnyHitKHR, ahit, "ahit", vec![attr]);
+        b.entry_point(spirv::ExecutionModel::GLCompute, ahit, "compute", vec![]);
+        let module = b.module();
+
+        let reflection = reflect::ray_tracing(&module);
+        let models: Vec<spirv::ExecutionModel> =
+            reflection.stages.iter().map(|s| s.execution_model).collect();
+        assert_eq!(vec![spirv::ExecutionModel::ClosestHitKHR, spirv::ExecutionModel::AnyHitKHR],
+                   models);
+        assert_eq!(Some(4), reflection.acceleration_structures[0].count);
+        assert_eq!(tlases, reflection.acceleration_structures[0].variable);
+        assert_eq!(1, reflection.hit_attributes.len());
+        assert_eq!(Some(8), reflection.hit_attributes[0].size);
+        assert_eq!(spirv::StorageClass::IncomingRayPayloadKHR,
+                   reflection.ray_payloads[0].storage_class);
+        assert!(!reflection.ray_payloads[0].passed);
+    }
+}
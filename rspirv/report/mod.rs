@@ -0,0 +1,32 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reports over SPIR-V modules.
+//!
+//! Reports in this module summarize a
+//! [`mr::Module`](../mr/struct.Module.html) for humans, e.g., what it
+//! holds or where the bytes of its binary go. They render as plain text
+//! with `Display`, and as JSON with the `json` feature. The
+//! [`statistics`](fn.statistics.html) of a module also export as CSV, for
+//! build dashboards.
+
+pub use self::outline::{outline, EntryPointOutline, Outline};
+pub use self::shared_memory::{shared_memory, SharedMemory, SharedVariable};
+pub use self::size::{size_breakdown, FunctionSize, SizeBreakdown};
+pub use self::stats::{statistics, FunctionStatistics, Statistics};
+
+mod outline;
+mod shared_memory;
+mod size;
+mod stats;
@@ -0,0 +1,246 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grammar;
+use mr;
+use spirv;
+
+#[cfg(feature = "json")]
+use serde_json::Value;
+use spirv::Word;
+use std::collections::HashMap;
+use std::fmt;
+
+/// An entry point of a module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntryPointOutline {
+    pub execution_model: spirv::ExecutionModel,
+    pub name: String,
+    /// The result id of the function of the entry point.
+    pub function: Word,
+    /// The interface variables, with the names given them by `OpName`.
+    pub interface: Vec<(Word, Option<String>)>,
+}
+
+/// A summary of a module.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Outline {
+    /// The SPIR-V version of the header, if any.
+    pub version: Option<(u8, u8)>,
+    pub capabilities: Vec<spirv::Capability>,
+    pub extensions: Vec<String>,
+    pub entry_points: Vec<EntryPointOutline>,
+    /// The number of type declarations.
+    pub types: usize,
+    /// The number of constants and specialization constants.
+    pub constants: usize,
+    /// The number of module-scope variables.
+    pub global_variables: usize,
+    /// The number of functions, declarations included.
+    pub functions: usize,
+    /// The number of blocks in all functions.
+    pub blocks: usize,
+    /// The number of instructions in the whole module, labels included.
+    pub instructions: usize,
+}
+
+impl Outline {
+    /// Exports this outline as a JSON value.
+    ///
+    /// The value is an object with a field per field of the outline.
+    /// `version` is a string like `"1.3"`, and each entry point an object
+    /// with `execution_model`, `name`, `function`, and `interface`, an
+    /// array of objects with `id`, and `name` when known.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Value {
+        let entry_points: Vec<Value> = self.entry_points
+            .iter()
+            .map(|e| {
+                let interface: Vec<Value> = e.interface
+                    .iter()
+                    .map(|(id, name)| {
+                        let mut json = json!({ "id": id });
+                        if let Some(name) = name {
+                            json["name"] = json!(name);
+                        }
+                        json
+                    })
+                    .collect();
+                json!({
+                    "execution_model": format!("{:?}", e.execution_model),
+                    "name": e.name,
+                    "function": e.function,
+                    "interface": interface,
+                })
+            })
+            .collect();
+        let capabilities: Vec<String> =
+            self.capabilities.iter().map(|c| format!("{:?}", c)).collect();
+        json!({
+            "version": self.version.map(|(major, minor)| format!("{}.{}", major, minor)),
+            "capabilities": capabilities,
+            "extensions": self.extensions,
+            "entry_points": entry_points,
+            "types": self.types,
+            "constants": self.constants,
+            "global_variables": self.global_variables,
+            "functions": self.functions,
+            "blocks": self.blocks,
+            "instructions": self.instructions,
+        })
+    }
+}
+
+/// Renders the outline in a few lines, an entry point per line.
+impl fmt::Display for Outline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.version {
+            Some((major, minor)) => writeln!(f, "SPIR-V {}.{}", major, minor)?,
+            None => writeln!(f, "SPIR-V")?,
+        }
+        let capabilities: Vec<String> =
+            self.capabilities.iter().map(|c| format!("{:?}", c)).collect();
+        writeln!(f, "capabilities: {}", capabilities.join(", "))?;
+        writeln!(f, "extensions: {}", self.extensions.join(", "))?;
+        for e in &self.entry_points {
+            let interface: Vec<String> = e.interface
+                .iter()
+                .map(|(id, name)| match name {
+                    Some(name) => format!("%{} ({})", id, name),
+                    None => format!("%{}", id),
+                })
+                .collect();
+            writeln!(f, "entry point {:?} {:?} %{}: {}",
+                     e.execution_model, e.name, e.function, interface.join(", "))?;
+        }
+        writeln!(f, "types: {}, constants: {}, global variables: {}",
+                 self.types, self.constants, self.global_variables)?;
+        write!(f, "functions: {}, blocks: {}, instructions: {}",
+               self.functions, self.blocks, self.instructions)
+    }
+}
+
+/// Summarizes `module`: its version, capabilities, extensions, and entry
+/// points with their interfaces, and how many types, constants, variables,
+/// functions, blocks, and instructions it has.
+pub fn outline(module: &mr::Module) -> Outline {
+    let names: HashMap<Word, &str> = module.debugs
+        .iter()
+        .filter(|inst| inst.class.opcode == spirv::Op::Name)
+        .filter_map(|inst| match (inst.operands.first(), inst.operands.get(1)) {
+            (Some(&mr::Operand::IdRef(id)), Some(mr::Operand::LiteralString(name))) => {
+                Some((id, name.as_str()))
+            }
+            _ => None,
+        })
+        .collect();
+    let mut outline = Outline {
+        version: module.header.as_ref().map(|header| header.version()),
+        capabilities: module.capabilities
+            .iter()
+            .filter_map(|inst| match inst.operands.first() {
+                Some(&mr::Operand::Capability(c)) => Some(c),
+                _ => None,
+            })
+            .collect(),
+        extensions: module.extensions
+            .iter()
+            .filter_map(|inst| match inst.operands.first() {
+                Some(mr::Operand::LiteralString(name)) => Some(name.clone()),
+                _ => None,
+            })
+            .collect(),
+        functions: module.functions.len(),
+        ..Default::default()
+    };
+    for inst in &module.entry_points {
+        if let [mr::Operand::ExecutionModel(model),
+                mr::Operand::IdRef(function),
+                mr::Operand::LiteralString(ref name),
+                ref interface @ ..] = inst.operands[..] {
+            outline.entry_points.push(EntryPointOutline {
+                execution_model: model,
+                name: name.clone(),
+                function,
+                interface: interface.iter()
+                    .filter_map(mr::Operand::id)
+                    .map(|id| (id, names.get(&id).map(|&name| name.to_owned())))
+                    .collect(),
+            });
+        }
+    }
+    for inst in &module.types_global_values {
+        let opcode = inst.class.opcode;
+        if grammar::reflect::is_type(opcode) {
+            outline.types += 1;
+        } else if grammar::reflect::is_constant(opcode) {
+            outline.constants += 1;
+        } else if grammar::reflect::is_variable(opcode) {
+            outline.global_variables += 1;
+        }
+    }
+    outline.instructions = module.global_inst_iter().count();
+    for f in &module.functions {
+        outline.blocks += f.basic_blocks.len();
+        outline.instructions += f.def.iter().chain(&f.end).count() + f.parameters.len();
+        for block in &f.basic_blocks {
+            outline.instructions += block.label.iter().count() + block.instructions.len();
+        }
+    }
+    outline
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::outline;
+
+    #[test]
+    fn test_outline() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.extension("SPV_KHR_storage_buffer_storage_class");
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let ptr = b.type_pointer(None, spirv::StorageClass::Output, float);
+        let color = b.variable(ptr, None, spirv::StorageClass::Output, None);
+        b.name(color, "color");
+        let one = b.constant_f32(float, 1.0);
+        let fty = b.type_function(void, vec![]);
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.store(color, one, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::Fragment, main, "main", vec![color]);
+        let m = b.module();
+
+        let outline = outline(&m);
+        assert_eq!(outline.capabilities, vec![spirv::Capability::Shader]);
+        assert_eq!(outline.entry_points[0].interface, vec![(color, Some("color".to_owned()))]);
+        assert_eq!((outline.types, outline.constants, outline.global_variables), (4, 1, 1));
+        assert_eq!((outline.functions, outline.blocks, outline.instructions), (1, 1, 16));
+        let text = outline.to_string();
+        assert!(text.starts_with("SPIR-V 1."));
+        assert!(text.contains(&format!("entry point Fragment \"main\" %{}: %{} (color)\n",
+                                       main,
+                                       color)));
+        assert!(text.ends_with("functions: 1, blocks: 1, instructions: 16"));
+        #[cfg(feature = "json")]
+        assert_eq!(outline.to_json()["entry_points"][0]["interface"][0]["name"], "color");
+    }
+}
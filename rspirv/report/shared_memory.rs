@@ -0,0 +1,294 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use reflect;
+use spirv;
+
+#[cfg(feature = "json")]
+use serde_json::Value;
+use spirv::Word;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+/// A `Workgroup` variable used by an entry point.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SharedVariable {
+    /// The result id of the `OpVariable`.
+    pub id: Word,
+    /// The name given to the variable by `OpName`.
+    pub name: Option<String>,
+    /// The type the variable points to.
+    pub type_id: Word,
+    /// The size of the variable in bytes, if it can be computed.
+    pub size: Option<u32>,
+    /// Whether the variable is a `Block` with an explicit layout, as
+    /// allowed by `WorkgroupMemoryExplicitLayoutKHR`.
+    pub explicit_layout: bool,
+}
+
+/// The shared memory of an entry point.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SharedMemory {
+    /// The name of the entry point.
+    pub entry_point: String,
+    /// The `Workgroup` variables the entry point uses, in module order.
+    pub variables: Vec<SharedVariable>,
+}
+
+impl SharedMemory {
+    /// Returns the shared memory in bytes, if the sizes of all variables
+    /// are known.
+    ///
+    /// Variables with an explicit layout alias each other, and take up as
+    /// much memory as the largest of them.
+    pub fn total(&self) -> Option<u32> {
+        let mut implicit = 0;
+        let mut explicit = 0;
+        for v in &self.variables {
+            let size = v.size?;
+            if v.explicit_layout {
+                explicit = explicit.max(size);
+            } else {
+                implicit += size;
+            }
+        }
+        Some(implicit + explicit)
+    }
+
+    /// Exports this report as a JSON value.
+    ///
+    /// The value is an object with `entry_point`, `total`, and
+    /// `variables`, an array of objects with `id`, `type_id`, `size`, and
+    /// `explicit_layout`, and `name` when known. Unknown sizes are `null`.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Value {
+        let variables: Vec<Value> = self.variables
+            .iter()
+            .map(|v| {
+                let mut json = json!({
+                    "id": v.id,
+                    "type_id": v.type_id,
+                    "size": v.size,
+                    "explicit_layout": v.explicit_layout,
+                });
+                if let Some(ref name) = v.name {
+                    json["name"] = json!(name);
+                }
+                json
+            })
+            .collect();
+        json!({
+            "entry_point": self.entry_point,
+            "variables": variables,
+            "total": self.total(),
+        })
+    }
+}
+
+/// Renders the report as a table of variables and their sizes.
+impl fmt::Display for SharedMemory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "shared memory of {:?}", self.entry_point)?;
+        let rows: Vec<(String, Option<u32>)> = self.variables
+            .iter()
+            .map(|v| {
+                let label = match v.name {
+                    Some(ref name) => format!("%{} ({})", v.id, name),
+                    None => format!("%{}", v.id),
+                };
+                let label = if v.explicit_layout {
+                    label + ", explicit layout"
+                } else {
+                    label
+                };
+                (label, v.size)
+            })
+            .chain(Some(("total".to_string(), self.total())))
+            .collect();
+        let width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+        for (i, (label, size)) in rows.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            match size {
+                Some(size) => write!(f, "{:<width$}  {:>8} bytes", label, size, width = width)?,
+                None => write!(f, "{:<width$}  {:>8}", label, "unknown", width = width)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reports the `Workgroup` variables used by the entry point named
+/// `entry_point` and their sizes, or returns `None` if there is no such
+/// entry point.
+///
+/// Variables used are those listed in the interface of the entry point,
+/// and those referred to by the functions it calls, directly or not.
+/// Variables of `Block` structs are sized by their layout decorations, and
+/// others by the `std430` rule, which implementations may pad differently.
+pub fn shared_memory(module: &mr::Module, entry_point: &str) -> Option<SharedMemory> {
+    let (function, interface) = module.entry_points.iter().find_map(|inst| {
+        match inst.operands[..] {
+            [_,
+             mr::Operand::IdRef(function),
+             mr::Operand::LiteralString(ref name),
+             ref interface @ ..] if name == entry_point => Some((function, interface)),
+            _ => None,
+        }
+    })?;
+    let functions: HashMap<Word, &mr::Function> = module.functions
+        .iter()
+        .filter_map(|f| f.def.as_ref()?.result_id.map(|id| (id, f)))
+        .collect();
+
+    let mut used: BTreeSet<Word> = interface.iter().filter_map(mr::Operand::id).collect();
+    let mut visited = BTreeSet::new();
+    let mut stack = vec![function];
+    while let Some(id) = stack.pop() {
+        if !visited.insert(id) {
+            continue;
+        }
+        let f = match functions.get(&id) {
+            Some(f) => f,
+            None => continue,
+        };
+        for inst in f.basic_blocks.iter().flat_map(|b| &b.instructions) {
+            let ids = inst.operands.iter().filter_map(mr::Operand::id);
+            if inst.class.opcode == spirv::Op::FunctionCall {
+                stack.extend(inst.operands.first().and_then(mr::Operand::id));
+            }
+            used.extend(ids);
+        }
+    }
+
+    let names: HashMap<Word, &str> = module.debugs
+        .iter()
+        .filter(|inst| inst.class.opcode == spirv::Op::Name)
+        .filter_map(|inst| match (inst.operands.first(), inst.operands.get(1)) {
+            (Some(&mr::Operand::IdRef(id)), Some(mr::Operand::LiteralString(name))) => {
+                Some((id, name.as_str()))
+            }
+            _ => None,
+        })
+        .collect();
+    let is_block = |ty: Word| {
+        module.annotations.iter().any(|inst| {
+            inst.class.opcode == spirv::Op::Decorate &&
+            inst.operands.first() == Some(&mr::Operand::IdRef(ty)) &&
+            inst.operands.get(1) == Some(&mr::Operand::Decoration(spirv::Decoration::Block))
+        })
+    };
+    let pointees: HashMap<Word, Word> = module.types_global_values
+        .iter()
+        .filter(|inst| inst.class.opcode == spirv::Op::TypePointer)
+        .filter_map(|inst| Some((inst.result_id?, inst.operands.get(1)?.id()?)))
+        .collect();
+
+    let workgroup = mr::Operand::StorageClass(spirv::StorageClass::Workgroup);
+    let variables = module.types_global_values
+        .iter()
+        .filter(|inst| {
+            inst.class.opcode == spirv::Op::Variable && inst.operands.first() == Some(&workgroup)
+        })
+        .filter_map(|inst| {
+            let id = inst.result_id.filter(|id| used.contains(id))?;
+            let type_id = *pointees.get(&inst.result_type?)?;
+            let explicit_layout = is_block(type_id);
+            let size = if explicit_layout {
+                reflect::type_size(module, type_id)
+            } else {
+                mr::LayoutRule::Std430.size_and_align(module, type_id).map(|(size, _)| size)
+            };
+            Some(SharedVariable {
+                id,
+                name: names.get(&id).map(|&name| name.to_owned()),
+                type_id,
+                size,
+                explicit_layout,
+            })
+        })
+        .collect();
+    Some(SharedMemory {
+        entry_point: entry_point.to_owned(),
+        variables,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::shared_memory;
+
+    #[test]
+    fn test_shared_memory() {
+        let mut b = mr::Builder::new();
+        b.capability(spirv::Capability::Shader);
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let vec3 = b.type_vector(uint, 3);
+        let sixteen = b.constant_u32(uint, 16);
+        let tile = b.type_array(vec3, sixteen);
+        let tile_ptr = b.type_pointer(None, spirv::StorageClass::Workgroup, tile);
+        let counter_ptr = b.type_pointer(None, spirv::StorageClass::Workgroup, uint);
+        let tiles = b.variable(tile_ptr, None, spirv::StorageClass::Workgroup, None);
+        b.name(tiles, "tiles");
+        let counter = b.variable(counter_ptr, None, spirv::StorageClass::Workgroup, None);
+        let unused = b.variable(counter_ptr, None, spirv::StorageClass::Workgroup, None);
+        let block = b.type_struct(vec![uint, uint]);
+        b.decorate(block, spirv::Decoration::Block, vec![]);
+        for member in 0..2 {
+            let offset = vec![mr::Operand::LiteralInt32(member * 8)];
+            b.member_decorate(block, member, spirv::Decoration::Offset, offset);
+        }
+        let block_ptr = b.type_pointer(None, spirv::StorageClass::Workgroup, block);
+        let explicit = b.variable(block_ptr, None, spirv::StorageClass::Workgroup, None);
+        let zero = b.constant_u32(uint, 0);
+        let fty = b.type_function(void, vec![]);
+
+        let helper = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.store(counter, zero, None, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.function_call(void, None, helper, vec![]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::GLCompute, main, "main", vec![tiles, explicit]);
+        let m = b.module();
+
+        let report = shared_memory(&m, "main").unwrap();
+        let ids: Vec<_> = report.variables
+            .iter()
+            .map(|v| (v.id, v.size, v.explicit_layout))
+            .collect();
+        // vec3 is aligned to 16 bytes, and so are the elements of `tiles`.
+        assert_eq!(ids,
+                   vec![(tiles, Some(256), false),
+                        (counter, Some(4), false),
+                        (explicit, Some(12), true)]);
+        assert!(!report.variables.iter().any(|v| v.id == unused));
+        assert_eq!(report.total(), Some(272));
+        let text = report.to_string();
+        assert!(text.contains(&format!("%{} (tiles)", tiles)));
+        assert!(text.ends_with("272 bytes"));
+        assert!(shared_memory(&m, "other").is_none());
+    }
+}
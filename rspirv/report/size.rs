@@ -0,0 +1,288 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grammar;
+use mr;
+use spirv;
+
+use binary::Assemble;
+#[cfg(feature = "json")]
+use serde_json::Value;
+use spirv::Word;
+use std::fmt;
+
+/// The size of one function of a module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FunctionSize {
+    /// The result id of the `OpFunction`.
+    pub id: Option<Word>,
+    /// The name given to the function by `OpName`.
+    pub name: Option<String>,
+    /// The bytes of the function, debug info excluded.
+    pub bytes: usize,
+}
+
+/// The bytes of a module binary, attributed to categories.
+///
+/// Every byte of the binary is attributed to exactly one category, so the
+/// categories add up to [`total`](#method.total).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SizeBreakdown {
+    /// `OpString` instructions.
+    pub strings: usize,
+    /// Other debug instructions: sources, names, and module processes;
+    /// `OpLine` and `OpNoLine` wherever they appear; and
+    /// `NonSemantic.Shader.DebugInfo.100` extended instructions and their
+    /// import.
+    pub debug_info: usize,
+    /// Annotation instructions.
+    pub decorations: usize,
+    /// Types, constants, and global variables.
+    pub types_constants: usize,
+    /// Each function, in module order.
+    pub functions: Vec<FunctionSize>,
+    /// The header, capabilities, extensions, other imports, the memory
+    /// model, entry points, and execution modes.
+    pub other: usize,
+}
+
+impl SizeBreakdown {
+    /// Returns the size of the whole binary in bytes.
+    pub fn total(&self) -> usize {
+        self.strings + self.debug_info + self.decorations + self.types_constants +
+        self.functions.iter().map(|f| f.bytes).sum::<usize>() + self.other
+    }
+
+    /// Returns the categories as rows of labels and bytes, functions last.
+    fn rows(&self) -> Vec<(String, usize)> {
+        let mut rows = vec![("strings".to_string(), self.strings),
+                            ("debug info".to_string(), self.debug_info),
+                            ("decorations".to_string(), self.decorations),
+                            ("types and constants".to_string(), self.types_constants),
+                            ("other".to_string(), self.other)];
+        for f in &self.functions {
+            let label = match (f.id, f.name.as_ref()) {
+                (Some(id), Some(name)) => format!("function %{} ({})", id, name),
+                (Some(id), None) => format!("function %{}", id),
+                (None, _) => "function".to_string(),
+            };
+            rows.push((label, f.bytes));
+        }
+        rows
+    }
+
+    /// Exports this breakdown as a JSON value.
+    ///
+    /// The value is an object with a field per category, in bytes, plus
+    /// `total`. `functions` is an array of objects with `bytes`, and `id`
+    /// and `name` when known.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Value {
+        let functions: Vec<Value> = self.functions
+            .iter()
+            .map(|f| {
+                let mut json = json!({ "bytes": f.bytes });
+                if let Some(id) = f.id {
+                    json["id"] = json!(id);
+                }
+                if let Some(ref name) = f.name {
+                    json["name"] = json!(name);
+                }
+                json
+            })
+            .collect();
+        json!({
+            "strings": self.strings,
+            "debug_info": self.debug_info,
+            "decorations": self.decorations,
+            "types_constants": self.types_constants,
+            "functions": functions,
+            "other": self.other,
+            "total": self.total(),
+        })
+    }
+}
+
+/// Renders the breakdown as a table of bytes and shares of the total.
+impl fmt::Display for SizeBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let total = self.total();
+        let share = |bytes: usize| if total == 0 {
+            0.0
+        } else {
+            bytes as f64 * 100.0 / total as f64
+        };
+        let rows = self.rows();
+        let width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+        for (label, bytes) in rows {
+            writeln!(f, "{:<width$}  {:>8} bytes  {:>5.1}%", label, bytes, share(bytes),
+                     width = width)?;
+        }
+        write!(f, "{:<width$}  {:>8} bytes", "total", total, width = width)
+    }
+}
+
+/// Returns the bytes of `inst` in the binary.
+fn size(inst: &mr::Instruction) -> usize {
+    inst.assemble().len() * 4
+}
+
+/// Attributes the bytes of the binary of `module` to categories.
+///
+/// # Examples
+///
+/// ```
+/// extern crate rspirv;
+/// extern crate spirv_headers as spirv;
+///
+/// use rspirv::binary::Assemble;
+/// use rspirv::report::size_breakdown;
+///
+/// fn main() {
+///     let mut b = rspirv::mr::Builder::new();
+///     b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+///     b.string("shader.glsl");
+///     let module = b.module();
+///
+///     let sizes = size_breakdown(&module);
+///     assert_eq!(sizes.strings, 20);
+///     assert_eq!(sizes.total(), module.assemble().len() * 4);
+/// }
+/// ```
+pub fn size_breakdown(module: &mr::Module) -> SizeBreakdown {
+    let debug_info_set = mr::ExtInstRegistry::of(module).id_of(grammar::ExtInstSet::DebugInfo100);
+    let is_debug_info = |inst: &mr::Instruction| {
+        grammar::reflect::is_location_debug(inst.class.opcode) ||
+        (debug_info_set.is_some() && inst.class.opcode == spirv::Op::ExtInst &&
+         inst.operands.first().and_then(mr::Operand::id) == debug_info_set)
+    };
+
+    let mut sizes = SizeBreakdown {
+        other: module.header.as_ref().map_or(0, |h| h.assemble().len() * 4),
+        ..Default::default()
+    };
+
+    for inst in module.capabilities
+        .iter()
+        .chain(&module.extensions)
+        .chain(&module.memory_model)
+        .chain(&module.entry_points)
+        .chain(&module.execution_modes) {
+        sizes.other += size(inst);
+    }
+    for inst in &module.ext_inst_imports {
+        if debug_info_set.is_some() && inst.result_id == debug_info_set {
+            sizes.debug_info += size(inst);
+        } else {
+            sizes.other += size(inst);
+        }
+    }
+    for inst in &module.debugs {
+        if inst.class.opcode == spirv::Op::String {
+            sizes.strings += size(inst);
+        } else {
+            sizes.debug_info += size(inst);
+        }
+    }
+    sizes.decorations = module.annotations.iter().map(size).sum();
+    for inst in &module.types_global_values {
+        if is_debug_info(inst) {
+            sizes.debug_info += size(inst);
+        } else {
+            sizes.types_constants += size(inst);
+        }
+    }
+
+    for f in &module.functions {
+        let id = f.def.as_ref().and_then(|d| d.result_id);
+        let mut bytes = 0;
+        for inst in f.def.iter().chain(&f.parameters).chain(&f.end) {
+            bytes += size(inst);
+        }
+        for inst in f.basic_blocks.iter().flat_map(|bb| bb.label.iter().chain(&bb.instructions)) {
+            if is_debug_info(inst) {
+                sizes.debug_info += size(inst);
+            } else {
+                bytes += size(inst);
+            }
+        }
+        let name = id.and_then(|id| {
+            module.debugs.iter().find_map(|inst| match (inst.class.opcode, &inst.operands[..]) {
+                (spirv::Op::Name, &[mr::Operand::IdRef(target), mr::Operand::LiteralString(ref name)])
+                    if target == id => Some(name.clone()),
+                _ => None,
+            })
+        });
+        sizes.functions.push(FunctionSize { id, name, bytes });
+    }
+    sizes
+}
+
+#[cfg(test)]
+mod tests {
+    use binary::Assemble;
+    use mr;
+    use spirv;
+
+    use super::size_breakdown;
+
+    fn build() -> mr::Module {
+        let mut b = mr::Builder::new();
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        let file = b.string("a.comp");
+        let void = b.type_void();
+        b.decorate(void, spirv::Decoration::RelaxedPrecision, vec![]);
+        let voidfvoid = b.type_function(void, vec![]);
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        b.name(f, "main");
+        b.begin_basic_block(None).unwrap();
+        b.line(file, 1, 1);
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.module()
+    }
+
+    #[test]
+    fn test_size_breakdown() {
+        let m = build();
+        let sizes = size_breakdown(&m);
+        assert_eq!(sizes.total(), m.assemble().len() * 4);
+        // OpString "a.comp": 1 + 1 + 2 words.
+        assert_eq!(sizes.strings, 16);
+        // OpName "main": 1 + 1 + 2 words, OpLine: 4 words.
+        assert_eq!(sizes.debug_info, 32);
+        assert_eq!(sizes.decorations, 12);
+        // OpTypeVoid and OpTypeFunction.
+        assert_eq!(sizes.types_constants, 20);
+        // The header and OpMemoryModel.
+        assert_eq!(sizes.other, 32);
+        assert_eq!(sizes.functions.len(), 1);
+        assert_eq!(sizes.functions[0].name, Some("main".to_string()));
+        // OpFunction, OpLabel, OpReturn, and OpFunctionEnd.
+        assert_eq!(sizes.functions[0].bytes, 36);
+
+        let text = sizes.to_string();
+        assert!(text.contains("function %4 (main)"));
+        assert!(text.lines().last().unwrap().starts_with("total"));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_to_json() {
+        let json = size_breakdown(&build()).to_json();
+        assert_eq!(json["strings"], 16);
+        assert_eq!(json["functions"][0]["name"], "main");
+        assert_eq!(json["total"], json!(json["other"].as_u64().unwrap() + 116));
+    }
+}
@@ -0,0 +1,415 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use analysis;
+use mr;
+use spirv;
+
+#[cfg(feature = "json")]
+use serde_json::Value;
+use spirv::Word;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+/// The statistics of one function of a module.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FunctionStatistics {
+    /// The result id of the `OpFunction`.
+    pub id: Option<Word>,
+    /// The name given to the function by `OpName`.
+    pub name: Option<String>,
+    /// The number of blocks.
+    pub blocks: usize,
+    /// The number of instructions in the blocks, labels excluded.
+    pub instructions: usize,
+    /// The number of instructions in the blocks per opcode name, e.g.,
+    /// `"Load"`.
+    pub instruction_mix: BTreeMap<&'static str, usize>,
+    /// The number of loops, i.e., of `OpLoopMerge` instructions.
+    pub loops: usize,
+    /// The largest number of structured constructs a block is nested in.
+    pub max_nesting: usize,
+    /// The number of conditional branches and switches.
+    pub branches: usize,
+    /// The number of conditional branches and switches whose condition or
+    /// selector can differ between invocations.
+    pub divergent_branches: usize,
+}
+
+/// Per-function statistics of a module, for tracking shaders over time.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Statistics {
+    /// Each function, in module order.
+    pub functions: Vec<FunctionStatistics>,
+}
+
+/// The columns of the CSV export before the instruction mix.
+const CSV_COLUMNS: [&str; 8] = ["id", "name", "blocks", "instructions", "loops", "max_nesting",
+                                "branches", "divergent_branches"];
+
+impl Statistics {
+    /// Exports these statistics as a JSON value.
+    ///
+    /// The value is an object with `functions`, an array of objects with a
+    /// field per field of the function statistics, `id` and `name` only
+    /// when known. `instruction_mix` is an object from opcode names to
+    /// counts.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Value {
+        let functions: Vec<Value> = self.functions
+            .iter()
+            .map(|f| {
+                let mut json = json!({
+                    "blocks": f.blocks,
+                    "instructions": f.instructions,
+                    "instruction_mix": f.instruction_mix,
+                    "loops": f.loops,
+                    "max_nesting": f.max_nesting,
+                    "branches": f.branches,
+                    "divergent_branches": f.divergent_branches,
+                });
+                if let Some(id) = f.id {
+                    json["id"] = json!(id);
+                }
+                if let Some(ref name) = f.name {
+                    json["name"] = json!(name);
+                }
+                json
+            })
+            .collect();
+        json!({ "functions": functions })
+    }
+
+    /// Exports these statistics as CSV, a row per function.
+    ///
+    /// The header row names the columns: `id`, `name`, `blocks`,
+    /// `instructions`, `loops`, `max_nesting`, `branches`, and
+    /// `divergent_branches`, then an `Op*` column per opcode used by any
+    /// function, in alphabetical order. Unknown ids and names are empty.
+    pub fn to_csv(&self) -> String {
+        let opcodes: BTreeSet<&str> = self.functions
+            .iter()
+            .flat_map(|f| f.instruction_mix.keys().cloned())
+            .collect();
+        let mut header: Vec<String> = CSV_COLUMNS.iter().map(|c| c.to_string()).collect();
+        header.extend(opcodes.iter().map(|opname| format!("Op{}", opname)));
+        let mut csv = header.join(",");
+        csv.push('\n');
+        for f in &self.functions {
+            let mut row = vec![f.id.map_or(String::new(), |id| id.to_string()),
+                               f.name.as_ref().map_or(String::new(), |name| csv_field(name)),
+                               f.blocks.to_string(),
+                               f.instructions.to_string(),
+                               f.loops.to_string(),
+                               f.max_nesting.to_string(),
+                               f.branches.to_string(),
+                               f.divergent_branches.to_string()];
+            row.extend(opcodes.iter().map(|opname| {
+                f.instruction_mix.get(opname).cloned().unwrap_or(0).to_string()
+            }));
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// Quotes `field` for CSV if it holds separators, quotes, or line breaks.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Renders the statistics as a few lines per function.
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, function) in self.functions.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            match (function.id, function.name.as_ref()) {
+                (Some(id), Some(name)) => writeln!(f, "function %{} ({})", id, name)?,
+                (Some(id), None) => writeln!(f, "function %{}", id)?,
+                (None, _) => writeln!(f, "function")?,
+            }
+            writeln!(f, "blocks: {}, instructions: {}, loops: {}, max nesting: {}",
+                     function.blocks, function.instructions, function.loops,
+                     function.max_nesting)?;
+            writeln!(f, "branches: {}, divergent: {}",
+                     function.branches, function.divergent_branches)?;
+            let mix: Vec<String> = function.instruction_mix
+                .iter()
+                .map(|(opname, count)| format!("{} {}", opname, count))
+                .collect();
+            write!(f, "mix: {}", mix.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects per-function statistics of `module`: the instruction mix, the
+/// loops, how deeply structured control flow nests, and how many branches
+/// can diverge.
+///
+/// Divergence is estimated from data dependencies alone. Values are
+/// divergent if they are loaded from `Input` variables, other than
+/// built-ins uniform across a draw or a workgroup, or if they are function
+/// parameters, function call and atomic results, or computed from
+/// divergent values, including through variables divergent values are
+/// stored to.
+pub fn statistics(module: &mr::Module) -> Statistics {
+    let names: HashMap<Word, &str> = module.debugs
+        .iter()
+        .filter(|inst| inst.class.opcode == spirv::Op::Name)
+        .filter_map(|inst| match (inst.operands.first(), inst.operands.get(1)) {
+            (Some(&mr::Operand::IdRef(id)), Some(mr::Operand::LiteralString(name))) => {
+                Some((id, name.as_str()))
+            }
+            _ => None,
+        })
+        .collect();
+    let inputs = divergent_inputs(module);
+
+    let functions = module.functions
+        .iter()
+        .map(|f| {
+            let id = f.def.as_ref().and_then(|d| d.result_id);
+            let mut stats = FunctionStatistics {
+                id,
+                name: id.and_then(|id| names.get(&id)).map(|&name| name.to_owned()),
+                blocks: f.basic_blocks.len(),
+                max_nesting: max_nesting(f),
+                ..Default::default()
+            };
+            let divergent = divergent_values(f, &inputs);
+            for block in &f.basic_blocks {
+                for inst in &block.instructions {
+                    stats.instructions += 1;
+                    *stats.instruction_mix.entry(inst.class.opname).or_insert(0) += 1;
+                    match inst.class.opcode {
+                        spirv::Op::LoopMerge => stats.loops += 1,
+                        spirv::Op::BranchConditional | spirv::Op::Switch => {
+                            stats.branches += 1;
+                            if inst.operands.first().and_then(mr::Operand::id)
+                                .is_some_and(|id| divergent.contains(&id)) {
+                                stats.divergent_branches += 1;
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            stats
+        })
+        .collect();
+    Statistics { functions }
+}
+
+/// Returns the largest number of structured constructs a block of
+/// `function` is nested in.
+///
+/// A construct holds the blocks its header dominates, but its merge block
+/// does not.
+fn max_nesting(function: &mr::Function) -> usize {
+    let dominators = analysis::Dominators::new(function);
+    let constructs: Vec<(Word, Word)> = function.basic_blocks
+        .iter()
+        .filter_map(|block| {
+            let header = block.label.as_ref()?.result_id?;
+            let merge = block.instructions.iter().find(|inst| {
+                matches!(inst.class.opcode, spirv::Op::SelectionMerge | spirv::Op::LoopMerge)
+            })?;
+            Some((header, merge.operands.first().and_then(mr::Operand::id)?))
+        })
+        .collect();
+    function.basic_blocks
+        .iter()
+        .filter_map(|block| block.label.as_ref()?.result_id)
+        .filter(|&label| dominators.is_reachable(label))
+        .map(|label| {
+            constructs
+                .iter()
+                .filter(|&&(header, merge)| {
+                    dominators.dominates(header, label) && !dominators.dominates(merge, label)
+                })
+                .count()
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Returns the `Input` variables of `module` whose values can differ
+/// between invocations.
+fn divergent_inputs(module: &mr::Module) -> BTreeSet<Word> {
+    let uniform: BTreeSet<Word> = module.annotations
+        .iter()
+        .filter(|inst| inst.class.opcode == spirv::Op::Decorate)
+        .filter_map(|inst| match inst.operands[..] {
+            [mr::Operand::IdRef(target),
+             mr::Operand::Decoration(spirv::Decoration::BuiltIn),
+             mr::Operand::BuiltIn(spirv::BuiltIn::NumWorkgroups |
+                                  spirv::BuiltIn::WorkgroupSize |
+                                  spirv::BuiltIn::WorkgroupId |
+                                  spirv::BuiltIn::SubgroupSize |
+                                  spirv::BuiltIn::NumSubgroups |
+                                  spirv::BuiltIn::BaseVertex |
+                                  spirv::BuiltIn::BaseInstance |
+                                  spirv::BuiltIn::DrawIndex)] => Some(target),
+            _ => None,
+        })
+        .collect();
+    module.types_global_values
+        .iter()
+        .filter(|inst| inst.class.opcode == spirv::Op::Variable)
+        .filter(|inst| {
+            inst.operands.first() == Some(&mr::Operand::StorageClass(spirv::StorageClass::Input))
+        })
+        .filter_map(|inst| inst.result_id)
+        .filter(|id| !uniform.contains(id))
+        .collect()
+}
+
+/// Returns the values of `function` that can differ between invocations,
+/// given the divergent `inputs` of the module.
+fn divergent_values(function: &mr::Function, inputs: &BTreeSet<Word>) -> BTreeSet<Word> {
+    let mut divergent = inputs.clone();
+    divergent.extend(function.parameters.iter().filter_map(|p| p.result_id));
+    // The variables access chains point into.
+    let mut bases: HashMap<Word, Word> = HashMap::new();
+    let instructions = || function.basic_blocks.iter().flat_map(|b| b.instructions.iter());
+    for inst in instructions() {
+        if matches!(inst.class.opcode,
+                    spirv::Op::AccessChain |
+                    spirv::Op::InBoundsAccessChain |
+                    spirv::Op::PtrAccessChain |
+                    spirv::Op::InBoundsPtrAccessChain) {
+            if let (Some(id), Some(base)) = (inst.result_id,
+                                             inst.operands.first().and_then(mr::Operand::id)) {
+                let base = bases.get(&base).cloned().unwrap_or(base);
+                bases.insert(id, base);
+            }
+        }
+    }
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for inst in instructions() {
+            let uses_divergent = inst.operands
+                .iter()
+                .filter_map(mr::Operand::id)
+                .any(|id| divergent.contains(&id));
+            if inst.class.opcode == spirv::Op::Store {
+                if let Some(pointer) = inst.operands.first().and_then(mr::Operand::id) {
+                    let base = bases.get(&pointer).cloned().unwrap_or(pointer);
+                    if uses_divergent && !divergent.contains(&base) {
+                        divergent.insert(base);
+                        changed = true;
+                    }
+                }
+            } else if let Some(id) = inst.result_id {
+                let source = inst.class.opcode == spirv::Op::FunctionCall ||
+                    inst.class.opname.starts_with("Atomic");
+                if (source || uses_divergent) && divergent.insert(id) {
+                    changed = true;
+                }
+            }
+        }
+    }
+    divergent
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::statistics;
+
+    fn build() -> mr::Module {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let uint = b.type_int(32, 0);
+        let boolean = b.type_bool();
+        let input = b.type_pointer(None, spirv::StorageClass::Input, uint);
+        let index = b.variable(input, None, spirv::StorageClass::Input, None);
+        let group = b.variable(input, None, spirv::StorageClass::Input, None);
+        b.decorate(group, spirv::Decoration::BuiltIn,
+                   vec![mr::Operand::BuiltIn(spirv::BuiltIn::WorkgroupId)]);
+        let zero = b.constant_u32(uint, 0);
+        let voidfvoid = b.type_function(void, vec![]);
+        let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidfvoid).unwrap();
+        b.name(f, "main, or \"entry\"");
+        let labels: Vec<spirv::Word> = (0..5).map(|_| b.id()).collect();
+        b.begin_basic_block(None).unwrap();
+        let i = b.load(uint, None, index, None, vec![]).unwrap();
+        let g = b.load(uint, None, group, None, vec![]).unwrap();
+        b.branch(labels[0]).unwrap();
+        // A loop with a divergent exit, around an if on a uniform condition.
+        b.begin_basic_block(Some(labels[0])).unwrap();
+        b.loop_merge(labels[4], labels[3], spirv::LoopControl::NONE, vec![]).unwrap();
+        let exit = b.iequal(boolean, None, i, zero).unwrap();
+        b.branch_conditional(exit, labels[4], labels[1], vec![]).unwrap();
+        b.begin_basic_block(Some(labels[1])).unwrap();
+        let skip = b.iequal(boolean, None, g, zero).unwrap();
+        b.selection_merge(labels[3], spirv::SelectionControl::NONE).unwrap();
+        b.branch_conditional(skip, labels[3], labels[2], vec![]).unwrap();
+        b.begin_basic_block(Some(labels[2])).unwrap();
+        b.branch(labels[3]).unwrap();
+        b.begin_basic_block(Some(labels[3])).unwrap();
+        b.branch(labels[0]).unwrap();
+        b.begin_basic_block(Some(labels[4])).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.module()
+    }
+
+    #[test]
+    fn test_statistics() {
+        let stats = statistics(&build());
+        assert_eq!(stats.functions.len(), 1);
+        let main = &stats.functions[0];
+        assert_eq!(main.name, Some("main, or \"entry\"".to_string()));
+        assert_eq!((main.blocks, main.instructions, main.loops), (6, 12, 1));
+        assert_eq!(main.instruction_mix["Branch"], 3);
+        assert_eq!(main.instruction_mix["IEqual"], 2);
+        // The body of the if is nested in both the loop and the if.
+        assert_eq!(main.max_nesting, 2);
+        assert_eq!((main.branches, main.divergent_branches), (2, 1));
+
+        let csv = stats.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next().unwrap(),
+                   "id,name,blocks,instructions,loops,max_nesting,branches,divergent_branches,\
+                    OpBranch,OpBranchConditional,OpIEqual,OpLoad,OpLoopMerge,OpReturn,\
+                    OpSelectionMerge");
+        assert_eq!(lines.next().unwrap(),
+                   format!("{},\"main, or \"\"entry\"\"\",6,12,1,2,2,1,3,2,2,2,1,1,1",
+                           main.id.unwrap()));
+        assert_eq!(lines.next(), None);
+        assert!(stats.to_string().contains("branches: 2, divergent: 1"));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_to_json() {
+        let json = statistics(&build()).to_json();
+        assert_eq!(json["functions"][0]["loops"], 1);
+        assert_eq!(json["functions"][0]["instruction_mix"]["LoopMerge"], 1);
+        assert_eq!(json["functions"][0]["divergent_branches"], 1);
+    }
+}
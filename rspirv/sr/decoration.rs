@@ -71,4 +71,11 @@ pub enum Decoration {
     PassthroughNV,
     ViewportRelativeNV,
     SecondaryViewportRelativeNV(u32),
+    PerPrimitiveEXT,
+    NonUniform,
+    RestrictPointer,
+    AliasedPointer,
+    HlslCounterBufferGOOGLE(spirv::Word),
+    HlslSemanticGOOGLE(String),
+    UserTypeGOOGLE(String),
 }
@@ -0,0 +1,224 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use spirv;
+
+use super::{Context, Type, TypeToken};
+use sr::types::TypeEnum;
+
+/// The operands of an image type.
+///
+/// `depth`, `arrayed`, `ms`, and `sampled` are the literals of the
+/// `OpTypeImage` operands: for `depth`, 0 for no depth image, 1 for a depth
+/// image, and 2 for unknown; for `sampled`, 0 for known only at run time, 1
+/// for use with a sampler, and 2 for a storage image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Image {
+    /// The type of the components of texels.
+    pub sampled_type: TypeToken,
+    pub dim: spirv::Dim,
+    pub depth: u32,
+    pub arrayed: u32,
+    pub ms: u32,
+    pub sampled: u32,
+    pub image_format: spirv::ImageFormat,
+    pub access_qualifier: Option<spirv::AccessQualifier>,
+}
+
+impl Image {
+    /// Returns a single-sampled, non-arrayed image of the given dimension
+    /// for use with a sampler, without depth and of unknown format.
+    pub fn sampled(sampled_type: TypeToken, dim: spirv::Dim) -> Image {
+        Image {
+            sampled_type,
+            dim,
+            depth: 0,
+            arrayed: 0,
+            ms: 0,
+            sampled: 1,
+            image_format: spirv::ImageFormat::Unknown,
+            access_qualifier: None,
+        }
+    }
+
+    /// Returns a single-sampled, non-arrayed storage image of the given
+    /// dimension and format.
+    pub fn storage(sampled_type: TypeToken,
+                   dim: spirv::Dim,
+                   image_format: spirv::ImageFormat)
+                   -> Image {
+        Image {
+            sampled: 2,
+            image_format,
+            ..Image::sampled(sampled_type, dim)
+        }
+    }
+
+    /// Returns true if this is declared a depth image.
+    pub fn is_depth(&self) -> bool {
+        self.depth == 1
+    }
+
+    /// Returns true if this is a storage image, whose format must be known
+    /// unless the module declares the `StorageImageReadWithoutFormat` and
+    /// `StorageImageWriteWithoutFormat` capabilities to read and write it.
+    ///
+    /// Subpass data is read as an input attachment, and needs no format.
+    pub fn needs_format(&self) -> bool {
+        self.sampled == 2 && self.dim != spirv::Dim::DimSubpassData
+    }
+
+    /// Returns true if the image instruction `opcode` may access an image
+    /// of this type, or a sampled image of it for sampling instructions.
+    ///
+    /// Sampling needs a single-sampled image for use with a sampler, other
+    /// than a buffer or subpass data, and gathers a 2D, cube, or
+    /// rectangle image; depth comparisons need a depth image or one of
+    /// unknown depth. Fetches need an image for use with a sampler, other
+    /// than a cube or subpass data, reads and writes a storage image, and
+    /// queries an image with the dimensions, levels, or samples they return.
+    /// Instructions other than image instructions are never compatible.
+    pub fn compatible_with(&self, opcode: spirv::Op) -> bool {
+        use spirv::Dim::*;
+        use spirv::Op::*;
+        let with_sampler = self.sampled == 0 || self.sampled == 1;
+        let storage = self.sampled == 0 || self.sampled == 2;
+        let samplable = with_sampler && self.ms == 0 &&
+                        self.dim != DimBuffer && self.dim != DimSubpassData;
+        let comparable = samplable && self.depth != 0;
+        let gatherable = matches!(self.dim, Dim2D | DimCube | DimRect);
+        match opcode {
+            ImageSampleImplicitLod |
+            ImageSampleExplicitLod |
+            ImageSampleProjImplicitLod |
+            ImageSampleProjExplicitLod |
+            ImageSparseSampleImplicitLod |
+            ImageSparseSampleExplicitLod |
+            ImageSparseSampleProjImplicitLod |
+            ImageSparseSampleProjExplicitLod => samplable,
+            ImageSampleDrefImplicitLod |
+            ImageSampleDrefExplicitLod |
+            ImageSampleProjDrefImplicitLod |
+            ImageSampleProjDrefExplicitLod |
+            ImageSparseSampleDrefImplicitLod |
+            ImageSparseSampleDrefExplicitLod |
+            ImageSparseSampleProjDrefImplicitLod |
+            ImageSparseSampleProjDrefExplicitLod => comparable,
+            ImageGather | ImageSparseGather => samplable && gatherable,
+            ImageDrefGather | ImageSparseDrefGather => comparable && gatherable,
+            ImageFetch | ImageSparseFetch => {
+                with_sampler && self.dim != DimCube && self.dim != DimSubpassData
+            }
+            ImageRead | ImageSparseRead => storage,
+            ImageWrite => storage && self.dim != DimSubpassData,
+            ImageQuerySizeLod | ImageQueryLevels => {
+                with_sampler && self.ms == 0 && matches!(self.dim, Dim1D | Dim2D | Dim3D | DimCube)
+            }
+            ImageQuerySize => {
+                self.dim != DimSubpassData &&
+                (self.dim == DimBuffer || self.ms == 1 || self.sampled != 1)
+            }
+            ImageQueryLod => samplable && matches!(self.dim, Dim1D | Dim2D | Dim3D | DimCube),
+            ImageQuerySamples => self.ms == 1 && self.dim == Dim2D,
+            _ => false,
+        }
+    }
+}
+
+impl Type {
+    /// Returns the operands of this type if it is an image type.
+    pub fn image(&self) -> Option<Image> {
+        match self.ty {
+            TypeEnum::Image { sampled_type,
+                              dim,
+                              depth,
+                              arrayed,
+                              ms,
+                              sampled,
+                              image_format,
+                              access_qualifier } => {
+                Some(Image {
+                    sampled_type,
+                    dim,
+                    depth,
+                    arrayed,
+                    ms,
+                    sampled,
+                    image_format,
+                    access_qualifier,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Context {
+    /// Returns the token of the image type with the given operands.
+    pub fn type_image_of(&mut self, image: &Image) -> TypeToken {
+        self.type_image(image.sampled_type,
+                        image.dim,
+                        image.depth,
+                        image.arrayed,
+                        image.ms,
+                        image.sampled,
+                        image.image_format,
+                        image.access_qualifier)
+    }
+
+    /// Returns the operands of the image type `token`, or of the image
+    /// type of the sampled image type `token`.
+    pub fn image_of(&self, token: TypeToken) -> Option<Image> {
+        let t = self.get_type(token);
+        match t.ty {
+            TypeEnum::SampledImage { image_type } => self.get_type(image_type).image(),
+            _ => t.image(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spirv;
+
+    use sr::{Context, Image};
+
+    #[test]
+    fn test_image() {
+        let mut c = Context::new();
+        let float = c.type_float(32);
+        let shadow = Image {
+            depth: 1,
+            ..Image::sampled(float, spirv::Dim::Dim2D)
+        };
+        let image = c.type_image_of(&shadow);
+        let sampled = c.type_sampled_image(image);
+        assert_eq!(c.image_of(sampled), Some(shadow));
+        assert!(shadow.is_depth() && !shadow.needs_format());
+        assert!(shadow.compatible_with(spirv::Op::ImageSampleDrefImplicitLod));
+        assert!(shadow.compatible_with(spirv::Op::ImageDrefGather));
+        assert!(!shadow.compatible_with(spirv::Op::ImageWrite));
+        assert!(!shadow.compatible_with(spirv::Op::IAdd));
+
+        let storage = Image::storage(float, spirv::Dim::Dim3D, spirv::ImageFormat::Rgba32f);
+        let image = c.type_image_of(&storage);
+        assert_eq!(c.get_type(image).image(), Some(storage));
+        assert!(storage.needs_format());
+        assert!(storage.compatible_with(spirv::Op::ImageWrite));
+        assert!(storage.compatible_with(spirv::Op::ImageQuerySize));
+        assert!(!storage.compatible_with(spirv::Op::ImageSampleImplicitLod));
+        assert!(!storage.compatible_with(spirv::Op::ImageSampleDrefImplicitLod));
+        assert!(c.image_of(float).is_none());
+    }
+}
@@ -17,9 +17,11 @@
 pub use self::constants::{Constant, ConstantToken};
 pub use self::context::Context;
 pub use self::decoration::Decoration;
+pub use self::image::Image;
 pub use self::types::{Type, TypeToken};
 
 mod constants;
 mod context;
 mod decoration;
+mod image;
 mod types;
@@ -20,8 +20,13 @@
 pub(in sr) enum TypeEnum {
     Void,
     Bool,
-    Int { width: u32, signedness: u32 },
-    Float { width: u32 },
+    Int {
+        width: u32,
+        signedness: u32,
+    },
+    Float {
+        width: u32,
+    },
     Vector {
         component_type: TypeToken,
         component_count: u32,
@@ -41,14 +46,22 @@ pub(in sr) enum TypeEnum {
         access_qualifier: Option<spirv::AccessQualifier>,
     },
     Sampler,
-    SampledImage { image_type: TypeToken },
+    SampledImage {
+        image_type: TypeToken,
+    },
     Array {
         element_type: TypeToken,
         length: ConstantToken,
     },
-    RuntimeArray { element_type: TypeToken },
-    Struct { field_types: Vec<TypeToken> },
-    Opaque { type_name: String },
+    RuntimeArray {
+        element_type: TypeToken,
+    },
+    Struct {
+        field_types: Vec<TypeToken>,
+    },
+    Opaque {
+        type_name: String,
+    },
     Pointer {
         storage_class: spirv::StorageClass,
         pointee_type: TypeToken,
@@ -61,10 +74,22 @@ pub(in sr) enum TypeEnum {
     DeviceEvent,
     ReserveId,
     Queue,
-    Pipe { qualifier: spirv::AccessQualifier },
-    ForwardPointer { storage_class: spirv::StorageClass },
+    Pipe {
+        qualifier: spirv::AccessQualifier,
+    },
+    ForwardPointer {
+        storage_class: spirv::StorageClass,
+    },
     PipeStorage,
     NamedBarrier,
+    CooperativeMatrixKHR {
+        component_type: TypeToken,
+        scope: spirv::Word,
+        rows: TypeToken,
+        columns: TypeToken,
+        usage: TypeToken,
+    },
+    AccelerationStructureKHR,
 }
 impl Type {
     pub fn is_void_type(&self) -> bool {
@@ -205,4 +230,16 @@ impl Type {
             _ => false,
         }
     }
+    pub fn is_cooperative_matrix_khr_type(&self) -> bool {
+        match self.ty {
+            TypeEnum::CooperativeMatrixKHR { .. } => true,
+            _ => false,
+        }
+    }
+    pub fn is_acceleration_structure_khr_type(&self) -> bool {
+        match self.ty {
+            TypeEnum::AccelerationStructureKHR => true,
+            _ => false,
+        }
+    }
 }
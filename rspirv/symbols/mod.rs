@@ -0,0 +1,24 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Symbols of SPIR-V modules.
+//!
+//! A [`SymbolTable`](struct.SymbolTable.html) relates the names a
+//! [`mr::Module`](../mr/struct.Module.html) gives with `OpName`,
+//! `OpMemberName`, `OpEntryPoint`, and linkage decorations to the ids they
+//! name, and renames them consistently, as refactoring tools do.
+
+pub use self::table::{Symbol, SymbolKind, SymbolTable};
+
+mod table;
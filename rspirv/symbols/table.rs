@@ -0,0 +1,271 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grammar::reflect;
+use mr;
+use spirv;
+
+use spirv::Word;
+use std::collections::HashMap;
+
+/// What a symbol names.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolKind {
+    /// A function, named by `OpName` or a linkage decoration.
+    Function,
+    /// An entry point, named by `OpEntryPoint`.
+    EntryPoint,
+    /// A module-scope or function-local variable.
+    Variable,
+    /// A type.
+    Type,
+    /// A member of a struct type, named by `OpMemberName`.
+    Member(u32),
+    /// A constant or specialization constant.
+    Constant,
+    /// Any other id, e.g., a function parameter or a label.
+    Value,
+}
+
+/// A name given to an id.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    /// The id named, or the struct type of a member or function of an
+    /// entry point.
+    pub id: Word,
+    pub kind: SymbolKind,
+}
+
+/// The symbols of a module, in the order the names are given.
+///
+/// Names need not be unique: ids may share names, and an id may have
+/// several, e.g., as a function and an entry point.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolTable {
+    symbols: Vec<Symbol>,
+}
+
+impl SymbolTable {
+    /// Collects the symbols of `module`.
+    ///
+    /// Symbols come from `OpName` and `OpMemberName`, the names of
+    /// `OpEntryPoint`s, and the names of `LinkageAttributes` decorations
+    /// not naming the same id as an `OpName` of the same text.
+    pub fn new(module: &mr::Module) -> SymbolTable {
+        let mut kinds = HashMap::new();
+        for inst in &module.types_global_values {
+            if let Some(id) = inst.result_id {
+                kinds.insert(id, global_kind(inst.class.opcode));
+            }
+        }
+        for f in &module.functions {
+            if let Some(id) = f.def.as_ref().and_then(|def| def.result_id) {
+                kinds.insert(id, SymbolKind::Function);
+            }
+            let variables = f.basic_blocks
+                .iter()
+                .flat_map(|b| b.instructions.iter())
+                .filter(|inst| inst.class.opcode == spirv::Op::Variable);
+            for id in variables.filter_map(|inst| inst.result_id) {
+                kinds.insert(id, SymbolKind::Variable);
+            }
+        }
+        let kind = |id| kinds.get(&id).cloned().unwrap_or(SymbolKind::Value);
+
+        let mut symbols: Vec<Symbol> = vec![];
+        for inst in &module.debugs {
+            match (inst.class.opcode, &inst.operands[..]) {
+                (spirv::Op::Name,
+                 &[mr::Operand::IdRef(id), mr::Operand::LiteralString(ref name)]) => {
+                    symbols.push(Symbol { name: name.clone(), id, kind: kind(id) });
+                }
+                (spirv::Op::MemberName,
+                 &[mr::Operand::IdRef(id),
+                   mr::Operand::LiteralInt32(member),
+                   mr::Operand::LiteralString(ref name)]) => {
+                    let kind = SymbolKind::Member(member);
+                    symbols.push(Symbol { name: name.clone(), id, kind });
+                }
+                _ => (),
+            }
+        }
+        for inst in &module.entry_points {
+            if let (Some(&mr::Operand::IdRef(id)), Some(mr::Operand::LiteralString(name))) =
+                   (inst.operands.get(1), inst.operands.get(2))
+            {
+                symbols.push(Symbol { name: name.clone(), id, kind: SymbolKind::EntryPoint });
+            }
+        }
+        for inst in &module.annotations {
+            if let Some((id, name)) = linkage_name(inst) {
+                let named = symbols.iter().any(|s| s.id == id && s.name == *name);
+                if !named {
+                    symbols.push(Symbol { name: name.clone(), id, kind: kind(id) });
+                }
+            }
+        }
+        SymbolTable { symbols }
+    }
+
+    /// Returns all symbols.
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Returns the symbols of the given `name`.
+    pub fn lookup<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Symbol> + 'a {
+        self.symbols.iter().filter(move |s| s.name == name)
+    }
+
+    /// Returns the first id named `name`, if any.
+    pub fn id_of(&self, name: &str) -> Option<Word> {
+        self.lookup(name).next().map(|s| s.id)
+    }
+
+    /// Returns the first name of the id `id`, not counting member names.
+    pub fn name_of(&self, id: Word) -> Option<&str> {
+        self.symbols
+            .iter()
+            .find(|s| s.id == id && !matches!(s.kind, SymbolKind::Member(_)))
+            .map(|s| s.name.as_str())
+    }
+
+    /// Returns the name of member `member` of the struct type `id`, if any.
+    pub fn member_name(&self, id: Word, member: u32) -> Option<&str> {
+        self.symbols
+            .iter()
+            .find(|s| s.id == id && s.kind == SymbolKind::Member(member))
+            .map(|s| s.name.as_str())
+    }
+
+    /// Renames all symbols named `old` to `new`, both in this table and in
+    /// `module`, and returns the number of names changed.
+    ///
+    /// `OpName`, `OpMemberName`, `OpEntryPoint`, and `LinkageAttributes`
+    /// names are changed alike, so that a function keeps matching its entry
+    /// points and linkage. Colliding with existing names of `new` is not
+    /// checked, since SPIR-V allows it.
+    pub fn rename(&mut self, module: &mut mr::Module, old: &str, new: &str) -> usize {
+        let rename = |operand: Option<&mut mr::Operand>| match operand {
+            Some(mr::Operand::LiteralString(name)) if name == old => {
+                *name = new.to_owned();
+                1
+            }
+            _ => 0,
+        };
+        let mut touched = vec![];
+        let mut renamed = 0;
+        for inst in &mut module.debugs {
+            renamed += match inst.class.opcode {
+                spirv::Op::Name => rename(inst.operands.get_mut(1)),
+                spirv::Op::MemberName => rename(inst.operands.get_mut(2)),
+                _ => 0,
+            };
+        }
+        touched.push((mr::Section::Debugs, renamed));
+        let mut renamed = 0;
+        for inst in &mut module.entry_points {
+            renamed += rename(inst.operands.get_mut(2));
+        }
+        touched.push((mr::Section::EntryPoints, renamed));
+        let mut renamed = 0;
+        for inst in &mut module.annotations {
+            if linkage_name(inst).is_some() {
+                renamed += rename(inst.operands.get_mut(2));
+            }
+        }
+        touched.push((mr::Section::Annotations, renamed));
+        let mut count = 0;
+        for (section, renamed) in touched {
+            if renamed > 0 {
+                module.touch(section);
+            }
+            count += renamed;
+        }
+        for symbol in self.symbols.iter_mut().filter(|s| s.name == old) {
+            symbol.name = new.to_owned();
+        }
+        count
+    }
+}
+
+fn global_kind(opcode: spirv::Op) -> SymbolKind {
+    if reflect::is_variable(opcode) {
+        SymbolKind::Variable
+    } else if reflect::is_constant(opcode) {
+        SymbolKind::Constant
+    } else if reflect::is_type(opcode) {
+        SymbolKind::Type
+    } else {
+        SymbolKind::Value
+    }
+}
+
+/// Returns the id and name of the `LinkageAttributes` decoration `inst`.
+fn linkage_name(inst: &mr::Instruction) -> Option<(Word, &String)> {
+    match (inst.class.opcode, &inst.operands[..]) {
+        (spirv::Op::Decorate,
+         &[mr::Operand::IdRef(id),
+           mr::Operand::Decoration(spirv::Decoration::LinkageAttributes),
+           mr::Operand::LiteralString(ref name),
+           ..]) => Some((id, name)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{SymbolKind, SymbolTable};
+
+    #[test]
+    fn test_symbol_table() {
+        let mut b = mr::Builder::new();
+        let void = b.type_void();
+        let float = b.type_float(32);
+        let light = b.type_struct(vec![float, float]);
+        let ptr = b.type_pointer(None, spirv::StorageClass::Private, light);
+        let sun = b.variable(ptr, None, spirv::StorageClass::Private, None);
+        let fty = b.type_function(void, vec![]);
+        let main = b.begin_function(void, None, spirv::FunctionControl::NONE, fty).unwrap();
+        b.begin_basic_block(None).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+        b.entry_point(spirv::ExecutionModel::Fragment, main, "shade", vec![]);
+        b.name(main, "shade");
+        b.name(light, "Light");
+        b.member_name(light, 1, "intensity");
+        b.name(sun, "sun");
+        let mut m = b.module();
+
+        let mut table = SymbolTable::new(&m);
+        assert_eq!(table.symbols().len(), 5);
+        assert_eq!(table.id_of("Light"), Some(light));
+        assert_eq!(table.name_of(sun), Some("sun"));
+        assert_eq!(table.member_name(light, 1), Some("intensity"));
+        let kinds: Vec<_> = table.lookup("shade").map(|s| s.kind).collect();
+        assert_eq!(kinds, vec![SymbolKind::Function, SymbolKind::EntryPoint]);
+        assert_eq!(table.lookup("sun").next().unwrap().kind, SymbolKind::Variable);
+        assert_eq!(table.lookup("Light").next().unwrap().kind, SymbolKind::Type);
+
+        assert_eq!(table.rename(&mut m, "shade", "main"), 2);
+        assert_eq!(m.entry_points[0].operands[2], mr::Operand::from("main"));
+        assert_eq!(table.lookup("main").count(), 2);
+        assert_eq!(table.id_of("shade"), None);
+        assert_eq!(SymbolTable::new(&m).symbols(), table.symbols());
+    }
+}
@@ -0,0 +1,497 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mr;
+use spirv;
+
+use std::mem;
+
+type Word = spirv::Word;
+
+/// Features and sizes of the modules generated by
+/// [`gen_module`](fn.gen_module.html).
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Whether to generate structured loops.
+    pub loops: bool,
+    /// Whether to generate if-else selection constructs.
+    pub branches: bool,
+    /// Whether to generate vector and struct values.
+    pub composites: bool,
+    /// Whether to generate specialization constants.
+    pub spec_constants: bool,
+    /// The maximum nesting depth of loops and selection constructs.
+    pub max_depth: u32,
+    /// The maximum number of statements in a straight-line region.
+    pub max_statements: u32,
+    /// The number of words the module writes to its output buffer.
+    pub outputs: u32,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            loops: true,
+            branches: true,
+            composites: true,
+            spec_constants: true,
+            max_depth: 3,
+            max_statements: 8,
+            outputs: 8,
+        }
+    }
+}
+
+/// A small xorshift generator, so that modules only depend on the seed.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        // Avoid the all-zero state, which xorshift never leaves.
+        Rng(seed ^ 0x9e37_79b9_7f4a_7c15)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a number in `0..n`.
+    fn below(&mut self, n: u32) -> u32 {
+        (self.next() % u64::from(n)) as u32
+    }
+
+    fn chance(&mut self, percent: u32) -> bool {
+        self.below(100) < percent
+    }
+
+    fn pick(&mut self, values: &[Word]) -> Word {
+        values[self.below(values.len() as u32) as usize]
+    }
+}
+
+struct Types {
+    uint: Word,
+    float: Word,
+    boolean: Word,
+    uvec2: Word,
+    pair: Word,
+    uint_function: Word,
+    uint_uniform: Word,
+}
+
+/// Values dominating the current position, by type.
+#[derive(Clone, Default)]
+struct Values {
+    uints: Vec<Word>,
+    floats: Vec<Word>,
+    bools: Vec<Word>,
+    uvec2s: Vec<Word>,
+    pairs: Vec<Word>,
+}
+
+struct Generator<'c> {
+    b: mr::Builder,
+    rng: Rng,
+    config: &'c Config,
+    t: Types,
+    /// Function-local variables holding unsigned integers.
+    vars: Vec<Word>,
+    constants: Vec<Word>,
+}
+
+/// Generates a valid module from the given `seed`.
+///
+/// The module has a single `GLCompute` entry point named `main`, which
+/// computes on unsigned integers, floats, and booleans, and writes
+/// `config.outputs` words to the runtime array in the only member of the
+/// uniform buffer block at descriptor set 0 and binding 0. All values are
+/// defined before use and all loops terminate, so the words written only
+/// depend on the module and its specialization constants. They are what
+/// the [oracle](fn.check_pass.html) compares.
+///
+/// The same `seed` and `config` always give the same module.
+pub fn gen_module(seed: u64, config: &Config) -> mr::Module {
+    let mut b = mr::Builder::new();
+    b.capability(spirv::Capability::Shader);
+    b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+
+    let uint = b.type_int(32, 0);
+    let float = b.type_float(32);
+    let boolean = b.type_bool();
+    let uvec2 = b.type_vector(uint, 2);
+    let pair = b.type_struct(vec![uint, float]);
+    let uint_function = b.type_pointer(None, spirv::StorageClass::Function, uint);
+    let uint_uniform = b.type_pointer(None, spirv::StorageClass::Uniform, uint);
+    let t = Types {
+        uint,
+        float,
+        boolean,
+        uvec2,
+        pair,
+        uint_function,
+        uint_uniform,
+    };
+
+    let mut g = Generator {
+        b,
+        rng: Rng::new(seed),
+        config,
+        t,
+        vars: vec![],
+        constants: vec![],
+    };
+    g.generate();
+    let mut module = g.b.module();
+    hoist_variables(&mut module);
+    module
+}
+
+impl<'c> Generator<'c> {
+    fn constant(&mut self, value: u32) -> Word {
+        while self.constants.len() <= value as usize {
+            let v = self.constants.len() as u32;
+            let id = self.b.constant_u32(self.t.uint, v);
+            self.constants.push(id);
+        }
+        self.constants[value as usize]
+    }
+
+    fn generate(&mut self) {
+        let t = &self.t;
+        let array = self.b.type_runtime_array(t.uint);
+        let block = self.b.type_struct(vec![array]);
+        let block_uniform = self.b.type_pointer(None, spirv::StorageClass::Uniform, block);
+        self.b.decorate(array, spirv::Decoration::ArrayStride, vec![mr::Operand::LiteralInt32(4)]);
+        self.b.decorate(block, spirv::Decoration::BufferBlock, vec![]);
+        self.b.member_decorate(block, 0, spirv::Decoration::Offset, vec![mr::Operand::LiteralInt32(0)]);
+        let output = self.b.variable(block_uniform, None, spirv::StorageClass::Uniform, None);
+        self.b.decorate(output, spirv::Decoration::DescriptorSet, vec![mr::Operand::LiteralInt32(0)]);
+        self.b.decorate(output, spirv::Decoration::Binding, vec![mr::Operand::LiteralInt32(0)]);
+
+        let mut values = self.globals();
+
+        let void = self.b.type_void();
+        let voidf = self.b.type_function(void, vec![]);
+        let f = self.b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+        self.b.entry_point(spirv::ExecutionModel::GLCompute, f, "main", vec![]);
+        self.b.execution_mode(f, spirv::ExecutionMode::LocalSize, vec![1, 1, 1]);
+        self.b.begin_basic_block(None).unwrap();
+
+        for _ in 0..1 + self.rng.below(3) {
+            let init = self.rng.pick(&values.uints);
+            let var = self.b.variable(self.t.uint_function, None, spirv::StorageClass::Function, Some(init));
+            self.vars.push(var);
+        }
+
+        self.region(&mut values, 0);
+
+        for k in 0..self.config.outputs {
+            let value = self.output_value(&values);
+            let (zero, index) = (self.constant(0), self.constant(k));
+            let pointer = self.b.access_chain(self.t.uint_uniform, None, output, vec![zero, index]).unwrap();
+            self.b.store(pointer, value, None, vec![]).unwrap();
+        }
+        self.b.ret().unwrap();
+        self.b.end_function().unwrap();
+    }
+
+    /// Generates the module-level constants.
+    fn globals(&mut self) -> Values {
+        let t = &self.t;
+        let mut values = Values::default();
+        for _ in 0..2 + self.rng.below(3) {
+            let v = self.rng.next() as u32 >> self.rng.below(32);
+            values.uints.push(self.b.constant_u32(t.uint, v));
+        }
+        for _ in 0..1 + self.rng.below(3) {
+            let v = (self.rng.below(2000) as f32 - 1000.0) / 8.0;
+            values.floats.push(self.b.constant_f32(t.float, v));
+        }
+        values.bools.push(self.b.constant_true(t.boolean));
+        values.bools.push(self.b.constant_false(t.boolean));
+
+        if self.config.spec_constants {
+            for spec_id in 0..1 + self.rng.below(3) {
+                let id = match self.rng.below(3) {
+                    0 => {
+                        let v = self.rng.below(1000);
+                        let id = self.b.spec_constant_u32(t.uint, v);
+                        values.uints.push(id);
+                        id
+                    }
+                    1 => {
+                        let v = self.rng.below(100) as f32 / 4.0;
+                        let id = self.b.spec_constant_f32(t.float, v);
+                        values.floats.push(id);
+                        id
+                    }
+                    _ => {
+                        let id = if self.rng.chance(50) {
+                            self.b.spec_constant_true(t.boolean)
+                        } else {
+                            self.b.spec_constant_false(t.boolean)
+                        };
+                        values.bools.push(id);
+                        id
+                    }
+                };
+                self.b.decorate(id, spirv::Decoration::SpecId, vec![mr::Operand::LiteralInt32(spec_id)]);
+            }
+        }
+
+        if self.config.composites {
+            let (x, y) = (self.rng.pick(&values.uints), self.rng.pick(&values.uints));
+            values.uvec2s.push(self.b.constant_composite(t.uvec2, vec![x, y]));
+            let (x, y) = (self.rng.pick(&values.uints), self.rng.pick(&values.floats));
+            values.pairs.push(self.b.constant_composite(t.pair, vec![x, y]));
+        }
+        values
+    }
+
+    /// Generates a sequence of statements into the current block, which may
+    /// end up in a different block if control flow is generated.
+    fn region(&mut self, values: &mut Values, depth: u32) {
+        for _ in 0..self.rng.below(self.config.max_statements + 1) {
+            let nested = depth < self.config.max_depth;
+            match self.rng.below(10) {
+                0 if nested && self.config.branches => self.selection(values, depth),
+                1 if nested && self.config.loops => self.repetition(values, depth),
+                2 | 3 if self.config.composites => self.composite(values),
+                4 => {
+                    let var = self.rng.pick(&self.vars);
+                    let value = self.rng.pick(&values.uints);
+                    self.b.store(var, value, None, vec![]).unwrap();
+                }
+                5 => {
+                    let var = self.rng.pick(&self.vars);
+                    let value = self.b.load(self.t.uint, None, var, None, vec![]).unwrap();
+                    values.uints.push(value);
+                }
+                _ => self.arithmetic(values),
+            }
+        }
+    }
+
+    fn arithmetic(&mut self, values: &mut Values) {
+        let t = &self.t;
+        let b = &mut self.b;
+        let (a, c) = (self.rng.pick(&values.uints), self.rng.pick(&values.uints));
+        let (x, y) = (self.rng.pick(&values.floats), self.rng.pick(&values.floats));
+        let (p, q) = (self.rng.pick(&values.bools), self.rng.pick(&values.bools));
+        match self.rng.below(16) {
+            0 => values.uints.push(b.iadd(t.uint, None, a, c).unwrap()),
+            1 => values.uints.push(b.isub(t.uint, None, a, c).unwrap()),
+            2 => values.uints.push(b.imul(t.uint, None, a, c).unwrap()),
+            3 => values.uints.push(b.bitwise_and(t.uint, None, a, c).unwrap()),
+            4 => values.uints.push(b.bitwise_or(t.uint, None, a, c).unwrap()),
+            5 => values.uints.push(b.bitwise_xor(t.uint, None, a, c).unwrap()),
+            6 => values.uints.push(b.select(t.uint, None, p, a, c).unwrap()),
+            7 => values.uints.push(b.bitcast(t.uint, None, x).unwrap()),
+            8 => values.floats.push(b.fadd(t.float, None, x, y).unwrap()),
+            9 => values.floats.push(b.fsub(t.float, None, x, y).unwrap()),
+            10 => values.floats.push(b.fmul(t.float, None, x, y).unwrap()),
+            11 => values.bools.push(b.uless_than(t.boolean, None, a, c).unwrap()),
+            12 => values.bools.push(b.iequal(t.boolean, None, a, c).unwrap()),
+            13 => values.bools.push(b.logical_and(t.boolean, None, p, q).unwrap()),
+            _ => values.bools.push(b.logical_not(t.boolean, None, p).unwrap()),
+        }
+    }
+
+    fn composite(&mut self, values: &mut Values) {
+        let t = &self.t;
+        let b = &mut self.b;
+        let (a, c) = (self.rng.pick(&values.uints), self.rng.pick(&values.uints));
+        let x = self.rng.pick(&values.floats);
+        match self.rng.below(5) {
+            0 => values.uvec2s.push(b.composite_construct(t.uvec2, None, vec![a, c]).unwrap()),
+            1 => values.pairs.push(b.composite_construct(t.pair, None, vec![a, x]).unwrap()),
+            2 => {
+                let v = self.rng.pick(&values.uvec2s);
+                let index = self.rng.below(2);
+                values.uints.push(b.composite_extract(t.uint, None, v, vec![index]).unwrap());
+            }
+            3 => {
+                let v = self.rng.pick(&values.pairs);
+                if self.rng.chance(50) {
+                    values.uints.push(b.composite_extract(t.uint, None, v, vec![0]).unwrap());
+                } else {
+                    values.floats.push(b.composite_extract(t.float, None, v, vec![1]).unwrap());
+                }
+            }
+            _ => {
+                let v = self.rng.pick(&values.uvec2s);
+                let index = self.rng.below(2);
+                values.uvec2s.push(b.composite_insert(t.uvec2, None, a, v, vec![index]).unwrap());
+            }
+        }
+    }
+
+    /// Generates an if-else construct.
+    fn selection(&mut self, values: &mut Values, depth: u32) {
+        let cond = self.rng.pick(&values.bools);
+        let (then_block, else_block, merge) = (self.b.id(), self.b.id(), self.b.id());
+        self.b.selection_merge(merge, spirv::SelectionControl::NONE).unwrap();
+        self.b.branch_conditional(cond, then_block, else_block, vec![]).unwrap();
+        for &block in &[then_block, else_block] {
+            // Values defined in the arms do not dominate the merge block.
+            let mut arm = values.clone();
+            self.b.begin_basic_block(Some(block)).unwrap();
+            self.region(&mut arm, depth + 1);
+            self.b.branch(merge).unwrap();
+        }
+        self.b.begin_basic_block(Some(merge)).unwrap();
+    }
+
+    /// Generates a loop running a small constant number of iterations.
+    fn repetition(&mut self, values: &mut Values, depth: u32) {
+        let t = &self.t;
+        let counter = self.b.variable(t.uint_function, None, spirv::StorageClass::Function, None);
+        let (zero, one) = (self.constant(0), self.constant(1));
+        let trips = self.rng.below(5);
+        let trips = self.constant(trips);
+        self.b.store(counter, zero, None, vec![]).unwrap();
+
+        let (header, body, cont, merge) = (self.b.id(), self.b.id(), self.b.id(), self.b.id());
+        self.b.branch(header).unwrap();
+        self.b.begin_basic_block(Some(header)).unwrap();
+        let i = self.b.load(self.t.uint, None, counter, None, vec![]).unwrap();
+        let cond = self.b.uless_than(self.t.boolean, None, i, trips).unwrap();
+        self.b.loop_merge(merge, cont, spirv::LoopControl::NONE, vec![]).unwrap();
+        self.b.branch_conditional(cond, body, merge, vec![]).unwrap();
+
+        // Neither can the body's values be used after the loop.
+        let mut inner = values.clone();
+        inner.uints.push(i);
+        self.b.begin_basic_block(Some(body)).unwrap();
+        self.region(&mut inner, depth + 1);
+        self.b.branch(cont).unwrap();
+
+        self.b.begin_basic_block(Some(cont)).unwrap();
+        let i = self.b.load(self.t.uint, None, counter, None, vec![]).unwrap();
+        let next = self.b.iadd(self.t.uint, None, i, one).unwrap();
+        self.b.store(counter, next, None, vec![]).unwrap();
+        self.b.branch(header).unwrap();
+        self.b.begin_basic_block(Some(merge)).unwrap();
+    }
+
+    /// Returns an unsigned integer summarizing some of the current values.
+    fn output_value(&mut self, values: &Values) -> Word {
+        let t = &self.t;
+        match self.rng.below(4) {
+            0 => {
+                let var = self.rng.pick(&self.vars);
+                self.b.load(t.uint, None, var, None, vec![]).unwrap()
+            }
+            1 => {
+                let x = self.rng.pick(&values.floats);
+                self.b.bitcast(t.uint, None, x).unwrap()
+            }
+            2 => {
+                let p = self.rng.pick(&values.bools);
+                let (zero, one) = (self.constant(0), self.constant(1));
+                self.b.select(self.t.uint, None, p, one, zero).unwrap()
+            }
+            _ => self.rng.pick(&values.uints),
+        }
+    }
+}
+
+/// Moves the function-local variables into the entry block of each function,
+/// where SPIR-V requires them to be.
+fn hoist_variables(module: &mut mr::Module) {
+    for f in &mut module.functions {
+        let mut vars = vec![];
+        for block in &mut f.basic_blocks {
+            let (found, rest) = mem::take(&mut block.instructions)
+                .into_iter()
+                .partition(|inst| inst.class.opcode == spirv::Op::Variable);
+            let found: Vec<_> = found;
+            vars.extend(found);
+            block.instructions = rest;
+        }
+        if let Some(entry) = f.basic_blocks.first_mut() {
+            vars.append(&mut entry.instructions);
+            entry.instructions = vars;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spirv;
+
+    use binary::{Assemble, Disassemble};
+    use mr;
+    use super::{gen_module, Config};
+
+    #[test]
+    fn test_deterministic() {
+        let config = Config::default();
+        for seed in 0..8 {
+            assert_eq!(gen_module(seed, &config).disassemble(),
+                       gen_module(seed, &config).disassemble());
+        }
+        assert_ne!(gen_module(1, &config).disassemble(),
+                   gen_module(2, &config).disassemble());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let config = Config::default();
+        for seed in 0..64 {
+            let module = gen_module(seed, &config);
+            let loaded = mr::load_words(module.assemble()).unwrap();
+            assert_eq!(loaded.disassemble(), module.disassemble());
+        }
+    }
+
+    #[test]
+    fn test_variables_in_entry_block() {
+        let module = gen_module(3, &Config::default());
+        let blocks = &module.functions[0].basic_blocks;
+        let leading = blocks[0]
+            .instructions
+            .iter()
+            .take_while(|inst| inst.class.opcode == spirv::Op::Variable)
+            .count();
+        let total = blocks
+            .iter()
+            .flat_map(|block| &block.instructions)
+            .filter(|inst| inst.class.opcode == spirv::Op::Variable)
+            .count();
+        assert!(leading > 0);
+        assert_eq!(leading, total);
+    }
+
+    #[test]
+    fn test_features_disabled() {
+        let config = Config {
+            loops: false,
+            branches: false,
+            composites: false,
+            spec_constants: false,
+            ..Config::default()
+        };
+        for seed in 0..16 {
+            let module = gen_module(seed, &config);
+            assert_eq!(module.functions[0].basic_blocks.len(), 1);
+            assert!(module.annotations.iter().all(|inst| {
+                inst.operands[1] != mr::Operand::Decoration(spirv::Decoration::SpecId)
+            }));
+        }
+    }
+}
@@ -0,0 +1,204 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use binary;
+use mr;
+use passes;
+
+use binary::Assemble;
+
+/// The number of unchanged lines shown around each change of a diff.
+const CONTEXT: usize = 2;
+
+/// Asserts that a module equals the module assembled from SPIR-V text, up
+/// to the numbering of ids, printing an instruction-level diff otherwise.
+///
+/// See [`module_diff`](testing/fn.module_diff.html) for how the modules are
+/// compared.
+///
+/// # Examples
+///
+/// ```
+/// extern crate rspirv;
+/// extern crate spirv_headers as spirv;
+///
+/// fn main() {
+///     let mut b = rspirv::mr::Builder::new();
+///     b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+///     let float = b.type_float(32);
+///     b.constant_f32(float, 1.0);
+///
+///     rspirv::testing::assert_module_eq!(b.module(), "
+///         OpMemoryModel Logical GLSL450
+///         %float = OpTypeFloat 32
+///         %one = OpConstant %float 1
+///     ");
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_module_eq {
+    ($actual:expr, $expected:expr $(,)*) => {
+        if let Some(diff) = $crate::testing::module_diff(&$actual, $expected) {
+            panic!("modules differ (- expected, + actual):\n{}", diff);
+        }
+    };
+}
+
+/// Compares `actual` with the module assembled from the SPIR-V text
+/// `expected`, and returns an instruction-level diff if they differ.
+///
+/// Ids are compacted in both modules before comparing, so that modules
+/// only numbering their ids differently are equal; the text can thus name
+/// its ids. The module headers are not compared.
+///
+/// Each line of the diff is an instruction, disassembled, prefixed by `-`
+/// if only in the expected module, `+` if only in `actual`, and spaces
+/// otherwise. Only instructions near changes are shown, runs of others are
+/// elided as `...`.
+///
+/// # Panics
+///
+/// Panics if `expected` cannot be assembled, or either module cannot be
+/// loaded from its binary.
+pub fn module_diff(actual: &mr::Module, expected: &str) -> Option<String> {
+    let words = binary::assemble_text(expected)
+        .unwrap_or_else(|err| panic!("cannot assemble the expected module: {}", err));
+    let expected = mr::load_words(words)
+        .unwrap_or_else(|err| panic!("cannot load the expected module: {}", err));
+    // Modules cannot be cloned, but round-trip through their binaries.
+    let mut words = match actual.header {
+        Some(_) => vec![],
+        None => mr::ModuleHeader::new(0).assemble(),
+    };
+    words.extend(actual.assemble());
+    let actual = mr::load_words(words)
+        .unwrap_or_else(|err| panic!("cannot load the actual module: {}", err));
+    let expected = lines(expected);
+    let actual = lines(actual);
+    if expected == actual {
+        None
+    } else {
+        Some(diff(&expected, &actual))
+    }
+}
+
+/// Returns the disassembled instructions of `module`, ids compacted.
+fn lines(mut module: mr::Module) -> Vec<String> {
+    passes::compact_ids(&mut module);
+    module.header = None;
+    binary::disassemble_with(&module, &binary::DisasOptions::default())
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with(';'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Renders the difference between the lines `expected` and `actual`, along
+/// their longest common subsequence.
+fn diff(expected: &[String], actual: &[String]) -> String {
+    // The length of the longest common subsequence of the suffixes.
+    let mut lcs = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lcs[i][j] = if expected[i] == actual[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes: Vec<(char, &str)> = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            changes.push((' ', &expected[i]));
+            i += 1;
+            j += 1;
+        } else if i < expected.len() && (j == actual.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            changes.push(('-', &expected[i]));
+            i += 1;
+        } else {
+            changes.push(('+', &actual[j]));
+            j += 1;
+        }
+    }
+
+    let near_change = |index: usize| {
+        let start = index.saturating_sub(CONTEXT);
+        let end = (index + CONTEXT + 1).min(changes.len());
+        changes[start..end].iter().any(|&(sign, _)| sign != ' ')
+    };
+    let mut text = String::new();
+    let mut elided = false;
+    for (index, &(sign, line)) in changes.iter().enumerate() {
+        if near_change(index) {
+            text.push_str(&format!("{} {}\n", sign, line));
+            elided = false;
+        } else if !elided {
+            text.push_str("  ...\n");
+            elided = true;
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::module_diff;
+
+    #[test]
+    fn test_module_diff() {
+        let mut b = mr::Builder::new();
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        b.id();
+        let uint = b.type_int(32, 0);
+        for value in 0..6 {
+            b.constant_u32(uint, value);
+        }
+        let m = b.module();
+
+        let expected = "
+            OpMemoryModel Logical GLSL450
+            %uint = OpTypeInt 32 0
+            %0 = OpConstant %uint 0
+            %1 = OpConstant %uint 1
+            %2 = OpConstant %uint 2
+            %3 = OpConstant %uint 3
+            %4 = OpConstant %uint 4
+            %5 = OpConstant %uint 5
+        ";
+        assert_module_eq!(m, expected);
+        let differing = expected.replace("%uint 4", "%uint 6");
+        assert_eq!(module_diff(&m, &differing).unwrap(),
+                   "  ...\n  \
+                    %4 = OpConstant  %1  2\n  \
+                    %5 = OpConstant  %1  3\n\
+                    - %6 = OpConstant  %1  6\n\
+                    + %6 = OpConstant  %1  4\n  \
+                    %7 = OpConstant  %1  5\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "modules differ")]
+    fn test_assert_module_eq_panics() {
+        let mut b = mr::Builder::new();
+        b.type_float(32);
+        assert_module_eq!(b.module(), "%float = OpTypeFloat 64");
+    }
+}
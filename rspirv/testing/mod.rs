@@ -0,0 +1,36 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Randomized differential testing of module transforms.
+//!
+//! [`gen_module`](fn.gen_module.html) generates valid compute modules from
+//! a seed, with the features selected in a [`Config`](struct.Config.html).
+//! The modules write their results to a buffer, which the oracle obtains by
+//! running them in the [interpreter](../interp/index.html).
+//! [`check_pass`](fn.check_pass.html) combines both to check that a
+//! transform preserves the results of many generated modules.
+//!
+//! [`assert_module_eq!`](macro.assert_module_eq.html) compares a module
+//! with one written as SPIR-V text, for golden tests of transforms and
+//! builders, in this crate and in crates building on it.
+
+pub use self::generate::{gen_module, Config};
+pub use self::golden::module_diff;
+pub use self::oracle::{check_pass, compare, outputs, Error, Failure, Result};
+#[doc(inline)]
+pub use assert_module_eq;
+
+mod generate;
+mod golden;
+mod oracle;
@@ -0,0 +1,220 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use interp;
+use mr;
+use spirv;
+
+use std::{error, fmt, result};
+use std::ops::Range;
+use super::generate::{gen_module, Config};
+
+/// Oracle errors.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The module has no `GLCompute` entry point.
+    MissingEntryPoint,
+    /// The module could not be interpreted.
+    Interp(interp::Error),
+    /// The two modules write different words at the given output index.
+    Mismatch {
+        index: usize,
+        before: u32,
+        after: u32,
+    },
+}
+
+impl Error {
+    /// Gives an descriptive string for each error.
+    ///
+    /// This method is intended to be used by fmt::Display and error::Error to
+    /// avoid duplication in implementation. So it's private.
+    fn describe(&self) -> &str {
+        match *self {
+            Error::MissingEntryPoint => "missing GLCompute entry point",
+            Error::Interp(_) => "interpretation failed",
+            Error::Mismatch { .. } => "output mismatch",
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        self.describe()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::MissingEntryPoint => write!(f, "{}", self.describe()),
+            Error::Interp(ref err) => write!(f, "{}: {}", self.describe(), err),
+            Error::Mismatch { index, before, after } => {
+                write!(f,
+                       "{} at index {}: {:#x} before, {:#x} after",
+                       self.describe(),
+                       index,
+                       before,
+                       after)
+            }
+        }
+    }
+}
+
+impl From<interp::Error> for Error {
+    fn from(err: interp::Error) -> Error {
+        Error::Interp(err)
+    }
+}
+
+pub type Result<T> = result::Result<T, Error>;
+
+/// Runs the entry point of `module` and returns the first `len` words it
+/// leaves in its output buffer.
+///
+/// `module` is expected to follow the conventions of the modules generated
+/// by [`gen_module`](fn.gen_module.html): a single invocation of its
+/// `GLCompute` entry point writes its results to the buffer at descriptor
+/// set 0 and binding 0, which starts zeroed. Specialization constants take
+/// their default values. See the [interpreter](../interp/index.html) for the
+/// supported instructions.
+pub fn outputs(module: &mr::Module, len: usize) -> Result<Vec<u32>> {
+    let name = module.entry_points
+        .iter()
+        .find_map(|inst| match (inst.operands.first(), inst.operands.get(2)) {
+            (Some(&mr::Operand::ExecutionModel(spirv::ExecutionModel::GLCompute)),
+             Some(mr::Operand::LiteralString(name))) => Some(name),
+            _ => None,
+        })
+        .ok_or(Error::MissingEntryPoint)?;
+    let mut interpreter = interp::Interpreter::new(module);
+    interpreter.bind_buffer(0, 0, vec![0; len]);
+    interpreter.run(name, [1, 1, 1])?;
+    Ok(interpreter.buffer(0, 0).unwrap_or(&[]).to_vec())
+}
+
+/// Checks that `before` and `after` write the same first `len` output words.
+pub fn compare(before: &mr::Module, after: &mr::Module, len: usize) -> Result<()> {
+    let expected = outputs(before, len)?;
+    let actual = outputs(after, len)?;
+    match expected.iter().zip(&actual).position(|(x, y)| x != y) {
+        Some(index) => {
+            Err(Error::Mismatch {
+                index,
+                before: expected[index],
+                after: actual[index],
+            })
+        }
+        None => Ok(()),
+    }
+}
+
+/// A failed differential test.
+#[derive(Debug)]
+pub struct Failure {
+    /// The seed of the generated module the transform broke.
+    ///
+    /// Pass it to [`gen_module`](fn.gen_module.html) with the same config to
+    /// reproduce the failure.
+    pub seed: u64,
+    /// What went wrong.
+    pub error: Error,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "seed {}: {}", self.seed, self.error)
+    }
+}
+
+/// Differentially tests the transform `pass` on modules generated from each
+/// of the given `seeds`.
+///
+/// For each seed, generates a module with [`gen_module`](fn.gen_module.html),
+/// runs `pass` on a second copy of it, and [compares](fn.compare.html) the
+/// outputs of both. Stops at the first seed where the outputs differ or
+/// either module fails to run.
+///
+/// # Examples
+///
+/// ```
+/// use rspirv::testing::{check_pass, Config};
+///
+/// // Stripping debug instructions must not change the results.
+/// let result = check_pass(&Config::default(), 0..16, |module| module.debugs.clear());
+/// assert!(result.is_ok());
+/// ```
+pub fn check_pass<F>(config: &Config, seeds: Range<u64>, mut pass: F) -> result::Result<(), Failure>
+    where F: FnMut(&mut mr::Module)
+{
+    for seed in seeds {
+        let before = gen_module(seed, config);
+        let mut after = gen_module(seed, config);
+        pass(&mut after);
+        compare(&before, &after, config.outputs as usize).map_err(|error| Failure { seed, error })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use mr;
+    use spirv;
+
+    use super::{check_pass, compare, outputs, Error};
+    use testing::{gen_module, Config};
+
+    #[test]
+    fn test_generated_modules_run() {
+        let config = Config::default();
+        for seed in 0..128 {
+            let module = gen_module(seed, &config);
+            assert_eq!(outputs(&module, config.outputs as usize).unwrap().len(), 8);
+        }
+    }
+
+    #[test]
+    fn test_identity_pass() {
+        assert!(check_pass(&Config::default(), 0..32, |_| {}).is_ok());
+    }
+
+    #[test]
+    fn test_detects_broken_pass() {
+        // Swapping the operands of every subtraction changes some results.
+        let swap = |module: &mut mr::Module| {
+            for block in &mut module.functions[0].basic_blocks {
+                for inst in &mut block.instructions {
+                    if inst.class.opcode == spirv::Op::ISub {
+                        inst.operands.swap(0, 1);
+                    }
+                }
+            }
+        };
+        let failure = check_pass(&Config::default(), 0..256, swap).unwrap_err();
+        assert_matches!(failure.error, Error::Mismatch { .. });
+
+        let config = Config::default();
+        let before = gen_module(failure.seed, &config);
+        let mut after = gen_module(failure.seed, &config);
+        swap(&mut after);
+        assert_eq!(compare(&before, &after, 8), Err(failure.error));
+    }
+
+    #[test]
+    fn test_missing_entry_point() {
+        let mut b = mr::Builder::new();
+        b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+        assert_eq!(outputs(&b.module(), 1), Err(Error::MissingEntryPoint));
+    }
+}
@@ -0,0 +1,220 @@
+// Copyright 2017 Google Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cross-checks the validator and disassembler against those of the SPIR-V
+//! tools over the benchmark corpus.
+//!
+//! The corpus is as for the `corpus` benchmark: every `.spv` file under
+//! `benches/corpus/` and under the directories listed in the
+//! `RSPIRV_CORPUS` environment variable, and a module synthesized with the
+//! builder. The check is skipped if `spirv-val` or `spirv-dis` is not on
+//! `PATH`.
+//!
+//! For each module, the divergences reported are:
+//!
+//! * `spirv-val` accepting a module rspirv fails to load, or one
+//!   [`TargetEnv::validate`] rejects. The reverse is not reported, since
+//!   rspirv only validates what depends on the environment.
+//! * `spirv-dis --raw-id` disassembling a module differently, ignoring
+//!   comments and whitespace.
+//!
+//! The target environment is named by `RSPIRV_TARGET_ENV` as the SPIR-V
+//! tools spell it, e.g., `vulkan1.1`, and defaults to the universal one of
+//! the SPIR-V version of each module.
+//!
+//! Run with `cargo test -p rspirv --test spirv_tools`.
+
+extern crate rspirv;
+extern crate spirv_headers as spirv;
+
+use rspirv::binary::{Assemble, Disassemble};
+use rspirv::env::TargetEnv;
+use rspirv::mr;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+
+/// Collects the `.spv` files under `dir` recursively.
+fn collect(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        if path.is_dir() {
+            collect(&path, files);
+        } else if path.extension().is_some_and(|e| e == "spv") {
+            files.push(path);
+        }
+    }
+}
+
+/// A fragment shader writing a constant color.
+fn fragment() -> Vec<u32> {
+    let mut b = mr::Builder::new();
+    b.capability(spirv::Capability::Shader);
+    b.memory_model(spirv::AddressingModel::Logical, spirv::MemoryModel::GLSL450);
+    let void = b.type_void();
+    let float = b.type_float(32);
+    let vec4 = b.type_vector(float, 4);
+    let output = b.type_pointer(None, spirv::StorageClass::Output, vec4);
+    let color = b.variable(output, None, spirv::StorageClass::Output, None);
+    b.decorate(color, spirv::Decoration::Location, vec![mr::Operand::LiteralInt32(0)]);
+    let one = b.constant_f32(float, 1.0);
+    let white = b.constant_composite(vec4, vec![one, one, one, one]);
+    let voidf = b.type_function(void, vec![]);
+    let f = b.begin_function(void, None, spirv::FunctionControl::NONE, voidf).unwrap();
+    b.entry_point(spirv::ExecutionModel::Fragment, f, "main", vec![color]);
+    b.execution_mode(f, spirv::ExecutionMode::OriginUpperLeft, vec![]);
+    b.name(f, "main");
+    b.begin_basic_block(None).unwrap();
+    b.store(color, white, None, vec![]).unwrap();
+    b.ret().unwrap();
+    b.end_function().unwrap();
+    b.module().assemble()
+}
+
+/// Returns true if the tool of the given `name` runs.
+fn available(name: &str) -> bool {
+    Command::new(name).arg("--version").output().is_ok_and(|out| out.status.success())
+}
+
+/// Runs the tool of the given `name` over `path`, and returns whether it
+/// succeeded with its output.
+fn run(name: &str, args: &[&str], path: &Path) -> (bool, String) {
+    let out = Command::new(name).args(args).arg(path).output().unwrap();
+    let mut text = String::from_utf8_lossy(&out.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&out.stderr));
+    (out.status.success(), text)
+}
+
+/// Returns the lines of a disassembly without comments, with whitespace
+/// collapsed.
+fn normalize(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty() && !line.starts_with(';'))
+        .collect()
+}
+
+/// Cross-checks the module of the given `code`, and returns the divergences
+/// found.
+///
+/// `target` is the target environment with its name, if given.
+fn check(code: &[u32], path: &Path, target: Option<&(TargetEnv, String)>) -> Vec<String> {
+    let mut divergences = vec![];
+    let module = mr::load_words(code);
+    let version = module.as_ref()
+        .ok()
+        .and_then(|m| m.header.as_ref())
+        .map_or((1, 0), |header| header.version());
+    let (target, name) = match target {
+        Some(&(target, ref name)) => (target, name.clone()),
+        None => {
+            let (major, minor) = version;
+            (TargetEnv::Universal(major, minor), format!("spv{}.{}", major, minor))
+        }
+    };
+
+    let (valid, message) = run("spirv-val", &["--target-env", &name], path);
+    match module {
+        Ok(ref module) => {
+            if let Err(err) = target.validate(module) {
+                if valid {
+                    divergences.push(format!("spirv-val accepts, rspirv rejects: {}", err));
+                }
+            }
+        }
+        Err(ref err) => {
+            if valid {
+                divergences.push(format!("spirv-val accepts, rspirv fails to load: {}", err));
+            }
+            return divergences;
+        }
+    }
+    if !valid {
+        println!("  spirv-val rejects: {}", message.trim());
+    }
+
+    let (disassembled, theirs) = run("spirv-dis", &["--raw-id", "--no-color"], path);
+    if disassembled {
+        let ours = normalize(&module.unwrap().disassemble());
+        let theirs = normalize(&theirs);
+        let first = (0..ours.len().max(theirs.len())).find(|&i| ours.get(i) != theirs.get(i));
+        if let Some(i) = first {
+            divergences.push(format!("disassembly differs at line {}: {:?} by rspirv, {:?} by \
+                                      spirv-dis",
+                                     i + 1,
+                                     ours.get(i).map_or("", |l| l.as_str()),
+                                     theirs.get(i).map_or("", |l| l.as_str())));
+        }
+    }
+    divergences
+}
+
+fn main() {
+    if !available("spirv-val") || !available("spirv-dis") {
+        println!("skipping: spirv-val and spirv-dis not on PATH");
+        return;
+    }
+    let target = env::var("RSPIRV_TARGET_ENV").ok().map(|name| match TargetEnv::from_name(&name) {
+        Some(target) => (target, name),
+        None => panic!("unknown target environment {}", name),
+    });
+
+    let dir = env::temp_dir().join(format!("rspirv-spirv-tools-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let mut corpus = vec![(dir.join("synthetic-fragment.spv"), fragment())];
+    for (path, code) in &corpus {
+        let bytes: Vec<u8> = code.iter().flat_map(|w| w.to_le_bytes()).collect();
+        fs::write(path, bytes).unwrap();
+    }
+    let mut files = vec![];
+    collect(&Path::new(env!("CARGO_MANIFEST_DIR")).join("benches/corpus"), &mut files);
+    if let Some(dirs) = env::var_os("RSPIRV_CORPUS") {
+        for dir in env::split_paths(&dirs) {
+            collect(&dir, &mut files);
+        }
+    }
+    files.sort();
+    for file in files {
+        let bytes = fs::read(&file).unwrap_or_default();
+        if bytes.len() % 4 != 0 {
+            println!("skipping unreadable {}", file.display());
+            continue;
+        }
+        let code = bytes.chunks(4).map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect();
+        corpus.push((file, code));
+    }
+
+    let mut diverged = 0;
+    for (path, code) in &corpus {
+        println!("{}", path.display());
+        let divergences = check(code, path, target.as_ref());
+        for divergence in &divergences {
+            println!("  {}", divergence);
+        }
+        if !divergences.is_empty() {
+            diverged += 1;
+        }
+    }
+    let _ = fs::remove_dir_all(&dir);
+    println!("{} of {} modules diverged", diverged, corpus.len());
+    if diverged > 0 {
+        process::exit(1);
+    }
+}
@@ -13,6 +13,11 @@
 // limitations under the License.
 
 //! The module containing utility functions for:
-//! * handling numbers.
+//! * hashing bytes,
+//! * handling numbers,
+//! * writing tests.
 
+pub mod hash;
 pub mod num;
+#[cfg(test)]
+pub mod test;
@@ -14,6 +14,7 @@
 
 #![allow(dead_code)]
 
+use prelude::*;
 use std::mem;
 
 /// Splits the given u32 `value` into a vector of bytes in little-endian format.
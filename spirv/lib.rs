@@ -19,15 +19,17 @@
 //! This crate contains Rust definitions of all SPIR-V structs, enums,
 //! and constants.
 //!
+//! This crate is `no_std`; the `FromPrimitive` conversions of the value enums
+//! come from `num-traits` without its `std` feature.
+//!
 //! The version of this crate is the version of SPIR-V it contains.
 
+#![no_std]
 #![allow(non_camel_case_types)]
 #![cfg_attr(rustfmt, rustfmt_skip)]
 
 #[macro_use]
 extern crate bitflags;
-extern crate num;
-#[macro_use]
-extern crate num_derive;
+extern crate num_traits;
 
 include!("spirv.rs");
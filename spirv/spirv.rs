@@ -34,6 +34,10 @@ bitflags!{
         const CONST_OFFSETS = 0x0020;
         const SAMPLE = 0x0040;
         const MIN_LOD = 0x0080;
+        const MAKE_TEXEL_AVAILABLE = 0x0100;
+        const MAKE_TEXEL_VISIBLE = 0x0200;
+        const NON_PRIVATE_TEXEL = 0x0400;
+        const VOLATILE_TEXEL = 0x0800;
     }
 }
 
@@ -95,6 +99,10 @@ bitflags!{
         const CROSS_WORKGROUP_MEMORY = 0x0200;
         const ATOMIC_COUNTER_MEMORY = 0x0400;
         const IMAGE_MEMORY = 0x0800;
+        const OUTPUT_MEMORY = 0x1000;
+        const MAKE_AVAILABLE = 0x2000;
+        const MAKE_VISIBLE = 0x4000;
+        const VOLATILE = 0x8000;
     }
 }
 
@@ -105,6 +113,9 @@ bitflags!{
         const VOLATILE = 0x0001;
         const ALIGNED = 0x0002;
         const NONTEMPORAL = 0x0004;
+        const MAKE_POINTER_AVAILABLE = 0x0008;
+        const MAKE_POINTER_VISIBLE = 0x0010;
+        const NON_PRIVATE_POINTER = 0x0020;
     }
 }
 
@@ -116,9 +127,21 @@ bitflags!{
     }
 }
 
+bitflags!{
+    /// SPIR-V operand kind: [CooperativeMatrixOperands](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_cooperative_matrix_operands_a_cooperative_matrix_operands)
+    pub struct CooperativeMatrixOperands : u32 {
+        const NONE_KHR = 0x0000;
+        const MATRIX_ASIGNED_COMPONENTS_KHR = 0x0001;
+        const MATRIX_BSIGNED_COMPONENTS_KHR = 0x0002;
+        const MATRIX_CSIGNED_COMPONENTS_KHR = 0x0004;
+        const MATRIX_RESULT_SIGNED_COMPONENTS_KHR = 0x0008;
+        const SATURATING_ACCUMULATION_KHR = 0x0010;
+    }
+}
+
 /// SPIR-V operand kind: [SourceLanguage](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_source_language_a_source_language)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum SourceLanguage {
     Unknown = 0,
     ESSL = 1,
@@ -128,9 +151,27 @@ pub enum SourceLanguage {
     HLSL = 5,
 }
 
+impl num_traits::FromPrimitive for SourceLanguage {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => SourceLanguage::Unknown,
+            1 => SourceLanguage::ESSL,
+            2 => SourceLanguage::GLSL,
+            3 => SourceLanguage::OpenCL_C,
+            4 => SourceLanguage::OpenCL_CPP,
+            5 => SourceLanguage::HLSL,
+            _ => return None,
+        })
+    }
+}
+
 /// SPIR-V operand kind: [ExecutionModel](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_execution_model_a_execution_model)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ExecutionModel {
     Vertex = 0,
     TessellationControl = 1,
@@ -139,29 +180,98 @@ pub enum ExecutionModel {
     Fragment = 4,
     GLCompute = 5,
     Kernel = 6,
+    RayGenerationKHR = 5313,
+    IntersectionKHR = 5314,
+    AnyHitKHR = 5315,
+    ClosestHitKHR = 5316,
+    MissKHR = 5317,
+    CallableKHR = 5318,
+    TaskEXT = 5364,
+    MeshEXT = 5365,
+}
+
+impl num_traits::FromPrimitive for ExecutionModel {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => ExecutionModel::Vertex,
+            1 => ExecutionModel::TessellationControl,
+            2 => ExecutionModel::TessellationEvaluation,
+            3 => ExecutionModel::Geometry,
+            4 => ExecutionModel::Fragment,
+            5 => ExecutionModel::GLCompute,
+            6 => ExecutionModel::Kernel,
+            5313 => ExecutionModel::RayGenerationKHR,
+            5314 => ExecutionModel::IntersectionKHR,
+            5315 => ExecutionModel::AnyHitKHR,
+            5316 => ExecutionModel::ClosestHitKHR,
+            5317 => ExecutionModel::MissKHR,
+            5318 => ExecutionModel::CallableKHR,
+            5364 => ExecutionModel::TaskEXT,
+            5365 => ExecutionModel::MeshEXT,
+            _ => return None,
+        })
+    }
 }
 
 /// SPIR-V operand kind: [AddressingModel](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_addressing_model_a_addressing_model)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum AddressingModel {
     Logical = 0,
     Physical32 = 1,
     Physical64 = 2,
+    PhysicalStorageBuffer64 = 5348,
+}
+
+impl num_traits::FromPrimitive for AddressingModel {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => AddressingModel::Logical,
+            1 => AddressingModel::Physical32,
+            2 => AddressingModel::Physical64,
+            5348 => AddressingModel::PhysicalStorageBuffer64,
+            _ => return None,
+        })
+    }
 }
 
 /// SPIR-V operand kind: [MemoryModel](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_memory_model_a_memory_model)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum MemoryModel {
     Simple = 0,
     GLSL450 = 1,
     OpenCL = 2,
+    Vulkan = 3,
+}
+
+impl num_traits::FromPrimitive for MemoryModel {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => MemoryModel::Simple,
+            1 => MemoryModel::GLSL450,
+            2 => MemoryModel::OpenCL,
+            3 => MemoryModel::Vulkan,
+            _ => return None,
+        })
+    }
 }
 
 /// SPIR-V operand kind: [ExecutionMode](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_execution_mode_a_execution_mode)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ExecutionMode {
     Invocations = 0,
     SpacingEqual = 1,
@@ -202,12 +312,80 @@ pub enum ExecutionMode {
     LocalSizeId = 38,
     LocalSizeHintId = 39,
     PostDepthCoverage = 4446,
+    DenormPreserve = 4459,
+    DenormFlushToZero = 4460,
+    SignedZeroInfNanPreserve = 4461,
+    RoundingModeRTE = 4462,
+    RoundingModeRTZ = 4463,
     StencilRefReplacingEXT = 5027,
+    OutputLinesEXT = 5269,
+    OutputPrimitivesEXT = 5270,
+    OutputTrianglesEXT = 5298,
+}
+
+impl num_traits::FromPrimitive for ExecutionMode {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => ExecutionMode::Invocations,
+            1 => ExecutionMode::SpacingEqual,
+            2 => ExecutionMode::SpacingFractionalEven,
+            3 => ExecutionMode::SpacingFractionalOdd,
+            4 => ExecutionMode::VertexOrderCw,
+            5 => ExecutionMode::VertexOrderCcw,
+            6 => ExecutionMode::PixelCenterInteger,
+            7 => ExecutionMode::OriginUpperLeft,
+            8 => ExecutionMode::OriginLowerLeft,
+            9 => ExecutionMode::EarlyFragmentTests,
+            10 => ExecutionMode::PointMode,
+            11 => ExecutionMode::Xfb,
+            12 => ExecutionMode::DepthReplacing,
+            14 => ExecutionMode::DepthGreater,
+            15 => ExecutionMode::DepthLess,
+            16 => ExecutionMode::DepthUnchanged,
+            17 => ExecutionMode::LocalSize,
+            18 => ExecutionMode::LocalSizeHint,
+            19 => ExecutionMode::InputPoints,
+            20 => ExecutionMode::InputLines,
+            21 => ExecutionMode::InputLinesAdjacency,
+            22 => ExecutionMode::Triangles,
+            23 => ExecutionMode::InputTrianglesAdjacency,
+            24 => ExecutionMode::Quads,
+            25 => ExecutionMode::Isolines,
+            26 => ExecutionMode::OutputVertices,
+            27 => ExecutionMode::OutputPoints,
+            28 => ExecutionMode::OutputLineStrip,
+            29 => ExecutionMode::OutputTriangleStrip,
+            30 => ExecutionMode::VecTypeHint,
+            31 => ExecutionMode::ContractionOff,
+            33 => ExecutionMode::Initializer,
+            34 => ExecutionMode::Finalizer,
+            35 => ExecutionMode::SubgroupSize,
+            36 => ExecutionMode::SubgroupsPerWorkgroup,
+            37 => ExecutionMode::SubgroupsPerWorkgroupId,
+            38 => ExecutionMode::LocalSizeId,
+            39 => ExecutionMode::LocalSizeHintId,
+            4446 => ExecutionMode::PostDepthCoverage,
+            4459 => ExecutionMode::DenormPreserve,
+            4460 => ExecutionMode::DenormFlushToZero,
+            4461 => ExecutionMode::SignedZeroInfNanPreserve,
+            4462 => ExecutionMode::RoundingModeRTE,
+            4463 => ExecutionMode::RoundingModeRTZ,
+            5027 => ExecutionMode::StencilRefReplacingEXT,
+            5269 => ExecutionMode::OutputLinesEXT,
+            5270 => ExecutionMode::OutputPrimitivesEXT,
+            5298 => ExecutionMode::OutputTrianglesEXT,
+            _ => return None,
+        })
+    }
 }
 
 /// SPIR-V operand kind: [StorageClass](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_storage_class_a_storage_class)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum StorageClass {
     UniformConstant = 0,
     Input = 1,
@@ -222,11 +400,52 @@ pub enum StorageClass {
     AtomicCounter = 10,
     Image = 11,
     StorageBuffer = 12,
+    CallableDataKHR = 5328,
+    IncomingCallableDataKHR = 5329,
+    RayPayloadKHR = 5338,
+    HitAttributeKHR = 5339,
+    IncomingRayPayloadKHR = 5342,
+    ShaderRecordBufferKHR = 5343,
+    PhysicalStorageBuffer = 5349,
+    TaskPayloadWorkgroupEXT = 5402,
+}
+
+impl num_traits::FromPrimitive for StorageClass {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => StorageClass::UniformConstant,
+            1 => StorageClass::Input,
+            2 => StorageClass::Uniform,
+            3 => StorageClass::Output,
+            4 => StorageClass::Workgroup,
+            5 => StorageClass::CrossWorkgroup,
+            6 => StorageClass::Private,
+            7 => StorageClass::Function,
+            8 => StorageClass::Generic,
+            9 => StorageClass::PushConstant,
+            10 => StorageClass::AtomicCounter,
+            11 => StorageClass::Image,
+            12 => StorageClass::StorageBuffer,
+            5328 => StorageClass::CallableDataKHR,
+            5329 => StorageClass::IncomingCallableDataKHR,
+            5338 => StorageClass::RayPayloadKHR,
+            5339 => StorageClass::HitAttributeKHR,
+            5342 => StorageClass::IncomingRayPayloadKHR,
+            5343 => StorageClass::ShaderRecordBufferKHR,
+            5349 => StorageClass::PhysicalStorageBuffer,
+            5402 => StorageClass::TaskPayloadWorkgroupEXT,
+            _ => return None,
+        })
+    }
 }
 
 /// SPIR-V operand kind: [Dim](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_dim_a_dim)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Dim {
     Dim1D = 0,
     Dim2D = 1,
@@ -237,9 +456,28 @@ pub enum Dim {
     DimSubpassData = 6,
 }
 
+impl num_traits::FromPrimitive for Dim {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => Dim::Dim1D,
+            1 => Dim::Dim2D,
+            2 => Dim::Dim3D,
+            3 => Dim::DimCube,
+            4 => Dim::DimRect,
+            5 => Dim::DimBuffer,
+            6 => Dim::DimSubpassData,
+            _ => return None,
+        })
+    }
+}
+
 /// SPIR-V operand kind: [SamplerAddressingMode](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_sampler_addressing_mode_a_sampler_addressing_mode)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum SamplerAddressingMode {
     None = 0,
     ClampToEdge = 1,
@@ -248,17 +486,48 @@ pub enum SamplerAddressingMode {
     RepeatMirrored = 4,
 }
 
+impl num_traits::FromPrimitive for SamplerAddressingMode {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => SamplerAddressingMode::None,
+            1 => SamplerAddressingMode::ClampToEdge,
+            2 => SamplerAddressingMode::Clamp,
+            3 => SamplerAddressingMode::Repeat,
+            4 => SamplerAddressingMode::RepeatMirrored,
+            _ => return None,
+        })
+    }
+}
+
 /// SPIR-V operand kind: [SamplerFilterMode](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_sampler_filter_mode_a_sampler_filter_mode)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum SamplerFilterMode {
     Nearest = 0,
     Linear = 1,
 }
 
+impl num_traits::FromPrimitive for SamplerFilterMode {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => SamplerFilterMode::Nearest,
+            1 => SamplerFilterMode::Linear,
+            _ => return None,
+        })
+    }
+}
+
 /// SPIR-V operand kind: [ImageFormat](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_image_format_a_image_format)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ImageFormat {
     Unknown = 0,
     Rgba32f = 1,
@@ -302,9 +571,61 @@ pub enum ImageFormat {
     R8ui = 39,
 }
 
+impl num_traits::FromPrimitive for ImageFormat {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => ImageFormat::Unknown,
+            1 => ImageFormat::Rgba32f,
+            2 => ImageFormat::Rgba16f,
+            3 => ImageFormat::R32f,
+            4 => ImageFormat::Rgba8,
+            5 => ImageFormat::Rgba8Snorm,
+            6 => ImageFormat::Rg32f,
+            7 => ImageFormat::Rg16f,
+            8 => ImageFormat::R11fG11fB10f,
+            9 => ImageFormat::R16f,
+            10 => ImageFormat::Rgba16,
+            11 => ImageFormat::Rgb10A2,
+            12 => ImageFormat::Rg16,
+            13 => ImageFormat::Rg8,
+            14 => ImageFormat::R16,
+            15 => ImageFormat::R8,
+            16 => ImageFormat::Rgba16Snorm,
+            17 => ImageFormat::Rg16Snorm,
+            18 => ImageFormat::Rg8Snorm,
+            19 => ImageFormat::R16Snorm,
+            20 => ImageFormat::R8Snorm,
+            21 => ImageFormat::Rgba32i,
+            22 => ImageFormat::Rgba16i,
+            23 => ImageFormat::Rgba8i,
+            24 => ImageFormat::R32i,
+            25 => ImageFormat::Rg32i,
+            26 => ImageFormat::Rg16i,
+            27 => ImageFormat::Rg8i,
+            28 => ImageFormat::R16i,
+            29 => ImageFormat::R8i,
+            30 => ImageFormat::Rgba32ui,
+            31 => ImageFormat::Rgba16ui,
+            32 => ImageFormat::Rgba8ui,
+            33 => ImageFormat::R32ui,
+            34 => ImageFormat::Rgb10a2ui,
+            35 => ImageFormat::Rg32ui,
+            36 => ImageFormat::Rg16ui,
+            37 => ImageFormat::Rg8ui,
+            38 => ImageFormat::R16ui,
+            39 => ImageFormat::R8ui,
+            _ => return None,
+        })
+    }
+}
+
 /// SPIR-V operand kind: [ImageChannelOrder](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_image_channel_order_a_image_channel_order)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ImageChannelOrder {
     R = 0,
     A = 1,
@@ -328,9 +649,41 @@ pub enum ImageChannelOrder {
     ABGR = 19,
 }
 
+impl num_traits::FromPrimitive for ImageChannelOrder {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => ImageChannelOrder::R,
+            1 => ImageChannelOrder::A,
+            2 => ImageChannelOrder::RG,
+            3 => ImageChannelOrder::RA,
+            4 => ImageChannelOrder::RGB,
+            5 => ImageChannelOrder::RGBA,
+            6 => ImageChannelOrder::BGRA,
+            7 => ImageChannelOrder::ARGB,
+            8 => ImageChannelOrder::Intensity,
+            9 => ImageChannelOrder::Luminance,
+            10 => ImageChannelOrder::Rx,
+            11 => ImageChannelOrder::RGx,
+            12 => ImageChannelOrder::RGBx,
+            13 => ImageChannelOrder::Depth,
+            14 => ImageChannelOrder::DepthStencil,
+            15 => ImageChannelOrder::sRGB,
+            16 => ImageChannelOrder::sRGBx,
+            17 => ImageChannelOrder::sRGBA,
+            18 => ImageChannelOrder::sBGRA,
+            19 => ImageChannelOrder::ABGR,
+            _ => return None,
+        })
+    }
+}
+
 /// SPIR-V operand kind: [ImageChannelDataType](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_image_channel_data_type_a_image_channel_data_type)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ImageChannelDataType {
     SnormInt8 = 0,
     SnormInt16 = 1,
@@ -351,9 +704,38 @@ pub enum ImageChannelDataType {
     UnormInt101010_2 = 16,
 }
 
+impl num_traits::FromPrimitive for ImageChannelDataType {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => ImageChannelDataType::SnormInt8,
+            1 => ImageChannelDataType::SnormInt16,
+            2 => ImageChannelDataType::UnormInt8,
+            3 => ImageChannelDataType::UnormInt16,
+            4 => ImageChannelDataType::UnormShort565,
+            5 => ImageChannelDataType::UnormShort555,
+            6 => ImageChannelDataType::UnormInt101010,
+            7 => ImageChannelDataType::SignedInt8,
+            8 => ImageChannelDataType::SignedInt16,
+            9 => ImageChannelDataType::SignedInt32,
+            10 => ImageChannelDataType::UnsignedInt8,
+            11 => ImageChannelDataType::UnsignedInt16,
+            12 => ImageChannelDataType::UnsignedInt32,
+            13 => ImageChannelDataType::HalfFloat,
+            14 => ImageChannelDataType::Float,
+            15 => ImageChannelDataType::UnormInt24,
+            16 => ImageChannelDataType::UnormInt101010_2,
+            _ => return None,
+        })
+    }
+}
+
 /// SPIR-V operand kind: [FPRoundingMode](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_fp_rounding_mode_a_fp_rounding_mode)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum FPRoundingMode {
     RTE = 0,
     RTZ = 1,
@@ -361,26 +743,71 @@ pub enum FPRoundingMode {
     RTN = 3,
 }
 
+impl num_traits::FromPrimitive for FPRoundingMode {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => FPRoundingMode::RTE,
+            1 => FPRoundingMode::RTZ,
+            2 => FPRoundingMode::RTP,
+            3 => FPRoundingMode::RTN,
+            _ => return None,
+        })
+    }
+}
+
 /// SPIR-V operand kind: [LinkageType](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_linkage_type_a_linkage_type)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum LinkageType {
     Export = 0,
     Import = 1,
 }
 
+impl num_traits::FromPrimitive for LinkageType {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => LinkageType::Export,
+            1 => LinkageType::Import,
+            _ => return None,
+        })
+    }
+}
+
 /// SPIR-V operand kind: [AccessQualifier](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_access_qualifier_a_access_qualifier)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum AccessQualifier {
     ReadOnly = 0,
     WriteOnly = 1,
     ReadWrite = 2,
 }
 
+impl num_traits::FromPrimitive for AccessQualifier {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => AccessQualifier::ReadOnly,
+            1 => AccessQualifier::WriteOnly,
+            2 => AccessQualifier::ReadWrite,
+            _ => return None,
+        })
+    }
+}
+
 /// SPIR-V operand kind: [FunctionParameterAttribute](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_function_parameter_attribute_a_function_parameter_attribute)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum FunctionParameterAttribute {
     Zext = 0,
     Sext = 1,
@@ -392,9 +819,29 @@ pub enum FunctionParameterAttribute {
     NoReadWrite = 7,
 }
 
+impl num_traits::FromPrimitive for FunctionParameterAttribute {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => FunctionParameterAttribute::Zext,
+            1 => FunctionParameterAttribute::Sext,
+            2 => FunctionParameterAttribute::ByVal,
+            3 => FunctionParameterAttribute::Sret,
+            4 => FunctionParameterAttribute::NoAlias,
+            5 => FunctionParameterAttribute::NoCapture,
+            6 => FunctionParameterAttribute::NoWrite,
+            7 => FunctionParameterAttribute::NoReadWrite,
+            _ => return None,
+        })
+    }
+}
+
 /// SPIR-V operand kind: [Decoration](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_decoration_a_decoration)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Decoration {
     RelaxedPrecision = 0,
     SpecId = 1,
@@ -447,11 +894,88 @@ pub enum Decoration {
     PassthroughNV = 5250,
     ViewportRelativeNV = 5252,
     SecondaryViewportRelativeNV = 5256,
+    PerPrimitiveEXT = 5271,
+    NonUniform = 5300,
+    RestrictPointer = 5355,
+    AliasedPointer = 5356,
+    HlslCounterBufferGOOGLE = 5634,
+    HlslSemanticGOOGLE = 5635,
+    UserTypeGOOGLE = 5636,
+}
+
+impl num_traits::FromPrimitive for Decoration {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => Decoration::RelaxedPrecision,
+            1 => Decoration::SpecId,
+            2 => Decoration::Block,
+            3 => Decoration::BufferBlock,
+            4 => Decoration::RowMajor,
+            5 => Decoration::ColMajor,
+            6 => Decoration::ArrayStride,
+            7 => Decoration::MatrixStride,
+            8 => Decoration::GLSLShared,
+            9 => Decoration::GLSLPacked,
+            10 => Decoration::CPacked,
+            11 => Decoration::BuiltIn,
+            13 => Decoration::NoPerspective,
+            14 => Decoration::Flat,
+            15 => Decoration::Patch,
+            16 => Decoration::Centroid,
+            17 => Decoration::Sample,
+            18 => Decoration::Invariant,
+            19 => Decoration::Restrict,
+            20 => Decoration::Aliased,
+            21 => Decoration::Volatile,
+            22 => Decoration::Constant,
+            23 => Decoration::Coherent,
+            24 => Decoration::NonWritable,
+            25 => Decoration::NonReadable,
+            26 => Decoration::Uniform,
+            28 => Decoration::SaturatedConversion,
+            29 => Decoration::Stream,
+            30 => Decoration::Location,
+            31 => Decoration::Component,
+            32 => Decoration::Index,
+            33 => Decoration::Binding,
+            34 => Decoration::DescriptorSet,
+            35 => Decoration::Offset,
+            36 => Decoration::XfbBuffer,
+            37 => Decoration::XfbStride,
+            38 => Decoration::FuncParamAttr,
+            39 => Decoration::FPRoundingMode,
+            40 => Decoration::FPFastMathMode,
+            41 => Decoration::LinkageAttributes,
+            42 => Decoration::NoContraction,
+            43 => Decoration::InputAttachmentIndex,
+            44 => Decoration::Alignment,
+            45 => Decoration::MaxByteOffset,
+            46 => Decoration::AlignmentId,
+            47 => Decoration::MaxByteOffsetId,
+            4999 => Decoration::ExplicitInterpAMD,
+            5248 => Decoration::OverrideCoverageNV,
+            5250 => Decoration::PassthroughNV,
+            5252 => Decoration::ViewportRelativeNV,
+            5256 => Decoration::SecondaryViewportRelativeNV,
+            5271 => Decoration::PerPrimitiveEXT,
+            5300 => Decoration::NonUniform,
+            5355 => Decoration::RestrictPointer,
+            5356 => Decoration::AliasedPointer,
+            5634 => Decoration::HlslCounterBufferGOOGLE,
+            5635 => Decoration::HlslSemanticGOOGLE,
+            5636 => Decoration::UserTypeGOOGLE,
+            _ => return None,
+        })
+    }
 }
 
 /// SPIR-V operand kind: [BuiltIn](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_built_in_a_built_in)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum BuiltIn {
     Position = 0,
     PointSize = 1,
@@ -517,40 +1041,201 @@ pub enum BuiltIn {
     SecondaryViewportMaskNV = 5258,
     PositionPerViewNV = 5261,
     ViewportMaskPerViewNV = 5262,
+    PrimitivePointIndicesEXT = 5294,
+    PrimitiveLineIndicesEXT = 5295,
+    PrimitiveTriangleIndicesEXT = 5296,
+    CullPrimitiveEXT = 5299,
+    LaunchIdKHR = 5319,
+    LaunchSizeKHR = 5320,
+    WorldRayOriginKHR = 5321,
+    WorldRayDirectionKHR = 5322,
+    ObjectRayOriginKHR = 5323,
+    ObjectRayDirectionKHR = 5324,
+    RayTminKHR = 5325,
+    RayTmaxKHR = 5326,
+    InstanceCustomIndexKHR = 5327,
+    ObjectToWorldKHR = 5330,
+    WorldToObjectKHR = 5331,
+    HitKindKHR = 5333,
+    IncomingRayFlagsKHR = 5351,
+    RayGeometryIndexKHR = 5352,
+}
+
+impl num_traits::FromPrimitive for BuiltIn {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => BuiltIn::Position,
+            1 => BuiltIn::PointSize,
+            3 => BuiltIn::ClipDistance,
+            4 => BuiltIn::CullDistance,
+            5 => BuiltIn::VertexId,
+            6 => BuiltIn::InstanceId,
+            7 => BuiltIn::PrimitiveId,
+            8 => BuiltIn::InvocationId,
+            9 => BuiltIn::Layer,
+            10 => BuiltIn::ViewportIndex,
+            11 => BuiltIn::TessLevelOuter,
+            12 => BuiltIn::TessLevelInner,
+            13 => BuiltIn::TessCoord,
+            14 => BuiltIn::PatchVertices,
+            15 => BuiltIn::FragCoord,
+            16 => BuiltIn::PointCoord,
+            17 => BuiltIn::FrontFacing,
+            18 => BuiltIn::SampleId,
+            19 => BuiltIn::SamplePosition,
+            20 => BuiltIn::SampleMask,
+            22 => BuiltIn::FragDepth,
+            23 => BuiltIn::HelperInvocation,
+            24 => BuiltIn::NumWorkgroups,
+            25 => BuiltIn::WorkgroupSize,
+            26 => BuiltIn::WorkgroupId,
+            27 => BuiltIn::LocalInvocationId,
+            28 => BuiltIn::GlobalInvocationId,
+            29 => BuiltIn::LocalInvocationIndex,
+            30 => BuiltIn::WorkDim,
+            31 => BuiltIn::GlobalSize,
+            32 => BuiltIn::EnqueuedWorkgroupSize,
+            33 => BuiltIn::GlobalOffset,
+            34 => BuiltIn::GlobalLinearId,
+            36 => BuiltIn::SubgroupSize,
+            37 => BuiltIn::SubgroupMaxSize,
+            38 => BuiltIn::NumSubgroups,
+            39 => BuiltIn::NumEnqueuedSubgroups,
+            40 => BuiltIn::SubgroupId,
+            41 => BuiltIn::SubgroupLocalInvocationId,
+            42 => BuiltIn::VertexIndex,
+            43 => BuiltIn::InstanceIndex,
+            4416 => BuiltIn::SubgroupEqMaskKHR,
+            4417 => BuiltIn::SubgroupGeMaskKHR,
+            4418 => BuiltIn::SubgroupGtMaskKHR,
+            4419 => BuiltIn::SubgroupLeMaskKHR,
+            4420 => BuiltIn::SubgroupLtMaskKHR,
+            4424 => BuiltIn::BaseVertex,
+            4425 => BuiltIn::BaseInstance,
+            4426 => BuiltIn::DrawIndex,
+            4438 => BuiltIn::DeviceIndex,
+            4440 => BuiltIn::ViewIndex,
+            4992 => BuiltIn::BaryCoordNoPerspAMD,
+            4993 => BuiltIn::BaryCoordNoPerspCentroidAMD,
+            4994 => BuiltIn::BaryCoordNoPerspSampleAMD,
+            4995 => BuiltIn::BaryCoordSmoothAMD,
+            4996 => BuiltIn::BaryCoordSmoothCentroidAMD,
+            4997 => BuiltIn::BaryCoordSmoothSampleAMD,
+            4998 => BuiltIn::BaryCoordPullModelAMD,
+            5014 => BuiltIn::FragStencilRefEXT,
+            5253 => BuiltIn::ViewportMaskNV,
+            5257 => BuiltIn::SecondaryPositionNV,
+            5258 => BuiltIn::SecondaryViewportMaskNV,
+            5261 => BuiltIn::PositionPerViewNV,
+            5262 => BuiltIn::ViewportMaskPerViewNV,
+            5294 => BuiltIn::PrimitivePointIndicesEXT,
+            5295 => BuiltIn::PrimitiveLineIndicesEXT,
+            5296 => BuiltIn::PrimitiveTriangleIndicesEXT,
+            5299 => BuiltIn::CullPrimitiveEXT,
+            5319 => BuiltIn::LaunchIdKHR,
+            5320 => BuiltIn::LaunchSizeKHR,
+            5321 => BuiltIn::WorldRayOriginKHR,
+            5322 => BuiltIn::WorldRayDirectionKHR,
+            5323 => BuiltIn::ObjectRayOriginKHR,
+            5324 => BuiltIn::ObjectRayDirectionKHR,
+            5325 => BuiltIn::RayTminKHR,
+            5326 => BuiltIn::RayTmaxKHR,
+            5327 => BuiltIn::InstanceCustomIndexKHR,
+            5330 => BuiltIn::ObjectToWorldKHR,
+            5331 => BuiltIn::WorldToObjectKHR,
+            5333 => BuiltIn::HitKindKHR,
+            5351 => BuiltIn::IncomingRayFlagsKHR,
+            5352 => BuiltIn::RayGeometryIndexKHR,
+            _ => return None,
+        })
+    }
 }
 
 /// SPIR-V operand kind: [Scope](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_scope_a_scope)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Scope {
     CrossDevice = 0,
     Device = 1,
     Workgroup = 2,
     Subgroup = 3,
     Invocation = 4,
+    QueueFamily = 5,
+}
+
+impl num_traits::FromPrimitive for Scope {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => Scope::CrossDevice,
+            1 => Scope::Device,
+            2 => Scope::Workgroup,
+            3 => Scope::Subgroup,
+            4 => Scope::Invocation,
+            5 => Scope::QueueFamily,
+            _ => return None,
+        })
+    }
 }
 
 /// SPIR-V operand kind: [GroupOperation](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_group_operation_a_group_operation)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum GroupOperation {
     Reduce = 0,
     InclusiveScan = 1,
     ExclusiveScan = 2,
 }
 
+impl num_traits::FromPrimitive for GroupOperation {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => GroupOperation::Reduce,
+            1 => GroupOperation::InclusiveScan,
+            2 => GroupOperation::ExclusiveScan,
+            _ => return None,
+        })
+    }
+}
+
 /// SPIR-V operand kind: [KernelEnqueueFlags](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_kernel_enqueue_flags_a_kernel_enqueue_flags)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum KernelEnqueueFlags {
     NoWait = 0,
     WaitKernel = 1,
     WaitWorkGroup = 2,
 }
 
+impl num_traits::FromPrimitive for KernelEnqueueFlags {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => KernelEnqueueFlags::NoWait,
+            1 => KernelEnqueueFlags::WaitKernel,
+            2 => KernelEnqueueFlags::WaitWorkGroup,
+            _ => return None,
+        })
+    }
+}
+
 /// SPIR-V operand kind: [Capability](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_capability_a_capability)
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Capability {
     Matrix = 0,
     Shader = 1,
@@ -611,6 +1296,11 @@ pub enum Capability {
     SubgroupDispatch = 58,
     NamedBarrier = 59,
     PipeStorage = 60,
+    GroupNonUniform = 61,
+    GroupNonUniformVote = 62,
+    GroupNonUniformBallot = 64,
+    GroupNonUniformShuffle = 65,
+    GroupNonUniformShuffleRelative = 66,
     SubgroupBallotKHR = 4423,
     DrawParameters = 4427,
     SubgroupVoteKHR = 4431,
@@ -624,6 +1314,15 @@ pub enum Capability {
     VariablePointers = 4442,
     AtomicStorageOps = 4445,
     SampleMaskPostDepthCoverage = 4447,
+    StorageBuffer8BitAccess = 4448,
+    UniformAndStorageBuffer8BitAccess = 4449,
+    StoragePushConstant8 = 4450,
+    DenormPreserve = 4464,
+    DenormFlushToZero = 4465,
+    SignedZeroInfNanPreserve = 4466,
+    RoundingModeRTE = 4467,
+    RoundingModeRTZ = 4468,
+    RayTracingKHR = 4479,
     ImageGatherBiasLodAMD = 5009,
     FragmentMaskAMD = 5010,
     StencilExportEXT = 5013,
@@ -634,6 +1333,26 @@ pub enum Capability {
     ShaderViewportMaskNV = 5255,
     ShaderStereoViewNV = 5259,
     PerViewAttributesNV = 5260,
+    MeshShadingEXT = 5283,
+    ShaderNonUniform = 5301,
+    RuntimeDescriptorArray = 5302,
+    InputAttachmentArrayDynamicIndexing = 5303,
+    UniformTexelBufferArrayDynamicIndexing = 5304,
+    StorageTexelBufferArrayDynamicIndexing = 5305,
+    UniformBufferArrayNonUniformIndexing = 5306,
+    SampledImageArrayNonUniformIndexing = 5307,
+    StorageBufferArrayNonUniformIndexing = 5308,
+    StorageImageArrayNonUniformIndexing = 5309,
+    InputAttachmentArrayNonUniformIndexing = 5310,
+    UniformTexelBufferArrayNonUniformIndexing = 5311,
+    StorageTexelBufferArrayNonUniformIndexing = 5312,
+    VulkanMemoryModel = 5345,
+    VulkanMemoryModelDeviceScope = 5346,
+    PhysicalStorageBufferAddresses = 5347,
+    DemoteToHelperInvocation = 5379,
+    CooperativeMatrixKHR = 6022,
+    AtomicFloat32AddEXT = 6033,
+    AtomicFloat64AddEXT = 6034,
 }
 
 #[allow(non_upper_case_globals)]
@@ -642,9 +1361,184 @@ impl Capability {
     pub const StorageUniform16: Capability = Capability::UniformAndStorageBuffer16BitAccess;
     pub const ShaderViewportIndexLayerNV: Capability = Capability::ShaderViewportIndexLayerEXT;
 }
+
+impl num_traits::FromPrimitive for Capability {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => Capability::Matrix,
+            1 => Capability::Shader,
+            2 => Capability::Geometry,
+            3 => Capability::Tessellation,
+            4 => Capability::Addresses,
+            5 => Capability::Linkage,
+            6 => Capability::Kernel,
+            7 => Capability::Vector16,
+            8 => Capability::Float16Buffer,
+            9 => Capability::Float16,
+            10 => Capability::Float64,
+            11 => Capability::Int64,
+            12 => Capability::Int64Atomics,
+            13 => Capability::ImageBasic,
+            14 => Capability::ImageReadWrite,
+            15 => Capability::ImageMipmap,
+            17 => Capability::Pipes,
+            18 => Capability::Groups,
+            19 => Capability::DeviceEnqueue,
+            20 => Capability::LiteralSampler,
+            21 => Capability::AtomicStorage,
+            22 => Capability::Int16,
+            23 => Capability::TessellationPointSize,
+            24 => Capability::GeometryPointSize,
+            25 => Capability::ImageGatherExtended,
+            27 => Capability::StorageImageMultisample,
+            28 => Capability::UniformBufferArrayDynamicIndexing,
+            29 => Capability::SampledImageArrayDynamicIndexing,
+            30 => Capability::StorageBufferArrayDynamicIndexing,
+            31 => Capability::StorageImageArrayDynamicIndexing,
+            32 => Capability::ClipDistance,
+            33 => Capability::CullDistance,
+            34 => Capability::ImageCubeArray,
+            35 => Capability::SampleRateShading,
+            36 => Capability::ImageRect,
+            37 => Capability::SampledRect,
+            38 => Capability::GenericPointer,
+            39 => Capability::Int8,
+            40 => Capability::InputAttachment,
+            41 => Capability::SparseResidency,
+            42 => Capability::MinLod,
+            43 => Capability::Sampled1D,
+            44 => Capability::Image1D,
+            45 => Capability::SampledCubeArray,
+            46 => Capability::SampledBuffer,
+            47 => Capability::ImageBuffer,
+            48 => Capability::ImageMSArray,
+            49 => Capability::StorageImageExtendedFormats,
+            50 => Capability::ImageQuery,
+            51 => Capability::DerivativeControl,
+            52 => Capability::InterpolationFunction,
+            53 => Capability::TransformFeedback,
+            54 => Capability::GeometryStreams,
+            55 => Capability::StorageImageReadWithoutFormat,
+            56 => Capability::StorageImageWriteWithoutFormat,
+            57 => Capability::MultiViewport,
+            58 => Capability::SubgroupDispatch,
+            59 => Capability::NamedBarrier,
+            60 => Capability::PipeStorage,
+            61 => Capability::GroupNonUniform,
+            62 => Capability::GroupNonUniformVote,
+            64 => Capability::GroupNonUniformBallot,
+            65 => Capability::GroupNonUniformShuffle,
+            66 => Capability::GroupNonUniformShuffleRelative,
+            4423 => Capability::SubgroupBallotKHR,
+            4427 => Capability::DrawParameters,
+            4431 => Capability::SubgroupVoteKHR,
+            4433 => Capability::StorageBuffer16BitAccess,
+            4434 => Capability::UniformAndStorageBuffer16BitAccess,
+            4435 => Capability::StoragePushConstant16,
+            4436 => Capability::StorageInputOutput16,
+            4437 => Capability::DeviceGroup,
+            4439 => Capability::MultiView,
+            4441 => Capability::VariablePointersStorageBuffer,
+            4442 => Capability::VariablePointers,
+            4445 => Capability::AtomicStorageOps,
+            4447 => Capability::SampleMaskPostDepthCoverage,
+            4448 => Capability::StorageBuffer8BitAccess,
+            4449 => Capability::UniformAndStorageBuffer8BitAccess,
+            4450 => Capability::StoragePushConstant8,
+            4464 => Capability::DenormPreserve,
+            4465 => Capability::DenormFlushToZero,
+            4466 => Capability::SignedZeroInfNanPreserve,
+            4467 => Capability::RoundingModeRTE,
+            4468 => Capability::RoundingModeRTZ,
+            4479 => Capability::RayTracingKHR,
+            5009 => Capability::ImageGatherBiasLodAMD,
+            5010 => Capability::FragmentMaskAMD,
+            5013 => Capability::StencilExportEXT,
+            5015 => Capability::ImageReadWriteLodAMD,
+            5249 => Capability::SampleMaskOverrideCoverageNV,
+            5251 => Capability::GeometryShaderPassthroughNV,
+            5254 => Capability::ShaderViewportIndexLayerEXT,
+            5255 => Capability::ShaderViewportMaskNV,
+            5259 => Capability::ShaderStereoViewNV,
+            5260 => Capability::PerViewAttributesNV,
+            5283 => Capability::MeshShadingEXT,
+            5301 => Capability::ShaderNonUniform,
+            5302 => Capability::RuntimeDescriptorArray,
+            5303 => Capability::InputAttachmentArrayDynamicIndexing,
+            5304 => Capability::UniformTexelBufferArrayDynamicIndexing,
+            5305 => Capability::StorageTexelBufferArrayDynamicIndexing,
+            5306 => Capability::UniformBufferArrayNonUniformIndexing,
+            5307 => Capability::SampledImageArrayNonUniformIndexing,
+            5308 => Capability::StorageBufferArrayNonUniformIndexing,
+            5309 => Capability::StorageImageArrayNonUniformIndexing,
+            5310 => Capability::InputAttachmentArrayNonUniformIndexing,
+            5311 => Capability::UniformTexelBufferArrayNonUniformIndexing,
+            5312 => Capability::StorageTexelBufferArrayNonUniformIndexing,
+            5345 => Capability::VulkanMemoryModel,
+            5346 => Capability::VulkanMemoryModelDeviceScope,
+            5347 => Capability::PhysicalStorageBufferAddresses,
+            5379 => Capability::DemoteToHelperInvocation,
+            6022 => Capability::CooperativeMatrixKHR,
+            6033 => Capability::AtomicFloat32AddEXT,
+            6034 => Capability::AtomicFloat64AddEXT,
+            _ => return None,
+        })
+    }
+}
+
+/// SPIR-V operand kind: [CooperativeMatrixLayout](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_cooperative_matrix_layout_a_cooperative_matrix_layout)
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CooperativeMatrixLayout {
+    RowMajorKHR = 0,
+    ColumnMajorKHR = 1,
+}
+
+impl num_traits::FromPrimitive for CooperativeMatrixLayout {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => CooperativeMatrixLayout::RowMajorKHR,
+            1 => CooperativeMatrixLayout::ColumnMajorKHR,
+            _ => return None,
+        })
+    }
+}
+
+/// SPIR-V operand kind: [CooperativeMatrixUse](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_cooperative_matrix_use_a_cooperative_matrix_use)
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CooperativeMatrixUse {
+    MatrixAKHR = 0,
+    MatrixBKHR = 1,
+    MatrixAccumulatorKHR = 2,
+}
+
+impl num_traits::FromPrimitive for CooperativeMatrixUse {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => CooperativeMatrixUse::MatrixAKHR,
+            1 => CooperativeMatrixUse::MatrixBKHR,
+            2 => CooperativeMatrixUse::MatrixAccumulatorKHR,
+            _ => return None,
+        })
+    }
+}
+
 /// SPIR-V [instructions](https://www.khronos.org/registry/spir-v/specs/1.2/SPIRV.html#_a_id_instructions_a_instructions) opcodes
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Op {
     Nop = 0,
     Undef = 1,
@@ -952,12 +1846,40 @@ pub enum Op {
     ModuleProcessed = 330,
     ExecutionModeId = 331,
     DecorateId = 332,
+    GroupNonUniformElect = 333,
+    GroupNonUniformAll = 334,
+    GroupNonUniformAny = 335,
+    GroupNonUniformAllEqual = 336,
+    GroupNonUniformBroadcast = 337,
+    GroupNonUniformBroadcastFirst = 338,
+    GroupNonUniformBallot = 339,
+    GroupNonUniformInverseBallot = 340,
+    GroupNonUniformBallotBitExtract = 341,
+    GroupNonUniformBallotBitCount = 342,
+    GroupNonUniformBallotFindLSB = 343,
+    GroupNonUniformBallotFindMSB = 344,
+    GroupNonUniformShuffle = 345,
+    GroupNonUniformShuffleXor = 346,
+    GroupNonUniformShuffleUp = 347,
+    GroupNonUniformShuffleDown = 348,
+    CopyLogical = 400,
+    TerminateInvocation = 4416,
     SubgroupBallotKHR = 4421,
     SubgroupFirstInvocationKHR = 4422,
     SubgroupAllKHR = 4428,
     SubgroupAnyKHR = 4429,
     SubgroupAllEqualKHR = 4430,
     SubgroupReadInvocationKHR = 4432,
+    TraceRayKHR = 4445,
+    ExecuteCallableKHR = 4446,
+    ConvertUToAccelerationStructureKHR = 4447,
+    IgnoreIntersectionKHR = 4448,
+    TerminateRayKHR = 4449,
+    TypeCooperativeMatrixKHR = 4456,
+    CooperativeMatrixLoadKHR = 4457,
+    CooperativeMatrixStoreKHR = 4458,
+    CooperativeMatrixMulAddKHR = 4459,
+    CooperativeMatrixLengthKHR = 4460,
     GroupIAddNonUniformAMD = 5000,
     GroupFAddNonUniformAMD = 5001,
     GroupFMinNonUniformAMD = 5002,
@@ -968,11 +1890,391 @@ pub enum Op {
     GroupSMaxNonUniformAMD = 5007,
     FragmentMaskFetchAMD = 5011,
     FragmentFetchAMD = 5012,
+    EmitMeshTasksEXT = 5294,
+    SetMeshOutputsEXT = 5295,
+    ReportIntersectionKHR = 5334,
+    TypeAccelerationStructureKHR = 5341,
+    DemoteToHelperInvocation = 5380,
+    IsHelperInvocationEXT = 5381,
+    DecorateString = 5632,
+    MemberDecorateString = 5633,
+    AtomicFAddEXT = 6035,
+}
+
+impl num_traits::FromPrimitive for Op {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => Op::Nop,
+            1 => Op::Undef,
+            2 => Op::SourceContinued,
+            3 => Op::Source,
+            4 => Op::SourceExtension,
+            5 => Op::Name,
+            6 => Op::MemberName,
+            7 => Op::String,
+            8 => Op::Line,
+            10 => Op::Extension,
+            11 => Op::ExtInstImport,
+            12 => Op::ExtInst,
+            14 => Op::MemoryModel,
+            15 => Op::EntryPoint,
+            16 => Op::ExecutionMode,
+            17 => Op::Capability,
+            19 => Op::TypeVoid,
+            20 => Op::TypeBool,
+            21 => Op::TypeInt,
+            22 => Op::TypeFloat,
+            23 => Op::TypeVector,
+            24 => Op::TypeMatrix,
+            25 => Op::TypeImage,
+            26 => Op::TypeSampler,
+            27 => Op::TypeSampledImage,
+            28 => Op::TypeArray,
+            29 => Op::TypeRuntimeArray,
+            30 => Op::TypeStruct,
+            31 => Op::TypeOpaque,
+            32 => Op::TypePointer,
+            33 => Op::TypeFunction,
+            34 => Op::TypeEvent,
+            35 => Op::TypeDeviceEvent,
+            36 => Op::TypeReserveId,
+            37 => Op::TypeQueue,
+            38 => Op::TypePipe,
+            39 => Op::TypeForwardPointer,
+            41 => Op::ConstantTrue,
+            42 => Op::ConstantFalse,
+            43 => Op::Constant,
+            44 => Op::ConstantComposite,
+            45 => Op::ConstantSampler,
+            46 => Op::ConstantNull,
+            48 => Op::SpecConstantTrue,
+            49 => Op::SpecConstantFalse,
+            50 => Op::SpecConstant,
+            51 => Op::SpecConstantComposite,
+            52 => Op::SpecConstantOp,
+            54 => Op::Function,
+            55 => Op::FunctionParameter,
+            56 => Op::FunctionEnd,
+            57 => Op::FunctionCall,
+            59 => Op::Variable,
+            60 => Op::ImageTexelPointer,
+            61 => Op::Load,
+            62 => Op::Store,
+            63 => Op::CopyMemory,
+            64 => Op::CopyMemorySized,
+            65 => Op::AccessChain,
+            66 => Op::InBoundsAccessChain,
+            67 => Op::PtrAccessChain,
+            68 => Op::ArrayLength,
+            69 => Op::GenericPtrMemSemantics,
+            70 => Op::InBoundsPtrAccessChain,
+            71 => Op::Decorate,
+            72 => Op::MemberDecorate,
+            73 => Op::DecorationGroup,
+            74 => Op::GroupDecorate,
+            75 => Op::GroupMemberDecorate,
+            77 => Op::VectorExtractDynamic,
+            78 => Op::VectorInsertDynamic,
+            79 => Op::VectorShuffle,
+            80 => Op::CompositeConstruct,
+            81 => Op::CompositeExtract,
+            82 => Op::CompositeInsert,
+            83 => Op::CopyObject,
+            84 => Op::Transpose,
+            86 => Op::SampledImage,
+            87 => Op::ImageSampleImplicitLod,
+            88 => Op::ImageSampleExplicitLod,
+            89 => Op::ImageSampleDrefImplicitLod,
+            90 => Op::ImageSampleDrefExplicitLod,
+            91 => Op::ImageSampleProjImplicitLod,
+            92 => Op::ImageSampleProjExplicitLod,
+            93 => Op::ImageSampleProjDrefImplicitLod,
+            94 => Op::ImageSampleProjDrefExplicitLod,
+            95 => Op::ImageFetch,
+            96 => Op::ImageGather,
+            97 => Op::ImageDrefGather,
+            98 => Op::ImageRead,
+            99 => Op::ImageWrite,
+            100 => Op::Image,
+            101 => Op::ImageQueryFormat,
+            102 => Op::ImageQueryOrder,
+            103 => Op::ImageQuerySizeLod,
+            104 => Op::ImageQuerySize,
+            105 => Op::ImageQueryLod,
+            106 => Op::ImageQueryLevels,
+            107 => Op::ImageQuerySamples,
+            109 => Op::ConvertFToU,
+            110 => Op::ConvertFToS,
+            111 => Op::ConvertSToF,
+            112 => Op::ConvertUToF,
+            113 => Op::UConvert,
+            114 => Op::SConvert,
+            115 => Op::FConvert,
+            116 => Op::QuantizeToF16,
+            117 => Op::ConvertPtrToU,
+            118 => Op::SatConvertSToU,
+            119 => Op::SatConvertUToS,
+            120 => Op::ConvertUToPtr,
+            121 => Op::PtrCastToGeneric,
+            122 => Op::GenericCastToPtr,
+            123 => Op::GenericCastToPtrExplicit,
+            124 => Op::Bitcast,
+            126 => Op::SNegate,
+            127 => Op::FNegate,
+            128 => Op::IAdd,
+            129 => Op::FAdd,
+            130 => Op::ISub,
+            131 => Op::FSub,
+            132 => Op::IMul,
+            133 => Op::FMul,
+            134 => Op::UDiv,
+            135 => Op::SDiv,
+            136 => Op::FDiv,
+            137 => Op::UMod,
+            138 => Op::SRem,
+            139 => Op::SMod,
+            140 => Op::FRem,
+            141 => Op::FMod,
+            142 => Op::VectorTimesScalar,
+            143 => Op::MatrixTimesScalar,
+            144 => Op::VectorTimesMatrix,
+            145 => Op::MatrixTimesVector,
+            146 => Op::MatrixTimesMatrix,
+            147 => Op::OuterProduct,
+            148 => Op::Dot,
+            149 => Op::IAddCarry,
+            150 => Op::ISubBorrow,
+            151 => Op::UMulExtended,
+            152 => Op::SMulExtended,
+            154 => Op::Any,
+            155 => Op::All,
+            156 => Op::IsNan,
+            157 => Op::IsInf,
+            158 => Op::IsFinite,
+            159 => Op::IsNormal,
+            160 => Op::SignBitSet,
+            161 => Op::LessOrGreater,
+            162 => Op::Ordered,
+            163 => Op::Unordered,
+            164 => Op::LogicalEqual,
+            165 => Op::LogicalNotEqual,
+            166 => Op::LogicalOr,
+            167 => Op::LogicalAnd,
+            168 => Op::LogicalNot,
+            169 => Op::Select,
+            170 => Op::IEqual,
+            171 => Op::INotEqual,
+            172 => Op::UGreaterThan,
+            173 => Op::SGreaterThan,
+            174 => Op::UGreaterThanEqual,
+            175 => Op::SGreaterThanEqual,
+            176 => Op::ULessThan,
+            177 => Op::SLessThan,
+            178 => Op::ULessThanEqual,
+            179 => Op::SLessThanEqual,
+            180 => Op::FOrdEqual,
+            181 => Op::FUnordEqual,
+            182 => Op::FOrdNotEqual,
+            183 => Op::FUnordNotEqual,
+            184 => Op::FOrdLessThan,
+            185 => Op::FUnordLessThan,
+            186 => Op::FOrdGreaterThan,
+            187 => Op::FUnordGreaterThan,
+            188 => Op::FOrdLessThanEqual,
+            189 => Op::FUnordLessThanEqual,
+            190 => Op::FOrdGreaterThanEqual,
+            191 => Op::FUnordGreaterThanEqual,
+            194 => Op::ShiftRightLogical,
+            195 => Op::ShiftRightArithmetic,
+            196 => Op::ShiftLeftLogical,
+            197 => Op::BitwiseOr,
+            198 => Op::BitwiseXor,
+            199 => Op::BitwiseAnd,
+            200 => Op::Not,
+            201 => Op::BitFieldInsert,
+            202 => Op::BitFieldSExtract,
+            203 => Op::BitFieldUExtract,
+            204 => Op::BitReverse,
+            205 => Op::BitCount,
+            207 => Op::DPdx,
+            208 => Op::DPdy,
+            209 => Op::Fwidth,
+            210 => Op::DPdxFine,
+            211 => Op::DPdyFine,
+            212 => Op::FwidthFine,
+            213 => Op::DPdxCoarse,
+            214 => Op::DPdyCoarse,
+            215 => Op::FwidthCoarse,
+            218 => Op::EmitVertex,
+            219 => Op::EndPrimitive,
+            220 => Op::EmitStreamVertex,
+            221 => Op::EndStreamPrimitive,
+            224 => Op::ControlBarrier,
+            225 => Op::MemoryBarrier,
+            227 => Op::AtomicLoad,
+            228 => Op::AtomicStore,
+            229 => Op::AtomicExchange,
+            230 => Op::AtomicCompareExchange,
+            231 => Op::AtomicCompareExchangeWeak,
+            232 => Op::AtomicIIncrement,
+            233 => Op::AtomicIDecrement,
+            234 => Op::AtomicIAdd,
+            235 => Op::AtomicISub,
+            236 => Op::AtomicSMin,
+            237 => Op::AtomicUMin,
+            238 => Op::AtomicSMax,
+            239 => Op::AtomicUMax,
+            240 => Op::AtomicAnd,
+            241 => Op::AtomicOr,
+            242 => Op::AtomicXor,
+            245 => Op::Phi,
+            246 => Op::LoopMerge,
+            247 => Op::SelectionMerge,
+            248 => Op::Label,
+            249 => Op::Branch,
+            250 => Op::BranchConditional,
+            251 => Op::Switch,
+            252 => Op::Kill,
+            253 => Op::Return,
+            254 => Op::ReturnValue,
+            255 => Op::Unreachable,
+            256 => Op::LifetimeStart,
+            257 => Op::LifetimeStop,
+            259 => Op::GroupAsyncCopy,
+            260 => Op::GroupWaitEvents,
+            261 => Op::GroupAll,
+            262 => Op::GroupAny,
+            263 => Op::GroupBroadcast,
+            264 => Op::GroupIAdd,
+            265 => Op::GroupFAdd,
+            266 => Op::GroupFMin,
+            267 => Op::GroupUMin,
+            268 => Op::GroupSMin,
+            269 => Op::GroupFMax,
+            270 => Op::GroupUMax,
+            271 => Op::GroupSMax,
+            274 => Op::ReadPipe,
+            275 => Op::WritePipe,
+            276 => Op::ReservedReadPipe,
+            277 => Op::ReservedWritePipe,
+            278 => Op::ReserveReadPipePackets,
+            279 => Op::ReserveWritePipePackets,
+            280 => Op::CommitReadPipe,
+            281 => Op::CommitWritePipe,
+            282 => Op::IsValidReserveId,
+            283 => Op::GetNumPipePackets,
+            284 => Op::GetMaxPipePackets,
+            285 => Op::GroupReserveReadPipePackets,
+            286 => Op::GroupReserveWritePipePackets,
+            287 => Op::GroupCommitReadPipe,
+            288 => Op::GroupCommitWritePipe,
+            291 => Op::EnqueueMarker,
+            292 => Op::EnqueueKernel,
+            293 => Op::GetKernelNDrangeSubGroupCount,
+            294 => Op::GetKernelNDrangeMaxSubGroupSize,
+            295 => Op::GetKernelWorkGroupSize,
+            296 => Op::GetKernelPreferredWorkGroupSizeMultiple,
+            297 => Op::RetainEvent,
+            298 => Op::ReleaseEvent,
+            299 => Op::CreateUserEvent,
+            300 => Op::IsValidEvent,
+            301 => Op::SetUserEventStatus,
+            302 => Op::CaptureEventProfilingInfo,
+            303 => Op::GetDefaultQueue,
+            304 => Op::BuildNDRange,
+            305 => Op::ImageSparseSampleImplicitLod,
+            306 => Op::ImageSparseSampleExplicitLod,
+            307 => Op::ImageSparseSampleDrefImplicitLod,
+            308 => Op::ImageSparseSampleDrefExplicitLod,
+            309 => Op::ImageSparseSampleProjImplicitLod,
+            310 => Op::ImageSparseSampleProjExplicitLod,
+            311 => Op::ImageSparseSampleProjDrefImplicitLod,
+            312 => Op::ImageSparseSampleProjDrefExplicitLod,
+            313 => Op::ImageSparseFetch,
+            314 => Op::ImageSparseGather,
+            315 => Op::ImageSparseDrefGather,
+            316 => Op::ImageSparseTexelsResident,
+            317 => Op::NoLine,
+            318 => Op::AtomicFlagTestAndSet,
+            319 => Op::AtomicFlagClear,
+            320 => Op::ImageSparseRead,
+            321 => Op::SizeOf,
+            322 => Op::TypePipeStorage,
+            323 => Op::ConstantPipeStorage,
+            324 => Op::CreatePipeFromPipeStorage,
+            325 => Op::GetKernelLocalSizeForSubgroupCount,
+            326 => Op::GetKernelMaxNumSubgroups,
+            327 => Op::TypeNamedBarrier,
+            328 => Op::NamedBarrierInitialize,
+            329 => Op::MemoryNamedBarrier,
+            330 => Op::ModuleProcessed,
+            331 => Op::ExecutionModeId,
+            332 => Op::DecorateId,
+            333 => Op::GroupNonUniformElect,
+            334 => Op::GroupNonUniformAll,
+            335 => Op::GroupNonUniformAny,
+            336 => Op::GroupNonUniformAllEqual,
+            337 => Op::GroupNonUniformBroadcast,
+            338 => Op::GroupNonUniformBroadcastFirst,
+            339 => Op::GroupNonUniformBallot,
+            340 => Op::GroupNonUniformInverseBallot,
+            341 => Op::GroupNonUniformBallotBitExtract,
+            342 => Op::GroupNonUniformBallotBitCount,
+            343 => Op::GroupNonUniformBallotFindLSB,
+            344 => Op::GroupNonUniformBallotFindMSB,
+            345 => Op::GroupNonUniformShuffle,
+            346 => Op::GroupNonUniformShuffleXor,
+            347 => Op::GroupNonUniformShuffleUp,
+            348 => Op::GroupNonUniformShuffleDown,
+            400 => Op::CopyLogical,
+            4416 => Op::TerminateInvocation,
+            4421 => Op::SubgroupBallotKHR,
+            4422 => Op::SubgroupFirstInvocationKHR,
+            4428 => Op::SubgroupAllKHR,
+            4429 => Op::SubgroupAnyKHR,
+            4430 => Op::SubgroupAllEqualKHR,
+            4432 => Op::SubgroupReadInvocationKHR,
+            4445 => Op::TraceRayKHR,
+            4446 => Op::ExecuteCallableKHR,
+            4447 => Op::ConvertUToAccelerationStructureKHR,
+            4448 => Op::IgnoreIntersectionKHR,
+            4449 => Op::TerminateRayKHR,
+            4456 => Op::TypeCooperativeMatrixKHR,
+            4457 => Op::CooperativeMatrixLoadKHR,
+            4458 => Op::CooperativeMatrixStoreKHR,
+            4459 => Op::CooperativeMatrixMulAddKHR,
+            4460 => Op::CooperativeMatrixLengthKHR,
+            5000 => Op::GroupIAddNonUniformAMD,
+            5001 => Op::GroupFAddNonUniformAMD,
+            5002 => Op::GroupFMinNonUniformAMD,
+            5003 => Op::GroupUMinNonUniformAMD,
+            5004 => Op::GroupSMinNonUniformAMD,
+            5005 => Op::GroupFMaxNonUniformAMD,
+            5006 => Op::GroupUMaxNonUniformAMD,
+            5007 => Op::GroupSMaxNonUniformAMD,
+            5011 => Op::FragmentMaskFetchAMD,
+            5012 => Op::FragmentFetchAMD,
+            5294 => Op::EmitMeshTasksEXT,
+            5295 => Op::SetMeshOutputsEXT,
+            5334 => Op::ReportIntersectionKHR,
+            5341 => Op::TypeAccelerationStructureKHR,
+            5380 => Op::DemoteToHelperInvocation,
+            5381 => Op::IsHelperInvocationEXT,
+            5632 => Op::DecorateString,
+            5633 => Op::MemberDecorateString,
+            6035 => Op::AtomicFAddEXT,
+            _ => return None,
+        })
+    }
 }
 
 /// [GLSL.std.450](https://www.khronos.org/registry/spir-v/specs/1.0/GLSL.std.450.html) extended instruction opcode
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum GLOp {
     Round = 1,
     RoundEven = 2,
@@ -1057,9 +2359,102 @@ pub enum GLOp {
     NClamp = 81,
 }
 
+impl num_traits::FromPrimitive for GLOp {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            1 => GLOp::Round,
+            2 => GLOp::RoundEven,
+            3 => GLOp::Trunc,
+            4 => GLOp::FAbs,
+            5 => GLOp::SAbs,
+            6 => GLOp::FSign,
+            7 => GLOp::SSign,
+            8 => GLOp::Floor,
+            9 => GLOp::Ceil,
+            10 => GLOp::Fract,
+            11 => GLOp::Radians,
+            12 => GLOp::Degrees,
+            13 => GLOp::Sin,
+            14 => GLOp::Cos,
+            15 => GLOp::Tan,
+            16 => GLOp::Asin,
+            17 => GLOp::Acos,
+            18 => GLOp::Atan,
+            19 => GLOp::Sinh,
+            20 => GLOp::Cosh,
+            21 => GLOp::Tanh,
+            22 => GLOp::Asinh,
+            23 => GLOp::Acosh,
+            24 => GLOp::Atanh,
+            25 => GLOp::Atan2,
+            26 => GLOp::Pow,
+            27 => GLOp::Exp,
+            28 => GLOp::Log,
+            29 => GLOp::Exp2,
+            30 => GLOp::Log2,
+            31 => GLOp::Sqrt,
+            32 => GLOp::InverseSqrt,
+            33 => GLOp::Determinant,
+            34 => GLOp::MatrixInverse,
+            35 => GLOp::Modf,
+            36 => GLOp::ModfStruct,
+            37 => GLOp::FMin,
+            38 => GLOp::UMin,
+            39 => GLOp::SMin,
+            40 => GLOp::FMax,
+            41 => GLOp::UMax,
+            42 => GLOp::SMax,
+            43 => GLOp::FClamp,
+            44 => GLOp::UClamp,
+            45 => GLOp::SClamp,
+            46 => GLOp::FMix,
+            47 => GLOp::IMix,
+            48 => GLOp::Step,
+            49 => GLOp::SmoothStep,
+            50 => GLOp::Fma,
+            51 => GLOp::Frexp,
+            52 => GLOp::FrexpStruct,
+            53 => GLOp::Ldexp,
+            54 => GLOp::PackSnorm4x8,
+            55 => GLOp::PackUnorm4x8,
+            56 => GLOp::PackSnorm2x16,
+            57 => GLOp::PackUnorm2x16,
+            58 => GLOp::PackHalf2x16,
+            59 => GLOp::PackDouble2x32,
+            60 => GLOp::UnpackSnorm2x16,
+            61 => GLOp::UnpackUnorm2x16,
+            62 => GLOp::UnpackHalf2x16,
+            63 => GLOp::UnpackSnorm4x8,
+            64 => GLOp::UnpackUnorm4x8,
+            65 => GLOp::UnpackDouble2x32,
+            66 => GLOp::Length,
+            67 => GLOp::Distance,
+            68 => GLOp::Cross,
+            69 => GLOp::Normalize,
+            70 => GLOp::FaceForward,
+            71 => GLOp::Reflect,
+            72 => GLOp::Refract,
+            73 => GLOp::FindILsb,
+            74 => GLOp::FindSMsb,
+            75 => GLOp::FindUMsb,
+            76 => GLOp::InterpolateAtCentroid,
+            77 => GLOp::InterpolateAtSample,
+            78 => GLOp::InterpolateAtOffset,
+            79 => GLOp::NMin,
+            80 => GLOp::NMax,
+            81 => GLOp::NClamp,
+            _ => return None,
+        })
+    }
+}
+
 /// [OpenCL.std](https://www.khronos.org/registry/spir-v/specs/1.2/OpenCL.ExtendedInstructionSet.100.html) extended instruction opcode
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum CLOp {
     acos = 0,
     acosh = 1,
@@ -1224,3 +2619,403 @@ pub enum CLOp {
     printf = 184,
     prefetch = 185,
 }
+
+impl num_traits::FromPrimitive for CLOp {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => CLOp::acos,
+            1 => CLOp::acosh,
+            2 => CLOp::acospi,
+            3 => CLOp::asin,
+            4 => CLOp::asinh,
+            5 => CLOp::asinpi,
+            6 => CLOp::atan,
+            7 => CLOp::atan2,
+            8 => CLOp::atanh,
+            9 => CLOp::atanpi,
+            10 => CLOp::atan2pi,
+            11 => CLOp::cbrt,
+            12 => CLOp::ceil,
+            13 => CLOp::copysign,
+            14 => CLOp::cos,
+            15 => CLOp::cosh,
+            16 => CLOp::cospi,
+            17 => CLOp::erfc,
+            18 => CLOp::erf,
+            19 => CLOp::exp,
+            20 => CLOp::exp2,
+            21 => CLOp::exp10,
+            22 => CLOp::expm1,
+            23 => CLOp::fabs,
+            24 => CLOp::fdim,
+            25 => CLOp::floor,
+            26 => CLOp::fma,
+            27 => CLOp::fmax,
+            28 => CLOp::fmin,
+            29 => CLOp::fmod,
+            30 => CLOp::fract,
+            31 => CLOp::frexp,
+            32 => CLOp::hypot,
+            33 => CLOp::ilogb,
+            34 => CLOp::ldexp,
+            35 => CLOp::lgamma,
+            36 => CLOp::lgamma_r,
+            37 => CLOp::log,
+            38 => CLOp::log2,
+            39 => CLOp::log10,
+            40 => CLOp::log1p,
+            41 => CLOp::logb,
+            42 => CLOp::mad,
+            43 => CLOp::maxmag,
+            44 => CLOp::minmag,
+            45 => CLOp::modf,
+            46 => CLOp::nan,
+            47 => CLOp::nextafter,
+            48 => CLOp::pow,
+            49 => CLOp::pown,
+            50 => CLOp::powr,
+            51 => CLOp::remainder,
+            52 => CLOp::remquo,
+            53 => CLOp::rint,
+            54 => CLOp::rootn,
+            55 => CLOp::round,
+            56 => CLOp::rsqrt,
+            57 => CLOp::sin,
+            58 => CLOp::sincos,
+            59 => CLOp::sinh,
+            60 => CLOp::sinpi,
+            61 => CLOp::sqrt,
+            62 => CLOp::tan,
+            63 => CLOp::tanh,
+            64 => CLOp::tanpi,
+            65 => CLOp::tgamma,
+            66 => CLOp::trunc,
+            67 => CLOp::half_cos,
+            68 => CLOp::half_divide,
+            69 => CLOp::half_exp,
+            70 => CLOp::half_exp2,
+            71 => CLOp::half_exp10,
+            72 => CLOp::half_log,
+            73 => CLOp::half_log2,
+            74 => CLOp::half_log10,
+            75 => CLOp::half_powr,
+            76 => CLOp::half_recip,
+            77 => CLOp::half_rsqrt,
+            78 => CLOp::half_sin,
+            79 => CLOp::half_sqrt,
+            80 => CLOp::half_tan,
+            81 => CLOp::native_cos,
+            82 => CLOp::native_divide,
+            83 => CLOp::native_exp,
+            84 => CLOp::native_exp2,
+            85 => CLOp::native_exp10,
+            86 => CLOp::native_log,
+            87 => CLOp::native_log2,
+            88 => CLOp::native_log10,
+            89 => CLOp::native_powr,
+            90 => CLOp::native_recip,
+            91 => CLOp::native_rsqrt,
+            92 => CLOp::native_sin,
+            93 => CLOp::native_sqrt,
+            94 => CLOp::native_tan,
+            141 => CLOp::s_abs,
+            142 => CLOp::s_abs_diff,
+            143 => CLOp::s_add_sat,
+            144 => CLOp::u_add_sat,
+            145 => CLOp::s_hadd,
+            146 => CLOp::u_hadd,
+            147 => CLOp::s_rhadd,
+            148 => CLOp::u_rhadd,
+            149 => CLOp::s_clamp,
+            150 => CLOp::u_clamp,
+            151 => CLOp::clz,
+            152 => CLOp::ctz,
+            153 => CLOp::s_mad_hi,
+            154 => CLOp::u_mad_sat,
+            155 => CLOp::s_mad_sat,
+            156 => CLOp::s_max,
+            157 => CLOp::u_max,
+            158 => CLOp::s_min,
+            159 => CLOp::u_min,
+            160 => CLOp::s_mul_hi,
+            161 => CLOp::rotate,
+            162 => CLOp::s_sub_sat,
+            163 => CLOp::u_sub_sat,
+            164 => CLOp::u_upsample,
+            165 => CLOp::s_upsample,
+            166 => CLOp::popcount,
+            167 => CLOp::s_mad24,
+            168 => CLOp::u_mad24,
+            169 => CLOp::s_mul24,
+            170 => CLOp::u_mul24,
+            201 => CLOp::u_abs,
+            202 => CLOp::u_abs_diff,
+            203 => CLOp::u_mul_hi,
+            204 => CLOp::u_mad_hi,
+            95 => CLOp::fclamp,
+            96 => CLOp::degrees,
+            97 => CLOp::fmax_common,
+            98 => CLOp::fmin_common,
+            99 => CLOp::mix,
+            100 => CLOp::radians,
+            101 => CLOp::step,
+            102 => CLOp::smoothstep,
+            103 => CLOp::sign,
+            104 => CLOp::cross,
+            105 => CLOp::distance,
+            106 => CLOp::length,
+            107 => CLOp::normalize,
+            108 => CLOp::fast_distance,
+            109 => CLOp::fast_length,
+            110 => CLOp::fast_normalize,
+            186 => CLOp::bitselect,
+            187 => CLOp::select,
+            171 => CLOp::vloadn,
+            172 => CLOp::vstoren,
+            173 => CLOp::vload_half,
+            174 => CLOp::vload_halfn,
+            175 => CLOp::vstore_half,
+            176 => CLOp::vstore_half_r,
+            177 => CLOp::vstore_halfn,
+            178 => CLOp::vstore_halfn_r,
+            179 => CLOp::vloada_halfn,
+            180 => CLOp::vstorea_halfn,
+            181 => CLOp::vstorea_halfn_r,
+            182 => CLOp::shuffle,
+            183 => CLOp::shuffle2,
+            184 => CLOp::printf,
+            185 => CLOp::prefetch,
+            _ => return None,
+        })
+    }
+}
+
+/// [NonSemantic.Shader.DebugInfo.100](https://github.khronos.org/SPIRV-Registry/nonsemantic/NonSemantic.Shader.DebugInfo.100.html) extended instruction opcode
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DebugInfoOp {
+    DebugInfoNone = 0,
+    DebugCompilationUnit = 1,
+    DebugTypeBasic = 2,
+    DebugTypePointer = 3,
+    DebugTypeQualifier = 4,
+    DebugTypeArray = 5,
+    DebugTypeVector = 6,
+    DebugTypedef = 7,
+    DebugTypeFunction = 8,
+    DebugTypeEnum = 9,
+    DebugTypeComposite = 10,
+    DebugTypeMember = 11,
+    DebugTypeInheritance = 12,
+    DebugTypePtrToMember = 13,
+    DebugTypeTemplate = 14,
+    DebugTypeTemplateParameter = 15,
+    DebugTypeTemplateTemplateParameter = 16,
+    DebugTypeTemplateParameterPack = 17,
+    DebugGlobalVariable = 18,
+    DebugFunctionDeclaration = 19,
+    DebugFunction = 20,
+    DebugLexicalBlock = 21,
+    DebugLexicalBlockDiscriminator = 22,
+    DebugScope = 23,
+    DebugNoScope = 24,
+    DebugInlinedAt = 25,
+    DebugLocalVariable = 26,
+    DebugInlinedVariable = 27,
+    DebugDeclare = 28,
+    DebugValue = 29,
+    DebugOperation = 30,
+    DebugExpression = 31,
+    DebugMacroDef = 32,
+    DebugMacroUndef = 33,
+    DebugImportedEntity = 34,
+    DebugSource = 35,
+    DebugFunctionDefinition = 101,
+    DebugSourceContinued = 102,
+    DebugLine = 103,
+    DebugNoLine = 104,
+    DebugBuildIdentifier = 105,
+    DebugStoragePath = 106,
+    DebugEntryPoint = 107,
+    DebugTypeMatrix = 108,
+}
+
+impl num_traits::FromPrimitive for DebugInfoOp {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => DebugInfoOp::DebugInfoNone,
+            1 => DebugInfoOp::DebugCompilationUnit,
+            2 => DebugInfoOp::DebugTypeBasic,
+            3 => DebugInfoOp::DebugTypePointer,
+            4 => DebugInfoOp::DebugTypeQualifier,
+            5 => DebugInfoOp::DebugTypeArray,
+            6 => DebugInfoOp::DebugTypeVector,
+            7 => DebugInfoOp::DebugTypedef,
+            8 => DebugInfoOp::DebugTypeFunction,
+            9 => DebugInfoOp::DebugTypeEnum,
+            10 => DebugInfoOp::DebugTypeComposite,
+            11 => DebugInfoOp::DebugTypeMember,
+            12 => DebugInfoOp::DebugTypeInheritance,
+            13 => DebugInfoOp::DebugTypePtrToMember,
+            14 => DebugInfoOp::DebugTypeTemplate,
+            15 => DebugInfoOp::DebugTypeTemplateParameter,
+            16 => DebugInfoOp::DebugTypeTemplateTemplateParameter,
+            17 => DebugInfoOp::DebugTypeTemplateParameterPack,
+            18 => DebugInfoOp::DebugGlobalVariable,
+            19 => DebugInfoOp::DebugFunctionDeclaration,
+            20 => DebugInfoOp::DebugFunction,
+            21 => DebugInfoOp::DebugLexicalBlock,
+            22 => DebugInfoOp::DebugLexicalBlockDiscriminator,
+            23 => DebugInfoOp::DebugScope,
+            24 => DebugInfoOp::DebugNoScope,
+            25 => DebugInfoOp::DebugInlinedAt,
+            26 => DebugInfoOp::DebugLocalVariable,
+            27 => DebugInfoOp::DebugInlinedVariable,
+            28 => DebugInfoOp::DebugDeclare,
+            29 => DebugInfoOp::DebugValue,
+            30 => DebugInfoOp::DebugOperation,
+            31 => DebugInfoOp::DebugExpression,
+            32 => DebugInfoOp::DebugMacroDef,
+            33 => DebugInfoOp::DebugMacroUndef,
+            34 => DebugInfoOp::DebugImportedEntity,
+            35 => DebugInfoOp::DebugSource,
+            101 => DebugInfoOp::DebugFunctionDefinition,
+            102 => DebugInfoOp::DebugSourceContinued,
+            103 => DebugInfoOp::DebugLine,
+            104 => DebugInfoOp::DebugNoLine,
+            105 => DebugInfoOp::DebugBuildIdentifier,
+            106 => DebugInfoOp::DebugStoragePath,
+            107 => DebugInfoOp::DebugEntryPoint,
+            108 => DebugInfoOp::DebugTypeMatrix,
+            _ => return None,
+        })
+    }
+}
+
+/// [NonSemantic.DebugPrintf](https://github.khronos.org/SPIRV-Registry/nonsemantic/NonSemantic.DebugPrintf.html) extended instruction opcode
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DebugPrintfOp {
+    DebugPrintf = 1,
+}
+
+impl num_traits::FromPrimitive for DebugPrintfOp {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            1 => DebugPrintfOp::DebugPrintf,
+            _ => return None,
+        })
+    }
+}
+
+/// [NonSemantic.ClspvReflection](https://github.khronos.org/SPIRV-Registry/nonsemantic/NonSemantic.ClspvReflection.html) extended instruction opcode
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ClspvReflectionOp {
+    Kernel = 1,
+    ArgumentInfo = 2,
+    ArgumentStorageBuffer = 3,
+    ArgumentUniform = 4,
+    ArgumentPodStorageBuffer = 5,
+    ArgumentPodUniform = 6,
+    ArgumentPodPushConstant = 7,
+    ArgumentSampledImage = 8,
+    ArgumentStorageImage = 9,
+    ArgumentSampler = 10,
+    ArgumentWorkgroup = 11,
+    SpecConstantWorkgroupSize = 12,
+    SpecConstantGlobalOffset = 13,
+    SpecConstantWorkDim = 14,
+    PushConstantGlobalOffset = 15,
+    PushConstantEnqueuedLocalSize = 16,
+    PushConstantGlobalSize = 17,
+    PushConstantRegionOffset = 18,
+    PushConstantNumWorkgroups = 19,
+    PushConstantRegionGroupOffset = 20,
+    ConstantDataStorageBuffer = 21,
+    ConstantDataUniform = 22,
+    LiteralSampler = 23,
+    PropertyRequiredWorkgroupSize = 24,
+    SpecConstantSubgroupMaxSize = 25,
+    ArgumentPointerPushConstant = 26,
+    ArgumentPointerUniform = 27,
+    ProgramScopeVariablesStorageBuffer = 28,
+    ProgramScopeVariablePointerRelocation = 29,
+    ImageArgumentInfoChannelOrderPushConstant = 30,
+    ImageArgumentInfoChannelDataTypePushConstant = 31,
+    ImageArgumentInfoChannelOrderUniform = 32,
+    ImageArgumentInfoChannelDataTypeUniform = 33,
+    ArgumentStorageTexelBuffer = 34,
+    ArgumentUniformTexelBuffer = 35,
+    ConstantDataPointerPushConstant = 36,
+    ProgramScopeVariablePointerPushConstant = 37,
+    PrintfInfo = 38,
+    PrintfBufferStorageBuffer = 39,
+    PrintfBufferPointerPushConstant = 40,
+    NormalizedSamplerMaskPushConstant = 41,
+}
+
+impl num_traits::FromPrimitive for ClspvReflectionOp {
+    fn from_i64(n: i64) -> Option<Self> {
+        if n < 0 { None } else { Self::from_u64(n as u64) }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            1 => ClspvReflectionOp::Kernel,
+            2 => ClspvReflectionOp::ArgumentInfo,
+            3 => ClspvReflectionOp::ArgumentStorageBuffer,
+            4 => ClspvReflectionOp::ArgumentUniform,
+            5 => ClspvReflectionOp::ArgumentPodStorageBuffer,
+            6 => ClspvReflectionOp::ArgumentPodUniform,
+            7 => ClspvReflectionOp::ArgumentPodPushConstant,
+            8 => ClspvReflectionOp::ArgumentSampledImage,
+            9 => ClspvReflectionOp::ArgumentStorageImage,
+            10 => ClspvReflectionOp::ArgumentSampler,
+            11 => ClspvReflectionOp::ArgumentWorkgroup,
+            12 => ClspvReflectionOp::SpecConstantWorkgroupSize,
+            13 => ClspvReflectionOp::SpecConstantGlobalOffset,
+            14 => ClspvReflectionOp::SpecConstantWorkDim,
+            15 => ClspvReflectionOp::PushConstantGlobalOffset,
+            16 => ClspvReflectionOp::PushConstantEnqueuedLocalSize,
+            17 => ClspvReflectionOp::PushConstantGlobalSize,
+            18 => ClspvReflectionOp::PushConstantRegionOffset,
+            19 => ClspvReflectionOp::PushConstantNumWorkgroups,
+            20 => ClspvReflectionOp::PushConstantRegionGroupOffset,
+            21 => ClspvReflectionOp::ConstantDataStorageBuffer,
+            22 => ClspvReflectionOp::ConstantDataUniform,
+            23 => ClspvReflectionOp::LiteralSampler,
+            24 => ClspvReflectionOp::PropertyRequiredWorkgroupSize,
+            25 => ClspvReflectionOp::SpecConstantSubgroupMaxSize,
+            26 => ClspvReflectionOp::ArgumentPointerPushConstant,
+            27 => ClspvReflectionOp::ArgumentPointerUniform,
+            28 => ClspvReflectionOp::ProgramScopeVariablesStorageBuffer,
+            29 => ClspvReflectionOp::ProgramScopeVariablePointerRelocation,
+            30 => ClspvReflectionOp::ImageArgumentInfoChannelOrderPushConstant,
+            31 => ClspvReflectionOp::ImageArgumentInfoChannelDataTypePushConstant,
+            32 => ClspvReflectionOp::ImageArgumentInfoChannelOrderUniform,
+            33 => ClspvReflectionOp::ImageArgumentInfoChannelDataTypeUniform,
+            34 => ClspvReflectionOp::ArgumentStorageTexelBuffer,
+            35 => ClspvReflectionOp::ArgumentUniformTexelBuffer,
+            36 => ClspvReflectionOp::ConstantDataPointerPushConstant,
+            37 => ClspvReflectionOp::ProgramScopeVariablePointerPushConstant,
+            38 => ClspvReflectionOp::PrintfInfo,
+            39 => ClspvReflectionOp::PrintfBufferStorageBuffer,
+            40 => ClspvReflectionOp::PrintfBufferPointerPushConstant,
+            41 => ClspvReflectionOp::NormalizedSamplerMaskPushConstant,
+            _ => return None,
+        })
+    }
+}